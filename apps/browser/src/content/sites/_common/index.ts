@@ -16,19 +16,35 @@ interface NativeMetadataPayload {
 	};
 }
 
+interface NativeWebpageStatePayload {
+	kind: 'NativeWebpageState';
+	data: {
+		tab_id: number;
+		url: string;
+		title: string | null;
+		html: string;
+		lang: string | null;
+		description: string | null;
+		site_name: string | null;
+		byline: string | null;
+	};
+}
+
 interface ErrorPayload {
 	kind: 'Error';
 	data: string;
 }
 
-type CommonMessage = { type: 'GET_METADATA' };
+type CommonMessage = { type: 'GET_METADATA' } | { type: 'GET_PAGE_STATE'; tab_id: number };
+
+/// Cap on the serialized HTML shipped for `GET_PAGE_STATE`. Keeps the
+/// reply comfortably under the native-messaging host's frame limit.
+const MAX_PAGE_HTML_CHARS = 2 * 1024 * 1024;
 
 function isCommonMessage(value: unknown): value is CommonMessage {
-	return (
-		typeof value === 'object' &&
-		value !== null &&
-		(value as { type?: unknown }).type === 'GET_METADATA'
-	);
+	if (typeof value !== 'object' || value === null) return false;
+	const t = (value as { type?: unknown }).type;
+	return t === 'GET_METADATA' || t === 'GET_PAGE_STATE';
 }
 
 /// Page-metadata content-script handler. Separate from the tool
@@ -54,6 +70,34 @@ async function handleGetMetadata(): Promise<NativeMetadataPayload | ErrorPayload
 	}
 }
 
+/// Raw page snapshot for the desktop's readability fallback. Only the
+/// top frame answers so iframes don't race the reply.
+function handleGetPageState(tabId: number): NativeWebpageStatePayload | ErrorPayload | undefined {
+	if (window.top !== window) return undefined;
+	try {
+		const meta = (selector: string) =>
+			document.querySelector<HTMLMetaElement>(selector)?.content?.trim() || null;
+		return {
+			kind: 'NativeWebpageState',
+			data: {
+				tab_id: tabId,
+				url: window.location.href,
+				title: document.title || null,
+				html: document.documentElement.outerHTML.slice(0, MAX_PAGE_HTML_CHARS),
+				lang: document.documentElement.lang || null,
+				description:
+					meta('meta[name="description"]') ?? meta('meta[property="og:description"]'),
+				site_name: meta('meta[property="og:site_name"]'),
+				byline: meta('meta[name="author"]'),
+			},
+		};
+	} catch (error) {
+		const message = error instanceof Error ? error.message : String(error);
+		console.error('Common watcher failed to snapshot page', { error });
+		return { kind: 'Error', data: message };
+	}
+}
+
 async function resolveDocumentFavicon(): Promise<string> {
 	const records: IconLinkRecord[] = Array.from(
 		document.querySelectorAll<HTMLLinkElement>('link[rel]'),
@@ -92,6 +136,10 @@ export function main() {
 	// eslint-disable-next-line @typescript-eslint/promise-function-async -- see comment above; converting this to `async` reintroduces the listener-ownership race.
 	browser.runtime.onMessage.addListener((message) => {
 		if (!isCommonMessage(message)) return undefined;
+		if (message.type === 'GET_PAGE_STATE') {
+			const state = handleGetPageState(message.tab_id);
+			return state === undefined ? undefined : Promise.resolve(state);
+		}
 		return handleGetMetadata();
 	});
 }
@@ -8,6 +8,7 @@ import {
 	resolveChannelHandle,
 	resolveSearchQuery,
 	resolveYoutubeTools,
+	startFrameSampler,
 } from '../../../shared/content/tools/youtube';
 
 let initialized = false;
@@ -89,10 +90,12 @@ function describeYoutube(): string {
 /// alongside the YouTube-specific tools appropriate for the current
 /// page — `resolveYoutubeTools` is re-evaluated per `LIST_TOOLS` call so
 /// SPA navigation between e.g. `/watch` and `/results` flips the surface
-/// without a content-script reload.
+/// without a content-script reload. The frame sampler runs for the
+/// lifetime of the bundle and idles off video pages.
 export function main() {
 	if (initialized) return;
 	initialized = true;
+	startFrameSampler();
 	installToolHandlers(
 		watcherFromTools(
 			() => [...webTools, ...resolveYoutubeTools()],
@@ -118,6 +118,17 @@ async function onRequestFrame(frame: RequestFrame): Promise<Frame> {
 		case 'CANCEL_TOOL':
 			return await forwardTabRpc(frame, 'CANCEL_TOOL');
 
+		// Generic page snapshot, answered by `_common` on every site. The
+		// desktop only asks when `GET_CONTEXT` came back empty and runs
+		// its own readability pass over the returned HTML.
+		case 'GET_PAGE_STATE':
+			return await forwardTabRpc(frame, 'GET_PAGE_STATE');
+
+		// Structured webmail thread, answered by the Gmail / Outlook site
+		// bundles. PII redaction happens desktop-side.
+		case 'GET_EMAIL_THREAD':
+			return await forwardTabRpc(frame, 'GET_EMAIL_THREAD');
+
 		default:
 			return errorFrame(frame, 400, `Unknown action: ${frame.action}`);
 	}
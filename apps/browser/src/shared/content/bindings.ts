@@ -7,6 +7,9 @@ export type CancelFrame = {
 	id: number,
 };
 
+/**  Webmail client the extension scraped an [`NativeEmailThread`] from. */
+export type EmailProvider = "gmail" | "outlook";
+
 /**  Failure response correlated with a [`RequestFrame`] by `id`. */
 export type ErrorFrame = {
 	id: number,
@@ -40,18 +43,54 @@ export type Frame = {
  */
 export type FrameKind = ({ Request: RequestFrame }) & { Cancel?: never; Error?: never; Event?: never; Register?: never; Response?: never; Shutdown?: never } | ({ Response: ResponseFrame }) & { Cancel?: never; Error?: never; Event?: never; Register?: never; Request?: never; Shutdown?: never } | ({ Event: EventFrame }) & { Cancel?: never; Error?: never; Register?: never; Request?: never; Response?: never; Shutdown?: never } | ({ Error: ErrorFrame }) & { Cancel?: never; Event?: never; Register?: never; Request?: never; Response?: never; Shutdown?: never } | ({ Cancel: CancelFrame }) & { Error?: never; Event?: never; Register?: never; Request?: never; Response?: never; Shutdown?: never } | ({ Register: RegisterFrame }) & { Cancel?: never; Error?: never; Event?: never; Request?: never; Response?: never; Shutdown?: never } | ({ Shutdown: ShutdownFrame }) & { Cancel?: never; Error?: never; Event?: never; Register?: never; Request?: never; Response?: never };
 
+/**
+ *  One message of an open email thread, as rendered in the webmail UI.
+ * 
+ *  Only messages the user has expanded are included — collapsed
+ *  messages in Gmail/Outlook have no body in the DOM and the extension
+ *  never expands them on the user's behalf.
+ */
+export type NativeEmailMessage = {
+	sender_name: string | null,
+	sender_address: string | null,
+	recipients?: string[],
+	/**
+	 *  Timestamp as displayed by the client. Left as a string because
+	 *  webmail UIs render localized, relative dates ("Yesterday, 9:14").
+	 */
+	sent_at: string | null,
+	/**  Visible plain-text body, quoted replies included. */
+	body: string,
+};
+
+/**
+ *  Snapshot of the email thread open in the focused webmail tab,
+ *  answered by the Gmail/Outlook site bundles for the
+ *  `GET_EMAIL_THREAD` bridge action.
+ */
+export type NativeEmailThread = {
+	/**  Same tab id contract as [`crate::NativeMetadata::tab_id`]. */
+	tab_id: number,
+	provider: EmailProvider,
+	subject: string | null,
+	/**  Messages in display order (oldest first). */
+	messages: NativeEmailMessage[],
+};
+
 /**
  *  Envelope for every payload the browser native-messaging host
  *  exchanges with the desktop bridge. Externally tagged on `kind` with
  *  the inner payload under `data` so the JSON shape stays stable as
  *  new wire-payload variants are added.
  * 
- *  At present only [`NativeMetadata`] crosses the bridge — page content
- *  is delivered through granular adapter tools (`browser_web_*`,
- *  `browser_youtube_*`, …) rather than through pre-bundled assets or
- *  snapshots.
+ *  Page content is primarily delivered through granular adapter tools
+ *  (`browser_web_*`, `browser_youtube_*`, …). The snapshot payloads are
+ *  the exceptions: [`NativeWebpageState`] is a generic fallback for
+ *  pages no per-site watcher understands, and [`NativeEmailThread`]
+ *  carries the open webmail thread so the desktop can apply its own
+ *  redaction before anything reaches the LLM.
  */
-export type NativeMessage = { kind: "NativeMetadata"; data: NativeMetadata };
+export type NativeMessage = { kind: "NativeMetadata"; data: NativeMetadata } | { kind: "NativeWebpageState"; data: NativeWebpageState } | { kind: "NativeEmailThread"; data: NativeEmailThread };
 
 export type NativeMetadata = {
 	/**
@@ -74,6 +113,36 @@ export type NativeMetadata = {
 	title: string | null,
 };
 
+/**
+ *  Raw snapshot of an arbitrary web page, answered by the `_common`
+ *  content script for the `GET_PAGE_STATE` bridge action.
+ * 
+ *  Used as the fallback context source when the focused tab has no
+ *  per-site watcher (or the watcher returned no context blocks). The
+ *  extension ships the page verbatim; boilerplate removal and
+ *  main-content detection happen desktop-side so the heuristics can be
+ *  tuned without an extension release.
+ */
+export type NativeWebpageState = {
+	/**  Same tab id contract as [`crate::NativeMetadata::tab_id`]. */
+	tab_id: number,
+	url: string,
+	title: string | null,
+	/**
+	 *  Serialized `document.documentElement.outerHTML`, truncated by the
+	 *  extension to stay under [`crate::MAX_FRAME_SIZE`].
+	 */
+	html: string,
+	/**  `<html lang>` attribute, when present. */
+	lang: string | null,
+	/**  `<meta name="description">` or `og:description`. */
+	description: string | null,
+	/**  `og:site_name`, when present. */
+	site_name: string | null,
+	/**  `<meta name="author">`, when present. */
+	byline: string | null,
+};
+
 /**
  *  Inline JSON payload carried by Request/Response/Event frames.
  * 
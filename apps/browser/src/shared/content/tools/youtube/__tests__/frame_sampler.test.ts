@@ -0,0 +1,59 @@
+import {
+	FrameBuffer,
+	SIMILARITY_THRESHOLD,
+	differenceHash,
+	hammingDistance,
+	pickSpread,
+	type SampledFrame,
+} from '../frame_sampler';
+import { describe, it, expect } from 'vitest';
+
+function frame(current_time: number, hash: bigint, video_id = 'abc'): SampledFrame {
+	return { video_id, current_time, width: 16, height: 9, image_base64: '', hash };
+}
+
+describe('differenceHash', () => {
+	it('sets a bit where a pixel is brighter than its right neighbour', () => {
+		const rgba = new Uint8ClampedArray(9 * 8 * 4);
+		// Bright first column, dark elsewhere: only x = 0 compares brighter.
+		for (let y = 0; y < 8; y++) rgba.fill(255, y * 36, y * 36 + 3);
+		const hash = differenceHash(rgba);
+		expect(hammingDistance(hash, 0n)).toBe(8);
+		expect(hash >> 63n).toBe(1n);
+	});
+});
+
+describe('pickSpread', () => {
+	it('spreads evenly and ends with the newest', () => {
+		expect(pickSpread([0, 1, 2, 3, 4, 5, 6], 3)).toEqual([0, 3, 6]);
+		expect(pickSpread([0, 1, 2], 5)).toEqual([0, 1, 2]);
+		expect(pickSpread([0, 1, 2], 1)).toEqual([2]);
+	});
+});
+
+describe('FrameBuffer', () => {
+	it('skips frames that look like the newest kept one', () => {
+		const buffer = new FrameBuffer();
+		expect(buffer.push(frame(1, 0n))).toBe(true);
+		expect(buffer.push(frame(2, (1n << BigInt(SIMILARITY_THRESHOLD)) - 1n))).toBe(false);
+		expect(buffer.push(frame(3, 0xffffn))).toBe(true);
+		expect(buffer.recent('abc', 10, 8).map((f) => f.current_time)).toEqual([1, 3]);
+	});
+
+	it('limits to the requested stretch of playback', () => {
+		const buffer = new FrameBuffer();
+		for (let t = 0; t < 20; t++) buffer.push(frame(t, t % 2 === 0 ? 0n : 0xffffn));
+		const recent = buffer.recent('abc', 5, 8);
+		expect(recent.map((f) => f.current_time)).toEqual([14, 15, 16, 17, 18, 19]);
+	});
+
+	it('starts over after a backwards seek or a new video', () => {
+		const buffer = new FrameBuffer();
+		buffer.push(frame(50, 0n));
+		buffer.push(frame(10, 0xffffn));
+		expect(buffer.recent('abc', 30, 8).map((f) => f.current_time)).toEqual([10]);
+		buffer.push(frame(11, 0n, 'xyz'));
+		expect(buffer.recent('abc', 30, 8)).toEqual([]);
+		expect(buffer.recent('xyz', 30, 8)).toHaveLength(1);
+	});
+});
@@ -0,0 +1,57 @@
+import {
+	flattenWithTimestamps,
+	formatTimestamp,
+	selectWindow,
+	toSegments,
+} from '../get_transcript_window';
+import { describe, it, expect } from 'vitest';
+
+const SNIPPETS = [
+	{ start: 0, duration: 4, text: 'intro' },
+	{ start: 4, duration: 3, text: '  \n ' },
+	{ start: 7, duration: 5, text: 'first\npoint' },
+	{ start: 60, duration: 6, text: 'second point' },
+	{ start: 3_700, duration: 2, text: 'outro' },
+];
+
+describe('toSegments', () => {
+	it('derives end times and drops blank lines', () => {
+		expect(toSegments(SNIPPETS).slice(0, 2)).toEqual([
+			{ start: 0, end: 4, text: 'intro' },
+			{ start: 7, end: 12, text: 'first point' },
+		]);
+	});
+});
+
+describe('selectWindow', () => {
+	const segments = toSegments(SNIPPETS);
+
+	it('keeps segments overlapping either edge whole', () => {
+		const selected = selectWindow(segments, 10, 5, 52);
+		expect(selected.windowStart).toBe(5);
+		expect(selected.windowEnd).toBe(62);
+		expect(selected.segments.map((s) => s.text)).toEqual(['first point', 'second point']);
+	});
+
+	it('clamps the window at the start of the video', () => {
+		const selected = selectWindow(segments, 2, 30, 1);
+		expect(selected.windowStart).toBe(0);
+		expect(selected.segments.map((s) => s.text)).toEqual(['intro']);
+	});
+});
+
+describe('flattenWithTimestamps', () => {
+	it('prefixes each line with its start time', () => {
+		const text = flattenWithTimestamps(toSegments(SNIPPETS));
+		expect(text.split('\n')).toEqual([
+			'[0:00] intro',
+			'[0:07] first point',
+			'[1:00] second point',
+			'[1:01:40] outro',
+		]);
+	});
+
+	it('formats hours with padded minutes', () => {
+		expect(formatTimestamp(3_605.9)).toBe('1:00:05');
+	});
+});
@@ -0,0 +1,157 @@
+/// Rolling buffer of recent video frames, so a question about "what just
+/// happened" can be answered from several frames instead of only the one
+/// on screen when the question was asked.
+///
+/// While a watch or shorts video plays, the sampler grabs a downscaled
+/// frame every `SAMPLE_INTERVAL_MS`. Each frame gets a 64-bit difference
+/// hash (9×8 greyscale, one bit per horizontal neighbour comparison) and
+/// is dropped when it is within `SIMILARITY_THRESHOLD` bits of the last
+/// kept frame — the same measure the desktop uses to skip duplicate
+/// screenshots — so a static slide costs one entry, not thirty.
+
+import { getCurrentVideoId } from './_lib';
+
+export interface SampledFrame {
+	video_id: string;
+	/// Playback position the frame was captured at, in seconds.
+	current_time: number;
+	width: number;
+	height: number;
+	/// JPEG, base64-encoded without the `data:` prefix.
+	image_base64: string;
+	hash: bigint;
+}
+
+const SAMPLE_INTERVAL_MS = 1_000;
+/// Oldest playback time kept, relative to the newest frame.
+export const BUFFER_SECONDS = 30;
+/// Hashes this many bits apart or fewer count as the same frame.
+export const SIMILARITY_THRESHOLD = 5;
+/// Longest edge of a buffered frame. Enough to read on-screen text on a
+/// slide while keeping thirty buffered frames to a few megabytes.
+const MAX_EDGE = 768;
+const JPEG_QUALITY = 0.8;
+
+export function hammingDistance(a: bigint, b: bigint): number {
+	let diff = a ^ b;
+	let count = 0;
+	while (diff > 0n) {
+		count += Number(diff & 1n);
+		diff >>= 1n;
+	}
+	return count;
+}
+
+/// Difference hash of a 9×8 RGBA pixel block (`ImageData.data` layout).
+export function differenceHash(rgba: Uint8ClampedArray): bigint {
+	const luma = (i: number) => 0.299 * rgba[i] + 0.587 * rgba[i + 1] + 0.114 * rgba[i + 2];
+	let bits = 0n;
+	for (let y = 0; y < 8; y++) {
+		for (let x = 0; x < 8; x++) {
+			const here = (y * 9 + x) * 4;
+			bits = (bits << 1n) | (luma(here) > luma(here + 4) ? 1n : 0n);
+		}
+	}
+	return bits;
+}
+
+/// Up to `count` frames spread evenly across `frames` (oldest first),
+/// always ending with the newest one.
+export function pickSpread<T>(frames: readonly T[], count: number): T[] {
+	if (count <= 0 || frames.length === 0) return [];
+	if (frames.length <= count) return [...frames];
+	if (count === 1) return [frames[frames.length - 1]];
+	const step = (frames.length - 1) / (count - 1);
+	return Array.from({ length: count }, (_, i) => frames[Math.round(i * step)]);
+}
+
+/// Frames of one video, oldest first. Seeking backwards or switching
+/// videos starts the buffer over, so "the last M seconds" always means
+/// the stretch the user just watched.
+export class FrameBuffer {
+	private frames: SampledFrame[] = [];
+
+	/// Add `frame` unless it looks like the newest buffered frame.
+	/// Returns whether it was kept.
+	push(frame: SampledFrame): boolean {
+		const last = this.frames.at(-1);
+		if (last && (last.video_id !== frame.video_id || frame.current_time < last.current_time)) {
+			this.frames = [];
+		}
+		const newest = this.frames.at(-1);
+		if (newest && hammingDistance(newest.hash, frame.hash) <= SIMILARITY_THRESHOLD) {
+			return false;
+		}
+		this.frames.push(frame);
+		const oldest = frame.current_time - BUFFER_SECONDS;
+		this.frames = this.frames.filter((f) => f.current_time >= oldest);
+		return true;
+	}
+
+	/// Up to `count` frames of `videoId` from the `seconds` before the
+	/// newest one, spread evenly, oldest first.
+	recent(videoId: string, seconds: number, count: number): SampledFrame[] {
+		const newest = this.frames.at(-1);
+		if (!newest || newest.video_id !== videoId) return [];
+		const from = newest.current_time - seconds;
+		return pickSpread(this.frames.filter((f) => f.current_time >= from), count);
+	}
+}
+
+export const FRAME_BUFFER = new FrameBuffer();
+
+/// Created lazily, like the single-frame tool's canvas, so the module
+/// can be imported outside a browser.
+let frameCanvas: HTMLCanvasElement | null = null;
+let hashCanvas: HTMLCanvasElement | null = null;
+
+/// Capture the player's current frame, downscaled. `null` when there is
+/// no player, it hasn't decoded a frame yet, or the page isn't a video.
+export function captureFrame(): SampledFrame | null {
+	const videoId = getCurrentVideoId();
+	const player = document.querySelector<HTMLVideoElement>('video.html5-main-video');
+	if (videoId === null || !player || player.readyState < 2 || player.videoWidth === 0) {
+		return null;
+	}
+	const scale = Math.min(1, MAX_EDGE / Math.max(player.videoWidth, player.videoHeight));
+	frameCanvas ??= document.createElement('canvas');
+	frameCanvas.width = Math.round(player.videoWidth * scale);
+	frameCanvas.height = Math.round(player.videoHeight * scale);
+	const ctx = frameCanvas.getContext('2d');
+	hashCanvas ??= document.createElement('canvas');
+	hashCanvas.width = 9;
+	hashCanvas.height = 8;
+	const hashCtx = hashCanvas.getContext('2d', { willReadFrequently: true });
+	if (!ctx || !hashCtx) return null;
+	ctx.drawImage(player, 0, 0, frameCanvas.width, frameCanvas.height);
+	hashCtx.drawImage(frameCanvas, 0, 0, 9, 8);
+	return {
+		video_id: videoId,
+		current_time: player.currentTime,
+		width: frameCanvas.width,
+		height: frameCanvas.height,
+		image_base64: frameCanvas.toDataURL('image/jpeg', JPEG_QUALITY).split(',')[1],
+		hash: differenceHash(hashCtx.getImageData(0, 0, 9, 8).data),
+	};
+}
+
+let samplerTimer: ReturnType<typeof setInterval> | null = null;
+
+/// Start sampling into [`FRAME_BUFFER`]. Idempotent. Samples only while
+/// the tab is visible and the video is playing — a paused or hidden
+/// video has nothing new to show.
+export function startFrameSampler(): void {
+	if (samplerTimer !== null) return;
+	samplerTimer = setInterval(() => {
+		if (document.visibilityState !== 'visible') return;
+		const player = document.querySelector<HTMLVideoElement>('video.html5-main-video');
+		if (!player || player.paused) return;
+		try {
+			const frame = captureFrame();
+			if (frame) FRAME_BUFFER.push(frame);
+		} catch {
+			// A tainted canvas (e.g. an ad served cross-origin) can't be
+			// read back; skip this tick rather than stop sampling.
+		}
+	}, SAMPLE_INTERVAL_MS);
+}
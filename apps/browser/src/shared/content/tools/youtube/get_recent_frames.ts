@@ -0,0 +1,73 @@
+import { requireCurrentVideoId, requirePlayer } from './_lib';
+import { BUFFER_SECONDS, FRAME_BUFFER, captureFrame } from './frame_sampler';
+import { z } from 'zod';
+import { zodToJsonSchema } from 'zod-to-json-schema';
+import type { Tool } from '../types';
+
+const DEFAULT_COUNT = 4;
+const DEFAULT_SECONDS = 10;
+
+const Args = z
+	.object({
+		count: z.number().int().min(1).max(8).optional(),
+		seconds: z.number().positive().max(BUFFER_SECONDS).optional(),
+	})
+	.strict();
+
+type ArgsT = z.infer<typeof Args>;
+
+const Frame = z.object({
+	current_time: z.number(),
+	width: z.number().int().nonnegative(),
+	height: z.number().int().nonnegative(),
+	image_base64: z.string(),
+});
+
+const Out = z.object({
+	video_id: z.string(),
+	current_time: z.number(),
+	frames: z.array(Frame),
+});
+
+type Result = z.infer<typeof Out>;
+
+export async function executeGetRecentFrames(args: ArgsT): Promise<Result> {
+	const videoId = requireCurrentVideoId();
+	const player = requirePlayer();
+	// The sampler may be up to a tick behind; make sure the frame on
+	// screen right now is the last one returned.
+	const now = captureFrame();
+	if (now) FRAME_BUFFER.push(now);
+	const frames = FRAME_BUFFER.recent(
+		videoId,
+		args.seconds ?? DEFAULT_SECONDS,
+		args.count ?? DEFAULT_COUNT,
+	);
+	return {
+		video_id: videoId,
+		current_time: player.currentTime,
+		frames: frames.map((f) => ({
+			current_time: f.current_time,
+			width: f.width,
+			height: f.height,
+			image_base64: f.image_base64,
+		})),
+	};
+}
+
+export const getRecentFrames: Tool<typeof Args, Result> = {
+	descriptor: {
+		name: 'youtube_get_recent_frames',
+		description: `Return up to \`count\` distinct frames (default ${DEFAULT_COUNT}, at most 8) from the last \`seconds\` of playback (default ${DEFAULT_SECONDS}, at most ${BUFFER_SECONDS}) on the active YouTube watch or shorts page, oldest first, each a base64-encoded JPEG with its playback \`current_time\`. Frames are sampled about once a second while the video plays, and near-identical frames are skipped, so a static slide yields one frame and a fast-changing scene yields several spread across the window. The last frame is what is on screen now. Prefer this over \`youtube_get_current_frame\` when the question is about what just happened rather than what is visible. Returns fewer frames (possibly only the current one) right after the user seeks or opens the video.`,
+		parameters: zodToJsonSchema(Args) as Record<string, unknown>,
+		output_schema: zodToJsonSchema(Out) as Record<string, unknown>,
+		timeout_ms: 3_000,
+		source: { kind: 'bridge', app_kind: 'browser' },
+		required_contexts: [],
+		requires_user_approval: false,
+	},
+	argsSchema: Args,
+	async run(args) {
+		return await executeGetRecentFrames(args);
+	},
+};
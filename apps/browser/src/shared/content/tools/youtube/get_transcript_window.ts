@@ -0,0 +1,129 @@
+import { fetchTranscriptOrExplain, requireCurrentVideoId, requirePlayer } from './_lib';
+import { z } from 'zod';
+import { zodToJsonSchema } from 'zod-to-json-schema';
+import type { Tool } from '../types';
+
+/// Seconds either side of `at` when the caller doesn't say.
+const DEFAULT_WINDOW_SECONDS = 30;
+
+const Args = z
+	.object({
+		at: z.number().nonnegative().optional(),
+		before: z.number().nonnegative().max(600).optional(),
+		after: z.number().nonnegative().max(600).optional(),
+		language: z.string().min(2).optional(),
+	})
+	.strict();
+
+type ArgsT = z.infer<typeof Args>;
+
+const Segment = z.object({
+	start: z.number().nonnegative(),
+	end: z.number().nonnegative(),
+	text: z.string(),
+});
+
+export type TranscriptSegment = z.infer<typeof Segment>;
+
+const Out = z.object({
+	video_id: z.string(),
+	language: z.string(),
+	is_generated: z.boolean(),
+	at: z.number(),
+	window_start: z.number(),
+	window_end: z.number(),
+	segments: z.array(Segment),
+	text: z.string(),
+});
+
+type Result = z.infer<typeof Out>;
+
+function normalizeText(input: string): string {
+	return input.replace(/\s+/g, ' ').trim();
+}
+
+/// Turn raw `{start, duration}` snippets into `{start, end}` segments,
+/// dropping lines that are empty once whitespace is collapsed (YouTube
+/// emits those for music cues and pauses).
+export function toSegments(
+	snippets: readonly { start: number; duration: number; text: string }[],
+): TranscriptSegment[] {
+	return snippets
+		.map((s) => ({ start: s.start, end: s.start + s.duration, text: normalizeText(s.text) }))
+		.filter((s) => s.text.length > 0);
+}
+
+/// Segments that overlap `[at - before, at + after]`, kept whole like
+/// [`filterTranscriptRange`]. The window is clamped at zero so a
+/// position near the start of the video doesn't ask for negative time.
+export function selectWindow(
+	segments: readonly TranscriptSegment[],
+	at: number,
+	before: number,
+	after: number,
+): { windowStart: number; windowEnd: number; segments: TranscriptSegment[] } {
+	const windowStart = Math.max(0, at - before);
+	const windowEnd = at + after;
+	return {
+		windowStart,
+		windowEnd,
+		segments: segments.filter((s) => s.end > windowStart && s.start < windowEnd),
+	};
+}
+
+/// `M:SS` or `H:MM:SS`, the form YouTube shows and
+/// [`parseHmsTimestamp`] reads back.
+export function formatTimestamp(seconds: number): string {
+	const total = Math.floor(seconds);
+	const h = Math.floor(total / 3_600);
+	const m = Math.floor((total % 3_600) / 60);
+	const s = String(total % 60).padStart(2, '0');
+	return h > 0 ? `${h}:${String(m).padStart(2, '0')}:${s}` : `${m}:${s}`;
+}
+
+/// One line per segment, each prefixed with its start time in brackets,
+/// so a prompt built from the text alone can still cite when something
+/// was said.
+export function flattenWithTimestamps(segments: readonly TranscriptSegment[]): string {
+	return segments.map((s) => `[${formatTimestamp(s.start)}] ${s.text}`).join('\n');
+}
+
+export async function executeGetTranscriptWindow(args: ArgsT): Promise<Result> {
+	const videoId = requireCurrentVideoId();
+	const at = args.at ?? requirePlayer().currentTime;
+	const fetched = await fetchTranscriptOrExplain(videoId, args.language);
+	const selected = selectWindow(
+		toSegments(fetched.snippets),
+		at,
+		args.before ?? DEFAULT_WINDOW_SECONDS,
+		args.after ?? DEFAULT_WINDOW_SECONDS,
+	);
+	return {
+		video_id: fetched.videoId,
+		language: fetched.languageCode,
+		is_generated: fetched.isGenerated,
+		at,
+		window_start: selected.windowStart,
+		window_end: selected.windowEnd,
+		segments: selected.segments,
+		text: flattenWithTimestamps(selected.segments),
+	};
+}
+
+export const getTranscriptWindow: Tool<typeof Args, Result> = {
+	descriptor: {
+		name: 'youtube_get_transcript_window',
+		description:
+			"Return the part of the active YouTube video's transcript around a playback position, both as structured `segments` (`start` / `end` in seconds from the start of the video, plus `text`) and as `text` with one `[M:SS] line` per segment, ready to quote with citations. `at` defaults to the user's current playback position, so with no arguments this answers \"what is being said right now\". `before` / `after` (seconds, default 30 each, at most 600) size the window; segments that overlap it are included whole, and `window_start` / `window_end` report the bounds actually used. Use `youtube_get_transcript` or `youtube_get_timed_transcript` for the whole video instead. Optional `language` is a YouTube caption-track code such as `'en'` or `'pt-BR'` — call `youtube_list_captions` for the codes on this video; defaults to `'en'`. Fails when the video has no captions, the player hasn't loaded (when `at` is omitted), or the YouTube data layer can't be reached.",
+		parameters: zodToJsonSchema(Args) as Record<string, unknown>,
+		output_schema: zodToJsonSchema(Out) as Record<string, unknown>,
+		timeout_ms: 10_000,
+		source: { kind: 'bridge', app_kind: 'browser' },
+		required_contexts: [],
+		requires_user_approval: false,
+	},
+	argsSchema: Args,
+	async run(args) {
+		return await executeGetTranscriptWindow(args);
+	},
+};
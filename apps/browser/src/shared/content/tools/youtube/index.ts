@@ -2,8 +2,10 @@ import { getPageKind, type PageKind } from './_lib';
 import { getCurrentFrame } from './get_current_frame';
 import { getCurrentTimestamp } from './get_current_timestamp';
 import { getPageContext } from './get_page_context';
+import { getRecentFrames } from './get_recent_frames';
 import { getTimedTranscript } from './get_timed_transcript';
 import { getTranscript } from './get_transcript';
+import { getTranscriptWindow } from './get_transcript_window';
 import { getVideoMetadata } from './get_video_metadata';
 import { listCaptions } from './list_captions';
 import { listChapters } from './list_chapters';
@@ -17,8 +19,10 @@ export {
 	getCurrentFrame,
 	getCurrentTimestamp,
 	getPageContext,
+	getRecentFrames,
 	getTimedTranscript,
 	getTranscript,
+	getTranscriptWindow,
 	getVideoMetadata,
 	listCaptions,
 	listChapters,
@@ -37,6 +41,7 @@ export {
 	resolveWatchVideoId,
 } from './_lib';
 export type { PageKind } from './_lib';
+export { startFrameSampler } from './frame_sampler';
 
 type ToolList = readonly Tool<z.ZodTypeAny, unknown>[];
 
@@ -48,16 +53,23 @@ const WATCH: ToolList = [
 	getPageContext,
 	getCurrentTimestamp,
 	getCurrentFrame,
+	getRecentFrames,
 	listCaptions,
 	getTranscript,
 	getTimedTranscript,
+	getTranscriptWindow,
 	getVideoMetadata,
 	listChapters,
 	listRecommendations,
 	seekTo,
 ] as const;
 
-const SHORTS: ToolList = [getPageContext, getCurrentTimestamp, getCurrentFrame] as const;
+const SHORTS: ToolList = [
+	getPageContext,
+	getCurrentTimestamp,
+	getCurrentFrame,
+	getRecentFrames,
+] as const;
 
 const SEARCH: ToolList = [getPageContext, listSearchResults] as const;
 
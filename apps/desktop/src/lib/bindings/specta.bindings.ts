@@ -25,6 +25,17 @@ export const commands = {
 	 *  so one bad asset can't block the rest of the page from rendering.
 	 */
 	activityList: (limit: number, offset: number) => typedError<SavedActivity[], SavedActivityError>(__TAURI_INVOKE("activity_list", { limit, offset })),
+	/**
+	 *  Stream up to `limit` persisted activities in rail order, starting
+	 *  after `cursor` (or at the most recent when `None`), sending each
+	 *  decorated batch to `channel` as soon as its icons are resolved.
+	 * 
+	 *  Returns the cursor to continue from, or `None` once the history is
+	 *  exhausted. If a later batch fails, the ones already sent stay
+	 *  valid; retrying from the same `cursor` re-sends them, which the
+	 *  rail's id dedupe absorbs.
+	 */
+	activityStream: (cursor: string | null, limit: number, channel: Channel<SavedActivity[]>) => typedError<string | null, SavedActivityError>(__TAURI_INVOKE("activity_stream", { cursor, limit, channel })),
 	chatCollectContext: (threadId: string) => typedError<ChatContext, StreamError>(__TAURI_INVOKE("chat_collect_context", { threadId })),
 	chatSendQuery: (threadId: string, channel: Channel<ChatServerMessage>, request: ChatSendRequest) => typedError<null, StreamError>(__TAURI_INVOKE("chat_send_query", { threadId, channel, request })),
 	chatRegenerate: (threadId: string, aiMessageId: string, channel: Channel<ChatServerMessage>) => typedError<null, StreamError>(__TAURI_INVOKE("chat_regenerate", { threadId, aiMessageId, channel })),
@@ -35,6 +46,56 @@ export const commands = {
 	settingsSetGeneral: (generalSettings: GeneralSettings) => typedError<GeneralSettings, SettingsError>(__TAURI_INVOKE("settings_set_general", { generalSettings })),
 	settingsGetApi: () => __TAURI_INVOKE<APISettings>("settings_get_api"),
 	settingsSetApi: (apiSettings: APISettings) => typedError<APISettings, SettingsError>(__TAURI_INVOKE("settings_set_api", { apiSettings })),
+	settingsGetFileAccess: () => __TAURI_INVOKE<FileAccessSettings>("settings_get_file_access"),
+	/**
+	 *  Replace the folders the assistant's file tools may read. Takes effect
+	 *  on the next chat turn; the tool backend reads the list fresh each
+	 *  time it advertises tools.
+	 */
+	settingsSetFileAccess: (fileAccess: FileAccessSettings) => typedError<FileAccessSettings, SettingsError>(__TAURI_INVOKE("settings_set_file_access", { fileAccess })),
+	settingsGetShell: () => __TAURI_INVOKE<ShellSettings>("settings_get_shell"),
+	/**
+	 *  Replace the workspace and program allowlist for the assistant's
+	 *  `run_command` tool. Program names are trimmed and deduplicated; a
+	 *  path, or a program the shell policy always refuses, is rejected so
+	 *  the list never shows something that can't run.
+	 */
+	settingsSetShell: (shell: ShellSettings) => typedError<ShellSettings, SettingsError>(__TAURI_INVOKE("settings_set_shell", { shell })),
+	settingsGetModeration: () => __TAURI_INVOKE<ModerationSettings>("settings_get_moderation"),
+	/**
+	 *  Replace the secret-scanning rules for outgoing context. Takes effect
+	 *  on the next chat turn. Rejects custom patterns that aren't valid
+	 *  regular expressions.
+	 */
+	settingsSetModeration: (moderation: ModerationSettings) => typedError<ModerationSettings, SettingsError>(__TAURI_INVOKE("settings_set_moderation", { moderation })),
+	settingsGetVoice: () => __TAURI_INVOKE<VoiceSettings>("settings_get_voice"),
+	/**
+	 *  Replace the push-to-talk settings. Takes effect from the next
+	 *  utterance.
+	 */
+	settingsSetVoice: (voice: VoiceSettings) => typedError<VoiceSettings, SettingsError>(__TAURI_INVOKE("settings_set_voice", { voice })),
+	settingsGetRegionCapture: () => __TAURI_INVOKE<RegionCaptureSettings>("settings_get_region_capture"),
+	/**  Replace the region-capture settings and re-register the shortcut. */
+	settingsSetRegionCapture: (regionCapture: RegionCaptureSettings) => typedError<RegionCaptureSettings, SettingsError>(__TAURI_INVOKE("settings_set_region_capture", { regionCapture })),
+	settingsGetTransfers: () => __TAURI_INVOKE<TransferSettings>("settings_get_transfers"),
+	/**
+	 *  Replace the bandwidth caps and sync schedule. Queued transfers are
+	 *  re-checked right away; ones in flight pick up the new caps from their
+	 *  next chunk.
+	 */
+	settingsSetTransfers: (transfers: TransferSettings) => typedError<TransferSettings, SettingsError>(__TAURI_INVOKE("settings_set_transfers", { transfers })),
+	settingsGetScripts: () => __TAURI_INVOKE<ScriptSettings>("settings_get_scripts"),
+	/**
+	 *  Replace the user's scripts and reschedule them. New scripts get an
+	 *  id; a script that doesn't compile is rejected with the error.
+	 */
+	settingsSetScripts: (scripts: ScriptSettings) => typedError<ScriptSettings, SettingsError>(__TAURI_INVOKE("settings_set_scripts", { scripts })),
+	/**
+	 *  Rewrite the personal database without its free space. The periodic
+	 *  maintenance reclaims most of it already; this is the button on the
+	 *  settings page for the rest.
+	 */
+	settingsCompactDatabase: () => typedError<DatabaseCompaction, SettingsError>(__TAURI_INVOKE("settings_compact_database")),
 	settingsGetShared: () => __TAURI_INVOKE<SharedSettings>("settings_get_shared"),
 	settingsSetShared: (shared: SharedSettings) => typedError<SharedSettings, SettingsError>(__TAURI_INVOKE("settings_set_shared", { shared })),
 	settingsGetDesktop: () => __TAURI_INVOKE<DesktopSettings>("settings_get_desktop"),
@@ -68,12 +129,19 @@ export const commands = {
 	 */
 	settingsRecordTelemetryConsent: (consent: TelemetryConsent) => typedError<TelemetryConsent, SettingsError>(__TAURI_INVOKE("settings_record_telemetry_consent", { consent })),
 	/**
-	 *  Returns the per-install telemetry state — currently just the
-	 *  anonymous distinct id. The cross-device consent toggles live under
-	 *  the cloud `desktop` section and are surfaced via
+	 *  Returns the per-install telemetry state: the anonymous distinct id
+	 *  and the local analytics kill switch. The cross-device consent toggles
+	 *  live under the cloud `desktop` section and are surfaced via
 	 *  [`settings_get_desktop`].
 	 */
 	settingsGetLocalTelemetry: () => __TAURI_INVOKE<TelemetryLocal>("settings_get_local_telemetry"),
+	/**
+	 *  Turn product analytics off (or back on) for this install, overriding
+	 *  the synced consent. Local-only: another device of the same user keeps
+	 *  its own choice. The frontend re-fetches the telemetry bootstrap
+	 *  afterwards to stop or start PostHog.
+	 */
+	settingsSetAnalyticsDisabled: (disabled: boolean) => typedError<TelemetryLocal, SettingsError>(__TAURI_INVOKE("settings_set_analytics_disabled", { disabled })),
 	/**
 	 *  Convenience read for the early-boot / pre-auth path where the
 	 *  frontend needs only the consent toggles. Equivalent to
@@ -81,6 +149,17 @@ export const commands = {
 	 *  documents that *only* the consent block crosses this boundary.
 	 */
 	settingsGetTelemetryConsent: () => __TAURI_INVOKE<TelemetryConsent>("settings_get_telemetry_consent"),
+	/**
+	 *  The registry of editable settings, for pages that render their form
+	 *  from it rather than from a hand-written section type.
+	 */
+	settingsGetSchema: () => __TAURI_INVOKE<SettingsSchema>("settings_get_schema"),
+	/**
+	 *  Write one registry key. The value is validated against the registry
+	 *  before anything changes; local keys persist to `local.json`, cloud
+	 *  keys to the cache followed by a push.
+	 */
+	settingsSetValue: (key: string, value: JsonValue) => typedError<null, SettingsError>(__TAURI_INVOKE("settings_set_value", { key, value })),
 	systemCheckBackendConnection: (serverAddress: string | null) => typedError<string, SystemError>(__TAURI_INVOKE("system_check_backend_connection", { serverAddress })),
 	systemGetLlmInfo: () => typedError<RedactedLlmConfig, SystemError>(__TAURI_INVOKE("system_get_llm_info")),
 	/**
@@ -121,16 +200,169 @@ export const commands = {
 	 */
 	frontendReady: () => typedError<null, SystemError>(__TAURI_INVOKE("frontend_ready")),
 	systemReinitTelemetry: () => __TAURI_INVOKE<void>("system_reinit_telemetry"),
+	/**
+	 *  Crashes waiting for the user to say whether to send them. Empty in
+	 *  builds that have nowhere to send them.
+	 */
+	systemPendingCrashReports: () => __TAURI_INVOKE<PendingCrashReport[]>("system_pending_crash_reports"),
+	/**
+	 *  The user's answer to "send crash report?": upload the saved reports
+	 *  (`send`) or delete them. Returns how many were sent. Sending is
+	 *  consent for these reports only; it doesn't turn error reporting on.
+	 */
+	systemResolveCrashReports: (send: boolean) => typedError<number, SystemError>(__TAURI_INVOKE("system_resolve_crash_reports", { send })),
 	systemRotateTelemetryDistinctId: () => typedError<string, SystemError>(__TAURI_INVOKE("system_rotate_telemetry_distinct_id")),
+	toolConsentRespond: (requestId: string, decision: ConsentDecision) => typedError<null, ToolConsentError>(__TAURI_INVOKE("tool_consent_respond", { requestId, decision })),
+	/**
+	 *  Push-to-talk pressed: open the microphone and stream transcripts to
+	 *  `channel`. Cancels an utterance still in progress.
+	 */
+	voiceStart: (channel: Channel<VoiceEvent>) => typedError<null, VoiceError>(__TAURI_INVOKE("voice_start", { channel })),
+	/**
+	 *  Push-to-talk released: stop recording. The final transcript arrives on
+	 *  the channel passed to [`voice_start`].
+	 */
+	voiceStop: () => __TAURI_INVOKE<void>("voice_stop"),
+	/**  Throw the current utterance away without a transcript. */
+	voiceCancel: () => __TAURI_INVOKE<void>("voice_cancel"),
+	/**
+	 *  Read `text`, the next piece of a streamed response, aloud. Sentences
+	 *  are spoken as soon as they are complete; call [`speech_finish`] after
+	 *  the last piece.
+	 */
+	speechPush: (text: string) => typedError<null, VoiceError>(__TAURI_INVOKE("speech_push", { text })),
+	/**  The response is complete: read whatever is left of it. */
+	speechFinish: () => __TAURI_INVOKE<void>("speech_finish"),
+	speechPause: () => __TAURI_INVOKE<void>("speech_pause"),
+	/**
+	 *  Continue after [`speech_pause`], from the start of the interrupted
+	 *  sentence.
+	 */
+	speechResume: () => __TAURI_INVOKE<void>("speech_resume"),
+	/**  Stop reading and drop the rest of the response. */
+	speechStop: () => __TAURI_INVOKE<void>("speech_stop"),
+	/**
+	 *  The platform voices the settings page can offer. Empty where the
+	 *  platform doesn't let us choose.
+	 */
+	speechListVoices: () => typedError<SpeechVoice[], VoiceError>(__TAURI_INVOKE("speech_list_voices")),
+	/**  Open the selection overlay, as the capture shortcut does. */
+	regionCaptureStart: () => typedError<null, RegionCaptureError>(__TAURI_INVOKE("region_capture_start")),
+	/**  The frozen screen, as a data URL, for the overlay to draw. */
+	regionCaptureFrame: () => typedError<string, RegionCaptureError>(__TAURI_INVOKE("region_capture_frame")),
+	/**
+	 *  Attach the selected region to the next question: close the overlay and
+	 *  bring the main window forward with the capture in the question box.
+	 *  `annotations` are drawn onto the capture first, placed like the
+	 *  selection. The capture is saved as an asset, and its text read, in the
+	 *  background.
+	 */
+	regionCaptureSelect: (selection: RegionSelection, annotations: RegionAnnotation[]) => typedError<null, RegionCaptureError>(__TAURI_INVOKE("region_capture_select", { selection, annotations })),
+	/**  Close the overlay without capturing. */
+	regionCaptureCancel: () => __TAURI_INVOKE<void>("region_capture_cancel"),
+	/**  Detach the capture from the question box before it is sent. */
+	regionCaptureDiscard: () => __TAURI_INVOKE<void>("region_capture_discard"),
+	pluginList: () => typedError<PluginInfo[], PluginCommandError>(__TAURI_INVOKE("plugin_list")),
+	/**
+	 *  Install the plugin in the directory at `path`. It starts out
+	 *  disabled; enable it with [`plugin_enable`] once the user has approved
+	 *  its capabilities.
+	 */
+	pluginInstall: (path: string) => typedError<PluginInfo, PluginCommandError>(__TAURI_INVOKE("plugin_install", { path })),
+	/**
+	 *  Replace an installed plugin with the newer version in the directory
+	 *  at `path`. Comes back disabled if the new version asks for more than
+	 *  the user granted.
+	 */
+	pluginUpdate: (path: string) => typedError<PluginInfo, PluginCommandError>(__TAURI_INVOKE("plugin_update", { path })),
+	/**
+	 *  Enable a plugin with the capabilities the user approved in the
+	 *  permission prompt. `allow_unsandboxed` records that the user agreed
+	 *  to run an executable plugin without an OS sandbox; without it such a
+	 *  plugin is refused where there's no sandbox.
+	 */
+	pluginEnable: (id: string, granted: Capability[], allowUnsandboxed: boolean) => typedError<PluginInfo, PluginCommandError>(__TAURI_INVOKE("plugin_enable", { id, granted, allowUnsandboxed })),
+	pluginDisable: (id: string) => typedError<PluginInfo, PluginCommandError>(__TAURI_INVOKE("plugin_disable", { id })),
+	pluginUninstall: (id: string) => typedError<null, PluginCommandError>(__TAURI_INVOKE("plugin_uninstall", { id })),
+	/**
+	 *  Run a saved script now, enabled or not, and wait for it to finish.
+	 *  The outcome is in the returned run, which is audited like any other.
+	 */
+	scriptRunNow: (id: string) => typedError<ScriptRun, ScriptCommandError>(__TAURI_INVOKE("script_run_now", { id })),
+	/**
+	 *  The newest runs from the audit log, of one script or of all, newest
+	 *  first.
+	 */
+	scriptListRuns: (scriptId: string | null, limit: number) => typedError<ScriptRun[], ScriptCommandError>(__TAURI_INVOKE("script_list_runs", { scriptId, limit })),
+	/**
+	 *  Start a focus session. `distractions` are the activity names counted
+	 *  as off-topic; the built-in list of video and social sites when `None`.
+	 */
+	focusStart: (goal: string, plannedMinutes: number, distractions: string[] | null) => typedError<FocusSession, FocusCommandError>(__TAURI_INVOKE("focus_start", { goal, plannedMinutes, distractions })),
+	focusPause: () => typedError<FocusSession, FocusCommandError>(__TAURI_INVOKE("focus_pause")),
+	focusResume: () => typedError<FocusSession, FocusCommandError>(__TAURI_INVOKE("focus_resume")),
+	/**
+	 *  End the session in progress early as completed. Its recap is written
+	 *  in the background and announced with a `FocusNotification`.
+	 */
+	focusStop: () => typedError<FocusSession, FocusCommandError>(__TAURI_INVOKE("focus_stop")),
+	/**
+	 *  End the session in progress as cancelled, without a recap.
+	 */
+	focusCancel: () => typedError<FocusSession, FocusCommandError>(__TAURI_INVOKE("focus_cancel")),
+	/**
+	 *  The session running or paused, with its activities and metrics so far.
+	 */
+	focusActive: () => typedError<FocusDetails | null, FocusCommandError>(__TAURI_INVOKE("focus_active")),
+	focusGet: (id: string) => typedError<FocusDetails, FocusCommandError>(__TAURI_INVOKE("focus_get", { id })),
+	/**
+	 *  Sessions newest first.
+	 */
+	focusList: (limit: number, offset: number) => typedError<FocusSession[], FocusCommandError>(__TAURI_INVOKE("focus_list", { limit, offset })),
+	focusUpdateGoal: (id: string, goal: string) => typedError<FocusSession, FocusCommandError>(__TAURI_INVOKE("focus_update_goal", { id, goal })),
+	/**
+	 *  Delete a session and its activities, ending it if it is in progress.
+	 */
+	focusDelete: (id: string) => typedError<null, FocusCommandError>(__TAURI_INVOKE("focus_delete", { id })),
+	/**
+	 *  Write an ended session's recap again and wait for it.
+	 */
+	focusGenerateRecap: (id: string) => typedError<FocusSession, FocusCommandError>(__TAURI_INVOKE("focus_generate_recap", { id })),
+	/**
+	 *  Every budget with the time spent against it today, oldest first.
+	 */
+	screenTimeStatus: () => typedError<BudgetStatus[], ScreenTimeCommandError>(__TAURI_INVOKE("screen_time_status")),
+	/**
+	 *  Budget `daily_minutes` a day for the app or site named `target`.
+	 */
+	screenTimeCreateBudget: (target: string, dailyMinutes: number) => typedError<ScreenTimeBudget, ScreenTimeCommandError>(__TAURI_INVOKE("screen_time_create_budget", { target, dailyMinutes })),
+	screenTimeUpdateBudget: (id: string, target: string, dailyMinutes: number, enabled: boolean) => typedError<ScreenTimeBudget, ScreenTimeCommandError>(__TAURI_INVOKE("screen_time_update_budget", { id, target, dailyMinutes, enabled })),
+	screenTimeDeleteBudget: (id: string) => typedError<null, ScreenTimeCommandError>(__TAURI_INVOKE("screen_time_delete_budget", { id })),
+	/**
+	 *  The report on this week so far for `weeks_ago` 0, last week for 1, and
+	 *  so on.
+	 */
+	screenTimeReport: (weeksAgo: number) => typedError<ScreenTimeReport, ScreenTimeCommandError>(__TAURI_INVOKE("screen_time_report", { weeksAgo })),
 	threadList: (limit: number, offset: number) => typedError<Thread[], ThreadError>(__TAURI_INVOKE("thread_list", { limit, offset })),
 	threadListByActivity: (activityId: string, limit: number, offset: number) => typedError<Thread[], ThreadError>(__TAURI_INVOKE("thread_list_by_activity", { activityId, limit, offset })),
 	threadCreate: () => typedError<Thread, ThreadError>(__TAURI_INVOKE("thread_create")),
 	threadDelete: (threadId: string) => typedError<null, ThreadError>(__TAURI_INVOKE("thread_delete", { threadId })),
 	threadGetMessages: (threadId: string, limit: number, offset: number) => typedError<MessageNode[], ThreadError>(__TAURI_INVOKE("thread_get_messages", { threadId, limit, offset })),
 	threadSwitchBranch: (threadId: string, messageId: string, direction: number) => typedError<MessageNode[], ThreadError>(__TAURI_INVOKE("thread_switch_branch", { threadId, messageId, direction })),
-	threadGenerateTitle: (threadId: string) => typedError<Thread, ThreadError>(__TAURI_INVOKE("thread_generate_title", { threadId })),
+	threadGenerateTitle: (threadId: string, regenerate: boolean) => typedError<Thread, ThreadError>(__TAURI_INVOKE("thread_generate_title", { threadId, regenerate })),
 	threadSearchThreads: (query: string, limit: number, offset: number) => typedError<SearchThreadResult[], ThreadError>(__TAURI_INVOKE("thread_search_threads", { query, limit, offset })),
 	threadSearchMessages: (query: string, limit: number, offset: number) => typedError<SearchMessageResult[], ThreadError>(__TAURI_INVOKE("thread_search_messages", { query, limit, offset })),
+	/**
+	 *  `None` when the backend isn't in local mode, so the picker can hide its
+	 *  model manager.
+	 */
+	localModelList: () => typedError<LocalModel[] | null, ThreadError>(__TAURI_INVOKE("local_model_list")),
+	/**
+	 *  Pull `model`, forwarding progress to `channel`. Resolves once the pull
+	 *  is done or has failed; the last event on `channel` says which.
+	 */
+	localModelPull: (model: string, channel: Channel<LocalModelPullEvent>) => typedError<null, ThreadError>(__TAURI_INVOKE("local_model_pull", { model, channel })),
+	localModelDelete: (model: string) => typedError<null, ThreadError>(__TAURI_INVOKE("local_model_delete", { model })),
 };
 
 /** Events */
@@ -138,10 +370,18 @@ export const events = {
 	authStateChanged: makeEvent<AuthStateChanged>("auth-state-changed"),
 	browserExtensionStatusChanged: makeEvent<BrowserExtensionStatusChanged>("browser-extension-status-changed"),
 	consentGate: makeEvent<ConsentGate>("consent-gate"),
+	focusNotification: makeEvent<FocusNotification>("focus-notification"),
+	regionCaptured: makeEvent<RegionCaptured>("region-captured"),
 	savedActivityLiveSessionEnded: makeEvent<SavedActivityLiveSessionEnded>("saved-activity-live-session-ended"),
 	savedActivityUpserted: makeEvent<SavedActivityUpserted>("saved-activity-upserted"),
+	screenTimeNotification: makeEvent<ScreenTimeNotification>("screen-time-notification"),
+	scriptNotification: makeEvent<ScriptNotification>("script-notification"),
+	serverNotification: makeEvent<ServerNotification>("server-notification"),
+	speechStateChanged: makeEvent<SpeechStateChanged>("speech-state-changed"),
 	timelineAppEvent: makeEvent<TimelineAppEvent>("timeline-app-event"),
 	timelineAssetsEvent: makeEvent<TimelineAssetsEvent>("timeline-assets-event"),
+	toolConsentRequested: makeEvent<ToolConsentRequested>("tool-consent-requested"),
+	updateProgress: makeEvent<UpdateProgress>("update-progress"),
 };
 
 /* Types */
@@ -188,6 +428,14 @@ export type AccentColor = {
 	iconBg: string,
 };
 
+export type ActivityTime = {
+	name: string,
+	secs: number,
+	/**  Stretches spent in it. */
+	visits: number,
+	offTopic: boolean,
+};
+
 export type Annotation = { type: "citation"; id?: string | null; url?: string | null; title?: string | null; start_index?: bigint | null; end_index?: bigint | null; cited_text?: string | null; extras?: { [key in string]: unknown } | null } | { type: "non_standard_annotation"; id?: string | null; value: { [key in string]: unknown } };
 
 /**
@@ -212,6 +460,25 @@ export type AnyMessage = {
 	type: "remove",
 } & RemoveMessage;
 
+/**  One call a script made into the host API. */
+export type ApiCall = {
+	function: string,
+	/**
+	 *  What was asked for, shortened. What came back, such as clipboard
+	 *  contents or the model's answer, isn't kept.
+	 */
+	summary: string | null,
+	durationMs: number,
+	error: string | null,
+};
+
+export type AppUsage = {
+	name: string,
+	secs: number,
+	/**  Days of the week it was used on. */
+	days: number,
+};
+
 export type AudioContentBlock = {
 	id?: string | null,
 	file_id?: string | null,
@@ -304,6 +571,20 @@ export type BrowserExtensionStatusChanged = {
 	state: BrowserExtensionState,
 };
 
+/**  A budget with the time spent against it today. */
+export type BudgetStatus = {
+	budget: ScreenTimeBudget,
+	usedSecs: number,
+};
+
+/**  How a week went against one budget. */
+export type BudgetWeek = {
+	budget: ScreenTimeBudget,
+	secs: number,
+	/**  Days the daily allowance was exceeded. */
+	daysOver: number,
+};
+
 /**
  *  Per-turn host metadata returned by `chat_collect_context`.
  * 
@@ -313,6 +594,22 @@ export type BrowserExtensionStatusChanged = {
  *  pulled by the chat bridge from the [`thread_core::ToolBackend`] at
  *  turn start — no round trip through the UI is required for that.
  */
+/**
+ *  Something a plugin asks to be allowed to do. The user grants all of a
+ *  plugin's capabilities or none when enabling it.
+ */
+export type Capability = 
+/**  Offer the tools listed in the manifest to the assistant. */
+"tools" | 
+/**  Add text to the context of every chat turn. */
+"context" | 
+/**
+ *  Reach the network. Without it the sandbox, where the platform has
+ *  one, cuts a subprocess off; a module can only fetch from its
+ *  `allowed_hosts`.
+ */
+"network";
+
 export type ChatContext = {
 	assetChips: ContextChip[],
 };
@@ -336,6 +633,12 @@ export type ChatMessage = {
  *  when the user sent the message, so the server can record the link in
  *  `activity_threads`. Optional because non-desktop clients (web, mobile)
  *  have no timeline; absent values skip the link step entirely.
+ * 
+ *  Attachments ride in `content_blocks` as `image`, `text-plain` or `file`
+ *  blocks. Each either inlines its bytes (`base64`, or `text` for plain
+ *  text) or names an asset the caller already uploaded by `file_id`; a
+ *  client-supplied `url` is rejected. Documents are read into text before
+ *  the model sees them.
  */
 export type ChatSendRequest = {
 	content_blocks: ContentBlock[],
@@ -361,17 +664,28 @@ export type ChatSendRequest = {
 export type ChatServerMessage = 
 /**  The user's message has been persisted; clients should display it. */
 { type: "confirmed_human_message"; message: MessageNode } | 
-/**  One streaming chunk from the AI. */
+/**
+ *  One streaming chunk from the AI. The last chunk of each model
+ *  round has `chunk_position: "last"` and carries the round's
+ *  `usage_metadata` and the provider's `finish_reason` (in
+ *  `response_metadata`, e.g. `"stop"`, `"tool_calls"` or `"length"`).
+ */
 { type: "chunk"; chunk: AIMessageChunk } | 
 /**
  *  The thread's auto-generated title was updated this turn. Non-terminal;
  *  clients update the thread row in place. Emitted at most once per turn,
- *  always before any terminal frame.
+ *  always before any terminal frame. `tags` are the topic tags generated
+ *  with the title; omitted when there are none.
  */
-{ type: "title_updated"; title: string } | 
+{ type: "title_updated"; title: string; tags?: string[] } | 
 /**
  *  The turn ended successfully; tree positions for everything that was
- *  persisted during this turn (human + AI + any tool messages).
+ *  persisted during this turn (human + AI + any tool messages). When
+ *  the answer cites source passages, its text block carries one
+ *  `citation` annotation per cited passage: the marker's char span in
+ *  the text, the passage (`cited_text`), the source `title`, and in
+ *  `extras` the source's `file_id` plus its `paragraph` or, for
+ *  transcripts, `start_seconds`.
  */
 { type: "final"; messages: MessageNode[] } | 
 /**  The turn aborted with an error. The connection is closed after this. */
@@ -420,6 +734,11 @@ export type Claims = {
  */
 export type ConnectionMode = { kind: "default" } | { kind: "custom"; url: string };
 
+/**
+ *  The user's answer to a consent prompt.
+ */
+export type ConsentDecision = "allow_once" | "allow_always" | "deny";
+
 /**
  *  Pushed from Rust to the frontend whenever the desktop telemetry
  *  consent gate flips. Fired once during startup (in response to
@@ -483,6 +802,15 @@ export type ContextChip = {
 	domain: string | null,
 };
 
+/**
+ *  Size of the personal database before and after
+ *  [`settings_compact_database`], in bytes.
+ */
+export type DatabaseCompaction = {
+	bytes_before: number,
+	bytes_after: number,
+};
+
 /**
  *  Desktop-only cloud-synced settings. Mobile and web each have their
  *  own platform sections to keep concepts that don't translate (window
@@ -527,8 +855,19 @@ export type DesktopSettings = {
 	 *  specific to the data actually collected.
 	 */
 	telemetry?: TelemetryConsent,
+	/**
+	 *  Voice used to read responses aloud: a platform voice id, or the
+	 *  provider's voice name when speech goes through a provider. `None`
+	 *  uses the system default.
+	 */
+	speechVoice?: string | null,
+	speechRate?: SpeechRate,
 } & { [key in string]: unknown };
 
+export type FileAccessSettings = {
+	roots: string[],
+};
+
 export type FileContentBlock = {
 	id?: string | null,
 	file_id?: string | null,
@@ -539,8 +878,99 @@ export type FileContentBlock = {
 	extras?: { [key in string]: unknown } | null,
 };
 
+/**  A stretch of a session spent in one activity. */
+export type FocusActivity = {
+	id: string,
+	name: string,
+	processName: string,
+	startedAt: string,
+	/**  `None` for the stretch in progress. */
+	endedAt: string | null,
+};
+
+export type FocusCommandError = { type: "Unavailable" } | { type: "NotFound"; data: string } | { type: "Conflict"; data: string } | { type: "Invalid"; data: string } | { type: "Other"; data: string };
+
+/**  A session with its activities and what they add up to. */
+export type FocusDetails = {
+	session: FocusSession,
+	activities: FocusActivity[],
+	metrics: FocusMetrics,
+};
+
+/**  How a session went, from its activity stretches. */
+export type FocusMetrics = {
+	/**  Running time so far, pauses excluded. */
+	activeSecs: number,
+	/**  Times focus moved to a different activity. */
+	switches: number,
+	/**  Time in activities on the session's distraction list. */
+	offTopicSecs: number,
+	/**  Time per activity, longest first. */
+	activities: ActivityTime[],
+};
+
+/**  A focus session needs the user's attention. */
+export type FocusNotification = {
+	sessionId: string,
+	kind: FocusNotificationKind,
+	title: string,
+	body: string,
+};
+
+/**
+ *  `completed` when a session ran its planned length, `recap_ready` once
+ *  its recap is written.
+ */
+export type FocusNotificationKind = "completed" | "recap_ready";
+
+/**  A timed work interval. */
+export type FocusSession = {
+	id: string,
+	/**  What the user meant to work on. */
+	goal: string,
+	plannedSecs: number,
+	state: FocusState,
+	/**
+	 *  Activity names counted as off-topic, matched case-insensitively
+	 *  against the activity's name and process name.
+	 */
+	distractions: string[],
+	startedAt: string,
+	/**  When the pause in progress began. */
+	pausedAt: string | null,
+	/**  Length of the finished pauses. */
+	pausedSecs: number,
+	endedAt: string | null,
+	/**  The model's end-of-session recap, once written. */
+	recap: string | null,
+	/**  The chat thread the recap was written in, to continue from. */
+	recapThreadId: string | null,
+};
+
+/**
+ *  Where a session is. A session is `completed` whether it ran its
+ *  planned length or was stopped early by the user.
+ */
+export type FocusState = "running" | "paused" | "completed" | "cancelled";
+
 export type GeneralSettings = {
 	autostart: boolean,
+	/**
+	 *  Keep user context on this machine: chat and activity sync only
+	 *  work against a local backend with local models. Enforced by
+	 *  `euro_endpoint::EndpointManager`.
+	 */
+	localOnly?: boolean,
+};
+
+/**
+ *  A global shortcut, e.g. `{ "modifiers": ["ctrl", "shift"], "key": "keys" }`.
+ *  Modifiers are `ctrl`, `alt`, `shift` or `meta`; keys are DOM
+ *  `KeyboardEvent.code` names, lowercased.
+ */
+export type Hotkey = {
+	modifiers: string[],
+	key: string,
 };
 
 export type HumanMessage = {
@@ -597,6 +1027,42 @@ export type InvalidToolCallBlock = {
 	extras?: { [key in string]: unknown } | null,
 };
 
+export type JsonValue = null | boolean | number | string | JsonValue[] | { [key in string]: JsonValue };
+
+/**  A model installed on the deployment's Ollama server. */
+export type LocalModel = {
+	/**  Name as used in model settings and pulls, e.g. `llama3.1:8b`. */
+	name: string,
+	/**  Size on disk, in bytes. */
+	size_bytes: bigint,
+	digest: string,
+	family?: string | null,
+	/**  Human-readable parameter count, e.g. `8.0B`. */
+	parameter_size?: string | null,
+	/**  e.g. `Q4_K_M`. */
+	quantization?: string | null,
+	modified_at?: string | null,
+};
+
+/**
+ *  One line of the `application/x-ndjson` body of
+ *  `POST /models/local/pull`.
+ * 
+ *  The stream ends after a `Done` or a `Failed`; a body that ends without
+ *  either means the connection dropped and the pull can be retried, which
+ *  resumes it.
+ */
+export type LocalModelPullEvent = 
+/**
+ *  The pull moved on. `status` is Ollama's free-form phase, e.g.
+ *  `pulling manifest`; byte counts are set while a layer downloads.
+ */
+{ type: "progress"; status: string; total?: bigint | null; completed?: bigint | null } | 
+/**  The model is installed. */
+{ type: "done" } | 
+/**  The pull stopped; `message` says why. */
+{ type: "failed"; message: string };
+
 export type LoginToken = {
 	code_challenge: string,
 	/**
@@ -623,12 +1089,75 @@ export type ModelRef = {
 	model: string,
 };
 
+export type ModerationAction = "redact" | "block";
+
+/**  An OpenAI-compatible `/moderations` endpoint. */
+export type ModerationProviderSettings = {
+	/**  API root, e.g. `https://api.openai.com/v1`. */
+	baseUrl: string,
+	apiKey: string,
+};
+
+export type ModerationSettings = {
+	enabled: boolean,
+	action: ModerationAction,
+	/**  Extra regular expressions to treat as secrets. */
+	customPatterns: string[],
+	/**  Hosted moderation endpoint consulted after the local pass. */
+	provider: ModerationProviderSettings | null,
+	/**
+	 *  Strings text read off the screen may keep even when they look like
+	 *  a secret.
+	 */
+	ocrAllowlist: string[],
+	/**
+	 *  How closely text read off each app is checked, by process name.
+	 *  Password managers not listed here are `strict`.
+	 */
+	ocrAppSensitivity: { [key in string]: OcrSensitivity },
+};
+
 export type NonStandardContentBlock = {
 	id?: string | null,
 	value?: { [key in string]: unknown },
 	index?: BlockIndex | null,
 };
 
+/**
+ *  One delivered event. `id` increases monotonically and is the cursor a
+ *  reconnecting client resumes from.
+ */
+export type Notification = {
+	id: bigint,
+	event: NotificationEvent,
+	created_at: string,
+};
+
+/**
+ *  Something that happened server-side that a connected client should
+ *  react to without polling.
+ * 
+ *  Clients must ignore events they don't know: new kinds are added without
+ *  bumping the protocol, and an older client decodes them as
+ *  [`NotificationEvent::Unknown`].
+ */
+export type NotificationEvent = 
+/**  Another user shared a thread with this one. */
+{ type: "thread_shared"; thread_id: string } | 
+/**  The user's plan changed; refetch entitlements and usage limits. */
+{ type: "subscription_changed"; plan_id: string } | 
+/**  A data export finished building and can be downloaded. */
+{ type: "export_ready"; export_id: string } | 
+/**
+ *  Clients older than `min_version` must update before continuing.
+ *  Sent to every user.
+ */
+{ type: "update_required"; min_version: string } | 
+/**  An event kind this build doesn't know about. */
+({ type: "unknown" }) & { thread_id?: never; plan_id?: never; export_id?: never; min_version?: never };
+
+export type OcrSensitivity = "relaxed" | "standard" | "strict";
+
 export type OutputTokenDetails = {
 	audio?: bigint | null,
 	reasoning?: bigint | null,
@@ -643,6 +1172,15 @@ export type OutputTokenDetails = {
  */
 export type PaymentError = { type: "Auth"; data: AuthError } | { type: "Backend"; data: string } | { type: "BadResponse"; data: string };
 
+/**  A crash from an earlier run, saved while error reporting was off. */
+export type PendingCrashReport = {
+	id: string,
+	/**  Milliseconds since the Unix epoch. */
+	crashedAtMs: number,
+	message: string,
+	release: string,
+};
+
 export type PlainTextContentBlock = {
 	id?: string | null,
 	file_id?: string | null,
@@ -656,6 +1194,55 @@ export type PlainTextContentBlock = {
 	extras?: { [key in string]: unknown } | null,
 };
 
+export type PluginCommandError = { type: "Unavailable" } | { type: "InvalidManifest"; data: string } | { type: "NotInstalled"; data: string } | { type: "AlreadyInstalled"; data: string } | 
+/**  Updates only go forward; `data` is the installed version. */
+{ type: "NotNewer"; data: string } | 
+/**  The approval didn't cover these. Ask again with them listed. */
+{ type: "CapabilitiesNotGranted"; data: Capability[] } | 
+/**
+ *  There's no OS sandbox for this executable plugin. Ask again, saying
+ *  it will run unsandboxed, and pass `allowUnsandboxed`.
+ */
+{ type: "SandboxUnavailable" } | { type: "Other"; data: string };
+
+/**  An installed plugin, as the settings UI shows it. */
+export type PluginInfo = {
+	id: string,
+	name: string,
+	version: string,
+	description: string,
+	/**  What the plugin asks for. Enabling it grants all of these. */
+	capabilities: Capability[],
+	/**
+	 *  What the user granted. Short of `capabilities` after an update
+	 *  that asks for more, which leaves the plugin disabled.
+	 */
+	granted: Capability[],
+	/**
+	 *  Hosts a module may fetch from once granted
+	 *  [`Capability::Network`].
+	 */
+	allowedHosts: string[],
+	tools: ToolSummary[],
+	enabled: boolean,
+	/**
+	 *  Whether the plugin is a WebAssembly module rather than an
+	 *  executable.
+	 */
+	module: boolean,
+	/**
+	 *  Whether the plugin runs sandboxed on this machine. Modules always
+	 *  do; executables need an OS sandbox.
+	 */
+	sandboxed: boolean,
+	/**
+	 *  Whether the user agreed to run it without a sandbox. An
+	 *  executable that isn't `sandboxed` only starts with this set.
+	 */
+	allowUnsandboxed: boolean,
+	usage: ResourceUsage,
+};
+
 export type ReasoningContentBlock = {
 	id?: string | null,
 	reasoning?: string | null,
@@ -680,7 +1267,64 @@ export type RedactedLlmConfig = {
 	roles: Roles,
 };
 
-export type RedactedProvider = { kind: "openai"; has_api_key: boolean; base_url: string | null; organization: string | null } | { kind: "anthropic"; has_api_key: boolean; base_url: string | null } | { kind: "google"; credentials: RedactedGoogleCreds; project: string | null } | { kind: "bedrock"; region: string; credentials: RedactedAwsCreds } | { kind: "openai_compatible"; base_url: string; has_api_key: boolean; header_names: string[]; has_overrides: boolean };
+export type RedactedProvider = { kind: "openai"; has_api_key: boolean; base_url: string | null; organization: string | null } | { kind: "anthropic"; has_api_key: boolean; base_url: string | null } | { kind: "google"; credentials: RedactedGoogleCreds; project: string | null } | { kind: "bedrock"; region: string; credentials: RedactedAwsCreds } | { kind: "azure_openai"; endpoint: string; has_api_key: boolean; api_version: string; header_names: string[] } | { kind: "openrouter"; base_url: string | null; has_api_key: boolean; header_names: string[] } | { kind: "openai_compatible"; base_url: string; has_api_key: boolean; header_names: string[]; has_overrides: boolean };
+
+/**
+ *  Markup the user drew on the overlay, drawn onto the capture in its
+ *  default colour.
+ */
+export type RegionAnnotation = { type: "Box"; from: RegionPoint; to: RegionPoint } | { type: "Highlight"; from: RegionPoint; to: RegionPoint } | { type: "Arrow"; from: RegionPoint; to: RegionPoint } | { type: "Label"; at: RegionPoint; text: string };
+
+export type RegionCaptureError = { type: "NoDisplay" } | 
+/**  The overlay isn't open, or was already answered. */
+{ type: "NotSelecting" } | 
+/**  The rectangle is too small to be deliberate. The overlay stays open. */
+{ type: "EmptySelection" } | { type: "Capture"; data: string };
+
+export type RegionCaptureSettings = {
+	/**  Register the global capture shortcut. */
+	enabled: boolean,
+	/**  The platform default (the launcher shortcut plus Shift) when unset. */
+	hotkey: Hotkey | null,
+	/**
+	 *  Directory holding the `ocrs` detection and recognition models.
+	 *  Captures are attached without their text when unset, or when the
+	 *  build has no OCR support.
+	 */
+	ocrModelsDir: string | null,
+	/**
+	 *  Format captures are saved to the asset library in. What is sent to
+	 *  the model is unaffected.
+	 */
+	storageFormat: ScreenshotFormat,
+	/**  Quality from 1 to 100 for the lossy formats, JPEG and AVIF. */
+	storageQuality: number,
+};
+
+/**
+ *  Emitted when the user has selected a region, so the chat view can show
+ *  it alongside the question box.
+ */
+export type RegionCaptured = {
+	chip: ContextChip,
+};
+
+/**  A point on the overlay, as fractions of its width and height. */
+export type RegionPoint = {
+	x: number,
+	y: number,
+};
+
+/**
+ *  The rectangle the overlay reports, as fractions of its width and
+ *  height. `width` and `height` are negative for a drag up or left.
+ */
+export type RegionSelection = {
+	left: number,
+	top: number,
+	width: number,
+	height: number,
+};
 
 export type RemoveMessage = {
 	id: string,
@@ -689,6 +1333,47 @@ export type RemoveMessage = {
 	response_metadata?: { [key in string]: unknown },
 };
 
+/**
+ *  What a plugin has used since the app started, for the settings UI to
+ *  show next to what it was granted.
+ */
+export type ResourceUsage = {
+	calls: bigint,
+	/**  Calls stopped for running out of time or a [`Limit`]. */
+	limitHits: bigint,
+	/**  Wall time spent answering calls. */
+	busyMs: bigint,
+	/**
+	 *  Roughly one unit per WebAssembly instruction. Modules only, as are
+	 *  the fields below.
+	 */
+	fuel: bigint,
+	/**  Largest memory a module instance has grown to. */
+	peakMemoryBytes: bigint,
+	httpRequests: bigint,
+	httpBytesReceived: bigint,
+	/**  Current size of the key-value store. */
+	storedBytes: bigint,
+};
+
+/**
+ *  How much harm a tool call can do without the user noticing.
+ */
+export type RiskLevel = 
+/**
+ *  Reads context the user is already sharing. Never prompts.
+ */
+"safe" | 
+/**
+ *  Changes files, settings, or remote state. Prompts until the user
+ *  allows the tool permanently.
+ */
+"elevated" | 
+/**
+ *  Runs arbitrary commands or code. Prompts on every call.
+ */
+"dangerous";
+
 export type Role = "Free" | "Tier1";
 
 /**
@@ -702,6 +1387,10 @@ export type Roles = {
 	vision: ModelRef | null,
 };
 
+export type RunOutcome = "succeeded" | "failed" | "timed_out" | "cancelled";
+
+export type RunTrigger = "manual" | "schedule";
+
 /**
  *  Frontend-facing view of one persisted parent activity, with its
  *  most recent session embedded inline.
@@ -734,7 +1423,8 @@ export type SavedActivity = {
 };
 
 /**
- *  Errors surfaced to the frontend from [`activity_list`].
+ *  Errors surfaced to the frontend from [`activity_list`] and
+ *  [`activity_stream`].
  * 
  *  Externally tagged so the JS side gets `{ type: "Network", data: "..." }`
  *  and can branch on `type` rather than parsing strings. Variants are
@@ -742,7 +1432,7 @@ export type SavedActivity = {
  *  to `accent: None, icon_base64: None` on that row instead of failing
  *  the whole call.
  */
-export type SavedActivityError = { type: "StateUnavailable"; data: string } | { type: "Network"; data: string };
+export type SavedActivityError = { type: "StateUnavailable"; data: string } | { type: "Network"; data: string } | { type: "Channel"; data: string };
 
 /**
  *  Push event fired after the cloud closing PATCH of `ended_at`
@@ -785,7 +1475,100 @@ export type SavedActivitySession = {
  */
 export type SavedActivityUpserted = SavedActivity;
 
+/**  A daily allowance of time in one app or site. */
+export type ScreenTimeBudget = {
+	id: string,
+	/**
+	 *  Activity name or process name the budget covers, e.g. `youtube`
+	 *  for the site or `steam` for the app.
+	 */
+	target: string,
+	dailySecs: number,
+	/**  A disabled budget is kept and reported on but never nudges. */
+	enabled: boolean,
+	createdAt: string,
+};
+
+export type ScreenTimeCommandError = { type: "Unavailable" } | { type: "NotFound"; data: string } | { type: "Invalid"; data: string } | { type: "Other"; data: string };
+
+/**  A nudge about screen time. */
+export type ScreenTimeNotification = {
+	kind: ScreenTimeNotificationKind,
+	/**  The budget gone over, for `budget_exceeded`. */
+	budgetId: string | null,
+	title: string,
+	body: string,
+};
+
+/**
+ *  `budget_exceeded` when a budget's daily allowance is used up,
+ *  `weekly_report` when last week's report is ready.
+ */
+export type ScreenTimeNotificationKind = "budget_exceeded" | "weekly_report";
+
+/**  Where a week's time went, Monday through Sunday. */
+export type ScreenTimeReport = {
+	/**  The Monday the week starts on. */
+	start: string,
+	/**  The Sunday it ends on. */
+	end: string,
+	totalSecs: number,
+	/**  Time per activity, longest first. */
+	apps: AppUsage[],
+	/**  Every budget, in the order they were created. */
+	budgets: BudgetWeek[],
+};
+
 /**  One message hit returned by full-text search. */
+export type ScreenshotFormat = "png" | "jpeg" | 
+/**  Lossless, and about a quarter smaller than PNG on screenshots. */
+"webp" | "avif";
+
+export type ScriptCommandError = { type: "Unavailable" } | { type: "NotFound"; data: string } | { type: "AlreadyRunning"; data: string } | { type: "Other"; data: string };
+
+/**  A script asked to tell the user something. */
+export type ScriptNotification = {
+	/**  Name of the script that sent it. */
+	script: string,
+	title: string,
+	body: string,
+};
+
+/**  One finished run, as kept in the audit log. */
+export type ScriptRun = {
+	id: string,
+	scriptId: string,
+	/**  The name when it ran, which may have changed since. */
+	scriptName: string,
+	trigger: RunTrigger,
+	startedAt: string,
+	durationMs: number,
+	outcome: RunOutcome,
+	/**  Why the run failed, for [`RunOutcome::Failed`]. */
+	error: string | null,
+	/**  The script's final value, unless it was `()`. */
+	result: string | null,
+	/**  Lines written with `print`. */
+	output: string[],
+	calls: ApiCall[],
+};
+
+export type ScriptSettings = {
+	scripts: UserScript[],
+};
+
+/**  When a script runs on its own. */
+export type ScriptTrigger = 
+/**  Only when the user runs it. */
+{ kind: "manual" } | 
+/**
+ *  Every `minutes`, counted from when the app starts or the script
+ *  is saved.
+ */
+{ kind: "every"; minutes: number } | 
+/**  Once a day at `hour:minute` local time. */
+{ kind: "daily"; hour: number; minute: number };
+
 export type SearchMessageResult = {
 	id: string,
 	thread_id: string,
@@ -803,6 +1586,15 @@ export type SearchThreadResult = {
 	updated_at: string,
 };
 
+/**
+ *  Server push relayed from the notification socket. The frontend
+ *  refreshes whatever the event invalidates; on `resync` it refreshes
+ *  everything a notification could have touched.
+ */
+export type ServerNotification = 
+{ type: "notification"; notification: Notification } | 
+({ type: "resync" }) & { notification?: never };
+
 export type ServerToolCall = {
 	id: string,
 	name: string,
@@ -830,6 +1622,28 @@ export type ServerToolResult = {
 
 export type ServerToolStatus = "success" | "error";
 
+/**  One entry in the registry. */
+export type SettingDefinition = {
+	/**  Dotted path within the scope's document, e.g. `shared.theme`. */
+	key: string,
+	scope: SettingScope,
+	type: SettingType,
+	/**
+	 *  Fresh-install value, identical to what the section's `Default`
+	 *  impl produces.
+	 */
+	default: unknown,
+};
+
+/**  Which settings document a key lives in, and therefore who may see it. */
+export type SettingScope = "local" | "shared" | "desktop";
+
+/**
+ *  The values a setting accepts. Drives both the form control the UI
+ *  renders and the check [`SettingsSchema::validate`] applies.
+ */
+export type SettingType = { kind: "bool" } | { kind: "number"; min: number; max: number; step: number } | { kind: "choice"; options: string[] };
+
 /**
  *  Typed error surface for the `settings_*` IPC commands. Externally
  *  tagged so the JS side gets `{ type: "Persistence", data: "..." }`
@@ -838,7 +1652,17 @@ export type ServerToolStatus = "success" | "error";
  *  commands share `Persistence` (any setter that hits the on-disk
  *  settings files).
  */
-export type SettingsError = { type: "Persistence"; data: string } | { type: "EndpointSwitch"; data: string };
+export type SettingsError = { type: "Persistence"; data: string } | { type: "EndpointSwitch"; data: string } | { type: "InvalidValue"; data: string };
+
+/**
+ *  `GET /settings/schema` — 200 body, and the desktop's
+ *  `settings_get_schema` result.
+ */
+export type SettingsSchema = {
+	/**  The cloud blob version the registry describes. */
+	schemaVersion: number,
+	settings: SettingDefinition[],
+};
 
 /**
  *  Cross-platform cloud-synced settings. Anything in this section
@@ -852,15 +1676,66 @@ export type SettingsError = { type: "Persistence"; data: string } | { type: "End
  * 
  *  `dynamic_accent` defaults to `true` — the design pulls the OS / wallpaper
  *  accent by default and a user who has never touched the toggle should see
- *  the dynamic behaviour. This is the one field where the product default
- *  differs from `bool::default()`, which is why this struct has a hand-rolled
- *  `Default` instead of `#[derive]`.
+ *  the dynamic behaviour. `web_access` likewise defaults to `true`: the
+ *  assistant may search and read the web unless the user opts out. Those
+ *  two fields are where the product default differs from `bool::default()`,
+ *  which is why this struct has a hand-rolled `Default` instead of
+ *  `#[derive]`.
  */
 export type SharedSettings = {
 	theme?: ThemePreference,
 	dynamicAccent?: boolean,
+	/**
+	 *  Whether the assistant may use the `web_search` and `fetch_url`
+	 *  tools. Enforced server-side when a chat turn is prepared.
+	 */
+	webAccess?: boolean,
+	/**
+	 *  Whether the assistant remembers facts about the user across
+	 *  threads: learning them from new messages and recalling them into
+	 *  later chats. Off unless the user opts in.
+	 */
+	memory?: boolean,
 } & { [key in string]: unknown };
 
+/**
+ *  Speaking-rate multiplier for reading responses aloud, where `1.0` is
+ *  the voice's normal pace. Always finite and within
+ *  `[SpeechRate::MIN, SpeechRate::MAX]` by construction.
+ */
+export type ShellSettings = {
+	/**
+	 *  Folder commands run in; a `cwd` the model asks for must be inside
+	 *  it.
+	 */
+	workspace?: string | null,
+	/**
+	 *  Programs the assistant may run, by file name (`git`, `cargo`).
+	 */
+	allowedPrograms: string[],
+};
+
+export type SpeechRate = number | null;
+
+/**  What a [`SpeechReader`] is doing, reported on every change. */
+export type SpeechState = "idle" | "speaking" | "paused";
+
+/**
+ *  Emitted whenever reading aloud starts, pauses or ends, so the
+ *  response view can show the matching controls.
+ */
+export type SpeechStateChanged = {
+	state: SpeechState,
+};
+
+/**  A voice installed on this machine. */
+export type SpeechVoice = {
+	id: string,
+	name: string,
+	/**  BCP-47 tag, e.g. `en-US`. */
+	language: string,
+};
+
 /**
  *  Typed error surface for the streaming `chat_*` IPC commands.
  *  Externally tagged so the JS side can branch on `error.type`.
@@ -985,6 +1860,12 @@ export type TelemetryLocal = {
 	 *  until the user explicitly rotates it.
 	 */
 	distinctId?: string | null,
+	/**
+	 *  Strip product analytics from this install entirely, whatever the
+	 *  synced consent says: the frontend is never handed a PostHog key.
+	 *  Error reporting still follows the consent toggles.
+	 */
+	analyticsDisabled?: boolean,
 };
 
 export type TextContentBlock = {
@@ -1023,6 +1904,18 @@ export type Thread = {
 	created_at: string,
 	updated_at: string,
 	active_leaf_id?: string | null,
+	/**
+	 *  The model every turn in this thread uses; `None` follows the
+	 *  deployment default.
+	 */
+	model?: ThreadModel | null,
+	/**  Short topic tags generated with the auto-title. */
+	tags?: string[],
+	/**  Pinned threads list ahead of the rest, most recently pinned first. */
+	pinned?: boolean,
+	starred?: boolean,
+	/**  The [`ThreadFolder`](crate::ThreadFolder) the thread is filed in. */
+	folder_id?: string | null,
 };
 
 /**
@@ -1032,7 +1925,16 @@ export type Thread = {
  *  a dedicated variant so the UI can render an empty state instead of a
  *  generic toast.
  */
-export type ThreadError = { type: "NotFound" } | { type: "Backend"; data: string } | { type: "BadResponse"; data: string } | { type: "StateUnavailable"; data: string } | { type: "Internal"; data: string };
+export type ThreadError = { type: "NotFound" } | { type: "Backend"; data: string } | { type: "BadResponse"; data: string } | { type: "StateUnavailable"; data: string } | { type: "Internal"; data: string } | { type: "LocalOnly"; data: string };
+
+/**  A model pinned to a thread. */
+export type ThreadModel = {
+	/**  Provider id from the deployment's LLM config, e.g. `openai`. */
+	provider: string,
+	model: string,
+	/**  Sampling temperature; `None` keeps the provider's default. */
+	temperature?: number | null,
+};
 
 export type TimelineAppEvent = {
 	name: string,
@@ -1090,6 +1992,24 @@ export type ToolCallChunkBlock = {
 	extras?: { [key in string]: unknown } | null,
 };
 
+export type ToolConsentError = 
+/**
+ *  The prompt already timed out, was cancelled, or was answered.
+ */
+{ type: "UnknownRequest"; data: string };
+
+/**
+ *  Emitted when the assistant wants to run a tool that needs the user's
+ *  approval. The frontend shows a prompt and answers through
+ *  [`tool_consent_respond`] with the same `request_id`.
+ */
+export type ToolConsentRequested = {
+	requestId: string,
+	tool: string,
+	risk: RiskLevel,
+	arguments: unknown,
+};
+
 export type ToolDefinition = {
 	name: string,
 	description: string,
@@ -1132,11 +2052,43 @@ export type ToolSource =
 
 export type ToolStatus = "success" | "error";
 
+export type ToolSummary = {
+	name: string,
+	description: string,
+};
+
+export type TransferSettings = {
+	/**  Upload cap in KiB/s across all transfers; 0 for no cap. */
+	maxUploadKibPerSec: number,
+	/**  Download cap in KiB/s across all transfers; 0 for no cap. */
+	maxDownloadKibPerSec: number,
+	/**  Hold background syncs while the OS reports a metered connection. */
+	pauseOnMetered: boolean,
+	/**
+	 *  Run background syncs only between `night_start_hour` and
+	 *  `night_end_hour`, local time. Transfers the user is waiting on
+	 *  always run.
+	 */
+	nightOnly: boolean,
+	nightStartHour: number,
+	nightEndHour: number,
+	/**  Transfers in flight at once. */
+	maxConcurrent: number,
+};
+
 export type UpdateInfo = {
 	version: string,
 	body: string | null,
 };
 
+/**  Where the background download of an update has got to. */
+export type UpdateProgress = 
+{ type: "downloading"; version: string; downloaded: number; total: number | null } | 
+{ type: "verifying"; version: string } | 
+/**  Staged; installs on exit or from `system_install_update`. */
+{ type: "ready"; version: string } | 
+{ type: "failed"; version: string; error: string };
+
 export type UsageMetadata = {
 	input_tokens: bigint,
 	output_tokens: bigint,
@@ -1145,6 +2097,19 @@ export type UsageMetadata = {
 	output_token_details?: OutputTokenDetails | null,
 };
 
+export type UserScript = {
+	/**  Assigned when the script is first saved. */
+	id?: string,
+	name: string,
+	/**  Rhai source. */
+	source: string,
+	trigger?: ScriptTrigger,
+	/**  Disabled scripts keep their schedule but only run by hand. */
+	enabled?: boolean,
+	/**  Wall-clock budget for one run, model calls included. */
+	timeoutSecs?: number,
+};
+
 export type VideoContentBlock = {
 	id?: string | null,
 	file_id?: string | null,
@@ -1155,6 +2120,68 @@ export type VideoContentBlock = {
 	extras?: { [key in string]: unknown } | null,
 };
 
+/**
+ *  Progress of a [`VoiceSession`], in order: `Listening`, any number of
+ *  `Partial`s, then exactly one of `Final` or `Failed` unless the session
+ *  is cancelled.
+ */
+export type VoiceEvent = 
+/**  The microphone is open. */
+{ type: "listening" } | 
+/**  Transcript of everything said so far; replaces the previous one. */
+{ type: "partial"; text: string } | 
+/**
+ *  The finished transcript. `submit` asks for the question to be sent
+ *  as is; it is never set for an empty transcript.
+ */
+{ type: "final"; text: string; submit: boolean } | 
+/**  Recognition failed; `message` says why. */
+{ type: "failed"; message: string };
+
+export type VoiceError = { type: "NoMicrophone" } | 
+/**  Neither a Whisper model nor a usable provider is configured. */
+{ type: "NoRecognizer" } | 
+/**  The microphone, recognizer or speech output failed. */
+{ type: "Unavailable"; data: string };
+
+/**
+ *  An OpenAI-compatible audio endpoint: `/audio/transcriptions` for
+ *  recognition, `/audio/speech` for speech.
+ */
+export type VoiceProviderSettings = {
+	/**  API root, e.g. `https://api.openai.com/v1`. */
+	baseUrl: string,
+	apiKey: string,
+	model: string,
+};
+
+export type VoiceSettings = {
+	/**
+	 *  Path to a whisper.cpp `ggml` model for on-device recognition. Used
+	 *  first when the build has Whisper support.
+	 */
+	whisperModel: string | null,
+	/**
+	 *  Hosted recognizer, used when there is no local model. `model` is
+	 *  e.g. `whisper-1`.
+	 */
+	provider: VoiceProviderSettings | null,
+	/**  Spoken language, e.g. `en`. Detected when unset. */
+	language: string | null,
+	/**
+	 *  Submit the question after this many milliseconds of silence.
+	 *  When unset, the transcript waits in the question box until the
+	 *  shortcut is released.
+	 */
+	autoSubmitAfterMs: number | null,
+	/**
+	 *  Hosted voice for reading responses aloud, used instead of the
+	 *  platform's when the build has provider speech. `model` is e.g.
+	 *  `tts-1`.
+	 */
+	speechProvider: VoiceProviderSettings | null,
+};
+
 /**
  *  Wire-side descriptor for a tool. One per call, one per entry in the
  *  per-turn `CapabilityUpdate.tools` list.
@@ -1191,9 +2218,9 @@ export type WireToolDescriptor = {
 	 */
 	required_contexts?: string[],
 	/**
-	 *  If true, the server must obtain explicit user approval before
-	 *  dispatching the call. Not enforced in v1 (all v1 tools are
-	 *  read-only) but declared so the protocol is stable.
+	 *  If true, the user must approve the call before it runs. The
+	 *  desktop enforces this client-side: its tool backend prompts
+	 *  before dispatching and reports a refusal as a tool error.
 	 */
 	requires_user_approval?: boolean,
 } & ToolDefinition;
@@ -7,14 +7,15 @@ import {
 	type SavedActivityLiveSessionEnded,
 } from '$lib/bindings/specta.bindings.js';
 import { InjectionToken } from '@eurora/shared/context';
+import { Channel } from '@tauri-apps/api/core';
 
 /**
- * Page size for the initial snapshot fetch and every subsequent
- * `loadMore` call. The backend's `MAX_LIST_LIMIT` is 100, so this
- * stays well under the cap while keeping the rail responsive on cold
- * start (one round-trip lights up the visible window).
+ * Rows streamed by the initial snapshot and by every subsequent
+ * `loadMore` call. They arrive in server-sized batches (20 rows), each
+ * applied as soon as its icons resolve, so a larger page costs no extra
+ * latency before the visible window lights up.
  */
-const PAGE_SIZE = 20;
+const PAGE_SIZE = 100;
 
 /**
  * Persisted-activity store backing the timeline rail.
@@ -28,9 +29,9 @@ const PAGE_SIZE = 20;
  * list is unbounded — growth is whatever the user actually scrolls
  * through.
  *
- * 1. `init()` hydrates the first `PAGE_SIZE` rows from `GET /activities`
- *    via the `activityList` tauri command — the cloud is the source of
- *    truth across restarts.
+ * 1. `init()` hydrates the first `PAGE_SIZE` rows from
+ *    `GET /activities/stream` via the `activityStream` tauri command,
+ *    batch by batch — the cloud is the source of truth across restarts.
  * 2. The `savedActivityUpserted` tauri event surfaces freshly-tracked
  *    sessions as soon as the cloud `POST /activity-sessions` succeeds.
  *    The payload is atomic: it carries the (possibly upserted) parent
@@ -51,13 +52,12 @@ const PAGE_SIZE = 20;
  * activity ids the snapshot has not yet inserted.
  *
  * A fourth flow loads older history on demand: `loadMore` extends the
- * tail of `recent` with the next page of `GET /activities` and is
- * fired by the rail's bottom-sentinel `IntersectionObserver` as the
- * user scrolls toward the loaded edge. `paginatedOffset` is the
- * server-side cursor for that fetch; it advances **only** by the row
- * count the server returned, never by live prepends, so an event
- * landing mid-fetch can't shift the cursor and cause a row to be
- * missed.
+ * tail of `recent` with the next page of the stream and is fired by
+ * the rail's bottom-sentinel `IntersectionObserver` as the user scrolls
+ * toward the loaded edge. `cursor` is the opaque keyset position the
+ * server handed back after the last page; it names the last row
+ * streamed rather than a row count, so live prepends can't shift it
+ * and cause a row to be missed.
  *
  * On top of the chronological list the service tracks two distinct
  * "current app" concepts that callers must not conflate:
@@ -92,7 +92,7 @@ export class ActivityService {
 	activeApp: SavedActivity | undefined = $derived(this.recent[this.activeIndex]);
 
 	private readonly listeners = new ListenerBag();
-	private paginatedOffset = 0;
+	private cursor: string | null = null;
 
 	async init(): Promise<void> {
 		this.listeners.add(
@@ -105,14 +105,7 @@ export class ActivityService {
 		);
 
 		try {
-			const snapshot = unwrap(await commands.activityList(PAGE_SIZE, 0));
-			// Advance the cursor by the server-confirmed row count *before*
-			// applying rows, so a `savedActivityUpserted` event landing
-			// during the fetch can't end up shifting our pagination view
-			// of older history.
-			this.paginatedOffset = snapshot.length;
-			this.hasMore = snapshot.length === PAGE_SIZE;
-			for (const row of snapshot) this.applyActivity(row);
+			await this.streamPage();
 		} catch (error) {
 			console.error('Failed to load recent activities:', error);
 		}
@@ -123,7 +116,7 @@ export class ActivityService {
 		this.activeIndex = 0;
 		this.hasMore = true;
 		this.loadingMore = false;
-		this.paginatedOffset = 0;
+		this.cursor = null;
 		await this.listeners.destroy();
 	}
 
@@ -171,7 +164,7 @@ export class ActivityService {
 	}
 
 	/**
-	 * Fetch the next page of older activities and merge them into
+	 * Stream the next page of older activities and merge them into
 	 * `recent`. Fired by the rail's bottom-sentinel observer as the
 	 * user scrolls toward the loaded edge; safe to call directly if a
 	 * future caller wants to pre-warm the rail.
@@ -186,16 +179,7 @@ export class ActivityService {
 		if (this.loadingMore || !this.hasMore) return;
 		this.loadingMore = true;
 		try {
-			const page = unwrap(await commands.activityList(PAGE_SIZE, this.paginatedOffset));
-			// Advance the cursor by the row count the server actually
-			// returned, *before* routing rows through `applyActivity`.
-			// Live prepends arriving mid-fetch are then guaranteed not
-			// to move the cursor, and the id-dedup in `applyActivity`
-			// makes any overlap between the page and the live stream a
-			// no-op.
-			this.paginatedOffset += page.length;
-			this.hasMore = page.length === PAGE_SIZE;
-			for (const row of page) this.applyActivity(row);
+			await this.streamPage();
 		} catch (error) {
 			console.error('Failed to load more activities:', error);
 		} finally {
@@ -203,6 +187,23 @@ export class ActivityService {
 		}
 	}
 
+	/**
+	 * Stream up to `PAGE_SIZE` rows after `cursor`, applying each batch
+	 * as it arrives. The cursor only advances once the whole page has
+	 * landed, so a failed page is retried from where it started; the
+	 * id-dedup in `applyActivity` absorbs the rows it re-sends, as well
+	 * as any overlap with live prepends.
+	 */
+	private async streamPage(): Promise<void> {
+		const channel = new Channel<SavedActivity[]>();
+		channel.onmessage = (rows) => {
+			for (const row of rows) this.applyActivity(row);
+		};
+		const next = unwrap(await commands.activityStream(this.cursor, PAGE_SIZE, channel));
+		this.cursor = next;
+		this.hasMore = next !== null;
+	}
+
 	private applyActivity(incoming: SavedActivity): void {
 		// `activeIndex === 0` is the implicit "follow live" mode: a new
 		// arrival simply becomes the new top and `activeIndex` stays 0.
@@ -117,8 +117,17 @@ export class AppearanceService {
 		root.style.setProperty(TEXT_SCALE_VAR, String(this.textScale));
 	}
 
+	/**
+	 * Read the current shared section, patch the appearance fields, and
+	 * write it back, so fields owned elsewhere (e.g. `webAccess`) survive.
+	 */
 	private async persistShared(): Promise<void> {
-		const next: SharedSettings = { theme: this.theme, dynamicAccent: this.dynamicAccent };
+		const current = await commands.settingsGetShared();
+		const next: SharedSettings = {
+			...current,
+			theme: this.theme,
+			dynamicAccent: this.dynamicAccent,
+		};
 		unwrap(await commands.settingsSetShared(next));
 	}
 
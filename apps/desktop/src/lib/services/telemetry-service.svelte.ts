@@ -117,6 +117,21 @@ export class TelemetryService {
 		}
 	}
 
+	/**
+	 * Turn product analytics off (or back on) for this install regardless
+	 * of the synced consent, then reapply the SDKs against the new
+	 * bootstrap so PostHog stops or starts straight away.
+	 */
+	async setAnalyticsDisabled(disabled: boolean): Promise<void> {
+		try {
+			unwrap(await commands.settingsSetAnalyticsDisabled(disabled));
+		} catch (error) {
+			console.error('Failed to update local analytics setting:', error);
+			return;
+		}
+		await this.refresh();
+	}
+
 	capture(event: string, properties?: Record<string, unknown>): void {
 		if (!this.bootstrap?.allowsMetrics || !this.posthogStarted) return;
 		posthog.capture(event, properties);
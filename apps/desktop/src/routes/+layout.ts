@@ -5,7 +5,7 @@ export const prerender = true;
 export const ssr = false;
 
 export async function load({ url }) {
-	if (url.pathname.startsWith('/onboarding')) {
+	if (url.pathname.startsWith('/onboarding') || url.pathname.startsWith('/region-capture')) {
 		return {};
 	}
 	try {
@@ -107,7 +107,7 @@ export const commands = {
 	threadDelete: (threadId: string) => typedError<null, ThreadError>(__TAURI_INVOKE("thread_delete", { threadId })),
 	threadGetMessages: (threadId: string, limit: number, offset: number) => typedError<MessageNode[], ThreadError>(__TAURI_INVOKE("thread_get_messages", { threadId, limit, offset })),
 	threadSwitchBranch: (threadId: string, messageId: string, direction: number) => typedError<MessageNode[], ThreadError>(__TAURI_INVOKE("thread_switch_branch", { threadId, messageId, direction })),
-	threadGenerateTitle: (threadId: string) => typedError<Thread, ThreadError>(__TAURI_INVOKE("thread_generate_title", { threadId })),
+	threadGenerateTitle: (threadId: string, regenerate: boolean) => typedError<Thread, ThreadError>(__TAURI_INVOKE("thread_generate_title", { threadId, regenerate })),
 	threadSearchThreads: (query: string, limit: number, offset: number) => typedError<SearchThreadResult[], ThreadError>(__TAURI_INVOKE("thread_search_threads", { query, limit, offset })),
 	threadSearchMessages: (query: string, limit: number, offset: number) => typedError<SearchMessageResult[], ThreadError>(__TAURI_INVOKE("thread_search_messages", { query, limit, offset })),
 	chatCollectContext: (threadId: string) => typedError<ChatContext, StreamError>(__TAURI_INVOKE("chat_collect_context", { threadId })),
@@ -223,6 +223,12 @@ export type ChatMessage = {
  *  when the user sent the message, so the server can record the link in
  *  `activity_threads`. Optional because non-desktop clients (web, mobile)
  *  have no timeline; absent values skip the link step entirely.
+ * 
+ *  Attachments ride in `content_blocks` as `image`, `text-plain` or `file`
+ *  blocks. Each either inlines its bytes (`base64`, or `text` for plain
+ *  text) or names an asset the caller already uploaded by `file_id`; a
+ *  client-supplied `url` is rejected. Documents are read into text before
+ *  the model sees them.
  */
 export type ChatSendRequest = {
 	content_blocks: ContentBlock[],
@@ -242,11 +248,21 @@ export type ChatSendRequest = {
 export type ChatServerMessage = 
 /**  The user's message has been persisted; clients should display it. */
 { type: "confirmed_human_message"; message: MessageNode } | 
-/**  One streaming chunk from the AI. */
+/**
+ *  One streaming chunk from the AI. The last chunk of each model
+ *  round has `chunk_position: "last"` and carries the round's
+ *  `usage_metadata` and the provider's `finish_reason` (in
+ *  `response_metadata`, e.g. `"stop"`, `"tool_calls"` or `"length"`).
+ */
 { type: "chunk"; chunk: AIMessageChunk } | 
 /**
  *  The turn ended successfully; tree positions for everything that was
- *  persisted during this turn (human + AI + any tool messages).
+ *  persisted during this turn (human + AI + any tool messages). When
+ *  the answer cites source passages, its text block carries one
+ *  `citation` annotation per cited passage: the marker's char span in
+ *  the text, the passage (`cited_text`), the source `title`, and in
+ *  `extras` the source's `file_id` plus its `paragraph` or, for
+ *  transcripts, `start_seconds`.
  */
 { type: "final"; messages: MessageNode[] } | 
 /**  The turn aborted with an error. The connection is closed after this. */
@@ -448,10 +464,10 @@ export type PlainTextContentBlock = {
 /**
  *  Third-party identity provider supported by the auth service.
  * 
- *  Wire format is lowercase JSON (`"google"`, `"github"`, `"apple"`)
- *  so it reads naturally in URLs and request bodies.
+ *  Wire format is lowercase JSON (`"google"`, `"github"`, `"apple"`,
+ *  `"microsoft"`) so it reads naturally in URLs and request bodies.
  */
-export type Provider = "google" | "github" | "apple";
+export type Provider = "google" | "github" | "apple" | "microsoft";
 
 export type ReasoningContentBlock = {
 	id?: string | null,
@@ -624,6 +640,12 @@ export type TelemetryLocal = {
 	 *  until the user explicitly rotates it.
 	 */
 	distinctId?: string | null,
+	/**
+	 *  Strip product analytics from this install entirely, whatever the
+	 *  synced consent says: the frontend is never handed a PostHog key.
+	 *  Error reporting still follows the consent toggles.
+	 */
+	analyticsDisabled?: boolean,
 };
 
 export type TextContentBlock = {
@@ -642,6 +664,18 @@ export type Thread = {
 	created_at: string,
 	updated_at: string,
 	active_leaf_id?: string | null,
+	/**
+	 *  The model every turn in this thread uses; `None` follows the
+	 *  deployment default.
+	 */
+	model?: ThreadModel | null,
+	/**  Short topic tags generated with the auto-title. */
+	tags?: string[],
+	/**  Pinned threads list ahead of the rest, most recently pinned first. */
+	pinned?: boolean,
+	starred?: boolean,
+	/**  The [`ThreadFolder`](crate::ThreadFolder) the thread is filed in. */
+	folder_id?: string | null,
 };
 
 /**
@@ -651,7 +685,16 @@ export type Thread = {
  *  a dedicated variant so the UI can render an empty state instead of a
  *  generic toast.
  */
-export type ThreadError = { type: "NotFound" } | { type: "Backend"; data: string } | { type: "BadResponse"; data: string } | { type: "StateUnavailable"; data: string } | { type: "Internal"; data: string };
+export type ThreadError = { type: "NotFound" } | { type: "Backend"; data: string } | { type: "BadResponse"; data: string } | { type: "StateUnavailable"; data: string } | { type: "Internal"; data: string } | { type: "LocalOnly"; data: string };
+
+/**  A model pinned to a thread. */
+export type ThreadModel = {
+	/**  Provider id from the deployment's LLM config, e.g. `openai`. */
+	provider: string,
+	model: string,
+	/**  Sampling temperature; `None` keeps the provider's default. */
+	temperature?: number | null,
+};
 
 export type ToolCall = {
 	id?: string | null,
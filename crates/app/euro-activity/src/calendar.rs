@@ -0,0 +1,329 @@
+//! iCalendar export of the activity timeline.
+//!
+//! The timeline records a session every time focus moves, which is far
+//! too fine-grained for a calendar. [`focus_blocks`] walks the sessions
+//! in start order and merges consecutive runs of the same activity that
+//! are separated by at most [`MERGE_GAP`], dropping blocks shorter than
+//! [`MIN_BLOCK`]. [`to_ics`] then writes one `VEVENT` per block (RFC 5545)
+//! naming the activity, its applications and the documents that were open,
+//! so the file can be imported or subscribed to next to a real calendar.
+
+use std::fmt::Write as _;
+
+use activity_core::ActivityWithSessions;
+use chrono::{DateTime, TimeDelta, Utc};
+use uuid::Uuid;
+
+/// Longest pause between two sessions of one activity that still counts
+/// as the same block.
+pub const MERGE_GAP: TimeDelta = TimeDelta::minutes(5);
+
+/// Shortest block worth an event.
+pub const MIN_BLOCK: TimeDelta = TimeDelta::minutes(5);
+
+/// Most window titles listed in one event's description.
+const MAX_DOCUMENTS: usize = 10;
+
+/// Longest content line, in octets, before it is folded.
+const MAX_LINE_OCTETS: usize = 75;
+
+/// A stretch of time spent in one activity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FocusBlock {
+    /// Id of the block's first session, stable across exports.
+    pub id: Uuid,
+    pub activity_name: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    /// Distinct process names, in the order they were first seen.
+    pub apps: Vec<String>,
+    /// Distinct window titles, in the order they were first seen.
+    pub documents: Vec<String>,
+}
+
+/// Merge the sessions of `timeline` that overlap `[since, until)` into
+/// focus blocks, oldest first.
+///
+/// Sessions are clipped to the range; a session that is still open runs
+/// until `now`. A session of another activity in between always ends the
+/// current block, so blocks never overlap.
+pub fn focus_blocks(
+    timeline: &[ActivityWithSessions],
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Vec<FocusBlock> {
+    let mut sessions: Vec<_> = timeline
+        .iter()
+        .flat_map(|entry| {
+            entry.sessions.iter().filter_map(move |session| {
+                let start = session.started_at.max(since);
+                let end = session.ended_at.unwrap_or(now).min(until);
+                (end > start).then_some((entry, session, start, end))
+            })
+        })
+        .collect();
+    sessions.sort_by_key(|(_, session, start, _)| (*start, session.id));
+
+    let mut blocks = Vec::new();
+    let mut current: Option<(Uuid, FocusBlock)> = None;
+    for (entry, session, start, end) in sessions {
+        let activity_id = entry.activity.id;
+        match current.as_mut() {
+            Some((id, block)) if *id == activity_id && start - block.ended_at <= MERGE_GAP => {
+                block.ended_at = block.ended_at.max(end);
+            }
+            _ => {
+                blocks.extend(current.take().map(|(_, block)| block));
+                current = Some((
+                    activity_id,
+                    FocusBlock {
+                        id: session.id,
+                        activity_name: entry.activity.display_name.clone(),
+                        started_at: start,
+                        ended_at: end,
+                        apps: Vec::new(),
+                        documents: Vec::new(),
+                    },
+                ));
+            }
+        }
+        if let Some((_, block)) = current.as_mut() {
+            push_distinct(&mut block.apps, Some(session.process_name.as_str()));
+            push_distinct(&mut block.documents, session.window_title.as_deref());
+        }
+    }
+    blocks.extend(current.map(|(_, block)| block));
+    blocks.retain(|block| block.ended_at - block.started_at >= MIN_BLOCK);
+    blocks
+}
+
+fn push_distinct(values: &mut Vec<String>, value: Option<&str>) {
+    if let Some(value) = value.map(str::trim)
+        && !value.is_empty()
+        && !values.iter().any(|v| v == value)
+    {
+        values.push(value.to_string());
+    }
+}
+
+/// Render `blocks` as an iCalendar document with CRLF line endings.
+///
+/// Events are marked transparent so importing them never shows the user
+/// as busy. `stamp` is written as every event's `DTSTAMP`.
+pub fn to_ics(blocks: &[FocusBlock], stamp: DateTime<Utc>) -> String {
+    let mut out = String::new();
+    for line in [
+        "BEGIN:VCALENDAR",
+        "VERSION:2.0",
+        "PRODID:-//Eurora//Activity Timeline//EN",
+        "CALSCALE:GREGORIAN",
+        "METHOD:PUBLISH",
+        "X-WR-CALNAME:Eurora activity",
+    ] {
+        push_line(&mut out, line);
+    }
+    let stamp = ics_timestamp(stamp);
+    for block in blocks {
+        push_line(&mut out, "BEGIN:VEVENT");
+        push_line(&mut out, &format!("UID:{}@eurora", block.id));
+        push_line(&mut out, &format!("DTSTAMP:{stamp}"));
+        push_line(
+            &mut out,
+            &format!("DTSTART:{}", ics_timestamp(block.started_at)),
+        );
+        push_line(
+            &mut out,
+            &format!("DTEND:{}", ics_timestamp(block.ended_at)),
+        );
+        push_line(
+            &mut out,
+            &format!("SUMMARY:{}", escape_text(&block.activity_name)),
+        );
+        push_line(
+            &mut out,
+            &format!("DESCRIPTION:{}", escape_text(&description(block))),
+        );
+        push_line(&mut out, "TRANSP:TRANSPARENT");
+        push_line(&mut out, "END:VEVENT");
+    }
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+fn description(block: &FocusBlock) -> String {
+    let mut text = format!("Apps: {}", block.apps.join(", "));
+    if !block.documents.is_empty() {
+        text.push_str("\n\nDocuments:");
+        for document in block.documents.iter().take(MAX_DOCUMENTS) {
+            let _ = write!(text, "\n- {document}");
+        }
+        let more = block.documents.len().saturating_sub(MAX_DOCUMENTS);
+        if more > 0 {
+            let _ = write!(text, "\n- and {more} more");
+        }
+    }
+    text
+}
+
+fn ics_timestamp(at: DateTime<Utc>) -> String {
+    at.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escape a TEXT value: backslash, semicolon, comma and newline.
+fn escape_text(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\n' => out.push_str("\\n"),
+            c if c.is_control() => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Append one content line, folded at [`MAX_LINE_OCTETS`] without
+/// splitting a UTF-8 sequence, each physical line ending in CRLF.
+fn push_line(out: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use activity_core::{Activity, ActivitySession};
+    use chrono::TimeZone;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 2, hour, minute, 0).unwrap()
+    }
+
+    fn activity(name: &str) -> ActivityWithSessions {
+        ActivityWithSessions {
+            activity: Activity {
+                id: Uuid::now_v7(),
+                user_id: Uuid::nil(),
+                identity_key: name.to_lowercase(),
+                display_name: name.to_string(),
+                icon_asset_id: None,
+                last_used_at: at(0, 0),
+                created_at: at(0, 0),
+                updated_at: at(0, 0),
+            },
+            sessions: Vec::new(),
+        }
+    }
+
+    fn add_session(
+        entry: &mut ActivityWithSessions,
+        title: &str,
+        started_at: DateTime<Utc>,
+        ended_at: Option<DateTime<Utc>>,
+    ) {
+        entry.sessions.push(ActivitySession {
+            id: Uuid::now_v7(),
+            activity_id: entry.activity.id,
+            process_name: entry.activity.identity_key.clone(),
+            process_id: None,
+            window_title: Some(title.to_string()),
+            url: None,
+            device_id: None,
+            started_at,
+            ended_at,
+            created_at: started_at,
+            updated_at: started_at,
+        });
+    }
+
+    #[test]
+    fn merges_short_gaps_and_splits_on_other_activities() {
+        let mut code = activity("Code");
+        add_session(&mut code, "main.rs", at(9, 0), Some(at(9, 20)));
+        add_session(&mut code, "lib.rs", at(9, 23), Some(at(9, 40)));
+        add_session(&mut code, "main.rs", at(10, 30), Some(at(11, 0)));
+        let mut mail = activity("Mail");
+        add_session(&mut mail, "Inbox", at(10, 0), Some(at(10, 30)));
+
+        let blocks = focus_blocks(&[code, mail], at(0, 0), at(23, 0), at(23, 0));
+        let spans: Vec<_> = blocks
+            .iter()
+            .map(|b| (b.activity_name.as_str(), b.started_at, b.ended_at))
+            .collect();
+        assert_eq!(
+            spans,
+            [
+                ("Code", at(9, 0), at(9, 40)),
+                ("Mail", at(10, 0), at(10, 30)),
+                ("Code", at(10, 30), at(11, 0)),
+            ]
+        );
+        assert_eq!(blocks[0].documents, ["main.rs", "lib.rs"]);
+        assert_eq!(blocks[0].apps, ["code"]);
+    }
+
+    #[test]
+    fn clips_to_the_range_and_drops_short_blocks() {
+        let mut code = activity("Code");
+        add_session(&mut code, "early", at(7, 0), Some(at(8, 30)));
+        add_session(&mut code, "glance", at(9, 0), Some(at(9, 2)));
+        add_session(&mut code, "open", at(9, 30), None);
+
+        let blocks = focus_blocks(&[code], at(8, 0), at(12, 0), at(10, 0));
+        let spans: Vec<_> = blocks.iter().map(|b| (b.started_at, b.ended_at)).collect();
+        assert_eq!(spans, [(at(8, 0), at(8, 30)), (at(9, 30), at(10, 0))]);
+    }
+
+    #[test]
+    fn escapes_text_values() {
+        assert_eq!(escape_text("a,b;c\\d\r\ne"), "a\\,b\\;c\\\\d\\ne");
+    }
+
+    #[test]
+    fn folds_long_lines_on_char_boundaries() {
+        let mut out = String::new();
+        push_line(&mut out, &format!("SUMMARY:{}", "é".repeat(60)));
+        for line in out.split("\r\n").filter(|l| !l.is_empty()) {
+            assert!(line.len() <= MAX_LINE_OCTETS, "{line}");
+        }
+        assert_eq!(
+            out.replace("\r\n ", "").trim_end(),
+            format!("SUMMARY:{}", "é".repeat(60))
+        );
+    }
+
+    #[test]
+    fn writes_one_event_per_block() {
+        let block = FocusBlock {
+            id: Uuid::nil(),
+            activity_name: "Code, Inc".to_string(),
+            started_at: at(9, 0),
+            ended_at: at(9, 40),
+            apps: vec!["code".to_string()],
+            documents: vec!["main.rs".to_string()],
+        };
+        let ics = to_ics(&[block], at(12, 0));
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 1);
+        assert!(ics.contains("\r\nDTSTART:20260302T090000Z\r\n"));
+        assert!(ics.contains("\r\nDTEND:20260302T094000Z\r\n"));
+        assert!(ics.contains("\r\nDTSTAMP:20260302T120000Z\r\n"));
+        assert!(ics.contains("\r\nSUMMARY:Code\\, Inc\r\n"));
+        assert!(ics.contains("\r\nDESCRIPTION:Apps: code\\n\\nDocuments:\\n- main.rs\r\n"));
+    }
+}
@@ -0,0 +1,366 @@
+//! Language detection and prompt templates for code editors and
+//! terminals.
+//!
+//! The code strategy has two weak signals to work from: the window title
+//! (editors put the open file name there; terminals put the running
+//! command or working directory) and, for terminals, whatever text the
+//! title carries. [`CodeLanguage::from_window_title`] looks for a file
+//! name with a known extension; [`CodeLanguage::detect_from_text`]
+//! falls back to keyword scoring. The detected language is used to tag
+//! the fenced code blocks the model is asked to transcribe from the
+//! window screenshot, and to pick idioms in the [`CodeTask`] templates.
+
+/// Languages the heuristics can tell apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CodeLanguage {
+    Rust,
+    Python,
+    JavaScript,
+    TypeScript,
+    Go,
+    Java,
+    Kotlin,
+    C,
+    Cpp,
+    CSharp,
+    Ruby,
+    Php,
+    Swift,
+    Shell,
+    Sql,
+    Html,
+    Css,
+    Json,
+    Yaml,
+    Toml,
+    Markdown,
+}
+
+impl CodeLanguage {
+    /// Map a file extension (without the dot, any case) to a language.
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        let lang = match ext.to_ascii_lowercase().as_str() {
+            "rs" => Self::Rust,
+            "py" | "pyi" => Self::Python,
+            "js" | "mjs" | "cjs" | "jsx" => Self::JavaScript,
+            "ts" | "mts" | "cts" | "tsx" => Self::TypeScript,
+            "go" => Self::Go,
+            "java" => Self::Java,
+            "kt" | "kts" => Self::Kotlin,
+            "c" | "h" => Self::C,
+            "cc" | "cpp" | "cxx" | "hpp" | "hh" | "hxx" => Self::Cpp,
+            "cs" => Self::CSharp,
+            "rb" => Self::Ruby,
+            "php" => Self::Php,
+            "swift" => Self::Swift,
+            "sh" | "bash" | "zsh" | "fish" => Self::Shell,
+            "sql" => Self::Sql,
+            "html" | "htm" | "svelte" | "vue" => Self::Html,
+            "css" | "scss" | "sass" | "less" => Self::Css,
+            "json" | "jsonc" => Self::Json,
+            "yml" | "yaml" => Self::Yaml,
+            "toml" => Self::Toml,
+            "md" | "markdown" => Self::Markdown,
+            _ => return None,
+        };
+        Some(lang)
+    }
+
+    /// Find the first token in `title` that looks like a file name with a
+    /// known extension. Handles the common title shapes:
+    /// `main.rs - eurora - Visual Studio Code`,
+    /// `eurora – src/main.rs`, `vim lib.rs`, `● index.ts — app`.
+    pub fn from_window_title(title: &str) -> Option<Self> {
+        title
+            .split(|c: char| c.is_whitespace() || matches!(c, '/' | '\\' | '[' | ']' | '(' | ')'))
+            .map(|token| token.trim_matches(|c: char| !c.is_alphanumeric() && c != '.' && c != '_'))
+            .filter_map(|token| token.rsplit_once('.'))
+            .filter(|(stem, _)| !stem.is_empty())
+            .find_map(|(_, ext)| Self::from_extension(ext))
+    }
+
+    /// Keyword scoring over free text. Returns the best-scoring language
+    /// when it clears a small threshold, `None` when nothing stands out.
+    pub fn detect_from_text(text: &str) -> Option<Self> {
+        const MIN_SCORE: usize = 2;
+
+        let scores = LANGUAGE_MARKERS.iter().map(|(lang, markers)| {
+            let score = markers
+                .iter()
+                .filter(|marker| text.contains(**marker))
+                .count();
+            (*lang, score)
+        });
+
+        let mut best: Option<(Self, usize)> = None;
+        for (lang, score) in scores {
+            if score >= MIN_SCORE && best.is_none_or(|(_, top)| score > top) {
+                best = Some((lang, score));
+            }
+        }
+        best.map(|(lang, _)| lang)
+    }
+
+    /// Info string used to tag fenced code blocks.
+    pub fn fence_tag(self) -> &'static str {
+        match self {
+            Self::Rust => "rust",
+            Self::Python => "python",
+            Self::JavaScript => "javascript",
+            Self::TypeScript => "typescript",
+            Self::Go => "go",
+            Self::Java => "java",
+            Self::Kotlin => "kotlin",
+            Self::C => "c",
+            Self::Cpp => "cpp",
+            Self::CSharp => "csharp",
+            Self::Ruby => "ruby",
+            Self::Php => "php",
+            Self::Swift => "swift",
+            Self::Shell => "sh",
+            Self::Sql => "sql",
+            Self::Html => "html",
+            Self::Css => "css",
+            Self::Json => "json",
+            Self::Yaml => "yaml",
+            Self::Toml => "toml",
+            Self::Markdown => "markdown",
+        }
+    }
+
+    pub fn display_name(self) -> &'static str {
+        match self {
+            Self::Rust => "Rust",
+            Self::Python => "Python",
+            Self::JavaScript => "JavaScript",
+            Self::TypeScript => "TypeScript",
+            Self::Go => "Go",
+            Self::Java => "Java",
+            Self::Kotlin => "Kotlin",
+            Self::C => "C",
+            Self::Cpp => "C++",
+            Self::CSharp => "C#",
+            Self::Ruby => "Ruby",
+            Self::Php => "PHP",
+            Self::Swift => "Swift",
+            Self::Shell => "shell",
+            Self::Sql => "SQL",
+            Self::Html => "HTML",
+            Self::Css => "CSS",
+            Self::Json => "JSON",
+            Self::Yaml => "YAML",
+            Self::Toml => "TOML",
+            Self::Markdown => "Markdown",
+        }
+    }
+
+    /// Conventional test framework, used by [`CodeTask::WriteTest`].
+    fn test_framework(self) -> Option<&'static str> {
+        match self {
+            Self::Rust => Some("a `#[cfg(test)]` module with `#[test]` functions"),
+            Self::Python => Some("pytest"),
+            Self::JavaScript | Self::TypeScript => {
+                Some("the project's existing runner (Vitest or Jest)")
+            }
+            Self::Go => Some("the standard `testing` package with table-driven tests"),
+            Self::Java | Self::Kotlin => Some("JUnit 5"),
+            Self::CSharp => Some("xUnit"),
+            Self::Ruby => Some("RSpec"),
+            Self::Php => Some("PHPUnit"),
+            Self::Swift => Some("XCTest"),
+            Self::C | Self::Cpp => Some("GoogleTest"),
+            _ => None,
+        }
+    }
+}
+
+/// Substrings characteristic enough that two hits make a language
+/// likely. Ordered so that supersets (TypeScript over JavaScript, C++
+/// over C) win ties by appearing first.
+const LANGUAGE_MARKERS: &[(CodeLanguage, &[&str])] = &[
+    (
+        CodeLanguage::Rust,
+        &[
+            "fn ",
+            "let mut ",
+            "impl ",
+            "pub fn",
+            "::new(",
+            "cargo ",
+            "-> Result<",
+            "&self",
+        ],
+    ),
+    (
+        CodeLanguage::TypeScript,
+        &[
+            "interface ",
+            ": string",
+            ": number",
+            "export type ",
+            "as const",
+            "tsc ",
+        ],
+    ),
+    (
+        CodeLanguage::Python,
+        &[
+            "def ", "import ", "self.", "elif ", "__init__", "python ", "pip ",
+        ],
+    ),
+    (
+        CodeLanguage::Go,
+        &[
+            "func ",
+            "package ",
+            ":= ",
+            "go run",
+            "go build",
+            "err != nil",
+        ],
+    ),
+    (
+        CodeLanguage::JavaScript,
+        &[
+            "const ",
+            "function ",
+            "=> {",
+            "require(",
+            "npm ",
+            "console.log",
+        ],
+    ),
+    (
+        CodeLanguage::Java,
+        &[
+            "public class ",
+            "private ",
+            "System.out",
+            "@Override",
+            "mvn ",
+            "gradle ",
+        ],
+    ),
+    (
+        CodeLanguage::Cpp,
+        &["#include <", "std::", "template<", "nullptr", "cout <<"],
+    ),
+    (
+        CodeLanguage::C,
+        &["#include <", "printf(", "malloc(", "int main(", "gcc "],
+    ),
+    (
+        CodeLanguage::Shell,
+        &["$ ", "sudo ", "cd ", "ls ", "export ", "grep "],
+    ),
+    (
+        CodeLanguage::Sql,
+        &[
+            "SELECT ",
+            " FROM ",
+            " WHERE ",
+            "INSERT INTO",
+            "CREATE TABLE",
+        ],
+    ),
+];
+
+/// Canned code-assistance framings surfaced alongside the code context,
+/// so the model answers the common asks in a consistent shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodeTask {
+    Explain,
+    FindBug,
+    WriteTest,
+}
+
+impl CodeTask {
+    pub const ALL: &'static [CodeTask] =
+        &[CodeTask::Explain, CodeTask::FindBug, CodeTask::WriteTest];
+
+    /// One-paragraph instruction for this task, specialised to `language`
+    /// when known.
+    pub fn template(self, language: Option<CodeLanguage>) -> String {
+        let lang = language.map_or("the code", CodeLanguage::display_name);
+        match self {
+            CodeTask::Explain => format!(
+                "If the user asks what this does: explain {lang} top-down — purpose first, then the control flow, then anything non-obvious. Quote the relevant lines."
+            ),
+            CodeTask::FindBug => format!(
+                "If the user asks to find a bug: list concrete defects in {lang} ordered by severity, each with the offending line, why it is wrong, and a minimal fix. Say so plainly if nothing looks wrong."
+            ),
+            CodeTask::WriteTest => {
+                let framework = language
+                    .and_then(CodeLanguage::test_framework)
+                    .unwrap_or("the project's test framework");
+                format!(
+                    "If the user asks for tests: write focused tests using {framework} covering the happy path, edge cases, and the failure modes visible in the code."
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_language_from_editor_titles() {
+        assert_eq!(
+            CodeLanguage::from_window_title("main.rs - eurora - Visual Studio Code"),
+            Some(CodeLanguage::Rust)
+        );
+        assert_eq!(
+            CodeLanguage::from_window_title("eurora – src/components/App.tsx"),
+            Some(CodeLanguage::TypeScript)
+        );
+        assert_eq!(
+            CodeLanguage::from_window_title("● handler.py — backend"),
+            Some(CodeLanguage::Python)
+        );
+        assert_eq!(
+            CodeLanguage::from_window_title("vim lib.go"),
+            Some(CodeLanguage::Go)
+        );
+    }
+
+    #[test]
+    fn ignores_titles_without_file_names() {
+        assert_eq!(CodeLanguage::from_window_title("user@host: ~/src"), None);
+        assert_eq!(
+            CodeLanguage::from_window_title("Welcome - Visual Studio Code"),
+            None
+        );
+        assert_eq!(CodeLanguage::from_window_title(".bashrc"), None);
+    }
+
+    #[test]
+    fn detects_language_from_text() {
+        assert_eq!(
+            CodeLanguage::detect_from_text(
+                "pub fn run(&self) -> Result<(), Error> { let mut x = 1; }"
+            ),
+            Some(CodeLanguage::Rust)
+        );
+        assert_eq!(
+            CodeLanguage::detect_from_text("if err != nil { return err }\nfunc main() {}"),
+            Some(CodeLanguage::Go)
+        );
+        assert_eq!(CodeLanguage::detect_from_text("hello world"), None);
+    }
+
+    #[test]
+    fn write_test_template_names_framework() {
+        assert!(
+            CodeTask::WriteTest
+                .template(Some(CodeLanguage::Python))
+                .contains("pytest")
+        );
+        assert!(
+            CodeTask::WriteTest
+                .template(None)
+                .contains("the project's test framework")
+        );
+    }
+}
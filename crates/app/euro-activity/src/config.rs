@@ -30,6 +30,10 @@ pub struct PrivacyConfig {
     pub anonymize_data: bool,
     pub exclude_patterns: Vec<String>,
     pub ignored_applications: Vec<String>,
+    #[serde(default)]
+    pub email: EmailRedactionConfig,
+    #[serde(default)]
+    pub moderation: ModerationConfig,
 }
 
 impl Default for PrivacyConfig {
@@ -44,10 +48,100 @@ impl Default for PrivacyConfig {
                 r"key".to_string(),
             ],
             ignored_applications: vec![],
+            email: EmailRedactionConfig::default(),
+            moderation: ModerationConfig::default(),
         }
     }
 }
 
+/// PII handling for email threads pulled from webmail tabs. Applied
+/// desktop-side before the thread is rendered into chat context, so
+/// nothing masked here ever leaves the machine.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailRedactionConfig {
+    /// Replace email addresses (senders, recipients, and addresses in
+    /// bodies) with `[email]`. Sender display names are kept.
+    pub redact_addresses: bool,
+    /// Replace phone numbers in bodies with `[phone]`.
+    pub redact_phone_numbers: bool,
+    /// Drop quoted reply history (`> ...` lines and everything after an
+    /// `On <date>, <name> wrote:` marker) so each message only carries
+    /// what its sender actually wrote.
+    pub strip_quoted_replies: bool,
+}
+
+impl Default for EmailRedactionConfig {
+    fn default() -> Self {
+        Self {
+            redact_addresses: true,
+            redact_phone_numbers: true,
+            strip_quoted_replies: true,
+        }
+    }
+}
+
+/// Secret scanning for everything the assistant sends upstream: the
+/// activity prelude and every tool result. See [`crate::moderation`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModerationConfig {
+    pub enabled: bool,
+    pub action: ModerationAction,
+    /// API keys, access tokens and private keys.
+    pub detect_secrets: bool,
+    /// Payment card numbers that pass the Luhn check.
+    pub detect_card_numbers: bool,
+    /// Extra regular expressions, matched case-sensitively.
+    pub custom_patterns: Vec<String>,
+    /// Strings OCR output may keep even when a detector matches them,
+    /// compared exactly. For sample keys in documentation and the like.
+    pub ocr_allowlist: Vec<String>,
+    /// How closely OCR output from each app is checked, by normalized
+    /// process name. Apps not listed get [`OcrSensitivity::Strict`] if
+    /// they are a known password manager, [`OcrSensitivity::Standard`]
+    /// otherwise.
+    pub ocr_app_sensitivity: HashMap<String, OcrSensitivity>,
+}
+
+impl Default for ModerationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            action: ModerationAction::Redact,
+            detect_secrets: true,
+            detect_card_numbers: true,
+            custom_patterns: Vec::new(),
+            ocr_allowlist: Vec::new(),
+            ocr_app_sensitivity: HashMap::new(),
+        }
+    }
+}
+
+/// How closely text read off the screen is checked before it is kept.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OcrSensitivity {
+    /// Only the detectors every payload goes through.
+    Relaxed,
+    /// Those, plus values next to a credential label such as
+    /// `Password:` or `API key:`.
+    #[default]
+    Standard,
+    /// None of the text is kept.
+    Strict,
+}
+
+/// What happens to context that contains a match.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationAction {
+    /// Mask each match with a placeholder and send the rest.
+    #[default]
+    Redact,
+    /// Withhold the whole block or tool result.
+    Block,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StrategyConfig {
     pub enabled: bool,
@@ -156,6 +250,16 @@ impl ActivityConfigBuilder {
         self
     }
 
+    pub fn email_redaction(mut self, email: EmailRedactionConfig) -> Self {
+        self.config.global.privacy.email = email;
+        self
+    }
+
+    pub fn moderation(mut self, moderation: ModerationConfig) -> Self {
+        self.config.global.privacy.moderation = moderation;
+        self
+    }
+
     pub fn ignore_application(mut self, app: String) -> Self {
         self.config.global.privacy.ignored_applications.push(app);
         self
@@ -293,6 +397,7 @@ mod tests {
         assert_eq!(config.global.max_snapshots_per_activity, 100);
         assert!(config.global.privacy.collect_content);
         assert!(!config.global.privacy.anonymize_data);
+        assert!(config.global.privacy.email.redact_addresses);
     }
 
     #[test]
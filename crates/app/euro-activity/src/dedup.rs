@@ -0,0 +1,106 @@
+//! Near-duplicate screenshot suppression.
+//!
+//! Capturing the same screen again and again produces frames that differ
+//! only in a caret or a clock. [`DuplicateFilter`] compares each frame's
+//! perceptual hash with the last frame that was actually stored and lets
+//! through only frames that look different, counting what it skipped.
+
+use euro_vision::phash::{DEFAULT_SIMILARITY_THRESHOLD, FrameHash};
+
+/// How many frames a [`DuplicateFilter`] has kept and skipped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SuppressionStats {
+    pub kept: u64,
+    pub suppressed: u64,
+}
+
+impl SuppressionStats {
+    /// The share of frames skipped, from 0 to 1. 0 before any frames.
+    pub fn rate(&self) -> f64 {
+        let total = self.kept + self.suppressed;
+        if total == 0 {
+            0.0
+        } else {
+            self.suppressed as f64 / total as f64
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DuplicateFilter {
+    /// Hashes this many bits apart or fewer count as the same frame.
+    threshold: u32,
+    last_kept: Option<FrameHash>,
+    stats: SuppressionStats,
+}
+
+impl Default for DuplicateFilter {
+    fn default() -> Self {
+        Self::new(DEFAULT_SIMILARITY_THRESHOLD)
+    }
+}
+
+impl DuplicateFilter {
+    pub fn new(threshold: u32) -> Self {
+        Self {
+            threshold,
+            last_kept: None,
+            stats: SuppressionStats::default(),
+        }
+    }
+
+    /// Whether `hash` looks like the last kept frame. Counts it as
+    /// suppressed if so; a frame that isn't a duplicate only counts once
+    /// it is [`Self::keep`]-ed, so a failed store doesn't hide the next
+    /// copy.
+    pub fn is_duplicate(&mut self, hash: FrameHash) -> bool {
+        let duplicate = self
+            .last_kept
+            .is_some_and(|last| last.is_similar(hash, self.threshold));
+        if duplicate {
+            self.stats.suppressed += 1;
+        }
+        duplicate
+    }
+
+    /// Record that the frame hashing to `hash` was stored.
+    pub fn keep(&mut self, hash: FrameHash) {
+        self.last_kept = Some(hash);
+        self.stats.kept += 1;
+    }
+
+    pub fn stats(&self) -> SuppressionStats {
+        self.stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_frames_like_the_last_kept_one_are_suppressed() {
+        let mut filter = DuplicateFilter::new(2);
+        let first = FrameHash(0);
+        assert!(!filter.is_duplicate(first));
+        filter.keep(first);
+
+        assert!(filter.is_duplicate(FrameHash(0b11)));
+        assert!(!filter.is_duplicate(FrameHash(0b111)));
+        filter.keep(FrameHash(0b111));
+        assert!(filter.is_duplicate(FrameHash(0b111)));
+
+        let stats = filter.stats();
+        assert_eq!((stats.kept, stats.suppressed), (2, 2));
+        assert_eq!(stats.rate(), 0.5);
+    }
+
+    #[test]
+    fn unstored_frames_do_not_suppress_the_next() {
+        let mut filter = DuplicateFilter::default();
+        assert!(!filter.is_duplicate(FrameHash(42)));
+        // The store failed, so nothing was kept.
+        assert!(!filter.is_duplicate(FrameHash(42)));
+        assert_eq!(filter.stats().rate(), 0.0);
+    }
+}
@@ -0,0 +1,254 @@
+//! Chat context for webmail threads (Gmail, Outlook on the web).
+//!
+//! The site bundles answer `GET_EMAIL_THREAD` with a structured
+//! [`NativeEmailThread`]; this module applies the user's
+//! [`EmailRedactionConfig`] and renders the thread with a prompt
+//! template tuned for "summarize this thread" / "draft a reply" style
+//! questions. Redaction runs here rather than in the extension so the
+//! policy lives next to the rest of the privacy settings.
+
+use std::sync::LazyLock;
+
+use euro_browser::{NativeEmailMessage, NativeEmailThread};
+use regex::Regex;
+
+use crate::config::EmailRedactionConfig;
+
+/// Upper bound on the rendered thread. Long threads keep their newest
+/// messages — those are what "reply to this" questions are about.
+pub const MAX_THREAD_CHARS: usize = 32_000;
+
+const ADDRESS_PLACEHOLDER: &str = "[email]";
+const PHONE_PLACEHOLDER: &str = "[phone]";
+
+static ADDRESS_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\b[a-z0-9._%+-]+@[a-z0-9.-]+\.[a-z]{2,}\b").unwrap());
+
+/// International and North-American style numbers with at least seven
+/// digits. Deliberately conservative: dates and order numbers without
+/// separators are left alone.
+static PHONE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:\+\d{1,3}[\s.-]?)?(?:\(\d{2,4}\)[\s.-]?|\d{2,4}[\s.-])\d{3,4}[\s.-]\d{3,4}\b")
+        .unwrap()
+});
+
+/// `On Tue, 3 Jun 2025 at 10:14, Jane Doe <jane@example.com> wrote:`
+/// and the Outlook `From: ... Sent: ...` header block.
+static REPLY_MARKER_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?m)^(?:On .{4,200} wrote:\s*$|-{2,} ?Original Message ?-{2,}|From: .+\n(?:Sent|Date): )",
+    )
+    .unwrap()
+});
+
+/// Render `thread` as a single natural-language context block, or
+/// `None` when no message has a visible body.
+pub fn render_thread(thread: &NativeEmailThread, config: &EmailRedactionConfig) -> Option<String> {
+    let messages: Vec<String> = thread
+        .messages
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, message)| render_message(idx + 1, message, config))
+        .collect();
+    if messages.is_empty() {
+        return None;
+    }
+
+    let mut header = format!(
+        "The user is reading an email thread in {}.",
+        thread.provider.display_name()
+    );
+    if let Some(subject) = thread.subject.as_deref().map(str::trim)
+        && !subject.is_empty()
+    {
+        header.push_str(&format!(" Subject: \"{}\".", redact(subject, config)));
+    }
+    header.push_str(&format!(
+        " {} message(s) are visible, oldest first. When summarizing, attribute points to their senders; when drafting a reply, answer the most recent message.",
+        messages.len()
+    ));
+
+    // Keep the newest messages that fit; everything older than the
+    // first one that doesn't is dropped so the kept run stays contiguous.
+    let total = messages.len();
+    let mut budget = MAX_THREAD_CHARS.saturating_sub(header.len());
+    let mut kept: Vec<String> = messages
+        .into_iter()
+        .rev()
+        .take_while(|message| {
+            let cost = message.len() + 2;
+            let fits = cost <= budget;
+            if fits {
+                budget -= cost;
+            }
+            fits
+        })
+        .collect();
+    kept.reverse();
+    let omitted = total - kept.len();
+
+    let mut text = header;
+    if omitted > 0 {
+        text.push_str(&format!(
+            "\n\n[{omitted} earlier message(s) omitted for length]"
+        ));
+    }
+    for message in kept {
+        text.push_str("\n\n");
+        text.push_str(&message);
+    }
+    Some(text)
+}
+
+fn render_message(
+    index: usize,
+    message: &NativeEmailMessage,
+    config: &EmailRedactionConfig,
+) -> Option<String> {
+    let body = if config.strip_quoted_replies {
+        strip_quoted(&message.body)
+    } else {
+        message.body.trim().to_string()
+    };
+    if body.is_empty() {
+        return None;
+    }
+
+    let sender = match (
+        message.sender_name.as_deref().filter(|s| !s.is_empty()),
+        message.sender_address.as_deref().filter(|s| !s.is_empty()),
+    ) {
+        (Some(name), Some(address)) if !config.redact_addresses => format!("{name} <{address}>"),
+        (Some(name), _) => name.to_string(),
+        (None, Some(address)) if !config.redact_addresses => address.to_string(),
+        (None, Some(_)) => ADDRESS_PLACEHOLDER.to_string(),
+        (None, None) => "Unknown sender".to_string(),
+    };
+
+    let mut out = format!("[{index}] From: {}", redact(&sender, config));
+    if !message.recipients.is_empty() {
+        let recipients: Vec<String> = message
+            .recipients
+            .iter()
+            .map(|r| redact(r, config))
+            .collect();
+        out.push_str(&format!("\nTo: {}", recipients.join(", ")));
+    }
+    if let Some(sent_at) = message.sent_at.as_deref().filter(|s| !s.is_empty()) {
+        out.push_str(&format!("\nSent: {sent_at}"));
+    }
+    out.push_str("\n\n");
+    out.push_str(&redact(&body, config));
+    Some(out)
+}
+
+/// Apply the address / phone masks from `config` to `text`.
+pub fn redact(text: &str, config: &EmailRedactionConfig) -> String {
+    let mut out = text.to_string();
+    if config.redact_addresses {
+        out = ADDRESS_RE
+            .replace_all(&out, ADDRESS_PLACEHOLDER)
+            .into_owned();
+    }
+    if config.redact_phone_numbers {
+        out = PHONE_RE.replace_all(&out, PHONE_PLACEHOLDER).into_owned();
+    }
+    out
+}
+
+/// Cut `body` at the first reply marker and drop `>`-quoted lines.
+fn strip_quoted(body: &str) -> String {
+    let head = REPLY_MARKER_RE
+        .find(body)
+        .map_or(body, |m| &body[..m.start()]);
+    head.lines()
+        .filter(|line| !line.trim_start().starts_with('>'))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use euro_browser::EmailProvider;
+
+    use super::*;
+
+    fn message(name: &str, address: &str, body: &str) -> NativeEmailMessage {
+        NativeEmailMessage {
+            sender_name: Some(name.to_string()),
+            sender_address: Some(address.to_string()),
+            recipients: vec!["bob@example.com".to_string()],
+            sent_at: Some("Yesterday, 9:14".to_string()),
+            body: body.to_string(),
+        }
+    }
+
+    fn thread(messages: Vec<NativeEmailMessage>) -> NativeEmailThread {
+        NativeEmailThread {
+            tab_id: 7,
+            provider: EmailProvider::Gmail,
+            subject: Some("Quarterly report".to_string()),
+            messages,
+        }
+    }
+
+    #[test]
+    fn redacts_addresses_and_phone_numbers_by_default() {
+        let text = render_thread(
+            &thread(vec![message(
+                "Alice",
+                "alice@example.com",
+                "Call me at +1 555-123-4567 or write to alice.work@corp.io.",
+            )]),
+            &EmailRedactionConfig::default(),
+        )
+        .unwrap();
+
+        assert!(text.contains("[1] From: Alice\nTo: [email]"));
+        assert!(text.contains("Call me at [phone] or write to [email]."));
+        assert!(!text.contains("example.com"));
+    }
+
+    #[test]
+    fn keeps_addresses_when_redaction_disabled() {
+        let config = EmailRedactionConfig {
+            redact_addresses: false,
+            ..Default::default()
+        };
+        let text = render_thread(
+            &thread(vec![message("Alice", "alice@example.com", "Hi Bob")]),
+            &config,
+        )
+        .unwrap();
+        assert!(text.contains("From: Alice <alice@example.com>"));
+    }
+
+    #[test]
+    fn strips_quoted_history() {
+        let body = "Sounds good.\n\nOn Tue, 3 Jun 2025 at 10:14, Bob wrote:\n> Can we ship Friday?";
+        assert_eq!(strip_quoted(body), "Sounds good.");
+        assert_eq!(strip_quoted("> quoted\nreply"), "reply");
+    }
+
+    #[test]
+    fn returns_none_without_visible_bodies() {
+        let thread = thread(vec![message("Alice", "alice@example.com", "  ")]);
+        assert!(render_thread(&thread, &EmailRedactionConfig::default()).is_none());
+    }
+
+    #[test]
+    fn drops_oldest_messages_over_budget() {
+        let long = "x".repeat(MAX_THREAD_CHARS / 2);
+        let thread = thread(vec![
+            message("Old", "old@example.com", &long),
+            message("Mid", "mid@example.com", &long),
+            message("New", "new@example.com", "latest"),
+        ]);
+        let text = render_thread(&thread, &EmailRedactionConfig::default()).unwrap();
+        assert!(text.contains("[1 earlier message(s) omitted for length]"));
+        assert!(!text.contains("From: Old"));
+        assert!(text.contains("From: New"));
+    }
+}
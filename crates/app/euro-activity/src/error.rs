@@ -34,6 +34,9 @@ pub enum ActivityError {
 
     #[error("Unknown error: {0}")]
     Unknown(String),
+
+    #[error("{0}")]
+    LocalOnly(String),
 }
 
 impl ActivityError {
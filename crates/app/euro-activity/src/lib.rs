@@ -1,5 +1,11 @@
+pub mod calendar;
+pub mod code;
 pub mod config;
+pub mod dedup;
+pub mod email;
 pub mod error;
+pub mod moderation;
+pub mod readability;
 pub mod storage;
 pub mod strategies;
 pub mod tool_backend;
@@ -7,14 +13,16 @@ pub mod types;
 mod utils;
 
 pub use config::{
-    ActivityConfig, ActivityConfigBuilder, ApplicationConfig, GlobalConfig, PrivacyConfig,
-    SnapshotFrequency, StrategyConfig,
+    ActivityConfig, ActivityConfigBuilder, ApplicationConfig, EmailRedactionConfig, GlobalConfig,
+    ModerationAction, ModerationConfig, OcrSensitivity, PrivacyConfig, SnapshotFrequency,
+    StrategyConfig,
 };
 pub use error::{ActivityError, ActivityResult};
+pub use moderation::{ModeratedToolBackend, ModerationProvider, Moderator, OpenAiModeration};
 pub use storage::ActivityStorage;
 pub use strategies::ActivityStrategy;
 pub use strategies::{
-    ActivityReport, BrowserStrategy, DefaultStrategy, NoStrategy, PreviewStrategy,
+    ActivityReport, BrowserStrategy, CodeStrategy, DefaultStrategy, NoStrategy, PreviewStrategy,
 };
 pub use tool_backend::ActivityToolBackend;
 pub use types::{
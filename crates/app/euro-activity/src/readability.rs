@@ -0,0 +1,330 @@
+//! Main-content extraction for arbitrary web pages.
+//!
+//! A deliberately small take on Mozilla's Readability heuristics,
+//! applied to the raw HTML the extension ships in a
+//! [`NativeWebpageState`]. The pipeline is:
+//!
+//! 1. Drop elements that never carry article content (`<script>`,
+//!    `<style>`, `<nav>`, `<header>`, `<footer>`, `<aside>`, forms, …).
+//! 2. Narrow to the first `<article>` or `<main>` element when the page
+//!    declares one.
+//! 3. Split the remainder into text blocks at block-level tags, score
+//!    each block by text length and link density, and keep the ones
+//!    that look like prose.
+//!
+//! The output is plain text with one block per paragraph — enough for
+//! the LLM to answer questions about the page without paying for the
+//! surrounding chrome.
+
+use std::sync::LazyLock;
+
+use euro_browser::NativeWebpageState;
+use regex::Regex;
+
+/// Upper bound on the extracted text handed to the chat layer. Pages
+/// larger than this are cut at a paragraph boundary.
+pub const MAX_EXTRACTED_CHARS: usize = 24_000;
+
+/// Blocks shorter than this are dropped unless they are headings —
+/// navigation crumbs, button labels, and cookie banners live here.
+const MIN_BLOCK_CHARS: usize = 25;
+
+/// Blocks whose linked text exceeds this share of their total text are
+/// treated as link lists (menus, related-article rails) and dropped.
+const MAX_LINK_DENSITY: f32 = 0.5;
+
+/// Elements removed wholesale, content included, before scoring.
+const STRIPPED_ELEMENTS: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "canvas", "iframe", "nav", "header",
+    "footer", "aside", "form", "button", "select", "figure",
+];
+
+static COMMENT_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<!--.*?-->").unwrap());
+
+static STRIPPED_RES: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    STRIPPED_ELEMENTS
+        .iter()
+        .map(|tag| Regex::new(&format!(r"(?is)<{tag}\b[^>]*>.*?</{tag}\s*>")).unwrap())
+        .collect()
+});
+
+static BOILERPLATE_ATTR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?is)<(div|section|ul|p)\b[^>]*(?:class|id)\s*=\s*["'][^"']*\b(?:cookie|consent|banner|sidebar|share|social|newsletter|promo|advert|ads?|comments?|related|breadcrumbs?)\b[^"']*["'][^>]*>.*?</(?:div|section|ul|p)\s*>"#,
+    )
+    .unwrap()
+});
+
+static ARTICLE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<article\b[^>]*>(.*)</article\s*>").unwrap());
+
+static MAIN_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<main\b[^>]*>(.*)</main\s*>").unwrap());
+
+static BODY_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<body\b[^>]*>(.*)</body\s*>").unwrap());
+
+static BLOCK_BREAK_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)</?(?:p|div|section|article|li|ul|ol|tr|table|blockquote|pre|br|hr|dd|dt)\b[^>]*>",
+    )
+    .unwrap()
+});
+
+static HEADING_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<h([1-6])\b[^>]*>(.*?)</h[1-6]\s*>").unwrap());
+
+static LINK_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<a\b[^>]*>(.*?)</a\s*>").unwrap());
+
+static TAG_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<[^>]*>").unwrap());
+
+static WHITESPACE_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\s+").unwrap());
+
+/// Marker inserted in place of block-level tags so the scorer can split
+/// on it after tags are stripped. Private-use codepoint so it never
+/// collides with page text.
+const BLOCK_MARKER: &str = "\u{E000}";
+
+/// Result of a readability pass over one page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractedPage {
+    pub title: Option<String>,
+    pub byline: Option<String>,
+    pub excerpt: Option<String>,
+    /// Main content, one paragraph per line-pair.
+    pub text: String,
+    /// `true` when `text` was cut at [`MAX_EXTRACTED_CHARS`].
+    pub truncated: bool,
+}
+
+impl ExtractedPage {
+    /// `true` when the pass found nothing worth sending.
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty() && self.excerpt.is_none()
+    }
+}
+
+/// Run the readability pass over `state`.
+pub fn extract(state: &NativeWebpageState) -> ExtractedPage {
+    let blocks = extract_blocks(&state.html);
+
+    let mut text = String::new();
+    let mut truncated = false;
+    for block in blocks {
+        if text.len() + block.len() + 2 > MAX_EXTRACTED_CHARS {
+            truncated = true;
+            break;
+        }
+        if !text.is_empty() {
+            text.push_str("\n\n");
+        }
+        text.push_str(&block);
+    }
+
+    ExtractedPage {
+        title: non_empty(state.title.as_deref()),
+        byline: non_empty(state.byline.as_deref()),
+        excerpt: non_empty(state.description.as_deref()),
+        text,
+        truncated,
+    }
+}
+
+/// Split `html` into scored prose blocks, in document order.
+fn extract_blocks(html: &str) -> Vec<String> {
+    let mut cleaned = COMMENT_RE.replace_all(html, "").into_owned();
+    for re in STRIPPED_RES.iter() {
+        cleaned = re.replace_all(&cleaned, " ").into_owned();
+    }
+    cleaned = BOILERPLATE_ATTR_RE.replace_all(&cleaned, " ").into_owned();
+
+    let content = ARTICLE_RE
+        .captures(&cleaned)
+        .or_else(|| MAIN_RE.captures(&cleaned))
+        .or_else(|| BODY_RE.captures(&cleaned))
+        .and_then(|caps| caps.get(1))
+        .map_or(cleaned.as_str(), |m| m.as_str());
+
+    // Headings are kept regardless of length, so tag them before the
+    // generic block split flattens everything.
+    let with_headings = HEADING_RE.replace_all(content, |caps: &regex::Captures<'_>| {
+        let level: usize = caps[1].parse().unwrap_or(1);
+        format!(
+            "{BLOCK_MARKER}{} {}{BLOCK_MARKER}",
+            "#".repeat(level),
+            &caps[2]
+        )
+    });
+    let split = BLOCK_BREAK_RE.replace_all(&with_headings, BLOCK_MARKER);
+
+    split.split(BLOCK_MARKER).filter_map(score_block).collect()
+}
+
+/// Return the cleaned text of `raw` if it looks like prose, `None`
+/// otherwise.
+fn score_block(raw: &str) -> Option<String> {
+    let text = normalize_text(raw);
+    if text.is_empty() {
+        return None;
+    }
+    if text.starts_with('#') {
+        return Some(text);
+    }
+
+    let total = text.chars().count();
+    if total < MIN_BLOCK_CHARS {
+        return None;
+    }
+
+    let linked: usize = LINK_RE
+        .captures_iter(raw)
+        .map(|caps| normalize_text(&caps[1]).chars().count())
+        .sum();
+    if linked as f32 / total as f32 > MAX_LINK_DENSITY {
+        return None;
+    }
+
+    Some(text)
+}
+
+/// Strip tags, decode the common entities, and collapse whitespace.
+fn normalize_text(fragment: &str) -> String {
+    let stripped = TAG_RE.replace_all(fragment, " ");
+    let decoded = decode_entities(&stripped);
+    WHITESPACE_RE.replace_all(decoded.trim(), " ").into_owned()
+}
+
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(pos) = rest.find('&') {
+        out.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+        let Some(end) = rest.find(';').filter(|&end| end <= 12) else {
+            out.push('&');
+            rest = &rest[1..];
+            continue;
+        };
+        let entity = &rest[1..end];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" | "#39" => Some('\''),
+            "nbsp" => Some(' '),
+            "mdash" => Some('—'),
+            "ndash" => Some('–'),
+            "hellip" => Some('…'),
+            _ => entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(ch) => {
+                out.push(ch);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(ToOwned::to_owned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(html: &str) -> NativeWebpageState {
+        NativeWebpageState {
+            tab_id: 1,
+            url: "https://example.com/post".to_string(),
+            title: Some("A post".to_string()),
+            html: html.to_string(),
+            lang: None,
+            description: None,
+            site_name: None,
+            byline: None,
+        }
+    }
+
+    #[test]
+    fn drops_navigation_and_scripts() {
+        let page = extract(&state(
+            r#"<html><body>
+                <nav><a href="/">Home</a><a href="/about">About us and our long story</a></nav>
+                <script>var tracking = "this should never appear anywhere";</script>
+                <p>The main paragraph of the article talks about something useful.</p>
+                <footer>Copyright notice that is long enough to pass the length filter</footer>
+            </body></html>"#,
+        ));
+        assert_eq!(
+            page.text,
+            "The main paragraph of the article talks about something useful."
+        );
+    }
+
+    #[test]
+    fn prefers_article_element_when_present() {
+        let page = extract(&state(
+            r#"<body>
+                <div>Some sidebar copy that is not in the article element at all.</div>
+                <article><h1>Heading</h1><p>Article body text that is long enough to keep.</p></article>
+            </body>"#,
+        ));
+        assert_eq!(
+            page.text,
+            "# Heading\n\nArticle body text that is long enough to keep."
+        );
+    }
+
+    #[test]
+    fn drops_link_dense_blocks() {
+        let page = extract(&state(
+            r#"<body>
+                <div><a href="/a">First related story</a> <a href="/b">Second related story</a></div>
+                <p>Body text with <a href="/x">one link</a> inside a longer sentence.</p>
+            </body>"#,
+        ));
+        assert_eq!(
+            page.text,
+            "Body text with one link inside a longer sentence."
+        );
+    }
+
+    #[test]
+    fn decodes_entities() {
+        assert_eq!(
+            decode_entities("a &amp; b &#169; &#x41; &bogus;"),
+            "a & b © A &bogus;"
+        );
+    }
+
+    #[test]
+    fn truncates_at_block_boundary() {
+        let paragraph = format!("<p>{}</p>", "word ".repeat(2_000));
+        let html = format!("<body>{}</body>", paragraph.repeat(5));
+        let page = extract(&state(&html));
+        assert!(page.truncated);
+        assert!(page.text.len() <= MAX_EXTRACTED_CHARS);
+    }
+}
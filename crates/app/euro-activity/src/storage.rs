@@ -1,19 +1,34 @@
 use activity_core::{
-    ActivityErrorResponse, ActivityInsert, ActivityWithLatestSession, InsertActivitySessionRequest,
-    InsertActivitySessionResponse, ListActivitiesResponse, UpdateActivitySessionRequest,
-    UpdateActivitySessionResponse,
+    ActivityBatch, ActivityErrorResponse, ActivityInsert, ActivityTimelineQuery,
+    ActivityTimelineResponse, ActivityWithLatestSession, Device, InsertActivitySessionRequest,
+    InsertActivitySessionResponse, ListActivitiesResponse, RegisterDeviceRequest,
+    StreamActivitiesQuery, UpdateActivitySessionRequest, UpdateActivitySessionResponse,
 };
+use asset_core::{Asset, CreateAssetRequest};
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64_STANDARD};
 use chrono::{DateTime, Utc};
 use euro_auth::AuthManager;
 use euro_endpoint::EndpointManager;
+use euro_transfer::{Direction, Priority, TransferManager};
+use euro_vision::encode::ImageEncoding;
+use euro_vision::phash::FrameHash;
+use futures::{Stream, StreamExt, stream, stream::BoxStream};
+use image::RgbaImage;
 use reqwest::StatusCode;
 use secrecy::ExposeSecret;
-use std::{io::Cursor, sync::Arc};
+use std::{
+    io::Cursor,
+    pin::pin,
+    sync::{Arc, Mutex, PoisonError},
+};
 use uuid::Uuid;
 
+use crate::dedup::{DuplicateFilter, SuppressionStats};
 use crate::{ActivityError, ActivitySession, error::ActivityResult};
 
+/// Size of the pieces an asset upload is paced in under the bandwidth cap.
+const UPLOAD_CHUNK_BYTES: usize = 64 * 1024;
+
 /// HTTP client wrapper used to persist activity sessions.
 ///
 /// The session write path (`POST /activity-sessions`) speaks JSON over
@@ -22,22 +37,41 @@ use crate::{ActivityError, ActivitySession, error::ActivityResult};
 /// round trip; the response carries both rows so the caller can update
 /// the rail without a follow-up `GET /activities`.
 ///
-/// Asset persistence was removed alongside the bundled-context channel —
-/// the LLM pulls page contents through granular tools per turn, so there
-/// is no longer a server-side asset store fed by this client.
+/// Page contents are not persisted as assets — the LLM pulls them through
+/// granular tools per turn. The one asset this client writes is a screen
+/// region the user captured explicitly, via [`Self::save_screenshot`],
+/// which skips frames that look like the last one stored.
+///
+/// Asset bytes in either direction go through the [`TransferManager`], so
+/// they count against the user's bandwidth caps.
+///
+/// Sessions and assets carry this install's `device_id` so the user's
+/// machines don't blur into one timeline. The backend keeps the tag only
+/// once [`Self::register_device`] has made the device known.
 pub struct ActivityStorage {
     endpoint_manager: Arc<EndpointManager>,
     auth_manager: AuthManager,
+    transfers: TransferManager,
+    device_id: Option<Uuid>,
     http: reqwest::Client,
+    screenshots: Mutex<DuplicateFilter>,
 }
 
 impl ActivityStorage {
-    pub fn new(endpoint_manager: Arc<EndpointManager>, auth_manager: AuthManager) -> Self {
+    pub fn new(
+        endpoint_manager: Arc<EndpointManager>,
+        auth_manager: AuthManager,
+        transfers: TransferManager,
+        device_id: Option<Uuid>,
+    ) -> Self {
         let http = endpoint_manager.client();
         Self {
             endpoint_manager,
             auth_manager,
+            transfers,
+            device_id,
             http,
+            screenshots: Mutex::new(DuplicateFilter::default()),
         }
     }
 
@@ -45,6 +79,12 @@ impl ActivityStorage {
         self.endpoint_manager.url(path)
     }
 
+    fn ensure_local_backend(&self) -> ActivityResult<()> {
+        self.endpoint_manager
+            .ensure_local_backend()
+            .map_err(|e| ActivityError::LocalOnly(e.to_string()))
+    }
+
     async fn bearer(&self) -> ActivityResult<String> {
         let token = self
             .auth_manager
@@ -62,10 +102,14 @@ impl ActivityStorage {
     /// *without* `ended_at`: the parent's `ended_at IS NULL` invariant
     /// is the rail's live indicator; the backend bumps `last_used_at`
     /// when the session closes for real.
+    ///
+    /// Refused in local-only mode unless the backend is on this machine:
+    /// window titles and URLs are user context.
     pub async fn save_session_to_service(
         &self,
         session: &ActivitySession,
     ) -> ActivityResult<InsertActivitySessionResponse> {
+        self.ensure_local_backend()?;
         let icon_png_base64 = match session.icon.as_ref() {
             Some(icon) => {
                 let mut bytes: Vec<u8> = Vec::new();
@@ -89,6 +133,7 @@ impl ActivityStorage {
             url: session.url.as_ref().map(|u| u.to_string()),
             started_at: session.started_at,
             ended_at: session.ended_at,
+            device_id: self.device_id,
         };
 
         let bearer = self.bearer().await?;
@@ -146,6 +191,115 @@ impl ActivityStorage {
         Ok(body.activities)
     }
 
+    /// Stream the persisted activities in rail order from
+    /// `GET /activities/stream`, yielding each batch as soon as its line
+    /// arrives.
+    ///
+    /// The server queries the next batch only once the previous one has
+    /// been read, so a consumer that awaits between items paces the whole
+    /// pipeline. A dropped connection surfaces as a network error; resume
+    /// by passing the last batch's `next_cursor` as `query.cursor`.
+    pub async fn stream_activities(
+        &self,
+        query: &StreamActivitiesQuery,
+    ) -> ActivityResult<BoxStream<'static, ActivityResult<ActivityBatch>>> {
+        let bearer = self.bearer().await?;
+        let response = self
+            .http
+            .get(self.url("/activities/stream"))
+            .header("Authorization", bearer)
+            .query(query)
+            .send()
+            .await
+            .map_err(|e| ActivityError::network(format!("activity stream request failed: {e}")))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(map_http_error_response(status, response).await);
+        }
+
+        Ok(ndjson_lines(response.bytes_stream()).boxed())
+    }
+
+    /// Fetch every persisted session overlapping `[since, until)`, grouped
+    /// under its parent activity, from `GET /activities/timeline`.
+    ///
+    /// The server caps the range at `activity_core::MAX_TIMELINE_DAYS`;
+    /// a longer one surfaces as a network error carrying its typed
+    /// [`ActivityErrorResponse`] body. `device_id` narrows it to the
+    /// sessions from one of the user's devices; `None` merges them all.
+    pub async fn activity_timeline(
+        &self,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+        device_id: Option<Uuid>,
+    ) -> ActivityResult<ActivityTimelineResponse> {
+        let bearer = self.bearer().await?;
+        let response = self
+            .http
+            .get(self.url("/activities/timeline"))
+            .header("Authorization", bearer)
+            .query(&ActivityTimelineQuery {
+                since,
+                until,
+                device_id,
+            })
+            .send()
+            .await
+            .map_err(|e| {
+                ActivityError::network(format!("activity timeline request failed: {e}"))
+            })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(map_http_error_response(status, response).await);
+        }
+
+        response.json().await.map_err(|e| {
+            ActivityError::network(format!("Failed to decode activity timeline response: {e}"))
+        })
+    }
+
+    /// This install's device id, if it has one.
+    pub fn device_id(&self) -> Option<Uuid> {
+        self.device_id
+    }
+
+    /// Register this install with `PUT /devices/{id}` under `name`, or
+    /// refresh its name and last-seen time if it already is. A no-op
+    /// `Ok(None)` without a device id.
+    pub async fn register_device(
+        &self,
+        name: String,
+        platform: String,
+    ) -> ActivityResult<Option<Device>> {
+        let Some(device_id) = self.device_id else {
+            return Ok(None);
+        };
+        let bearer = self.bearer().await?;
+        let response = self
+            .http
+            .put(self.url(&format!("/devices/{device_id}")))
+            .header("Authorization", bearer)
+            .json(&RegisterDeviceRequest { name, platform })
+            .send()
+            .await
+            .map_err(|e| {
+                ActivityError::network(format!("device registration request failed: {e}"))
+            })?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(map_http_error_response(status, response).await);
+        }
+
+        response
+            .json()
+            .await
+            .map(Some)
+            .map_err(|e| ActivityError::network(format!("Failed to decode device response: {e}")))
+    }
+
     /// Fetch the raw bytes for an asset by id.
     ///
     /// `None` indicates a clean 404 (the asset does not exist, or is
@@ -153,11 +307,14 @@ impl ActivityStorage {
     /// conflates the two so foreign ids can't be probed). Any other
     /// non-success status surfaces as [`ActivityError::Network`]. The
     /// returned MIME type mirrors the value recorded at upload time.
+    /// The body is paced under the download cap.
     pub async fn fetch_asset_bytes(
         &self,
         asset_id: Uuid,
+        priority: Priority,
     ) -> ActivityResult<Option<(Vec<u8>, String)>> {
         let bearer = self.bearer().await?;
+        let permit = self.transfers.acquire(Direction::Download, priority).await;
         let response = self
             .http
             .get(self.url(&format!("/v1/assets/{asset_id}")))
@@ -181,12 +338,127 @@ impl ActivityStorage {
             .map(|s| s.to_owned())
             .unwrap_or_else(|| "application/octet-stream".to_string());
 
-        let bytes = response
-            .bytes()
+        let mut body = pin!(permit.throttle(response.bytes_stream()));
+        let mut bytes = Vec::new();
+        while let Some(chunk) = body.next().await {
+            let chunk = chunk
+                .map_err(|e| ActivityError::network(format!("Failed to read asset bytes: {e}")))?;
+            bytes.extend_from_slice(&chunk);
+        }
+
+        Ok(Some((bytes, mime_type)))
+    }
+
+    /// Upload `content` to the asset service and return the stored record.
+    /// The upload waits for a transfer slot at `priority` and is paced
+    /// under the upload cap.
+    ///
+    /// Refused in local-only mode unless the backend is on this machine,
+    /// like every other write of user context.
+    pub async fn save_asset(
+        &self,
+        name: String,
+        content: &[u8],
+        mime_type: String,
+        metadata: serde_json::Value,
+        priority: Priority,
+    ) -> ActivityResult<Asset> {
+        self.ensure_local_backend()?;
+        let request = CreateAssetRequest {
+            name,
+            content: BASE64_STANDARD.encode(content),
+            mime_type,
+            metadata: Some(metadata),
+            device_id: self.device_id,
+        };
+        let body = serde_json::to_vec(&request)
+            .map_err(|e| ActivityError::invalid_data(format!("Failed to encode asset: {e}")))?;
+        let content_length = body.len();
+        let chunks: Vec<Vec<u8>> = body
+            .chunks(UPLOAD_CHUNK_BYTES)
+            .map(<[u8]>::to_vec)
+            .collect();
+
+        let bearer = self.bearer().await?;
+        let permit = self.transfers.acquire(Direction::Upload, priority).await;
+        let body = permit.throttle(stream::iter(chunks).map(Ok::<_, std::io::Error>));
+        let response = self
+            .http
+            .post(self.url("/v1/assets"))
+            .header("Authorization", bearer)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(reqwest::header::CONTENT_LENGTH, content_length)
+            .body(reqwest::Body::wrap_stream(body))
+            .send()
             .await
-            .map_err(|e| ActivityError::network(format!("Failed to read asset bytes: {e}")))?;
+            .map_err(|e| ActivityError::network(format!("asset upload request failed: {e}")))?;
 
-        Ok(Some((bytes.to_vec(), mime_type)))
+        let status = response.status();
+        if !status.is_success() {
+            return Err(map_http_error_response(status, response).await);
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| ActivityError::network(format!("Failed to decode asset response: {e}")))
+    }
+
+    /// Store `frame` as an asset named `{name}.{extension}` in `encoding`,
+    /// unless it looks like the last screenshot stored, in which case
+    /// `Ok(None)`. The frame's perceptual hash goes into the metadata as
+    /// `phash`.
+    pub async fn save_screenshot(
+        &self,
+        name: &str,
+        frame: RgbaImage,
+        encoding: ImageEncoding,
+        mut metadata: serde_json::Value,
+    ) -> ActivityResult<Option<Asset>> {
+        let hash = FrameHash::of(&frame);
+        if self.screenshots().is_duplicate(hash) {
+            let stats = self.screenshot_suppression();
+            tracing::debug!(
+                suppressed = stats.suppressed,
+                kept = stats.kept,
+                rate = stats.rate(),
+                "Skipped storing a near-duplicate screenshot"
+            );
+            return Ok(None);
+        }
+
+        let bytes = tokio::task::spawn_blocking(move || encoding.encode(&frame))
+            .await
+            .map_err(|e| ActivityError::unknown(format!("screenshot encoding panicked: {e}")))?
+            .map_err(|e| ActivityError::invalid_data(format!("screenshot encoding failed: {e}")))?;
+        if let Some(fields) = metadata.as_object_mut() {
+            fields.insert("phash".into(), format!("{:016x}", hash.0).into());
+        }
+        let asset = self
+            .save_asset(
+                format!("{name}.{}", encoding.extension()),
+                &bytes,
+                encoding.mime_type().to_owned(),
+                metadata,
+                Priority::Interactive,
+            )
+            .await?;
+        self.screenshots().keep(hash);
+        Ok(Some(asset))
+    }
+
+    /// How many screenshots [`Self::save_screenshot`] has stored and
+    /// skipped as near-duplicates since startup.
+    pub fn screenshot_suppression(&self) -> SuppressionStats {
+        self.screenshots().stats()
+    }
+
+    fn screenshots(&self) -> std::sync::MutexGuard<'_, DuplicateFilter> {
+        // The filter holds only counters and a hash, so a panic
+        // mid-update can't leave it inconsistent.
+        self.screenshots
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
     }
 
     /// PATCH a session's `ended_at` on the backend.
@@ -223,6 +495,7 @@ impl ActivityStorage {
         window_title: String,
         url: Option<String>,
     ) -> ActivityResult<UpdateActivitySessionResponse> {
+        self.ensure_local_backend()?;
         let request = UpdateActivitySessionRequest {
             window_title: Some(window_title),
             url,
@@ -260,6 +533,51 @@ impl ActivityStorage {
     }
 }
 
+/// Split a chunked response body into newline-delimited JSON values.
+/// Chunk boundaries don't line up with lines, so partial lines are
+/// buffered until their newline arrives.
+fn ndjson_lines<S, B, T>(body: S) -> impl Stream<Item = ActivityResult<T>> + Send
+where
+    S: Stream<Item = reqwest::Result<B>> + Send + 'static,
+    B: AsRef<[u8]> + Send,
+    T: serde::de::DeserializeOwned + Send,
+{
+    stream::unfold(
+        (Box::pin(body), Vec::new(), false),
+        |(mut body, mut buf, mut done)| async move {
+            loop {
+                if let Some(pos) = buf.iter().position(|b| *b == b'\n') {
+                    let line: Vec<u8> = buf.drain(..=pos).collect();
+                    if line.trim_ascii().is_empty() {
+                        continue;
+                    }
+                    return Some((decode_line(&line), (body, buf, done)));
+                }
+                if done {
+                    if buf.trim_ascii().is_empty() {
+                        return None;
+                    }
+                    let line = std::mem::take(&mut buf);
+                    return Some((decode_line(&line), (body, buf, done)));
+                }
+                match body.next().await {
+                    Some(Ok(chunk)) => buf.extend_from_slice(chunk.as_ref()),
+                    Some(Err(e)) => {
+                        let err = ActivityError::network(format!("activity stream failed: {e}"));
+                        return Some((Err(err), (body, Vec::new(), true)));
+                    }
+                    None => done = true,
+                }
+            }
+        },
+    )
+}
+
+fn decode_line<T: serde::de::DeserializeOwned>(line: &[u8]) -> ActivityResult<T> {
+    serde_json::from_slice(line)
+        .map_err(|e| ActivityError::network(format!("Failed to decode activity stream line: {e}")))
+}
+
 async fn map_http_error_response(status: StatusCode, response: reqwest::Response) -> ActivityError {
     let body_text = response.text().await.unwrap_or_default();
     if let Ok(parsed) = serde_json::from_str::<ActivityErrorResponse>(&body_text) {
@@ -271,3 +589,18 @@ async fn map_http_error_response(status: StatusCode, response: reqwest::Response
         ActivityError::network(format!("service returned {status}: {body_text}"))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn ndjson_lines_reassembles_lines_split_across_chunks() {
+        let chunks: [&[u8]; 3] = [b"{\"n\":1}\n{\"n\"", b":2}\n\n", b"{\"n\":3}"];
+        let body = stream::iter(chunks.map(reqwest::Result::Ok));
+        let values: Vec<serde_json::Value> =
+            ndjson_lines(body).map(|line| line.unwrap()).collect().await;
+        let ns: Vec<_> = values.iter().map(|v| v["n"].as_i64().unwrap()).collect();
+        assert_eq!(ns, [1, 2, 3]);
+    }
+}
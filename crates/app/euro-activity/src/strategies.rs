@@ -11,12 +11,14 @@ use tokio::sync::mpsc;
 use url::Url;
 
 pub mod browser;
+pub mod code;
 pub mod default;
 pub mod no_strategy;
 pub mod preview;
 pub mod word;
 
 pub use browser::BrowserStrategy;
+pub use code::CodeStrategy;
 pub use default::DefaultStrategy;
 use euro_browser::NativeMetadata;
 pub use no_strategy::NoStrategy;
@@ -87,6 +89,7 @@ impl From<NativeMetadata> for StrategyMetadata {
 pub enum ActivityStrategy {
     BrowserStrategy,
     WordStrategy,
+    CodeStrategy,
     PreviewStrategy,
     DefaultStrategy,
     NoStrategy,
@@ -98,15 +101,17 @@ impl ActivityStrategy {
     /// Strategies are tried in priority order: [`NoStrategy`] suppresses
     /// tracking for Eurora's own processes, [`BrowserStrategy`] handles
     /// known browsers, [`WordStrategy`] handles the Microsoft Word
-    /// integration, [`PreviewStrategy`] handles macOS Preview.app (and
+    /// integration, [`CodeStrategy`] handles code editors and terminals,
+    /// [`PreviewStrategy`] handles macOS Preview.app (and
     /// is a no-op on other targets), and any other process falls through
     /// to [`DefaultStrategy`].
     ///
     /// The full [`FocusedWindow`] is threaded through (rather than just
     /// the process name) because [`DefaultStrategy`] is window-bound at
-    /// construction — it cannot exist without a target window. The
-    /// specialised strategies still match by process name only and pick
-    /// up their per-window state via `start_tracking`.
+    /// construction — it cannot exist without a target window
+    /// ([`CodeStrategy`] is bound the same way). The other specialised
+    /// strategies still match by process name only and pick up their
+    /// per-window state via `start_tracking`.
     pub async fn new(focus_window: &FocusedWindow) -> ActivityResult<ActivityStrategy> {
         let process_name = focus_window.process_name.as_str();
         if NoStrategy::matches_process(process_name) {
@@ -118,6 +123,9 @@ impl ActivityStrategy {
         if WordStrategy::matches_process(process_name) {
             return WordStrategy::create().await;
         }
+        if let Some(strategy) = CodeStrategy::new(focus_window.clone()) {
+            return Ok(ActivityStrategy::CodeStrategy(strategy));
+        }
         if PreviewStrategy::matches_process(process_name) {
             return PreviewStrategy::create().await;
         }
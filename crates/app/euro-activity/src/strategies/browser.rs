@@ -1,8 +1,10 @@
-use agent_chain_core::messages::{ContentBlock, ContentBlocks};
+use agent_chain_core::messages::{ContentBlock, ContentBlocks, TextContentBlock};
 use async_trait::async_trait;
 use euro_bridge::{BridgeError, Payload};
 pub use euro_bridge::{BridgeService, EventFrame, Frame, FrameKind, RequestFrame, ResponseFrame};
-use euro_browser::{NativeMessage, NativeMetadata};
+use euro_browser::{
+    EmailProvider, NativeEmailThread, NativeMessage, NativeMetadata, NativeWebpageState,
+};
 use euro_process::Browser;
 use focus_tracker::FocusedWindow;
 use serde::{Deserialize, Serialize};
@@ -15,11 +17,13 @@ use thread_core::{ToolBackendCall, ToolErrorWire, WireToolDescriptor};
 use tokio::sync::mpsc;
 use url::Url;
 
+use crate::config::EmailRedactionConfig;
 pub use crate::strategies::ActivityStrategyFunctionality;
 use crate::strategies::{ActivityReport, StrategyMetadata};
 pub use crate::strategies::{ActivityStrategy, StrategySupport};
 use crate::types::base_domain_label;
 use crate::{ActivityError, ActivitySession, error::ActivityResult};
+use crate::{email, readability};
 
 /// Bridge action the extension answers with the active tab's tool list.
 const ACTION_LIST_TOOLS: &str = "LIST_TOOLS";
@@ -45,6 +49,18 @@ const ACTION_GET_METADATA: &str = "GET_METADATA";
 /// a [`ContentBlock`] and surfaces it through the chat-context puller.
 const ACTION_GET_CONTEXT: &str = "GET_CONTEXT";
 
+/// Bridge action that asks the `_common` content script for a raw
+/// [`NativeWebpageState`] snapshot of the active tab. Only issued when
+/// [`ACTION_GET_CONTEXT`] produced nothing, so pages without a per-site
+/// watcher still get a readability-extracted summary.
+const ACTION_GET_PAGE_STATE: &str = "GET_PAGE_STATE";
+
+/// Bridge action answered by the Gmail / Outlook site bundles with a
+/// [`NativeEmailThread`] for the open conversation. Issued ahead of
+/// [`ACTION_GET_CONTEXT`] whenever the active tab is a webmail client so
+/// the thread goes through desktop-side PII redaction.
+const ACTION_GET_EMAIL_THREAD: &str = "GET_EMAIL_THREAD";
+
 /// Wire payload returned by the extension for [`ACTION_LIST_TOOLS`]. The
 /// shape stays in sync with `apps/browser/src/shared/background/native-messenger.ts`.
 #[derive(Debug, Deserialize)]
@@ -88,6 +104,11 @@ pub struct BrowserStrategy {
 
     #[serde(skip)]
     last_url: Arc<tokio::sync::Mutex<Option<Url>>>,
+
+    /// PII policy applied to webmail threads before they become chat
+    /// context.
+    #[serde(skip)]
+    email_redaction: EmailRedactionConfig,
 }
 
 impl BrowserStrategy {
@@ -156,7 +177,10 @@ impl BrowserStrategy {
                 if event_frame.action.as_str() == "TAB_ACTIVATED"
                     || event_frame.action.as_str() == "TAB_UPDATED"
                 {
-                    let NativeMessage::NativeMetadata(data) = native_message;
+                    let NativeMessage::NativeMetadata(data) = native_message else {
+                        tracing::debug!("Ignoring {} event without metadata", event_frame.action);
+                        continue;
+                    };
                     let metadata = StrategyMetadata::from(data);
 
                     let Some(url) = metadata.url else {
@@ -236,8 +260,63 @@ impl BrowserStrategy {
             ActivityError::invalid_data(format!("Failed to decode metadata: {}", e))
         })?;
 
-        let NativeMessage::NativeMetadata(metadata) = native_message;
-        Ok(metadata)
+        match native_message {
+            NativeMessage::NativeMetadata(metadata) => Ok(metadata),
+            _ => Err(ActivityError::invalid_data(
+                "Metadata response carried a non-metadata payload",
+            )),
+        }
+    }
+
+    /// Fallback context for pages without a per-site watcher: fetch the
+    /// raw page via `GET_PAGE_STATE` and run it through
+    /// [`readability::extract`]. Same best-effort contract as
+    /// `get_context` — every failure path yields an empty block list.
+    async fn fetch_page_context(
+        &self,
+        service: &'static BridgeService,
+        pid: u32,
+        tab_id: i32,
+    ) -> ContentBlocks {
+        let payload = match Payload::from_value(&json!({ "tab_id": tab_id })) {
+            Ok(p) => p,
+            Err(err) => {
+                tracing::warn!("GET_PAGE_STATE payload encode failed: {err}");
+                return ContentBlocks::new();
+            }
+        };
+        let response = match service
+            .send_request(pid, ACTION_GET_PAGE_STATE, Some(payload))
+            .await
+        {
+            Ok(resp) => resp,
+            Err(err) => {
+                tracing::debug!("GET_PAGE_STATE bridge call failed: {err}");
+                return ContentBlocks::new();
+            }
+        };
+        let Some(payload) = response.payload else {
+            return ContentBlocks::new();
+        };
+        let state = match payload.deserialize::<NativeMessage>() {
+            Ok(NativeMessage::NativeWebpageState(state)) => state,
+            Ok(_) => {
+                tracing::warn!("GET_PAGE_STATE returned a non-page-state payload");
+                return ContentBlocks::new();
+            }
+            Err(err) => {
+                tracing::warn!("GET_PAGE_STATE payload decode failed: {err}");
+                return ContentBlocks::new();
+            }
+        };
+
+        match render_page_context(&state) {
+            Some(text) => vec![ContentBlock::Text(
+                TextContentBlock::builder().text(text).build(),
+            )]
+            .into(),
+            None => ContentBlocks::new(),
+        }
     }
 
     /// `(service, pid, metadata)` triple for the active tab, suitable
@@ -283,6 +362,48 @@ impl BrowserStrategy {
         }
     }
 
+    /// Webmail context: ask the site bundle for the open thread via
+    /// `GET_EMAIL_THREAD` and render it through [`email::render_thread`]
+    /// with this strategy's redaction policy. `None` on any failure so
+    /// the caller falls through to the generic `GET_CONTEXT` path.
+    async fn fetch_email_context(
+        &self,
+        service: &'static BridgeService,
+        pid: u32,
+        tab_id: i32,
+    ) -> Option<ContentBlocks> {
+        let payload = Payload::from_value(&json!({ "tab_id": tab_id })).ok()?;
+        let response = match service
+            .send_request(pid, ACTION_GET_EMAIL_THREAD, Some(payload))
+            .await
+        {
+            Ok(resp) => resp,
+            Err(err) => {
+                tracing::debug!("GET_EMAIL_THREAD bridge call failed: {err}");
+                return None;
+            }
+        };
+        let thread: NativeEmailThread = match response.payload?.deserialize::<NativeMessage>() {
+            Ok(NativeMessage::NativeEmailThread(thread)) => thread,
+            Ok(_) => {
+                tracing::warn!("GET_EMAIL_THREAD returned a non-thread payload");
+                return None;
+            }
+            Err(err) => {
+                tracing::warn!("GET_EMAIL_THREAD payload decode failed: {err}");
+                return None;
+            }
+        };
+
+        let text = email::render_thread(&thread, &self.email_redaction)?;
+        Some(
+            vec![ContentBlock::Text(
+                TextContentBlock::builder().text(text).build(),
+            )]
+            .into(),
+        )
+    }
+
     async fn resolve_messenger_pid(&self, process_name: &str, fallback_pid: u32) -> u32 {
         if let Some(service) = &self.bridge_service
             && let Some(pid) = service.find_pid_by_app_name(process_name)
@@ -380,6 +501,12 @@ impl BrowserStrategy {
         strategy.initialize_service().await?;
         Ok(strategy)
     }
+
+    /// Override the default [`EmailRedactionConfig`].
+    pub fn with_email_redaction(mut self, config: EmailRedactionConfig) -> Self {
+        self.email_redaction = config;
+        self
+    }
 }
 
 #[async_trait]
@@ -535,10 +662,30 @@ impl ActivityStrategyFunctionality for BrowserStrategy {
     /// matching content script for its blocks. Any failure path —
     /// missing tab, disconnected messenger, malformed payload — returns
     /// an empty [`ContentBlocks`] rather than aborting the chat turn.
+    ///
+    /// Webmail tabs (Gmail, Outlook) are tried first through
+    /// [`Self::fetch_email_context`] so the thread is redacted
+    /// desktop-side. When the content script answers but has nothing to
+    /// say (no per-site watcher for this page), falls back to
+    /// [`Self::fetch_page_context`] so arbitrary pages still get a
+    /// readability summary.
     async fn get_context(&self) -> ActivityResult<ContentBlocks> {
         let Some((service, pid, metadata)) = self.fetch_active_tab().await else {
             return Ok(ContentBlocks::new());
         };
+        let is_webmail = metadata
+            .url
+            .as_deref()
+            .and_then(|raw| Url::parse(raw).ok())
+            .and_then(|url| url.host_str().and_then(EmailProvider::from_host))
+            .is_some();
+        if is_webmail
+            && let Some(blocks) = self
+                .fetch_email_context(service, pid, metadata.tab_id)
+                .await
+        {
+            return Ok(blocks);
+        }
         let payload = match Payload::from_value(&json!({ "tab_id": metadata.tab_id })) {
             Ok(p) => p,
             Err(err) => {
@@ -561,16 +708,21 @@ impl ActivityStrategyFunctionality for BrowserStrategy {
         };
 
         let Some(payload) = response.payload else {
-            return Ok(ContentBlocks::new());
+            return Ok(self.fetch_page_context(service, pid, metadata.tab_id).await);
         };
 
-        match payload.deserialize::<GetContextPayload>() {
-            Ok(payload) => Ok(payload.blocks.into()),
+        let blocks = match payload.deserialize::<GetContextPayload>() {
+            Ok(payload) => payload.blocks,
             Err(err) => {
                 tracing::warn!("GET_CONTEXT payload decode failed: {err}");
-                Ok(ContentBlocks::new())
+                Vec::new()
             }
+        };
+        if !blocks.is_empty() {
+            return Ok(blocks.into());
         }
+
+        Ok(self.fetch_page_context(service, pid, metadata.tab_id).await)
     }
 
     async fn dispatch_tool(&self, call: ToolBackendCall) -> Result<Value, ToolErrorWire> {
@@ -643,6 +795,39 @@ impl ActivityStrategyFunctionality for BrowserStrategy {
     }
 }
 
+/// Format a readability pass over `state` as the single natural-language
+/// context block the chat layer expects. `None` when the page yielded
+/// no usable content.
+fn render_page_context(state: &NativeWebpageState) -> Option<String> {
+    let page = readability::extract(state);
+    if page.is_empty() {
+        return None;
+    }
+
+    let title = page.title.as_deref().unwrap_or(state.url.as_str());
+    let mut text = format!(
+        "The user is viewing the web page `{title}` ({}).",
+        state.url
+    );
+    if let Some(site) = state.site_name.as_deref().filter(|s| !s.is_empty()) {
+        text.push_str(&format!(" Site: {site}."));
+    }
+    if let Some(byline) = page.byline.as_deref() {
+        text.push_str(&format!(" Author: {byline}."));
+    }
+    if let Some(excerpt) = page.excerpt.as_deref() {
+        text.push_str(&format!("\n\nDescription: {excerpt}"));
+    }
+    if !page.text.is_empty() {
+        text.push_str("\n\nMain content:\n\n");
+        text.push_str(&page.text);
+        if page.truncated {
+            text.push_str("\n\n[content truncated]");
+        }
+    }
+    Some(text)
+}
+
 /// The extension wraps its reply in `{"ok": <value>}` on success and
 /// `{"err": <ToolErrorWire>}` on failure so the discriminator is
 /// explicit. Any other shape is treated as a transport-side decode error.
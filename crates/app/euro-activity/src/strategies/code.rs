@@ -0,0 +1,259 @@
+//! Activity strategy for code editors, IDEs, and terminal emulators.
+//!
+//! Like [`super::DefaultStrategy`], each instance is bound to the
+//! [`FocusedWindow`] it was built for and defers every focus change back
+//! to the dispatcher. What it adds is per-turn context: a screenshot of
+//! the editor/terminal window plus a short preamble naming the app, the
+//! detected language, and the code-task templates from
+//! [`crate::code::CodeTask`]. The model reads the visible code off the
+//! screenshot and is asked to transcribe what it quotes into fenced
+//! blocks tagged with the detected language.
+
+use agent_chain_core::messages::{
+    ContentBlock, ContentBlocks, ImageContentBlock, TextContentBlock,
+};
+use async_trait::async_trait;
+use euro_process::{CodeApp, CodeAppKind};
+use focus_tracker::FocusedWindow;
+use serde_json::Value;
+use thread_core::{ToolBackendCall, ToolErrorWire, WireToolDescriptor};
+use tokio::sync::mpsc;
+
+use crate::{
+    code::{CodeLanguage, CodeTask},
+    error::ActivityResult,
+    strategies::{ActivityReport, ActivityStrategyFunctionality, StrategyMetadata},
+    types::ActivitySession,
+};
+
+#[derive(Clone)]
+pub struct CodeStrategy {
+    /// The window this strategy is responsible for. Set at construction
+    /// and refreshed by `start_tracking`.
+    focused_window: FocusedWindow,
+
+    app: CodeApp,
+
+    sender: Option<mpsc::UnboundedSender<ActivityReport>>,
+}
+
+impl CodeStrategy {
+    /// `true` if `process_name` is a known editor or terminal.
+    pub fn matches_process(process_name: &str) -> bool {
+        CodeApp::from_process_name(process_name).is_some()
+    }
+
+    /// Build a strategy bound to `focused_window`. Returns `None` when
+    /// the window's process is not a known code application.
+    pub fn new(focused_window: FocusedWindow) -> Option<Self> {
+        let app = CodeApp::from_process_name(&focused_window.process_name)?;
+        Some(Self {
+            focused_window,
+            app,
+            sender: None,
+        })
+    }
+
+    fn build_session(&self) -> ActivitySession {
+        ActivitySession::new_process(
+            self.focused_window.process_name.clone(),
+            self.focused_window.process_id,
+            self.focused_window.window_title.clone(),
+            self.focused_window.icon.clone(),
+        )
+    }
+
+    fn detect_language(&self) -> Option<CodeLanguage> {
+        let title = self.focused_window.window_title.as_deref()?;
+        CodeLanguage::from_window_title(title).or_else(|| CodeLanguage::detect_from_text(title))
+    }
+
+    /// Natural-language preamble for the current turn.
+    fn render_preamble(&self, language: Option<CodeLanguage>, has_screenshot: bool) -> String {
+        let surface = match self.app.kind() {
+            CodeAppKind::Editor => "code editor",
+            CodeAppKind::Terminal => "terminal",
+        };
+        let mut text = format!(
+            "The user is working in a {surface} ({}).",
+            self.focused_window.process_name
+        );
+        if let Some(title) = self.focused_window.window_title.as_deref()
+            && !title.is_empty()
+        {
+            text.push_str(&format!(" Window title: `{title}`."));
+        }
+        if let Some(language) = language {
+            text.push_str(&format!(
+                " The visible code appears to be {}.",
+                language.display_name()
+            ));
+        }
+
+        if has_screenshot {
+            let fence = language.map_or("", CodeLanguage::fence_tag);
+            text.push_str(&format!(
+                "\n\nA screenshot of the window is attached. When you refer to code in it, transcribe the relevant lines exactly into fenced code blocks (```{fence}), dropping line-number gutters and editor decorations."
+            ));
+        }
+
+        text.push('\n');
+        for task in CodeTask::ALL {
+            text.push_str("\n- ");
+            text.push_str(&task.template(language));
+        }
+        text
+    }
+}
+
+impl std::fmt::Debug for CodeStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CodeStrategy")
+            .field("focused_window", &self.focused_window)
+            .field("app", &self.app)
+            .field("has_sender", &self.sender.is_some())
+            .finish()
+    }
+}
+
+#[async_trait]
+impl ActivityStrategyFunctionality for CodeStrategy {
+    fn can_handle_process(&self, _focus_window: &FocusedWindow) -> bool {
+        false
+    }
+
+    async fn start_tracking(
+        &mut self,
+        focus_window: &FocusedWindow,
+        sender: mpsc::UnboundedSender<ActivityReport>,
+    ) -> ActivityResult<()> {
+        tracing::debug!(
+            "Code strategy starting tracking for: {:?}",
+            focus_window.process_name
+        );
+
+        self.focused_window = focus_window.clone();
+        self.sender = Some(sender.clone());
+
+        let _ = sender.send(ActivityReport::NewActivity(self.build_session()));
+
+        Ok(())
+    }
+
+    async fn handle_process_change(
+        &mut self,
+        focus_window: &FocusedWindow,
+    ) -> ActivityResult<bool> {
+        tracing::debug!(
+            "Code strategy handling process change to: {}",
+            focus_window.process_name
+        );
+        // Window-bound, same as `DefaultStrategy`: let the dispatcher
+        // rebuild for whatever has focus now.
+        Ok(false)
+    }
+
+    async fn stop_tracking(&mut self) -> ActivityResult<()> {
+        tracing::debug!("Code strategy stopping tracking");
+        self.sender = None;
+        Ok(())
+    }
+
+    async fn get_metadata(&self) -> ActivityResult<StrategyMetadata> {
+        Ok(StrategyMetadata {
+            url: None,
+            title: self.focused_window.window_title.clone(),
+            icon: self.focused_window.icon.clone(),
+        })
+    }
+
+    async fn get_tools(&self) -> ActivityResult<Vec<WireToolDescriptor>> {
+        Ok(vec![])
+    }
+
+    /// Screenshot the bound window and pair it with the code preamble.
+    /// Capture failures (no permission, window gone) degrade to the
+    /// text-only preamble rather than failing the turn.
+    async fn get_context(&self) -> ActivityResult<ContentBlocks> {
+        let screenshot =
+            match euro_vision::capture::capture_window_by_pid(self.focused_window.process_id).await
+            {
+                Ok(capture) => capture,
+                Err(err) => {
+                    tracing::debug!("Code strategy window capture failed: {err}");
+                    None
+                }
+            };
+
+        let language = self.detect_language();
+        let preamble = self.render_preamble(language, screenshot.is_some());
+
+        let mut blocks = vec![ContentBlock::Text(
+            TextContentBlock::builder().text(preamble).build(),
+        )];
+        if let Some(capture) = screenshot {
+            match ImageContentBlock::builder()
+                .base64(capture.png_base64)
+                .mime_type("image/png".to_string())
+                .build()
+            {
+                Ok(image) => blocks.push(ContentBlock::Image(image)),
+                Err(err) => tracing::debug!("Code strategy image block rejected: {err}"),
+            }
+        }
+        Ok(blocks.into())
+    }
+
+    async fn dispatch_tool(&self, call: ToolBackendCall) -> Result<Value, ToolErrorWire> {
+        Err(ToolErrorWire::ContextUnavailable {
+            tool: call.name,
+            reason: "no tools available for this strategy".to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(name: &str, title: Option<&str>) -> FocusedWindow {
+        FocusedWindow {
+            process_id: 42,
+            process_name: name.to_string(),
+            window_title: title.map(ToOwned::to_owned),
+            icon: None,
+        }
+    }
+
+    #[test]
+    fn only_binds_to_code_apps() {
+        assert!(CodeStrategy::new(window("not-an-editor", None)).is_none());
+        assert!(CodeStrategy::new(window(CodeApp::VsCode.process_name(), None)).is_some());
+    }
+
+    #[test]
+    fn preamble_names_language_and_fence() {
+        let strategy = CodeStrategy::new(window(
+            CodeApp::VsCode.process_name(),
+            Some("main.rs - eurora - Visual Studio Code"),
+        ))
+        .unwrap();
+        let language = strategy.detect_language();
+        assert_eq!(language, Some(CodeLanguage::Rust));
+
+        let text = strategy.render_preamble(language, true);
+        assert!(text.starts_with("The user is working in a code editor"));
+        assert!(text.contains("appears to be Rust"));
+        assert!(text.contains("```rust"));
+        assert!(text.contains("If the user asks for tests"));
+    }
+
+    #[test]
+    fn preamble_without_screenshot_skips_transcription_hint() {
+        let strategy =
+            CodeStrategy::new(window(CodeApp::SystemTerminal.process_name(), None)).unwrap();
+        let text = strategy.render_preamble(None, false);
+        assert!(text.starts_with("The user is working in a terminal"));
+        assert!(!text.contains("screenshot"));
+    }
+}
@@ -10,11 +10,12 @@ use std::sync::Arc;
 use auth_core::{
     AppleIdTokenLoginRequest, AppleNativeUser, AssociateLoginTokenRequest, AuthErrorResponse,
     CheckEmailRequest, CheckEmailResponse, GoogleIdTokenLoginRequest, LoginByLoginTokenRequest,
-    LoginRequest, MobileThirdPartyAuthUrlRequest, RegisterRequest, ThirdPartyAuthUrlRequest,
-    ThirdPartyAuthUrlResponse, TokenResponse, VerifyEmailRequest,
+    LoginRequest, LoopbackThirdPartyAuthUrlRequest, MobileThirdPartyAuthUrlRequest,
+    RegisterRequest, ThirdPartyAuthUrlRequest, ThirdPartyAuthUrlResponse, TokenResponse,
+    VerifyEmailRequest,
 };
 use euro_endpoint::EndpointManager;
-use reqwest::{RequestBuilder, Response};
+use reqwest::{RequestBuilder, Response, StatusCode};
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 
@@ -55,6 +56,23 @@ impl AuthClient {
         self.post_json("/auth/login", &body, None).await
     }
 
+    /// Finish a third-party sign-in the desktop received on its own
+    /// loopback redirect, the same call the SPA makes from its callback
+    /// page.
+    pub async fn login_third_party(
+        &self,
+        provider: auth_core::Provider,
+        code: impl Into<String>,
+        state: impl Into<String>,
+    ) -> AuthResult<TokenResponse> {
+        let body = LoginRequest::ThirdParty {
+            provider,
+            code: code.into(),
+            state: state.into(),
+        };
+        self.post_json("/auth/login", &body, None).await
+    }
+
     pub async fn register(
         &self,
         email: impl Into<String>,
@@ -88,6 +106,28 @@ impl AuthClient {
             .await
     }
 
+    /// Long-poll form of [`Self::login_by_login_token`]: the backend
+    /// holds the request until the web sign-in associates the challenge.
+    /// `Ok(None)` means the hold elapsed first; call again.
+    pub async fn wait_for_login_token(
+        &self,
+        login_token: impl Into<String>,
+    ) -> AuthResult<Option<TokenResponse>> {
+        let body = LoginByLoginTokenRequest {
+            token: login_token.into(),
+        };
+        let response = self
+            .request("/auth/login-token/wait", None)
+            .json(&body)
+            .send()
+            .await
+            .map_err(AuthError::from_transport)?;
+        if response.status() == StatusCode::REQUEST_TIMEOUT {
+            return Ok(None);
+        }
+        decode_typed(response).await.map(Some)
+    }
+
     pub async fn associate_login_token(
         &self,
         access_token: impl AsRef<str>,
@@ -154,6 +194,22 @@ impl AuthClient {
         self.post_json("/auth/oauth/mobile/url", &body, None).await
     }
 
+    /// Desktop loopback OAuth start: get a provider-authorisation URL
+    /// that redirects to `redirect_uri` on this machine instead of the
+    /// hosted web app.
+    pub async fn loopback_third_party_auth_url(
+        &self,
+        provider: auth_core::Provider,
+        redirect_uri: impl Into<String>,
+    ) -> AuthResult<ThirdPartyAuthUrlResponse> {
+        let body = LoopbackThirdPartyAuthUrlRequest {
+            provider,
+            redirect_uri: redirect_uri.into(),
+        };
+        self.post_json("/auth/oauth/loopback/url", &body, None)
+            .await
+    }
+
     /// Native Google sign-in: trade an iOS / Android-issued ID token
     /// for a session token pair.
     pub async fn login_by_google_id_token(
@@ -211,6 +267,10 @@ impl AuthClient {
 
 async fn send_typed<R: DeserializeOwned>(builder: RequestBuilder) -> AuthResult<R> {
     let response = builder.send().await.map_err(AuthError::from_transport)?;
+    decode_typed(response).await
+}
+
+async fn decode_typed<R: DeserializeOwned>(response: Response) -> AuthResult<R> {
     let status = response.status();
     let url = response.url().to_string();
     if !status.is_success() {
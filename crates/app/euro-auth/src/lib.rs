@@ -1,6 +1,7 @@
 mod client;
 mod error;
 mod events;
+mod loopback;
 mod manager;
 mod secret_store;
 
@@ -11,4 +12,5 @@ pub use auth_core::*;
 pub use client::*;
 pub use error::{AuthError, AuthResult};
 pub use events::AuthEvent;
+pub use loopback::LoopbackListener;
 pub use manager::*;
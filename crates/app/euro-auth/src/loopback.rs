@@ -0,0 +1,174 @@
+//! Loopback redirect receiver for desktop OAuth (RFC 8252 §7.3).
+//!
+//! Self-hosted deployments may not run the web app, so the hosted login
+//! page the desktop normally opens isn't there. Instead the desktop
+//! listens on `127.0.0.1`, asks the backend for a provider URL that
+//! redirects back to that port, and reads `code` / `state` off the one
+//! request the browser makes. The rest is the ordinary third-party
+//! login: the code goes to `/auth/login` exactly as the SPA would send it.
+
+use anyhow::anyhow;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use url::Url;
+
+use crate::error::{AuthError, AuthResult};
+
+const CALLBACK_PATH: &str = "/callback";
+/// The browser's request head is a few hundred bytes; anything past
+/// this is not a redirect we issued.
+const MAX_REQUEST_HEAD: usize = 8 * 1024;
+
+const DONE_PAGE: &str = "<!doctype html><meta charset=\"utf-8\"><title>Eurora</title>\
+<p>Sign-in finished. You can close this tab and return to Eurora.</p>";
+
+/// A bound loopback port waiting for the provider's redirect.
+#[derive(Debug)]
+pub struct LoopbackListener {
+    listener: TcpListener,
+    redirect_uri: String,
+}
+
+/// `code` and `state` as the provider handed them back.
+#[derive(Debug)]
+pub(crate) struct LoopbackCallback {
+    pub(crate) code: String,
+    pub(crate) state: String,
+}
+
+impl LoopbackListener {
+    /// Bind `127.0.0.1:{port}`. Pass `0` to let the OS pick a free port,
+    /// which suits every provider that accepts any loopback port
+    /// (Google, Microsoft, GitHub); pin one only if the OAuth app was
+    /// registered with a fixed port.
+    pub async fn bind(port: u16) -> AuthResult<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", port))
+            .await
+            .map_err(|e| AuthError::Other(anyhow!("failed to bind loopback port {port}: {e}")))?;
+        let port = listener
+            .local_addr()
+            .map_err(|e| AuthError::Other(anyhow::Error::new(e)))?
+            .port();
+        Ok(Self {
+            listener,
+            redirect_uri: format!("http://127.0.0.1:{port}{CALLBACK_PATH}"),
+        })
+    }
+
+    /// The redirect URI to register the sign-in with.
+    #[must_use]
+    pub fn redirect_uri(&self) -> &str {
+        &self.redirect_uri
+    }
+
+    /// Serve until the browser lands on the callback path. Requests for
+    /// anything else (the browser's `/favicon.ico`, say) get a 404 and
+    /// the wait goes on.
+    pub(crate) async fn wait_for_callback(self) -> AuthResult<LoopbackCallback> {
+        loop {
+            let (mut stream, _) = self
+                .listener
+                .accept()
+                .await
+                .map_err(|e| AuthError::Other(anyhow::Error::new(e)))?;
+            let Some(target) = read_request_target(&mut stream).await else {
+                continue;
+            };
+            match parse_callback(&target) {
+                None => respond(&mut stream, "404 Not Found", "").await,
+                Some(result) => {
+                    respond(&mut stream, "200 OK", DONE_PAGE).await;
+                    return result;
+                }
+            }
+        }
+    }
+}
+
+/// Read the request head and return the target of a `GET` request line.
+async fn read_request_target(stream: &mut TcpStream) -> Option<String> {
+    let mut head = Vec::with_capacity(1024);
+    let mut buf = [0u8; 1024];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        if head.len() >= MAX_REQUEST_HEAD {
+            return None;
+        }
+        let n = stream.read(&mut buf).await.ok()?;
+        if n == 0 {
+            break;
+        }
+        head.extend_from_slice(&buf[..n]);
+    }
+    let head = std::str::from_utf8(&head).ok()?;
+    let mut parts = head.lines().next()?.split(' ');
+    match (parts.next(), parts.next()) {
+        (Some("GET"), Some(target)) => Some(target.to_owned()),
+        _ => None,
+    }
+}
+
+/// `None` when `target` isn't the callback path. Otherwise the
+/// provider's `code` / `state`, or its `error` if the user declined.
+fn parse_callback(target: &str) -> Option<AuthResult<LoopbackCallback>> {
+    let url = Url::parse(&format!("http://127.0.0.1{target}")).ok()?;
+    if url.path() != CALLBACK_PATH {
+        return None;
+    }
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+    };
+    if let Some(error) = param("error") {
+        let description = param("error_description").unwrap_or_default();
+        return Some(Err(AuthError::Other(anyhow!(
+            "sign-in was not completed: {error} {description}"
+        ))));
+    }
+    Some(match (param("code"), param("state")) {
+        (Some(code), Some(state)) => Ok(LoopbackCallback { code, state }),
+        _ => Err(AuthError::Other(anyhow!(
+            "sign-in redirect is missing `code` or `state`"
+        ))),
+    })
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, body: &str) {
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/html; charset=utf-8\r\n\
+         Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    // The browser going away before reading the page is harmless.
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn callback_yields_code_and_state() {
+        let callback = parse_callback("/callback?code=abc&state=s%201")
+            .unwrap()
+            .unwrap();
+        assert_eq!(callback.code, "abc");
+        assert_eq!(callback.state, "s 1");
+    }
+
+    #[test]
+    fn other_paths_are_not_the_callback() {
+        assert!(parse_callback("/favicon.ico").is_none());
+    }
+
+    #[test]
+    fn provider_error_and_missing_params_fail() {
+        assert!(
+            parse_callback("/callback?error=access_denied&state=s")
+                .unwrap()
+                .is_err()
+        );
+        assert!(parse_callback("/callback?code=abc").unwrap().is_err());
+    }
+}
@@ -1,6 +1,7 @@
 use crate::client::AuthClient;
 use crate::error::{AuthError, AuthResult};
 use crate::events::AuthEvent;
+use crate::loopback::LoopbackListener;
 use crate::secret_store::SecretStore;
 use anyhow::Result;
 use auth_core::{Claims, TokenResponse};
@@ -432,6 +433,27 @@ impl AuthManager {
         Ok(claims)
     }
 
+    /// Long-poll form of [`AuthManager::complete_login`]: the backend
+    /// holds the request until the web sign-in finishes, so the session
+    /// arrives as soon as it exists. `Ok(None)` means the hold elapsed
+    /// first and the caller should simply call again.
+    pub async fn wait_for_login(&self) -> AuthResult<Option<Claims>> {
+        let verifier = self
+            .secret_store
+            .pkce_verifier()?
+            .ok_or(AuthError::LoginChallengeExpired)?;
+        let Some(response) = self
+            .auth_client
+            .wait_for_login_token(verifier.expose_secret().to_owned())
+            .await?
+        else {
+            return Ok(None);
+        };
+        let claims = self.complete_session(response)?;
+        self.secret_store.clear_pkce_verifier()?;
+        Ok(Some(claims))
+    }
+
     pub async fn resend_verification_email(&self) -> Result<()> {
         let access_token = self
             .secret_store
@@ -443,6 +465,46 @@ impl AuthManager {
             .map_err(anyhow::Error::from)
     }
 
+    /// Sign in with a third-party provider without the hosted web app:
+    /// the provider redirects straight back to `listener`.
+    ///
+    /// `open_url` receives the provider URL to show the user (open a
+    /// browser, print it). Resolves once the browser lands on the
+    /// loopback port; callers bound the wait themselves.
+    pub async fn login_via_loopback(
+        &self,
+        provider: auth_core::Provider,
+        listener: LoopbackListener,
+        open_url: impl FnOnce(&str),
+    ) -> AuthResult<Claims> {
+        let url = self
+            .auth_client
+            .loopback_third_party_auth_url(provider, listener.redirect_uri())
+            .await?
+            .url;
+        let expected_state = url::Url::parse(&url)
+            .ok()
+            .and_then(|url| {
+                url.query_pairs()
+                    .find(|(key, _)| key == "state")
+                    .map(|(_, value)| value.into_owned())
+            })
+            .ok_or_else(|| anyhow::anyhow!("sign-in URL carries no `state`"))?;
+        open_url(&url);
+
+        let callback = listener.wait_for_callback().await?;
+        // Anything on this machine can hit the port; only the redirect
+        // for the URL we just handed out may complete the sign-in.
+        if callback.state != expected_state {
+            return Err(anyhow::anyhow!("sign-in redirect does not match this attempt").into());
+        }
+        let response = self
+            .auth_client
+            .login_third_party(provider, callback.code, callback.state)
+            .await?;
+        Ok(self.complete_session(response)?)
+    }
+
     /// Build a provider-authorisation URL for the mobile in-app browser
     /// flow. The backend stamps the supplied `code_challenge` as the
     /// OAuth `state`, so the same value identifies the device when the
@@ -486,6 +548,9 @@ mod tests {
             aud: String::new(),
             email_verified: true,
             jti: String::new(),
+            analytics: Default::default(),
+            token_version: 0,
+            act: None,
         }
     }
 
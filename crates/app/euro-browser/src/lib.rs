@@ -45,7 +45,9 @@ pub fn type_collection() -> specta::Types {
     let mut types = euro_bridge_protocol::type_collection();
     types
         .register_mut::<NativeMessage>()
-        .register_mut::<NativeMetadata>();
+        .register_mut::<NativeMetadata>()
+        .register_mut::<NativeWebpageState>()
+        .register_mut::<NativeEmailThread>();
     // Per-tool argument/return types now live entirely in the extension's
     // TypeScript (`apps/browser/src/shared/background/observers/*`) and
     // are surfaced to the desktop via `WireToolDescriptor` schemas in
@@ -85,6 +87,10 @@ mod tests {
             // native-messaging payloads
             "NativeMessage",
             "NativeMetadata",
+            "NativeWebpageState",
+            "NativeEmailThread",
+            "NativeEmailMessage",
+            "EmailProvider",
         ] {
             assert!(
                 names.iter().any(|n| n == required),
@@ -1,21 +1,29 @@
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
+mod email;
 mod metadata;
+mod webpage;
 
+pub use email::*;
 pub use metadata::*;
+pub use webpage::*;
 
 /// Envelope for every payload the browser native-messaging host
 /// exchanges with the desktop bridge. Externally tagged on `kind` with
 /// the inner payload under `data` so the JSON shape stays stable as
 /// new wire-payload variants are added.
 ///
-/// At present only [`NativeMetadata`] crosses the bridge — page content
-/// is delivered through granular adapter tools (`browser_web_*`,
-/// `browser_youtube_*`, …) rather than through pre-bundled assets or
-/// snapshots.
+/// Page content is primarily delivered through granular adapter tools
+/// (`browser_web_*`, `browser_youtube_*`, …). The snapshot payloads are
+/// the exceptions: [`NativeWebpageState`] is a generic fallback for
+/// pages no per-site watcher understands, and [`NativeEmailThread`]
+/// carries the open webmail thread so the desktop can apply its own
+/// redaction before anything reaches the LLM.
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 #[serde(tag = "kind", content = "data")]
 pub enum NativeMessage {
     NativeMetadata(NativeMetadata),
+    NativeWebpageState(NativeWebpageState),
+    NativeEmailThread(NativeEmailThread),
 }
@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// Webmail client the extension scraped an [`NativeEmailThread`] from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum EmailProvider {
+    Gmail,
+    Outlook,
+}
+
+impl EmailProvider {
+    /// Identify the webmail client serving `host`, if any.
+    pub fn from_host(host: &str) -> Option<Self> {
+        match host {
+            "mail.google.com" => Some(Self::Gmail),
+            "outlook.live.com" | "outlook.office.com" | "outlook.office365.com" => {
+                Some(Self::Outlook)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn display_name(self) -> &'static str {
+        match self {
+            Self::Gmail => "Gmail",
+            Self::Outlook => "Outlook",
+        }
+    }
+}
+
+/// One message of an open email thread, as rendered in the webmail UI.
+///
+/// Only messages the user has expanded are included — collapsed
+/// messages in Gmail/Outlook have no body in the DOM and the extension
+/// never expands them on the user's behalf.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct NativeEmailMessage {
+    pub sender_name: Option<String>,
+    pub sender_address: Option<String>,
+    #[serde(default)]
+    pub recipients: Vec<String>,
+    /// Timestamp as displayed by the client. Left as a string because
+    /// webmail UIs render localized, relative dates ("Yesterday, 9:14").
+    pub sent_at: Option<String>,
+    /// Visible plain-text body, quoted replies included.
+    pub body: String,
+}
+
+/// Snapshot of the email thread open in the focused webmail tab,
+/// answered by the Gmail/Outlook site bundles for the
+/// `GET_EMAIL_THREAD` bridge action.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct NativeEmailThread {
+    /// Same tab id contract as [`crate::NativeMetadata::tab_id`].
+    pub tab_id: i32,
+    pub provider: EmailProvider,
+    pub subject: Option<String>,
+    /// Messages in display order (oldest first).
+    pub messages: Vec<NativeEmailMessage>,
+}
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// Raw snapshot of an arbitrary web page, answered by the `_common`
+/// content script for the `GET_PAGE_STATE` bridge action.
+///
+/// Used as the fallback context source when the focused tab has no
+/// per-site watcher (or the watcher returned no context blocks). The
+/// extension ships the page verbatim; boilerplate removal and
+/// main-content detection happen desktop-side so the heuristics can be
+/// tuned without an extension release.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+pub struct NativeWebpageState {
+    /// Same tab id contract as [`crate::NativeMetadata::tab_id`].
+    pub tab_id: i32,
+    pub url: String,
+    pub title: Option<String>,
+    /// Serialized `document.documentElement.outerHTML`, truncated by the
+    /// extension to stay under [`crate::MAX_FRAME_SIZE`].
+    pub html: String,
+    /// `<html lang>` attribute, when present.
+    pub lang: Option<String>,
+    /// `<meta name="description">` or `og:description`.
+    pub description: Option<String>,
+    /// `og:site_name`, when present.
+    pub site_name: Option<String>,
+    /// `<meta name="author">`, when present.
+    pub byline: Option<String>,
+}
@@ -0,0 +1,211 @@
+//! `eur ask` — one chat turn from the command line.
+//!
+//! The turn runs through the same [`ChatBridge`] the desktop app uses,
+//! but with a [`NoTools`] backend: there is no activity to inspect from a
+//! terminal, so the LLM only sees the question and whatever the caller
+//! attached.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use agent_chain_core::messages::{AnyMessage, ContentBlock, ImageContentBlock, TextContentBlock};
+use anyhow::{Context, Result, anyhow, bail};
+use async_trait::async_trait;
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64_STANDARD};
+use clap::Args;
+use euro_thread::{ChatBridge, ChatSendRequest, ChatServerMessage, ChatSinkError, TurnOpening};
+use euro_transport_policy::CHAT_STREAM_TIMEOUT;
+use serde_json::Value;
+use thread_core::{ToolBackend, ToolBackendCall, ToolErrorWire, WireToolDescriptor};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use crate::Session;
+
+/// Largest text file inlined into the question, in bytes.
+const MAX_TEXT_ATTACHMENT_BYTES: usize = 256 * 1024;
+
+#[derive(Debug, Args)]
+pub(crate) struct AskArgs {
+    /// The question to ask.
+    question: String,
+
+    /// Continue an existing thread instead of starting a new one.
+    #[arg(long)]
+    thread: Option<Uuid>,
+
+    /// Attach a file: images are sent as images, anything else is
+    /// inlined as text.
+    #[arg(long, short)]
+    file: Option<PathBuf>,
+
+    /// Attach a screenshot of the primary monitor.
+    #[arg(long)]
+    screenshot: bool,
+}
+
+pub(crate) async fn run(session: &Session, args: AskArgs) -> Result<()> {
+    let question = args.question.trim();
+    if question.is_empty() {
+        bail!("question must not be empty");
+    }
+
+    let mut content_blocks = vec![ContentBlock::Text(
+        TextContentBlock::builder()
+            .text(question.to_string())
+            .build(),
+    )];
+    if let Some(path) = &args.file {
+        content_blocks.push(file_block(path).await?);
+    }
+    if args.screenshot {
+        let capture = euro_vision::capture::capture_primary_monitor()
+            .await?
+            .ok_or_else(|| anyhow!("no monitor available to capture"))?;
+        content_blocks.push(image_block(capture.png_base64, "image/png")?);
+    }
+
+    let thread_id = match args.thread {
+        Some(id) => id,
+        None => session.thread_manager.create(None).await?.id,
+    };
+
+    let cancel = CancellationToken::new();
+    let socket = session
+        .thread_manager
+        .open_chat_socket(thread_id, cancel.clone())
+        .await?;
+    let opening = TurnOpening::Send(ChatSendRequest {
+        content_blocks,
+        parent_message_id: None,
+        asset_chips_json: None,
+        activity_id: None,
+        request_id: None,
+    });
+
+    // Only the terminal frames matter here: `Final` carries the
+    // persisted AI message, `Error` the reason the turn aborted.
+    let outcome = std::sync::Mutex::new(None);
+    let sink = |event: ChatServerMessage| -> Result<(), ChatSinkError> {
+        match event {
+            ChatServerMessage::Final { messages } => {
+                *outcome.lock().map_err(|e| ChatSinkError(e.to_string()))? = Some(Ok(messages));
+            }
+            ChatServerMessage::Error { message, .. } => {
+                *outcome.lock().map_err(|e| ChatSinkError(e.to_string()))? = Some(Err(message));
+            }
+            _ => {}
+        }
+        Ok(())
+    };
+
+    let bridge = ChatBridge::new(Arc::new(NoTools));
+    let turn = bridge.run_turn(socket, opening, cancel.clone(), &sink);
+    match tokio::time::timeout(CHAT_STREAM_TIMEOUT, turn).await {
+        Ok(result) => result?,
+        Err(_) => {
+            cancel.cancel();
+            bail!(
+                "chat turn timed out after {}s",
+                CHAT_STREAM_TIMEOUT.as_secs()
+            );
+        }
+    }
+
+    let messages = outcome
+        .into_inner()
+        .map_err(|e| anyhow!(e.to_string()))?
+        .ok_or_else(|| anyhow!("turn ended without a final frame"))?
+        .map_err(|message| anyhow!("chat turn failed: {message}"))?;
+    let answer = messages
+        .iter()
+        .rev()
+        .find_map(|node| match &node.message {
+            AnyMessage::AIMessage(message) => Some(message.text()),
+            _ => None,
+        })
+        .unwrap_or_default();
+
+    println!("{answer}");
+    eprintln!("\nthread: {thread_id}");
+    Ok(())
+}
+
+async fn file_block(path: &Path) -> Result<ContentBlock> {
+    let bytes = tokio::fs::read(path)
+        .await
+        .with_context(|| format!("reading {}", path.display()))?;
+
+    if let Some(mime_type) = image_mime_type(path) {
+        return image_block(BASE64_STANDARD.encode(&bytes), mime_type);
+    }
+
+    if bytes.len() > MAX_TEXT_ATTACHMENT_BYTES {
+        bail!(
+            "{} is larger than {} KiB; upload it with `eur assets upload` instead",
+            path.display(),
+            MAX_TEXT_ATTACHMENT_BYTES / 1024
+        );
+    }
+    let text = String::from_utf8(bytes)
+        .with_context(|| format!("{} is neither an image nor UTF-8 text", path.display()))?;
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    Ok(ContentBlock::Text(
+        TextContentBlock::builder()
+            .text(format!("Attached file `{name}`:\n\n```\n{text}\n```"))
+            .build(),
+    ))
+}
+
+fn image_block(base64: String, mime_type: &str) -> Result<ContentBlock> {
+    let image = ImageContentBlock::builder()
+        .base64(base64)
+        .mime_type(mime_type.to_string())
+        .build()
+        .map_err(|e| anyhow!("image rejected: {e}"))?;
+    Ok(ContentBlock::Image(image))
+}
+
+pub(crate) fn image_mime_type(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    match ext.as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        _ => None,
+    }
+}
+
+/// Tool backend with an empty tool surface.
+struct NoTools;
+
+#[async_trait]
+impl ToolBackend for NoTools {
+    async fn list_tools(&self) -> Vec<WireToolDescriptor> {
+        Vec::new()
+    }
+
+    async fn dispatch(&self, call: ToolBackendCall) -> Result<Value, ToolErrorWire> {
+        Err(ToolErrorWire::ContextUnavailable {
+            tool: call.name,
+            reason: "no tools are available from the command line".to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn image_mime_type_is_case_insensitive() {
+        assert_eq!(image_mime_type(Path::new("a/shot.PNG")), Some("image/png"));
+        assert_eq!(image_mime_type(Path::new("photo.jpeg")), Some("image/jpeg"));
+        assert_eq!(image_mime_type(Path::new("notes.md")), None);
+        assert_eq!(image_mime_type(Path::new("Makefile")), None);
+    }
+}
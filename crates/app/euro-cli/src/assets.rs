@@ -0,0 +1,122 @@
+//! `eur assets` — raw upload / download against the asset service.
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result, bail};
+use asset_core::{Asset, CreateAssetRequest};
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64_STANDARD};
+use clap::Subcommand;
+use reqwest::StatusCode;
+use secrecy::ExposeSecret;
+use uuid::Uuid;
+
+use crate::Session;
+use crate::ask::image_mime_type;
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum AssetsCommand {
+    /// Upload a file and print the created asset as JSON.
+    Upload {
+        path: PathBuf,
+        /// Override the MIME type guessed from the file extension.
+        #[arg(long)]
+        mime_type: Option<String>,
+    },
+    /// Download an asset's bytes.
+    Download {
+        asset_id: Uuid,
+        /// Destination file.
+        #[arg(long, short)]
+        output: PathBuf,
+    },
+}
+
+pub(crate) async fn run(session: &Session, command: AssetsCommand) -> Result<()> {
+    match command {
+        AssetsCommand::Upload { path, mime_type } => {
+            let bytes = tokio::fs::read(&path)
+                .await
+                .with_context(|| format!("reading {}", path.display()))?;
+            let request = CreateAssetRequest {
+                name: path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| "upload".to_string()),
+                content: BASE64_STANDARD.encode(&bytes),
+                mime_type: mime_type.unwrap_or_else(|| {
+                    image_mime_type(&path)
+                        .unwrap_or("application/octet-stream")
+                        .to_string()
+                }),
+                metadata: None,
+                device_id: None,
+            };
+
+            let token = session.auth_manager.get_or_refresh_access_token().await?;
+            let response = session
+                .endpoint_manager
+                .client()
+                .post(session.endpoint_manager.url("/v1/assets"))
+                .bearer_auth(token.expose_secret())
+                .json(&request)
+                .send()
+                .await?;
+            let status = response.status();
+            if !status.is_success() {
+                bail!("upload failed ({status}): {}", response.text().await?);
+            }
+            let asset: Asset = response.json().await?;
+            println!("{}", serde_json::to_string_pretty(&asset)?);
+            Ok(())
+        }
+        AssetsCommand::Download { asset_id, output } => {
+            let Some(asset) = fetch_asset(session, asset_id).await? else {
+                bail!("asset {asset_id} not found");
+            };
+            tokio::fs::write(&output, &asset.bytes)
+                .await
+                .with_context(|| format!("writing {}", output.display()))?;
+            eprintln!("Wrote {} bytes to {}.", asset.bytes.len(), output.display());
+            Ok(())
+        }
+    }
+}
+
+/// Raw asset content as served by `GET /v1/assets/{id}`.
+pub(crate) struct AssetBytes {
+    pub bytes: Vec<u8>,
+    pub mime_type: String,
+}
+
+/// Fetch an asset's bytes. `None` is a clean 404: the backend answers the
+/// same for missing assets and ones owned by someone else.
+pub(crate) async fn fetch_asset(session: &Session, asset_id: Uuid) -> Result<Option<AssetBytes>> {
+    let token = session.auth_manager.get_or_refresh_access_token().await?;
+    let response = session
+        .endpoint_manager
+        .client()
+        .get(
+            session
+                .endpoint_manager
+                .url(&format!("/v1/assets/{asset_id}")),
+        )
+        .bearer_auth(token.expose_secret())
+        .send()
+        .await?;
+    let status = response.status();
+    if status == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !status.is_success() {
+        bail!("download failed ({status}): {}", response.text().await?);
+    }
+
+    let mime_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or("application/octet-stream")
+        .to_string();
+    let bytes = response.bytes().await?.to_vec();
+    Ok(Some(AssetBytes { bytes, mime_type }))
+}
@@ -0,0 +1,37 @@
+//! `eur db` — maintenance for the desktop app's local database.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use clap::Subcommand;
+use euro_personal_db::DB_FILE_NAME;
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum DbCommand {
+    /// Check the personal database for corruption and migration drift.
+    /// Read-only; exits non-zero when problems are found.
+    Doctor {
+        /// Database file. Defaults to the one in the data directory.
+        #[arg(long)]
+        path: Option<PathBuf>,
+    },
+}
+
+pub(crate) async fn run(data_dir: &Path, command: &DbCommand) -> Result<()> {
+    match command {
+        DbCommand::Doctor { path } => {
+            let path = path.clone().unwrap_or_else(|| data_dir.join(DB_FILE_NAME));
+            let report = euro_personal_db::doctor(&path)
+                .await
+                .with_context(|| format!("checking {}", path.display()))?;
+            println!("{report}");
+            if !report.is_healthy() {
+                bail!(
+                    "the personal database needs attention; quit the desktop app and restore a \
+                     backup listed above, or move the file aside to start fresh"
+                );
+            }
+            Ok(())
+        }
+    }
+}
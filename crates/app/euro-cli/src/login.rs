@@ -0,0 +1,122 @@
+//! `eur login` — the desktop app's PKCE login-token flow, headless.
+//!
+//! [`AuthManager::begin_login`] stores a verifier in the secret store and
+//! hands back its challenge; the user finishes sign-in in a browser at
+//! the printed URL, which associates the challenge with their account.
+//! Meanwhile [`AuthManager::wait_for_login`] holds a request open until
+//! the backend accepts the verifier, re-issuing it each time the
+//! backend's hold elapses, until the challenge lapses.
+//!
+//! With `--provider`, sign-in skips the hosted page entirely (for
+//! self-hosted backends without the web app): we listen on a loopback
+//! port and [`AuthManager::login_via_loopback`] has the provider
+//! redirect straight back to it.
+//!
+//! [`AuthManager::begin_login`]: euro_auth::AuthManager::begin_login
+//! [`AuthManager::wait_for_login`]: euro_auth::AuthManager::wait_for_login
+//! [`AuthManager::login_via_loopback`]: euro_auth::AuthManager::login_via_loopback
+
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, bail};
+use clap::{Args, ValueEnum};
+use euro_auth::{LoopbackListener, Provider};
+use url::Url;
+
+use crate::Session;
+
+const DEFAULT_WEB_URL: &str = "https://www.eurora-labs.com";
+/// Backoff after a failed wait (network, server error), doubling up to
+/// [`MAX_RETRY_DELAY`]. A wait that simply elapsed is re-issued at once.
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+/// Matches how long the backend keeps the OAuth state row alive.
+const LOOPBACK_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Args)]
+pub(crate) struct LoginArgs {
+    /// Web app hosting the sign-in page.
+    #[arg(long, env = "WEB_URL", default_value = DEFAULT_WEB_URL)]
+    web_url: String,
+    /// Sign in directly with this provider, redirecting to a local port
+    /// instead of the web app's sign-in page.
+    #[arg(long, value_enum)]
+    provider: Option<LoopbackProvider>,
+    /// Local port for the `--provider` redirect; 0 picks a free one.
+    #[arg(long, default_value_t = 0, requires = "provider")]
+    loopback_port: u16,
+}
+
+/// Providers that can redirect to a loopback address. Apple only
+/// redirects to registered HTTPS URLs, so it isn't offered.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub(crate) enum LoopbackProvider {
+    Google,
+    Github,
+    Microsoft,
+}
+
+impl From<LoopbackProvider> for Provider {
+    fn from(provider: LoopbackProvider) -> Self {
+        match provider {
+            LoopbackProvider::Google => Provider::Google,
+            LoopbackProvider::Github => Provider::Github,
+            LoopbackProvider::Microsoft => Provider::Microsoft,
+        }
+    }
+}
+
+pub(crate) async fn run(session: &Session, args: LoginArgs) -> Result<()> {
+    if let Some(provider) = args.provider {
+        return run_loopback(session, provider.into(), args.loopback_port).await;
+    }
+    let auth = &session.auth_manager;
+    let challenge = auth.begin_login()?;
+
+    let mut url = Url::parse(&format!("{}/login", args.web_url.trim_end_matches('/')))
+        .context("invalid --web-url")?;
+    url.query_pairs_mut()
+        .append_pair("code_challenge", &challenge.code_challenge)
+        .append_pair("code_challenge_method", "S256");
+
+    eprintln!("Open this URL in a browser to sign in:\n\n  {url}\n");
+    eprintln!("Waiting for sign-in to complete...");
+
+    let deadline = Instant::now() + Duration::from_secs(u64::from(challenge.expires_in));
+    let mut retry_delay = RETRY_DELAY;
+    loop {
+        match auth.wait_for_login().await {
+            Ok(Some(claims)) => {
+                eprintln!("Signed in as {}.", claims.email);
+                return Ok(());
+            }
+            Ok(None) => retry_delay = RETRY_DELAY,
+            Err(euro_auth::AuthError::LoginChallengeExpired) => {
+                bail!("login challenge expired; run `eur login` again")
+            }
+            Err(err) => {
+                tracing::debug!("waiting for sign-in failed: {err}");
+                tokio::time::sleep(retry_delay).await;
+                retry_delay = (retry_delay * 2).min(MAX_RETRY_DELAY);
+            }
+        }
+        if Instant::now() >= deadline {
+            bail!("timed out waiting for sign-in; run `eur login` again");
+        }
+    }
+}
+
+async fn run_loopback(session: &Session, provider: Provider, port: u16) -> Result<()> {
+    let listener = LoopbackListener::bind(port).await?;
+    let login = session
+        .auth_manager
+        .login_via_loopback(provider, listener, |url| {
+            eprintln!("Open this URL in a browser to sign in:\n\n  {url}\n");
+            eprintln!("Waiting for sign-in to complete...");
+        });
+    let claims = tokio::time::timeout(LOOPBACK_TIMEOUT, login)
+        .await
+        .map_err(|_| anyhow::anyhow!("timed out waiting for sign-in; run `eur login` again"))??;
+    eprintln!("Signed in as {}.", claims.email);
+    Ok(())
+}
@@ -0,0 +1,157 @@
+//! `eur` — headless command-line client for the Eurora backend.
+//!
+//! Reuses the desktop app's client crates end to end: [`euro_settings`]
+//! resolves the API endpoint, [`euro_auth::AuthManager`] owns the session
+//! (stored in the same encrypted secret store the desktop app uses, so
+//! signing in once covers both), and [`euro_thread`] drives chat turns
+//! over the same WebSocket bridge.
+//!
+//! Subcommands:
+//!
+//! - `login` — PKCE login-token flow: prints a browser URL, then waits
+//!   until the sign-in completes there. `--provider` signs in through a
+//!   loopback redirect instead, for backends without the web app.
+//! - `ask` — one-shot question, optionally with a file or a screenshot of
+//!   the primary monitor attached.
+//! - `threads list` / `threads export` — browse and dump chat history.
+//! - `assets upload` / `assets download` — move raw bytes in and out of
+//!   the asset service.
+//! - `mcp` — Model Context Protocol server on stdio, so external agents
+//!   can use Eurora as a context source.
+//! - `db doctor` — read-only health check of the desktop app's local
+//!   database. Needs no session or network.
+
+mod ask;
+mod assets;
+mod db;
+mod login;
+mod mcp;
+mod threads;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use euro_auth::AuthManager;
+use euro_endpoint::EndpointManager;
+use euro_settings::SettingsState;
+use euro_thread::ThreadManager;
+use tracing_subscriber::EnvFilter;
+
+/// Tauri bundle identifier of the release desktop build. Its app-data
+/// directory holds the secret store, so defaulting to it lets the CLI
+/// pick up an existing desktop session.
+const DESKTOP_IDENTIFIER: &str = "com.eurora-labs.eurora";
+
+#[derive(Debug, Parser)]
+#[command(name = "eur", version, about = "Headless Eurora client")]
+struct Cli {
+    /// Directory holding the encrypted session store and the local
+    /// database. Defaults to the release desktop app's data directory.
+    #[arg(long, env = "EURORA_DATA_DIR", global = true)]
+    data_dir: Option<PathBuf>,
+
+    /// API base URL. Defaults to the endpoint configured in the desktop
+    /// app's `local.json`.
+    #[arg(long, env = "EURORA_API_URL", global = true)]
+    api_url: Option<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Sign in through the browser and store the session locally.
+    Login(login::LoginArgs),
+    /// Drop the stored session.
+    Logout,
+    /// Ask a one-shot question and print the answer.
+    Ask(ask::AskArgs),
+    /// Browse and export chat threads.
+    #[command(subcommand)]
+    Threads(threads::ThreadsCommand),
+    /// Upload and download raw assets.
+    #[command(subcommand)]
+    Assets(assets::AssetsCommand),
+    /// Serve Eurora context and tools over MCP on stdin / stdout.
+    Mcp,
+    /// Maintain the desktop app's local database.
+    #[command(subcommand)]
+    Db(db::DbCommand),
+}
+
+impl Cli {
+    fn data_dir(&self) -> Result<PathBuf> {
+        match &self.data_dir {
+            Some(dir) => Ok(dir.clone()),
+            None => Ok(dirs::data_dir()
+                .context("no platform data dir")?
+                .join(DESKTOP_IDENTIFIER)),
+        }
+    }
+}
+
+/// Shared clients for one CLI invocation.
+pub(crate) struct Session {
+    pub endpoint_manager: Arc<EndpointManager>,
+    pub auth_manager: AuthManager,
+    pub thread_manager: ThreadManager,
+}
+
+impl Session {
+    fn open(cli: &Cli) -> Result<Self> {
+        let endpoint_url = match &cli.api_url {
+            Some(url) => url.clone(),
+            None => SettingsState::load_or_migrate_from_default_path()?
+                .local
+                .api
+                .endpoint()
+                .to_string(),
+        };
+        let data_dir = cli.data_dir()?;
+
+        let endpoint_manager = Arc::new(EndpointManager::new(&endpoint_url)?);
+        let auth_manager = AuthManager::new(endpoint_manager.clone(), &data_dir)
+            .with_context(|| format!("opening session store in {}", data_dir.display()))?;
+        let thread_manager = ThreadManager::new(endpoint_manager.clone(), auth_manager.clone());
+        Ok(Self {
+            endpoint_manager,
+            auth_manager,
+            thread_manager,
+        })
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn")),
+        )
+        .with_writer(std::io::stderr)
+        .init();
+
+    let cli = Cli::parse();
+    // Local maintenance runs before the session opens: it must work
+    // offline and without a configured endpoint.
+    if let Command::Db(command) = &cli.command {
+        return db::run(&cli.data_dir()?, command).await;
+    }
+    let session = Session::open(&cli)?;
+
+    match cli.command {
+        Command::Login(args) => login::run(&session, args).await,
+        Command::Logout => {
+            session.auth_manager.logout().await;
+            eprintln!("Signed out.");
+            Ok(())
+        }
+        Command::Ask(args) => ask::run(&session, args).await,
+        Command::Threads(command) => threads::run(&session, command).await,
+        Command::Assets(command) => assets::run(&session, command).await,
+        Command::Mcp => mcp::run(&session).await,
+        Command::Db(_) => unreachable!("handled before opening the session"),
+    }
+}
@@ -0,0 +1,474 @@
+//! `eur mcp` — Model Context Protocol server over stdio.
+//!
+//! Lets external agents use Eurora as a context source. The server
+//! speaks newline-delimited JSON-RPC 2.0 on stdin / stdout (logs go to
+//! stderr) and exposes:
+//!
+//! - resources: the current screen context, recent activities, and
+//!   recent threads;
+//! - tools: `get_current_context`, `list_recent_activities`,
+//!   `search_timeline`, `search_threads`, and `fetch_asset`.
+//!
+//! Everything except the current context comes from the backend with
+//! the CLI's stored session. The current context only exists inside the
+//! running desktop app, so it is read through the app's localhost API
+//! (`localApi` in `local.json`); when that is disabled the tool says so
+//! instead of failing the whole server.
+//!
+//! Only the stdio transport is implemented: MCP clients launch local
+//! servers as subprocesses, so there is nothing to gain from an HTTP
+//! listener here.
+
+use activity_core::{ActivityWithLatestSession, ListActivitiesResponse, MAX_LIST_LIMIT};
+use agent_chain_core::messages::ContentBlock;
+use anyhow::{Context, Result, anyhow, bail};
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64_STANDARD};
+use euro_settings::{LocalApiSettings, SettingsState};
+use secrecy::ExposeSecret;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use uuid::Uuid;
+
+use crate::Session;
+use crate::assets::fetch_asset;
+
+/// Protocol revision this server implements.
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+const CURRENT_CONTEXT_URI: &str = "eurora://context/current";
+const RECENT_ACTIVITIES_URI: &str = "eurora://activities/recent";
+const RECENT_THREADS_URI: &str = "eurora://threads/recent";
+
+const DEFAULT_LIST_LIMIT: u32 = 20;
+
+// JSON-RPC 2.0 error codes.
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+#[derive(Debug, Deserialize)]
+struct RpcRequest {
+    /// Absent for notifications, which get no response.
+    #[serde(default)]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+/// A JSON-RPC error surfaced to the client as-is.
+#[derive(Debug)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn invalid_params(message: impl Into<String>) -> Self {
+        Self {
+            code: INVALID_PARAMS,
+            message: message.into(),
+        }
+    }
+}
+
+pub(crate) async fn run(session: &Session) -> Result<()> {
+    let local_api = SettingsState::load_or_migrate_from_default_path()
+        .map(|s| s.local.local_api)
+        .unwrap_or_default();
+    let server = McpServer {
+        session,
+        local_api,
+        http: reqwest::Client::new(),
+    };
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Some(response) = server.handle_line(&line).await else {
+            continue;
+        };
+        let mut bytes = serde_json::to_vec(&response)?;
+        bytes.push(b'\n');
+        stdout.write_all(&bytes).await?;
+        stdout.flush().await?;
+    }
+    Ok(())
+}
+
+struct McpServer<'a> {
+    session: &'a Session,
+    local_api: LocalApiSettings,
+    http: reqwest::Client,
+}
+
+impl McpServer<'_> {
+    async fn handle_line(&self, line: &str) -> Option<Value> {
+        let request: RpcRequest = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(err) => {
+                return Some(error_response(
+                    Value::Null,
+                    RpcError {
+                        code: PARSE_ERROR,
+                        message: err.to_string(),
+                    },
+                ));
+            }
+        };
+        let id = request.id?;
+        Some(match self.dispatch(&request.method, request.params).await {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(err) => error_response(id, err),
+        })
+    }
+
+    async fn dispatch(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "initialize" => Ok(json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": { "tools": {}, "resources": {} },
+                "serverInfo": { "name": "eurora", "version": env!("CARGO_PKG_VERSION") },
+            })),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": tool_descriptors() })),
+            "tools/call" => {
+                let name = params
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| RpcError::invalid_params("missing tool name"))?;
+                let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+                // Tool failures are results, not protocol errors, so the
+                // calling model gets to see and react to them.
+                Ok(match self.call_tool(name, &arguments).await {
+                    Ok(content) => json!({ "content": content, "isError": false }),
+                    Err(err) => json!({
+                        "content": [{ "type": "text", "text": format!("{err:#}") }],
+                        "isError": true,
+                    }),
+                })
+            }
+            "resources/list" => Ok(json!({ "resources": resource_descriptors() })),
+            "resources/read" => {
+                let uri = params
+                    .get("uri")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| RpcError::invalid_params("missing resource uri"))?;
+                let text = self
+                    .read_resource(uri)
+                    .await
+                    .map_err(|e| RpcError::invalid_params(format!("{e:#}")))?;
+                Ok(json!({
+                    "contents": [{ "uri": uri, "mimeType": "application/json", "text": text }],
+                }))
+            }
+            _ => Err(RpcError {
+                code: METHOD_NOT_FOUND,
+                message: format!("method not found: {method}"),
+            }),
+        }
+    }
+
+    async fn call_tool(&self, name: &str, arguments: &Value) -> Result<Vec<Value>> {
+        let limit = arguments
+            .get("limit")
+            .and_then(Value::as_u64)
+            .map_or(DEFAULT_LIST_LIMIT, |l| {
+                l.clamp(1, u64::from(MAX_LIST_LIMIT)) as u32
+            });
+        match name {
+            "get_current_context" => Ok(self
+                .current_context()
+                .await?
+                .iter()
+                .filter_map(content_block_to_mcp)
+                .collect()),
+            "list_recent_activities" => {
+                let activities = self.recent_activities(limit).await?;
+                Ok(vec![text_content(serde_json::to_string_pretty(
+                    &activities,
+                )?)])
+            }
+            "search_timeline" => {
+                let query = required_str(arguments, "query")?;
+                let matches: Vec<_> = self
+                    .recent_activities(MAX_LIST_LIMIT)
+                    .await?
+                    .into_iter()
+                    .filter(|a| activity_matches(a, query))
+                    .take(limit as usize)
+                    .collect();
+                Ok(vec![text_content(serde_json::to_string_pretty(&matches)?)])
+            }
+            "search_threads" => {
+                let query = required_str(arguments, "query")?;
+                let results = self
+                    .session
+                    .thread_manager
+                    .search_messages(query.to_string(), limit, 0)
+                    .await?;
+                Ok(vec![text_content(serde_json::to_string_pretty(&results)?)])
+            }
+            "fetch_asset" => {
+                let asset_id: Uuid = required_str(arguments, "asset_id")?
+                    .parse()
+                    .context("asset_id must be a UUID")?;
+                let asset = fetch_asset(self.session, asset_id)
+                    .await?
+                    .ok_or_else(|| anyhow!("asset {asset_id} not found"))?;
+                Ok(vec![asset_to_mcp(asset.bytes, &asset.mime_type)])
+            }
+            _ => bail!("unknown tool: {name}"),
+        }
+    }
+
+    async fn read_resource(&self, uri: &str) -> Result<String> {
+        let value = match uri {
+            CURRENT_CONTEXT_URI => serde_json::to_value(self.current_context().await?)?,
+            RECENT_ACTIVITIES_URI => {
+                serde_json::to_value(self.recent_activities(DEFAULT_LIST_LIMIT).await?)?
+            }
+            RECENT_THREADS_URI => serde_json::to_value(
+                self.session
+                    .thread_manager
+                    .list_threads(DEFAULT_LIST_LIMIT, 0)
+                    .await?,
+            )?,
+            _ => bail!("unknown resource: {uri}"),
+        };
+        Ok(serde_json::to_string_pretty(&value)?)
+    }
+
+    /// Context blocks from the running desktop app's localhost API.
+    async fn current_context(&self) -> Result<Vec<ContentBlock>> {
+        #[derive(Deserialize)]
+        struct CurrentContextResponse {
+            blocks: Vec<ContentBlock>,
+        }
+
+        let (true, Some(token)) = (self.local_api.enabled, &self.local_api.token) else {
+            bail!(
+                "current context needs the desktop app's local API; \
+                 enable `localApi.enabled` in local.json and restart the app"
+            );
+        };
+        let response = self
+            .http
+            .get(format!(
+                "http://127.0.0.1:{}/v1/context/current",
+                self.local_api.port
+            ))
+            .bearer_auth(token)
+            .send()
+            .await
+            .context("desktop app is not reachable on its local API port")?
+            .error_for_status()?;
+        Ok(response.json::<CurrentContextResponse>().await?.blocks)
+    }
+
+    async fn recent_activities(&self, limit: u32) -> Result<Vec<ActivityWithLatestSession>> {
+        let token = self
+            .session
+            .auth_manager
+            .get_or_refresh_access_token()
+            .await?;
+        let response: ListActivitiesResponse = self
+            .session
+            .endpoint_manager
+            .client()
+            .get(self.session.endpoint_manager.url("/activities"))
+            .bearer_auth(token.expose_secret())
+            .query(&[("limit", limit), ("offset", 0)])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(response.activities)
+    }
+}
+
+fn tool_descriptors() -> Value {
+    let limit = json!({
+        "type": "integer",
+        "minimum": 1,
+        "maximum": MAX_LIST_LIMIT,
+        "description": "Maximum number of results.",
+    });
+    json!([
+        {
+            "name": "get_current_context",
+            "description": "What the user is looking at right now in the Eurora desktop app: \
+                            a summary of the focused window, page, or document, plus a \
+                            screenshot when available.",
+            "inputSchema": { "type": "object", "properties": {} },
+        },
+        {
+            "name": "list_recent_activities",
+            "description": "Apps, pages, and documents the user worked in recently, newest \
+                            first, with the latest session's window title and URL.",
+            "inputSchema": { "type": "object", "properties": { "limit": limit.clone() } },
+        },
+        {
+            "name": "search_timeline",
+            "description": "Find recent activities whose name, window title, or URL \
+                            contains the query (case-insensitive).",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "query": { "type": "string" }, "limit": limit.clone() },
+                "required": ["query"],
+            },
+        },
+        {
+            "name": "search_threads",
+            "description": "Full-text search over the user's Eurora chat messages.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "query": { "type": "string" }, "limit": limit },
+                "required": ["query"],
+            },
+        },
+        {
+            "name": "fetch_asset",
+            "description": "Fetch a stored Eurora asset (e.g. an activity icon) by id.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "asset_id": { "type": "string", "format": "uuid" } },
+                "required": ["asset_id"],
+            },
+        },
+    ])
+}
+
+fn resource_descriptors() -> Value {
+    json!([
+        {
+            "uri": CURRENT_CONTEXT_URI,
+            "name": "Current screen context",
+            "mimeType": "application/json",
+        },
+        {
+            "uri": RECENT_ACTIVITIES_URI,
+            "name": "Recent activities",
+            "mimeType": "application/json",
+        },
+        {
+            "uri": RECENT_THREADS_URI,
+            "name": "Recent chat threads",
+            "mimeType": "application/json",
+        },
+    ])
+}
+
+fn error_response(id: Value, err: RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": err.code, "message": err.message },
+    })
+}
+
+fn required_str<'a>(arguments: &'a Value, key: &str) -> Result<&'a str> {
+    arguments
+        .get(key)
+        .and_then(Value::as_str)
+        .filter(|s| !s.trim().is_empty())
+        .ok_or_else(|| anyhow!("missing required argument `{key}`"))
+}
+
+fn text_content(text: String) -> Value {
+    json!({ "type": "text", "text": text })
+}
+
+fn content_block_to_mcp(block: &ContentBlock) -> Option<Value> {
+    match block {
+        ContentBlock::Text(text) => Some(text_content(text.text.clone())),
+        ContentBlock::Image(image) => Some(json!({
+            "type": "image",
+            "data": image.base64.as_ref()?,
+            "mimeType": image.mime_type.as_deref().unwrap_or("image/png"),
+        })),
+        _ => None,
+    }
+}
+
+fn asset_to_mcp(bytes: Vec<u8>, mime_type: &str) -> Value {
+    if mime_type.starts_with("image/") {
+        return json!({
+            "type": "image",
+            "data": BASE64_STANDARD.encode(&bytes),
+            "mimeType": mime_type,
+        });
+    }
+    match String::from_utf8(bytes) {
+        Ok(text) => text_content(text),
+        Err(err) => json!({
+            "type": "text",
+            "text": format!(
+                "binary asset ({mime_type}, {} bytes), base64: {}",
+                err.as_bytes().len(),
+                BASE64_STANDARD.encode(err.as_bytes())
+            ),
+        }),
+    }
+}
+
+fn activity_matches(activity: &ActivityWithLatestSession, query: &str) -> bool {
+    let query = query.to_lowercase();
+    let session = activity.latest_session.as_ref();
+    [
+        Some(activity.activity.display_name.as_str()),
+        session.and_then(|s| s.window_title.as_deref()),
+        session.and_then(|s| s.url.as_deref()),
+    ]
+    .into_iter()
+    .flatten()
+    .any(|field| field.to_lowercase().contains(&query))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_requests_and_notifications() {
+        let request: RpcRequest = serde_json::from_str(
+            r#"{"jsonrpc":"2.0","id":7,"method":"tools/call","params":{"name":"fetch_asset"}}"#,
+        )
+        .unwrap();
+        assert_eq!(request.id, Some(json!(7)));
+        assert_eq!(request.params["name"], "fetch_asset");
+
+        let notification: RpcRequest =
+            serde_json::from_str(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#)
+                .unwrap();
+        assert!(notification.id.is_none());
+    }
+
+    #[test]
+    fn every_tool_has_an_object_schema() {
+        let tools = tool_descriptors();
+        let tools = tools.as_array().unwrap();
+        assert_eq!(tools.len(), 5);
+        for tool in tools {
+            assert_eq!(tool["inputSchema"]["type"], "object");
+        }
+    }
+
+    #[test]
+    fn binary_assets_fall_back_to_base64_text() {
+        let image = asset_to_mcp(vec![1, 2, 3], "image/png");
+        assert_eq!(image["type"], "image");
+        assert_eq!(image["data"], "AQID");
+
+        let text = asset_to_mcp(b"hello".to_vec(), "text/plain");
+        assert_eq!(text["text"], "hello");
+
+        let binary = asset_to_mcp(vec![0xff, 0xfe], "application/octet-stream");
+        assert!(binary["text"].as_str().unwrap().ends_with("//4="));
+    }
+}
@@ -0,0 +1,119 @@
+//! `eur threads` — list threads and export one as JSON or Markdown.
+
+use std::path::PathBuf;
+
+use agent_chain_core::messages::AnyMessage;
+use anyhow::Result;
+use clap::{Subcommand, ValueEnum};
+use euro_thread::{MessageNode, Thread};
+use uuid::Uuid;
+
+use crate::Session;
+
+/// Page size used when walking a thread's full message history.
+const EXPORT_PAGE: u32 = 100;
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum ThreadsCommand {
+    /// List threads, most recently updated first.
+    List {
+        #[arg(long, default_value_t = 20)]
+        limit: u32,
+        #[arg(long, default_value_t = 0)]
+        offset: u32,
+        /// Print JSON instead of a table.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Export one thread's active branch.
+    Export {
+        thread_id: Uuid,
+        #[arg(long, value_enum, default_value_t = ExportFormat::Markdown)]
+        format: ExportFormat,
+        /// Write to this file instead of stdout.
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub(crate) enum ExportFormat {
+    Json,
+    Markdown,
+}
+
+pub(crate) async fn run(session: &Session, command: ThreadsCommand) -> Result<()> {
+    match command {
+        ThreadsCommand::List {
+            limit,
+            offset,
+            json,
+        } => {
+            let threads = session.thread_manager.list_threads(limit, offset).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&threads)?);
+            } else {
+                for thread in &threads {
+                    println!(
+                        "{}  {}  {}",
+                        thread.id,
+                        thread.updated_at.format("%Y-%m-%d %H:%M"),
+                        thread.title
+                    );
+                }
+            }
+            Ok(())
+        }
+        ThreadsCommand::Export {
+            thread_id,
+            format,
+            output,
+        } => {
+            let thread = session.thread_manager.get_thread(thread_id).await?;
+            let mut messages = Vec::new();
+            loop {
+                let page = session
+                    .thread_manager
+                    .get_messages(thread_id, EXPORT_PAGE, messages.len() as u32)
+                    .await?;
+                let done = (page.len() as u32) < EXPORT_PAGE;
+                messages.extend(page);
+                if done {
+                    break;
+                }
+            }
+
+            let rendered = match format {
+                ExportFormat::Json => serde_json::to_string_pretty(&serde_json::json!({
+                    "thread": thread,
+                    "messages": messages,
+                }))?,
+                ExportFormat::Markdown => render_markdown(&thread, &messages),
+            };
+            match output {
+                Some(path) => tokio::fs::write(&path, rendered).await?,
+                None => println!("{rendered}"),
+            }
+            Ok(())
+        }
+    }
+}
+
+fn render_markdown(thread: &Thread, messages: &[MessageNode]) -> String {
+    let mut out = format!("# {}\n", thread.title);
+    for node in messages {
+        let role = match &node.message {
+            AnyMessage::HumanMessage(_) => "User",
+            AnyMessage::AIMessage(_) => "Assistant",
+            // System preludes and tool round-trips are plumbing, not
+            // conversation.
+            _ => continue,
+        };
+        let text = node.message.text();
+        if text.trim().is_empty() {
+            continue;
+        }
+        out.push_str(&format!("\n## {role}\n\n{}\n", text.trim()));
+    }
+    out
+}
@@ -61,6 +61,10 @@ const BACKEND_SERVICES: &[BackendService] = &[
         name: "auth",
         types: auth_core::type_collection,
     },
+    BackendService {
+        name: "notification",
+        types: notification_core::type_collection,
+    },
     BackendService {
         name: "settings",
         types: settings_core::type_collection,
@@ -7,6 +7,9 @@ pub enum EndpointError {
 
     #[error("Failed to build HTTP client: {0}")]
     Build(#[source] reqwest::Error),
+
+    #[error("Local-only mode: {0}")]
+    LocalOnly(String),
 }
 
 pub type Result<T> = std::result::Result<T, EndpointError>;
@@ -10,7 +10,9 @@ mod error;
 pub use error::{EndpointError, Result};
 
 use std::sync::RwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
 
+use llm_core::{RedactedLlmConfig, is_loopback};
 use url::Url;
 
 /// Default base URL the binary was compiled against. Baked at
@@ -25,9 +27,18 @@ pub const DEFAULT_API_URL: &str = env!("BACKEND_URL");
 /// connection pool across every consumer that takes one from
 /// [`EndpointManager::client`]. The base URL is parsed up front and
 /// re-validated on every change via [`EndpointManager::set_global_backend_url`].
+/// The client asks for gzip or zstd responses and decodes them, which
+/// shrinks message histories and thread lists several times over.
+///
+/// Also the one place local-only mode is enforced: while it is on,
+/// anything that ships user context (chat turns, activity sessions)
+/// must pass [`EndpointManager::ensure_context_egress`] or
+/// [`EndpointManager::ensure_local_backend`] first, so no feature needs
+/// its own flag.
 pub struct EndpointManager {
     client: reqwest::Client,
     base_url: RwLock<Url>,
+    local_only: AtomicBool,
 }
 
 impl EndpointManager {
@@ -46,6 +57,7 @@ impl EndpointManager {
         Ok(Self {
             client,
             base_url: RwLock::new(base_url),
+            local_only: AtomicBool::new(false),
         })
     }
 
@@ -85,6 +97,69 @@ impl EndpointManager {
         tracing::info!("Switched API endpoint");
         Ok(())
     }
+
+    pub fn set_local_only(&self, enabled: bool) {
+        self.local_only.store(enabled, Ordering::Relaxed);
+        tracing::info!(enabled, "Set local-only mode");
+    }
+
+    pub fn is_local_only(&self) -> bool {
+        self.local_only.load(Ordering::Relaxed)
+    }
+
+    /// In local-only mode, fail unless the backend runs on this machine.
+    /// Enough for requests that stop at the backend, like activity
+    /// sessions.
+    pub fn ensure_local_backend(&self) -> Result<()> {
+        if !self.is_local_only() {
+            return Ok(());
+        }
+        let url = self.current_url();
+        if is_loopback(&url) {
+            return Ok(());
+        }
+        Err(EndpointError::LocalOnly(format!(
+            "the backend at {} is not on this machine",
+            url.host_str().unwrap_or("(no host)")
+        )))
+    }
+
+    /// In local-only mode, fail unless both the backend and every model
+    /// it is configured to use run on this machine. Required before a
+    /// chat turn, which forwards context to the models.
+    ///
+    /// Asks the backend's `/llm/info` on every call, so a backend that
+    /// was switched to a hosted provider is caught on the next turn. If
+    /// the answer can't be had, the check fails.
+    pub async fn ensure_context_egress(&self) -> Result<()> {
+        self.ensure_local_backend()?;
+        if !self.is_local_only() {
+            return Ok(());
+        }
+        let info = self
+            .client
+            .get(self.url("llm/info"))
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| {
+                EndpointError::LocalOnly(format!("could not confirm the backend's models: {e}"))
+            })?
+            .json::<RedactedLlmConfig>()
+            .await
+            .map_err(|e| {
+                EndpointError::LocalOnly(format!("could not confirm the backend's models: {e}"))
+            })?;
+        let remote = info.remote_providers();
+        if remote.is_empty() {
+            return Ok(());
+        }
+        let names: Vec<&str> = remote.iter().map(|id| id.as_str()).collect();
+        Err(EndpointError::LocalOnly(format!(
+            "the backend sends prompts to remote providers ({})",
+            names.join(", ")
+        )))
+    }
 }
 
 // Normalise the base URL so its path ends with `/`, ensuring `Url::join`
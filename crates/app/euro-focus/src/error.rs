@@ -0,0 +1,26 @@
+use thiserror::Error;
+use uuid::Uuid;
+
+pub type FocusResult<T> = std::result::Result<T, FocusError>;
+
+#[derive(Debug, Error)]
+pub enum FocusError {
+    #[error("Focus session storage: {0}")]
+    Db(#[from] euro_personal_db::PersonalDbError),
+
+    #[error("Focus session {0} not found")]
+    NotFound(Uuid),
+
+    /// Only one session can be running or paused at a time.
+    #[error("A focus session is already in progress")]
+    AlreadyActive,
+
+    #[error("No focus session is running")]
+    NotRunning,
+
+    #[error("No focus session is paused")]
+    NotPaused,
+
+    #[error("{0}")]
+    Invalid(String),
+}
@@ -0,0 +1,25 @@
+//! Focus sessions: timed work intervals, pomodoro style.
+//!
+//! A [`FocusSession`] has a goal and a planned length. The
+//! [`FocusTracker`] starts, pauses, resumes and ends it, and while it runs
+//! splits it into [`FocusActivity`] stretches as the activity the user is
+//! in changes. Everything is kept in the personal database, so a session
+//! survives a restart.
+//!
+//! [`metrics::compute`] turns the stretches into [`FocusMetrics`]: time
+//! per activity, how often focus switched, and how much time went to
+//! activities on the session's distraction list. [`recap_prompt`] puts
+//! the same figures into the question the app asks the model for an
+//! end-of-session recap.
+
+mod error;
+pub mod metrics;
+mod recap;
+mod session;
+mod tracker;
+
+pub use error::{FocusError, FocusResult};
+pub use metrics::{ActivityTime, DEFAULT_DISTRACTIONS, FocusMetrics};
+pub use recap::recap_prompt;
+pub use session::{FocusActivity, FocusDetails, FocusSession, FocusState};
+pub use tracker::{FocusTracker, MAX_PLANNED_SECS, MIN_PLANNED_SECS};
@@ -0,0 +1,187 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "specta")]
+use specta::Type;
+
+use crate::session::{FocusActivity, FocusSession};
+
+/// Activity names counted as off-topic when a session is started without
+/// its own list: the usual video, social and streaming sites, matched
+/// against the base domain a browser activity is named after.
+pub const DEFAULT_DISTRACTIONS: &[&str] = &[
+    "facebook",
+    "instagram",
+    "netflix",
+    "reddit",
+    "tiktok",
+    "twitch",
+    "twitter",
+    "x",
+    "youtube",
+];
+
+/// How a session went, from its activity stretches.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+#[serde(rename_all = "camelCase")]
+pub struct FocusMetrics {
+    /// Running time so far, pauses excluded.
+    pub active_secs: u32,
+    /// Times focus moved to a different activity.
+    pub switches: u32,
+    /// Time in activities on the session's distraction list.
+    pub off_topic_secs: u32,
+    /// Time per activity, longest first.
+    pub activities: Vec<ActivityTime>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityTime {
+    pub name: String,
+    pub secs: u32,
+    /// Stretches spent in it.
+    pub visits: u32,
+    pub off_topic: bool,
+}
+
+/// Whether an activity is on `distractions`, by name or process name,
+/// ignoring ASCII case and a trailing `.exe`.
+pub fn is_distraction(distractions: &[String], name: &str, process_name: &str) -> bool {
+    let process_name = process_name.strip_suffix(".exe").unwrap_or(process_name);
+    distractions.iter().any(|entry| {
+        let entry = entry.trim();
+        entry.eq_ignore_ascii_case(name) || entry.eq_ignore_ascii_case(process_name)
+    })
+}
+
+/// Add up `activities`, oldest first, for `session` as of `now`. The
+/// stretch in progress counts up to `now`, or to the session's end.
+pub fn compute(
+    session: &FocusSession,
+    activities: &[FocusActivity],
+    now: DateTime<Utc>,
+) -> FocusMetrics {
+    let horizon = session.ended_at.unwrap_or(now);
+    let mut metrics = FocusMetrics {
+        active_secs: session.active_secs(now),
+        ..FocusMetrics::default()
+    };
+    let mut previous: Option<&str> = None;
+    for activity in activities {
+        let end = activity.ended_at.unwrap_or(horizon).min(horizon);
+        let secs = u32::try_from((end - activity.started_at).num_seconds()).unwrap_or(0);
+        let off_topic = is_distraction(
+            &session.distractions,
+            &activity.name,
+            &activity.process_name,
+        );
+
+        // A pause splits a stretch without moving focus anywhere.
+        if previous.is_some_and(|name| name != activity.name) {
+            metrics.switches += 1;
+        }
+        previous = Some(activity.name.as_str());
+        if off_topic {
+            metrics.off_topic_secs += secs;
+        }
+        match metrics
+            .activities
+            .iter_mut()
+            .find(|total| total.name == activity.name)
+        {
+            Some(total) => {
+                total.secs += secs;
+                total.visits += 1;
+            }
+            None => metrics.activities.push(ActivityTime {
+                name: activity.name.clone(),
+                secs,
+                visits: 1,
+                off_topic,
+            }),
+        }
+    }
+    metrics
+        .activities
+        .sort_by(|a, b| b.secs.cmp(&a.secs).then_with(|| a.name.cmp(&b.name)));
+    metrics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::{TimeDelta, TimeZone};
+    use uuid::Uuid;
+
+    use crate::session::FocusState;
+
+    fn at(minute: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap() + TimeDelta::minutes(minute)
+    }
+
+    fn stretch(name: &str, from: i64, to: Option<i64>) -> FocusActivity {
+        FocusActivity {
+            id: Uuid::now_v7(),
+            name: name.to_string(),
+            process_name: format!("{}.exe", name.to_lowercase()),
+            started_at: at(from),
+            ended_at: to.map(at),
+        }
+    }
+
+    #[test]
+    fn distractions_match_name_or_process_ignoring_case() {
+        let list = vec!["youtube".to_string(), "Steam".to_string()];
+        assert!(is_distraction(&list, "Youtube", "firefox"));
+        assert!(is_distraction(&list, "Games", "steam.exe"));
+        assert!(!is_distraction(&list, "Code", "code"));
+    }
+
+    #[test]
+    fn counts_switches_time_and_off_topic_time() {
+        let session = FocusSession {
+            id: Uuid::now_v7(),
+            goal: "Report".to_string(),
+            planned_secs: 25 * 60,
+            state: FocusState::Running,
+            distractions: vec!["youtube".to_string()],
+            started_at: at(0),
+            paused_at: None,
+            paused_secs: 0,
+            ended_at: None,
+            recap: None,
+            recap_thread_id: None,
+        };
+        let activities = [
+            stretch("Code", 0, Some(10)),
+            stretch("Youtube", 10, Some(13)),
+            stretch("Code", 13, Some(15)),
+            // Paused from 15 to 17, then resumed in the same activity.
+            stretch("Code", 17, None),
+        ];
+
+        let metrics = compute(&session, &activities, at(20));
+        assert_eq!(metrics.switches, 2);
+        assert_eq!(metrics.off_topic_secs, 3 * 60);
+        assert_eq!(
+            metrics.activities,
+            [
+                ActivityTime {
+                    name: "Code".to_string(),
+                    secs: 15 * 60,
+                    visits: 3,
+                    off_topic: false,
+                },
+                ActivityTime {
+                    name: "Youtube".to_string(),
+                    secs: 3 * 60,
+                    visits: 1,
+                    off_topic: true,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use std::fmt::Write as _;
+
+use crate::session::{FocusDetails, FocusState};
+
+/// Most activities listed in a recap prompt.
+const MAX_PROMPT_ACTIVITIES: usize = 12;
+
+/// The question the model is asked when a session ends: the goal, how
+/// long it ran against the plan, and where the time went.
+pub fn recap_prompt(details: &FocusDetails) -> String {
+    let session = &details.session;
+    let metrics = &details.metrics;
+    let goal = match session.goal.as_str() {
+        "" => "(no goal given)",
+        goal => goal,
+    };
+    let ending = match session.state {
+        FocusState::Cancelled => "I cancelled it",
+        _ => "it's over",
+    };
+
+    let mut prompt = format!(
+        "I just ran a focus session and {ending}. Write me a short recap: what I worked \
+         on, how focused I stayed, and one concrete suggestion for my next session. Keep it \
+         under 120 words and don't use any tools.\n\n\
+         Goal: {goal}\n\
+         Planned: {}\n\
+         Focused: {}\n\
+         Paused: {}\n\
+         Switched activities {} times.\n\
+         Off-topic time: {}\n",
+        minutes(session.planned_secs),
+        minutes(metrics.active_secs),
+        minutes(session.paused_secs),
+        metrics.switches,
+        minutes(metrics.off_topic_secs),
+    );
+    if !metrics.activities.is_empty() {
+        prompt.push_str("\nTime per activity:\n");
+        for activity in metrics.activities.iter().take(MAX_PROMPT_ACTIVITIES) {
+            let _ = writeln!(
+                prompt,
+                "- {}: {}, {} visit(s){}",
+                activity.name,
+                minutes(activity.secs),
+                activity.visits,
+                if activity.off_topic {
+                    " (off-topic)"
+                } else {
+                    ""
+                }
+            );
+        }
+    }
+    prompt
+}
+
+fn minutes(secs: u32) -> String {
+    match secs {
+        0..60 => format!("{secs} s"),
+        _ => format!("{} min", (secs + 30) / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    use crate::metrics::{ActivityTime, FocusMetrics};
+    use crate::session::FocusSession;
+
+    #[test]
+    fn prompt_lists_goal_totals_and_activities() {
+        let details = FocusDetails {
+            session: FocusSession {
+                id: Uuid::nil(),
+                goal: "Draft the Q3 report".to_string(),
+                planned_secs: 25 * 60,
+                state: FocusState::Completed,
+                distractions: vec!["youtube".to_string()],
+                started_at: Utc::now(),
+                paused_at: None,
+                paused_secs: 90,
+                ended_at: Some(Utc::now()),
+                recap: None,
+                recap_thread_id: None,
+            },
+            activities: Vec::new(),
+            metrics: FocusMetrics {
+                active_secs: 24 * 60,
+                switches: 4,
+                off_topic_secs: 45,
+                activities: vec![ActivityTime {
+                    name: "Youtube".to_string(),
+                    secs: 45,
+                    visits: 2,
+                    off_topic: true,
+                }],
+            },
+        };
+        let prompt = recap_prompt(&details);
+        assert!(prompt.contains("Goal: Draft the Q3 report\n"));
+        assert!(prompt.contains("Planned: 25 min\n"));
+        assert!(prompt.contains("Paused: 2 min\n"));
+        assert!(prompt.contains("Switched activities 4 times."));
+        assert!(prompt.contains("- Youtube: 45 s, 2 visit(s) (off-topic)\n"));
+    }
+}
@@ -0,0 +1,211 @@
+use chrono::{DateTime, Utc};
+use euro_personal_db::{FocusActivityRecord, FocusSessionRecord};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "specta")]
+use specta::Type;
+use uuid::Uuid;
+
+use crate::metrics::FocusMetrics;
+
+/// Where a session is. A session is `completed` whether it ran its
+/// planned length or was stopped early by the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+#[serde(rename_all = "snake_case")]
+pub enum FocusState {
+    Running,
+    Paused,
+    Completed,
+    Cancelled,
+}
+
+impl FocusState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::Paused => "paused",
+            Self::Completed => "completed",
+            Self::Cancelled => "cancelled",
+        }
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        match text {
+            "running" => Some(Self::Running),
+            "paused" => Some(Self::Paused),
+            "completed" => Some(Self::Completed),
+            "cancelled" => Some(Self::Cancelled),
+            _ => None,
+        }
+    }
+
+    /// Running or paused.
+    pub fn is_open(self) -> bool {
+        matches!(self, Self::Running | Self::Paused)
+    }
+}
+
+/// A timed work interval.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+#[serde(rename_all = "camelCase")]
+pub struct FocusSession {
+    pub id: Uuid,
+    /// What the user meant to work on.
+    pub goal: String,
+    pub planned_secs: u32,
+    pub state: FocusState,
+    /// Activity names counted as off-topic, matched case-insensitively
+    /// against the activity's name and process name.
+    pub distractions: Vec<String>,
+    pub started_at: DateTime<Utc>,
+    /// When the pause in progress began.
+    pub paused_at: Option<DateTime<Utc>>,
+    /// Length of the finished pauses.
+    pub paused_secs: u32,
+    pub ended_at: Option<DateTime<Utc>>,
+    /// The model's end-of-session recap, once written.
+    pub recap: Option<String>,
+    /// The chat thread the recap was written in, to continue from.
+    pub recap_thread_id: Option<Uuid>,
+}
+
+impl FocusSession {
+    /// Seconds spent running, pauses excluded, up to `now` for a session
+    /// that hasn't ended.
+    pub fn active_secs(&self, now: DateTime<Utc>) -> u32 {
+        let end = self.ended_at.or(self.paused_at).unwrap_or(now);
+        let elapsed = u32::try_from((end - self.started_at).num_seconds()).unwrap_or(0);
+        elapsed.saturating_sub(self.paused_secs)
+    }
+
+    /// Running time left before the planned length is reached.
+    pub fn remaining_secs(&self, now: DateTime<Utc>) -> u32 {
+        self.planned_secs.saturating_sub(self.active_secs(now))
+    }
+
+    pub(crate) fn from_record(record: FocusSessionRecord) -> Option<Self> {
+        Some(Self {
+            id: record.id,
+            goal: record.goal,
+            planned_secs: record.planned_secs,
+            state: FocusState::parse(&record.state)?,
+            distractions: record.distractions,
+            started_at: record.started_at,
+            paused_at: record.paused_at,
+            paused_secs: record.paused_secs,
+            ended_at: record.ended_at,
+            recap: record.recap,
+            recap_thread_id: record.recap_thread_id,
+        })
+    }
+
+    pub(crate) fn to_record(&self) -> FocusSessionRecord {
+        FocusSessionRecord {
+            id: self.id,
+            goal: self.goal.clone(),
+            planned_secs: self.planned_secs,
+            state: self.state.as_str().to_string(),
+            distractions: self.distractions.clone(),
+            started_at: self.started_at,
+            paused_at: self.paused_at,
+            paused_secs: self.paused_secs,
+            ended_at: self.ended_at,
+            recap: self.recap.clone(),
+            recap_thread_id: self.recap_thread_id,
+        }
+    }
+}
+
+/// A stretch of a session spent in one activity.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+#[serde(rename_all = "camelCase")]
+pub struct FocusActivity {
+    pub id: Uuid,
+    pub name: String,
+    pub process_name: String,
+    pub started_at: DateTime<Utc>,
+    /// `None` for the stretch in progress.
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+impl From<FocusActivityRecord> for FocusActivity {
+    fn from(record: FocusActivityRecord) -> Self {
+        Self {
+            id: record.id,
+            name: record.name,
+            process_name: record.process_name,
+            started_at: record.started_at,
+            ended_at: record.ended_at,
+        }
+    }
+}
+
+/// A session with its activities and what they add up to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+#[serde(rename_all = "camelCase")]
+pub struct FocusDetails {
+    pub session: FocusSession,
+    pub activities: Vec<FocusActivity>,
+    pub metrics: FocusMetrics,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::{TimeDelta, TimeZone};
+
+    #[test]
+    fn active_time_leaves_out_pauses() {
+        let start = Utc.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap();
+        let minutes = |m| start + TimeDelta::minutes(m);
+        let mut session = FocusSession {
+            id: Uuid::nil(),
+            goal: String::new(),
+            planned_secs: 25 * 60,
+            state: FocusState::Running,
+            distractions: Vec::new(),
+            started_at: start,
+            paused_at: None,
+            paused_secs: 5 * 60,
+            ended_at: None,
+            recap: None,
+            recap_thread_id: None,
+        };
+        assert_eq!(session.active_secs(minutes(20)), 15 * 60);
+        assert_eq!(session.remaining_secs(minutes(20)), 10 * 60);
+
+        session.state = FocusState::Paused;
+        session.paused_at = Some(minutes(20));
+        assert_eq!(session.active_secs(minutes(40)), 15 * 60);
+
+        assert_eq!(session.remaining_secs(minutes(90)), 10 * 60);
+        session.paused_at = None;
+        session.ended_at = Some(minutes(60));
+        assert_eq!(session.remaining_secs(minutes(90)), 0);
+    }
+
+    #[test]
+    fn records_round_trip() {
+        let session = FocusSession {
+            id: Uuid::now_v7(),
+            goal: "Report".to_string(),
+            planned_secs: 60,
+            state: FocusState::Cancelled,
+            distractions: vec!["youtube".to_string()],
+            started_at: Utc::now(),
+            paused_at: None,
+            paused_secs: 0,
+            ended_at: Some(Utc::now()),
+            recap: None,
+            recap_thread_id: None,
+        };
+        assert_eq!(
+            FocusSession::from_record(session.to_record()),
+            Some(session)
+        );
+    }
+}
@@ -0,0 +1,479 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use euro_personal_db::{FocusActivityRecord, PersonalDb};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::error::{FocusError, FocusResult};
+use crate::metrics::{self, DEFAULT_DISTRACTIONS};
+use crate::session::{FocusActivity, FocusDetails, FocusSession, FocusState};
+
+/// Shortest session [`FocusTracker::start`] accepts.
+pub const MIN_PLANNED_SECS: u32 = 60;
+/// Longest session [`FocusTracker::start`] accepts.
+pub const MAX_PLANNED_SECS: u32 = 4 * 60 * 60;
+/// Longest goal, in characters.
+const MAX_GOAL_CHARS: usize = 200;
+
+/// The activity the user is in, as last reported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CurrentActivity {
+    name: String,
+    process_name: String,
+}
+
+#[derive(Default)]
+struct TrackerState {
+    /// The session running or paused, if any.
+    open: Option<FocusSession>,
+    /// The activity the user is in, whether or not a session runs.
+    current: Option<CurrentActivity>,
+    /// The activity of the open session's stretch in progress.
+    stretch: Option<CurrentActivity>,
+}
+
+/// Owns the session in progress and writes every change to the personal
+/// database. Cheap to clone; clones share state. Every method takes the
+/// time to act at, so callers and tests decide what "now" is.
+#[derive(Clone)]
+pub struct FocusTracker {
+    db: PersonalDb,
+    state: Arc<Mutex<TrackerState>>,
+}
+
+impl FocusTracker {
+    /// A tracker over `db`, picking up a session left running or paused
+    /// when the app last quit. Time the app was closed counts as running.
+    pub async fn load(db: PersonalDb) -> FocusResult<Self> {
+        let open = db
+            .open_focus_session()
+            .await?
+            .and_then(FocusSession::from_record);
+        Ok(Self {
+            db,
+            state: Arc::new(Mutex::new(TrackerState {
+                open,
+                ..TrackerState::default()
+            })),
+        })
+    }
+
+    /// The session running or paused, if any.
+    pub async fn active(&self) -> Option<FocusSession> {
+        self.state.lock().await.open.clone()
+    }
+
+    /// Start a session working on `goal` for `planned_secs`, counting
+    /// `distractions` as off-topic, or [`DEFAULT_DISTRACTIONS`] when
+    /// `None`. Fails while another session is running or paused.
+    pub async fn start(
+        &self,
+        goal: &str,
+        planned_secs: u32,
+        distractions: Option<Vec<String>>,
+        now: DateTime<Utc>,
+    ) -> FocusResult<FocusSession> {
+        let goal = validate_goal(goal)?;
+        if !(MIN_PLANNED_SECS..=MAX_PLANNED_SECS).contains(&planned_secs) {
+            return Err(FocusError::Invalid(format!(
+                "A session must last between {} and {} minutes",
+                MIN_PLANNED_SECS / 60,
+                MAX_PLANNED_SECS / 60
+            )));
+        }
+        let distractions = distractions
+            .unwrap_or_else(|| DEFAULT_DISTRACTIONS.iter().map(|d| d.to_string()).collect())
+            .into_iter()
+            .map(|d| d.trim().to_string())
+            .filter(|d| !d.is_empty())
+            .collect();
+
+        let mut state = self.state.lock().await;
+        if state.open.is_some() {
+            return Err(FocusError::AlreadyActive);
+        }
+        let session = FocusSession {
+            id: Uuid::now_v7(),
+            goal,
+            planned_secs,
+            state: FocusState::Running,
+            distractions,
+            started_at: now,
+            paused_at: None,
+            paused_secs: 0,
+            ended_at: None,
+            recap: None,
+            recap_thread_id: None,
+        };
+        self.db.save_focus_session(&session.to_record()).await?;
+        state.open = Some(session.clone());
+        self.open_stretch(&mut state, now).await?;
+        tracing::debug!(session_id = %session.id, planned_secs, "Focus session started");
+        Ok(session)
+    }
+
+    pub async fn pause(&self, now: DateTime<Utc>) -> FocusResult<FocusSession> {
+        let mut state = self.state.lock().await;
+        let mut session = match &state.open {
+            Some(session) if session.state == FocusState::Running => session.clone(),
+            _ => return Err(FocusError::NotRunning),
+        };
+        session.state = FocusState::Paused;
+        session.paused_at = Some(now);
+        self.db.save_focus_session(&session.to_record()).await?;
+        self.db.switch_focus_activity(session.id, now, None).await?;
+        state.stretch = None;
+        state.open = Some(session.clone());
+        Ok(session)
+    }
+
+    pub async fn resume(&self, now: DateTime<Utc>) -> FocusResult<FocusSession> {
+        let mut state = self.state.lock().await;
+        let mut session = match &state.open {
+            Some(session) if session.state == FocusState::Paused => session.clone(),
+            _ => return Err(FocusError::NotPaused),
+        };
+        end_pause(&mut session, now);
+        session.state = FocusState::Running;
+        self.db.save_focus_session(&session.to_record()).await?;
+        state.open = Some(session.clone());
+        self.open_stretch(&mut state, now).await?;
+        Ok(session)
+    }
+
+    /// End the open session as completed.
+    pub async fn stop(&self, now: DateTime<Utc>) -> FocusResult<FocusSession> {
+        self.finish(FocusState::Completed, now).await
+    }
+
+    /// End the open session as cancelled.
+    pub async fn cancel(&self, now: DateTime<Utc>) -> FocusResult<FocusSession> {
+        self.finish(FocusState::Cancelled, now).await
+    }
+
+    /// Complete the running session once it has run its planned length.
+    /// Returns the session it completed, if any.
+    pub async fn complete_if_due(&self, now: DateTime<Utc>) -> FocusResult<Option<FocusSession>> {
+        let due = self
+            .state
+            .lock()
+            .await
+            .open
+            .as_ref()
+            .is_some_and(|session| {
+                session.state == FocusState::Running && session.remaining_secs(now) == 0
+            });
+        if !due {
+            return Ok(None);
+        }
+        match self.finish(FocusState::Completed, now).await {
+            Ok(session) => Ok(Some(session)),
+            // Stopped by hand in between.
+            Err(FocusError::NotRunning) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn finish(&self, end: FocusState, now: DateTime<Utc>) -> FocusResult<FocusSession> {
+        let mut state = self.state.lock().await;
+        let Some(mut session) = state.open.clone() else {
+            return Err(FocusError::NotRunning);
+        };
+        end_pause(&mut session, now);
+        session.state = end;
+        session.ended_at = Some(now);
+        self.db.switch_focus_activity(session.id, now, None).await?;
+        self.db.save_focus_session(&session.to_record()).await?;
+        state.open = None;
+        state.stretch = None;
+        tracing::debug!(session_id = %session.id, state = end.as_str(), "Focus session ended");
+        Ok(session)
+    }
+
+    /// Note that focus moved to an activity. While a session runs, a new
+    /// activity closes the current stretch and opens one for it.
+    pub async fn record_activity(
+        &self,
+        name: &str,
+        process_name: &str,
+        now: DateTime<Utc>,
+    ) -> FocusResult<()> {
+        let mut state = self.state.lock().await;
+        state.current = Some(CurrentActivity {
+            name: name.to_string(),
+            process_name: process_name.to_string(),
+        });
+        let running = state
+            .open
+            .as_ref()
+            .is_some_and(|session| session.state == FocusState::Running);
+        if running && state.stretch != state.current {
+            self.open_stretch(&mut state, now).await?;
+        }
+        Ok(())
+    }
+
+    /// Close the open session's stretch and start one in the current
+    /// activity, if known.
+    async fn open_stretch(&self, state: &mut TrackerState, now: DateTime<Utc>) -> FocusResult<()> {
+        let Some(session_id) = state.open.as_ref().map(|session| session.id) else {
+            return Ok(());
+        };
+        let next = state.current.as_ref().map(|current| FocusActivityRecord {
+            id: Uuid::now_v7(),
+            session_id,
+            name: current.name.clone(),
+            process_name: current.process_name.clone(),
+            started_at: now,
+            ended_at: None,
+        });
+        self.db
+            .switch_focus_activity(session_id, now, next.as_ref())
+            .await?;
+        state.stretch = state.current.clone();
+        Ok(())
+    }
+
+    /// A session with its activities and metrics as of `now`.
+    pub async fn details(&self, id: Uuid, now: DateTime<Utc>) -> FocusResult<FocusDetails> {
+        let session = self.get(id).await?;
+        let activities: Vec<FocusActivity> = self
+            .db
+            .focus_activities(id)
+            .await?
+            .into_iter()
+            .map(FocusActivity::from)
+            .collect();
+        let metrics = metrics::compute(&session, &activities, now);
+        Ok(FocusDetails {
+            session,
+            activities,
+            metrics,
+        })
+    }
+
+    pub async fn get(&self, id: Uuid) -> FocusResult<FocusSession> {
+        if let Some(open) = self.active().await
+            && open.id == id
+        {
+            return Ok(open);
+        }
+        self.db
+            .focus_session(id)
+            .await?
+            .and_then(FocusSession::from_record)
+            .ok_or(FocusError::NotFound(id))
+    }
+
+    /// Sessions newest first.
+    pub async fn list(&self, limit: u32, offset: u32) -> FocusResult<Vec<FocusSession>> {
+        Ok(self
+            .db
+            .list_focus_sessions(limit, offset)
+            .await?
+            .into_iter()
+            .filter_map(FocusSession::from_record)
+            .collect())
+    }
+
+    pub async fn update_goal(&self, id: Uuid, goal: &str) -> FocusResult<FocusSession> {
+        let goal = validate_goal(goal)?;
+        self.modify(id, |session| session.goal = goal).await
+    }
+
+    /// Keep the recap written for a session and the thread it was written
+    /// in.
+    pub async fn set_recap(
+        &self,
+        id: Uuid,
+        recap: String,
+        thread_id: Option<Uuid>,
+    ) -> FocusResult<FocusSession> {
+        self.modify(id, |session| {
+            session.recap = Some(recap);
+            session.recap_thread_id = thread_id;
+        })
+        .await
+    }
+
+    /// Delete a session, ending it first if it is open.
+    pub async fn delete(&self, id: Uuid) -> FocusResult<()> {
+        let mut state = self.state.lock().await;
+        if !self.db.delete_focus_session(id).await? {
+            return Err(FocusError::NotFound(id));
+        }
+        if state.open.as_ref().is_some_and(|open| open.id == id) {
+            state.open = None;
+            state.stretch = None;
+        }
+        Ok(())
+    }
+
+    async fn modify(
+        &self,
+        id: Uuid,
+        change: impl FnOnce(&mut FocusSession),
+    ) -> FocusResult<FocusSession> {
+        let mut state = self.state.lock().await;
+        let mut session = match &state.open {
+            Some(open) if open.id == id => open.clone(),
+            _ => self
+                .db
+                .focus_session(id)
+                .await?
+                .and_then(FocusSession::from_record)
+                .ok_or(FocusError::NotFound(id))?,
+        };
+        change(&mut session);
+        self.db.save_focus_session(&session.to_record()).await?;
+        if let Some(open) = state.open.as_mut()
+            && open.id == id
+        {
+            *open = session.clone();
+        }
+        Ok(session)
+    }
+}
+
+/// Fold the pause in progress, if any, into `paused_secs`.
+fn end_pause(session: &mut FocusSession, now: DateTime<Utc>) {
+    if let Some(paused_at) = session.paused_at.take() {
+        let secs = u32::try_from((now - paused_at).num_seconds()).unwrap_or(0);
+        session.paused_secs = session.paused_secs.saturating_add(secs);
+    }
+}
+
+fn validate_goal(goal: &str) -> FocusResult<String> {
+    let goal = goal.trim();
+    if goal.chars().count() > MAX_GOAL_CHARS {
+        return Err(FocusError::Invalid(format!(
+            "The goal must be at most {MAX_GOAL_CHARS} characters"
+        )));
+    }
+    Ok(goal.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::{TimeDelta, TimeZone};
+    use euro_personal_db::DB_FILE_NAME;
+
+    fn at(minute: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap() + TimeDelta::minutes(minute)
+    }
+
+    async fn tracker() -> (tempfile::TempDir, FocusTracker) {
+        let dir = tempfile::tempdir().unwrap();
+        let db = PersonalDb::open(dir.path().join(DB_FILE_NAME))
+            .await
+            .unwrap();
+        let tracker = FocusTracker::load(db).await.unwrap();
+        (dir, tracker)
+    }
+
+    #[tokio::test]
+    async fn tracks_activities_through_pause_and_stop() {
+        let (_dir, tracker) = tracker().await;
+        tracker
+            .record_activity("Code", "code", at(-5))
+            .await
+            .unwrap();
+
+        let session = tracker.start("Report", 25 * 60, None, at(0)).await.unwrap();
+        assert!(matches!(
+            tracker.start("Again", 25 * 60, None, at(1)).await,
+            Err(FocusError::AlreadyActive)
+        ));
+        tracker
+            .record_activity("Youtube", "firefox", at(10))
+            .await
+            .unwrap();
+        // Repeated reports of the same activity don't split the stretch.
+        tracker
+            .record_activity("Youtube", "firefox", at(11))
+            .await
+            .unwrap();
+        tracker
+            .record_activity("Code", "code", at(12))
+            .await
+            .unwrap();
+        tracker.pause(at(15)).await.unwrap();
+        // Ignored while paused, but remembered for the resume.
+        tracker
+            .record_activity("Slack", "slack", at(16))
+            .await
+            .unwrap();
+        tracker.resume(at(18)).await.unwrap();
+        let stopped = tracker.stop(at(20)).await.unwrap();
+
+        assert_eq!(stopped.state, FocusState::Completed);
+        assert_eq!(stopped.paused_secs, 3 * 60);
+        assert!(tracker.active().await.is_none());
+
+        let details = tracker.details(session.id, at(30)).await.unwrap();
+        let names: Vec<_> = details.activities.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, ["Code", "Youtube", "Code", "Slack"]);
+        assert!(details.activities.iter().all(|a| a.ended_at.is_some()));
+        assert_eq!(details.metrics.active_secs, 17 * 60);
+        assert_eq!(details.metrics.switches, 3);
+        assert_eq!(details.metrics.off_topic_secs, 2 * 60);
+    }
+
+    #[tokio::test]
+    async fn completes_when_due_and_restores_open_sessions() {
+        let (dir, tracker) = tracker().await;
+        let session = tracker
+            .start("", 5 * 60, Some(vec![]), at(0))
+            .await
+            .unwrap();
+        tracker.pause(at(1)).await.unwrap();
+        assert!(matches!(
+            tracker.pause(at(2)).await,
+            Err(FocusError::NotRunning)
+        ));
+
+        let reloaded = FocusTracker::load(
+            PersonalDb::open(dir.path().join(DB_FILE_NAME))
+                .await
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            reloaded.active().await.map(|s| s.state),
+            Some(FocusState::Paused)
+        );
+
+        reloaded.resume(at(3)).await.unwrap();
+        assert_eq!(reloaded.complete_if_due(at(6)).await.unwrap(), None);
+        let completed = reloaded.complete_if_due(at(7)).await.unwrap().unwrap();
+        assert_eq!(completed.id, session.id);
+        assert_eq!(completed.ended_at, Some(at(7)));
+
+        let recapped = reloaded
+            .set_recap(session.id, "Short and sweet.".to_string(), None)
+            .await
+            .unwrap();
+        assert_eq!(recapped.recap.as_deref(), Some("Short and sweet."));
+        assert_eq!(reloaded.list(10, 0).await.unwrap(), [recapped]);
+
+        reloaded.delete(session.id).await.unwrap();
+        assert!(matches!(
+            reloaded.get(session.id).await,
+            Err(FocusError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn rejects_out_of_range_lengths() {
+        let (_dir, tracker) = tracker().await;
+        assert!(matches!(
+            tracker.start("Report", 30, None, at(0)).await,
+            Err(FocusError::Invalid(_))
+        ));
+        assert!(tracker.active().await.is_none());
+    }
+}
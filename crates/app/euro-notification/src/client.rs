@@ -0,0 +1,288 @@
+//! Connection driver for the notification socket.
+//!
+//! [`NotificationClient::run`] owns the socket. It connects with `?after=`
+//! set to the newest cursor it has seen, forwards frames to subscribers,
+//! and on any failure — including missed heartbeats — reconnects after an
+//! exponential, jittered delay. Auth transitions are followed: signing out
+//! drops the socket, and signing in as someone else starts over without a
+//! cursor.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use euro_auth::{AuthEvent, AuthManager};
+use euro_endpoint::EndpointManager;
+use futures::StreamExt;
+use notification_core::{Notification, NotificationEvent, NotificationServerMessage};
+use secrecy::ExposeSecret;
+use tokio::net::TcpStream;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::{sleep, timeout};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::{HeaderValue, header};
+use tokio_tungstenite::tungstenite::protocol::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use crate::error::{Error, Result};
+
+/// Three of the server's 25-second heartbeat intervals: one late frame
+/// can be jitter, three mean the connection is dead.
+const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(75);
+
+const MIN_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// Caps the backoff so a client recovers within a minute of the backend
+/// coming back.
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+/// Updates buffered per subscriber before it lags.
+const UPDATE_CHANNEL_CAPACITY: usize = 64;
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// What subscribers of a [`NotificationClient`] receive.
+#[derive(Debug, Clone)]
+pub enum NotificationUpdate {
+    Notification(Notification),
+    /// Events were missed and can't be replayed; refetch whatever state
+    /// they would have invalidated.
+    Resync,
+}
+
+/// Handle to the notification socket.
+///
+/// Cheap to clone: holds an `Arc<EndpointManager>`, an `AuthManager`
+/// (itself `Arc` internally), and the sending half of the update channel.
+/// Subscribe first, then spawn [`NotificationClient::run`] on the app's
+/// runtime.
+#[derive(Clone)]
+pub struct NotificationClient {
+    endpoint_manager: Arc<EndpointManager>,
+    auth_manager: AuthManager,
+    updates: broadcast::Sender<NotificationUpdate>,
+}
+
+/// Why a connection ended.
+enum SessionEnd {
+    /// Transport failure, server close, or heartbeat timeout.
+    Dropped { was_ready: bool },
+    /// The signed-in user changed; reconnect straight away.
+    AuthChanged,
+}
+
+impl NotificationClient {
+    pub fn new(endpoint_manager: Arc<EndpointManager>, auth_manager: AuthManager) -> Self {
+        let (updates, _) = broadcast::channel(UPDATE_CHANNEL_CAPACITY);
+        Self {
+            endpoint_manager,
+            auth_manager,
+            updates,
+        }
+    }
+
+    /// Subscribe to notifications. Lagging subscribers should treat
+    /// `RecvError::Lagged` like [`NotificationUpdate::Resync`].
+    #[must_use]
+    pub fn subscribe(&self) -> broadcast::Receiver<NotificationUpdate> {
+        self.updates.subscribe()
+    }
+
+    /// Keep the socket open for as long as the future is polled.
+    pub async fn run(&self) {
+        let mut driver = Driver {
+            client: self,
+            auth_events: Some(self.auth_manager.subscribe()),
+            cursor: None,
+            subject: None,
+            signed_out: false,
+        };
+        driver.run().await;
+    }
+
+    async fn connect(&self, after: Option<i64>) -> Result<Socket> {
+        let token = self
+            .auth_manager
+            .get_or_refresh_access_token()
+            .await
+            .map_err(|e| Error::Auth(e.to_string()))?;
+
+        let mut url = self.endpoint_manager.url("/notifications/ws");
+        let scheme = match url.scheme() {
+            "https" => "wss",
+            "http" => "ws",
+            other => {
+                return Err(Error::InvalidUrl(format!(
+                    "endpoint URL has unsupported scheme for WebSocket: {other}"
+                )));
+            }
+        };
+        url.set_scheme(scheme)
+            .map_err(|()| Error::InvalidUrl(format!("Failed to switch scheme to {scheme}")))?;
+        if let Some(after) = after {
+            url.query_pairs_mut()
+                .append_pair("after", &after.to_string());
+        }
+
+        let mut req = url
+            .as_str()
+            .into_client_request()
+            .map_err(|e| Error::InvalidUrl(e.to_string()))?;
+        req.headers_mut().insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {}", token.expose_secret()))
+                .map_err(|e| Error::Auth(format!("Invalid bearer header: {e}")))?,
+        );
+
+        let (socket, _response) = tokio_tungstenite::connect_async(req).await?;
+        Ok(socket)
+    }
+}
+
+struct Driver<'a> {
+    client: &'a NotificationClient,
+    /// `None` once the auth bus has closed; auth changes are then ignored.
+    auth_events: Option<broadcast::Receiver<AuthEvent>>,
+    /// The newest cursor seen for the current user.
+    cursor: Option<i64>,
+    /// The user the cursor belongs to, once an auth event has named one.
+    subject: Option<String>,
+    signed_out: bool,
+}
+
+impl Driver<'_> {
+    async fn run(&mut self) {
+        let mut delay = MIN_RECONNECT_DELAY;
+        loop {
+            if self.signed_out {
+                self.next_auth_change().await;
+                continue;
+            }
+
+            match self.client.connect(self.cursor).await {
+                Ok(socket) => match self.session(socket).await {
+                    SessionEnd::AuthChanged => {
+                        delay = MIN_RECONNECT_DELAY;
+                        continue;
+                    }
+                    SessionEnd::Dropped { was_ready } => {
+                        if was_ready {
+                            delay = MIN_RECONNECT_DELAY;
+                        }
+                    }
+                },
+                Err(e) => tracing::debug!(error = %e, "Notification socket connect failed"),
+            }
+
+            // Jitter so clients don't reconnect in lockstep after a
+            // backend restart.
+            let jittered = delay.mul_f64(rand::random_range(0.5..1.0));
+            tokio::select! {
+                _ = sleep(jittered) => {}
+                _ = self.next_auth_change() => delay = MIN_RECONNECT_DELAY,
+            }
+            delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+        }
+    }
+
+    async fn session(&mut self, mut socket: Socket) -> SessionEnd {
+        let mut was_ready = false;
+        loop {
+            let frame = tokio::select! {
+                frame = timeout(HEARTBEAT_TIMEOUT, socket.next()) => frame,
+                _ = self.next_auth_change() => return SessionEnd::AuthChanged,
+            };
+            let text = match frame {
+                Ok(Some(Ok(Message::Text(text)))) => text,
+                Ok(Some(Ok(Message::Close(_)))) | Ok(None) => {
+                    tracing::debug!("Notification socket closed by server");
+                    return SessionEnd::Dropped { was_ready };
+                }
+                Ok(Some(Ok(_))) => continue,
+                Ok(Some(Err(e))) => {
+                    tracing::debug!(error = %e, "Notification socket failed");
+                    return SessionEnd::Dropped { was_ready };
+                }
+                Err(_) => {
+                    tracing::warn!("Notification socket missed its heartbeats; reconnecting");
+                    return SessionEnd::Dropped { was_ready };
+                }
+            };
+
+            match serde_json::from_str::<NotificationServerMessage>(&text) {
+                Ok(message) => was_ready |= self.handle(message),
+                Err(e) => tracing::warn!(error = %e, "Ignoring malformed notification frame"),
+            }
+        }
+    }
+
+    /// Apply one server frame. Returns `true` once the replay is done.
+    fn handle(&mut self, message: NotificationServerMessage) -> bool {
+        match message {
+            NotificationServerMessage::Notification { notification } => {
+                self.cursor = Some(self.cursor.unwrap_or(0).max(notification.id));
+                if notification.event == NotificationEvent::Unknown {
+                    tracing::debug!(id = notification.id, "Ignoring unknown notification");
+                } else {
+                    let _ = self
+                        .client
+                        .updates
+                        .send(NotificationUpdate::Notification(notification));
+                }
+                false
+            }
+            NotificationServerMessage::Ready { cursor } => {
+                self.cursor = Some(cursor);
+                true
+            }
+            NotificationServerMessage::Resync { cursor } => {
+                self.cursor = Some(cursor);
+                let _ = self.client.updates.send(NotificationUpdate::Resync);
+                false
+            }
+            NotificationServerMessage::Heartbeat => false,
+        }
+    }
+
+    /// Resolve once the signed-in user changes: a sign-out, a sign-in, or
+    /// a different subject. Token refreshes for the same user don't count.
+    async fn next_auth_change(&mut self) {
+        loop {
+            let Some(events) = self.auth_events.as_mut() else {
+                return std::future::pending().await;
+            };
+            let claims = match events.recv().await {
+                Ok(AuthEvent { claims }) => claims,
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => {
+                    self.auth_events = None;
+                    continue;
+                }
+            };
+
+            let Some(claims) = claims else {
+                if !self.signed_out {
+                    self.signed_out = true;
+                    self.subject = None;
+                    self.cursor = None;
+                    return;
+                }
+                continue;
+            };
+
+            let changed = match self.subject.replace(claims.sub.clone()) {
+                Some(previous) if previous != claims.sub => {
+                    self.cursor = None;
+                    true
+                }
+                Some(_) => false,
+                // The first user we hear about: possibly a sign-in we were
+                // waiting on, and at worst one needless reconnect.
+                None => true,
+            };
+            if changed || std::mem::take(&mut self.signed_out) {
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Failed to acquire access token: {0}")]
+    Auth(String),
+
+    #[error("WebSocket transport error: {0}")]
+    WebSocket(#[source] tokio_tungstenite::tungstenite::Error),
+
+    #[error("Invalid endpoint URL: {0}")]
+    InvalidUrl(String),
+}
+
+impl From<tokio_tungstenite::tungstenite::Error> for Error {
+    fn from(err: tokio_tungstenite::tungstenite::Error) -> Self {
+        Self::WebSocket(err)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,14 @@
+//! Desktop client for the Eurora push notification socket.
+//!
+//! Wire types come from [`notification_core`]. [`NotificationClient`] keeps
+//! one WebSocket to `/notifications/ws` open for the signed-in user,
+//! reconnecting with backoff and resuming from the last cursor it saw so
+//! nothing is missed across sleeps and network changes. Consumers
+//! subscribe to the resulting [`NotificationUpdate`] stream.
+
+mod client;
+mod error;
+
+pub use client::{NotificationClient, NotificationUpdate};
+pub use error::{Error, Result};
+pub use notification_core::{Notification, NotificationEvent};
@@ -0,0 +1,122 @@
+//! Pre-migration snapshots.
+//!
+//! Backups are written with `VACUUM INTO`, which produces a consistent,
+//! compacted copy even while other connections hold the database open in
+//! WAL mode. They live in a `backups` directory next to the database and
+//! are named `<stem>-<UTC timestamp>-v<schema version>.sqlite`, so sorting
+//! by name sorts by age.
+
+use std::path::{Path, PathBuf};
+
+use sqlx::SqlitePool;
+
+use crate::error::{PersonalDbError, PersonalDbResult};
+
+/// Backups kept per database. Older ones are deleted after each new backup.
+pub const MAX_BACKUPS: usize = 3;
+
+/// Directory the backups of the database at `db_path` are written to.
+pub fn backup_dir(db_path: &Path) -> PathBuf {
+    db_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join("backups")
+}
+
+/// Backups of the database at `db_path`, oldest first.
+pub async fn list_backups(db_path: &Path) -> PersonalDbResult<Vec<PathBuf>> {
+    let dir = backup_dir(db_path);
+    let prefix = format!("{}-", file_stem(db_path));
+
+    let mut entries = match tokio::fs::read_dir(&dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(PersonalDbError::io(dir, e)),
+    };
+
+    let mut backups = Vec::new();
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .map_err(|e| PersonalDbError::io(&dir, e))?
+    {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with(&prefix) && name.ends_with(".sqlite") {
+            backups.push(entry.path());
+        }
+    }
+    backups.sort();
+    Ok(backups)
+}
+
+/// Snapshot the database behind `pool` and prune old backups. `version` is
+/// the schema version being backed up, recorded in the file name.
+pub(crate) async fn create(
+    pool: &SqlitePool,
+    db_path: &Path,
+    version: i64,
+) -> PersonalDbResult<PathBuf> {
+    let dir = backup_dir(db_path);
+    tokio::fs::create_dir_all(&dir)
+        .await
+        .map_err(|e| PersonalDbError::io(&dir, e))?;
+
+    let timestamp = chrono::Utc::now().format("%Y%m%dT%H%M%S%3fZ");
+    let target = dir.join(format!(
+        "{}-{timestamp}-v{version}.sqlite",
+        file_stem(db_path)
+    ));
+    sqlx::query("VACUUM INTO ?")
+        .bind(target.to_string_lossy().into_owned())
+        .execute(pool)
+        .await?;
+
+    let backups = list_backups(db_path).await?;
+    let excess = backups.len().saturating_sub(MAX_BACKUPS);
+    for old in &backups[..excess] {
+        if let Err(e) = tokio::fs::remove_file(old).await {
+            tracing::warn!(path = %old.display(), "Failed to remove old personal database backup: {e}");
+        }
+    }
+
+    Ok(target)
+}
+
+fn file_stem(db_path: &Path) -> String {
+    db_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "database".to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DB_FILE_NAME, PersonalDb};
+
+    #[tokio::test]
+    async fn create_writes_a_usable_copy_and_prunes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(DB_FILE_NAME);
+        let db = PersonalDb::open(&path).await.unwrap();
+
+        let first = create(db.pool(), &path, 1).await.unwrap();
+        assert!(first.starts_with(backup_dir(&path)));
+        let copy = PersonalDb::open(&first).await.unwrap();
+        let rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM app_meta")
+            .fetch_one(copy.pool())
+            .await
+            .unwrap();
+        assert_eq!(rows, 1);
+        copy.close().await;
+
+        for version in 2..=(MAX_BACKUPS as i64 + 1) {
+            tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+            create(db.pool(), &path, version).await.unwrap();
+        }
+        let backups = list_backups(&path).await.unwrap();
+        assert_eq!(backups.len(), MAX_BACKUPS);
+        assert!(!backups.contains(&first));
+    }
+}
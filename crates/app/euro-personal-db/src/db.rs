@@ -0,0 +1,168 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use sqlx::migrate::Migrator;
+use sqlx::sqlite::{
+    SqliteAutoVacuum, SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions,
+    SqliteSynchronous,
+};
+
+use crate::backup;
+use crate::doctor::{migration_status, quick_check};
+use crate::error::{PersonalDbError, PersonalDbResult};
+
+/// File name of the personal database inside the app data directory.
+pub const DB_FILE_NAME: &str = "personal_database.sqlite";
+
+/// How much of the file SQLite reads through a memory map instead of
+/// `read` calls.
+const MMAP_SIZE: u64 = 256 * 1024 * 1024;
+
+pub(crate) static MIGRATOR: Migrator = sqlx::migrate!("./src/migrations");
+
+/// An open, fully migrated personal database.
+#[derive(Debug, Clone)]
+pub struct PersonalDb {
+    pool: SqlitePool,
+    path: PathBuf,
+}
+
+impl PersonalDb {
+    /// Open the database at `path`, creating it if needed, and apply any
+    /// pending migrations.
+    ///
+    /// Refuses to touch a database that fails its integrity check, was
+    /// migrated by a newer build, or whose applied migrations no longer
+    /// match this build's; run [`doctor`](crate::doctor) for details.
+    pub async fn open(path: impl Into<PathBuf>) -> PersonalDbResult<Self> {
+        let path = path.into();
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| PersonalDbError::io(parent, e))?;
+        }
+
+        let options = SqliteConnectOptions::new()
+            .filename(&path)
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal)
+            // In WAL mode, NORMAL skips the fsync per commit; a power cut
+            // can roll back the last commits but never corrupts the file.
+            .synchronous(SqliteSynchronous::Normal)
+            // Takes effect on new files only; `compact` converts old ones.
+            .auto_vacuum(SqliteAutoVacuum::Incremental)
+            .pragma("mmap_size", MMAP_SIZE.to_string())
+            .foreign_keys(true)
+            .busy_timeout(Duration::from_secs(5));
+        let pool = SqlitePoolOptions::new()
+            .max_connections(4)
+            .connect_with(options)
+            .await?;
+
+        let db = Self { pool, path };
+        db.migrate().await?;
+        Ok(db)
+    }
+
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Close every pooled connection, checkpointing the WAL.
+    pub async fn close(&self) {
+        self.pool.close().await;
+    }
+
+    async fn migrate(&self) -> PersonalDbResult<()> {
+        let status = {
+            let mut conn = self.pool.acquire().await?;
+            migration_status(&mut conn).await?
+        };
+
+        if let Some(&version) = status.unknown.first() {
+            return Err(PersonalDbError::NewerSchema(version));
+        }
+        if let Some(&version) = status.modified.first() {
+            return Err(PersonalDbError::ChecksumMismatch(version));
+        }
+        if let Some(&version) = status.dirty.first() {
+            return Err(PersonalDbError::Dirty(version));
+        }
+        if status.pending.is_empty() {
+            return Ok(());
+        }
+
+        // A fresh file has nothing worth backing up.
+        if let Some(&current) = status.applied.last() {
+            let mut conn = self.pool.acquire().await?;
+            let problems = quick_check(&mut conn).await?;
+            if !problems.is_empty() {
+                return Err(PersonalDbError::Corrupt(problems.join("; ")));
+            }
+            drop(conn);
+
+            let backup = backup::create(&self.pool, &self.path, current).await?;
+            tracing::info!(
+                backup = %backup.display(),
+                pending = status.pending.len(),
+                "Backed up personal database before migrating"
+            );
+        }
+
+        MIGRATOR.run(&self.pool).await?;
+        tracing::debug!(
+            path = %self.path.display(),
+            applied = status.pending.len(),
+            "Personal database migrations applied"
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::list_backups;
+
+    #[tokio::test]
+    async fn open_creates_and_migrates() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(DB_FILE_NAME);
+
+        let db = PersonalDb::open(&path).await.unwrap();
+        let created: String =
+            sqlx::query_scalar("SELECT value FROM app_meta WHERE key = 'created_at'")
+                .fetch_one(db.pool())
+                .await
+                .unwrap();
+        assert!(!created.is_empty());
+        db.close().await;
+
+        // Nothing was pending on a fresh file, and nothing is on reopen.
+        PersonalDb::open(&path).await.unwrap().close().await;
+        assert!(list_backups(&path).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn open_refuses_schema_from_newer_build() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(DB_FILE_NAME);
+
+        let db = PersonalDb::open(&path).await.unwrap();
+        sqlx::query(
+            "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) \
+             VALUES (29990101000000, 'future', 1, x'00', 0)",
+        )
+        .execute(db.pool())
+        .await
+        .unwrap();
+        db.close().await;
+
+        let err = PersonalDb::open(&path).await.unwrap_err();
+        assert!(matches!(err, PersonalDbError::NewerSchema(29990101000000)));
+    }
+}
@@ -0,0 +1,279 @@
+//! Read-only health check behind `eur db doctor`.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use sqlx::sqlite::{SqliteConnectOptions, SqliteConnection};
+use sqlx::{ConnectOptions, Row};
+
+use crate::backup::list_backups;
+use crate::db::MIGRATOR;
+use crate::error::{PersonalDbError, PersonalDbResult};
+
+/// How the migrations recorded in a database compare with the ones this
+/// build ships. Every list holds migration versions in ascending order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationStatus {
+    /// Recorded, successful, and matching this build.
+    pub applied: Vec<i64>,
+    /// Shipped with this build but not yet recorded.
+    pub pending: Vec<i64>,
+    /// Recorded but unknown to this build.
+    pub unknown: Vec<i64>,
+    /// Recorded with a checksum that differs from this build's SQL.
+    pub modified: Vec<i64>,
+    /// Recorded as started but not finished.
+    pub dirty: Vec<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DoctorReport {
+    pub path: PathBuf,
+    /// Lines reported by `PRAGMA integrity_check`, or the error that kept
+    /// the database from being read at all. Empty when healthy.
+    pub integrity_errors: Vec<String>,
+    pub foreign_key_violations: Vec<String>,
+    pub migrations: MigrationStatus,
+    /// Pre-migration backups, oldest first.
+    pub backups: Vec<PathBuf>,
+}
+
+impl DoctorReport {
+    /// Pending migrations don't count against health: they're applied the
+    /// next time the app opens the database.
+    pub fn is_healthy(&self) -> bool {
+        self.integrity_errors.is_empty()
+            && self.foreign_key_violations.is_empty()
+            && self.migrations.unknown.is_empty()
+            && self.migrations.modified.is_empty()
+            && self.migrations.dirty.is_empty()
+    }
+}
+
+/// Inspect the database at `path` without modifying it.
+///
+/// Corruption shows up in the report rather than as an error; `Err` means
+/// the check itself couldn't run, e.g. because the file doesn't exist.
+pub async fn doctor(path: &Path) -> PersonalDbResult<DoctorReport> {
+    let exists = tokio::fs::try_exists(path)
+        .await
+        .map_err(|e| PersonalDbError::io(path, e))?;
+    if !exists {
+        return Err(PersonalDbError::NotFound(path.to_path_buf()));
+    }
+
+    let mut report = DoctorReport {
+        path: path.to_path_buf(),
+        integrity_errors: Vec::new(),
+        foreign_key_violations: Vec::new(),
+        migrations: MigrationStatus::default(),
+        backups: list_backups(path).await?,
+    };
+
+    // A file that isn't a database (or is damaged badly enough) fails on
+    // the first query; that's a finding, not a reason to abort.
+    if let Err(e) = inspect(path, &mut report).await {
+        report.integrity_errors.push(e.to_string());
+    }
+    Ok(report)
+}
+
+async fn inspect(path: &Path, report: &mut DoctorReport) -> Result<(), sqlx::Error> {
+    let mut conn = SqliteConnectOptions::new()
+        .filename(path)
+        .read_only(true)
+        .connect()
+        .await?;
+
+    let rows = sqlx::query("PRAGMA integrity_check")
+        .fetch_all(&mut conn)
+        .await?;
+    report.integrity_errors = rows
+        .iter()
+        .map(|row| row.get::<String, _>(0))
+        .filter(|line| line != "ok")
+        .collect();
+
+    let rows = sqlx::query("PRAGMA foreign_key_check")
+        .fetch_all(&mut conn)
+        .await?;
+    report.foreign_key_violations = rows
+        .iter()
+        .map(|row| {
+            let table: String = row.get(0);
+            let rowid: Option<i64> = row.get(1);
+            let parent: String = row.get(2);
+            match rowid {
+                Some(rowid) => format!("{table} row {rowid} references missing {parent} row"),
+                None => format!("{table} references missing {parent} row"),
+            }
+        })
+        .collect();
+
+    report.migrations = migration_status(&mut conn).await?;
+    Ok(())
+}
+
+/// `PRAGMA quick_check` problems; empty when the database is sound.
+pub(crate) async fn quick_check(conn: &mut SqliteConnection) -> Result<Vec<String>, sqlx::Error> {
+    let lines: Vec<String> = sqlx::query_scalar("PRAGMA quick_check")
+        .fetch_all(conn)
+        .await?;
+    Ok(lines.into_iter().filter(|line| line != "ok").collect())
+}
+
+pub(crate) async fn migration_status(
+    conn: &mut SqliteConnection,
+) -> Result<MigrationStatus, sqlx::Error> {
+    let has_table: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations')",
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let recorded: Vec<(i64, bool, Vec<u8>)> = if has_table {
+        sqlx::query_as("SELECT version, success, checksum FROM _sqlx_migrations ORDER BY version")
+            .fetch_all(&mut *conn)
+            .await?
+    } else {
+        Vec::new()
+    };
+
+    let mut status = MigrationStatus::default();
+    for (version, success, checksum) in &recorded {
+        let shipped = MIGRATOR
+            .iter()
+            .find(|m| m.version == *version && !m.migration_type.is_down_migration());
+        match shipped {
+            None => status.unknown.push(*version),
+            Some(_) if !success => status.dirty.push(*version),
+            Some(m) if m.checksum.as_ref() != checksum.as_slice() => {
+                status.modified.push(*version);
+            }
+            Some(_) => status.applied.push(*version),
+        }
+    }
+    status.pending = MIGRATOR
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+        .map(|m| m.version)
+        .filter(|v| !recorded.iter().any(|(recorded, ..)| recorded == v))
+        .collect();
+    Ok(status)
+}
+
+impl fmt::Display for DoctorReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn list<T: fmt::Display>(
+            f: &mut fmt::Formatter<'_>,
+            label: &str,
+            items: &[T],
+        ) -> fmt::Result {
+            if items.is_empty() {
+                return Ok(());
+            }
+            writeln!(f, "  {label}:")?;
+            items.iter().try_for_each(|item| writeln!(f, "    {item}"))
+        }
+
+        writeln!(f, "Database: {}", self.path.display())?;
+
+        if self.integrity_errors.is_empty() {
+            writeln!(f, "Integrity: ok")?;
+        } else {
+            writeln!(f, "Integrity: {} problem(s)", self.integrity_errors.len())?;
+            list(f, "errors", &self.integrity_errors)?;
+        }
+
+        if self.foreign_key_violations.is_empty() {
+            writeln!(f, "Foreign keys: ok")?;
+        } else {
+            writeln!(
+                f,
+                "Foreign keys: {} violation(s)",
+                self.foreign_key_violations.len()
+            )?;
+            list(f, "violations", &self.foreign_key_violations)?;
+        }
+
+        let m = &self.migrations;
+        writeln!(
+            f,
+            "Migrations: {} applied, {} pending",
+            m.applied.len(),
+            m.pending.len()
+        )?;
+        list(f, "unknown to this version of the app", &m.unknown)?;
+        list(f, "changed since they were applied", &m.modified)?;
+        list(f, "partially applied", &m.dirty)?;
+
+        writeln!(f, "Backups: {}", self.backups.len())?;
+        let backups: Vec<_> = self.backups.iter().map(|p| p.display()).collect();
+        list(f, "files", &backups)?;
+
+        write!(
+            f,
+            "Status: {}",
+            if self.is_healthy() {
+                "healthy"
+            } else {
+                "needs attention"
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DB_FILE_NAME, PersonalDb};
+
+    #[tokio::test]
+    async fn fresh_database_is_healthy() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(DB_FILE_NAME);
+        PersonalDb::open(&path).await.unwrap().close().await;
+
+        let report = doctor(&path).await.unwrap();
+        assert!(report.is_healthy(), "{report}");
+        assert_eq!(report.migrations.applied.len(), MIGRATOR.iter().count());
+        assert!(report.migrations.pending.is_empty());
+    }
+
+    #[tokio::test]
+    async fn garbage_file_is_reported_not_returned() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(DB_FILE_NAME);
+        tokio::fs::write(&path, vec![0xAB; 8192]).await.unwrap();
+
+        let report = doctor(&path).await.unwrap();
+        assert!(!report.is_healthy());
+        assert!(!report.integrity_errors.is_empty());
+    }
+
+    #[tokio::test]
+    async fn missing_file_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = doctor(&dir.path().join(DB_FILE_NAME)).await.unwrap_err();
+        assert!(matches!(err, PersonalDbError::NotFound(_)));
+    }
+
+    #[tokio::test]
+    async fn flags_unknown_migrations() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(DB_FILE_NAME);
+        let db = PersonalDb::open(&path).await.unwrap();
+        sqlx::query(
+            "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) \
+             VALUES (29990101000000, 'future', 1, x'00', 0)",
+        )
+        .execute(db.pool())
+        .await
+        .unwrap();
+        db.close().await;
+
+        let report = doctor(&path).await.unwrap();
+        assert_eq!(report.migrations.unknown, vec![29990101000000]);
+        assert!(!report.is_healthy());
+    }
+}
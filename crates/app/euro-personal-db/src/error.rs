@@ -0,0 +1,53 @@
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Errors surfaced while opening, migrating, or inspecting the personal
+/// database.
+#[derive(Debug, Error)]
+pub enum PersonalDbError {
+    #[error("personal database not found: {0}")]
+    NotFound(PathBuf),
+
+    #[error("I/O error at {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("database error: {0}")]
+    Sqlx(#[from] sqlx::Error),
+
+    #[error("migration failed: {0}")]
+    Migrate(#[from] sqlx::migrate::MigrateError),
+
+    /// `PRAGMA quick_check` reported problems, so the database was left
+    /// untouched instead of being migrated.
+    #[error("personal database failed its integrity check: {0}")]
+    Corrupt(String),
+
+    /// The database records a migration this build doesn't know about,
+    /// typically because a newer version of the app has opened it.
+    #[error("personal database was migrated by a newer version of the app (migration {0})")]
+    NewerSchema(i64),
+
+    /// An applied migration's SQL no longer matches what shipped with this
+    /// build.
+    #[error("migration {0} was changed after it was applied")]
+    ChecksumMismatch(i64),
+
+    /// A migration started but never finished.
+    #[error("migration {0} was left partially applied")]
+    Dirty(i64),
+}
+
+pub type PersonalDbResult<T> = Result<T, PersonalDbError>;
+
+impl PersonalDbError {
+    pub(crate) fn io(path: impl Into<PathBuf>, source: std::io::Error) -> Self {
+        Self::Io {
+            path: path.into(),
+            source,
+        }
+    }
+}
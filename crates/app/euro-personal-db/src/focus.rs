@@ -0,0 +1,371 @@
+//! `focus_sessions` and `focus_session_activities`: the rows behind
+//! focus-session tracking. The state machine and the metrics live in
+//! `euro-focus`; this module only stores and reads back what it decides.
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use uuid::Uuid;
+
+use crate::db::PersonalDb;
+use crate::error::PersonalDbResult;
+
+/// One row of `focus_sessions`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FocusSessionRecord {
+    pub id: Uuid,
+    pub goal: String,
+    pub planned_secs: u32,
+    /// `running`, `paused`, `completed` or `cancelled`.
+    pub state: String,
+    /// Activity names counted as off-topic.
+    pub distractions: Vec<String>,
+    pub started_at: DateTime<Utc>,
+    /// When the pause in progress began.
+    pub paused_at: Option<DateTime<Utc>>,
+    /// Length of the finished pauses.
+    pub paused_secs: u32,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub recap: Option<String>,
+    /// The chat thread the recap was written in.
+    pub recap_thread_id: Option<Uuid>,
+}
+
+/// One row of `focus_session_activities`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FocusActivityRecord {
+    pub id: Uuid,
+    pub session_id: Uuid,
+    /// Display name of the activity, e.g. `Code` or `Youtube`.
+    pub name: String,
+    pub process_name: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+#[derive(sqlx::FromRow)]
+struct FocusSessionRow {
+    id: String,
+    goal: String,
+    planned_secs: i64,
+    state: String,
+    distractions: String,
+    started_at: String,
+    paused_at: Option<String>,
+    paused_secs: i64,
+    ended_at: Option<String>,
+    recap: Option<String>,
+    recap_thread_id: Option<String>,
+}
+
+impl FocusSessionRow {
+    /// `None` for a row this build can't read, which is skipped.
+    fn into_record(self) -> Option<FocusSessionRecord> {
+        Some(FocusSessionRecord {
+            id: Uuid::parse_str(&self.id).ok()?,
+            goal: self.goal,
+            planned_secs: u32::try_from(self.planned_secs).ok()?,
+            state: self.state,
+            distractions: serde_json::from_str(&self.distractions).ok()?,
+            started_at: parse_time(&self.started_at)?,
+            paused_at: parse_optional_time(self.paused_at.as_deref())?,
+            paused_secs: u32::try_from(self.paused_secs).ok()?,
+            ended_at: parse_optional_time(self.ended_at.as_deref())?,
+            recap: self.recap,
+            recap_thread_id: match self.recap_thread_id {
+                Some(id) => Some(Uuid::parse_str(&id).ok()?),
+                None => None,
+            },
+        })
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct FocusActivityRow {
+    id: String,
+    session_id: String,
+    name: String,
+    process_name: String,
+    started_at: String,
+    ended_at: Option<String>,
+}
+
+impl FocusActivityRow {
+    fn into_record(self) -> Option<FocusActivityRecord> {
+        Some(FocusActivityRecord {
+            id: Uuid::parse_str(&self.id).ok()?,
+            session_id: Uuid::parse_str(&self.session_id).ok()?,
+            name: self.name,
+            process_name: self.process_name,
+            started_at: parse_time(&self.started_at)?,
+            ended_at: parse_optional_time(self.ended_at.as_deref())?,
+        })
+    }
+}
+
+const SESSION_COLUMNS: &str = "id, goal, planned_secs, state, distractions, started_at, \
+                               paused_at, paused_secs, ended_at, recap, recap_thread_id";
+
+impl PersonalDb {
+    /// Insert `session`, or overwrite the row with its id.
+    pub async fn save_focus_session(&self, session: &FocusSessionRecord) -> PersonalDbResult<()> {
+        sqlx::query(
+            "INSERT INTO focus_sessions (id, goal, planned_secs, state, distractions, \
+             started_at, paused_at, paused_secs, ended_at, recap, recap_thread_id) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?) \
+             ON CONFLICT (id) DO UPDATE SET goal = excluded.goal, \
+             planned_secs = excluded.planned_secs, state = excluded.state, \
+             distractions = excluded.distractions, started_at = excluded.started_at, \
+             paused_at = excluded.paused_at, paused_secs = excluded.paused_secs, \
+             ended_at = excluded.ended_at, recap = excluded.recap, \
+             recap_thread_id = excluded.recap_thread_id",
+        )
+        .bind(session.id.to_string())
+        .bind(&session.goal)
+        .bind(i64::from(session.planned_secs))
+        .bind(&session.state)
+        .bind(serde_json::Value::from(session.distractions.clone()).to_string())
+        .bind(stamp(session.started_at))
+        .bind(session.paused_at.map(stamp))
+        .bind(i64::from(session.paused_secs))
+        .bind(session.ended_at.map(stamp))
+        .bind(session.recap.as_deref())
+        .bind(session.recap_thread_id.map(|id| id.to_string()))
+        .execute(self.pool())
+        .await?;
+        Ok(())
+    }
+
+    pub async fn focus_session(&self, id: Uuid) -> PersonalDbResult<Option<FocusSessionRecord>> {
+        let row: Option<FocusSessionRow> = sqlx::query_as(&format!(
+            "SELECT {SESSION_COLUMNS} FROM focus_sessions WHERE id = ?"
+        ))
+        .bind(id.to_string())
+        .fetch_optional(self.pool())
+        .await?;
+        Ok(row.and_then(FocusSessionRow::into_record))
+    }
+
+    /// The newest session still `running` or `paused`, if any.
+    pub async fn open_focus_session(&self) -> PersonalDbResult<Option<FocusSessionRecord>> {
+        let row: Option<FocusSessionRow> = sqlx::query_as(&format!(
+            "SELECT {SESSION_COLUMNS} FROM focus_sessions \
+             WHERE state IN ('running', 'paused') ORDER BY started_at DESC LIMIT 1"
+        ))
+        .fetch_optional(self.pool())
+        .await?;
+        Ok(row.and_then(FocusSessionRow::into_record))
+    }
+
+    /// Sessions newest first.
+    pub async fn list_focus_sessions(
+        &self,
+        limit: u32,
+        offset: u32,
+    ) -> PersonalDbResult<Vec<FocusSessionRecord>> {
+        let rows: Vec<FocusSessionRow> = sqlx::query_as(&format!(
+            "SELECT {SESSION_COLUMNS} FROM focus_sessions \
+             ORDER BY started_at DESC LIMIT ? OFFSET ?"
+        ))
+        .bind(i64::from(limit))
+        .bind(i64::from(offset))
+        .fetch_all(self.pool())
+        .await?;
+        Ok(rows
+            .into_iter()
+            .filter_map(FocusSessionRow::into_record)
+            .collect())
+    }
+
+    /// Delete a session and its activities. `false` when there was no such
+    /// session.
+    pub async fn delete_focus_session(&self, id: Uuid) -> PersonalDbResult<bool> {
+        let result = sqlx::query("DELETE FROM focus_sessions WHERE id = ?")
+            .bind(id.to_string())
+            .execute(self.pool())
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Close the session's stretch in progress, if any, at `ended_at` and
+    /// open `next`, if given, in one transaction.
+    pub async fn switch_focus_activity(
+        &self,
+        session_id: Uuid,
+        ended_at: DateTime<Utc>,
+        next: Option<&FocusActivityRecord>,
+    ) -> PersonalDbResult<()> {
+        let mut tx = self.pool().begin().await?;
+        sqlx::query(
+            "UPDATE focus_session_activities SET ended_at = ? \
+             WHERE session_id = ? AND ended_at IS NULL",
+        )
+        .bind(stamp(ended_at))
+        .bind(session_id.to_string())
+        .execute(&mut *tx)
+        .await?;
+        if let Some(next) = next {
+            sqlx::query(
+                "INSERT INTO focus_session_activities \
+                 (id, session_id, name, process_name, started_at, ended_at) \
+                 VALUES (?, ?, ?, ?, ?, ?)",
+            )
+            .bind(next.id.to_string())
+            .bind(next.session_id.to_string())
+            .bind(&next.name)
+            .bind(&next.process_name)
+            .bind(stamp(next.started_at))
+            .bind(next.ended_at.map(stamp))
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// The session's activity stretches, oldest first.
+    pub async fn focus_activities(
+        &self,
+        session_id: Uuid,
+    ) -> PersonalDbResult<Vec<FocusActivityRecord>> {
+        let rows: Vec<FocusActivityRow> = sqlx::query_as(
+            "SELECT id, session_id, name, process_name, started_at, ended_at \
+             FROM focus_session_activities WHERE session_id = ? ORDER BY started_at, id",
+        )
+        .bind(session_id.to_string())
+        .fetch_all(self.pool())
+        .await?;
+        Ok(rows
+            .into_iter()
+            .filter_map(FocusActivityRow::into_record)
+            .collect())
+    }
+}
+
+/// Fixed-width RFC 3339, so stored stamps sort as text.
+pub(crate) fn stamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+pub(crate) fn parse_time(text: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(text)
+        .ok()
+        .map(|at| at.to_utc())
+}
+
+/// `Some(None)` for a missing stamp, `None` for one that doesn't parse.
+fn parse_optional_time(text: Option<&str>) -> Option<Option<DateTime<Utc>>> {
+    match text {
+        Some(text) => parse_time(text).map(Some),
+        None => Some(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::DB_FILE_NAME;
+
+    fn at(minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 2, 9, minute, 0).unwrap()
+    }
+
+    fn session(state: &str, started_at: DateTime<Utc>) -> FocusSessionRecord {
+        FocusSessionRecord {
+            id: Uuid::now_v7(),
+            goal: "Write the report".to_string(),
+            planned_secs: 25 * 60,
+            state: state.to_string(),
+            distractions: vec!["youtube".to_string()],
+            started_at,
+            paused_at: None,
+            paused_secs: 0,
+            ended_at: None,
+            recap: None,
+            recap_thread_id: None,
+        }
+    }
+
+    fn activity(session_id: Uuid, name: &str, started_at: DateTime<Utc>) -> FocusActivityRecord {
+        FocusActivityRecord {
+            id: Uuid::now_v7(),
+            session_id,
+            name: name.to_string(),
+            process_name: name.to_lowercase(),
+            started_at,
+            ended_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn sessions_round_trip_and_list_newest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = PersonalDb::open(dir.path().join(DB_FILE_NAME))
+            .await
+            .unwrap();
+
+        let mut done = session("completed", at(0));
+        done.ended_at = Some(at(25));
+        done.recap = Some("Went well.".to_string());
+        done.recap_thread_id = Some(Uuid::now_v7());
+        db.save_focus_session(&done).await.unwrap();
+        let mut open = session("running", at(30));
+        db.save_focus_session(&open).await.unwrap();
+
+        assert_eq!(db.focus_session(done.id).await.unwrap(), Some(done.clone()));
+        assert_eq!(db.open_focus_session().await.unwrap(), Some(open.clone()));
+        let ids: Vec<_> = db
+            .list_focus_sessions(10, 0)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|s| s.id)
+            .collect();
+        assert_eq!(ids, [open.id, done.id]);
+
+        open.state = "cancelled".to_string();
+        open.ended_at = Some(at(31));
+        db.save_focus_session(&open).await.unwrap();
+        assert_eq!(db.open_focus_session().await.unwrap(), None);
+        assert_eq!(db.list_focus_sessions(1, 1).await.unwrap(), [done]);
+    }
+
+    #[tokio::test]
+    async fn switching_activities_closes_the_open_stretch() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = PersonalDb::open(dir.path().join(DB_FILE_NAME))
+            .await
+            .unwrap();
+        let focus = session("running", at(0));
+        db.save_focus_session(&focus).await.unwrap();
+
+        let code = activity(focus.id, "Code", at(0));
+        db.switch_focus_activity(focus.id, at(0), Some(&code))
+            .await
+            .unwrap();
+        let youtube = activity(focus.id, "Youtube", at(10));
+        db.switch_focus_activity(focus.id, at(10), Some(&youtube))
+            .await
+            .unwrap();
+        db.switch_focus_activity(focus.id, at(12), None)
+            .await
+            .unwrap();
+
+        let stretches = db.focus_activities(focus.id).await.unwrap();
+        let spans: Vec<_> = stretches
+            .iter()
+            .map(|a| (a.name.as_str(), a.started_at, a.ended_at))
+            .collect();
+        assert_eq!(
+            spans,
+            [
+                ("Code", at(0), Some(at(10))),
+                ("Youtube", at(10), Some(at(12)))
+            ]
+        );
+
+        assert!(db.delete_focus_session(focus.id).await.unwrap());
+        assert!(db.focus_activities(focus.id).await.unwrap().is_empty());
+        assert!(!db.delete_focus_session(focus.id).await.unwrap());
+    }
+}
@@ -0,0 +1,47 @@
+//! The desktop app's local SQLite database.
+//!
+//! Local features keep their tables in one file in the app data directory
+//! (`personal_database.sqlite`). This crate owns that file's schema:
+//!
+//! - [`PersonalDb::open`] — open (or create) the database and bring it up
+//!   to date by running the versioned SQL migrations in `src/migrations`.
+//!   Before migrating an existing database it runs `PRAGMA quick_check`
+//!   and writes a backup next to it, so a bad migration never costs the
+//!   user their data.
+//! - [`doctor`] — read-only health check for the `eur db doctor` command:
+//!   full integrity check, foreign-key violations, migration drift, and
+//!   the backups on disk.
+//! - [`PersonalDb::maintain`] and [`PersonalDb::compact`] — incremental
+//!   vacuum and planner statistics on a schedule, and a full rewrite on
+//!   demand.
+//! - [`ActivityWriter`] — batches activity and OCR records into
+//!   `activity_log`, journaling them so a crash between flushes loses
+//!   nothing. [`PersonalDb::search_activity`] reads them back.
+//! - [`FocusSessionRecord`] and [`FocusActivityRecord`] — focus sessions
+//!   and the activities each one spanned, for `euro-focus`.
+//! - [`ScreenTimeBudgetRecord`] and [`ScreenTimeUsageRecord`] — daily
+//!   time budgets per app or site and the time spent in each activity per
+//!   day, for `euro-screen-time`.
+//!
+//! Adding a table means adding a new `<timestamp>_<name>.sql` file to
+//! `src/migrations`. Applied migrations are checksummed, so never edit one
+//! that has shipped.
+
+mod backup;
+mod db;
+mod doctor;
+mod error;
+mod focus;
+mod maintenance;
+mod screen_time;
+mod search;
+mod write_buffer;
+
+pub use backup::{MAX_BACKUPS, backup_dir, list_backups};
+pub use db::{DB_FILE_NAME, PersonalDb};
+pub use doctor::{DoctorReport, MigrationStatus, doctor};
+pub use error::{PersonalDbError, PersonalDbResult};
+pub use focus::{FocusActivityRecord, FocusSessionRecord};
+pub use maintenance::{CompactReport, MAINTENANCE_INTERVAL};
+pub use screen_time::{ScreenTimeBudgetRecord, ScreenTimeUsageRecord};
+pub use write_buffer::{ActivityRecord, ActivityWriter, WriteBufferConfig, journal_path};
@@ -0,0 +1,109 @@
+//! Keeping the database small and its query plans current.
+//!
+//! The database is created with `auto_vacuum = INCREMENTAL`, so pages
+//! freed by deletes can be handed back to the file system a batch at a
+//! time instead of by rewriting the whole file. [`PersonalDb::maintain`]
+//! does that and refreshes the planner's statistics; the app runs it
+//! every [`MAINTENANCE_INTERVAL`]. [`PersonalDb::compact`] is the full
+//! rewrite, for the settings page to offer on demand. It is also what
+//! switches a database created before incremental vacuum to it.
+
+use std::time::Duration;
+
+use crate::db::PersonalDb;
+use crate::error::PersonalDbResult;
+
+/// How often the app runs [`PersonalDb::maintain`].
+pub const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Database size before and after [`PersonalDb::compact`], in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompactReport {
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+impl PersonalDb {
+    /// Return free pages to the file system and let SQLite re-`ANALYZE`
+    /// the tables whose statistics have drifted. Cheap enough to run
+    /// while the app is in use.
+    pub async fn maintain(&self) -> PersonalDbResult<()> {
+        sqlx::query("PRAGMA incremental_vacuum")
+            .execute(self.pool())
+            .await?;
+        sqlx::query("PRAGMA optimize").execute(self.pool()).await?;
+        Ok(())
+    }
+
+    /// Rewrite the whole database without free space, refresh every
+    /// table's statistics and shrink the WAL. Blocks other writers for
+    /// as long as the rewrite takes.
+    pub async fn compact(&self) -> PersonalDbResult<CompactReport> {
+        let mut conn = self.pool().acquire().await?;
+        let bytes_before = size(&mut conn).await?;
+        // `auto_vacuum` only changes on an existing database through a
+        // full VACUUM, which this is.
+        sqlx::query("PRAGMA auto_vacuum = INCREMENTAL")
+            .execute(&mut *conn)
+            .await?;
+        sqlx::query("VACUUM").execute(&mut *conn).await?;
+        sqlx::query("ANALYZE").execute(&mut *conn).await?;
+        sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)")
+            .execute(&mut *conn)
+            .await?;
+        let bytes_after = size(&mut conn).await?;
+
+        tracing::info!(bytes_before, bytes_after, "Compacted personal database");
+        Ok(CompactReport {
+            bytes_before,
+            bytes_after,
+        })
+    }
+}
+
+async fn size(conn: &mut sqlx::SqliteConnection) -> PersonalDbResult<u64> {
+    let pages: i64 = sqlx::query_scalar("PRAGMA page_count")
+        .fetch_one(&mut *conn)
+        .await?;
+    let page_size: i64 = sqlx::query_scalar("PRAGMA page_size")
+        .fetch_one(&mut *conn)
+        .await?;
+    Ok(u64::try_from(pages * page_size).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DB_FILE_NAME;
+
+    #[tokio::test]
+    async fn compact_returns_deleted_space() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = PersonalDb::open(dir.path().join(DB_FILE_NAME))
+            .await
+            .unwrap();
+        let blob = "x".repeat(64 * 1024);
+        for key in 0..64 {
+            sqlx::query("INSERT INTO app_meta (key, value) VALUES (?, ?)")
+                .bind(format!("filler-{key}"))
+                .bind(&blob)
+                .execute(db.pool())
+                .await
+                .unwrap();
+        }
+        sqlx::query("DELETE FROM app_meta WHERE key LIKE 'filler-%'")
+            .execute(db.pool())
+            .await
+            .unwrap();
+
+        db.maintain().await.unwrap();
+        let report = db.compact().await.unwrap();
+        assert!(report.bytes_after < 1024 * 1024, "{report:?}");
+
+        let auto_vacuum: i64 = sqlx::query_scalar("PRAGMA auto_vacuum")
+            .fetch_one(db.pool())
+            .await
+            .unwrap();
+        assert_eq!(auto_vacuum, 2, "incremental");
+    }
+}
@@ -0,0 +1,14 @@
+-- Baseline schema for the desktop app's personal database.
+--
+-- Local features add their tables in later migrations. `app_meta` is a
+-- small key/value table for the database's own bookkeeping, so there's
+-- somewhere to record facts about the store (e.g. which install created
+-- it) without inventing a table per value.
+
+CREATE TABLE app_meta (
+    key         TEXT PRIMARY KEY NOT NULL,
+    value       TEXT NOT NULL,
+    updated_at  TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+) STRICT;
+
+INSERT INTO app_meta (key, value) VALUES ('created_at', strftime('%Y-%m-%dT%H:%M:%fZ', 'now'));
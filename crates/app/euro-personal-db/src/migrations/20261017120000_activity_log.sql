@@ -0,0 +1,15 @@
+-- Activity and OCR records captured on this device.
+--
+-- Rows arrive in batches through the write-behind buffer, which journals
+-- them to a file first and may replay that journal after a crash. `id`
+-- is generated by the app, so a replayed row is ignored instead of
+-- duplicated. `payload` is the record's JSON, shaped by its `kind`.
+
+CREATE TABLE activity_log (
+    id           TEXT PRIMARY KEY NOT NULL,
+    kind         TEXT NOT NULL,
+    recorded_at  TEXT NOT NULL,
+    payload      TEXT NOT NULL
+) STRICT;
+
+CREATE INDEX activity_log_recorded_at ON activity_log (recorded_at);
@@ -0,0 +1,37 @@
+-- Focus sessions: timed work intervals the user starts, pauses and
+-- stops, and the activities they were in while one ran.
+--
+-- The app keeps at most one session `running` or `paused` at a time.
+-- `paused_secs` adds up finished pauses; `paused_at` marks the pause in
+-- progress. `distractions` is the JSON array of activity names counted
+-- as off-topic for the session.
+
+CREATE TABLE focus_sessions (
+    id               TEXT PRIMARY KEY NOT NULL,
+    goal             TEXT NOT NULL,
+    planned_secs     INTEGER NOT NULL,
+    state            TEXT NOT NULL,
+    distractions     TEXT NOT NULL,
+    started_at       TEXT NOT NULL,
+    paused_at        TEXT,
+    paused_secs      INTEGER NOT NULL DEFAULT 0,
+    ended_at         TEXT,
+    recap            TEXT,
+    recap_thread_id  TEXT
+) STRICT;
+
+CREATE INDEX focus_sessions_started_at ON focus_sessions (started_at);
+
+-- One row per stretch of a session spent in one activity. The stretch in
+-- progress has no `ended_at`.
+CREATE TABLE focus_session_activities (
+    id            TEXT PRIMARY KEY NOT NULL,
+    session_id    TEXT NOT NULL REFERENCES focus_sessions (id) ON DELETE CASCADE,
+    name          TEXT NOT NULL,
+    process_name  TEXT NOT NULL,
+    started_at    TEXT NOT NULL,
+    ended_at      TEXT
+) STRICT;
+
+CREATE INDEX focus_session_activities_session_id
+    ON focus_session_activities (session_id, started_at);
@@ -0,0 +1,34 @@
+-- Screen-time budgets: a daily allowance of time per app or site, the
+-- time actually spent in each activity per day, and the nudges already
+-- sent so none goes out twice.
+--
+-- `target` matches an activity's name or process name, ignoring case.
+-- Days are the user's local calendar days, as `YYYY-MM-DD`.
+
+CREATE TABLE screen_time_budgets (
+    id          TEXT PRIMARY KEY NOT NULL,
+    target      TEXT NOT NULL,
+    daily_secs  INTEGER NOT NULL,
+    enabled     INTEGER NOT NULL DEFAULT 1,
+    created_at  TEXT NOT NULL
+) STRICT;
+
+-- Seconds spent in each activity per day, added to as time passes.
+CREATE TABLE screen_time_usage (
+    day           TEXT NOT NULL,
+    name          TEXT NOT NULL,
+    process_name  TEXT NOT NULL,
+    secs          INTEGER NOT NULL,
+    PRIMARY KEY (day, name, process_name)
+) STRICT;
+
+-- One row per nudge sent: `budget` with the budget id for an exceeded
+-- budget on `day`, `weekly` with an empty subject for the report on the
+-- week starting `day`.
+CREATE TABLE screen_time_nudges (
+    kind     TEXT NOT NULL,
+    subject  TEXT NOT NULL,
+    day      TEXT NOT NULL,
+    sent_at  TEXT NOT NULL,
+    PRIMARY KEY (kind, subject, day)
+) STRICT;
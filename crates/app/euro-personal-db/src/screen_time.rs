@@ -0,0 +1,279 @@
+//! `screen_time_budgets`, `screen_time_usage` and `screen_time_nudges`:
+//! the rows behind screen-time budgets. Matching activities to budgets and
+//! deciding when to nudge live in `euro-screen-time`.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use uuid::Uuid;
+
+use crate::db::PersonalDb;
+use crate::error::PersonalDbResult;
+use crate::focus::{parse_time, stamp};
+
+/// One row of `screen_time_budgets`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScreenTimeBudgetRecord {
+    pub id: Uuid,
+    /// Activity name or process name the budget covers.
+    pub target: String,
+    pub daily_secs: u32,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One row of `screen_time_usage`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScreenTimeUsageRecord {
+    /// Local calendar day.
+    pub day: NaiveDate,
+    pub name: String,
+    pub process_name: String,
+    pub secs: u32,
+}
+
+#[derive(sqlx::FromRow)]
+struct BudgetRow {
+    id: String,
+    target: String,
+    daily_secs: i64,
+    enabled: bool,
+    created_at: String,
+}
+
+impl BudgetRow {
+    /// `None` for a row this build can't read, which is skipped.
+    fn into_record(self) -> Option<ScreenTimeBudgetRecord> {
+        Some(ScreenTimeBudgetRecord {
+            id: Uuid::parse_str(&self.id).ok()?,
+            target: self.target,
+            daily_secs: u32::try_from(self.daily_secs).ok()?,
+            enabled: self.enabled,
+            created_at: parse_time(&self.created_at)?,
+        })
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct UsageRow {
+    day: String,
+    name: String,
+    process_name: String,
+    secs: i64,
+}
+
+impl UsageRow {
+    fn into_record(self) -> Option<ScreenTimeUsageRecord> {
+        Some(ScreenTimeUsageRecord {
+            day: parse_day(&self.day)?,
+            name: self.name,
+            process_name: self.process_name,
+            secs: u32::try_from(self.secs).ok()?,
+        })
+    }
+}
+
+impl PersonalDb {
+    /// Insert `budget`, or overwrite the row with its id.
+    pub async fn save_screen_time_budget(
+        &self,
+        budget: &ScreenTimeBudgetRecord,
+    ) -> PersonalDbResult<()> {
+        sqlx::query(
+            "INSERT INTO screen_time_budgets (id, target, daily_secs, enabled, created_at) \
+             VALUES (?, ?, ?, ?, ?) \
+             ON CONFLICT (id) DO UPDATE SET target = excluded.target, \
+             daily_secs = excluded.daily_secs, enabled = excluded.enabled",
+        )
+        .bind(budget.id.to_string())
+        .bind(&budget.target)
+        .bind(i64::from(budget.daily_secs))
+        .bind(budget.enabled)
+        .bind(stamp(budget.created_at))
+        .execute(self.pool())
+        .await?;
+        Ok(())
+    }
+
+    pub async fn screen_time_budget(
+        &self,
+        id: Uuid,
+    ) -> PersonalDbResult<Option<ScreenTimeBudgetRecord>> {
+        let row: Option<BudgetRow> = sqlx::query_as(
+            "SELECT id, target, daily_secs, enabled, created_at \
+             FROM screen_time_budgets WHERE id = ?",
+        )
+        .bind(id.to_string())
+        .fetch_optional(self.pool())
+        .await?;
+        Ok(row.and_then(BudgetRow::into_record))
+    }
+
+    /// Every budget, oldest first.
+    pub async fn screen_time_budgets(&self) -> PersonalDbResult<Vec<ScreenTimeBudgetRecord>> {
+        let rows: Vec<BudgetRow> = sqlx::query_as(
+            "SELECT id, target, daily_secs, enabled, created_at \
+             FROM screen_time_budgets ORDER BY created_at, id",
+        )
+        .fetch_all(self.pool())
+        .await?;
+        Ok(rows
+            .into_iter()
+            .filter_map(BudgetRow::into_record)
+            .collect())
+    }
+
+    /// `false` when there was no such budget.
+    pub async fn delete_screen_time_budget(&self, id: Uuid) -> PersonalDbResult<bool> {
+        let result = sqlx::query("DELETE FROM screen_time_budgets WHERE id = ?")
+            .bind(id.to_string())
+            .execute(self.pool())
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Add `secs` to the time spent in an activity on `day`.
+    pub async fn add_screen_time(
+        &self,
+        day: NaiveDate,
+        name: &str,
+        process_name: &str,
+        secs: u32,
+    ) -> PersonalDbResult<()> {
+        sqlx::query(
+            "INSERT INTO screen_time_usage (day, name, process_name, secs) \
+             VALUES (?, ?, ?, ?) \
+             ON CONFLICT (day, name, process_name) DO UPDATE SET secs = secs + excluded.secs",
+        )
+        .bind(format_day(day))
+        .bind(name)
+        .bind(process_name)
+        .bind(i64::from(secs))
+        .execute(self.pool())
+        .await?;
+        Ok(())
+    }
+
+    /// Time per activity per day from `first` through `last`, oldest day
+    /// first.
+    pub async fn screen_time_usage(
+        &self,
+        first: NaiveDate,
+        last: NaiveDate,
+    ) -> PersonalDbResult<Vec<ScreenTimeUsageRecord>> {
+        let rows: Vec<UsageRow> = sqlx::query_as(
+            "SELECT day, name, process_name, secs FROM screen_time_usage \
+             WHERE day BETWEEN ? AND ? ORDER BY day, name, process_name",
+        )
+        .bind(format_day(first))
+        .bind(format_day(last))
+        .fetch_all(self.pool())
+        .await?;
+        Ok(rows.into_iter().filter_map(UsageRow::into_record).collect())
+    }
+
+    /// Record that the `kind` nudge about `subject` for `day` went out.
+    /// `false` when it already had, so the caller sends nothing.
+    pub async fn claim_screen_time_nudge(
+        &self,
+        kind: &str,
+        subject: &str,
+        day: NaiveDate,
+        sent_at: DateTime<Utc>,
+    ) -> PersonalDbResult<bool> {
+        let result = sqlx::query(
+            "INSERT OR IGNORE INTO screen_time_nudges (kind, subject, day, sent_at) \
+             VALUES (?, ?, ?, ?)",
+        )
+        .bind(kind)
+        .bind(subject)
+        .bind(format_day(day))
+        .bind(stamp(sent_at))
+        .execute(self.pool())
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+fn format_day(day: NaiveDate) -> String {
+    day.format("%Y-%m-%d").to_string()
+}
+
+fn parse_day(text: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DB_FILE_NAME;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, d).unwrap()
+    }
+
+    #[tokio::test]
+    async fn usage_adds_up_per_day_and_activity() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = PersonalDb::open(dir.path().join(DB_FILE_NAME))
+            .await
+            .unwrap();
+
+        db.add_screen_time(day(2), "Youtube", "firefox", 60)
+            .await
+            .unwrap();
+        db.add_screen_time(day(2), "Youtube", "firefox", 30)
+            .await
+            .unwrap();
+        db.add_screen_time(day(3), "Code", "code", 120)
+            .await
+            .unwrap();
+        db.add_screen_time(day(9), "Code", "code", 5).await.unwrap();
+
+        let usage = db.screen_time_usage(day(2), day(8)).await.unwrap();
+        let rows: Vec<_> = usage
+            .iter()
+            .map(|u| (u.day, u.name.as_str(), u.secs))
+            .collect();
+        assert_eq!(rows, [(day(2), "Youtube", 90), (day(3), "Code", 120)]);
+    }
+
+    #[tokio::test]
+    async fn budgets_round_trip_and_nudges_go_out_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = PersonalDb::open(dir.path().join(DB_FILE_NAME))
+            .await
+            .unwrap();
+
+        let mut budget = ScreenTimeBudgetRecord {
+            id: Uuid::now_v7(),
+            target: "youtube".to_string(),
+            daily_secs: 30 * 60,
+            enabled: true,
+            created_at: parse_time("2026-03-02T09:00:00Z").unwrap(),
+        };
+        db.save_screen_time_budget(&budget).await.unwrap();
+        budget.enabled = false;
+        db.save_screen_time_budget(&budget).await.unwrap();
+        assert_eq!(db.screen_time_budgets().await.unwrap(), [budget.clone()]);
+
+        let subject = budget.id.to_string();
+        let now = Utc::now();
+        assert!(
+            db.claim_screen_time_nudge("budget", &subject, day(2), now)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !db.claim_screen_time_nudge("budget", &subject, day(2), now)
+                .await
+                .unwrap()
+        );
+        assert!(
+            db.claim_screen_time_nudge("budget", &subject, day(3), now)
+                .await
+                .unwrap()
+        );
+
+        assert!(db.delete_screen_time_budget(budget.id).await.unwrap());
+        assert_eq!(db.screen_time_budget(budget.id).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,94 @@
+//! Reading `activity_log` back, for the timeline search scripts use.
+
+use chrono::DateTime;
+use uuid::Uuid;
+
+use crate::db::PersonalDb;
+use crate::error::PersonalDbResult;
+use crate::write_buffer::ActivityRecord;
+
+impl PersonalDb {
+    /// The newest records, at most `limit`, whose payload contains `text`.
+    /// Matching ignores ASCII case; an empty `text` matches everything.
+    pub async fn search_activity(
+        &self,
+        text: &str,
+        limit: u32,
+    ) -> PersonalDbResult<Vec<ActivityRecord>> {
+        let pattern = format!("%{}%", escape_like(text));
+        let rows: Vec<(String, String, String, String)> = sqlx::query_as(
+            "SELECT id, kind, recorded_at, payload FROM activity_log \
+             WHERE payload LIKE ? ESCAPE '\\' \
+             ORDER BY recorded_at DESC LIMIT ?",
+        )
+        .bind(pattern)
+        .bind(i64::from(limit))
+        .fetch_all(self.pool())
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|(id, kind, recorded_at, payload)| {
+                let record = ActivityRecord {
+                    id: Uuid::parse_str(&id).ok()?,
+                    kind,
+                    recorded_at: DateTime::parse_from_rfc3339(&recorded_at).ok()?.to_utc(),
+                    payload: serde_json::from_str(&payload).ok()?,
+                };
+                Some(record)
+            })
+            .collect())
+    }
+}
+
+fn escape_like(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{ActivityWriter, DB_FILE_NAME, WriteBufferConfig};
+
+    #[tokio::test]
+    async fn search_matches_payload_text_newest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = PersonalDb::open(dir.path().join(DB_FILE_NAME))
+            .await
+            .unwrap();
+        let config = WriteBufferConfig {
+            flush_interval: Duration::from_secs(3600),
+            max_buffered: 1,
+        };
+        let writer = ActivityWriter::open(db.clone(), config).await.unwrap();
+        for (kind, text) in [
+            ("focus", "Quarterly report.xlsx"),
+            ("ocr", "the REPORT is due friday"),
+            ("ocr", "100% done_now"),
+        ] {
+            let record = ActivityRecord::new(kind, serde_json::json!({ "text": text }));
+            writer.record(record).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        let hits = db.search_activity("report", 10).await.unwrap();
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].kind, "ocr");
+        assert_eq!(hits[1].kind, "focus");
+
+        assert_eq!(db.search_activity("report", 1).await.unwrap().len(), 1);
+        // `%` and `_` are matched literally.
+        assert_eq!(db.search_activity("0% done_", 10).await.unwrap().len(), 1);
+        assert!(db.search_activity("_now%", 10).await.unwrap().is_empty());
+        assert_eq!(db.search_activity("", 10).await.unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,319 @@
+//! Write-behind buffer for activity and OCR records.
+//!
+//! Capture produces a record every few seconds, and committing each one
+//! on its own costs a WAL append and an fsync apiece. [`ActivityWriter`]
+//! keeps records in memory instead and writes them to `activity_log` in
+//! one transaction once [`WriteBufferConfig::max_buffered`] have
+//! accumulated or [`WriteBufferConfig::flush_interval`] has passed,
+//! whichever comes first.
+//!
+//! Every record is appended to a journal file next to the database
+//! before it is buffered, and the journal is emptied once its records
+//! have been committed. Opening the writer replays whatever a crash left
+//! in the journal. The journal isn't fsynced per record, that being the
+//! write it exists to avoid, so it survives the app crashing but not the
+//! machine losing power. Rows are keyed by the record's id, so a record
+//! that was committed just before the crash is not inserted twice.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::db::PersonalDb;
+use crate::error::{PersonalDbError, PersonalDbResult};
+
+/// One row of `activity_log`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActivityRecord {
+    pub id: Uuid,
+    /// What the record is, e.g. `focus` or `ocr`. Decides the shape of
+    /// `payload`.
+    pub kind: String,
+    pub recorded_at: DateTime<Utc>,
+    pub payload: serde_json::Value,
+}
+
+impl ActivityRecord {
+    /// A record of `kind`, stamped now.
+    pub fn new(kind: impl Into<String>, payload: serde_json::Value) -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            kind: kind.into(),
+            recorded_at: Utc::now(),
+            payload,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriteBufferConfig {
+    /// Longest a record waits in memory before it is committed.
+    pub flush_interval: Duration,
+    /// Buffered records that trigger a flush without waiting for the
+    /// interval.
+    pub max_buffered: usize,
+}
+
+impl Default for WriteBufferConfig {
+    fn default() -> Self {
+        Self {
+            flush_interval: Duration::from_secs(5),
+            max_buffered: 256,
+        }
+    }
+}
+
+/// Journal of the records not yet committed to the database at
+/// `db_path`.
+pub fn journal_path(db_path: &Path) -> PathBuf {
+    let mut name = db_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".activity-journal");
+    db_path.with_file_name(name)
+}
+
+/// Batches [`ActivityRecord`]s into `activity_log`. Cheap to clone;
+/// clones share the buffer.
+#[derive(Clone)]
+pub struct ActivityWriter {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    db: PersonalDb,
+    config: WriteBufferConfig,
+    journal_path: PathBuf,
+    state: Mutex<State>,
+}
+
+struct State {
+    buffer: Vec<ActivityRecord>,
+    journal: tokio::fs::File,
+}
+
+impl ActivityWriter {
+    /// Replay any journal a crash left behind, then start buffering. The
+    /// periodic flush runs on the current tokio runtime until every clone
+    /// of the writer is dropped.
+    pub async fn open(db: PersonalDb, config: WriteBufferConfig) -> PersonalDbResult<Self> {
+        let journal_path = journal_path(db.path());
+        let leftover = read_journal(&journal_path).await?;
+        if !leftover.is_empty() {
+            insert_batch(&db, &leftover).await?;
+            tracing::info!(
+                records = leftover.len(),
+                "Replayed activity records journaled before the last exit"
+            );
+        }
+
+        let journal = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&journal_path)
+            .await
+            .map_err(|e| PersonalDbError::io(&journal_path, e))?;
+        journal
+            .set_len(0)
+            .await
+            .map_err(|e| PersonalDbError::io(&journal_path, e))?;
+
+        let inner = Arc::new(Inner {
+            db,
+            config,
+            journal_path,
+            state: Mutex::new(State {
+                buffer: Vec::new(),
+                journal,
+            }),
+        });
+        tokio::spawn(flush_periodically(Arc::downgrade(&inner)));
+        Ok(Self { inner })
+    }
+
+    /// Journal `record` and buffer it, flushing if the buffer is full.
+    /// A failed flush keeps the records for the next attempt.
+    pub async fn record(&self, record: ActivityRecord) -> PersonalDbResult<()> {
+        let journal_path = &self.inner.journal_path;
+        let mut line = serde_json::to_vec(&record)
+            .map_err(|e| PersonalDbError::io(journal_path, std::io::Error::other(e)))?;
+        line.push(b'\n');
+
+        let mut state = self.inner.state.lock().await;
+        state
+            .journal
+            .write_all(&line)
+            .await
+            .map_err(|e| PersonalDbError::io(journal_path, e))?;
+        state
+            .journal
+            .flush()
+            .await
+            .map_err(|e| PersonalDbError::io(journal_path, e))?;
+        state.buffer.push(record);
+
+        if state.buffer.len() >= self.inner.config.max_buffered {
+            self.inner.flush(&mut state).await?;
+        }
+        Ok(())
+    }
+
+    /// Commit everything buffered now, e.g. before the app exits.
+    pub async fn flush(&self) -> PersonalDbResult<()> {
+        let mut state = self.inner.state.lock().await;
+        self.inner.flush(&mut state).await
+    }
+
+    /// Records waiting to be committed.
+    pub async fn buffered(&self) -> usize {
+        self.inner.state.lock().await.buffer.len()
+    }
+}
+
+impl Inner {
+    async fn flush(&self, state: &mut State) -> PersonalDbResult<()> {
+        if state.buffer.is_empty() {
+            return Ok(());
+        }
+        insert_batch(&self.db, &state.buffer).await?;
+        tracing::trace!(records = state.buffer.len(), "Flushed activity records");
+        state.buffer.clear();
+        state
+            .journal
+            .set_len(0)
+            .await
+            .map_err(|e| PersonalDbError::io(&self.journal_path, e))
+    }
+}
+
+async fn flush_periodically(inner: Weak<Inner>) {
+    let Some(period) = inner.upgrade().map(|inner| inner.config.flush_interval) else {
+        return;
+    };
+    let mut ticks = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+    ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        ticks.tick().await;
+        let Some(inner) = inner.upgrade() else {
+            return;
+        };
+        let mut state = inner.state.lock().await;
+        if let Err(e) = inner.flush(&mut state).await {
+            tracing::warn!("Failed to flush activity records: {e}");
+        }
+    }
+}
+
+async fn insert_batch(db: &PersonalDb, records: &[ActivityRecord]) -> PersonalDbResult<()> {
+    let mut tx = db.pool().begin().await?;
+    for record in records {
+        sqlx::query(
+            "INSERT OR IGNORE INTO activity_log (id, kind, recorded_at, payload) \
+             VALUES (?, ?, ?, ?)",
+        )
+        .bind(record.id.to_string())
+        .bind(&record.kind)
+        .bind(record.recorded_at.to_rfc3339())
+        .bind(record.payload.to_string())
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+/// The records in the journal at `path`. A line that doesn't parse, such
+/// as one cut short by the crash, is skipped.
+async fn read_journal(path: &Path) -> PersonalDbResult<Vec<ActivityRecord>> {
+    let text = match tokio::fs::read_to_string(path).await {
+        Ok(text) => text,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(PersonalDbError::io(path, e)),
+    };
+    let mut records = Vec::new();
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        match serde_json::from_str(line) {
+            Ok(record) => records.push(record),
+            Err(e) => tracing::warn!("Skipping unreadable activity journal line: {e}"),
+        }
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DB_FILE_NAME;
+
+    async fn count(db: &PersonalDb) -> i64 {
+        sqlx::query_scalar("SELECT COUNT(*) FROM activity_log")
+            .fetch_one(db.pool())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn records_are_committed_once_the_buffer_fills() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = PersonalDb::open(dir.path().join(DB_FILE_NAME))
+            .await
+            .unwrap();
+        let config = WriteBufferConfig {
+            flush_interval: Duration::from_secs(3600),
+            max_buffered: 3,
+        };
+        let writer = ActivityWriter::open(db.clone(), config).await.unwrap();
+
+        for n in 0..2 {
+            let record = ActivityRecord::new("ocr", serde_json::json!({ "n": n }));
+            writer.record(record).await.unwrap();
+        }
+        assert_eq!(count(&db).await, 0);
+        assert_eq!(writer.buffered().await, 2);
+
+        let record = ActivityRecord::new("ocr", serde_json::json!({ "n": 2 }));
+        writer.record(record).await.unwrap();
+        assert_eq!(count(&db).await, 3);
+        assert_eq!(writer.buffered().await, 0);
+    }
+
+    #[tokio::test]
+    async fn a_crash_loses_nothing_journaled() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = PersonalDb::open(dir.path().join(DB_FILE_NAME))
+            .await
+            .unwrap();
+        let config = WriteBufferConfig {
+            flush_interval: Duration::from_secs(3600),
+            max_buffered: 100,
+        };
+
+        let writer = ActivityWriter::open(db.clone(), config).await.unwrap();
+        let record = ActivityRecord::new("focus", serde_json::json!({ "app": "editor" }));
+        writer.record(record.clone()).await.unwrap();
+        // Dropped without a flush, as a crash would leave it.
+        drop(writer);
+        assert_eq!(count(&db).await, 0);
+
+        // A torn final line is skipped, not fatal.
+        let path = journal_path(db.path());
+        let mut journal = tokio::fs::read_to_string(&path).await.unwrap();
+        journal.push_str("{\"id\":");
+        tokio::fs::write(&path, journal).await.unwrap();
+
+        let writer = ActivityWriter::open(db.clone(), config).await.unwrap();
+        assert_eq!(count(&db).await, 1);
+        let kind: String = sqlx::query_scalar("SELECT kind FROM activity_log WHERE id = ?")
+            .bind(record.id.to_string())
+            .fetch_one(db.pool())
+            .await
+            .unwrap();
+        assert_eq!(kind, "focus");
+        assert_eq!(writer.buffered().await, 0);
+        assert!(tokio::fs::read(&path).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,57 @@
+use thiserror::Error;
+
+use crate::manifest::Capability;
+use crate::usage::Limit;
+
+pub type PluginResult<T> = std::result::Result<T, PluginError>;
+
+#[derive(Debug, Error)]
+pub enum PluginError {
+    #[error("Plugin storage: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid plugin manifest: {0}")]
+    InvalidManifest(String),
+
+    #[error("Plugin {0} is not installed")]
+    NotInstalled(String),
+
+    #[error("Plugin {0} is already installed")]
+    AlreadyInstalled(String),
+
+    #[error("Plugin {0} is not enabled")]
+    NotEnabled(String),
+
+    #[error("Version {new} is not newer than the installed {installed}")]
+    NotNewer {
+        installed: semver::Version,
+        new: semver::Version,
+    },
+
+    #[error("Capabilities not granted: {0:?}")]
+    CapabilitiesNotGranted(Vec<Capability>),
+
+    /// An executable plugin with no OS sandbox to run in, which the user
+    /// hasn't agreed to run without one.
+    #[error("No sandbox is available for this plugin on this machine")]
+    SandboxUnavailable,
+
+    #[error("Plugin failed to start: {0}")]
+    Spawn(String),
+
+    #[error("Plugin stopped responding: {0}")]
+    Transport(String),
+
+    #[error("Plugin call timed out")]
+    Timeout,
+
+    #[error("Plugin call cancelled")]
+    Cancelled,
+
+    #[error("Plugin went over its {0} limit")]
+    LimitExceeded(Limit),
+
+    /// The plugin answered with an error of its own.
+    #[error("{0}")]
+    Plugin(String),
+}
@@ -0,0 +1,96 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+use tokio_util::sync::CancellationToken;
+
+use crate::error::PluginResult;
+use crate::manifest::{Capability, Entry, PluginManifest};
+use crate::process::PluginProcess;
+use crate::protocol::Call;
+use crate::usage::{self, SharedUsage};
+use crate::wasm::WasmPlugin;
+
+/// A started plugin, whichever way it runs.
+pub(crate) struct Instance {
+    runtime: Runtime,
+    usage: SharedUsage,
+}
+
+enum Runtime {
+    Process(PluginProcess),
+    Wasm(WasmPlugin),
+}
+
+impl Instance {
+    pub async fn start(
+        manifest: &PluginManifest,
+        plugin_dir: &Path,
+        data_dir: &Path,
+        granted: &BTreeSet<Capability>,
+        allowed_hosts: &BTreeSet<String>,
+        allow_unsandboxed: bool,
+        usage: SharedUsage,
+    ) -> PluginResult<Self> {
+        let runtime = match &manifest.entry {
+            Entry::Command { command, args } => Runtime::Process(
+                PluginProcess::start(
+                    &manifest.id,
+                    &plugin_dir.join(command),
+                    args,
+                    plugin_dir,
+                    data_dir,
+                    granted,
+                    allow_unsandboxed,
+                )
+                .await?,
+            ),
+            Entry::Module { module } => Runtime::Wasm(
+                WasmPlugin::start(
+                    &manifest.id,
+                    &plugin_dir.join(module),
+                    data_dir,
+                    granted,
+                    allowed_hosts,
+                    usage.clone(),
+                )
+                .await?,
+            ),
+        };
+        Ok(Self { runtime, usage })
+    }
+
+    pub async fn call(
+        &self,
+        call: Call<'_>,
+        timeout: Duration,
+        cancel: Option<&CancellationToken>,
+    ) -> PluginResult<Value> {
+        let started = Instant::now();
+        let result = match &self.runtime {
+            Runtime::Process(process) => process.call(call, timeout, cancel).await,
+            Runtime::Wasm(module) => module.call(call, timeout, cancel).await,
+        };
+        let busy_ms = started.elapsed().as_millis() as u64;
+        usage::update(&self.usage, |usage| {
+            usage.calls += 1;
+            usage.busy_ms += busy_ms;
+        });
+        result
+    }
+
+    pub fn is_running(&self) -> bool {
+        match &self.runtime {
+            Runtime::Process(process) => process.is_running(),
+            Runtime::Wasm(module) => module.is_running(),
+        }
+    }
+
+    pub fn stop(&self) {
+        match &self.runtime {
+            Runtime::Process(process) => process.stop(),
+            Runtime::Wasm(module) => module.stop(),
+        }
+    }
+}
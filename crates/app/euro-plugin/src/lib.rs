@@ -0,0 +1,56 @@
+//! Third-party plugins for the desktop: context providers and tools that
+//! ship separately from the app.
+//!
+//! A plugin is a directory with an [`eurora-plugin.json`](MANIFEST_FILE)
+//! manifest naming it, its version, the [`Capability`]s it needs, the
+//! tools it offers and what to run: a WebAssembly component or an
+//! executable. The [`PluginRegistry`] installs, enables, disables,
+//! updates and uninstalls plugins, and starts each enabled one the first
+//! time it's needed.
+//!
+//! Plugins are installed disabled. Enabling one takes the user's consent
+//! to everything its manifest asks for, and an update that asks for more
+//! goes back to disabled until the user approves again.
+//!
+//! Components run in-process on a metered WASI runtime that only lets
+//! them reach the network through the hosts the user approved.
+//! Executables run as subprocesses in the OS sandbox described in
+//! [`sandbox`], which cuts them off from the network unless
+//! [`Capability::Network`] was granted. What each plugin has used is
+//! kept in its [`ResourceUsage`].
+//!
+//! # Protocol
+//!
+//! Components implement the `plugin` world in `wit/plugin.wit`.
+//! Executables get the same calls over stdin and stdout, one JSON object
+//! per line. Requests carry an `id`, a `method` and, for most methods,
+//! `params`:
+//!
+//! - `initialize` is sent first, with the protocol version, the granted
+//!   capabilities and the plugin's writable data directory.
+//! - `call_tool` runs one of the manifest's tools with `name` and
+//!   `arguments`.
+//! - `context` asks for text to add to the upcoming chat turn.
+//!
+//! The plugin answers each with `{"id": …, "result": …}` or
+//! `{"id": …, "error": {"message": …}}`, in any order. Whatever it
+//! writes to stderr ends up in the app's debug log.
+
+mod error;
+mod instance;
+mod manifest;
+mod process;
+mod protocol;
+mod registry;
+pub mod sandbox;
+mod usage;
+mod wasm;
+
+pub use error::{PluginError, PluginResult};
+pub use manifest::{
+    Capability, Entry, MANIFEST_FILE, PluginManifest, TOOL_NAME_SEPARATOR, ToolManifest,
+    split_tool_name,
+};
+pub use protocol::PROTOCOL_VERSION;
+pub use registry::{PluginInfo, PluginRegistry, ToolSummary};
+pub use usage::{Limit, ResourceUsage};
@@ -0,0 +1,327 @@
+use std::collections::{BTreeSet, HashSet};
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+#[cfg(feature = "specta")]
+use specta::Type;
+
+use crate::error::{PluginError, PluginResult};
+
+/// File at the root of every plugin directory describing it.
+pub const MANIFEST_FILE: &str = "eurora-plugin.json";
+
+/// Separates the plugin id from the tool name in the names tools are
+/// advertised under, e.g. `acme-jira__search_issues`.
+pub const TOOL_NAME_SEPARATOR: &str = "__";
+
+/// Longest advertised tool name most model providers accept.
+const MAX_TOOL_NAME_LEN: usize = 64;
+const MAX_PLUGIN_ID_LEN: usize = 32;
+
+/// Something a plugin asks to be allowed to do. The user grants all of a
+/// plugin's capabilities or none when enabling it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    /// Offer the tools listed in the manifest to the assistant.
+    Tools,
+    /// Add text to the context of every chat turn.
+    Context,
+    /// Reach the network. Without it the sandbox, where the platform has
+    /// one, cuts a subprocess off; a module can only fetch from its
+    /// `allowed_hosts`.
+    Network,
+}
+
+impl Capability {
+    /// The name plugins see in `initialize`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Tools => "tools",
+            Self::Context => "context",
+            Self::Network => "network",
+        }
+    }
+}
+
+/// What runs when a plugin starts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Entry {
+    /// A WebAssembly component built against `wit/plugin.wit`, run
+    /// in-process.
+    Module { module: PathBuf },
+    /// An executable speaking the JSON protocol, run as a sandboxed
+    /// subprocess.
+    Command {
+        command: PathBuf,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+}
+
+impl Entry {
+    /// The file to run, relative to the plugin directory.
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Module { module } => module,
+            Self::Command { command, .. } => command,
+        }
+    }
+
+    pub fn is_module(&self) -> bool {
+        matches!(self, Self::Module { .. })
+    }
+}
+
+/// A tool a plugin offers, as the model sees it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolManifest {
+    pub name: String,
+    pub description: String,
+    /// JSON Schema for the arguments.
+    #[serde(default = "empty_object_schema")]
+    pub parameters: Value,
+    /// Per-call timeout. Defaults to 30 seconds.
+    #[serde(default)]
+    pub timeout_ms: Option<u32>,
+}
+
+fn empty_object_schema() -> Value {
+    serde_json::json!({ "type": "object", "properties": {} })
+}
+
+/// The contents of [`MANIFEST_FILE`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginManifest {
+    /// Lowercase letters, digits and dashes; unique among installed
+    /// plugins.
+    pub id: String,
+    pub name: String,
+    pub version: semver::Version,
+    #[serde(default)]
+    pub description: String,
+    #[serde(flatten)]
+    pub entry: Entry,
+    #[serde(default)]
+    pub capabilities: BTreeSet<Capability>,
+    /// Hosts a module may fetch from with [`Capability::Network`], like
+    /// `api.example.com` or `*.example.com`. Subprocesses can't be held
+    /// to a list, so only modules have one.
+    #[serde(default)]
+    pub allowed_hosts: BTreeSet<String>,
+    #[serde(default)]
+    pub tools: Vec<ToolManifest>,
+}
+
+impl PluginManifest {
+    /// Read and validate the manifest in `dir`.
+    pub fn load(dir: &Path) -> PluginResult<Self> {
+        let raw = std::fs::read(dir.join(MANIFEST_FILE))?;
+        let manifest: Self = serde_json::from_slice(&raw)
+            .map_err(|e| PluginError::InvalidManifest(e.to_string()))?;
+        manifest.validate(dir)?;
+        Ok(manifest)
+    }
+
+    fn validate(&self, dir: &Path) -> PluginResult<()> {
+        let invalid = |reason: String| Err(PluginError::InvalidManifest(reason));
+
+        if !is_valid_id(&self.id) {
+            return invalid(format!(
+                "id `{}` must be 1-{MAX_PLUGIN_ID_LEN} lowercase letters, digits or dashes",
+                self.id
+            ));
+        }
+        let path = self.entry.path();
+        let inside_dir = path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+        if !inside_dir || path.as_os_str().is_empty() {
+            return invalid(format!(
+                "{} must be a relative path inside the plugin",
+                path.display()
+            ));
+        }
+        if !dir.join(path).is_file() {
+            return invalid(format!("{} not found", path.display()));
+        }
+
+        if !self.entry.is_module() && !self.allowed_hosts.is_empty() {
+            return invalid("allowed_hosts only applies to modules".into());
+        }
+        if self.entry.is_module()
+            && self.capabilities.contains(&Capability::Network)
+            && self.allowed_hosts.is_empty()
+        {
+            return invalid("modules with the `network` capability must list allowed_hosts".into());
+        }
+        if let Some(host) = self.allowed_hosts.iter().find(|host| !is_valid_host(host)) {
+            return invalid(format!("allowed host `{host}` is not a host name"));
+        }
+
+        if !self.tools.is_empty() && !self.capabilities.contains(&Capability::Tools) {
+            return invalid("tools are listed without the `tools` capability".into());
+        }
+        let mut seen = HashSet::new();
+        for tool in &self.tools {
+            let valid_name = !tool.name.is_empty()
+                && !tool.name.contains(TOOL_NAME_SEPARATOR)
+                && tool
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+            if !valid_name {
+                return invalid(format!("tool name `{}` is not allowed", tool.name));
+            }
+            if self.qualified_tool_name(&tool.name).len() > MAX_TOOL_NAME_LEN {
+                return invalid(format!("tool name `{}` is too long", tool.name));
+            }
+            if !seen.insert(tool.name.as_str()) {
+                return invalid(format!("tool `{}` is listed twice", tool.name));
+            }
+        }
+        Ok(())
+    }
+
+    /// The name `tool` is advertised to the model under.
+    pub fn qualified_tool_name(&self, tool: &str) -> String {
+        format!("{}{TOOL_NAME_SEPARATOR}{tool}", self.id)
+    }
+}
+
+fn is_valid_id(id: &str) -> bool {
+    (1..=MAX_PLUGIN_ID_LEN).contains(&id.len())
+        && !id.starts_with('-')
+        && id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+/// A lowercase host name, optionally with a leading `*.` for its
+/// subdomains.
+fn is_valid_host(pattern: &str) -> bool {
+    let host = pattern.strip_prefix("*.").unwrap_or(pattern);
+    !host.is_empty()
+        && host.split('.').all(|label| {
+            !label.is_empty()
+                && !label.starts_with('-')
+                && label
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        })
+}
+
+/// Split an advertised tool name into plugin id and tool name.
+pub fn split_tool_name(qualified: &str) -> Option<(&str, &str)> {
+    qualified.split_once(TOOL_NAME_SEPARATOR)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_plugin(dir: &Path, manifest: Value) {
+        std::fs::create_dir_all(dir.join("bin")).unwrap();
+        std::fs::write(dir.join("bin/plugin"), b"").unwrap();
+        std::fs::write(dir.join("plugin.wasm"), b"").unwrap();
+        std::fs::write(dir.join(MANIFEST_FILE), manifest.to_string()).unwrap();
+    }
+
+    fn manifest(overrides: Value) -> Value {
+        let mut manifest = serde_json::json!({
+            "id": "acme-jira",
+            "name": "Jira",
+            "version": "1.0.0",
+            "command": "bin/plugin",
+            "capabilities": ["tools"],
+            "tools": [{ "name": "search_issues", "description": "Search Jira." }],
+        });
+        for (key, value) in overrides.as_object().unwrap() {
+            manifest[key] = value.clone();
+        }
+        manifest
+    }
+
+    #[test]
+    fn a_valid_manifest_loads() {
+        let dir = tempfile::tempdir().unwrap();
+        write_plugin(dir.path(), manifest(serde_json::json!({})));
+
+        let loaded = PluginManifest::load(dir.path()).unwrap();
+        assert_eq!(loaded.version, semver::Version::new(1, 0, 0));
+        assert_eq!(
+            loaded.qualified_tool_name("search_issues"),
+            "acme-jira__search_issues"
+        );
+        assert_eq!(
+            split_tool_name("acme-jira__search_issues"),
+            Some(("acme-jira", "search_issues"))
+        );
+        assert!(!loaded.entry.is_module());
+    }
+
+    #[test]
+    fn modules_list_the_hosts_they_fetch_from() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut module = manifest(serde_json::json!({
+            "module": "plugin.wasm",
+            "capabilities": ["tools", "network"],
+            "allowed_hosts": ["api.atlassian.com", "*.atlassian.net"],
+        }));
+        module.as_object_mut().unwrap().remove("command");
+        write_plugin(dir.path(), module.clone());
+
+        let loaded = PluginManifest::load(dir.path()).unwrap();
+        assert!(loaded.entry.is_module());
+        assert_eq!(loaded.allowed_hosts.len(), 2);
+
+        for overrides in [
+            serde_json::json!({ "allowed_hosts": [] }),
+            serde_json::json!({ "allowed_hosts": ["https://api.atlassian.com"] }),
+            serde_json::json!({ "allowed_hosts": ["*"] }),
+        ] {
+            let mut invalid = module.clone();
+            for (key, value) in overrides.as_object().unwrap() {
+                invalid[key] = value.clone();
+            }
+            write_plugin(dir.path(), invalid);
+            assert!(
+                matches!(
+                    PluginManifest::load(dir.path()),
+                    Err(PluginError::InvalidManifest(_))
+                ),
+                "accepted {overrides}"
+            );
+        }
+    }
+
+    #[test]
+    fn manifests_are_checked() {
+        for overrides in [
+            serde_json::json!({ "id": "Acme Jira" }),
+            serde_json::json!({ "command": "../escape" }),
+            serde_json::json!({ "command": "/usr/bin/env" }),
+            serde_json::json!({ "command": "bin/missing" }),
+            serde_json::json!({ "capabilities": [] }),
+            serde_json::json!({ "allowed_hosts": ["api.atlassian.com"] }),
+            serde_json::json!({ "tools": [{ "name": "a__b", "description": "" }] }),
+            serde_json::json!({ "tools": [
+                { "name": "same", "description": "" },
+                { "name": "same", "description": "" },
+            ] }),
+        ] {
+            let dir = tempfile::tempdir().unwrap();
+            write_plugin(dir.path(), manifest(overrides.clone()));
+            assert!(
+                matches!(
+                    PluginManifest::load(dir.path()),
+                    Err(PluginError::InvalidManifest(_))
+                ),
+                "accepted {overrides}"
+            );
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use futures::StreamExt;
+use request_correlator::{RequestCorrelator, WaitError};
+use serde_json::Value;
+use tokio::io::AsyncWriteExt;
+use tokio::process::{Child, ChildStdin};
+use tokio_util::codec::{FramedRead, LinesCodec};
+use tokio_util::sync::CancellationToken;
+
+use crate::error::{PluginError, PluginResult};
+use crate::manifest::Capability;
+use crate::protocol::{Call, PROTOCOL_VERSION, Request, Response};
+use crate::sandbox::{self, Confinement};
+
+/// How long a plugin gets to answer `initialize`.
+const INITIALIZE_TIMEOUT: Duration = Duration::from_secs(10);
+/// Longest line a plugin may write; anything longer ends the process.
+const MAX_LINE_BYTES: usize = 4 * 1024 * 1024;
+/// Longest stderr line kept for the log.
+const MAX_LOG_LINE_BYTES: usize = 8 * 1024;
+
+/// One running plugin.
+pub(crate) struct PluginProcess {
+    id: String,
+    child: Mutex<Child>,
+    stdin: tokio::sync::Mutex<ChildStdin>,
+    pending: RequestCorrelator<u64, Value, String>,
+    next_id: AtomicU64,
+    exited: CancellationToken,
+}
+
+impl PluginProcess {
+    /// Start `program` from the plugin in `plugin_dir` and wait for it to
+    /// initialize.
+    pub async fn start(
+        id: &str,
+        program: &Path,
+        args: &[String],
+        plugin_dir: &Path,
+        data_dir: &Path,
+        granted: &BTreeSet<Capability>,
+        allow_unsandboxed: bool,
+    ) -> PluginResult<Self> {
+        std::fs::create_dir_all(data_dir)?;
+        let mut child = sandbox::command(
+            program,
+            args,
+            &Confinement {
+                plugin_dir,
+                data_dir,
+                network: granted.contains(&Capability::Network),
+                allow_unsandboxed,
+            },
+        )?
+        .spawn()
+        .map_err(|e| PluginError::Spawn(e.to_string()))?;
+
+        let (Some(stdin), Some(stdout), Some(stderr)) =
+            (child.stdin.take(), child.stdout.take(), child.stderr.take())
+        else {
+            return Err(PluginError::Spawn("stdio not piped".into()));
+        };
+
+        let pending = RequestCorrelator::new();
+        let exited = CancellationToken::new();
+        tokio::spawn(read_responses(
+            id.to_owned(),
+            FramedRead::new(stdout, LinesCodec::new_with_max_length(MAX_LINE_BYTES)),
+            pending.clone(),
+            exited.clone(),
+        ));
+        tokio::spawn(forward_logs(
+            id.to_owned(),
+            FramedRead::new(stderr, LinesCodec::new_with_max_length(MAX_LOG_LINE_BYTES)),
+        ));
+
+        let process = Self {
+            id: id.to_owned(),
+            child: Mutex::new(child),
+            stdin: tokio::sync::Mutex::new(stdin),
+            pending,
+            next_id: AtomicU64::new(1),
+            exited,
+        };
+        process
+            .call(
+                Call::Initialize {
+                    protocol_version: PROTOCOL_VERSION,
+                    granted,
+                    data_dir,
+                },
+                INITIALIZE_TIMEOUT,
+                None,
+            )
+            .await?;
+        tracing::info!(plugin = %process.id, "Plugin started");
+        Ok(process)
+    }
+
+    /// Send `call` and wait for the answer.
+    pub async fn call(
+        &self,
+        call: Call<'_>,
+        timeout: Duration,
+        cancel: Option<&CancellationToken>,
+    ) -> PluginResult<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let guard = self.pending.register(id);
+        // Checked after registering: an exit from here on fails the guard.
+        if !self.is_running() {
+            return Err(PluginError::Transport("plugin exited".into()));
+        }
+
+        let mut line = serde_json::to_vec(&Request { id, call })
+            .map_err(|e| PluginError::Transport(e.to_string()))?;
+        line.push(b'\n');
+        {
+            let mut stdin = self.stdin.lock().await;
+            stdin
+                .write_all(&line)
+                .await
+                .map_err(|e| PluginError::Transport(e.to_string()))?;
+            stdin
+                .flush()
+                .await
+                .map_err(|e| PluginError::Transport(e.to_string()))?;
+        }
+
+        let answer = match cancel {
+            Some(cancel) => guard.wait_cancellable(timeout, cancel).await,
+            None => guard.wait(timeout).await,
+        };
+        match answer {
+            Ok(Ok(value)) => Ok(value),
+            Ok(Err(message)) => Err(PluginError::Plugin(message)),
+            Err(WaitError::Timeout) => Err(PluginError::Timeout),
+            Err(WaitError::Cancelled) => Err(PluginError::Cancelled),
+            Err(WaitError::SenderDropped) => Err(PluginError::Transport("plugin exited".into())),
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        !self.exited.is_cancelled()
+    }
+
+    pub fn stop(&self) {
+        if let Ok(mut child) = self.child.lock()
+            && let Err(e) = child.start_kill()
+        {
+            tracing::debug!(plugin = %self.id, "Plugin already stopped: {e}");
+        }
+    }
+}
+
+impl Drop for PluginProcess {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Route answers to their callers until the plugin closes stdout, then
+/// fail whatever is still waiting.
+async fn read_responses(
+    id: String,
+    mut lines: FramedRead<tokio::process::ChildStdout, LinesCodec>,
+    pending: RequestCorrelator<u64, Value, String>,
+    exited: CancellationToken,
+) {
+    while let Some(line) = lines.next().await {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                tracing::warn!(plugin = %id, "Stopped reading from plugin: {e}");
+                break;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<Response>(&line) {
+            Ok(response) => pending.resolve(response.id, response.into_result()),
+            Err(e) => tracing::warn!(plugin = %id, "Ignoring malformed plugin message: {e}"),
+        }
+    }
+    tracing::info!(plugin = %id, "Plugin exited");
+    exited.cancel();
+    pending.shutdown_with(|_| "plugin exited".to_string());
+}
+
+async fn forward_logs(id: String, mut lines: FramedRead<tokio::process::ChildStderr, LinesCodec>) {
+    while let Some(line) = lines.next().await {
+        match line {
+            Ok(line) => tracing::debug!(plugin = %id, "{line}"),
+            // Over-long line: skipped by the codec, keep going.
+            Err(tokio_util::codec::LinesCodecError::MaxLineLengthExceeded) => {}
+            Err(_) => break,
+        }
+    }
+}
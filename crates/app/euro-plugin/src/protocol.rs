@@ -0,0 +1,104 @@
+//! Messages exchanged with a plugin process, one JSON object per line.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::manifest::Capability;
+
+/// Bumped when a message changes shape incompatibly. Sent in
+/// [`Call::Initialize`] so a plugin can refuse a host it doesn't know.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Host to plugin. Every request gets exactly one [`Response`] with the
+/// same `id`.
+#[derive(Debug, Serialize)]
+pub(crate) struct Request<'a> {
+    pub id: u64,
+    #[serde(flatten)]
+    pub call: Call<'a>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub(crate) enum Call<'a> {
+    /// First message after start.
+    Initialize {
+        protocol_version: u32,
+        granted: &'a BTreeSet<Capability>,
+        data_dir: &'a Path,
+    },
+    /// Run one of the manifest's tools. The result is handed to the model
+    /// as is.
+    CallTool { name: &'a str, arguments: &'a Value },
+    /// Text to add to the upcoming chat turn; `null` for none.
+    Context,
+}
+
+/// Plugin to host.
+#[derive(Debug, Deserialize)]
+pub(crate) struct Response {
+    pub id: u64,
+    #[serde(default)]
+    pub result: Value,
+    #[serde(default)]
+    pub error: Option<ResponseError>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ResponseError {
+    pub message: String,
+}
+
+impl Response {
+    pub fn into_result(self) -> Result<Value, String> {
+        match self.error {
+            Some(error) => Err(error.message),
+            None => Ok(self.result),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_carry_method_and_params() {
+        let arguments = serde_json::json!({ "query": "bug" });
+        let request = Request {
+            id: 7,
+            call: Call::CallTool {
+                name: "search_issues",
+                arguments: &arguments,
+            },
+        };
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({
+                "id": 7,
+                "method": "call_tool",
+                "params": { "name": "search_issues", "arguments": { "query": "bug" } },
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(Request {
+                id: 8,
+                call: Call::Context
+            })
+            .unwrap(),
+            serde_json::json!({ "id": 8, "method": "context" })
+        );
+    }
+
+    #[test]
+    fn responses_are_results_or_errors() {
+        let ok: Response = serde_json::from_str(r#"{"id":1,"result":{"n":1}}"#).unwrap();
+        assert_eq!(ok.into_result(), Ok(serde_json::json!({ "n": 1 })));
+        let err: Response =
+            serde_json::from_str(r#"{"id":2,"error":{"message":"no such issue"}}"#).unwrap();
+        assert_eq!(err.into_result(), Err("no such issue".to_string()));
+    }
+}
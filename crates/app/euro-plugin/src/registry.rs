@@ -0,0 +1,644 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+#[cfg(feature = "specta")]
+use specta::Type;
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+use crate::error::{PluginError, PluginResult};
+use crate::instance::Instance;
+use crate::manifest::{Capability, PluginManifest, ToolManifest, split_tool_name};
+use crate::protocol::Call;
+use crate::sandbox;
+use crate::usage::{ResourceUsage, SharedUsage};
+
+/// Which plugins are enabled and what they were granted.
+const REGISTRY_FILE: &str = "registry.json";
+const INSTALLED_DIR: &str = "installed";
+const DATA_DIR: &str = "data";
+
+const DEFAULT_TOOL_TIMEOUT: Duration = Duration::from_secs(30);
+/// Context is gathered at the start of every turn, so a slow plugin
+/// gets left out rather than waited for.
+const CONTEXT_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RegistryFile {
+    #[serde(default)]
+    plugins: BTreeMap<String, PluginRecord>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct PluginRecord {
+    enabled: bool,
+    granted: BTreeSet<Capability>,
+    /// The `allowed_hosts` approved along with the capabilities.
+    #[serde(default)]
+    hosts: BTreeSet<String>,
+    /// The user agreed to run this executable without an OS sandbox,
+    /// which the machine doesn't have.
+    #[serde(default)]
+    allow_unsandboxed: bool,
+}
+
+/// An installed plugin, as the settings UI shows it.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+#[serde(rename_all = "camelCase")]
+pub struct PluginInfo {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    /// What the plugin asks for. Enabling it grants all of these.
+    pub capabilities: Vec<Capability>,
+    /// What the user granted. Short of `capabilities` after an update
+    /// that asks for more, which leaves the plugin disabled.
+    pub granted: Vec<Capability>,
+    /// Hosts a module may fetch from once granted
+    /// [`Capability::Network`].
+    pub allowed_hosts: Vec<String>,
+    pub tools: Vec<ToolSummary>,
+    pub enabled: bool,
+    /// Whether the plugin is a WebAssembly module rather than an
+    /// executable.
+    pub module: bool,
+    /// Whether the plugin runs sandboxed on this machine. Modules always
+    /// do; executables need an OS sandbox.
+    pub sandboxed: bool,
+    /// Whether the user agreed to run it without a sandbox. An
+    /// executable that isn't `sandboxed` only starts with this set.
+    pub allow_unsandboxed: bool,
+    pub usage: ResourceUsage,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct ToolSummary {
+    pub name: String,
+    pub description: String,
+}
+
+struct State {
+    file: RegistryFile,
+    manifests: BTreeMap<String, PluginManifest>,
+    running: HashMap<String, Arc<Instance>>,
+    /// Kept across restarts of a plugin, not of the app.
+    usage: HashMap<String, SharedUsage>,
+}
+
+/// Installed plugins and their running instances, kept under one
+/// directory:
+///
+/// - `installed/<id>/` holds the plugin as installed, read-only to it.
+/// - `data/<id>/` is the plugin's own writable directory. It survives
+///   updates and goes with an uninstall.
+/// - `registry.json` records which plugins are enabled and what they
+///   were granted.
+///
+/// Instances start on first use and stop when their plugin is disabled,
+/// updated or uninstalled.
+pub struct PluginRegistry {
+    root: PathBuf,
+    state: Mutex<State>,
+}
+
+impl PluginRegistry {
+    /// Load the plugins installed under `root`. Plugins whose manifest no
+    /// longer loads are skipped.
+    pub fn open(root: impl Into<PathBuf>) -> PluginResult<Self> {
+        let root = root.into();
+        let installed = root.join(INSTALLED_DIR);
+        std::fs::create_dir_all(&installed)?;
+
+        let mut manifests = BTreeMap::new();
+        for entry in std::fs::read_dir(&installed)? {
+            let path = entry?.path();
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            if name.starts_with('.') {
+                // Left over from an interrupted install or update.
+                remove_dir(&path);
+                continue;
+            }
+            match PluginManifest::load(&path) {
+                Ok(manifest) if manifest.id == name => {
+                    manifests.insert(manifest.id.clone(), manifest);
+                }
+                Ok(manifest) => {
+                    tracing::warn!("Skipping plugin in {name}: manifest says {}", manifest.id);
+                }
+                Err(e) => tracing::warn!("Skipping plugin {name}: {e}"),
+            }
+        }
+
+        let mut file: RegistryFile = match std::fs::read(root.join(REGISTRY_FILE)) {
+            Ok(raw) => serde_json::from_slice(&raw).unwrap_or_else(|e| {
+                tracing::warn!("Unreadable plugin registry, starting with every plugin off: {e}");
+                RegistryFile::default()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => RegistryFile::default(),
+            Err(e) => return Err(e.into()),
+        };
+        file.plugins.retain(|id, _| manifests.contains_key(id));
+
+        Ok(Self {
+            root,
+            state: Mutex::new(State {
+                file,
+                manifests,
+                running: HashMap::new(),
+                usage: HashMap::new(),
+            }),
+        })
+    }
+
+    pub async fn list(&self) -> Vec<PluginInfo> {
+        let state = self.state.lock().await;
+        state
+            .manifests
+            .values()
+            .map(|manifest| info(&state, manifest))
+            .collect()
+    }
+
+    /// Copy the plugin in `source` in. It starts out disabled.
+    pub async fn install(&self, source: &Path) -> PluginResult<PluginInfo> {
+        let manifest = PluginManifest::load(source)?;
+        let mut state = self.state.lock().await;
+        if state.manifests.contains_key(&manifest.id) {
+            return Err(PluginError::AlreadyInstalled(manifest.id));
+        }
+
+        let manifest = self.copy_in(source, &manifest.id)?;
+        tracing::info!(plugin = %manifest.id, version = %manifest.version, "Plugin installed");
+        state
+            .file
+            .plugins
+            .insert(manifest.id.clone(), PluginRecord::default());
+        state
+            .manifests
+            .insert(manifest.id.clone(), manifest.clone());
+        self.save(&state.file)?;
+        Ok(info(&state, &manifest))
+    }
+
+    /// Replace an installed plugin with the newer version in `source`,
+    /// keeping its data. A version that asks for capabilities the user
+    /// hasn't granted is left disabled until they approve it.
+    pub async fn update(&self, source: &Path) -> PluginResult<PluginInfo> {
+        let manifest = PluginManifest::load(source)?;
+        let mut state = self.state.lock().await;
+        let Some(installed) = state.manifests.get(&manifest.id) else {
+            return Err(PluginError::NotInstalled(manifest.id));
+        };
+        if manifest.version <= installed.version {
+            return Err(PluginError::NotNewer {
+                installed: installed.version.clone(),
+                new: manifest.version,
+            });
+        }
+
+        stop(&mut state, &manifest.id);
+        let manifest = self.copy_in(source, &manifest.id)?;
+        let record = state.file.plugins.entry(manifest.id.clone()).or_default();
+        let asks_for_more = !manifest.capabilities.is_subset(&record.granted)
+            || !manifest.allowed_hosts.is_subset(&record.hosts);
+        if record.enabled && asks_for_more {
+            tracing::info!(plugin = %manifest.id, "Update asks for new capabilities; disabling");
+            record.enabled = false;
+        }
+        tracing::info!(plugin = %manifest.id, version = %manifest.version, "Plugin updated");
+        state
+            .manifests
+            .insert(manifest.id.clone(), manifest.clone());
+        self.save(&state.file)?;
+        Ok(info(&state, &manifest))
+    }
+
+    /// Enable a plugin. `granted` is what the user approved and must
+    /// cover every capability the manifest asks for. An executable on a
+    /// machine without an OS sandbox is refused with
+    /// [`PluginError::SandboxUnavailable`] unless `allow_unsandboxed`
+    /// says the user accepted running it without one.
+    pub async fn enable(
+        &self,
+        id: &str,
+        granted: BTreeSet<Capability>,
+        allow_unsandboxed: bool,
+    ) -> PluginResult<PluginInfo> {
+        let mut state = self.state.lock().await;
+        let Some(manifest) = state.manifests.get(id).cloned() else {
+            return Err(PluginError::NotInstalled(id.to_owned()));
+        };
+        let missing: Vec<Capability> = manifest
+            .capabilities
+            .difference(&granted)
+            .copied()
+            .collect();
+        if !missing.is_empty() {
+            return Err(PluginError::CapabilitiesNotGranted(missing));
+        }
+        if !is_sandboxed(&manifest) && !allow_unsandboxed {
+            return Err(PluginError::SandboxUnavailable);
+        }
+
+        stop(&mut state, id);
+        let record = state.file.plugins.entry(id.to_owned()).or_default();
+        record.enabled = true;
+        record.granted = manifest.capabilities.clone();
+        record.hosts = manifest.allowed_hosts.clone();
+        record.allow_unsandboxed = allow_unsandboxed;
+        self.save(&state.file)?;
+        Ok(info(&state, &manifest))
+    }
+
+    pub async fn disable(&self, id: &str) -> PluginResult<PluginInfo> {
+        let mut state = self.state.lock().await;
+        let Some(manifest) = state.manifests.get(id).cloned() else {
+            return Err(PluginError::NotInstalled(id.to_owned()));
+        };
+        stop(&mut state, id);
+        state.file.plugins.entry(id.to_owned()).or_default().enabled = false;
+        self.save(&state.file)?;
+        Ok(info(&state, &manifest))
+    }
+
+    /// Remove a plugin and its data.
+    pub async fn uninstall(&self, id: &str) -> PluginResult<()> {
+        let mut state = self.state.lock().await;
+        if state.manifests.remove(id).is_none() {
+            return Err(PluginError::NotInstalled(id.to_owned()));
+        }
+        stop(&mut state, id);
+        state.file.plugins.remove(id);
+        state.usage.remove(id);
+        self.save(&state.file)?;
+        remove_dir(&self.root.join(INSTALLED_DIR).join(id));
+        remove_dir(&self.root.join(DATA_DIR).join(id));
+        tracing::info!(plugin = %id, "Plugin uninstalled");
+        Ok(())
+    }
+
+    /// Tools of every enabled plugin granted [`Capability::Tools`], under
+    /// their advertised names.
+    pub async fn tools(&self) -> Vec<(String, ToolManifest)> {
+        let state = self.state.lock().await;
+        state
+            .manifests
+            .values()
+            .filter(|manifest| is_granted(&state, &manifest.id, Capability::Tools))
+            .flat_map(|manifest| {
+                manifest
+                    .tools
+                    .iter()
+                    .map(|tool| (manifest.qualified_tool_name(&tool.name), tool.clone()))
+            })
+            .collect()
+    }
+
+    /// Run the tool advertised as `qualified`.
+    pub async fn call_tool(
+        &self,
+        qualified: &str,
+        arguments: &Value,
+        cancel: &CancellationToken,
+    ) -> PluginResult<Value> {
+        let Some((id, name)) = split_tool_name(qualified) else {
+            return Err(PluginError::NotInstalled(qualified.to_owned()));
+        };
+        let (process, timeout) = {
+            let mut state = self.state.lock().await;
+            let tool = state
+                .manifests
+                .get(id)
+                .and_then(|manifest| manifest.tools.iter().find(|tool| tool.name == name))
+                .ok_or_else(|| PluginError::NotInstalled(qualified.to_owned()))?;
+            let timeout = tool
+                .timeout_ms
+                .map_or(DEFAULT_TOOL_TIMEOUT, |ms| Duration::from_millis(ms.into()));
+            if !is_granted(&state, id, Capability::Tools) {
+                return Err(PluginError::NotEnabled(id.to_owned()));
+            }
+            (self.instance(&mut state, id).await?, timeout)
+        };
+        process
+            .call(Call::CallTool { name, arguments }, timeout, Some(cancel))
+            .await
+    }
+
+    /// Context text from every enabled plugin granted
+    /// [`Capability::Context`], with the plugin's name. Plugins that fail
+    /// or are slow to answer are left out.
+    pub async fn collect_context(&self) -> Vec<(String, String)> {
+        let processes = {
+            let mut state = self.state.lock().await;
+            let ids: Vec<String> = state
+                .manifests
+                .keys()
+                .filter(|id| is_granted(&state, id, Capability::Context))
+                .cloned()
+                .collect();
+            let mut processes = Vec::new();
+            for id in ids {
+                match self.instance(&mut state, &id).await {
+                    Ok(process) => processes.push((state.manifests[&id].name.clone(), process)),
+                    Err(e) => tracing::warn!(plugin = %id, "Plugin unavailable for context: {e}"),
+                }
+            }
+            processes
+        };
+
+        let answers = processes.into_iter().map(|(name, process)| async move {
+            match process.call(Call::Context, CONTEXT_TIMEOUT, None).await {
+                Ok(Value::String(text)) if !text.trim().is_empty() => Some((name, text)),
+                Ok(Value::Null | Value::String(_)) => None,
+                Ok(other) => {
+                    tracing::warn!("Plugin {name} sent context that isn't text: {other}");
+                    None
+                }
+                Err(e) => {
+                    tracing::warn!("Plugin {name} gave no context: {e}");
+                    None
+                }
+            }
+        });
+        futures::future::join_all(answers)
+            .await
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    /// Stop every running plugin.
+    pub async fn shutdown(&self) {
+        let mut state = self.state.lock().await;
+        for (_, process) in state.running.drain() {
+            process.stop();
+        }
+    }
+
+    /// The running instance of `id`, started if it isn't.
+    async fn instance(&self, state: &mut State, id: &str) -> PluginResult<Arc<Instance>> {
+        if let Some(instance) = state.running.get(id)
+            && instance.is_running()
+        {
+            return Ok(Arc::clone(instance));
+        }
+        let manifest = state
+            .manifests
+            .get(id)
+            .ok_or_else(|| PluginError::NotInstalled(id.to_owned()))?;
+        let record = state.file.plugins.get(id).cloned().unwrap_or_default();
+        let usage = Arc::clone(state.usage.entry(id.to_owned()).or_default());
+        let instance = Arc::new(
+            Instance::start(
+                manifest,
+                &self.root.join(INSTALLED_DIR).join(id),
+                &self.root.join(DATA_DIR).join(id),
+                &record.granted,
+                &record.hosts,
+                record.allow_unsandboxed,
+                usage,
+            )
+            .await?,
+        );
+        state.running.insert(id.to_owned(), Arc::clone(&instance));
+        Ok(instance)
+    }
+
+    /// Copy `source` over `installed/<id>`, through a staging directory so
+    /// a failed copy leaves the installed version alone.
+    fn copy_in(&self, source: &Path, id: &str) -> PluginResult<PluginManifest> {
+        let installed = self.root.join(INSTALLED_DIR);
+        let staging = installed.join(format!(".{id}.new"));
+        let previous = installed.join(format!(".{id}.old"));
+        let target = installed.join(id);
+
+        remove_dir(&staging);
+        copy_dir(source, &staging)?;
+        let manifest = match PluginManifest::load(&staging) {
+            Ok(manifest) if manifest.id == id => manifest,
+            Ok(_) => {
+                remove_dir(&staging);
+                return Err(PluginError::InvalidManifest(
+                    "plugin changed while copying".into(),
+                ));
+            }
+            Err(e) => {
+                remove_dir(&staging);
+                return Err(e);
+            }
+        };
+
+        if target.exists() {
+            remove_dir(&previous);
+            std::fs::rename(&target, &previous)?;
+        }
+        std::fs::rename(&staging, &target)?;
+        remove_dir(&previous);
+        Ok(manifest)
+    }
+
+    fn save(&self, file: &RegistryFile) -> PluginResult<()> {
+        let json = serde_json::to_vec_pretty(file)
+            .map_err(|e| PluginError::Io(std::io::Error::other(e)))?;
+        let tmp = self.root.join(format!("{REGISTRY_FILE}.tmp"));
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, self.root.join(REGISTRY_FILE))?;
+        Ok(())
+    }
+}
+
+fn is_granted(state: &State, id: &str, capability: Capability) -> bool {
+    state
+        .file
+        .plugins
+        .get(id)
+        .is_some_and(|record| record.enabled && record.granted.contains(&capability))
+}
+
+fn stop(state: &mut State, id: &str) {
+    if let Some(process) = state.running.remove(id) {
+        process.stop();
+    }
+}
+
+fn info(state: &State, manifest: &PluginManifest) -> PluginInfo {
+    let record = state.file.plugins.get(&manifest.id);
+    let usage = state
+        .usage
+        .get(&manifest.id)
+        .and_then(|usage| usage.lock().ok().map(|usage| usage.clone()))
+        .unwrap_or_default();
+    PluginInfo {
+        id: manifest.id.clone(),
+        name: manifest.name.clone(),
+        version: manifest.version.to_string(),
+        description: manifest.description.clone(),
+        capabilities: manifest.capabilities.iter().copied().collect(),
+        granted: record
+            .map(|record| record.granted.iter().copied().collect())
+            .unwrap_or_default(),
+        allowed_hosts: manifest.allowed_hosts.iter().cloned().collect(),
+        tools: manifest
+            .tools
+            .iter()
+            .map(|tool| ToolSummary {
+                name: tool.name.clone(),
+                description: tool.description.clone(),
+            })
+            .collect(),
+        enabled: record.is_some_and(|record| record.enabled),
+        module: manifest.entry.is_module(),
+        sandboxed: is_sandboxed(manifest),
+        allow_unsandboxed: record.is_some_and(|record| record.allow_unsandboxed),
+        usage,
+    }
+}
+
+/// Whether `manifest`'s plugin runs sandboxed on this machine.
+fn is_sandboxed(manifest: &PluginManifest) -> bool {
+    manifest.entry.is_module() || sandbox::is_available()
+}
+
+/// Copy the files under `from` to `to`. Symlinks are skipped so a plugin
+/// can't point its install at files outside it.
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let target = to.join(entry.file_name());
+        if file_type.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else if file_type.is_file() {
+            std::fs::copy(entry.path(), &target)?;
+        } else {
+            tracing::warn!("Not copying {}: not a regular file", entry.path().display());
+        }
+    }
+    Ok(())
+}
+
+fn remove_dir(path: &Path) {
+    if let Err(e) = std::fs::remove_dir_all(path)
+        && e.kind() != std::io::ErrorKind::NotFound
+    {
+        tracing::warn!("Failed to remove {}: {e}", path.display());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::MANIFEST_FILE;
+
+    fn write_plugin(dir: &Path, version: &str, capabilities: &[&str]) {
+        std::fs::create_dir_all(dir).unwrap();
+        std::fs::write(dir.join("run"), b"").unwrap();
+        let manifest = serde_json::json!({
+            "id": "acme",
+            "name": "Acme",
+            "version": version,
+            "command": "run",
+            "capabilities": capabilities,
+            "tools": [{ "name": "lookup", "description": "Look something up." }],
+        });
+        std::fs::write(dir.join(MANIFEST_FILE), manifest.to_string()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn plugins_are_installed_disabled_and_enabled_with_consent() {
+        let root = tempfile::tempdir().unwrap();
+        let source = tempfile::tempdir().unwrap();
+        write_plugin(source.path(), "1.0.0", &["tools"]);
+
+        let registry = PluginRegistry::open(root.path()).unwrap();
+        let installed = registry.install(source.path()).await.unwrap();
+        assert!(!installed.enabled);
+        assert!(registry.tools().await.is_empty());
+        assert!(matches!(
+            registry.install(source.path()).await,
+            Err(PluginError::AlreadyInstalled(_))
+        ));
+
+        assert!(matches!(
+            registry.enable("acme", BTreeSet::new(), true).await,
+            Err(PluginError::CapabilitiesNotGranted(missing)) if missing == [Capability::Tools]
+        ));
+        registry
+            .enable("acme", BTreeSet::from([Capability::Tools]), true)
+            .await
+            .unwrap();
+        let tools = registry.tools().await;
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0].0, "acme__lookup");
+
+        // Survives a restart.
+        drop(registry);
+        let registry = PluginRegistry::open(root.path()).unwrap();
+        assert!(registry.list().await[0].enabled);
+
+        registry.disable("acme").await.unwrap();
+        assert!(registry.tools().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn updates_asking_for_more_need_approval_again() {
+        let root = tempfile::tempdir().unwrap();
+        let source = tempfile::tempdir().unwrap();
+        write_plugin(source.path(), "1.0.0", &["tools"]);
+
+        let registry = PluginRegistry::open(root.path()).unwrap();
+        registry.install(source.path()).await.unwrap();
+        registry
+            .enable("acme", BTreeSet::from([Capability::Tools]), true)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            registry.update(source.path()).await,
+            Err(PluginError::NotNewer { .. })
+        ));
+
+        write_plugin(source.path(), "1.1.0", &["tools", "network"]);
+        let updated = registry.update(source.path()).await.unwrap();
+        assert_eq!(updated.version, "1.1.0");
+        assert!(!updated.enabled);
+        assert_eq!(updated.granted, [Capability::Tools]);
+
+        registry.uninstall("acme").await.unwrap();
+        assert!(registry.list().await.is_empty());
+        assert!(!root.path().join(INSTALLED_DIR).join("acme").exists());
+    }
+
+    #[tokio::test]
+    async fn executables_without_a_sandbox_need_an_opt_in() {
+        let root = tempfile::tempdir().unwrap();
+        let source = tempfile::tempdir().unwrap();
+        write_plugin(source.path(), "1.0.0", &["tools"]);
+
+        let registry = PluginRegistry::open(root.path()).unwrap();
+        registry.install(source.path()).await.unwrap();
+        let granted = BTreeSet::from([Capability::Tools]);
+        let enabled = registry.enable("acme", granted.clone(), false).await;
+        if sandbox::is_available() {
+            assert!(enabled.unwrap().sandboxed);
+        } else {
+            assert!(matches!(enabled, Err(PluginError::SandboxUnavailable)));
+            assert!(!registry.list().await[0].enabled);
+
+            registry.enable("acme", granted, true).await.unwrap();
+            drop(registry);
+            let registry = PluginRegistry::open(root.path()).unwrap();
+            assert!(registry.list().await[0].allow_unsandboxed);
+        }
+    }
+}
@@ -0,0 +1,244 @@
+//! How a plugin process is started, and what it can reach.
+//!
+//! Every plugin starts with an empty environment, its own data directory
+//! as home and working directory, and nothing but pipes for stdio. On top
+//! of that the OS confines it where it can:
+//!
+//! - Linux runs it under bubblewrap (`bwrap`), when installed: system
+//!   directories and the plugin read-only, its data directory writable,
+//!   nothing else of the filesystem, and no network unless granted.
+//! - macOS runs it under `sandbox-exec` with a profile to the same effect.
+//! - Windows, and Linux without bubblewrap, get no OS sandbox. An
+//!   executable plugin only runs there if the user opted in when enabling
+//!   it; [`command`] refuses otherwise.
+
+use std::path::Path;
+use std::process::Stdio;
+
+use tokio::process::Command;
+
+use crate::error::{PluginError, PluginResult};
+
+/// What a plugin process gets to see.
+pub(crate) struct Confinement<'a> {
+    pub plugin_dir: &'a Path,
+    pub data_dir: &'a Path,
+    pub network: bool,
+    /// Run without an OS sandbox when there is none. Only set when the
+    /// user said so for this plugin.
+    pub allow_unsandboxed: bool,
+}
+
+/// Whether plugins run inside an OS sandbox on this machine.
+pub fn is_available() -> bool {
+    platform::is_available()
+}
+
+/// The command that runs `program` with `args` confined to `confinement`.
+/// Fails with [`PluginError::SandboxUnavailable`] when there is no OS
+/// sandbox and the user hasn't allowed running without one.
+pub(crate) fn command(
+    program: &Path,
+    args: &[String],
+    confinement: &Confinement<'_>,
+) -> PluginResult<Command> {
+    let mut command = match platform::command(program, args, confinement) {
+        Some(command) => command,
+        None if confinement.allow_unsandboxed => {
+            tracing::warn!("No OS sandbox; running plugin unsandboxed as the user allowed");
+            let mut command = Command::new(program);
+            command.args(args);
+            command
+        }
+        None => return Err(PluginError::SandboxUnavailable),
+    };
+    command
+        .env_clear()
+        .envs(platform::base_env())
+        .env("HOME", confinement.data_dir)
+        .env("TMPDIR", confinement.data_dir)
+        .env("EURORA_PLUGIN_DATA", confinement.data_dir)
+        .current_dir(confinement.data_dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    Ok(command)
+}
+
+cfg_select! {
+    target_os = "linux" => {
+        mod platform {
+            use std::ffi::OsString;
+            use std::path::{Path, PathBuf};
+
+            use tokio::process::Command;
+
+            use super::Confinement;
+
+            fn bwrap() -> Option<PathBuf> {
+                let path = std::env::var_os("PATH")?;
+                std::env::split_paths(&path)
+                    .map(|dir| dir.join("bwrap"))
+                    .find(|candidate| candidate.is_file())
+            }
+
+            pub(super) fn is_available() -> bool {
+                bwrap().is_some()
+            }
+
+            pub(super) fn base_env() -> Vec<(&'static str, OsString)> {
+                vec![("PATH", "/usr/local/bin:/usr/bin:/bin".into())]
+            }
+
+            /// `None` when bubblewrap isn't installed.
+            pub(super) fn command(
+                program: &Path,
+                args: &[String],
+                confinement: &Confinement<'_>,
+            ) -> Option<Command> {
+                let bwrap = bwrap()?;
+                let mut command = Command::new(bwrap);
+                command.args(["--die-with-parent", "--new-session", "--unshare-all"]);
+                if confinement.network {
+                    command.arg("--share-net");
+                }
+                command.args(["--ro-bind", "/usr", "/usr"]);
+                for dir in ["/bin", "/sbin", "/lib", "/lib64", "/etc"] {
+                    command.args(["--ro-bind-try", dir, dir]);
+                }
+                command
+                    .args(["--proc", "/proc", "--dev", "/dev", "--tmpfs", "/tmp"])
+                    .arg("--ro-bind")
+                    .arg(confinement.plugin_dir)
+                    .arg(confinement.plugin_dir)
+                    .arg("--bind")
+                    .arg(confinement.data_dir)
+                    .arg(confinement.data_dir)
+                    .arg("--chdir")
+                    .arg(confinement.data_dir)
+                    .arg("--")
+                    .arg(program)
+                    .args(args);
+                Some(command)
+            }
+        }
+    }
+    target_os = "macos" => {
+        mod platform {
+            use std::ffi::OsString;
+            use std::path::Path;
+
+            use tokio::process::Command;
+
+            use super::Confinement;
+
+            const SANDBOX_EXEC: &str = "/usr/bin/sandbox-exec";
+
+            const PROFILE: &str = r#"(version 1)
+(deny default)
+(allow process-fork process-exec)
+(allow signal (target self))
+(allow sysctl-read)
+(allow mach-lookup)
+(allow ipc-posix-shm)
+(allow file-read-metadata)
+(allow file-read*
+    (subpath "/usr")
+    (subpath "/bin")
+    (subpath "/System")
+    (subpath "/Library")
+    (subpath "/private/etc")
+    (subpath "/private/var/db/timezone")
+    (subpath "/dev")
+    (subpath (param "PLUGIN_DIR")))
+(allow file-read* file-write*
+    (literal "/dev/null")
+    (subpath (param "DATA_DIR")))
+"#;
+
+            const NETWORK_RULES: &str = r#"(allow network*)
+(allow system-socket)
+(allow file-read* (subpath "/private/var/run"))
+"#;
+
+            pub(super) fn is_available() -> bool {
+                Path::new(SANDBOX_EXEC).is_file()
+            }
+
+            pub(super) fn base_env() -> Vec<(&'static str, OsString)> {
+                vec![("PATH", "/usr/bin:/bin".into())]
+            }
+
+            /// The profile matches real paths, so resolve symlinks such as
+            /// `/var` -> `/private/var` first.
+            fn real(path: &Path) -> OsString {
+                path.canonicalize()
+                    .unwrap_or_else(|_| path.to_path_buf())
+                    .into_os_string()
+            }
+
+            /// `None` when `sandbox-exec` is missing.
+            pub(super) fn command(
+                program: &Path,
+                args: &[String],
+                confinement: &Confinement<'_>,
+            ) -> Option<Command> {
+                if !is_available() {
+                    return None;
+                }
+                let mut profile = PROFILE.to_string();
+                if confinement.network {
+                    profile.push_str(NETWORK_RULES);
+                }
+                let mut plugin_dir = OsString::from("PLUGIN_DIR=");
+                plugin_dir.push(real(confinement.plugin_dir));
+                let mut data_dir = OsString::from("DATA_DIR=");
+                data_dir.push(real(confinement.data_dir));
+
+                let mut command = Command::new(SANDBOX_EXEC);
+                command
+                    .arg("-p")
+                    .arg(profile)
+                    .arg("-D")
+                    .arg(plugin_dir)
+                    .arg("-D")
+                    .arg(data_dir)
+                    .arg(program)
+                    .args(args);
+                Some(command)
+            }
+        }
+    }
+    _ => {
+        mod platform {
+            use std::ffi::OsString;
+            use std::path::Path;
+
+            use tokio::process::Command;
+
+            use super::Confinement;
+
+            pub(super) fn is_available() -> bool {
+                false
+            }
+
+            /// Windows programs don't start without these.
+            pub(super) fn base_env() -> Vec<(&'static str, OsString)> {
+                ["SystemRoot", "windir", "PATH", "PATHEXT"]
+                    .into_iter()
+                    .filter_map(|key| Some((key, std::env::var_os(key)?)))
+                    .collect()
+            }
+
+            /// Always `None`: there is no OS sandbox to run under.
+            pub(super) fn command(
+                _program: &Path,
+                _args: &[String],
+                _confinement: &Confinement<'_>,
+            ) -> Option<Command> {
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+#[cfg(feature = "specta")]
+use specta::Type;
+#[cfg(feature = "specta")]
+use specta_typescript::BigInt;
+
+/// What a plugin has used since the app started, for the settings UI to
+/// show next to what it was granted.
+#[derive(Debug, Clone, Default, Serialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceUsage {
+    #[cfg_attr(feature = "specta", specta(type = BigInt))]
+    pub calls: u64,
+    /// Calls stopped for running out of time or a [`Limit`].
+    #[cfg_attr(feature = "specta", specta(type = BigInt))]
+    pub limit_hits: u64,
+    /// Wall time spent answering calls.
+    #[cfg_attr(feature = "specta", specta(type = BigInt))]
+    pub busy_ms: u64,
+    /// Roughly one unit per WebAssembly instruction. Modules only, as are
+    /// the fields below.
+    #[cfg_attr(feature = "specta", specta(type = BigInt))]
+    pub fuel: u64,
+    /// Largest memory a module instance has grown to.
+    #[cfg_attr(feature = "specta", specta(type = BigInt))]
+    pub peak_memory_bytes: u64,
+    #[cfg_attr(feature = "specta", specta(type = BigInt))]
+    pub http_requests: u64,
+    #[cfg_attr(feature = "specta", specta(type = BigInt))]
+    pub http_bytes_received: u64,
+    /// Current size of the key-value store.
+    #[cfg_attr(feature = "specta", specta(type = BigInt))]
+    pub stored_bytes: u64,
+}
+
+pub(crate) type SharedUsage = Arc<Mutex<ResourceUsage>>;
+
+pub(crate) fn update(usage: &SharedUsage, f: impl FnOnce(&mut ResourceUsage)) {
+    if let Ok(mut usage) = usage.lock() {
+        f(&mut usage);
+    }
+}
+
+/// A per-call budget a module can run out of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    Fuel,
+    Memory,
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Fuel => "fuel",
+            Self::Memory => "memory",
+        })
+    }
+}
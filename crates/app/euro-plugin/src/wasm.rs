@@ -0,0 +1,278 @@
+//! Plugins shipped as WebAssembly components, run in-process on wasmtime
+//! with WASI preview 2 and the host functions in `wit/plugin.wit`.
+//!
+//! A module gets no ambient authority. WASI gives it a `files/`
+//! directory under its data directory as `/data`, clocks and randomness,
+//! and no sockets. The network is only reachable through `http-fetch`,
+//! and only to the hosts the user approved. Every call is metered: it
+//! runs on a fuel budget and a deadline, memory is capped, and what it
+//! used goes into the plugin's [`ResourceUsage`](crate::ResourceUsage).
+//!
+//! A call that traps, runs out of a budget or is cancelled leaves the
+//! instance behind; the registry starts a fresh one for the next call.
+
+mod host;
+
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+use wasmtime::component::{Component, HasSelf, Linker};
+use wasmtime::{Config, Engine, Store, Trap};
+use wasmtime_wasi::{DirPerms, FilePerms, WasiCtxBuilder};
+
+use self::host::{HostState, Kv, Network};
+use crate::error::{PluginError, PluginResult};
+use crate::manifest::Capability;
+use crate::protocol::{Call, PROTOCOL_VERSION};
+use crate::usage::{self, Limit, SharedUsage};
+
+wasmtime::component::bindgen!({
+    path: "wit",
+    world: "plugin",
+    imports: { default: async },
+    exports: { default: async },
+});
+
+/// Fuel a single call may burn; several seconds of straight-line work.
+const FUEL_PER_CALL: u64 = 10_000_000_000;
+/// How often a running call hands control back to the executor, so it
+/// can be timed out or cancelled.
+const FUEL_YIELD_INTERVAL: u64 = 1_000_000;
+/// How long a module gets to answer `initialize`.
+const INITIALIZE_TIMEOUT: Duration = Duration::from_secs(10);
+const WASI_DATA_DIR: &str = "/data";
+
+/// One instantiated module.
+pub(crate) struct WasmPlugin {
+    id: String,
+    /// `None` once stopped or left unusable by a failed call.
+    loaded: Mutex<Option<Loaded>>,
+    stopped: CancellationToken,
+    usage: SharedUsage,
+}
+
+struct Loaded {
+    store: Store<HostState>,
+    bindings: Plugin,
+}
+
+impl WasmPlugin {
+    /// Compile and instantiate the component at `module` and initialize
+    /// it.
+    pub async fn start(
+        id: &str,
+        module: &Path,
+        data_dir: &Path,
+        granted: &BTreeSet<Capability>,
+        allowed_hosts: &BTreeSet<String>,
+        usage: SharedUsage,
+    ) -> PluginResult<Self> {
+        let engine = engine()?;
+        let component = {
+            let module = module.to_owned();
+            tokio::task::spawn_blocking(move || Component::from_file(engine, module))
+                .await
+                .map_err(|e| PluginError::Spawn(e.to_string()))?
+                .map_err(|e| PluginError::Spawn(format!("{e:#}")))?
+        };
+
+        let files_dir = data_dir.join("files");
+        std::fs::create_dir_all(&files_dir)?;
+        let wasi = WasiCtxBuilder::new()
+            .preopened_dir(&files_dir, WASI_DATA_DIR, DirPerms::all(), FilePerms::all())
+            .map_err(|e| PluginError::Spawn(format!("{e:#}")))?
+            .env("EURORA_PLUGIN_DATA", WASI_DATA_DIR)
+            .build();
+        let network = if granted.contains(&Capability::Network) {
+            Some(
+                Network::new(allowed_hosts.clone())
+                    .map_err(|e| PluginError::Spawn(e.to_string()))?,
+            )
+        } else {
+            None
+        };
+        let kv = Kv::open(data_dir.join("kv.json"))?;
+        let state = HostState::new(id.to_owned(), wasi, network, kv, usage.clone());
+
+        let mut linker = Linker::new(engine);
+        wasmtime_wasi::p2::add_to_linker_async(&mut linker)
+            .map_err(|e| PluginError::Spawn(format!("{e:#}")))?;
+        Plugin::add_to_linker::<_, HasSelf<_>>(&mut linker, |state: &mut HostState| state)
+            .map_err(|e| PluginError::Spawn(format!("{e:#}")))?;
+
+        let mut store = Store::new(engine, state);
+        store.limiter(|state| &mut state.memory);
+        store
+            .fuel_async_yield_interval(Some(FUEL_YIELD_INTERVAL))
+            .map_err(|e| PluginError::Spawn(format!("{e:#}")))?;
+        // Instantiation runs start functions, so it gets a budget too.
+        store
+            .set_fuel(FUEL_PER_CALL)
+            .map_err(|e| PluginError::Spawn(format!("{e:#}")))?;
+        let bindings = Plugin::instantiate_async(&mut store, &component, &linker)
+            .await
+            .map_err(|e| PluginError::Spawn(format!("{e:#}")))?;
+
+        let plugin = Self {
+            id: id.to_owned(),
+            loaded: Mutex::new(Some(Loaded { store, bindings })),
+            stopped: CancellationToken::new(),
+            usage,
+        };
+        plugin
+            .call(
+                Call::Initialize {
+                    protocol_version: PROTOCOL_VERSION,
+                    granted,
+                    data_dir: Path::new(WASI_DATA_DIR),
+                },
+                INITIALIZE_TIMEOUT,
+                None,
+            )
+            .await?;
+        tracing::info!(plugin = %plugin.id, "Module started");
+        Ok(plugin)
+    }
+
+    /// Run `call` on a fresh budget. Calls take turns: a module is
+    /// single-threaded.
+    pub async fn call(
+        &self,
+        call: Call<'_>,
+        timeout: Duration,
+        cancel: Option<&CancellationToken>,
+    ) -> PluginResult<Value> {
+        let mut slot = self.loaded.lock().await;
+        let Some(loaded) = slot.as_mut() else {
+            return Err(PluginError::Transport("module stopped".into()));
+        };
+        loaded
+            .store
+            .set_fuel(FUEL_PER_CALL)
+            .map_err(|e| PluginError::Transport(format!("{e:#}")))?;
+        loaded.store.data_mut().begin_call();
+
+        let started = Instant::now();
+        let never = CancellationToken::new();
+        let cancel = cancel.unwrap_or(&never);
+        let outcome = tokio::select! {
+            outcome = tokio::time::timeout(timeout, loaded.run(call)) => {
+                outcome.map_err(|_| PluginError::Timeout)
+            }
+            () = cancel.cancelled() => Err(PluginError::Cancelled),
+            () = self.stopped.cancelled() => Err(PluginError::Transport("module stopped".into())),
+        };
+        let outcome = match outcome {
+            Ok(Ok(answer)) => Ok(answer),
+            Ok(Err(trap)) => Err(classify(&trap, &loaded.store)),
+            Err(e) => Err(e),
+        };
+
+        let fuel = FUEL_PER_CALL - loaded.store.get_fuel().unwrap_or(0);
+        let peak = loaded.store.data().memory.peak as u64;
+        usage::update(&self.usage, |usage| {
+            usage.fuel += fuel;
+            usage.peak_memory_bytes = usage.peak_memory_bytes.max(peak);
+            if matches!(
+                outcome,
+                Err(PluginError::LimitExceeded(_) | PluginError::Timeout)
+            ) {
+                usage.limit_hits += 1;
+            }
+        });
+        tracing::debug!(
+            plugin = %self.id,
+            fuel,
+            elapsed_ms = started.elapsed().as_millis() as u64,
+            "Module call finished"
+        );
+
+        match outcome {
+            Ok(Ok(value)) => Ok(value),
+            Ok(Err(message)) => Err(PluginError::Plugin(message)),
+            Err(e) => {
+                tracing::warn!(plugin = %self.id, "Dropping module instance: {e}");
+                *slot = None;
+                Err(e)
+            }
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        !self.stopped.is_cancelled()
+            && self
+                .loaded
+                .try_lock()
+                .map_or(true, |loaded| loaded.is_some())
+    }
+
+    pub fn stop(&self) {
+        self.stopped.cancel();
+    }
+}
+
+impl Loaded {
+    async fn run(&mut self, call: Call<'_>) -> wasmtime::Result<Result<Value, String>> {
+        match call {
+            Call::Initialize {
+                protocol_version,
+                granted,
+                ..
+            } => {
+                let granted: Vec<String> = granted
+                    .iter()
+                    .map(|capability| capability.as_str().to_owned())
+                    .collect();
+                let answer = self
+                    .bindings
+                    .call_initialize(&mut self.store, protocol_version, &granted)
+                    .await?;
+                Ok(answer.map(|()| Value::Null))
+            }
+            Call::CallTool { name, arguments } => {
+                let answer = self
+                    .bindings
+                    .call_call_tool(&mut self.store, name, &arguments.to_string())
+                    .await?;
+                // Plain text is passed on as a string.
+                Ok(answer.map(|text| serde_json::from_str(&text).unwrap_or(Value::String(text))))
+            }
+            Call::Context => {
+                let answer = self.bindings.call_context(&mut self.store).await?;
+                Ok(answer.map(|text| text.map_or(Value::Null, Value::String)))
+            }
+        }
+    }
+}
+
+/// What a failed call ran into.
+fn classify(error: &wasmtime::Error, store: &Store<HostState>) -> PluginError {
+    if store.data().memory.exceeded {
+        return PluginError::LimitExceeded(Limit::Memory);
+    }
+    match error.downcast_ref::<Trap>() {
+        Some(Trap::OutOfFuel) => PluginError::LimitExceeded(Limit::Fuel),
+        _ => PluginError::Plugin(format!("module crashed: {error:#}")),
+    }
+}
+
+/// The engine every module runs on, created on first use.
+fn engine() -> PluginResult<&'static Engine> {
+    static ENGINE: OnceLock<Result<Engine, String>> = OnceLock::new();
+    ENGINE
+        .get_or_init(|| {
+            let mut config = Config::new();
+            config
+                .async_support(true)
+                .wasm_component_model(true)
+                .consume_fuel(true);
+            Engine::new(&config).map_err(|e| format!("{e:#}"))
+        })
+        .as_ref()
+        .map_err(|e| PluginError::Spawn(e.clone()))
+}
@@ -0,0 +1,334 @@
+//! The `host` interface from `wit/plugin.wit`, and the per-store state
+//! behind it.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use wasmtime::ResourceLimiter;
+use wasmtime::component::ResourceTable;
+use wasmtime_wasi::{WasiCtx, WasiCtxView, WasiView};
+
+use super::eurora::plugin::host::{Host, HttpRequest, HttpResponse};
+use crate::usage::{self, SharedUsage};
+
+/// Most memory a module instance may grow to.
+pub(super) const MAX_MEMORY_BYTES: usize = 256 * 1024 * 1024;
+const MAX_TABLE_ELEMENTS: usize = 100_000;
+const MAX_HTTP_REQUESTS_PER_CALL: u32 = 32;
+const MAX_HTTP_BODY_BYTES: usize = 8 * 1024 * 1024;
+const MAX_REDIRECTS: usize = 5;
+const HTTP_TIMEOUT: Duration = Duration::from_secs(20);
+const MAX_KV_KEY_BYTES: usize = 256;
+const MAX_KV_BYTES: usize = 10 * 1024 * 1024;
+
+/// Everything a module's store holds besides the module itself.
+pub(super) struct HostState {
+    pub wasi: WasiCtx,
+    pub table: ResourceTable,
+    pub memory: MemoryCap,
+    plugin: String,
+    /// `None` unless the user granted [`crate::Capability::Network`].
+    network: Option<Network>,
+    kv: Kv,
+    usage: SharedUsage,
+    http_requests: u32,
+}
+
+impl HostState {
+    pub fn new(
+        plugin: String,
+        wasi: WasiCtx,
+        network: Option<Network>,
+        kv: Kv,
+        usage: SharedUsage,
+    ) -> Self {
+        usage::update(&usage, |usage| usage.stored_bytes = kv.bytes as u64);
+        Self {
+            wasi,
+            table: ResourceTable::new(),
+            memory: MemoryCap::default(),
+            plugin,
+            network,
+            kv,
+            usage,
+            http_requests: 0,
+        }
+    }
+
+    /// Reset the per-call budgets.
+    pub fn begin_call(&mut self) {
+        self.http_requests = 0;
+    }
+}
+
+impl WasiView for HostState {
+    fn ctx(&mut self) -> WasiCtxView<'_> {
+        WasiCtxView {
+            ctx: &mut self.wasi,
+            table: &mut self.table,
+        }
+    }
+}
+
+/// Caps memory at [`MAX_MEMORY_BYTES`] and remembers how close the
+/// module came.
+#[derive(Debug, Default)]
+pub(super) struct MemoryCap {
+    pub peak: usize,
+    pub exceeded: bool,
+}
+
+impl ResourceLimiter for MemoryCap {
+    fn memory_growing(
+        &mut self,
+        _current: usize,
+        desired: usize,
+        _maximum: Option<usize>,
+    ) -> wasmtime::Result<bool> {
+        if desired > MAX_MEMORY_BYTES {
+            // Trap rather than fail the grow, so the call is reported as
+            // over its limit instead of however the module copes.
+            self.exceeded = true;
+            return Err(wasmtime::Error::msg("memory limit exceeded"));
+        }
+        self.peak = self.peak.max(desired);
+        Ok(true)
+    }
+
+    fn table_growing(
+        &mut self,
+        _current: usize,
+        desired: usize,
+        _maximum: Option<usize>,
+    ) -> wasmtime::Result<bool> {
+        Ok(desired <= MAX_TABLE_ELEMENTS)
+    }
+}
+
+/// HTTP client held to the hosts the user approved, redirects included.
+pub(super) struct Network {
+    client: reqwest::Client,
+    hosts: Arc<BTreeSet<String>>,
+}
+
+impl Network {
+    pub fn new(hosts: BTreeSet<String>) -> reqwest::Result<Self> {
+        let hosts = Arc::new(hosts);
+        let redirect_hosts = Arc::clone(&hosts);
+        let client = reqwest::Client::builder()
+            .timeout(HTTP_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::custom(move |attempt| {
+                let allowed = attempt.url().scheme() == "https"
+                    && attempt
+                        .url()
+                        .host_str()
+                        .is_some_and(|host| is_allowed(&redirect_hosts, host));
+                if attempt.previous().len() >= MAX_REDIRECTS {
+                    attempt.error("too many redirects")
+                } else if allowed {
+                    attempt.follow()
+                } else {
+                    attempt.stop()
+                }
+            }))
+            .build()?;
+        Ok(Self { client, hosts })
+    }
+}
+
+/// Whether `host` matches one of `patterns`, where `*.example.com`
+/// matches the subdomains of `example.com` but not itself.
+fn is_allowed(patterns: &BTreeSet<String>, host: &str) -> bool {
+    let host = host.to_ascii_lowercase();
+    patterns
+        .iter()
+        .any(|pattern| match pattern.strip_prefix("*.") {
+            Some(domain) => host
+                .strip_suffix(domain)
+                .is_some_and(|sub| sub.len() > 1 && sub.ends_with('.')),
+            None => *pattern == host,
+        })
+}
+
+/// The `kv-*` store, kept as one JSON file next to the module's `/data`
+/// so WASI writes can't get around the quota.
+pub(super) struct Kv {
+    path: PathBuf,
+    entries: BTreeMap<String, String>,
+    bytes: usize,
+}
+
+impl Kv {
+    pub fn open(path: PathBuf) -> std::io::Result<Self> {
+        let entries: BTreeMap<String, String> = match std::fs::read(&path) {
+            Ok(raw) => serde_json::from_slice(&raw).map_err(std::io::Error::other)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e),
+        };
+        let bytes = entries
+            .iter()
+            .map(|(key, value)| key.len() + value.len())
+            .sum();
+        Ok(Self {
+            path,
+            entries,
+            bytes,
+        })
+    }
+
+    /// Write the store out through a temp file, so a crash mid-write
+    /// leaves the old file whole. The file can be up to [`MAX_KV_BYTES`],
+    /// so the I/O runs off the async workers.
+    async fn save(&self) -> std::io::Result<()> {
+        let json = serde_json::to_vec(&self.entries).map_err(std::io::Error::other)?;
+        let tmp = self.path.with_extension("json.tmp");
+        tokio::fs::write(&tmp, json).await?;
+        tokio::fs::rename(&tmp, &self.path).await
+    }
+}
+
+impl Host for HostState {
+    async fn http_fetch(&mut self, request: HttpRequest) -> Result<HttpResponse, String> {
+        let Some(network) = &self.network else {
+            return Err("the network capability wasn't granted".into());
+        };
+        if self.http_requests >= MAX_HTTP_REQUESTS_PER_CALL {
+            return Err(format!(
+                "at most {MAX_HTTP_REQUESTS_PER_CALL} requests per call"
+            ));
+        }
+        let url = reqwest::Url::parse(&request.url).map_err(|e| e.to_string())?;
+        if url.scheme() != "https" {
+            return Err("only https URLs can be fetched".into());
+        }
+        let host = url.host_str().unwrap_or_default();
+        if !is_allowed(&network.hosts, host) {
+            return Err(format!("{host} is not one of the plugin's allowed hosts"));
+        }
+        let method =
+            reqwest::Method::from_bytes(request.method.as_bytes()).map_err(|e| e.to_string())?;
+
+        let mut builder = network.client.request(method, url);
+        for (name, value) in request.headers {
+            builder = builder.header(name, value);
+        }
+        if let Some(body) = request.body {
+            builder = builder.body(body);
+        }
+        self.http_requests += 1;
+        usage::update(&self.usage, |usage| usage.http_requests += 1);
+
+        let mut response = builder.send().await.map_err(|e| e.to_string())?;
+        let status = response.status().as_u16();
+        let headers = response
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_owned())))
+            .collect();
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+            usage::update(&self.usage, |usage| {
+                usage.http_bytes_received += chunk.len() as u64;
+            });
+            if body.len() + chunk.len() > MAX_HTTP_BODY_BYTES {
+                return Err(format!(
+                    "response is over {} MiB",
+                    MAX_HTTP_BODY_BYTES / (1024 * 1024)
+                ));
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok(HttpResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+
+    async fn kv_get(&mut self, key: String) -> Option<String> {
+        self.kv.entries.get(&key).cloned()
+    }
+
+    async fn kv_set(&mut self, key: String, value: String) -> Result<(), String> {
+        if key.is_empty() || key.len() > MAX_KV_KEY_BYTES {
+            return Err(format!("keys must be 1-{MAX_KV_KEY_BYTES} bytes"));
+        }
+        let replaced = self
+            .kv
+            .entries
+            .get(&key)
+            .map_or(0, |old| key.len() + old.len());
+        let bytes = self.kv.bytes - replaced + key.len() + value.len();
+        if bytes > MAX_KV_BYTES {
+            return Err(format!(
+                "the store is limited to {} MiB",
+                MAX_KV_BYTES / (1024 * 1024)
+            ));
+        }
+        let previous = self.kv.entries.insert(key.clone(), value);
+        if let Err(e) = self.kv.save().await {
+            match previous {
+                Some(previous) => self.kv.entries.insert(key, previous),
+                None => self.kv.entries.remove(&key),
+            };
+            return Err(e.to_string());
+        }
+        self.kv.bytes = bytes;
+        usage::update(&self.usage, |usage| usage.stored_bytes = bytes as u64);
+        Ok(())
+    }
+
+    async fn kv_delete(&mut self, key: String) -> Result<(), String> {
+        let Some(previous) = self.kv.entries.remove(&key) else {
+            return Ok(());
+        };
+        if let Err(e) = self.kv.save().await {
+            self.kv.entries.insert(key, previous);
+            return Err(e.to_string());
+        }
+        self.kv.bytes -= key.len() + previous.len();
+        let bytes = self.kv.bytes;
+        usage::update(&self.usage, |usage| usage.stored_bytes = bytes as u64);
+        Ok(())
+    }
+
+    async fn log(&mut self, message: String) {
+        tracing::debug!(plugin = %self.plugin, "{message}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hosts_match_exactly_or_as_subdomains() {
+        let patterns = BTreeSet::from(["api.example.com".to_string(), "*.example.net".to_string()]);
+        assert!(is_allowed(&patterns, "api.example.com"));
+        assert!(is_allowed(&patterns, "API.example.com"));
+        assert!(!is_allowed(&patterns, "example.com"));
+        assert!(!is_allowed(&patterns, "api.example.com.evil.org"));
+        assert!(is_allowed(&patterns, "eu.example.net"));
+        assert!(!is_allowed(&patterns, "example.net"));
+        assert!(!is_allowed(&patterns, "badexample.net"));
+    }
+
+    #[tokio::test]
+    async fn saved_entries_survive_a_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("kv.json");
+        let mut kv = Kv::open(path.clone()).unwrap();
+        kv.entries.insert("token".into(), "abc".into());
+        kv.save().await.unwrap();
+
+        let reopened = Kv::open(path.clone()).unwrap();
+        assert_eq!(
+            reopened.entries.get("token").map(String::as_str),
+            Some("abc")
+        );
+        assert_eq!(reopened.bytes, "token".len() + "abc".len());
+        assert!(!path.with_extension("json.tmp").exists());
+    }
+}
@@ -0,0 +1,180 @@
+//! Catalog of code editors and terminal emulators Eurora can identify by
+//! their executable name.
+//!
+//! Adding a new application is a single variant in [`CodeApp`] plus the
+//! corresponding arms in [`CodeApp::process_name`] and [`CodeApp::kind`].
+//! The compiler enforces that both match expressions cover every variant.
+
+use crate::{os_pick, process_name_matches};
+
+/// Whether a [`CodeApp`] is an editor/IDE or a terminal emulator. Drives
+/// which prompt framing the code strategy uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CodeAppKind {
+    Editor,
+    Terminal,
+}
+
+/// Code editors, IDEs, and terminal emulators Eurora can identify by
+/// their focused-window process name.
+///
+/// Variants are grouped by kind (editors, then terminals) for
+/// readability.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CodeApp {
+    VsCode,
+    Cursor,
+    Zed,
+    SublimeText,
+    IntelliJ,
+    PyCharm,
+    WebStorm,
+    GoLand,
+    RustRover,
+    /// The platform's default terminal: Windows Terminal, GNOME
+    /// Terminal, or macOS Terminal.app.
+    SystemTerminal,
+    Konsole,
+    ITerm2,
+    Alacritty,
+    Kitty,
+    WezTerm,
+    Ghostty,
+}
+
+impl CodeApp {
+    /// Every known application, in declaration order.
+    ///
+    /// Order is not part of the public contract; iterate with `.iter()` if
+    /// you need a stable view.
+    pub const ALL: &'static [CodeApp] = &[
+        CodeApp::VsCode,
+        CodeApp::Cursor,
+        CodeApp::Zed,
+        CodeApp::SublimeText,
+        CodeApp::IntelliJ,
+        CodeApp::PyCharm,
+        CodeApp::WebStorm,
+        CodeApp::GoLand,
+        CodeApp::RustRover,
+        CodeApp::SystemTerminal,
+        CodeApp::Konsole,
+        CodeApp::ITerm2,
+        CodeApp::Alacritty,
+        CodeApp::Kitty,
+        CodeApp::WezTerm,
+        CodeApp::Ghostty,
+    ];
+
+    /// Executable / process name reported by the focus tracker on the
+    /// current target OS.
+    pub fn process_name(self) -> &'static str {
+        match self {
+            CodeApp::VsCode => os_pick("Code.exe", "code", "Code"),
+            CodeApp::Cursor => os_pick("Cursor.exe", "cursor", "Cursor"),
+            CodeApp::Zed => os_pick("zed.exe", "zed-editor", "Zed"),
+            CodeApp::SublimeText => os_pick("sublime_text.exe", "sublime_text", "Sublime Text"),
+            CodeApp::IntelliJ => os_pick("idea64.exe", "idea", "IntelliJ IDEA"),
+            CodeApp::PyCharm => os_pick("pycharm64.exe", "pycharm", "PyCharm"),
+            CodeApp::WebStorm => os_pick("webstorm64.exe", "webstorm", "WebStorm"),
+            CodeApp::GoLand => os_pick("goland64.exe", "goland", "GoLand"),
+            CodeApp::RustRover => os_pick("rustrover64.exe", "rustrover", "RustRover"),
+            CodeApp::SystemTerminal => {
+                os_pick("WindowsTerminal.exe", "gnome-terminal-server", "Terminal")
+            }
+            CodeApp::Konsole => os_pick("konsole.exe", "konsole", "Konsole"),
+            CodeApp::ITerm2 => os_pick("iterm2.exe", "iterm2", "iTerm2"),
+            CodeApp::Alacritty => os_pick("alacritty.exe", "alacritty", "Alacritty"),
+            CodeApp::Kitty => os_pick("kitty.exe", "kitty", "kitty"),
+            CodeApp::WezTerm => os_pick("wezterm-gui.exe", "wezterm-gui", "WezTerm"),
+            CodeApp::Ghostty => os_pick("ghostty.exe", "ghostty", "Ghostty"),
+        }
+    }
+
+    /// Editor or terminal.
+    pub const fn kind(self) -> CodeAppKind {
+        match self {
+            CodeApp::VsCode
+            | CodeApp::Cursor
+            | CodeApp::Zed
+            | CodeApp::SublimeText
+            | CodeApp::IntelliJ
+            | CodeApp::PyCharm
+            | CodeApp::WebStorm
+            | CodeApp::GoLand
+            | CodeApp::RustRover => CodeAppKind::Editor,
+            CodeApp::SystemTerminal
+            | CodeApp::Konsole
+            | CodeApp::ITerm2
+            | CodeApp::Alacritty
+            | CodeApp::Kitty
+            | CodeApp::WezTerm
+            | CodeApp::Ghostty => CodeAppKind::Terminal,
+        }
+    }
+
+    /// Resolve a focused-process executable name to a known code
+    /// application.
+    ///
+    /// Matching is case-insensitive on Windows and byte-exact elsewhere; see
+    /// `process_name_matches` in the crate root.
+    pub fn from_process_name(process_name: &str) -> Option<Self> {
+        if process_name.is_empty() {
+            return None;
+        }
+        CodeApp::ALL
+            .iter()
+            .copied()
+            .find(|app| process_name_matches(app.process_name(), process_name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn every_code_app_round_trips_through_process_name() {
+        for app in CodeApp::ALL {
+            assert_eq!(
+                CodeApp::from_process_name(app.process_name()),
+                Some(*app),
+                "round-trip failed for {app:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn process_names_are_unique() {
+        let mut seen = HashSet::new();
+        for app in CodeApp::ALL {
+            let name = app.process_name();
+            assert!(
+                seen.insert(name),
+                "duplicate process name {name:?} for {app:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn kind_classification_is_stable() {
+        assert_eq!(CodeApp::VsCode.kind(), CodeAppKind::Editor);
+        assert_eq!(CodeApp::RustRover.kind(), CodeAppKind::Editor);
+        assert_eq!(CodeApp::SystemTerminal.kind(), CodeAppKind::Terminal);
+        assert_eq!(CodeApp::Ghostty.kind(), CodeAppKind::Terminal);
+    }
+
+    #[test]
+    fn does_not_collide_with_browsers() {
+        for app in CodeApp::ALL {
+            assert_eq!(crate::Browser::from_process_name(app.process_name()), None);
+        }
+    }
+
+    #[test]
+    fn unknown_process_does_not_resolve() {
+        assert_eq!(CodeApp::from_process_name(""), None);
+        assert_eq!(CodeApp::from_process_name("not-an-editor"), None);
+    }
+}
@@ -1,11 +1,13 @@
 mod ancestry;
 mod app_process;
 mod browser;
+mod code_app;
 mod process_name;
 
 pub use ancestry::{browser_ancestor_pid, parent_pid};
 pub use app_process::AppProcess;
 pub use browser::{Browser, BrowserStore};
+pub use code_app::{CodeApp, CodeAppKind};
 pub use process_name::lookup_process_name;
 
 #[inline(always)]
@@ -0,0 +1,87 @@
+use chrono::{DateTime, Utc};
+use euro_personal_db::ScreenTimeBudgetRecord;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "specta")]
+use specta::Type;
+use uuid::Uuid;
+
+/// A daily allowance of time in one app or site.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+#[serde(rename_all = "camelCase")]
+pub struct ScreenTimeBudget {
+    pub id: Uuid,
+    /// Activity name or process name the budget covers, e.g. `youtube`
+    /// for the site or `steam` for the app.
+    pub target: String,
+    pub daily_secs: u32,
+    /// A disabled budget is kept and reported on but never nudges.
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ScreenTimeBudget {
+    /// Whether time in an activity counts against this budget: its name or
+    /// process name is the target, ignoring ASCII case and a trailing
+    /// `.exe`.
+    pub fn covers(&self, name: &str, process_name: &str) -> bool {
+        let process_name = process_name.strip_suffix(".exe").unwrap_or(process_name);
+        self.target.eq_ignore_ascii_case(name) || self.target.eq_ignore_ascii_case(process_name)
+    }
+
+    pub(crate) fn to_record(&self) -> ScreenTimeBudgetRecord {
+        ScreenTimeBudgetRecord {
+            id: self.id,
+            target: self.target.clone(),
+            daily_secs: self.daily_secs,
+            enabled: self.enabled,
+            created_at: self.created_at,
+        }
+    }
+}
+
+impl From<ScreenTimeBudgetRecord> for ScreenTimeBudget {
+    fn from(record: ScreenTimeBudgetRecord) -> Self {
+        Self {
+            id: record.id,
+            target: record.target,
+            daily_secs: record.daily_secs,
+            enabled: record.enabled,
+            created_at: record.created_at,
+        }
+    }
+}
+
+/// A budget with the time spent against it today.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetStatus {
+    pub budget: ScreenTimeBudget,
+    pub used_secs: u32,
+}
+
+impl BudgetStatus {
+    pub fn exceeded(&self) -> bool {
+        self.used_secs > self.budget.daily_secs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn covers_name_or_process_ignoring_case() {
+        let budget = ScreenTimeBudget {
+            id: Uuid::nil(),
+            target: "Steam".to_string(),
+            daily_secs: 3600,
+            enabled: true,
+            created_at: Utc::now(),
+        };
+        assert!(budget.covers("Games", "steam.exe"));
+        assert!(budget.covers("STEAM", "launcher"));
+        assert!(!budget.covers("Code", "code"));
+    }
+}
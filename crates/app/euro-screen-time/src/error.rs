@@ -0,0 +1,16 @@
+use thiserror::Error;
+use uuid::Uuid;
+
+pub type ScreenTimeResult<T> = std::result::Result<T, ScreenTimeError>;
+
+#[derive(Debug, Error)]
+pub enum ScreenTimeError {
+    #[error("Screen-time storage: {0}")]
+    Db(#[from] euro_personal_db::PersonalDbError),
+
+    #[error("Screen-time budget {0} not found")]
+    NotFound(Uuid),
+
+    #[error("{0}")]
+    Invalid(String),
+}
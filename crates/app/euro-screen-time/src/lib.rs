@@ -0,0 +1,20 @@
+//! Screen-time budgets: a daily allowance of time per app or site.
+//!
+//! The [`ScreenTimeTracker`] is fed the activity stream and counts the
+//! time spent in each activity per local day into the personal database.
+//! A [`ScreenTimeBudget`] covers every activity whose name or process name
+//! is its target. [`ScreenTimeTracker::newly_exceeded`] returns the
+//! budgets gone over today, once each, for the app to nudge about, and
+//! [`ScreenTimeTracker::weekly_report_due`] returns last week's
+//! [`ScreenTimeReport`] once, at the start of a new week.
+
+mod budget;
+mod error;
+mod report;
+mod tracker;
+mod usage;
+
+pub use budget::{BudgetStatus, ScreenTimeBudget};
+pub use error::{ScreenTimeError, ScreenTimeResult};
+pub use report::{AppUsage, BudgetWeek, ScreenTimeReport, week_start};
+pub use tracker::{MAX_UNSEEN_GAP, MIN_DAILY_SECS, ScreenTimeTracker};
@@ -0,0 +1,189 @@
+use chrono::{Datelike, Days, NaiveDate};
+use euro_personal_db::ScreenTimeUsageRecord;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "specta")]
+use specta::Type;
+
+use crate::budget::ScreenTimeBudget;
+
+/// Where a week's time went, Monday through Sunday.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+#[serde(rename_all = "camelCase")]
+pub struct ScreenTimeReport {
+    /// The Monday the week starts on.
+    pub start: NaiveDate,
+    /// The Sunday it ends on.
+    pub end: NaiveDate,
+    pub total_secs: u32,
+    /// Time per activity, longest first.
+    pub apps: Vec<AppUsage>,
+    /// Every budget, in the order they were created.
+    pub budgets: Vec<BudgetWeek>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+#[serde(rename_all = "camelCase")]
+pub struct AppUsage {
+    pub name: String,
+    pub secs: u32,
+    /// Days of the week it was used on.
+    pub days: u32,
+}
+
+/// How a week went against one budget.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetWeek {
+    pub budget: ScreenTimeBudget,
+    pub secs: u32,
+    /// Days the daily allowance was exceeded.
+    pub days_over: u32,
+}
+
+/// The Monday of the week `day` falls in.
+pub fn week_start(day: NaiveDate) -> NaiveDate {
+    day - Days::new(u64::from(day.weekday().num_days_from_monday()))
+}
+
+/// Add up `usage` for the week starting `start`. Rows outside the week are
+/// ignored.
+pub(crate) fn build(
+    start: NaiveDate,
+    budgets: &[ScreenTimeBudget],
+    usage: &[ScreenTimeUsageRecord],
+) -> ScreenTimeReport {
+    let end = start + Days::new(6);
+    let usage: Vec<_> = usage
+        .iter()
+        .filter(|row| (start..=end).contains(&row.day))
+        .collect();
+
+    let mut apps: Vec<AppUsage> = Vec::new();
+    let mut seen: Vec<(&str, NaiveDate)> = Vec::new();
+    for row in &usage {
+        let first_that_day = !seen.contains(&(row.name.as_str(), row.day));
+        if first_that_day {
+            seen.push((row.name.as_str(), row.day));
+        }
+        match apps.iter_mut().find(|app| app.name == row.name) {
+            Some(app) => {
+                app.secs += row.secs;
+                app.days += u32::from(first_that_day);
+            }
+            None => apps.push(AppUsage {
+                name: row.name.clone(),
+                secs: row.secs,
+                days: 1,
+            }),
+        }
+    }
+    apps.sort_by(|a, b| b.secs.cmp(&a.secs).then_with(|| a.name.cmp(&b.name)));
+
+    let budgets = budgets
+        .iter()
+        .map(|budget| {
+            let mut week = BudgetWeek {
+                budget: budget.clone(),
+                secs: 0,
+                days_over: 0,
+            };
+            for day in start.iter_days().take(7) {
+                let secs = usage
+                    .iter()
+                    .filter(|row| row.day == day && budget.covers(&row.name, &row.process_name))
+                    .map(|row| row.secs)
+                    .sum::<u32>();
+                week.secs += secs;
+                week.days_over += u32::from(secs > budget.daily_secs);
+            }
+            week
+        })
+        .collect();
+
+    ScreenTimeReport {
+        start,
+        end,
+        total_secs: apps.iter().map(|app| app.secs).sum(),
+        apps,
+        budgets,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, d).unwrap()
+    }
+
+    fn usage(d: u32, name: &str, process_name: &str, minutes: u32) -> ScreenTimeUsageRecord {
+        ScreenTimeUsageRecord {
+            day: day(d),
+            name: name.to_string(),
+            process_name: process_name.to_string(),
+            secs: minutes * 60,
+        }
+    }
+
+    #[test]
+    fn weeks_start_on_monday() {
+        // 2 March 2026 is a Monday.
+        assert_eq!(week_start(day(2)), day(2));
+        assert_eq!(week_start(day(8)), day(2));
+        assert_eq!(week_start(day(9)), day(9));
+    }
+
+    #[test]
+    fn adds_up_apps_and_days_over_budget() {
+        let budget = ScreenTimeBudget {
+            id: Uuid::nil(),
+            target: "youtube".to_string(),
+            daily_secs: 30 * 60,
+            enabled: true,
+            created_at: Utc::now(),
+        };
+        let rows = [
+            usage(2, "Youtube", "firefox", 40),
+            usage(2, "Code", "code", 120),
+            usage(3, "Youtube", "firefox", 20),
+            usage(3, "Youtube", "chrome", 15),
+            usage(4, "Code", "code", 60),
+            // The next week.
+            usage(9, "Youtube", "firefox", 300),
+        ];
+
+        let report = build(day(2), &[budget.clone()], &rows);
+        assert_eq!(report.end, day(8));
+        assert_eq!(report.total_secs, (40 + 120 + 20 + 15 + 60) * 60);
+        assert_eq!(
+            report.apps,
+            [
+                AppUsage {
+                    name: "Code".to_string(),
+                    secs: 180 * 60,
+                    days: 2,
+                },
+                AppUsage {
+                    name: "Youtube".to_string(),
+                    secs: 75 * 60,
+                    days: 2,
+                },
+            ]
+        );
+        assert_eq!(
+            report.budgets,
+            [BudgetWeek {
+                budget,
+                secs: 75 * 60,
+                days_over: 2,
+            }]
+        );
+    }
+}
@@ -0,0 +1,353 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Days, Local, NaiveDate, TimeDelta, Utc};
+use euro_personal_db::PersonalDb;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::budget::{BudgetStatus, ScreenTimeBudget};
+use crate::error::{ScreenTimeError, ScreenTimeResult};
+use crate::report::{self, ScreenTimeReport, week_start};
+use crate::usage::split_by_day;
+
+/// Smallest daily allowance a budget accepts.
+pub const MIN_DAILY_SECS: u32 = 60;
+/// Longest gap between two updates that still counts as time in the
+/// activity. A longer one means the machine slept or the app stalled, so
+/// it isn't counted. Callers should [`ScreenTimeTracker::flush`] well
+/// within it.
+pub const MAX_UNSEEN_GAP: TimeDelta = TimeDelta::minutes(5);
+/// Longest target, in characters.
+const MAX_TARGET_CHARS: usize = 100;
+
+const BUDGET_NUDGE: &str = "budget";
+const WEEKLY_NUDGE: &str = "weekly";
+
+/// The activity the user is in, and since when its time was last counted.
+struct Current {
+    name: String,
+    process_name: String,
+    since: DateTime<Utc>,
+}
+
+/// Counts the time spent in each activity into the personal database and
+/// checks it against the budgets. Cheap to clone; clones share state.
+/// Every method takes the time to act at, so callers and tests decide
+/// what "now" is. Days are the user's local days.
+#[derive(Clone)]
+pub struct ScreenTimeTracker {
+    db: PersonalDb,
+    current: Arc<Mutex<Option<Current>>>,
+}
+
+impl ScreenTimeTracker {
+    pub fn new(db: PersonalDb) -> Self {
+        Self {
+            db,
+            current: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// The user moved to another activity: count the time in the previous
+    /// one and start counting this one.
+    pub async fn record_activity(
+        &self,
+        name: &str,
+        process_name: &str,
+        now: DateTime<Utc>,
+    ) -> ScreenTimeResult<()> {
+        let mut current = self.current.lock().await;
+        self.count(&mut current, now).await?;
+        *current = Some(Current {
+            name: name.to_string(),
+            process_name: process_name.to_string(),
+            since: now,
+        });
+        Ok(())
+    }
+
+    /// Count the time in the current activity up to `now`.
+    pub async fn flush(&self, now: DateTime<Utc>) -> ScreenTimeResult<()> {
+        let mut current = self.current.lock().await;
+        self.count(&mut current, now).await
+    }
+
+    async fn count(
+        &self,
+        current: &mut Option<Current>,
+        now: DateTime<Utc>,
+    ) -> ScreenTimeResult<()> {
+        let Some(current) = current else {
+            return Ok(());
+        };
+        if now - current.since <= MAX_UNSEEN_GAP {
+            for (day, secs) in split_by_day(current.since, now, &Local) {
+                if secs > 0 {
+                    self.db
+                        .add_screen_time(day, &current.name, &current.process_name, secs)
+                        .await?;
+                }
+            }
+        }
+        current.since = current.since.max(now);
+        Ok(())
+    }
+
+    /// Every budget with the time spent against it today, oldest first.
+    pub async fn status(&self, now: DateTime<Utc>) -> ScreenTimeResult<Vec<BudgetStatus>> {
+        self.flush(now).await?;
+        let today = local_day(now);
+        let usage = self.db.screen_time_usage(today, today).await?;
+        Ok(self
+            .budgets()
+            .await?
+            .into_iter()
+            .map(|budget| BudgetStatus {
+                used_secs: usage
+                    .iter()
+                    .filter(|row| budget.covers(&row.name, &row.process_name))
+                    .map(|row| row.secs)
+                    .sum(),
+                budget,
+            })
+            .collect())
+    }
+
+    /// The enabled budgets exceeded today that haven't been reported yet.
+    /// Each budget is returned at most once a day.
+    pub async fn newly_exceeded(&self, now: DateTime<Utc>) -> ScreenTimeResult<Vec<BudgetStatus>> {
+        let today = local_day(now);
+        let mut exceeded = Vec::new();
+        for status in self.status(now).await? {
+            if status.budget.enabled
+                && status.exceeded()
+                && self
+                    .db
+                    .claim_screen_time_nudge(
+                        BUDGET_NUDGE,
+                        &status.budget.id.to_string(),
+                        today,
+                        now,
+                    )
+                    .await?
+            {
+                exceeded.push(status);
+            }
+        }
+        Ok(exceeded)
+    }
+
+    /// Last week's report, the first time this is called in a new week.
+    /// `None` after that, and for a week with no time counted.
+    pub async fn weekly_report_due(
+        &self,
+        now: DateTime<Utc>,
+    ) -> ScreenTimeResult<Option<ScreenTimeReport>> {
+        let last_week = week_start(local_day(now)) - Days::new(7);
+        if !self
+            .db
+            .claim_screen_time_nudge(WEEKLY_NUDGE, "", last_week, now)
+            .await?
+        {
+            return Ok(None);
+        }
+        let report = self.report(last_week).await?;
+        Ok((report.total_secs > 0).then_some(report))
+    }
+
+    /// The report on this week so far for `0`, last week for `1`, and so
+    /// on.
+    pub async fn report_weeks_ago(
+        &self,
+        weeks_ago: u32,
+        now: DateTime<Utc>,
+    ) -> ScreenTimeResult<ScreenTimeReport> {
+        self.flush(now).await?;
+        let start = week_start(local_day(now))
+            .checked_sub_days(Days::new(7 * u64::from(weeks_ago)))
+            .ok_or_else(|| ScreenTimeError::Invalid("That week is out of range".to_string()))?;
+        self.report(start).await
+    }
+
+    async fn report(&self, start: NaiveDate) -> ScreenTimeResult<ScreenTimeReport> {
+        let usage = self
+            .db
+            .screen_time_usage(start, start + Days::new(6))
+            .await?;
+        Ok(report::build(start, &self.budgets().await?, &usage))
+    }
+
+    /// Every budget, oldest first.
+    pub async fn budgets(&self) -> ScreenTimeResult<Vec<ScreenTimeBudget>> {
+        Ok(self
+            .db
+            .screen_time_budgets()
+            .await?
+            .into_iter()
+            .map(ScreenTimeBudget::from)
+            .collect())
+    }
+
+    pub async fn create_budget(
+        &self,
+        target: &str,
+        daily_secs: u32,
+        now: DateTime<Utc>,
+    ) -> ScreenTimeResult<ScreenTimeBudget> {
+        let budget = ScreenTimeBudget {
+            id: Uuid::now_v7(),
+            target: validate_target(target)?,
+            daily_secs: validate_daily_secs(daily_secs)?,
+            enabled: true,
+            created_at: now,
+        };
+        self.db.save_screen_time_budget(&budget.to_record()).await?;
+        Ok(budget)
+    }
+
+    pub async fn update_budget(
+        &self,
+        id: Uuid,
+        target: &str,
+        daily_secs: u32,
+        enabled: bool,
+    ) -> ScreenTimeResult<ScreenTimeBudget> {
+        let mut budget: ScreenTimeBudget = self
+            .db
+            .screen_time_budget(id)
+            .await?
+            .ok_or(ScreenTimeError::NotFound(id))?
+            .into();
+        budget.target = validate_target(target)?;
+        budget.daily_secs = validate_daily_secs(daily_secs)?;
+        budget.enabled = enabled;
+        self.db.save_screen_time_budget(&budget.to_record()).await?;
+        Ok(budget)
+    }
+
+    pub async fn delete_budget(&self, id: Uuid) -> ScreenTimeResult<()> {
+        if self.db.delete_screen_time_budget(id).await? {
+            Ok(())
+        } else {
+            Err(ScreenTimeError::NotFound(id))
+        }
+    }
+}
+
+fn local_day(at: DateTime<Utc>) -> NaiveDate {
+    at.with_timezone(&Local).date_naive()
+}
+
+fn validate_target(target: &str) -> ScreenTimeResult<String> {
+    let target = target.trim();
+    if target.is_empty() {
+        return Err(ScreenTimeError::Invalid(
+            "Name the app or site the budget is for".to_string(),
+        ));
+    }
+    if target.chars().count() > MAX_TARGET_CHARS {
+        return Err(ScreenTimeError::Invalid(format!(
+            "The app or site name must be at most {MAX_TARGET_CHARS} characters"
+        )));
+    }
+    Ok(target.to_string())
+}
+
+fn validate_daily_secs(daily_secs: u32) -> ScreenTimeResult<u32> {
+    const DAY_SECS: u32 = 24 * 60 * 60;
+    if !(MIN_DAILY_SECS..=DAY_SECS).contains(&daily_secs) {
+        return Err(ScreenTimeError::Invalid(
+            "A daily budget must be between 1 minute and 24 hours".to_string(),
+        ));
+    }
+    Ok(daily_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::TimeZone;
+    use euro_personal_db::DB_FILE_NAME;
+
+    /// Noon UTC, far enough from local midnight in most zones that the
+    /// few minutes a test spans stay on one local day.
+    fn at(minute: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 4, 12, 0, 0).unwrap() + TimeDelta::minutes(minute)
+    }
+
+    async fn tracker() -> (tempfile::TempDir, ScreenTimeTracker) {
+        let dir = tempfile::tempdir().unwrap();
+        let db = PersonalDb::open(dir.path().join(DB_FILE_NAME))
+            .await
+            .unwrap();
+        (dir, ScreenTimeTracker::new(db))
+    }
+
+    #[tokio::test]
+    async fn budgets_are_reported_once_when_exceeded() {
+        let (_dir, tracker) = tracker().await;
+        let budget = tracker
+            .create_budget(" youtube ", 3 * 60, at(0))
+            .await
+            .unwrap();
+        assert_eq!(budget.target, "youtube");
+
+        tracker
+            .record_activity("Youtube", "firefox", at(0))
+            .await
+            .unwrap();
+        tracker.flush(at(2)).await.unwrap();
+        assert!(tracker.newly_exceeded(at(2)).await.unwrap().is_empty());
+
+        tracker
+            .record_activity("Code", "code", at(4))
+            .await
+            .unwrap();
+        let exceeded = tracker.newly_exceeded(at(4)).await.unwrap();
+        assert_eq!(exceeded.len(), 1);
+        assert_eq!(exceeded[0].used_secs, 4 * 60);
+        assert!(tracker.newly_exceeded(at(5)).await.unwrap().is_empty());
+
+        tracker
+            .update_budget(budget.id, "youtube", 10 * 60, false)
+            .await
+            .unwrap();
+        let status = tracker.status(at(5)).await.unwrap();
+        assert!(!status[0].exceeded());
+        assert!(!status[0].budget.enabled);
+    }
+
+    #[tokio::test]
+    async fn gaps_longer_than_the_limit_are_not_counted() {
+        let (_dir, tracker) = tracker().await;
+        tracker
+            .record_activity("Code", "code", at(0))
+            .await
+            .unwrap();
+        tracker.flush(at(1)).await.unwrap();
+        // Asleep for an hour.
+        tracker.flush(at(61)).await.unwrap();
+        tracker.flush(at(62)).await.unwrap();
+
+        let report = tracker.report_weeks_ago(0, at(62)).await.unwrap();
+        assert_eq!(report.total_secs, 2 * 60);
+    }
+
+    #[tokio::test]
+    async fn rejects_bad_budgets() {
+        let (_dir, tracker) = tracker().await;
+        assert!(matches!(
+            tracker.create_budget("  ", 600, at(0)).await,
+            Err(ScreenTimeError::Invalid(_))
+        ));
+        assert!(matches!(
+            tracker.create_budget("youtube", 10, at(0)).await,
+            Err(ScreenTimeError::Invalid(_))
+        ));
+        assert!(matches!(
+            tracker.delete_budget(Uuid::now_v7()).await,
+            Err(ScreenTimeError::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,47 @@
+use chrono::{DateTime, NaiveDate, NaiveTime, TimeZone, Utc};
+
+/// Split the time from `from` to `to` into seconds per calendar day in
+/// `tz`, earliest first. Empty when `to` isn't after `from`.
+pub(crate) fn split_by_day<Tz: TimeZone>(
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    tz: &Tz,
+) -> Vec<(NaiveDate, u32)> {
+    let mut days = Vec::new();
+    let mut cursor = from;
+    while cursor < to {
+        let day = cursor.with_timezone(tz).date_naive();
+        // A midnight skipped by a clock change ends the split at `to`.
+        let day_end = day
+            .succ_opt()
+            .and_then(|next| {
+                tz.from_local_datetime(&next.and_time(NaiveTime::MIN))
+                    .earliest()
+            })
+            .map(|midnight| midnight.to_utc().min(to))
+            .unwrap_or(to);
+        let secs = u32::try_from((day_end - cursor).num_seconds()).unwrap_or(0);
+        days.push((day, secs));
+        cursor = day_end;
+    }
+    days
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_at_midnight() {
+        let from = Utc.with_ymd_and_hms(2026, 3, 2, 23, 50, 0).unwrap();
+        let to = Utc.with_ymd_and_hms(2026, 3, 3, 0, 5, 0).unwrap();
+        assert_eq!(
+            split_by_day(from, to, &Utc),
+            [
+                (NaiveDate::from_ymd_opt(2026, 3, 2).unwrap(), 600),
+                (NaiveDate::from_ymd_opt(2026, 3, 3).unwrap(), 300),
+            ]
+        );
+        assert!(split_by_day(to, from, &Utc).is_empty());
+    }
+}
@@ -0,0 +1,138 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "specta")]
+use specta::Type;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::error::ScriptResult;
+
+/// Past this size the log is cut back to its newest [`KEEP_RUNS`].
+const MAX_LOG_BYTES: u64 = 4 * 1024 * 1024;
+const KEEP_RUNS: usize = 1_000;
+
+/// One finished run, as kept in the audit log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptRun {
+    pub id: Uuid,
+    pub script_id: String,
+    /// The name when it ran, which may have changed since.
+    pub script_name: String,
+    pub trigger: RunTrigger,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u32,
+    pub outcome: RunOutcome,
+    /// Why the run failed, for [`RunOutcome::Failed`].
+    pub error: Option<String>,
+    /// The script's final value, unless it was `()`.
+    pub result: Option<String>,
+    /// Lines written with `print`.
+    pub output: Vec<String>,
+    pub calls: Vec<ApiCall>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+#[serde(rename_all = "snake_case")]
+pub enum RunTrigger {
+    Manual,
+    Schedule,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+#[serde(rename_all = "snake_case")]
+pub enum RunOutcome {
+    Succeeded,
+    Failed,
+    TimedOut,
+    Cancelled,
+}
+
+/// One call a script made into the host API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+#[serde(rename_all = "camelCase")]
+pub struct ApiCall {
+    pub function: String,
+    /// What was asked for, shortened. What came back, such as clipboard
+    /// contents or the model's answer, isn't kept.
+    pub summary: Option<String>,
+    pub duration_ms: u32,
+    pub error: Option<String>,
+}
+
+/// Every script run, one JSON object per line, oldest first.
+pub struct AuditLog {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl AuditLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    pub async fn append(&self, run: &ScriptRun) -> ScriptResult<()> {
+        let mut line = serde_json::to_vec(run).map_err(std::io::Error::other)?;
+        line.push(b'\n');
+
+        let _guard = self.lock.lock().await;
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        file.write_all(&line).await?;
+        file.flush().await?;
+        if file.metadata().await?.len() > MAX_LOG_BYTES {
+            drop(file);
+            self.trim().await?;
+        }
+        Ok(())
+    }
+
+    /// The newest runs, of one script or of all, newest first. Lines that
+    /// don't parse are skipped.
+    pub async fn recent(
+        &self,
+        script_id: Option<&str>,
+        limit: usize,
+    ) -> ScriptResult<Vec<ScriptRun>> {
+        let _guard = self.lock.lock().await;
+        let text = match tokio::fs::read_to_string(&self.path).await {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(text
+            .lines()
+            .rev()
+            .filter_map(|line| serde_json::from_str::<ScriptRun>(line).ok())
+            .filter(|run| script_id.is_none_or(|id| run.script_id == id))
+            .take(limit)
+            .collect())
+    }
+
+    /// Keep only the newest [`KEEP_RUNS`] lines.
+    async fn trim(&self) -> ScriptResult<()> {
+        let text = tokio::fs::read_to_string(&self.path).await?;
+        let lines: Vec<&str> = text.lines().collect();
+        let kept = lines[lines.len().saturating_sub(KEEP_RUNS)..].join("\n") + "\n";
+        let tmp = self.path.with_extension("jsonl.tmp");
+        tokio::fs::write(&tmp, kept).await?;
+        tokio::fs::rename(&tmp, &self.path).await?;
+        Ok(())
+    }
+}
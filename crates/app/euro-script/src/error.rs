@@ -0,0 +1,22 @@
+use thiserror::Error;
+
+pub type ScriptResult<T> = std::result::Result<T, ScriptError>;
+
+#[derive(Debug, Error)]
+pub enum ScriptError {
+    #[error("Script audit log: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The source doesn't parse; the message carries the position.
+    #[error("{0}")]
+    Compile(String),
+
+    #[error("Script {0} not found")]
+    NotFound(String),
+
+    #[error("Script {0} is already running")]
+    AlreadyRunning(String),
+
+    #[error("Script runner stopped")]
+    Stopped,
+}
@@ -0,0 +1,27 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+/// The curated API scripts call, implemented by the app. Errors are
+/// handed to the script as catchable Rhai errors.
+#[async_trait]
+pub trait ScriptHost: Send + Sync {
+    /// Run one chat turn in a new thread and return the answer.
+    async fn ask_model(&self, question: &str) -> Result<String, String>;
+
+    /// Recent timeline records mentioning `text`, newest first.
+    async fn search_timeline(&self, text: &str, limit: u32) -> Result<Vec<TimelineEntry>, String>;
+
+    async fn read_clipboard(&self) -> Result<String, String>;
+
+    /// Show the user a notification from `script`.
+    async fn notify(&self, script: &str, title: &str, body: &str) -> Result<(), String>;
+}
+
+/// One timeline record, as scripts see it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimelineEntry {
+    /// What was recorded, e.g. `focus` or `ocr`.
+    pub kind: String,
+    pub recorded_at: DateTime<Utc>,
+    pub text: String,
+}
@@ -0,0 +1,38 @@
+//! User-written automations for the desktop, in Rhai.
+//!
+//! A [`Script`] is Rhai source with a [`Schedule`] and a timeout. The
+//! [`ScriptRunner`] runs it on a blocking thread in an engine with no
+//! file, module or network access of its own. The only way out is the
+//! curated API below, which the app implements through [`ScriptHost`]:
+//!
+//! - `ask_model(question)` — one chat turn in a new thread; returns the
+//!   answer.
+//! - `search_timeline(text)`, `search_timeline(text, limit)` — recent
+//!   timeline records mentioning `text`, newest first, as maps with
+//!   `kind`, `recorded_at` and `text`.
+//! - `read_clipboard()` — the clipboard's text.
+//! - `notify(body)`, `notify(title, body)` — show the user a
+//!   notification.
+//!
+//! Lines written with `print` become the run's output. A run stops at
+//! the script's timeout, host calls included, and at limits on
+//! operations, nesting, API calls, and the size of strings, arrays and
+//! maps. Every run is appended to the [`AuditLog`] with the API calls it
+//! made.
+//!
+//! The [`ScriptScheduler`] starts runs as their schedules come due, at
+//! most one at a time per script.
+
+mod audit;
+mod error;
+mod host;
+mod runner;
+mod schedule;
+mod scheduler;
+
+pub use audit::{ApiCall, AuditLog, RunOutcome, RunTrigger, ScriptRun};
+pub use error::{ScriptError, ScriptResult};
+pub use host::{ScriptHost, TimelineEntry};
+pub use runner::ScriptRunner;
+pub use schedule::{Schedule, Script};
+pub use scheduler::ScriptScheduler;
@@ -0,0 +1,478 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use chrono::Utc;
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Map};
+use tokio::runtime::Handle;
+use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use crate::audit::{ApiCall, AuditLog, RunOutcome, RunTrigger, ScriptRun};
+use crate::error::{ScriptError, ScriptResult};
+use crate::host::ScriptHost;
+use crate::schedule::Script;
+
+type RhaiResult<T> = Result<T, Box<EvalAltResult>>;
+
+/// Runs in flight at once; more wait their turn.
+const MAX_CONCURRENT_RUNS: usize = 2;
+/// Roughly a few seconds of straight computation; waiting on the host
+/// doesn't count.
+const MAX_OPERATIONS: u64 = 50_000_000;
+const MAX_CALL_LEVELS: usize = 32;
+const MAX_EXPR_DEPTH: usize = 64;
+const MAX_FUNCTION_EXPR_DEPTH: usize = 32;
+const MAX_STRING_BYTES: usize = 1024 * 1024;
+const MAX_COLLECTION_LEN: usize = 10_000;
+const MAX_API_CALLS: usize = 100;
+const MAX_OUTPUT_LINES: usize = 200;
+const MAX_OUTPUT_LINE_CHARS: usize = 1_000;
+const MAX_SUMMARY_CHARS: usize = 120;
+const DEFAULT_SEARCH_LIMIT: i64 = 20;
+const MAX_SEARCH_LIMIT: i64 = 200;
+
+/// Runs scripts against a [`ScriptHost`] and audits every run.
+#[derive(Clone)]
+pub struct ScriptRunner {
+    host: Arc<dyn ScriptHost>,
+    audit: Arc<AuditLog>,
+    slots: Arc<Semaphore>,
+}
+
+impl ScriptRunner {
+    pub fn new(host: Arc<dyn ScriptHost>, audit: AuditLog) -> Self {
+        Self {
+            host,
+            audit: Arc::new(audit),
+            slots: Arc::new(Semaphore::new(MAX_CONCURRENT_RUNS)),
+        }
+    }
+
+    pub fn audit(&self) -> &AuditLog {
+        &self.audit
+    }
+
+    /// Parse `source` without running it. The error names the first
+    /// problem and where it is.
+    pub fn check(source: &str) -> ScriptResult<()> {
+        sandboxed_engine()
+            .compile(source)
+            .map(|_| ())
+            .map_err(|e| ScriptError::Compile(e.to_string()))
+    }
+
+    /// Run `script` to completion, its timeout, or `cancel`, and record
+    /// the run in the audit log.
+    pub async fn run(
+        &self,
+        script: &Script,
+        trigger: RunTrigger,
+        cancel: CancellationToken,
+    ) -> ScriptResult<ScriptRun> {
+        let _slot = self
+            .slots
+            .acquire()
+            .await
+            .map_err(|_| ScriptError::Stopped)?;
+        let started_at = Utc::now();
+        let started = Instant::now();
+        let bridge = Arc::new(Bridge {
+            host: Arc::clone(&self.host),
+            runtime: Handle::current(),
+            script_name: script.name.clone(),
+            deadline: tokio::time::Instant::now() + script.timeout,
+            cancel: cancel.clone(),
+            calls: Mutex::new(Vec::new()),
+            output: Mutex::new(Vec::new()),
+        });
+
+        let evaluated = tokio::task::spawn_blocking({
+            let source = script.source.clone();
+            let bridge = Arc::clone(&bridge);
+            move || evaluate(&source, bridge)
+        })
+        .await
+        .map_err(|_| ScriptError::Stopped)?;
+
+        // A host call cut short by the deadline or cancellation surfaces
+        // as an ordinary error, so the cause is read from the bridge.
+        let (outcome, error, result) = match evaluated {
+            Ok(value) if value.is_unit() => (RunOutcome::Succeeded, None, None),
+            Ok(value) => (RunOutcome::Succeeded, None, Some(value.to_string())),
+            Err(_) if cancel.is_cancelled() => (RunOutcome::Cancelled, None, None),
+            Err(_) if bridge.past_deadline() => (RunOutcome::TimedOut, None, None),
+            Err(e) => (RunOutcome::Failed, Some(e.to_string()), None),
+        };
+        let run = ScriptRun {
+            id: Uuid::now_v7(),
+            script_id: script.id.clone(),
+            script_name: script.name.clone(),
+            trigger,
+            started_at,
+            duration_ms: elapsed_ms(started),
+            outcome,
+            error,
+            result,
+            output: take(&bridge.output),
+            calls: take(&bridge.calls),
+        };
+        tracing::info!(
+            script = %script.id,
+            outcome = ?run.outcome,
+            duration_ms = run.duration_ms,
+            "Script run finished"
+        );
+        if let Err(e) = self.audit.append(&run).await {
+            tracing::warn!(script = %script.id, "Failed to audit script run: {e}");
+        }
+        Ok(run)
+    }
+}
+
+/// An engine that can't reach anything the host doesn't register, with
+/// limits on how much it computes and allocates.
+fn sandboxed_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_module_resolver(DummyModuleResolver::new())
+        .set_max_modules(0)
+        .set_max_operations(MAX_OPERATIONS)
+        .set_max_call_levels(MAX_CALL_LEVELS)
+        .set_max_expr_depths(MAX_EXPR_DEPTH, MAX_FUNCTION_EXPR_DEPTH)
+        .set_max_string_size(MAX_STRING_BYTES)
+        .set_max_array_size(MAX_COLLECTION_LEN)
+        .set_max_map_size(MAX_COLLECTION_LEN);
+    engine.disable_symbol("eval").disable_symbol("import");
+    engine
+}
+
+/// Compile and run `source` with the host API registered. Blocks, so it
+/// runs on a blocking thread.
+fn evaluate(source: &str, bridge: Arc<Bridge>) -> RhaiResult<Dynamic> {
+    let mut engine = sandboxed_engine();
+    {
+        let bridge = Arc::clone(&bridge);
+        engine.on_progress(move |_| bridge.should_stop().then_some(Dynamic::UNIT));
+    }
+    {
+        let bridge = Arc::clone(&bridge);
+        engine.on_print(move |text| bridge.print(text));
+    }
+    {
+        let bridge = Arc::clone(&bridge);
+        engine.on_debug(move |text, _, _| bridge.print(text));
+    }
+    register_api(&mut engine, &bridge);
+
+    engine.eval::<Dynamic>(source)
+}
+
+fn register_api(engine: &mut Engine, bridge: &Arc<Bridge>) {
+    let b = Arc::clone(bridge);
+    engine.register_fn("ask_model", move |question: &str| -> RhaiResult<String> {
+        let host = Arc::clone(&b.host);
+        let question = question.to_owned();
+        b.call(
+            "ask_model",
+            Some(shorten(&question, MAX_SUMMARY_CHARS)),
+            async move { host.ask_model(&question).await },
+        )
+    });
+
+    let b = Arc::clone(bridge);
+    engine.register_fn("search_timeline", move |text: &str| {
+        search_timeline(&b, text, DEFAULT_SEARCH_LIMIT)
+    });
+    let b = Arc::clone(bridge);
+    engine.register_fn("search_timeline", move |text: &str, limit: i64| {
+        search_timeline(&b, text, limit)
+    });
+
+    let b = Arc::clone(bridge);
+    engine.register_fn("read_clipboard", move || -> RhaiResult<String> {
+        let host = Arc::clone(&b.host);
+        b.call("read_clipboard", None, async move {
+            host.read_clipboard().await
+        })
+    });
+
+    let b = Arc::clone(bridge);
+    engine.register_fn("notify", move |body: &str| {
+        let title = b.script_name.clone();
+        notify(&b, &title, body)
+    });
+    let b = Arc::clone(bridge);
+    engine.register_fn("notify", move |title: &str, body: &str| {
+        notify(&b, title, body)
+    });
+}
+
+fn search_timeline(bridge: &Bridge, text: &str, limit: i64) -> RhaiResult<Array> {
+    let host = Arc::clone(&bridge.host);
+    let query = text.to_owned();
+    let limit = limit.clamp(1, MAX_SEARCH_LIMIT) as u32;
+    let entries = bridge.call(
+        "search_timeline",
+        Some(shorten(text, MAX_SUMMARY_CHARS)),
+        async move { host.search_timeline(&query, limit).await },
+    )?;
+    Ok(entries
+        .into_iter()
+        .map(|entry| {
+            let mut map = Map::new();
+            map.insert("kind".into(), entry.kind.into());
+            map.insert("recorded_at".into(), entry.recorded_at.to_rfc3339().into());
+            map.insert("text".into(), entry.text.into());
+            Dynamic::from_map(map)
+        })
+        .collect())
+}
+
+fn notify(bridge: &Bridge, title: &str, body: &str) -> RhaiResult<()> {
+    let host = Arc::clone(&bridge.host);
+    let script = bridge.script_name.clone();
+    let (title, body) = (title.to_owned(), body.to_owned());
+    bridge.call(
+        "notify",
+        Some(shorten(&title, MAX_SUMMARY_CHARS)),
+        async move { host.notify(&script, &title, &body).await },
+    )
+}
+
+/// What a run's host functions share.
+struct Bridge {
+    host: Arc<dyn ScriptHost>,
+    /// Host calls are async; the script waits on them from its blocking
+    /// thread.
+    runtime: Handle,
+    script_name: String,
+    deadline: tokio::time::Instant,
+    cancel: CancellationToken,
+    calls: Mutex<Vec<ApiCall>>,
+    output: Mutex<Vec<String>>,
+}
+
+impl Bridge {
+    fn past_deadline(&self) -> bool {
+        tokio::time::Instant::now() >= self.deadline
+    }
+
+    fn should_stop(&self) -> bool {
+        self.cancel.is_cancelled() || self.past_deadline()
+    }
+
+    fn print(&self, text: &str) {
+        if let Ok(mut output) = self.output.lock()
+            && output.len() < MAX_OUTPUT_LINES
+        {
+            output.push(shorten(text, MAX_OUTPUT_LINE_CHARS));
+        }
+    }
+
+    /// Wait for `request`, bounded by the run's deadline and
+    /// cancellation, and record the call.
+    fn call<T>(
+        &self,
+        function: &str,
+        summary: Option<String>,
+        request: impl Future<Output = Result<T, String>>,
+    ) -> RhaiResult<T> {
+        if self.should_stop() {
+            return Err("script stopped".into());
+        }
+        let made = self.calls.lock().map_or(0, |calls| calls.len());
+        if made >= MAX_API_CALLS {
+            return Err(format!("at most {MAX_API_CALLS} API calls per run").into());
+        }
+
+        let started = Instant::now();
+        let result = self.runtime.block_on(async {
+            tokio::select! {
+                result = tokio::time::timeout_at(self.deadline, request) => {
+                    result.unwrap_or_else(|_| Err("timed out".into()))
+                }
+                () = self.cancel.cancelled() => Err("cancelled".into()),
+            }
+        });
+        if let Ok(mut calls) = self.calls.lock() {
+            calls.push(ApiCall {
+                function: function.to_owned(),
+                summary,
+                duration_ms: elapsed_ms(started),
+                error: result.as_ref().err().cloned(),
+            });
+        }
+        result.map_err(Into::into)
+    }
+}
+
+/// Runs and calls end within the script's timeout, so this doesn't cap
+/// in practice.
+fn elapsed_ms(started: Instant) -> u32 {
+    u32::try_from(started.elapsed().as_millis()).unwrap_or(u32::MAX)
+}
+
+fn shorten(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_owned(),
+    }
+}
+
+fn take<T>(items: &Mutex<Vec<T>>) -> Vec<T> {
+    items
+        .lock()
+        .map(|mut items| std::mem::take(&mut *items))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+
+    use super::*;
+    use crate::host::TimelineEntry;
+    use crate::schedule::Schedule;
+
+    struct FakeHost;
+
+    #[async_trait]
+    impl ScriptHost for FakeHost {
+        async fn ask_model(&self, question: &str) -> Result<String, String> {
+            Ok(format!("answer to {question}"))
+        }
+
+        async fn search_timeline(
+            &self,
+            text: &str,
+            _limit: u32,
+        ) -> Result<Vec<TimelineEntry>, String> {
+            Ok(vec![TimelineEntry {
+                kind: "ocr".into(),
+                recorded_at: Utc::now(),
+                text: format!("mentions {text}"),
+            }])
+        }
+
+        async fn read_clipboard(&self) -> Result<String, String> {
+            Err("clipboard is empty".into())
+        }
+
+        async fn notify(&self, _script: &str, _title: &str, _body: &str) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    fn script(source: &str, timeout: Duration) -> Script {
+        Script {
+            id: "s1".into(),
+            name: "Standup".into(),
+            source: source.into(),
+            schedule: Schedule::Manual,
+            timeout,
+        }
+    }
+
+    fn runner(dir: &tempfile::TempDir) -> ScriptRunner {
+        ScriptRunner::new(
+            Arc::new(FakeHost),
+            AuditLog::new(dir.path().join("audit.jsonl")),
+        )
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn scripts_call_the_host_and_runs_are_audited() {
+        let dir = tempfile::tempdir().unwrap();
+        let runner = runner(&dir);
+        let source = r#"
+            let hits = search_timeline("report");
+            print(hits[0].text);
+            try { read_clipboard(); } catch (e) { }
+            notify("done");
+            ask_model("what's up?")
+        "#;
+
+        let run = runner
+            .run(
+                &script(source, Duration::from_secs(10)),
+                RunTrigger::Manual,
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(run.outcome, RunOutcome::Succeeded);
+        assert_eq!(run.result.as_deref(), Some("answer to what's up?"));
+        assert_eq!(run.output, ["mentions report"]);
+        let functions: Vec<_> = run.calls.iter().map(|c| c.function.as_str()).collect();
+        assert_eq!(
+            functions,
+            ["search_timeline", "read_clipboard", "notify", "ask_model"]
+        );
+        assert_eq!(run.calls[1].error.as_deref(), Some("clipboard is empty"));
+
+        let audited = runner.audit().recent(Some("s1"), 10).await.unwrap();
+        assert_eq!(audited.len(), 1);
+        assert_eq!(audited[0].id, run.id);
+        assert!(
+            runner
+                .audit()
+                .recent(Some("other"), 10)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn runaway_scripts_are_stopped() {
+        let dir = tempfile::tempdir().unwrap();
+        let runner = runner(&dir);
+
+        let run = runner
+            .run(
+                &script("loop { }", Duration::from_millis(200)),
+                RunTrigger::Schedule,
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(run.outcome, RunOutcome::TimedOut);
+
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let run = runner
+            .run(
+                &script("loop { }", Duration::from_secs(60)),
+                RunTrigger::Manual,
+                cancel,
+            )
+            .await
+            .unwrap();
+        assert_eq!(run.outcome, RunOutcome::Cancelled);
+
+        let run = runner
+            .run(
+                &script("eval(\"1\")", Duration::from_secs(10)),
+                RunTrigger::Manual,
+                CancellationToken::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(run.outcome, RunOutcome::Failed);
+    }
+
+    #[test]
+    fn check_reports_syntax_errors() {
+        assert!(ScriptRunner::check("let x = 1; x + 1").is_ok());
+        assert!(matches!(
+            ScriptRunner::check("let = ;"),
+            Err(ScriptError::Compile(_))
+        ));
+        assert!(ScriptRunner::check("import \"fs\" as fs;").is_err());
+    }
+}
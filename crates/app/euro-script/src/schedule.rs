@@ -0,0 +1,98 @@
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveTime, TimeDelta, TimeZone};
+
+/// A script as the runner and scheduler see it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Script {
+    pub id: String,
+    pub name: String,
+    /// Rhai source.
+    pub source: String,
+    pub schedule: Schedule,
+    /// Wall-clock budget for one run, host calls included.
+    pub timeout: Duration,
+}
+
+/// When a script runs without being asked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Schedule {
+    /// Never; only by hand.
+    Manual,
+    /// Every interval, counted from when the schedule was set.
+    Every(Duration),
+    /// Once a day at `hour:minute` in the clock's time zone.
+    Daily { hour: u32, minute: u32 },
+}
+
+impl Schedule {
+    /// The first time after `now` this schedule comes due. `None` for
+    /// [`Schedule::Manual`], or a time that doesn't exist.
+    pub fn next_after<Tz: TimeZone>(&self, now: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        match *self {
+            Self::Manual => None,
+            Self::Every(interval) => Some(now.clone() + TimeDelta::from_std(interval).ok()?),
+            Self::Daily { hour, minute } => {
+                let time = NaiveTime::from_hms_opt(hour, minute, 0)?;
+                // Two days ahead covers a time skipped by a DST change
+                // tomorrow.
+                now.date_naive()
+                    .iter_days()
+                    .take(3)
+                    .filter_map(|day| {
+                        now.timezone()
+                            .from_local_datetime(&day.and_time(time))
+                            .earliest()
+                    })
+                    .find(|at| at > now)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+
+    #[test]
+    fn schedules_come_due_after_now() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 14, 9, 30, 0).unwrap();
+
+        assert_eq!(Schedule::Manual.next_after(&now), None);
+        assert_eq!(
+            Schedule::Every(Duration::from_secs(15 * 60)).next_after(&now),
+            Some(Utc.with_ymd_and_hms(2026, 3, 14, 9, 45, 0).unwrap())
+        );
+        assert_eq!(
+            Schedule::Daily {
+                hour: 17,
+                minute: 0
+            }
+            .next_after(&now),
+            Some(Utc.with_ymd_and_hms(2026, 3, 14, 17, 0, 0).unwrap())
+        );
+        // Already past today, and exactly now, both mean tomorrow.
+        assert_eq!(
+            Schedule::Daily { hour: 8, minute: 0 }.next_after(&now),
+            Some(Utc.with_ymd_and_hms(2026, 3, 15, 8, 0, 0).unwrap())
+        );
+        assert_eq!(
+            Schedule::Daily {
+                hour: 9,
+                minute: 30
+            }
+            .next_after(&now),
+            Some(Utc.with_ymd_and_hms(2026, 3, 15, 9, 30, 0).unwrap())
+        );
+        assert_eq!(
+            Schedule::Daily {
+                hour: 24,
+                minute: 0
+            }
+            .next_after(&now),
+            None
+        );
+    }
+}
@@ -0,0 +1,264 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Local, Utc};
+use tokio_util::sync::CancellationToken;
+
+use crate::audit::{RunTrigger, ScriptRun};
+use crate::error::{ScriptError, ScriptResult};
+use crate::runner::ScriptRunner;
+use crate::schedule::Script;
+
+/// How often [`ScriptScheduler::run`] looks for scripts that are due.
+const TICK: Duration = Duration::from_secs(15);
+
+/// Keeps the user's scripts and starts them as their schedules come due.
+/// Cheap to clone; clones share the same scripts.
+#[derive(Clone)]
+pub struct ScriptScheduler {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    runner: ScriptRunner,
+    entries: Mutex<BTreeMap<String, Entry>>,
+    shutdown: CancellationToken,
+}
+
+struct Entry {
+    script: Script,
+    next_run: Option<DateTime<Utc>>,
+    running: bool,
+}
+
+impl ScriptScheduler {
+    pub fn new(runner: ScriptRunner) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                runner,
+                entries: Mutex::new(BTreeMap::new()),
+                shutdown: CancellationToken::new(),
+            }),
+        }
+    }
+
+    /// Replace the scripts. One whose schedule didn't change keeps its
+    /// next run; a run in progress carries on with the old source.
+    pub fn set_scripts(&self, scripts: Vec<Script>) {
+        let now = Local::now();
+        let mut entries = self.inner.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let mut previous = std::mem::take(&mut *entries);
+        for script in scripts {
+            let (next_run, running) = match previous.remove(&script.id) {
+                Some(old) if old.script.schedule == script.schedule => (old.next_run, old.running),
+                Some(old) => (next_run(&script, &now), old.running),
+                None => (next_run(&script, &now), false),
+            };
+            entries.insert(
+                script.id.clone(),
+                Entry {
+                    script,
+                    next_run,
+                    running,
+                },
+            );
+        }
+    }
+
+    /// Run a script now, whatever its schedule, and wait for it.
+    pub async fn run_now(&self, id: &str) -> ScriptResult<ScriptRun> {
+        let script = self.inner.claim(id)?;
+        self.inner.execute(script, RunTrigger::Manual).await
+    }
+
+    /// The newest audited runs, of one script or of all.
+    pub async fn runs(
+        &self,
+        script_id: Option<&str>,
+        limit: usize,
+    ) -> ScriptResult<Vec<ScriptRun>> {
+        self.inner.runner.audit().recent(script_id, limit).await
+    }
+
+    /// Start scripts as they come due, until [`Self::shutdown`].
+    pub async fn run(&self) {
+        let mut tick = tokio::time::interval(TICK);
+        tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                () = self.inner.shutdown.cancelled() => break,
+                _ = tick.tick() => {}
+            }
+            for script in self.inner.claim_due(&Local::now()) {
+                let inner = Arc::clone(&self.inner);
+                tokio::spawn(async move {
+                    let id = script.id.clone();
+                    if let Err(e) = inner.execute(script, RunTrigger::Schedule).await {
+                        tracing::warn!(script = %id, "Scheduled script run failed: {e}");
+                    }
+                });
+            }
+        }
+        tracing::debug!("Script scheduler stopped");
+    }
+
+    /// Stop scheduling and cancel the runs in progress.
+    pub fn shutdown(&self) {
+        self.inner.shutdown.cancel();
+    }
+}
+
+impl Inner {
+    /// Mark `id` as running and hand out its script.
+    fn claim(&self, id: &str) -> ScriptResult<Script> {
+        if self.shutdown.is_cancelled() {
+            return Err(ScriptError::Stopped);
+        }
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = entries
+            .get_mut(id)
+            .ok_or_else(|| ScriptError::NotFound(id.to_owned()))?;
+        if entry.running {
+            return Err(ScriptError::AlreadyRunning(id.to_owned()));
+        }
+        entry.running = true;
+        Ok(entry.script.clone())
+    }
+
+    /// Claim every script due by `now` and move its next run on. A script
+    /// still running when it comes due again skips that turn.
+    fn claim_due(&self, now: &DateTime<Local>) -> Vec<Script> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let mut due = Vec::new();
+        for entry in entries.values_mut() {
+            if entry.next_run.is_none_or(|at| at > *now) {
+                continue;
+            }
+            entry.next_run = next_run(&entry.script, now);
+            if entry.running {
+                tracing::debug!(script = %entry.script.id, "Skipping run, previous still going");
+                continue;
+            }
+            entry.running = true;
+            due.push(entry.script.clone());
+        }
+        due
+    }
+
+    async fn execute(&self, script: Script, trigger: RunTrigger) -> ScriptResult<ScriptRun> {
+        let _running = Running {
+            entries: &self.entries,
+            id: &script.id,
+        };
+        self.runner
+            .run(&script, trigger, self.shutdown.child_token())
+            .await
+    }
+}
+
+/// Clears an entry's `running` flag however its run ends.
+struct Running<'a> {
+    entries: &'a Mutex<BTreeMap<String, Entry>>,
+    id: &'a str,
+}
+
+impl Drop for Running<'_> {
+    fn drop(&mut self) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(entry) = entries.get_mut(self.id) {
+            entry.running = false;
+        }
+    }
+}
+
+fn next_run(script: &Script, now: &DateTime<Local>) -> Option<DateTime<Utc>> {
+    script
+        .schedule
+        .next_after(now)
+        .map(|at| at.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+
+    use super::*;
+    use crate::audit::{AuditLog, RunOutcome};
+    use crate::host::{ScriptHost, TimelineEntry};
+    use crate::schedule::Schedule;
+
+    struct NoHost;
+
+    #[async_trait]
+    impl ScriptHost for NoHost {
+        async fn ask_model(&self, _question: &str) -> Result<String, String> {
+            Err("offline".into())
+        }
+
+        async fn search_timeline(
+            &self,
+            _text: &str,
+            _limit: u32,
+        ) -> Result<Vec<TimelineEntry>, String> {
+            Ok(Vec::new())
+        }
+
+        async fn read_clipboard(&self) -> Result<String, String> {
+            Ok(String::new())
+        }
+
+        async fn notify(&self, _script: &str, _title: &str, _body: &str) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    fn script(id: &str, schedule: Schedule) -> Script {
+        Script {
+            id: id.into(),
+            name: id.into(),
+            source: "40 + 2".into(),
+            schedule,
+            timeout: Duration::from_secs(10),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn due_scripts_are_claimed_once_and_rescheduled() {
+        let dir = tempfile::tempdir().unwrap();
+        let scheduler = ScriptScheduler::new(ScriptRunner::new(
+            Arc::new(NoHost),
+            AuditLog::new(dir.path().join("audit.jsonl")),
+        ));
+        let every = Schedule::Every(Duration::from_secs(60));
+        scheduler.set_scripts(vec![
+            script("hourly", every),
+            script("manual", Schedule::Manual),
+        ]);
+
+        let later = Local::now() + chrono::TimeDelta::minutes(2);
+        let due = scheduler.inner.claim_due(&later);
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].id, "hourly");
+        // Already claimed, and its next run has moved on.
+        assert!(scheduler.inner.claim_due(&later).is_empty());
+        assert!(matches!(
+            scheduler.run_now("hourly").await,
+            Err(ScriptError::AlreadyRunning(_))
+        ));
+
+        let run = scheduler.run_now("manual").await.unwrap();
+        assert_eq!(run.outcome, RunOutcome::Succeeded);
+        assert_eq!(run.result.as_deref(), Some("42"));
+        assert!(matches!(
+            scheduler.run_now("missing").await,
+            Err(ScriptError::NotFound(_))
+        ));
+
+        // Re-setting an unchanged schedule keeps the claimed state and
+        // the next run.
+        scheduler.set_scripts(vec![script("hourly", every)]);
+        assert!(scheduler.inner.claim_due(&later).is_empty());
+        assert_eq!(scheduler.runs(None, 10).await.unwrap().len(), 1);
+    }
+}
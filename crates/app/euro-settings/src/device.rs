@@ -0,0 +1,44 @@
+//! Local-only device identity: the id this install registers with the
+//! backend so synced sessions and assets can be told apart from those of
+//! the user's other machines.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use uuid::Uuid;
+
+/// This install's device identity. Persisted in `local.json`; syncing it
+/// would make two machines share an id.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq, Type)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DeviceSettings {
+    /// `None` until the first start after install, then a fresh UUID v4
+    /// kept for the life of the install.
+    pub id: Option<Uuid>,
+}
+
+impl DeviceSettings {
+    /// Lazily fill `id` with a fresh UUID v4. Returns `true` if the field
+    /// was mutated so callers can decide whether to persist.
+    pub fn ensure_id(&mut self) -> bool {
+        if self.id.is_none() {
+            self.id = Some(Uuid::new_v4());
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ensure_id_is_idempotent_after_first_call() {
+        let mut device = DeviceSettings::default();
+        assert!(device.ensure_id());
+        let id = device.id;
+        assert!(!device.ensure_id());
+        assert_eq!(device.id, id);
+    }
+}
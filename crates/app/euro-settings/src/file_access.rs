@@ -0,0 +1,17 @@
+//! Folders the assistant may read through its local file tools.
+//!
+//! Empty by default: the `read_file` / `list_dir` / `grep_files` tools
+//! are only offered once the user shares at least one folder. Paths are
+//! machine-specific, so this lives in `local.json` rather than the
+//! synced settings.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq, Type)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FileAccessSettings {
+    pub roots: Vec<PathBuf>,
+}
@@ -5,10 +5,18 @@ use specta::Type;
 #[serde(rename_all = "camelCase")]
 pub struct GeneralSettings {
     pub autostart: bool,
+    /// Keep user context on this machine: chat and activity sync only
+    /// work against a local backend with local models. Enforced by
+    /// `euro_endpoint::EndpointManager`.
+    #[serde(default)]
+    pub local_only: bool,
 }
 
 impl Default for GeneralSettings {
     fn default() -> Self {
-        Self { autostart: true }
+        Self {
+            autostart: true,
+            local_only: false,
+        }
     }
 }
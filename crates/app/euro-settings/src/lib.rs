@@ -3,7 +3,11 @@
 //! The crate owns three pieces:
 //!
 //! - [`LocalSettings`] — per-install state persisted to `local.json`
-//!   (autostart, API endpoint, telemetry distinct id).
+//!   (autostart, API endpoint, telemetry distinct id, device id,
+//!   localhost API, remembered tool-consent decisions, folders shared
+//!   with the assistant, the programs it may run, secret scanning of
+//!   outgoing context, voice input, the region-capture hotkey, bandwidth
+//!   caps, sync scheduling and user scripts).
 //! - [`CloudSettingsCache`] — last-pulled mirror of the user-scoped cloud
 //!   settings blob, persisted to `cloud.json`. The wire shape lives in
 //!   the `settings-core` crate; this file is just a local cache plus
@@ -25,26 +29,48 @@
 
 pub mod api;
 pub mod cloud_cache;
+pub mod device;
 pub mod effective;
+pub mod file_access;
 pub mod general;
 pub mod local;
+pub mod local_api;
+pub mod moderation;
 pub mod persistence;
+pub mod region_capture;
+pub mod scripts;
+pub mod shell;
 pub mod state;
 pub mod sync;
 pub mod telemetry;
+pub mod tool_permissions;
+pub mod transfers;
+pub mod voice;
 
 pub use api::{APISettings, ConnectionMode, DEFAULT_API_URL};
 pub use cloud_cache::CloudSettingsCache;
+pub use device::DeviceSettings;
 pub use effective::EffectiveSettings;
+pub use file_access::FileAccessSettings;
 pub use general::GeneralSettings;
 pub use local::LocalSettings;
+pub use local_api::{DEFAULT_LOCAL_API_PORT, LocalApiSettings};
+pub use moderation::{
+    ModerationAction, ModerationProviderSettings, ModerationSettings, OcrSensitivity,
+};
 pub use persistence::default_config_dir;
+pub use region_capture::{Hotkey, RegionCaptureSettings, ScreenshotFormat};
+pub use scripts::{MAX_SCRIPT_TIMEOUT_SECS, ScriptSettings, ScriptTrigger, UserScript};
+pub use shell::ShellSettings;
 pub use state::SettingsState;
 pub use sync::{
     AuthIdentity, AuthManagerIdentity, BackoffConfig, PullOutcome, PushOutcome, ReqwestTransport,
     SettingsTransport, SyncEngine, SyncError, SyncResult, SyncStatus,
 };
 pub use telemetry::TelemetryLocal;
+pub use tool_permissions::ToolPermissionSettings;
+pub use transfers::TransferSettings;
+pub use voice::{VoiceProviderSettings, VoiceSettings};
 
 // Wire types from settings-core that IPC handlers and the frontend
 // bindings consume directly. Re-exported so app crates can take a
@@ -55,6 +81,7 @@ pub use telemetry::TelemetryLocal;
 // in `settings-core`, so call sites use it through this re-export.
 pub use settings_core::{
     CURRENT_SCHEMA_VERSION, CloudSettings, DEFAULT_SCALE, DESKTOP_CONSENT_VERSION, DesktopSettings,
-    InterfaceScale, MobileSettings, SharedSettings, TelemetryConsent, TextScale, ThemePreference,
+    InterfaceScale, InvalidSetting, MobileSettings, SettingDefinition, SettingScope, SettingType,
+    SettingsSchema, SharedSettings, SpeechRate, TelemetryConsent, TextScale, ThemePreference,
     WebSettings,
 };
@@ -1,7 +1,13 @@
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
-use crate::{api::APISettings, general::GeneralSettings, telemetry::TelemetryLocal};
+use crate::{
+    api::APISettings, device::DeviceSettings, file_access::FileAccessSettings,
+    general::GeneralSettings, local_api::LocalApiSettings, moderation::ModerationSettings,
+    region_capture::RegionCaptureSettings, scripts::ScriptSettings, shell::ShellSettings,
+    telemetry::TelemetryLocal, tool_permissions::ToolPermissionSettings,
+    transfers::TransferSettings, voice::VoiceSettings,
+};
 
 /// On-disk shape of `~/.config/eurora/local.json`.
 ///
@@ -10,7 +16,22 @@ use crate::{api::APISettings, general::GeneralSettings, telemetry::TelemetryLoca
 /// - the API endpoint is the transport the sync engine itself uses
 ///   (chicken/egg if synced),
 /// - the anonymous telemetry distinct id, whose rotation must break
-///   cross-device linkage.
+///   cross-device linkage,
+/// - the device id, which two machines would share if it synced,
+/// - the opt-in localhost API, whose token must never leave the machine,
+/// - tool-consent grants, which are scoped to the machine they were
+///   given on,
+/// - folders shared with the assistant and the programs it may run,
+///   which are local paths and installs,
+/// - secret-scanning rules for outgoing context, which may carry a
+///   provider key,
+/// - voice input, which names a local model file and may carry a
+///   provider key,
+/// - the region-capture hotkey, which must not clash with this
+///   machine's other shortcuts, and its local OCR models,
+/// - bandwidth caps and sync scheduling, which depend on this machine's
+///   link,
+/// - user scripts, which read this machine's clipboard and timeline.
 ///
 /// Per-field `#[serde(default)]` ensures partial files written by an
 /// older build round-trip through deserialise.
@@ -20,6 +41,16 @@ pub struct LocalSettings {
     pub general: GeneralSettings,
     pub api: APISettings,
     pub telemetry: TelemetryLocal,
+    pub device: DeviceSettings,
+    pub local_api: LocalApiSettings,
+    pub tool_permissions: ToolPermissionSettings,
+    pub file_access: FileAccessSettings,
+    pub shell: ShellSettings,
+    pub moderation: ModerationSettings,
+    pub voice: VoiceSettings,
+    pub region_capture: RegionCaptureSettings,
+    pub transfers: TransferSettings,
+    pub scripts: ScriptSettings,
 }
 
 #[cfg(test)]
@@ -41,6 +72,17 @@ mod tests {
         assert!(s.general.autostart);
         assert!(matches!(s.api.mode, ConnectionMode::Default));
         assert!(s.telemetry.distinct_id.is_none());
+        assert!(s.device.id.is_none());
+        assert!(!s.local_api.enabled);
+        assert!(s.tool_permissions.always_allowed.is_empty());
+        assert!(s.file_access.roots.is_empty());
+        assert!(!s.shell.is_configured());
+        assert!(s.moderation.enabled);
+        assert!(s.voice.provider.is_none());
+        assert!(s.region_capture.enabled);
+        assert!(s.transfers.pause_on_metered);
+        assert!(!s.transfers.night_only);
+        assert!(s.scripts.scripts.is_empty());
     }
 
     #[test]
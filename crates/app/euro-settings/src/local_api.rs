@@ -0,0 +1,79 @@
+//! Opt-in localhost HTTP API for scripting the desktop app.
+//!
+//! Off by default. Power users flip `localApi.enabled` in `local.json`;
+//! on the next launch the desktop binds `127.0.0.1:<port>` and mints a
+//! bearer token the first time it's needed. The token lives here, next
+//! to the rest of the per-install state, so scripts can read it from
+//! `local.json` instead of going through the UI.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use uuid::Uuid;
+
+/// Default listen port. One above the browser bridge's 1431.
+pub const DEFAULT_LOCAL_API_PORT: u16 = 1432;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Type)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LocalApiSettings {
+    pub enabled: bool,
+    pub port: u16,
+    /// Bearer token required on every request. `None` until the server
+    /// first starts.
+    pub token: Option<String>,
+}
+
+impl Default for LocalApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_LOCAL_API_PORT,
+            token: None,
+        }
+    }
+}
+
+impl LocalApiSettings {
+    /// Lazily mint `token`. Returns `true` if the field was mutated so
+    /// callers can decide whether to persist.
+    pub fn ensure_token(&mut self) -> bool {
+        if self.token.as_deref().is_some_and(|t| !t.is_empty()) {
+            return false;
+        }
+        self.rotate_token();
+        true
+    }
+
+    /// Replace the token with a fresh one, invalidating every script
+    /// that holds the old value.
+    pub fn rotate_token(&mut self) {
+        self.token = Some(format!(
+            "{}{}",
+            Uuid::new_v4().simple(),
+            Uuid::new_v4().simple()
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabled_by_default() {
+        let s = LocalApiSettings::default();
+        assert!(!s.enabled);
+        assert_eq!(s.port, DEFAULT_LOCAL_API_PORT);
+        assert!(s.token.is_none());
+    }
+
+    #[test]
+    fn ensure_token_is_idempotent_after_first_call() {
+        let mut s = LocalApiSettings::default();
+        assert!(s.ensure_token());
+        let token = s.token.clone();
+        assert_eq!(token.as_deref().map(str::len), Some(64));
+        assert!(!s.ensure_token());
+        assert_eq!(s.token, token);
+    }
+}
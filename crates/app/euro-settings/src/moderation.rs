@@ -0,0 +1,72 @@
+//! Secret scanning for context the assistant sends upstream.
+//!
+//! On by default, masking API keys, private keys and card numbers before
+//! page text, OCR output, clipboard contents or file contents leave the
+//! machine. Kept per-install: custom patterns tend to name local
+//! projects, and the optional provider key must never be synced.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Type)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ModerationSettings {
+    pub enabled: bool,
+    pub action: ModerationAction,
+    /// Extra regular expressions to treat as secrets.
+    pub custom_patterns: Vec<String>,
+    /// Hosted moderation endpoint consulted after the local pass.
+    pub provider: Option<ModerationProviderSettings>,
+    /// Strings text read off the screen may keep even when they look like
+    /// a secret.
+    pub ocr_allowlist: Vec<String>,
+    /// How closely text read off each app is checked, by process name.
+    /// Password managers not listed here are `strict`.
+    pub ocr_app_sensitivity: BTreeMap<String, OcrSensitivity>,
+}
+
+impl Default for ModerationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            action: ModerationAction::Redact,
+            custom_patterns: Vec::new(),
+            provider: None,
+            ocr_allowlist: Vec::new(),
+            ocr_app_sensitivity: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum ModerationAction {
+    /// Mask each match and send the rest.
+    #[default]
+    Redact,
+    /// Withhold anything that contains a match.
+    Block,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum OcrSensitivity {
+    /// Only the detectors all outgoing context goes through.
+    Relaxed,
+    /// Those, plus values shown next to labels like `Password:`.
+    #[default]
+    Standard,
+    /// Keep none of the app's text.
+    Strict,
+}
+
+/// An OpenAI-compatible `/moderations` endpoint.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ModerationProviderSettings {
+    /// API root, e.g. `https://api.openai.com/v1`.
+    pub base_url: String,
+    pub api_key: String,
+}
@@ -0,0 +1,59 @@
+//! The screen-region capture hotkey, on-device OCR, and the format
+//! captures are stored in.
+//!
+//! Kept per-install: shortcuts that are free on one machine can clash on
+//! another, and the OCR models are local files.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Type)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RegionCaptureSettings {
+    /// Register the global capture shortcut.
+    pub enabled: bool,
+    /// The platform default (the launcher shortcut plus Shift) when unset.
+    pub hotkey: Option<Hotkey>,
+    /// Directory holding the `ocrs` detection and recognition models.
+    /// Captures are attached without their text when unset, or when the
+    /// build has no OCR support.
+    pub ocr_models_dir: Option<String>,
+    /// Format captures are saved to the asset library in. What is sent to
+    /// the model is unaffected.
+    pub storage_format: ScreenshotFormat,
+    /// Quality from 1 to 100 for the lossy formats, JPEG and AVIF.
+    pub storage_quality: u8,
+}
+
+impl Default for RegionCaptureSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            hotkey: None,
+            ocr_models_dir: None,
+            storage_format: ScreenshotFormat::default(),
+            storage_quality: 75,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum ScreenshotFormat {
+    Png,
+    Jpeg,
+    /// Lossless, and about a quarter smaller than PNG on screenshots.
+    #[default]
+    Webp,
+    Avif,
+}
+
+/// A global shortcut, e.g. `{ "modifiers": ["ctrl", "shift"], "key": "keys" }`.
+/// Modifiers are `ctrl`, `alt`, `shift` or `meta`; keys are DOM
+/// `KeyboardEvent.code` names, lowercased.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct Hotkey {
+    pub modifiers: Vec<String>,
+    pub key: String,
+}
@@ -0,0 +1,58 @@
+//! User scripts: small Rhai automations written in settings and run by
+//! the desktop's script scheduler.
+//!
+//! Kept per-install: scripts read this machine's clipboard and timeline,
+//! and their schedules are in its local time.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// Longest a run may be given before it's stopped.
+pub const MAX_SCRIPT_TIMEOUT_SECS: u32 = 15 * 60;
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq, Type)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ScriptSettings {
+    pub scripts: Vec<UserScript>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct UserScript {
+    /// Assigned when the script is first saved.
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    /// Rhai source.
+    pub source: String,
+    #[serde(default)]
+    pub trigger: ScriptTrigger,
+    /// Disabled scripts keep their schedule but only run by hand.
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+    /// Wall-clock budget for one run, model calls included.
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u32,
+}
+
+/// When a script runs on its own.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq, Type)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ScriptTrigger {
+    /// Only when the user runs it.
+    #[default]
+    Manual,
+    /// Every `minutes`, counted from when the app starts or the script
+    /// is saved.
+    Every { minutes: u32 },
+    /// Once a day at `hour:minute` local time.
+    Daily { hour: u8, minute: u8 },
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+fn default_timeout_secs() -> u32 {
+    120
+}
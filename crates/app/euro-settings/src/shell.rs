@@ -0,0 +1,29 @@
+//! The assistant's `run_command` tool.
+//!
+//! Off by default: the tool is only offered once the user picks a
+//! workspace folder and names at least one program it may run. Every
+//! call still asks first. Paths and installed programs are
+//! machine-specific, so this lives in `local.json` rather than the
+//! synced settings.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq, Type)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ShellSettings {
+    /// Folder commands run in; a `cwd` the model asks for must be inside
+    /// it.
+    pub workspace: Option<PathBuf>,
+    /// Programs the assistant may run, by file name (`git`, `cargo`).
+    pub allowed_programs: Vec<String>,
+}
+
+impl ShellSettings {
+    /// Whether the tool should be offered at all.
+    pub fn is_configured(&self) -> bool {
+        self.workspace.is_some() && !self.allowed_programs.is_empty()
+    }
+}
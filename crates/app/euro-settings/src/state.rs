@@ -1,3 +1,7 @@
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use settings_core::{InvalidSetting, SettingScope, SettingsSchema};
+
 use crate::{cloud_cache::CloudSettingsCache, effective::EffectiveSettings, local::LocalSettings};
 
 /// Single owner of the on-disk settings split. Stored in `tauri::Manager`
@@ -19,4 +23,123 @@ impl SettingsState {
     pub fn effective(&self) -> EffectiveSettings<'_> {
         EffectiveSettings::new(&self.local, &self.cache)
     }
+
+    /// Write one key from the [`SettingsSchema`] registry after
+    /// validating `value` against it. Returns the key's scope so the
+    /// caller knows which file to persist and whether to request a push;
+    /// on error nothing is changed.
+    pub fn set_value(&mut self, key: &str, value: Value) -> Result<SettingScope, InvalidSetting> {
+        let schema = SettingsSchema::current();
+        schema.validate(key, &value)?;
+        let def = schema
+            .get(key)
+            .expect("validate succeeded, so the key is registered");
+        let path: Vec<&str> = def.path().collect();
+        let written = match def.scope {
+            SettingScope::Local => write_path(&mut self.local, &path, value),
+            SettingScope::Shared | SettingScope::Desktop => {
+                write_path(&mut self.cache.settings, &path, value)
+            }
+        };
+        if written {
+            Ok(def.scope)
+        } else {
+            Err(InvalidSetting::WrongType {
+                key: def.key.clone(),
+                expected: def.value_type.json_type(),
+            })
+        }
+    }
+}
+
+/// Round-trip `document` through JSON with the value at `path` replaced.
+/// `false` if the path doesn't resolve or the result no longer
+/// deserializes, in which case `document` is left as it was.
+fn write_path<T: Serialize + DeserializeOwned>(
+    document: &mut T,
+    path: &[&str],
+    value: Value,
+) -> bool {
+    let Ok(mut json) = serde_json::to_value(&*document) else {
+        return false;
+    };
+    let Some((field, parents)) = path.split_last() else {
+        return false;
+    };
+    let Some(parent) = parents
+        .iter()
+        .try_fold(&mut json, |node, segment| node.get_mut(*segment))
+        .and_then(Value::as_object_mut)
+    else {
+        return false;
+    };
+    parent.insert((*field).to_owned(), value);
+    match serde_json::from_value(json) {
+        Ok(updated) => {
+            *document = updated;
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use settings_core::ThemePreference;
+
+    use super::*;
+
+    #[test]
+    fn local_defaults_match_the_registry() {
+        let schema = SettingsSchema::current();
+        let local = serde_json::to_value(LocalSettings::default()).unwrap();
+        for def in schema
+            .settings
+            .iter()
+            .filter(|def| def.scope == SettingScope::Local)
+        {
+            let stored = def
+                .path()
+                .try_fold(&local, |node, segment| node.get(segment));
+            assert_eq!(stored, Some(&def.default), "{}", def.key);
+        }
+    }
+
+    #[test]
+    fn set_value_writes_local_and_cloud_keys() {
+        let mut state = SettingsState::default();
+        assert_eq!(
+            state.set_value("general.autostart", json!(false)),
+            Ok(SettingScope::Local)
+        );
+        assert!(!state.local.general.autostart);
+
+        assert_eq!(
+            state.set_value("shared.theme", json!("dark")),
+            Ok(SettingScope::Shared)
+        );
+        assert_eq!(state.cache.settings.shared.theme, ThemePreference::Dark);
+
+        assert_eq!(
+            state.set_value("desktop.textScale", json!(1.25)),
+            Ok(SettingScope::Desktop)
+        );
+        assert_eq!(state.cache.settings.desktop.text_scale.get(), 1.25);
+    }
+
+    #[test]
+    fn set_value_leaves_state_untouched_on_invalid_values() {
+        let mut state = SettingsState::default();
+        let before = state.clone();
+        assert!(matches!(
+            state.set_value("desktop.interfaceScale", json!(3.0)),
+            Err(InvalidSetting::OutOfRange { .. })
+        ));
+        assert!(matches!(
+            state.set_value("api.mode", json!({ "kind": "default" })),
+            Err(InvalidSetting::UnknownKey(_))
+        ));
+        assert_eq!(state, before);
+    }
 }
@@ -23,6 +23,10 @@ pub struct TelemetryLocal {
     /// telemetry for the first time, then a fresh UUID v4 that survives
     /// until the user explicitly rotates it.
     pub distinct_id: Option<String>,
+    /// Strip product analytics from this install entirely, whatever the
+    /// synced consent says: the frontend is never handed a PostHog key.
+    /// Error reporting still follows the consent toggles.
+    pub analytics_disabled: bool,
 }
 
 impl TelemetryLocal {
@@ -67,4 +71,10 @@ mod tests {
         assert!(local.distinct_id.is_some());
         assert_ne!(local.distinct_id, first);
     }
+
+    #[test]
+    fn analytics_stay_enabled_for_existing_local_files() {
+        let local: TelemetryLocal = serde_json::from_str(r#"{"distinctId":"abc"}"#).unwrap();
+        assert!(!local.analytics_disabled);
+    }
 }
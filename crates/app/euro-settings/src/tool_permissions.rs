@@ -0,0 +1,19 @@
+//! Persisted answers to agent tool-consent prompts.
+//!
+//! When the user picks "always allow" for a tool the assistant wants to
+//! run, the tool name lands here so the prompt doesn't come back on the
+//! next launch. Kept per-install rather than synced: granting a tool
+//! write access on one machine says nothing about another.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq, Type)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ToolPermissionSettings {
+    /// Tools the user allowed permanently. Only consulted for elevated
+    /// tools; dangerous ones prompt regardless.
+    pub always_allowed: BTreeSet<String>,
+}
@@ -0,0 +1,40 @@
+//! Bandwidth caps and scheduling for uploads and downloads.
+//!
+//! Kept per-install: how much of the link to leave free, and whether it
+//! is metered, depends on the machine and where it is.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Type)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TransferSettings {
+    /// Upload cap in KiB/s across all transfers; 0 for no cap.
+    pub max_upload_kib_per_sec: u32,
+    /// Download cap in KiB/s across all transfers; 0 for no cap.
+    pub max_download_kib_per_sec: u32,
+    /// Hold background syncs while the OS reports a metered connection.
+    pub pause_on_metered: bool,
+    /// Run background syncs only between `night_start_hour` and
+    /// `night_end_hour`, local time. Transfers the user is waiting on
+    /// always run.
+    pub night_only: bool,
+    pub night_start_hour: u8,
+    pub night_end_hour: u8,
+    /// Transfers in flight at once.
+    pub max_concurrent: u8,
+}
+
+impl Default for TransferSettings {
+    fn default() -> Self {
+        Self {
+            max_upload_kib_per_sec: 0,
+            max_download_kib_per_sec: 0,
+            pause_on_metered: true,
+            night_only: false,
+            night_start_hour: 1,
+            night_end_hour: 6,
+            max_concurrent: 3,
+        }
+    }
+}
@@ -0,0 +1,52 @@
+//! Push-to-talk voice input and hosted speech output.
+//!
+//! Kept per-install: the Whisper model is a local file, and the optional
+//! provider keys must never be synced. The voice and rate responses are
+//! read with are synced, in the desktop section.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Type)]
+#[serde(rename_all = "camelCase", default)]
+pub struct VoiceSettings {
+    /// Path to a whisper.cpp `ggml` model for on-device recognition. Used
+    /// first when the build has Whisper support.
+    pub whisper_model: Option<String>,
+    /// Hosted recognizer, used when there is no local model. `model` is
+    /// e.g. `whisper-1`.
+    pub provider: Option<VoiceProviderSettings>,
+    /// Spoken language, e.g. `en`. Detected when unset.
+    pub language: Option<String>,
+    /// Submit the question after this many milliseconds of silence.
+    /// When unset, the transcript waits in the question box until the
+    /// shortcut is released.
+    pub auto_submit_after_ms: Option<u32>,
+    /// Hosted voice for reading responses aloud, used instead of the
+    /// platform's when the build has provider speech. `model` is e.g.
+    /// `tts-1`.
+    pub speech_provider: Option<VoiceProviderSettings>,
+}
+
+impl Default for VoiceSettings {
+    fn default() -> Self {
+        Self {
+            whisper_model: None,
+            provider: None,
+            language: None,
+            auto_submit_after_ms: Some(1_500),
+            speech_provider: None,
+        }
+    }
+}
+
+/// An OpenAI-compatible audio endpoint: `/audio/transcriptions` for
+/// recognition, `/audio/speech` for speech.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct VoiceProviderSettings {
+    /// API root, e.g. `https://api.openai.com/v1`.
+    pub base_url: String,
+    pub api_key: String,
+    pub model: String,
+}
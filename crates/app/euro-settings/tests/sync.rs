@@ -344,6 +344,9 @@ fn claims_for(uid: Uuid) -> Claims {
         aud: String::new(),
         email_verified: true,
         jti: String::new(),
+        analytics: Default::default(),
+        token_version: 0,
+        act: None,
     }
 }
 
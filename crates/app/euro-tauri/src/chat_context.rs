@@ -1,8 +1,9 @@
 //! Desktop implementation of [`euro_thread::commands::ChatContextProvider`].
 //!
-//! Returns the single [`ContextChip`] describing the user's current
-//! activity, used by the UI to render a chip alongside the in-flight
-//! human message and to persist the chip set on `ChatSendRequest.asset_chips_json`.
+//! Returns the [`ContextChip`] describing the user's current activity,
+//! plus one for a screen region the user captured for the question, used
+//! by the UI to render chips alongside the in-flight human message and to
+//! persist the chip set on `ChatSendRequest.asset_chips_json`.
 //!
 //! The LLM-facing prelude (`"The user is currently watching ..."`) is
 //! delivered separately — it ships in the `system_blocks` field of the
@@ -23,6 +24,8 @@ use tauri::{AppHandle, Manager};
 use tokio::sync::Mutex;
 use uuid::Uuid;
 
+use crate::region_capture::RegionCaptureState;
+
 pub struct TimelineChatContextProvider {
     app_handle: AppHandle,
 }
@@ -43,11 +46,12 @@ impl ChatContextProvider for TimelineChatContextProvider {
 
         let timeline = timeline_state.lock().await;
 
-        let asset_chips = timeline
-            .get_context_chip()
-            .await
-            .map(|chip| vec![chip])
-            .unwrap_or_default();
+        let mut asset_chips: Vec<_> = timeline.get_context_chip().await.into_iter().collect();
+        if let Some(state) = self.app_handle.try_state::<RegionCaptureState>()
+            && let Some(capture) = state.pending.lock().as_ref()
+        {
+            asset_chips.push(capture.chip());
+        }
 
         Ok(ChatContext { asset_chips })
     }
@@ -0,0 +1,86 @@
+//! Desktop wiring for the backend device registry.
+//!
+//! Each install makes up a device id once and keeps it in `local.json`
+//! (see [`euro_settings::DeviceSettings`]); the timeline tags every
+//! synced session and asset with it. [`start`] registers the device
+//! under this machine's host name at startup and again whenever a user
+//! signs in, since the backend only keeps the tag for devices it knows.
+
+use activity_core::MAX_DEVICE_NAME_CHARS;
+use euro_activity::ActivityStorage;
+use euro_auth::AuthManager;
+use euro_settings::SettingsState;
+use euro_timeline::TimelineManager;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+use tokio::sync::Mutex;
+use tokio::sync::broadcast::error::RecvError;
+
+/// Give this install a device id if it has none yet and persist it. An
+/// id that can't be persisted is dropped again: a fresh one every start
+/// would register a new device each time.
+pub fn ensure_id(settings: &mut SettingsState) {
+    if settings.local.device.ensure_id()
+        && let Err(e) = settings.save_local_to_default_path()
+    {
+        tracing::warn!("Could not persist device id, syncing without one: {e}");
+        settings.local.device.id = None;
+    }
+}
+
+/// Register the device now and on every sign-in.
+pub fn start(app_handle: &AppHandle, auth_manager: &AuthManager) {
+    let mut auth_events = auth_manager.subscribe();
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let storage = app_handle
+            .state::<Mutex<TimelineManager>>()
+            .lock()
+            .await
+            .activity_storage
+            .clone();
+        if storage.device_id().is_none() {
+            return;
+        }
+        register(&storage).await;
+
+        let mut subject = None;
+        loop {
+            match auth_events.recv().await {
+                Ok(event) => {
+                    let signed_in = event.claims.map(|claims| claims.sub);
+                    if signed_in.is_some() && signed_in != subject {
+                        register(&storage).await;
+                    }
+                    subject = signed_in;
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!(skipped, "Device registration auth feed lagged");
+                }
+                Err(RecvError::Closed) => return,
+            }
+        }
+    });
+}
+
+async fn register(storage: &Arc<ActivityStorage>) {
+    match storage
+        .register_device(device_name(), std::env::consts::OS.to_string())
+        .await
+    {
+        Ok(_) => tracing::debug!("Registered this device"),
+        // Signed out, offline or local-only: the next sign-in retries.
+        Err(e) => tracing::debug!("Could not register this device: {e}"),
+    }
+}
+
+/// This machine's host name, or the platform when it has none.
+fn device_name() -> String {
+    let host = std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok());
+    host.map(|name| name.trim().chars().take(MAX_DEVICE_NAME_CHARS).collect())
+        .filter(|name: &String| !name.is_empty())
+        .unwrap_or_else(|| std::env::consts::OS.to_string())
+}
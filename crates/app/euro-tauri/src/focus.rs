@@ -0,0 +1,192 @@
+//! Desktop wiring for [`euro_focus`].
+//!
+//! [`start`] runs once the personal database is open: it manages a
+//! [`FocusTracker`] for the `focus_*` commands, feeds it every activity
+//! change from the timeline, and completes the running session when its
+//! planned length is up. When a session completes the model is asked for
+//! a recap in a new chat thread, the same way a script's `ask_model`
+//! runs, and the recap is kept on the session. Both moments reach the
+//! frontend as [`FocusNotification`] events.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use euro_focus::{FocusSession, FocusTracker};
+use euro_personal_db::PersonalDb;
+use euro_timeline::TimelineManager;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{AppHandle, Manager};
+use tauri_specta::Event;
+use thread_core::ToolBackend;
+use tokio::sync::Mutex;
+use tokio::sync::broadcast::error::RecvError;
+use uuid::Uuid;
+
+use crate::shared_types::SharedThreadManager;
+
+/// How often the running session is checked against its planned length.
+const DUE_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// `completed` when a session ran its planned length, `recap_ready` once
+/// its recap is written.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum FocusNotificationKind {
+    Completed,
+    RecapReady,
+}
+
+/// A focus session needs the user's attention.
+#[derive(Clone, Debug, Serialize, Deserialize, Type, Event)]
+#[serde(rename_all = "camelCase")]
+pub struct FocusNotification {
+    pub session_id: Uuid,
+    pub kind: FocusNotificationKind,
+    pub title: String,
+    pub body: String,
+}
+
+/// Load the tracker over `db`, manage it on the app, and start feeding
+/// and timing it.
+pub async fn start(app_handle: &AppHandle, db: PersonalDb) {
+    let tracker = match FocusTracker::load(db).await {
+        Ok(tracker) => tracker,
+        Err(e) => {
+            tracing::error!("Could not load focus sessions: {e}");
+            return;
+        }
+    };
+    app_handle.manage(tracker.clone());
+
+    let activity_handle = app_handle.clone();
+    let activity_tracker = tracker.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut rx = {
+            let timeline = activity_handle.state::<Mutex<TimelineManager>>();
+            let timeline = timeline.lock().await;
+            timeline.subscribe_to_activity_events()
+        };
+        loop {
+            match rx.recv().await {
+                Ok(event) => {
+                    if let Err(e) = activity_tracker
+                        .record_activity(&event.name, &event.process_name, Utc::now())
+                        .await
+                    {
+                        tracing::warn!("Could not record focus activity: {e}");
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!(skipped, "Focus activity feed lagged");
+                }
+                Err(RecvError::Closed) => return,
+            }
+        }
+    });
+
+    let due_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut ticks = tokio::time::interval(DUE_CHECK_INTERVAL);
+        loop {
+            ticks.tick().await;
+            match tracker.complete_if_due(Utc::now()).await {
+                Ok(Some(session)) => {
+                    notify(
+                        &due_handle,
+                        &session,
+                        FocusNotificationKind::Completed,
+                        "Focus session complete",
+                        completed_body(&session),
+                    );
+                    spawn_recap(due_handle.clone(), tracker.clone(), session.id);
+                }
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Could not complete focus session: {e}"),
+            }
+        }
+    });
+}
+
+/// Write the session's recap in the background and notify the user
+/// when it's ready. A failure is logged; the session is kept without one.
+pub fn spawn_recap(app_handle: AppHandle, tracker: FocusTracker, session_id: Uuid) {
+    tauri::async_runtime::spawn(async move {
+        match write_recap(&app_handle, &tracker, session_id).await {
+            Ok(session) => notify(
+                &app_handle,
+                &session,
+                FocusNotificationKind::RecapReady,
+                "Focus recap ready",
+                session.recap.clone().unwrap_or_default(),
+            ),
+            Err(e) => tracing::warn!(%session_id, "Could not write focus recap: {e}"),
+        }
+    });
+}
+
+/// Ask the model for the session's recap in a new thread and keep it on
+/// the session.
+pub async fn write_recap(
+    app_handle: &AppHandle,
+    tracker: &FocusTracker,
+    session_id: Uuid,
+) -> Result<FocusSession, String> {
+    let details = tracker
+        .details(session_id, Utc::now())
+        .await
+        .map_err(|e| e.to_string())?;
+    let thread_manager = app_handle
+        .try_state::<SharedThreadManager>()
+        .ok_or("chat is unavailable")?
+        .inner()
+        .clone();
+    let backend = app_handle
+        .try_state::<Arc<dyn ToolBackend>>()
+        .ok_or("chat is unavailable")?
+        .inner()
+        .clone();
+
+    let thread = thread_manager
+        .create(None)
+        .await
+        .map_err(|e| e.to_string())?;
+    let recap = crate::local_api::run_turn(
+        &thread_manager,
+        &backend,
+        thread.id,
+        &euro_focus::recap_prompt(&details),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+    tracker
+        .set_recap(session_id, recap.trim().to_string(), Some(thread.id))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+fn notify(
+    app_handle: &AppHandle,
+    session: &FocusSession,
+    kind: FocusNotificationKind,
+    title: &str,
+    body: String,
+) {
+    let notification = FocusNotification {
+        session_id: session.id,
+        kind,
+        title: title.to_string(),
+        body,
+    };
+    if let Err(e) = notification.emit(app_handle) {
+        tracing::warn!("Failed to emit FocusNotification: {e}");
+    }
+}
+
+fn completed_body(session: &FocusSession) -> String {
+    match session.goal.as_str() {
+        "" => "Time's up. Your recap is on its way.".to_string(),
+        goal => format!("Time's up on {goal}. Your recap is on its way."),
+    }
+}
@@ -9,9 +9,17 @@
 //! pass fully qualified paths to `collect_commands!` and let
 //! module-relative macro resolution find them.
 
+use crate::focus::FocusNotification;
 use crate::procedures::activity::{SavedActivityLiveSessionEnded, SavedActivityUpserted};
+use crate::procedures::notification::ServerNotification;
+use crate::procedures::region_capture::RegionCaptured;
 use crate::procedures::system::{BrowserExtensionStatusChanged, ConsentGate};
 use crate::procedures::timeline::{TimelineAppEvent, TimelineAssetsEvent};
+use crate::procedures::tool_consent::ToolConsentRequested;
+use crate::procedures::voice::SpeechStateChanged;
+use crate::screen_time::ScreenTimeNotification;
+use crate::scripts::ScriptNotification;
+use crate::updater::UpdateProgress;
 use euro_auth::tauri::AuthStateChanged;
 
 /// Assemble the tauri-specta IPC surface — every typed command and event
@@ -33,6 +41,7 @@ pub fn build_specta() -> tauri_specta::Builder<tauri::Wry> {
             crate::procedures::auth::auth_refresh_session,
             crate::procedures::auth::auth_resend_verification_email,
             crate::procedures::activity::activity_list,
+            crate::procedures::activity::activity_stream,
             euro_thread::commands::chat::chat_collect_context,
             euro_thread::commands::chat::chat_send_query,
             euro_thread::commands::chat::chat_regenerate,
@@ -43,13 +52,31 @@ pub fn build_specta() -> tauri_specta::Builder<tauri::Wry> {
             crate::procedures::settings::settings_set_general,
             crate::procedures::settings::settings_get_api,
             crate::procedures::settings::settings_set_api,
+            crate::procedures::settings::settings_get_file_access,
+            crate::procedures::settings::settings_set_file_access,
+            crate::procedures::settings::settings_get_shell,
+            crate::procedures::settings::settings_set_shell,
+            crate::procedures::settings::settings_get_moderation,
+            crate::procedures::settings::settings_set_moderation,
+            crate::procedures::settings::settings_get_voice,
+            crate::procedures::settings::settings_set_voice,
+            crate::procedures::settings::settings_get_region_capture,
+            crate::procedures::settings::settings_set_region_capture,
+            crate::procedures::settings::settings_get_transfers,
+            crate::procedures::settings::settings_set_transfers,
+            crate::procedures::settings::settings_get_scripts,
+            crate::procedures::settings::settings_set_scripts,
+            crate::procedures::settings::settings_compact_database,
             crate::procedures::settings::settings_get_shared,
             crate::procedures::settings::settings_set_shared,
             crate::procedures::settings::settings_get_desktop,
             crate::procedures::settings::settings_set_desktop,
             crate::procedures::settings::settings_record_telemetry_consent,
             crate::procedures::settings::settings_get_local_telemetry,
+            crate::procedures::settings::settings_set_analytics_disabled,
             crate::procedures::settings::settings_get_telemetry_consent,
+            crate::procedures::settings::settings_get_schema,
+            crate::procedures::settings::settings_set_value,
             crate::procedures::system::system_check_backend_connection,
             crate::procedures::system::system_get_llm_info,
             crate::procedures::system::system_test_backend_url,
@@ -67,7 +94,48 @@ pub fn build_specta() -> tauri_specta::Builder<tauri::Wry> {
             crate::procedures::system::system_get_telemetry_bootstrap,
             crate::procedures::system::frontend_ready,
             crate::procedures::system::system_reinit_telemetry,
+            crate::procedures::system::system_pending_crash_reports,
+            crate::procedures::system::system_resolve_crash_reports,
             crate::procedures::system::system_rotate_telemetry_distinct_id,
+            crate::procedures::tool_consent::tool_consent_respond,
+            crate::procedures::voice::voice_start,
+            crate::procedures::voice::voice_stop,
+            crate::procedures::voice::voice_cancel,
+            crate::procedures::voice::speech_push,
+            crate::procedures::voice::speech_finish,
+            crate::procedures::voice::speech_pause,
+            crate::procedures::voice::speech_resume,
+            crate::procedures::voice::speech_stop,
+            crate::procedures::voice::speech_list_voices,
+            crate::procedures::region_capture::region_capture_start,
+            crate::procedures::region_capture::region_capture_frame,
+            crate::procedures::region_capture::region_capture_select,
+            crate::procedures::region_capture::region_capture_cancel,
+            crate::procedures::region_capture::region_capture_discard,
+            crate::procedures::plugins::plugin_list,
+            crate::procedures::plugins::plugin_install,
+            crate::procedures::plugins::plugin_update,
+            crate::procedures::plugins::plugin_enable,
+            crate::procedures::plugins::plugin_disable,
+            crate::procedures::plugins::plugin_uninstall,
+            crate::procedures::scripts::script_run_now,
+            crate::procedures::scripts::script_list_runs,
+            crate::procedures::focus::focus_start,
+            crate::procedures::focus::focus_pause,
+            crate::procedures::focus::focus_resume,
+            crate::procedures::focus::focus_stop,
+            crate::procedures::focus::focus_cancel,
+            crate::procedures::focus::focus_active,
+            crate::procedures::focus::focus_get,
+            crate::procedures::focus::focus_list,
+            crate::procedures::focus::focus_update_goal,
+            crate::procedures::focus::focus_delete,
+            crate::procedures::focus::focus_generate_recap,
+            crate::procedures::screen_time::screen_time_status,
+            crate::procedures::screen_time::screen_time_create_budget,
+            crate::procedures::screen_time::screen_time_update_budget,
+            crate::procedures::screen_time::screen_time_delete_budget,
+            crate::procedures::screen_time::screen_time_report,
             euro_thread::commands::thread::thread_list,
             euro_thread::commands::thread::thread_list_by_activity,
             euro_thread::commands::thread::thread_create,
@@ -77,6 +145,9 @@ pub fn build_specta() -> tauri_specta::Builder<tauri::Wry> {
             euro_thread::commands::thread::thread_generate_title,
             euro_thread::commands::thread::thread_search_threads,
             euro_thread::commands::thread::thread_search_messages,
+            euro_thread::commands::local_model::local_model_list,
+            euro_thread::commands::local_model::local_model_pull,
+            euro_thread::commands::local_model::local_model_delete,
         ])
         .events(tauri_specta::collect_events![
             AuthStateChanged,
@@ -86,5 +157,13 @@ pub fn build_specta() -> tauri_specta::Builder<tauri::Wry> {
             SavedActivityLiveSessionEnded,
             BrowserExtensionStatusChanged,
             ConsentGate,
+            ToolConsentRequested,
+            SpeechStateChanged,
+            RegionCaptured,
+            ServerNotification,
+            UpdateProgress,
+            ScriptNotification,
+            FocusNotification,
+            ScreenTimeNotification,
         ])
 }
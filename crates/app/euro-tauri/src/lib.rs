@@ -7,11 +7,25 @@
 
 pub mod browser_launcher;
 pub mod chat_context;
+pub mod devices;
+pub mod focus;
+pub mod local_api;
+pub mod local_tools;
+pub mod moderation;
 pub mod native_messaging;
 pub mod office_addin;
+pub mod plugins;
 pub mod procedures;
+pub mod region_capture;
+pub mod screen_time;
+pub mod scripts;
 pub mod shared_types;
+pub mod startup;
+pub mod tool_consent;
+pub mod transfers;
+pub mod updater;
 pub mod util;
+pub mod voice;
 pub mod window;
 
 // `ipc` must be declared after `procedures` so the per-command
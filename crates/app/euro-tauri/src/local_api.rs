@@ -0,0 +1,400 @@
+//! Opt-in localhost HTTP/JSON API for scripting the desktop app.
+//!
+//! Enabled by `localApi.enabled` in `local.json` (see
+//! [`euro_settings::LocalApiSettings`]). Binds loopback only and requires
+//! `Authorization: Bearer <token>` on every route, where the token is
+//! the one persisted next to the flag. Calendar apps subscribing to the
+//! `.ics` feed can't send headers, so that route also accepts the token
+//! as a `?token=` query parameter. Routes:
+//!
+//! - `POST /v1/ask` — run one chat turn (creating a thread unless one is
+//!   given) and return the assistant's final answer as text.
+//! - `GET /v1/context/current` — the LLM-facing context blocks the
+//!   active activity strategy would attach to the next turn.
+//! - `GET /v1/threads` — the signed-in user's threads, newest first.
+//! - `GET /v1/activity/calendar.ics` — the activity timeline between
+//!   `since` and `until` (RFC 3339, default the last 7 days) as an
+//!   iCalendar file with one event per focus block. `device` narrows it
+//!   to one of the user's devices; by default all of them are merged.
+//!
+//! Handlers reuse the same [`ThreadManager`] and [`ToolBackend`] the
+//! Tauri IPC surface uses, so a scripted turn is indistinguishable from
+//! one typed into the app.
+
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+
+use activity_core::MAX_TIMELINE_DAYS;
+use agent_chain_core::messages::{AnyMessage, ContentBlock, TextContentBlock};
+use axum::{
+    Json, Router,
+    extract::{Query, Request, State},
+    http::{StatusCode, header},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use chrono::{DateTime, TimeDelta, Utc};
+use euro_activity::{ActivityError, ActivityStorage, calendar};
+use euro_thread::{ChatBridge, ChatSendRequest, ChatServerMessage, ChatSinkError, TurnOpening};
+use euro_transport_policy::CHAT_STREAM_TIMEOUT;
+use serde::{Deserialize, Serialize};
+use thread_core::{Thread, ToolBackend};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use crate::shared_types::SharedThreadManager;
+
+const DEFAULT_THREAD_PAGE: u32 = 20;
+const MAX_THREAD_PAGE: u32 = 100;
+const DEFAULT_CALENDAR_DAYS: i64 = 7;
+
+#[derive(Clone)]
+struct LocalApiState {
+    token: Arc<str>,
+    thread_manager: SharedThreadManager,
+    backend: Arc<dyn ToolBackend>,
+    activity_storage: Arc<ActivityStorage>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum LocalApiError {
+    #[error("missing or invalid bearer token")]
+    Unauthorized,
+
+    #[error("{0}")]
+    BadRequest(&'static str),
+
+    #[error(transparent)]
+    Thread(#[from] euro_thread::Error),
+
+    #[error(transparent)]
+    Activity(#[from] ActivityError),
+
+    #[error("chat turn failed: {0}")]
+    Turn(String),
+
+    #[error("chat turn timed out after {0}s")]
+    Timeout(u64),
+}
+
+#[derive(Serialize)]
+struct ErrorBody {
+    error: String,
+}
+
+impl IntoResponse for LocalApiError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            LocalApiError::Unauthorized => StatusCode::UNAUTHORIZED,
+            LocalApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            LocalApiError::Thread(euro_thread::Error::ThreadNotFound) => StatusCode::NOT_FOUND,
+            LocalApiError::Thread(euro_thread::Error::Auth(_)) => StatusCode::UNAUTHORIZED,
+            LocalApiError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+            LocalApiError::Thread(_) | LocalApiError::Activity(_) | LocalApiError::Turn(_) => {
+                StatusCode::BAD_GATEWAY
+            }
+        };
+        if status != StatusCode::UNAUTHORIZED {
+            tracing::warn!("Local API request failed: {self}");
+        }
+        (
+            status,
+            Json(ErrorBody {
+                error: self.to_string(),
+            }),
+        )
+            .into_response()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct AskRequest {
+    question: String,
+    /// Continue an existing thread. A new one is created when absent.
+    #[serde(default)]
+    thread_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+struct AskResponse {
+    thread_id: Uuid,
+    answer: String,
+}
+
+#[derive(Debug, Serialize)]
+struct CurrentContextResponse {
+    blocks: Vec<ContentBlock>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ThreadsQuery {
+    #[serde(default)]
+    limit: Option<u32>,
+    #[serde(default)]
+    offset: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+struct ThreadsResponse {
+    threads: Vec<Thread>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CalendarQuery {
+    #[serde(default)]
+    since: Option<DateTime<Utc>>,
+    #[serde(default)]
+    until: Option<DateTime<Utc>>,
+    #[serde(default)]
+    device: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FeedTokenQuery {
+    #[serde(default)]
+    token: Option<String>,
+}
+
+/// Bind the local API on `127.0.0.1:port` and spawn its accept loop.
+/// Returns once the socket is listening so a bind failure (port taken)
+/// surfaces to the caller instead of vanishing inside the task.
+pub async fn spawn(
+    port: u16,
+    token: String,
+    thread_manager: SharedThreadManager,
+    backend: Arc<dyn ToolBackend>,
+    activity_storage: Arc<ActivityStorage>,
+) -> std::io::Result<SocketAddr> {
+    let state = LocalApiState {
+        token: token.into(),
+        thread_manager,
+        backend,
+        activity_storage,
+    };
+    let listener =
+        tokio::net::TcpListener::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, port))).await?;
+    let local_addr = listener.local_addr()?;
+
+    tauri::async_runtime::spawn(async move {
+        if let Err(err) = axum::serve(listener, router(state)).await {
+            tracing::error!("Local API server stopped: {err}");
+        }
+    });
+    Ok(local_addr)
+}
+
+fn router(state: LocalApiState) -> Router {
+    let feeds = Router::new()
+        .route("/v1/activity/calendar.ics", get(activity_calendar))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            require_feed_token,
+        ));
+    Router::new()
+        .route("/v1/ask", post(ask))
+        .route("/v1/context/current", get(current_context))
+        .route("/v1/threads", get(list_threads))
+        .layer(middleware::from_fn_with_state(state.clone(), require_token))
+        .merge(feeds)
+        .with_state(state)
+}
+
+async fn require_token(
+    State(state): State<LocalApiState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, LocalApiError> {
+    let presented = bearer_token(&request).ok_or(LocalApiError::Unauthorized)?;
+    if !constant_time_eq(presented.as_bytes(), state.token.as_bytes()) {
+        return Err(LocalApiError::Unauthorized);
+    }
+    Ok(next.run(request).await)
+}
+
+/// [`require_token`] for subscribable feeds: the token may also come as
+/// the `token` query parameter.
+async fn require_feed_token(
+    State(state): State<LocalApiState>,
+    Query(query): Query<FeedTokenQuery>,
+    request: Request,
+    next: Next,
+) -> Result<Response, LocalApiError> {
+    let presented = bearer_token(&request)
+        .or(query.token.as_deref())
+        .ok_or(LocalApiError::Unauthorized)?;
+    if !constant_time_eq(presented.as_bytes(), state.token.as_bytes()) {
+        return Err(LocalApiError::Unauthorized);
+    }
+    Ok(next.run(request).await)
+}
+
+fn bearer_token(request: &Request) -> Option<&str> {
+    request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+async fn ask(
+    State(state): State<LocalApiState>,
+    Json(body): Json<AskRequest>,
+) -> Result<Json<AskResponse>, LocalApiError> {
+    let question = body.question.trim();
+    if question.is_empty() {
+        return Err(LocalApiError::BadRequest("question must not be empty"));
+    }
+
+    let thread_id = match body.thread_id {
+        Some(id) => id,
+        None => state.thread_manager.create(None).await?.id,
+    };
+    let answer = run_turn(&state.thread_manager, &state.backend, thread_id, question).await?;
+
+    Ok(Json(AskResponse { thread_id, answer }))
+}
+
+/// Run one chat turn in `thread_id` and return the assistant's final
+/// answer as text. Dropping the future cancels the turn.
+pub(crate) async fn run_turn(
+    thread_manager: &SharedThreadManager,
+    backend: &Arc<dyn ToolBackend>,
+    thread_id: Uuid,
+    question: &str,
+) -> Result<String, LocalApiError> {
+    let cancel = CancellationToken::new();
+    let cancel_on_drop = cancel.clone().drop_guard();
+    let socket = thread_manager
+        .open_chat_socket(thread_id, cancel.clone())
+        .await?;
+    let opening = TurnOpening::Send(ChatSendRequest {
+        content_blocks: vec![ContentBlock::Text(
+            TextContentBlock::builder()
+                .text(question.to_string())
+                .build(),
+        )],
+        parent_message_id: None,
+        asset_chips_json: None,
+        activity_id: None,
+        request_id: None,
+    });
+
+    // Only the terminal frames matter here: `Final` carries the
+    // persisted AI message, `Error` the reason the turn aborted.
+    let outcome = std::sync::Mutex::new(None);
+    let sink = |event: ChatServerMessage| -> Result<(), ChatSinkError> {
+        match event {
+            ChatServerMessage::Final { messages } => {
+                *outcome.lock().map_err(|e| ChatSinkError(e.to_string()))? = Some(Ok(messages));
+            }
+            ChatServerMessage::Error { message, .. } => {
+                *outcome.lock().map_err(|e| ChatSinkError(e.to_string()))? = Some(Err(message));
+            }
+            _ => {}
+        }
+        Ok(())
+    };
+
+    let bridge = ChatBridge::new(backend.clone());
+    let turn = bridge.run_turn(socket, opening, cancel.clone(), &sink);
+    match tokio::time::timeout(CHAT_STREAM_TIMEOUT, turn).await {
+        Ok(Ok(())) => {}
+        Ok(Err(err)) => return Err(err.into()),
+        Err(_) => return Err(LocalApiError::Timeout(CHAT_STREAM_TIMEOUT.as_secs())),
+    }
+
+    let messages = outcome
+        .into_inner()
+        .ok()
+        .flatten()
+        .ok_or_else(|| LocalApiError::Turn("turn ended without a final frame".into()))?
+        .map_err(LocalApiError::Turn)?;
+    cancel_on_drop.disarm();
+    Ok(messages
+        .iter()
+        .rev()
+        .find_map(|node| match &node.message {
+            AnyMessage::AIMessage(message) => Some(message.text()),
+            _ => None,
+        })
+        .unwrap_or_default())
+}
+
+async fn current_context(
+    State(state): State<LocalApiState>,
+) -> Result<Json<CurrentContextResponse>, LocalApiError> {
+    let blocks = state.backend.collect_system_blocks().await;
+    Ok(Json(CurrentContextResponse { blocks }))
+}
+
+async fn list_threads(
+    State(state): State<LocalApiState>,
+    Query(query): Query<ThreadsQuery>,
+) -> Result<Json<ThreadsResponse>, LocalApiError> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_THREAD_PAGE)
+        .clamp(1, MAX_THREAD_PAGE);
+    let threads = state
+        .thread_manager
+        .list_threads(limit, query.offset.unwrap_or(0))
+        .await?;
+    Ok(Json(ThreadsResponse { threads }))
+}
+
+async fn activity_calendar(
+    State(state): State<LocalApiState>,
+    Query(query): Query<CalendarQuery>,
+) -> Result<Response, LocalApiError> {
+    let now = Utc::now();
+    let until = query.until.unwrap_or(now);
+    let since = query
+        .since
+        .unwrap_or(until - TimeDelta::days(DEFAULT_CALENDAR_DAYS));
+    if since >= until {
+        return Err(LocalApiError::BadRequest("since must be before until"));
+    }
+    if until - since > TimeDelta::days(i64::from(MAX_TIMELINE_DAYS)) {
+        return Err(LocalApiError::BadRequest(
+            "the range is longer than the activity timeline serves",
+        ));
+    }
+
+    let timeline = state
+        .activity_storage
+        .activity_timeline(since, until, query.device)
+        .await?;
+    if timeline.truncated {
+        tracing::warn!("Activity calendar range held too many sessions; the latest are missing");
+    }
+    let blocks = calendar::focus_blocks(&timeline.activities, since, until, now);
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/calendar; charset=utf-8"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"eurora-activity.ics\"",
+            ),
+        ],
+        calendar::to_ics(&blocks, now),
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_comparison_requires_exact_match() {
+        assert!(constant_time_eq(b"abc123", b"abc123"));
+        assert!(!constant_time_eq(b"abc123", b"abc124"));
+        assert!(!constant_time_eq(b"abc", b"abc123"));
+        assert!(!constant_time_eq(b"", b"abc"));
+    }
+}
@@ -0,0 +1,146 @@
+//! Tools that run in-process on the desktop, alongside whatever the
+//! active activity strategy offers.
+//!
+//! Today that's the read-only file tools from [`agent_chain::files`],
+//! scoped to the folders listed under `fileAccess.roots` in
+//! `local.json`, and [`agent_chain::shell`]'s `run_command`, confined to
+//! the workspace and programs under `shell`. A tool with nothing
+//! configured isn't advertised at all. `run_command` is advertised as
+//! needing approval, and [`crate::tool_consent`] asks before every call.
+//!
+//! It also attaches a screen region the user captured for their question
+//! (see [`crate::region_capture`]) to the turn it was captured for.
+
+use std::sync::Arc;
+
+use agent_chain::files::FileRoots;
+use agent_chain::shell::{self, RunCommandTool, ShellPolicy};
+use agent_chain::tools::{BaseTool, DynTool, ToolInput, ToolOutput};
+use async_trait::async_trait;
+use serde_json::{Value, json};
+use tauri::{AppHandle, Manager};
+use thread_core::{ToolBackend, ToolBackendCall, ToolErrorWire, ToolSource, WireToolDescriptor};
+
+use crate::region_capture::RegionCaptureState;
+use crate::shared_types::SharedSettingsState;
+
+/// Walking a large folder for `grep_files` can take a while.
+const LOCAL_TOOL_TIMEOUT_MS: u32 = 30_000;
+
+/// The consent prompt (up to two minutes) plus the command's own
+/// timeout, with room to spare.
+const RUN_COMMAND_TIMEOUT_MS: u32 = 180_000;
+
+/// [`ToolBackend`] that adds the desktop's local tools to `inner`'s.
+pub struct LocalToolBackend {
+    inner: Arc<dyn ToolBackend>,
+    app_handle: AppHandle,
+}
+
+impl LocalToolBackend {
+    pub fn new(inner: Arc<dyn ToolBackend>, app_handle: AppHandle) -> Self {
+        Self { inner, app_handle }
+    }
+
+    /// Built fresh from settings on every call so a change to the shared
+    /// folders or allowed programs applies from the next turn without a
+    /// restart.
+    async fn tools(&self) -> Vec<DynTool> {
+        let Some(state) = self.app_handle.try_state::<SharedSettingsState>() else {
+            return Vec::new();
+        };
+        let (roots, shell) = {
+            let settings = state.lock().await;
+            (
+                settings.local.file_access.roots.clone(),
+                settings.local.shell.clone(),
+            )
+        };
+
+        let mut tools: Vec<DynTool> = Vec::new();
+        if !roots.is_empty() {
+            let roots = Arc::new(FileRoots::new(roots));
+            if !roots.is_empty() {
+                tools.extend(
+                    roots
+                        .tools()
+                        .into_iter()
+                        .map(|tool| Arc::new(tool) as DynTool),
+                );
+            }
+        }
+        if let Some(workspace) = shell.workspace.filter(|_| shell.is_configured()) {
+            let policy = ShellPolicy::builder()
+                .root(workspace)
+                .allowed(shell.allowed_programs)
+                .build();
+            tools.push(Arc::new(RunCommandTool::new(policy)));
+        }
+        tools
+    }
+}
+
+#[async_trait]
+impl ToolBackend for LocalToolBackend {
+    async fn list_tools(&self) -> Vec<WireToolDescriptor> {
+        let mut tools = self.inner.list_tools().await;
+        tools.extend(self.tools().await.iter().map(|tool| {
+            let runs_commands = tool.name() == shell::TOOL_NAME;
+            WireToolDescriptor {
+                definition: tool.definition(),
+                output_schema: json!({ "type": "object" }),
+                timeout_ms: if runs_commands {
+                    RUN_COMMAND_TIMEOUT_MS
+                } else {
+                    LOCAL_TOOL_TIMEOUT_MS
+                },
+                source: ToolSource::ClientLocal,
+                required_contexts: Vec::new(),
+                requires_user_approval: runs_commands,
+            }
+        }));
+        tools
+    }
+
+    async fn collect_system_blocks(&self) -> Vec<agent_chain_core::messages::ContentBlock> {
+        let mut blocks = self.inner.collect_system_blocks().await;
+        // Taken, not copied: the capture belongs to this turn only.
+        let capture = self
+            .app_handle
+            .try_state::<RegionCaptureState>()
+            .and_then(|state| state.pending.lock().take());
+        if let Some(capture) = capture {
+            blocks.extend(capture.into_blocks());
+        }
+        blocks
+    }
+
+    async fn dispatch(&self, call: ToolBackendCall) -> Result<Value, ToolErrorWire> {
+        let tools = self.tools().await;
+        let Some(tool) = tools.iter().find(|t| t.name() == call.name) else {
+            return self.inner.dispatch(call).await;
+        };
+
+        let args = match call.arguments {
+            Value::Object(map) => map.into_iter().collect(),
+            Value::Null => Default::default(),
+            other => {
+                return Err(ToolErrorWire::Decode {
+                    message: format!("expected an object, got {other}"),
+                });
+            }
+        };
+        let output = tokio::select! {
+            output = tool.run(ToolInput::Dict(args), None, None) => output,
+            () = call.cancel.cancelled() => return Err(ToolErrorWire::Cancelled),
+        };
+        match output {
+            Ok(ToolOutput::Json(value)) => Ok(value),
+            Ok(ToolOutput::String(text)) => Ok(Value::String(text)),
+            Ok(other) => Ok(Value::String(other.to_string_lossy())),
+            Err(e) => Err(ToolErrorWire::Adapter {
+                message: e.to_string(),
+            }),
+        }
+    }
+}
@@ -3,24 +3,38 @@
     windows_subsystem = "windows"
 )]
 
-use euro_activity::ActivityToolBackend;
+use agent_chain_core::tools::{RiskLevel, ToolPermissions};
+use euro_activity::{ActivityToolBackend, ModeratedToolBackend};
 use euro_endpoint::EndpointManager;
+use euro_personal_db::PersonalDb;
 use euro_settings::{CloudSettingsCache, SettingsState};
 use euro_tauri::chat_context::TimelineChatContextProvider;
 use euro_tauri::{
     MAIN_WINDOW_LABEL, WindowState, build_specta, create_window,
+    local_tools::LocalToolBackend,
+    moderation::{SharedModerator, moderator_from_settings},
+    plugins::PluginToolBackend,
     procedures::{
         accent::accent_from_image,
         activity::{
             SavedActivityLiveSessionEnded, SavedActivityUpserted, saved_activity_from_parts,
         },
+        notification::ServerNotification,
         system::{
             BrowserExtensionStatusChanged, SAFARI_BRIDGE_APP_KIND, resolve_browser_extension_state,
         },
         timeline::{TimelineAppEvent, TimelineAssetsEvent},
     },
+    region_capture::{self, RegionCaptureState},
     shared_types::{ActiveStreamTokens, SharedHttpClient, SharedThreadManager},
     show_and_focus_main,
+    startup::{Deferred, StartupTimer},
+    tool_consent::{
+        ConsentToolBackend, PendingConsents, SettingsDecisionStore, TauriConsentHandler,
+    },
+    updater::UpdaterState,
+    util::get_db_path,
+    voice::VoiceState,
 };
 use euro_telemetry::{Controller as TelemetryController, sentry_tracing};
 use euro_thread::commands::SharedChatContextProvider;
@@ -31,6 +45,7 @@ use tauri::{
     menu::{Menu, MenuItem},
     tray::TrayIconBuilder,
 };
+use tauri_plugin_global_shortcut::ShortcutState;
 use tauri_specta::Event;
 use thread_core::ToolBackend;
 use tokio::sync::Mutex;
@@ -215,10 +230,10 @@ fn install_native_messaging_manifests(app: &tauri::App) -> bool {
     }
 }
 
-fn install_office_word_addin(app: &tauri::App) {
+fn install_office_word_addin(app: &tauri::AppHandle) {
     use euro_tauri::office_addin::{Error, InstallOutcome, install_for_app};
 
-    match install_for_app(app.handle()) {
+    match install_for_app(app) {
         Ok(InstallOutcome::Installed { manifest_path }) => tracing::info!(
             "Installed Office add-in manifest at {}",
             manifest_path.display()
@@ -277,6 +292,27 @@ fn bind_and_serve_bridge() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// The global-shortcut plugin, routing the region-capture shortcut to the
+/// selection overlay. The shortcut itself is registered from settings
+/// during setup.
+fn global_shortcut_plugin() -> tauri::plugin::TauriPlugin<tauri::Wry> {
+    tauri_plugin_global_shortcut::Builder::new()
+        .with_handler(|app, shortcut, event| {
+            if event.state() != ShortcutState::Pressed
+                || !region_capture::is_capture_shortcut(app, shortcut)
+            {
+                return;
+            }
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = region_capture::begin(&app).await {
+                    tracing::warn!("Failed to start region capture: {e}");
+                }
+            });
+        })
+        .build()
+}
+
 fn register_autostart(tauri_app: &mut tauri::App, settings: &SettingsState) {
     let should_register = !cfg!(debug_assertions) && !cfg!(target_os = "macos");
     let started_by_autostart = std::env::args().any(|arg| arg == "--startup-launch");
@@ -356,6 +392,9 @@ fn init_state(
     tauri_app: &tauri::App,
     endpoint_manager: &std::sync::Arc<EndpointManager>,
     auth_manager: &euro_auth::AuthManager,
+    local: &euro_settings::LocalSettings,
+    http_client: &SharedHttpClient,
+    transfers: &euro_transfer::TransferManager,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let app_handle = tauri_app.handle();
 
@@ -366,13 +405,60 @@ fn init_state(
     let timeline = euro_timeline::TimelineManager::builder()
         .endpoint_manager(endpoint_manager.clone())
         .auth_manager(auth_manager.clone())
+        .transfers(transfers.clone())
+        .maybe_device_id(local.device.id)
         .build()?;
     // `ToolBackend` shares the same `Arc<RwLock<ActivityStrategy>>` the
     // collector swaps on focus changes — the chat side always sees the
     // freshest strategy without any reconnection.
-    let backend: std::sync::Arc<dyn ToolBackend> = std::sync::Arc::new(ActivityToolBackend::new(
-        timeline.collector.active_strategy(),
-    ));
+    let activity_backend: std::sync::Arc<dyn ToolBackend> = std::sync::Arc::new(
+        ActivityToolBackend::new(timeline.collector.active_strategy()),
+    );
+    let local_backend: std::sync::Arc<dyn ToolBackend> =
+        std::sync::Arc::new(LocalToolBackend::new(activity_backend, app_handle.clone()));
+    // Enabled plugins add their tools and context on top; see
+    // `euro_tauri::plugins`.
+    let plugin_registry = euro_tauri::plugins::open(app_handle)?;
+    let plugin_backend: std::sync::Arc<dyn ToolBackend> = std::sync::Arc::new(
+        PluginToolBackend::new(local_backend, plugin_registry.clone()),
+    );
+    // Everything the plugin, local or activity backend returns is
+    // scanned for secrets before it leaves the machine. A broken custom
+    // pattern falls back to the built-in detectors rather than to no
+    // scanning.
+    let moderator = moderator_from_settings(&local.moderation, http_client).unwrap_or_else(|e| {
+        tracing::warn!("Invalid moderation settings, using built-in detectors only: {e}");
+        euro_activity::Moderator::new(euro_activity::ModerationConfig::default())
+            .expect("default moderation config has no custom patterns")
+    });
+    let moderator: SharedModerator =
+        std::sync::Arc::new(std::sync::RwLock::new(std::sync::Arc::new(moderator)));
+    let moderated_backend: std::sync::Arc<dyn ToolBackend> =
+        std::sync::Arc::new(ModeratedToolBackend::new(plugin_backend, moderator.clone()));
+    // Every tool call passes the consent check before it reaches the
+    // plugin, local or activity backend; tools that declare
+    // `requires_user_approval` prompt the user through the UI, and
+    // `run_command` prompts on every call, whatever was answered before.
+    let pending_consents = PendingConsents::default();
+    let permissions = ToolPermissions::builder()
+        .risks(std::collections::HashMap::from([(
+            agent_chain::shell::TOOL_NAME.to_string(),
+            RiskLevel::Dangerous,
+        )]))
+        .handler(std::sync::Arc::new(TauriConsentHandler::new(
+            app_handle.clone(),
+            pending_consents.clone(),
+        )))
+        .store(std::sync::Arc::new(SettingsDecisionStore::new(
+            app_handle.clone(),
+            local.tool_permissions.always_allowed.clone(),
+        )))
+        .build();
+    let backend: std::sync::Arc<dyn ToolBackend> =
+        std::sync::Arc::new(ConsentToolBackend::new(moderated_backend, permissions));
+    app_handle.manage(pending_consents);
+    app_handle.manage(plugin_registry);
+    app_handle.manage(moderator);
     app_handle.manage(Mutex::new(timeline));
     app_handle.manage(backend);
 
@@ -385,6 +471,50 @@ fn init_state(
     Ok(())
 }
 
+/// Start the opt-in localhost API when `localApi.enabled` is set,
+/// minting (and persisting) its bearer token on first use. Must run
+/// after [`init_state`] — the server shares the thread manager and tool
+/// backend registered there. Bind failures are logged, not fatal: the
+/// API is a power-user convenience and must never block startup.
+fn start_local_api(tauri_app: &tauri::App, settings: &mut SettingsState) {
+    let config = &mut settings.local.local_api;
+    if !config.enabled {
+        return;
+    }
+    if config.ensure_token()
+        && let Err(e) = settings.save_local_to_default_path()
+    {
+        tracing::warn!("Could not persist local API token, not starting local API: {e}");
+        return;
+    }
+
+    let port = settings.local.local_api.port;
+    let Some(token) = settings.local.local_api.token.clone() else {
+        return;
+    };
+    let thread_manager = tauri_app.state::<SharedThreadManager>().inner().clone();
+    let backend = tauri_app
+        .state::<std::sync::Arc<dyn ToolBackend>>()
+        .inner()
+        .clone();
+    let app_handle = tauri_app.handle().clone();
+
+    tauri::async_runtime::spawn(async move {
+        let activity_storage = app_handle
+            .state::<Mutex<TimelineManager>>()
+            .lock()
+            .await
+            .activity_storage
+            .clone();
+        match euro_tauri::local_api::spawn(port, token, thread_manager, backend, activity_storage)
+            .await
+        {
+            Ok(addr) => tracing::info!("Local API listening on http://{addr}"),
+            Err(e) => tracing::warn!("Could not start local API on port {port}: {e}"),
+        }
+    });
+}
+
 /// Drain a `broadcast::Receiver` forever, applying `handler` to each event.
 ///
 /// `while let Ok(_) = rx.recv().await` exits the loop on `Lagged`, which
@@ -542,6 +672,74 @@ fn spawn_timeline_listeners(app_handle: tauri::AppHandle) {
     });
 }
 
+/// Open the personal database, migrating it if this build ships new
+/// migrations, and manage it on the app once it's ready. Local features
+/// that need it look it up with `try_state::<PersonalDb>()`. A database
+/// that can't be opened is logged rather than fatal — the rest of the app
+/// works without it, and `eur db doctor` explains what's wrong. An open
+/// database starts focus session and screen-time tracking and gets
+/// [`PersonalDb::maintain`] every [`euro_personal_db::MAINTENANCE_INTERVAL`]
+/// for the life of the app.
+fn open_personal_db(app_handle: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let path = match get_db_path(&app_handle) {
+            Ok(path) => path,
+            Err(e) => {
+                tracing::error!("Failed to resolve personal database path: {e}");
+                return;
+            }
+        };
+        match PersonalDb::open(path).await {
+            Ok(db) => {
+                app_handle.manage(db.clone());
+                euro_tauri::focus::start(&app_handle, db.clone()).await;
+                euro_tauri::screen_time::start(&app_handle, db.clone());
+                let mut ticks = tokio::time::interval(euro_personal_db::MAINTENANCE_INTERVAL);
+                // Skip the immediate first tick; startup has enough to do.
+                ticks.tick().await;
+                loop {
+                    ticks.tick().await;
+                    if let Err(e) = db.maintain().await {
+                        tracing::warn!("Personal database maintenance failed: {e}");
+                    }
+                }
+            }
+            Err(e) => {
+                tracing::error!("Failed to open personal database (run `eur db doctor`): {e}");
+            }
+        }
+    });
+}
+
+/// Keep the server push socket open and relay what it delivers to the
+/// frontend as [`ServerNotification`]s. The client follows auth
+/// transitions itself, so this runs for the life of the app.
+fn spawn_notification_bridge(
+    app_handle: tauri::AppHandle,
+    endpoint_manager: std::sync::Arc<EndpointManager>,
+    auth_manager: euro_auth::AuthManager,
+) {
+    let client = euro_notification::NotificationClient::new(endpoint_manager, auth_manager);
+    // Subscribe before the socket starts so the first replay isn't lost.
+    let mut updates = client.subscribe();
+    tauri::async_runtime::spawn(async move { client.run().await });
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let notification = match updates.recv().await {
+                Ok(update) => ServerNotification::from(update),
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                    tracing::warn!("Server notification bridge lagged by {n} events");
+                    ServerNotification::Resync
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            };
+            if let Err(e) = notification.emit(&app_handle) {
+                tracing::warn!(error = %e, "Failed to emit ServerNotification");
+            }
+        }
+    });
+}
+
 /// Forward bridge registry changes — native-messenger registrations,
 /// disconnects, and bundled-extension state reports — to the frontend as
 /// `BrowserExtensionStatusChanged` events.
@@ -657,6 +855,12 @@ fn main() {
     // applied later, after `SettingsState::load_or_migrate` runs inside
     // `setup`. The intervening Sentry events are tagged with no user
     // scope — acceptable for the boot window.
+    // Panics Sentry isn't running to see (the user hasn't opted in) are
+    // written to disk, and the frontend asks about them on next launch.
+    if let Some(dir) = euro_telemetry::crash::default_crash_dir() {
+        euro_telemetry::crash::install_crash_recorder(dir);
+    }
+
     let early_cache = CloudSettingsCache::peek_from_default_path();
     let telemetry_controller = std::sync::Arc::new(TelemetryController::init(
         early_cache
@@ -707,6 +911,7 @@ fn main() {
                 .plugin(tauri_plugin_os::init())
                 .plugin(tauri_plugin_clipboard_manager::init())
                 .plugin(tauri_plugin_updater::Builder::new().build())
+                .plugin(global_shortcut_plugin())
                 .invoke_handler(specta.invoke_handler())
                 .setup(move |tauri_app| {
                     // `mount_events` must run inside `setup` so the typed
@@ -714,9 +919,21 @@ fn main() {
                     // chance to emit. Move `specta` into the closure so its
                     // event registry stays alive for the app lifetime.
                     specta.mount_events(tauri_app);
+                    euro_tauri::updater::guard_boot(tauri_app.handle());
 
+                    let mut startup = StartupTimer::start();
+                    let mut deferred = Deferred::default();
+
+                    startup.phase("bridge");
                     let messenger_replaced = install_native_messaging_manifests(tauri_app);
-                    install_office_word_addin(tauri_app);
+                    // Word picks the add-in up on its next launch, not ours,
+                    // so installing it can wait until the window is up.
+                    {
+                        let app_handle = tauri_app.handle().clone();
+                        deferred.push("office_addin", move || {
+                            install_office_word_addin(&app_handle);
+                        });
+                    }
                     bind_and_serve_bridge()?;
 
                     // Trigger the OS-level screen-capture permission prompt
@@ -744,12 +961,13 @@ fn main() {
                             .open_browser_purge_window(MESSENGER_PURGE_WINDOW);
                     }
 
+                    startup.phase("settings");
                     let data_dir = tauri_app.path().app_data_dir()?;
 
                     let started_by_autostart =
                         std::env::args().any(|arg| arg == "--startup-launch");
 
-                    let settings = SettingsState::load_or_migrate_from_default_path()?;
+                    let mut settings = SettingsState::load_or_migrate_from_default_path()?;
                     // The persisted ConnectionMode always resolves to a
                     // non-empty URL, so we never need an env-fallback path.
                     let endpoint_url = settings.local.api.endpoint();
@@ -760,6 +978,7 @@ fn main() {
                         "Resolved API endpoint at startup"
                     );
                     let endpoint_manager = std::sync::Arc::new(EndpointManager::new(endpoint_url)?);
+                    endpoint_manager.set_local_only(settings.local.general.local_only);
 
                     // Reconcile the early-Sentry guard against the
                     // settings we just authoritatively loaded from disk.
@@ -776,6 +995,7 @@ fn main() {
                         settings.local.telemetry.distinct_id.as_deref(),
                     );
 
+                    startup.phase("state");
                     let http_client: SharedHttpClient = reqwest::Client::builder()
                         .timeout(std::time::Duration::from_secs(5))
                         .build()
@@ -784,7 +1004,12 @@ fn main() {
                     tauri_app.manage(endpoint_manager.clone());
                     tauri_app.manage(telemetry_controller.clone());
                     tauri_app.manage(WindowState::default());
-                    tauri_app.manage(http_client);
+                    tauri_app.manage(http_client.clone());
+                    tauri_app.manage(VoiceState::default());
+                    tauri_app.manage(RegionCaptureState::default());
+                    let transfers = euro_tauri::transfers::start(&settings.local.transfers);
+                    tauri_app.manage(transfers.clone());
+                    tauri_app.manage(UpdaterState::default());
 
                     // Single shared AuthManager so concurrent refreshes
                     // from any consumer (thread, timeline, sync) coalesce
@@ -801,10 +1026,30 @@ fn main() {
                     // frontend starts firing IPC calls, and any procedure
                     // that does `try_state::<...>()` will see `None` if its
                     // backing manager hasn't been registered yet.
-                    init_state(tauri_app, &endpoint_manager, &auth_manager)?;
+                    euro_tauri::devices::ensure_id(&mut settings);
+                    init_state(
+                        tauri_app,
+                        &endpoint_manager,
+                        &auth_manager,
+                        &settings.local,
+                        &http_client,
+                        &transfers,
+                    )?;
+                    euro_tauri::devices::start(tauri_app.handle(), &auth_manager);
 
                     register_autostart(tauri_app, &settings);
+                    region_capture::apply_shortcut(
+                        tauri_app.handle(),
+                        &settings.local.region_capture,
+                    );
+                    start_local_api(tauri_app, &mut settings);
+                    if let Err(e) =
+                        euro_tauri::scripts::start(tauri_app.handle(), &settings.local.scripts)
+                    {
+                        tracing::warn!("Could not start user scripts: {e}");
+                    }
 
+                    startup.phase("settings_sync");
                     // Wrap settings in `Arc<Mutex<...>>` so the sync
                     // engine and the IPC handlers share one in-memory
                     // copy. The engine writes back to it after a pull
@@ -864,17 +1109,29 @@ fn main() {
                     tauri_app.manage(settings_state);
                     tauri_app.manage(sync_engine);
 
+                    startup.phase("window");
                     setup_main_window(tauri_app, started_by_autostart)?;
                     setup_tray(tauri_app)?;
 
+                    startup.phase("background");
                     spawn_timeline_listeners(tauri_app.handle().clone());
                     spawn_browser_status_bridge(tauri_app.handle().clone());
+                    spawn_notification_bridge(
+                        tauri_app.handle().clone(),
+                        endpoint_manager.clone(),
+                        auth_manager.clone(),
+                    );
+                    open_personal_db(tauri_app.handle().clone());
+                    euro_tauri::updater::spawn(tauri_app.handle().clone(), transfers);
 
                     // The chat-side `ToolBackend` is constructed and
                     // managed inside `init_state`; tools are sourced
                     // dynamically from the active activity strategy, so
                     // there's no per-app catalog to register here.
 
+                    deferred.spawn();
+                    startup.finish();
+                    euro_tauri::updater::confirm_boot_later(tauri_app.handle().clone());
                     Ok(())
                 })
                 .plugin(tauri_plugin_http::init())
@@ -939,17 +1196,39 @@ fn main() {
                         tauri_plugin_window_state::StateFlags::all()
                             & !tauri_plugin_window_state::StateFlags::DECORATIONS,
                     )
+                    // The overlay is sized to whichever monitor it opens on.
+                    .with_denylist(&[region_capture::OVERLAY_WINDOW_LABEL])
                     .build(),
             );
 
             builder
                 .build(tauri_context)
                 .expect("Failed to build tauri app")
-                .run(|_app_handle, event| {
+                .run(|app_handle, event| {
                     if matches!(event, tauri::RunEvent::Exit) {
+                        let app_handle = app_handle.clone();
                         let (tx, rx) = std::sync::mpsc::sync_channel::<()>(1);
                         tauri::async_runtime::spawn(async move {
                             euro_bridge::stop_bridge_server().await;
+                            if let Some(scripts) =
+                                app_handle.try_state::<euro_script::ScriptScheduler>()
+                            {
+                                scripts.shutdown();
+                            }
+                            if let Some(plugins) =
+                                app_handle.try_state::<euro_tauri::plugins::SharedPluginRegistry>()
+                            {
+                                plugins.shutdown().await;
+                            }
+                            // Swap in a downloaded update so the next start
+                            // runs it.
+                            match euro_tauri::updater::apply_staged(&app_handle).await {
+                                Ok(Some(version)) => {
+                                    tracing::info!("Installed update {version} on exit");
+                                }
+                                Ok(None) => {}
+                                Err(e) => tracing::warn!("Failed to install update on exit: {e}"),
+                            }
                             let _ = tx.send(());
                         });
                         let _ = rx.recv();
@@ -0,0 +1,56 @@
+//! Desktop wiring for [`euro_activity::moderation`].
+//!
+//! The [`Moderator`] is built from the `moderation` section of
+//! `local.json` and shared between the [`ModeratedToolBackend`] in the
+//! tool chain and `settings_set_moderation`, which swaps in a rebuilt
+//! one so edits apply from the next chat turn.
+//!
+//! [`ModeratedToolBackend`]: euro_activity::ModeratedToolBackend
+
+use std::sync::{Arc, RwLock};
+
+use euro_activity::{ActivityResult, ModerationConfig, Moderator, OpenAiModeration};
+use euro_settings::{ModerationAction, ModerationSettings, OcrSensitivity};
+
+/// Tauri state holding the moderator currently in effect.
+pub type SharedModerator = Arc<RwLock<Arc<Moderator>>>;
+
+/// Build the moderator `settings` describe. Fails on an invalid custom
+/// pattern.
+pub fn moderator_from_settings(
+    settings: &ModerationSettings,
+    http: &reqwest::Client,
+) -> ActivityResult<Moderator> {
+    let moderator = Moderator::new(ModerationConfig {
+        enabled: settings.enabled,
+        action: match settings.action {
+            ModerationAction::Redact => euro_activity::ModerationAction::Redact,
+            ModerationAction::Block => euro_activity::ModerationAction::Block,
+        },
+        custom_patterns: settings.custom_patterns.clone(),
+        ocr_allowlist: settings.ocr_allowlist.clone(),
+        ocr_app_sensitivity: settings
+            .ocr_app_sensitivity
+            .iter()
+            .map(|(app, sensitivity)| {
+                let sensitivity = match sensitivity {
+                    OcrSensitivity::Relaxed => euro_activity::OcrSensitivity::Relaxed,
+                    OcrSensitivity::Standard => euro_activity::OcrSensitivity::Standard,
+                    OcrSensitivity::Strict => euro_activity::OcrSensitivity::Strict,
+                };
+                (euro_activity::normalize_process_name(app), sensitivity)
+            })
+            .collect(),
+        ..Default::default()
+    })?;
+    Ok(match &settings.provider {
+        Some(provider) if settings.enabled => {
+            moderator.with_provider(Arc::new(OpenAiModeration::new(
+                http.clone(),
+                &provider.base_url,
+                provider.api_key.clone().into(),
+            )))
+        }
+        _ => moderator,
+    })
+}
@@ -0,0 +1,103 @@
+//! Desktop wiring for [`euro_plugin`].
+//!
+//! The [`PluginRegistry`] lives under `<app data>/plugins` and is
+//! registered as Tauri state for the `plugin_*` commands.
+//! [`PluginToolBackend`] puts the tools of enabled plugins next to the
+//! local and activity ones, and their context into every turn's prelude.
+//! It sits inside the moderation and consent wrappers, so plugin output
+//! is scanned for secrets and every plugin tool call asks the user first.
+
+use std::sync::Arc;
+
+use agent_chain_core::messages::{ContentBlock, TextContentBlock};
+use agent_chain_core::tools::ToolDefinition;
+use async_trait::async_trait;
+use euro_plugin::{PluginError, PluginRegistry};
+use serde_json::{Value, json};
+use tauri::{AppHandle, Manager};
+use thread_core::{ToolBackend, ToolBackendCall, ToolErrorWire, ToolSource, WireToolDescriptor};
+
+/// Matches the default in [`euro_plugin::ToolManifest::timeout_ms`].
+const PLUGIN_TOOL_TIMEOUT_MS: u32 = 30_000;
+
+pub type SharedPluginRegistry = Arc<PluginRegistry>;
+
+/// Open the registry in the app data directory.
+pub fn open(app_handle: &AppHandle) -> Result<SharedPluginRegistry, Box<dyn std::error::Error>> {
+    let root = app_handle.path().app_data_dir()?.join("plugins");
+    Ok(Arc::new(PluginRegistry::open(root)?))
+}
+
+/// [`ToolBackend`] that adds enabled plugins' tools and context to
+/// `inner`'s.
+pub struct PluginToolBackend {
+    inner: Arc<dyn ToolBackend>,
+    registry: SharedPluginRegistry,
+}
+
+impl PluginToolBackend {
+    pub fn new(inner: Arc<dyn ToolBackend>, registry: SharedPluginRegistry) -> Self {
+        Self { inner, registry }
+    }
+}
+
+#[async_trait]
+impl ToolBackend for PluginToolBackend {
+    async fn list_tools(&self) -> Vec<WireToolDescriptor> {
+        let mut tools = self.inner.list_tools().await;
+        tools.extend(self.registry.tools().await.into_iter().map(|(name, tool)| {
+            WireToolDescriptor {
+                definition: ToolDefinition {
+                    name,
+                    description: tool.description,
+                    parameters: tool.parameters,
+                },
+                output_schema: json!({}),
+                timeout_ms: tool.timeout_ms.unwrap_or(PLUGIN_TOOL_TIMEOUT_MS),
+                source: ToolSource::ClientLocal,
+                required_contexts: Vec::new(),
+                // Third-party code: the user sees every call first,
+                // unless they've chosen to always allow the tool.
+                requires_user_approval: true,
+            }
+        }));
+        tools
+    }
+
+    async fn collect_system_blocks(&self) -> Vec<ContentBlock> {
+        let mut blocks = self.inner.collect_system_blocks().await;
+        for (plugin, text) in self.registry.collect_context().await {
+            blocks.push(ContentBlock::Text(
+                TextContentBlock::builder()
+                    .text(format!("Context from the {plugin} plugin:\n{text}"))
+                    .build(),
+            ));
+        }
+        blocks
+    }
+
+    async fn dispatch(&self, call: ToolBackendCall) -> Result<Value, ToolErrorWire> {
+        let is_plugin_tool = self
+            .registry
+            .tools()
+            .await
+            .iter()
+            .any(|(name, _)| *name == call.name);
+        if !is_plugin_tool {
+            return self.inner.dispatch(call).await;
+        }
+        self.registry
+            .call_tool(&call.name, &call.arguments, &call.cancel)
+            .await
+            .map_err(|e| match e {
+                PluginError::Timeout => ToolErrorWire::Timeout,
+                PluginError::Cancelled => ToolErrorWire::Cancelled,
+                PluginError::Spawn(message) | PluginError::Transport(message) => {
+                    ToolErrorWire::Transport { message }
+                }
+                other => ToolErrorWire::Adapter {
+                    message: other.to_string(),
+                },
+            })
+    }
+}
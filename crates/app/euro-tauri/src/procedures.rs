@@ -1,7 +1,15 @@
 pub mod accent;
 pub mod activity;
 pub mod auth;
+pub mod focus;
+pub mod notification;
 pub mod payment;
+pub mod plugins;
+pub mod region_capture;
+pub mod screen_time;
+pub mod scripts;
 pub mod settings;
 pub mod system;
 pub mod timeline;
+pub mod tool_consent;
+pub mod voice;
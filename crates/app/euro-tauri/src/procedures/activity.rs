@@ -1,13 +1,17 @@
 //! Persisted-activity surface exposed to the desktop frontend.
 //!
-//! Three responsibilities live here:
+//! Four responsibilities live here:
 //!
-//! - [`activity_list`] — the one-shot fetch the rail uses on mount to
-//!   hydrate from the most recent persisted activities (parents, with
-//!   their latest session embedded inline). Bridges the
-//!   `GET /activities` HTTP endpoint and the `GET /v1/assets/{id}` icon
-//!   endpoint, decorating each row with a precomputed [`AccentColor`]
-//!   and a `data:` URL the frontend can drop straight into `<img src>`.
+//! - [`activity_list`] — a one-shot page of the most recent persisted
+//!   activities (parents, with their latest session embedded inline).
+//!   Bridges the `GET /activities` HTTP endpoint and the
+//!   `GET /v1/assets/{id}` icon endpoint, decorating each row with a
+//!   precomputed [`AccentColor`] and a `data:` URL the frontend can drop
+//!   straight into `<img src>`.
+//! - [`activity_stream`] — what the rail uses to hydrate and to load
+//!   older history: the same decorated rows, read from
+//!   `GET /activities/stream` and forwarded batch by batch over a
+//!   `Channel` so the first rows render before the rest have arrived.
 //! - [`SavedActivityUpserted`] — the tauri-specta event the persist
 //!   path emits *after* a successful `POST /activity-sessions`. Carries
 //!   the (possibly upserted) parent activity and the new live session
@@ -29,15 +33,17 @@ use std::sync::Arc;
 
 use activity_core::{
     Activity as WireActivity, ActivitySession as WireActivitySession, ActivityWithLatestSession,
+    StreamActivitiesQuery,
 };
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64_STANDARD};
 use chrono::{DateTime, Utc};
 use euro_activity::ActivityStorage;
 use euro_timeline::TimelineManager;
-use futures::future;
+use euro_transfer::Priority;
+use futures::{StreamExt, future};
 use serde::{Deserialize, Serialize};
 use specta::Type;
-use tauri::{AppHandle, Manager};
+use tauri::{AppHandle, Manager, ipc::Channel};
 use tauri_specta::Event;
 use thiserror::Error;
 use tokio::sync::Mutex;
@@ -116,7 +122,8 @@ pub struct SavedActivityLiveSessionEnded {
     pub ended_at: DateTime<Utc>,
 }
 
-/// Errors surfaced to the frontend from [`activity_list`].
+/// Errors surfaced to the frontend from [`activity_list`] and
+/// [`activity_stream`].
 ///
 /// Externally tagged so the JS side gets `{ type: "Network", data: "..." }`
 /// and can branch on `type` rather than parsing strings. Variants are
@@ -130,6 +137,8 @@ pub enum SavedActivityError {
     StateUnavailable(&'static str),
     #[error("network: {0}")]
     Network(String),
+    #[error("channel: {0}")]
+    Channel(String),
 }
 
 impl From<euro_activity::ActivityError> for SavedActivityError {
@@ -163,6 +172,54 @@ pub async fn activity_list(
     Ok(enriched)
 }
 
+/// Stream up to `limit` persisted activities in rail order, starting
+/// after `cursor` (or at the most recent when `None`), sending each
+/// decorated batch to `channel` as soon as its icons are resolved.
+///
+/// Returns the cursor to continue from, or `None` once the history is
+/// exhausted. If a later batch fails, the ones already sent stay
+/// valid; retrying from the same `cursor` re-sends them, which the
+/// rail's id dedupe absorbs.
+#[tauri::command]
+#[specta::specta]
+pub async fn activity_stream(
+    app_handle: AppHandle,
+    cursor: Option<String>,
+    limit: u32,
+    channel: Channel<Vec<SavedActivity>>,
+) -> Result<Option<String>, SavedActivityError> {
+    let activity_storage = activity_storage(&app_handle).await?;
+    let storage: &ActivityStorage = &activity_storage;
+
+    let mut batches = storage
+        .stream_activities(&StreamActivitiesQuery {
+            cursor,
+            limit: Some(limit),
+            ..Default::default()
+        })
+        .await?;
+
+    let mut next_cursor = None;
+    while let Some(batch) = batches.next().await {
+        let batch = batch?;
+        // Icons are fetched before the next line is read, which is what
+        // paces the server.
+        let enriched = future::join_all(
+            batch
+                .activities
+                .into_iter()
+                .map(|row| enrich_row(storage, row)),
+        )
+        .await;
+        channel
+            .send(enriched)
+            .map_err(|e| SavedActivityError::Channel(e.to_string()))?;
+        next_cursor = batch.next_cursor;
+    }
+
+    Ok(next_cursor)
+}
+
 async fn enrich_row(storage: &ActivityStorage, row: ActivityWithLatestSession) -> SavedActivity {
     let (accent, icon_base64) = match row.activity.icon_asset_id {
         Some(asset_id) => fetch_icon_assets(storage, asset_id).await,
@@ -212,7 +269,7 @@ async fn fetch_icon_assets(
     storage: &ActivityStorage,
     asset_id: Uuid,
 ) -> (Option<AccentColor>, Option<String>) {
-    match storage.fetch_asset_bytes(asset_id).await {
+    match storage.fetch_asset_bytes(asset_id, Priority::Normal).await {
         Ok(Some((bytes, mime_type))) => {
             let accent = decode_image(&bytes).as_ref().and_then(accent_from_image);
             let icon_base64 = Some(format!(
@@ -0,0 +1,164 @@
+use chrono::Utc;
+use euro_focus::{FocusDetails, FocusError, FocusSession, FocusTracker};
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Manager};
+use thiserror::Error;
+use uuid::Uuid;
+
+/// Most sessions [`focus_list`] returns at once.
+const MAX_SESSIONS_PAGE: u32 = 100;
+
+#[derive(Debug, Error, Serialize, Type)]
+#[serde(tag = "type", content = "data")]
+pub enum FocusCommandError {
+    #[error("focus sessions unavailable")]
+    Unavailable,
+    #[error("focus session {0} not found")]
+    NotFound(String),
+    /// The request doesn't fit the session's state, e.g. pausing when
+    /// nothing runs or starting while a session is in progress.
+    #[error("{0}")]
+    Conflict(String),
+    #[error("{0}")]
+    Invalid(String),
+    #[error("{0}")]
+    Other(String),
+}
+
+impl From<FocusError> for FocusCommandError {
+    fn from(err: FocusError) -> Self {
+        match err {
+            FocusError::NotFound(id) => Self::NotFound(id.to_string()),
+            FocusError::AlreadyActive | FocusError::NotRunning | FocusError::NotPaused => {
+                Self::Conflict(err.to_string())
+            }
+            FocusError::Invalid(message) => Self::Invalid(message),
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+fn tracker(app_handle: &AppHandle) -> Result<FocusTracker, FocusCommandError> {
+    app_handle
+        .try_state::<FocusTracker>()
+        .map(|state| state.inner().clone())
+        .ok_or(FocusCommandError::Unavailable)
+}
+
+/// Start a focus session. `distractions` are the activity names counted
+/// as off-topic; the built-in list of video and social sites when `None`.
+#[tauri::command]
+#[specta::specta]
+pub async fn focus_start(
+    app_handle: AppHandle,
+    goal: String,
+    planned_minutes: u32,
+    distractions: Option<Vec<String>>,
+) -> Result<FocusSession, FocusCommandError> {
+    Ok(tracker(&app_handle)?
+        .start(
+            &goal,
+            planned_minutes.saturating_mul(60),
+            distractions,
+            Utc::now(),
+        )
+        .await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn focus_pause(app_handle: AppHandle) -> Result<FocusSession, FocusCommandError> {
+    Ok(tracker(&app_handle)?.pause(Utc::now()).await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn focus_resume(app_handle: AppHandle) -> Result<FocusSession, FocusCommandError> {
+    Ok(tracker(&app_handle)?.resume(Utc::now()).await?)
+}
+
+/// End the session in progress early as completed. Its recap is written
+/// in the background and announced with a `FocusNotification`.
+#[tauri::command]
+#[specta::specta]
+pub async fn focus_stop(app_handle: AppHandle) -> Result<FocusSession, FocusCommandError> {
+    let tracker = tracker(&app_handle)?;
+    let session = tracker.stop(Utc::now()).await?;
+    crate::focus::spawn_recap(app_handle, tracker, session.id);
+    Ok(session)
+}
+
+/// End the session in progress as cancelled, without a recap.
+#[tauri::command]
+#[specta::specta]
+pub async fn focus_cancel(app_handle: AppHandle) -> Result<FocusSession, FocusCommandError> {
+    Ok(tracker(&app_handle)?.cancel(Utc::now()).await?)
+}
+
+/// The session running or paused, with its activities and metrics so far.
+#[tauri::command]
+#[specta::specta]
+pub async fn focus_active(
+    app_handle: AppHandle,
+) -> Result<Option<FocusDetails>, FocusCommandError> {
+    let tracker = tracker(&app_handle)?;
+    let Some(session) = tracker.active().await else {
+        return Ok(None);
+    };
+    Ok(Some(tracker.details(session.id, Utc::now()).await?))
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn focus_get(app_handle: AppHandle, id: Uuid) -> Result<FocusDetails, FocusCommandError> {
+    Ok(tracker(&app_handle)?.details(id, Utc::now()).await?)
+}
+
+/// Sessions newest first.
+#[tauri::command]
+#[specta::specta]
+pub async fn focus_list(
+    app_handle: AppHandle,
+    limit: u32,
+    offset: u32,
+) -> Result<Vec<FocusSession>, FocusCommandError> {
+    Ok(tracker(&app_handle)?
+        .list(limit.clamp(1, MAX_SESSIONS_PAGE), offset)
+        .await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn focus_update_goal(
+    app_handle: AppHandle,
+    id: Uuid,
+    goal: String,
+) -> Result<FocusSession, FocusCommandError> {
+    Ok(tracker(&app_handle)?.update_goal(id, &goal).await?)
+}
+
+/// Delete a session and its activities, ending it if it is in progress.
+#[tauri::command]
+#[specta::specta]
+pub async fn focus_delete(app_handle: AppHandle, id: Uuid) -> Result<(), FocusCommandError> {
+    Ok(tracker(&app_handle)?.delete(id).await?)
+}
+
+/// Write an ended session's recap again and wait for it.
+#[tauri::command]
+#[specta::specta]
+pub async fn focus_generate_recap(
+    app_handle: AppHandle,
+    id: Uuid,
+) -> Result<FocusSession, FocusCommandError> {
+    let tracker = tracker(&app_handle)?;
+    if tracker.get(id).await?.state.is_open() {
+        return Err(FocusCommandError::Conflict(
+            "End the session before writing its recap".to_string(),
+        ));
+    }
+    crate::focus::write_recap(&app_handle, &tracker, id)
+        .await
+        .map_err(FocusCommandError::Other)
+}
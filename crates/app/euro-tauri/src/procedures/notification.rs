@@ -0,0 +1,24 @@
+use euro_notification::NotificationUpdate;
+use notification_core::Notification;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri_specta::Event;
+
+/// Server push relayed from the notification socket. The frontend
+/// refreshes whatever the event invalidates; on `resync` it refreshes
+/// everything a notification could have touched.
+#[derive(Clone, Debug, Serialize, Deserialize, Type, Event)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerNotification {
+    Notification { notification: Notification },
+    Resync,
+}
+
+impl From<NotificationUpdate> for ServerNotification {
+    fn from(update: NotificationUpdate) -> Self {
+        match update {
+            NotificationUpdate::Notification(notification) => Self::Notification { notification },
+            NotificationUpdate::Resync => Self::Resync,
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use std::path::PathBuf;
+
+use euro_plugin::{Capability, PluginError, PluginInfo};
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Manager};
+use thiserror::Error;
+
+use crate::plugins::SharedPluginRegistry;
+
+#[derive(Debug, Error, Serialize, Type)]
+#[serde(tag = "type", content = "data")]
+pub enum PluginCommandError {
+    #[error("plugins unavailable")]
+    Unavailable,
+    #[error("invalid plugin: {0}")]
+    InvalidManifest(String),
+    #[error("plugin {0} is not installed")]
+    NotInstalled(String),
+    #[error("plugin {0} is already installed")]
+    AlreadyInstalled(String),
+    /// Updates only go forward; `data` is the installed version.
+    #[error("version is not newer than the installed {0}")]
+    NotNewer(String),
+    /// The approval didn't cover these. Ask again with them listed.
+    #[error("capabilities not granted: {0:?}")]
+    CapabilitiesNotGranted(Vec<Capability>),
+    /// There's no OS sandbox for this executable plugin. Ask again, saying
+    /// it will run unsandboxed, and pass `allowUnsandboxed`.
+    #[error("no sandbox is available for this plugin")]
+    SandboxUnavailable,
+    #[error("{0}")]
+    Other(String),
+}
+
+impl From<PluginError> for PluginCommandError {
+    fn from(err: PluginError) -> Self {
+        match err {
+            PluginError::InvalidManifest(reason) => Self::InvalidManifest(reason),
+            PluginError::NotInstalled(id) => Self::NotInstalled(id),
+            PluginError::AlreadyInstalled(id) => Self::AlreadyInstalled(id),
+            PluginError::NotNewer { installed, .. } => Self::NotNewer(installed.to_string()),
+            PluginError::CapabilitiesNotGranted(missing) => Self::CapabilitiesNotGranted(missing),
+            PluginError::SandboxUnavailable => Self::SandboxUnavailable,
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+fn registry(app_handle: &AppHandle) -> Result<SharedPluginRegistry, PluginCommandError> {
+    app_handle
+        .try_state::<SharedPluginRegistry>()
+        .map(|state| state.inner().clone())
+        .ok_or(PluginCommandError::Unavailable)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn plugin_list(app_handle: AppHandle) -> Result<Vec<PluginInfo>, PluginCommandError> {
+    Ok(registry(&app_handle)?.list().await)
+}
+
+/// Install the plugin in the directory at `path`. It starts out
+/// disabled; enable it with [`plugin_enable`] once the user has approved
+/// its capabilities.
+#[tauri::command]
+#[specta::specta]
+pub async fn plugin_install(
+    app_handle: AppHandle,
+    path: String,
+) -> Result<PluginInfo, PluginCommandError> {
+    Ok(registry(&app_handle)?.install(&PathBuf::from(path)).await?)
+}
+
+/// Replace an installed plugin with the newer version in the directory
+/// at `path`. Comes back disabled if the new version asks for more than
+/// the user granted.
+#[tauri::command]
+#[specta::specta]
+pub async fn plugin_update(
+    app_handle: AppHandle,
+    path: String,
+) -> Result<PluginInfo, PluginCommandError> {
+    Ok(registry(&app_handle)?.update(&PathBuf::from(path)).await?)
+}
+
+/// Enable a plugin with the capabilities the user approved in the
+/// permission prompt. `allow_unsandboxed` records that the user agreed
+/// to run an executable plugin without an OS sandbox; without it such a
+/// plugin is refused where there's no sandbox.
+#[tauri::command]
+#[specta::specta]
+pub async fn plugin_enable(
+    app_handle: AppHandle,
+    id: String,
+    granted: Vec<Capability>,
+    allow_unsandboxed: bool,
+) -> Result<PluginInfo, PluginCommandError> {
+    Ok(registry(&app_handle)?
+        .enable(&id, granted.into_iter().collect(), allow_unsandboxed)
+        .await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn plugin_disable(
+    app_handle: AppHandle,
+    id: String,
+) -> Result<PluginInfo, PluginCommandError> {
+    Ok(registry(&app_handle)?.disable(&id).await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn plugin_uninstall(app_handle: AppHandle, id: String) -> Result<(), PluginCommandError> {
+    Ok(registry(&app_handle)?.uninstall(&id).await?)
+}
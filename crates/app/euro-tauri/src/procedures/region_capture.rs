@@ -0,0 +1,386 @@
+use euro_timeline::TimelineManager;
+use euro_vision::annotate::{Annotation, Point, Shape};
+use euro_vision::capture::{CaptureError, CapturedImage, downscale_to_transport_size};
+use euro_vision::region::Selection;
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use specta::Type;
+use tauri::{AppHandle, Manager};
+use tauri_specta::Event;
+use thiserror::Error;
+use thread_core::ContextChip;
+use uuid::Uuid;
+
+use crate::region_capture::{
+    self, PendingCapture, RegionCaptureState, model_readable, storage_encoding,
+};
+use crate::shared_types::SharedSettingsState;
+use crate::window::show_and_focus_main;
+
+/// Emitted when the user has selected a region, so the chat view can show
+/// it alongside the question box.
+#[derive(Clone, Debug, Serialize, Deserialize, Type, Event)]
+#[serde(rename_all = "camelCase")]
+pub struct RegionCaptured {
+    pub chip: ContextChip,
+}
+
+/// The rectangle the overlay reports, as fractions of its width and
+/// height. `width` and `height` are negative for a drag up or left.
+#[derive(Debug, Clone, Copy, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct RegionSelection {
+    pub left: f64,
+    pub top: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl From<RegionSelection> for Selection {
+    fn from(selection: RegionSelection) -> Self {
+        Self {
+            left: selection.left,
+            top: selection.top,
+            width: selection.width,
+            height: selection.height,
+        }
+    }
+}
+
+/// A point on the overlay, as fractions of its width and height.
+#[derive(Debug, Clone, Copy, Deserialize, Type)]
+pub struct RegionPoint {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl From<RegionPoint> for Point {
+    fn from(point: RegionPoint) -> Self {
+        Self {
+            x: point.x,
+            y: point.y,
+        }
+    }
+}
+
+/// Markup the user drew on the overlay, drawn onto the capture in its
+/// default colour.
+#[derive(Debug, Clone, Deserialize, Type)]
+#[serde(tag = "type")]
+pub enum RegionAnnotation {
+    Box { from: RegionPoint, to: RegionPoint },
+    Highlight { from: RegionPoint, to: RegionPoint },
+    Arrow { from: RegionPoint, to: RegionPoint },
+    Label { at: RegionPoint, text: String },
+}
+
+impl From<RegionAnnotation> for Annotation {
+    fn from(annotation: RegionAnnotation) -> Self {
+        Annotation::new(match annotation {
+            RegionAnnotation::Box { from, to } => Shape::Box {
+                from: from.into(),
+                to: to.into(),
+            },
+            RegionAnnotation::Highlight { from, to } => Shape::Highlight {
+                from: from.into(),
+                to: to.into(),
+            },
+            RegionAnnotation::Arrow { from, to } => Shape::Arrow {
+                from: from.into(),
+                to: to.into(),
+            },
+            RegionAnnotation::Label { at, text } => Shape::Label {
+                at: at.into(),
+                text,
+            },
+        })
+    }
+}
+
+#[derive(Debug, Error, Serialize, Type)]
+#[serde(tag = "type", content = "data")]
+pub enum RegionCaptureError {
+    #[error("no display to capture")]
+    NoDisplay,
+    /// The overlay isn't open, or was already answered.
+    #[error("not selecting a region")]
+    NotSelecting,
+    /// The rectangle is too small to be deliberate. The overlay stays open.
+    #[error("selection too small")]
+    EmptySelection,
+    #[error("{0}")]
+    Capture(String),
+}
+
+impl From<CaptureError> for RegionCaptureError {
+    fn from(err: CaptureError) -> Self {
+        Self::Capture(err.to_string())
+    }
+}
+
+impl From<tauri::Error> for RegionCaptureError {
+    fn from(err: tauri::Error) -> Self {
+        Self::Capture(err.to_string())
+    }
+}
+
+/// Open the selection overlay, as the capture shortcut does.
+#[tauri::command]
+#[specta::specta]
+pub async fn region_capture_start(app_handle: AppHandle) -> Result<(), RegionCaptureError> {
+    region_capture::begin(&app_handle).await
+}
+
+/// The frozen screen, as a data URL, for the overlay to draw.
+#[tauri::command]
+#[specta::specta]
+pub async fn region_capture_frame(app_handle: AppHandle) -> Result<String, RegionCaptureError> {
+    app_handle
+        .state::<RegionCaptureState>()
+        .selecting
+        .lock()
+        .as_ref()
+        .map(|selecting| selecting.preview.clone())
+        .ok_or(RegionCaptureError::NotSelecting)
+}
+
+/// Attach the selected region to the next question: close the overlay and
+/// bring the main window forward with the capture in the question box.
+/// `annotations` are drawn onto the capture first, placed like the
+/// selection. The capture is saved as an asset, and its text read, in the
+/// background.
+#[tauri::command]
+#[specta::specta]
+pub async fn region_capture_select(
+    app_handle: AppHandle,
+    selection: RegionSelection,
+    annotations: Vec<RegionAnnotation>,
+) -> Result<(), RegionCaptureError> {
+    let state = app_handle.state::<RegionCaptureState>();
+    let annotated = !annotations.is_empty();
+    let annotations: Vec<Annotation> = annotations.into_iter().map(Into::into).collect();
+    let pixels = {
+        let selecting = state.selecting.lock();
+        let selecting = selecting.as_ref().ok_or(RegionCaptureError::NotSelecting)?;
+        selecting
+            .frame
+            .crop_annotated(selection.into(), &annotations)
+            .ok_or(RegionCaptureError::EmptySelection)?
+    };
+    region_capture::end_selection(&app_handle);
+
+    #[cfg(feature = "ocr")]
+    let for_ocr = pixels.clone();
+    let (image, scaled) = tokio::task::spawn_blocking(move || {
+        let scaled = downscale_to_transport_size(pixels);
+        CapturedImage::from_rgba(scaled.clone()).map(|image| (image, scaled))
+    })
+    .await
+    .map_err(|e| RegionCaptureError::Capture(e.to_string()))?
+    .map_err(|e| RegionCaptureError::Capture(e.to_string()))?;
+    let capture = PendingCapture {
+        id: Uuid::new_v4(),
+        image,
+        text: None,
+        annotated,
+        asset: None,
+    };
+    let chip = capture.chip();
+    tauri::async_runtime::spawn(save_capture(
+        app_handle.clone(),
+        capture.id,
+        scaled,
+        annotated,
+    ));
+    #[cfg(feature = "ocr")]
+    {
+        // The capture is of whatever was in focus when the shortcut fired.
+        let process_name = match app_handle.try_state::<tokio::sync::Mutex<TimelineManager>>() {
+            Some(timeline) => timeline.lock().await.current_process_name().await,
+            None => None,
+        };
+        tauri::async_runtime::spawn(recognize_text(
+            app_handle.clone(),
+            capture.id,
+            for_ocr,
+            process_name,
+        ));
+    }
+    *state.pending.lock() = Some(capture);
+
+    if let Err(e) = show_and_focus_main(&app_handle) {
+        tracing::warn!("Failed to show main window for region capture: {e}");
+    }
+    if let Err(e) = (RegionCaptured { chip }).emit(&app_handle) {
+        tracing::warn!("Failed to emit region capture: {e}");
+    }
+    Ok(())
+}
+
+/// Close the overlay without capturing.
+#[tauri::command]
+#[specta::specta]
+pub async fn region_capture_cancel(app_handle: AppHandle) {
+    region_capture::end_selection(&app_handle);
+}
+
+/// Detach the capture from the question box before it is sent.
+#[tauri::command]
+#[specta::specta]
+pub async fn region_capture_discard(app_handle: AppHandle) {
+    app_handle
+        .state::<RegionCaptureState>()
+        .pending
+        .lock()
+        .take();
+}
+
+/// Keep the capture with the user's other assets. Best effort: skipped
+/// when it looks like the last capture stored, refused in local-only mode
+/// with a remote backend, and a failure only costs the record, not the
+/// question. Once saved, a capture still waiting for its turn is sent as
+/// a reference to the asset rather than as inline base64.
+async fn save_capture(app_handle: AppHandle, capture_id: Uuid, frame: RgbaImage, annotated: bool) {
+    let Some(timeline) = app_handle.try_state::<tokio::sync::Mutex<TimelineManager>>() else {
+        return;
+    };
+    let storage = timeline.lock().await.activity_storage.clone();
+    let encoding = storage_encoding(
+        &app_handle
+            .state::<SharedSettingsState>()
+            .lock()
+            .await
+            .local
+            .region_capture,
+    );
+    let metadata = json!({
+        "source": "region_capture",
+        "capture_id": capture_id,
+        "width": frame.width(),
+        "height": frame.height(),
+        "annotated": annotated,
+    });
+    match storage
+        .save_screenshot(
+            &format!("screenshot-{capture_id}"),
+            frame,
+            encoding,
+            metadata,
+        )
+        .await
+    {
+        Ok(Some(asset)) => {
+            tracing::debug!(asset_id = %asset.id, "Saved region capture");
+            if model_readable(&asset.mime_type)
+                && let Some(pending) = app_handle
+                    .state::<RegionCaptureState>()
+                    .pending
+                    .lock()
+                    .as_mut()
+                    .filter(|pending| pending.id == capture_id)
+            {
+                pending.asset = Some((asset.id, asset.mime_type));
+            }
+        }
+        Ok(None) => tracing::debug!("Region capture matches the last one saved, not saved again"),
+        Err(e) => tracing::info!("Region capture not saved: {e}"),
+    }
+}
+
+/// Read the text in the capture with the models from settings, and add it
+/// to the capture if it is still waiting for a turn. The text goes through
+/// the moderator's OCR pass first, with the sensitivity of `process_name`,
+/// and is dropped if that refuses it.
+#[cfg(feature = "ocr")]
+async fn recognize_text(
+    app_handle: AppHandle,
+    capture_id: Uuid,
+    pixels: image::RgbaImage,
+    process_name: Option<String>,
+) {
+    use std::sync::Arc;
+
+    use euro_vision::ocr::TextRecognizer;
+    use euro_vision::ocr_queue::Priority;
+
+    let Some(models_dir) = app_handle
+        .state::<SharedSettingsState>()
+        .lock()
+        .await
+        .local
+        .region_capture
+        .ocr_models_dir
+        .clone()
+    else {
+        return;
+    };
+    let state = app_handle.state::<RegionCaptureState>();
+    let cached = state
+        .recognizer
+        .lock()
+        .clone()
+        .filter(|(dir, _)| *dir == models_dir)
+        .map(|(_, recognizer)| recognizer);
+    let dir = models_dir.clone();
+    // The user is about to ask about this capture, so it goes ahead of
+    // any background recognition.
+    let result = state
+        .ocr_queue
+        .submit(Priority::User, move || {
+            let recognizer = match cached {
+                Some(recognizer) => recognizer,
+                None => Arc::new(TextRecognizer::load(std::path::Path::new(&dir))?),
+            };
+            let text = recognizer.recognize(&pixels)?;
+            anyhow::Ok((recognizer, text))
+        })
+        .output()
+        .await;
+
+    match result {
+        Some(Ok((recognizer, text))) => {
+            *state.recognizer.lock() = Some((models_dir, recognizer));
+            let text = match redact_ocr_text(&app_handle, &text, process_name.as_deref()) {
+                Ok(text) => text,
+                Err(e) => {
+                    tracing::debug!("Region capture text withheld: {e}");
+                    return;
+                }
+            };
+            let mut pending = state.pending.lock();
+            if let Some(capture) = pending.as_mut().filter(|c| c.id == capture_id)
+                && !text.is_empty()
+            {
+                capture.text = Some(text);
+            }
+        }
+        Some(Err(e)) => tracing::warn!("Region capture OCR failed: {e:#}"),
+        None => tracing::warn!("Region capture OCR job panicked"),
+    }
+}
+
+/// Run `text` through the OCR pass of the moderator in effect. Without
+/// one, the built-in detectors still apply.
+#[cfg(feature = "ocr")]
+fn redact_ocr_text(
+    app_handle: &AppHandle,
+    text: &str,
+    process_name: Option<&str>,
+) -> Result<String, euro_activity::moderation::ModerationError> {
+    use std::sync::Arc;
+
+    use crate::moderation::SharedModerator;
+
+    let moderator = match app_handle.try_state::<SharedModerator>() {
+        Some(shared) => shared
+            .read()
+            .map(|m| m.clone())
+            .unwrap_or_else(|e| e.into_inner().clone()),
+        None => Arc::new(
+            euro_activity::Moderator::new(euro_activity::ModerationConfig::default())
+                .expect("default moderation config has no custom patterns"),
+        ),
+    };
+    moderator.redact_ocr(text, process_name)
+}
@@ -0,0 +1,97 @@
+use chrono::Utc;
+use euro_screen_time::{
+    BudgetStatus, ScreenTimeBudget, ScreenTimeError, ScreenTimeReport, ScreenTimeTracker,
+};
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Manager};
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Debug, Error, Serialize, Type)]
+#[serde(tag = "type", content = "data")]
+pub enum ScreenTimeCommandError {
+    #[error("screen time unavailable")]
+    Unavailable,
+    #[error("screen-time budget {0} not found")]
+    NotFound(String),
+    #[error("{0}")]
+    Invalid(String),
+    #[error("{0}")]
+    Other(String),
+}
+
+impl From<ScreenTimeError> for ScreenTimeCommandError {
+    fn from(err: ScreenTimeError) -> Self {
+        match err {
+            ScreenTimeError::NotFound(id) => Self::NotFound(id.to_string()),
+            ScreenTimeError::Invalid(message) => Self::Invalid(message),
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+fn tracker(app_handle: &AppHandle) -> Result<ScreenTimeTracker, ScreenTimeCommandError> {
+    app_handle
+        .try_state::<ScreenTimeTracker>()
+        .map(|state| state.inner().clone())
+        .ok_or(ScreenTimeCommandError::Unavailable)
+}
+
+/// Every budget with the time spent against it today, oldest first.
+#[tauri::command]
+#[specta::specta]
+pub async fn screen_time_status(
+    app_handle: AppHandle,
+) -> Result<Vec<BudgetStatus>, ScreenTimeCommandError> {
+    Ok(tracker(&app_handle)?.status(Utc::now()).await?)
+}
+
+/// Budget `daily_minutes` a day for the app or site named `target`.
+#[tauri::command]
+#[specta::specta]
+pub async fn screen_time_create_budget(
+    app_handle: AppHandle,
+    target: String,
+    daily_minutes: u32,
+) -> Result<ScreenTimeBudget, ScreenTimeCommandError> {
+    Ok(tracker(&app_handle)?
+        .create_budget(&target, daily_minutes.saturating_mul(60), Utc::now())
+        .await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn screen_time_update_budget(
+    app_handle: AppHandle,
+    id: Uuid,
+    target: String,
+    daily_minutes: u32,
+    enabled: bool,
+) -> Result<ScreenTimeBudget, ScreenTimeCommandError> {
+    Ok(tracker(&app_handle)?
+        .update_budget(id, &target, daily_minutes.saturating_mul(60), enabled)
+        .await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn screen_time_delete_budget(
+    app_handle: AppHandle,
+    id: Uuid,
+) -> Result<(), ScreenTimeCommandError> {
+    Ok(tracker(&app_handle)?.delete_budget(id).await?)
+}
+
+/// The report on this week so far for `weeks_ago` 0, last week for 1, and
+/// so on.
+#[tauri::command]
+#[specta::specta]
+pub async fn screen_time_report(
+    app_handle: AppHandle,
+    weeks_ago: u32,
+) -> Result<ScreenTimeReport, ScreenTimeCommandError> {
+    Ok(tracker(&app_handle)?
+        .report_weeks_ago(weeks_ago, Utc::now())
+        .await?)
+}
@@ -0,0 +1,65 @@
+use euro_script::{ScriptError, ScriptRun, ScriptScheduler};
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Manager};
+use thiserror::Error;
+
+/// Most runs [`script_list_runs`] returns at once.
+const MAX_RUNS_PAGE: u32 = 200;
+
+#[derive(Debug, Error, Serialize, Type)]
+#[serde(tag = "type", content = "data")]
+pub enum ScriptCommandError {
+    #[error("scripts unavailable")]
+    Unavailable,
+    #[error("script {0} not found")]
+    NotFound(String),
+    #[error("script {0} is already running")]
+    AlreadyRunning(String),
+    #[error("{0}")]
+    Other(String),
+}
+
+impl From<ScriptError> for ScriptCommandError {
+    fn from(err: ScriptError) -> Self {
+        match err {
+            ScriptError::NotFound(id) => Self::NotFound(id),
+            ScriptError::AlreadyRunning(id) => Self::AlreadyRunning(id),
+            ScriptError::Stopped => Self::Unavailable,
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+fn scheduler(app_handle: &AppHandle) -> Result<ScriptScheduler, ScriptCommandError> {
+    app_handle
+        .try_state::<ScriptScheduler>()
+        .map(|state| state.inner().clone())
+        .ok_or(ScriptCommandError::Unavailable)
+}
+
+/// Run a saved script now, enabled or not, and wait for it to finish.
+/// The outcome is in the returned run, which is audited like any other.
+#[tauri::command]
+#[specta::specta]
+pub async fn script_run_now(
+    app_handle: AppHandle,
+    id: String,
+) -> Result<ScriptRun, ScriptCommandError> {
+    Ok(scheduler(&app_handle)?.run_now(&id).await?)
+}
+
+/// The newest runs from the audit log, of one script or of all, newest
+/// first.
+#[tauri::command]
+#[specta::specta]
+pub async fn script_list_runs(
+    app_handle: AppHandle,
+    script_id: Option<String>,
+    limit: u32,
+) -> Result<Vec<ScriptRun>, ScriptCommandError> {
+    let limit = limit.clamp(1, MAX_RUNS_PAGE) as usize;
+    Ok(scheduler(&app_handle)?
+        .runs(script_id.as_deref(), limit)
+        .await?)
+}
@@ -1,17 +1,24 @@
 use std::sync::Arc;
 
+use euro_personal_db::PersonalDb;
+use euro_script::{ScriptRunner, ScriptScheduler};
 use euro_settings::{
-    APISettings, DesktopSettings, GeneralSettings, SharedSettings, SyncEngine, TelemetryConsent,
-    TelemetryLocal,
+    APISettings, DesktopSettings, FileAccessSettings, GeneralSettings, MAX_SCRIPT_TIMEOUT_SECS,
+    ModerationSettings, RegionCaptureSettings, ScriptSettings, ScriptTrigger, SettingScope,
+    SettingsSchema, SharedSettings, ShellSettings, SyncEngine, TelemetryConsent, TelemetryLocal,
+    TransferSettings, UserScript, VoiceSettings,
 };
+use euro_transfer::TransferManager;
 use serde::Serialize;
 use specta::Type;
 use tauri::{AppHandle, Manager};
 use tauri_specta::Event;
 use thiserror::Error;
 
+use crate::moderation::{SharedModerator, moderator_from_settings};
 use crate::procedures::system::ConsentGate;
-use crate::shared_types::{SharedEndpointManager, SharedSettingsState};
+use crate::shared_types::{SharedEndpointManager, SharedHttpClient, SharedSettingsState};
+use crate::voice::VoiceState;
 use euro_telemetry::Controller as TelemetryController;
 
 /// Typed error surface for the `settings_*` IPC commands. Externally
@@ -27,6 +34,8 @@ pub enum SettingsError {
     Persistence(String),
     #[error("endpoint switch: {0}")]
     EndpointSwitch(String),
+    #[error("invalid value: {0}")]
+    InvalidValue(String),
 }
 
 // --- General (local) ------------------------------------------------------
@@ -51,6 +60,13 @@ pub async fn settings_set_general(
     settings
         .save_local_to_default_path()
         .map_err(|e| SettingsError::Persistence(e.to_string()))?;
+    app_handle
+        .state::<SharedEndpointManager>()
+        .set_local_only(settings.local.general.local_only);
+    // A cached provider recognizer may no longer be allowed.
+    if let Some(voice) = app_handle.try_state::<VoiceState>() {
+        voice.transcriber.lock().take();
+    }
 
     Ok(settings.local.general.clone())
 }
@@ -90,6 +106,338 @@ pub async fn settings_set_api(
     Ok(settings.local.api.clone())
 }
 
+// --- File access (local) -------------------------------------------------
+
+#[tauri::command]
+#[specta::specta]
+pub async fn settings_get_file_access(app_handle: AppHandle) -> FileAccessSettings {
+    let state = app_handle.state::<SharedSettingsState>();
+    state.lock().await.local.file_access.clone()
+}
+
+/// Replace the folders the assistant's file tools may read. Takes effect
+/// on the next chat turn; the tool backend reads the list fresh each
+/// time it advertises tools.
+#[tauri::command]
+#[specta::specta]
+pub async fn settings_set_file_access(
+    app_handle: AppHandle,
+    file_access: FileAccessSettings,
+) -> Result<FileAccessSettings, SettingsError> {
+    let state = app_handle.state::<SharedSettingsState>();
+    let mut settings = state.lock().await;
+
+    settings.local.file_access = file_access;
+    settings
+        .save_local_to_default_path()
+        .map_err(|e| SettingsError::Persistence(e.to_string()))?;
+
+    Ok(settings.local.file_access.clone())
+}
+
+// --- Shell (local) -------------------------------------------------------
+
+#[tauri::command]
+#[specta::specta]
+pub async fn settings_get_shell(app_handle: AppHandle) -> ShellSettings {
+    let state = app_handle.state::<SharedSettingsState>();
+    state.lock().await.local.shell.clone()
+}
+
+/// Replace the workspace and program allowlist for the assistant's
+/// `run_command` tool. Program names are trimmed and deduplicated; a
+/// path, or a program the shell policy always refuses, is rejected so
+/// the list never shows something that can't run.
+#[tauri::command]
+#[specta::specta]
+pub async fn settings_set_shell(
+    app_handle: AppHandle,
+    shell: ShellSettings,
+) -> Result<ShellSettings, SettingsError> {
+    let shell = validate_shell(shell)?;
+    let state = app_handle.state::<SharedSettingsState>();
+    let mut settings = state.lock().await;
+
+    settings.local.shell = shell;
+    settings
+        .save_local_to_default_path()
+        .map_err(|e| SettingsError::Persistence(e.to_string()))?;
+
+    Ok(settings.local.shell.clone())
+}
+
+fn validate_shell(shell: ShellSettings) -> Result<ShellSettings, SettingsError> {
+    if let Some(workspace) = &shell.workspace {
+        if !workspace.is_dir() {
+            return Err(SettingsError::InvalidValue(format!(
+                "{} is not a folder",
+                workspace.display()
+            )));
+        }
+    }
+
+    let mut programs: Vec<String> = Vec::new();
+    for program in &shell.allowed_programs {
+        let program = program.trim();
+        if program.is_empty() || programs.iter().any(|p| p == program) {
+            continue;
+        }
+        if program.contains(['/', '\\']) {
+            return Err(SettingsError::InvalidValue(format!(
+                "{program}: name the program, not its path"
+            )));
+        }
+        programs.push(program.to_string());
+    }
+
+    let policy = agent_chain::shell::ShellPolicy::builder()
+        .root(std::env::temp_dir())
+        .allowed(programs.clone())
+        .build();
+    if let Some(refused) = programs.iter().find(|p| !policy.allows(p)) {
+        return Err(SettingsError::InvalidValue(format!(
+            "{refused} can start other programs and is never allowed"
+        )));
+    }
+
+    Ok(ShellSettings {
+        workspace: shell.workspace,
+        allowed_programs: programs,
+    })
+}
+
+// --- Moderation (local) --------------------------------------------------
+
+#[tauri::command]
+#[specta::specta]
+pub async fn settings_get_moderation(app_handle: AppHandle) -> ModerationSettings {
+    let state = app_handle.state::<SharedSettingsState>();
+    state.lock().await.local.moderation.clone()
+}
+
+/// Replace the secret-scanning rules for outgoing context. Takes effect
+/// on the next chat turn. Rejects custom patterns that aren't valid
+/// regular expressions.
+#[tauri::command]
+#[specta::specta]
+pub async fn settings_set_moderation(
+    app_handle: AppHandle,
+    moderation: ModerationSettings,
+) -> Result<ModerationSettings, SettingsError> {
+    let http = app_handle.state::<SharedHttpClient>();
+    let moderator = moderator_from_settings(&moderation, &http)
+        .map_err(|e| SettingsError::InvalidValue(e.to_string()))?;
+
+    let state = app_handle.state::<SharedSettingsState>();
+    let mut settings = state.lock().await;
+    settings.local.moderation = moderation;
+    settings
+        .save_local_to_default_path()
+        .map_err(|e| SettingsError::Persistence(e.to_string()))?;
+
+    if let Some(shared) = app_handle.try_state::<SharedModerator>()
+        && let Ok(mut current) = shared.write()
+    {
+        *current = Arc::new(moderator);
+    }
+
+    Ok(settings.local.moderation.clone())
+}
+
+// --- Voice input (local) -------------------------------------------------
+
+#[tauri::command]
+#[specta::specta]
+pub async fn settings_get_voice(app_handle: AppHandle) -> VoiceSettings {
+    let state = app_handle.state::<SharedSettingsState>();
+    state.lock().await.local.voice.clone()
+}
+
+/// Replace the push-to-talk settings. Takes effect from the next
+/// utterance.
+#[tauri::command]
+#[specta::specta]
+pub async fn settings_set_voice(
+    app_handle: AppHandle,
+    voice: VoiceSettings,
+) -> Result<VoiceSettings, SettingsError> {
+    let state = app_handle.state::<SharedSettingsState>();
+    let mut settings = state.lock().await;
+    settings.local.voice = voice;
+    settings
+        .save_local_to_default_path()
+        .map_err(|e| SettingsError::Persistence(e.to_string()))?;
+
+    if let Some(voice) = app_handle.try_state::<VoiceState>() {
+        voice.transcriber.lock().take();
+    }
+
+    Ok(settings.local.voice.clone())
+}
+
+// --- Region capture (local) ----------------------------------------------
+
+#[tauri::command]
+#[specta::specta]
+pub async fn settings_get_region_capture(app_handle: AppHandle) -> RegionCaptureSettings {
+    let state = app_handle.state::<SharedSettingsState>();
+    state.lock().await.local.region_capture.clone()
+}
+
+/// Replace the region-capture settings and re-register the shortcut.
+#[tauri::command]
+#[specta::specta]
+pub async fn settings_set_region_capture(
+    app_handle: AppHandle,
+    region_capture: RegionCaptureSettings,
+) -> Result<RegionCaptureSettings, SettingsError> {
+    let state = app_handle.state::<SharedSettingsState>();
+    let mut settings = state.lock().await;
+    settings.local.region_capture = region_capture;
+    settings
+        .save_local_to_default_path()
+        .map_err(|e| SettingsError::Persistence(e.to_string()))?;
+
+    crate::region_capture::apply_shortcut(&app_handle, &settings.local.region_capture);
+
+    Ok(settings.local.region_capture.clone())
+}
+
+// --- Transfers (local) ---------------------------------------------------
+
+#[tauri::command]
+#[specta::specta]
+pub async fn settings_get_transfers(app_handle: AppHandle) -> TransferSettings {
+    let state = app_handle.state::<SharedSettingsState>();
+    state.lock().await.local.transfers.clone()
+}
+
+/// Replace the bandwidth caps and sync schedule. Queued transfers are
+/// re-checked right away; ones in flight pick up the new caps from their
+/// next chunk.
+#[tauri::command]
+#[specta::specta]
+pub async fn settings_set_transfers(
+    app_handle: AppHandle,
+    transfers: TransferSettings,
+) -> Result<TransferSettings, SettingsError> {
+    if transfers.night_start_hour > 23 || transfers.night_end_hour > 23 {
+        return Err(SettingsError::InvalidValue(
+            "night hours must be between 0 and 23".into(),
+        ));
+    }
+    let state = app_handle.state::<SharedSettingsState>();
+    let mut settings = state.lock().await;
+    settings.local.transfers = transfers;
+    settings
+        .save_local_to_default_path()
+        .map_err(|e| SettingsError::Persistence(e.to_string()))?;
+
+    if let Some(manager) = app_handle.try_state::<TransferManager>() {
+        manager.set_policy(crate::transfers::policy_from_settings(
+            &settings.local.transfers,
+        ));
+    }
+
+    Ok(settings.local.transfers.clone())
+}
+
+// --- Scripts (local) -----------------------------------------------------
+
+#[tauri::command]
+#[specta::specta]
+pub async fn settings_get_scripts(app_handle: AppHandle) -> ScriptSettings {
+    let state = app_handle.state::<SharedSettingsState>();
+    state.lock().await.local.scripts.clone()
+}
+
+/// Replace the user's scripts and reschedule them. New scripts get an
+/// id; a script that doesn't compile is rejected with the error.
+#[tauri::command]
+#[specta::specta]
+pub async fn settings_set_scripts(
+    app_handle: AppHandle,
+    mut scripts: ScriptSettings,
+) -> Result<ScriptSettings, SettingsError> {
+    for script in &mut scripts.scripts {
+        validate_script(script)?;
+        if script.id.is_empty() {
+            script.id = uuid::Uuid::new_v4().to_string();
+        }
+    }
+    let state = app_handle.state::<SharedSettingsState>();
+    let mut settings = state.lock().await;
+    settings.local.scripts = scripts;
+    settings
+        .save_local_to_default_path()
+        .map_err(|e| SettingsError::Persistence(e.to_string()))?;
+
+    if let Some(scheduler) = app_handle.try_state::<ScriptScheduler>() {
+        scheduler.set_scripts(crate::scripts::scripts_from_settings(
+            &settings.local.scripts,
+        ));
+    }
+
+    Ok(settings.local.scripts.clone())
+}
+
+fn validate_script(script: &UserScript) -> Result<(), SettingsError> {
+    let invalid = |reason: String| Err(SettingsError::InvalidValue(reason));
+    let name = script.name.trim();
+    if name.is_empty() {
+        return invalid("script name must not be empty".into());
+    }
+    if !(1..=MAX_SCRIPT_TIMEOUT_SECS).contains(&script.timeout_secs) {
+        return invalid(format!(
+            "{name}: timeout must be between 1 and {MAX_SCRIPT_TIMEOUT_SECS} seconds"
+        ));
+    }
+    match script.trigger {
+        ScriptTrigger::Every { minutes: 0 } => {
+            return invalid(format!("{name}: interval must be at least a minute"));
+        }
+        ScriptTrigger::Daily { hour, minute } if hour > 23 || minute > 59 => {
+            return invalid(format!(
+                "{name}: {hour:02}:{minute:02} is not a time of day"
+            ));
+        }
+        _ => {}
+    }
+    ScriptRunner::check(&script.source)
+        .map_err(|e| SettingsError::InvalidValue(format!("{name}: {e}")))
+}
+
+// --- Personal database (local) -------------------------------------------
+
+/// Size of the personal database before and after
+/// [`settings_compact_database`], in bytes.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct DatabaseCompaction {
+    pub bytes_before: f64,
+    pub bytes_after: f64,
+}
+
+/// Rewrite the personal database without its free space. The periodic
+/// maintenance reclaims most of it already; this is the button on the
+/// settings page for the rest.
+#[tauri::command]
+#[specta::specta]
+pub async fn settings_compact_database(
+    app_handle: AppHandle,
+) -> Result<DatabaseCompaction, SettingsError> {
+    let db = app_handle
+        .try_state::<PersonalDb>()
+        .ok_or_else(|| SettingsError::Persistence("personal database is not open".into()))?;
+    let report = db
+        .compact()
+        .await
+        .map_err(|e| SettingsError::Persistence(e.to_string()))?;
+    Ok(DatabaseCompaction {
+        bytes_before: report.bytes_before as f64,
+        bytes_after: report.bytes_after as f64,
+    })
+}
+
 // --- Shared cloud section -------------------------------------------------
 
 #[tauri::command]
@@ -224,9 +572,9 @@ pub async fn settings_record_telemetry_consent(
 
 // --- Telemetry (local) ----------------------------------------------------
 
-/// Returns the per-install telemetry state — currently just the
-/// anonymous distinct id. The cross-device consent toggles live under
-/// the cloud `desktop` section and are surfaced via
+/// Returns the per-install telemetry state: the anonymous distinct id
+/// and the local analytics kill switch. The cross-device consent toggles
+/// live under the cloud `desktop` section and are surfaced via
 /// [`settings_get_desktop`].
 #[tauri::command]
 #[specta::specta]
@@ -235,6 +583,27 @@ pub async fn settings_get_local_telemetry(app_handle: AppHandle) -> TelemetryLoc
     state.lock().await.local.telemetry.clone()
 }
 
+/// Turn product analytics off (or back on) for this install, overriding
+/// the synced consent. Local-only: another device of the same user keeps
+/// its own choice. The frontend re-fetches the telemetry bootstrap
+/// afterwards to stop or start PostHog.
+#[tauri::command]
+#[specta::specta]
+pub async fn settings_set_analytics_disabled(
+    app_handle: AppHandle,
+    disabled: bool,
+) -> Result<TelemetryLocal, SettingsError> {
+    let state = app_handle.state::<SharedSettingsState>();
+    let mut settings = state.lock().await;
+
+    settings.local.telemetry.analytics_disabled = disabled;
+    settings
+        .save_local_to_default_path()
+        .map_err(|e| SettingsError::Persistence(e.to_string()))?;
+
+    Ok(settings.local.telemetry.clone())
+}
+
 /// Convenience read for the early-boot / pre-auth path where the
 /// frontend needs only the consent toggles. Equivalent to
 /// `settings_get_desktop().telemetry`, but typed so the IPC surface
@@ -245,3 +614,41 @@ pub async fn settings_get_telemetry_consent(app_handle: AppHandle) -> TelemetryC
     let state = app_handle.state::<SharedSettingsState>();
     state.lock().await.cache.settings.desktop.telemetry.clone()
 }
+
+// --- Registry -------------------------------------------------------------
+
+/// The registry of editable settings, for pages that render their form
+/// from it rather than from a hand-written section type.
+#[tauri::command]
+#[specta::specta]
+pub async fn settings_get_schema() -> SettingsSchema {
+    SettingsSchema::current()
+}
+
+/// Write one registry key. The value is validated against the registry
+/// before anything changes; local keys persist to `local.json`, cloud
+/// keys to the cache followed by a push.
+#[tauri::command]
+#[specta::specta]
+pub async fn settings_set_value(
+    app_handle: AppHandle,
+    key: String,
+    value: serde_json::Value,
+) -> Result<(), SettingsError> {
+    let state = app_handle.state::<SharedSettingsState>();
+    let mut settings = state.lock().await;
+
+    let scope = settings
+        .set_value(&key, value)
+        .map_err(|e| SettingsError::InvalidValue(e.to_string()))?;
+    match scope {
+        SettingScope::Local => settings.save_local_to_default_path(),
+        SettingScope::Shared | SettingScope::Desktop => settings.save_cache_to_default_path(),
+    }
+    .map_err(|e| SettingsError::Persistence(e.to_string()))?;
+
+    if scope.is_cloud() {
+        app_handle.state::<SyncEngine>().request_push();
+    }
+    Ok(())
+}
@@ -1,4 +1,3 @@
-use std::path::PathBuf;
 use std::sync::Arc;
 
 use euro_activity::ContextChip;
@@ -31,20 +30,6 @@ pub const OPEN_BROWSER_EXTENSION_SETTINGS_ACTION: &str = "OPEN_BROWSER_EXTENSION
 /// `appKind` value passed to `BridgeWebSocketClient`.
 pub const SAFARI_BRIDGE_APP_KIND: &str = "safari";
 
-#[cfg(target_os = "macos")]
-fn find_outermost_app_bundle() -> Option<PathBuf> {
-    let exe = std::env::current_exe().ok()?;
-    let mut outermost: Option<PathBuf> = None;
-    let mut count = 0u32;
-    for ancestor in exe.ancestors() {
-        if ancestor.extension().is_some_and(|ext| ext == "app") {
-            outermost = Some(ancestor.to_path_buf());
-            count += 1;
-        }
-    }
-    if count >= 2 { outermost } else { None }
-}
-
 /// Typed error surface for the `system_*` IPC commands. Externally tagged
 /// so the JS side gets `{ type: "BackendUnreachable", data: "..." }` and
 /// can branch on `type` without parsing strings. Variants are grouped by
@@ -406,23 +391,18 @@ pub async fn system_check_for_update(
 pub async fn system_install_update(app_handle: AppHandle) -> Result<(), SystemError> {
     tracing::debug!("Installing update...");
 
-    #[cfg(target_os = "macos")]
-    let outer_app: Option<PathBuf> = find_outermost_app_bundle();
-    #[cfg(not(target_os = "macos"))]
-    let outer_app: Option<PathBuf> = None;
-
-    let mut builder = app_handle.updater_builder();
-
-    if let Some(ref outer) = outer_app {
-        let exe_inside_outer = outer.join("Contents").join("MacOS").join("Eurora");
-        tracing::debug!(
-            "Using outer app executable path for updater: {}",
-            exe_inside_outer.display()
-        );
-        builder = builder.executable_path(&exe_inside_outer);
+    // The background updater has usually downloaded and verified it
+    // already.
+    match crate::updater::apply_staged(&app_handle).await {
+        Ok(Some(version)) => {
+            tracing::debug!("Staged update {version} installed, restarting application");
+            crate::updater::restart(&app_handle);
+        }
+        Ok(None) => {}
+        Err(e) => tracing::warn!("Failed to install staged update, downloading it again: {e}"),
     }
 
-    let updater = builder.build().map_err(|e| {
+    let updater = crate::updater::updater(&app_handle).map_err(|e| {
         tracing::error!("Failed to build updater: {e}");
         SystemError::Updater(format!("Failed to build updater: {e}"))
     })?;
@@ -442,8 +422,8 @@ pub async fn system_install_update(app_handle: AppHandle) -> Result<(), SystemEr
         update.version
     );
 
-    update
-        .download_and_install(
+    let bytes = update
+        .download(
             |chunk_length, content_length| {
                 tracing::debug!("Downloaded {} from {:?}", chunk_length, content_length);
             },
@@ -453,28 +433,16 @@ pub async fn system_install_update(app_handle: AppHandle) -> Result<(), SystemEr
         )
         .await
         .map_err(|e| {
-            tracing::error!("Failed to download and install update: {e}");
-            SystemError::Updater(format!("Failed to download and install update: {e}"))
+            tracing::error!("Failed to download update: {e}");
+            SystemError::Updater(format!("Failed to download update: {e}"))
         })?;
+    crate::updater::install(&app_handle, &update, &bytes).map_err(|e| {
+        tracing::error!("Failed to install update: {e}");
+        SystemError::Updater(format!("Failed to install update: {e}"))
+    })?;
 
     tracing::debug!("Update installed, restarting application");
-
-    if let Some(ref outer) = outer_app {
-        tracing::info!(
-            "Scheduling restart of outer app bundle: {}",
-            outer.display()
-        );
-        let _ = std::process::Command::new("sh")
-            .args(["-c", "sleep 2 && exec open \"$1\"", "--"])
-            .arg(outer)
-            .stdin(std::process::Stdio::null())
-            .stdout(std::process::Stdio::null())
-            .stderr(std::process::Stdio::null())
-            .spawn();
-        std::process::exit(0);
-    }
-
-    app_handle.restart();
+    crate::updater::restart(&app_handle);
 }
 
 #[tauri::command]
@@ -603,17 +571,26 @@ pub async fn system_get_telemetry_bootstrap(
 
     let consent = settings.cache.settings.desktop.telemetry.clone();
     let distinct_id = settings.local.telemetry.distinct_id.clone();
+    let analytics = !settings.local.telemetry.analytics_disabled;
     drop(settings);
 
+    // With analytics disabled locally the PostHog key never reaches the
+    // frontend, so the SDK can't start no matter what the consent says.
+    let posthog = |value: &'static str| {
+        euro_telemetry::non_empty(value)
+            .filter(|_| analytics)
+            .map(str::to_owned)
+    };
+
     Ok(TelemetryBootstrap {
         allows_errors: consent.allows_errors_on_desktop(),
-        allows_metrics: consent.allows_metrics_on_desktop(),
-        allows_identification: consent.allows_identification_on_desktop(),
+        allows_metrics: analytics && consent.allows_metrics_on_desktop(),
+        allows_identification: analytics && consent.allows_identification_on_desktop(),
         consent,
         distinct_id,
         sentry_dsn: euro_telemetry::non_empty(euro_telemetry::SENTRY_DSN).map(str::to_owned),
-        posthog_key: euro_telemetry::non_empty(euro_telemetry::POSTHOG_KEY).map(str::to_owned),
-        posthog_host: euro_telemetry::non_empty(euro_telemetry::POSTHOG_HOST).map(str::to_owned),
+        posthog_key: posthog(euro_telemetry::POSTHOG_KEY),
+        posthog_host: posthog(euro_telemetry::POSTHOG_HOST),
         channel: euro_telemetry::non_empty(euro_telemetry::RELEASE_CHANNEL).map(str::to_owned),
         release: euro_telemetry::non_empty(euro_telemetry::RELEASE_VERSION).map(str::to_owned),
     })
@@ -643,6 +620,62 @@ pub async fn frontend_ready(app_handle: AppHandle) -> Result<(), SystemError> {
     Ok(())
 }
 
+/// A crash from an earlier run, saved while error reporting was off.
+#[derive(Clone, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingCrashReport {
+    pub id: String,
+    /// Milliseconds since the Unix epoch.
+    pub crashed_at_ms: f64,
+    pub message: String,
+    pub release: String,
+}
+
+/// Crashes waiting for the user to say whether to send them. Empty in
+/// builds that have nowhere to send them.
+#[tauri::command]
+#[specta::specta]
+pub async fn system_pending_crash_reports() -> Vec<PendingCrashReport> {
+    let Some(dir) = euro_telemetry::crash::default_crash_dir()
+        .filter(|_| euro_telemetry::crash::can_send_crash_reports())
+    else {
+        return Vec::new();
+    };
+    tokio::task::spawn_blocking(move || euro_telemetry::crash::pending_crash_reports(&dir))
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|report| PendingCrashReport {
+            id: report.id,
+            crashed_at_ms: report.crashed_at_ms as f64,
+            message: report.message,
+            release: report.release,
+        })
+        .collect()
+}
+
+/// The user's answer to "send crash report?": upload the saved reports
+/// (`send`) or delete them. Returns how many were sent. Sending is
+/// consent for these reports only; it doesn't turn error reporting on.
+#[tauri::command]
+#[specta::specta]
+pub async fn system_resolve_crash_reports(send: bool) -> Result<u32, SystemError> {
+    let Some(dir) = euro_telemetry::crash::default_crash_dir() else {
+        return Ok(0);
+    };
+    let sent = tokio::task::spawn_blocking(move || {
+        if send {
+            euro_telemetry::crash::send_crash_reports(&dir)
+        } else {
+            euro_telemetry::crash::discard_crash_reports(&dir);
+            0
+        }
+    })
+    .await
+    .map_err(|e| SystemError::Persistence(e.to_string()))?;
+    Ok(u32::try_from(sent).unwrap_or(u32::MAX))
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn system_reinit_telemetry(app_handle: AppHandle) {
@@ -0,0 +1,47 @@
+use agent_chain_core::tools::{ConsentDecision, RiskLevel};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use specta::Type;
+use specta_typescript::Unknown;
+use tauri::{AppHandle, Manager};
+use tauri_specta::Event;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::tool_consent::PendingConsents;
+
+/// Emitted when the assistant wants to run a tool that needs the user's
+/// approval. The frontend shows a prompt and answers through
+/// [`tool_consent_respond`] with the same `request_id`.
+#[derive(Clone, Debug, Serialize, Deserialize, Type, Event)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolConsentRequested {
+    pub request_id: Uuid,
+    pub tool: String,
+    pub risk: RiskLevel,
+    #[specta(type = Unknown)]
+    pub arguments: Value,
+}
+
+#[derive(Debug, Error, Serialize, Type)]
+#[serde(tag = "type", content = "data")]
+pub enum ToolConsentError {
+    /// The prompt already timed out, was cancelled, or was answered.
+    #[error("no pending consent request {0}")]
+    UnknownRequest(Uuid),
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn tool_consent_respond(
+    app_handle: AppHandle,
+    request_id: Uuid,
+    decision: ConsentDecision,
+) -> Result<(), ToolConsentError> {
+    let pending = app_handle.state::<PendingConsents>();
+    if pending.resolve(request_id, decision) {
+        Ok(())
+    } else {
+        Err(ToolConsentError::UnknownRequest(request_id))
+    }
+}
@@ -0,0 +1,189 @@
+use euro_voice::{PlatformSpeech, SpeechReader, SpeechState, SpeechVoice, VoiceEvent};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{AppHandle, Manager, ipc::Channel};
+use tauri_specta::Event;
+use thiserror::Error;
+
+use crate::shared_types::{SharedEndpointManager, SharedHttpClient, SharedSettingsState};
+use crate::voice::{
+    SpeechSetup, VoiceSession, VoiceState, config_from_settings, transcriber_from_settings,
+};
+
+/// Emitted whenever reading aloud starts, pauses or ends, so the
+/// response view can show the matching controls.
+#[derive(Clone, Debug, Serialize, Deserialize, Type, Event)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeechStateChanged {
+    pub state: SpeechState,
+}
+
+#[derive(Debug, Error, Serialize, Type)]
+#[serde(tag = "type", content = "data")]
+pub enum VoiceError {
+    #[error("no microphone available")]
+    NoMicrophone,
+    /// Neither a Whisper model nor a usable provider is configured.
+    #[error("no speech recognizer configured")]
+    NoRecognizer,
+    /// The microphone, recognizer or speech output failed.
+    #[error("{0}")]
+    Unavailable(String),
+}
+
+impl From<euro_voice::VoiceError> for VoiceError {
+    fn from(err: euro_voice::VoiceError) -> Self {
+        match err {
+            euro_voice::VoiceError::NoInputDevice => Self::NoMicrophone,
+            euro_voice::VoiceError::NoTranscriber => Self::NoRecognizer,
+            other => Self::Unavailable(other.to_string()),
+        }
+    }
+}
+
+/// Push-to-talk pressed: open the microphone and stream transcripts to
+/// `channel`. Cancels an utterance still in progress.
+#[tauri::command]
+#[specta::specta]
+pub async fn voice_start(
+    app_handle: AppHandle,
+    channel: Channel<VoiceEvent>,
+) -> Result<(), VoiceError> {
+    let voice = app_handle.state::<VoiceState>();
+    if let Some(mut previous) = voice.session.lock().take() {
+        previous.cancel();
+    }
+
+    let settings = app_handle
+        .state::<SharedSettingsState>()
+        .lock()
+        .await
+        .local
+        .voice
+        .clone();
+    let cached = voice.transcriber.lock().clone();
+    let transcriber = match cached {
+        Some(transcriber) => transcriber,
+        None => {
+            let http = app_handle.state::<SharedHttpClient>().inner().clone();
+            let local_only = app_handle.state::<SharedEndpointManager>().is_local_only();
+            let for_build = settings.clone();
+            // Loading a Whisper model reads and initialises hundreds of MB.
+            let transcriber = tokio::task::spawn_blocking(move || {
+                transcriber_from_settings(&for_build, &http, local_only)
+            })
+            .await
+            .map_err(|e| VoiceError::Unavailable(e.to_string()))??;
+            *voice.transcriber.lock() = Some(transcriber.clone());
+            transcriber
+        }
+    };
+
+    let session =
+        VoiceSession::start(transcriber, config_from_settings(&settings), move |event| {
+            if let Err(e) = channel.send(event) {
+                tracing::debug!(error = %e, "Dropped voice event");
+            }
+        })?;
+    *voice.session.lock() = Some(session);
+    Ok(())
+}
+
+/// Push-to-talk released: stop recording. The final transcript arrives on
+/// the channel passed to [`voice_start`].
+#[tauri::command]
+#[specta::specta]
+pub async fn voice_stop(app_handle: AppHandle) {
+    if let Some(mut session) = app_handle.state::<VoiceState>().session.lock().take() {
+        session.stop();
+    }
+}
+
+/// Throw the current utterance away without a transcript.
+#[tauri::command]
+#[specta::specta]
+pub async fn voice_cancel(app_handle: AppHandle) {
+    if let Some(mut session) = app_handle.state::<VoiceState>().session.lock().take() {
+        session.cancel();
+    }
+}
+
+/// Read `text`, the next piece of a streamed response, aloud. Sentences
+/// are spoken as soon as they are complete; call [`speech_finish`] after
+/// the last piece.
+#[tauri::command]
+#[specta::specta]
+pub async fn speech_push(app_handle: AppHandle, text: String) -> Result<(), VoiceError> {
+    let local_only = app_handle.state::<SharedEndpointManager>().is_local_only();
+    let setup = SpeechSetup::from_settings(
+        &*app_handle.state::<SharedSettingsState>().lock().await,
+        local_only,
+    );
+
+    let voice = app_handle.state::<VoiceState>();
+    let mut reader = voice.reader.lock();
+    if reader
+        .as_ref()
+        .is_none_or(|(built_from, _)| *built_from != setup)
+    {
+        // Drop the old reader first; two can't share the audio device.
+        reader.take();
+        let http = app_handle.state::<SharedHttpClient>();
+        let emitter = app_handle.clone();
+        let started = SpeechReader::start(setup.backend(&http), move |state| {
+            let event = SpeechStateChanged { state };
+            if let Err(e) = event.emit(&emitter) {
+                tracing::debug!(error = %e, "Failed to emit speech state");
+            }
+        })?;
+        *reader = Some((setup, started));
+    }
+    if let Some((_, reader)) = reader.as_ref() {
+        reader.push(text);
+    }
+    Ok(())
+}
+
+/// The response is complete: read whatever is left of it.
+#[tauri::command]
+#[specta::specta]
+pub async fn speech_finish(app_handle: AppHandle) {
+    with_reader(&app_handle, SpeechReader::finish);
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn speech_pause(app_handle: AppHandle) {
+    with_reader(&app_handle, SpeechReader::pause);
+}
+
+/// Continue after [`speech_pause`], from the start of the interrupted
+/// sentence.
+#[tauri::command]
+#[specta::specta]
+pub async fn speech_resume(app_handle: AppHandle) {
+    with_reader(&app_handle, SpeechReader::resume);
+}
+
+/// Stop reading and drop the rest of the response.
+#[tauri::command]
+#[specta::specta]
+pub async fn speech_stop(app_handle: AppHandle) {
+    with_reader(&app_handle, SpeechReader::stop);
+}
+
+/// The platform voices the settings page can offer. Empty where the
+/// platform doesn't let us choose.
+#[tauri::command]
+#[specta::specta]
+pub async fn speech_list_voices() -> Result<Vec<SpeechVoice>, VoiceError> {
+    Ok(tokio::task::spawn_blocking(PlatformSpeech::voices)
+        .await
+        .map_err(|e| VoiceError::Unavailable(e.to_string()))??)
+}
+
+fn with_reader(app_handle: &AppHandle, f: impl FnOnce(&SpeechReader)) {
+    if let Some((_, reader)) = app_handle.state::<VoiceState>().reader.lock().as_ref() {
+        f(reader);
+    }
+}
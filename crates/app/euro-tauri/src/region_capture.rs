@@ -0,0 +1,227 @@
+//! Desktop wiring for region capture: the global shortcut, the selection
+//! overlay, and handing the capture to the next chat turn.
+//!
+//! The shortcut freezes the monitor under the cursor and opens a
+//! borderless overlay over it. The overlay draws the still, lets the user
+//! drag out a rectangle and reports it through `region_capture_select`,
+//! which crops the still and brings the main window forward with the
+//! capture attached. Uploading the capture as an asset and reading its
+//! text run in the background; the image (a reference to the asset if the
+//! upload is done, inline otherwise), plus whatever text OCR found by the
+//! time the question is sent, goes out with the next turn through
+//! [`crate::local_tools::LocalToolBackend`], so the secret scanner sees the
+//! text like any other context. The text has been through the moderator's
+//! OCR pass before it is kept at all, which also masks labelled passwords
+//! and drops text read off password managers.
+
+use agent_chain_core::messages::{ContentBlock, ImageContentBlock, TextContentBlock};
+use euro_settings::{RegionCaptureSettings, ScreenshotFormat};
+use euro_vision::capture::CapturedImage;
+use euro_vision::encode::ImageEncoding;
+use euro_vision::region::{ScreenFrame, freeze_monitor_at};
+use parking_lot::Mutex;
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
+use thread_core::ContextChip;
+use uuid::Uuid;
+
+use crate::procedures::region_capture::RegionCaptureError;
+use crate::util::{get_default_region_capture_shortcut, hotkey_to_shortcut};
+
+/// Label of the selection overlay window, which is also its route.
+pub const OVERLAY_WINDOW_LABEL: &str = "region-capture";
+
+/// Tauri state for region capture.
+#[derive(Default)]
+pub struct RegionCaptureState {
+    /// The still the overlay is showing, while it is open.
+    pub selecting: Mutex<Option<Selecting>>,
+    /// The capture waiting to go out with the next turn.
+    pub pending: Mutex<Option<PendingCapture>>,
+    /// The registered shortcut, so a settings change can swap it out.
+    pub shortcut: Mutex<Option<Shortcut>>,
+    /// The OCR engine and the models directory it was loaded from.
+    #[cfg(feature = "ocr")]
+    pub recognizer: Mutex<Option<(String, std::sync::Arc<euro_vision::ocr::TextRecognizer>)>>,
+    /// Where OCR runs, so reading a capture never competes with more
+    /// than its share of cores.
+    #[cfg(feature = "ocr")]
+    pub ocr_queue: euro_vision::ocr_queue::OcrQueue,
+}
+
+pub struct Selecting {
+    pub frame: ScreenFrame,
+    /// `frame` as a JPEG data URL, encoded once for the overlay.
+    pub preview: String,
+}
+
+pub struct PendingCapture {
+    pub id: Uuid,
+    pub image: CapturedImage,
+    /// Filled in by OCR when it finishes, if it finds anything.
+    pub text: Option<String>,
+    /// Whether the user drew on the capture.
+    pub annotated: bool,
+    /// The asset the capture was saved as, with its MIME type, once the
+    /// upload finishes. A turn sent after that references the asset
+    /// instead of carrying the image inline.
+    pub asset: Option<(Uuid, String)>,
+}
+
+impl PendingCapture {
+    pub fn chip(&self) -> ContextChip {
+        ContextChip {
+            id: self.id.to_string(),
+            name: "Screenshot".to_owned(),
+            icon: None,
+            domain: None,
+        }
+    }
+
+    pub fn into_blocks(self) -> Vec<ContentBlock> {
+        let mut note = "The user selected this region of their screen to ask about.".to_owned();
+        if self.annotated {
+            note.push_str(" They drew on it to point at what they mean.");
+        }
+        if let Some(text) = &self.text {
+            note.push_str("\n\nText recognized in it:\n");
+            note.push_str(text);
+        }
+        let mut blocks = vec![ContentBlock::Text(
+            TextContentBlock::builder().text(note).build(),
+        )];
+        let image = match self.asset {
+            Some((asset_id, mime_type)) => ImageContentBlock::builder()
+                .file_id(asset_id.to_string())
+                .mime_type(mime_type)
+                .build(),
+            None => ImageContentBlock::builder()
+                .base64(self.image.png_base64)
+                .mime_type("image/png".to_string())
+                .build(),
+        };
+        match image {
+            Ok(image) => blocks.push(ContentBlock::Image(image)),
+            Err(err) => tracing::debug!("Region capture image block rejected: {err}"),
+        }
+        blocks
+    }
+}
+
+/// Whether chat models read images stored as `mime_type`. Captures stored
+/// in other formats (AVIF) still go out inline as PNG.
+pub fn model_readable(mime_type: &str) -> bool {
+    matches!(mime_type, "image/png" | "image/jpeg" | "image/webp")
+}
+
+/// The format `settings` ask for captures to be stored in.
+pub fn storage_encoding(settings: &RegionCaptureSettings) -> ImageEncoding {
+    let quality = settings.storage_quality;
+    match settings.storage_format {
+        ScreenshotFormat::Png => ImageEncoding::Png,
+        ScreenshotFormat::Jpeg => ImageEncoding::Jpeg { quality },
+        ScreenshotFormat::Webp => ImageEncoding::Webp,
+        ScreenshotFormat::Avif => ImageEncoding::Avif { quality },
+    }
+}
+
+/// The shortcut `settings` ask for, or `None` when capture is off. A
+/// hotkey that doesn't parse falls back to the default.
+pub fn shortcut_from_settings(settings: &RegionCaptureSettings) -> Option<Shortcut> {
+    if !settings.enabled {
+        return None;
+    }
+    let Some(hotkey) = &settings.hotkey else {
+        return Some(get_default_region_capture_shortcut());
+    };
+    Some(hotkey_to_shortcut(hotkey).unwrap_or_else(|| {
+        tracing::warn!(?hotkey, "Unknown region capture hotkey, using the default");
+        get_default_region_capture_shortcut()
+    }))
+}
+
+/// Register the shortcut `settings` ask for in place of the current one.
+/// A shortcut another app already holds is logged and skipped.
+pub fn apply_shortcut(app: &AppHandle, settings: &RegionCaptureSettings) {
+    let state = app.state::<RegionCaptureState>();
+    let shortcuts = app.global_shortcut();
+    let mut current = state.shortcut.lock();
+    if let Some(previous) = current.take()
+        && let Err(e) = shortcuts.unregister(previous)
+    {
+        tracing::warn!("Failed to unregister region capture shortcut {previous:?}: {e}");
+    }
+    let Some(shortcut) = shortcut_from_settings(settings) else {
+        return;
+    };
+    match shortcuts.register(shortcut) {
+        Ok(()) => *current = Some(shortcut),
+        Err(e) => tracing::warn!("Failed to register region capture shortcut {shortcut:?}: {e}"),
+    }
+}
+
+pub fn is_capture_shortcut(app: &AppHandle, shortcut: &Shortcut) -> bool {
+    app.try_state::<RegionCaptureState>()
+        .is_some_and(|state| state.shortcut.lock().as_ref() == Some(shortcut))
+}
+
+/// Freeze the monitor under the cursor and open the selection overlay on
+/// it. Focuses the overlay instead if one is already open.
+pub async fn begin(app: &AppHandle) -> Result<(), RegionCaptureError> {
+    if let Some(overlay) = app.get_webview_window(OVERLAY_WINDOW_LABEL) {
+        overlay.set_focus()?;
+        return Ok(());
+    }
+
+    let cursor = app.cursor_position()?;
+    let monitor = match app.monitor_from_point(cursor.x, cursor.y)? {
+        Some(monitor) => monitor,
+        None => app
+            .primary_monitor()?
+            .ok_or(RegionCaptureError::NoDisplay)?,
+    };
+    let scale = monitor.scale_factor();
+    let cursor = cursor.to_logical::<f64>(scale);
+    let frame = freeze_monitor_at(cursor.x, cursor.y)
+        .await?
+        .ok_or(RegionCaptureError::NoDisplay)?;
+    let (frame, preview) = tokio::task::spawn_blocking(move || {
+        let preview = frame.preview_jpeg_base64();
+        (frame, preview)
+    })
+    .await
+    .map_err(|e| RegionCaptureError::Capture(e.to_string()))?;
+    let preview = format!(
+        "data:image/jpeg;base64,{}",
+        preview.map_err(|e| RegionCaptureError::Capture(e.to_string()))?
+    );
+    *app.state::<RegionCaptureState>().selecting.lock() = Some(Selecting { frame, preview });
+
+    let position = monitor.position().to_logical::<f64>(scale);
+    let size = monitor.size().to_logical::<f64>(scale);
+    WebviewWindowBuilder::new(
+        app,
+        OVERLAY_WINDOW_LABEL,
+        WebviewUrl::App(OVERLAY_WINDOW_LABEL.into()),
+    )
+    .title("Capture region")
+    .position(position.x, position.y)
+    .inner_size(size.width, size.height)
+    .decorations(false)
+    .resizable(false)
+    .always_on_top(true)
+    .skip_taskbar(true)
+    .focused(true)
+    .build()?;
+    Ok(())
+}
+
+/// Close the overlay and drop its still.
+pub fn end_selection(app: &AppHandle) {
+    app.state::<RegionCaptureState>().selecting.lock().take();
+    if let Some(overlay) = app.get_webview_window(OVERLAY_WINDOW_LABEL)
+        && let Err(e) = overlay.close()
+    {
+        tracing::warn!("Failed to close region capture overlay: {e}");
+    }
+}
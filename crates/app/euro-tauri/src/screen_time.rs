@@ -0,0 +1,157 @@
+//! Desktop wiring for [`euro_screen_time`].
+//!
+//! [`start`] runs once the personal database is open: it manages a
+//! [`ScreenTimeTracker`] for the `screen_time_*` commands, feeds it every
+//! activity change from the timeline, and every [`CHECK_INTERVAL`] counts
+//! the time in the current activity and checks it against the budgets. A
+//! budget gone over and the weekly report both reach the frontend as
+//! [`ScreenTimeNotification`] events.
+
+use std::time::Duration;
+
+use chrono::Utc;
+use euro_personal_db::PersonalDb;
+use euro_screen_time::{BudgetStatus, ScreenTimeReport, ScreenTimeTracker};
+use euro_timeline::TimelineManager;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{AppHandle, Manager};
+use tauri_specta::Event;
+use tokio::sync::Mutex;
+use tokio::sync::broadcast::error::RecvError;
+use uuid::Uuid;
+
+/// How often time is counted and budgets are checked. Well within
+/// [`euro_screen_time::MAX_UNSEEN_GAP`], so only sleep goes uncounted.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// `budget_exceeded` when a budget's daily allowance is used up,
+/// `weekly_report` when last week's report is ready.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ScreenTimeNotificationKind {
+    BudgetExceeded,
+    WeeklyReport,
+}
+
+/// A nudge about screen time.
+#[derive(Clone, Debug, Serialize, Deserialize, Type, Event)]
+#[serde(rename_all = "camelCase")]
+pub struct ScreenTimeNotification {
+    pub kind: ScreenTimeNotificationKind,
+    /// The budget gone over, for `budget_exceeded`.
+    pub budget_id: Option<Uuid>,
+    pub title: String,
+    pub body: String,
+}
+
+/// Create the tracker over `db`, manage it on the app, and start feeding
+/// and checking it.
+pub fn start(app_handle: &AppHandle, db: PersonalDb) {
+    let tracker = ScreenTimeTracker::new(db);
+    app_handle.manage(tracker.clone());
+
+    let activity_handle = app_handle.clone();
+    let activity_tracker = tracker.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut rx = {
+            let timeline = activity_handle.state::<Mutex<TimelineManager>>();
+            let timeline = timeline.lock().await;
+            timeline.subscribe_to_activity_events()
+        };
+        loop {
+            match rx.recv().await {
+                Ok(event) => {
+                    if let Err(e) = activity_tracker
+                        .record_activity(&event.name, &event.process_name, Utc::now())
+                        .await
+                    {
+                        tracing::warn!("Could not record screen time: {e}");
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!(skipped, "Screen-time activity feed lagged");
+                }
+                Err(RecvError::Closed) => return,
+            }
+        }
+    });
+
+    let check_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut ticks = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            ticks.tick().await;
+            let now = Utc::now();
+            match tracker.newly_exceeded(now).await {
+                Ok(exceeded) => {
+                    for status in exceeded {
+                        notify_exceeded(&check_handle, &status);
+                    }
+                }
+                Err(e) => tracing::warn!("Could not check screen-time budgets: {e}"),
+            }
+            match tracker.weekly_report_due(now).await {
+                Ok(Some(report)) => notify_weekly(&check_handle, &report),
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Could not write the weekly screen-time report: {e}"),
+            }
+        }
+    });
+}
+
+fn notify_exceeded(app_handle: &AppHandle, status: &BudgetStatus) {
+    emit(
+        app_handle,
+        ScreenTimeNotification {
+            kind: ScreenTimeNotificationKind::BudgetExceeded,
+            budget_id: Some(status.budget.id),
+            title: format!("Time's up for {}", status.budget.target),
+            body: format!(
+                "You've spent {} there today, over your {} budget.",
+                format_secs(status.used_secs),
+                format_secs(status.budget.daily_secs),
+            ),
+        },
+    );
+}
+
+fn notify_weekly(app_handle: &AppHandle, report: &ScreenTimeReport) {
+    let mut body = format!("{} of screen time", format_secs(report.total_secs));
+    if let Some(top) = report.apps.first() {
+        body.push_str(&format!(", most of it in {}", top.name));
+    }
+    body.push('.');
+    let over: u32 = report.budgets.iter().map(|week| week.days_over).sum();
+    match over {
+        0 => {}
+        1 => body.push_str(" You went over a budget once."),
+        n => body.push_str(&format!(" You went over your budgets {n} times.")),
+    }
+    emit(
+        app_handle,
+        ScreenTimeNotification {
+            kind: ScreenTimeNotificationKind::WeeklyReport,
+            budget_id: None,
+            title: "Your week in screen time".to_string(),
+            body,
+        },
+    );
+}
+
+fn emit(app_handle: &AppHandle, notification: ScreenTimeNotification) {
+    if let Err(e) = notification.emit(app_handle) {
+        tracing::warn!("Failed to emit ScreenTimeNotification: {e}");
+    }
+}
+
+/// `1 h 5 min`, `45 min`, or `under a minute`.
+fn format_secs(secs: u32) -> String {
+    let minutes = secs / 60;
+    match (minutes / 60, minutes % 60) {
+        (0, 0) => "under a minute".to_string(),
+        (0, m) => format!("{m} min"),
+        (h, 0) => format!("{h} h"),
+        (h, m) => format!("{h} h {m} min"),
+    }
+}
@@ -0,0 +1,165 @@
+//! Desktop wiring for [`euro_script`].
+//!
+//! Scripts are stored in `local.json` (see [`euro_settings::ScriptSettings`])
+//! and edited through the `settings_*_scripts` commands. The
+//! [`ScriptScheduler`] runs them as they come due and is registered as
+//! Tauri state for the `script_*` commands; every run is audited in
+//! `<app data>/scripts/audit.jsonl`. [`TauriScriptHost`] gives scripts
+//! the same thread manager and tool backend as the chat UI, the personal
+//! database, and the clipboard. Notifications reach the frontend as
+//! [`ScriptNotification`] events.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use euro_personal_db::PersonalDb;
+use euro_script::{
+    AuditLog, Schedule, Script, ScriptHost, ScriptRunner, ScriptScheduler, TimelineEntry,
+};
+use euro_settings::{ScriptSettings, ScriptTrigger, UserScript};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use specta::Type;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_specta::Event;
+use thread_core::ToolBackend;
+
+use crate::shared_types::SharedThreadManager;
+
+/// A script asked to tell the user something.
+#[derive(Clone, Debug, Serialize, Deserialize, Type, Event)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptNotification {
+    /// Name of the script that sent it.
+    pub script: String,
+    pub title: String,
+    pub body: String,
+}
+
+/// Start the scheduler with the scripts in `settings` and manage it on
+/// the app.
+pub fn start(
+    app_handle: &AppHandle,
+    settings: &ScriptSettings,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let audit = AuditLog::new(
+        app_handle
+            .path()
+            .app_data_dir()?
+            .join("scripts")
+            .join("audit.jsonl"),
+    );
+    let host = Arc::new(TauriScriptHost {
+        app_handle: app_handle.clone(),
+    });
+    let scheduler = ScriptScheduler::new(ScriptRunner::new(host, audit));
+    scheduler.set_scripts(scripts_from_settings(settings));
+    {
+        let scheduler = scheduler.clone();
+        tauri::async_runtime::spawn(async move { scheduler.run().await });
+    }
+    app_handle.manage(scheduler);
+    Ok(())
+}
+
+/// The scripts in `settings` as the scheduler takes them. Disabled
+/// scripts can still be run by hand.
+pub fn scripts_from_settings(settings: &ScriptSettings) -> Vec<Script> {
+    settings.scripts.iter().map(script_from_settings).collect()
+}
+
+fn script_from_settings(script: &UserScript) -> Script {
+    let schedule = match script.trigger {
+        _ if !script.enabled => Schedule::Manual,
+        ScriptTrigger::Manual => Schedule::Manual,
+        ScriptTrigger::Every { minutes } => {
+            Schedule::Every(Duration::from_secs(u64::from(minutes) * 60))
+        }
+        ScriptTrigger::Daily { hour, minute } => Schedule::Daily {
+            hour: u32::from(hour),
+            minute: u32::from(minute),
+        },
+    };
+    Script {
+        id: script.id.clone(),
+        name: script.name.clone(),
+        source: script.source.clone(),
+        schedule,
+        timeout: Duration::from_secs(u64::from(script.timeout_secs)),
+    }
+}
+
+/// The API scripts see, backed by the running app.
+pub struct TauriScriptHost {
+    app_handle: AppHandle,
+}
+
+#[async_trait]
+impl ScriptHost for TauriScriptHost {
+    async fn ask_model(&self, question: &str) -> Result<String, String> {
+        let question = question.trim();
+        if question.is_empty() {
+            return Err("question must not be empty".into());
+        }
+        let thread_manager = self
+            .app_handle
+            .try_state::<SharedThreadManager>()
+            .ok_or("chat is unavailable")?
+            .inner()
+            .clone();
+        let backend = self
+            .app_handle
+            .try_state::<Arc<dyn ToolBackend>>()
+            .ok_or("chat is unavailable")?
+            .inner()
+            .clone();
+        let thread = thread_manager
+            .create(None)
+            .await
+            .map_err(|e| e.to_string())?;
+        crate::local_api::run_turn(&thread_manager, &backend, thread.id, question)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    async fn search_timeline(&self, text: &str, limit: u32) -> Result<Vec<TimelineEntry>, String> {
+        let db = self
+            .app_handle
+            .try_state::<PersonalDb>()
+            .ok_or("the personal database is not open")?;
+        let records = db
+            .search_activity(text, limit)
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(records
+            .into_iter()
+            .map(|record| TimelineEntry {
+                kind: record.kind,
+                recorded_at: record.recorded_at,
+                text: match record.payload {
+                    Value::String(text) => text,
+                    payload => payload.to_string(),
+                },
+            })
+            .collect())
+    }
+
+    async fn read_clipboard(&self) -> Result<String, String> {
+        self.app_handle
+            .clipboard()
+            .read_text()
+            .map_err(|e| e.to_string())
+    }
+
+    async fn notify(&self, script: &str, title: &str, body: &str) -> Result<(), String> {
+        ScriptNotification {
+            script: script.to_owned(),
+            title: title.to_owned(),
+            body: body.to_owned(),
+        }
+        .emit(&self.app_handle)
+        .map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,97 @@
+//! Timing the startup path and keeping work off it.
+//!
+//! Everything in Tauri's `setup` runs before the main window is created,
+//! so each step there delays the first paint. [`StartupTimer`] logs how
+//! long every phase of `setup` took, and warns when the whole run is over
+//! [`SLOW_STARTUP`], so a step that grows shows up in the logs rather than
+//! as a vague "the app got slower". [`Deferred`] holds the work that
+//! doesn't have to be done before the window exists: it runs after
+//! `setup`, one task at a time in the order the tasks were added, so a
+//! task can rely on the ones before it having finished.
+//!
+//! Engines that are expensive to create and not needed by every session,
+//! like the OCR recognizer and the voice transcriber, aren't warmed up at
+//! all; they load on first use and stay cached in their state.
+
+use std::time::{Duration, Instant};
+
+/// Startup slower than this is logged as a warning.
+pub const SLOW_STARTUP: Duration = Duration::from_secs(2);
+
+/// Logs the duration of each named phase of startup.
+pub struct StartupTimer {
+    started: Instant,
+    phase: Option<(&'static str, Instant)>,
+}
+
+impl StartupTimer {
+    pub fn start() -> Self {
+        Self {
+            started: Instant::now(),
+            phase: None,
+        }
+    }
+
+    /// End the current phase, if any, and start `name`.
+    pub fn phase(&mut self, name: &'static str) {
+        self.end_phase();
+        self.phase = Some((name, Instant::now()));
+    }
+
+    /// End the last phase and log the total.
+    pub fn finish(mut self) -> Duration {
+        self.end_phase();
+        let total = self.started.elapsed();
+        if total > SLOW_STARTUP {
+            tracing::warn!(
+                elapsed_ms = total.as_millis(),
+                "Startup took longer than {SLOW_STARTUP:?}"
+            );
+        } else {
+            tracing::info!(elapsed_ms = total.as_millis(), "Startup finished");
+        }
+        total
+    }
+
+    fn end_phase(&mut self) {
+        if let Some((name, started)) = self.phase.take() {
+            tracing::info!(
+                phase = name,
+                elapsed_ms = started.elapsed().as_millis(),
+                "Startup phase finished"
+            );
+        }
+    }
+}
+
+type Task = Box<dyn FnOnce() + Send>;
+
+/// Blocking work to run once startup is done, in order.
+#[derive(Default)]
+pub struct Deferred {
+    tasks: Vec<(&'static str, Task)>,
+}
+
+impl Deferred {
+    pub fn push(&mut self, name: &'static str, task: impl FnOnce() + Send + 'static) {
+        self.tasks.push((name, Box::new(task)));
+    }
+
+    /// Run the tasks one after another on the blocking pool. A task that
+    /// panics is logged, and the ones after it still run.
+    pub fn spawn(self) {
+        tauri::async_runtime::spawn_blocking(move || {
+            for (name, task) in self.tasks {
+                let started = Instant::now();
+                let outcome = std::panic::catch_unwind(std::panic::AssertUnwindSafe(task));
+                let elapsed_ms = started.elapsed().as_millis();
+                match outcome {
+                    Ok(()) => {
+                        tracing::debug!(task = name, elapsed_ms, "Deferred startup task done");
+                    }
+                    Err(_) => tracing::error!(task = name, "Deferred startup task panicked"),
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,253 @@
+//! User consent for agent tool calls.
+//!
+//! [`ConsentToolBackend`] wraps the activity tool backend and runs every
+//! call through [`ToolPermissions`] before dispatching it. Risk comes
+//! from the descriptor the tool advertised: `requires_user_approval`
+//! tools are [`RiskLevel::Elevated`], everything else is safe. A higher
+//! level configured on [`ToolPermissions`] for the tool's name wins, which
+//! is how `run_command` asks on every call.
+//!
+//! Prompts surface in the UI as
+//! [`ToolConsentRequested`](crate::procedures::tool_consent::ToolConsentRequested)
+//! and resolve when the frontend calls
+//! [`tool_consent_respond`](crate::procedures::tool_consent::tool_consent_respond).
+//! "Always allow" answers persist to `local.json` through
+//! [`SettingsDecisionStore`].
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex as StdMutex, RwLock};
+use std::time::Duration;
+
+use agent_chain_core::tools::{
+    ConsentDecision, ConsentHandler, DecisionStore, PermissionRequest, RiskLevel, ToolPermissions,
+};
+use async_trait::async_trait;
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+use tauri_specta::Event;
+use thread_core::{ToolBackend, ToolBackendCall, ToolErrorWire, WireToolDescriptor};
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+use crate::procedures::tool_consent::ToolConsentRequested;
+use crate::shared_types::SharedSettingsState;
+
+/// How long a prompt waits for an answer before it counts as a denial.
+const CONSENT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Prompts waiting on the frontend, keyed by `request_id`. Registered as
+/// Tauri state so [`tool_consent_respond`](crate::procedures::tool_consent::tool_consent_respond)
+/// can resolve them.
+#[derive(Clone, Default)]
+pub struct PendingConsents {
+    inner: Arc<StdMutex<HashMap<Uuid, oneshot::Sender<ConsentDecision>>>>,
+}
+
+impl PendingConsents {
+    fn insert(&self, request_id: Uuid) -> oneshot::Receiver<ConsentDecision> {
+        let (tx, rx) = oneshot::channel();
+        if let Ok(mut pending) = self.inner.lock() {
+            pending.insert(request_id, tx);
+        }
+        rx
+    }
+
+    fn remove(&self, request_id: Uuid) {
+        if let Ok(mut pending) = self.inner.lock() {
+            pending.remove(&request_id);
+        }
+    }
+
+    /// Deliver the user's answer. Returns `false` when nothing is waiting
+    /// on `request_id` any more.
+    pub fn resolve(&self, request_id: Uuid, decision: ConsentDecision) -> bool {
+        let sender = self
+            .inner
+            .lock()
+            .ok()
+            .and_then(|mut pending| pending.remove(&request_id));
+        sender.is_some_and(|tx| tx.send(decision).is_ok())
+    }
+}
+
+/// Asks the desktop UI and waits for the answer. No answer within
+/// [`CONSENT_TIMEOUT`] is a denial.
+pub struct TauriConsentHandler {
+    app_handle: AppHandle,
+    pending: PendingConsents,
+}
+
+impl TauriConsentHandler {
+    pub fn new(app_handle: AppHandle, pending: PendingConsents) -> Self {
+        Self {
+            app_handle,
+            pending,
+        }
+    }
+}
+
+#[async_trait]
+impl ConsentHandler for TauriConsentHandler {
+    async fn request_consent(&self, request: &PermissionRequest) -> ConsentDecision {
+        let request_id = Uuid::new_v4();
+        let answer = self.pending.insert(request_id);
+
+        let event = ToolConsentRequested {
+            request_id,
+            tool: request.tool.clone(),
+            risk: request.risk,
+            arguments: request.arguments.clone(),
+        };
+        if let Err(e) = event.emit(&self.app_handle) {
+            tracing::warn!("Failed to emit tool consent prompt: {e}");
+            self.pending.remove(request_id);
+            return ConsentDecision::Deny;
+        }
+
+        match tokio::time::timeout(CONSENT_TIMEOUT, answer).await {
+            Ok(Ok(decision)) => decision,
+            _ => {
+                self.pending.remove(request_id);
+                ConsentDecision::Deny
+            }
+        }
+    }
+}
+
+/// [`DecisionStore`] backed by `local.json`. Reads come from an in-memory
+/// copy; writes update it and persist in the background.
+pub struct SettingsDecisionStore {
+    app_handle: AppHandle,
+    allowed: RwLock<BTreeSet<String>>,
+}
+
+impl SettingsDecisionStore {
+    pub fn new(app_handle: AppHandle, allowed: BTreeSet<String>) -> Self {
+        Self {
+            app_handle,
+            allowed: RwLock::new(allowed),
+        }
+    }
+
+    fn persist(&self) {
+        let Ok(allowed) = self.allowed.read().map(|a| a.clone()) else {
+            return;
+        };
+        let app_handle = self.app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            let Some(state) = app_handle.try_state::<SharedSettingsState>() else {
+                return;
+            };
+            let mut settings = state.lock().await;
+            settings.local.tool_permissions.always_allowed = allowed;
+            if let Err(e) = settings.save_local_to_default_path() {
+                tracing::warn!("Failed to persist tool permissions: {e}");
+            }
+        });
+    }
+}
+
+impl DecisionStore for SettingsDecisionStore {
+    fn is_always_allowed(&self, tool: &str) -> bool {
+        self.allowed
+            .read()
+            .map(|allowed| allowed.contains(tool))
+            .unwrap_or(false)
+    }
+
+    fn allow_always(&self, tool: &str) {
+        let changed = self
+            .allowed
+            .write()
+            .map(|mut allowed| allowed.insert(tool.to_string()))
+            .unwrap_or(false);
+        if changed {
+            self.persist();
+        }
+    }
+
+    fn revoke(&self, tool: &str) {
+        let changed = self
+            .allowed
+            .write()
+            .map(|mut allowed| allowed.remove(tool))
+            .unwrap_or(false);
+        if changed {
+            self.persist();
+        }
+    }
+}
+
+/// Enforces [`ToolPermissions`] in front of another [`ToolBackend`].
+pub struct ConsentToolBackend {
+    inner: Arc<dyn ToolBackend>,
+    permissions: ToolPermissions,
+    /// Risk per tool name, refreshed from the descriptors on every
+    /// `list_tools`. Tools the LLM calls without having been listed fall
+    /// back to [`RiskLevel::Safe`] — the inner backend rejects unknown
+    /// names anyway.
+    risks: RwLock<HashMap<String, RiskLevel>>,
+}
+
+impl ConsentToolBackend {
+    pub fn new(inner: Arc<dyn ToolBackend>, permissions: ToolPermissions) -> Self {
+        Self {
+            inner,
+            permissions,
+            risks: RwLock::new(HashMap::new()),
+        }
+    }
+
+    fn risk_of(&self, tool: &str) -> RiskLevel {
+        self.risks
+            .read()
+            .ok()
+            .and_then(|risks| risks.get(tool).copied())
+            .unwrap_or_else(|| self.permissions.risk_of(tool))
+    }
+}
+
+fn descriptor_risk(descriptor: &WireToolDescriptor) -> RiskLevel {
+    if descriptor.requires_user_approval {
+        RiskLevel::Elevated
+    } else {
+        RiskLevel::Safe
+    }
+}
+
+#[async_trait]
+impl ToolBackend for ConsentToolBackend {
+    async fn list_tools(&self) -> Vec<WireToolDescriptor> {
+        let tools = self.inner.list_tools().await;
+        if let Ok(mut risks) = self.risks.write() {
+            *risks = tools
+                .iter()
+                .map(|t| {
+                    let risk = descriptor_risk(t).max(self.permissions.risk_of(t.name()));
+                    (t.name().to_string(), risk)
+                })
+                .collect();
+        }
+        tools
+    }
+
+    async fn collect_system_blocks(&self) -> Vec<agent_chain_core::messages::ContentBlock> {
+        self.inner.collect_system_blocks().await
+    }
+
+    async fn dispatch(&self, call: ToolBackendCall) -> Result<Value, ToolErrorWire> {
+        let risk = self.risk_of(&call.name);
+        // Race the prompt against the call's own cancellation so a turn
+        // the user aborted doesn't leave a dialog hanging.
+        let authorized = tokio::select! {
+            result = self.permissions.authorize_with_risk(&call.name, risk, &call.arguments) => result,
+            () = call.cancel.cancelled() => return Err(ToolErrorWire::Cancelled),
+        };
+        if let Err(e) = authorized {
+            return Err(ToolErrorWire::Adapter {
+                message: e.to_string(),
+            });
+        }
+        self.inner.dispatch(call).await
+    }
+}
@@ -0,0 +1,32 @@
+//! Desktop wiring for [`euro_transfer`].
+//!
+//! One [`TransferManager`] is built from the `transfers` section of
+//! `local.json` at startup and registered as Tauri state. Asset uploads
+//! and downloads go through it, `settings_set_transfers` pushes edits to
+//! it, and a background task keeps it told whether the connection is
+//! metered.
+
+use euro_settings::TransferSettings;
+use euro_transfer::{NightWindow, TransferManager, TransferPolicy};
+
+/// The policy `settings` describe.
+pub fn policy_from_settings(settings: &TransferSettings) -> TransferPolicy {
+    let kib = |kib_per_sec: u32| (kib_per_sec > 0).then(|| u64::from(kib_per_sec) * 1024);
+    TransferPolicy {
+        upload_bytes_per_sec: kib(settings.max_upload_kib_per_sec),
+        download_bytes_per_sec: kib(settings.max_download_kib_per_sec),
+        pause_on_metered: settings.pause_on_metered,
+        night_window: settings.night_only.then_some(NightWindow {
+            start_hour: settings.night_start_hour,
+            end_hour: settings.night_end_hour,
+        }),
+        max_concurrent: usize::from(settings.max_concurrent),
+    }
+}
+
+/// Build the manager and start watching the network cost.
+pub fn start(settings: &TransferSettings) -> TransferManager {
+    let manager = TransferManager::new(policy_from_settings(settings));
+    tauri::async_runtime::spawn(euro_transfer::watch_network_cost(manager.clone()));
+    manager
+}
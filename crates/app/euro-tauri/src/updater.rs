@@ -0,0 +1,344 @@
+//! Background updates.
+//!
+//! A task polls the release channel, downloads new bundles into
+//! `<app data>/updates` under the transfer policy, checks their signature
+//! against [`signature::PINNED_KEYS`] and keeps the verified bundle staged.
+//! The staged bundle is installed when the app exits, or right away when
+//! the user asks from the UI, so the new version comes up on the next
+//! start. [`boot_guard`] rolls a version back if it keeps failing to
+//! start. The UI follows along through [`UpdateProgress`] events.
+//!
+//! The update server lists one full signed bundle per platform, so there
+//! is nothing to build a delta against; an interrupted download picks up
+//! where it stopped instead of starting over.
+
+mod boot_guard;
+mod signature;
+mod staging;
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use euro_transfer::TransferManager;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_updater::{Update, Updater, UpdaterExt};
+use tauri_specta::Event;
+use thiserror::Error;
+use tokio::sync::Mutex;
+
+use self::boot_guard::BootCheck;
+use self::staging::Staging;
+
+/// How often the release channel is polled.
+const UPDATE_CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+/// How soon a failed check or download is tried again.
+const UPDATE_RETRY_INTERVAL: Duration = Duration::from_secs(15 * 60);
+/// How long a new version has to stay up after startup before it counts
+/// as working.
+const BOOT_CONFIRM_DELAY: Duration = Duration::from_secs(30);
+/// Bytes downloaded between two [`UpdateProgress::Downloading`] events.
+const PROGRESS_STEP: u64 = 512 * 1024;
+
+#[derive(Debug, Error)]
+pub enum UpdaterError {
+    #[error("io: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("boot marker: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("http: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("download: {0}")]
+    Download(String),
+    #[error("bad signature: {0}")]
+    BadSignature(String),
+    #[error("no copy of the previous install to roll back to")]
+    RollbackUnavailable,
+    #[error("updater: {0}")]
+    Plugin(#[from] tauri_plugin_updater::Error),
+    #[error("app data directory: {0}")]
+    Path(#[from] tauri::Error),
+}
+
+/// Where the background download of an update has got to.
+#[derive(Clone, Debug, Serialize, Deserialize, Type, Event)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum UpdateProgress {
+    Downloading {
+        version: String,
+        downloaded: f64,
+        total: Option<f64>,
+    },
+    Verifying {
+        version: String,
+    },
+    /// Staged; installs on exit or from `system_install_update`.
+    Ready {
+        version: String,
+    },
+    Failed {
+        version: String,
+        error: String,
+    },
+}
+
+/// The verified update waiting to be installed, if any.
+#[derive(Default)]
+pub struct UpdaterState {
+    ready: Mutex<Option<ReadyUpdate>>,
+}
+
+struct ReadyUpdate {
+    update: Update,
+    bundle: PathBuf,
+}
+
+/// Where downloads, the boot marker and the rollback copy live.
+pub fn updates_dir(app: &AppHandle) -> Result<PathBuf, UpdaterError> {
+    Ok(app.path().app_data_dir()?.join("updates"))
+}
+
+/// An updater that installs over the outer app bundle on macOS, where
+/// the binary can sit in a nested bundle.
+pub(crate) fn updater(app: &AppHandle) -> Result<Updater, UpdaterError> {
+    let mut builder = app.updater_builder();
+    if let Some(outer) = outer_app_bundle() {
+        let exe_inside_outer = outer.join("Contents").join("MacOS").join("Eurora");
+        tracing::debug!(
+            "Using outer app executable path for updater: {}",
+            exe_inside_outer.display()
+        );
+        builder = builder.executable_path(&exe_inside_outer);
+    }
+    Ok(builder.build()?)
+}
+
+/// Count this start against a freshly installed version, and restart into
+/// the previous one if it has failed too often. Call first thing in
+/// `setup`.
+pub fn guard_boot(app: &AppHandle) {
+    let dir = match updates_dir(app) {
+        Ok(dir) => dir,
+        Err(e) => {
+            tracing::warn!("Skipping update boot check: {e}");
+            return;
+        }
+    };
+    match boot_guard::on_start(&dir, &app.package_info().version.to_string()) {
+        BootCheck::Normal => {}
+        BootCheck::Trial { attempt } => {
+            tracing::info!(attempt, "Starting a freshly installed update");
+        }
+        BootCheck::RolledBack { version } => {
+            tracing::warn!("Rolled back to {version}; restarting");
+            restart(app);
+        }
+    }
+}
+
+/// Mark the running version as working once it has stayed up for
+/// [`BOOT_CONFIRM_DELAY`]. Call when startup has finished.
+pub fn confirm_boot_later(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(BOOT_CONFIRM_DELAY).await;
+        match updates_dir(&app) {
+            Ok(dir) => boot_guard::confirm(&dir),
+            Err(e) => tracing::warn!("Skipping update boot confirmation: {e}"),
+        }
+    });
+}
+
+/// Poll the release channel and stage new versions, forever.
+pub fn spawn(app: AppHandle, transfers: TransferManager) {
+    tauri::async_runtime::spawn(async move {
+        // No overall timeout: a throttled bundle download can take a while.
+        let http = match reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(30))
+            .build()
+        {
+            Ok(http) => http,
+            Err(e) => {
+                tracing::error!("Background updates disabled: {e}");
+                return;
+            }
+        };
+        loop {
+            let next = match check_and_stage(&app, &http, &transfers).await {
+                Ok(()) => UPDATE_CHECK_INTERVAL,
+                Err(e) => {
+                    tracing::warn!("Background update failed: {e}");
+                    UPDATE_RETRY_INTERVAL
+                }
+            };
+            tokio::time::sleep(next).await;
+        }
+    });
+}
+
+async fn check_and_stage(
+    app: &AppHandle,
+    http: &reqwest::Client,
+    transfers: &TransferManager,
+) -> Result<(), UpdaterError> {
+    let Some(update) = updater(app)?.check().await? else {
+        return Ok(());
+    };
+    let state = app.state::<UpdaterState>();
+    if state
+        .ready
+        .lock()
+        .await
+        .as_ref()
+        .is_some_and(|ready| ready.update.version == update.version)
+    {
+        return Ok(());
+    }
+
+    let version = update.version.clone();
+    let staging = Staging::new(updates_dir(app)?);
+    staging.clean(Some(&version)).await;
+    let bundle = match staging.staged(&version) {
+        Some(bundle) => bundle,
+        None => match download(app, http, transfers, &staging, &update).await {
+            Ok(bundle) => bundle,
+            Err(e) => {
+                emit(
+                    app,
+                    UpdateProgress::Failed {
+                        version,
+                        error: e.to_string(),
+                    },
+                );
+                return Err(e);
+            }
+        },
+    };
+
+    tracing::info!(version, "Update staged");
+    *state.ready.lock().await = Some(ReadyUpdate { update, bundle });
+    emit(app, UpdateProgress::Ready { version });
+    Ok(())
+}
+
+async fn download(
+    app: &AppHandle,
+    http: &reqwest::Client,
+    transfers: &TransferManager,
+    staging: &Staging,
+    update: &Update,
+) -> Result<PathBuf, UpdaterError> {
+    let version = update.version.as_str();
+    let mut reported = None;
+    let part = staging
+        .download(
+            http,
+            transfers,
+            update.download_url.clone(),
+            version,
+            |downloaded, total| {
+                let due = reported.is_none_or(|last| {
+                    downloaded >= last + PROGRESS_STEP || Some(downloaded) == total
+                });
+                if due {
+                    reported = Some(downloaded);
+                    emit(
+                        app,
+                        UpdateProgress::Downloading {
+                            version: version.to_owned(),
+                            downloaded: downloaded as f64,
+                            total: total.map(|total| total as f64),
+                        },
+                    );
+                }
+            },
+        )
+        .await?;
+
+    emit(
+        app,
+        UpdateProgress::Verifying {
+            version: version.to_owned(),
+        },
+    );
+    let bytes = tokio::fs::read(&part).await?;
+    if let Err(e) = signature::verify(&bytes, &update.signature) {
+        staging.discard_partial(version).await;
+        return Err(e);
+    }
+    staging.promote(version).await
+}
+
+/// Install the staged update, if there is one, and return its version.
+/// The caller restarts or lets the app exit.
+pub async fn apply_staged(app: &AppHandle) -> Result<Option<String>, UpdaterError> {
+    let Some(state) = app.try_state::<UpdaterState>() else {
+        return Ok(None);
+    };
+    let Some(ready) = state.ready.lock().await.take() else {
+        return Ok(None);
+    };
+    let bytes = tokio::fs::read(&ready.bundle).await?;
+    install(app, &ready.update, &bytes)?;
+    Staging::new(updates_dir(app)?).clean(None).await;
+    Ok(Some(ready.update.version))
+}
+
+/// Check `bytes` against the pinned keys, set up the rollback and install
+/// them over the running version.
+pub fn install(app: &AppHandle, update: &Update, bytes: &[u8]) -> Result<(), UpdaterError> {
+    // Checked again: the bundle has sat on disk since it was downloaded.
+    signature::verify(bytes, &update.signature)?;
+    let dir = updates_dir(app)?;
+    if let Err(e) = boot_guard::prepare(&dir, &update.current_version, &update.version) {
+        tracing::warn!("Installing {} without a rollback copy: {e}", update.version);
+    }
+    tracing::info!(version = %update.version, "Installing update");
+    update.install(bytes)?;
+    Ok(())
+}
+
+/// Restart into whatever is installed now.
+pub fn restart(app: &AppHandle) -> ! {
+    if let Some(outer) = outer_app_bundle() {
+        tracing::info!(
+            "Scheduling restart of outer app bundle: {}",
+            outer.display()
+        );
+        let _ = std::process::Command::new("sh")
+            .args(["-c", "sleep 2 && exec open \"$1\"", "--"])
+            .arg(&outer)
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn();
+        std::process::exit(0);
+    }
+    app.restart()
+}
+
+fn emit(app: &AppHandle, progress: UpdateProgress) {
+    if let Err(e) = progress.emit(app) {
+        tracing::warn!("Failed to emit update progress: {e}");
+    }
+}
+
+/// The outermost of two or more nested `.app` bundles around the binary.
+#[cfg(target_os = "macos")]
+fn outer_app_bundle() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    let mut outermost: Option<PathBuf> = None;
+    let mut count = 0u32;
+    for ancestor in exe.ancestors() {
+        if ancestor.extension().is_some_and(|ext| ext == "app") {
+            outermost = Some(ancestor.to_path_buf());
+            count += 1;
+        }
+    }
+    if count >= 2 { outermost } else { None }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn outer_app_bundle() -> Option<PathBuf> {
+    None
+}
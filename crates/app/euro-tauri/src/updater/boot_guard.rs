@@ -0,0 +1,322 @@
+//! Rolling back an update that doesn't start.
+//!
+//! Before an update is installed, the current install is copied aside and
+//! a marker naming the new version is written. Every start of that
+//! version counts an attempt against the marker; [`confirm`] removes it
+//! once the app has stayed up. A version that fails to get there
+//! [`MAX_FAILED_BOOTS`] times is replaced by the copy on the next start.
+//!
+//! Copying the install aside works for the AppImage on Linux and the app
+//! bundle on macOS. Windows and the Linux system packages are installed
+//! by an installer that owns the files, so they get no copy and are never
+//! rolled back.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::UpdaterError;
+
+/// Starts a new version gets to reach [`confirm`] before it is rolled
+/// back.
+pub const MAX_FAILED_BOOTS: u32 = 2;
+
+const MARKER_FILE: &str = "pending-boot.json";
+const BACKUP_NAME: &str = "rollback";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PendingBoot {
+    previous_version: String,
+    version: String,
+    /// Copy of the previous install, and where it goes back to.
+    backup: Option<Backup>,
+    attempts: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Backup {
+    copy: PathBuf,
+    install: PathBuf,
+}
+
+/// What [`on_start`] found.
+#[derive(Debug, PartialEq, Eq)]
+pub enum BootCheck {
+    /// No update is on trial.
+    Normal,
+    /// This start is attempt `attempt` of a freshly installed version.
+    Trial { attempt: u32 },
+    /// The previous version was put back; restart into it.
+    RolledBack { version: String },
+}
+
+/// Copy the current install aside and record that `version` is about to
+/// replace `previous_version`. Call right before installing.
+pub fn prepare(dir: &Path, previous_version: &str, version: &str) -> Result<(), UpdaterError> {
+    let backup = match platform::install_location() {
+        Some(install) => {
+            let copy = dir.join(BACKUP_NAME);
+            platform::copy_install(&install, &copy)?;
+            Some(Backup { copy, install })
+        }
+        None => None,
+    };
+    write_marker(
+        dir,
+        &PendingBoot {
+            previous_version: previous_version.to_owned(),
+            version: version.to_owned(),
+            backup,
+            attempts: 0,
+        },
+    )
+}
+
+/// Count this start against a pending update, rolling it back if it has
+/// used up its attempts. Call first thing at startup.
+pub fn on_start(dir: &Path, current_version: &str) -> BootCheck {
+    let Some(mut pending) = read_marker(dir) else {
+        return BootCheck::Normal;
+    };
+    if pending.version != current_version {
+        // The install never took, or this is the rolled-back version.
+        clear(dir, &pending);
+        return BootCheck::Normal;
+    }
+
+    if pending.attempts >= MAX_FAILED_BOOTS {
+        tracing::error!(
+            version = %pending.version,
+            attempts = pending.attempts,
+            "Update failed to start; rolling back to {}",
+            pending.previous_version
+        );
+        let restored = match &pending.backup {
+            Some(backup) => platform::copy_install(&backup.copy, &backup.install),
+            None => Err(UpdaterError::RollbackUnavailable),
+        };
+        return match restored {
+            Ok(()) => {
+                clear(dir, &pending);
+                BootCheck::RolledBack {
+                    version: pending.previous_version,
+                }
+            }
+            Err(e) => {
+                // Nothing to go back to; stop counting so the app isn't
+                // rolled back on every start from here on.
+                tracing::error!("Rollback failed: {e}");
+                clear(dir, &pending);
+                BootCheck::Normal
+            }
+        };
+    }
+
+    pending.attempts += 1;
+    if let Err(e) = write_marker(dir, &pending) {
+        tracing::warn!("Failed to record update boot attempt: {e}");
+    }
+    BootCheck::Trial {
+        attempt: pending.attempts,
+    }
+}
+
+/// The running version works: drop the marker and the copy of the
+/// previous install.
+pub fn confirm(dir: &Path) {
+    if let Some(pending) = read_marker(dir) {
+        tracing::info!(version = %pending.version, "Update confirmed");
+        clear(dir, &pending);
+    }
+}
+
+fn clear(dir: &Path, pending: &PendingBoot) {
+    if let Some(backup) = &pending.backup {
+        let removed = if backup.copy.is_dir() {
+            std::fs::remove_dir_all(&backup.copy)
+        } else {
+            std::fs::remove_file(&backup.copy)
+        };
+        if let Err(e) = removed
+            && e.kind() != std::io::ErrorKind::NotFound
+        {
+            tracing::warn!("Failed to remove rollback copy: {e}");
+        }
+    }
+    if let Err(e) = std::fs::remove_file(dir.join(MARKER_FILE))
+        && e.kind() != std::io::ErrorKind::NotFound
+    {
+        tracing::warn!("Failed to remove update boot marker: {e}");
+    }
+}
+
+fn read_marker(dir: &Path) -> Option<PendingBoot> {
+    let raw = std::fs::read(dir.join(MARKER_FILE)).ok()?;
+    match serde_json::from_slice(&raw) {
+        Ok(pending) => Some(pending),
+        Err(e) => {
+            tracing::warn!("Ignoring unreadable update boot marker: {e}");
+            None
+        }
+    }
+}
+
+fn write_marker(dir: &Path, pending: &PendingBoot) -> Result<(), UpdaterError> {
+    let json = serde_json::to_vec_pretty(pending)?;
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(dir.join(MARKER_FILE), json)?;
+    Ok(())
+}
+
+cfg_select! {
+    target_os = "macos" => {
+        mod platform {
+            use std::path::{Path, PathBuf};
+
+            use crate::updater::UpdaterError;
+
+            /// The outermost `.app` bundle the running binary is in.
+            pub(super) fn install_location() -> Option<PathBuf> {
+                let exe = std::env::current_exe().ok()?;
+                exe.ancestors()
+                    .filter(|ancestor| ancestor.extension().is_some_and(|ext| ext == "app"))
+                    .last()
+                    .map(Path::to_path_buf)
+            }
+
+            /// Replace `to` with a copy of the bundle at `from`. `ditto`
+            /// keeps the code signature and extended attributes intact.
+            pub(super) fn copy_install(from: &Path, to: &Path) -> Result<(), UpdaterError> {
+                if to.exists() {
+                    std::fs::remove_dir_all(to)?;
+                }
+                let status = std::process::Command::new("ditto").arg(from).arg(to).status()?;
+                if !status.success() {
+                    return Err(UpdaterError::Io(std::io::Error::other(format!(
+                        "ditto exited with {status}"
+                    ))));
+                }
+                Ok(())
+            }
+        }
+    }
+    target_os = "linux" => {
+        mod platform {
+            use std::path::{Path, PathBuf};
+
+            use crate::updater::UpdaterError;
+
+            /// The AppImage being run. Package-manager installs have none.
+            pub(super) fn install_location() -> Option<PathBuf> {
+                std::env::var_os("APPIMAGE").map(PathBuf::from)
+            }
+
+            /// Replace the file at `to` with a copy of `from`, through a
+            /// temporary file next to it so `to` is never half-written.
+            pub(super) fn copy_install(from: &Path, to: &Path) -> Result<(), UpdaterError> {
+                let staging = to.with_extension("rollback-tmp");
+                std::fs::copy(from, &staging)?;
+                std::fs::rename(&staging, to)?;
+                Ok(())
+            }
+        }
+    }
+    _ => {
+        mod platform {
+            use std::path::{Path, PathBuf};
+
+            use crate::updater::UpdaterError;
+
+            pub(super) fn install_location() -> Option<PathBuf> {
+                None
+            }
+
+            pub(super) fn copy_install(_from: &Path, _to: &Path) -> Result<(), UpdaterError> {
+                Err(UpdaterError::RollbackUnavailable)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pending(dir: &Path, version: &str) {
+        write_marker(
+            dir,
+            &PendingBoot {
+                previous_version: "1.0.0".into(),
+                version: version.into(),
+                backup: None,
+                attempts: 0,
+            },
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn starts_count_until_confirmed() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(on_start(dir.path(), "1.0.0"), BootCheck::Normal);
+
+        pending(dir.path(), "1.1.0");
+        assert_eq!(
+            on_start(dir.path(), "1.1.0"),
+            BootCheck::Trial { attempt: 1 }
+        );
+        confirm(dir.path());
+        assert_eq!(on_start(dir.path(), "1.1.0"), BootCheck::Normal);
+    }
+
+    #[test]
+    fn a_version_that_never_started_is_forgotten() {
+        let dir = tempfile::tempdir().unwrap();
+        pending(dir.path(), "1.1.0");
+        assert_eq!(on_start(dir.path(), "1.0.0"), BootCheck::Normal);
+        assert!(read_marker(dir.path()).is_none());
+    }
+
+    #[test]
+    fn failed_starts_without_a_copy_stop_counting() {
+        let dir = tempfile::tempdir().unwrap();
+        pending(dir.path(), "1.1.0");
+        for attempt in 1..=MAX_FAILED_BOOTS {
+            assert_eq!(on_start(dir.path(), "1.1.0"), BootCheck::Trial { attempt });
+        }
+        assert_eq!(on_start(dir.path(), "1.1.0"), BootCheck::Normal);
+        assert!(read_marker(dir.path()).is_none());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn failed_starts_restore_the_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let install = dir.path().join("Eurora.AppImage");
+        let copy = dir.path().join(BACKUP_NAME);
+        std::fs::write(&install, b"new").unwrap();
+        std::fs::write(&copy, b"old").unwrap();
+        write_marker(
+            dir.path(),
+            &PendingBoot {
+                previous_version: "1.0.0".into(),
+                version: "1.1.0".into(),
+                backup: Some(Backup {
+                    copy: copy.clone(),
+                    install: install.clone(),
+                }),
+                attempts: MAX_FAILED_BOOTS,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            on_start(dir.path(), "1.1.0"),
+            BootCheck::RolledBack {
+                version: "1.0.0".into()
+            }
+        );
+        assert_eq!(std::fs::read(&install).unwrap(), b"old");
+        assert!(!copy.exists());
+    }
+}
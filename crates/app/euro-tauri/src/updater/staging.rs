@@ -0,0 +1,175 @@
+//! Downloading update bundles into the updates directory, resuming where
+//! an interrupted download stopped.
+//!
+//! A bundle downloads to `<version>.part`. Once complete and verified it
+//! is renamed to `<version>.bundle`, where it waits to be installed.
+
+use std::path::{Path, PathBuf};
+use std::pin::pin;
+
+use euro_transfer::{Direction, Priority, TransferManager};
+use futures::StreamExt;
+use reqwest::StatusCode;
+use tokio::io::AsyncWriteExt;
+
+use super::UpdaterError;
+
+pub struct Staging {
+    dir: PathBuf,
+}
+
+impl Staging {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The verified bundle for `version`, if one is waiting.
+    pub fn staged(&self, version: &str) -> Option<PathBuf> {
+        let path = self.bundle_path(version);
+        path.is_file().then_some(path)
+    }
+
+    /// Download the bundle for `version` from `url`, continuing a partial
+    /// download if there is one. `on_progress` gets the bytes on disk and
+    /// the total, when the server says. Returns the partial file, now
+    /// complete; pass it to [`Self::promote`] once verified.
+    pub async fn download(
+        &self,
+        http: &reqwest::Client,
+        transfers: &TransferManager,
+        url: reqwest::Url,
+        version: &str,
+        mut on_progress: impl FnMut(u64, Option<u64>),
+    ) -> Result<PathBuf, UpdaterError> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let part = self.part_path(version);
+        let mut offset = match tokio::fs::metadata(&part).await {
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        };
+
+        let permit = transfers
+            .acquire(Direction::Download, Priority::Background)
+            .await;
+        let mut request = http.get(url);
+        if offset > 0 {
+            tracing::info!(version, offset, "Resuming update download");
+            request = request.header(reqwest::header::RANGE, format!("bytes={offset}-"));
+        }
+        let response = request.send().await?;
+
+        let status = response.status();
+        if status == StatusCode::RANGE_NOT_SATISFIABLE {
+            // The partial file is as long as the bundle, or longer than a
+            // bundle that changed under it; start over either way.
+            tokio::fs::remove_file(&part).await?;
+            return Err(UpdaterError::Download(
+                "partial download no longer matches the bundle".into(),
+            ));
+        }
+        if !status.is_success() {
+            return Err(UpdaterError::Download(format!(
+                "update server returned {status}"
+            )));
+        }
+        // A server that ignores the range sends the whole bundle again.
+        if status != StatusCode::PARTIAL_CONTENT {
+            offset = 0;
+        }
+        let total = response.content_length().map(|len| len + offset);
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(offset > 0)
+            .truncate(offset == 0)
+            .open(&part)
+            .await?;
+        let mut body = pin!(permit.throttle(response.bytes_stream()));
+        let mut downloaded = offset;
+        on_progress(downloaded, total);
+        while let Some(chunk) = body.next().await {
+            let chunk = chunk?;
+            file.write_all(&chunk).await?;
+            downloaded += chunk.len() as u64;
+            on_progress(downloaded, total);
+        }
+        file.flush().await?;
+
+        if total.is_some_and(|total| downloaded != total) {
+            return Err(UpdaterError::Download(format!(
+                "download ended at {downloaded} of {} bytes",
+                total.unwrap_or_default()
+            )));
+        }
+        Ok(part)
+    }
+
+    /// Mark the downloaded bundle for `version` as verified.
+    pub async fn promote(&self, version: &str) -> Result<PathBuf, UpdaterError> {
+        let bundle = self.bundle_path(version);
+        tokio::fs::rename(self.part_path(version), &bundle).await?;
+        Ok(bundle)
+    }
+
+    /// Delete the partial download for `version`, so the next attempt
+    /// starts from scratch.
+    pub async fn discard_partial(&self, version: &str) {
+        if let Err(e) = tokio::fs::remove_file(self.part_path(version)).await
+            && e.kind() != std::io::ErrorKind::NotFound
+        {
+            tracing::warn!("Failed to delete partial update download: {e}");
+        }
+    }
+
+    /// Delete every download except those for `keep`, such as bundles for
+    /// versions that have since been superseded or installed.
+    pub async fn clean(&self, keep: Option<&str>) {
+        let Ok(mut entries) = tokio::fs::read_dir(&self.dir).await else {
+            return;
+        };
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let path = entry.path();
+            let Some(version) = download_version(&path) else {
+                continue;
+            };
+            if Some(version) != keep
+                && let Err(e) = tokio::fs::remove_file(&path).await
+            {
+                tracing::warn!("Failed to delete old update download: {e}");
+            }
+        }
+    }
+
+    fn part_path(&self, version: &str) -> PathBuf {
+        self.dir.join(format!("{version}.part"))
+    }
+
+    fn bundle_path(&self, version: &str) -> PathBuf {
+        self.dir.join(format!("{version}.bundle"))
+    }
+}
+
+/// The version a `.part` or `.bundle` file belongs to.
+fn download_version(path: &Path) -> Option<&str> {
+    match path.extension()?.to_str()? {
+        "part" | "bundle" => path.file_stem()?.to_str(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_downloads_are_cleaned() {
+        assert_eq!(download_version(Path::new("/u/1.2.0.part")), Some("1.2.0"));
+        assert_eq!(
+            download_version(Path::new("/u/1.2.0.bundle")),
+            Some("1.2.0")
+        );
+        assert_eq!(download_version(Path::new("/u/pending-boot.json")), None);
+        assert_eq!(download_version(Path::new("/u/rollback")), None);
+    }
+}
@@ -1,7 +1,6 @@
 use tauri::Manager;
 use tauri_plugin_global_shortcut::{Code, Modifiers, Shortcut};
 
-#[allow(unused)]
 pub fn string_modifiers_to_tauri(modifiers: &[String]) -> Option<Modifiers> {
     let mut tauri_modifiers = Modifiers::empty();
 
@@ -22,7 +21,6 @@ pub fn string_modifiers_to_tauri(modifiers: &[String]) -> Option<Modifiers> {
     }
 }
 
-#[allow(unused)]
 pub fn string_key_to_tauri_code(key: &str) -> Option<Code> {
     match key.to_lowercase().as_str() {
         "space" => Some(Code::Space),
@@ -101,7 +99,6 @@ pub fn string_key_to_tauri_code(key: &str) -> Option<Code> {
     }
 }
 
-#[allow(unused)]
 pub fn get_default_shortcut() -> Shortcut {
     #[cfg(target_os = "macos")]
     return Shortcut::new(Some(Modifiers::ALT), Code::Space);
@@ -116,6 +113,20 @@ pub fn get_default_shortcut() -> Shortcut {
     return Shortcut::new(Some(Modifiers::CONTROL), Code::Space);
 }
 
+/// The launcher shortcut plus Shift.
+pub fn get_default_region_capture_shortcut() -> Shortcut {
+    let launcher = get_default_shortcut();
+    Shortcut::new(Some(launcher.mods | Modifiers::SHIFT), launcher.key)
+}
+
+/// Parse a shortcut from settings, or `None` if any part is unknown.
+pub fn hotkey_to_shortcut(hotkey: &euro_settings::Hotkey) -> Option<Shortcut> {
+    Some(Shortcut::new(
+        Some(string_modifiers_to_tauri(&hotkey.modifiers)?),
+        string_key_to_tauri_code(&hotkey.key)?,
+    ))
+}
+
 pub fn get_db_path(app_handle: &tauri::AppHandle) -> Result<String, String> {
     let base_path = app_handle
         .path()
@@ -123,6 +134,6 @@ pub fn get_db_path(app_handle: &tauri::AppHandle) -> Result<String, String> {
         .map_err(|e| format!("Failed to resolve app data directory: {e}"))?;
     std::fs::create_dir_all(&base_path)
         .map_err(|e| format!("Failed to create app data directory: {e}"))?;
-    let db_path = base_path.join("personal_database.sqlite");
+    let db_path = base_path.join(euro_personal_db::DB_FILE_NAME);
     Ok(db_path.to_string_lossy().to_string())
 }
@@ -0,0 +1,134 @@
+//! Desktop wiring for [`euro_voice`].
+//!
+//! The recognizer is built from the `voice` section of `local.json` on
+//! the first push-to-talk and kept, since loading a Whisper model takes
+//! seconds. `settings_set_voice` drops it so the next utterance picks up
+//! the new settings.
+//!
+//! The speech reader is built on the first response read aloud, from the
+//! voice and rate in the synced desktop settings, and rebuilt whenever
+//! those change, including through sync from another device.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use euro_settings::{SettingsState, SpeechRate, VoiceProviderSettings, VoiceSettings};
+use euro_voice::{
+    PlatformSpeech, ProviderTranscriber, SpeechBackend, SpeechReader, Transcriber, VoiceConfig,
+    VoiceError, VoiceResult,
+};
+use parking_lot::Mutex;
+
+pub use euro_voice::VoiceSession;
+
+/// Tauri state for voice: the cached recognizer, the utterance in
+/// progress and the speech reader, if any.
+#[derive(Default)]
+pub struct VoiceState {
+    pub transcriber: Mutex<Option<Arc<dyn Transcriber>>>,
+    pub session: Mutex<Option<VoiceSession>>,
+    pub reader: Mutex<Option<(SpeechSetup, SpeechReader)>>,
+}
+
+/// Whether audio or text may go to `provider`. In local-only mode only a
+/// provider on this machine qualifies.
+fn provider_allowed(provider: &VoiceProviderSettings, local_only: bool) -> bool {
+    !local_only || url::Url::parse(&provider.base_url).is_ok_and(|url| llm_core::is_loopback(&url))
+}
+
+/// Build the recognizer `settings` describe: the local Whisper model when
+/// the build supports it and one is set, otherwise the hosted provider.
+pub fn transcriber_from_settings(
+    settings: &VoiceSettings,
+    http: &reqwest::Client,
+    local_only: bool,
+) -> VoiceResult<Arc<dyn Transcriber>> {
+    #[cfg(feature = "whisper")]
+    if let Some(path) = &settings.whisper_model {
+        match euro_voice::WhisperTranscriber::load(std::path::Path::new(path)) {
+            Ok(whisper) => return Ok(Arc::new(whisper)),
+            Err(e) if settings.provider.is_some() => {
+                tracing::warn!(error = %e, "Whisper model failed to load, using provider ASR");
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    let provider = settings
+        .provider
+        .as_ref()
+        .filter(|provider| provider_allowed(provider, local_only))
+        .ok_or(VoiceError::NoTranscriber)?;
+    Ok(Arc::new(ProviderTranscriber::new(
+        http.clone(),
+        &provider.base_url,
+        provider.api_key.clone().into(),
+        provider.model.clone(),
+    )))
+}
+
+pub fn config_from_settings(settings: &VoiceSettings) -> VoiceConfig {
+    VoiceConfig {
+        language: settings.language.clone(),
+        auto_submit_after: settings
+            .auto_submit_after_ms
+            .map(|ms| Duration::from_millis(ms.into())),
+        ..Default::default()
+    }
+}
+
+/// The settings a speech reader was built from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpeechSetup {
+    voice: Option<String>,
+    rate: SpeechRate,
+    provider: Option<VoiceProviderSettings>,
+}
+
+impl SpeechSetup {
+    pub fn from_settings(settings: &SettingsState, local_only: bool) -> Self {
+        let desktop = &settings.cache.settings.desktop;
+        Self {
+            voice: desktop.speech_voice.clone(),
+            rate: desktop.speech_rate,
+            provider: settings
+                .local
+                .voice
+                .speech_provider
+                .clone()
+                .filter(|provider| provider_allowed(provider, local_only)),
+        }
+    }
+
+    /// A constructor for the backend, to run on the reader's thread.
+    /// Hosted speech is only used when the build supports it; otherwise
+    /// the platform voices are.
+    pub fn backend(
+        &self,
+        http: &reqwest::Client,
+    ) -> impl FnOnce() -> VoiceResult<Box<dyn SpeechBackend>> + Send + 'static {
+        let setup = self.clone();
+        #[cfg(feature = "provider-tts")]
+        let (http, runtime) = (http.clone(), tokio::runtime::Handle::current());
+        #[cfg(not(feature = "provider-tts"))]
+        let _ = http;
+        move || -> VoiceResult<Box<dyn SpeechBackend>> {
+            #[cfg(feature = "provider-tts")]
+            if let Some(provider) = setup.provider {
+                return Ok(Box::new(euro_voice::ProviderSpeech::new(
+                    http,
+                    runtime,
+                    &provider.base_url,
+                    provider.api_key.into(),
+                    provider.model,
+                    setup.voice,
+                    setup.rate.get(),
+                )?));
+            }
+            Ok(Box::new(PlatformSpeech::new(
+                setup.voice.as_deref(),
+                setup.rate.get(),
+            )?))
+        }
+    }
+}
@@ -11,9 +11,11 @@
 //! crate.
 
 use std::sync::Mutex;
+use std::sync::atomic::Ordering;
 
 use sentry::ClientInitGuard;
 
+use crate::crash::SENTRY_ACTIVE;
 use crate::{RELEASE_CHANNEL, RELEASE_VERSION, SENTRY_DSN, scrub};
 
 /// Owns the live Sentry guard. The guard's `Drop` impl flushes pending
@@ -38,8 +40,10 @@ impl Controller {
     /// the operator-side join key, not a PII channel).
     #[must_use]
     pub fn init(enabled: bool, distinct_id: Option<&str>) -> Self {
+        let guard = build_guard(enabled);
+        SENTRY_ACTIVE.store(guard.is_some(), Ordering::Relaxed);
         let controller = Self {
-            guard: Mutex::new(build_guard(enabled)),
+            guard: Mutex::new(guard),
         };
         if controller.is_active() {
             register_panic_hook();
@@ -70,6 +74,7 @@ impl Controller {
     pub fn reapply(&self, enabled: bool, distinct_id: Option<&str>) {
         let new_guard = build_guard(enabled);
         let active = new_guard.is_some();
+        SENTRY_ACTIVE.store(active, Ordering::Relaxed);
         let old = {
             let mut slot = self.guard.lock().expect("telemetry guard mutex poisoned");
             std::mem::replace(&mut *slot, new_guard)
@@ -107,7 +112,13 @@ fn build_guard(enabled: bool) -> Option<ClientInitGuard> {
         return None;
     }
 
-    let options = sentry::ClientOptions {
+    Some(sentry::init((SENTRY_DSN, client_options())))
+}
+
+/// Options shared by the live client and the one that uploads saved
+/// crash reports, so both are scrubbed and tagged the same way.
+pub(crate) fn client_options() -> sentry::ClientOptions {
+    sentry::ClientOptions {
         release: Some(RELEASE_VERSION.into()),
         environment: Some(RELEASE_CHANNEL.into()),
         send_default_pii: false,
@@ -115,9 +126,7 @@ fn build_guard(enabled: bool) -> Option<ClientInitGuard> {
         traces_sample_rate: 0.0,
         before_send: Some(std::sync::Arc::new(scrub::scrub_event)),
         ..Default::default()
-    };
-
-    Some(sentry::init((SENTRY_DSN, options)))
+    }
 }
 
 /// `Once`-guarded panic hook installer. Forwards to the previous hook
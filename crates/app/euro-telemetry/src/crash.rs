@@ -0,0 +1,273 @@
+//! Crash reports kept on disk until the user decides what to do with them.
+//!
+//! While error reporting is on, panics reach Sentry live through the
+//! [`Controller`](crate::Controller)'s hook. While it's off they would be
+//! lost, so [`install_crash_recorder`] writes each one to a JSON file in
+//! the crash directory instead: the panic message and location, the
+//! thread, a backtrace, and the release, channel and target it happened
+//! on so the backtrace can be symbolicated against the right build.
+//!
+//! On the next launch the app lists them with [`pending_crash_reports`]
+//! and asks whether to send them. A yes goes through
+//! [`send_crash_reports`], which uploads those reports and nothing else,
+//! through a one-off Sentry client scrubbed like the live one. A no goes
+//! through [`discard_crash_reports`]. Only the newest
+//! [`MAX_CRASH_REPORTS`] are kept either way.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{RELEASE_CHANNEL, RELEASE_VERSION, SENTRY_DSN};
+
+/// Reports kept on disk. Older ones are deleted as new ones are written.
+pub const MAX_CRASH_REPORTS: usize = 10;
+
+/// How long [`send_crash_reports`] waits for the upload to finish.
+const SEND_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Set while a Sentry client is live, so the recorder leaves panics to it.
+pub(crate) static SENTRY_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrashReport {
+    /// File stem of the report, unique per crash.
+    pub id: String,
+    /// Milliseconds since the Unix epoch.
+    pub crashed_at_ms: u64,
+    pub message: String,
+    /// `file:line:column` of the panic.
+    pub location: Option<String>,
+    pub thread: Option<String>,
+    pub backtrace: String,
+    pub release: String,
+    pub channel: String,
+    /// `<os>-<arch>`, e.g. `macos-aarch64`.
+    pub target: String,
+}
+
+/// Where the desktop app keeps its crash reports.
+#[must_use]
+pub fn default_crash_dir() -> Option<PathBuf> {
+    Some(dirs::data_local_dir()?.join("eurora").join("crashes"))
+}
+
+/// Whether this build can upload crash reports at all. Dev builds have no
+/// DSN, so there is nothing to ask the user.
+#[must_use]
+pub fn can_send_crash_reports() -> bool {
+    !SENTRY_DSN.is_empty()
+}
+
+/// Write a report to `dir` for every panic Sentry isn't around to see.
+/// Chains to the previous hook, so the panic is still printed. Only the
+/// first call installs the hook.
+pub fn install_crash_recorder(dir: PathBuf) {
+    static HOOK_INSTALLED: std::sync::Once = std::sync::Once::new();
+    HOOK_INSTALLED.call_once(|| {
+        let next = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            if !SENTRY_ACTIVE.load(Ordering::Relaxed) {
+                record(&dir, info);
+            }
+            next(info);
+        }));
+    });
+}
+
+fn record(dir: &Path, info: &std::panic::PanicHookInfo<'_>) {
+    let message = info
+        .payload()
+        .downcast_ref::<&str>()
+        .map(|s| (*s).to_owned())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "<non-string panic payload>".to_owned());
+    let crashed_at_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX));
+    let report = CrashReport {
+        id: format!("crash-{crashed_at_ms}-{}", std::process::id()),
+        crashed_at_ms,
+        message,
+        location: info.location().map(ToString::to_string),
+        thread: std::thread::current().name().map(str::to_owned),
+        backtrace: std::backtrace::Backtrace::force_capture().to_string(),
+        release: RELEASE_VERSION.to_owned(),
+        channel: RELEASE_CHANNEL.to_owned(),
+        target: format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH),
+    };
+
+    // Best effort: the process is going down, and there's no one to tell.
+    let Ok(json) = serde_json::to_vec_pretty(&report) else {
+        return;
+    };
+    if std::fs::create_dir_all(dir).is_err() {
+        return;
+    }
+    let _ = std::fs::write(dir.join(format!("{}.json", report.id)), json);
+    prune(dir);
+}
+
+/// Delete all but the newest [`MAX_CRASH_REPORTS`] reports.
+fn prune(dir: &Path) {
+    let mut paths = report_paths(dir);
+    if paths.len() > MAX_CRASH_REPORTS {
+        let excess = paths.len() - MAX_CRASH_REPORTS;
+        for path in paths.drain(..excess) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Report files in `dir`, oldest first. Ids start with the crash time, so
+/// sorting by name sorts by age.
+fn report_paths(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension().is_some_and(|ext| ext == "json")
+                && path
+                    .file_name()
+                    .is_some_and(|name| name.to_string_lossy().starts_with("crash-"))
+        })
+        .collect();
+    paths.sort();
+    paths
+}
+
+/// Reports waiting for the user's decision, oldest first. Files that
+/// can't be read are skipped.
+#[must_use]
+pub fn pending_crash_reports(dir: &Path) -> Vec<CrashReport> {
+    report_paths(dir)
+        .into_iter()
+        .filter_map(|path| {
+            let bytes = std::fs::read(&path).ok()?;
+            serde_json::from_slice(&bytes).ok()
+        })
+        .collect()
+}
+
+/// Delete every report in `dir`.
+pub fn discard_crash_reports(dir: &Path) {
+    for path in report_paths(dir) {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+/// Upload every report in `dir`, then delete them. Blocks until the upload
+/// finishes or times out, so call it off the async executor. Returns how
+/// many were sent; zero when this build has no DSN, in which case the
+/// reports are left alone.
+pub fn send_crash_reports(dir: &Path) -> usize {
+    if !can_send_crash_reports() {
+        return 0;
+    }
+    let reports = pending_crash_reports(dir);
+    if reports.is_empty() {
+        return 0;
+    }
+
+    // The defaults bring the transport and the debug-images integration,
+    // which lists the loaded binaries the backtrace is symbolicated with.
+    let options = sentry::apply_defaults((SENTRY_DSN, crate::controller::client_options()).into());
+    let client = sentry::Client::from(options);
+    for report in &reports {
+        client.capture_event(to_event(report), None);
+    }
+    client.close(Some(SEND_TIMEOUT));
+    discard_crash_reports(dir);
+    tracing::info!(count = reports.len(), "Sent crash reports");
+    reports.len()
+}
+
+fn to_event(report: &CrashReport) -> sentry::protocol::Event<'static> {
+    use sentry::protocol::{Event, Exception, Level, Map};
+
+    let mut extra = Map::new();
+    extra.insert("backtrace".into(), report.backtrace.clone().into());
+    extra.insert("location".into(), report.location.clone().into());
+    extra.insert("thread".into(), report.thread.clone().into());
+    let mut tags = Map::new();
+    tags.insert("target".into(), report.target.clone());
+    tags.insert("recorded".into(), "offline".into());
+
+    Event {
+        level: Level::Fatal,
+        timestamp: UNIX_EPOCH + Duration::from_millis(report.crashed_at_ms),
+        release: Some(report.release.clone().into()),
+        environment: Some(report.channel.clone().into()),
+        exception: vec![Exception {
+            ty: "panic".into(),
+            value: Some(report.message.clone()),
+            ..Default::default()
+        }]
+        .into(),
+        extra,
+        tags,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(id: &str) -> CrashReport {
+        CrashReport {
+            id: id.to_owned(),
+            crashed_at_ms: 0,
+            message: "boom".to_owned(),
+            location: Some("src/main.rs:1:1".to_owned()),
+            thread: Some("main".to_owned()),
+            backtrace: String::new(),
+            release: "0.0.0".to_owned(),
+            channel: "dev".to_owned(),
+            target: "linux-x86_64".to_owned(),
+        }
+    }
+
+    fn write(dir: &Path, report: &CrashReport) {
+        std::fs::create_dir_all(dir).unwrap();
+        let json = serde_json::to_vec(report).unwrap();
+        std::fs::write(dir.join(format!("{}.json", report.id)), json).unwrap();
+    }
+
+    #[test]
+    fn only_the_newest_reports_are_kept() {
+        let dir = std::env::temp_dir().join(format!("euro-crash-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        for n in 0..MAX_CRASH_REPORTS + 2 {
+            write(&dir, &report(&format!("crash-{n:04}")));
+        }
+        std::fs::write(dir.join("notes.txt"), "not a report").unwrap();
+
+        prune(&dir);
+        let pending = pending_crash_reports(&dir);
+        assert_eq!(pending.len(), MAX_CRASH_REPORTS);
+        assert_eq!(pending[0].id, "crash-0002");
+
+        discard_crash_reports(&dir);
+        assert!(pending_crash_reports(&dir).is_empty());
+        assert!(dir.join("notes.txt").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn events_carry_the_build_they_came_from() {
+        let event = to_event(&report("crash-1"));
+        assert_eq!(event.release.as_deref(), Some("0.0.0"));
+        assert_eq!(event.environment.as_deref(), Some("dev"));
+        assert_eq!(event.exception.values[0].value.as_deref(), Some("boom"));
+        assert_eq!(
+            event.tags.get("target").map(String::as_str),
+            Some("linux-x86_64")
+        );
+    }
+}
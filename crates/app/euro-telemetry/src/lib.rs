@@ -10,6 +10,8 @@
 //!   crate's `build.rs` ([`SENTRY_DSN`], [`POSTHOG_KEY`], etc.).
 //! * The path-scrubbing `before_send` hook that strips the user's home
 //!   directory from every string-bearing field of an outgoing event.
+//! * Crash reports for panics Sentry wasn't running to see ([`crash`]):
+//!   written to disk, then sent or discarded on the user's say-so.
 //!
 //! ## What it doesn't own
 //!
@@ -32,6 +34,7 @@
 //! events to a stale project.
 
 mod controller;
+pub mod crash;
 mod scrub;
 
 pub use controller::Controller;
@@ -426,6 +426,7 @@ mod tests {
             parent_message_id: None,
             asset_chips_json: None,
             activity_id: None,
+            request_id: None,
         })
     }
 
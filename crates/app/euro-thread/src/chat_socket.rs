@@ -25,6 +25,7 @@ use tokio_tungstenite::{Connector, MaybeTlsStream, WebSocketStream};
 use tokio_util::sync::CancellationToken;
 
 use crate::error::{Error, Result};
+use crate::trace_context::TRACEPARENT;
 
 /// Owned handle to a chat WebSocket.
 ///
@@ -72,9 +73,12 @@ impl ChatOutbound {
 
 impl ChatSocket {
     /// Open a chat WebSocket against `url`, authenticating with `bearer`.
+    /// `traceparent` is forwarded on the handshake so the server-side
+    /// turn joins the caller's trace.
     pub async fn connect(
         url: reqwest::Url,
         bearer: String,
+        traceparent: String,
         cancel: CancellationToken,
     ) -> Result<Self> {
         let mut req = url
@@ -86,6 +90,11 @@ impl ChatSocket {
             HeaderValue::from_str(&bearer)
                 .map_err(|e| Error::ChatProtocol(format!("Invalid bearer header: {e}")))?,
         );
+        req.headers_mut().insert(
+            TRACEPARENT,
+            HeaderValue::from_str(&traceparent)
+                .map_err(|e| Error::ChatProtocol(format!("Invalid traceparent header: {e}")))?,
+        );
 
         let (stream, _response) = tokio_tungstenite::connect_async_tls_with_config(
             req,
@@ -261,6 +270,7 @@ mod tests {
             parent_message_id: None,
             asset_chips_json: None,
             activity_id: None,
+            request_id: None,
         })
     }
 
@@ -21,6 +21,7 @@
 pub mod chat;
 pub mod context;
 pub mod error;
+pub mod local_model;
 pub mod state;
 pub mod thread;
 
@@ -70,7 +70,10 @@ pub async fn chat_regenerate(
         &app_handle,
         thread_id,
         channel,
-        TurnOpening::Regenerate(RegenerateRequest { ai_message_id }),
+        TurnOpening::Regenerate(RegenerateRequest {
+            ai_message_id,
+            request_id: None,
+        }),
     )
     .await
 }
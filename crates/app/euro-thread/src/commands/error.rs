@@ -6,7 +6,8 @@ use thiserror::Error;
 /// Externally tagged so the JS side can branch on `error.type` without
 /// parsing strings. `NotFound` lifts [`crate::Error::ThreadNotFound`] to
 /// a dedicated variant so the UI can render an empty state instead of a
-/// generic toast.
+/// generic toast; `LocalOnly` does the same for requests local-only
+/// mode refused.
 #[derive(Debug, Error, Serialize, Type)]
 #[serde(tag = "type", content = "data")]
 pub enum ThreadError {
@@ -20,6 +21,8 @@ pub enum ThreadError {
     StateUnavailable(&'static str),
     #[error("internal: {0}")]
     Internal(String),
+    #[error("{0}")]
+    LocalOnly(String),
 }
 
 impl From<crate::Error> for ThreadError {
@@ -29,6 +32,8 @@ impl From<crate::Error> for ThreadError {
             E::ThreadNotFound => ThreadError::NotFound,
             E::Transport(ref e) => ThreadError::Backend(e.to_string()),
             E::WebSocket(ref e) => ThreadError::Backend(e.to_string()),
+            E::PullInterrupted => ThreadError::Backend(err.to_string()),
+            E::LocalOnly(message) => ThreadError::LocalOnly(message),
             E::Service { .. } => ThreadError::BadResponse(err.to_string()),
             E::Encode(e) | E::Decode(e) => ThreadError::BadResponse(e.to_string()),
             E::Auth(_) | E::InvalidUrl(_) | E::ChatProtocol(_) | E::Sink(_) | E::Cancelled => {
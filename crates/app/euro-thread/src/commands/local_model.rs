@@ -0,0 +1,48 @@
+//! Local-mode model management for the desktop model picker: list, pull
+//! and delete the models on the backend's Ollama server. Desktop only;
+//! mobile never talks to a local-mode backend.
+
+use tauri::{AppHandle, ipc::Channel};
+use thread_core::{LocalModel, LocalModelPullEvent};
+
+use super::error::ThreadError;
+use super::thread::thread_manager;
+
+/// `None` when the backend isn't in local mode, so the picker can hide its
+/// model manager.
+#[tauri::command]
+#[specta::specta]
+pub async fn local_model_list(
+    app_handle: AppHandle,
+) -> Result<Option<Vec<LocalModel>>, ThreadError> {
+    let manager = thread_manager(&app_handle, ThreadError::StateUnavailable)?;
+    Ok(manager.list_local_models().await?)
+}
+
+/// Pull `model`, forwarding progress to `channel`. Resolves once the pull
+/// is done or has failed; the last event on `channel` says which.
+#[tauri::command]
+#[specta::specta]
+pub async fn local_model_pull(
+    app_handle: AppHandle,
+    model: String,
+    channel: Channel<LocalModelPullEvent>,
+) -> Result<(), ThreadError> {
+    let manager = thread_manager(&app_handle, ThreadError::StateUnavailable)?;
+    Ok(manager
+        .pull_local_model(model, |event| {
+            // The pull carries on if the window went away; the next list
+            // shows the outcome.
+            if let Err(e) = channel.send(event) {
+                tracing::debug!(error = %e, "Dropped model pull progress");
+            }
+        })
+        .await?)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn local_model_delete(app_handle: AppHandle, model: String) -> Result<(), ThreadError> {
+    let manager = thread_manager(&app_handle, ThreadError::StateUnavailable)?;
+    Ok(manager.delete_local_model(model).await?)
+}
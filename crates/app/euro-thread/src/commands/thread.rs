@@ -84,9 +84,10 @@ pub async fn thread_switch_branch(
 pub async fn thread_generate_title(
     app_handle: AppHandle,
     thread_id: Uuid,
+    regenerate: bool,
 ) -> Result<Thread, ThreadError> {
     let manager = thread_manager(&app_handle, ThreadError::StateUnavailable)?;
-    Ok(manager.generate_thread_title(thread_id).await?)
+    Ok(manager.generate_thread_title(thread_id, regenerate).await?)
 }
 
 #[tauri::command]
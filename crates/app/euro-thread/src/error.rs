@@ -41,6 +41,12 @@ pub enum Error {
 
     #[error("Chat event sink failed: {0}")]
     Sink(#[source] ChatSinkError),
+
+    #[error("Model pull ended before it finished")]
+    PullInterrupted,
+
+    #[error("{0}")]
+    LocalOnly(String),
 }
 
 impl Error {
@@ -48,6 +54,12 @@ impl Error {
         if status == reqwest::StatusCode::NOT_FOUND {
             return Self::ThreadNotFound;
         }
+        Self::service(status, body)
+    }
+
+    /// Like [`Self::from_response`], but keeps the server's message for a
+    /// `404` too, for routes where it isn't about a thread.
+    pub(crate) fn service(status: reqwest::StatusCode, body: &str) -> Self {
         match serde_json::from_str::<ThreadErrorResponse>(body) {
             Ok(parsed) => Self::Service {
                 status,
@@ -9,6 +9,7 @@ mod chat_bridge;
 mod chat_socket;
 mod error;
 mod manager;
+mod trace_context;
 
 #[cfg(feature = "tauri")]
 pub mod commands;
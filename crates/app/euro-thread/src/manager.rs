@@ -16,16 +16,23 @@ use secrecy::ExposeSecret;
 use serde::de::DeserializeOwned;
 use serde::ser::Serialize;
 use thread_core::{
-    CreateThreadRequest, CreateThreadResponse, DeleteThreadResponse, GenerateThreadTitleRequest,
-    GenerateThreadTitleResponse, GetMessagesQuery, GetMessagesResponse, GetThreadResponse,
-    ListThreadsQuery, ListThreadsResponse, MessageNode, SearchMessagesQuery,
-    SearchMessagesResponse, SearchThreadsQuery, SearchThreadsResponse, SwitchBranchRequest, Thread,
+    CreateThreadFolderRequest, CreateThreadRequest, CreateThreadResponse, DeleteLocalModelQuery,
+    DeleteMemoryResponse, DeleteThreadFolderResponse, DeleteThreadResponse,
+    GenerateThreadTitleRequest, GenerateThreadTitleResponse, GetMessagesQuery, GetMessagesResponse,
+    GetThreadResponse, ListLocalModelsResponse, ListMemoriesResponse, ListProviderModelsResponse,
+    ListThreadFoldersResponse, ListThreadsQuery, ListThreadsResponse, LocalModel,
+    LocalModelPullEvent, Memory, MemoryRequest, MemoryResponse, MessageNode, MoveThreadRequest,
+    MoveThreadResponse, ProviderModel, PullLocalModelRequest, SearchMessagesQuery,
+    SearchMessagesResponse, SearchThreadsQuery, SearchThreadsResponse, SetThreadModelRequest,
+    SetThreadModelResponse, SwitchBranchRequest, Thread, ThreadFolder, ThreadFolderResponse,
+    ThreadModel, UpdateThreadFolderRequest, UpdateThreadRequest, UpdateThreadResponse,
 };
 use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
 use crate::chat_socket::ChatSocket;
 use crate::error::{Error, Result};
+use crate::trace_context::{TRACEPARENT, TraceParent};
 
 /// HTTP / WebSocket client for the thread service.
 ///
@@ -84,6 +91,7 @@ impl ThreadManager {
             .http
             .get(self.url(path))
             .header(header::AUTHORIZATION, bearer)
+            .header(TRACEPARENT, traced(path))
             .send()
             .await?;
         decode(response).await
@@ -99,6 +107,7 @@ impl ThreadManager {
             .http
             .get(self.url(path))
             .header(header::AUTHORIZATION, bearer)
+            .header(TRACEPARENT, traced(path))
             .query(query)
             .send()
             .await?;
@@ -115,6 +124,24 @@ impl ThreadManager {
             .http
             .post(self.url(path))
             .header(header::AUTHORIZATION, bearer)
+            .header(TRACEPARENT, traced(path))
+            .json(body)
+            .send()
+            .await?;
+        decode(response).await
+    }
+
+    async fn patch_json<B: Serialize, R: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<R> {
+        let bearer = self.bearer().await?;
+        let response = self
+            .http
+            .patch(self.url(path))
+            .header(header::AUTHORIZATION, bearer)
+            .header(TRACEPARENT, traced(path))
             .json(body)
             .send()
             .await?;
@@ -127,6 +154,7 @@ impl ThreadManager {
             .http
             .delete(self.url(path))
             .header(header::AUTHORIZATION, bearer)
+            .header(TRACEPARENT, traced(path))
             .send()
             .await?;
         decode(response).await
@@ -142,6 +170,7 @@ impl ThreadManager {
         let query = ListThreadsQuery {
             limit: Some(limit),
             offset: Some(offset),
+            ..Default::default()
         };
         let response: ListThreadsResponse = self.get_json_query("/threads", &query).await?;
         Ok(response.threads)
@@ -156,6 +185,7 @@ impl ThreadManager {
         let query = ListThreadsQuery {
             limit: Some(limit),
             offset: Some(offset),
+            ..Default::default()
         };
         let response: ListThreadsResponse = self
             .get_json_query(&format!("/threads/by-activity/{activity_id}"), &query)
@@ -173,6 +203,60 @@ impl ThreadManager {
         Ok(())
     }
 
+    /// Set the thread's pinned / starred flags; `None` leaves one as is.
+    pub async fn update_thread(
+        &self,
+        thread_id: Uuid,
+        pinned: Option<bool>,
+        starred: Option<bool>,
+    ) -> Result<Thread> {
+        let body = UpdateThreadRequest { pinned, starred };
+        let response: UpdateThreadResponse = self
+            .patch_json(&format!("/threads/{thread_id}"), &body)
+            .await?;
+        Ok(response.thread)
+    }
+
+    /// File the thread into `folder_id`, or unfile it with `None`.
+    pub async fn move_thread(&self, thread_id: Uuid, folder_id: Option<Uuid>) -> Result<Thread> {
+        let body = MoveThreadRequest { folder_id };
+        let response: MoveThreadResponse = self
+            .post_json(&format!("/threads/{thread_id}/folder"), &body)
+            .await?;
+        Ok(response.thread)
+    }
+
+    pub async fn list_thread_folders(&self) -> Result<Vec<ThreadFolder>> {
+        let response: ListThreadFoldersResponse = self.get_json("/threads/folders").await?;
+        Ok(response.folders)
+    }
+
+    pub async fn create_thread_folder(&self, name: String) -> Result<ThreadFolder> {
+        let body = CreateThreadFolderRequest { name };
+        let response: ThreadFolderResponse = self.post_json("/threads/folders", &body).await?;
+        Ok(response.folder)
+    }
+
+    pub async fn update_thread_folder(
+        &self,
+        folder_id: Uuid,
+        name: Option<String>,
+        position: Option<i32>,
+    ) -> Result<ThreadFolder> {
+        let body = UpdateThreadFolderRequest { name, position };
+        let response: ThreadFolderResponse = self
+            .patch_json(&format!("/threads/folders/{folder_id}"), &body)
+            .await?;
+        Ok(response.folder)
+    }
+
+    pub async fn delete_thread_folder(&self, folder_id: Uuid) -> Result<()> {
+        let _: DeleteThreadFolderResponse = self
+            .delete(&format!("/threads/folders/{folder_id}"))
+            .await?;
+        Ok(())
+    }
+
     pub async fn get_messages(
         &self,
         thread_id: Uuid,
@@ -208,14 +292,67 @@ impl ThreadManager {
         Ok(response.messages)
     }
 
-    pub async fn generate_thread_title(&self, thread_id: Uuid) -> Result<Thread> {
-        let body = GenerateThreadTitleRequest::default();
+    /// Auto-title the thread. With `regenerate` an existing title and its
+    /// tags are replaced; otherwise only a placeholder title is.
+    pub async fn generate_thread_title(&self, thread_id: Uuid, regenerate: bool) -> Result<Thread> {
+        let body = GenerateThreadTitleRequest { regenerate };
         let response: GenerateThreadTitleResponse = self
             .post_json(&format!("/threads/{thread_id}/title"), &body)
             .await?;
         Ok(response.thread)
     }
 
+    /// Pin the thread to `model`, or pass `None` to follow the deployment
+    /// default again.
+    pub async fn set_thread_model(
+        &self,
+        thread_id: Uuid,
+        model: Option<ThreadModel>,
+    ) -> Result<Thread> {
+        let body = SetThreadModelRequest { model };
+        let response: SetThreadModelResponse = self
+            .post_json(&format!("/threads/{thread_id}/model"), &body)
+            .await?;
+        Ok(response.thread)
+    }
+
+    /// Models the backend's gateway providers offer, to pin threads to.
+    pub async fn list_provider_models(&self) -> Result<Vec<ProviderModel>> {
+        let response: ListProviderModelsResponse = self.get_json("/models").await?;
+        Ok(response.models)
+    }
+
+    /// Everything the assistant remembers about the user, newest first.
+    pub async fn list_memories(&self) -> Result<Vec<Memory>> {
+        let response: ListMemoriesResponse = self.get_json("/memories").await?;
+        Ok(response.memories)
+    }
+
+    pub async fn create_memory(&self, content: String) -> Result<Memory> {
+        let body = MemoryRequest { content };
+        let response: MemoryResponse = self.post_json("/memories", &body).await?;
+        Ok(response.memory)
+    }
+
+    pub async fn update_memory(&self, memory_id: Uuid, content: String) -> Result<Memory> {
+        let body = MemoryRequest { content };
+        let response: MemoryResponse = self
+            .patch_json(&format!("/memories/{memory_id}"), &body)
+            .await?;
+        Ok(response.memory)
+    }
+
+    pub async fn delete_memory(&self, memory_id: Uuid) -> Result<()> {
+        let _: DeleteMemoryResponse = self.delete(&format!("/memories/{memory_id}")).await?;
+        Ok(())
+    }
+
+    /// Forget everything the assistant remembers about the user.
+    pub async fn delete_all_memories(&self) -> Result<()> {
+        let _: DeleteMemoryResponse = self.delete("/memories").await?;
+        Ok(())
+    }
+
     pub async fn search_threads(
         &self,
         query: String,
@@ -245,6 +382,90 @@ impl ThreadManager {
             .await
     }
 
+    /// Models installed on the backend's Ollama server, or `None` when the
+    /// backend isn't in local mode.
+    pub async fn list_local_models(&self) -> Result<Option<Vec<LocalModel>>> {
+        let path = "/models/local";
+        let bearer = self.bearer().await?;
+        let response = self
+            .http
+            .get(self.url(path))
+            .header(header::AUTHORIZATION, bearer)
+            .header(TRACEPARENT, traced(path))
+            .send()
+            .await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let response: ListLocalModelsResponse = decode(response).await?;
+        Ok(Some(response.models))
+    }
+
+    /// Pull `model` onto the backend's Ollama server, handing each event
+    /// to `on_event` as it arrives. Returns once a `Done` or `Failed`
+    /// event has been handed over, or [`Error::PullInterrupted`] if the
+    /// stream ends first; pulling again resumes where it stopped.
+    pub async fn pull_local_model(
+        &self,
+        model: String,
+        mut on_event: impl FnMut(LocalModelPullEvent),
+    ) -> Result<()> {
+        let path = "/models/local/pull";
+        let bearer = self.bearer().await?;
+        let mut response = self
+            .http
+            .post(self.url(path))
+            .header(header::AUTHORIZATION, bearer)
+            .header(TRACEPARENT, traced(path))
+            .json(&PullLocalModelRequest { model })
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::service(status, &body));
+        }
+
+        let mut buffer = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            buffer.extend_from_slice(&chunk);
+            while let Some(end) = buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=end).collect();
+                if line.trim_ascii().is_empty() {
+                    continue;
+                }
+                let event: LocalModelPullEvent =
+                    serde_json::from_slice(&line).map_err(Error::Decode)?;
+                let finished = !matches!(event, LocalModelPullEvent::Progress { .. });
+                on_event(event);
+                if finished {
+                    return Ok(());
+                }
+            }
+        }
+        Err(Error::PullInterrupted)
+    }
+
+    /// Remove `model` from the backend's Ollama server.
+    pub async fn delete_local_model(&self, model: String) -> Result<()> {
+        let path = "/models/local";
+        let bearer = self.bearer().await?;
+        let response = self
+            .http
+            .delete(self.url(path))
+            .header(header::AUTHORIZATION, bearer)
+            .header(TRACEPARENT, traced(path))
+            .query(&DeleteLocalModelQuery { model })
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::service(status, &body));
+        }
+        Ok(())
+    }
+
     /// Open a bidirectional chat WebSocket for `thread_id`.
     ///
     /// The handshake authenticates with a fresh bearer; the returned
@@ -253,17 +474,34 @@ impl ThreadManager {
     /// [`crate::chat_bridge::ChatBridge`] — is responsible for sending
     /// the per-turn `CapabilityUpdate` and opening frame and driving the
     /// inbound loop.
+    ///
+    /// Every turn ships the user's context, so this is where local-only
+    /// mode is enforced for chat.
     pub async fn open_chat_socket(
         &self,
         thread_id: Uuid,
         cancel: CancellationToken,
     ) -> Result<ChatSocket> {
+        self.endpoint_manager
+            .ensure_context_egress()
+            .await
+            .map_err(|e| Error::LocalOnly(e.to_string()))?;
         let url = self.ws_url(&format!("/threads/{thread_id}/chat"))?;
         let bearer = self.bearer().await?;
-        ChatSocket::connect(url, bearer, cancel).await
+        let traceparent = traced(url.path());
+        ChatSocket::connect(url, bearer, traceparent, cancel).await
     }
 }
 
+/// Mint a `traceparent` for one outbound call and log its trace ID, so a
+/// slow or failed request in the desktop log can be found in the
+/// backend's traces.
+fn traced(path: &str) -> String {
+    let traceparent = TraceParent::new();
+    tracing::debug!(trace_id = %traceparent.trace_id(), path, "thread service request");
+    traceparent.to_string()
+}
+
 async fn decode<R: DeserializeOwned>(response: reqwest::Response) -> Result<R> {
     let status = response.status();
     if status.is_success() {
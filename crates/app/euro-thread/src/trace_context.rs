@@ -0,0 +1,64 @@
+//! W3C `traceparent` headers for outbound requests.
+//!
+//! Every HTTP call and chat-socket handshake starts a fresh trace so the
+//! backend's request span (and the provider calls beneath it) can be
+//! looked up from a desktop log line. The client records no spans of its
+//! own; the header only seeds the trace ID and marks it sampled.
+
+use std::fmt;
+
+use uuid::Uuid;
+
+/// Header name from the W3C Trace Context spec.
+pub(crate) const TRACEPARENT: &str = "traceparent";
+
+/// A freshly generated trace / parent-span ID pair.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TraceParent {
+    trace_id: u128,
+    span_id: u64,
+}
+
+impl TraceParent {
+    pub(crate) fn new() -> Self {
+        // v4 UUIDs carry 122 random bits; the spec forbids all-zero IDs,
+        // which the fixed version / variant bits already rule out.
+        let trace_id = Uuid::new_v4().as_u128();
+        let (span_id, _) = Uuid::new_v4().as_u64_pair();
+        Self { trace_id, span_id }
+    }
+
+    pub(crate) fn trace_id(&self) -> String {
+        format!("{:032x}", self.trace_id)
+    }
+}
+
+impl fmt::Display for TraceParent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "00-{:032x}-{:016x}-01", self.trace_id, self.span_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_as_sampled_version_00() {
+        let tp = TraceParent::new();
+        let value = tp.to_string();
+        let parts: Vec<&str> = value.split('-').collect();
+        assert_eq!(parts.len(), 4);
+        assert_eq!(parts[0], "00");
+        assert_eq!(parts[1], tp.trace_id());
+        assert_eq!(parts[1].len(), 32);
+        assert_eq!(parts[2].len(), 16);
+        assert_eq!(parts[3], "01");
+        assert!(parts[1].bytes().all(|b| b.is_ascii_hexdigit()));
+    }
+
+    #[test]
+    fn ids_differ_between_requests() {
+        assert_ne!(TraceParent::new().trace_id(), TraceParent::new().trace_id());
+    }
+}
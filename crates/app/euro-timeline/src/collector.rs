@@ -7,7 +7,8 @@ use crate::{
 use chrono::{DateTime, Utc};
 use euro_activity::strategies::{ActivityReport, StrategySupport};
 use euro_activity::{
-    ActivitySession, ContextChip, NoStrategy, strategies::ActivityStrategyFunctionality,
+    ActivityError, ActivitySession, ContextChip, NoStrategy,
+    strategies::ActivityStrategyFunctionality,
 };
 use focus_tracker::{
     FocusTracker, FocusTrackerConfig, FocusedWindow, IconConfig, IgnoreRule, WindowTitleMatch,
@@ -457,6 +458,9 @@ fn spawn_session_insert(
                 };
                 let _ = saved_tx.send(event);
             }
+            Err(err @ ActivityError::LocalOnly(_)) => {
+                tracing::debug!(session_id = %session_id, "Session insert skipped: {err}");
+            }
             Err(err) => {
                 tracing::warn!(
                     session_id = %session_id,
@@ -536,12 +540,18 @@ fn spawn_session_patch_title(
                 return;
             }
         };
-        if let Err(err) = storage.update_session_title(session_id, title, url).await {
-            tracing::warn!(
-                session_id = %session_id,
-                error = %err,
-                "Session title PATCH failed",
-            );
+        match storage.update_session_title(session_id, title, url).await {
+            Ok(_) => {}
+            Err(err @ ActivityError::LocalOnly(_)) => {
+                tracing::debug!(session_id = %session_id, "Session title PATCH skipped: {err}");
+            }
+            Err(err) => {
+                tracing::warn!(
+                    session_id = %session_id,
+                    error = %err,
+                    "Session title PATCH failed",
+                );
+            }
         }
     });
 }
@@ -1,8 +1,10 @@
 use bon::bon;
 use euro_auth::AuthManager;
 use euro_endpoint::EndpointManager;
+use euro_transfer::TransferManager;
 use std::sync::Arc;
 use tokio::sync::Mutex;
+use uuid::Uuid;
 
 use crate::{
     ActivityStorage, ContextChip,
@@ -25,6 +27,8 @@ impl TimelineManager {
     pub fn new(
         endpoint_manager: Arc<EndpointManager>,
         auth_manager: AuthManager,
+        transfers: TransferManager,
+        device_id: Option<Uuid>,
     ) -> TimelineResult<Self> {
         let timeline_config = TimelineConfig::default();
         timeline_config.validate()?;
@@ -32,7 +36,12 @@ impl TimelineManager {
             timeline_config.storage.clone(),
         )));
 
-        let activity_storage = Arc::new(ActivityStorage::new(endpoint_manager, auth_manager));
+        let activity_storage = Arc::new(ActivityStorage::new(
+            endpoint_manager,
+            auth_manager,
+            transfers,
+            device_id,
+        ));
 
         let collector = CollectorService::new_with_timeline_config(
             Arc::clone(&storage),
@@ -69,6 +78,14 @@ impl TimelineManager {
             .map(|session| session.get_context_chip())
     }
 
+    /// Process name of the app in focus, if a session is live.
+    pub async fn current_process_name(&self) -> Option<String> {
+        let storage = self.storage.lock().await;
+        storage
+            .get_current_session()
+            .map(|session| session.process_name.clone())
+    }
+
     pub fn subscribe_to_activity_events(&self) -> tokio::sync::broadcast::Receiver<ActivityEvent> {
         self.collector.subscribe_to_activity_events()
     }
@@ -0,0 +1,27 @@
+//! Bandwidth limits and scheduling for the desktop's uploads and
+//! downloads, so syncing large assets doesn't saturate the user's link.
+//!
+//! Every transfer asks the [`TransferManager`] for a [`TransferPermit`]
+//! before it starts and passes its bytes through the permit:
+//!
+//! - Permits are handed out up to [`TransferPolicy::max_concurrent`] at a
+//!   time, most urgent [`Priority`] first and in arrival order within a
+//!   priority.
+//! - Bytes are paced by a per-direction cap shared by every transfer in
+//!   flight, so two uploads under a 1 MiB/s cap get about half each.
+//! - [`Priority::Background`] transfers are held while the connection is
+//!   metered, if [`TransferPolicy::pause_on_metered`] is set, and outside
+//!   the [`NightWindow`] when one is configured. A transfer the user is
+//!   waiting on is never held, only paced.
+//!
+//! Whether the connection is metered comes from the OS, polled by
+//! [`watch_network_cost`].
+
+mod limiter;
+mod manager;
+mod network;
+mod policy;
+
+pub use manager::{TransferManager, TransferPermit};
+pub use network::{is_metered, watch_network_cost};
+pub use policy::{Direction, NightWindow, Priority, TransferPolicy};
@@ -0,0 +1,108 @@
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+/// Token bucket holding up to one second of traffic.
+///
+/// A caller takes its bytes up front, driving the bucket negative if it
+/// has to, then sleeps until the debt is paid off. Concurrent callers
+/// queue up behind one another's debt, so the cap holds however many
+/// transfers share it.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    rate: Option<u64>,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(rate: Option<u64>) -> Self {
+        Self {
+            bucket: Mutex::new(Bucket {
+                rate,
+                tokens: rate.unwrap_or(0) as f64,
+                updated: Instant::now(),
+            }),
+        }
+    }
+
+    /// Change the cap. Debt already taken is kept, so transfers in
+    /// flight slow down or speed up from their next chunk.
+    pub(crate) fn set_rate(&self, rate: Option<u64>) {
+        let mut bucket = self.lock();
+        bucket.refill(Instant::now());
+        bucket.rate = rate;
+        bucket.tokens = match rate {
+            Some(rate) => bucket.tokens.min(rate as f64),
+            None => 0.0,
+        };
+    }
+
+    /// Wait until `bytes` may be sent under the cap.
+    pub(crate) async fn acquire(&self, bytes: usize) {
+        let wait = {
+            let mut bucket = self.lock();
+            let now = Instant::now();
+            bucket.refill(now);
+            let Some(rate) = bucket.rate else {
+                return;
+            };
+            bucket.tokens -= bytes as f64;
+            if bucket.tokens >= 0.0 {
+                return;
+            }
+            Duration::from_secs_f64(-bucket.tokens / rate as f64)
+        };
+        tokio::time::sleep(wait).await;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Bucket> {
+        // The bucket is two numbers updated together, so a panic
+        // elsewhere can't leave it half-written.
+        self.bucket.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Bucket {
+    fn refill(&mut self, now: Instant) {
+        if let Some(rate) = self.rate {
+            let elapsed = now.duration_since(self.updated).as_secs_f64();
+            self.tokens = (self.tokens + elapsed * rate as f64).min(rate as f64);
+        }
+        self.updated = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn bytes_past_the_first_second_wait_for_the_cap() {
+        let limiter = RateLimiter::new(Some(1000));
+        let start = Instant::now();
+
+        limiter.acquire(1000).await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        limiter.acquire(500).await;
+        limiter.acquire(1500).await;
+        assert_eq!(start.elapsed().as_millis(), 2000);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn lifting_the_cap_stops_the_waiting() {
+        let limiter = RateLimiter::new(Some(10));
+        limiter.set_rate(None);
+        let start = Instant::now();
+
+        limiter.acquire(1 << 20).await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+    }
+}
@@ -0,0 +1,373 @@
+use std::cmp::Reverse;
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use futures::{Stream, StreamExt};
+use tokio::sync::Notify;
+
+use crate::limiter::RateLimiter;
+use crate::policy::{Direction, Priority, TransferPolicy};
+
+/// How often a transfer held back by the night window looks at the clock
+/// again. Policy and network changes wake it sooner.
+const SCHEDULE_RECHECK: Duration = Duration::from_secs(60);
+
+/// Hands out [`TransferPermit`]s under the current [`TransferPolicy`].
+///
+/// Cheap to clone; clones share the queue, the caps and the slots.
+#[derive(Debug, Clone)]
+pub struct TransferManager {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    state: Mutex<State>,
+    /// Woken on every change that could let a queued transfer start.
+    changed: Notify,
+    upload: RateLimiter,
+    download: RateLimiter,
+}
+
+#[derive(Debug)]
+struct State {
+    policy: TransferPolicy,
+    metered: bool,
+    active: usize,
+    /// Queued transfers, most urgent first, then in arrival order.
+    queue: BTreeSet<(Reverse<Priority>, u64)>,
+    next_ticket: u64,
+}
+
+impl State {
+    fn is_held(&self, priority: Priority) -> bool {
+        if !priority.is_deferrable() {
+            return false;
+        }
+        let metered = self.metered && self.policy.pause_on_metered;
+        let outside_window = self
+            .policy
+            .night_window
+            .is_some_and(|window| !window.is_open_now());
+        metered || outside_window
+    }
+
+    /// Whether `ticket` is the queued transfer that gets the next free
+    /// slot. Held transfers don't block the ones behind them.
+    fn is_next(&self, ticket: (Reverse<Priority>, u64)) -> bool {
+        self.active < self.policy.max_concurrent.max(1)
+            && self
+                .queue
+                .iter()
+                .find(|(Reverse(priority), _)| !self.is_held(*priority))
+                == Some(&ticket)
+    }
+}
+
+impl Default for TransferManager {
+    fn default() -> Self {
+        Self::new(TransferPolicy::default())
+    }
+}
+
+impl TransferManager {
+    pub fn new(policy: TransferPolicy) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                upload: RateLimiter::new(policy.rate(Direction::Upload)),
+                download: RateLimiter::new(policy.rate(Direction::Download)),
+                state: Mutex::new(State {
+                    policy,
+                    metered: false,
+                    active: 0,
+                    queue: BTreeSet::new(),
+                    next_ticket: 0,
+                }),
+                changed: Notify::new(),
+            }),
+        }
+    }
+
+    /// Apply a new policy. Queued transfers are re-checked against it
+    /// right away; ones in flight keep their slot and pick up the new
+    /// caps from their next chunk.
+    pub fn set_policy(&self, policy: TransferPolicy) {
+        self.inner.upload.set_rate(policy.rate(Direction::Upload));
+        self.inner
+            .download
+            .set_rate(policy.rate(Direction::Download));
+        self.inner.lock().policy = policy;
+        self.inner.changed.notify_waiters();
+    }
+
+    pub fn policy(&self) -> TransferPolicy {
+        self.inner.lock().policy.clone()
+    }
+
+    /// Record whether the connection is metered.
+    pub fn set_metered(&self, metered: bool) {
+        let mut state = self.inner.lock();
+        if state.metered == metered {
+            return;
+        }
+        state.metered = metered;
+        drop(state);
+        tracing::info!(metered, "Network cost changed");
+        self.inner.changed.notify_waiters();
+    }
+
+    /// Wait for a slot for one transfer. Dropping the future gives up its
+    /// place in the queue.
+    pub async fn acquire(&self, direction: Direction, priority: Priority) -> TransferPermit {
+        let ticket = self.enqueue(priority);
+        let mut logged_hold = false;
+        loop {
+            let notified = {
+                let mut state = self.inner.lock();
+                if state.is_next(ticket.key) {
+                    // Dropping the ticket on return is then a no-op.
+                    state.queue.remove(&ticket.key);
+                    state.active += 1;
+                    return TransferPermit {
+                        inner: Arc::clone(&self.inner),
+                        direction,
+                    };
+                }
+                if !logged_hold && state.is_held(priority) {
+                    logged_hold = true;
+                    tracing::debug!(
+                        ?direction,
+                        metered = state.metered,
+                        "Holding a background transfer until the policy allows it"
+                    );
+                }
+                // Created under the lock, so a change made between
+                // releasing it and awaiting still wakes this transfer.
+                self.inner.changed.notified()
+            };
+            let _ = tokio::time::timeout(SCHEDULE_RECHECK, notified).await;
+        }
+    }
+
+    fn enqueue(&self, priority: Priority) -> Ticket {
+        let mut state = self.inner.lock();
+        let key = (Reverse(priority), state.next_ticket);
+        state.next_ticket += 1;
+        state.queue.insert(key);
+        Ticket {
+            inner: Arc::clone(&self.inner),
+            key,
+        }
+    }
+}
+
+impl Inner {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // Every update leaves the state consistent before anything that
+        // could panic, so a poisoned lock is still safe to use.
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn limiter(&self, direction: Direction) -> &RateLimiter {
+        match direction {
+            Direction::Upload => &self.upload,
+            Direction::Download => &self.download,
+        }
+    }
+}
+
+/// A place in the queue, given up on drop if no permit was handed out.
+struct Ticket {
+    inner: Arc<Inner>,
+    key: (Reverse<Priority>, u64),
+}
+
+impl Drop for Ticket {
+    fn drop(&mut self) {
+        self.inner.lock().queue.remove(&self.key);
+        self.inner.changed.notify_waiters();
+    }
+}
+
+/// One transfer's slot. Send its bytes through [`Self::consume`] or
+/// [`Self::throttle`] to keep them under the cap; the slot is freed on
+/// drop.
+#[derive(Debug)]
+pub struct TransferPermit {
+    inner: Arc<Inner>,
+    direction: Direction,
+}
+
+impl TransferPermit {
+    /// Wait until `bytes` more may be sent.
+    pub async fn consume(&self, bytes: usize) {
+        self.inner.limiter(self.direction).acquire(bytes).await;
+    }
+
+    /// Pace `stream` under the cap, holding the slot until the stream is
+    /// dropped. Each item waits its turn before it is yielded, so keep
+    /// items small (tens of KiB) for an even rate.
+    pub fn throttle<S, B, E>(self, stream: S) -> impl Stream<Item = Result<B, E>> + Send
+    where
+        S: Stream<Item = Result<B, E>> + Send,
+        B: AsRef<[u8]> + Send,
+        E: Send,
+    {
+        let permit = Arc::new(self);
+        stream.then(move |item| {
+            let permit = Arc::clone(&permit);
+            async move {
+                if let Ok(bytes) = &item {
+                    permit.consume(bytes.as_ref().len()).await;
+                }
+                item
+            }
+        })
+    }
+}
+
+impl Drop for TransferPermit {
+    fn drop(&mut self) {
+        self.inner.lock().active -= 1;
+        self.inner.changed.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::pin::pin;
+
+    use super::*;
+    use crate::policy::NightWindow;
+
+    async fn collect<S: Stream<Item = Result<Vec<u8>, ()>>>(stream: S) -> Vec<u8> {
+        let mut stream = pin!(stream);
+        let mut buffer = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            buffer.extend_from_slice(&chunk.unwrap());
+        }
+        buffer
+    }
+
+    fn policy(max_concurrent: usize) -> TransferPolicy {
+        TransferPolicy {
+            max_concurrent,
+            ..TransferPolicy::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn freed_slots_go_to_the_most_urgent_transfer() {
+        let manager = TransferManager::new(policy(1));
+        let first = manager
+            .acquire(Direction::Upload, Priority::Background)
+            .await;
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        for priority in [
+            Priority::Background,
+            Priority::Normal,
+            Priority::Interactive,
+        ] {
+            let (manager, tx) = (manager.clone(), tx.clone());
+            tokio::spawn(async move {
+                let permit = manager.acquire(Direction::Upload, priority).await;
+                tx.send(priority).unwrap();
+                drop(permit);
+            });
+        }
+        // Let all three join the queue before the slot frees up.
+        while manager.inner.lock().queue.len() < 3 {
+            tokio::task::yield_now().await;
+        }
+        drop(first);
+
+        let mut order = Vec::new();
+        for _ in 0..3 {
+            order.push(rx.recv().await.unwrap());
+        }
+        assert_eq!(
+            order,
+            [
+                Priority::Interactive,
+                Priority::Normal,
+                Priority::Background
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn metered_connections_hold_only_background_transfers() {
+        let manager = TransferManager::new(policy(4));
+        manager.set_metered(true);
+
+        let interactive = manager.acquire(Direction::Download, Priority::Interactive);
+        let _permit = tokio::time::timeout(Duration::from_secs(1), interactive)
+            .await
+            .expect("interactive transfers ignore the network cost");
+
+        let background = tokio::spawn({
+            let manager = manager.clone();
+            async move {
+                manager
+                    .acquire(Direction::Upload, Priority::Background)
+                    .await
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!background.is_finished());
+
+        manager.set_metered(false);
+        tokio::time::timeout(Duration::from_secs(1), background)
+            .await
+            .expect("resumed once the connection is unmetered")
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn a_closed_night_window_holds_background_transfers() {
+        let hour = chrono::Timelike::hour(&chrono::Local::now()) as u8;
+        let closed = NightWindow {
+            start_hour: (hour + 1) % 24,
+            end_hour: (hour + 2) % 24,
+        };
+        let manager = TransferManager::new(TransferPolicy {
+            night_window: Some(closed),
+            ..policy(4)
+        });
+
+        let held = manager.acquire(Direction::Upload, Priority::Background);
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), held)
+                .await
+                .is_err()
+        );
+        assert!(manager.inner.lock().queue.is_empty());
+
+        manager.set_policy(policy(4));
+        let _permit = tokio::time::timeout(
+            Duration::from_secs(1),
+            manager.acquire(Direction::Upload, Priority::Background),
+        )
+        .await
+        .expect("runs once the window is lifted");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn throttled_streams_share_the_cap() {
+        let manager = TransferManager::new(TransferPolicy {
+            upload_bytes_per_sec: Some(1000),
+            ..policy(4)
+        });
+        let chunks = || futures::stream::iter((0..4).map(|_| Ok::<_, ()>(vec![0u8; 250])));
+        let start = tokio::time::Instant::now();
+
+        let a = manager.acquire(Direction::Upload, Priority::Normal).await;
+        let b = manager.acquire(Direction::Upload, Priority::Normal).await;
+        let (a, b) = tokio::join!(collect(a.throttle(chunks())), collect(b.throttle(chunks())));
+        assert_eq!(a.len() + b.len(), 2000);
+        assert_eq!(start.elapsed().as_millis(), 1000);
+        assert_eq!(manager.inner.lock().active, 0);
+    }
+}
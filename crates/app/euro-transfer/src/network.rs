@@ -0,0 +1,99 @@
+//! Whether the current connection is billed by use, as the OS reports it.
+//!
+//! - Linux asks NetworkManager over D-Bus (`busctl`), which reports a
+//!   guess for connections nobody has marked either way.
+//! - Windows reads the cost of the internet connection profile.
+//! - Elsewhere, and wherever the question can't be answered, the
+//!   connection is taken to be unmetered.
+
+use std::time::Duration;
+
+use crate::TransferManager;
+
+/// How often [`watch_network_cost`] asks the OS again.
+const NETWORK_COST_POLL: Duration = Duration::from_secs(60);
+
+/// Poll the network cost and feed it to `manager`, forever. Spawn it once
+/// at startup.
+pub async fn watch_network_cost(manager: TransferManager) {
+    loop {
+        match tokio::task::spawn_blocking(is_metered).await {
+            Ok(metered) => manager.set_metered(metered.unwrap_or(false)),
+            Err(e) => tracing::warn!("Network cost probe panicked: {e}"),
+        }
+        tokio::time::sleep(NETWORK_COST_POLL).await;
+    }
+}
+
+/// `Some(true)` if the OS says the connection is metered, `None` if it
+/// can't tell. Blocking.
+pub fn is_metered() -> Option<bool> {
+    platform::is_metered()
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    pub(super) fn is_metered() -> Option<bool> {
+        let output = std::process::Command::new("busctl")
+            .args([
+                "get-property",
+                "org.freedesktop.NetworkManager",
+                "/org/freedesktop/NetworkManager",
+                "org.freedesktop.NetworkManager",
+                "Metered",
+            ])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        parse_nm_metered(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    /// Parse `busctl`'s `u <NMMetered>` output: 1 and 3 are "yes" and
+    /// "guess yes", 2 and 4 "no" and "guess no", 0 unknown.
+    pub(super) fn parse_nm_metered(output: &str) -> Option<bool> {
+        match output.trim().strip_prefix("u ")?.trim() {
+            "1" | "3" => Some(true),
+            "2" | "4" => Some(false),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use windows::Networking::Connectivity::{NetworkCostType, NetworkInformation};
+
+    pub(super) fn is_metered() -> Option<bool> {
+        let profile = NetworkInformation::GetInternetConnectionProfile().ok()?;
+        let cost = profile.GetConnectionCost().ok()?;
+        let metered = match cost.NetworkCostType().ok()? {
+            NetworkCostType::Fixed | NetworkCostType::Variable => true,
+            NetworkCostType::Unrestricted => false,
+            _ => return None,
+        };
+        Some(metered || cost.Roaming().unwrap_or(false) || cost.OverDataLimit().unwrap_or(false))
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+mod platform {
+    pub(super) fn is_metered() -> Option<bool> {
+        None
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::platform::parse_nm_metered;
+
+    #[test]
+    fn network_manager_guesses_count() {
+        assert_eq!(parse_nm_metered("u 1\n"), Some(true));
+        assert_eq!(parse_nm_metered("u 3\n"), Some(true));
+        assert_eq!(parse_nm_metered("u 4\n"), Some(false));
+        assert_eq!(parse_nm_metered("u 0\n"), None);
+        assert_eq!(parse_nm_metered(""), None);
+    }
+}
@@ -0,0 +1,124 @@
+use chrono::Timelike;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Upload,
+    Download,
+}
+
+/// How urgently a transfer should get a slot. Ordered so that the more
+/// urgent priority compares greater.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// Bulk sync nobody is waiting on. The only priority held back on a
+    /// metered connection or outside the night window.
+    Background,
+    /// Fetches that fill in the UI, such as activity icons.
+    Normal,
+    /// Something the user just did and is waiting on, such as a region
+    /// capture attached to a question.
+    Interactive,
+}
+
+impl Priority {
+    /// `true` if transfers at this priority may wait for a better time.
+    pub fn is_deferrable(self) -> bool {
+        matches!(self, Self::Background)
+    }
+}
+
+/// Hours of the local day during which background transfers run, from
+/// `start_hour` up to but not including `end_hour`. Wraps past midnight
+/// when `start_hour > end_hour`; equal hours mean the whole day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NightWindow {
+    pub start_hour: u8,
+    pub end_hour: u8,
+}
+
+impl NightWindow {
+    pub fn contains(&self, hour: u32) -> bool {
+        let (start, end) = (u32::from(self.start_hour), u32::from(self.end_hour));
+        match start.cmp(&end) {
+            std::cmp::Ordering::Equal => true,
+            std::cmp::Ordering::Less => (start..end).contains(&hour),
+            std::cmp::Ordering::Greater => hour >= start || hour < end,
+        }
+    }
+
+    /// Whether the window is open at the current local time.
+    pub fn is_open_now(&self) -> bool {
+        self.contains(chrono::Local::now().hour())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransferPolicy {
+    /// Upload cap in bytes per second across all transfers; `None` for
+    /// no cap.
+    pub upload_bytes_per_sec: Option<u64>,
+    /// Download cap in bytes per second across all transfers; `None` for
+    /// no cap.
+    pub download_bytes_per_sec: Option<u64>,
+    /// Hold background transfers while the connection is metered.
+    pub pause_on_metered: bool,
+    /// Run background transfers only inside this window.
+    pub night_window: Option<NightWindow>,
+    /// Transfers in flight at once, whatever their direction. At least
+    /// one is always allowed.
+    pub max_concurrent: usize,
+}
+
+impl Default for TransferPolicy {
+    fn default() -> Self {
+        Self {
+            upload_bytes_per_sec: None,
+            download_bytes_per_sec: None,
+            pause_on_metered: true,
+            night_window: None,
+            max_concurrent: 3,
+        }
+    }
+}
+
+impl TransferPolicy {
+    pub(crate) fn rate(&self, direction: Direction) -> Option<u64> {
+        match direction {
+            Direction::Upload => self.upload_bytes_per_sec,
+            Direction::Download => self.download_bytes_per_sec,
+        }
+        .filter(|&rate| rate > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn night_window_wraps_past_midnight() {
+        let night = NightWindow {
+            start_hour: 23,
+            end_hour: 6,
+        };
+        assert!(night.contains(23));
+        assert!(night.contains(0));
+        assert!(night.contains(5));
+        assert!(!night.contains(6));
+        assert!(!night.contains(12));
+
+        let afternoon = NightWindow {
+            start_hour: 13,
+            end_hour: 17,
+        };
+        assert!(afternoon.contains(13));
+        assert!(!afternoon.contains(17));
+        assert!(!afternoon.contains(2));
+
+        let always = NightWindow {
+            start_hour: 4,
+            end_hour: 4,
+        };
+        assert!((0..24).all(|hour| always.contains(hour)));
+    }
+}
@@ -0,0 +1,55 @@
+//! Compare storage formats on a real screenshot: encoded size relative to
+//! PNG, and encode time.
+//!
+//! Captures the primary monitor, or reads the image given as the first
+//! argument, downscales it to the transport size like a stored capture,
+//! then encodes it with every format and quality worth considering.
+//!
+//! Usage: cargo run --release -p euro-vision --example encoding_benchmark [image]
+
+use std::time::Instant;
+
+use euro_vision::capture::downscale_to_transport_size;
+use euro_vision::encode::ImageEncoding;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let frame = match std::env::args().nth(1) {
+        Some(path) => image::open(&path)?.to_rgba8(),
+        None => {
+            let monitors = xcap::Monitor::all()?;
+            let monitor = monitors
+                .iter()
+                .find(|m| m.is_primary().unwrap_or(false))
+                .or(monitors.first())
+                .ok_or("no monitor to capture")?;
+            monitor.capture_image()?
+        }
+    };
+    let frame = downscale_to_transport_size(frame);
+    println!("frame: {}x{}", frame.width(), frame.height());
+
+    let encodings = [
+        ImageEncoding::Png,
+        ImageEncoding::Webp,
+        ImageEncoding::Jpeg { quality: 90 },
+        ImageEncoding::Jpeg { quality: 75 },
+        ImageEncoding::Avif { quality: 85 },
+        ImageEncoding::Avif { quality: 70 },
+        ImageEncoding::Avif { quality: 50 },
+    ];
+    let mut png_size = None;
+    for encoding in encodings {
+        let started = Instant::now();
+        let bytes = encoding.encode(&frame)?;
+        let elapsed = started.elapsed();
+        let png_size = *png_size.get_or_insert(bytes.len());
+        println!(
+            "{:<28} {:>9} bytes  {:>5.1}% of PNG  {:>7.1} ms",
+            format!("{encoding:?}"),
+            bytes.len(),
+            bytes.len() as f64 * 100.0 / png_size as f64,
+            elapsed.as_secs_f64() * 1000.0,
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,434 @@
+//! Drawing the user's markup onto a capture: boxes, highlights, arrows and
+//! text labels, so they can point the model at the part of the screen they
+//! mean.
+//!
+//! Shapes are placed in fractions of the image, like
+//! [`crate::region::Selection`], so the same markup lands in the same place
+//! whether it is drawn on a full frame or a downscaled copy. Stroke widths
+//! and text size follow the image's long edge for the same reason. Labels
+//! use a small built-in bitmap font rather than a system one, so what gets
+//! drawn doesn't depend on the machine.
+
+use image::{Rgba, RgbaImage};
+
+/// Box, arrow and label colour when none is given.
+pub const DEFAULT_COLOR: Rgba<u8> = Rgba([239, 68, 68, 255]);
+/// Highlight colour when none is given.
+pub const DEFAULT_HIGHLIGHT_COLOR: Rgba<u8> = Rgba([250, 204, 21, 255]);
+
+/// How opaque a highlight is over what it covers.
+const HIGHLIGHT_ALPHA: f32 = 0.35;
+/// Labels longer than this are cut short.
+const MAX_LABEL_CHARS: usize = 60;
+
+/// A point on the image, as fractions of its width and height.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    /// An outlined rectangle between two corners.
+    Box { from: Point, to: Point },
+    /// A translucent filled rectangle between two corners.
+    Highlight { from: Point, to: Point },
+    /// A line from `from` with its head at `to`.
+    Arrow { from: Point, to: Point },
+    /// Text on a filled tag with its top-left corner at `at`.
+    Label { at: Point, text: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    pub shape: Shape,
+    pub color: Rgba<u8>,
+}
+
+impl Annotation {
+    /// `shape` in its default colour.
+    pub fn new(shape: Shape) -> Self {
+        let color = match shape {
+            Shape::Highlight { .. } => DEFAULT_HIGHLIGHT_COLOR,
+            _ => DEFAULT_COLOR,
+        };
+        Self { shape, color }
+    }
+
+    /// The same annotation with its points moved by `f`.
+    pub fn map_points(&self, f: impl Fn(Point) -> Point) -> Self {
+        let shape = match &self.shape {
+            Shape::Box { from, to } => Shape::Box {
+                from: f(*from),
+                to: f(*to),
+            },
+            Shape::Highlight { from, to } => Shape::Highlight {
+                from: f(*from),
+                to: f(*to),
+            },
+            Shape::Arrow { from, to } => Shape::Arrow {
+                from: f(*from),
+                to: f(*to),
+            },
+            Shape::Label { at, text } => Shape::Label {
+                at: f(*at),
+                text: text.clone(),
+            },
+        };
+        Self {
+            shape,
+            color: self.color,
+        }
+    }
+}
+
+/// Draw `annotations` onto `image` in order, later ones on top.
+pub fn annotate(image: &mut RgbaImage, annotations: &[Annotation]) {
+    let (width, height) = image.dimensions();
+    if width == 0 || height == 0 {
+        return;
+    }
+    // One unit is a stroke width and a dot of label text.
+    let unit = i64::from((width.max(height) / 500).max(2));
+    let to_px = |p: Point| {
+        // `as` saturates, and maps NaN to 0.
+        (
+            (p.x * f64::from(width)).round() as i64,
+            (p.y * f64::from(height)).round() as i64,
+        )
+    };
+
+    for annotation in annotations {
+        let color = annotation.color;
+        match &annotation.shape {
+            Shape::Box { from, to } => {
+                let (x0, y0) = to_px(*from);
+                let (x1, y1) = to_px(*to);
+                let (left, right) = (x0.min(x1), x0.max(x1));
+                let (top, bottom) = (y0.min(y1), y0.max(y1));
+                fill_rect(image, left, top, right, top + unit, color, 1.0);
+                fill_rect(image, left, bottom - unit, right, bottom, color, 1.0);
+                fill_rect(image, left, top, left + unit, bottom, color, 1.0);
+                fill_rect(image, right - unit, top, right, bottom, color, 1.0);
+            }
+            Shape::Highlight { from, to } => {
+                let (x0, y0) = to_px(*from);
+                let (x1, y1) = to_px(*to);
+                fill_rect(
+                    image,
+                    x0.min(x1),
+                    y0.min(y1),
+                    x0.max(x1),
+                    y0.max(y1),
+                    color,
+                    HIGHLIGHT_ALPHA,
+                );
+            }
+            Shape::Arrow { from, to } => {
+                let (x0, y0) = to_px(*from);
+                let (x1, y1) = to_px(*to);
+                draw_arrow(image, (x0, y0), (x1, y1), unit, color);
+            }
+            Shape::Label { at, text } => {
+                draw_label(image, to_px(*at), text, unit, color);
+            }
+        }
+    }
+}
+
+/// Blend `color` over the pixels in `[left, right) × [top, bottom)`,
+/// clipped to the image.
+fn fill_rect(
+    image: &mut RgbaImage,
+    left: i64,
+    top: i64,
+    right: i64,
+    bottom: i64,
+    color: Rgba<u8>,
+    alpha: f32,
+) {
+    let (width, height) = image.dimensions();
+    let left = left.clamp(0, i64::from(width)) as u32;
+    let right = right.clamp(0, i64::from(width)) as u32;
+    let top = top.clamp(0, i64::from(height)) as u32;
+    let bottom = bottom.clamp(0, i64::from(height)) as u32;
+    for y in top..bottom {
+        for x in left..right {
+            blend(image.get_pixel_mut(x, y), color, alpha);
+        }
+    }
+}
+
+fn blend(pixel: &mut Rgba<u8>, color: Rgba<u8>, alpha: f32) {
+    let alpha = alpha * f32::from(color[3]) / 255.0;
+    for (under, over) in pixel.0.iter_mut().zip(color.0).take(3) {
+        let (from, to) = (f32::from(*under), f32::from(over));
+        *under = (from + (to - from) * alpha).round() as u8;
+    }
+    pixel[3] = pixel[3].max((alpha * 255.0).round() as u8);
+}
+
+/// A shaft `unit` wide ending in a triangular head at `tip`.
+fn draw_arrow(
+    image: &mut RgbaImage,
+    tail: (i64, i64),
+    tip: (i64, i64),
+    unit: i64,
+    color: Rgba<u8>,
+) {
+    let (dx, dy) = ((tip.0 - tail.0) as f64, (tip.1 - tail.1) as f64);
+    let length = dx.hypot(dy);
+    if length < 1.0 {
+        return;
+    }
+    let (ux, uy) = (dx / length, dy / length);
+    let unit = unit as f64;
+    let head_length = (unit * 5.0).min(length);
+    let head_half_width = unit * 3.0;
+    let tip = (tip.0 as f64, tip.1 as f64);
+    let base = (tip.0 - ux * head_length, tip.1 - uy * head_length);
+    let corners = [
+        tip,
+        (base.0 - uy * head_half_width, base.1 + ux * head_half_width),
+        (base.0 + uy * head_half_width, base.1 - ux * head_half_width),
+    ];
+    let tail = (tail.0 as f64, tail.1 as f64);
+    let half_stroke = unit / 2.0;
+
+    let reach = head_half_width.max(half_stroke);
+    let (width, height) = image.dimensions();
+    let span = |a: f64, b: f64, max: u32| {
+        let lo = (a.min(b) - reach).floor().max(0.0) as u32;
+        let hi = ((a.max(b) + reach).ceil().max(0.0) as u32).min(max);
+        lo..hi
+    };
+    for y in span(tail.1, tip.1, height) {
+        for x in span(tail.0, tip.0, width) {
+            let p = (f64::from(x) + 0.5, f64::from(y) + 0.5);
+            if distance_to_segment(p, tail, base) <= half_stroke || in_triangle(p, corners) {
+                blend(image.get_pixel_mut(x, y), color, 1.0);
+            }
+        }
+    }
+}
+
+fn distance_to_segment(p: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (abx, aby) = (b.0 - a.0, b.1 - a.1);
+    let length_sq = abx * abx + aby * aby;
+    let t = if length_sq == 0.0 {
+        0.0
+    } else {
+        (((p.0 - a.0) * abx + (p.1 - a.1) * aby) / length_sq).clamp(0.0, 1.0)
+    };
+    (p.0 - (a.0 + t * abx)).hypot(p.1 - (a.1 + t * aby))
+}
+
+fn in_triangle(p: (f64, f64), [a, b, c]: [(f64, f64); 3]) -> bool {
+    let side = |from: (f64, f64), to: (f64, f64)| {
+        (to.0 - from.0) * (p.1 - from.1) - (to.1 - from.1) * (p.0 - from.0)
+    };
+    let (ab, bc, ca) = (side(a, b), side(b, c), side(c, a));
+    (ab >= 0.0 && bc >= 0.0 && ca >= 0.0) || (ab <= 0.0 && bc <= 0.0 && ca <= 0.0)
+}
+
+/// White text on a tag of `color`, moved inward if it would hang off the
+/// image.
+fn draw_label(image: &mut RgbaImage, at: (i64, i64), text: &str, unit: i64, color: Rgba<u8>) {
+    let text: Vec<char> = text.trim().chars().take(MAX_LABEL_CHARS).collect();
+    if text.is_empty() {
+        return;
+    }
+    let padding = unit * 2;
+    let advance = unit * (GLYPH_WIDTH + 1);
+    let tag_width = advance * text.len() as i64 - unit + padding * 2;
+    let tag_height = unit * GLYPH_HEIGHT + padding * 2;
+    let (width, height) = image.dimensions();
+    let left = at.0.min(i64::from(width) - tag_width).max(0);
+    let top = at.1.min(i64::from(height) - tag_height).max(0);
+    fill_rect(
+        image,
+        left,
+        top,
+        left + tag_width,
+        top + tag_height,
+        color,
+        1.0,
+    );
+
+    let white = Rgba([255, 255, 255, 255]);
+    for (i, c) in text.into_iter().enumerate() {
+        let glyph_left = left + padding + advance * i as i64;
+        for (row, bits) in glyph(c).into_iter().enumerate() {
+            for column in 0..GLYPH_WIDTH {
+                if bits & (1 << (GLYPH_WIDTH - 1 - column)) == 0 {
+                    continue;
+                }
+                let x = glyph_left + column * unit;
+                let y = top + padding + row as i64 * unit;
+                fill_rect(image, x, y, x + unit, y + unit, white, 1.0);
+            }
+        }
+    }
+}
+
+const GLYPH_WIDTH: i64 = 5;
+const GLYPH_HEIGHT: i64 = 7;
+
+/// A 5×7 bitmap of `c`, one byte per row with the leftmost dot in bit 4.
+/// Letters are drawn in capitals; characters without a glyph draw as `?`.
+fn glyph(c: char) -> [u8; 7] {
+    match c.to_ascii_uppercase() {
+        'A' => [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'D' => [0x1E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x1E],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        'H' => [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'I' => [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'P' => [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+        'Q' => [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+        'R' => [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'T' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        'X' => [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04],
+        'Z' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        ' ' => [0x00; 7],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        ',' => [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08],
+        ':' => [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00],
+        '!' => [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        '+' => [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00],
+        '=' => [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00],
+        '_' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F],
+        '/' => [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00],
+        '(' => [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02],
+        ')' => [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08],
+        '#' => [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A],
+        '\'' => [0x04, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00],
+        '"' => [0x0A, 0x0A, 0x00, 0x00, 0x00, 0x00, 0x00],
+        _ => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLACK: Rgba<u8> = Rgba([0, 0, 0, 255]);
+
+    fn canvas() -> RgbaImage {
+        RgbaImage::from_pixel(200, 100, BLACK)
+    }
+
+    fn point(x: f64, y: f64) -> Point {
+        Point { x, y }
+    }
+
+    #[test]
+    fn boxes_outline_without_filling() {
+        let mut image = canvas();
+        annotate(
+            &mut image,
+            &[Annotation::new(Shape::Box {
+                from: point(0.1, 0.2),
+                to: point(0.5, 0.8),
+            })],
+        );
+        // Corners at (20, 20) and (100, 80), two pixels thick.
+        assert_eq!(*image.get_pixel(20, 20), DEFAULT_COLOR);
+        assert_eq!(*image.get_pixel(99, 79), DEFAULT_COLOR);
+        assert_eq!(*image.get_pixel(60, 50), BLACK);
+        assert_eq!(*image.get_pixel(19, 20), BLACK);
+    }
+
+    #[test]
+    fn highlights_tint_what_they_cover() {
+        let mut image = canvas();
+        annotate(
+            &mut image,
+            &[Annotation::new(Shape::Highlight {
+                from: point(0.0, 0.0),
+                to: point(0.5, 0.5),
+            })],
+        );
+        let tinted = image.get_pixel(10, 10);
+        assert!(tinted[0] > 0 && tinted[0] < DEFAULT_HIGHLIGHT_COLOR[0]);
+        assert_eq!(*image.get_pixel(150, 75), BLACK);
+    }
+
+    #[test]
+    fn arrows_reach_their_tip() {
+        let mut image = canvas();
+        annotate(
+            &mut image,
+            &[Annotation::new(Shape::Arrow {
+                from: point(0.1, 0.5),
+                to: point(0.9, 0.5),
+            })],
+        );
+        assert_eq!(*image.get_pixel(175, 50), DEFAULT_COLOR);
+        assert_eq!(*image.get_pixel(60, 50), DEFAULT_COLOR);
+        assert_eq!(*image.get_pixel(60, 10), BLACK);
+    }
+
+    #[test]
+    fn labels_stay_on_the_image() {
+        let mut image = canvas();
+        annotate(
+            &mut image,
+            &[Annotation::new(Shape::Label {
+                at: point(0.99, 0.99),
+                text: "Save".into(),
+            })],
+        );
+        // Pushed up and left to fit, so the bottom-right corner is tagged.
+        assert_eq!(*image.get_pixel(199, 99), DEFAULT_COLOR);
+        assert!(image.pixels().any(|p| *p == Rgba([255, 255, 255, 255])));
+    }
+
+    #[test]
+    fn nothing_off_the_image_panics() {
+        let mut image = canvas();
+        annotate(
+            &mut image,
+            &[
+                Annotation::new(Shape::Box {
+                    from: point(-1.0, -1.0),
+                    to: point(2.0, f64::NAN),
+                }),
+                Annotation::new(Shape::Arrow {
+                    from: point(-3.0, 0.5),
+                    to: point(4.0, 0.5),
+                }),
+                Annotation::new(Shape::Label {
+                    at: point(0.0, 0.0),
+                    text: "x".repeat(200),
+                }),
+            ],
+        );
+    }
+}
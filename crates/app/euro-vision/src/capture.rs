@@ -17,6 +17,8 @@ use image::{ImageFormat, RgbaImage, imageops::FilterType};
 use thiserror::Error;
 use tokio::task::JoinError;
 
+use crate::layout::{LogicalRect, MonitorLayout};
+
 /// Anthropic vision recommendation: images larger than this on the long edge
 /// are downscaled before transport. Keeps the payload small and the PNG
 /// encode fast without meaningfully hurting LLM legibility.
@@ -31,6 +33,20 @@ pub struct CapturedImage {
     pub height: u32,
 }
 
+impl CapturedImage {
+    /// Downscale `image` to the transport size and PNG-encode it.
+    pub fn from_rgba(image: RgbaImage) -> Result<Self, image::ImageError> {
+        let scaled = downscale_to_transport_size(image);
+        let (width, height) = scaled.dimensions();
+        let png_base64 = encode_png_base64(&scaled)?;
+        Ok(Self {
+            png_base64,
+            width,
+            height,
+        })
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum CaptureError {
     #[error("xcap failed: {0}")]
@@ -53,6 +69,20 @@ pub async fn capture_window_by_pid(pid: u32) -> Result<Option<CapturedImage>, Ca
     tokio::task::spawn_blocking(move || capture_window_by_pid_blocking(pid)).await?
 }
 
+/// Capture the primary monitor (or the first one, if none reports as
+/// primary). Returns `Ok(None)` when the OS exposes no monitors at all.
+pub async fn capture_primary_monitor() -> Result<Option<CapturedImage>, CaptureError> {
+    tokio::task::spawn_blocking(capture_primary_monitor_blocking).await?
+}
+
+/// Capture `region`, given in logical desktop coordinates (see
+/// [`crate::layout`]), at full resolution from the monitor holding most of
+/// it. The part hanging off that monitor is clipped. Returns `Ok(None)` when
+/// the region is on no monitor.
+pub async fn capture_region_rgba(region: LogicalRect) -> Result<Option<RgbaImage>, CaptureError> {
+    tokio::task::spawn_blocking(move || capture_region_rgba_blocking(region)).await?
+}
+
 /// Trigger any permission prompts the host OS attaches to screen capture,
 /// so the user grants once at app start rather than mid-chat.
 ///
@@ -80,16 +110,48 @@ fn capture_window_by_pid_blocking(pid: u32) -> Result<Option<CapturedImage>, Cap
         return Ok(None);
     };
 
-    let raw = window.capture_image()?;
-    let scaled = downscale_to_max_edge(raw, MAX_EDGE_PX);
-    let (width, height) = scaled.dimensions();
-    let png_base64 = encode_png_base64(&scaled)?;
+    Ok(Some(CapturedImage::from_rgba(window.capture_image()?)?))
+}
 
-    Ok(Some(CapturedImage {
-        png_base64,
-        width,
-        height,
-    }))
+fn capture_primary_monitor_blocking() -> Result<Option<CapturedImage>, CaptureError> {
+    let Some(monitor) = primary_monitor()? else {
+        return Ok(None);
+    };
+
+    Ok(Some(CapturedImage::from_rgba(monitor.capture_image()?)?))
+}
+
+fn capture_region_rgba_blocking(region: LogicalRect) -> Result<Option<RgbaImage>, CaptureError> {
+    let monitors = xcap::Monitor::all()?;
+    let layout = MonitorLayout::from_xcap(&monitors)?;
+    let Some(index) = layout.index_for(region) else {
+        return Ok(None);
+    };
+    let Some(rect) = layout.monitors()[index].to_capture_pixels(region) else {
+        return Ok(None);
+    };
+    let image = monitors[index].capture_image()?;
+    // The capture can come back a pixel off the size the OS reported for
+    // the monitor, so clip against what we actually got.
+    let (width, height) = image.dimensions();
+    let (x, y) = (rect.x as u32, rect.y as u32);
+    if x >= width || y >= height {
+        return Ok(None);
+    }
+    let (w, h) = (rect.width.min(width - x), rect.height.min(height - y));
+    Ok(Some(
+        image::imageops::crop_imm(&image, x, y, w, h).to_image(),
+    ))
+}
+
+/// The primary monitor, or the first one if none reports as primary.
+pub(crate) fn primary_monitor() -> Result<Option<xcap::Monitor>, CaptureError> {
+    let mut monitors = xcap::Monitor::all()?;
+    let primary = monitors
+        .iter()
+        .position(|m| m.is_primary().unwrap_or(false))
+        .unwrap_or(0);
+    Ok((primary < monitors.len()).then(|| monitors.swap_remove(primary)))
 }
 
 fn prime_capture_permission_blocking() -> Result<(), CaptureError> {
@@ -118,6 +180,11 @@ fn select_best_window(windows: &[xcap::Window], pid: u32) -> Option<&xcap::Windo
         })
 }
 
+/// `image` shrunk, if need be, to the size frames are sent and stored at.
+pub fn downscale_to_transport_size(image: RgbaImage) -> RgbaImage {
+    downscale_to_max_edge(image, MAX_EDGE_PX)
+}
+
 fn downscale_to_max_edge(image: RgbaImage, max_edge: u32) -> RgbaImage {
     let (w, h) = image.dimensions();
     let longest = w.max(h);
@@ -0,0 +1,142 @@
+//! Encoding frames for storage.
+//!
+//! Frames sent to a model go out as PNG ([`crate::capture::CapturedImage`]),
+//! which every provider accepts. Frames kept as assets can use a denser
+//! codec. On screenshots (large flat areas, sharp text), lossless WebP
+//! typically comes out around a quarter smaller than PNG with identical
+//! pixels, at a similar encode time, so it is the default. AVIF at
+//! quality 70 to 80 is several times smaller again, but lossy and much
+//! slower to encode; JPEG is only worth it for photos.
+//! `cargo run -p euro-vision --example encoding_benchmark` measures all
+//! of them on the current screen.
+
+use image::buffer::ConvertBuffer;
+use image::codecs::avif::AvifEncoder;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::{ExtendedColorType, ImageEncoder, ImageError, RgbImage, RgbaImage};
+
+/// AVIF encoder speed, 1 (slowest, smallest) to 10. 8 keeps a 1568 px
+/// frame well under a second.
+const AVIF_SPEED: u8 = 8;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ImageEncoding {
+    Png,
+    /// `quality` from 1 to 100.
+    Jpeg {
+        quality: u8,
+    },
+    /// Lossless WebP.
+    #[default]
+    Webp,
+    /// `quality` from 1 to 100.
+    Avif {
+        quality: u8,
+    },
+}
+
+impl ImageEncoding {
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            Self::Png => "image/png",
+            Self::Jpeg { .. } => "image/jpeg",
+            Self::Webp => "image/webp",
+            Self::Avif { .. } => "image/avif",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Jpeg { .. } => "jpg",
+            Self::Webp => "webp",
+            Self::Avif { .. } => "avif",
+        }
+    }
+
+    /// `image` in this format. CPU-bound, so callers run it off the async
+    /// runtime.
+    pub fn encode(self, image: &RgbaImage) -> Result<Vec<u8>, ImageError> {
+        let (width, height) = image.dimensions();
+        let mut bytes = Vec::new();
+        match self {
+            Self::Png => PngEncoder::new(&mut bytes).write_image(
+                image.as_raw(),
+                width,
+                height,
+                ExtendedColorType::Rgba8,
+            )?,
+            Self::Jpeg { quality } => {
+                // JPEG has no alpha channel.
+                let rgb: RgbImage = image.convert();
+                JpegEncoder::new_with_quality(&mut bytes, quality.clamp(1, 100)).write_image(
+                    rgb.as_raw(),
+                    width,
+                    height,
+                    ExtendedColorType::Rgb8,
+                )?
+            }
+            Self::Webp => WebPEncoder::new_lossless(&mut bytes).write_image(
+                image.as_raw(),
+                width,
+                height,
+                ExtendedColorType::Rgba8,
+            )?,
+            Self::Avif { quality } => {
+                AvifEncoder::new_with_speed_quality(&mut bytes, AVIF_SPEED, quality.clamp(1, 100))
+                    .write_image(image.as_raw(), width, height, ExtendedColorType::Rgba8)?
+            }
+        }
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Flat panels with a few lines of "text", like a screenshot.
+    fn screenshot_like() -> RgbaImage {
+        RgbaImage::from_fn(320, 200, |x, y| {
+            if y % 20 < 2 && x % 7 < 5 {
+                image::Rgba([20, 20, 20, 255])
+            } else if x < 80 {
+                image::Rgba([240, 240, 245, 255])
+            } else {
+                image::Rgba([255, 255, 255, 255])
+            }
+        })
+    }
+
+    #[test]
+    fn lossless_formats_round_trip() {
+        let frame = screenshot_like();
+        for encoding in [ImageEncoding::Png, ImageEncoding::Webp] {
+            let bytes = encoding.encode(&frame).expect("encode");
+            let decoded = image::load_from_memory(&bytes).expect("decode").to_rgba8();
+            assert_eq!(decoded.as_raw(), frame.as_raw(), "{encoding:?}");
+        }
+    }
+
+    #[test]
+    fn bytes_match_the_declared_format() {
+        let frame = screenshot_like();
+        for encoding in [
+            ImageEncoding::Png,
+            ImageEncoding::Jpeg { quality: 80 },
+            ImageEncoding::Webp,
+            ImageEncoding::Avif { quality: 70 },
+        ] {
+            let bytes = encoding.encode(&frame).expect("encode");
+            if let ImageEncoding::Avif { .. } = encoding {
+                // An ISO-BMFF `ftyp` box with the AVIF brand.
+                assert_eq!(&bytes[4..12], b"ftypavif");
+                continue;
+            }
+            let guessed = image::guess_format(&bytes).expect("recognisable");
+            assert_eq!(guessed.to_mime_type(), encoding.mime_type());
+        }
+    }
+}
@@ -0,0 +1,329 @@
+//! Where the monitors are, and how their pixels map to what the user sees.
+//!
+//! The OSes disagree on the space they place monitors in: macOS uses
+//! points, Windows and X11 use physical pixels. On a mixed-DPI setup
+//! neither space alone turns a point the user pointed at into a pixel of a
+//! capture. [`MonitorLayout`] describes every monitor both ways, in a
+//! logical space where a monitor's origin and size are its physical ones
+//! divided by its own scale factor. That is the convention Tauri and winit
+//! use, so coordinates from a webview or a window event can be handed to
+//! [`crate::capture::capture_region_rgba`] as they are.
+
+use crate::capture::CaptureError;
+
+/// Whether the OS reports monitor bounds in points rather than pixels.
+const DESKTOP_IN_POINTS: bool = cfg!(target_os = "macos");
+
+/// A rectangle in logical desktop coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LogicalRect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl LogicalRect {
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+
+    /// The overlap of the two rectangles, or `None` if they don't touch.
+    pub fn intersection(&self, other: &LogicalRect) -> Option<LogicalRect> {
+        let left = self.x.max(other.x);
+        let top = self.y.max(other.y);
+        let right = (self.x + self.width).min(other.x + other.width);
+        let bottom = (self.y + self.height).min(other.y + other.height);
+        (right > left && bottom > top).then(|| LogicalRect {
+            x: left,
+            y: top,
+            width: right - left,
+            height: bottom - top,
+        })
+    }
+
+    fn area(&self) -> f64 {
+        self.width * self.height
+    }
+}
+
+/// A rectangle in physical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PhysicalRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// One monitor's place on the desktop.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorGeometry {
+    pub id: u32,
+    pub name: String,
+    pub is_primary: bool,
+    /// Physical pixels per logical pixel.
+    pub scale_factor: f64,
+    /// Bounds in logical desktop coordinates.
+    pub logical: LogicalRect,
+    /// Bounds in physical desktop pixels. Its size is the size of a
+    /// capture of this monitor.
+    pub physical: PhysicalRect,
+}
+
+impl MonitorGeometry {
+    /// Build from bounds as the OS reports them, in points or pixels
+    /// depending on `desktop_in_points`.
+    fn from_desktop(
+        id: u32,
+        name: String,
+        is_primary: bool,
+        scale_factor: f64,
+        bounds: PhysicalRect,
+        desktop_in_points: bool,
+    ) -> Self {
+        let scale = if scale_factor.is_finite() && scale_factor > 0.0 {
+            scale_factor
+        } else {
+            1.0
+        };
+        let physical = if desktop_in_points {
+            let px = |points: f64| (points * scale).round();
+            PhysicalRect {
+                x: px(f64::from(bounds.x)) as i32,
+                y: px(f64::from(bounds.y)) as i32,
+                width: px(f64::from(bounds.width)) as u32,
+                height: px(f64::from(bounds.height)) as u32,
+            }
+        } else {
+            bounds
+        };
+        let logical = LogicalRect {
+            x: f64::from(physical.x) / scale,
+            y: f64::from(physical.y) / scale,
+            width: f64::from(physical.width) / scale,
+            height: f64::from(physical.height) / scale,
+        };
+        Self {
+            id,
+            name,
+            is_primary,
+            scale_factor: scale,
+            logical,
+            physical,
+        }
+    }
+
+    /// The part of `rect` on this monitor, in pixels of a capture of it
+    /// (relative to the monitor's top-left), or `None` if `rect` is off
+    /// this monitor or rounds away to nothing.
+    pub fn to_capture_pixels(&self, rect: LogicalRect) -> Option<PhysicalRect> {
+        let on_monitor = self.logical.intersection(&rect)?;
+        let px = |logical: f64, max: u32| {
+            // `as` saturates, so a rounding overshoot can't wrap.
+            ((logical * self.scale_factor).round().max(0.0) as u32).min(max)
+        };
+        let (width, height) = (self.physical.width, self.physical.height);
+        let left = px(on_monitor.x - self.logical.x, width);
+        let top = px(on_monitor.y - self.logical.y, height);
+        let right = px(on_monitor.x + on_monitor.width - self.logical.x, width);
+        let bottom = px(on_monitor.y + on_monitor.height - self.logical.y, height);
+        (right > left && bottom > top).then_some(PhysicalRect {
+            x: left as i32,
+            y: top as i32,
+            width: right - left,
+            height: bottom - top,
+        })
+    }
+}
+
+/// Every monitor on the desktop, in the order the OS lists them.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MonitorLayout {
+    monitors: Vec<MonitorGeometry>,
+}
+
+impl MonitorLayout {
+    /// The layout right now. Monitors can come and go, so don't hold on to
+    /// it across user actions.
+    pub async fn current() -> Result<Self, CaptureError> {
+        tokio::task::spawn_blocking(|| Self::from_xcap(&xcap::Monitor::all()?)).await?
+    }
+
+    /// Describe `monitors`, keeping their order so indices line up.
+    pub(crate) fn from_xcap(monitors: &[xcap::Monitor]) -> Result<Self, CaptureError> {
+        let monitors = monitors
+            .iter()
+            .map(|monitor| {
+                Ok(MonitorGeometry::from_desktop(
+                    monitor.id()?,
+                    monitor.name()?,
+                    monitor.is_primary().unwrap_or(false),
+                    f64::from(monitor.scale_factor()?),
+                    PhysicalRect {
+                        x: monitor.x()?,
+                        y: monitor.y()?,
+                        width: monitor.width()?,
+                        height: monitor.height()?,
+                    },
+                    DESKTOP_IN_POINTS,
+                ))
+            })
+            .collect::<Result<_, CaptureError>>()?;
+        Ok(Self { monitors })
+    }
+
+    pub fn monitors(&self) -> &[MonitorGeometry] {
+        &self.monitors
+    }
+
+    /// The primary monitor, or the first one if none reports as primary.
+    pub fn primary(&self) -> Option<&MonitorGeometry> {
+        self.primary_index().map(|i| &self.monitors[i])
+    }
+
+    /// The monitor containing the logical point `(x, y)`.
+    pub fn monitor_at(&self, x: f64, y: f64) -> Option<&MonitorGeometry> {
+        self.index_at(x, y).map(|i| &self.monitors[i])
+    }
+
+    /// The monitor holding the largest part of `rect`.
+    pub fn monitor_for(&self, rect: LogicalRect) -> Option<&MonitorGeometry> {
+        self.index_for(rect).map(|i| &self.monitors[i])
+    }
+
+    pub(crate) fn primary_index(&self) -> Option<usize> {
+        let primary = self.monitors.iter().position(|m| m.is_primary).unwrap_or(0);
+        (primary < self.monitors.len()).then_some(primary)
+    }
+
+    pub(crate) fn index_at(&self, x: f64, y: f64) -> Option<usize> {
+        self.monitors.iter().position(|m| m.logical.contains(x, y))
+    }
+
+    pub(crate) fn index_for(&self, rect: LogicalRect) -> Option<usize> {
+        self.monitors
+            .iter()
+            .enumerate()
+            .filter_map(|(i, m)| Some((i, m.logical.intersection(&rect)?.area())))
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(i, _)| i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bounds(x: i32, y: i32, width: u32, height: u32) -> PhysicalRect {
+        PhysicalRect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    fn rect(x: f64, y: f64, width: f64, height: f64) -> LogicalRect {
+        LogicalRect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// A 4K laptop panel at 2x with a 1080p monitor at 1x to its right, as
+    /// Windows places them.
+    fn mixed_dpi() -> MonitorLayout {
+        MonitorLayout {
+            monitors: vec![
+                MonitorGeometry::from_desktop(
+                    1,
+                    "Built-in".into(),
+                    true,
+                    2.0,
+                    bounds(0, 0, 3840, 2160),
+                    false,
+                ),
+                MonitorGeometry::from_desktop(
+                    2,
+                    "External".into(),
+                    false,
+                    1.0,
+                    bounds(3840, 0, 1920, 1080),
+                    false,
+                ),
+            ],
+        }
+    }
+
+    #[test]
+    fn pixel_desktops_divide_by_scale() {
+        let layout = mixed_dpi();
+        assert_eq!(layout.monitors()[0].logical, rect(0.0, 0.0, 1920.0, 1080.0));
+        assert_eq!(
+            layout.monitors()[1].logical,
+            rect(3840.0, 0.0, 1920.0, 1080.0)
+        );
+    }
+
+    #[test]
+    fn point_desktops_multiply_by_scale() {
+        let retina = MonitorGeometry::from_desktop(
+            1,
+            "Retina".into(),
+            true,
+            2.0,
+            bounds(0, 0, 1512, 982),
+            true,
+        );
+        assert_eq!(retina.physical, bounds(0, 0, 3024, 1964));
+        assert_eq!(retina.logical, rect(0.0, 0.0, 1512.0, 982.0));
+    }
+
+    #[test]
+    fn logical_regions_map_to_each_monitors_pixels() {
+        let layout = mixed_dpi();
+        let region = rect(100.0, 50.0, 200.0, 100.0);
+        let laptop = layout.monitor_for(region).expect("on the laptop");
+        assert_eq!(laptop.id, 1);
+        assert_eq!(
+            laptop.to_capture_pixels(region),
+            Some(bounds(200, 100, 400, 200))
+        );
+
+        let region = rect(3940.0, 50.0, 200.0, 100.0);
+        let external = layout.monitor_for(region).expect("on the external");
+        assert_eq!(external.id, 2);
+        assert_eq!(
+            external.to_capture_pixels(region),
+            Some(bounds(100, 50, 200, 100))
+        );
+    }
+
+    #[test]
+    fn regions_hanging_off_a_monitor_are_clipped() {
+        let layout = mixed_dpi();
+        let region = rect(3800.0, 0.0, 300.0, 100.0);
+        let monitor = layout.monitor_for(region).expect("on the external");
+        assert_eq!(monitor.id, 2);
+        assert_eq!(
+            monitor.to_capture_pixels(region),
+            Some(bounds(0, 0, 260, 100))
+        );
+    }
+
+    #[test]
+    fn points_off_every_monitor_have_none() {
+        let layout = mixed_dpi();
+        assert_eq!(layout.monitor_at(1000.0, 500.0).map(|m| m.id), Some(1));
+        assert!(layout.monitor_at(2500.0, 500.0).is_none());
+        assert!(
+            layout
+                .monitor_for(rect(2000.0, 0.0, 100.0, 100.0))
+                .is_none()
+        );
+        assert_eq!(layout.primary().map(|m| m.id), Some(1));
+    }
+}
@@ -13,12 +13,31 @@
 //! `xcap` behind an async API and exposes a one-shot
 //! [`capture::prime_capture_permission`] hook so the macOS Screen Recording
 //! TCC prompt can be triggered at app start instead of on first use.
+//!
+//! [`layout`] describes where the monitors are and their scale factors,
+//! so logical coordinates from the UI can be captured at the right pixels
+//! on mixed-DPI setups ([`capture::capture_region_rgba`]).
+//!
+//! [`region`] freezes a monitor and crops the rectangle the user selects
+//! on it, [`annotate`] draws the user's boxes, arrows and labels onto a
+//! capture, and [`ocr`] (behind the `ocr` feature) reads the text in a
+//! capture on-device, one job at a time per free core through
+//! [`ocr_queue`]. [`phash`] fingerprints frames so near-duplicates can be
+//! skipped, and [`encode`] picks the format frames are stored in.
 
 use anyhow::{Result, anyhow};
 use base64::{Engine as _, engine::general_purpose};
 use image::{ImageBuffer, Rgb, Rgba};
 
+pub mod annotate;
 pub mod capture;
+pub mod encode;
+pub mod layout;
+#[cfg(feature = "ocr")]
+pub mod ocr;
+pub mod ocr_queue;
+pub mod phash;
+pub mod region;
 
 /// PNG-encode an RGBA image and return the raw base64 payload (no `data:` prefix).
 pub fn rgba_to_png_base64_raw(image: &ImageBuffer<Rgba<u8>, Vec<u8>>) -> Result<String> {
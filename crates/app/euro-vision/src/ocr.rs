@@ -0,0 +1,44 @@
+//! On-device text recognition with `ocrs`.
+//!
+//! The models are not bundled: the caller points [`TextRecognizer::load`]
+//! at a directory holding `text-detection.rten` and
+//! `text-recognition.rten` from the `ocrs` model releases. Recognition is
+//! synchronous and CPU-bound, so callers run it off the async runtime.
+
+use std::path::Path;
+
+use anyhow::{Context as _, Result};
+use image::RgbaImage;
+use ocrs::{ImageSource, OcrEngine, OcrEngineParams};
+use rten::Model;
+
+pub const DETECTION_MODEL_FILE: &str = "text-detection.rten";
+pub const RECOGNITION_MODEL_FILE: &str = "text-recognition.rten";
+
+/// A loaded OCR engine. Loading takes a moment, so callers keep it around.
+pub struct TextRecognizer {
+    engine: OcrEngine,
+}
+
+impl TextRecognizer {
+    pub fn load(models_dir: &Path) -> Result<Self> {
+        let load = |file: &str| {
+            let path = models_dir.join(file);
+            Model::load_file(&path).with_context(|| format!("loading {}", path.display()))
+        };
+        let engine = OcrEngine::new(OcrEngineParams {
+            detection_model: Some(load(DETECTION_MODEL_FILE)?),
+            recognition_model: Some(load(RECOGNITION_MODEL_FILE)?),
+            ..Default::default()
+        })?;
+        Ok(Self { engine })
+    }
+
+    /// The text in `image`, one line per detected line, top to bottom.
+    pub fn recognize(&self, image: &RgbaImage) -> Result<String> {
+        let source = ImageSource::from_bytes(image.as_raw(), image.dimensions())?;
+        let input = self.engine.prepare_input(source)?;
+        let text = self.engine.get_text(&input)?;
+        Ok(text.trim().to_owned())
+    }
+}
@@ -0,0 +1,303 @@
+//! A local queue for OCR and other CPU-heavy recognition jobs.
+//!
+//! Recognition saturates a core for a second or more per frame, so jobs
+//! must not run wherever they happen to be asked for. The queue starts
+//! at most [`default_concurrency`] jobs at a time on tokio's blocking
+//! pool, the rest wait in priority order:
+//!
+//! - [`Priority::User`] jobs (the user is waiting on the answer, e.g. a
+//!   region capture they are about to ask about) start before any
+//!   [`Priority::Background`] job, oldest first within a priority.
+//! - [`OcrQueue::set_paused`] holds background jobs back, for the host to
+//!   call while the machine is on battery or running hot. User jobs still
+//!   start.
+//!
+//! [`OcrQueue::status`] reports where a job is, for as long as the queue
+//! remembers it: the last [`FINISHED_STATUSES_KEPT`] finished jobs.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use tokio::sync::oneshot;
+
+/// How many finished jobs [`OcrQueue::status`] still knows about.
+pub const FINISHED_STATUSES_KEPT: usize = 256;
+
+/// Who is waiting on a job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    /// Indexing nobody is watching, e.g. the timeline's frames.
+    Background,
+    /// The user asked for it and is waiting.
+    User,
+}
+
+/// Where a job is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Finished,
+}
+
+/// Identifies a submitted job for [`OcrQueue::status`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct JobId(u64);
+
+/// A submitted job: its id, and its output once it has run. The output
+/// is `None` if the job panicked.
+pub struct OcrJob<T> {
+    pub id: JobId,
+    output: oneshot::Receiver<T>,
+}
+
+impl<T> OcrJob<T> {
+    pub async fn output(self) -> Option<T> {
+        self.output.await.ok()
+    }
+}
+
+/// Half the cores, at least one: background OCR leaves room for the UI
+/// and whatever the user is doing.
+pub fn default_concurrency() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| (n.get() / 2).max(1))
+        .unwrap_or(1)
+}
+
+/// The queue. Cheap to clone; clones share the queue.
+#[derive(Clone)]
+pub struct OcrQueue {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    concurrency: usize,
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    queued: BinaryHeap<Queued>,
+    running: usize,
+    paused: bool,
+    next_id: u64,
+    statuses: HashMap<JobId, JobStatus>,
+    finished: VecDeque<JobId>,
+}
+
+struct Queued {
+    priority: Priority,
+    id: JobId,
+    work: Box<dyn FnOnce() + Send>,
+}
+
+impl Queued {
+    /// Higher priority first, then lower (older) id.
+    fn rank(&self) -> (Priority, Reverse<JobId>) {
+        (self.priority, Reverse(self.id))
+    }
+}
+
+impl PartialEq for Queued {
+    fn eq(&self, other: &Self) -> bool {
+        self.rank() == other.rank()
+    }
+}
+
+impl Eq for Queued {}
+
+impl PartialOrd for Queued {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Queued {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.rank().cmp(&other.rank())
+    }
+}
+
+impl Default for OcrQueue {
+    fn default() -> Self {
+        Self::new(default_concurrency())
+    }
+}
+
+impl OcrQueue {
+    /// A queue running at most `concurrency` jobs at once (at least one).
+    pub fn new(concurrency: usize) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                concurrency: concurrency.max(1),
+                state: Mutex::new(State::default()),
+            }),
+        }
+    }
+
+    /// Queue `work` to run on the blocking pool. Must be called from
+    /// within a tokio runtime.
+    pub fn submit<T, F>(&self, priority: Priority, work: F) -> OcrJob<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+        let id = {
+            let mut state = self.inner.lock();
+            let id = JobId(state.next_id);
+            state.next_id += 1;
+            state.statuses.insert(id, JobStatus::Queued);
+            state.queued.push(Queued {
+                priority,
+                id,
+                work: Box::new(move || {
+                    let _ = tx.send(work());
+                }),
+            });
+            id
+        };
+        Inner::pump(&self.inner);
+        OcrJob { id, output: rx }
+    }
+
+    /// Hold background jobs back (`true`) or let them run again.
+    pub fn set_paused(&self, paused: bool) {
+        self.inner.lock().paused = paused;
+        if !paused {
+            Inner::pump(&self.inner);
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.inner.lock().paused
+    }
+
+    /// `None` for a job the queue never had or has forgotten.
+    pub fn status(&self, id: JobId) -> Option<JobStatus> {
+        self.inner.lock().statuses.get(&id).copied()
+    }
+
+    /// How many jobs are waiting to start.
+    pub fn queued(&self) -> usize {
+        self.inner.lock().queued.len()
+    }
+}
+
+impl Inner {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Start queued jobs while there is room.
+    fn pump(this: &Arc<Self>) {
+        let mut state = this.lock();
+        while state.running < this.concurrency {
+            let startable = state
+                .queued
+                .peek()
+                .is_some_and(|job| !state.paused || job.priority == Priority::User);
+            if !startable {
+                break;
+            }
+            let Some(job) = state.queued.pop() else {
+                break;
+            };
+            state.running += 1;
+            state.statuses.insert(job.id, JobStatus::Running);
+            let inner = Arc::clone(this);
+            tokio::task::spawn_blocking(move || {
+                // Finish even if the job panics, so its slot frees up.
+                let finish = Finish { inner, id: job.id };
+                (job.work)();
+                drop(finish);
+            });
+        }
+    }
+
+    fn finish(this: &Arc<Self>, id: JobId) {
+        {
+            let mut state = this.lock();
+            state.running -= 1;
+            state.statuses.insert(id, JobStatus::Finished);
+            state.finished.push_back(id);
+            while state.finished.len() > FINISHED_STATUSES_KEPT {
+                if let Some(old) = state.finished.pop_front() {
+                    state.statuses.remove(&old);
+                }
+            }
+        }
+        Self::pump(this);
+    }
+}
+
+struct Finish {
+    inner: Arc<Inner>,
+    id: JobId,
+}
+
+impl Drop for Finish {
+    fn drop(&mut self) {
+        Inner::finish(&self.inner, self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn user_jobs_overtake_queued_background_jobs() {
+        let queue = OcrQueue::new(1);
+        let (gate_tx, gate_rx) = mpsc::channel::<()>();
+        let blocker = queue.submit(Priority::Background, move || {
+            gate_rx.recv().ok();
+        });
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let record = |name: &'static str| {
+            let order = Arc::clone(&order);
+            move || order.lock().unwrap().push(name)
+        };
+        let background = queue.submit(Priority::Background, record("background"));
+        let user = queue.submit(Priority::User, record("user"));
+        assert_eq!(queue.status(background.id), Some(JobStatus::Queued));
+
+        gate_tx.send(()).unwrap();
+        blocker.output().await.unwrap();
+        let (background_id, user_id) = (background.id, user.id);
+        background.output().await.unwrap();
+        user.output().await.unwrap();
+
+        assert_eq!(*order.lock().unwrap(), ["user", "background"]);
+        assert_eq!(queue.status(background_id), Some(JobStatus::Finished));
+        assert_eq!(queue.status(user_id), Some(JobStatus::Finished));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn paused_queue_still_runs_user_jobs() {
+        let queue = OcrQueue::new(2);
+        queue.set_paused(true);
+        let background = queue.submit(Priority::Background, || 1);
+        let user = queue.submit(Priority::User, || 2);
+
+        assert_eq!(user.output().await, Some(2));
+        assert_eq!(queue.status(background.id), Some(JobStatus::Queued));
+
+        queue.set_paused(false);
+        assert_eq!(background.output().await, Some(1));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn a_panicking_job_frees_its_slot() {
+        let queue = OcrQueue::new(1);
+        let panicked = queue.submit(Priority::User, || -> u8 { panic!("boom") });
+        let next = queue.submit(Priority::User, || 7);
+
+        assert_eq!(panicked.output().await, None);
+        assert_eq!(next.output().await, Some(7));
+    }
+}
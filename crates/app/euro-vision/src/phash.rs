@@ -0,0 +1,79 @@
+//! Perceptual hashing, for telling near-identical frames apart from
+//! frames that actually changed.
+//!
+//! A [`FrameHash`] is a 64-bit difference hash (dHash): the frame is
+//! shrunk to 9×8 greyscale and each bit records whether a pixel is
+//! brighter than its right-hand neighbour. Re-encoding, a blinking caret
+//! or a clock ticking over moves a few bits at most; a different page or
+//! window moves dozens. The Hamming distance between two hashes is the
+//! measure of how different the frames look.
+
+use image::{RgbaImage, imageops::FilterType};
+
+/// Hashes this many bits apart or fewer look the same to a person.
+pub const DEFAULT_SIMILARITY_THRESHOLD: u32 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FrameHash(pub u64);
+
+impl FrameHash {
+    pub fn of(image: &RgbaImage) -> Self {
+        let grey = image::imageops::grayscale(image);
+        let small = image::imageops::resize(&grey, 9, 8, FilterType::Triangle);
+        let mut bits = 0u64;
+        for y in 0..8 {
+            for x in 0..8 {
+                let brighter = small.get_pixel(x, y)[0] > small.get_pixel(x + 1, y)[0];
+                bits = (bits << 1) | u64::from(brighter);
+            }
+        }
+        Self(bits)
+    }
+
+    /// How many of the 64 bits differ: 0 for frames that look the same.
+    pub fn distance(self, other: FrameHash) -> u32 {
+        (self.0 ^ other.0).count_ones()
+    }
+
+    pub fn is_similar(self, other: FrameHash, threshold: u32) -> bool {
+        self.distance(other) <= threshold
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gradient(width: u32, height: u32, flip: bool) -> RgbaImage {
+        RgbaImage::from_fn(width, height, |x, _| {
+            let v = (x * 255 / width) as u8;
+            let v = if flip { 255 - v } else { v };
+            image::Rgba([v, v, v, 255])
+        })
+    }
+
+    #[test]
+    fn small_changes_stay_within_threshold() {
+        let frame = gradient(320, 200, false);
+        let mut caret = frame.clone();
+        for y in 90..110 {
+            caret.put_pixel(160, y, image::Rgba([0, 0, 0, 255]));
+        }
+        let (a, b) = (FrameHash::of(&frame), FrameHash::of(&caret));
+        assert!(a.is_similar(b, DEFAULT_SIMILARITY_THRESHOLD));
+    }
+
+    #[test]
+    fn scaling_keeps_the_hash() {
+        let a = FrameHash::of(&gradient(1600, 1000, false));
+        let b = FrameHash::of(&gradient(400, 250, false));
+        assert!(a.is_similar(b, DEFAULT_SIMILARITY_THRESHOLD));
+    }
+
+    #[test]
+    fn different_frames_are_far_apart() {
+        let a = FrameHash::of(&gradient(320, 200, false));
+        let b = FrameHash::of(&gradient(320, 200, true));
+        assert!(a.distance(b) > 32);
+    }
+}
@@ -0,0 +1,204 @@
+//! Region capture: freeze the monitor under the cursor, let the user drag
+//! out a rectangle over the still, then crop it.
+//!
+//! Selecting over a still rather than the live screen keeps the selection
+//! overlay itself out of the capture, and means what the user framed is
+//! exactly what gets attached even if the screen changes meanwhile. The
+//! overlay reports its rectangle as fractions of its own size, which keeps
+//! this module out of the logical-vs-physical pixel question entirely.
+
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64_STANDARD};
+use image::{ImageFormat, RgbImage, RgbaImage, buffer::ConvertBuffer};
+
+use crate::annotate::{Annotation, Point, annotate};
+use crate::capture::CaptureError;
+use crate::layout::MonitorLayout;
+
+/// Rectangles narrower or shorter than this, in pixels, are a stray click
+/// rather than a selection.
+const MIN_EDGE_PX: u32 = 8;
+
+/// A rectangle on a [`ScreenFrame`], as fractions of its width and height.
+/// `width` and `height` may be negative when the user dragged up or left.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Selection {
+    pub left: f64,
+    pub top: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// A still of one monitor, held while the user selects a region on it.
+#[derive(Debug, Clone)]
+pub struct ScreenFrame {
+    image: RgbaImage,
+}
+
+impl ScreenFrame {
+    pub fn new(image: RgbaImage) -> Self {
+        Self { image }
+    }
+
+    /// The frame as a base64 JPEG (no `data:` prefix) for the overlay to
+    /// draw under the selection. JPEG because PNG-encoding a large monitor
+    /// at full resolution takes long enough to make the overlay lag the
+    /// hotkey.
+    pub fn preview_jpeg_base64(&self) -> Result<String, image::ImageError> {
+        let rgb: RgbImage = self.image.convert();
+        let mut bytes = Vec::new();
+        rgb.write_to(&mut std::io::Cursor::new(&mut bytes), ImageFormat::Jpeg)?;
+        Ok(BASE64_STANDARD.encode(&bytes))
+    }
+
+    /// The pixels under `selection` at full resolution, or `None` when it
+    /// is too small to be deliberate.
+    pub fn crop(&self, selection: Selection) -> Option<RgbaImage> {
+        self.crop_annotated(selection, &[])
+    }
+
+    /// [`Self::crop`] with `annotations` drawn on the crop. They are placed
+    /// in fractions of the frame, like the selection, since that is the
+    /// space the user drew them in.
+    pub fn crop_annotated(
+        &self,
+        selection: Selection,
+        annotations: &[Annotation],
+    ) -> Option<RgbaImage> {
+        let (width, height) = self.image.dimensions();
+        let (x, y, w, h) = pixel_rect(selection, width, height)?;
+        let mut cropped = image::imageops::crop_imm(&self.image, x, y, w, h).to_image();
+        if !annotations.is_empty() {
+            let to_crop = |p: Point| Point {
+                x: (p.x * f64::from(width) - f64::from(x)) / f64::from(w),
+                y: (p.y * f64::from(height) - f64::from(y)) / f64::from(h),
+            };
+            let annotations: Vec<_> = annotations
+                .iter()
+                .map(|annotation| annotation.map_points(to_crop))
+                .collect();
+            annotate(&mut cropped, &annotations);
+        }
+        Some(cropped)
+    }
+}
+
+/// Freeze the monitor containing the logical point `(x, y)` (see
+/// [`crate::layout`]). Falls back to the primary monitor when no monitor
+/// contains the point, and returns `Ok(None)` when the OS exposes no
+/// monitors at all.
+pub async fn freeze_monitor_at(x: f64, y: f64) -> Result<Option<ScreenFrame>, CaptureError> {
+    tokio::task::spawn_blocking(move || freeze_monitor_at_blocking(x, y)).await?
+}
+
+fn freeze_monitor_at_blocking(x: f64, y: f64) -> Result<Option<ScreenFrame>, CaptureError> {
+    let monitors = xcap::Monitor::all()?;
+    let layout = MonitorLayout::from_xcap(&monitors)?;
+    let index = match layout.index_at(x, y) {
+        Some(index) => index,
+        None => {
+            tracing::debug!("No monitor at ({x}, {y}), using the primary");
+            let Some(index) = layout.primary_index() else {
+                return Ok(None);
+            };
+            index
+        }
+    };
+    Ok(Some(ScreenFrame::new(monitors[index].capture_image()?)))
+}
+
+/// `selection` in whole pixels of a `width` × `height` frame, as
+/// `(x, y, width, height)`, clamped to the frame.
+fn pixel_rect(selection: Selection, width: u32, height: u32) -> Option<(u32, u32, u32, u32)> {
+    let edge = |fraction: f64, size: u32| {
+        // `as` saturates, and maps NaN to 0.
+        (fraction.clamp(0.0, 1.0) * f64::from(size)).round() as u32
+    };
+    let (x0, x1) = (
+        edge(selection.left, width),
+        edge(selection.left + selection.width, width),
+    );
+    let (y0, y1) = (
+        edge(selection.top, height),
+        edge(selection.top + selection.height, height),
+    );
+    let (left, right) = (x0.min(x1), x0.max(x1));
+    let (top, bottom) = (y0.min(y1), y0.max(y1));
+    (right - left >= MIN_EDGE_PX && bottom - top >= MIN_EDGE_PX).then_some((
+        left,
+        top,
+        right - left,
+        bottom - top,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn selection(left: f64, top: f64, width: f64, height: f64) -> Selection {
+        Selection {
+            left,
+            top,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn fractions_map_to_pixels() {
+        assert_eq!(
+            pixel_rect(selection(0.25, 0.5, 0.5, 0.25), 400, 200),
+            Some((100, 100, 200, 50))
+        );
+    }
+
+    #[test]
+    fn backwards_drags_and_overhangs_are_normalised() {
+        assert_eq!(
+            pixel_rect(selection(0.75, 0.75, -0.5, -1.0), 400, 200),
+            Some((100, 0, 200, 150))
+        );
+        assert_eq!(
+            pixel_rect(selection(-0.5, 0.5, 1.0, 2.0), 400, 200),
+            Some((0, 100, 200, 100))
+        );
+    }
+
+    #[test]
+    fn clicks_are_not_selections() {
+        assert_eq!(pixel_rect(selection(0.5, 0.5, 0.0, 0.0), 400, 200), None);
+        assert_eq!(pixel_rect(selection(0.5, 0.5, 0.01, 0.5), 400, 200), None);
+        assert_eq!(
+            pixel_rect(selection(f64::NAN, 0.0, 0.5, 0.5), 400, 200),
+            None
+        );
+    }
+
+    #[test]
+    fn crop_keeps_the_selected_pixels() {
+        let frame = ScreenFrame::new(RgbaImage::from_fn(100, 100, |x, y| {
+            image::Rgba([x as u8, y as u8, 0, 255])
+        }));
+        let cropped = frame.crop(selection(0.1, 0.2, 0.5, 0.3)).expect("crop");
+        assert_eq!(cropped.dimensions(), (50, 30));
+        assert_eq!(cropped.get_pixel(0, 0), &image::Rgba([10, 20, 0, 255]));
+    }
+
+    #[test]
+    fn annotations_follow_the_crop() {
+        let frame = ScreenFrame::new(RgbaImage::from_pixel(100, 100, image::Rgba([0, 0, 0, 255])));
+        let outline = Annotation::new(crate::annotate::Shape::Box {
+            from: Point { x: 0.5, y: 0.5 },
+            to: Point { x: 0.6, y: 0.6 },
+        });
+        let cropped = frame
+            .crop_annotated(
+                selection(0.4, 0.4, 0.4, 0.4),
+                std::slice::from_ref(&outline),
+            )
+            .expect("crop");
+        // The box's corner at (50, 50) in the frame is (10, 10) in the crop.
+        assert_eq!(cropped.get_pixel(10, 10), &outline.color);
+        assert_eq!(cropped.get_pixel(5, 5), &image::Rgba([0, 0, 0, 255]));
+    }
+}
@@ -0,0 +1,140 @@
+//! Speech recognizers.
+
+use async_trait::async_trait;
+use secrecy::{ExposeSecret, SecretString};
+use serde::Deserialize;
+
+use crate::audio::encode_wav;
+use crate::error::{VoiceError, VoiceResult};
+
+/// Turns 16 kHz mono audio into text.
+#[async_trait]
+pub trait Transcriber: Send + Sync {
+    async fn transcribe(&self, samples: &[f32], language: Option<&str>) -> VoiceResult<String>;
+
+    /// Whether to re-run on the growing buffer while the user is still
+    /// talking. Off for recognizers billed per request.
+    fn supports_partials(&self) -> bool {
+        true
+    }
+}
+
+/// An OpenAI-compatible `/audio/transcriptions` endpoint.
+pub struct ProviderTranscriber {
+    http: reqwest::Client,
+    url: String,
+    api_key: SecretString,
+    model: String,
+}
+
+impl ProviderTranscriber {
+    /// `base_url` is the API root, e.g. `https://api.openai.com/v1`.
+    pub fn new(
+        http: reqwest::Client,
+        base_url: &str,
+        api_key: SecretString,
+        model: String,
+    ) -> Self {
+        Self {
+            http,
+            url: format!("{}/audio/transcriptions", base_url.trim_end_matches('/')),
+            api_key,
+            model,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct TranscriptionResponse {
+    text: String,
+}
+
+#[async_trait]
+impl Transcriber for ProviderTranscriber {
+    async fn transcribe(&self, samples: &[f32], language: Option<&str>) -> VoiceResult<String> {
+        let file = reqwest::multipart::Part::bytes(encode_wav(samples))
+            .file_name("speech.wav")
+            .mime_str("audio/wav")
+            .map_err(|e| VoiceError::Transcription(e.to_string()))?;
+        let mut form = reqwest::multipart::Form::new()
+            .part("file", file)
+            .text("model", self.model.clone());
+        if let Some(language) = language {
+            form = form.text("language", language.to_owned());
+        }
+        let response = self
+            .http
+            .post(&self.url)
+            .bearer_auth(self.api_key.expose_secret())
+            .multipart(form)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| VoiceError::Transcription(e.to_string()))?;
+        let body: TranscriptionResponse = response
+            .json()
+            .await
+            .map_err(|e| VoiceError::Transcription(e.to_string()))?;
+        Ok(body.text.trim().to_owned())
+    }
+
+    fn supports_partials(&self) -> bool {
+        false
+    }
+}
+
+/// On-device recognition with a whisper.cpp `ggml` model.
+#[cfg(feature = "whisper")]
+pub struct WhisperTranscriber {
+    context: std::sync::Arc<whisper_rs::WhisperContext>,
+}
+
+#[cfg(feature = "whisper")]
+impl WhisperTranscriber {
+    /// Load the model at `path`. Slow for the larger models, so callers
+    /// keep the result around.
+    pub fn load(path: &std::path::Path) -> VoiceResult<Self> {
+        let path = path
+            .to_str()
+            .ok_or_else(|| VoiceError::Transcription("model path is not UTF-8".to_owned()))?;
+        let context = whisper_rs::WhisperContext::new_with_params(
+            path,
+            whisper_rs::WhisperContextParameters::default(),
+        )
+        .map_err(|e| VoiceError::Transcription(e.to_string()))?;
+        Ok(Self {
+            context: std::sync::Arc::new(context),
+        })
+    }
+}
+
+#[cfg(feature = "whisper")]
+#[async_trait]
+impl Transcriber for WhisperTranscriber {
+    async fn transcribe(&self, samples: &[f32], language: Option<&str>) -> VoiceResult<String> {
+        use whisper_rs::{FullParams, SamplingStrategy};
+
+        let context = self.context.clone();
+        let samples = samples.to_vec();
+        let language = language.unwrap_or("auto").to_owned();
+        tokio::task::spawn_blocking(move || {
+            let fail = |e: whisper_rs::WhisperError| VoiceError::Transcription(e.to_string());
+            let mut state = context.create_state().map_err(fail)?;
+            let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+            params.set_language(Some(language.as_str()));
+            params.set_print_progress(false);
+            params.set_print_realtime(false);
+            params.set_print_special(false);
+            params.set_print_timestamps(false);
+            state.full(params, &samples).map_err(fail)?;
+
+            let mut text = String::new();
+            for segment in 0..state.full_n_segments().map_err(fail)? {
+                text.push_str(&state.full_get_segment_text(segment).map_err(fail)?);
+            }
+            Ok(text.trim().to_owned())
+        })
+        .await
+        .map_err(|e| VoiceError::Transcription(e.to_string()))?
+    }
+}
@@ -0,0 +1,85 @@
+//! Sample conversion between what the microphone produces and what the
+//! recognizers expect: 16 kHz mono `f32`.
+
+/// Sample rate every [`Transcriber`](crate::Transcriber) receives.
+pub const SAMPLE_RATE: u32 = 16_000;
+
+/// Downmix interleaved `input` with `channels` channels recorded at `rate`
+/// Hz to mono and resample it to [`SAMPLE_RATE`] by linear interpolation.
+pub fn to_mono_16k(input: &[f32], channels: usize, rate: u32) -> Vec<f32> {
+    let channels = channels.max(1);
+    let mono: Vec<f32> = input
+        .chunks_exact(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect();
+    if rate == SAMPLE_RATE || mono.is_empty() {
+        return mono;
+    }
+
+    let step = rate as f64 / SAMPLE_RATE as f64;
+    let len = (mono.len() as f64 / step).floor() as usize;
+    (0..len)
+        .map(|i| {
+            let pos = i as f64 * step;
+            let index = pos as usize;
+            let frac = (pos - index as f64) as f32;
+            let a = mono[index];
+            let b = mono.get(index + 1).copied().unwrap_or(a);
+            a + (b - a) * frac
+        })
+        .collect()
+}
+
+/// Encode 16 kHz mono `samples` as a 16-bit PCM WAV file, the format every
+/// hosted transcription API accepts.
+pub fn encode_wav(samples: &[f32]) -> Vec<u8> {
+    let data_len = (samples.len() * 2) as u32;
+    let mut wav = Vec::with_capacity(44 + data_len as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&1u16.to_le_bytes()); // mono
+    wav.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    wav.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        let pcm = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        wav.extend_from_slice(&pcm.to_le_bytes());
+    }
+    wav
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stereo_48k_becomes_mono_16k() {
+        let input: Vec<f32> = (0..4_800).flat_map(|_| [0.5, -0.5]).collect();
+        let output = to_mono_16k(&input, 2, 48_000);
+        assert_eq!(output.len(), 1_600);
+        assert!(output.iter().all(|s| *s == 0.0));
+    }
+
+    #[test]
+    fn native_rate_is_passed_through() {
+        let input = vec![0.1, 0.2, 0.3];
+        assert_eq!(to_mono_16k(&input, 1, SAMPLE_RATE), input);
+    }
+
+    #[test]
+    fn wav_header_describes_the_payload() {
+        let wav = encode_wav(&[0.0, 1.0, -1.0]);
+        assert_eq!(wav.len(), 44 + 6);
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(&wav[8..12], b"WAVE");
+        assert_eq!(u32::from_le_bytes(wav[24..28].try_into().unwrap()), 16_000);
+        assert_eq!(u32::from_le_bytes(wav[40..44].try_into().unwrap()), 6);
+        assert_eq!(i16::from_le_bytes([wav[46], wav[47]]), i16::MAX);
+    }
+}
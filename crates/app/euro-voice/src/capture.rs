@@ -0,0 +1,112 @@
+//! Microphone capture.
+//!
+//! `cpal` streams are not `Send` on every platform, so each capture owns
+//! a thread that builds the stream, keeps it alive and drops it when the
+//! [`Microphone`] is dropped.
+
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SampleFormat, SizedSample, Stream, StreamConfig};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::audio::to_mono_16k;
+use crate::error::{VoiceError, VoiceResult};
+
+/// A running capture from the default input device. Recording stops when
+/// this is dropped, which also closes the sample channel.
+pub struct Microphone {
+    stop: Option<mpsc::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Microphone {
+    /// Start recording, sending 16 kHz mono chunks to `samples`. Returns
+    /// once the device is open, or with the reason it could not be.
+    pub fn start(samples: UnboundedSender<Vec<f32>>) -> VoiceResult<Self> {
+        let (stop_tx, stop_rx) = mpsc::channel();
+        let (ready_tx, ready_rx) = mpsc::channel();
+        let thread = std::thread::Builder::new()
+            .name("voice-capture".to_owned())
+            .spawn(move || match open_stream(samples) {
+                Ok(stream) => {
+                    let _ = ready_tx.send(Ok(()));
+                    // Blocks until the sender is used or dropped.
+                    let _ = stop_rx.recv();
+                    drop(stream);
+                }
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e));
+                }
+            })
+            .map_err(|e| VoiceError::Capture(e.to_string()))?;
+
+        ready_rx
+            .recv()
+            .map_err(|_| VoiceError::Capture("capture thread exited".to_owned()))??;
+        Ok(Self {
+            stop: Some(stop_tx),
+            thread: Some(thread),
+        })
+    }
+}
+
+impl Drop for Microphone {
+    fn drop(&mut self) {
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn open_stream(samples: UnboundedSender<Vec<f32>>) -> VoiceResult<Stream> {
+    let device = cpal::default_host()
+        .default_input_device()
+        .ok_or(VoiceError::NoInputDevice)?;
+    let supported = device
+        .default_input_config()
+        .map_err(|e| VoiceError::Capture(e.to_string()))?;
+    let format = supported.sample_format();
+    let config: StreamConfig = supported.into();
+
+    let stream = match format {
+        SampleFormat::F32 => build_stream::<f32>(&device, &config, samples),
+        SampleFormat::I16 => build_stream::<i16>(&device, &config, samples),
+        SampleFormat::U16 => build_stream::<u16>(&device, &config, samples),
+        SampleFormat::I32 => build_stream::<i32>(&device, &config, samples),
+        other => Err(VoiceError::Capture(format!(
+            "unsupported sample format {other}"
+        ))),
+    }?;
+    stream
+        .play()
+        .map_err(|e| VoiceError::Capture(e.to_string()))?;
+    Ok(stream)
+}
+
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &StreamConfig,
+    samples: UnboundedSender<Vec<f32>>,
+) -> VoiceResult<Stream>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let channels = config.channels as usize;
+    let rate = config.sample_rate.0;
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _| {
+                let input: Vec<f32> = data.iter().map(|s| f32::from_sample(*s)).collect();
+                // The receiver is gone once the session has finished.
+                let _ = samples.send(to_mono_16k(&input, channels, rate));
+            },
+            |e| tracing::warn!(error = %e, "Microphone stream error"),
+            None,
+        )
+        .map_err(|e| VoiceError::Capture(e.to_string()))
+}
@@ -0,0 +1,21 @@
+use thiserror::Error;
+
+pub type VoiceResult<T> = std::result::Result<T, VoiceError>;
+
+#[derive(Debug, Error)]
+pub enum VoiceError {
+    #[error("No microphone available")]
+    NoInputDevice,
+
+    #[error("Microphone error: {0}")]
+    Capture(String),
+
+    #[error("Transcription failed: {0}")]
+    Transcription(String),
+
+    #[error("No speech recognizer configured")]
+    NoTranscriber,
+
+    #[error("Speech output failed: {0}")]
+    Speech(String),
+}
@@ -0,0 +1,42 @@
+//! Voice for the launcher: push-to-talk questions and spoken answers.
+//!
+//! A [`VoiceSession`] covers one utterance: it opens the default
+//! microphone when the shortcut is pressed, streams [`VoiceEvent::Partial`]
+//! transcripts into the question box while the user talks, and ends with a
+//! [`VoiceEvent::Final`] when the key is released or, with auto-submit on,
+//! once the user has been quiet for a moment.
+//!
+//! Recognition goes through the [`Transcriber`] trait:
+//!
+//! - `WhisperTranscriber` runs whisper.cpp on-device. Behind the
+//!   `whisper` feature, since it builds the C++ library.
+//! - [`ProviderTranscriber`] posts the audio to an OpenAI-compatible
+//!   `/audio/transcriptions` endpoint, for builds or machines without a
+//!   local model.
+//!
+//! Answers are read aloud by a [`SpeechReader`], which takes a response
+//! as it streams in and speaks it sentence by sentence through the
+//! platform's voices or, behind the `provider-tts` feature, a hosted
+//! `/audio/speech` endpoint.
+
+mod asr;
+mod audio;
+mod capture;
+mod error;
+mod sentences;
+mod session;
+mod speech;
+mod vad;
+
+#[cfg(feature = "whisper")]
+pub use asr::WhisperTranscriber;
+pub use asr::{ProviderTranscriber, Transcriber};
+pub use audio::{SAMPLE_RATE, encode_wav, to_mono_16k};
+pub use capture::Microphone;
+pub use error::{VoiceError, VoiceResult};
+pub use sentences::SentenceSplitter;
+pub use session::{VoiceConfig, VoiceEvent, VoiceSession};
+#[cfg(feature = "provider-tts")]
+pub use speech::ProviderSpeech;
+pub use speech::{PlatformSpeech, SpeechBackend, SpeechReader, SpeechState, SpeechVoice};
+pub use vad::SilenceDetector;
@@ -0,0 +1,169 @@
+//! Sentence chunking for streamed responses, so speech can start on the
+//! first sentence instead of waiting for the whole answer.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+/// Words that end in a period without ending the sentence.
+const ABBREVIATIONS: &[&str] = &["e.g", "i.e", "mr", "mrs", "ms", "dr", "vs", "st", "no"];
+
+static LINK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[([^\]]*)\]\([^)]*\)").expect("valid regex"));
+static LINE_MARKER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(#{1,6}|[-*+]|\d+[.)]|>)\s+").expect("valid regex"));
+
+/// Cuts markdown text, fed in arbitrary pieces, into speakable sentences.
+///
+/// Markdown decoration is dropped and fenced code blocks are skipped
+/// entirely: code read aloud is noise.
+#[derive(Debug, Default)]
+pub struct SentenceSplitter {
+    /// The unfinished line, minus any sentences already returned from it.
+    pending: String,
+    in_code: bool,
+}
+
+impl SentenceSplitter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `text` and return the sentences it completed.
+    pub fn push(&mut self, text: &str) -> Vec<String> {
+        self.pending.push_str(text);
+        let mut sentences = Vec::new();
+        while let Some(newline) = self.pending.find('\n') {
+            let line: String = self.pending.drain(..=newline).collect();
+            self.complete_line(&line, &mut sentences);
+        }
+        // Wait for the rest of a line that may open or close a fence.
+        if !self.in_code && !self.pending.trim_start().starts_with('`') {
+            let consumed = split(&self.pending, &mut sentences);
+            self.pending.drain(..consumed);
+        }
+        sentences
+    }
+
+    /// The end of the text: return whatever is left.
+    pub fn finish(&mut self) -> Vec<String> {
+        let line = std::mem::take(&mut self.pending);
+        let mut sentences = Vec::new();
+        self.complete_line(&line, &mut sentences);
+        self.in_code = false;
+        sentences
+    }
+
+    fn complete_line(&mut self, line: &str, sentences: &mut Vec<String>) {
+        if line.trim_start().starts_with("```") {
+            self.in_code = !self.in_code;
+            return;
+        }
+        if self.in_code {
+            return;
+        }
+        let consumed = split(line, sentences);
+        push_clean(&line[consumed..], sentences);
+    }
+}
+
+/// Append the complete sentences at the start of `text` to `sentences`
+/// and return how many bytes they took up.
+fn split(text: &str, sentences: &mut Vec<String>) -> usize {
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if !matches!(c, '.' | '!' | '?' | '…') {
+            continue;
+        }
+        // The next character decides; at the end of the text, wait.
+        let Some(&(next_i, next)) = chars.peek() else {
+            break;
+        };
+        if !next.is_whitespace() || (c == '.' && !ends_sentence(&text[start..i])) {
+            continue;
+        }
+        push_clean(&text[start..next_i], sentences);
+        start = next_i;
+    }
+    start
+}
+
+/// Whether a period after `text` ends a sentence rather than an
+/// abbreviation or a list number.
+fn ends_sentence(text: &str) -> bool {
+    let word = text
+        .rsplit(char::is_whitespace)
+        .next()
+        .unwrap_or_default()
+        .trim_start_matches(['(', '"', '\''])
+        .to_lowercase();
+    let list_number =
+        !word.is_empty() && word.len() <= 3 && word.bytes().all(|b| b.is_ascii_digit());
+    !(ABBREVIATIONS.contains(&word.as_str()) || list_number)
+}
+
+fn push_clean(text: &str, sentences: &mut Vec<String>) {
+    let text = LINE_MARKER.replace(text.trim(), "");
+    let text = LINK.replace_all(&text, "$1");
+    let text: String = text
+        .chars()
+        .filter(|c| !matches!(c, '*' | '`' | '~' | '#'))
+        .collect();
+    let text = text.trim();
+    if text.chars().any(char::is_alphanumeric) {
+        sentences.push(text.to_owned());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed(chunks: &[&str]) -> Vec<String> {
+        let mut splitter = SentenceSplitter::new();
+        let mut sentences: Vec<String> = chunks
+            .iter()
+            .flat_map(|chunk| splitter.push(chunk))
+            .collect();
+        sentences.extend(splitter.finish());
+        sentences
+    }
+
+    #[test]
+    fn sentences_are_released_as_soon_as_they_end() {
+        let mut splitter = SentenceSplitter::new();
+        assert!(splitter.push("Hello the").is_empty());
+        assert!(splitter.push("re.").is_empty());
+        assert_eq!(splitter.push(" How are"), vec!["Hello there."]);
+        assert_eq!(splitter.finish(), vec!["How are"]);
+    }
+
+    #[test]
+    fn decimals_abbreviations_and_list_numbers_do_not_split() {
+        assert_eq!(
+            feed(&["Pi is 3.14, e.g. roughly. Ask Dr. Who! Fine?"]),
+            vec!["Pi is 3.14, e.g. roughly.", "Ask Dr. Who!", "Fine?"]
+        );
+        assert_eq!(
+            feed(&["Steps:\n1. Open it.\n2. Close it.\n"]),
+            vec!["Steps:", "Open it.", "Close it."]
+        );
+    }
+
+    #[test]
+    fn code_blocks_are_skipped() {
+        assert_eq!(
+            feed(&["Run this:\n``", "`rust\nfn main() {}\n```\nDone."]),
+            vec!["Run this:", "Done."]
+        );
+    }
+
+    #[test]
+    fn markdown_is_stripped() {
+        assert_eq!(
+            feed(&["## Summary\n- **Bold** and `code` with [a link](https://x.y).\n"]),
+            vec!["Summary", "Bold and code with a link."]
+        );
+    }
+}
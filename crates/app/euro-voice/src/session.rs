@@ -0,0 +1,355 @@
+//! One push-to-talk utterance, from key press to final transcript.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::sync::mpsc::{UnboundedReceiver, unbounded_channel};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+#[cfg(feature = "specta")]
+use specta::Type;
+
+use crate::asr::Transcriber;
+use crate::audio::SAMPLE_RATE;
+use crate::capture::Microphone;
+use crate::error::VoiceResult;
+use crate::vad::SilenceDetector;
+
+#[derive(Debug, Clone)]
+pub struct VoiceConfig {
+    /// BCP-47 language hint; `None` lets the recognizer detect it.
+    pub language: Option<String>,
+    /// End the utterance after this much quiet following speech and submit
+    /// the transcript. When unset, only releasing the key ends it and the
+    /// transcript is left in the question box.
+    pub auto_submit_after: Option<Duration>,
+    /// How much new audio to collect between partial transcripts.
+    pub partial_interval: Duration,
+    /// Hard cap on a single utterance.
+    pub max_duration: Duration,
+    /// RMS level above which audio counts as speech.
+    pub speech_threshold: f32,
+}
+
+impl Default for VoiceConfig {
+    fn default() -> Self {
+        Self {
+            language: None,
+            auto_submit_after: Some(Duration::from_millis(1_500)),
+            partial_interval: Duration::from_secs(1),
+            max_duration: Duration::from_secs(60),
+            speech_threshold: 0.015,
+        }
+    }
+}
+
+/// Progress of a [`VoiceSession`], in order: `Listening`, any number of
+/// `Partial`s, then exactly one of `Final` or `Failed` unless the session
+/// is cancelled.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum VoiceEvent {
+    /// The microphone is open.
+    Listening,
+    /// Transcript of everything said so far; replaces the previous one.
+    Partial { text: String },
+    /// The finished transcript. `submit` asks for the question to be sent
+    /// as is; it is never set for an empty transcript.
+    Final { text: String, submit: bool },
+    /// Recognition failed; `message` says why.
+    Failed { message: String },
+}
+
+enum Stop {
+    Finish,
+    Cancel,
+}
+
+/// A running utterance. Finish it with [`stop`](Self::stop) when the key
+/// is released, or throw it away with [`cancel`](Self::cancel); dropping
+/// it cancels too.
+pub struct VoiceSession {
+    stop: Option<oneshot::Sender<Stop>>,
+    task: JoinHandle<()>,
+}
+
+impl VoiceSession {
+    /// Open the microphone and start transcribing. `on_event` is called
+    /// from a Tokio task.
+    pub fn start<F>(
+        transcriber: Arc<dyn Transcriber>,
+        config: VoiceConfig,
+        on_event: F,
+    ) -> VoiceResult<Self>
+    where
+        F: Fn(VoiceEvent) + Send + 'static,
+    {
+        let (tx, rx) = unbounded_channel();
+        let microphone = Microphone::start(tx)?;
+        Ok(Self::spawn(
+            rx,
+            Some(microphone),
+            transcriber,
+            config,
+            on_event,
+        ))
+    }
+
+    fn spawn<F>(
+        samples: UnboundedReceiver<Vec<f32>>,
+        microphone: Option<Microphone>,
+        transcriber: Arc<dyn Transcriber>,
+        config: VoiceConfig,
+        on_event: F,
+    ) -> Self
+    where
+        F: Fn(VoiceEvent) + Send + 'static,
+    {
+        let (stop_tx, stop_rx) = oneshot::channel();
+        let task = tokio::spawn(run(
+            samples,
+            stop_rx,
+            microphone,
+            transcriber,
+            config,
+            on_event,
+        ));
+        Self {
+            stop: Some(stop_tx),
+            task,
+        }
+    }
+
+    /// Stop recording and transcribe what was said.
+    pub fn stop(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(Stop::Finish);
+        }
+    }
+
+    /// Stop recording and discard the audio. No further events are sent.
+    pub fn cancel(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(Stop::Cancel);
+        }
+    }
+
+    /// Whether the final event has been sent, or the session cancelled.
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+}
+
+impl Drop for VoiceSession {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+fn sample_count(duration: Duration) -> usize {
+    (duration.as_secs_f64() * SAMPLE_RATE as f64) as usize
+}
+
+async fn run<F>(
+    mut samples: UnboundedReceiver<Vec<f32>>,
+    mut stop: oneshot::Receiver<Stop>,
+    microphone: Option<Microphone>,
+    transcriber: Arc<dyn Transcriber>,
+    config: VoiceConfig,
+    on_event: F,
+) where
+    F: Fn(VoiceEvent) + Send + 'static,
+{
+    on_event(VoiceEvent::Listening);
+
+    let language = config.language.as_deref();
+    let partial_len = sample_count(config.partial_interval);
+    let max_len = sample_count(config.max_duration);
+    let mut detector = SilenceDetector::new(config.speech_threshold);
+    let mut buffer = Vec::new();
+    let mut transcribed_len = 0;
+
+    let submit = loop {
+        tokio::select! {
+            // Audio recorded before the key was released still counts.
+            biased;
+            chunk = samples.recv() => {
+                // The microphone went away; finish with what we have.
+                let Some(chunk) = chunk else { break false };
+                detector.push(&chunk);
+                buffer.extend(chunk);
+            }
+            signal = &mut stop => match signal {
+                Ok(Stop::Finish) => break false,
+                // A dropped handle counts as a cancel.
+                Ok(Stop::Cancel) | Err(_) => return,
+            },
+        }
+
+        if let Some(after) = config.auto_submit_after
+            && detector.heard_speech()
+            && detector.silence() >= after
+        {
+            break true;
+        }
+        if buffer.len() >= max_len {
+            break config.auto_submit_after.is_some();
+        }
+        if transcriber.supports_partials()
+            && detector.heard_speech()
+            && buffer.len() - transcribed_len >= partial_len
+        {
+            transcribed_len = buffer.len();
+            match transcriber.transcribe(&buffer, language).await {
+                Ok(text) if !text.is_empty() => on_event(VoiceEvent::Partial { text }),
+                Ok(_) => {}
+                Err(e) => tracing::debug!(error = %e, "Partial transcription failed"),
+            }
+        }
+    };
+    drop(microphone);
+
+    if !detector.heard_speech() {
+        on_event(VoiceEvent::Final {
+            text: String::new(),
+            submit: false,
+        });
+        return;
+    }
+    match transcriber.transcribe(&buffer, language).await {
+        Ok(text) => {
+            let submit = submit && !text.is_empty();
+            on_event(VoiceEvent::Final { text, submit });
+        }
+        Err(e) => on_event(VoiceEvent::Failed {
+            message: e.to_string(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+    use tokio::sync::mpsc::UnboundedSender;
+
+    use super::*;
+
+    /// Answers with the number of seconds it was given.
+    struct Seconds;
+
+    #[async_trait]
+    impl Transcriber for Seconds {
+        async fn transcribe(&self, samples: &[f32], _: Option<&str>) -> VoiceResult<String> {
+            Ok(format!("{}s", samples.len() / SAMPLE_RATE as usize))
+        }
+    }
+
+    fn speech(seconds: usize) -> Vec<f32> {
+        (0..seconds * SAMPLE_RATE as usize)
+            .map(|i| 0.5 * (i as f32 * 0.1).sin())
+            .collect()
+    }
+
+    fn quiet(seconds: usize) -> Vec<f32> {
+        vec![0.0; seconds * SAMPLE_RATE as usize]
+    }
+
+    fn session(
+        config: VoiceConfig,
+    ) -> (
+        VoiceSession,
+        UnboundedSender<Vec<f32>>,
+        Arc<Mutex<Vec<VoiceEvent>>>,
+    ) {
+        let (tx, rx) = unbounded_channel();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        let session = VoiceSession::spawn(rx, None, Arc::new(Seconds), config, move |event| {
+            sink.lock().unwrap().push(event)
+        });
+        (session, tx, events)
+    }
+
+    #[tokio::test]
+    async fn silence_after_speech_submits() {
+        let (mut session, tx, events) = session(VoiceConfig::default());
+        tx.send(speech(1)).unwrap();
+        tx.send(speech(1)).unwrap();
+        tx.send(quiet(2)).unwrap();
+        (&mut session.task).await.unwrap();
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                VoiceEvent::Listening,
+                VoiceEvent::Partial { text: "1s".into() },
+                VoiceEvent::Partial { text: "2s".into() },
+                VoiceEvent::Final {
+                    text: "4s".into(),
+                    submit: true
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn release_finishes_without_submitting_when_auto_submit_is_off() {
+        let (mut session, tx, events) = session(VoiceConfig {
+            auto_submit_after: None,
+            ..Default::default()
+        });
+        tx.send(speech(1)).unwrap();
+        tx.send(quiet(3)).unwrap();
+        session.stop();
+        (&mut session.task).await.unwrap();
+
+        let events = events.lock().unwrap();
+        assert_eq!(
+            events.last(),
+            Some(&VoiceEvent::Final {
+                text: "4s".into(),
+                submit: false
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn cancel_sends_nothing_more() {
+        let (mut session, tx, events) = session(VoiceConfig::default());
+        tx.send(speech(1)).unwrap();
+        session.cancel();
+        (&mut session.task).await.unwrap();
+
+        assert!(
+            !events
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|event| matches!(event, VoiceEvent::Final { .. }))
+        );
+    }
+
+    #[tokio::test]
+    async fn no_speech_gives_an_empty_transcript() {
+        let (mut session, tx, events) = session(VoiceConfig::default());
+        tx.send(quiet(1)).unwrap();
+        drop(tx);
+        (&mut session.task).await.unwrap();
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                VoiceEvent::Listening,
+                VoiceEvent::Final {
+                    text: String::new(),
+                    submit: false
+                },
+            ]
+        );
+    }
+}
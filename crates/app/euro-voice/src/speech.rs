@@ -0,0 +1,458 @@
+//! Reading assistant responses aloud.
+//!
+//! A [`SpeechReader`] owns a thread that feeds sentences to a
+//! [`SpeechBackend`] one at a time. Text is pushed in as it streams in and
+//! cut up by a [`SentenceSplitter`], so speech starts with the first
+//! sentence of a response.
+//!
+//! Backends are built on the reader's thread because neither platform
+//! voices nor audio output are `Send` everywhere.
+
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "specta")]
+use specta::Type;
+
+use crate::error::{VoiceError, VoiceResult};
+use crate::sentences::SentenceSplitter;
+
+/// How often a playing sentence is checked for completion.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Platform voices report "not speaking" for a moment after being handed
+/// text; a sentence isn't considered finished before this.
+const START_GRACE: Duration = Duration::from_millis(300);
+
+/// Something that can say one sentence at a time.
+pub trait SpeechBackend {
+    /// Start saying `text`, replacing anything still being said.
+    fn speak(&mut self, text: &str) -> VoiceResult<()>;
+    fn is_speaking(&self) -> VoiceResult<bool>;
+    fn stop(&mut self) -> VoiceResult<()>;
+}
+
+/// A voice installed on this machine.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+#[serde(rename_all = "camelCase")]
+pub struct SpeechVoice {
+    pub id: String,
+    pub name: String,
+    /// BCP-47 tag, e.g. `en-US`.
+    pub language: String,
+}
+
+/// The operating system's voices, through the `tts` crate.
+pub struct PlatformSpeech {
+    tts: tts::Tts,
+}
+
+impl PlatformSpeech {
+    /// `voice` is a [`SpeechVoice::id`]; unknown ids fall back to the
+    /// system default. `rate` multiplies the voice's normal pace.
+    pub fn new(voice: Option<&str>, rate: f32) -> VoiceResult<Self> {
+        let mut tts = tts::Tts::default().map_err(speech_error)?;
+        let features = tts.supported_features();
+        if features.rate {
+            let rate = (tts.normal_rate() * rate).clamp(tts.min_rate(), tts.max_rate());
+            tts.set_rate(rate).map_err(speech_error)?;
+        }
+        if features.voice
+            && let Some(id) = voice
+        {
+            match tts
+                .voices()
+                .map_err(speech_error)?
+                .iter()
+                .find(|v| v.id() == id)
+            {
+                Some(found) => {
+                    tts.set_voice(found).map_err(speech_error)?;
+                }
+                None => tracing::debug!(voice = id, "Unknown voice, using the default"),
+            }
+        }
+        Ok(Self { tts })
+    }
+
+    /// Voices to offer in settings. Empty where the platform can't pick.
+    pub fn voices() -> VoiceResult<Vec<SpeechVoice>> {
+        let tts = tts::Tts::default().map_err(speech_error)?;
+        if !tts.supported_features().voice {
+            return Ok(Vec::new());
+        }
+        Ok(tts
+            .voices()
+            .map_err(speech_error)?
+            .into_iter()
+            .map(|voice| SpeechVoice {
+                id: voice.id(),
+                name: voice.name(),
+                language: voice.language().to_string(),
+            })
+            .collect())
+    }
+}
+
+impl SpeechBackend for PlatformSpeech {
+    fn speak(&mut self, text: &str) -> VoiceResult<()> {
+        self.tts.speak(text, true).map(|_| ()).map_err(speech_error)
+    }
+
+    fn is_speaking(&self) -> VoiceResult<bool> {
+        self.tts.is_speaking().map_err(speech_error)
+    }
+
+    fn stop(&mut self) -> VoiceResult<()> {
+        self.tts.stop().map(|_| ()).map_err(speech_error)
+    }
+}
+
+fn speech_error(err: tts::Error) -> VoiceError {
+    VoiceError::Speech(err.to_string())
+}
+
+/// An OpenAI-compatible `/audio/speech` endpoint, played through the
+/// default output device.
+#[cfg(feature = "provider-tts")]
+pub struct ProviderSpeech {
+    http: reqwest::Client,
+    runtime: tokio::runtime::Handle,
+    url: String,
+    api_key: secrecy::SecretString,
+    model: String,
+    voice: String,
+    speed: f32,
+    // Audio stops when the stream is dropped.
+    _output: rodio::OutputStream,
+    sink: rodio::Sink,
+}
+
+#[cfg(feature = "provider-tts")]
+impl ProviderSpeech {
+    /// `base_url` is the API root, e.g. `https://api.openai.com/v1`.
+    /// Requests run on `runtime`, from the reader's thread.
+    pub fn new(
+        http: reqwest::Client,
+        runtime: tokio::runtime::Handle,
+        base_url: &str,
+        api_key: secrecy::SecretString,
+        model: String,
+        voice: Option<String>,
+        rate: f32,
+    ) -> VoiceResult<Self> {
+        let (output, handle) =
+            rodio::OutputStream::try_default().map_err(|e| VoiceError::Speech(e.to_string()))?;
+        let sink = rodio::Sink::try_new(&handle).map_err(|e| VoiceError::Speech(e.to_string()))?;
+        Ok(Self {
+            http,
+            runtime,
+            url: format!("{}/audio/speech", base_url.trim_end_matches('/')),
+            api_key,
+            model,
+            voice: voice.unwrap_or_else(|| "alloy".to_owned()),
+            speed: rate,
+            _output: output,
+            sink,
+        })
+    }
+}
+
+#[cfg(feature = "provider-tts")]
+impl SpeechBackend for ProviderSpeech {
+    fn speak(&mut self, text: &str) -> VoiceResult<()> {
+        use secrecy::ExposeSecret;
+
+        let request = self
+            .http
+            .post(&self.url)
+            .bearer_auth(self.api_key.expose_secret())
+            .json(&serde_json::json!({
+                "model": self.model,
+                "voice": self.voice,
+                "input": text,
+                "speed": self.speed,
+                "response_format": "mp3",
+            }));
+        let audio = self
+            .runtime
+            .block_on(async {
+                request
+                    .send()
+                    .await
+                    .and_then(|r| r.error_for_status())?
+                    .bytes()
+                    .await
+            })
+            .map_err(|e| VoiceError::Speech(e.to_string()))?;
+        let source = rodio::Decoder::new(std::io::Cursor::new(audio))
+            .map_err(|e| VoiceError::Speech(e.to_string()))?;
+        self.sink.stop();
+        self.sink.append(source);
+        self.sink.play();
+        Ok(())
+    }
+
+    fn is_speaking(&self) -> VoiceResult<bool> {
+        Ok(!self.sink.empty())
+    }
+
+    fn stop(&mut self) -> VoiceResult<()> {
+        self.sink.stop();
+        Ok(())
+    }
+}
+
+/// What a [`SpeechReader`] is doing, reported on every change.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+#[serde(rename_all = "camelCase")]
+pub enum SpeechState {
+    Idle,
+    Speaking,
+    Paused,
+}
+
+enum Command {
+    Push(String),
+    Finish,
+    Pause,
+    Resume,
+    Stop,
+}
+
+/// Reads text aloud on its own thread. Dropping it stops speech.
+pub struct SpeechReader {
+    commands: Option<mpsc::Sender<Command>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl SpeechReader {
+    /// Start a reader whose backend `build` creates on the reader's
+    /// thread. Returns once the backend is up, or with its error.
+    /// `on_state` is called from the reader's thread.
+    pub fn start<B, F>(build: B, on_state: F) -> VoiceResult<Self>
+    where
+        B: FnOnce() -> VoiceResult<Box<dyn SpeechBackend>> + Send + 'static,
+        F: Fn(SpeechState) + Send + 'static,
+    {
+        let (commands, rx) = mpsc::channel();
+        let (ready_tx, ready_rx) = mpsc::channel();
+        let thread = std::thread::Builder::new()
+            .name("speech".to_owned())
+            .spawn(move || match build() {
+                Ok(backend) => {
+                    let _ = ready_tx.send(Ok(()));
+                    read(backend, rx, on_state);
+                }
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e));
+                }
+            })
+            .map_err(|e| VoiceError::Speech(e.to_string()))?;
+
+        ready_rx
+            .recv()
+            .map_err(|_| VoiceError::Speech("speech thread exited".to_owned()))??;
+        Ok(Self {
+            commands: Some(commands),
+            thread: Some(thread),
+        })
+    }
+
+    /// Queue more of a response. Complete sentences start playing.
+    pub fn push(&self, text: impl Into<String>) {
+        self.send(Command::Push(text.into()));
+    }
+
+    /// The response is complete: read the rest of it.
+    pub fn finish(&self) {
+        self.send(Command::Finish);
+    }
+
+    /// Stop mid-sentence; [`resume`](Self::resume) restarts that sentence.
+    pub fn pause(&self) {
+        self.send(Command::Pause);
+    }
+
+    pub fn resume(&self) {
+        self.send(Command::Resume);
+    }
+
+    /// Stop and forget everything queued.
+    pub fn stop(&self) {
+        self.send(Command::Stop);
+    }
+
+    fn send(&self, command: Command) {
+        if let Some(commands) = &self.commands {
+            let _ = commands.send(command);
+        }
+    }
+}
+
+impl Drop for SpeechReader {
+    fn drop(&mut self) {
+        self.stop();
+        self.commands.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn read<F>(mut backend: Box<dyn SpeechBackend>, commands: mpsc::Receiver<Command>, on_state: F)
+where
+    F: Fn(SpeechState),
+{
+    let mut splitter = SentenceSplitter::new();
+    let mut queue = VecDeque::new();
+    // The sentence being said, and when it was started.
+    let mut current: Option<(String, Instant)> = None;
+    let mut paused = false;
+    let mut state = SpeechState::Idle;
+
+    loop {
+        if !paused
+            && current.is_none()
+            && let Some(sentence) = queue.pop_front()
+        {
+            match backend.speak(&sentence) {
+                Ok(()) => current = Some((sentence, Instant::now())),
+                Err(e) => tracing::warn!(error = %e, "Failed to speak sentence"),
+            }
+        }
+
+        let next = match (paused, &current) {
+            (true, _) => SpeechState::Paused,
+            (false, Some(_)) => SpeechState::Speaking,
+            (false, None) if !queue.is_empty() => continue,
+            (false, None) => SpeechState::Idle,
+        };
+        if next != state {
+            state = next;
+            on_state(state);
+        }
+
+        let command = if current.is_some() && !paused {
+            commands.recv_timeout(POLL_INTERVAL)
+        } else {
+            commands.recv().map_err(|_| RecvTimeoutError::Disconnected)
+        };
+        match command {
+            Ok(Command::Push(text)) => queue.extend(splitter.push(&text)),
+            Ok(Command::Finish) => queue.extend(splitter.finish()),
+            Ok(Command::Pause) => {
+                if current.is_some() && !paused {
+                    let _ = backend.stop();
+                }
+                paused = true;
+            }
+            Ok(Command::Resume) if paused => {
+                paused = false;
+                if let Some((sentence, started)) = &mut current {
+                    match backend.speak(sentence) {
+                        Ok(()) => *started = Instant::now(),
+                        Err(e) => {
+                            tracing::warn!(error = %e, "Failed to resume speech");
+                            current = None;
+                        }
+                    }
+                }
+            }
+            Ok(Command::Resume) => {}
+            Ok(Command::Stop) => {
+                let _ = backend.stop();
+                splitter = SentenceSplitter::new();
+                queue.clear();
+                current = None;
+                paused = false;
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                if let Some((_, started)) = &current
+                    && started.elapsed() >= START_GRACE
+                    && !backend.is_speaking().unwrap_or(false)
+                {
+                    current = None;
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                let _ = backend.stop();
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    /// Finishes every sentence as soon as the grace period is over.
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl SpeechBackend for Recorder {
+        fn speak(&mut self, text: &str) -> VoiceResult<()> {
+            self.0.lock().unwrap().push(text.to_owned());
+            Ok(())
+        }
+
+        fn is_speaking(&self) -> VoiceResult<bool> {
+            Ok(false)
+        }
+
+        fn stop(&mut self) -> VoiceResult<()> {
+            Ok(())
+        }
+    }
+
+    fn reader() -> (
+        SpeechReader,
+        Arc<Mutex<Vec<String>>>,
+        mpsc::Receiver<SpeechState>,
+    ) {
+        let spoken = Arc::new(Mutex::new(Vec::new()));
+        let (state_tx, states) = mpsc::channel();
+        let backend = Recorder(spoken.clone());
+        let reader = SpeechReader::start(
+            move || Ok(Box::new(backend) as Box<dyn SpeechBackend>),
+            move |state| {
+                let _ = state_tx.send(state);
+            },
+        )
+        .unwrap();
+        (reader, spoken, states)
+    }
+
+    fn next_state(states: &mpsc::Receiver<SpeechState>) -> SpeechState {
+        states.recv_timeout(Duration::from_secs(5)).unwrap()
+    }
+
+    #[test]
+    fn streamed_text_is_read_sentence_by_sentence() {
+        let (reader, spoken, states) = reader();
+        reader.push("First one. Sec");
+        assert_eq!(next_state(&states), SpeechState::Speaking);
+        reader.push("ond one.");
+        reader.finish();
+        assert_eq!(next_state(&states), SpeechState::Idle);
+        assert_eq!(*spoken.lock().unwrap(), vec!["First one.", "Second one."]);
+    }
+
+    #[test]
+    fn stop_drops_the_queue() {
+        let (reader, spoken, states) = reader();
+        reader.push("One. Two. Three. ");
+        assert_eq!(next_state(&states), SpeechState::Speaking);
+        reader.stop();
+        assert_eq!(next_state(&states), SpeechState::Idle);
+        assert_eq!(*spoken.lock().unwrap(), vec!["One."]);
+    }
+}
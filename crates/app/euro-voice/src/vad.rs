@@ -0,0 +1,95 @@
+//! Energy-based voice activity detection, used to end an utterance once
+//! the speaker has gone quiet.
+
+use std::time::Duration;
+
+use crate::audio::SAMPLE_RATE;
+
+/// 30 ms at [`SAMPLE_RATE`].
+const FRAME_LEN: usize = 480;
+
+/// Tracks whether speech has been heard and how long it has been quiet
+/// since.
+#[derive(Debug, Clone)]
+pub struct SilenceDetector {
+    /// RMS level above which a frame counts as speech.
+    threshold: f32,
+    /// Samples not yet making up a whole frame.
+    pending: Vec<f32>,
+    heard_speech: bool,
+    quiet_frames: usize,
+}
+
+impl SilenceDetector {
+    pub fn new(threshold: f32) -> Self {
+        Self {
+            threshold,
+            pending: Vec::with_capacity(FRAME_LEN),
+            heard_speech: false,
+            quiet_frames: 0,
+        }
+    }
+
+    /// Feed 16 kHz mono samples.
+    pub fn push(&mut self, samples: &[f32]) {
+        self.pending.extend_from_slice(samples);
+        let whole = self.pending.len() / FRAME_LEN * FRAME_LEN;
+        for frame in self.pending[..whole].chunks_exact(FRAME_LEN) {
+            let rms = (frame.iter().map(|s| s * s).sum::<f32>() / FRAME_LEN as f32).sqrt();
+            if rms >= self.threshold {
+                self.heard_speech = true;
+                self.quiet_frames = 0;
+            } else {
+                self.quiet_frames += 1;
+            }
+        }
+        self.pending.drain(..whole);
+    }
+
+    pub fn heard_speech(&self) -> bool {
+        self.heard_speech
+    }
+
+    /// How long it has been quiet since the last speech, zero before any.
+    pub fn silence(&self) -> Duration {
+        if !self.heard_speech {
+            return Duration::ZERO;
+        }
+        Duration::from_millis((self.quiet_frames * FRAME_LEN) as u64 * 1_000 / SAMPLE_RATE as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(frames: usize, amplitude: f32) -> Vec<f32> {
+        (0..frames * FRAME_LEN)
+            .map(|i| amplitude * (i as f32 * 0.1).sin())
+            .collect()
+    }
+
+    #[test]
+    fn silence_is_only_counted_after_speech() {
+        let mut detector = SilenceDetector::new(0.02);
+        detector.push(&tone(32, 0.0));
+        assert!(!detector.heard_speech());
+        assert_eq!(detector.silence(), Duration::ZERO);
+
+        detector.push(&tone(16, 0.5));
+        assert!(detector.heard_speech());
+        assert_eq!(detector.silence(), Duration::ZERO);
+
+        detector.push(&tone(32, 0.0));
+        assert_eq!(detector.silence(), Duration::from_millis(960));
+    }
+
+    #[test]
+    fn speech_resets_the_silence() {
+        let mut detector = SilenceDetector::new(0.02);
+        detector.push(&tone(10, 0.5));
+        detector.push(&tone(20, 0.0));
+        detector.push(&tone(10, 0.5));
+        assert_eq!(detector.silence(), Duration::ZERO);
+    }
+}
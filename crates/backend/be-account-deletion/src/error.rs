@@ -0,0 +1,34 @@
+use be_payment_service::PaymentError;
+use be_remote_db::DbError;
+use be_storage::StorageError;
+use thiserror::Error;
+
+/// Why an erasure attempt stopped. The attempt is retried from the top.
+#[derive(Debug, Error)]
+pub enum EraseError {
+    /// Some of the account's threads or assets are on legal hold. Nothing
+    /// was erased; the attempt is retried until the holds are released.
+    #[error("Account has items on legal hold")]
+    LegalHold,
+
+    #[error("Failed to cancel subscriptions: {0}")]
+    Billing(#[from] PaymentError),
+
+    #[error("Failed to delete stored objects: {0}")]
+    Storage(#[from] StorageError),
+
+    #[error("Database error: {0}")]
+    Database(#[from] DbError),
+}
+
+impl EraseError {
+    /// Stable label for the audit trail and structured logs.
+    pub fn step(&self) -> &'static str {
+        match self {
+            Self::LegalHold => "legal_hold",
+            Self::Billing(_) => "billing",
+            Self::Storage(_) => "storage",
+            Self::Database(_) => "database",
+        }
+    }
+}
@@ -0,0 +1,52 @@
+//! Background erasure of accounts whose deletion grace period is over.
+//!
+//! A user asks for their account to be deleted through `DELETE
+//! /auth/account` (see `be-auth-service`), which locks the account,
+//! revokes its sessions, and schedules the deletion. This crate runs the
+//! worker that, once the grace period is over, erases the account in this
+//! order:
+//!
+//! 1. Cancels the user's Stripe subscriptions (when billing is
+//!    configured).
+//! 2. Deletes every storage object the account's rows point at — asset
+//!    contents and data export archives.
+//! 3. Deletes the user row and with it, through `ON DELETE CASCADE`,
+//!    threads, messages, assets, activities, OAuth credentials, settings,
+//!    and everything else the account owns.
+//! 4. Emails a final confirmation to the address the account had.
+//!
+//! Each step is idempotent and recorded in `account_deletion_events`; a
+//! failed attempt is retried from the top once its lease lapses. While
+//! any of the account's threads or assets is on legal hold, no step runs:
+//! every attempt stops with a `legal_hold` failure until the holds are
+//! released.
+
+mod error;
+mod worker;
+
+use std::sync::Arc;
+
+use be_email_service::EmailService;
+use be_payment_service::cancel::SubscriptionCanceller;
+use be_remote_db::DatabaseManager;
+use be_storage::StorageService;
+
+pub use error::EraseError;
+pub use worker::AccountDeletionWorkerHandle;
+
+/// Everything the worker needs to erase an account.
+pub struct AccountEraser {
+    pub db: Arc<DatabaseManager>,
+    pub storage: Arc<StorageService>,
+    /// `None` in dev mode; the confirmation email is skipped.
+    pub email: Option<Arc<EmailService>>,
+    /// `None` in dev mode; there are no subscriptions to cancel.
+    pub billing: Option<Arc<SubscriptionCanceller>>,
+}
+
+/// Start the account deletion worker. The caller owns the returned handle
+/// and should shut it down after the server stops.
+pub fn init_account_deletion_worker(eraser: AccountEraser) -> AccountDeletionWorkerHandle {
+    tracing::debug!("Initializing account deletion worker");
+    worker::spawn_worker(Arc::new(eraser))
+}
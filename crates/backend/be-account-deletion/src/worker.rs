@@ -0,0 +1,234 @@
+//! The worker loop: claim deletions whose grace period is over and erase
+//! them one after another.
+
+use std::sync::Arc;
+
+use be_remote_db::{AccountDeletion, DatabaseManager, DbError};
+use chrono::Duration as ChronoDuration;
+use serde_json::json;
+use tokio::sync::oneshot;
+use tokio::time::{Duration, sleep};
+use uuid::Uuid;
+
+use crate::AccountEraser;
+use crate::error::EraseError;
+
+/// How long a claimed deletion stays leased, and so how long a failed
+/// attempt waits before it is retried. Comfortably longer than erasing
+/// even a large account takes.
+const LEASE: ChronoDuration = ChronoDuration::minutes(30);
+
+/// Deletions claimed per tick.
+const BATCH_SIZE: i64 = 5;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+pub struct AccountDeletionWorkerHandle {
+    shutdown: oneshot::Sender<()>,
+    join: tokio::task::JoinHandle<()>,
+}
+
+impl AccountDeletionWorkerHandle {
+    pub async fn shutdown(self) {
+        let Self { shutdown, join } = self;
+        let _ = shutdown.send(());
+        let _ = join.await;
+    }
+}
+
+/// Spawn the background worker. An erasure in progress when shutdown is
+/// requested is abandoned; its lease lapses and the next attempt redoes
+/// the idempotent steps.
+pub(crate) fn spawn_worker(eraser: Arc<AccountEraser>) -> AccountDeletionWorkerHandle {
+    let (shutdown_tx, mut shutdown_rx) = oneshot::channel::<()>();
+
+    let join = tokio::spawn(async move {
+        tracing::info!("Account deletion worker started");
+        loop {
+            tokio::select! {
+                result = tick(&eraser) => {
+                    if let Err(e) = result {
+                        tracing::error!(error = %e, "Account deletion worker tick failed");
+                    }
+                }
+                _ = &mut shutdown_rx => break,
+            }
+
+            tokio::select! {
+                _ = sleep(POLL_INTERVAL) => {}
+                _ = &mut shutdown_rx => break,
+            }
+        }
+        tracing::info!("Account deletion worker shutting down");
+    });
+
+    AccountDeletionWorkerHandle {
+        shutdown: shutdown_tx,
+        join,
+    }
+}
+
+async fn tick(eraser: &AccountEraser) -> Result<(), DbError> {
+    let claimed = eraser
+        .db
+        .claim_due_account_deletions()
+        .limit(BATCH_SIZE)
+        .lease(LEASE)
+        .call()
+        .await?;
+    for deletion in claimed {
+        run_deletion(eraser, &deletion).await;
+    }
+    Ok(())
+}
+
+#[tracing::instrument(
+    skip_all,
+    fields(deletion_id = %deletion.id, user_id = %deletion.user_id, attempt = deletion.attempts)
+)]
+async fn run_deletion(eraser: &AccountEraser, deletion: &AccountDeletion) {
+    let Err(e) = erase(eraser, deletion).await else {
+        return;
+    };
+
+    tracing::error!(
+        step = e.step(),
+        error = %e,
+        "Account deletion attempt failed; retrying once the lease lapses"
+    );
+    if let Err(db_err) = eraser
+        .db
+        .fail_account_deletion_attempt()
+        .id(deletion.id)
+        .error(e.to_string())
+        .call()
+        .await
+    {
+        tracing::error!(error = %db_err, "Failed to record account deletion failure");
+    }
+    record_best_effort(
+        &eraser.db,
+        deletion.id,
+        "attempt_failed",
+        json!({ "step": e.step(), "attempt": deletion.attempts }),
+    )
+    .await;
+}
+
+async fn erase(eraser: &AccountEraser, deletion: &AccountDeletion) -> Result<(), EraseError> {
+    let user_id = deletion.user_id;
+
+    // Held items must outlive the account, and deleting the user row
+    // would take them with it. Nothing is touched until the holds go.
+    if eraser
+        .db
+        .account_has_legal_hold()
+        .user_id(user_id)
+        .call()
+        .await?
+    {
+        return Err(EraseError::LegalHold);
+    }
+
+    if let Some(billing) = &eraser.billing {
+        let cancelled = billing.cancel_all_for_user(user_id).await?;
+        record(
+            &eraser.db,
+            deletion.id,
+            "subscriptions_cancelled",
+            json!({ "count": cancelled }),
+        )
+        .await?;
+    }
+
+    let uris = eraser
+        .db
+        .list_account_storage_uris()
+        .user_id(user_id)
+        .call()
+        .await?;
+    for uri in &uris {
+        eraser.storage.delete(uri).await?;
+    }
+    record(
+        &eraser.db,
+        deletion.id,
+        "storage_deleted",
+        json!({ "objects": uris.len() }),
+    )
+    .await?;
+
+    // Past this point the deletion is `completed` and won't be retried.
+    let counts = eraser
+        .db
+        .erase_account()
+        .deletion_id(deletion.id)
+        .user_id(user_id)
+        .call()
+        .await
+        .map_err(|e| {
+            if e.is_on_legal_hold() {
+                EraseError::LegalHold
+            } else {
+                e.into()
+            }
+        })?;
+    tracing::info!(
+        threads = counts.threads,
+        messages = counts.messages,
+        assets = counts.assets,
+        oauth_credentials = counts.oauth_credentials,
+        "Account erased"
+    );
+
+    send_confirmation(eraser, deletion).await;
+    Ok(())
+}
+
+/// Best effort: the account is already gone, so a failure here is
+/// recorded but never retried.
+async fn send_confirmation(eraser: &AccountEraser, deletion: &AccountDeletion) {
+    let Some(address) = deletion.email.as_deref() else {
+        return;
+    };
+
+    let kind = match &eraser.email {
+        None => {
+            tracing::warn!("email service not configured, skipping account deletion confirmation");
+            "confirmation_skipped"
+        }
+        Some(email) => match email.send_account_deleted_email(address).await {
+            Ok(()) => "confirmation_sent",
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to send account deletion confirmation");
+                "confirmation_failed"
+            }
+        },
+    };
+    record_best_effort(&eraser.db, deletion.id, kind, json!({})).await;
+}
+
+async fn record(
+    db: &DatabaseManager,
+    deletion_id: Uuid,
+    kind: &str,
+    detail: serde_json::Value,
+) -> Result<(), DbError> {
+    db.record_account_deletion_event()
+        .deletion_id(deletion_id)
+        .kind(kind)
+        .detail(detail)
+        .call()
+        .await
+}
+
+async fn record_best_effort(
+    db: &DatabaseManager,
+    deletion_id: Uuid,
+    kind: &str,
+    detail: serde_json::Value,
+) {
+    if let Err(e) = record(db, deletion_id, kind, detail).await {
+        tracing::error!(%deletion_id, kind, error = %e, "Failed to record account deletion event");
+    }
+}
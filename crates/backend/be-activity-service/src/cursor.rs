@@ -0,0 +1,69 @@
+//! Opaque keyset cursor for `GET /activities/stream`.
+//!
+//! A cursor is the `(last_used_at, id)` of the last activity a batch
+//! carried, base64url-encoded so clients treat it as a token rather than
+//! something to construct. The encoding is not a contract; only cursors
+//! this service issued are accepted.
+
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, SecondsFormat, Utc};
+use uuid::Uuid;
+
+use crate::error::{ActivityResult, ActivityServiceError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ActivityCursor {
+    pub last_used_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl ActivityCursor {
+    pub fn encode(&self) -> String {
+        let raw = format!(
+            "{}|{}",
+            self.last_used_at
+                .to_rfc3339_opts(SecondsFormat::Micros, true),
+            self.id
+        );
+        URL_SAFE_NO_PAD.encode(raw)
+    }
+
+    pub fn decode(cursor: &str) -> ActivityResult<Self> {
+        let invalid = || ActivityServiceError::invalid_argument("cursor is not valid");
+        let raw = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+        let raw = String::from_utf8(raw).map_err(|_| invalid())?;
+        let (last_used_at, id) = raw.split_once('|').ok_or_else(invalid)?;
+        Ok(Self {
+            last_used_at: DateTime::parse_from_rfc3339(last_used_at)
+                .map_err(|_| invalid())?
+                .with_timezone(&Utc),
+            id: id.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_with_microsecond_precision() {
+        let cursor = ActivityCursor {
+            last_used_at: DateTime::from_timestamp_micros(1_780_000_000_123_456).unwrap(),
+            id: Uuid::now_v7(),
+        };
+        assert_eq!(ActivityCursor::decode(&cursor.encode()).unwrap(), cursor);
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        for cursor in [
+            "",
+            "not base64 ***",
+            &URL_SAFE_NO_PAD.encode("no-separator"),
+        ] {
+            let err = ActivityCursor::decode(cursor).unwrap_err();
+            assert_eq!(err.error_kind(), "invalid_argument");
+        }
+    }
+}
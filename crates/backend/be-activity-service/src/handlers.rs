@@ -1,27 +1,40 @@
 use std::sync::Arc;
 
 use activity_core::{
-    Activity as WireActivity, ActivitySession as WireActivitySession,
-    ActivityWithLatestSession as WireActivityWithLatestSession, DEFAULT_LIST_LIMIT,
+    Activity as WireActivity, ActivityBatch, ActivitySession as WireActivitySession,
+    ActivityTimelineQuery, ActivityTimelineResponse,
+    ActivityWithLatestSession as WireActivityWithLatestSession, ActivityWithSessions,
+    DEFAULT_LIST_LIMIT, DEFAULT_STREAM_BATCH_SIZE, Device as WireDevice,
     InsertActivitySessionRequest, InsertActivitySessionResponse, ListActivitiesQuery,
-    ListActivitiesResponse, ListActivitySessionsResponse, MAX_LIST_LIMIT,
-    UpdateActivitySessionRequest, UpdateActivitySessionResponse,
+    ListActivitiesResponse, ListActivitySessionsResponse, ListDevicesResponse,
+    MAX_DEVICE_NAME_CHARS, MAX_LIST_LIMIT, MAX_TIMELINE_DAYS, MAX_TIMELINE_SESSIONS,
+    RegisterDeviceRequest, StreamActivitiesQuery, UpdateActivitySessionRequest,
+    UpdateActivitySessionResponse,
 };
 use axum::{
     Json,
+    body::Body,
     extract::{Path, Query, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
 };
 use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64_STANDARD};
+use be_analytics::Subject;
 use be_asset::CreateAssetInput;
 use be_auth_core::AuthUser;
 use be_remote_db::PaginationParams;
+use chrono::{DateTime, TimeDelta, Utc};
+use futures::stream::{self, StreamExt};
 use uuid::Uuid;
 
 use crate::analytics;
+use crate::cursor::ActivityCursor;
 use crate::error::{ActivityResult, ActivityServiceError};
 use crate::service::AppState;
 
 const ICON_MIME_TYPE: &str = "image/png";
+/// Longest platform name accepted by `PUT /devices/{id}`.
+const MAX_PLATFORM_CHARS: usize = 32;
 
 #[tracing::instrument(skip_all, fields(user_id, limit, offset))]
 pub async fn list_activities(
@@ -30,6 +43,7 @@ pub async fn list_activities(
     Query(query): Query<ListActivitiesQuery>,
 ) -> ActivityResult<Json<ListActivitiesResponse>> {
     let user_id = user.user_id()?;
+    let subject = Subject::from_claims(user.claims());
 
     let limit = query.limit.unwrap_or(DEFAULT_LIST_LIMIT);
     let offset = query.offset.unwrap_or(0);
@@ -54,13 +68,13 @@ pub async fn list_activities(
         .await
         .map_err(|e| {
             let err = ActivityServiceError::from(e);
-            analytics::track_activities_list_failed(err.error_kind());
+            analytics::track_activities_list_failed(&subject, err.error_kind());
             err
         })?;
 
     let result_count = rows.len();
     tracing::debug!(result_count, "Listed activities");
-    analytics::track_activities_listed(limit, offset, result_count);
+    analytics::track_activities_listed(&subject, limit, offset, result_count);
 
     Ok(Json(ListActivitiesResponse {
         activities: rows
@@ -73,6 +87,222 @@ pub async fn list_activities(
     }))
 }
 
+/// `GET /activities/stream`: the activity list as newline-delimited JSON
+/// [`ActivityBatch`]es.
+///
+/// The first batch is fetched before the response starts, so a bad
+/// cursor or a failing query still gets the usual error envelope. Each
+/// later batch is queried only once the previous one has been written,
+/// so a slow reader holds at most one batch in memory. A failure
+/// mid-stream aborts the body; the client resumes from the last
+/// `next_cursor` it received.
+#[tracing::instrument(skip_all, fields(user_id, batch_size, limit))]
+pub async fn stream_activities(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Query(query): Query<StreamActivitiesQuery>,
+) -> ActivityResult<Response> {
+    let user_id = user.user_id()?;
+    let subject = Subject::from_claims(user.claims());
+
+    let batch_size = query.batch_size.unwrap_or(DEFAULT_STREAM_BATCH_SIZE);
+    if batch_size == 0 || batch_size > MAX_LIST_LIMIT {
+        return Err(ActivityServiceError::invalid_argument(format!(
+            "batch_size must be between 1 and {MAX_LIST_LIMIT}"
+        )));
+    }
+    if query.limit == Some(0) {
+        return Err(ActivityServiceError::invalid_argument(
+            "limit must be at least 1",
+        ));
+    }
+    if let (Some(since), Some(until)) = (query.since, query.until)
+        && since >= until
+    {
+        return Err(ActivityServiceError::invalid_argument(
+            "since must be before until",
+        ));
+    }
+    let after = query
+        .cursor
+        .as_deref()
+        .map(ActivityCursor::decode)
+        .transpose()?;
+
+    let span = tracing::Span::current();
+    span.record("user_id", tracing::field::display(user_id));
+    span.record("batch_size", batch_size);
+    if let Some(limit) = query.limit {
+        span.record("limit", limit);
+    }
+
+    let mut walk = ActivityWalk {
+        state,
+        user_id,
+        after,
+        since: query.since,
+        until: query.until,
+        batch_size,
+        remaining: query.limit,
+        done: false,
+    };
+    let first = walk.next_batch().await.inspect_err(|e| {
+        analytics::track_activities_list_failed(&subject, e.error_kind());
+    })?;
+
+    let rest = stream::unfold(walk, |mut walk| async move {
+        match walk.next_batch().await {
+            Ok(Some(batch)) => Some((Ok(batch), walk)),
+            Ok(None) => None,
+            Err(e) => {
+                tracing::error!(error = %e, "Activity stream failed mid-body");
+                walk.done = true;
+                Some((Err(e), walk))
+            }
+        }
+    });
+    let lines = stream::iter(first.map(Ok))
+        .chain(rest)
+        .map(|batch| batch.map(encode_line));
+
+    Ok((
+        [(header::CONTENT_TYPE, "application/x-ndjson")],
+        Body::from_stream(lines),
+    )
+        .into_response())
+}
+
+/// Cursor state for one `GET /activities/stream` response.
+struct ActivityWalk {
+    state: Arc<AppState>,
+    user_id: Uuid,
+    after: Option<ActivityCursor>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    batch_size: u32,
+    /// Rows still allowed by the caller's `limit`; `None` is unbounded.
+    remaining: Option<u32>,
+    done: bool,
+}
+
+impl ActivityWalk {
+    /// The next batch, or `None` once the range or `limit` is used up.
+    /// The first call always yields a batch, possibly empty, so every
+    /// response carries at least one line.
+    async fn next_batch(&mut self) -> ActivityResult<Option<ActivityBatch>> {
+        if self.done {
+            return Ok(None);
+        }
+        let want = self
+            .remaining
+            .map_or(self.batch_size, |remaining| remaining.min(self.batch_size));
+
+        // One extra row tells us whether another batch follows without a
+        // trailing empty query.
+        let mut rows = self
+            .state
+            .db
+            .list_activities_page()
+            .user_id(self.user_id)
+            .maybe_after(self.after.map(|c| (c.last_used_at, c.id)))
+            .maybe_since(self.since)
+            .maybe_until(self.until)
+            .limit(i64::from(want) + 1)
+            .call()
+            .await?;
+        let has_more = rows.len() > want as usize;
+        rows.truncate(want as usize);
+
+        if let Some((last, _)) = rows.last() {
+            self.after = Some(ActivityCursor {
+                last_used_at: last.last_used_at,
+                id: last.id,
+            });
+        }
+        if let Some(remaining) = self.remaining.as_mut() {
+            *remaining -= rows.len() as u32;
+        }
+        self.done = !has_more || self.remaining == Some(0);
+
+        Ok(Some(ActivityBatch {
+            next_cursor: has_more.then(|| self.after.map(|c| c.encode())).flatten(),
+            activities: rows
+                .into_iter()
+                .map(|(activity, latest_session)| WireActivityWithLatestSession {
+                    activity: activity_to_wire(activity),
+                    latest_session: latest_session.map(session_to_wire),
+                })
+                .collect(),
+        }))
+    }
+}
+
+fn encode_line(batch: ActivityBatch) -> Vec<u8> {
+    // Plain data with string keys; serialization can't fail.
+    let mut line = serde_json::to_vec(&batch).unwrap_or_default();
+    line.push(b'\n');
+    line
+}
+
+/// `GET /activities/timeline`: every session overlapping a time range,
+/// grouped under its parent activity.
+///
+/// Unlike the list endpoints this is not paged: the range is capped at
+/// [`MAX_TIMELINE_DAYS`] and the response at [`MAX_TIMELINE_SESSIONS`]
+/// sessions, dropping the latest ones and setting `truncated` when the
+/// range holds more. `device_id` narrows it to one device; each session
+/// carries its device either way, for clients grouping by device.
+#[tracing::instrument(skip_all, fields(user_id, since = %query.since, until = %query.until))]
+pub async fn activity_timeline(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Query(query): Query<ActivityTimelineQuery>,
+) -> ActivityResult<Json<ActivityTimelineResponse>> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    if query.since >= query.until {
+        return Err(ActivityServiceError::invalid_argument(
+            "since must be before until",
+        ));
+    }
+    if query.until - query.since > TimeDelta::days(i64::from(MAX_TIMELINE_DAYS)) {
+        return Err(ActivityServiceError::invalid_argument(format!(
+            "the range must span at most {MAX_TIMELINE_DAYS} days"
+        )));
+    }
+
+    // One extra session tells us whether the range was cut short.
+    let mut groups = state
+        .db
+        .list_activity_sessions_between()
+        .user_id(user_id)
+        .since(query.since)
+        .until(query.until)
+        .maybe_device_id(query.device_id)
+        .limit(i64::from(MAX_TIMELINE_SESSIONS) + 1)
+        .call()
+        .await
+        .map_err(ActivityServiceError::from)?;
+    let session_count: usize = groups.iter().map(|(_, sessions)| sessions.len()).sum();
+    let truncated = session_count > MAX_TIMELINE_SESSIONS as usize;
+    if truncated {
+        be_remote_db::drop_latest_session(&mut groups);
+    }
+    tracing::debug!(session_count, truncated, "Loaded activity timeline");
+
+    Ok(Json(ActivityTimelineResponse {
+        activities: groups
+            .into_iter()
+            .map(|(activity, sessions)| ActivityWithSessions {
+                activity: activity_to_wire(activity),
+                sessions: sessions.into_iter().map(session_to_wire).collect(),
+            })
+            .collect(),
+        truncated,
+    }))
+}
+
 #[tracing::instrument(skip_all, fields(user_id, activity_id = %activity_id, limit, offset))]
 pub async fn list_activity_sessions(
     State(state): State<Arc<AppState>>,
@@ -118,10 +348,11 @@ pub async fn insert_activity_session(
     Json(body): Json<InsertActivitySessionRequest>,
 ) -> ActivityResult<Json<InsertActivitySessionResponse>> {
     let user_id = user.user_id()?;
+    let subject = Subject::from_claims(user.claims());
 
     let icon_bytes =
         decode_optional_icon(body.activity.icon_png_base64.as_deref()).inspect_err(|e| {
-            analytics::track_activity_insert_failed(e.error_kind());
+            analytics::track_activity_insert_failed(&subject, e.error_kind());
         })?;
 
     let has_icon = icon_bytes.is_some();
@@ -150,13 +381,14 @@ pub async fn insert_activity_session(
                         content,
                         mime_type: ICON_MIME_TYPE.to_string(),
                         metadata: None,
+                        device_id: body.device_id,
                     },
                     user_id,
                 )
                 .await
                 .map_err(|e| {
                     let err = ActivityServiceError::from(e);
-                    analytics::track_activity_insert_failed(err.error_kind());
+                    analytics::track_activity_insert_failed(&subject, err.error_kind());
                     err
                 })?
                 .id,
@@ -176,13 +408,14 @@ pub async fn insert_activity_session(
         .maybe_process_id(body.process_id)
         .maybe_window_title(body.window_title)
         .maybe_url(body.url)
+        .maybe_device_id(body.device_id)
         .started_at(body.started_at)
         .maybe_ended_at(body.ended_at)
         .call()
         .await
         .map_err(|e| {
             let err = ActivityServiceError::from(e);
-            analytics::track_activity_insert_failed(err.error_kind());
+            analytics::track_activity_insert_failed(&subject, err.error_kind());
             err
         })?;
 
@@ -191,7 +424,12 @@ pub async fn insert_activity_session(
         session_id = %session.id,
         "Created activity session"
     );
-    analytics::track_activity_session_inserted(has_icon, has_ended_at, &activity.identity_key);
+    analytics::track_activity_session_inserted(
+        &subject,
+        has_icon,
+        has_ended_at,
+        &activity.identity_key,
+    );
 
     Ok(Json(InsertActivitySessionResponse {
         activity: activity_to_wire(activity),
@@ -207,6 +445,7 @@ pub async fn patch_activity_session(
     Json(body): Json<UpdateActivitySessionRequest>,
 ) -> ActivityResult<Json<UpdateActivitySessionResponse>> {
     let user_id = user.user_id()?;
+    let subject = Subject::from_claims(user.claims());
 
     let span = tracing::Span::current();
     span.record("user_id", tracing::field::display(user_id));
@@ -242,18 +481,95 @@ pub async fn patch_activity_session(
         .await
         .map_err(|e| {
             let err = ActivityServiceError::from(e);
-            analytics::track_activity_session_update_failed(err.error_kind());
+            analytics::track_activity_session_update_failed(&subject, err.error_kind());
             err
         })?;
 
     tracing::debug!(session_id = %session.id, "Patched activity session");
-    analytics::track_activity_session_updated(set_ended_at, set_window_title, set_url);
+    analytics::track_activity_session_updated(&subject, set_ended_at, set_window_title, set_url);
 
     Ok(Json(UpdateActivitySessionResponse {
         session: session_to_wire(session),
     }))
 }
 
+/// `GET /devices`: the caller's devices, most recently seen first.
+#[tracing::instrument(skip_all, fields(user_id))]
+pub async fn list_devices(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+) -> ActivityResult<Json<ListDevicesResponse>> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    let devices = state.db.list_devices().user_id(user_id).call().await?;
+
+    Ok(Json(ListDevicesResponse {
+        devices: devices.into_iter().map(device_to_wire).collect(),
+    }))
+}
+
+/// `PUT /devices/{id}`: register the caller's device `id`, or rename it
+/// and mark it seen. An id registered by another account is a 404.
+#[tracing::instrument(skip_all, fields(user_id, device_id = %device_id))]
+pub async fn register_device(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(device_id): Path<Uuid>,
+    Json(body): Json<RegisterDeviceRequest>,
+) -> ActivityResult<Json<WireDevice>> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    let name = body.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_DEVICE_NAME_CHARS {
+        return Err(ActivityServiceError::invalid_argument(format!(
+            "name must be between 1 and {MAX_DEVICE_NAME_CHARS} characters"
+        )));
+    }
+    let platform = body.platform.trim();
+    if platform.is_empty() || platform.chars().count() > MAX_PLATFORM_CHARS {
+        return Err(ActivityServiceError::invalid_argument(format!(
+            "platform must be between 1 and {MAX_PLATFORM_CHARS} characters"
+        )));
+    }
+
+    let device = state
+        .db
+        .upsert_device()
+        .id(device_id)
+        .user_id(user_id)
+        .name(name)
+        .platform(platform)
+        .call()
+        .await?;
+    tracing::debug!("Registered device");
+
+    Ok(Json(device_to_wire(device)))
+}
+
+/// `DELETE /devices/{id}`: forget one of the caller's devices. Its
+/// sessions and assets stay, no longer tagged with it.
+#[tracing::instrument(skip_all, fields(user_id, device_id = %device_id))]
+pub async fn delete_device(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(device_id): Path<Uuid>,
+) -> ActivityResult<StatusCode> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    state
+        .db
+        .delete_device()
+        .id(device_id)
+        .user_id(user_id)
+        .call()
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
 fn decode_optional_icon(b64: Option<&str>) -> ActivityResult<Option<Vec<u8>>> {
     match b64 {
         Some(s) if !s.is_empty() => BASE64_STANDARD
@@ -285,6 +601,7 @@ fn session_to_wire(session: be_remote_db::ActivitySession) -> WireActivitySessio
         process_id: session.process_id,
         window_title: session.window_title,
         url: session.url,
+        device_id: session.device_id,
         started_at: session.started_at,
         ended_at: session.ended_at,
         created_at: session.created_at,
@@ -292,6 +609,16 @@ fn session_to_wire(session: be_remote_db::ActivitySession) -> WireActivitySessio
     }
 }
 
+fn device_to_wire(device: be_remote_db::Device) -> WireDevice {
+    WireDevice {
+        id: device.id,
+        name: device.name,
+        platform: device.platform,
+        last_seen_at: device.last_seen_at,
+        created_at: device.created_at,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -317,4 +644,56 @@ mod tests {
         let err = decode_optional_icon(Some("not valid base64 *****")).unwrap_err();
         assert_eq!(err.error_kind(), "invalid_base64");
     }
+
+    fn activity() -> be_remote_db::Activity {
+        let now = Utc::now();
+        be_remote_db::Activity {
+            id: Uuid::now_v7(),
+            user_id: Uuid::nil(),
+            identity_key: "code".to_string(),
+            display_name: "Code".to_string(),
+            icon_asset_id: None,
+            last_used_at: now,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn session(activity_id: Uuid, minute: i64) -> be_remote_db::ActivitySession {
+        let started_at = DateTime::UNIX_EPOCH + TimeDelta::minutes(minute);
+        be_remote_db::ActivitySession {
+            id: Uuid::now_v7(),
+            activity_id,
+            user_id: Uuid::nil(),
+            process_name: "code".to_string(),
+            process_id: None,
+            window_title: None,
+            url: None,
+            device_id: None,
+            started_at,
+            ended_at: Some(started_at + TimeDelta::minutes(1)),
+            created_at: started_at,
+            updated_at: started_at,
+        }
+    }
+
+    #[test]
+    fn drop_latest_session_removes_the_newest_session_and_empty_parents() {
+        let (first, second) = (activity(), activity());
+        let mut groups = vec![
+            (
+                first.clone(),
+                vec![session(first.id, 0), session(first.id, 5)],
+            ),
+            (second.clone(), vec![session(second.id, 3)]),
+        ];
+
+        be_remote_db::drop_latest_session(&mut groups);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].1.len(), 1);
+
+        be_remote_db::drop_latest_session(&mut groups);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].0.id, first.id);
+    }
 }
@@ -1,19 +1,21 @@
 //! HTTP activity service.
 //!
-//! Exposes a small Axum router under `/activities` that the desktop app
-//! talks to via JSON. Authentication and Casbin authorization are applied
+//! Exposes a small Axum router under `/activities` (plus the `/devices`
+//! registry the sessions are tagged with) that the desktop app talks to
+//! via JSON. Authentication and Casbin authorization are applied
 //! by the surrounding `be-authz` middleware in `be-monolith`; this crate
 //! only assumes that a verified [`be_auth_core::Claims`] has been inserted
 //! into request extensions by the time a handler runs.
 
 pub mod analytics;
+mod cursor;
 mod error;
 mod handlers;
 mod service;
 
 use std::sync::Arc;
 
-use axum::{Router, routing::get, routing::patch};
+use axum::{Router, routing::get, routing::patch, routing::put};
 use be_asset::AssetService;
 use be_remote_db::DatabaseManager;
 use tower::ServiceBuilder;
@@ -30,6 +32,8 @@ pub use service::AppState;
 pub fn create_router(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/activities", get(handlers::list_activities))
+        .route("/activities/stream", get(handlers::stream_activities))
+        .route("/activities/timeline", get(handlers::activity_timeline))
         .route(
             "/activities/{id}/sessions",
             get(handlers::list_activity_sessions),
@@ -42,6 +46,11 @@ pub fn create_router(state: Arc<AppState>) -> Router {
             "/activity-sessions/{id}",
             patch(handlers::patch_activity_session),
         )
+        .route("/devices", get(handlers::list_devices))
+        .route(
+            "/devices/{id}",
+            put(handlers::register_device).delete(handlers::delete_device),
+        )
         .layer(ServiceBuilder::new().layer(TraceLayer::new_for_http()))
         .with_state(state)
 }
@@ -50,6 +50,9 @@ fn sample_claims(sub: &str) -> Claims {
         aud: "eurora".to_string(),
         email_verified: true,
         jti: "jti".to_string(),
+        analytics: Default::default(),
+        token_version: 0,
+        act: None,
     }
 }
 
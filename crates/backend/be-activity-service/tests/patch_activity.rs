@@ -15,7 +15,7 @@
 use std::sync::{Arc, Mutex};
 
 use activity_core::{
-    ActivityErrorResponse, ActivityInsert, InsertActivitySessionRequest,
+    ActivityBatch, ActivityErrorResponse, ActivityInsert, InsertActivitySessionRequest,
     InsertActivitySessionResponse, ListActivitiesResponse, UpdateActivitySessionRequest,
     UpdateActivitySessionResponse,
 };
@@ -56,6 +56,9 @@ fn claims_for(user_id: Uuid) -> Claims {
         aud: "eurora".into(),
         email_verified: true,
         jti: Uuid::now_v7().to_string(),
+        analytics: Default::default(),
+        token_version: 0,
+        act: None,
     }
 }
 
@@ -89,7 +92,7 @@ async fn spawn_app(pool: PgPool) -> AppHarness {
     let other = seed_user(&pool).await;
     let storage_root = tempfile::tempdir().expect("tempdir for storage root");
 
-    let db = Arc::new(DatabaseManager { pool: pool.clone() });
+    let db = Arc::new(DatabaseManager::from_pool(pool.clone()));
     let storage_config = StorageConfig::FS {
         root: storage_root.path().to_string_lossy().into_owned(),
     };
@@ -157,6 +160,7 @@ fn insert_body(identity_key: &str, display_name: &str) -> InsertActivitySessionR
         url: Some("https://youtube.com/watch?v=abc".to_string()),
         started_at: fixed_started_at(),
         ended_at: None,
+        device_id: None,
     }
 }
 
@@ -379,6 +383,7 @@ async fn list_activities_returns_parents_with_latest_session(pool: PgPool) {
             url: None,
             started_at: fixed_started_at() + chrono::Duration::seconds(1),
             ended_at: None,
+            device_id: None,
         },
     )
     .await;
@@ -400,3 +405,134 @@ async fn list_activities_returns_parents_with_latest_session(pool: PgPool) {
         Some(code.session.id),
     );
 }
+
+/// Post one session each for `keys`, then pin `last_used_at` so the
+/// first key is the most recent, a minute apart. Returns the parent ids
+/// in rail order.
+async fn seed_activities(app: &AppHarness, keys: &[&str]) -> Vec<Uuid> {
+    let mut ids = Vec::new();
+    for key in keys {
+        ids.push(post_session(app, &insert_body(key, key)).await.activity.id);
+    }
+    // Pinned after every insert, since opening a session can bump the
+    // parents of the sessions it closes.
+    for (i, id) in ids.iter().enumerate() {
+        sqlx::query("UPDATE activities SET last_used_at = $2 WHERE id = $1")
+            .bind(id)
+            .bind(fixed_started_at() - chrono::Duration::minutes(i as i64))
+            .execute(&app.pool)
+            .await
+            .expect("pin last_used_at");
+    }
+    ids
+}
+
+async fn stream_batches(app: &AppHarness, query: &[(&str, String)]) -> Vec<ActivityBatch> {
+    let response = reqwest::Client::new()
+        .get(app.url("/activities/stream"))
+        .query(query)
+        .send()
+        .await
+        .expect("GET");
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()[reqwest::header::CONTENT_TYPE],
+        "application/x-ndjson"
+    );
+    let body = response.text().await.expect("body");
+    body.lines()
+        .map(|line| serde_json::from_str(line).expect("decode batch"))
+        .collect()
+}
+
+fn batch_ids(batch: &ActivityBatch) -> Vec<Uuid> {
+    batch.activities.iter().map(|a| a.activity.id).collect()
+}
+
+#[sqlx::test(migrations = "../be-remote-db/src/migrations")]
+async fn stream_activities_walks_the_rail_in_batches(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let ids = seed_activities(&app, &["a", "b", "c", "d", "e"]).await;
+
+    let batches = stream_batches(&app, &[("batch_size", "2".into())]).await;
+
+    let sizes: Vec<usize> = batches.iter().map(|b| b.activities.len()).collect();
+    assert_eq!(sizes, [2, 2, 1]);
+    assert_eq!(batches.iter().flat_map(batch_ids).collect::<Vec<_>>(), ids);
+    assert!(batches[0].next_cursor.is_some());
+    assert!(batches[1].next_cursor.is_some());
+    assert!(batches[2].next_cursor.is_none());
+}
+
+#[sqlx::test(migrations = "../be-remote-db/src/migrations")]
+async fn stream_activities_resumes_from_cursor_after_limit(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let ids = seed_activities(&app, &["a", "b", "c", "d", "e"]).await;
+
+    let first = stream_batches(&app, &[("batch_size", "2".into()), ("limit", "3".into())]).await;
+    assert_eq!(
+        first.iter().flat_map(batch_ids).collect::<Vec<_>>(),
+        ids[..3]
+    );
+    let cursor = first
+        .last()
+        .unwrap()
+        .next_cursor
+        .clone()
+        .expect("more rows");
+
+    let rest = stream_batches(&app, &[("cursor", cursor)]).await;
+    assert_eq!(
+        rest.iter().flat_map(batch_ids).collect::<Vec<_>>(),
+        ids[3..]
+    );
+    assert!(rest.last().unwrap().next_cursor.is_none());
+}
+
+#[sqlx::test(migrations = "../be-remote-db/src/migrations")]
+async fn stream_activities_filters_by_time_range(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let ids = seed_activities(&app, &["a", "b", "c", "d"]).await;
+
+    // `since` is inclusive and `until` exclusive: minutes -2 and -1.
+    let since = fixed_started_at() - chrono::Duration::minutes(2);
+    let until = fixed_started_at();
+    let batches = stream_batches(
+        &app,
+        &[("since", since.to_rfc3339()), ("until", until.to_rfc3339())],
+    )
+    .await;
+
+    assert_eq!(batches.len(), 1);
+    assert_eq!(batch_ids(&batches[0]), ids[1..3]);
+    assert!(batches[0].next_cursor.is_none());
+}
+
+#[sqlx::test(migrations = "../be-remote-db/src/migrations")]
+async fn stream_activities_is_scoped_to_the_caller(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    seed_activities(&app, &["a", "b"]).await;
+
+    app.act_as(app.other);
+    let batches = stream_batches(&app, &[]).await;
+
+    assert_eq!(batches.len(), 1);
+    assert!(batches[0].activities.is_empty());
+    assert!(batches[0].next_cursor.is_none());
+}
+
+#[sqlx::test(migrations = "../be-remote-db/src/migrations")]
+async fn stream_activities_rejects_invalid_cursor(pool: PgPool) {
+    let app = spawn_app(pool).await;
+
+    let response = reqwest::Client::new()
+        .get(app.url("/activities/stream"))
+        .query(&[("cursor", "not-a-cursor")])
+        .send()
+        .await
+        .expect("GET");
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body: ActivityErrorResponse = response.json().await.expect("decode");
+    assert_eq!(body.error, "invalid_argument");
+}
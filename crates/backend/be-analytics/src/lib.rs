@@ -8,11 +8,84 @@
 //!   indefinitely);
 //! * capture failures land at a uniform log level (`warn`) across services
 //!   instead of one service silencing them at `debug` and another shouting
-//!   at `error`.
+//!   at `error`;
+//! * events about a signed-in user honour their [`AnalyticsConsent`]:
+//!   build them through [`Subject::event`], which returns `None` for users
+//!   who opted out and swaps the user ID for a salted hash for users who
+//!   chose anonymous analytics.
+//!
+//! Events that aren't about a user (webhooks, update checks) keep using
+//! [`Event::new_anon`] directly.
 
+use std::sync::OnceLock;
 use std::time::Duration;
 
+pub use auth_core::AnalyticsConsent;
+use auth_core::Claims;
 pub use posthog_rs::Event;
+use sha2::{Digest, Sha256};
+
+/// Secret mixed into pseudonymous IDs. Without it an anonymous user's ID
+/// could be recovered by hashing candidate user IDs.
+pub const ENV_ID_SALT: &str = "POSTHOG_ID_SALT";
+
+static ID_SALT: OnceLock<String> = OnceLock::new();
+
+/// Install the salt for pseudonymous IDs. Call once at startup; later
+/// calls are ignored. Until a salt is set, events for anonymous users go
+/// out with no user ID at all.
+pub fn set_id_salt(salt: String) {
+    if ID_SALT.set(salt).is_err() {
+        tracing::warn!("analytics ID salt already set; ignoring");
+    }
+}
+
+/// The signed-in user an event is about, and what they agreed to.
+#[derive(Debug, Clone)]
+pub struct Subject {
+    user_id: String,
+    consent: AnalyticsConsent,
+}
+
+impl Subject {
+    pub fn new(user_id: impl Into<String>, consent: AnalyticsConsent) -> Self {
+        Self {
+            user_id: user_id.into(),
+            consent,
+        }
+    }
+
+    pub fn from_claims(claims: &Claims) -> Self {
+        Self::new(claims.sub.clone(), claims.analytics)
+    }
+
+    /// Start an event about this user, or `None` if they opted out.
+    pub fn event(&self, name: &str) -> Option<Event> {
+        match self.consent {
+            AnalyticsConsent::Off => None,
+            AnalyticsConsent::Anonymous => {
+                let Some(salt) = ID_SALT.get() else {
+                    return Some(Event::new_anon(name));
+                };
+                let id = pseudonymous_id(salt, &self.user_id);
+                let mut event = Event::new(name, id.as_str());
+                // Count the hash as a distinct ID without building a
+                // person profile around it.
+                event.insert_prop("$process_person_profile", false).ok();
+                Some(event)
+            }
+            AnalyticsConsent::Identified => Some(Event::new(name, self.user_id.as_str())),
+        }
+    }
+}
+
+fn pseudonymous_id(salt: &str, user_id: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(b":");
+    hasher.update(user_id.as_bytes());
+    hex::encode(hasher.finalize())
+}
 
 /// How long [`capture_async`] waits for a posthog request before giving up.
 ///
@@ -32,3 +105,23 @@ pub fn capture_async(event: Event) {
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opted_out_users_get_no_event() {
+        let subject = Subject::new("user", AnalyticsConsent::Off);
+        assert!(subject.event("anything").is_none());
+    }
+
+    #[test]
+    fn pseudonymous_ids_depend_on_the_salt() {
+        let id = pseudonymous_id("salt", "user");
+        assert_eq!(id, pseudonymous_id("salt", "user"));
+        assert_ne!(id, pseudonymous_id("other", "user"));
+        assert_ne!(id, pseudonymous_id("salt", "other"));
+        assert!(!id.contains("user"));
+    }
+}
@@ -0,0 +1,149 @@
+//! ClamAV daemon (clamd) over TCP, using the `INSTREAM` command.
+//!
+//! The file is sent as length-prefixed chunks terminated by a zero-length
+//! chunk; clamd answers with one line — `stream: OK`,
+//! `stream: <signature> FOUND`, or `... ERROR` — and closes the
+//! connection.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use crate::{MalwareScanner, ScanError, ScanVerdict};
+
+/// Covers connecting, streaming and clamd's scan of a file at the size
+/// limit.
+const SCAN_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Bytes per `INSTREAM` chunk.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct ClamAvScanner {
+    addr: String,
+}
+
+impl ClamAvScanner {
+    pub fn new(addr: impl Into<String>) -> Self {
+        Self { addr: addr.into() }
+    }
+
+    async fn instream(&self, content: &[u8]) -> Result<ScanVerdict, ScanError> {
+        let mut stream = TcpStream::connect(&self.addr).await?;
+
+        stream.write_all(b"zINSTREAM\0").await?;
+        for chunk in content.chunks(CHUNK_SIZE) {
+            stream
+                .write_all(&(chunk.len() as u32).to_be_bytes())
+                .await?;
+            stream.write_all(chunk).await?;
+        }
+        stream.write_all(&0u32.to_be_bytes()).await?;
+        stream.flush().await?;
+
+        let mut reply = Vec::new();
+        stream.read_to_end(&mut reply).await?;
+        parse_reply(&reply)
+    }
+}
+
+#[async_trait]
+impl MalwareScanner for ClamAvScanner {
+    fn name(&self) -> &'static str {
+        "clamav"
+    }
+
+    async fn scan(&self, content: &[u8]) -> Result<ScanVerdict, ScanError> {
+        tokio::time::timeout(SCAN_TIMEOUT, self.instream(content))
+            .await
+            .map_err(|_| ScanError::Timeout(SCAN_TIMEOUT))?
+    }
+}
+
+fn parse_reply(reply: &[u8]) -> Result<ScanVerdict, ScanError> {
+    let reply = String::from_utf8_lossy(reply);
+    let reply = reply.trim_end_matches(['\0', '\n', '\r']).trim();
+    let result = reply.strip_prefix("stream:").unwrap_or(reply).trim();
+
+    if result == "OK" {
+        Ok(ScanVerdict::Clean)
+    } else if let Some(signature) = result.strip_suffix(" FOUND") {
+        Ok(ScanVerdict::Infected {
+            signature: signature.trim().to_string(),
+        })
+    } else {
+        Err(ScanError::Protocol(reply.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    #[test]
+    fn parses_clamd_replies() {
+        assert_eq!(parse_reply(b"stream: OK\0").unwrap(), ScanVerdict::Clean);
+        assert_eq!(
+            parse_reply(b"stream: Eicar-Test-Signature FOUND\0").unwrap(),
+            ScanVerdict::Infected {
+                signature: "Eicar-Test-Signature".into()
+            }
+        );
+        assert!(parse_reply(b"INSTREAM size limit exceeded. ERROR\0").is_err());
+        assert!(parse_reply(b"").is_err());
+    }
+
+    /// A one-shot clamd that checks the framing and flags anything
+    /// containing `EVIL`.
+    async fn fake_clamd() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut command = [0u8; 10];
+            socket.read_exact(&mut command).await.unwrap();
+            assert_eq!(&command, b"zINSTREAM\0");
+
+            let mut content = Vec::new();
+            loop {
+                let mut len = [0u8; 4];
+                socket.read_exact(&mut len).await.unwrap();
+                let len = u32::from_be_bytes(len) as usize;
+                if len == 0 {
+                    break;
+                }
+                let mut chunk = vec![0u8; len];
+                socket.read_exact(&mut chunk).await.unwrap();
+                content.extend_from_slice(&chunk);
+            }
+
+            let reply: &[u8] = if content.windows(4).any(|w| w == b"EVIL") {
+                b"stream: Fake.Evil FOUND\0"
+            } else {
+                b"stream: OK\0"
+            };
+            socket.write_all(reply).await.unwrap();
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn streams_content_to_clamd() {
+        let scanner = ClamAvScanner::new(fake_clamd().await);
+        let mut content = vec![b'a'; CHUNK_SIZE * 2 + 10];
+        assert_eq!(scanner.scan(&content).await.unwrap(), ScanVerdict::Clean);
+
+        let scanner = ClamAvScanner::new(fake_clamd().await);
+        content.extend_from_slice(b"EVIL");
+        assert_eq!(
+            scanner.scan(&content).await.unwrap(),
+            ScanVerdict::Infected {
+                signature: "Fake.Evil".into()
+            }
+        );
+    }
+}
@@ -0,0 +1,149 @@
+//! Scanner selection from the environment.
+//!
+//! | Variable                  | Meaning                                             |
+//! |---------------------------|-----------------------------------------------------|
+//! | `ASSET_SCAN_BACKEND`      | `clamav`, `http`, or unset / `none` to disable      |
+//! | `ASSET_SCAN_CLAMAV_ADDR`  | clamd TCP address, default `127.0.0.1:3310`         |
+//! | `ASSET_SCAN_HTTP_URL`     | endpoint that receives the raw bytes (`http`)       |
+//! | `ASSET_SCAN_HTTP_TOKEN`   | optional bearer token for that endpoint             |
+//! | `ASSET_SCAN_MAX_BYTES`    | larger assets are skipped, default 25 MiB           |
+
+use std::sync::Arc;
+
+use secrecy::SecretString;
+
+use crate::{ClamAvScanner, HttpScanner, MalwareScanner, ScanError};
+
+const DEFAULT_CLAMAV_ADDR: &str = "127.0.0.1:3310";
+
+/// clamd's own default `StreamMaxLength`; anything larger it refuses.
+const DEFAULT_MAX_BYTES: u64 = 25 * 1024 * 1024;
+
+#[derive(Debug, Clone)]
+pub enum ScanBackend {
+    ClamAv {
+        addr: String,
+    },
+    Http {
+        url: String,
+        token: Option<SecretString>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct ScanConfig {
+    /// `None` disables scanning.
+    pub backend: Option<ScanBackend>,
+    pub max_bytes: u64,
+}
+
+impl ScanConfig {
+    pub fn from_env() -> Result<Self, ScanError> {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Result<Self, ScanError> {
+        let var = |name: &str| lookup(name).filter(|v| !v.trim().is_empty());
+
+        let backend = match var("ASSET_SCAN_BACKEND")
+            .map(|v| v.trim().to_lowercase())
+            .as_deref()
+        {
+            None | Some("none") => None,
+            Some("clamav") => Some(ScanBackend::ClamAv {
+                addr: var("ASSET_SCAN_CLAMAV_ADDR")
+                    .unwrap_or_else(|| DEFAULT_CLAMAV_ADDR.to_string()),
+            }),
+            Some("http") => Some(ScanBackend::Http {
+                url: var("ASSET_SCAN_HTTP_URL").ok_or_else(|| {
+                    ScanError::Config("ASSET_SCAN_HTTP_URL must be set for the http backend".into())
+                })?,
+                token: var("ASSET_SCAN_HTTP_TOKEN").map(SecretString::from),
+            }),
+            Some(other) => {
+                return Err(ScanError::Config(format!(
+                    "unknown ASSET_SCAN_BACKEND `{other}` (expected `clamav`, `http` or `none`)"
+                )));
+            }
+        };
+
+        let max_bytes = match var("ASSET_SCAN_MAX_BYTES") {
+            None => DEFAULT_MAX_BYTES,
+            Some(v) => v.trim().parse().map_err(|_| {
+                ScanError::Config(format!(
+                    "ASSET_SCAN_MAX_BYTES must be a non-negative integer, got `{v}`"
+                ))
+            })?,
+        };
+
+        Ok(Self { backend, max_bytes })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.backend.is_some()
+    }
+
+    /// The configured scanner, or `None` when scanning is disabled.
+    pub fn scanner(&self) -> Option<Arc<dyn MalwareScanner>> {
+        match self.backend.as_ref()? {
+            ScanBackend::ClamAv { addr } => Some(Arc::new(ClamAvScanner::new(addr.clone()))),
+            ScanBackend::Http { url, token } => {
+                Some(Arc::new(HttpScanner::new(url.clone(), token.clone())))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn config_from(vars: &[(&str, &str)]) -> Result<ScanConfig, ScanError> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        ScanConfig::from_lookup(|name| vars.get(name).cloned())
+    }
+
+    #[test]
+    fn disabled_unless_a_backend_is_named() {
+        assert!(!config_from(&[]).unwrap().is_enabled());
+        assert!(
+            !config_from(&[("ASSET_SCAN_BACKEND", "none")])
+                .unwrap()
+                .is_enabled()
+        );
+    }
+
+    #[test]
+    fn clamav_defaults_to_local_daemon() {
+        let config = config_from(&[("ASSET_SCAN_BACKEND", "ClamAV")]).unwrap();
+        assert!(matches!(
+            config.backend,
+            Some(ScanBackend::ClamAv { ref addr }) if addr == DEFAULT_CLAMAV_ADDR
+        ));
+        assert_eq!(config.max_bytes, DEFAULT_MAX_BYTES);
+    }
+
+    #[test]
+    fn http_requires_url() {
+        assert!(config_from(&[("ASSET_SCAN_BACKEND", "http")]).is_err());
+        let config = config_from(&[
+            ("ASSET_SCAN_BACKEND", "http"),
+            ("ASSET_SCAN_HTTP_URL", "https://scan.example.com/v1/scan"),
+            ("ASSET_SCAN_MAX_BYTES", "1048576"),
+        ])
+        .unwrap();
+        assert!(config.is_enabled());
+        assert_eq!(config.max_bytes, 1024 * 1024);
+    }
+
+    #[test]
+    fn rejects_unknown_backend_and_bad_limit() {
+        assert!(config_from(&[("ASSET_SCAN_BACKEND", "virustotal")]).is_err());
+        assert!(config_from(&[("ASSET_SCAN_MAX_BYTES", "lots")]).is_err());
+    }
+}
@@ -0,0 +1,26 @@
+use thiserror::Error;
+
+/// Why a scan didn't produce a verdict. The scan is retried.
+#[derive(Debug, Error)]
+pub enum ScanError {
+    #[error("Configuration error: {0}")]
+    Config(String),
+
+    #[error("Scanner connection failed: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Scanner did not answer within {0:?}")]
+    Timeout(std::time::Duration),
+
+    #[error("Unexpected scanner reply: {0}")]
+    Protocol(String),
+
+    #[error("Scan request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("Failed to read asset: {0}")]
+    Storage(#[from] be_storage::StorageError),
+
+    #[error("Database error: {0}")]
+    Database(#[from] be_remote_db::DbError),
+}
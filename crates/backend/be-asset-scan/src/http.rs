@@ -0,0 +1,72 @@
+//! Generic HTTP scanning API.
+//!
+//! The raw bytes are `POST`ed as `application/octet-stream` (with a bearer
+//! token when configured) and the service answers with
+//! `{"infected": bool, "signature": string | null}`. Small adapters in
+//! front of commercial scanning APIs can speak this shape.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use secrecy::{ExposeSecret, SecretString};
+use serde::Deserialize;
+
+use crate::{MalwareScanner, ScanError, ScanVerdict};
+
+const SCAN_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[derive(Debug, Deserialize)]
+struct ScanResponse {
+    infected: bool,
+    #[serde(default)]
+    signature: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpScanner {
+    client: reqwest::Client,
+    url: String,
+    token: Option<SecretString>,
+}
+
+impl HttpScanner {
+    pub fn new(url: impl Into<String>, token: Option<SecretString>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.into(),
+            token,
+        }
+    }
+}
+
+#[async_trait]
+impl MalwareScanner for HttpScanner {
+    fn name(&self) -> &'static str {
+        "http"
+    }
+
+    async fn scan(&self, content: &[u8]) -> Result<ScanVerdict, ScanError> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .timeout(SCAN_TIMEOUT)
+            .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+            .body(content.to_vec());
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token.expose_secret());
+        }
+
+        let response: ScanResponse = request.send().await?.error_for_status()?.json().await?;
+
+        Ok(if response.infected {
+            ScanVerdict::Infected {
+                signature: response
+                    .signature
+                    .filter(|s| !s.trim().is_empty())
+                    .unwrap_or_else(|| "unknown".to_string()),
+            }
+        } else {
+            ScanVerdict::Clean
+        })
+    }
+}
@@ -0,0 +1,86 @@
+//! Background malware scanning of uploaded assets.
+//!
+//! With a scanner configured, the asset service stores new uploads as
+//! `pending` (see `AssetService::with_malware_scanning`) and this crate's
+//! worker picks them up shortly after:
+//!
+//! 1. Claims pending assets under a lease, oldest first.
+//! 2. Downloads each one (decrypted) and hands the bytes to the
+//!    configured [`MalwareScanner`] — a ClamAV daemon or an HTTP API.
+//! 3. Marks the asset `clean`, or `quarantined` with the detected
+//!    signature. The asset service refuses to serve quarantined assets.
+//! 4. Emails the owner about anything quarantined.
+//!
+//! A scan that errors is retried once its lease lapses; after
+//! [`MAX_SCAN_ATTEMPTS`] the asset is marked `skipped` and served like an
+//! unscanned one. Files larger than the configured limit are skipped
+//! without being downloaded.
+//!
+//! Self-hosters who don't run a scanner leave `ASSET_SCAN_BACKEND` unset:
+//! no worker starts and uploads are stored as `skipped`.
+
+mod clamav;
+mod config;
+mod error;
+mod http;
+mod worker;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use be_email_service::EmailService;
+use be_remote_db::DatabaseManager;
+use be_storage::StorageService;
+
+pub use clamav::ClamAvScanner;
+pub use config::{ScanBackend, ScanConfig};
+pub use error::ScanError;
+pub use http::HttpScanner;
+pub use worker::{AssetScanWorkerHandle, MAX_SCAN_ATTEMPTS};
+
+/// What a scanner concluded about one file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanVerdict {
+    Clean,
+    Infected { signature: String },
+}
+
+/// A malware scanning engine. Implementations must be safe to call
+/// concurrently.
+#[async_trait]
+pub trait MalwareScanner: Send + Sync {
+    /// Short name for logs (`clamav`, `http`).
+    fn name(&self) -> &'static str;
+
+    async fn scan(&self, content: &[u8]) -> Result<ScanVerdict, ScanError>;
+}
+
+/// Everything the worker needs to scan assets.
+pub struct AssetScanner {
+    pub db: Arc<DatabaseManager>,
+    pub storage: Arc<StorageService>,
+    pub scanner: Arc<dyn MalwareScanner>,
+    /// Assets larger than this are marked `skipped` without a scan.
+    pub max_bytes: u64,
+    /// `None` in dev mode; owners aren't notified.
+    pub email: Option<Arc<EmailService>>,
+}
+
+/// Start the scan worker when `config` enables scanning. The caller owns
+/// the returned handle and should shut it down after the server stops.
+pub fn init_asset_scan_worker(
+    config: &ScanConfig,
+    db: Arc<DatabaseManager>,
+    storage: Arc<StorageService>,
+    email: Option<Arc<EmailService>>,
+) -> Option<AssetScanWorkerHandle> {
+    let scanner = config.scanner()?;
+    tracing::info!(backend = scanner.name(), "Initializing asset scan worker");
+    Some(worker::spawn_worker(Arc::new(AssetScanner {
+        db,
+        storage,
+        scanner,
+        max_bytes: config.max_bytes,
+        email,
+    })))
+}
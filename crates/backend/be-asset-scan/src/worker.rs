@@ -0,0 +1,186 @@
+//! The worker loop: claim pending assets and scan them one after another.
+
+use std::sync::Arc;
+
+use be_remote_db::{AssetScanStatus, DbError, PendingAssetScan};
+use chrono::Duration as ChronoDuration;
+use tokio::sync::oneshot;
+use tokio::time::{Duration, sleep};
+
+use crate::error::ScanError;
+use crate::{AssetScanner, ScanVerdict};
+
+/// How long a claimed asset stays leased, and so how long a failed scan
+/// waits before it is retried. Well above a scan at the size limit.
+const LEASE: ChronoDuration = ChronoDuration::minutes(10);
+
+/// Assets claimed per tick.
+const BATCH_SIZE: i64 = 10;
+
+/// Short, so a fresh upload is usually scanned within seconds.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Attempts before a scan that keeps failing gives up and the asset is
+/// marked `skipped`.
+pub const MAX_SCAN_ATTEMPTS: i32 = 5;
+
+pub struct AssetScanWorkerHandle {
+    shutdown: oneshot::Sender<()>,
+    join: tokio::task::JoinHandle<()>,
+}
+
+impl AssetScanWorkerHandle {
+    pub async fn shutdown(self) {
+        let Self { shutdown, join } = self;
+        let _ = shutdown.send(());
+        let _ = join.await;
+    }
+}
+
+/// Spawn the background worker. A scan in progress when shutdown is
+/// requested is abandoned; its lease lapses and it is retried.
+pub(crate) fn spawn_worker(scanner: Arc<AssetScanner>) -> AssetScanWorkerHandle {
+    let (shutdown_tx, mut shutdown_rx) = oneshot::channel::<()>();
+
+    let join = tokio::spawn(async move {
+        tracing::info!("Asset scan worker started");
+        loop {
+            tokio::select! {
+                result = tick(&scanner) => {
+                    if let Err(e) = result {
+                        tracing::error!(error = %e, "Asset scan worker tick failed");
+                    }
+                }
+                _ = &mut shutdown_rx => break,
+            }
+
+            tokio::select! {
+                _ = sleep(POLL_INTERVAL) => {}
+                _ = &mut shutdown_rx => break,
+            }
+        }
+        tracing::info!("Asset scan worker shutting down");
+    });
+
+    AssetScanWorkerHandle {
+        shutdown: shutdown_tx,
+        join,
+    }
+}
+
+async fn tick(scanner: &AssetScanner) -> Result<(), DbError> {
+    let claimed = scanner
+        .db
+        .claim_pending_asset_scans()
+        .limit(BATCH_SIZE)
+        .lease(LEASE)
+        .call()
+        .await?;
+    for asset in claimed {
+        run_scan(scanner, &asset).await;
+    }
+    Ok(())
+}
+
+#[tracing::instrument(
+    skip_all,
+    fields(asset_id = %asset.id, user_id = %asset.user_id, attempt = asset.attempts)
+)]
+async fn run_scan(scanner: &AssetScanner, asset: &PendingAssetScan) {
+    let Err(e) = scan(scanner, asset).await else {
+        return;
+    };
+
+    if asset.attempts < MAX_SCAN_ATTEMPTS {
+        tracing::warn!(error = %e, "Asset scan failed; retrying once the lease lapses");
+        return;
+    }
+
+    tracing::error!(error = %e, "Asset scan failed too many times; serving it unscanned");
+    if let Err(db_err) = finish(scanner, asset, AssetScanStatus::Skipped, None).await {
+        tracing::error!(error = %db_err, "Failed to mark asset scan as skipped");
+    }
+}
+
+async fn scan(scanner: &AssetScanner, asset: &PendingAssetScan) -> Result<(), ScanError> {
+    let too_large = asset
+        .size_bytes
+        .is_some_and(|size| u64::try_from(size).unwrap_or(0) > scanner.max_bytes);
+    if too_large {
+        tracing::info!(
+            size_bytes = asset.size_bytes,
+            "Asset exceeds the scan size limit; skipping"
+        );
+        finish(scanner, asset, AssetScanStatus::Skipped, None).await?;
+        return Ok(());
+    }
+
+    let content = match scanner.storage.download(&asset.storage_uri).await {
+        Ok(content) => content,
+        Err(e) if e.is_not_found() => {
+            // Deleted between upload and scan; nothing left to serve.
+            finish(scanner, asset, AssetScanStatus::Skipped, None).await?;
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    match scanner.scanner.scan(&content).await? {
+        ScanVerdict::Clean => {
+            finish(scanner, asset, AssetScanStatus::Clean, None).await?;
+        }
+        ScanVerdict::Infected { signature } => {
+            tracing::warn!(signature = %signature, "Asset quarantined");
+            let quarantined = finish(
+                scanner,
+                asset,
+                AssetScanStatus::Quarantined,
+                Some(signature.clone()),
+            )
+            .await?;
+            if quarantined {
+                notify_owner(scanner, asset, &signature).await;
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn finish(
+    scanner: &AssetScanner,
+    asset: &PendingAssetScan,
+    status: AssetScanStatus,
+    signature: Option<String>,
+) -> Result<bool, DbError> {
+    scanner
+        .db
+        .finish_asset_scan()
+        .id(asset.id)
+        .status(status)
+        .maybe_signature(signature)
+        .call()
+        .await
+}
+
+/// Best effort: the asset is already quarantined, so a failed email is
+/// only logged.
+async fn notify_owner(scanner: &AssetScanner, asset: &PendingAssetScan, signature: &str) {
+    let Some(email) = &scanner.email else {
+        return;
+    };
+
+    let user = match scanner.db.get_user().id(asset.user_id).call().await {
+        Ok(user) => user,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to look up owner of quarantined asset");
+            return;
+        }
+    };
+
+    if let Err(e) = email
+        .send_asset_quarantined_email(&user.email, &asset.name, Some(signature))
+        .await
+    {
+        tracing::error!(error = %e, "Failed to send asset quarantined email");
+    }
+}
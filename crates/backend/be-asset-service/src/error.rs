@@ -1,7 +1,8 @@
 use std::borrow::Cow;
 
+use asset_core::BatchItemError;
 use axum::{
-    http::StatusCode,
+    http::{HeaderValue, StatusCode, header},
     response::{IntoResponse, Json, Response},
 };
 use be_asset::AssetError;
@@ -73,6 +74,30 @@ fn render_asset(err: &AssetError) -> Rendered {
             message: Cow::Borrowed("Asset not found"),
             details: None,
         },
+        AssetError::Quarantined => Rendered {
+            status: StatusCode::FORBIDDEN,
+            kind: "asset_quarantined",
+            message: Cow::Borrowed("Asset was quarantined by the malware scanner"),
+            details: None,
+        },
+        AssetError::Corrupted => Rendered {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            kind: "asset_corrupted",
+            message: Cow::Borrowed("Stored asset is corrupted"),
+            details: None,
+        },
+        AssetError::OnLegalHold => Rendered {
+            status: StatusCode::CONFLICT,
+            kind: "legal_hold",
+            message: Cow::Borrowed("Asset is under legal hold and can't be deleted"),
+            details: None,
+        },
+        AssetError::RangeNotSatisfiable { size } => Rendered {
+            status: StatusCode::RANGE_NOT_SATISFIABLE,
+            kind: "range_not_satisfiable",
+            message: Cow::Borrowed("Requested range is outside the asset"),
+            details: Some(format!("asset is {size} bytes")),
+        },
         AssetError::EmptyContent => Rendered {
             status: StatusCode::BAD_REQUEST,
             kind: "empty_content",
@@ -133,6 +158,100 @@ fn render_asset(err: &AssetError) -> Rendered {
                 details: None,
             }
         }
+        AssetError::StorageDelete(e) => {
+            tracing::error!(error = %e, "storage delete failed");
+            Rendered {
+                status: StatusCode::BAD_GATEWAY,
+                kind: "storage_delete",
+                message: Cow::Borrowed("Failed to delete asset from storage"),
+                details: None,
+            }
+        }
+        AssetError::DatabaseWrite(e) => {
+            tracing::error!(error = %e, "database write failed");
+            Rendered {
+                status: StatusCode::INTERNAL_SERVER_ERROR,
+                kind: "database_write",
+                message: Cow::Borrowed("Failed to update assets"),
+                details: None,
+            }
+        }
+        AssetError::ActivityNotFound => Rendered {
+            status: StatusCode::NOT_FOUND,
+            kind: "activity_not_found",
+            message: Cow::Borrowed("Activity not found"),
+            details: None,
+        },
+        AssetError::SelfGrant => Rendered {
+            status: StatusCode::BAD_REQUEST,
+            kind: "self_grant",
+            message: Cow::Borrowed("An asset can't be shared with its owner"),
+            details: None,
+        },
+        AssetError::GranteeNotFound => Rendered {
+            status: StatusCode::NOT_FOUND,
+            kind: "grantee_not_found",
+            message: Cow::Borrowed("User to share with not found"),
+            details: None,
+        },
+        AssetError::GrantNotFound => Rendered {
+            status: StatusCode::NOT_FOUND,
+            kind: "grant_not_found",
+            message: Cow::Borrowed("Asset grant not found"),
+            details: None,
+        },
+        AssetError::ExpiryInPast => Rendered {
+            status: StatusCode::BAD_REQUEST,
+            kind: "expiry_in_past",
+            message: Cow::Borrowed("Expiry must be in the future"),
+            details: None,
+        },
+        AssetError::InvalidLinkLifetime { max_secs } => Rendered {
+            status: StatusCode::BAD_REQUEST,
+            kind: "invalid_link_lifetime",
+            message: Cow::Owned(format!(
+                "Link lifetime must be between 1 and {max_secs} seconds"
+            )),
+            details: None,
+        },
+        AssetError::InvalidUploadSize { max } => Rendered {
+            status: StatusCode::BAD_REQUEST,
+            kind: "invalid_upload_size",
+            message: Cow::Owned(format!("Upload size must be between 1 and {max} bytes")),
+            details: None,
+        },
+        AssetError::TooManyUploads { max } => Rendered {
+            status: StatusCode::TOO_MANY_REQUESTS,
+            kind: "too_many_uploads",
+            message: Cow::Owned(format!(
+                "At most {max} uploads may be open at once; complete or abort one first"
+            )),
+            details: None,
+        },
+        AssetError::UploadNotFound => Rendered {
+            status: StatusCode::NOT_FOUND,
+            kind: "upload_not_found",
+            message: Cow::Borrowed("Upload session not found or expired"),
+            details: None,
+        },
+        AssetError::InvalidChunk(reason) => Rendered {
+            status: StatusCode::BAD_REQUEST,
+            kind: "invalid_chunk",
+            message: Cow::Borrowed("Chunk doesn't fit the upload"),
+            details: Some(reason.clone()),
+        },
+        AssetError::UploadIncomplete { missing } => Rendered {
+            status: StatusCode::CONFLICT,
+            kind: "upload_incomplete",
+            message: Cow::Borrowed("Upload is missing chunks"),
+            details: Some(format!("{missing} chunks not yet received")),
+        },
+        AssetError::BatchTooLarge { got, max } => Rendered {
+            status: StatusCode::BAD_REQUEST,
+            kind: "batch_too_large",
+            message: Cow::Owned(format!("At most {max} ids per batch")),
+            details: Some(format!("received {got} distinct ids")),
+        },
         AssetError::StorageConfig(e) => {
             tracing::error!(error = %e, "storage config error");
             Rendered {
@@ -145,8 +264,26 @@ fn render_asset(err: &AssetError) -> Rendered {
     }
 }
 
+/// Per-item error for batch responses: the same kind and message the
+/// single-item endpoints would return for `err`.
+pub(crate) fn batch_item_error(err: &AssetError) -> BatchItemError {
+    let Rendered { kind, message, .. } = render_asset(err);
+    BatchItemError {
+        error: kind.to_owned(),
+        message: message.into_owned(),
+    }
+}
+
 impl IntoResponse for AssetServiceError {
     fn into_response(self) -> Response {
+        // RFC 9110 §15.5.17: a 416 says how large the representation is.
+        let content_range = match &self {
+            Self::Asset(AssetError::RangeNotSatisfiable { size }) => {
+                Some(format!("bytes */{size}"))
+            }
+            _ => None,
+        };
+
         let Rendered {
             status,
             kind,
@@ -160,6 +297,10 @@ impl IntoResponse for AssetServiceError {
             details,
         };
 
-        (status, Json(body)).into_response()
+        let mut response = (status, Json(body)).into_response();
+        if let Some(value) = content_range.and_then(|v| HeaderValue::from_str(&v).ok()) {
+            response.headers_mut().insert(header::CONTENT_RANGE, value);
+        }
+        response
     }
 }
@@ -1,18 +1,28 @@
 use std::sync::Arc;
 
-use asset_core::{Asset, CreateAssetRequest};
+use asset_core::{
+    Asset, AssetGrant, AssetLink, AssetPreferences, BatchAssetResult, BatchAssetsRequest,
+    BatchGetAssetsResponse, BatchItemResult, BatchItemsResponse, BatchLinkAssetsRequest,
+    CreateAssetGrantRequest, CreateAssetLinkRequest, CreateAssetRequest, CreateUploadRequest,
+    ListAssetGrantsResponse, STORAGE_TIER_HEADER, SharedAssetsResponse, StorageTier, UploadSession,
+};
 use axum::{
     Json,
-    extract::{Path, State},
-    http::{StatusCode, header},
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
     response::{IntoResponse, Response},
 };
 use base64::{Engine as _, engine::general_purpose};
-use be_asset::CreateAssetInput;
+use be_asset::{BatchOutcome, CreateAssetInput};
 use be_auth_core::AuthUser;
 use uuid::Uuid;
 
-use crate::{error::AssetServiceError, service::AppState};
+use crate::{
+    error::{AssetServiceError, batch_item_error},
+    range::{RangeQuery, requested_range},
+    service::AppState,
+};
 
 #[tracing::instrument(skip_all, fields(user_id))]
 pub async fn create_asset_handler(
@@ -32,6 +42,7 @@ pub async fn create_asset_handler(
         content,
         mime_type: payload.mime_type,
         metadata: payload.metadata,
+        device_id: payload.device_id,
     };
 
     let asset: Asset = state.core.create_asset(input, user_id).await?;
@@ -49,21 +60,344 @@ const ASSET_CACHE_CONTROL: &str = "private, max-age=31536000, immutable";
 /// reuse them indefinitely. Ownership is enforced inside the domain
 /// service: an asset owned by a different user surfaces as a clean 404
 /// rather than a 403, preserving non-disclosure of foreign asset ids.
+///
+/// With `?offset=&length=` or a single `Range: bytes=…` header only that
+/// part is read from storage and returned as `206 Partial Content`, so the
+/// desktop player can seek inside a large recording.
+///
+/// A read served from the cold storage tier carries `x-storage-tier: cold`,
+/// so the client can tell a slow first load apart from a stalled one.
 #[tracing::instrument(skip_all, fields(user_id, asset_id = %asset_id))]
 pub async fn get_asset_bytes_handler(
     State(state): State<Arc<AppState>>,
     user: AuthUser,
     Path(asset_id): Path<Uuid>,
+    Query(range_query): Query<RangeQuery>,
+    request_headers: HeaderMap,
 ) -> Result<Response, AssetServiceError> {
     let user_id = user.user_id()?;
     tracing::Span::current().record("user_id", tracing::field::display(user_id));
 
+    if let Some(range) = requested_range(&range_query, &request_headers) {
+        let part = state
+            .core
+            .get_asset_range(asset_id, user_id, range.offset, range.length)
+            .await?;
+        let last = part.offset + part.bytes.len() as u64 - 1;
+        let headers = [
+            (header::CONTENT_TYPE, part.mime_type),
+            (header::CACHE_CONTROL, ASSET_CACHE_CONTROL.to_owned()),
+            (header::ACCEPT_RANGES, "bytes".to_owned()),
+            (
+                header::CONTENT_RANGE,
+                format!("bytes {}-{last}/{}", part.offset, part.total_size),
+            ),
+        ];
+        let response = (StatusCode::PARTIAL_CONTENT, headers, part.bytes).into_response();
+        return Ok(with_storage_tier(response, part.storage_tier));
+    }
+
     let asset = state.core.get_asset_bytes(asset_id, user_id).await?;
 
     let headers = [
         (header::CONTENT_TYPE, asset.mime_type),
         (header::CACHE_CONTROL, ASSET_CACHE_CONTROL.to_owned()),
+        (header::ACCEPT_RANGES, "bytes".to_owned()),
+    ];
+
+    let response = (StatusCode::OK, headers, asset.bytes).into_response();
+    Ok(with_storage_tier(response, asset.storage_tier))
+}
+
+fn with_storage_tier(mut response: Response, tier: StorageTier) -> Response {
+    if tier == StorageTier::Cold {
+        response
+            .headers_mut()
+            .insert(STORAGE_TIER_HEADER, HeaderValue::from_static("cold"));
+    }
+    response
+}
+
+/// Metadata for up to `MAX_BATCH_SIZE` assets in one round trip, with a
+/// per-id result so one missing asset doesn't fail the rest.
+#[tracing::instrument(skip_all, fields(user_id, count = payload.ids.len()))]
+pub async fn batch_get_assets_handler(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Json(payload): Json<BatchAssetsRequest>,
+) -> Result<Json<BatchGetAssetsResponse>, AssetServiceError> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    let outcome = state.core.get_assets(&payload.ids, user_id).await?;
+
+    let results = outcome
+        .into_iter()
+        .map(|(id, result)| match result {
+            Ok(asset) => BatchAssetResult {
+                id,
+                asset: Some(asset),
+                error: None,
+            },
+            Err(e) => BatchAssetResult {
+                id,
+                asset: None,
+                error: Some(batch_item_error(&e)),
+            },
+        })
+        .collect();
+
+    Ok(Json(BatchGetAssetsResponse { results }))
+}
+
+/// Delete up to `MAX_BATCH_SIZE` assets, reporting each id separately.
+#[tracing::instrument(skip_all, fields(user_id, count = payload.ids.len()))]
+pub async fn batch_delete_assets_handler(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Json(payload): Json<BatchAssetsRequest>,
+) -> Result<Json<BatchItemsResponse>, AssetServiceError> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    let outcome = state.core.delete_assets(&payload.ids, user_id).await?;
+
+    Ok(Json(items_response(outcome)))
+}
+
+/// Link up to `MAX_BATCH_SIZE` assets to one activity. An activity the
+/// caller doesn't own fails the whole request with 404.
+#[tracing::instrument(
+    skip_all,
+    fields(user_id, activity_id = %payload.activity_id, count = payload.asset_ids.len())
+)]
+pub async fn batch_link_assets_handler(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Json(payload): Json<BatchLinkAssetsRequest>,
+) -> Result<Json<BatchItemsResponse>, AssetServiceError> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    let outcome = state
+        .core
+        .link_assets_to_activity(payload.activity_id, &payload.asset_ids, user_id)
+        .await?;
+
+    Ok(Json(items_response(outcome)))
+}
+
+#[tracing::instrument(skip_all, fields(user_id))]
+pub async fn get_preferences_handler(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+) -> Result<Json<AssetPreferences>, AssetServiceError> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    Ok(Json(state.core.get_preferences(user_id).await?))
+}
+
+/// Replace the caller's upload preferences. Assets already stored are not
+/// reprocessed.
+#[tracing::instrument(skip_all, fields(user_id))]
+pub async fn put_preferences_handler(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Json(payload): Json<AssetPreferences>,
+) -> Result<Json<AssetPreferences>, AssetServiceError> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    Ok(Json(state.core.update_preferences(user_id, payload).await?))
+}
+
+/// Let another user read one of the caller's assets.
+#[tracing::instrument(skip_all, fields(user_id, asset_id = %asset_id))]
+pub async fn create_grant_handler(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(asset_id): Path<Uuid>,
+    Json(payload): Json<CreateAssetGrantRequest>,
+) -> Result<Response, AssetServiceError> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    let grant: AssetGrant = state
+        .core
+        .share_asset(asset_id, user_id, payload.user_id, payload.expires_at)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(grant)).into_response())
+}
+
+#[tracing::instrument(skip_all, fields(user_id, asset_id = %asset_id))]
+pub async fn list_grants_handler(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(asset_id): Path<Uuid>,
+) -> Result<Json<ListAssetGrantsResponse>, AssetServiceError> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    let grants = state.core.list_asset_grants(asset_id, user_id).await?;
+
+    Ok(Json(ListAssetGrantsResponse { grants }))
+}
+
+#[tracing::instrument(skip_all, fields(user_id, asset_id = %asset_id, grant_id = %grant_id))]
+pub async fn revoke_grant_handler(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path((asset_id, grant_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, AssetServiceError> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    state
+        .core
+        .revoke_asset_grant(grant_id, asset_id, user_id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Mint an expiring link to one of the caller's assets. The token is in
+/// this response only.
+#[tracing::instrument(skip_all, fields(user_id, asset_id = %asset_id))]
+pub async fn create_link_handler(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(asset_id): Path<Uuid>,
+    Json(payload): Json<CreateAssetLinkRequest>,
+) -> Result<Response, AssetServiceError> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    let link: AssetLink = state
+        .core
+        .create_asset_link(asset_id, user_id, payload.expires_in_secs)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(link)).into_response())
+}
+
+#[tracing::instrument(skip_all, fields(user_id))]
+pub async fn shared_with_me_handler(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+) -> Result<Json<SharedAssetsResponse>, AssetServiceError> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    let assets = state.core.list_shared_with_me(user_id).await?;
+
+    Ok(Json(SharedAssetsResponse { assets }))
+}
+
+/// Open a resumable upload. The response says which chunk size to use.
+#[tracing::instrument(skip_all, fields(user_id, size = payload.size))]
+pub async fn create_upload_handler(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Json(payload): Json<CreateUploadRequest>,
+) -> Result<Response, AssetServiceError> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    let session: UploadSession = state.core.create_upload(payload, user_id).await?;
+
+    Ok((StatusCode::CREATED, Json(session)).into_response())
+}
+
+/// Where an upload stands, for resuming after a disconnect.
+#[tracing::instrument(skip_all, fields(user_id, upload_id = %upload_id))]
+pub async fn get_upload_handler(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(upload_id): Path<Uuid>,
+) -> Result<Json<UploadSession>, AssetServiceError> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    Ok(Json(state.core.get_upload(upload_id, user_id).await?))
+}
+
+/// Store one chunk, sent as the raw request body.
+#[tracing::instrument(skip_all, fields(user_id, upload_id = %upload_id, offset = offset, len = body.len()))]
+pub async fn put_upload_chunk_handler(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path((upload_id, offset)): Path<(Uuid, u64)>,
+    body: Bytes,
+) -> Result<Json<UploadSession>, AssetServiceError> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    let session = state
+        .core
+        .put_upload_chunk(upload_id, user_id, offset, &body)
+        .await?;
+
+    Ok(Json(session))
+}
+
+/// Turn a fully received upload into an asset.
+#[tracing::instrument(skip_all, fields(user_id, upload_id = %upload_id))]
+pub async fn complete_upload_handler(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(upload_id): Path<Uuid>,
+) -> Result<Response, AssetServiceError> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    let asset: Asset = state.core.complete_upload(upload_id, user_id).await?;
+
+    Ok((StatusCode::CREATED, Json(asset)).into_response())
+}
+
+#[tracing::instrument(skip_all, fields(user_id, upload_id = %upload_id))]
+pub async fn abort_upload_handler(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(upload_id): Path<Uuid>,
+) -> Result<StatusCode, AssetServiceError> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    state.core.abort_upload(upload_id, user_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Download the asset a link token opens. Public: the token is the
+/// credential, so this route bypasses authentication. Unlike owner
+/// downloads the response isn't cacheable, since revoking the link must
+/// take effect.
+#[tracing::instrument(skip_all)]
+pub async fn get_shared_asset_handler(
+    State(state): State<Arc<AppState>>,
+    Path(token): Path<String>,
+) -> Result<Response, AssetServiceError> {
+    let asset = state.core.get_asset_bytes_by_link(&token).await?;
+
+    let headers = [
+        (header::CONTENT_TYPE, asset.mime_type),
+        (header::CACHE_CONTROL, "no-store".to_owned()),
     ];
 
-    Ok((StatusCode::OK, headers, asset.bytes).into_response())
+    let response = (StatusCode::OK, headers, asset.bytes).into_response();
+    Ok(with_storage_tier(response, asset.storage_tier))
+}
+
+fn items_response(outcome: BatchOutcome<()>) -> BatchItemsResponse {
+    let results = outcome
+        .into_iter()
+        .map(|(id, result)| BatchItemResult {
+            id,
+            error: result.err().as_ref().map(batch_item_error),
+        })
+        .collect();
+    BatchItemsResponse { results }
 }
@@ -6,9 +6,32 @@
 //! [`be_auth_core::Claims`] has been inserted into request extensions by the
 //! time a handler runs and pulls them back out via the
 //! [`be_auth_core::AuthUser`] extractor.
+//!
+//! The `/v1/assets/batch/*` routes take up to
+//! [`asset_core::MAX_BATCH_SIZE`] ids and answer with one result per id,
+//! so the timeline can fetch, delete, or regroup dozens of screenshots in
+//! one round trip.
+//!
+//! `/v1/assets/preferences` holds per-user upload settings; today that is
+//! whether EXIF / GPS metadata is stripped from uploaded images (on by
+//! default).
+//!
+//! Owners can share an asset with another user through
+//! `/v1/assets/{asset_id}/grants`, or mint an expiring link through
+//! `/v1/assets/{asset_id}/links`. Link holders download from the public
+//! `/shared/assets/{token}`; grantees use the regular download route and
+//! find what was shared with them under `/v1/assets/shared-with-me`.
+//!
+//! Large files can go through a resumable upload instead of one request:
+//! `POST /v1/assets/uploads` opens a session, chunks go to
+//! `PUT /v1/assets/uploads/{upload_id}/chunks/{offset}` as raw bytes,
+//! `GET /v1/assets/uploads/{upload_id}` says which arrived after a
+//! disconnect, and `POST /v1/assets/uploads/{upload_id}/complete` creates
+//! the asset.
 
 mod error;
 mod handlers;
+mod range;
 mod service;
 
 use std::sync::Arc;
@@ -16,7 +39,7 @@ use std::sync::Arc;
 use axum::{
     Router,
     extract::DefaultBodyLimit,
-    routing::{get, post},
+    routing::{delete, get, post, put},
 };
 use be_asset::AssetService as CoreAssetService;
 use tower_http::trace::TraceLayer;
@@ -36,6 +59,55 @@ pub fn create_router(state: Arc<AppState>) -> Router {
             "/v1/assets/{asset_id}",
             get(handlers::get_asset_bytes_handler),
         )
+        .route(
+            "/v1/assets/preferences",
+            get(handlers::get_preferences_handler).put(handlers::put_preferences_handler),
+        )
+        .route(
+            "/v1/assets/batch/get",
+            post(handlers::batch_get_assets_handler),
+        )
+        .route(
+            "/v1/assets/batch/delete",
+            post(handlers::batch_delete_assets_handler),
+        )
+        .route(
+            "/v1/assets/batch/link",
+            post(handlers::batch_link_assets_handler),
+        )
+        .route(
+            "/v1/assets/{asset_id}/grants",
+            get(handlers::list_grants_handler).post(handlers::create_grant_handler),
+        )
+        .route(
+            "/v1/assets/{asset_id}/grants/{grant_id}",
+            delete(handlers::revoke_grant_handler),
+        )
+        .route(
+            "/v1/assets/{asset_id}/links",
+            post(handlers::create_link_handler),
+        )
+        .route(
+            "/v1/assets/shared-with-me",
+            get(handlers::shared_with_me_handler),
+        )
+        .route("/v1/assets/uploads", post(handlers::create_upload_handler))
+        .route(
+            "/v1/assets/uploads/{upload_id}",
+            get(handlers::get_upload_handler).delete(handlers::abort_upload_handler),
+        )
+        .route(
+            "/v1/assets/uploads/{upload_id}/chunks/{offset}",
+            put(handlers::put_upload_chunk_handler),
+        )
+        .route(
+            "/v1/assets/uploads/{upload_id}/complete",
+            post(handlers::complete_upload_handler),
+        )
+        .route(
+            "/shared/assets/{token}",
+            get(handlers::get_shared_asset_handler),
+        )
         .layer(DefaultBodyLimit::max(MAX_ASSET_REQUEST_SIZE))
         .layer(TraceLayer::new_for_http())
         .with_state(state)
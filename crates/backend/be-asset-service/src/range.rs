@@ -0,0 +1,125 @@
+//! Partial-download requests for `GET /v1/assets/{asset_id}`.
+//!
+//! A client asks for part of an asset either with `?offset=&length=` or
+//! with a standard single `Range: bytes=…` header, which is what a
+//! `<video>` element sends when the user seeks. Range forms we don't
+//! serve (suffix ranges, multiple ranges, other units) are ignored and
+//! the whole asset is returned, as RFC 9110 §14.2 allows.
+
+use axum::http::{HeaderMap, header};
+use serde::Deserialize;
+
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct RangeQuery {
+    pub offset: Option<u64>,
+    pub length: Option<u64>,
+}
+
+/// Offset plus optional length; `None` length reads to the end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RequestedRange {
+    pub offset: u64,
+    pub length: Option<u64>,
+}
+
+/// The range to serve, if any. Query parameters win over the header.
+pub(crate) fn requested_range(query: &RangeQuery, headers: &HeaderMap) -> Option<RequestedRange> {
+    if query.offset.is_some() || query.length.is_some() {
+        return Some(RequestedRange {
+            offset: query.offset.unwrap_or(0),
+            length: query.length,
+        });
+    }
+    headers
+        .get(header::RANGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_range_header)
+}
+
+/// Parse `bytes=first-last` or `bytes=first-`.
+fn parse_range_header(value: &str) -> Option<RequestedRange> {
+    let (unit, spec) = value.trim().split_once('=')?;
+    if !unit.trim().eq_ignore_ascii_case("bytes") || spec.contains(',') {
+        return None;
+    }
+    let (first, last) = spec.trim().split_once('-')?;
+    let offset: u64 = first.trim().parse().ok()?;
+    let last = last.trim();
+    if last.is_empty() {
+        return Some(RequestedRange {
+            offset,
+            length: None,
+        });
+    }
+    let last: u64 = last.parse().ok()?;
+    // An inverted range is invalid and must be ignored.
+    let length = last.checked_sub(offset)?.checked_add(1)?;
+    Some(RequestedRange {
+        offset,
+        length: Some(length),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::RANGE, value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn parses_closed_and_open_ranges() {
+        assert_eq!(
+            parse_range_header("bytes=0-99"),
+            Some(RequestedRange {
+                offset: 0,
+                length: Some(100)
+            })
+        );
+        assert_eq!(
+            parse_range_header("Bytes= 500-"),
+            Some(RequestedRange {
+                offset: 500,
+                length: None
+            })
+        );
+    }
+
+    #[test]
+    fn ignores_unsupported_forms() {
+        assert_eq!(parse_range_header("bytes=-500"), None);
+        assert_eq!(parse_range_header("bytes=0-1,5-9"), None);
+        assert_eq!(parse_range_header("bytes=10-5"), None);
+        assert_eq!(parse_range_header("items=0-5"), None);
+        assert_eq!(parse_range_header("bytes=abc-"), None);
+    }
+
+    #[test]
+    fn query_takes_precedence_over_header() {
+        let query = RangeQuery {
+            offset: Some(10),
+            length: None,
+        };
+        assert_eq!(
+            requested_range(&query, &header("bytes=0-1")),
+            Some(RequestedRange {
+                offset: 10,
+                length: None
+            })
+        );
+        assert_eq!(
+            requested_range(&RangeQuery::default(), &header("bytes=0-1")),
+            Some(RequestedRange {
+                offset: 0,
+                length: Some(2)
+            })
+        );
+        assert_eq!(
+            requested_range(&RangeQuery::default(), &HeaderMap::new()),
+            None
+        );
+    }
+}
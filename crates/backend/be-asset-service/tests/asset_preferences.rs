@@ -0,0 +1,206 @@
+//! End-to-end HTTP round-trips for `/v1/assets/preferences` and the image
+//! metadata stripping it controls.
+//!
+//! Same setup as `get_asset_bytes.rs`: a fresh `#[sqlx::test]` database,
+//! filesystem storage in a tempdir, and `Claims` injected by a layer in
+//! place of the production authz middleware. Requires `DATABASE_URL`.
+
+use std::sync::Arc;
+
+use asset_core::{Asset, AssetPreferences, CreateAssetRequest, METADATA_STRIPPED_KEY};
+use axum::Router;
+use axum::extract::Request;
+use axum::middleware::Next;
+use base64::{Engine as _, engine::general_purpose};
+use be_asset::AssetService;
+use be_asset_service::AppState;
+use be_auth_core::{Claims, Role};
+use be_remote_db::DatabaseManager;
+use be_storage::{StorageConfig, StorageService};
+use reqwest::StatusCode;
+use sqlx::PgPool;
+use tempfile::TempDir;
+use uuid::Uuid;
+
+const EXIF_PAYLOAD: &[u8] = b"Exif\0\0GPS 51.5007N 0.1246W";
+
+/// A JPEG skeleton with an EXIF segment: SOI, APP1, SOS header, scan
+/// bytes, EOI. Enough for the segment walker; never decoded.
+fn jpeg_with_exif() -> Vec<u8> {
+    let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE1];
+    jpeg.extend_from_slice(&(EXIF_PAYLOAD.len() as u16 + 2).to_be_bytes());
+    jpeg.extend_from_slice(EXIF_PAYLOAD);
+    jpeg.extend_from_slice(&jpeg_without_exif()[2..]);
+    jpeg
+}
+
+fn jpeg_without_exif() -> Vec<u8> {
+    vec![
+        0xFF, 0xD8, 0xFF, 0xDA, 0x00, 0x08, 0x01, 0x01, 0x00, 0x00, 0x3F, 0x00, 0x12, 0x34, 0xFF,
+        0xD9,
+    ]
+}
+
+async fn seed_user(pool: &PgPool) -> Uuid {
+    let id = Uuid::now_v7();
+    sqlx::query("INSERT INTO users (id, email) VALUES ($1, $2)")
+        .bind(id)
+        .bind(format!("user-{id}@test.local"))
+        .execute(pool)
+        .await
+        .expect("seed user");
+    id
+}
+
+fn claims_for(user_id: Uuid) -> Claims {
+    Claims {
+        sub: user_id.to_string(),
+        email: format!("user-{user_id}@test.local"),
+        display_name: None,
+        iat: 0,
+        exp: i64::MAX,
+        token_type: "access".into(),
+        role: Role::Free,
+        aud: "eurora".into(),
+        email_verified: true,
+        jti: Uuid::now_v7().to_string(),
+        analytics: Default::default(),
+        token_version: 0,
+        act: None,
+    }
+}
+
+struct AppHarness {
+    base_url: String,
+    _storage_root: TempDir,
+}
+
+impl AppHarness {
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    async fn preferences(&self) -> AssetPreferences {
+        let response = reqwest::Client::new()
+            .get(self.url("/v1/assets/preferences"))
+            .send()
+            .await
+            .expect("GET preferences");
+        assert_eq!(response.status(), StatusCode::OK);
+        response.json().await.expect("preferences body")
+    }
+
+    async fn set_preferences(&self, preferences: AssetPreferences) {
+        let response = reqwest::Client::new()
+            .put(self.url("/v1/assets/preferences"))
+            .json(&preferences)
+            .send()
+            .await
+            .expect("PUT preferences");
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    /// Upload `content` as a JPEG and return the created asset and the
+    /// bytes the server stored.
+    async fn upload_jpeg(&self, content: &[u8]) -> (Asset, Vec<u8>) {
+        let response = reqwest::Client::new()
+            .post(self.url("/v1/assets"))
+            .json(&CreateAssetRequest {
+                name: "photo.jpg".into(),
+                content: general_purpose::STANDARD.encode(content),
+                mime_type: "image/jpeg".into(),
+                metadata: None,
+                device_id: None,
+            })
+            .send()
+            .await
+            .expect("POST asset");
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let asset: Asset = response.json().await.expect("asset body");
+
+        let stored = reqwest::Client::new()
+            .get(self.url(&format!("/v1/assets/{}", asset.id)))
+            .send()
+            .await
+            .expect("GET asset")
+            .bytes()
+            .await
+            .expect("asset bytes")
+            .to_vec();
+        (asset, stored)
+    }
+}
+
+async fn spawn_app(pool: PgPool) -> AppHarness {
+    let user = seed_user(&pool).await;
+    let storage_root = tempfile::tempdir().expect("storage tempdir");
+
+    let db = Arc::new(DatabaseManager::from_pool(pool));
+    let storage = Arc::new(
+        StorageService::builder()
+            .config(StorageConfig::FS {
+                root: storage_root.path().to_string_lossy().into_owned(),
+            })
+            .build()
+            .expect("build storage"),
+    );
+    let service = Arc::new(AssetService::new(db, storage));
+    let state = Arc::new(AppState::new(service));
+
+    let app: Router = be_asset_service::create_router(state).layer(axum::middleware::from_fn(
+        move |mut req: Request, next: Next| async move {
+            req.extensions_mut().insert(claims_for(user));
+            next.run(req).await
+        },
+    ));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind ephemeral");
+    let addr = listener.local_addr().expect("local_addr");
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.expect("serve");
+    });
+
+    AppHarness {
+        base_url: format!("http://{addr}"),
+        _storage_root: storage_root,
+    }
+}
+
+#[sqlx::test(migrations = "../be-remote-db/src/migrations")]
+async fn stripping_defaults_on_and_can_be_turned_off(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    assert!(app.preferences().await.strip_image_metadata);
+
+    app.set_preferences(AssetPreferences {
+        strip_image_metadata: false,
+    })
+    .await;
+    assert!(!app.preferences().await.strip_image_metadata);
+}
+
+#[sqlx::test(migrations = "../be-remote-db/src/migrations")]
+async fn exif_is_stripped_from_uploaded_jpeg(pool: PgPool) {
+    let app = spawn_app(pool).await;
+
+    let (asset, stored) = app.upload_jpeg(&jpeg_with_exif()).await;
+
+    assert_eq!(stored, jpeg_without_exif());
+    assert_eq!(asset.size_bytes, Some(stored.len() as i64));
+    assert_eq!(asset.metadata[METADATA_STRIPPED_KEY], true);
+}
+
+#[sqlx::test(migrations = "../be-remote-db/src/migrations")]
+async fn exif_is_kept_when_stripping_is_off(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    app.set_preferences(AssetPreferences {
+        strip_image_metadata: false,
+    })
+    .await;
+
+    let (asset, stored) = app.upload_jpeg(&jpeg_with_exif()).await;
+
+    assert_eq!(stored, jpeg_with_exif());
+    assert_eq!(asset.metadata[METADATA_STRIPPED_KEY], false);
+}
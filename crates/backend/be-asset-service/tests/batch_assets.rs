@@ -0,0 +1,254 @@
+//! End-to-end HTTP round-trips for the `/v1/assets/batch/*` routes.
+//!
+//! Same setup as `get_asset_bytes.rs`: a fresh `#[sqlx::test]` database,
+//! filesystem storage in a tempdir, and `Claims` injected by a layer in
+//! place of the production authz middleware. Requires `DATABASE_URL`.
+
+use std::sync::Arc;
+
+use asset_core::{
+    BatchAssetsRequest, BatchGetAssetsResponse, BatchItemsResponse, BatchLinkAssetsRequest,
+    MAX_BATCH_SIZE,
+};
+use axum::Router;
+use axum::extract::Request;
+use axum::middleware::Next;
+use be_asset::{AssetService, CreateAssetInput};
+use be_asset_service::AppState;
+use be_auth_core::{Claims, Role};
+use be_remote_db::DatabaseManager;
+use be_storage::{StorageConfig, StorageService};
+use reqwest::StatusCode;
+use sqlx::PgPool;
+use tempfile::TempDir;
+use uuid::Uuid;
+
+/// Minimal valid PNG (1×1) — passes the upload path's magic-byte sniff.
+const PNG_BYTES: &[u8] = &[
+    0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00, 0x00, 0x0D, 0x49, 0x48, 0x44, 0x52,
+    0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0x1F, 0x15, 0xC4,
+    0x89, 0x00, 0x00, 0x00, 0x0D, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9C, 0x63, 0xFA, 0xCF, 0x00, 0x00,
+    0x00, 0x02, 0x00, 0x01, 0xE5, 0x27, 0xDE, 0xFC, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4E, 0x44,
+    0xAE, 0x42, 0x60, 0x82,
+];
+
+async fn seed_user(pool: &PgPool) -> Uuid {
+    let id = Uuid::now_v7();
+    sqlx::query("INSERT INTO users (id, email) VALUES ($1, $2)")
+        .bind(id)
+        .bind(format!("user-{id}@test.local"))
+        .execute(pool)
+        .await
+        .expect("seed user");
+    id
+}
+
+async fn seed_activity(pool: &PgPool, user_id: Uuid) -> Uuid {
+    let id = Uuid::now_v7();
+    sqlx::query(
+        "INSERT INTO activities (id, user_id, identity_key, display_name) VALUES ($1, $2, $3, $3)",
+    )
+    .bind(id)
+    .bind(user_id)
+    .bind(format!("app-{id}"))
+    .execute(pool)
+    .await
+    .expect("seed activity");
+    id
+}
+
+fn claims_for(user_id: Uuid) -> Claims {
+    Claims {
+        sub: user_id.to_string(),
+        email: format!("user-{user_id}@test.local"),
+        display_name: None,
+        iat: 0,
+        exp: i64::MAX,
+        token_type: "access".into(),
+        role: Role::Free,
+        aud: "eurora".into(),
+        email_verified: true,
+        jti: Uuid::now_v7().to_string(),
+        analytics: Default::default(),
+        token_version: 0,
+        act: None,
+    }
+}
+
+struct AppHarness {
+    base_url: String,
+    service: Arc<AssetService>,
+    pool: PgPool,
+    user: Uuid,
+    other: Uuid,
+    _storage_root: TempDir,
+}
+
+impl AppHarness {
+    async fn post<B: serde::Serialize>(&self, path: &str, body: &B) -> reqwest::Response {
+        reqwest::Client::new()
+            .post(format!("{}{}", self.base_url, path))
+            .json(body)
+            .send()
+            .await
+            .expect("POST")
+    }
+
+    async fn upload(&self, owner: Uuid) -> Uuid {
+        self.service
+            .create_asset(
+                CreateAssetInput {
+                    name: "shot.png".into(),
+                    content: PNG_BYTES.to_vec(),
+                    mime_type: "image/png".into(),
+                    metadata: None,
+                    device_id: None,
+                },
+                owner,
+            )
+            .await
+            .expect("create_asset")
+            .id
+    }
+}
+
+async fn spawn_app(pool: PgPool) -> AppHarness {
+    let user = seed_user(&pool).await;
+    let other = seed_user(&pool).await;
+    let storage_root = tempfile::tempdir().expect("storage tempdir");
+
+    let db = Arc::new(DatabaseManager::from_pool(pool.clone()));
+    let storage = Arc::new(
+        StorageService::builder()
+            .config(StorageConfig::FS {
+                root: storage_root.path().to_string_lossy().into_owned(),
+            })
+            .build()
+            .expect("build storage"),
+    );
+    let service = Arc::new(AssetService::new(db, storage));
+    let state = Arc::new(AppState::new(Arc::clone(&service)));
+
+    let app: Router = be_asset_service::create_router(state).layer(axum::middleware::from_fn(
+        move |mut req: Request, next: Next| async move {
+            req.extensions_mut().insert(claims_for(user));
+            next.run(req).await
+        },
+    ));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind ephemeral");
+    let addr = listener.local_addr().expect("local_addr");
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.expect("serve");
+    });
+
+    AppHarness {
+        base_url: format!("http://{addr}"),
+        service,
+        pool,
+        user,
+        other,
+        _storage_root: storage_root,
+    }
+}
+
+#[sqlx::test(migrations = "../be-remote-db/src/migrations")]
+async fn batch_get_reports_each_id_in_request_order(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let mine = app.upload(app.user).await;
+    let theirs = app.upload(app.other).await;
+
+    let response = app
+        .post(
+            "/v1/assets/batch/get",
+            &BatchAssetsRequest {
+                ids: vec![theirs, mine, mine],
+            },
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: BatchGetAssetsResponse = response.json().await.unwrap();
+
+    assert_eq!(body.results.len(), 2);
+    assert_eq!(body.results[0].id, theirs);
+    assert!(body.results[0].asset.is_none());
+    assert_eq!(body.results[0].error.as_ref().unwrap().error, "not_found");
+    assert_eq!(body.results[1].id, mine);
+    assert_eq!(body.results[1].asset.as_ref().unwrap().id, mine);
+    assert!(body.results[1].error.is_none());
+}
+
+#[sqlx::test(migrations = "../be-remote-db/src/migrations")]
+async fn batch_delete_removes_owned_assets_only(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let a = app.upload(app.user).await;
+    let b = app.upload(app.user).await;
+    let theirs = app.upload(app.other).await;
+
+    let response = app
+        .post(
+            "/v1/assets/batch/delete",
+            &BatchAssetsRequest {
+                ids: vec![a, theirs, b],
+            },
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: BatchItemsResponse = response.json().await.unwrap();
+
+    let ids: Vec<Uuid> = body.results.iter().map(|r| r.id).collect();
+    assert_eq!(ids, [a, theirs, b]);
+    assert!(body.results[0].error.is_none());
+    assert_eq!(body.results[1].error.as_ref().unwrap().error, "not_found");
+    assert!(body.results[2].error.is_none());
+
+    assert!(app.service.get_asset_bytes(a, app.user).await.is_err());
+    assert!(app.service.get_asset_bytes(theirs, app.other).await.is_ok());
+}
+
+#[sqlx::test(migrations = "../be-remote-db/src/migrations")]
+async fn batch_link_attaches_assets_to_the_activity(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let activity = seed_activity(&app.pool, app.user).await;
+    let mine = app.upload(app.user).await;
+    let theirs = app.upload(app.other).await;
+
+    let response = app
+        .post(
+            "/v1/assets/batch/link",
+            &BatchLinkAssetsRequest {
+                activity_id: activity,
+                asset_ids: vec![mine, theirs],
+            },
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: BatchItemsResponse = response.json().await.unwrap();
+    assert!(body.results[0].error.is_none());
+    assert_eq!(body.results[1].error.as_ref().unwrap().error, "not_found");
+
+    let foreign_activity = seed_activity(&app.pool, app.other).await;
+    let response = app
+        .post(
+            "/v1/assets/batch/link",
+            &BatchLinkAssetsRequest {
+                activity_id: foreign_activity,
+                asset_ids: vec![mine],
+            },
+        )
+        .await;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test(migrations = "../be-remote-db/src/migrations")]
+async fn oversized_batch_is_rejected(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let ids: Vec<Uuid> = (0..=MAX_BATCH_SIZE).map(|_| Uuid::now_v7()).collect();
+
+    let response = app
+        .post("/v1/assets/batch/delete", &BatchAssetsRequest { ids })
+        .await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
@@ -1,4 +1,5 @@
-//! End-to-end HTTP round-trips for `GET /v1/assets/{id}`.
+//! End-to-end HTTP round-trips for `GET /v1/assets/{id}`, including
+//! partial downloads.
 //!
 //! Uses `#[sqlx::test]` to provision a fresh, isolated Postgres database
 //! per test (migrations applied automatically) and mounts the real asset
@@ -56,6 +57,9 @@ fn claims_for(user_id: Uuid) -> Claims {
         aud: "eurora".into(),
         email_verified: true,
         jti: Uuid::now_v7().to_string(),
+        analytics: Default::default(),
+        token_version: 0,
+        act: None,
     }
 }
 
@@ -87,7 +91,7 @@ async fn spawn_app(pool: PgPool) -> AppHarness {
     let other = seed_user(&pool).await;
     let storage_root = tempfile::tempdir().expect("storage tempdir");
 
-    let db = Arc::new(DatabaseManager { pool: pool.clone() });
+    let db = Arc::new(DatabaseManager::from_pool(pool.clone()));
     let storage = Arc::new(
         StorageService::builder()
             .config(StorageConfig::FS {
@@ -138,6 +142,7 @@ fn png_input() -> CreateAssetInput {
         content: PNG_BYTES.to_vec(),
         mime_type: "image/png".into(),
         metadata: None,
+        device_id: None,
     }
 }
 
@@ -231,3 +236,110 @@ async fn unauthenticated_returns_401(pool: PgPool) {
 
     assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
 }
+
+#[sqlx::test(migrations = "../be-remote-db/src/migrations")]
+async fn range_header_returns_partial_content(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let asset = app
+        .service
+        .create_asset(png_input(), app.primary)
+        .await
+        .expect("create_asset");
+
+    let response = reqwest::Client::new()
+        .get(app.url(&format!("/v1/assets/{}", asset.id)))
+        .header(reqwest::header::RANGE, "bytes=8-15")
+        .send()
+        .await
+        .expect("GET asset range");
+
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(
+        response
+            .headers()
+            .get(reqwest::header::CONTENT_RANGE)
+            .expect("content-range")
+            .to_str()
+            .unwrap(),
+        format!("bytes 8-15/{}", PNG_BYTES.len()),
+    );
+    let body = response.bytes().await.expect("body bytes");
+    assert_eq!(body.as_ref(), &PNG_BYTES[8..16]);
+}
+
+#[sqlx::test(migrations = "../be-remote-db/src/migrations")]
+async fn offset_query_reads_to_the_end(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let asset = app
+        .service
+        .create_asset(png_input(), app.primary)
+        .await
+        .expect("create_asset");
+
+    let response = reqwest::Client::new()
+        .get(app.url(&format!("/v1/assets/{}?offset=60", asset.id)))
+        .send()
+        .await
+        .expect("GET asset range");
+
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    let body = response.bytes().await.expect("body bytes");
+    assert_eq!(body.as_ref(), &PNG_BYTES[60..]);
+}
+
+#[sqlx::test(migrations = "../be-remote-db/src/migrations")]
+async fn range_past_the_end_returns_416(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let asset = app
+        .service
+        .create_asset(png_input(), app.primary)
+        .await
+        .expect("create_asset");
+
+    let response = reqwest::Client::new()
+        .get(app.url(&format!("/v1/assets/{}", asset.id)))
+        .header(
+            reqwest::header::RANGE,
+            format!("bytes={}-", PNG_BYTES.len()),
+        )
+        .send()
+        .await
+        .expect("GET asset range");
+
+    assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+    assert_eq!(
+        response
+            .headers()
+            .get(reqwest::header::CONTENT_RANGE)
+            .expect("content-range")
+            .to_str()
+            .unwrap(),
+        format!("bytes */{}", PNG_BYTES.len()),
+    );
+}
+
+#[sqlx::test(migrations = "../be-remote-db/src/migrations")]
+async fn quarantined_asset_returns_403(pool: PgPool) {
+    let app = spawn_app(pool.clone()).await;
+    let asset = app
+        .service
+        .create_asset(png_input(), app.primary)
+        .await
+        .expect("create_asset");
+    sqlx::query(
+        "UPDATE assets SET scan_status = 'quarantined', scan_signature = 'Eicar-Test-Signature' \
+         WHERE id = $1",
+    )
+    .bind(asset.id)
+    .execute(&pool)
+    .await
+    .expect("quarantine asset");
+
+    let response = reqwest::Client::new()
+        .get(app.url(&format!("/v1/assets/{}", asset.id)))
+        .send()
+        .await
+        .expect("GET asset");
+
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,174 @@
+//! End-to-end HTTP round-trips for resumable uploads under
+//! `/v1/assets/uploads`.
+//!
+//! Same setup as `batch_assets.rs`: a fresh `#[sqlx::test]` database,
+//! filesystem storage in a tempdir, and `Claims` injected by a layer in
+//! place of the production authz middleware. Requires `DATABASE_URL`.
+
+use std::sync::Arc;
+
+use asset_core::{Asset, CreateUploadRequest, UPLOAD_CHUNK_SIZE, UploadSession};
+use axum::Router;
+use axum::extract::Request;
+use axum::middleware::Next;
+use be_asset::AssetService;
+use be_asset_service::AppState;
+use be_auth_core::{Claims, Role};
+use be_remote_db::DatabaseManager;
+use be_storage::{StorageConfig, StorageService};
+use reqwest::StatusCode;
+use sqlx::PgPool;
+use tempfile::TempDir;
+use uuid::Uuid;
+
+async fn seed_user(pool: &PgPool) -> Uuid {
+    let id = Uuid::now_v7();
+    sqlx::query("INSERT INTO users (id, email) VALUES ($1, $2)")
+        .bind(id)
+        .bind(format!("user-{id}@test.local"))
+        .execute(pool)
+        .await
+        .expect("seed user");
+    id
+}
+
+fn claims_for(user_id: Uuid) -> Claims {
+    Claims {
+        sub: user_id.to_string(),
+        email: format!("user-{user_id}@test.local"),
+        display_name: None,
+        iat: 0,
+        exp: i64::MAX,
+        token_type: "access".into(),
+        role: Role::Free,
+        aud: "eurora".into(),
+        email_verified: true,
+        jti: Uuid::now_v7().to_string(),
+        analytics: Default::default(),
+        token_version: 0,
+        act: None,
+    }
+}
+
+async fn spawn_app(pool: PgPool) -> (String, TempDir) {
+    let user = seed_user(&pool).await;
+    let storage_root = tempfile::tempdir().expect("storage tempdir");
+
+    let db = Arc::new(DatabaseManager::from_pool(pool));
+    let storage = Arc::new(
+        StorageService::builder()
+            .config(StorageConfig::FS {
+                root: storage_root.path().to_string_lossy().into_owned(),
+            })
+            .build()
+            .expect("build storage"),
+    );
+    let state = Arc::new(AppState::new(Arc::new(AssetService::new(db, storage))));
+
+    let app: Router = be_asset_service::create_router(state).layer(axum::middleware::from_fn(
+        move |mut req: Request, next: Next| async move {
+            req.extensions_mut().insert(claims_for(user));
+            next.run(req).await
+        },
+    ));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind ephemeral");
+    let addr = listener.local_addr().expect("local_addr");
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.expect("serve");
+    });
+
+    (format!("http://{addr}"), storage_root)
+}
+
+#[sqlx::test(migrations = "../be-remote-db/src/migrations")]
+async fn upload_resumes_out_of_order_and_completes_into_an_asset(pool: PgPool) {
+    let (base, _storage_root) = spawn_app(pool).await;
+    let client = reqwest::Client::new();
+    let content: Vec<u8> = (0..UPLOAD_CHUNK_SIZE + 10).map(|i| i as u8).collect();
+    let (head, tail) = content.split_at(UPLOAD_CHUNK_SIZE as usize);
+
+    let response = client
+        .post(format!("{base}/v1/assets/uploads"))
+        .json(&CreateUploadRequest {
+            name: "recording.bin".into(),
+            mime_type: "application/octet-stream".into(),
+            size: content.len() as u64,
+            metadata: None,
+            device_id: None,
+        })
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let session: UploadSession = response.json().await.unwrap();
+    assert_eq!(session.chunk_size, UPLOAD_CHUNK_SIZE);
+    assert!(session.received_offsets.is_empty());
+    let upload = format!("{base}/v1/assets/uploads/{}", session.id);
+
+    // The tail arrives first; the head is lost to a disconnect.
+    let response = client
+        .put(format!("{upload}/chunks/{UPLOAD_CHUNK_SIZE}"))
+        .body(tail.to_vec())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = client
+        .post(format!("{upload}/complete"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let state: UploadSession = client
+        .get(&upload)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(state.received_offsets, [UPLOAD_CHUNK_SIZE]);
+
+    let response = client
+        .put(format!("{upload}/chunks/7"))
+        .body(head.to_vec())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = client
+        .put(format!("{upload}/chunks/0"))
+        .body(head.to_vec())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = client
+        .post(format!("{upload}/complete"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    let asset: Asset = response.json().await.unwrap();
+    assert_eq!(asset.size_bytes, Some(content.len() as i64));
+
+    let bytes = client
+        .get(format!("{base}/v1/assets/{}", asset.id))
+        .send()
+        .await
+        .unwrap()
+        .bytes()
+        .await
+        .unwrap();
+    assert_eq!(bytes.as_ref(), content.as_slice());
+
+    let response = client.get(&upload).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
@@ -26,9 +26,63 @@ pub enum AssetError {
     #[error("failed to read asset from database")]
     DatabaseRead(#[source] be_remote_db::DbError),
 
+    #[error("failed to delete asset from storage: {0}")]
+    StorageDelete(#[source] be_storage::StorageError),
+
+    #[error("failed to write asset changes to database")]
+    DatabaseWrite(#[source] be_remote_db::DbError),
+
     #[error("asset not found")]
     NotFound,
 
+    #[error("asset was quarantined by the malware scanner")]
+    Quarantined,
+
+    #[error("asset bytes don't match their checksum")]
+    Corrupted,
+
+    #[error("asset is under legal hold")]
+    OnLegalHold,
+
+    #[error("requested range is outside the asset ({size} bytes)")]
+    RangeNotSatisfiable { size: u64 },
+
+    #[error("activity not found")]
+    ActivityNotFound,
+
+    #[error("an asset can't be shared with its owner")]
+    SelfGrant,
+
+    #[error("the user to share with does not exist")]
+    GranteeNotFound,
+
+    #[error("asset grant not found")]
+    GrantNotFound,
+
+    #[error("expiry must be in the future")]
+    ExpiryInPast,
+
+    #[error("link lifetime must be between 1 and {max_secs} seconds")]
+    InvalidLinkLifetime { max_secs: u32 },
+
+    #[error("upload size must be between 1 and {max} bytes")]
+    InvalidUploadSize { max: u64 },
+
+    #[error("at most {max} uploads may be open at once")]
+    TooManyUploads { max: u32 },
+
+    #[error("upload session not found")]
+    UploadNotFound,
+
+    #[error("invalid chunk: {0}")]
+    InvalidChunk(String),
+
+    #[error("upload is missing {missing} chunks")]
+    UploadIncomplete { missing: usize },
+
+    #[error("batch of {got} ids exceeds the limit of {max}")]
+    BatchTooLarge { got: usize, max: usize },
+
     #[error("failed to configure storage from environment: {0}")]
     StorageConfig(#[source] be_storage::StorageError),
 }
@@ -0,0 +1,218 @@
+//! Sharing assets with other users and through links.
+//!
+//! An owner can let a named user read one of their assets, for good or
+//! until a given time, or mint a link that lets anyone holding it download
+//! the asset until it expires. Grantees read through the same
+//! [`AssetService::get_asset_bytes`] and [`AssetService::get_asset_range`]
+//! paths as owners, since the ownership check admits them; link holders go
+//! through [`AssetService::get_asset_bytes_by_link`]. Only the SHA-256 of a
+//! link token is stored, so the token is shown once, when the link is
+//! created.
+
+use asset_core::{Asset, AssetGrant, AssetLink, MAX_LINK_LIFETIME_SECS};
+use be_remote_db::DbError;
+use chrono::{DateTime, Duration, Utc};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{AssetBytes, AssetError, AssetResult, AssetService, refuse_quarantined};
+
+/// Bytes of randomness in a link token.
+const LINK_TOKEN_BYTES: usize = 32;
+
+impl AssetService {
+    /// Let `grantee_id` read the owner's asset until `expires_at`, or for
+    /// good with `None`. Sharing again with the same user replaces the
+    /// expiry.
+    pub async fn share_asset(
+        &self,
+        asset_id: Uuid,
+        owner_id: Uuid,
+        grantee_id: Uuid,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> AssetResult<AssetGrant> {
+        if grantee_id == owner_id {
+            return Err(AssetError::SelfGrant);
+        }
+        if expires_at.is_some_and(|at| at <= Utc::now()) {
+            return Err(AssetError::ExpiryInPast);
+        }
+
+        let grant = self
+            .db
+            .share_asset_with_user()
+            .asset_id(asset_id)
+            .owner_id(owner_id)
+            .grantee_id(grantee_id)
+            .maybe_expires_at(expires_at)
+            .call()
+            .await
+            .map_err(|e| match e {
+                e if e.is_not_found() => AssetError::NotFound,
+                DbError::ForeignKeyViolation { .. } => AssetError::GranteeNotFound,
+                e => AssetError::DatabaseWrite(e),
+            })?;
+
+        tracing::info!(%asset_id, grant_id = %grant.id, "Shared asset with a user");
+        Ok(grant_to_dto(grant))
+    }
+
+    /// Mint a link to the owner's asset that works for `expires_in_secs`.
+    pub async fn create_asset_link(
+        &self,
+        asset_id: Uuid,
+        owner_id: Uuid,
+        expires_in_secs: u32,
+    ) -> AssetResult<AssetLink> {
+        if expires_in_secs == 0 || expires_in_secs > MAX_LINK_LIFETIME_SECS {
+            return Err(AssetError::InvalidLinkLifetime {
+                max_secs: MAX_LINK_LIFETIME_SECS,
+            });
+        }
+
+        let token = new_link_token();
+        let grant = self
+            .db
+            .create_asset_link()
+            .asset_id(asset_id)
+            .owner_id(owner_id)
+            .token_hash(&hash_link_token(&token))
+            .expires_at(Utc::now() + Duration::seconds(i64::from(expires_in_secs)))
+            .call()
+            .await
+            .map_err(|e| {
+                if e.is_not_found() {
+                    AssetError::NotFound
+                } else {
+                    AssetError::DatabaseWrite(e)
+                }
+            })?;
+
+        tracing::info!(%asset_id, grant_id = %grant.id, "Created asset link");
+        Ok(AssetLink {
+            grant: grant_to_dto(grant),
+            path: format!("/shared/assets/{token}"),
+            token,
+        })
+    }
+
+    /// Every grant on the owner's asset, expired ones included, newest
+    /// first.
+    pub async fn list_asset_grants(
+        &self,
+        asset_id: Uuid,
+        owner_id: Uuid,
+    ) -> AssetResult<Vec<AssetGrant>> {
+        let grants = self
+            .db
+            .list_asset_grants()
+            .asset_id(asset_id)
+            .owner_id(owner_id)
+            .call()
+            .await
+            .map_err(|e| {
+                if e.is_not_found() {
+                    AssetError::NotFound
+                } else {
+                    AssetError::DatabaseRead(e)
+                }
+            })?;
+
+        Ok(grants.into_iter().map(grant_to_dto).collect())
+    }
+
+    /// Revoke one grant, user or link, on the owner's asset. Takes effect
+    /// on the next read.
+    pub async fn revoke_asset_grant(
+        &self,
+        grant_id: Uuid,
+        asset_id: Uuid,
+        owner_id: Uuid,
+    ) -> AssetResult<()> {
+        self.db
+            .delete_asset_grant()
+            .grant_id(grant_id)
+            .asset_id(asset_id)
+            .owner_id(owner_id)
+            .call()
+            .await
+            .map_err(|e| {
+                if e.is_not_found() {
+                    AssetError::GrantNotFound
+                } else {
+                    AssetError::DatabaseWrite(e)
+                }
+            })?;
+
+        tracing::info!(%asset_id, %grant_id, "Revoked asset grant");
+        Ok(())
+    }
+
+    /// Assets other users have shared with `user_id`, most recently shared
+    /// first. Expired grants are left out.
+    pub async fn list_shared_with_me(&self, user_id: Uuid) -> AssetResult<Vec<Asset>> {
+        let assets = self
+            .db
+            .list_assets_shared_with_user()
+            .user_id(user_id)
+            .call()
+            .await
+            .map_err(AssetError::DatabaseRead)?;
+
+        Ok(assets.into_iter().map(Self::db_asset_to_dto).collect())
+    }
+
+    /// All of the bytes of the asset a link `token` opens. Unknown, revoked
+    /// and expired tokens all fail with [`AssetError::NotFound`].
+    pub async fn get_asset_bytes_by_link(&self, token: &str) -> AssetResult<AssetBytes> {
+        let asset = self
+            .db
+            .get_asset_by_link(&hash_link_token(token))
+            .await
+            .map_err(|e| {
+                if e.is_not_found() {
+                    AssetError::NotFound
+                } else {
+                    AssetError::DatabaseRead(e)
+                }
+            })?;
+
+        self.read_bytes(refuse_quarantined(asset)?).await
+    }
+}
+
+fn grant_to_dto(grant: be_remote_db::AssetGrant) -> AssetGrant {
+    AssetGrant {
+        id: grant.id,
+        asset_id: grant.asset_id,
+        grantee_id: grant.grantee_id,
+        expires_at: grant.expires_at,
+        created_at: grant.created_at,
+    }
+}
+
+fn new_link_token() -> String {
+    use rand::Rng;
+
+    let mut bytes = [0u8; LINK_TOKEN_BYTES];
+    rand::rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+fn hash_link_token(token: &str) -> Vec<u8> {
+    Sha256::digest(token.as_bytes()).to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn link_tokens_are_random_and_stored_hashed() {
+        let token = new_link_token();
+        assert_eq!(token.len(), LINK_TOKEN_BYTES * 2);
+        assert_ne!(token, new_link_token());
+        assert_eq!(hash_link_token(&token).len(), 32);
+        assert_eq!(hash_link_token(&token), hash_link_token(&token));
+    }
+}
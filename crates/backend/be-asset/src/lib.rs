@@ -1,12 +1,18 @@
 mod error;
+mod grants;
+mod sanitize;
+mod uploads;
 
 pub use error::{AssetError, AssetResult};
 
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
-use asset_core::Asset;
-use be_remote_db::DatabaseManager;
-use be_storage::StorageService;
+use asset_core::{
+    Asset, AssetPreferences, MAX_BATCH_SIZE, METADATA_STRIPPED_KEY, ScanStatus, StorageTier,
+};
+use be_remote_db::{AssetScanStatus, AssetStorageTier, DatabaseManager};
+use be_storage::{StorageError, StorageService};
 use uuid::Uuid;
 
 /// Raw bytes for an asset paired with the MIME type recorded at upload.
@@ -18,13 +24,34 @@ use uuid::Uuid;
 pub struct AssetBytes {
     pub bytes: Vec<u8>,
     pub mime_type: String,
+    /// The tier the bytes were read from. `Cold` means this read was slow
+    /// and moved the asset back to hot.
+    pub storage_tier: StorageTier,
 }
 
+/// Part of an asset's bytes, returned by [`AssetService::get_asset_range`].
+#[derive(Debug, Clone)]
+pub struct AssetRange {
+    pub bytes: Vec<u8>,
+    pub mime_type: String,
+    /// Offset of `bytes[0]` within the asset.
+    pub offset: u64,
+    /// Size of the whole asset, for `Content-Range`.
+    pub total_size: u64,
+    /// As in [`AssetBytes::storage_tier`].
+    pub storage_tier: StorageTier,
+}
+
+/// `scan_signature` recorded on assets quarantined for failing their
+/// checksum check rather than by the malware scanner.
+pub const CORRUPTION_SIGNATURE: &str = "integrity:sha256-mismatch";
+
 const ALLOWED_MIME_TYPES: &[&str] = &[
     "image/png",
     "image/jpeg",
     "image/gif",
     "image/webp",
+    "image/avif",
     "image/svg+xml",
     "application/pdf",
     "text/plain",
@@ -41,6 +68,12 @@ fn validate_content_matches_mime(content: &[u8], declared_mime: &str) -> bool {
         "image/webp" => {
             content.len() >= 12 && &content[..4] == b"RIFF" && &content[8..12] == b"WEBP"
         }
+        // An ISO-BMFF `ftyp` box with an AVIF still or sequence brand.
+        "image/avif" => {
+            content.len() >= 12
+                && &content[4..8] == b"ftyp"
+                && matches!(&content[8..12], b"avif" | b"avis")
+        }
         "image/svg+xml" => {
             let bytes = content.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(content);
             std::str::from_utf8(bytes)
@@ -78,6 +111,53 @@ fn validate_content_matches_mime(content: &[u8], declared_mime: &str) -> bool {
     }
 }
 
+/// The lowercased type of `mime_type` without parameters, if uploads of
+/// it are accepted.
+fn supported_mime_base(mime_type: &str) -> AssetResult<String> {
+    if mime_type.is_empty() {
+        return Err(AssetError::MissingMimeType);
+    }
+
+    let mime_base = mime_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase();
+
+    if !ALLOWED_MIME_TYPES.contains(&mime_base.as_str()) {
+        return Err(AssetError::UnsupportedMimeType(mime_type.to_owned()));
+    }
+    Ok(mime_base)
+}
+
+/// `asset`, unless the malware scanner quarantined it.
+fn refuse_quarantined(asset: be_remote_db::Asset) -> AssetResult<be_remote_db::Asset> {
+    if asset.scan_status == AssetScanStatus::Quarantined {
+        tracing::warn!(asset_id = %asset.id, "refused download of quarantined asset");
+        return Err(AssetError::Quarantined);
+    }
+    Ok(asset)
+}
+
+/// Per-id outcome of a batch operation: one entry per distinct requested
+/// id, in request order.
+pub type BatchOutcome<T> = Vec<(Uuid, AssetResult<T>)>;
+
+/// Drop repeated ids (keeping first-seen order) and enforce
+/// [`MAX_BATCH_SIZE`] on what's left.
+fn distinct_batch(ids: &[Uuid]) -> AssetResult<Vec<Uuid>> {
+    let mut seen = HashSet::with_capacity(ids.len());
+    let distinct: Vec<Uuid> = ids.iter().copied().filter(|id| seen.insert(*id)).collect();
+    if distinct.len() > MAX_BATCH_SIZE {
+        return Err(AssetError::BatchTooLarge {
+            got: distinct.len(),
+            max: MAX_BATCH_SIZE,
+        });
+    }
+    Ok(distinct)
+}
+
 /// Domain input for [`AssetService::create_asset`]. Speaks in raw bytes so the
 /// transport layer (HTTP, gRPC, etc.) is free to choose its own encoding.
 #[derive(Debug, Clone)]
@@ -86,18 +166,59 @@ pub struct CreateAssetInput {
     pub content: Vec<u8>,
     pub mime_type: String,
     pub metadata: Option<serde_json::Value>,
+    /// The uploading device; see [`be_remote_db::Device`].
+    pub device_id: Option<Uuid>,
+}
+
+/// An asset's bytes once they are in storage.
+struct StoredContent {
+    asset_id: Uuid,
+    storage_uri: String,
+    checksum_sha256: Vec<u8>,
+    size_bytes: i64,
+}
+
+/// What [`CreateAssetInput`] says about an asset besides its bytes.
+struct AssetDetails {
+    name: String,
+    mime_type: String,
+    metadata: Option<serde_json::Value>,
+    device_id: Option<Uuid>,
 }
 
 #[derive(Debug)]
 pub struct AssetService {
     db: Arc<DatabaseManager>,
     storage: Arc<StorageService>,
+    scan_uploads: bool,
+    quarantine_corrupted: bool,
 }
 
 impl AssetService {
     pub fn new(db: Arc<DatabaseManager>, storage: Arc<StorageService>) -> Self {
         tracing::info!("Creating new AssetsService instance");
-        Self { db, storage }
+        Self {
+            db,
+            storage,
+            scan_uploads: false,
+            quarantine_corrupted: false,
+        }
+    }
+
+    /// Queue new uploads for the malware scan worker (`be-asset-scan`)
+    /// instead of marking them `skipped`. Only enable this when the worker
+    /// runs, or uploads stay `pending` forever.
+    pub fn with_malware_scanning(mut self, enabled: bool) -> Self {
+        self.scan_uploads = enabled;
+        self
+    }
+
+    /// Quarantine an asset whose bytes fail the checksum check on
+    /// download, so it's refused from then on instead of failing every
+    /// read. Off by default: corruption is reported either way.
+    pub fn with_corruption_quarantine(mut self, enabled: bool) -> Self {
+        self.quarantine_corrupted = enabled;
+        self
     }
 
     pub fn from_env(db: Arc<DatabaseManager>) -> AssetResult<Self> {
@@ -123,6 +244,17 @@ impl AssetService {
                 .map(|h| general_purpose::STANDARD.encode(h)),
             storage_uri: asset.storage_uri,
             metadata: asset.metadata,
+            device_id: asset.device_id,
+            scan_status: match asset.scan_status {
+                AssetScanStatus::Pending => ScanStatus::Pending,
+                AssetScanStatus::Clean => ScanStatus::Clean,
+                AssetScanStatus::Quarantined => ScanStatus::Quarantined,
+                AssetScanStatus::Skipped => ScanStatus::Skipped,
+            },
+            storage_tier: match asset.storage_tier {
+                AssetStorageTier::Hot => StorageTier::Hot,
+                AssetStorageTier::Cold => StorageTier::Cold,
+            },
             created_at: asset.created_at,
             updated_at: asset.updated_at,
         }
@@ -136,31 +268,27 @@ impl AssetService {
             content,
             mime_type,
             metadata,
+            device_id,
         } = input;
 
         if content.is_empty() {
             return Err(AssetError::EmptyContent);
         }
 
-        if mime_type.is_empty() {
-            return Err(AssetError::MissingMimeType);
-        }
-
-        let mime_base = mime_type
-            .split(';')
-            .next()
-            .unwrap_or("")
-            .trim()
-            .to_ascii_lowercase();
-
-        if !ALLOWED_MIME_TYPES.contains(&mime_base.as_str()) {
-            return Err(AssetError::UnsupportedMimeType(mime_type));
-        }
+        let mime_base = supported_mime_base(&mime_type)?;
 
         if !validate_content_matches_mime(&content, &mime_base) {
             return Err(AssetError::MimeTypeMismatch);
         }
 
+        let mut metadata = metadata;
+        let content = if sanitize::handles(&mime_base) {
+            self.sanitize_image(content, &mime_base, user_id, &mut metadata)
+                .await?
+        } else {
+            content
+        };
+
         let checksum_sha256 = StorageService::calculate_sha256(&content);
         let size_bytes = content.len() as i64;
 
@@ -181,18 +309,49 @@ impl AssetService {
                 AssetError::StorageUpload(e)
             })?;
 
+        self.record_asset(
+            StoredContent {
+                asset_id,
+                storage_uri,
+                checksum_sha256,
+                size_bytes,
+            },
+            AssetDetails {
+                name,
+                mime_type,
+                metadata,
+                device_id,
+            },
+            user_id,
+        )
+        .await
+    }
+
+    /// Insert the row for an asset whose bytes are already in storage.
+    async fn record_asset(
+        &self,
+        stored: StoredContent,
+        details: AssetDetails,
+        user_id: Uuid,
+    ) -> AssetResult<Asset> {
         let asset = self
             .db
             .create_asset()
-            .id(asset_id)
+            .id(stored.asset_id)
             .user_id(user_id)
-            .name(name)
-            .checksum_sha256(checksum_sha256)
-            .size_bytes(size_bytes)
-            .storage_uri(storage_uri)
+            .name(details.name)
+            .checksum_sha256(stored.checksum_sha256)
+            .size_bytes(stored.size_bytes)
+            .storage_uri(stored.storage_uri)
             .storage_backend(self.storage.get_backend_name().to_string())
-            .mime_type(mime_type)
-            .maybe_metadata(metadata)
+            .mime_type(details.mime_type)
+            .maybe_metadata(details.metadata)
+            .maybe_device_id(details.device_id)
+            .scan_status(if self.scan_uploads {
+                AssetScanStatus::Pending
+            } else {
+                AssetScanStatus::Skipped
+            })
             .call()
             .await
             .map_err(|e| {
@@ -205,16 +364,251 @@ impl AssetService {
         Ok(Self::db_asset_to_dto(asset))
     }
 
-    /// Read an asset's raw bytes scoped to its owner.
+    /// Strip metadata from an image upload if its owner has stripping on,
+    /// and record in the asset's metadata whether anything was removed.
+    async fn sanitize_image(
+        &self,
+        content: Vec<u8>,
+        mime_base: &str,
+        user_id: Uuid,
+        metadata: &mut Option<serde_json::Value>,
+    ) -> AssetResult<Vec<u8>> {
+        let enabled = self
+            .db
+            .get_strip_image_metadata()
+            .user_id(user_id)
+            .call()
+            .await
+            .map_err(AssetError::DatabaseRead)?;
+        let stripped = enabled
+            .then(|| sanitize::strip_metadata(&content, mime_base))
+            .flatten();
+
+        let record = metadata.get_or_insert_with(|| serde_json::json!({}));
+        if let Some(fields) = record.as_object_mut() {
+            fields.insert(METADATA_STRIPPED_KEY.to_owned(), stripped.is_some().into());
+        }
+
+        Ok(match stripped {
+            Some(stripped) => {
+                tracing::debug!(
+                    before = content.len(),
+                    after = stripped.len(),
+                    "Stripped image metadata"
+                );
+                stripped
+            }
+            None => content,
+        })
+    }
+
+    pub async fn get_preferences(&self, user_id: Uuid) -> AssetResult<AssetPreferences> {
+        let strip_image_metadata = self
+            .db
+            .get_strip_image_metadata()
+            .user_id(user_id)
+            .call()
+            .await
+            .map_err(AssetError::DatabaseRead)?;
+        Ok(AssetPreferences {
+            strip_image_metadata,
+        })
+    }
+
+    /// Only affects later uploads; stored assets are left as they are.
+    pub async fn update_preferences(
+        &self,
+        user_id: Uuid,
+        preferences: AssetPreferences,
+    ) -> AssetResult<AssetPreferences> {
+        self.db
+            .set_strip_image_metadata()
+            .user_id(user_id)
+            .enabled(preferences.strip_image_metadata)
+            .call()
+            .await
+            .map_err(AssetError::DatabaseWrite)?;
+        Ok(preferences)
+    }
+
+    /// Read an asset's raw bytes scoped to its owner and the users it was
+    /// shared with.
     ///
     /// The `user_id` predicate is enforced inside the DB query
-    /// (`get_asset_for_user`), so attempting to read another user's asset
-    /// surfaces as [`AssetError::NotFound`] — never as a permission error
+    /// (`get_asset_for_user`), so attempting to read another user's
+    /// unshared asset surfaces as [`AssetError::NotFound`] — never as a permission error
     /// that would leak the asset's existence. Bytes are pulled through the
     /// `StorageService`, which dispatches to the configured backend
     /// (filesystem in dev, S3 in prod) and transparently decrypts when the
-    /// `encryption` feature is enabled.
+    /// `encryption` feature is enabled. Assets the malware scanner
+    /// quarantined fail with [`AssetError::Quarantined`].
     pub async fn get_asset_bytes(&self, asset_id: Uuid, user_id: Uuid) -> AssetResult<AssetBytes> {
+        let asset = self.downloadable_asset(asset_id, user_id).await?;
+        self.read_bytes(asset).await
+    }
+
+    /// All of `asset`'s bytes, for a caller that has already checked it
+    /// may read them. Bytes are checked against the checksum taken at
+    /// upload; a mismatch fails with [`AssetError::Corrupted`].
+    async fn read_bytes(&self, asset: be_remote_db::Asset) -> AssetResult<AssetBytes> {
+        let downloaded = match &asset.checksum_sha256 {
+            Some(checksum) => {
+                self.storage
+                    .download_verified(&asset.storage_uri, checksum)
+                    .await
+            }
+            None => self.storage.download(&asset.storage_uri).await,
+        };
+        let bytes = match downloaded {
+            Ok(bytes) => bytes,
+            // The DB row points at storage that no longer holds the
+            // blob — surface as `NotFound` so HTTP callers render a
+            // clean 404 instead of a 500.
+            Err(e) if e.is_not_found() => return Err(AssetError::NotFound),
+            Err(e) if e.is_integrity_mismatch() => {
+                self.report_corruption(&asset, &e).await;
+                return Err(AssetError::Corrupted);
+            }
+            Err(e) => return Err(AssetError::StorageDownload(e)),
+        };
+
+        let storage_tier = self.restore_if_cold(&asset).await;
+        Ok(AssetBytes {
+            bytes,
+            mime_type: asset.mime_type,
+            storage_tier,
+        })
+    }
+
+    /// Read `length` bytes of an asset starting at `offset` (to the end
+    /// when `length` is `None`), with the same ownership scoping as
+    /// [`Self::get_asset_bytes`]. Lets a player seek inside a large
+    /// recording without fetching all of it; a range starting past the
+    /// end fails with [`AssetError::RangeNotSatisfiable`].
+    pub async fn get_asset_range(
+        &self,
+        asset_id: Uuid,
+        user_id: Uuid,
+        offset: u64,
+        length: Option<u64>,
+    ) -> AssetResult<AssetRange> {
+        let asset = self.downloadable_asset(asset_id, user_id).await?;
+
+        let range = self
+            .storage
+            .download_range(&asset.storage_uri, offset, length)
+            .await
+            .map_err(|e| match e {
+                e if e.is_not_found() => AssetError::NotFound,
+                StorageError::RangeNotSatisfiable { size } => {
+                    AssetError::RangeNotSatisfiable { size }
+                }
+                e => AssetError::StorageDownload(e),
+            })?;
+
+        let storage_tier = self.restore_if_cold(&asset).await;
+        Ok(AssetRange {
+            bytes: range.bytes,
+            mime_type: asset.mime_type,
+            offset: range.offset,
+            total_size: range.total_size,
+            storage_tier,
+        })
+    }
+
+    /// Log and count a checksum mismatch, and quarantine the asset when
+    /// [`Self::with_corruption_quarantine`] is on.
+    async fn report_corruption(&self, asset: &be_remote_db::Asset, err: &StorageError) {
+        tracing::error!(
+            asset_id = %asset.id,
+            user_id = %asset.user_id,
+            storage_backend = %asset.storage_backend,
+            error = %err,
+            "Asset failed its integrity check"
+        );
+        opentelemetry::global::meter("be-asset")
+            .u64_counter("asset.integrity_failures")
+            .with_description("Asset downloads whose bytes didn't match their checksum")
+            .build()
+            .add(
+                1,
+                &[opentelemetry::KeyValue::new(
+                    "storage_backend",
+                    asset.storage_backend.clone(),
+                )],
+            );
+
+        if !self.quarantine_corrupted {
+            return;
+        }
+        match self
+            .db
+            .quarantine_asset()
+            .id(asset.id)
+            .signature(CORRUPTION_SIGNATURE)
+            .call()
+            .await
+        {
+            Ok(_) => tracing::warn!(asset_id = %asset.id, "Quarantined corrupted asset"),
+            Err(e) => {
+                tracing::error!(asset_id = %asset.id, error = %e, "Failed to quarantine corrupted asset");
+            }
+        }
+    }
+
+    /// Move an asset that was just read from the cold store back to hot,
+    /// so the next read is fast again, and return the tier it was read
+    /// from. Best effort: the read already succeeded, so a failed move is
+    /// only logged and retried on the next read.
+    async fn restore_if_cold(&self, asset: &be_remote_db::Asset) -> StorageTier {
+        if be_storage::StorageTier::of(&asset.storage_uri) != be_storage::StorageTier::Cold {
+            return StorageTier::Hot;
+        }
+
+        let started = std::time::Instant::now();
+        let restored = async {
+            let hot_uri = self
+                .storage
+                .copy_to_tier(&asset.storage_uri, be_storage::StorageTier::Hot)
+                .await
+                .map_err(AssetError::StorageUpload)?;
+            let moved = self
+                .db
+                .move_asset_to_tier()
+                .id(asset.id)
+                .from_uri(&asset.storage_uri)
+                .to_uri(&hot_uri)
+                .tier(AssetStorageTier::Hot)
+                .call()
+                .await
+                .map_err(AssetError::DatabaseWrite)?;
+            let stale = if moved { &asset.storage_uri } else { &hot_uri };
+            if let Err(e) = self.storage.delete(stale).await {
+                tracing::warn!(asset_id = %asset.id, error = %e, "Failed to delete stale tier copy");
+            }
+            Ok::<_, AssetError>(moved)
+        };
+        match restored.await {
+            Ok(true) => tracing::info!(
+                asset_id = %asset.id,
+                elapsed_ms = started.elapsed().as_millis() as u64,
+                "Restored asset from cold storage"
+            ),
+            Ok(false) => {}
+            Err(e) => {
+                tracing::warn!(asset_id = %asset.id, error = %e, "Failed to restore asset from cold storage");
+            }
+        }
+        StorageTier::Cold
+    }
+
+    /// An asset the user may read, unless the malware scanner quarantined
+    /// it.
+    async fn downloadable_asset(
+        &self,
+        asset_id: Uuid,
+        user_id: Uuid,
+    ) -> AssetResult<be_remote_db::Asset> {
         let asset = self
             .db
             .get_asset_for_user()
@@ -230,25 +624,167 @@ impl AssetService {
                 }
             })?;
 
-        let bytes = self
-            .storage
-            .download(&asset.storage_uri)
+        refuse_quarantined(asset)
+    }
+
+    /// Metadata for up to [`MAX_BATCH_SIZE`] assets in one query. Ids the
+    /// user doesn't own come back as [`AssetError::NotFound`].
+    pub async fn get_assets(
+        &self,
+        ids: &[Uuid],
+        user_id: Uuid,
+    ) -> AssetResult<BatchOutcome<Asset>> {
+        let ids = distinct_batch(ids)?;
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut found: HashMap<Uuid, be_remote_db::Asset> = self
+            .db
+            .get_assets_for_user()
+            .ids(&ids)
+            .user_id(user_id)
+            .call()
+            .await
+            .map_err(AssetError::DatabaseRead)?
+            .into_iter()
+            .map(|asset| (asset.id, asset))
+            .collect();
+
+        Ok(ids
+            .into_iter()
+            .map(|id| {
+                let result = found
+                    .remove(&id)
+                    .map(Self::db_asset_to_dto)
+                    .ok_or(AssetError::NotFound);
+                (id, result)
+            })
+            .collect())
+    }
+
+    /// Delete up to [`MAX_BATCH_SIZE`] assets, stored object first and
+    /// then the row, so an object that fails to delete keeps its row and
+    /// can be retried. Objects already missing from storage count as
+    /// deleted. Assets under legal hold fail with
+    /// [`AssetError::OnLegalHold`] and keep their object.
+    pub async fn delete_assets(
+        &self,
+        ids: &[Uuid],
+        user_id: Uuid,
+    ) -> AssetResult<BatchOutcome<()>> {
+        let ids = distinct_batch(ids)?;
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let owned = self
+            .db
+            .get_assets_for_user()
+            .ids(&ids)
+            .user_id(user_id)
+            .call()
+            .await
+            .map_err(AssetError::DatabaseRead)?;
+
+        let held: HashSet<Uuid> = self
+            .db
+            .list_held_assets()
+            .ids(&ids)
+            .call()
+            .await
+            .map_err(AssetError::DatabaseRead)?
+            .into_iter()
+            .collect();
+
+        let mut failures: HashMap<Uuid, AssetError> = HashMap::new();
+        let mut removable = Vec::with_capacity(owned.len());
+        for asset in owned {
+            if held.contains(&asset.id) {
+                failures.insert(asset.id, AssetError::OnLegalHold);
+                continue;
+            }
+            match self.storage.delete(&asset.storage_uri).await {
+                Ok(()) => removable.push(asset.id),
+                Err(e) if e.is_not_found() => removable.push(asset.id),
+                Err(e) => {
+                    failures.insert(asset.id, AssetError::StorageDelete(e));
+                }
+            }
+        }
+
+        let deleted: HashSet<Uuid> = if removable.is_empty() {
+            HashSet::new()
+        } else {
+            self.db
+                .delete_assets_for_user()
+                .ids(&removable)
+                .user_id(user_id)
+                .call()
+                .await
+                .map_err(AssetError::DatabaseWrite)?
+                .into_iter()
+                .collect()
+        };
+
+        Ok(ids
+            .into_iter()
+            .map(|id| {
+                let result = if deleted.contains(&id) {
+                    Ok(())
+                } else {
+                    Err(failures.remove(&id).unwrap_or(AssetError::NotFound))
+                };
+                (id, result)
+            })
+            .collect())
+    }
+
+    /// Link up to [`MAX_BATCH_SIZE`] assets to one of the user's
+    /// activities. Linking is idempotent. The whole call fails with
+    /// [`AssetError::ActivityNotFound`] when the activity isn't the
+    /// user's; individual foreign asset ids fail with
+    /// [`AssetError::NotFound`].
+    pub async fn link_assets_to_activity(
+        &self,
+        activity_id: Uuid,
+        asset_ids: &[Uuid],
+        user_id: Uuid,
+    ) -> AssetResult<BatchOutcome<()>> {
+        let ids = distinct_batch(asset_ids)?;
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let linked: HashSet<Uuid> = self
+            .db
+            .link_assets_to_activity()
+            .activity_id(activity_id)
+            .user_id(user_id)
+            .asset_ids(&ids)
+            .call()
             .await
             .map_err(|e| {
                 if e.is_not_found() {
-                    // The DB row points at storage that no longer holds
-                    // the blob — surface as `NotFound` so HTTP callers
-                    // render a clean 404 instead of a 500.
-                    AssetError::NotFound
+                    AssetError::ActivityNotFound
                 } else {
-                    AssetError::StorageDownload(e)
+                    AssetError::DatabaseWrite(e)
                 }
-            })?;
-
-        Ok(AssetBytes {
-            bytes,
-            mime_type: asset.mime_type,
-        })
+            })?
+            .into_iter()
+            .collect();
+
+        Ok(ids
+            .into_iter()
+            .map(|id| {
+                let result = if linked.contains(&id) {
+                    Ok(())
+                } else {
+                    Err(AssetError::NotFound)
+                };
+                (id, result)
+            })
+            .collect())
     }
 }
 
@@ -256,6 +792,25 @@ impl AssetService {
 mod tests {
     use super::*;
 
+    #[test]
+    fn distinct_batch_collapses_duplicates_in_order() {
+        let a = Uuid::now_v7();
+        let b = Uuid::now_v7();
+        assert_eq!(distinct_batch(&[a, b, a, b]).unwrap(), [a, b]);
+    }
+
+    #[test]
+    fn distinct_batch_enforces_the_limit_after_dedup() {
+        let one = Uuid::now_v7();
+        assert!(distinct_batch(&vec![one; MAX_BATCH_SIZE + 1]).is_ok());
+
+        let many: Vec<Uuid> = (0..=MAX_BATCH_SIZE).map(|_| Uuid::now_v7()).collect();
+        assert!(matches!(
+            distinct_batch(&many),
+            Err(AssetError::BatchTooLarge { got, max }) if got == MAX_BATCH_SIZE + 1 && max == MAX_BATCH_SIZE
+        ));
+    }
+
     #[test]
     fn text_markdown_is_allowed() {
         assert!(ALLOWED_MIME_TYPES.contains(&"text/markdown"));
@@ -270,6 +825,20 @@ mod tests {
         assert!(validate_content_matches_mime(b"", "text/markdown"));
     }
 
+    #[test]
+    fn avif_is_recognised_by_its_brand() {
+        assert!(ALLOWED_MIME_TYPES.contains(&"image/avif"));
+        assert!(validate_content_matches_mime(
+            b"\0\0\0\x1cftypavif\0\0\0\0",
+            "image/avif"
+        ));
+        assert!(!validate_content_matches_mime(
+            b"\0\0\0\x1cftypheic\0\0\0\0",
+            "image/avif"
+        ));
+        assert!(!validate_content_matches_mime(b"ftyp", "image/avif"));
+    }
+
     #[test]
     fn text_markdown_rejects_invalid_utf8() {
         // Lone continuation byte — never valid UTF-8.
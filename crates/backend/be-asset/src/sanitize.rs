@@ -0,0 +1,395 @@
+//! Metadata stripping for image uploads.
+//!
+//! Photos carry EXIF (camera serials, capture time, GPS position) and
+//! screenshots often carry XMP, so by default both are removed before an
+//! image is stored. Stripping works at the container level — dropping
+//! JPEG APP1/APP13/COM segments, PNG text/`eXIf`/`tIME` chunks and WebP
+//! `EXIF`/`XMP ` chunks — so pixel data is copied untouched and an image
+//! without metadata is stored byte-for-byte as uploaded.
+//!
+//! The one exception is a JPEG whose EXIF rotates it: dropping the tag
+//! would show the photo sideways, so it is decoded, rotated and
+//! re-encoded instead.
+//!
+//! Uploads were never required to be well-formed images, so a container
+//! that can't be walked to the end is not an error: everything up to the
+//! unreadable part is filtered and the rest is kept as uploaded.
+
+use std::io::Cursor;
+
+use image::codecs::jpeg::JpegEncoder;
+use image::metadata::Orientation;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader};
+
+/// Quality for the rare rotated-JPEG re-encode. High enough that the
+/// generation loss isn't visible.
+const JPEG_QUALITY: u8 = 92;
+
+/// PNG chunks that hold metadata rather than pixels or colour data.
+const PNG_METADATA_CHUNKS: &[&[u8; 4]] = &[b"eXIf", b"tEXt", b"zTXt", b"iTXt", b"tIME"];
+
+/// WebP `VP8X` feature flags announcing EXIF and XMP chunks.
+const WEBP_EXIF_FLAG: u8 = 0x08;
+const WEBP_XMP_FLAG: u8 = 0x04;
+
+/// Whether uploads of `mime_base` go through [`strip_metadata`].
+pub(crate) fn handles(mime_base: &str) -> bool {
+    matches!(mime_base, "image/jpeg" | "image/png" | "image/webp")
+}
+
+/// The image without its metadata, or `None` when it had none and should
+/// be stored unchanged.
+pub(crate) fn strip_metadata(content: &[u8], mime_base: &str) -> Option<Vec<u8>> {
+    match mime_base {
+        "image/jpeg" => strip_jpeg(content),
+        "image/png" => strip_png(content),
+        "image/webp" => strip_webp(content),
+        _ => None,
+    }
+}
+
+fn strip_jpeg(content: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(content.len());
+    out.extend_from_slice(content.get(..2)?);
+    let mut stripped = false;
+    let mut pos = 2;
+
+    while pos < content.len() {
+        // Markers may be preceded by any number of 0xFF fill bytes.
+        let mut marker_at = pos + 1;
+        while content.get(marker_at) == Some(&0xFF) {
+            marker_at += 1;
+        }
+
+        let marker = match (content[pos], content.get(marker_at)) {
+            (0xFF, Some(&marker)) if !matches!(marker, 0xDA | 0xD9) => marker,
+            // Start of scan or end of image: the rest is entropy-coded
+            // data, which can't hold metadata segments. Anything that
+            // isn't a marker is kept as is too.
+            _ => {
+                out.extend_from_slice(&content[pos..]);
+                break;
+            }
+        };
+        // Standalone markers without a length.
+        if matches!(marker, 0x01 | 0xD0..=0xD7) {
+            out.extend_from_slice(&content[pos..=marker_at]);
+            pos = marker_at + 1;
+            continue;
+        }
+
+        // APP1 holds EXIF and XMP, APP13 Photoshop/IPTC, COM free text.
+        let is_metadata = matches!(marker, 0xE1 | 0xED | 0xFE);
+        let end = content
+            .get(marker_at + 1..marker_at + 3)
+            .map(|len| marker_at + 1 + usize::from(u16::from_be_bytes([len[0], len[1]])))
+            .filter(|&end| end > marker_at + 2 && end <= content.len());
+        let Some(end) = end else {
+            // Truncated segment: keep it unless it's metadata.
+            stripped |= is_metadata;
+            if !is_metadata {
+                out.extend_from_slice(&content[pos..]);
+            }
+            break;
+        };
+
+        if is_metadata {
+            stripped = true;
+        } else {
+            out.extend_from_slice(&content[pos..end]);
+        }
+        pos = end;
+    }
+
+    if !stripped {
+        return None;
+    }
+    match jpeg_orientation(content) {
+        Some(orientation) if orientation != Orientation::NoTransforms => {
+            match reencode_rotated_jpeg(&out, orientation) {
+                Ok(rotated) => Some(rotated),
+                Err(e) => {
+                    // Still strip; the photo just shows unrotated.
+                    tracing::warn!(error = %e, "Failed to re-encode rotated JPEG");
+                    Some(out)
+                }
+            }
+        }
+        _ => Some(out),
+    }
+}
+
+/// The EXIF orientation, if the JPEG declares one and it can be read.
+fn jpeg_orientation(content: &[u8]) -> Option<Orientation> {
+    let mut decoder = ImageReader::with_format(Cursor::new(content), ImageFormat::Jpeg)
+        .into_decoder()
+        .ok()?;
+    decoder.orientation().ok()
+}
+
+/// Decode the stripped JPEG, apply `orientation` to the pixels, and
+/// encode it again.
+fn reencode_rotated_jpeg(
+    stripped: &[u8],
+    orientation: Orientation,
+) -> Result<Vec<u8>, image::ImageError> {
+    let mut image = ImageReader::with_format(Cursor::new(stripped), ImageFormat::Jpeg).decode()?;
+    image.apply_orientation(orientation);
+    // JPEG has no alpha channel; decoded JPEGs never have one either.
+    let image = DynamicImage::ImageRgb8(image.into_rgb8());
+
+    let mut out = Vec::new();
+    image.write_with_encoder(JpegEncoder::new_with_quality(&mut out, JPEG_QUALITY))?;
+    Ok(out)
+}
+
+fn strip_png(content: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(content.len());
+    out.extend_from_slice(content.get(..8)?);
+    let mut stripped = false;
+    let mut pos = 8;
+
+    while let Some(header) = content.get(pos..pos + 8) {
+        let len = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let kind = &header[4..8];
+        let is_metadata = PNG_METADATA_CHUNKS.iter().any(|m| m.as_slice() == kind);
+        // Length, type, data, CRC.
+        let Some(end) = pos
+            .checked_add(12 + len)
+            .filter(|&end| end <= content.len())
+        else {
+            // Truncated chunk: keep it unless it's metadata.
+            stripped |= is_metadata;
+            if !is_metadata {
+                out.extend_from_slice(&content[pos..]);
+            }
+            return stripped.then_some(out);
+        };
+
+        if is_metadata {
+            stripped = true;
+        } else {
+            out.extend_from_slice(&content[pos..end]);
+        }
+        pos = end;
+
+        if kind == b"IEND" {
+            // Bytes appended after IEND are ignored by decoders but would
+            // still be stored.
+            stripped |= pos < content.len();
+            return stripped.then_some(out);
+        }
+    }
+
+    out.extend_from_slice(&content[pos..]);
+    stripped.then_some(out)
+}
+
+fn strip_webp(content: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(content.len());
+    out.extend_from_slice(content.get(..12)?);
+    let mut stripped = false;
+    let mut pos = 12;
+
+    while let Some(header) = content.get(pos..pos + 8) {
+        let kind = &header[..4];
+        let len = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+        let is_metadata = kind == b"EXIF" || kind == b"XMP ";
+        // Chunks are padded to an even size; a missing final pad byte is
+        // common enough to tolerate.
+        let Some(end) = pos
+            .checked_add(8 + len)
+            .filter(|&end| end <= content.len())
+            .map(|end| (end + (len & 1)).min(content.len()))
+        else {
+            // Truncated chunk: keep it unless it's metadata.
+            stripped |= is_metadata;
+            if !is_metadata {
+                out.extend_from_slice(&content[pos..]);
+            }
+            pos = content.len();
+            break;
+        };
+
+        if is_metadata {
+            stripped = true;
+        } else {
+            out.extend_from_slice(&content[pos..end]);
+        }
+        pos = end;
+    }
+
+    if !stripped {
+        return None;
+    }
+    out.extend_from_slice(&content[pos..]);
+
+    // Clear the flags announcing the dropped chunks, then fix up the RIFF
+    // size, which counts everything after the first eight bytes.
+    if out.get(12..16) == Some(b"VP8X".as_slice()) && out.len() > 20 {
+        out[20] &= !(WEBP_EXIF_FLAG | WEBP_XMP_FLAG);
+    }
+    let riff_size = u32::try_from(out.len() - 8).ok()?;
+    out[4..8].copy_from_slice(&riff_size.to_le_bytes());
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{ImageEncoder, Rgb, RgbImage};
+
+    use super::*;
+
+    fn segment(marker: u8, payload: &[u8]) -> Vec<u8> {
+        let mut out = vec![0xFF, marker];
+        out.extend_from_slice(&(payload.len() as u16 + 2).to_be_bytes());
+        out.extend_from_slice(payload);
+        out
+    }
+
+    fn png_chunk(kind: &[u8; 4], data: &[u8]) -> Vec<u8> {
+        let mut out = (data.len() as u32).to_be_bytes().to_vec();
+        out.extend_from_slice(kind);
+        out.extend_from_slice(data);
+        // Not checked by the stripper.
+        out.extend_from_slice(&[0; 4]);
+        out
+    }
+
+    /// A real 2×1 JPEG: red on the left, blue on the right.
+    fn two_pixel_jpeg() -> Vec<u8> {
+        let mut image = RgbImage::new(2, 1);
+        image.put_pixel(0, 0, Rgb([255, 0, 0]));
+        image.put_pixel(1, 0, Rgb([0, 0, 255]));
+        let mut out = Vec::new();
+        JpegEncoder::new_with_quality(&mut out, 100)
+            .write_image(image.as_raw(), 2, 1, image::ExtendedColorType::Rgb8)
+            .unwrap();
+        out
+    }
+
+    /// A minimal little-endian EXIF block holding only an orientation tag.
+    fn exif_with_orientation(orientation: u16) -> Vec<u8> {
+        let mut exif = b"Exif\0\0II*\0\x08\0\0\0".to_vec();
+        exif.extend_from_slice(&1u16.to_le_bytes()); // one IFD entry
+        exif.extend_from_slice(&0x0112u16.to_le_bytes()); // Orientation
+        exif.extend_from_slice(&3u16.to_le_bytes()); // SHORT
+        exif.extend_from_slice(&1u32.to_le_bytes()); // count
+        exif.extend_from_slice(&orientation.to_le_bytes());
+        exif.extend_from_slice(&[0, 0]); // value padding
+        exif.extend_from_slice(&0u32.to_le_bytes()); // no next IFD
+        exif
+    }
+
+    fn with_segment_after_soi(jpeg: &[u8], segment: &[u8]) -> Vec<u8> {
+        let mut out = jpeg[..2].to_vec();
+        out.extend_from_slice(segment);
+        out.extend_from_slice(&jpeg[2..]);
+        out
+    }
+
+    #[test]
+    fn jpeg_without_metadata_is_left_alone() {
+        let jpeg = two_pixel_jpeg();
+        assert_eq!(strip_metadata(&jpeg, "image/jpeg"), None);
+    }
+
+    #[test]
+    fn jpeg_exif_and_comments_are_dropped_losslessly() {
+        let clean = two_pixel_jpeg();
+        let mut tagged = with_segment_after_soi(&clean, &segment(0xFE, b"taken at home"));
+        tagged = with_segment_after_soi(&tagged, &segment(0xE1, &exif_with_orientation(1)));
+
+        let stripped = strip_metadata(&tagged, "image/jpeg").unwrap();
+        assert_eq!(stripped, clean);
+    }
+
+    #[test]
+    fn rotated_jpeg_is_reencoded_upright() {
+        // Orientation 6: rotate 90° clockwise to display.
+        let tagged =
+            with_segment_after_soi(&two_pixel_jpeg(), &segment(0xE1, &exif_with_orientation(6)));
+
+        let stripped = strip_metadata(&tagged, "image/jpeg").unwrap();
+        assert!(!stripped.windows(4).any(|w| w == b"Exif"));
+        let image = image::load_from_memory_with_format(&stripped, ImageFormat::Jpeg)
+            .unwrap()
+            .into_rgb8();
+        assert_eq!(image.dimensions(), (1, 2));
+        // Red was on the left, so after a clockwise turn it is on top.
+        assert!(image.get_pixel(0, 0)[0] > image.get_pixel(0, 0)[2]);
+    }
+
+    #[test]
+    fn truncated_files_are_filtered_as_far_as_they_go() {
+        let tagged = with_segment_after_soi(&two_pixel_jpeg(), &segment(0xE1, b"Exif\0\0"));
+        assert_eq!(
+            strip_metadata(&tagged[..8], "image/jpeg"),
+            Some(vec![0xFF, 0xD8])
+        );
+
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png.extend(png_chunk(b"IHDR", &[0; 13]));
+        let truncated_idat = &png_chunk(b"IDAT", &[1, 2, 3, 4])[..10];
+        png.extend_from_slice(truncated_idat);
+        assert_eq!(strip_metadata(&png, "image/png"), None);
+    }
+
+    #[test]
+    fn png_text_chunks_and_trailing_bytes_are_dropped() {
+        let mut clean = b"\x89PNG\r\n\x1a\n".to_vec();
+        clean.extend(png_chunk(b"IHDR", &[0; 13]));
+        clean.extend(png_chunk(b"IDAT", &[1, 2, 3]));
+        let iend = png_chunk(b"IEND", &[]);
+
+        let mut plain = clean.clone();
+        plain.extend(&iend);
+        assert_eq!(strip_metadata(&plain, "image/png"), None);
+
+        let mut tagged = b"\x89PNG\r\n\x1a\n".to_vec();
+        tagged.extend(png_chunk(b"IHDR", &[0; 13]));
+        tagged.extend(png_chunk(b"tEXt", b"Location\0home"));
+        tagged.extend(png_chunk(b"IDAT", &[1, 2, 3]));
+        tagged.extend(png_chunk(b"eXIf", b"II*\0"));
+        tagged.extend(&iend);
+        tagged.extend_from_slice(b"appended");
+
+        let stripped = strip_metadata(&tagged, "image/png").unwrap();
+        assert_eq!(stripped, plain);
+    }
+
+    #[test]
+    fn webp_exif_chunk_and_flag_are_dropped() {
+        let mut vp8x = vec![WEBP_EXIF_FLAG | WEBP_XMP_FLAG];
+        vp8x.extend_from_slice(&[0; 9]);
+        let chunk = |kind: &[u8], data: &[u8]| {
+            let mut out = kind.to_vec();
+            out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            out.extend_from_slice(data);
+            if data.len() % 2 == 1 {
+                out.push(0);
+            }
+            out
+        };
+        let riff = |body: Vec<u8>| {
+            let mut out = b"RIFF".to_vec();
+            out.extend_from_slice(&(body.len() as u32 + 4).to_le_bytes());
+            out.extend_from_slice(b"WEBP");
+            out.extend(body);
+            out
+        };
+
+        let mut body = chunk(b"VP8X", &vp8x);
+        body.extend(chunk(b"VP8L", &[1, 2, 3]));
+        body.extend(chunk(b"EXIF", b"II*\0"));
+        body.extend(chunk(b"XMP ", b"<x:xmpmeta/>"));
+        let tagged = riff(body);
+
+        let stripped = strip_metadata(&tagged, "image/webp").unwrap();
+        let mut expected_vp8x = vp8x.clone();
+        expected_vp8x[0] = 0;
+        let mut expected = chunk(b"VP8X", &expected_vp8x);
+        expected.extend(chunk(b"VP8L", &[1, 2, 3]));
+        assert_eq!(stripped, riff(expected));
+    }
+}
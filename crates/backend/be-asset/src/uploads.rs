@@ -0,0 +1,403 @@
+//! Resumable uploads, for large assets over connections that drop.
+//!
+//! A client opens a session with the asset's name, type and size, then
+//! sends the bytes in chunks of the session's `chunk_size`, in any order;
+//! a chunk sent again replaces the first copy, so a failed one is simply
+//! retried. After a disconnect [`AssetService::get_upload`] lists the
+//! chunks that arrived. [`AssetService::complete_upload`] assembles them
+//! and creates the asset through [`AssetService::create_asset`], with the
+//! same checks as a single-request upload.
+//!
+//! PDFs and other binaries are streamed into place one chunk at a time,
+//! so they may use the full [`MAX_UPLOAD_SIZE`]. Images, text and JSON
+//! are checked or rewritten as a whole, as is everything when storage
+//! encrypts objects; those are assembled in memory and capped at
+//! [`MAX_BUFFERED_UPLOAD_SIZE`]. A user has at most [`MAX_OPEN_UPLOADS`]
+//! sessions open. The asset is tagged with the device that opened the
+//! session.
+//!
+//! Each chunk is its own object until the session completes or is
+//! aborted. A session that receives no chunk for
+//! [`UPLOAD_SESSION_TTL_SECS`] is deleted with its chunks by the retention
+//! worker.
+
+use asset_core::{
+    Asset, CreateUploadRequest, MAX_BUFFERED_UPLOAD_SIZE, MAX_OPEN_UPLOADS, MAX_UPLOAD_SIZE,
+    UPLOAD_CHUNK_SIZE, UPLOAD_SESSION_TTL_SECS, UploadSession,
+};
+use be_remote_db::UploadChunk;
+use be_storage::StorageService;
+use chrono::{DateTime, Duration, Utc};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{
+    AssetDetails, AssetError, AssetResult, AssetService, CreateAssetInput, StoredContent,
+    supported_mime_base, validate_content_matches_mime,
+};
+
+impl AssetService {
+    /// Open an upload session for an asset of `request.size` bytes.
+    pub async fn create_upload(
+        &self,
+        request: CreateUploadRequest,
+        user_id: Uuid,
+    ) -> AssetResult<UploadSession> {
+        let mime_base = supported_mime_base(&request.mime_type)?;
+        let max = self.max_upload_size(&mime_base);
+        if request.size == 0 || request.size > max {
+            return Err(AssetError::InvalidUploadSize { max });
+        }
+
+        let session = self
+            .db
+            .create_upload_session()
+            .id(Uuid::now_v7())
+            .user_id(user_id)
+            .name(&request.name)
+            .mime_type(&request.mime_type)
+            .maybe_metadata(request.metadata)
+            .maybe_device_id(request.device_id)
+            .total_size(request.size as i64)
+            .chunk_size(UPLOAD_CHUNK_SIZE as i64)
+            .expires_at(next_expiry())
+            .max_open(i64::from(MAX_OPEN_UPLOADS))
+            .call()
+            .await
+            .map_err(AssetError::DatabaseCreate)?
+            .ok_or(AssetError::TooManyUploads {
+                max: MAX_OPEN_UPLOADS,
+            })?;
+
+        tracing::info!(upload_id = %session.id, size = request.size, "Opened upload session");
+        Ok(session_to_dto(session, &[]))
+    }
+
+    /// The user's upload session and the chunks it has received.
+    pub async fn get_upload(&self, upload_id: Uuid, user_id: Uuid) -> AssetResult<UploadSession> {
+        let session = self.upload_session(upload_id, user_id).await?;
+        let chunks = self.upload_chunks(upload_id).await?;
+        Ok(session_to_dto(session, &chunks))
+    }
+
+    /// Store the chunk starting at `offset`. It must start on a chunk
+    /// boundary and be exactly one chunk long, or run to the end of the
+    /// asset for the last chunk.
+    pub async fn put_upload_chunk(
+        &self,
+        upload_id: Uuid,
+        user_id: Uuid,
+        offset: u64,
+        bytes: &[u8],
+    ) -> AssetResult<UploadSession> {
+        let session = self.upload_session(upload_id, user_id).await?;
+        let expected = expected_chunk_len(&session, offset)?;
+        if bytes.len() as u64 != expected {
+            return Err(AssetError::InvalidChunk(format!(
+                "chunk at {offset} must be {expected} bytes, got {}",
+                bytes.len()
+            )));
+        }
+
+        let uri = format!("uploads/{user_id}/{upload_id}/{offset}");
+        self.storage
+            .write(&uri, bytes)
+            .await
+            .map_err(AssetError::StorageUpload)?;
+
+        let recorded = self
+            .db
+            .record_upload_chunk()
+            .session_id(upload_id)
+            .user_id(user_id)
+            .byte_offset(offset as i64)
+            .size(expected as i64)
+            .storage_uri(&uri)
+            .expires_at(next_expiry())
+            .call()
+            .await;
+        if let Err(e) = recorded {
+            // The session expired or was aborted while the chunk was in
+            // flight; nothing will ever clean up this object otherwise.
+            self.delete_chunk_objects(upload_id, std::slice::from_ref(&uri))
+                .await;
+            return Err(if e.is_not_found() {
+                AssetError::UploadNotFound
+            } else {
+                AssetError::DatabaseWrite(e)
+            });
+        }
+
+        self.get_upload(upload_id, user_id).await
+    }
+
+    /// Assemble a fully received upload into an asset and close the
+    /// session. Fails with [`AssetError::UploadIncomplete`] while chunks
+    /// are missing.
+    pub async fn complete_upload(&self, upload_id: Uuid, user_id: Uuid) -> AssetResult<Asset> {
+        let session = self.upload_session(upload_id, user_id).await?;
+        let chunks = self.upload_chunks(upload_id).await?;
+        let missing = chunk_count(&session).saturating_sub(chunks.len());
+        if missing > 0 {
+            return Err(AssetError::UploadIncomplete { missing });
+        }
+
+        // Checked again in case storage encryption was turned on since
+        // the session opened.
+        let mime_base = supported_mime_base(&session.mime_type)?;
+        let max = self.max_upload_size(&mime_base);
+        if session.total_size as u64 > max {
+            return Err(AssetError::InvalidUploadSize { max });
+        }
+
+        let asset = if self.streams(&mime_base) {
+            self.assemble_streamed(session, &chunks, &mime_base, user_id)
+                .await?
+        } else {
+            let mut content = Vec::with_capacity(session.total_size as usize);
+            for chunk in &chunks {
+                let bytes = self
+                    .storage
+                    .download(&chunk.storage_uri)
+                    .await
+                    .map_err(AssetError::StorageDownload)?;
+                content.extend_from_slice(&bytes);
+            }
+            self.create_asset(
+                CreateAssetInput {
+                    name: session.name,
+                    content,
+                    mime_type: session.mime_type,
+                    metadata: session.metadata,
+                    device_id: session.device_id,
+                },
+                user_id,
+            )
+            .await?
+        };
+
+        match self
+            .db
+            .delete_upload_session()
+            .id(upload_id)
+            .user_id(user_id)
+            .call()
+            .await
+        {
+            Ok(uris) => self.delete_chunk_objects(upload_id, &uris).await,
+            Err(e) => {
+                tracing::warn!(%upload_id, error = %e, "Failed to close completed upload session");
+            }
+        }
+
+        tracing::info!(%upload_id, asset_id = %asset.id, "Completed upload");
+        Ok(asset)
+    }
+
+    /// Drop an upload session and the chunks it received.
+    pub async fn abort_upload(&self, upload_id: Uuid, user_id: Uuid) -> AssetResult<()> {
+        let uris = self
+            .db
+            .delete_upload_session()
+            .id(upload_id)
+            .user_id(user_id)
+            .call()
+            .await
+            .map_err(|e| {
+                if e.is_not_found() {
+                    AssetError::UploadNotFound
+                } else {
+                    AssetError::DatabaseWrite(e)
+                }
+            })?;
+        self.delete_chunk_objects(upload_id, &uris).await;
+
+        tracing::info!(%upload_id, "Aborted upload");
+        Ok(())
+    }
+
+    /// Copy the chunks into the asset's object one at a time, so only one
+    /// chunk is ever in memory, then record the asset.
+    async fn assemble_streamed(
+        &self,
+        session: be_remote_db::UploadSession,
+        chunks: &[UploadChunk],
+        mime_base: &str,
+        user_id: Uuid,
+    ) -> AssetResult<Asset> {
+        let asset_id = Uuid::now_v7();
+        let extension = StorageService::extension_from_mime(&session.mime_type);
+        let storage_uri = StorageService::generate_path(&user_id, &asset_id, Some(extension));
+
+        let mut writer = self
+            .storage
+            .writer(&storage_uri)
+            .await
+            .map_err(AssetError::StorageUpload)?;
+        let mut hasher = Sha256::new();
+        let copied = async {
+            for (i, chunk) in chunks.iter().enumerate() {
+                let bytes = self
+                    .storage
+                    .download(&chunk.storage_uri)
+                    .await
+                    .map_err(AssetError::StorageDownload)?;
+                // The types that stream are recognised by their first
+                // bytes, which are all in the first chunk.
+                if i == 0 && !validate_content_matches_mime(&bytes, mime_base) {
+                    return Err(AssetError::MimeTypeMismatch);
+                }
+                hasher.update(&bytes);
+                writer
+                    .write(bytes)
+                    .await
+                    .map_err(AssetError::StorageUpload)?;
+            }
+            writer.close().await.map_err(AssetError::StorageUpload)
+        }
+        .await;
+        if let Err(e) = copied {
+            writer.abort().await;
+            return Err(e);
+        }
+
+        self.record_asset(
+            StoredContent {
+                asset_id,
+                storage_uri,
+                checksum_sha256: hasher.finalize().to_vec(),
+                size_bytes: session.total_size,
+            },
+            AssetDetails {
+                name: session.name,
+                mime_type: session.mime_type,
+                metadata: session.metadata,
+                device_id: session.device_id,
+            },
+            user_id,
+        )
+        .await
+    }
+
+    /// Whether an upload of `mime_base` is streamed into place rather than
+    /// assembled in memory.
+    fn streams(&self, mime_base: &str) -> bool {
+        matches!(mime_base, "application/pdf" | "application/octet-stream")
+            && !self.storage.seals_objects()
+    }
+
+    fn max_upload_size(&self, mime_base: &str) -> u64 {
+        if self.streams(mime_base) {
+            MAX_UPLOAD_SIZE
+        } else {
+            MAX_BUFFERED_UPLOAD_SIZE
+        }
+    }
+
+    async fn upload_session(
+        &self,
+        upload_id: Uuid,
+        user_id: Uuid,
+    ) -> AssetResult<be_remote_db::UploadSession> {
+        self.db
+            .get_upload_session()
+            .id(upload_id)
+            .user_id(user_id)
+            .call()
+            .await
+            .map_err(|e| {
+                if e.is_not_found() {
+                    AssetError::UploadNotFound
+                } else {
+                    AssetError::DatabaseRead(e)
+                }
+            })
+    }
+
+    async fn upload_chunks(&self, upload_id: Uuid) -> AssetResult<Vec<UploadChunk>> {
+        self.db
+            .list_upload_chunks(upload_id)
+            .await
+            .map_err(AssetError::DatabaseRead)
+    }
+
+    /// Best effort: a chunk object left behind only costs storage.
+    async fn delete_chunk_objects(&self, upload_id: Uuid, uris: &[String]) {
+        for uri in uris {
+            if let Err(e) = self.storage.delete(uri).await {
+                tracing::warn!(%upload_id, uri, error = %e, "Failed to delete upload chunk");
+            }
+        }
+    }
+}
+
+fn next_expiry() -> DateTime<Utc> {
+    Utc::now() + Duration::seconds(i64::from(UPLOAD_SESSION_TTL_SECS))
+}
+
+/// Length the chunk at `offset` must have, or why there is no chunk
+/// there.
+fn expected_chunk_len(session: &be_remote_db::UploadSession, offset: u64) -> AssetResult<u64> {
+    let (total, chunk_size) = (session.total_size as u64, session.chunk_size as u64);
+    if offset >= total {
+        return Err(AssetError::InvalidChunk(format!(
+            "offset {offset} is past the end of the {total}-byte upload"
+        )));
+    }
+    if !offset.is_multiple_of(chunk_size) {
+        return Err(AssetError::InvalidChunk(format!(
+            "offset {offset} is not a multiple of the {chunk_size}-byte chunk size"
+        )));
+    }
+    Ok(chunk_size.min(total - offset))
+}
+
+/// Chunks a complete upload has. Every recorded chunk passed
+/// [`expected_chunk_len`], so counting them is enough.
+fn chunk_count(session: &be_remote_db::UploadSession) -> usize {
+    (session.total_size as u64).div_ceil(session.chunk_size as u64) as usize
+}
+
+fn session_to_dto(session: be_remote_db::UploadSession, chunks: &[UploadChunk]) -> UploadSession {
+    UploadSession {
+        id: session.id,
+        name: session.name,
+        mime_type: session.mime_type,
+        size: session.total_size as u64,
+        chunk_size: session.chunk_size as u64,
+        received_offsets: chunks.iter().map(|c| c.byte_offset as u64).collect(),
+        expires_at: session.expires_at,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(total_size: i64, chunk_size: i64) -> be_remote_db::UploadSession {
+        be_remote_db::UploadSession {
+            id: Uuid::now_v7(),
+            user_id: Uuid::now_v7(),
+            name: "recording.webm".into(),
+            mime_type: "application/octet-stream".into(),
+            metadata: None,
+            device_id: None,
+            total_size,
+            chunk_size,
+            expires_at: next_expiry(),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn chunks_start_on_boundaries_and_the_last_runs_to_the_end() {
+        let session = session(10, 4);
+        assert_eq!(expected_chunk_len(&session, 0).unwrap(), 4);
+        assert_eq!(expected_chunk_len(&session, 4).unwrap(), 4);
+        assert_eq!(expected_chunk_len(&session, 8).unwrap(), 2);
+        assert!(expected_chunk_len(&session, 2).is_err());
+        assert!(expected_chunk_len(&session, 12).is_err());
+        assert_eq!(chunk_count(&session), 3);
+        assert_eq!(chunk_count(&self::session(8, 4)), 2);
+    }
+}
@@ -40,7 +40,7 @@ impl Harness {
             })
             .build()
             .expect("build storage");
-        let db = Arc::new(DatabaseManager { pool: pool.clone() });
+        let db = Arc::new(DatabaseManager::from_pool(pool.clone()));
         Self {
             service: AssetService::new(db, Arc::new(storage)),
             db_pool: pool,
@@ -66,6 +66,7 @@ fn png_input() -> CreateAssetInput {
         content: PNG_BYTES.to_vec(),
         mime_type: "image/png".into(),
         metadata: None,
+        device_id: None,
     }
 }
 
@@ -159,6 +160,43 @@ async fn storage_miss_collapses_to_not_found(pool: PgPool) {
     assert!(matches!(err, AssetError::NotFound), "got {err:?}");
 }
 
+#[sqlx::test(migrations = "../be-remote-db/src/migrations")]
+async fn corrupted_blob_fails_its_checksum_and_is_quarantined(pool: PgPool) {
+    let Harness {
+        service,
+        db_pool,
+        _storage_root,
+    } = Harness::new(pool);
+    let service = service.with_corruption_quarantine(true);
+    let user_id = seed_user(&db_pool).await;
+
+    let created = service
+        .create_asset(png_input(), user_id)
+        .await
+        .expect("create_asset");
+
+    // Flip the stored bytes behind the row's back.
+    let mut corrupted = PNG_BYTES.to_vec();
+    *corrupted.last_mut().unwrap() ^= 0xFF;
+    service
+        .storage()
+        .write(&storage_uri_for(&db_pool, created.id).await, &corrupted)
+        .await
+        .expect("overwrite blob");
+
+    let err = service
+        .get_asset_bytes(created.id, user_id)
+        .await
+        .expect_err("expected Corrupted");
+    assert!(matches!(err, AssetError::Corrupted), "got {err:?}");
+
+    let err = service
+        .get_asset_bytes(created.id, user_id)
+        .await
+        .expect_err("expected Quarantined");
+    assert!(matches!(err, AssetError::Quarantined), "got {err:?}");
+}
+
 async fn storage_uri_for(pool: &PgPool, asset_id: Uuid) -> String {
     sqlx::query_scalar::<_, String>("SELECT storage_uri FROM assets WHERE id = $1")
         .bind(asset_id)
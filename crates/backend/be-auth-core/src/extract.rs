@@ -90,6 +90,9 @@ mod tests {
             aud: "eurora".to_string(),
             email_verified: true,
             jti: "jti".to_string(),
+            analytics: Default::default(),
+            token_version: 0,
+            act: None,
         }
     }
 
@@ -0,0 +1,343 @@
+//! Signing keys for access and refresh tokens.
+//!
+//! Each token kind has its own [`JwtKeyRing`]. Every key on the ring is
+//! accepted when validating, the *primary* key signs new tokens, and its
+//! `kid` goes into the JWT header so validation picks the right key
+//! without trial decoding. Keys are declared in the environment the same
+//! way as `PKCE_ENCRYPTION_KEY` in `be-auth-service`:
+//!
+//! - `<PREFIX>`: the original single key, version `0`. Tokens minted
+//!   before `kid` headers existed carry none and validate against it.
+//! - `<PREFIX>_V_<N>`: additional keys, version `N` (kid `v<N>`).
+//! - `<PREFIX>_PRIMARY`: the version that signs. Defaults to the highest.
+//!
+//! `<PREFIX>` is `JWT_ACCESS_SECRET` / `JWT_REFRESH_SECRET` for HS256.
+//! With `JWT_ACCESS_ALGORITHM=RS256`, access tokens are signed with the
+//! PEM-encoded RSA keys in `JWT_ACCESS_PRIVATE_KEY[_V_<N>]` instead, and
+//! their public halves are served as a JWKS so other services can
+//! validate tokens without holding a secret. Refresh tokens never leave
+//! the auth service and stay HS256.
+//!
+//! Rotation is staged. First deploy the new key as `_V_<N+1>` while the
+//! old key stays primary, so every replica and every JWKS consumer
+//! accepts the new key before any token uses it. Then promote it, either
+//! with `_PRIMARY` on the next deploy or live through
+//! [`JwtKeyRing::promote_newest`]. Drop the old key once the
+//! longest-lived token it signed has expired.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::{Result, anyhow};
+use jsonwebtoken::jwk::{Jwk, JwkSet};
+use jsonwebtoken::{
+    Algorithm, DecodingKey, EncodingKey, Header, TokenData, Validation, decode, decode_header,
+    encode,
+};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::JwtConfigError;
+
+/// Selects the access-token algorithm: `HS256` (default) or `RS256`.
+pub const ENV_JWT_ACCESS_ALGORITHM: &str = "JWT_ACCESS_ALGORITHM";
+
+/// Trailing underscore keeps e.g. `JWT_ACCESS_SECRET_PRIMARY` out of the
+/// versioned set.
+const VERSIONED_SUFFIX: &str = "_V_";
+const PRIMARY_SUFFIX: &str = "_PRIMARY";
+
+struct JwtKey {
+    kid: String,
+    version: u8,
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+    /// Public half, for asymmetric keys only.
+    public_jwk: Option<Jwk>,
+}
+
+/// The keys for one token kind. Shared between clones of
+/// [`JwtConfig`](crate::JwtConfig), so a promotion is seen by every
+/// consumer in the process at once.
+pub struct JwtKeyRing {
+    algorithm: Algorithm,
+    /// Sorted by version, ascending.
+    keys: Vec<JwtKey>,
+    /// Index into `keys`.
+    primary: AtomicUsize,
+}
+
+impl JwtKeyRing {
+    /// A ring holding one HS256 key, version `0`.
+    pub fn hs256(secret: &[u8]) -> Self {
+        Self {
+            algorithm: Algorithm::HS256,
+            keys: vec![hs256_key(0, secret)],
+            primary: AtomicUsize::new(0),
+        }
+    }
+
+    /// Load a ring from `<prefix>`, `<prefix>_V_<N>` and
+    /// `<prefix>_PRIMARY`. `prefix` names an HS256 secret, or a PEM
+    /// private key when `algorithm` is `RS256`.
+    pub fn from_env(prefix: &'static str, algorithm: Algorithm) -> Result<Self, JwtConfigError> {
+        let mut declared: Vec<(u8, String, String)> = Vec::new();
+        if let Some(value) = non_empty_var(prefix) {
+            declared.push((0, prefix.to_owned(), value));
+        }
+        let versioned_prefix = format!("{prefix}{VERSIONED_SUFFIX}");
+        for (name, value) in std::env::vars() {
+            let Some(suffix) = name.strip_prefix(&versioned_prefix) else {
+                continue;
+            };
+            let version = suffix
+                .parse()
+                .map_err(|_| JwtConfigError::InvalidKeyVersion { name: name.clone() })?;
+            if !value.is_empty() {
+                declared.push((version, name, value));
+            }
+        }
+        if declared.is_empty() {
+            return Err(JwtConfigError::MissingSecret { name: prefix });
+        }
+        declared.sort_by_key(|(version, ..)| *version);
+        if let Some(pair) = declared.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+            return Err(JwtConfigError::InvalidKeyVersion {
+                name: pair[1].1.clone(),
+            });
+        }
+
+        let keys = declared
+            .into_iter()
+            .map(|(version, name, value)| match algorithm {
+                Algorithm::HS256 => Ok(hs256_key(version, value.as_bytes())),
+                Algorithm::RS256 => rs256_key(version, &value)
+                    .map_err(|reason| JwtConfigError::InvalidKey { name, reason }),
+                other => Err(JwtConfigError::UnsupportedAlgorithm {
+                    value: format!("{other:?}"),
+                }),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let primary_name = format!("{prefix}{PRIMARY_SUFFIX}");
+        let primary = match non_empty_var(&primary_name) {
+            Some(value) => {
+                let Ok(version) = value.trim().parse::<u8>() else {
+                    return Err(JwtConfigError::InvalidKeyVersion { name: primary_name });
+                };
+                let Some(index) = keys.iter().position(|key| key.version == version) else {
+                    return Err(JwtConfigError::PrimaryKeyMissing {
+                        name: primary_name,
+                        version,
+                    });
+                };
+                index
+            }
+            None => keys.len() - 1,
+        };
+
+        Ok(Self {
+            algorithm,
+            keys,
+            primary: AtomicUsize::new(primary),
+        })
+    }
+
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// `kid` of the key that signs new tokens.
+    pub fn primary_kid(&self) -> &str {
+        &self.primary_key().kid
+    }
+
+    /// Every `kid` accepted for validation, oldest first.
+    pub fn kids(&self) -> impl Iterator<Item = &str> {
+        self.keys.iter().map(|key| key.kid.as_str())
+    }
+
+    /// Make the highest-versioned key primary and return its `kid`. A
+    /// no-op when it already is. Only affects this process; other
+    /// replicas keep signing with their own primary until they are
+    /// redeployed with `_PRIMARY` updated, and their tokens stay valid
+    /// here because every key on the ring is accepted.
+    pub fn promote_newest(&self) -> &str {
+        let newest = self.keys.len() - 1;
+        self.primary.store(newest, Ordering::Relaxed);
+        &self.keys[newest].kid
+    }
+
+    /// Sign `claims` with the primary key, stamping its `kid`.
+    pub fn encode<T: Serialize>(&self, claims: &T) -> jsonwebtoken::errors::Result<String> {
+        let key = self.primary_key();
+        let mut header = Header::new(self.algorithm);
+        header.kid = Some(key.kid.clone());
+        encode(&header, claims, &key.encoding)
+    }
+
+    /// Verify `token` against the key its header names and decode its
+    /// claims. `validation` supplies everything except the algorithm,
+    /// which is pinned to the ring's so a token can't pick its own.
+    pub fn decode<T: DeserializeOwned>(
+        &self,
+        token: &str,
+        validation: &Validation,
+    ) -> Result<TokenData<T>> {
+        let header = decode_header(token).map_err(|e| anyhow!("Invalid token: {}", e))?;
+        let key = match header.kid.as_deref() {
+            Some(kid) => self.keys.iter().find(|key| key.kid == kid),
+            None => self.keys.iter().find(|key| key.version == 0),
+        }
+        .ok_or_else(|| anyhow!("Invalid token: unknown signing key"))?;
+
+        let mut validation = validation.clone();
+        validation.algorithms = vec![self.algorithm];
+        decode::<T>(token, &key.decoding, &validation).map_err(|e| anyhow!("Invalid token: {}", e))
+    }
+
+    /// Public keys for `GET /auth/.well-known/jwks.json`. Empty for
+    /// HS256 rings, whose keys are secrets.
+    pub fn jwks(&self) -> JwkSet {
+        JwkSet {
+            keys: self
+                .keys
+                .iter()
+                .filter_map(|key| key.public_jwk.clone())
+                .collect(),
+        }
+    }
+
+    fn primary_key(&self) -> &JwtKey {
+        &self.keys[self.primary.load(Ordering::Relaxed)]
+    }
+}
+
+impl std::fmt::Debug for JwtKeyRing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JwtKeyRing")
+            .field("algorithm", &self.algorithm)
+            .field("kids", &self.kids().collect::<Vec<_>>())
+            .field("primary", &self.primary_kid())
+            .finish()
+    }
+}
+
+fn kid(version: u8) -> String {
+    format!("v{version}")
+}
+
+fn hs256_key(version: u8, secret: &[u8]) -> JwtKey {
+    JwtKey {
+        kid: kid(version),
+        version,
+        encoding: EncodingKey::from_secret(secret),
+        decoding: DecodingKey::from_secret(secret),
+        public_jwk: None,
+    }
+}
+
+fn rs256_key(version: u8, pem: &str) -> Result<JwtKey, String> {
+    // Multi-line values are awkward in most secret stores; accept
+    // escaped newlines as well.
+    let pem = pem.replace("\\n", "\n");
+    let encoding = EncodingKey::from_rsa_pem(pem.as_bytes()).map_err(|e| e.to_string())?;
+    let mut jwk = Jwk::from_encoding_key(&encoding, Algorithm::RS256).map_err(|e| e.to_string())?;
+    jwk.common.key_id = Some(kid(version));
+    let decoding = DecodingKey::from_jwk(&jwk).map_err(|e| e.to_string())?;
+    Ok(JwtKey {
+        kid: kid(version),
+        version,
+        encoding,
+        decoding,
+        public_jwk: Some(jwk),
+    })
+}
+
+fn non_empty_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, serde::Serialize, serde::Deserialize, PartialEq)]
+    struct TestClaims {
+        sub: String,
+        exp: i64,
+    }
+
+    fn claims() -> TestClaims {
+        TestClaims {
+            sub: "user".into(),
+            exp: 4_102_444_800,
+        }
+    }
+
+    fn validation() -> Validation {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.required_spec_claims.clear();
+        validation
+    }
+
+    fn two_key_ring() -> JwtKeyRing {
+        JwtKeyRing {
+            algorithm: Algorithm::HS256,
+            keys: vec![hs256_key(0, b"old-secret"), hs256_key(1, b"new-secret")],
+            primary: AtomicUsize::new(0),
+        }
+    }
+
+    #[test]
+    fn tokens_carry_the_primary_kid() {
+        let ring = two_key_ring();
+        let token = ring.encode(&claims()).unwrap();
+        assert_eq!(decode_header(&token).unwrap().kid.as_deref(), Some("v0"));
+
+        assert_eq!(ring.promote_newest(), "v1");
+        let token = ring.encode(&claims()).unwrap();
+        assert_eq!(decode_header(&token).unwrap().kid.as_deref(), Some("v1"));
+    }
+
+    #[test]
+    fn every_key_on_the_ring_validates() {
+        let ring = two_key_ring();
+        let old = ring.encode(&claims()).unwrap();
+        ring.promote_newest();
+        let new = ring.encode(&claims()).unwrap();
+        for token in [old, new] {
+            let data = ring.decode::<TestClaims>(&token, &validation()).unwrap();
+            assert_eq!(data.claims, claims());
+        }
+    }
+
+    #[test]
+    fn tokens_without_kid_use_version_zero() {
+        let ring = two_key_ring();
+        let legacy = encode(
+            &Header::new(Algorithm::HS256),
+            &claims(),
+            &EncodingKey::from_secret(b"old-secret"),
+        )
+        .unwrap();
+        assert!(ring.decode::<TestClaims>(&legacy, &validation()).is_ok());
+    }
+
+    #[test]
+    fn unknown_kid_and_wrong_key_are_rejected() {
+        let ring = two_key_ring();
+        let mut header = Header::new(Algorithm::HS256);
+        header.kid = Some("v9".into());
+        let unknown = encode(&header, &claims(), &EncodingKey::from_secret(b"old-secret")).unwrap();
+        assert!(ring.decode::<TestClaims>(&unknown, &validation()).is_err());
+
+        header.kid = Some("v1".into());
+        let forged = encode(&header, &claims(), &EncodingKey::from_secret(b"old-secret")).unwrap();
+        assert!(ring.decode::<TestClaims>(&forged, &validation()).is_err());
+    }
+
+    #[test]
+    fn hs256_rings_publish_no_keys() {
+        assert!(two_key_ring().jwks().keys.is_empty());
+    }
+}
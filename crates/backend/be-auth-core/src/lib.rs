@@ -1,20 +1,22 @@
 mod extract;
+mod keys;
 
 use std::collections::HashSet;
+use std::sync::Arc;
 
 use anyhow::{Result, anyhow};
-use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Validation, decode};
+use jsonwebtoken::{Algorithm, Validation};
 
-pub use auth_core::{Claims, Role};
+pub use auth_core::{AnalyticsConsent, Claims, Role};
 pub use extract::{AuthUser, InvalidUserId, MissingClaims};
+pub use keys::{ENV_JWT_ACCESS_ALGORITHM, JwtKeyRing};
 
 #[derive(Clone)]
 pub struct JwtConfig {
-    pub access_token_encoding_key: EncodingKey,
-    pub access_token_decoding_key: DecodingKey,
-
-    pub refresh_token_encoding_key: EncodingKey,
-    pub refresh_token_decoding_key: DecodingKey,
+    /// Signs and validates access tokens. See [`JwtKeyRing`] for how
+    /// keys are declared and rotated.
+    pub access_keys: Arc<JwtKeyRing>,
+    pub refresh_keys: Arc<JwtKeyRing>,
 
     pub access_token_expiry_hours: i64,
     pub refresh_token_expiry_days: i64,
@@ -36,21 +38,44 @@ pub struct JwtConfig {
 pub enum JwtConfigError {
     #[error("JWT signing secret `{name}` is unset or empty")]
     MissingSecret { name: &'static str },
+
+    #[error("`{name}` does not name a valid key version (0-255)")]
+    InvalidKeyVersion { name: String },
+
+    #[error("`{name}` is {version} but no key with that version is configured")]
+    PrimaryKeyMissing { name: String, version: u8 },
+
+    #[error("JWT signing key `{name}` is invalid: {reason}")]
+    InvalidKey { name: String, reason: String },
+
+    #[error("unsupported `JWT_ACCESS_ALGORITHM` value `{value}` (expected HS256 or RS256)")]
+    UnsupportedAlgorithm { value: String },
 }
 
 impl JwtConfig {
     /// Build [`JwtConfig`] strictly from environment variables.
     ///
-    /// Returns [`JwtConfigError::MissingSecret`] if either
-    /// `JWT_ACCESS_SECRET` or `JWT_REFRESH_SECRET` is unset or blank.
-    /// Callers that want a debug-build placeholder should layer that
-    /// policy on top of this constructor — the goal here is to keep the
-    /// secret-loading path obvious in code, not bury the fallback behind
-    /// a convenience trait.
+    /// Returns [`JwtConfigError::MissingSecret`] if no access or no
+    /// refresh key is declared — neither `JWT_ACCESS_SECRET` nor any
+    /// `JWT_ACCESS_SECRET_V_<N>` (`JWT_ACCESS_PRIVATE_KEY*` in RS256
+    /// mode), likewise for `JWT_REFRESH_SECRET`. Callers that want a
+    /// debug-build placeholder should layer that policy on top of this
+    /// constructor — the goal here is to keep the secret-loading path
+    /// obvious in code, not bury the fallback behind a convenience trait.
     pub fn try_from_env() -> std::result::Result<Self, JwtConfigError> {
-        let access_secret = require_secret("JWT_ACCESS_SECRET")?;
-        let refresh_secret = require_secret("JWT_REFRESH_SECRET")?;
-        Ok(Self::from_secrets(&access_secret, &refresh_secret))
+        let access_keys = match std::env::var(ENV_JWT_ACCESS_ALGORITHM).as_deref() {
+            Err(_) | Ok("") | Ok("HS256") => {
+                JwtKeyRing::from_env("JWT_ACCESS_SECRET", Algorithm::HS256)?
+            }
+            Ok("RS256") => JwtKeyRing::from_env("JWT_ACCESS_PRIVATE_KEY", Algorithm::RS256)?,
+            Ok(other) => {
+                return Err(JwtConfigError::UnsupportedAlgorithm {
+                    value: other.to_owned(),
+                });
+            }
+        };
+        let refresh_keys = JwtKeyRing::from_env("JWT_REFRESH_SECRET", Algorithm::HS256)?;
+        Ok(Self::from_key_rings(access_keys, refresh_keys))
     }
 
     /// Construct a [`JwtConfig`] from explicit secrets. Used both by
@@ -58,15 +83,21 @@ impl JwtConfig {
     /// the secrets from elsewhere (e.g. a debug-build fallback to stable
     /// placeholders).
     pub fn from_secrets(access_secret: &str, refresh_secret: &str) -> Self {
+        Self::from_key_rings(
+            JwtKeyRing::hs256(access_secret.as_bytes()),
+            JwtKeyRing::hs256(refresh_secret.as_bytes()),
+        )
+    }
+
+    fn from_key_rings(access_keys: JwtKeyRing, refresh_keys: JwtKeyRing) -> Self {
         Self {
-            access_token_encoding_key: EncodingKey::from_secret(access_secret.as_bytes()),
-            access_token_decoding_key: DecodingKey::from_secret(access_secret.as_bytes()),
-            refresh_token_encoding_key: EncodingKey::from_secret(refresh_secret.as_bytes()),
-            refresh_token_decoding_key: DecodingKey::from_secret(refresh_secret.as_bytes()),
+            access_keys: Arc::new(access_keys),
+            refresh_keys: Arc::new(refresh_keys),
 
             access_token_expiry_hours: 1,
             refresh_token_expiry_days: 7,
 
+            // The algorithm is pinned per key ring at decode time.
             validation: {
                 let mut v = Validation::new(Algorithm::HS256);
                 v.set_audience(&["eurora"]);
@@ -84,17 +115,9 @@ impl JwtConfig {
     }
 }
 
-fn require_secret(name: &'static str) -> std::result::Result<String, JwtConfigError> {
-    match std::env::var(name) {
-        Ok(v) if !v.is_empty() => Ok(v),
-        _ => Err(JwtConfigError::MissingSecret { name }),
-    }
-}
-
 impl JwtConfig {
     pub fn validate_access_token(&self, token: &str) -> Result<Claims> {
-        let token_data = decode::<Claims>(token, &self.access_token_decoding_key, &self.validation)
-            .map_err(|e| anyhow!("Invalid token: {}", e))?;
+        let token_data = self.access_keys.decode::<Claims>(token, &self.validation)?;
 
         if token_data.claims.token_type != "access" {
             return Err(anyhow!("Invalid token type: expected access token"));
@@ -104,9 +127,9 @@ impl JwtConfig {
     }
 
     pub fn validate_refresh_token(&self, token: &str) -> Result<Claims> {
-        let token_data =
-            decode::<Claims>(token, &self.refresh_token_decoding_key, &self.validation)
-                .map_err(|e| anyhow!("Invalid token: {}", e))?;
+        let token_data = self
+            .refresh_keys
+            .decode::<Claims>(token, &self.validation)?;
 
         if token_data.claims.token_type != "refresh" {
             return Err(anyhow!("Invalid token type: expected refresh token"));
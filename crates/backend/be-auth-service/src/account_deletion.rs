@@ -0,0 +1,107 @@
+//! Account deletion requests and the lock they put on the account.
+//!
+//! `DELETE /auth/account` schedules the deletion one grace period out and
+//! revokes every refresh token in the same transaction. From then on the
+//! account is locked: every session mint goes through
+//! [`AuthService::ensure_account_active`] and is refused with
+//! [`AuthError::AccountPendingDeletion`]. Access tokens already handed out
+//! stay valid until they expire (`access_token_expiry_hours`), which is
+//! far shorter than any grace period.
+//!
+//! Erasing the data once the grace period is over is the
+//! `be-account-deletion` worker's job; it also writes the rest of the
+//! audit trail and sends the confirmation email.
+
+use anyhow::{Context, bail};
+use be_remote_db::AccountDeletion;
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::DEFAULT_ACCOUNT_DELETION_GRACE_DAYS;
+use crate::error::{AuthError, AuthResult};
+use crate::service::AuthService;
+
+/// Overrides [`DEFAULT_ACCOUNT_DELETION_GRACE_DAYS`]. Whole days, at
+/// least one.
+pub const ENV_ACCOUNT_DELETION_GRACE_DAYS: &str = "ACCOUNT_DELETION_GRACE_DAYS";
+
+pub(crate) fn grace_period_from_env() -> anyhow::Result<Duration> {
+    let raw = std::env::var(ENV_ACCOUNT_DELETION_GRACE_DAYS)
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty());
+    parse_grace_days(raw.as_deref())
+}
+
+fn parse_grace_days(raw: Option<&str>) -> anyhow::Result<Duration> {
+    let Some(raw) = raw else {
+        return Ok(Duration::days(DEFAULT_ACCOUNT_DELETION_GRACE_DAYS));
+    };
+    let days: i64 = raw
+        .parse()
+        .with_context(|| format!("invalid `{ENV_ACCOUNT_DELETION_GRACE_DAYS}` value `{raw}`"))?;
+    if days < 1 {
+        bail!("`{ENV_ACCOUNT_DELETION_GRACE_DAYS}` must be at least 1, got {days}");
+    }
+    Ok(Duration::days(days))
+}
+
+impl AuthService {
+    /// Schedule the user's account for deletion and revoke their
+    /// sessions. Idempotent: asking again while a deletion is open returns
+    /// the existing one, with its original date.
+    pub async fn request_account_deletion(&self, user_id: Uuid) -> AuthResult<AccountDeletion> {
+        let user = self.db().get_user().id(user_id).call().await.map_err(|e| {
+            if e.is_not_found() {
+                AuthError::InvalidToken
+            } else {
+                AuthError::Database(e)
+            }
+        })?;
+
+        let deletion = self
+            .db()
+            .schedule_account_deletion()
+            .user_id(user.id)
+            .email(user.email)
+            .scheduled_for(Utc::now() + self.account_deletion_grace())
+            .call()
+            .await?;
+        Ok(deletion)
+    }
+
+    /// Refuse to go on for an account that is scheduled for deletion.
+    pub(crate) async fn ensure_account_active(&self, user_id: Uuid) -> AuthResult<()> {
+        let open = self
+            .db()
+            .get_open_account_deletion()
+            .user_id(user_id)
+            .call()
+            .await?;
+        match open {
+            Some(_) => Err(AuthError::AccountPendingDeletion),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grace_days_default_and_override() {
+        assert_eq!(
+            parse_grace_days(None).unwrap(),
+            Duration::days(DEFAULT_ACCOUNT_DELETION_GRACE_DAYS)
+        );
+        assert_eq!(parse_grace_days(Some("30")).unwrap(), Duration::days(30));
+    }
+
+    #[test]
+    fn grace_days_rejects_garbage_and_zero() {
+        assert!(parse_grace_days(Some("two weeks")).is_err());
+        assert!(parse_grace_days(Some("0")).is_err());
+        assert!(parse_grace_days(Some("-3")).is_err());
+    }
+}
@@ -0,0 +1,41 @@
+//! Per-user product analytics consent.
+//!
+//! The choice is stored on the user row and copied into every JWT at mint
+//! time (see [`crate::tokens::generate_jwt_pair`]), which is how the other
+//! services learn it: `be-analytics` reads it off the caller's claims and
+//! drops or pseudonymises events accordingly. Changing it mints a fresh
+//! session so the new choice applies to the very next request instead of
+//! waiting for the access token to expire.
+
+use auth_core::AnalyticsConsent;
+use uuid::Uuid;
+
+use crate::error::{AuthError, AuthResult};
+use crate::service::{AuthService, MintedSession};
+
+impl AuthService {
+    pub async fn update_analytics_consent(
+        &self,
+        user_id: Uuid,
+        consent: AnalyticsConsent,
+    ) -> AuthResult<MintedSession> {
+        let user = self
+            .db()
+            .set_analytics_consent()
+            .user_id(user_id)
+            .consent(consent.into())
+            .call()
+            .await
+            .map_err(|e| {
+                if e.is_not_found() {
+                    AuthError::InvalidToken
+                } else {
+                    AuthError::Database(e)
+                }
+            })?;
+
+        let role = self.resolve_role(user.id).await?;
+        tracing::info!(user_id = %user.id, consent = consent.as_str(), "Analytics consent updated");
+        self.mint_session(&user, role).await
+    }
+}
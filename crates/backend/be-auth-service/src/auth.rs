@@ -6,7 +6,11 @@
 //!
 //! - [`AccessClaims`]: routes that need a valid **access** token. Looks
 //!   for `Authorization: Bearer …` first (desktop / mobile) and falls
-//!   back to the `eu_access` cookie (browser SPA).
+//!   back to the `eu_access` cookie (browser SPA). Impersonation
+//!   tokens are refused: support staff acting as a user must not be able
+//!   to change the user's credentials, consent, or account. So are
+//!   tokens minted before the user last signed out everywhere (their
+//!   `token_version` is behind the stored one).
 //! - [`RefreshClaims`]: routes that need a valid **refresh** token,
 //!   either as `Authorization: Bearer …` or as the `eu_refresh`
 //!   cookie. Carries the raw token alongside the parsed claims so the
@@ -21,6 +25,7 @@ use auth_core::Claims;
 use axum::extract::FromRequestParts;
 use axum::http::request::Parts;
 use axum_extra::extract::cookie::CookieJar;
+use uuid::Uuid;
 
 use crate::cookies::{ACCESS_COOKIE, REFRESH_COOKIE};
 use crate::error::AuthError;
@@ -62,6 +67,40 @@ impl FromRequestParts<Arc<AppState>> for AccessClaims {
                 );
                 AuthError::InvalidToken
             })?;
+        if let Some(actor) = &claims.act {
+            tracing::warn!(
+                %path,
+                sub = %claims.sub,
+                actor = %actor.sub,
+                "AccessClaims: impersonation token refused"
+            );
+            return Err(AuthError::Impersonated);
+        }
+        let user_id = Uuid::parse_str(&claims.sub).map_err(|_| AuthError::InvalidToken)?;
+        let current = state
+            .auth
+            .db()
+            .get_token_version()
+            .user_id(user_id)
+            .call()
+            .await
+            .map_err(|e| {
+                if e.is_not_found() {
+                    AuthError::InvalidToken
+                } else {
+                    AuthError::Database(e)
+                }
+            })?;
+        if claims.token_version < current {
+            tracing::warn!(
+                %path,
+                sub = %claims.sub,
+                token_version = claims.token_version,
+                current,
+                "AccessClaims: revoked token refused"
+            );
+            return Err(AuthError::InvalidToken);
+        }
         tracing::debug!(%path, sub = %claims.sub, "AccessClaims: validated");
         Ok(AccessClaims(claims))
     }
@@ -0,0 +1,73 @@
+//! `GET /auth/google/calendar` — current and upcoming events from the
+//! caller's primary Google Calendar, for meeting-prep chat context.
+//!
+//! Uses the access token stored at Google sign-in, refreshed through
+//! [`crate::google_tokens`] as needed. The endpoint only works for users
+//! who signed in with Google after `GOOGLE_CALENDAR_SCOPE` was enabled
+//! and haven't revoked the grant since; everyone else gets
+//! [`AuthError::CalendarNotAuthorized`] and the client simply omits the
+//! calendar context.
+
+use be_remote_db::OAuthProvider;
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::error::{AuthError, AuthResult};
+use crate::oauth::google::CALENDAR_READONLY_SCOPE;
+use crate::oauth::google::calendar::CalendarContext;
+use crate::service::AuthService;
+
+/// How far ahead to look for upcoming events.
+const CALENDAR_WINDOW_HOURS: i64 = 24;
+
+impl AuthService {
+    pub async fn calendar_context(&self, user_id: Uuid) -> AuthResult<CalendarContext> {
+        let google = self.google_oauth()?;
+        if !google.calendar_scope_enabled() {
+            return Err(AuthError::CalendarNotAuthorized);
+        }
+
+        let creds = match self
+            .db()
+            .get_oauth_credentials_by_provider_and_user()
+            .provider(OAuthProvider::Google)
+            .user_id(user_id)
+            .call()
+            .await
+        {
+            Ok(creds) => creds,
+            Err(e) if e.is_not_found() => return Err(AuthError::CalendarNotAuthorized),
+            Err(e) => return Err(AuthError::Database(e)),
+        };
+        if !has_calendar_scope(creds.scope.as_deref()) {
+            return Err(AuthError::CalendarNotAuthorized);
+        }
+
+        let Some(access_token) = self.google_access_token(&creds).await? else {
+            return Err(AuthError::CalendarNotAuthorized);
+        };
+        let now = Utc::now();
+        let events = google
+            .calendar_events(&access_token, now, Duration::hours(CALENDAR_WINDOW_HOURS))
+            .await?;
+        Ok(CalendarContext::from_events(now, events))
+    }
+}
+
+fn has_calendar_scope(scope: Option<&str>) -> bool {
+    scope.is_some_and(|s| s.split_whitespace().any(|s| s == CALENDAR_READONLY_SCOPE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calendar_scope_must_be_granted() {
+        assert!(!has_calendar_scope(None));
+        assert!(!has_calendar_scope(Some("openid email profile")));
+        assert!(has_calendar_scope(Some(
+            "openid email https://www.googleapis.com/auth/calendar.readonly profile"
+        )));
+    }
+}
@@ -61,6 +61,31 @@ pub enum AuthError {
     #[error("Email is already verified")]
     EmailAlreadyVerified,
 
+    /// The caller has no Google credentials carrying the calendar
+    /// scope (signed in another way, or before the scope was enabled).
+    #[error("Google Calendar access has not been granted")]
+    CalendarNotAuthorized,
+
+    /// The account is scheduled for deletion; see
+    /// [`crate::AuthService::request_account_deletion`].
+    #[error("This account is scheduled for deletion")]
+    AccountPendingDeletion,
+
+    /// The caller holds an impersonation token, which is refused by every
+    /// `/auth/*` route; see [`crate::impersonation`].
+    #[error("Not available while impersonating a user")]
+    Impersonated,
+
+    /// `/auth/login-token/wait` timed out before the challenge was
+    /// associated; the client should simply ask again.
+    #[error("Sign-in has not completed yet")]
+    LoginTokenPending,
+
+    /// This instance is already holding as many
+    /// `/auth/login-token/wait` requests as it will.
+    #[error("Too many sign-ins waiting, try again shortly")]
+    TooManyLoginWaiters,
+
     #[error("Password hashing failed: {0}")]
     PasswordHash(String),
 
@@ -88,9 +113,15 @@ impl AuthError {
             | AuthError::MissingAuthHeader
             | AuthError::InvalidAuthHeader
             | AuthError::InvalidToken => StatusCode::UNAUTHORIZED,
-            AuthError::EmailNotVerified => StatusCode::FORBIDDEN,
+            AuthError::EmailNotVerified
+            | AuthError::CalendarNotAuthorized
+            | AuthError::AccountPendingDeletion
+            | AuthError::Impersonated => StatusCode::FORBIDDEN,
             AuthError::EmailAlreadyVerified | AuthError::OAuthEmailConflict => StatusCode::CONFLICT,
-            AuthError::VerificationResendCooldown => StatusCode::TOO_MANY_REQUESTS,
+            AuthError::VerificationResendCooldown | AuthError::TooManyLoginWaiters => {
+                StatusCode::TOO_MANY_REQUESTS
+            }
+            AuthError::LoginTokenPending => StatusCode::REQUEST_TIMEOUT,
             AuthError::PasswordHash(_)
             | AuthError::TokenGeneration(_)
             | AuthError::Database(_)
@@ -114,8 +145,14 @@ impl AuthError {
             | AuthError::InvalidToken => error_kinds::UNAUTHENTICATED,
             AuthError::EmailNotVerified => error_kinds::EMAIL_NOT_VERIFIED,
             AuthError::EmailAlreadyVerified => "email_already_verified",
+            AuthError::CalendarNotAuthorized => "calendar_not_authorized",
             AuthError::OAuthEmailConflict => error_kinds::OAUTH_EMAIL_CONFLICT,
-            AuthError::VerificationResendCooldown => error_kinds::RATE_LIMITED,
+            AuthError::AccountPendingDeletion => error_kinds::ACCOUNT_PENDING_DELETION,
+            AuthError::Impersonated => error_kinds::IMPERSONATION_FORBIDDEN,
+            AuthError::VerificationResendCooldown | AuthError::TooManyLoginWaiters => {
+                error_kinds::RATE_LIMITED
+            }
+            AuthError::LoginTokenPending => error_kinds::LOGIN_TOKEN_PENDING,
             AuthError::PasswordHash(_)
             | AuthError::TokenGeneration(_)
             | AuthError::Database(_)
@@ -167,6 +204,8 @@ impl IntoResponse for AuthError {
                 tracing::warn!("oauth login rejected: email already registered");
             }
             AuthError::EmailNotVerified
+            | AuthError::AccountPendingDeletion
+            | AuthError::Impersonated
             | AuthError::InvalidCredentials
             | AuthError::InvalidToken
             | AuthError::MissingAuthHeader
@@ -176,9 +215,14 @@ impl IntoResponse for AuthError {
             AuthError::MissingCredentials
             | AuthError::InvalidInput(_)
             | AuthError::EmailAlreadyVerified
-            | AuthError::VerificationResendCooldown => {
+            | AuthError::CalendarNotAuthorized
+            | AuthError::VerificationResendCooldown
+            | AuthError::LoginTokenPending => {
                 tracing::debug!(error = %self, "auth-service client error");
             }
+            AuthError::TooManyLoginWaiters => {
+                tracing::warn!("login token wait refused: too many requests held");
+            }
         }
 
         let body = AuthErrorResponse {
@@ -202,6 +246,20 @@ mod tests {
         assert_eq!(err.status(), StatusCode::CONFLICT);
     }
 
+    #[test]
+    fn login_token_pending_maps_to_408_with_kind() {
+        let err = AuthError::LoginTokenPending;
+        assert_eq!(err.error_kind(), "login_token_pending");
+        assert_eq!(err.status(), StatusCode::REQUEST_TIMEOUT);
+    }
+
+    #[test]
+    fn too_many_login_waiters_maps_to_429() {
+        let err = AuthError::TooManyLoginWaiters;
+        assert_eq!(err.error_kind(), "rate_limited");
+        assert_eq!(err.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
     #[test]
     fn invalid_credentials_maps_to_401() {
         let err = AuthError::InvalidCredentials;
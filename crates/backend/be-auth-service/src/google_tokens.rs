@@ -0,0 +1,86 @@
+//! On-demand renewal of stored Google access tokens.
+//!
+//! Google access tokens live for an hour; the refresh token stored at
+//! sign-in keeps working until the user revokes access or Google expires
+//! the grant. [`AuthService::google_access_token`] hands out a usable
+//! access token for an `oauth_credentials` row, refreshing and persisting
+//! it when the stored one is missing or about to expire.
+//!
+//! A refresh refused with `invalid_grant` marks the row as needing
+//! re-auth and drops its dead tokens, so later calls fail without a
+//! round trip to Google. The flag clears the next time sign-in stores a
+//! new refresh token.
+
+use be_remote_db::OAuthCredentials;
+use chrono::{Duration, Utc};
+use secrecy::{ExposeSecret, SecretString};
+
+use crate::crypto::{decrypt_sensitive_string, encrypt_sensitive_string};
+use crate::error::AuthResult;
+use crate::oauth::OAuthError;
+use crate::service::AuthService;
+
+/// Refresh the stored access token when it expires within this margin,
+/// so it can't lapse between the check and the API call it is for.
+const ACCESS_TOKEN_EXPIRY_MARGIN_SECONDS: i64 = 60;
+
+impl AuthService {
+    /// A Google access token for `creds`, or `None` when the user has to
+    /// sign in with Google again first (no refresh token was ever
+    /// stored, or Google has revoked the grant).
+    pub(crate) async fn google_access_token(
+        &self,
+        creds: &OAuthCredentials,
+    ) -> AuthResult<Option<SecretString>> {
+        if creds.needs_reauth {
+            return Ok(None);
+        }
+
+        let fresh_until = Utc::now() + Duration::seconds(ACCESS_TOKEN_EXPIRY_MARGIN_SECONDS);
+        if let (Some(encrypted), Some(expiry)) = (&creds.access_token, creds.access_token_expiry)
+            && expiry > fresh_until
+        {
+            return Ok(Some(SecretString::from(decrypt_sensitive_string(
+                encrypted,
+            )?)));
+        }
+
+        let Some(encrypted_refresh) = &creds.refresh_token else {
+            return Ok(None);
+        };
+        let refresh_token = SecretString::from(decrypt_sensitive_string(encrypted_refresh)?);
+        let (access_token, expires_in) = match self
+            .google_oauth()?
+            .refresh_access_token(&refresh_token)
+            .await
+        {
+            Ok(refreshed) => refreshed,
+            Err(OAuthError::GrantRevoked) => {
+                tracing::info!(
+                    user_id = %creds.user_id,
+                    "Google refused the stored refresh token; marking link for re-auth"
+                );
+                self.db()
+                    .mark_oauth_credentials_needs_reauth()
+                    .id(creds.id)
+                    .call()
+                    .await?;
+                return Ok(None);
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let access_token_expiry = expires_in
+            .and_then(|d| Duration::from_std(d).ok())
+            .map(|d| Utc::now() + d);
+        self.db()
+            .update_oauth_credentials()
+            .id(creds.id)
+            .access_token(encrypt_sensitive_string(access_token.expose_secret())?)
+            .maybe_access_token_expiry(access_token_expiry)
+            .call()
+            .await?;
+
+        Ok(Some(access_token))
+    }
+}
@@ -12,11 +12,12 @@
 use std::sync::Arc;
 
 use auth_core::{
-    AppleIdTokenLoginRequest, AppleNativeUser, AssociateLoginTokenRequest, AuthSuccessResponse,
-    CheckEmailRequest, CheckEmailResponse, GoogleIdTokenLoginRequest, LoginByLoginTokenRequest,
-    LoginRequest, MobileThirdPartyAuthUrlRequest, Provider, RegisterRequest,
-    ThirdPartyAuthUrlRequest, ThirdPartyAuthUrlResponse, TokenResponse, UserResponse,
-    VerifyEmailRequest,
+    AccountDeletionResponse, AppleIdTokenLoginRequest, AppleNativeUser, AssociateLoginTokenRequest,
+    AuthSuccessResponse, CheckEmailRequest, CheckEmailResponse, GoogleIdTokenLoginRequest,
+    ImpersonationsResponse, LoginByLoginTokenRequest, LoginRequest,
+    LoopbackThirdPartyAuthUrlRequest, MobileThirdPartyAuthUrlRequest, Provider, RegisterRequest,
+    ThirdPartyAuthUrlRequest, ThirdPartyAuthUrlResponse, TokenResponse,
+    UpdateAnalyticsConsentRequest, UserResponse, VerifyEmailRequest,
 };
 use axum::{
     Form, Json,
@@ -30,6 +31,9 @@ use uuid::Uuid;
 
 use crate::apple_notifications::{AppleNotificationError, AppleNotificationOutcome};
 use crate::cookies::{self, AuthMode};
+use crate::impersonation::{ImpersonateUserRequest, ImpersonateUserResponse};
+use crate::oauth::google::calendar::CalendarContext;
+use crate::signing_keys::{self, SigningKeysResponse};
 use crate::{
     AppState, AuthResult,
     auth::{AccessClaims, RefreshClaims},
@@ -135,6 +139,101 @@ pub async fn logout(
     Ok((jar, StatusCode::NO_CONTENT))
 }
 
+/// Sign the caller out on every device, this one included.
+#[tracing::instrument(skip_all, fields(user_id))]
+pub async fn logout_all(
+    State(state): State<Arc<AppState>>,
+    jar: CookieJar,
+    AccessClaims(claims): AccessClaims,
+) -> AuthResult<(CookieJar, StatusCode)> {
+    let user_id = Uuid::parse_str(&claims.sub).map_err(|_| AuthError::InvalidToken)?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    let sessions_revoked = state.auth.logout_everywhere(user_id).await?;
+    tracing::info!(sessions_revoked, "Signed out everywhere");
+
+    let jar = cookies::clear_all(&state.cookies, jar);
+    Ok((jar, StatusCode::NO_CONTENT))
+}
+
+/// Schedule the caller's account for deletion. Sessions are revoked
+/// straight away, so browser callers also get their cookies cleared.
+#[tracing::instrument(skip_all, fields(user_id))]
+pub async fn delete_account(
+    State(state): State<Arc<AppState>>,
+    jar: CookieJar,
+    AccessClaims(claims): AccessClaims,
+) -> AuthResult<(CookieJar, StatusCode, Json<AccountDeletionResponse>)> {
+    let user_id = Uuid::parse_str(&claims.sub).map_err(|_| AuthError::InvalidToken)?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    let deletion = state.auth.request_account_deletion(user_id).await?;
+    tracing::info!(
+        deletion_id = %deletion.id,
+        scheduled_for = %deletion.scheduled_for,
+        "Account deletion scheduled",
+    );
+
+    let jar = cookies::clear_all(&state.cookies, jar);
+    Ok((
+        jar,
+        StatusCode::ACCEPTED,
+        Json(AccountDeletionResponse {
+            scheduled_for: deletion.scheduled_for.timestamp(),
+        }),
+    ))
+}
+
+/// Issue an impersonation token for another user. Reachable only with
+/// the `Admin` role.
+#[tracing::instrument(skip_all, fields(admin = %claims.sub, user_id = %body.user_id))]
+pub async fn impersonate_user(
+    State(state): State<Arc<AppState>>,
+    AccessClaims(claims): AccessClaims,
+    Json(body): Json<ImpersonateUserRequest>,
+) -> AuthResult<Json<ImpersonateUserResponse>> {
+    let response = state
+        .auth
+        .impersonate_user(&claims, body.user_id, &body.reason)
+        .await?;
+    Ok(Json(response))
+}
+
+/// Support sessions in which staff acted as the caller, with every
+/// request they made.
+#[tracing::instrument(skip_all)]
+pub async fn list_impersonations(
+    State(state): State<Arc<AppState>>,
+    AccessClaims(claims): AccessClaims,
+) -> AuthResult<Json<ImpersonationsResponse>> {
+    let user_id = Uuid::parse_str(&claims.sub).map_err(|_| AuthError::InvalidToken)?;
+    Ok(Json(state.auth.list_impersonations(user_id).await?))
+}
+
+/// Public keys for validating access tokens. Unauthenticated, like the
+/// rest of `/auth/*`.
+pub async fn jwks(State(state): State<Arc<AppState>>) -> Json<jsonwebtoken::jwk::JwkSet> {
+    Json(state.jwt_config().access_keys.jwks())
+}
+
+pub async fn list_signing_keys(State(state): State<Arc<AppState>>) -> Json<SigningKeysResponse> {
+    Json(state.jwt_config().into())
+}
+
+#[tracing::instrument(skip_all, fields(admin = %claims.sub))]
+pub async fn rotate_signing_keys(
+    State(state): State<Arc<AppState>>,
+    AccessClaims(claims): AccessClaims,
+) -> Json<SigningKeysResponse> {
+    let keys = signing_keys::rotate(state.jwt_config());
+    tracing::info!(
+        access_kid = %keys.access.primary_kid,
+        refresh_kid = %keys.refresh.primary_kid,
+        "JWT signing keys rotated",
+    );
+    Json(keys)
+}
+
 #[tracing::instrument(skip_all)]
 pub async fn me(
     State(_state): State<Arc<AppState>>,
@@ -144,6 +243,15 @@ pub async fn me(
     Ok(Json(UserResponse { user }))
 }
 
+#[tracing::instrument(skip_all)]
+pub async fn google_calendar(
+    State(state): State<Arc<AppState>>,
+    AccessClaims(claims): AccessClaims,
+) -> AuthResult<Json<CalendarContext>> {
+    let user_id = Uuid::parse_str(&claims.sub).map_err(|_| AuthError::InvalidToken)?;
+    Ok(Json(state.auth.calendar_context(user_id).await?))
+}
+
 #[tracing::instrument(skip_all, fields(provider = ?body.provider))]
 pub async fn oauth_url(
     State(state): State<Arc<AppState>>,
@@ -156,6 +264,20 @@ pub async fn oauth_url(
     Ok(Json(ThirdPartyAuthUrlResponse { url }))
 }
 
+/// Desktop loopback OAuth start: the desktop listens on a loopback port
+/// and gets back a provider-authorisation URL that redirects there.
+#[tracing::instrument(skip_all, fields(provider = ?body.provider))]
+pub async fn loopback_oauth_url(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<LoopbackThirdPartyAuthUrlRequest>,
+) -> AuthResult<Json<ThirdPartyAuthUrlResponse>> {
+    let url = state
+        .auth
+        .loopback_third_party_auth_url(body.provider, &body.redirect_uri)
+        .await?;
+    Ok(Json(ThirdPartyAuthUrlResponse { url }))
+}
+
 /// Mobile OAuth start: device generates a PKCE pair locally, sends the
 /// challenge here, gets back the provider-authorisation URL pointing at
 /// the backend's mobile-callback endpoint.
@@ -175,7 +297,8 @@ pub async fn mobile_oauth_url(
     Ok(Json(ThirdPartyAuthUrlResponse { url }))
 }
 
-/// Query params Google / GitHub send to our mobile-callback endpoint.
+/// Query params Google / GitHub / Microsoft send to our mobile-callback
+/// endpoint.
 /// `code` + `state` on success; `error` on user-cancel / provider rejection.
 #[derive(Debug, Deserialize)]
 pub struct MobileOAuthCallbackQuery {
@@ -206,6 +329,7 @@ pub async fn mobile_oauth_callback(
     let provider = match path.provider.as_str() {
         "google" => Provider::Google,
         "github" => Provider::Github,
+        "microsoft" => Provider::Microsoft,
         // Apple is intentionally excluded: its mobile callback uses
         // `response_mode=form_post` (POST, not GET) and is handled by a
         // separate route — see [`apple_mobile_callback`]. Falling
@@ -344,6 +468,18 @@ pub async fn login_token_exchange(
     Ok(Json(session.tokens))
 }
 
+/// Long-poll form of [`login_token_exchange`]: holds the request until
+/// the challenge is associated or the hold elapses, which answers
+/// `408 login_token_pending`. See [`crate::login_token_wait`].
+#[tracing::instrument(skip_all)]
+pub async fn login_token_wait(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<LoginByLoginTokenRequest>,
+) -> AuthResult<Json<TokenResponse>> {
+    let session = state.auth.wait_for_login_token(&body.token).await?;
+    Ok(Json(session.tokens))
+}
+
 #[tracing::instrument(skip_all)]
 pub async fn login_token_associate(
     State(state): State<Arc<AppState>>,
@@ -387,6 +523,26 @@ pub async fn email_verify(
     Ok(session_response(&state, &headers, jar, session))
 }
 
+/// Change the caller's analytics consent. Returns a fresh session whose
+/// claims carry the new choice.
+#[tracing::instrument(skip_all, fields(user_id, consent = body.consent.as_str()))]
+pub async fn update_analytics_consent(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    jar: CookieJar,
+    AccessClaims(claims): AccessClaims,
+    Json(body): Json<UpdateAnalyticsConsentRequest>,
+) -> AuthResult<(CookieJar, Json<AuthSuccessResponse>)> {
+    let user_id = Uuid::parse_str(&claims.sub).map_err(|_| AuthError::InvalidToken)?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    let session = state
+        .auth
+        .update_analytics_consent(user_id, body.consent)
+        .await?;
+    Ok(session_response(&state, &headers, jar, session))
+}
+
 #[tracing::instrument(skip_all)]
 pub async fn email_resend_verification(
     State(state): State<Arc<AppState>>,
@@ -0,0 +1,167 @@
+//! Support impersonation.
+//!
+//! `POST /admin/auth/impersonate` lets an admin act as a user to debug an
+//! issue from the user's side. The guard rails:
+//!
+//! - The admin gets a lone access token, valid for
+//!   [`IMPERSONATION_TOKEN_EXPIRY_MINUTES`], that names them in its `act`
+//!   claim. There is no refresh token to extend it.
+//! - `authz_middleware` refuses `/admin/*` routes to impersonation tokens
+//!   and [`AccessClaims`](crate::auth::AccessClaims) refuses every
+//!   `/auth/*` route, so an impersonating admin can't change the user's
+//!   credentials, consent or account, or impersonate anyone else.
+//! - Every request the token makes is recorded against the session
+//!   (`be-authz`), and the user sees the sessions, their stated reasons
+//!   and those requests at `GET /auth/impersonations`.
+
+use std::collections::HashMap;
+
+use auth_core::{Claims, ImpersonatedRequest, ImpersonationSessionInfo, ImpersonationsResponse};
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::IMPERSONATION_TOKEN_EXPIRY_MINUTES;
+use crate::error::{AuthError, AuthResult};
+use crate::service::AuthService;
+use crate::tokens::generate_impersonation_token;
+
+/// Longest reason accepted, in characters.
+const MAX_REASON_CHARS: usize = 500;
+
+/// Request body for `POST /admin/auth/impersonate`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImpersonateUserRequest {
+    pub user_id: Uuid,
+    /// Shown to the user alongside the session, e.g. the ticket being
+    /// worked on.
+    pub reason: String,
+}
+
+/// Response body for `POST /admin/auth/impersonate`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImpersonateUserResponse {
+    pub session_id: Uuid,
+    /// Bearer access token for the user, with the caller as `act`.
+    pub access_token: String,
+    /// Lifetime of `access_token` in seconds.
+    pub expires_in: i64,
+}
+
+impl AuthService {
+    pub async fn impersonate_user(
+        &self,
+        actor: &Claims,
+        user_id: Uuid,
+        reason: &str,
+    ) -> AuthResult<ImpersonateUserResponse> {
+        if actor.is_impersonated() {
+            return Err(AuthError::Impersonated);
+        }
+        let actor_id = Uuid::parse_str(&actor.sub).map_err(|_| AuthError::InvalidToken)?;
+        if actor_id == user_id {
+            return Err(AuthError::InvalidInput(
+                "Cannot impersonate yourself".to_string(),
+            ));
+        }
+        let reason = validate_reason(reason)?;
+
+        let user = self.db().get_user().id(user_id).call().await.map_err(|e| {
+            if e.is_not_found() {
+                AuthError::InvalidInput("No user with that id".to_string())
+            } else {
+                AuthError::Database(e)
+            }
+        })?;
+        // Also refuses accounts that are scheduled for deletion.
+        let role = self.resolve_role(user.id).await?;
+
+        let ttl = Duration::minutes(IMPERSONATION_TOKEN_EXPIRY_MINUTES);
+        let session = self
+            .db()
+            .create_impersonation_session()
+            .id(Uuid::now_v7())
+            .actor_id(actor_id)
+            .user_id(user.id)
+            .reason(reason)
+            .expires_at(Utc::now() + ttl)
+            .call()
+            .await?;
+        let access_token = generate_impersonation_token(
+            self.jwt_config(),
+            &user,
+            role,
+            actor_id,
+            session.id,
+            session.expires_at,
+        )?;
+
+        tracing::info!(
+            session_id = %session.id,
+            actor_id = %actor_id,
+            user_id = %user.id,
+            reason,
+            "Impersonation session started"
+        );
+        Ok(ImpersonateUserResponse {
+            session_id: session.id,
+            access_token,
+            expires_in: ttl.num_seconds(),
+        })
+    }
+
+    pub async fn list_impersonations(&self, user_id: Uuid) -> AuthResult<ImpersonationsResponse> {
+        let sessions = self.db().list_impersonation_sessions(user_id).await?;
+        let mut requests: HashMap<Uuid, Vec<ImpersonatedRequest>> = HashMap::new();
+        for request in self.db().list_impersonation_requests(user_id).await? {
+            requests
+                .entry(request.session_id)
+                .or_default()
+                .push(ImpersonatedRequest {
+                    method: request.method,
+                    path: request.path,
+                    status: u16::try_from(request.status).unwrap_or_default(),
+                    at: request.created_at.timestamp(),
+                });
+        }
+
+        let sessions = sessions
+            .into_iter()
+            .map(|session| ImpersonationSessionInfo {
+                id: session.id.to_string(),
+                reason: session.reason,
+                started_at: session.created_at.timestamp(),
+                expires_at: session.expires_at.timestamp(),
+                requests: requests.remove(&session.id).unwrap_or_default(),
+            })
+            .collect();
+        Ok(ImpersonationsResponse { sessions })
+    }
+}
+
+fn validate_reason(reason: &str) -> AuthResult<&str> {
+    let reason = reason.trim();
+    if reason.is_empty() {
+        return Err(AuthError::InvalidInput(
+            "A reason is required to impersonate a user".to_string(),
+        ));
+    }
+    if reason.chars().count() > MAX_REASON_CHARS {
+        return Err(AuthError::InvalidInput(format!(
+            "Reason must be at most {MAX_REASON_CHARS} characters"
+        )));
+    }
+    Ok(reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reason_is_required_and_bounded() {
+        assert_eq!(validate_reason("  ticket #42 ").unwrap(), "ticket #42");
+        assert!(validate_reason("   ").is_err());
+        assert!(validate_reason(&"x".repeat(MAX_REASON_CHARS + 1)).is_err());
+    }
+}
@@ -1,8 +1,12 @@
 //! HTTP authentication service.
 //!
 //! Exposes an Axum router under `/auth` that handles email+password and
-//! third-party (Google, GitHub) authentication, refresh-token rotation,
-//! email verification, and the device-pairing login-token flow.
+//! third-party (Google, GitHub, Apple, Microsoft) authentication, refresh-token rotation,
+//! email verification, the device-pairing login-token flow, analytics
+//! consent, account deletion requests, JWT signing keys (see
+//! [`signing_keys`]), and support impersonation (see [`impersonation`]).
+//! [`init_token_cleanup_worker`] spawns the background job that deletes
+//! spent tokens.
 //!
 //! Unlike the activity / asset services, the global `authz_middleware`
 //! bypasses the `/auth/*` prefix entirely so unauthenticated callers
@@ -11,16 +15,22 @@
 //! [`auth::RefreshClaims`] extractors using the shared
 //! [`be_auth_core::JwtConfig`].
 
+mod account_deletion;
+mod analytics_consent;
 pub mod apple_notifications;
 pub mod auth;
+mod calendar;
 pub mod cookies;
 pub mod crypto;
 mod email_check;
 mod email_verification;
 pub mod error;
+mod google_tokens;
 pub mod handlers;
+pub mod impersonation;
 mod log_redaction;
 mod login_token;
+mod login_token_wait;
 pub mod oauth;
 mod oauth_flow;
 mod password_auth;
@@ -28,6 +38,8 @@ mod passwords;
 mod plans;
 mod refresh;
 pub mod service;
+pub mod signing_keys;
+mod token_cleanup;
 mod tokens;
 
 use std::sync::Arc;
@@ -35,16 +47,19 @@ use std::sync::Arc;
 use anyhow::Result;
 use axum::{
     Router,
-    routing::{get, post},
+    routing::{delete, get, post, put},
 };
 use be_auth_core::JwtConfig;
 use be_email_service::EmailService;
 use be_remote_db::DatabaseManager;
 use tower_http::trace::TraceLayer;
 
+pub use account_deletion::ENV_ACCOUNT_DELETION_GRACE_DAYS;
 pub use cookies::{ACCESS_COOKIE, AuthMode, CookieConfig, CookieConfigError, REFRESH_COOKIE};
 pub use error::{AuthError, AuthResult};
+pub use passwords::{hash_password, validate_email, validate_password};
 pub use service::{AppState, AuthService, AuthServiceConfig, build_oauth_clients};
+pub use token_cleanup::{TokenCleanupWorkerHandle, init_token_cleanup_worker};
 
 pub use auth_core::{Claims, Role};
 pub use oauth::{NewOAuthIdentity, OAuthError, OAuthTokenBundle};
@@ -71,6 +86,15 @@ pub(crate) const VERIFICATION_TOKEN_BYTES: usize = 32;
 /// constraint is ever renamed, this constant must be updated to match.
 pub(crate) const USERS_EMAIL_UNIQUE_CONSTRAINT: &str = "users_email_key";
 
+/// Lifetime of a support impersonation token. Long enough to reproduce
+/// an issue, short enough that a leaked token is soon useless; there is
+/// no refresh token to extend it.
+pub(crate) const IMPERSONATION_TOKEN_EXPIRY_MINUTES: i64 = 15;
+
+/// Days between an account deletion request and the data being erased,
+/// unless overridden by [`ENV_ACCOUNT_DELETION_GRACE_DAYS`].
+pub(crate) const DEFAULT_ACCOUNT_DELETION_GRACE_DAYS: i64 = 14;
+
 /// Build the auth router with the supplied dependencies.
 ///
 /// Returns the bare router; the caller is expected to apply the
@@ -83,8 +107,16 @@ pub fn create_router(state: Arc<AppState>) -> Router {
         .route("/auth/register", post(handlers::register))
         .route("/auth/refresh", post(handlers::refresh))
         .route("/auth/logout", post(handlers::logout))
+        .route("/auth/logout/all", post(handlers::logout_all))
         .route("/auth/me", get(handlers::me))
         .route("/auth/oauth/url", post(handlers::oauth_url))
+        // Desktop loopback OAuth (self-hosted, no web SPA): the provider
+        // redirects to a port on the desktop, which then posts the code
+        // to `/auth/login` like the SPA would.
+        .route(
+            "/auth/oauth/loopback/url",
+            post(handlers::loopback_oauth_url),
+        )
         // Mobile OAuth: device hits `/auth/oauth/mobile/url` with its
         // PKCE challenge, opens the returned authorisation URL in an
         // in-app browser, then the provider 302s the user to
@@ -145,16 +177,45 @@ pub fn create_router(state: Arc<AppState>) -> Router {
             "/auth/login-token/exchange",
             post(handlers::login_token_exchange),
         )
+        // Same body and response as `/exchange`, but held until the
+        // web sign-in associates the challenge instead of answering at
+        // once; see [`login_token_wait`].
+        .route("/auth/login-token/wait", post(handlers::login_token_wait))
         .route(
             "/auth/login-token/associate",
             post(handlers::login_token_associate),
         )
+        // Meeting-prep context: current and upcoming events from the
+        // caller's primary Google Calendar. Requires a Google sign-in
+        // that granted the opt-in calendar scope.
+        .route("/auth/google/calendar", get(handlers::google_calendar))
         .route("/auth/email/check", post(handlers::email_check))
         .route("/auth/email/verify", post(handlers::email_verify))
         .route(
             "/auth/email/resend-verification",
             post(handlers::email_resend_verification),
         )
+        // Re-mints the session so the new consent is in the claims
+        // straight away; see [`analytics_consent`].
+        .route(
+            "/auth/analytics-consent",
+            put(handlers::update_analytics_consent),
+        )
+        // Schedules the caller's account for deletion after the grace
+        // period; see [`account_deletion`].
+        .route("/auth/account", delete(handlers::delete_account))
+        // Support sessions in which staff acted as the caller; see
+        // [`impersonation`].
+        .route("/auth/impersonations", get(handlers::list_impersonations))
+        .route("/auth/.well-known/jwks.json", get(handlers::jwks))
+        // Unlike the rest of this router these sit outside `/auth/*`, so
+        // `authz_middleware` checks them against the `Admin` policy.
+        .route("/admin/auth/signing-keys", get(handlers::list_signing_keys))
+        .route(
+            "/admin/auth/signing-keys/rotate",
+            post(handlers::rotate_signing_keys),
+        )
+        .route("/admin/auth/impersonate", post(handlers::impersonate_user))
         .layer(TraceLayer::new_for_http())
         .with_state(state)
 }
@@ -177,7 +238,14 @@ pub async fn init_auth_service(
     // to redirect to). Surface misconfigurations at boot rather than
     // on the first sign-in.
     oauth_clients.validate(&cookie_config)?;
-    let auth = AuthService::new(db, jwt_config, email_service, oauth_clients);
+    let account_deletion_grace = account_deletion::grace_period_from_env()?;
+    let auth = AuthService::new(
+        db,
+        jwt_config,
+        email_service,
+        oauth_clients,
+        account_deletion_grace,
+    );
     let state = Arc::new(AppState::new(auth, cookie_config));
     Ok(create_router(state))
 }
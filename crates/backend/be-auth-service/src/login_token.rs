@@ -2,10 +2,12 @@
 //!
 //! The desktop / CLI client generates a PKCE pair, sends the challenge
 //! to the web app via deep-link, and polls
-//! `/auth/login-token/exchange` with its verifier. Once the web user
-//! signs in, the web client posts to `/auth/login-token/associate`
-//! which links the *challenge* (already in the device's hands) to the
-//! authenticated user. The next poll then completes the device login.
+//! `/auth/login-token/exchange` with its verifier (or holds
+//! `/auth/login-token/wait`, see [`crate::login_token_wait`]). Once the
+//! web user signs in, the web client posts to
+//! `/auth/login-token/associate` which links the *challenge* (already in
+//! the device's hands) to the authenticated user. The next poll then
+//! completes the device login.
 //!
 //! Storing `sha256(challenge)` keeps stolen DB rows from giving an
 //! attacker the verifier.
@@ -60,14 +62,7 @@ impl AuthService {
 
         let role = self.resolve_role(user.id).await?;
 
-        let pair = generate_jwt_pair(
-            self.jwt_config(),
-            user.id,
-            &user.email,
-            user.display_name.clone(),
-            role.clone(),
-            user.email_verified,
-        )?;
+        let pair = generate_jwt_pair(self.jwt_config(), &user, role.clone(), user.email_verified)?;
 
         // Atomic: consume the login-token row and create the
         // refresh-token row in the same transaction.
@@ -110,7 +105,7 @@ impl AuthService {
         let token_hash = sha256_token(code_challenge);
         self.db()
             .create_login_token()
-            .token_hash(token_hash)
+            .token_hash(token_hash.clone())
             .user_id(user_id)
             .expires_at(Utc::now() + Duration::minutes(LOGIN_TOKEN_EXPIRY_MINUTES))
             .call()
@@ -120,6 +115,7 @@ impl AuthService {
                 AuthError::Database(e)
             })?;
 
+        self.login_token_waiters().notify(&token_hash);
         Ok(())
     }
 
@@ -135,7 +131,7 @@ impl AuthService {
         let token_hash = sha256_token(code_challenge);
         self.db()
             .create_login_token()
-            .token_hash(token_hash)
+            .token_hash(token_hash.clone())
             .user_id(user.id)
             .expires_at(Utc::now() + Duration::minutes(LOGIN_TOKEN_EXPIRY_MINUTES))
             .call()
@@ -149,13 +145,14 @@ impl AuthService {
                 AuthError::Database(e)
             })?;
 
+        self.login_token_waiters().notify(&token_hash);
         tracing::info!(user_id = %user.id, "associated login token with user");
         Ok(())
     }
 }
 
 /// Derive `BASE64URL(SHA256(verifier))` per RFC 7636 from the verifier.
-fn code_verifier_to_challenge(code_verifier: &str) -> String {
+pub(crate) fn code_verifier_to_challenge(code_verifier: &str) -> String {
     let verifier = PkceCodeVerifier::new(code_verifier.to_string());
     PkceCodeChallenge::from_code_verifier_sha256(&verifier)
         .as_str()
@@ -0,0 +1,209 @@
+//! Long-poll variant of the login-token exchange.
+//!
+//! `/auth/login-token/exchange` answers at once, so the desktop has to
+//! poll it on a timer until the web sign-in associates its challenge.
+//! `/auth/login-token/wait` holds the request instead: the association
+//! paths wake any waiter for that challenge on this instance, and the
+//! session goes out as soon as the row exists. A waiter also re-checks
+//! the table every [`RECHECK_INTERVAL`], which covers the association
+//! landing on another replica.
+//!
+//! A hold that elapses without an association ends in
+//! [`AuthError::LoginTokenPending`]; the client calls again straight
+//! away, and only backs off on transport or server errors.
+//!
+//! Held requests are cheap to open and each costs a lookup every
+//! [`RECHECK_INTERVAL`], so at most [`MAX_WAITERS`] are held per instance;
+//! the rest are turned away with [`AuthError::TooManyLoginWaiters`].
+//! `authz_middleware` also rate-limits the route per client, which keeps
+//! guessing verifiers in parallel from one address slow.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::{Notify, Semaphore, SemaphorePermit};
+use tokio::time::Instant;
+
+use crate::error::{AuthError, AuthResult};
+use crate::login_token::code_verifier_to_challenge;
+use crate::service::{AuthService, MintedSession};
+use crate::tokens::sha256_token;
+
+/// How long one request is held. Kept under the 30 s idle timeout
+/// common to load balancers so the hold ends on our terms.
+const WAIT_TIMEOUT: Duration = Duration::from_secs(25);
+
+/// How often a waiter looks at the table itself, for associations made
+/// on another instance.
+const RECHECK_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Most requests held at once on one instance. Far above the sign-ins
+/// in flight at any moment, and low enough that the rechecks stay a
+/// small load on the database.
+const MAX_WAITERS: usize = 256;
+
+/// Waiters keyed by `sha256(challenge)`, the same key as the
+/// `login_tokens` row.
+pub(crate) struct LoginTokenWaiters {
+    waiters: Mutex<HashMap<String, Arc<Notify>>>,
+    slots: Semaphore,
+}
+
+impl Default for LoginTokenWaiters {
+    fn default() -> Self {
+        Self::with_capacity(MAX_WAITERS)
+    }
+}
+
+impl LoginTokenWaiters {
+    fn with_capacity(max_waiters: usize) -> Self {
+        Self {
+            waiters: Mutex::default(),
+            slots: Semaphore::new(max_waiters),
+        }
+    }
+
+    /// A slot for one held request, released when dropped.
+    fn admit(&self) -> AuthResult<SemaphorePermit<'_>> {
+        self.slots
+            .try_acquire()
+            .map_err(|_| AuthError::TooManyLoginWaiters)
+    }
+
+    fn register(&self, token_hash: &str) -> WaiterGuard<'_> {
+        let notify = self
+            .waiters
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(token_hash.to_owned())
+            .or_default()
+            .clone();
+        WaiterGuard {
+            waiters: self,
+            token_hash: token_hash.to_owned(),
+            notify,
+        }
+    }
+
+    /// Wake whoever is waiting on `token_hash`. A no-op when nobody is.
+    pub(crate) fn notify(&self, token_hash: &str) {
+        if let Some(notify) = self
+            .waiters
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(token_hash)
+        {
+            notify.notify_waiters();
+        }
+    }
+}
+
+/// Drops the map entry once the last waiter for a challenge is gone
+/// (finished, timed out, or disconnected).
+struct WaiterGuard<'a> {
+    waiters: &'a LoginTokenWaiters,
+    token_hash: String,
+    notify: Arc<Notify>,
+}
+
+impl Drop for WaiterGuard<'_> {
+    fn drop(&mut self) {
+        let mut waiters = self
+            .waiters
+            .waiters
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        // Two references left: the map's and ours.
+        if Arc::strong_count(&self.notify) == 2 {
+            waiters.remove(&self.token_hash);
+        }
+    }
+}
+
+impl AuthService {
+    /// Exchange a PKCE verifier for a session, waiting up to
+    /// [`WAIT_TIMEOUT`] for the web sign-in to associate its challenge.
+    pub async fn wait_for_login_token(&self, code_verifier: &str) -> AuthResult<MintedSession> {
+        if !is_valid_code_verifier(code_verifier) {
+            return Err(AuthError::InvalidInput("Invalid login token".into()));
+        }
+        let _slot = self.login_token_waiters().admit()?;
+        let token_hash = sha256_token(&code_verifier_to_challenge(code_verifier));
+        let guard = self.login_token_waiters().register(&token_hash);
+        let deadline = Instant::now() + WAIT_TIMEOUT;
+
+        loop {
+            // Armed before the lookup, so an association landing between
+            // the lookup and the wait still wakes us.
+            let notified = guard.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            match self.login_by_login_token(code_verifier).await {
+                Err(AuthError::InvalidToken) => {}
+                result => return result,
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(AuthError::LoginTokenPending);
+            }
+            let recheck_at = deadline.min(now + RECHECK_INTERVAL);
+            tokio::select! {
+                _ = &mut notified => {}
+                _ = tokio::time::sleep_until(recheck_at) => {}
+            }
+        }
+    }
+}
+
+/// Per RFC 7636 §4.1: 43–128 characters from the unreserved set. The
+/// plain exchange takes any string; the held variant refuses junk
+/// before it ties up a request.
+fn is_valid_code_verifier(s: &str) -> bool {
+    (43..=128).contains(&s.len())
+        && s.bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verifier_must_be_rfc_7636_shaped() {
+        assert!(is_valid_code_verifier(&"a".repeat(43)));
+        assert!(is_valid_code_verifier(&"a-._~".repeat(25)));
+        assert!(!is_valid_code_verifier(&"a".repeat(42)));
+        assert!(!is_valid_code_verifier(&"a".repeat(129)));
+        assert!(!is_valid_code_verifier(&format!("{}+", "a".repeat(43))));
+    }
+
+    #[test]
+    fn waiters_over_capacity_are_turned_away() {
+        let waiters = LoginTokenWaiters::with_capacity(2);
+        let first = waiters.admit().unwrap();
+        let _second = waiters.admit().unwrap();
+        assert!(matches!(
+            waiters.admit(),
+            Err(AuthError::TooManyLoginWaiters)
+        ));
+
+        drop(first);
+        assert!(waiters.admit().is_ok());
+    }
+
+    #[test]
+    fn entry_outlives_all_but_the_last_waiter() {
+        let waiters = LoginTokenWaiters::default();
+        let first = waiters.register("hash");
+        let second = waiters.register("hash");
+        assert!(Arc::ptr_eq(&first.notify, &second.notify));
+
+        drop(first);
+        assert!(waiters.waiters.lock().unwrap().contains_key("hash"));
+        drop(second);
+        assert!(waiters.waiters.lock().unwrap().is_empty());
+    }
+}
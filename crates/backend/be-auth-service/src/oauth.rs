@@ -14,6 +14,7 @@
 pub mod apple;
 pub mod github;
 pub mod google;
+pub mod microsoft;
 pub mod provider_ext;
 
 use chrono::{DateTime, Utc};
@@ -42,6 +43,15 @@ pub enum OAuthError {
     #[error("OAuth user-info fetch failed: {0}")]
     UserInfoFetch(String),
 
+    #[error("Google Calendar request failed: {0}")]
+    CalendarFetch(String),
+
+    /// The provider answered a refresh with `invalid_grant`: the user
+    /// revoked access or the grant expired, and the stored refresh
+    /// token will never work again.
+    #[error("OAuth grant revoked or expired")]
+    GrantRevoked,
+
     #[error("OAuth response missing required field: {0}")]
     MissingField(&'static str),
 
@@ -113,6 +113,23 @@ impl GitHubOAuthClient {
         })
     }
 
+    /// Desktop loopback variant. GitHub ignores the port when matching
+    /// a loopback redirect against the registered callback URL, so one
+    /// `http://127.0.0.1/…` registration covers every port.
+    pub fn loopback_authorization_url(
+        &self,
+        state: &str,
+        pkce_challenge: &str,
+        redirect_uri: &str,
+    ) -> String {
+        Self::build_authorization_url(
+            self.config.client_id.expose_secret(),
+            redirect_uri,
+            state,
+            pkce_challenge,
+        )
+    }
+
     fn build_authorization_url(
         client_id: &str,
         redirect_uri: &str,
@@ -154,6 +171,16 @@ impl GitHubOAuthClient {
             .await
     }
 
+    pub async fn loopback_exchange_code(
+        &self,
+        code: &str,
+        pkce_verifier: &str,
+        redirect_uri: &str,
+    ) -> Result<GitHubUserInfo, OAuthError> {
+        self.exchange_code_with_redirect(code, pkce_verifier, redirect_uri)
+            .await
+    }
+
     async fn exchange_code_with_redirect(
         &self,
         code: &str,
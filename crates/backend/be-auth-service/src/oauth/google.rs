@@ -5,13 +5,23 @@ use std::time::Duration;
 use openidconnect::{
     AuthenticationFlow, AuthorizationCode, ClientId, ClientSecret, CsrfToken, EndpointMaybeSet,
     EndpointNotSet, EndpointSet, IssuerUrl, Nonce, OAuth2TokenResponse, PkceCodeChallenge,
-    PkceCodeVerifier, RedirectUrl, Scope, TokenResponse,
-    core::{CoreClient, CoreIdToken, CoreIdTokenClaims, CoreProviderMetadata, CoreResponseType},
+    PkceCodeVerifier, RedirectUrl, RefreshToken, RequestTokenError, Scope, TokenResponse,
+    core::{
+        CoreClient, CoreErrorResponseType, CoreIdToken, CoreIdTokenClaims, CoreProviderMetadata,
+        CoreResponseType,
+    },
 };
 use secrecy::{ExposeSecret, SecretString};
 
 use super::OAuthError;
 
+pub mod calendar;
+
+/// Read-only Calendar scope requested when `GOOGLE_CALENDAR_SCOPE` is
+/// enabled. Also the marker looked for in `oauth_credentials.scope`
+/// before the calendar endpoint will touch a user's tokens.
+pub const CALENDAR_READONLY_SCOPE: &str = "https://www.googleapis.com/auth/calendar.readonly";
+
 type DiscoveredClient = CoreClient<
     EndpointSet,
     EndpointNotSet,
@@ -42,6 +52,11 @@ pub struct GoogleOAuthConfig {
     /// Credential Manager already issues tokens against the server
     /// client ID, so no Android equivalent is needed.
     pub ios_client_id: Option<SecretString>,
+    /// Ask for [`CALENDAR_READONLY_SCOPE`] (plus offline access, so a
+    /// refresh token is issued) on the redirect flow. Off by default:
+    /// the extra consent screen is only worth showing once calendar
+    /// context is actually wanted.
+    pub calendar_scope: bool,
 }
 
 impl GoogleOAuthConfig {
@@ -63,6 +78,9 @@ impl GoogleOAuthConfig {
             .ok()
             .filter(|s| !s.is_empty())
             .map(SecretString::from);
+        let calendar_scope = env::var("GOOGLE_CALENDAR_SCOPE")
+            .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
 
         Ok(Self {
             client_id,
@@ -70,6 +88,7 @@ impl GoogleOAuthConfig {
             redirect_uri,
             mobile_redirect_uri,
             ios_client_id,
+            calendar_scope,
         })
     }
 }
@@ -87,6 +106,7 @@ pub struct GoogleOAuthClient {
     /// contains the server `client_id`; also contains `ios_client_id`
     /// when a native-iOS client is configured.
     accepted_audiences: Vec<ClientId>,
+    calendar_scope: bool,
     /// Shared HTTP client kept alive for connection pooling.
     http: reqwest::Client,
 }
@@ -152,6 +172,7 @@ impl GoogleOAuthClient {
             redirect_uri,
             mobile_redirect_uri: config.mobile_redirect_uri,
             accepted_audiences,
+            calendar_scope: config.calendar_scope,
             http,
         })
     }
@@ -164,6 +185,11 @@ impl GoogleOAuthClient {
         self.mobile_redirect_uri.as_deref()
     }
 
+    /// Whether sign-in asks for [`CALENDAR_READONLY_SCOPE`].
+    pub fn calendar_scope_enabled(&self) -> bool {
+        self.calendar_scope
+    }
+
     /// Build the authorisation URL. The caller supplies a pre-computed
     /// challenge (returned by `PkceCodeChallenge::new_random_sha256`) so
     /// the verifier never has to be hashed twice.
@@ -173,7 +199,7 @@ impl GoogleOAuthClient {
         pkce_challenge: PkceCodeChallenge,
         nonce: Nonce,
     ) -> String {
-        Self::build_authorization_url(&self.client, state, pkce_challenge, nonce)
+        self.build_authorization_url(&self.client, state, pkce_challenge, nonce)
     }
 
     /// Mobile variant: builds the URL against the mobile-redirect client
@@ -187,26 +213,57 @@ impl GoogleOAuthClient {
     ) -> Option<String> {
         self.mobile_client
             .as_ref()
-            .map(|c| Self::build_authorization_url(c, state, pkce_challenge, nonce))
+            .map(|c| self.build_authorization_url(c, state, pkce_challenge, nonce))
+    }
+
+    /// Desktop loopback variant: the same client with `redirect_uri` in
+    /// place of the configured one. Google only accepts an arbitrary
+    /// loopback port for "Desktop app" OAuth clients; a "Web
+    /// application" client needs each `http://127.0.0.1:{port}/…` URI
+    /// registered, so pin the desktop to a fixed port in that case.
+    pub fn loopback_authorization_url(
+        &self,
+        state: &str,
+        pkce_challenge: PkceCodeChallenge,
+        nonce: Nonce,
+        redirect_uri: &str,
+    ) -> Result<String, OAuthError> {
+        let client = self.loopback_client(redirect_uri)?;
+        Ok(self.build_authorization_url(&client, state, pkce_challenge, nonce))
+    }
+
+    fn loopback_client(&self, redirect_uri: &str) -> Result<DiscoveredClient, OAuthError> {
+        let redirect_url = RedirectUrl::new(redirect_uri.to_owned())
+            .map_err(|e| OAuthError::InvalidUrl(e.to_string()))?;
+        Ok(self.client.clone().set_redirect_uri(redirect_url))
     }
 
     fn build_authorization_url(
+        &self,
         client: &DiscoveredClient,
         state: &str,
         pkce_challenge: PkceCodeChallenge,
         nonce: Nonce,
     ) -> String {
         let state_str = state.to_string();
-        let (authorize_url, _, _) = client
+        let mut request = client
             .authorize_url(
                 AuthenticationFlow::<CoreResponseType>::AuthorizationCode,
                 || CsrfToken::new(state_str),
                 || nonce,
             )
             .add_scope(Scope::new("email".to_string()))
-            .add_scope(Scope::new("profile".to_string()))
-            .set_pkce_challenge(pkce_challenge)
-            .url();
+            .add_scope(Scope::new("profile".to_string()));
+        if self.calendar_scope {
+            // `access_type=offline` is what makes Google issue a
+            // refresh token; without it the calendar endpoint stops
+            // working an hour after sign-in.
+            request = request
+                .add_scope(Scope::new(CALENDAR_READONLY_SCOPE.to_string()))
+                .add_extra_param("access_type", "offline")
+                .add_extra_param("include_granted_scopes", "true");
+        }
+        let (authorize_url, _, _) = request.set_pkce_challenge(pkce_challenge).url();
 
         authorize_url.to_string()
     }
@@ -241,6 +298,18 @@ impl GoogleOAuthClient {
             .await
     }
 
+    pub async fn loopback_exchange_code(
+        &self,
+        code: &str,
+        pkce_verifier: String,
+        nonce: &Nonce,
+        redirect_uri: &str,
+    ) -> Result<GoogleUserInfo, OAuthError> {
+        let client = self.loopback_client(redirect_uri)?;
+        self.exchange_code_with(&client, code, pkce_verifier, nonce)
+            .await
+    }
+
     async fn exchange_code_with(
         &self,
         client: &DiscoveredClient,
@@ -311,6 +380,35 @@ impl GoogleOAuthClient {
         })
     }
 
+    /// Trade a stored refresh token for a fresh access token. Google
+    /// does not rotate refresh tokens, so only the access token and its
+    /// lifetime come back. A refused grant surfaces as
+    /// [`OAuthError::GrantRevoked`].
+    pub async fn refresh_access_token(
+        &self,
+        refresh_token: &SecretString,
+    ) -> Result<(SecretString, Option<Duration>), OAuthError> {
+        let token_response = self
+            .client
+            .exchange_refresh_token(&RefreshToken::new(refresh_token.expose_secret().to_owned()))
+            .map_err(|e| OAuthError::CodeExchange(e.to_string()))?
+            .request_async(&self.http)
+            .await
+            .map_err(|e| match e {
+                RequestTokenError::ServerResponse(ref response)
+                    if *response.error() == CoreErrorResponseType::InvalidGrant =>
+                {
+                    OAuthError::GrantRevoked
+                }
+                e => OAuthError::CodeExchange(e.to_string()),
+            })?;
+
+        Ok((
+            SecretString::from(token_response.access_token().secret().to_string()),
+            token_response.expires_in(),
+        ))
+    }
+
     /// Verify a Google ID token issued directly to a native client
     /// (Android Credential Manager, iOS GoogleSignIn SDK).
     ///
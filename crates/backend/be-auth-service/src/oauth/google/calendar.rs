@@ -0,0 +1,259 @@
+//! Read-only Google Calendar access for meeting-prep context.
+//!
+//! Only the primary calendar is read, and only a short window around
+//! "now": the point is to answer "prep me for my next meeting", not to
+//! mirror the user's calendar. Events come back in a provider-neutral
+//! [`CalendarEvent`] shape so the desktop client can render them as chat
+//! context without knowing anything about Google's API.
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::GoogleOAuthClient;
+use crate::oauth::OAuthError;
+
+const EVENTS_URL: &str = "https://www.googleapis.com/calendar/v3/calendars/primary/events";
+
+/// Hard cap on events returned per request.
+pub const MAX_EVENTS: usize = 10;
+
+/// One calendar event, trimmed to what is useful as chat context.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CalendarEvent {
+    pub id: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Date-only event. `start` / `end` are midnight UTC of the
+    /// respective days.
+    pub all_day: bool,
+    pub attendees: Vec<CalendarAttendee>,
+    pub html_link: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CalendarAttendee {
+    pub name: Option<String>,
+    pub email: Option<String>,
+    /// Google's `responseStatus`: `accepted`, `declined`, `tentative`,
+    /// or `needsAction`.
+    pub response_status: Option<String>,
+    pub organizer: bool,
+}
+
+/// Events overlapping `[now, now + window)`, split into the ones already
+/// under way and the ones still to come.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CalendarContext {
+    pub now: DateTime<Utc>,
+    pub current: Vec<CalendarEvent>,
+    pub upcoming: Vec<CalendarEvent>,
+}
+
+impl CalendarContext {
+    pub fn from_events(now: DateTime<Utc>, events: Vec<CalendarEvent>) -> Self {
+        let (current, upcoming) = events.into_iter().partition(|e| e.start <= now);
+        Self {
+            now,
+            current,
+            upcoming,
+        }
+    }
+}
+
+impl GoogleOAuthClient {
+    /// Fetch primary-calendar events overlapping `[now, now + window)`,
+    /// ordered by start time. Cancelled events are dropped.
+    pub async fn calendar_events(
+        &self,
+        access_token: &SecretString,
+        now: DateTime<Utc>,
+        window: Duration,
+    ) -> Result<Vec<CalendarEvent>, OAuthError> {
+        let mut url = Url::parse(EVENTS_URL).map_err(|e| OAuthError::InvalidUrl(e.to_string()))?;
+        url.query_pairs_mut()
+            .append_pair("timeMin", &now.to_rfc3339())
+            .append_pair("timeMax", &(now + window).to_rfc3339())
+            .append_pair("singleEvents", "true")
+            .append_pair("orderBy", "startTime")
+            .append_pair("maxResults", &MAX_EVENTS.to_string());
+
+        let response: ApiEventList = self
+            .http
+            .get(url)
+            .bearer_auth(access_token.expose_secret())
+            .send()
+            .await
+            .map_err(|e| OAuthError::CalendarFetch(e.to_string()))?
+            .error_for_status()
+            .map_err(|e| OAuthError::CalendarFetch(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| OAuthError::CalendarFetch(e.to_string()))?;
+
+        Ok(response
+            .items
+            .into_iter()
+            .filter_map(ApiEvent::into_event)
+            .take(MAX_EVENTS)
+            .collect())
+    }
+}
+
+#[derive(Deserialize)]
+struct ApiEventList {
+    #[serde(default)]
+    items: Vec<ApiEvent>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiEvent {
+    id: String,
+    status: Option<String>,
+    summary: Option<String>,
+    description: Option<String>,
+    location: Option<String>,
+    html_link: Option<String>,
+    start: ApiEventTime,
+    end: ApiEventTime,
+    #[serde(default)]
+    attendees: Vec<ApiAttendee>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiEventTime {
+    date_time: Option<DateTime<Utc>>,
+    date: Option<NaiveDate>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiAttendee {
+    email: Option<String>,
+    display_name: Option<String>,
+    response_status: Option<String>,
+    #[serde(default)]
+    organizer: bool,
+    /// Meeting rooms and other resources show up as attendees.
+    #[serde(default)]
+    resource: bool,
+}
+
+impl ApiEventTime {
+    fn resolve(&self) -> Option<(DateTime<Utc>, bool)> {
+        match (self.date_time, self.date) {
+            (Some(at), _) => Some((at, false)),
+            (None, Some(date)) => Some((date.and_hms_opt(0, 0, 0)?.and_utc(), true)),
+            (None, None) => None,
+        }
+    }
+}
+
+impl ApiEvent {
+    fn into_event(self) -> Option<CalendarEvent> {
+        if self.status.as_deref() == Some("cancelled") {
+            return None;
+        }
+        let (start, all_day) = self.start.resolve()?;
+        let (end, _) = self.end.resolve()?;
+
+        let attendees = self
+            .attendees
+            .into_iter()
+            .filter(|a| !a.resource)
+            .map(|a| CalendarAttendee {
+                name: a.display_name.filter(|s| !s.is_empty()),
+                email: a.email,
+                response_status: a.response_status,
+                organizer: a.organizer,
+            })
+            .collect();
+
+        Some(CalendarEvent {
+            id: self.id,
+            title: self
+                .summary
+                .filter(|s| !s.trim().is_empty())
+                .unwrap_or_else(|| "(no title)".to_string()),
+            description: self.description.filter(|s| !s.trim().is_empty()),
+            location: self.location.filter(|s| !s.trim().is_empty()),
+            start,
+            end,
+            all_day,
+            attendees,
+            html_link: self.html_link,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> Vec<CalendarEvent> {
+        let list: ApiEventList = serde_json::from_str(json).unwrap();
+        list.items
+            .into_iter()
+            .filter_map(ApiEvent::into_event)
+            .collect()
+    }
+
+    #[test]
+    fn parses_timed_event_with_attendees() {
+        let events = parse(
+            r#"{"items":[{
+                "id":"abc","status":"confirmed","summary":"Design review",
+                "description":"Walk through the new onboarding flow",
+                "start":{"dateTime":"2025-06-03T10:00:00+02:00"},
+                "end":{"dateTime":"2025-06-03T10:30:00+02:00"},
+                "attendees":[
+                    {"email":"ana@example.com","displayName":"Ana","responseStatus":"accepted","organizer":true},
+                    {"email":"room-1@resource.calendar.google.com","resource":true},
+                    {"email":"bo@example.com","responseStatus":"needsAction"}
+                ]
+            }]}"#,
+        );
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event.title, "Design review");
+        assert_eq!(event.start.to_rfc3339(), "2025-06-03T08:00:00+00:00");
+        assert!(!event.all_day);
+        assert_eq!(event.attendees.len(), 2);
+        assert!(event.attendees[0].organizer);
+        assert_eq!(event.attendees[1].name, None);
+    }
+
+    #[test]
+    fn handles_all_day_and_cancelled_events() {
+        let events = parse(
+            r#"{"items":[
+                {"id":"a","summary":"Offsite","start":{"date":"2025-06-04"},"end":{"date":"2025-06-05"}},
+                {"id":"b","status":"cancelled","summary":"Gone","start":{"date":"2025-06-04"},"end":{"date":"2025-06-05"}},
+                {"id":"c","start":{"dateTime":"2025-06-04T09:00:00Z"},"end":{"dateTime":"2025-06-04T09:15:00Z"}}
+            ]}"#,
+        );
+        assert_eq!(events.len(), 2);
+        assert!(events[0].all_day);
+        assert_eq!(events[1].title, "(no title)");
+    }
+
+    #[test]
+    fn splits_current_from_upcoming() {
+        let events = parse(
+            r#"{"items":[
+                {"id":"now","start":{"dateTime":"2025-06-04T08:45:00Z"},"end":{"dateTime":"2025-06-04T09:30:00Z"}},
+                {"id":"next","start":{"dateTime":"2025-06-04T10:00:00Z"},"end":{"dateTime":"2025-06-04T11:00:00Z"}}
+            ]}"#,
+        );
+        let now = "2025-06-04T09:00:00Z".parse().unwrap();
+        let context = CalendarContext::from_events(now, events);
+        assert_eq!(context.current[0].id, "now");
+        assert_eq!(context.upcoming[0].id, "next");
+    }
+}
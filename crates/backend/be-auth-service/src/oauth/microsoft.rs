@@ -0,0 +1,422 @@
+//! Sign in with Microsoft (Microsoft identity platform / Azure AD v2).
+//!
+//! Two things set Microsoft apart from the Google module:
+//!
+//! 1. **No OIDC discovery.** The multi-tenant `common` metadata
+//!    advertises the issuer as `https://login.microsoftonline.com/{tenantid}/v2.0`
+//!    — a template, not a URL — which [`openidconnect`]'s discovery
+//!    rejects as an issuer mismatch. Like the GitHub module, the token
+//!    endpoint is hit by hand. The ID token comes straight back from
+//!    that endpoint over TLS in exchange for our client secret, which
+//!    OIDC Core §3.1.3.7 accepts in place of a signature check; only
+//!    `aud` and `nonce` are validated.
+//! 2. **The `email` claim is not proof of ownership.** Any work-account
+//!    tenant admin can set a user's email to an arbitrary address.
+//!    Treating it as verified would let a hostile tenant sign in as
+//!    anyone whose account links by email, so it counts as verified
+//!    only for personal Microsoft accounts (whose tenant is fixed) or
+//!    when the tenant proves it owns the domain via the optional
+//!    `xms_edov` claim (enable it under "Token configuration" in the
+//!    app registration). Everyone else is turned away by the
+//!    orchestrator's `email_verified` check, exactly like an unverified
+//!    Google email.
+
+use std::env;
+use std::time::Duration;
+
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use secrecy::{ExposeSecret, SecretString};
+use serde::Deserialize;
+use url::Url;
+
+use super::OAuthError;
+
+/// Tenant used when `MICROSOFT_TENANT` is unset: work, school and
+/// personal accounts alike.
+const DEFAULT_TENANT: &str = "common";
+
+/// Tenant ID every personal Microsoft account (outlook.com, hotmail.com,
+/// live.com) belongs to.
+const CONSUMERS_TENANT_ID: &str = "9188040d-6c67-4c5b-b112-36a304b66dad";
+
+const SCOPE: &str = "openid email profile";
+
+#[derive(Debug, Clone)]
+pub struct MicrosoftOAuthConfig {
+    pub client_id: SecretString,
+    pub client_secret: SecretString,
+    pub redirect_uri: String,
+    /// See [`crate::oauth::google::GoogleOAuthConfig::mobile_redirect_uri`].
+    pub mobile_redirect_uri: Option<String>,
+    /// `common`, `organizations`, `consumers`, or a single tenant's ID
+    /// or domain.
+    pub tenant: String,
+}
+
+impl MicrosoftOAuthConfig {
+    pub fn from_env() -> Result<Self, OAuthError> {
+        let client_id = SecretString::from(
+            env::var("MICROSOFT_CLIENT_ID")
+                .map_err(|_| OAuthError::MissingEnvVar("MICROSOFT_CLIENT_ID"))?,
+        );
+        let client_secret = SecretString::from(
+            env::var("MICROSOFT_CLIENT_SECRET")
+                .map_err(|_| OAuthError::MissingEnvVar("MICROSOFT_CLIENT_SECRET"))?,
+        );
+        let redirect_uri = env::var("MICROSOFT_REDIRECT_URI")
+            .map_err(|_| OAuthError::MissingEnvVar("MICROSOFT_REDIRECT_URI"))?;
+        let mobile_redirect_uri = env::var("MICROSOFT_MOBILE_REDIRECT_URI")
+            .ok()
+            .filter(|s| !s.is_empty());
+        let tenant = env::var("MICROSOFT_TENANT")
+            .ok()
+            .map(|s| s.trim().to_owned())
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| DEFAULT_TENANT.to_owned());
+        if !tenant
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+        {
+            return Err(OAuthError::InvalidConfig(
+                "MICROSOFT_TENANT must be a tenant ID, a domain, or common/organizations/consumers",
+            ));
+        }
+
+        Ok(Self {
+            client_id,
+            client_secret,
+            redirect_uri,
+            mobile_redirect_uri,
+            tenant,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct MicrosoftTokenResponse {
+    access_token: Option<String>,
+    id_token: Option<String>,
+    expires_in: Option<u64>,
+    scope: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MicrosoftIdTokenClaims {
+    aud: String,
+    sub: String,
+    tid: String,
+    nonce: Option<String>,
+    email: Option<String>,
+    name: Option<String>,
+    /// "Email domain owner verified", an optional claim.
+    #[serde(default)]
+    xms_edov: Option<bool>,
+}
+
+pub struct MicrosoftOAuthClient {
+    config: MicrosoftOAuthConfig,
+    /// Shared HTTP client kept alive for connection pooling.
+    http: reqwest::Client,
+}
+
+fn build_http_client() -> Result<reqwest::Client, OAuthError> {
+    Ok(reqwest::ClientBuilder::new()
+        .redirect(reqwest::redirect::Policy::none())
+        .timeout(Duration::from_secs(30))
+        .build()?)
+}
+
+impl MicrosoftOAuthClient {
+    pub fn new(config: MicrosoftOAuthConfig) -> Result<Self, OAuthError> {
+        let http = build_http_client()?;
+        Ok(Self { config, http })
+    }
+
+    pub fn redirect_uri(&self) -> &str {
+        &self.config.redirect_uri
+    }
+
+    pub fn mobile_redirect_uri(&self) -> Option<&str> {
+        self.config.mobile_redirect_uri.as_deref()
+    }
+
+    pub fn authorization_url(&self, state: &str, pkce_challenge: &str, nonce: &str) -> String {
+        self.build_authorization_url(&self.config.redirect_uri, state, pkce_challenge, nonce)
+    }
+
+    pub fn mobile_authorization_url(
+        &self,
+        state: &str,
+        pkce_challenge: &str,
+        nonce: &str,
+    ) -> Option<String> {
+        self.config
+            .mobile_redirect_uri
+            .as_deref()
+            .map(|uri| self.build_authorization_url(uri, state, pkce_challenge, nonce))
+    }
+
+    /// Desktop loopback variant. Microsoft ignores the port of a
+    /// `localhost`/`127.0.0.1` redirect when matching it against the
+    /// app registration.
+    pub fn loopback_authorization_url(
+        &self,
+        state: &str,
+        pkce_challenge: &str,
+        nonce: &str,
+        redirect_uri: &str,
+    ) -> String {
+        self.build_authorization_url(redirect_uri, state, pkce_challenge, nonce)
+    }
+
+    fn endpoint(&self, path: &str) -> String {
+        format!(
+            "https://login.microsoftonline.com/{}/oauth2/v2.0/{path}",
+            self.config.tenant
+        )
+    }
+
+    fn build_authorization_url(
+        &self,
+        redirect_uri: &str,
+        state: &str,
+        pkce_challenge: &str,
+        nonce: &str,
+    ) -> String {
+        let mut url = Url::parse(&self.endpoint("authorize")).expect("tenant validated at load");
+        url.query_pairs_mut()
+            .append_pair("client_id", self.config.client_id.expose_secret())
+            .append_pair("response_type", "code")
+            .append_pair("response_mode", "query")
+            .append_pair("redirect_uri", redirect_uri)
+            .append_pair("scope", SCOPE)
+            .append_pair("state", state)
+            .append_pair("nonce", nonce)
+            .append_pair("code_challenge", pkce_challenge)
+            .append_pair("code_challenge_method", "S256");
+        url.into()
+    }
+
+    pub async fn exchange_code(
+        &self,
+        code: &str,
+        pkce_verifier: &str,
+        nonce: &str,
+    ) -> Result<MicrosoftUserInfo, OAuthError> {
+        self.exchange_code_with_redirect(code, pkce_verifier, nonce, &self.config.redirect_uri)
+            .await
+    }
+
+    pub async fn mobile_exchange_code(
+        &self,
+        code: &str,
+        pkce_verifier: &str,
+        nonce: &str,
+    ) -> Result<MicrosoftUserInfo, OAuthError> {
+        let redirect_uri = self
+            .config
+            .mobile_redirect_uri
+            .as_deref()
+            .ok_or(OAuthError::MissingEnvVar("MICROSOFT_MOBILE_REDIRECT_URI"))?;
+        self.exchange_code_with_redirect(code, pkce_verifier, nonce, redirect_uri)
+            .await
+    }
+
+    pub async fn loopback_exchange_code(
+        &self,
+        code: &str,
+        pkce_verifier: &str,
+        nonce: &str,
+        redirect_uri: &str,
+    ) -> Result<MicrosoftUserInfo, OAuthError> {
+        self.exchange_code_with_redirect(code, pkce_verifier, nonce, redirect_uri)
+            .await
+    }
+
+    async fn exchange_code_with_redirect(
+        &self,
+        code: &str,
+        pkce_verifier: &str,
+        nonce: &str,
+        redirect_uri: &str,
+    ) -> Result<MicrosoftUserInfo, OAuthError> {
+        // Failures come back as 400 with an `error` body, so the body is
+        // read before the status is looked at.
+        let token_resp: MicrosoftTokenResponse = self
+            .http
+            .post(self.endpoint("token"))
+            .form(&[
+                ("client_id", self.config.client_id.expose_secret()),
+                ("client_secret", self.config.client_secret.expose_secret()),
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", redirect_uri),
+                ("code_verifier", pkce_verifier),
+                ("scope", SCOPE),
+            ])
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        if let Some(error) = &token_resp.error {
+            let desc = token_resp.error_description.as_deref().unwrap_or(error);
+            return Err(OAuthError::CodeExchange(desc.to_string()));
+        }
+
+        let access_token = token_resp
+            .access_token
+            .ok_or(OAuthError::MissingField("access_token"))?;
+        let id_token = token_resp
+            .id_token
+            .ok_or(OAuthError::MissingField("id_token"))?;
+        let claims = decode_id_token(&id_token)?;
+        let identity = identity_from_claims(claims, self.config.client_id.expose_secret(), nonce)?;
+
+        Ok(MicrosoftUserInfo {
+            id: identity.id,
+            email: identity.email,
+            verified_email: identity.verified_email,
+            display_name: identity.display_name,
+            access_token: SecretString::from(access_token),
+            expires_in: token_resp.expires_in.map(Duration::from_secs),
+            scope: token_resp.scope.unwrap_or_else(|| SCOPE.to_owned()),
+        })
+    }
+}
+
+/// Read the claims of an ID token received directly from the token
+/// endpoint. See the module docs for why the signature isn't checked.
+fn decode_id_token(id_token: &str) -> Result<MicrosoftIdTokenClaims, OAuthError> {
+    let payload = id_token
+        .split('.')
+        .nth(1)
+        .ok_or_else(|| OAuthError::TokenVerification("malformed id_token".into()))?;
+    let bytes = URL_SAFE_NO_PAD
+        .decode(payload)
+        .map_err(|e| OAuthError::TokenVerification(format!("malformed id_token: {e}")))?;
+    serde_json::from_slice(&bytes)
+        .map_err(|e| OAuthError::TokenVerification(format!("malformed id_token claims: {e}")))
+}
+
+struct MicrosoftIdentity {
+    id: String,
+    email: String,
+    verified_email: bool,
+    display_name: Option<String>,
+}
+
+fn identity_from_claims(
+    claims: MicrosoftIdTokenClaims,
+    client_id: &str,
+    expected_nonce: &str,
+) -> Result<MicrosoftIdentity, OAuthError> {
+    if claims.aud != client_id {
+        return Err(OAuthError::TokenVerification(
+            "id_token audience mismatch".into(),
+        ));
+    }
+    if claims.nonce.as_deref() != Some(expected_nonce) {
+        return Err(OAuthError::TokenVerification(
+            "id_token nonce mismatch".into(),
+        ));
+    }
+    let email = claims
+        .email
+        .filter(|e| !e.is_empty())
+        .ok_or(OAuthError::MissingField("email"))?;
+    let verified_email = claims.tid == CONSUMERS_TENANT_ID || claims.xms_edov == Some(true);
+
+    Ok(MicrosoftIdentity {
+        id: claims.sub,
+        email,
+        verified_email,
+        display_name: claims.name.filter(|s| !s.is_empty()),
+    })
+}
+
+#[derive(Debug)]
+pub struct MicrosoftUserInfo {
+    pub id: String,
+    pub email: String,
+    pub verified_email: bool,
+    pub display_name: Option<String>,
+    pub access_token: SecretString,
+    pub expires_in: Option<Duration>,
+    pub scope: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLIENT_ID: &str = "client";
+    const NONCE: &str = "nonce";
+
+    fn id_token(claims: serde_json::Value) -> String {
+        let header = URL_SAFE_NO_PAD.encode(br#"{"alg":"RS256","typ":"JWT"}"#);
+        let payload = URL_SAFE_NO_PAD.encode(claims.to_string());
+        format!("{header}.{payload}.signature")
+    }
+
+    fn identity(claims: serde_json::Value) -> Result<MicrosoftIdentity, OAuthError> {
+        identity_from_claims(decode_id_token(&id_token(claims))?, CLIENT_ID, NONCE)
+    }
+
+    #[test]
+    fn personal_account_email_is_verified() {
+        let info = identity(serde_json::json!({
+            "aud": CLIENT_ID,
+            "sub": "abc",
+            "tid": CONSUMERS_TENANT_ID,
+            "nonce": NONCE,
+            "email": "user@outlook.com",
+            "name": "Ada",
+        }))
+        .unwrap();
+        assert_eq!(info.id, "abc");
+        assert_eq!(info.email, "user@outlook.com");
+        assert!(info.verified_email);
+        assert_eq!(info.display_name.as_deref(), Some("Ada"));
+    }
+
+    #[test]
+    fn work_account_email_needs_domain_ownership() {
+        let claims = |edov: Option<bool>| {
+            serde_json::json!({
+                "aud": CLIENT_ID,
+                "sub": "abc",
+                "tid": "72f988bf-86f1-41af-91ab-2d7cd011db47",
+                "nonce": NONCE,
+                "email": "user@contoso.com",
+                "xms_edov": edov,
+            })
+        };
+        assert!(!identity(claims(None)).unwrap().verified_email);
+        assert!(!identity(claims(Some(false))).unwrap().verified_email);
+        assert!(identity(claims(Some(true))).unwrap().verified_email);
+    }
+
+    #[test]
+    fn rejects_foreign_audience_and_wrong_nonce() {
+        let claims = |aud: &str, nonce: &str| {
+            serde_json::json!({
+                "aud": aud,
+                "sub": "abc",
+                "tid": CONSUMERS_TENANT_ID,
+                "nonce": nonce,
+                "email": "user@outlook.com",
+            })
+        };
+        assert!(matches!(
+            identity(claims("someone-else", NONCE)),
+            Err(OAuthError::TokenVerification(_))
+        ));
+        assert!(matches!(
+            identity(claims(CLIENT_ID, "replayed")),
+            Err(OAuthError::TokenVerification(_))
+        ));
+    }
+}
@@ -1,8 +1,8 @@
 //! [`OAuthProviderExt`]: per-provider variance behind the shared
 //! OAuth flow.
 //!
-//! Implemented by [`GoogleOAuthClient`], [`GitHubOAuthClient`], and
-//! [`AppleOAuthClient`]. Adding the third provider tipped the
+//! Implemented by [`GoogleOAuthClient`], [`GitHubOAuthClient`],
+//! [`AppleOAuthClient`], and [`MicrosoftOAuthClient`]. Adding the third provider tipped the
 //! enum-match-per-method approach in `oauth_flow.rs` past the point
 //! where each addition was structurally cheap — the trait pulls all
 //! per-provider variance into a single impl block per provider so
@@ -34,6 +34,7 @@ use crate::oauth::OAuthError;
 use crate::oauth::apple::AppleOAuthClient;
 use crate::oauth::github::GitHubOAuthClient;
 use crate::oauth::google::GoogleOAuthClient;
+use crate::oauth::microsoft::MicrosoftOAuthClient;
 
 /// Provider-agnostic identity shape consumed by the orchestrator in
 /// [`crate::oauth_flow`]. Carries provider-issued secrets in
@@ -95,6 +96,24 @@ pub trait OAuthProviderExt: Send + Sync {
         nonce: &Nonce,
     ) -> Option<String>;
 
+    /// Whether the provider can redirect to a desktop loopback address
+    /// (see [`crate::AuthService::loopback_third_party_auth_url`]).
+    fn supports_loopback(&self) -> bool {
+        true
+    }
+
+    /// Build the authorization URL for the desktop loopback flow, with
+    /// `redirect_uri` — already checked to be a loopback address — in
+    /// place of the configured one. Only called when
+    /// [`Self::supports_loopback`] is true.
+    fn loopback_authorization_url(
+        &self,
+        state: &str,
+        pkce_challenge: &PkceCodeChallenge,
+        nonce: &Nonce,
+        redirect_uri: &str,
+    ) -> Result<String, OAuthError>;
+
     /// Exchange an authorization code for an identity using the
     /// web-flow redirect URI.
     ///
@@ -117,6 +136,16 @@ pub trait OAuthProviderExt: Send + Sync {
         pkce_verifier: String,
         nonce: &Nonce,
     ) -> Result<OAuthIdentityRaw, OAuthError>;
+
+    /// Exchange against the loopback `redirect_uri` the authorization
+    /// URL was built with.
+    async fn loopback_exchange_code(
+        &self,
+        code: &str,
+        pkce_verifier: String,
+        nonce: &Nonce,
+        redirect_uri: &str,
+    ) -> Result<OAuthIdentityRaw, OAuthError>;
 }
 
 #[async_trait]
@@ -162,6 +191,22 @@ impl OAuthProviderExt for GoogleOAuthClient {
         )
     }
 
+    fn loopback_authorization_url(
+        &self,
+        state: &str,
+        pkce_challenge: &PkceCodeChallenge,
+        nonce: &Nonce,
+        redirect_uri: &str,
+    ) -> Result<String, OAuthError> {
+        GoogleOAuthClient::loopback_authorization_url(
+            self,
+            state,
+            pkce_challenge.clone(),
+            nonce.clone(),
+            redirect_uri,
+        )
+    }
+
     async fn exchange_code(
         &self,
         code: &str,
@@ -182,6 +227,24 @@ impl OAuthProviderExt for GoogleOAuthClient {
             GoogleOAuthClient::mobile_exchange_code(self, code, pkce_verifier, nonce).await?;
         Ok(google_user_info_to_raw(user_info))
     }
+
+    async fn loopback_exchange_code(
+        &self,
+        code: &str,
+        pkce_verifier: String,
+        nonce: &Nonce,
+        redirect_uri: &str,
+    ) -> Result<OAuthIdentityRaw, OAuthError> {
+        let user_info = GoogleOAuthClient::loopback_exchange_code(
+            self,
+            code,
+            pkce_verifier,
+            nonce,
+            redirect_uri,
+        )
+        .await?;
+        Ok(google_user_info_to_raw(user_info))
+    }
 }
 
 #[async_trait]
@@ -221,6 +284,21 @@ impl OAuthProviderExt for GitHubOAuthClient {
         GitHubOAuthClient::mobile_authorization_url(self, state, pkce_challenge.as_str())
     }
 
+    fn loopback_authorization_url(
+        &self,
+        state: &str,
+        pkce_challenge: &PkceCodeChallenge,
+        _nonce: &Nonce,
+        redirect_uri: &str,
+    ) -> Result<String, OAuthError> {
+        Ok(GitHubOAuthClient::loopback_authorization_url(
+            self,
+            state,
+            pkce_challenge.as_str(),
+            redirect_uri,
+        ))
+    }
+
     async fn exchange_code(
         &self,
         code: &str,
@@ -240,6 +318,19 @@ impl OAuthProviderExt for GitHubOAuthClient {
         let user_info = GitHubOAuthClient::mobile_exchange_code(self, code, &pkce_verifier).await?;
         Ok(github_user_info_to_raw(user_info))
     }
+
+    async fn loopback_exchange_code(
+        &self,
+        code: &str,
+        pkce_verifier: String,
+        _nonce: &Nonce,
+        redirect_uri: &str,
+    ) -> Result<OAuthIdentityRaw, OAuthError> {
+        let user_info =
+            GitHubOAuthClient::loopback_exchange_code(self, code, &pkce_verifier, redirect_uri)
+                .await?;
+        Ok(github_user_info_to_raw(user_info))
+    }
 }
 
 #[async_trait]
@@ -278,6 +369,23 @@ impl OAuthProviderExt for AppleOAuthClient {
         AppleOAuthClient::mobile_authorization_url(self, state, pkce_challenge, nonce)
     }
 
+    /// Apple only redirects to registered HTTPS URLs, via `form_post`.
+    fn supports_loopback(&self) -> bool {
+        false
+    }
+
+    fn loopback_authorization_url(
+        &self,
+        _state: &str,
+        _pkce_challenge: &PkceCodeChallenge,
+        _nonce: &Nonce,
+        _redirect_uri: &str,
+    ) -> Result<String, OAuthError> {
+        Err(OAuthError::InvalidConfig(
+            "Sign in with Apple has no loopback redirect",
+        ))
+    }
+
     async fn exchange_code(
         &self,
         code: &str,
@@ -298,6 +406,122 @@ impl OAuthProviderExt for AppleOAuthClient {
             AppleOAuthClient::mobile_exchange_code(self, code, pkce_verifier, nonce).await?;
         Ok(apple_user_info_to_raw(user_info))
     }
+
+    async fn loopback_exchange_code(
+        &self,
+        _code: &str,
+        _pkce_verifier: String,
+        _nonce: &Nonce,
+        _redirect_uri: &str,
+    ) -> Result<OAuthIdentityRaw, OAuthError> {
+        Err(OAuthError::InvalidConfig(
+            "Sign in with Apple has no loopback redirect",
+        ))
+    }
+}
+
+#[async_trait]
+impl OAuthProviderExt for MicrosoftOAuthClient {
+    fn provider(&self) -> OAuthProvider {
+        OAuthProvider::Microsoft
+    }
+
+    fn web_redirect_uri(&self) -> &str {
+        MicrosoftOAuthClient::redirect_uri(self)
+    }
+
+    fn mobile_redirect_uri(&self) -> Option<&str> {
+        MicrosoftOAuthClient::mobile_redirect_uri(self)
+    }
+
+    fn mobile_redirect_env_var(&self) -> &'static str {
+        "MICROSOFT_MOBILE_REDIRECT_URI"
+    }
+
+    fn authorization_url(
+        &self,
+        state: &str,
+        pkce_challenge: &PkceCodeChallenge,
+        nonce: &Nonce,
+    ) -> String {
+        MicrosoftOAuthClient::authorization_url(
+            self,
+            state,
+            pkce_challenge.as_str(),
+            nonce.secret(),
+        )
+    }
+
+    fn mobile_authorization_url(
+        &self,
+        state: &str,
+        pkce_challenge: &PkceCodeChallenge,
+        nonce: &Nonce,
+    ) -> Option<String> {
+        MicrosoftOAuthClient::mobile_authorization_url(
+            self,
+            state,
+            pkce_challenge.as_str(),
+            nonce.secret(),
+        )
+    }
+
+    fn loopback_authorization_url(
+        &self,
+        state: &str,
+        pkce_challenge: &PkceCodeChallenge,
+        nonce: &Nonce,
+        redirect_uri: &str,
+    ) -> Result<String, OAuthError> {
+        Ok(MicrosoftOAuthClient::loopback_authorization_url(
+            self,
+            state,
+            pkce_challenge.as_str(),
+            nonce.secret(),
+            redirect_uri,
+        ))
+    }
+
+    async fn exchange_code(
+        &self,
+        code: &str,
+        pkce_verifier: String,
+        nonce: &Nonce,
+    ) -> Result<OAuthIdentityRaw, OAuthError> {
+        let user_info =
+            MicrosoftOAuthClient::exchange_code(self, code, &pkce_verifier, nonce.secret()).await?;
+        Ok(microsoft_user_info_to_raw(user_info))
+    }
+
+    async fn mobile_exchange_code(
+        &self,
+        code: &str,
+        pkce_verifier: String,
+        nonce: &Nonce,
+    ) -> Result<OAuthIdentityRaw, OAuthError> {
+        let user_info =
+            MicrosoftOAuthClient::mobile_exchange_code(self, code, &pkce_verifier, nonce.secret())
+                .await?;
+        Ok(microsoft_user_info_to_raw(user_info))
+    }
+
+    async fn loopback_exchange_code(
+        &self,
+        code: &str,
+        pkce_verifier: String,
+        nonce: &Nonce,
+        redirect_uri: &str,
+    ) -> Result<OAuthIdentityRaw, OAuthError> {
+        let user_info = MicrosoftOAuthClient::loopback_exchange_code(
+            self,
+            code,
+            &pkce_verifier,
+            nonce.secret(),
+            redirect_uri,
+        )
+        .await?;
+        Ok(microsoft_user_info_to_raw(user_info))
+    }
 }
 
 fn google_user_info_to_raw(user_info: crate::oauth::google::GoogleUserInfo) -> OAuthIdentityRaw {
@@ -353,3 +577,24 @@ fn apple_user_info_to_raw(user_info: crate::oauth::apple::AppleUserInfo) -> OAut
         },
     }
 }
+
+fn microsoft_user_info_to_raw(
+    user_info: crate::oauth::microsoft::MicrosoftUserInfo,
+) -> OAuthIdentityRaw {
+    let access_token_expiry = user_info
+        .expires_in
+        .map(|d| Utc::now() + chrono::Duration::seconds(d.as_secs() as i64));
+
+    OAuthIdentityRaw {
+        provider_user_id: user_info.id,
+        email: user_info.email,
+        email_verified: user_info.verified_email,
+        display_name: user_info.display_name,
+        tokens: RawOAuthTokens {
+            access_token: Some(user_info.access_token),
+            refresh_token: None,
+            access_token_expiry,
+            scope: user_info.scope,
+        },
+    }
+}
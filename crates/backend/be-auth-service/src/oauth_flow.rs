@@ -13,6 +13,13 @@
 //!   shared [`OAuthProviderExt`] trait, resolves / creates the user,
 //!   and mints a session.
 //!
+//! [`AuthService::loopback_third_party_auth_url`] is the desktop
+//! variant for deployments without the hosted login page: the provider
+//! redirects to a port the desktop listens on, and the desktop posts
+//! the code to the same `login_third_party` path. The loopback URI is
+//! stored as the row's `redirect_uri`, which is what tells the callback
+//! to exchange against it instead of the configured web redirect.
+//!
 //! The shared post-resolution tail (`complete_oauth_login`) runs once
 //! per provider so token-issuing logic stays in lockstep. Every
 //! redirect-callback path — web/mobile × Google/GitHub/Apple — funnels
@@ -32,6 +39,7 @@ use chrono::{Duration, Utc};
 use openidconnect::{Nonce, PkceCodeChallenge};
 use secrecy::ExposeSecret;
 use sha2::{Digest, Sha256};
+use url::{Host, Url};
 
 use crate::OAUTH_STATE_EXPIRY_MINUTES;
 use crate::USERS_EMAIL_UNIQUE_CONSTRAINT;
@@ -109,6 +117,55 @@ impl AuthService {
         Ok(url)
     }
 
+    /// Mint the authorization URL for the desktop loopback flow
+    /// (RFC 8252 §7.3). `redirect_uri` must point at the loopback
+    /// interface; the desktop receives `code` and `state` there and
+    /// completes through [`Self::login_third_party`].
+    ///
+    /// No pairing token: the desktop holds the session itself once
+    /// `/auth/login` answers, so there is nothing to hand over.
+    pub async fn loopback_third_party_auth_url(
+        &self,
+        provider: Provider,
+        redirect_uri: &str,
+    ) -> AuthResult<String> {
+        let redirect_uri = parse_loopback_redirect_uri(redirect_uri)?;
+        let client = self.oauth_provider(provider)?;
+        if !client.supports_loopback() {
+            return Err(AuthError::InvalidInput(format!(
+                "{provider} sign-in does not support loopback redirects"
+            )));
+        }
+        tracing::info!(?provider, "Generating loopback OAuth URL");
+
+        let state = random_hex(OAUTH_STATE_BYTES);
+        let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+        let nonce = Nonce::new_random();
+
+        let url = client.loopback_authorization_url(
+            &state,
+            &pkce_challenge,
+            &nonce,
+            redirect_uri.as_str(),
+        )?;
+
+        let encrypted_pkce_verifier = encrypt_sensitive_string(pkce_verifier.secret())?;
+        let encrypted_nonce = Some(encrypt_sensitive_string(nonce.secret())?);
+
+        self.db()
+            .create_oauth_state()
+            .state(state)
+            .pkce_verifier(encrypted_pkce_verifier)
+            .redirect_uri(redirect_uri.into())
+            .expires_at(Utc::now() + Duration::minutes(OAUTH_STATE_EXPIRY_MINUTES))
+            .maybe_nonce(encrypted_nonce)
+            .maybe_login_token(None)
+            .call()
+            .await?;
+
+        Ok(url)
+    }
+
     /// Web/desktop OAuth callback completion. State + PKCE verifier +
     /// pairing token all come off the consumed `oauth_state` row.
     pub async fn login_third_party(
@@ -296,7 +353,7 @@ impl AuthService {
 
         let pkce_verifier = decrypt_sensitive_string(&oauth_state.pkce_verifier)?;
 
-        // OIDC providers (Google, Apple) require a nonce; GitHub
+        // OIDC providers (Google, Apple, Microsoft) require a nonce; GitHub
         // doesn't but the trait method takes one unconditionally —
         // synth a random one when the column is empty so the call
         // stays uniform.
@@ -317,7 +374,18 @@ impl AuthService {
                     .mobile_exchange_code(code, pkce_verifier, &nonce)
                     .await?
             }
-            CallbackFlow::Web => client.exchange_code(code, pkce_verifier, &nonce).await?,
+            CallbackFlow::Web if oauth_state.redirect_uri == client.web_redirect_uri() => {
+                client.exchange_code(code, pkce_verifier, &nonce).await?
+            }
+            // Only loopback rows carry another redirect URI. Anything
+            // else (a mobile row replayed against `/auth/login`) is
+            // refused before it reaches the provider.
+            CallbackFlow::Web => {
+                let redirect_uri = parse_loopback_redirect_uri(&oauth_state.redirect_uri)?;
+                client
+                    .loopback_exchange_code(code, pkce_verifier, &nonce, redirect_uri.as_str())
+                    .await?
+            }
         };
 
         if !raw.email_verified {
@@ -706,9 +774,7 @@ impl AuthService {
     ) -> AuthResult<MintedSession> {
         let pair = crate::tokens::generate_jwt_pair(
             self.jwt_config(),
-            user.id,
-            &user.email,
-            user.display_name.clone(),
+            user,
             role.clone(),
             override_email_verified,
         )?;
@@ -796,10 +862,62 @@ fn build_new_identity(
     })
 }
 
+/// Accept only `http://127.0.0.1:{port}/…` and `http://[::1]:{port}/…`:
+/// plain HTTP is fine because the traffic never leaves the machine,
+/// and an explicit port is required because the desktop picks one at
+/// random. `localhost` is refused (RFC 8252 §8.3) since it can resolve
+/// off-box, and query / fragment are refused so the provider's `code`
+/// and `state` are the only parameters the listener sees.
+fn parse_loopback_redirect_uri(raw: &str) -> AuthResult<Url> {
+    let invalid = || AuthError::InvalidInput("Invalid loopback redirect URI".into());
+    let url = Url::parse(raw).map_err(|_| invalid())?;
+    let loopback_host = match url.host() {
+        Some(Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(Host::Ipv6(ip)) => ip.is_loopback(),
+        _ => false,
+    };
+    if url.scheme() != "http"
+        || !loopback_host
+        || url.port().is_none()
+        || !url.username().is_empty()
+        || url.password().is_some()
+        || url.query().is_some()
+        || url.fragment().is_some()
+    {
+        return Err(invalid());
+    }
+    Ok(url)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn loopback_redirect_uri_must_be_plain_http_on_the_loopback_interface() {
+        for ok in [
+            "http://127.0.0.1:53682/callback",
+            "http://[::1]:53682/callback",
+            "http://127.0.0.1:8080/",
+        ] {
+            assert!(parse_loopback_redirect_uri(ok).is_ok(), "{ok}");
+        }
+        for bad in [
+            "https://127.0.0.1:53682/callback",
+            "http://localhost:53682/callback",
+            "http://192.168.1.10:53682/callback",
+            "http://eurora.example:53682/callback",
+            "http://127.0.0.1/callback",
+            "http://user@127.0.0.1:53682/callback",
+            "http://127.0.0.1:53682/callback?next=x",
+            "http://127.0.0.1:53682/callback#x",
+            "eurora://127.0.0.1:53682/callback",
+            "not a url",
+        ] {
+            assert!(parse_loopback_redirect_uri(bad).is_err(), "{bad}");
+        }
+    }
+
     #[test]
     fn expected_apple_native_nonce_hashes_with_sha256_base64url() {
         // Fixed-value check: SHA-256 of "raw-nonce" is well-known; the
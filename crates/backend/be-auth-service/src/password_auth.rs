@@ -116,14 +116,7 @@ impl AuthService {
         user: &be_remote_db::User,
         role: auth_core::Role,
     ) -> AuthResult<MintedSession> {
-        let pair = generate_jwt_pair(
-            self.jwt_config(),
-            user.id,
-            &user.email,
-            user.display_name.clone(),
-            role.clone(),
-            user.email_verified,
-        )?;
+        let pair = generate_jwt_pair(self.jwt_config(), user, role.clone(), user.email_verified)?;
 
         self.db()
             .create_refresh_token()
@@ -11,7 +11,7 @@ use crate::error::{AuthError, AuthResult};
 pub(crate) const MIN_PASSWORD_LENGTH: usize = 12;
 pub(crate) const MAX_PASSWORD_LENGTH: usize = 128;
 
-pub(crate) fn validate_email(email: &str) -> AuthResult<()> {
+pub fn validate_email(email: &str) -> AuthResult<()> {
     if EmailAddress::is_valid(email) {
         Ok(())
     } else {
@@ -21,7 +21,7 @@ pub(crate) fn validate_email(email: &str) -> AuthResult<()> {
     }
 }
 
-pub(crate) fn validate_password(password: &str) -> AuthResult<()> {
+pub fn validate_password(password: &str) -> AuthResult<()> {
     if password.len() < MIN_PASSWORD_LENGTH {
         return Err(AuthError::InvalidInput(format!(
             "Password must be at least {MIN_PASSWORD_LENGTH} characters"
@@ -35,7 +35,7 @@ pub(crate) fn validate_password(password: &str) -> AuthResult<()> {
     Ok(())
 }
 
-pub(crate) fn hash_password(password: &str) -> AuthResult<String> {
+pub fn hash_password(password: &str) -> AuthResult<String> {
     let salt = SaltString::generate(&mut OsRng);
     let hash = Argon2::default()
         .hash_password(password.as_bytes(), &salt)
@@ -12,12 +12,18 @@ const PLAN_FREE: &str = "free";
 impl AuthService {
     /// Look up the caller's role.
     ///
+    /// Every session mint resolves the role, so this is also where an
+    /// account scheduled for deletion is refused
+    /// ([`AuthError::AccountPendingDeletion`]).
+    ///
     /// In dev mode (debug builds) every user is `Tier1` — no payment
     /// service exists, so plan checks are bypassed. Otherwise we hit
     /// the DB; an absent plan row means `Free`, and any other DB error
     /// propagates rather than silently downgrading the user (which
     /// would be a billing / feature-gate hazard).
     pub(crate) async fn resolve_role(&self, user_id: Uuid) -> AuthResult<Role> {
+        self.ensure_account_active(user_id).await?;
+
         if self.dev_mode() {
             return Ok(Role::Tier1);
         }
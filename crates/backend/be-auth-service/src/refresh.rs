@@ -1,6 +1,7 @@
 //! Refresh-token rotation and logout.
 
 use auth_core::TokenResponse;
+use uuid::Uuid;
 
 use crate::error::{AuthError, AuthResult};
 use crate::service::{AuthService, MintedSession, user_info_from_row};
@@ -45,14 +46,7 @@ impl AuthService {
             .ensure_plan_and_resolve_role(user.id, &user.email)
             .await?;
 
-        let pair = generate_jwt_pair(
-            self.jwt_config(),
-            user.id,
-            &user.email,
-            user.display_name.clone(),
-            role.clone(),
-            user.email_verified,
-        )?;
+        let pair = generate_jwt_pair(self.jwt_config(), &user, role.clone(), user.email_verified)?;
 
         // Atomic swap. If the row vanished between the lookup above and
         // here (concurrent rotation, manual revocation), surface that as
@@ -99,4 +93,17 @@ impl AuthService {
             })?;
         Ok(())
     }
+
+    /// Sign the user out on every device: revoke all refresh tokens and
+    /// bump the token version so outstanding access tokens are refused
+    /// too. Returns how many sessions were revoked.
+    pub async fn logout_everywhere(&self, user_id: Uuid) -> AuthResult<u64> {
+        let revoked = self
+            .db()
+            .revoke_all_refresh_tokens_for_user()
+            .user_id(user_id)
+            .call()
+            .await?;
+        Ok(revoked)
+    }
 }
@@ -2,10 +2,10 @@
 //!
 //! Per-flow methods live in sibling modules (`password_auth`, `refresh`,
 //! `oauth_flow`, `email_verification`, `login_token`, `email_check`,
-//! `plans`) — each adds its own `impl AuthService` block. This file is
-//! the home of the struct definition, dependency wiring, and the
-//! accessor methods those flows use to reach back into the shared
-//! state.
+//! `plans`, `calendar`, `account_deletion`) — each adds its own `impl AuthService` block.
+//! This file is the home of the struct definition, dependency wiring,
+//! and the accessor methods those flows use to reach back into the
+//! shared state.
 
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -18,11 +18,13 @@ use uuid::Uuid;
 
 use crate::cookies::CookieConfig;
 use crate::error::{AuthError, AuthResult};
+use crate::login_token_wait::LoginTokenWaiters;
 use crate::oauth::apple::AppleOAuthClient;
 use crate::oauth::github::GitHubOAuthClient;
 use crate::oauth::google::GoogleOAuthClient;
+use crate::oauth::microsoft::MicrosoftOAuthClient;
 use crate::oauth::provider_ext::OAuthProviderExt;
-use crate::oauth::{OAuthError, apple, github, google};
+use crate::oauth::{OAuthError, apple, github, google, microsoft};
 
 /// A freshly minted session: the bearer-mode token envelope alongside
 /// the public user profile. Handlers serialise one or the other (or
@@ -74,6 +76,7 @@ pub(crate) fn user_info_from_row(
         display_name: user.display_name.clone(),
         email_verified,
         role,
+        analytics: user.analytics_consent.into(),
     }
 }
 
@@ -85,6 +88,7 @@ pub(crate) fn user_info_from_claims(claims: &Claims) -> AuthResult<UserInfo> {
         display_name: claims.display_name.clone(),
         email_verified: claims.email_verified,
         role: claims.role.clone(),
+        analytics: claims.analytics,
     })
 }
 
@@ -173,6 +177,11 @@ pub struct AuthService {
     /// avoids smearing those methods onto the trait with `Option` /
     /// `unused_variables` for the other providers.
     apple_oauth_client: Option<Arc<AppleOAuthClient>>,
+    /// How long a deletion request waits before the account is erased.
+    account_deletion_grace: chrono::Duration,
+    /// Requests held on `/auth/login-token/wait`; see
+    /// [`crate::login_token_wait`].
+    login_token_waiters: LoginTokenWaiters,
 }
 
 #[derive(Default)]
@@ -180,6 +189,7 @@ pub struct AuthServiceConfig {
     pub google: Option<GoogleOAuthClient>,
     pub github: Option<GitHubOAuthClient>,
     pub apple: Option<AppleOAuthClient>,
+    pub microsoft: Option<MicrosoftOAuthClient>,
 }
 
 impl AuthServiceConfig {
@@ -211,10 +221,12 @@ impl AuthService {
         jwt_config: JwtConfig,
         email_service: Option<Arc<EmailService>>,
         oauth_clients: AuthServiceConfig,
+        account_deletion_grace: chrono::Duration,
     ) -> Self {
         let google_oauth_client = oauth_clients.google.map(Arc::new);
         let github_oauth_client = oauth_clients.github.map(Arc::new);
         let apple_oauth_client = oauth_clients.apple.map(Arc::new);
+        let microsoft_oauth_client = oauth_clients.microsoft.map(Arc::new);
 
         let mut oauth_providers: HashMap<Provider, Arc<dyn OAuthProviderExt>> = HashMap::new();
         if let Some(g) = &google_oauth_client {
@@ -226,6 +238,9 @@ impl AuthService {
         if let Some(a) = &apple_oauth_client {
             oauth_providers.insert(Provider::Apple, a.clone());
         }
+        if let Some(m) = microsoft_oauth_client {
+            oauth_providers.insert(Provider::Microsoft, m);
+        }
 
         Self {
             db,
@@ -234,6 +249,8 @@ impl AuthService {
             oauth_providers,
             google_oauth_client,
             apple_oauth_client,
+            account_deletion_grace,
+            login_token_waiters: LoginTokenWaiters::default(),
         }
     }
 
@@ -249,6 +266,14 @@ impl AuthService {
         self.email_service.as_ref()
     }
 
+    pub(crate) fn account_deletion_grace(&self) -> chrono::Duration {
+        self.account_deletion_grace
+    }
+
+    pub(crate) fn login_token_waiters(&self) -> &LoginTokenWaiters {
+        &self.login_token_waiters
+    }
+
     /// Dev mode is tied to the build profile: debug builds skip
     /// payment/email/update wiring, release builds do not. There is no
     /// runtime override.
@@ -314,6 +339,7 @@ fn missing_env_var_hint(provider: Provider) -> &'static str {
         Provider::Apple => {
             "APPLE_TEAM_ID/APPLE_SERVICE_ID/APPLE_KEY_ID/APPLE_PRIVATE_KEY/APPLE_WEB_REDIRECT_URI"
         }
+        Provider::Microsoft => "MICROSOFT_CLIENT_ID/MICROSOFT_CLIENT_SECRET/MICROSOFT_REDIRECT_URI",
     }
 }
 
@@ -351,9 +377,19 @@ pub async fn build_oauth_clients() -> Result<AuthServiceConfig, OAuthError> {
         Err(e) => return Err(e),
     };
 
+    let microsoft = match microsoft::MicrosoftOAuthConfig::from_env() {
+        Ok(cfg) => Some(MicrosoftOAuthClient::new(cfg)?),
+        Err(OAuthError::MissingEnvVar(_)) => {
+            tracing::info!("Microsoft OAuth not configured; skipping");
+            None
+        }
+        Err(e) => return Err(e),
+    };
+
     Ok(AuthServiceConfig {
         google,
         github,
         apple,
+        microsoft,
     })
 }
@@ -0,0 +1,71 @@
+//! JWT signing-key administration and the public key set.
+//!
+//! - `GET /auth/.well-known/jwks.json` serves the public half of every
+//!   access-token key, so other services can validate tokens without the
+//!   shared secret. It is empty unless `JWT_ACCESS_ALGORITHM=RS256`.
+//! - `GET /admin/auth/signing-keys` lists the configured keys by `kid`.
+//! - `POST /admin/auth/signing-keys/rotate` makes the newest configured
+//!   key primary on this replica; see [`be_auth_core::JwtKeyRing`] for
+//!   the full rotation procedure.
+//!
+//! Key material itself never crosses the wire: keys are declared in the
+//! environment, and these endpoints only report and switch between them.
+
+use be_auth_core::{JwtConfig, JwtKeyRing};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SigningKeyRingInfo {
+    pub algorithm: String,
+    /// `kid` stamped on newly issued tokens.
+    pub primary_kid: String,
+    /// Every `kid` accepted for validation, oldest first.
+    pub kids: Vec<String>,
+}
+
+impl From<&JwtKeyRing> for SigningKeyRingInfo {
+    fn from(ring: &JwtKeyRing) -> Self {
+        Self {
+            algorithm: format!("{:?}", ring.algorithm()),
+            primary_kid: ring.primary_kid().to_owned(),
+            kids: ring.kids().map(str::to_owned).collect(),
+        }
+    }
+}
+
+/// Body of both admin endpoints.
+#[derive(Debug, Clone, Serialize)]
+pub struct SigningKeysResponse {
+    pub access: SigningKeyRingInfo,
+    pub refresh: SigningKeyRingInfo,
+}
+
+impl From<&JwtConfig> for SigningKeysResponse {
+    fn from(config: &JwtConfig) -> Self {
+        Self {
+            access: config.access_keys.as_ref().into(),
+            refresh: config.refresh_keys.as_ref().into(),
+        }
+    }
+}
+
+/// Promote the newest key of both rings. Returns the resulting state.
+pub(crate) fn rotate(config: &JwtConfig) -> SigningKeysResponse {
+    config.access_keys.promote_newest();
+    config.refresh_keys.promote_newest();
+    config.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_a_single_key_ring() {
+        let config = JwtConfig::from_secrets("access", "refresh");
+        let response = rotate(&config);
+        assert_eq!(response.access.algorithm, "HS256");
+        assert_eq!(response.access.primary_kid, "v0");
+        assert_eq!(response.refresh.kids, ["v0"]);
+    }
+}
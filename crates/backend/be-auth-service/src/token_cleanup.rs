@@ -0,0 +1,84 @@
+//! Background worker that deletes spent auth tokens.
+//!
+//! Refresh tokens, login tokens, email-verification tokens and OAuth
+//! states are only ever looked up by hash while they are live; once
+//! expired, consumed or (after a grace period) revoked they are dead
+//! weight. Every tick deletes them (see
+//! [`DatabaseManager::cleanup_expired_auth_data`] for the exact rules)
+//! and logs the table sizes afterwards, so unbounded growth shows up in
+//! the logs before it shows up in query latency.
+
+use std::sync::Arc;
+
+use be_remote_db::{DatabaseManager, DbError};
+use tokio::sync::oneshot;
+use tokio::time::{Duration, sleep};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+pub struct TokenCleanupWorkerHandle {
+    shutdown: oneshot::Sender<()>,
+    join: tokio::task::JoinHandle<()>,
+}
+
+impl TokenCleanupWorkerHandle {
+    pub async fn shutdown(self) {
+        let Self { shutdown, join } = self;
+        let _ = shutdown.send(());
+        let _ = join.await;
+    }
+}
+
+/// Spawn the background worker. A sweep interrupted by shutdown rolls
+/// back and is redone on the next start.
+pub fn init_token_cleanup_worker(db: Arc<DatabaseManager>) -> TokenCleanupWorkerHandle {
+    let (shutdown_tx, mut shutdown_rx) = oneshot::channel::<()>();
+
+    let join = tokio::spawn(async move {
+        tracing::info!("Auth token cleanup worker started");
+        loop {
+            tokio::select! {
+                result = tick(&db) => {
+                    if let Err(e) = result {
+                        tracing::error!(error = %e, "Auth token cleanup failed");
+                    }
+                }
+                _ = &mut shutdown_rx => break,
+            }
+
+            tokio::select! {
+                _ = sleep(POLL_INTERVAL) => {}
+                _ = &mut shutdown_rx => break,
+            }
+        }
+        tracing::info!("Auth token cleanup worker shutting down");
+    });
+
+    TokenCleanupWorkerHandle {
+        shutdown: shutdown_tx,
+        join,
+    }
+}
+
+async fn tick(db: &DatabaseManager) -> Result<(), DbError> {
+    let deleted = db.cleanup_expired_auth_data().call().await?;
+    if deleted.total() > 0 {
+        tracing::info!(
+            oauth_states = deleted.oauth_states,
+            login_tokens = deleted.login_tokens,
+            refresh_tokens = deleted.refresh_tokens,
+            verification_tokens = deleted.verification_tokens,
+            "Deleted spent auth tokens"
+        );
+    }
+
+    let sizes = db.auth_token_table_sizes().call().await?;
+    tracing::info!(
+        oauth_states = sizes.oauth_states,
+        login_tokens = sizes.login_tokens,
+        refresh_tokens = sizes.refresh_tokens,
+        verification_tokens = sizes.verification_tokens,
+        "Auth token table sizes"
+    );
+    Ok(())
+}
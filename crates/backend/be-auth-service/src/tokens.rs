@@ -5,14 +5,16 @@
 //!   makes a stolen DB row useless on its own.
 //! - [`random_hex`]: bias-free hex string generated from `byte_len` bytes
 //!   of OS randomness.
-//! - [`generate_jwt_pair`]: produce an access/refresh JWT pair from a
-//!   shared identity, returning the SHA-256 fingerprint of the refresh
-//!   token (for DB persistence) and its absolute expiry.
-
-use auth_core::{Claims, Role};
+//! - [`generate_jwt_pair`]: produce an access/refresh JWT pair for a
+//!   user, returning the SHA-256 fingerprint of the refresh token (for
+//!   DB persistence) and its absolute expiry. The user's analytics
+//!   consent and token version ride along in both tokens.
+//! - [`generate_impersonation_token`]: a lone access token carrying an
+//!   `act` claim, for support impersonation.
+
+use auth_core::{Actor, AnalyticsConsent, Claims, Role};
 use be_auth_core::JwtConfig;
 use chrono::{DateTime, Duration, Utc};
-use jsonwebtoken::{Algorithm, Header, encode};
 use rand::Rng;
 use sha2::{Digest, Sha256};
 use uuid::Uuid;
@@ -38,11 +40,11 @@ pub(crate) struct JwtPair {
     pub refresh_expires_at: DateTime<Utc>,
 }
 
+/// `email_verified` is passed separately from `user` because the OAuth
+/// flow may have just flipped it and the row can lag behind.
 pub(crate) fn generate_jwt_pair(
     config: &JwtConfig,
-    user_id: Uuid,
-    email: &str,
-    display_name: Option<String>,
+    user: &be_remote_db::User,
     role: Role,
     email_verified: bool,
 ) -> AuthResult<JwtPair> {
@@ -50,12 +52,14 @@ pub(crate) fn generate_jwt_pair(
     let access_exp = now + Duration::hours(config.access_token_expiry_hours);
     let refresh_exp = now + Duration::days(config.refresh_token_expiry_days);
 
-    let sub = user_id.to_string();
+    let sub = user.id.to_string();
     let aud = "eurora".to_string();
+    let display_name = user.display_name.clone();
+    let analytics: AnalyticsConsent = user.analytics_consent.into();
 
     let access_claims = Claims {
         sub: sub.clone(),
-        email: email.to_string(),
+        email: user.email.clone(),
         display_name: display_name.clone(),
         exp: access_exp.timestamp(),
         iat: now.timestamp(),
@@ -64,11 +68,14 @@ pub(crate) fn generate_jwt_pair(
         aud: aud.clone(),
         email_verified,
         jti: Uuid::now_v7().to_string(),
+        analytics,
+        token_version: user.token_version,
+        act: None,
     };
 
     let refresh_claims = Claims {
         sub,
-        email: email.to_string(),
+        email: user.email.clone(),
         display_name,
         exp: refresh_exp.timestamp(),
         iat: now.timestamp(),
@@ -77,12 +84,18 @@ pub(crate) fn generate_jwt_pair(
         aud,
         email_verified,
         jti: Uuid::now_v7().to_string(),
+        analytics,
+        token_version: user.token_version,
+        act: None,
     };
 
-    let header = Header::new(Algorithm::HS256);
-    let access_token = encode(&header, &access_claims, &config.access_token_encoding_key)
+    let access_token = config
+        .access_keys
+        .encode(&access_claims)
         .map_err(|e| AuthError::TokenGeneration(e.to_string()))?;
-    let refresh_token = encode(&header, &refresh_claims, &config.refresh_token_encoding_key)
+    let refresh_token = config
+        .refresh_keys
+        .encode(&refresh_claims)
         .map_err(|e| AuthError::TokenGeneration(e.to_string()))?;
 
     let refresh_token_hash = sha256_token(&refresh_token);
@@ -95,6 +108,41 @@ pub(crate) fn generate_jwt_pair(
     })
 }
 
+/// Mint an access token that lets `actor_id` act as `user`. There is
+/// deliberately no refresh token, so the session ends at `expires_at`.
+/// `session_id` doubles as the `jti`, which is how requests made with
+/// the token are tied back to the session.
+pub(crate) fn generate_impersonation_token(
+    config: &JwtConfig,
+    user: &be_remote_db::User,
+    role: Role,
+    actor_id: Uuid,
+    session_id: Uuid,
+    expires_at: DateTime<Utc>,
+) -> AuthResult<String> {
+    let claims = Claims {
+        sub: user.id.to_string(),
+        email: user.email.clone(),
+        display_name: user.display_name.clone(),
+        exp: expires_at.timestamp(),
+        iat: Utc::now().timestamp(),
+        token_type: "access".to_string(),
+        role,
+        aud: "eurora".to_string(),
+        email_verified: user.email_verified,
+        jti: session_id.to_string(),
+        analytics: user.analytics_consent.into(),
+        token_version: user.token_version,
+        act: Some(Actor {
+            sub: actor_id.to_string(),
+        }),
+    };
+    config
+        .access_keys
+        .encode(&claims)
+        .map_err(|e| AuthError::TokenGeneration(e.to_string()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -44,6 +44,20 @@ async fn email_not_verified_envelope() {
     assert_eq!(body.error, error_kinds::EMAIL_NOT_VERIFIED);
 }
 
+#[tokio::test]
+async fn account_pending_deletion_envelope() {
+    let (status, body) = decode(AuthError::AccountPendingDeletion).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body.error, error_kinds::ACCOUNT_PENDING_DELETION);
+}
+
+#[tokio::test]
+async fn impersonated_envelope() {
+    let (status, body) = decode(AuthError::Impersonated).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body.error, error_kinds::IMPERSONATION_FORBIDDEN);
+}
+
 #[tokio::test]
 async fn invalid_input_passes_message_through() {
     let err = AuthError::InvalidInput("Email already taken".to_string());
@@ -8,10 +8,11 @@
 //! tests don't need a running database.
 
 use std::collections::HashSet;
+use std::sync::Arc;
 
 use auth_core::Role;
-use be_auth_core::JwtConfig;
-use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Validation};
+use be_auth_core::{JwtConfig, JwtKeyRing};
+use jsonwebtoken::{Algorithm, Validation};
 use uuid::Uuid;
 
 const TEST_SECRET: &[u8] = b"test-secret-do-not-use-in-production";
@@ -21,10 +22,8 @@ fn build_test_jwt_config() -> JwtConfig {
     validation.set_audience(&["eurora"]);
     validation.required_spec_claims.insert("aud".to_string());
     JwtConfig {
-        access_token_encoding_key: EncodingKey::from_secret(TEST_SECRET),
-        access_token_decoding_key: DecodingKey::from_secret(TEST_SECRET),
-        refresh_token_encoding_key: EncodingKey::from_secret(TEST_SECRET),
-        refresh_token_decoding_key: DecodingKey::from_secret(TEST_SECRET),
+        access_keys: Arc::new(JwtKeyRing::hs256(TEST_SECRET)),
+        refresh_keys: Arc::new(JwtKeyRing::hs256(TEST_SECRET)),
         access_token_expiry_hours: 1,
         refresh_token_expiry_days: 7,
         validation,
@@ -40,7 +39,6 @@ mod helpers {
     use auth_core::{Claims, Role};
     use be_auth_core::JwtConfig;
     use chrono::{DateTime, Duration, Utc};
-    use jsonwebtoken::{Algorithm, Header, encode};
     use uuid::Uuid;
 
     pub struct JwtPair {
@@ -73,21 +71,19 @@ mod helpers {
             aud: aud.clone(),
             email_verified,
             jti: Uuid::now_v7().to_string(),
+            analytics: Default::default(),
+            token_version: 0,
+            act: None,
         };
 
-        let header = Header::new(Algorithm::HS256);
-        let access_token = encode(
-            &header,
-            &make_claims(access_exp, "access"),
-            &config.access_token_encoding_key,
-        )
-        .expect("encode access");
-        let refresh_token = encode(
-            &header,
-            &make_claims(refresh_exp, "refresh"),
-            &config.refresh_token_encoding_key,
-        )
-        .expect("encode refresh");
+        let access_token = config
+            .access_keys
+            .encode(&make_claims(access_exp, "access"))
+            .expect("encode access");
+        let refresh_token = config
+            .refresh_keys
+            .encode(&make_claims(refresh_exp, "refresh"))
+            .expect("encode refresh");
 
         JwtPair {
             access_token,
@@ -0,0 +1,210 @@
+//! Admin API for runtime policy management.
+//!
+//! | Method | Path                                   | Outcome                                   |
+//! |--------|----------------------------------------|-------------------------------------------|
+//! | POST   | `/admin/authz/reload`                  | `200 PolicyStats` after a forced reload.  |
+//! | GET    | `/admin/authz/role-assignments`        | `200 ListRoleAssignmentsResponse`.        |
+//! | POST   | `/admin/authz/role-assignments`        | `201 RoleAssignment`, `409` if it exists. |
+//! | DELETE | `/admin/authz/role-assignments/{id}`   | `204`, `404` if unknown.                  |
+//!
+//! Writes reload this replica's enforcer straight away; other replicas
+//! pick the change up on their next watcher tick (see
+//! [`CasbinAuthz::spawn_watcher`]). Access is granted by the `Admin` role
+//! in `policy.csv`, which is itself only ever handed out through a role
+//! assignment.
+
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get, post};
+use axum::{Json, Router};
+use be_remote_db::{DatabaseManager, DbError, RoleAssignment};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::enforcer::{CasbinAuthz, PolicyStats};
+
+/// Longest subject or role name accepted.
+const MAX_NAME_LEN: usize = 64;
+
+pub struct AuthzAdminState {
+    pub authz: CasbinAuthz,
+    pub db: Arc<DatabaseManager>,
+}
+
+impl AuthzAdminState {
+    pub fn new(authz: CasbinAuthz, db: Arc<DatabaseManager>) -> Self {
+        Self { authz, db }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateRoleAssignmentRequest {
+    /// A user id, or a role name to make that role inherit `role`.
+    pub subject: String,
+    pub role: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListRoleAssignmentsResponse {
+    pub assignments: Vec<RoleAssignment>,
+}
+
+/// Build the admin router. Layered inside the monolith's authz
+/// middleware like every other service router.
+pub fn admin_router(state: Arc<AuthzAdminState>) -> Router {
+    Router::new()
+        .route("/admin/authz/reload", post(reload_policies))
+        .route(
+            "/admin/authz/role-assignments",
+            get(list_role_assignments).post(create_role_assignment),
+        )
+        .route(
+            "/admin/authz/role-assignments/{id}",
+            delete(delete_role_assignment),
+        )
+        .with_state(state)
+}
+
+async fn reload_policies(
+    State(state): State<Arc<AuthzAdminState>>,
+) -> Result<Json<PolicyStats>, AdminError> {
+    let stats = state.authz.reload().await.map_err(AdminError::Reload)?;
+    tracing::info!(
+        policies = stats.policies,
+        role_assignments = stats.role_assignments,
+        "Casbin policies reloaded on request"
+    );
+    Ok(Json(stats))
+}
+
+async fn list_role_assignments(
+    State(state): State<Arc<AuthzAdminState>>,
+) -> Result<Json<ListRoleAssignmentsResponse>, AdminError> {
+    let assignments = state.db.list_role_assignments().await?;
+    Ok(Json(ListRoleAssignmentsResponse { assignments }))
+}
+
+async fn create_role_assignment(
+    State(state): State<Arc<AuthzAdminState>>,
+    Json(body): Json<CreateRoleAssignmentRequest>,
+) -> Result<(StatusCode, Json<RoleAssignment>), AdminError> {
+    validate_name("subject", &body.subject)?;
+    validate_name("role", &body.role)?;
+    let assignment = state
+        .db
+        .create_role_assignment()
+        .subject(&body.subject)
+        .role(&body.role)
+        .call()
+        .await?;
+    tracing::info!(subject = %assignment.subject, role = %assignment.role, "Role assigned");
+    state.authz.reload().await.map_err(AdminError::Reload)?;
+    Ok((StatusCode::CREATED, Json(assignment)))
+}
+
+async fn delete_role_assignment(
+    State(state): State<Arc<AuthzAdminState>>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AdminError> {
+    state.db.delete_role_assignment(id).await?;
+    tracing::info!(%id, "Role assignment removed");
+    state.authz.reload().await.map_err(AdminError::Reload)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Names end up as Casbin subjects; keep them to plain identifiers.
+fn validate_name(field: &str, value: &str) -> Result<(), AdminError> {
+    let valid = !value.is_empty()
+        && value.len() <= MAX_NAME_LEN
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':'));
+    if valid {
+        Ok(())
+    } else {
+        Err(AdminError::InvalidArgument(format!(
+            "{field} must be 1-{MAX_NAME_LEN} letters, digits, '-', '_' or ':'"
+        )))
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+enum AdminError {
+    #[error("{0}")]
+    InvalidArgument(String),
+    #[error("role assignment already exists")]
+    AlreadyExists,
+    #[error("role assignment not found")]
+    NotFound,
+    #[error("database error: {0}")]
+    Database(#[source] DbError),
+    #[error("policy reload failed: {0}")]
+    Reload(#[source] crate::AuthzError),
+}
+
+impl From<DbError> for AdminError {
+    fn from(err: DbError) -> Self {
+        match err {
+            DbError::NotFound { .. } => Self::NotFound,
+            err if err.is_unique_violation() => Self::AlreadyExists,
+            other => Self::Database(other),
+        }
+    }
+}
+
+impl IntoResponse for AdminError {
+    fn into_response(self) -> Response {
+        let (status, kind) = match &self {
+            Self::InvalidArgument(_) => (StatusCode::BAD_REQUEST, "invalid_argument"),
+            Self::AlreadyExists => (StatusCode::CONFLICT, "already_exists"),
+            Self::NotFound => (StatusCode::NOT_FOUND, "not_found"),
+            Self::Database(_) => (StatusCode::INTERNAL_SERVER_ERROR, "database_error"),
+            Self::Reload(_) => (StatusCode::INTERNAL_SERVER_ERROR, "reload_failed"),
+        };
+        let message = match &self {
+            Self::Database(e) => {
+                tracing::error!(error = %e, "Authz admin database error");
+                "Database operation failed".to_owned()
+            }
+            Self::Reload(e) => {
+                tracing::error!(error = %e, "Casbin policy reload failed");
+                self.to_string()
+            }
+            _ => self.to_string(),
+        };
+        (
+            status,
+            Json(serde_json::json!({ "error": kind, "message": message })),
+        )
+            .into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_plain_identifiers() {
+        for ok in [
+            "Admin",
+            "Tier1",
+            "0190f3a2-7c1e-7b4a-9d2e-5f6a7b8c9d0e",
+            "ops:read",
+        ] {
+            assert!(validate_name("role", ok).is_ok(), "{ok}");
+        }
+        for bad in [
+            "",
+            "a,b",
+            "has space",
+            "p, Free",
+            "x".repeat(MAX_NAME_LEN + 1).as_str(),
+        ] {
+            assert!(validate_name("role", bad).is_err(), "{bad:?}");
+        }
+    }
+}
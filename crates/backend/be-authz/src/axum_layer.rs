@@ -7,10 +7,15 @@ use axum::middleware::Next;
 use axum::response::{IntoResponse, Response};
 use axum_extra::extract::cookie::CookieJar;
 use be_auth_core::JwtConfig;
+use be_remote_db::DatabaseManager;
 
 use crate::CasbinAuthz;
-use crate::bypass::{is_email_verification_exempt, is_rest_bypass};
-use crate::rate_limit::{self, AuthFailureRateLimiter, HealthCheckRateLimiter, TrustedProxies};
+use crate::bypass::{LOGIN_TOKEN_WAIT_PATH, is_email_verification_exempt, is_rest_bypass};
+use crate::impersonation::{self, Impersonation};
+use crate::rate_limit::{
+    self, AuthFailureRateLimiter, HealthCheckRateLimiter, LoginWaitRateLimiter, TrustedProxies,
+};
+use crate::token_version::{self, TokenVersionCheck, TokenVersionRepo};
 
 /// Cookie name carrying the access JWT for the browser SPA flow. Kept
 /// in sync with `be_auth_service::ACCESS_COOKIE`; we don't pull the
@@ -23,7 +28,14 @@ pub struct AuthzState {
     pub jwt_config: JwtConfig,
     pub rate_limiter: AuthFailureRateLimiter,
     pub health_rate_limiter: HealthCheckRateLimiter,
+    pub login_wait_rate_limiter: LoginWaitRateLimiter,
     pub trusted_proxies: TrustedProxies,
+    /// Where requests made with impersonation tokens are recorded. Without
+    /// it they are only logged.
+    pub impersonation_audit: Option<Arc<DatabaseManager>>,
+    /// Where users' current token versions are read from. Without it,
+    /// revoked access tokens keep working until they expire.
+    pub token_versions: Option<Arc<dyn TokenVersionRepo>>,
 }
 
 impl AuthzState {
@@ -39,9 +51,27 @@ impl AuthzState {
             jwt_config,
             rate_limiter,
             health_rate_limiter,
+            login_wait_rate_limiter: rate_limit::new_login_wait_rate_limiter(),
             trusted_proxies,
+            impersonation_audit: None,
+            token_versions: None,
         }
     }
+
+    /// Record requests made with impersonation tokens in `db`, where the
+    /// impersonated user can read them back.
+    pub fn with_impersonation_audit(mut self, db: Arc<DatabaseManager>) -> Self {
+        self.impersonation_audit = Some(db);
+        self
+    }
+
+    /// Refuse access tokens whose `token_version` is behind the one in
+    /// `repo`, i.e. tokens revoked by signing out everywhere. Costs one
+    /// primary-key lookup per authenticated request.
+    pub fn with_token_versions(mut self, repo: Arc<dyn TokenVersionRepo>) -> Self {
+        self.token_versions = Some(repo);
+        self
+    }
 }
 
 /// Pull the access JWT out of the request, preferring the
@@ -110,6 +140,12 @@ pub async fn authz_middleware(
             tracing::warn!(ip = %client_ip, "Rate limited health check request");
             return too_many_requests_response();
         }
+        if raw_path == LOGIN_TOKEN_WAIT_PATH
+            && state.login_wait_rate_limiter.check_key(&client_ip).is_err()
+        {
+            tracing::warn!(ip = %client_ip, "Rate limited login token wait request");
+            return too_many_requests_response();
+        }
         tracing::debug!(path = %raw_path, "Bypassing authorization for public route");
         return next.run(req).await;
     }
@@ -155,6 +191,27 @@ pub async fn authz_middleware(
         }
     };
 
+    if let Some(repo) = &state.token_versions {
+        match token_version::check(repo.as_ref(), &claims).await {
+            TokenVersionCheck::Current => {}
+            TokenVersionCheck::Revoked => {
+                tracing::warn!(sub = %claims.sub, "Revoked access token refused");
+                return (
+                    StatusCode::UNAUTHORIZED,
+                    axum::Json(serde_json::json!({"error": "Invalid or expired token"})),
+                )
+                    .into_response();
+            }
+            TokenVersionCheck::Unknown => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    axum::Json(serde_json::json!({"error": "Authorization error"})),
+                )
+                    .into_response();
+            }
+        }
+    }
+
     if !claims.email_verified && !is_email_verification_exempt(&policy_path) {
         return (
             StatusCode::FORBIDDEN,
@@ -165,13 +222,39 @@ pub async fn authz_middleware(
             .into_response();
     }
 
+    let impersonation = Impersonation::from_claims(&claims);
+    if impersonation.is_some() && impersonation::is_refused_route(&policy_path) {
+        tracing::warn!(
+            path = %raw_path,
+            actor = ?claims.act,
+            "Impersonation token refused on restricted route"
+        );
+        return (
+            StatusCode::FORBIDDEN,
+            axum::Json(serde_json::json!({"error": "Not available while impersonating a user"})),
+        )
+            .into_response();
+    }
+
     let role = claims.role.to_string();
 
-    match state.authz.enforce(&role, &policy_path, &method) {
+    match state
+        .authz
+        .enforce_user(&role, &claims.sub, &policy_path, &method)
+    {
         Ok(true) => {
             tracing::debug!(role = %role, path = %raw_path, method = %method, "REST authorized");
             req.extensions_mut().insert(claims);
-            next.run(req).await
+            let response = next.run(req).await;
+            if let Some(impersonation) = impersonation {
+                impersonation.record(
+                    state.impersonation_audit.as_ref(),
+                    &method,
+                    &policy_path,
+                    response.status(),
+                );
+            }
+            response
         }
         Ok(false) => {
             if state.rate_limiter.check_key(&client_ip).is_err() {
@@ -210,9 +293,9 @@ mod tests {
     use axum::Router;
     use axum::body::Body;
     use axum::http::{HeaderValue, Method, Request, StatusCode, header};
-    use axum::routing::post;
-    use be_auth_core::{Claims, JwtConfig, Role};
-    use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, encode};
+    use axum::routing::{get, post};
+    use be_auth_core::{Claims, JwtConfig, JwtKeyRing, Role};
+    use jsonwebtoken::{Algorithm, Validation};
     use tower::ServiceExt;
     use tower_http::cors::{AllowOrigin, CorsLayer};
 
@@ -228,10 +311,8 @@ mod tests {
         validation.set_audience(&["eurora"]);
         validation.required_spec_claims.insert("aud".to_string());
         JwtConfig {
-            access_token_encoding_key: EncodingKey::from_secret(TEST_JWT_SECRET),
-            access_token_decoding_key: DecodingKey::from_secret(TEST_JWT_SECRET),
-            refresh_token_encoding_key: EncodingKey::from_secret(TEST_JWT_SECRET),
-            refresh_token_decoding_key: DecodingKey::from_secret(TEST_JWT_SECRET),
+            access_keys: Arc::new(JwtKeyRing::hs256(TEST_JWT_SECRET)),
+            refresh_keys: Arc::new(JwtKeyRing::hs256(TEST_JWT_SECRET)),
             access_token_expiry_hours: 1,
             refresh_token_expiry_days: 7,
             validation,
@@ -240,8 +321,29 @@ mod tests {
     }
 
     fn mint_access_token(jwt_config: &JwtConfig, email_verified: bool) -> String {
-        let now = chrono::Utc::now();
+        jwt_config
+            .access_keys
+            .encode(&access_claims(email_verified))
+            .expect("failed to encode test JWT")
+    }
+
+    /// A verified user's token held by a support agent.
+    fn mint_impersonation_token(jwt_config: &JwtConfig) -> String {
         let claims = Claims {
+            act: Some(auth_core::Actor {
+                sub: uuid::Uuid::new_v4().to_string(),
+            }),
+            ..access_claims(true)
+        };
+        jwt_config
+            .access_keys
+            .encode(&claims)
+            .expect("failed to encode test JWT")
+    }
+
+    fn access_claims(email_verified: bool) -> Claims {
+        let now = chrono::Utc::now();
+        Claims {
             sub: uuid::Uuid::new_v4().to_string(),
             email: "user@example.com".to_string(),
             display_name: None,
@@ -252,16 +354,20 @@ mod tests {
             aud: "eurora".to_string(),
             email_verified,
             jti: uuid::Uuid::new_v4().to_string(),
-        };
-        encode(
-            &Header::new(Algorithm::HS256),
-            &claims,
-            &jwt_config.access_token_encoding_key,
-        )
-        .expect("failed to encode test JWT")
+            analytics: Default::default(),
+            token_version: 0,
+            act: None,
+        }
     }
 
     async fn build_router(jwt_config: JwtConfig) -> Router {
+        build_router_with_token_versions(jwt_config, None).await
+    }
+
+    async fn build_router_with_token_versions(
+        jwt_config: JwtConfig,
+        token_versions: Option<Arc<dyn TokenVersionRepo>>,
+    ) -> Router {
         let base = env!("CARGO_MANIFEST_DIR");
         let model = format!("{base}/../../../config/authz/model.conf");
         let policy = format!("{base}/../../../config/authz/policy.csv");
@@ -269,13 +375,15 @@ mod tests {
             .await
             .expect("failed to init enforcer");
 
-        let state = Arc::new(AuthzState::new(
+        let mut state = AuthzState::new(
             authz,
             jwt_config,
             new_auth_failure_rate_limiter(),
             new_health_check_rate_limiter(),
             TrustedProxies::new(vec![]),
-        ));
+        );
+        state.token_versions = token_versions;
+        let state = Arc::new(state);
 
         let cors = CorsLayer::new()
             .allow_origin(AllowOrigin::list([HeaderValue::from_static(
@@ -294,6 +402,17 @@ mod tests {
         Router::new()
             .route("/payment/checkout", post(|| async { StatusCode::OK }))
             .route("/payment/portal", post(|| async { StatusCode::OK }))
+            .route(
+                "/models/local",
+                get(|| async { StatusCode::OK }).delete(|| async { StatusCode::OK }),
+            )
+            .route("/models/local/pull", post(|| async { StatusCode::OK }))
+            .route("/auth/login-token/wait", post(|| async { StatusCode::OK }))
+            .route(
+                "/webhooks",
+                get(|| async { StatusCode::OK }).post(|| async { StatusCode::OK }),
+            )
+            .route("/exports", post(|| async { StatusCode::OK }))
             .layer(axum::middleware::from_fn_with_state(
                 state,
                 authz_middleware,
@@ -415,4 +534,101 @@ mod tests {
             Some(TEST_ALLOWED_ORIGIN)
         );
     }
+
+    #[tokio::test]
+    async fn free_users_cannot_pull_or_delete_local_models() {
+        let jwt_config = build_test_jwt_config();
+        let token = mint_access_token(&jwt_config, true);
+        let router = build_router(jwt_config).await;
+
+        for (method, uri, expected) in [
+            (Method::GET, "/models/local", StatusCode::OK),
+            (Method::DELETE, "/models/local", StatusCode::FORBIDDEN),
+            (Method::POST, "/models/local/pull", StatusCode::FORBIDDEN),
+        ] {
+            let response = router
+                .clone()
+                .oneshot(request_with_origin(method.clone(), uri, Some(&token)))
+                .await
+                .expect("router should respond");
+            assert_eq!(response.status(), expected, "{method} {uri}");
+        }
+    }
+
+    #[tokio::test]
+    async fn impersonation_tokens_cannot_reach_webhooks_or_exports() {
+        let jwt_config = build_test_jwt_config();
+        let token = mint_impersonation_token(&jwt_config);
+        let router = build_router(jwt_config).await;
+
+        for (method, uri, expected) in [
+            (Method::POST, "/payment/checkout", StatusCode::OK),
+            (Method::GET, "/webhooks", StatusCode::FORBIDDEN),
+            (Method::POST, "/webhooks", StatusCode::FORBIDDEN),
+            (Method::POST, "/exports", StatusCode::FORBIDDEN),
+        ] {
+            let response = router
+                .clone()
+                .oneshot(request_with_origin(method.clone(), uri, Some(&token)))
+                .await
+                .expect("router should respond");
+            assert_eq!(response.status(), expected, "{method} {uri}");
+        }
+    }
+
+    #[tokio::test]
+    async fn login_token_wait_is_rate_limited_per_client() {
+        let router = build_router(build_test_jwt_config()).await;
+        let mut statuses = Vec::new();
+        for _ in 0..7 {
+            let response = router
+                .clone()
+                .oneshot(request_with_origin(
+                    Method::POST,
+                    "/auth/login-token/wait",
+                    None,
+                ))
+                .await
+                .expect("router should respond");
+            statuses.push(response.status());
+        }
+
+        // The burst of six goes through; the seventh waiter is refused.
+        assert!(statuses[..6].iter().all(|s| *s == StatusCode::OK));
+        assert_eq!(statuses[6], StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    /// Every user is at this version.
+    struct FixedTokenVersion(i32);
+
+    #[async_trait::async_trait]
+    impl TokenVersionRepo for FixedTokenVersion {
+        async fn get_token_version(&self, _user_id: uuid::Uuid) -> be_remote_db::DbResult<i32> {
+            Ok(self.0)
+        }
+    }
+
+    #[tokio::test]
+    async fn tokens_behind_the_stored_version_are_refused() {
+        for (stored, expected) in [(0, StatusCode::OK), (1, StatusCode::UNAUTHORIZED)] {
+            let jwt_config = build_test_jwt_config();
+            let token = mint_access_token(&jwt_config, true);
+            let router = build_router_with_token_versions(
+                jwt_config,
+                Some(Arc::new(FixedTokenVersion(stored))),
+            )
+            .await;
+
+            let response = router
+                .oneshot(request_with_origin(
+                    Method::POST,
+                    "/payment/checkout",
+                    Some(&token),
+                ))
+                .await
+                .expect("router should respond");
+
+            assert_eq!(response.status(), expected, "stored version {stored}");
+        }
+    }
 }
@@ -1,7 +1,16 @@
-pub(crate) const REST_BYPASS_PREFIXES: &[&str] =
-    &["/releases/", "/extensions/", "/download/", "/auth/"];
+pub(crate) const REST_BYPASS_PREFIXES: &[&str] = &[
+    "/releases/",
+    "/extensions/",
+    "/download/",
+    "/auth/",
+    "/shared/",
+];
 pub(crate) const REST_BYPASS_EXACT: &[&str] = &["/payment/webhook", "/health", "/llm/info"];
 
+/// Public, but holds each request for up to half a minute; rate-limited
+/// per client in `authz_middleware`.
+pub(crate) const LOGIN_TOKEN_WAIT_PATH: &str = "/auth/login-token/wait";
+
 /// REST paths that still require a valid JWT but do not require
 /// `email_verified` to be true. Distinct from `REST_BYPASS_*`, which skips
 /// authentication entirely. Keep this list narrow — every entry is a route
@@ -55,6 +64,7 @@ mod tests {
         assert!(is_rest_bypass("/auth/login"));
         assert!(is_rest_bypass("/auth/refresh"));
         assert!(is_rest_bypass("/auth/email/verify"));
+        assert!(is_rest_bypass("/shared/assets/abc123"));
     }
 
     #[test]
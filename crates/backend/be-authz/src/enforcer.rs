@@ -1,23 +1,96 @@
-use std::sync::Arc;
+//! Casbin enforcer with runtime reloads.
+//!
+//! Policies come from two places: the model and policy files under
+//! `config/authz/`, and the role assignments in the database
+//! (`authz_role_assignments`), which are applied on top as `g` rules. A
+//! reload rebuilds the enforcer from both off to the side and swaps it
+//! in, so requests in flight keep the enforcer they started with and a
+//! broken policy file leaves the previous one serving.
 
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::{Duration, SystemTime};
+
+use be_remote_db::{DatabaseManager, DbResult};
 use casbin::{CoreApi, DefaultModel, Enforcer, MgmtApi, prelude::FileAdapter};
+use serde::Serialize;
+use tokio::sync::Mutex;
 
 use crate::AuthzError;
 
+/// Seconds between checks for policy changes. Unset or `0` disables the
+/// watcher; reloads then only happen through the admin API.
+pub const ENV_AUTHZ_WATCH_INTERVAL_SECS: &str = "AUTHZ_WATCH_INTERVAL_SECS";
+
+/// Read [`ENV_AUTHZ_WATCH_INTERVAL_SECS`].
+pub fn watch_interval_from_env() -> Result<Option<Duration>, AuthzError> {
+    let Some(raw) = std::env::var(ENV_AUTHZ_WATCH_INTERVAL_SECS)
+        .ok()
+        .filter(|s| !s.trim().is_empty())
+    else {
+        return Ok(None);
+    };
+    let secs: u64 = raw.trim().parse().map_err(|_| {
+        AuthzError::Init(format!(
+            "invalid `{ENV_AUTHZ_WATCH_INTERVAL_SECS}` value `{raw}`"
+        ))
+    })?;
+    Ok((secs > 0).then(|| Duration::from_secs(secs)))
+}
+
+/// Source of the runtime role assignments, as `(subject, role)` pairs.
+/// `DatabaseManager` is the canonical impl; the trait keeps the enforcer
+/// testable without a database.
+#[async_trait::async_trait]
+pub trait RoleAssignmentSource: Send + Sync {
+    async fn role_assignments(&self) -> DbResult<Vec<(String, String)>>;
+}
+
+#[async_trait::async_trait]
+impl RoleAssignmentSource for DatabaseManager {
+    async fn role_assignments(&self) -> DbResult<Vec<(String, String)>> {
+        Ok(self
+            .list_role_assignments()
+            .await?
+            .into_iter()
+            .map(|a| (a.subject, a.role))
+            .collect())
+    }
+}
+
+/// What the enforcer was built from on its last (re)load.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PolicyStats {
+    pub policies: usize,
+    pub role_assignments: usize,
+}
+
+/// Inputs of the last load, compared by [`CasbinAuthz::reload_if_changed`].
+#[derive(Debug, Default, PartialEq)]
+struct Snapshot {
+    model_modified: Option<SystemTime>,
+    policy_modified: Option<SystemTime>,
+    role_assignments: Vec<(String, String)>,
+}
+
 #[derive(Clone)]
 pub struct CasbinAuthz {
-    enforcer: Arc<Enforcer>,
+    enforcer: Arc<RwLock<Arc<Enforcer>>>,
+    model_path: Arc<str>,
+    policy_path: Arc<str>,
+    role_assignments: Option<Arc<dyn RoleAssignmentSource>>,
+    /// Serializes reloads and remembers what the current enforcer was
+    /// built from.
+    loaded: Arc<Mutex<Snapshot>>,
 }
 
 impl CasbinAuthz {
     pub async fn new(model_path: &str, policy_path: &str) -> Result<Self, AuthzError> {
-        let model = DefaultModel::from_file(model_path)
-            .await
-            .map_err(|e| AuthzError::Init(format!("Failed to load model: {e}")))?;
-        let adapter = FileAdapter::new(policy_path.to_owned());
-        let enforcer = Enforcer::new(model, adapter)
-            .await
-            .map_err(|e| AuthzError::Init(e.to_string()))?;
+        let snapshot = Snapshot {
+            model_modified: modified(model_path).await,
+            policy_modified: modified(policy_path).await,
+            role_assignments: Vec::new(),
+        };
+        let enforcer = build_enforcer(model_path, policy_path, &[]).await?;
 
         tracing::info!(
             policies = enforcer.get_policy().len(),
@@ -25,16 +98,173 @@ impl CasbinAuthz {
         );
 
         Ok(Self {
-            enforcer: Arc::new(enforcer),
+            enforcer: Arc::new(RwLock::new(Arc::new(enforcer))),
+            model_path: model_path.into(),
+            policy_path: policy_path.into(),
+            role_assignments: None,
+            loaded: Arc::new(Mutex::new(snapshot)),
         })
     }
 
+    /// Layer the role assignments from `source` on top of the policy file,
+    /// now and on every reload.
+    pub async fn with_role_assignments(
+        mut self,
+        source: Arc<dyn RoleAssignmentSource>,
+    ) -> Result<Self, AuthzError> {
+        self.role_assignments = Some(source);
+        self.reload().await?;
+        Ok(self)
+    }
+
     #[must_use = "authorization result must be checked"]
     pub fn enforce(&self, role: &str, resource: &str, action: &str) -> Result<bool, AuthzError> {
-        self.enforcer
+        self.current()
             .enforce((role, resource, action))
             .map_err(|e| AuthzError::Enforcement(e.to_string()))
     }
+
+    /// [`Self::enforce`] for a signed-in user: allowed if either the role
+    /// in their token or a role assigned to `user_id` grants access.
+    #[must_use = "authorization result must be checked"]
+    pub fn enforce_user(
+        &self,
+        role: &str,
+        user_id: &str,
+        resource: &str,
+        action: &str,
+    ) -> Result<bool, AuthzError> {
+        let enforcer = self.current();
+        for subject in [role, user_id] {
+            if enforcer
+                .enforce((subject, resource, action))
+                .map_err(|e| AuthzError::Enforcement(e.to_string()))?
+            {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Rebuild the enforcer from the policy files and role assignments.
+    /// On error the current enforcer stays in place.
+    pub async fn reload(&self) -> Result<PolicyStats, AuthzError> {
+        let mut loaded = self.loaded.lock().await;
+        let snapshot = self.snapshot().await?;
+        let stats = self.install(&snapshot).await?;
+        *loaded = snapshot;
+        Ok(stats)
+    }
+
+    /// [`Self::reload`], but only if a policy file's modification time or
+    /// the role assignments changed since the last load. Lets a replica
+    /// pick up assignments another replica wrote.
+    pub async fn reload_if_changed(&self) -> Result<Option<PolicyStats>, AuthzError> {
+        let mut loaded = self.loaded.lock().await;
+        let snapshot = self.snapshot().await?;
+        if snapshot == *loaded {
+            return Ok(None);
+        }
+        let stats = self.install(&snapshot).await?;
+        *loaded = snapshot;
+        Ok(Some(stats))
+    }
+
+    /// Poll for policy changes every `interval`, reloading when
+    /// [`Self::reload_if_changed`] finds any. Failures are logged and the
+    /// current enforcer keeps serving.
+    pub fn spawn_watcher(&self, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let authz = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                match authz.reload_if_changed().await {
+                    Ok(Some(stats)) => tracing::info!(
+                        policies = stats.policies,
+                        role_assignments = stats.role_assignments,
+                        "Casbin policies changed, reloaded"
+                    ),
+                    Ok(None) => {}
+                    Err(e) => {
+                        tracing::error!(error = %e, "Casbin policy reload failed, keeping current policies");
+                    }
+                }
+            }
+        })
+    }
+
+    fn current(&self) -> Arc<Enforcer> {
+        self.enforcer
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    async fn snapshot(&self) -> Result<Snapshot, AuthzError> {
+        let role_assignments = match &self.role_assignments {
+            Some(source) => source
+                .role_assignments()
+                .await
+                .map_err(|e| AuthzError::Init(format!("Failed to load role assignments: {e}")))?,
+            None => Vec::new(),
+        };
+        Ok(Snapshot {
+            model_modified: modified(&self.model_path).await,
+            policy_modified: modified(&self.policy_path).await,
+            role_assignments,
+        })
+    }
+
+    async fn install(&self, snapshot: &Snapshot) -> Result<PolicyStats, AuthzError> {
+        let enforcer = build_enforcer(
+            &self.model_path,
+            &self.policy_path,
+            &snapshot.role_assignments,
+        )
+        .await?;
+        let stats = PolicyStats {
+            policies: enforcer.get_policy().len(),
+            role_assignments: snapshot.role_assignments.len(),
+        };
+        *self
+            .enforcer
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = Arc::new(enforcer);
+        Ok(stats)
+    }
+}
+
+async fn build_enforcer(
+    model_path: &str,
+    policy_path: &str,
+    role_assignments: &[(String, String)],
+) -> Result<Enforcer, AuthzError> {
+    let model = DefaultModel::from_file(model_path)
+        .await
+        .map_err(|e| AuthzError::Init(format!("Failed to load model: {e}")))?;
+    let adapter = FileAdapter::new(policy_path.to_owned());
+    let mut enforcer = Enforcer::new(model, adapter)
+        .await
+        .map_err(|e| AuthzError::Init(e.to_string()))?;
+
+    // Assignments live in the database; never write them into the file.
+    enforcer.enable_auto_save(false);
+    for (subject, role) in role_assignments {
+        // One at a time: a batch is rejected whole if any rule already
+        // exists in the file, and a duplicate is harmless.
+        enforcer
+            .add_grouping_policy(vec![subject.clone(), role.clone()])
+            .await
+            .map_err(|e| AuthzError::Init(format!("Failed to apply role assignment: {e}")))?;
+    }
+    Ok(enforcer)
+}
+
+async fn modified(path: &str) -> Option<SystemTime> {
+    tokio::fs::metadata(path).await.ok()?.modified().ok()
 }
 
 #[cfg(test)]
@@ -50,6 +280,24 @@ mod tests {
             .expect("failed to init enforcer")
     }
 
+    struct StaticAssignments(std::sync::Mutex<Vec<(String, String)>>);
+
+    impl StaticAssignments {
+        fn grant(&self, subject: &str, role: &str) {
+            self.0
+                .lock()
+                .unwrap()
+                .push((subject.to_owned(), role.to_owned()));
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl RoleAssignmentSource for StaticAssignments {
+        async fn role_assignments(&self) -> DbResult<Vec<(String, String)>> {
+            Ok(self.0.lock().unwrap().clone())
+        }
+    }
+
     #[tokio::test]
     async fn free_can_list_threads() {
         let authz = test_authz().await;
@@ -72,6 +320,20 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn free_can_cancel_chat() {
+        let authz = test_authz().await;
+        assert!(
+            authz
+                .enforce(
+                    "Free",
+                    "/threads/{thread_id}/chat/{request_id}/cancel",
+                    "POST"
+                )
+                .unwrap()
+        );
+    }
+
     #[tokio::test]
     async fn free_can_generate_title() {
         let authz = test_authz().await;
@@ -112,6 +374,28 @@ mod tests {
         assert!(!authz.enforce("Free", "/admin/users", "GET").unwrap());
     }
 
+    #[tokio::test]
+    async fn only_admins_manage_local_models() {
+        let source = Arc::new(StaticAssignments(Default::default()));
+        source.grant("admin-1", "Admin");
+        let authz = test_authz()
+            .await
+            .with_role_assignments(source)
+            .await
+            .unwrap();
+
+        assert!(
+            authz
+                .enforce_user("Free", "user-1", "/models/local", "GET")
+                .unwrap()
+        );
+        for (path, method) in [("/models/local", "DELETE"), ("/models/local/pull", "POST")] {
+            assert!(!authz.enforce_user("Free", "user-1", path, method).unwrap());
+            assert!(!authz.enforce_user("Tier1", "user-1", path, method).unwrap());
+            assert!(authz.enforce_user("Free", "admin-1", path, method).unwrap());
+        }
+    }
+
     #[tokio::test]
     async fn unknown_role_denied() {
         let authz = test_authz().await;
@@ -144,4 +428,53 @@ mod tests {
             );
         }
     }
+
+    #[tokio::test]
+    async fn role_assignments_grant_users_extra_roles() {
+        let source = Arc::new(StaticAssignments(Default::default()));
+        source.grant("user-1", "Admin");
+        let authz = test_authz()
+            .await
+            .with_role_assignments(source)
+            .await
+            .unwrap();
+
+        assert!(
+            authz
+                .enforce_user("Free", "user-1", "/admin/authz/reload", "POST")
+                .unwrap()
+        );
+        assert!(
+            !authz
+                .enforce_user("Free", "user-2", "/admin/authz/reload", "POST")
+                .unwrap()
+        );
+        // The token role still applies on its own.
+        assert!(
+            authz
+                .enforce_user("Free", "user-2", "/threads", "GET")
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn reload_if_changed_picks_up_new_assignments_once() {
+        let source = Arc::new(StaticAssignments(Default::default()));
+        let authz = test_authz()
+            .await
+            .with_role_assignments(source.clone())
+            .await
+            .unwrap();
+        assert_eq!(authz.reload_if_changed().await.unwrap(), None);
+
+        source.grant("user-1", "Admin");
+        let stats = authz.reload_if_changed().await.unwrap();
+        assert_eq!(stats.map(|s| s.role_assignments), Some(1));
+        assert!(
+            authz
+                .enforce_user("Free", "user-1", "/admin/authz/reload", "POST")
+                .unwrap()
+        );
+        assert_eq!(authz.reload_if_changed().await.unwrap(), None);
+    }
 }
@@ -165,6 +165,9 @@ mod tests {
             aud: "eurora".into(),
             email_verified: true,
             jti: Uuid::new_v4().to_string(),
+            analytics: Default::default(),
+            token_version: 0,
+            act: None,
         };
         req.extensions_mut().insert(claims);
         next.run(req).await
@@ -0,0 +1,112 @@
+//! Audit trail for requests made with impersonation tokens.
+//!
+//! Tokens carrying an `act` claim (issued by `POST /admin/auth/impersonate`)
+//! are refused on `/admin/*`, and on `/webhooks` and `/exports`, which can
+//! send the user's data somewhere the support agent can reach it. The
+//! `/auth` routes refuse them in `be-auth-service`. Everywhere else each
+//! request is logged with both identities and, once a database is attached with
+//! [`AuthzState::with_impersonation_audit`](crate::AuthzState::with_impersonation_audit),
+//! appended to the session's `impersonation_requests` trail, which the
+//! user reads back at `GET /auth/impersonations`.
+
+use std::sync::Arc;
+
+use axum::http::StatusCode;
+use be_auth_core::Claims;
+use be_remote_db::DatabaseManager;
+use uuid::Uuid;
+
+/// Route prefixes an impersonation token may never reach, whatever the
+/// impersonated user's roles: admin routes, and routes that move the
+/// user's data out of the service.
+const REFUSED_PREFIXES: &[&str] = &["/admin", "/webhooks", "/exports"];
+
+pub(crate) fn is_refused_route(policy_path: &str) -> bool {
+    REFUSED_PREFIXES.iter().any(|prefix| {
+        policy_path
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    })
+}
+
+/// Who is behind an impersonated request. Captured before the claims
+/// move into the request extensions.
+pub(crate) struct Impersonation {
+    actor: String,
+    user: String,
+    /// The token's `jti`, which is the session id.
+    session: String,
+}
+
+impl Impersonation {
+    pub(crate) fn from_claims(claims: &Claims) -> Option<Self> {
+        claims.act.as_ref().map(|actor| Self {
+            actor: actor.sub.clone(),
+            user: claims.sub.clone(),
+            session: claims.jti.clone(),
+        })
+    }
+
+    /// Log the request and, with a database, append it to the session's
+    /// trail. The write happens off the request path; a failure is
+    /// logged rather than failing a response that has already been
+    /// produced.
+    pub(crate) fn record(
+        self,
+        db: Option<&Arc<DatabaseManager>>,
+        method: &str,
+        path: &str,
+        status: StatusCode,
+    ) {
+        tracing::info!(
+            actor = %self.actor,
+            user = %self.user,
+            session = %self.session,
+            %method,
+            %path,
+            status = status.as_u16(),
+            "Impersonated request"
+        );
+        let Some(db) = db else {
+            return;
+        };
+        let Ok(session_id) = Uuid::parse_str(&self.session) else {
+            tracing::warn!(session = %self.session, "Impersonation token has a malformed jti");
+            return;
+        };
+        let db = db.clone();
+        let method = method.to_owned();
+        let path = path.to_owned();
+        tokio::spawn(async move {
+            let result = db
+                .record_impersonation_request()
+                .session_id(session_id)
+                .method(&method)
+                .path(&path)
+                .status(status.as_u16() as i16)
+                .call()
+                .await;
+            if let Err(e) = result {
+                tracing::error!(%session_id, error = %e, "Failed to record impersonated request");
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refused_routes_are_recognised_by_prefix() {
+        assert!(is_refused_route("/admin/authz/reload"));
+        assert!(is_refused_route("/admin/auth/impersonate"));
+        assert!(is_refused_route("/webhooks"));
+        assert!(is_refused_route("/webhooks/{endpoint_id}/deliveries"));
+        assert!(is_refused_route("/exports"));
+        assert!(is_refused_route("/exports/{export_id}/download"));
+        assert!(!is_refused_route("/administrator"));
+        assert!(!is_refused_route("/exportsx"));
+        assert!(!is_refused_route("/threads"));
+    }
+}
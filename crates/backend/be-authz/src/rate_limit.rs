@@ -8,6 +8,9 @@ pub type AuthFailureRateLimiter =
 pub type HealthCheckRateLimiter =
     Arc<RateLimiter<IpAddr, DefaultKeyedStateStore<IpAddr>, DefaultClock>>;
 
+pub type LoginWaitRateLimiter =
+    Arc<RateLimiter<IpAddr, DefaultKeyedStateStore<IpAddr>, DefaultClock>>;
+
 #[derive(Clone, Debug)]
 enum TrustedEntry {
     Exact(IpAddr),
@@ -117,6 +120,14 @@ pub fn new_health_check_rate_limiter() -> HealthCheckRateLimiter {
     ))
 }
 
+/// `/auth/login-token/wait` per client. A desktop signing in calls it
+/// about twice a minute; the burst leaves room for a few behind one NAT.
+pub fn new_login_wait_rate_limiter() -> LoginWaitRateLimiter {
+    Arc::new(RateLimiter::keyed(
+        Quota::per_minute(NonZeroU32::new(12).unwrap()).allow_burst(NonZeroU32::new(6).unwrap()),
+    ))
+}
+
 pub fn extract_client_ip(
     headers: &http::HeaderMap,
     peer_addr: Option<IpAddr>,
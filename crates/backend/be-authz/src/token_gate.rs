@@ -10,6 +10,10 @@ use uuid::Uuid;
 const HTTP_TOKEN_GATED_ROUTES: &[(Method, &str)] = &[
     (Method::POST, "/threads/{thread_id}/title"),
     (Method::GET, "/threads/{thread_id}/chat"),
+    (Method::POST, "/automations/{automation_id}/run"),
+    (Method::POST, "/workflows/{workflow_id}/run"),
+    (Method::POST, "/workflows/templates/{template_id}/run"),
+    (Method::POST, "/reports"),
 ];
 
 /// True if the (method, matched_path) tuple identifies a route whose call
@@ -110,12 +114,27 @@ mod tests {
             &Method::GET,
             "/threads/{thread_id}/chat"
         ));
+        assert!(is_http_token_gated(
+            &Method::POST,
+            "/automations/{automation_id}/run"
+        ));
+        assert!(is_http_token_gated(
+            &Method::POST,
+            "/workflows/{workflow_id}/run"
+        ));
+        assert!(is_http_token_gated(
+            &Method::POST,
+            "/workflows/templates/{template_id}/run"
+        ));
+        assert!(is_http_token_gated(&Method::POST, "/reports"));
     }
 
     #[test]
     fn http_non_gated_routes_pass() {
         assert!(!is_http_token_gated(&Method::GET, "/threads"));
         assert!(!is_http_token_gated(&Method::POST, "/threads"));
+        assert!(!is_http_token_gated(&Method::POST, "/automations"));
+        assert!(!is_http_token_gated(&Method::POST, "/workflows"));
         assert!(!is_http_token_gated(
             &Method::GET,
             "/threads/{thread_id}/messages"
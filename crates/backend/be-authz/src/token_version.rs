@@ -0,0 +1,51 @@
+//! Refusing access tokens revoked before they expire.
+//!
+//! Every token carries the user's `token_version` from when it was
+//! minted. Signing out everywhere bumps the stored version, so a token
+//! behind it was revoked even though its signature and expiry still
+//! check out. Attach a source with
+//! [`AuthzState::with_token_versions`](crate::AuthzState::with_token_versions)
+//! to enforce this; without one, tokens are only checked statelessly.
+
+use be_auth_core::Claims;
+use be_remote_db::{DatabaseManager, DbResult};
+use uuid::Uuid;
+
+/// Where the current token version of a user is read from.
+/// `DatabaseManager` is the canonical impl; the trait lets the
+/// middleware be tested without Postgres.
+#[async_trait::async_trait]
+pub trait TokenVersionRepo: Send + Sync {
+    async fn get_token_version(&self, user_id: Uuid) -> DbResult<i32>;
+}
+
+#[async_trait::async_trait]
+impl TokenVersionRepo for DatabaseManager {
+    async fn get_token_version(&self, user_id: Uuid) -> DbResult<i32> {
+        self.get_token_version().user_id(user_id).call().await
+    }
+}
+
+pub(crate) enum TokenVersionCheck {
+    Current,
+    /// The token predates the user's last sign-out everywhere, or the
+    /// user no longer exists.
+    Revoked,
+    /// The version couldn't be read.
+    Unknown,
+}
+
+pub(crate) async fn check(repo: &dyn TokenVersionRepo, claims: &Claims) -> TokenVersionCheck {
+    let Ok(user_id) = Uuid::parse_str(&claims.sub) else {
+        return TokenVersionCheck::Revoked;
+    };
+    match repo.get_token_version(user_id).await {
+        Ok(current) if claims.token_version < current => TokenVersionCheck::Revoked,
+        Ok(_) => TokenVersionCheck::Current,
+        Err(e) if e.is_not_found() => TokenVersionCheck::Revoked,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to read token version");
+            TokenVersionCheck::Unknown
+        }
+    }
+}
@@ -62,4 +62,54 @@ impl EmailService {
         tracing::info!(to = to, "Verification email sent");
         Ok(())
     }
+
+    /// Final confirmation that an account and its data are gone. Sent to
+    /// an address that no longer belongs to any account, so there is no
+    /// link to follow.
+    pub async fn send_account_deleted_email(&self, to: &str) -> Result<(), EmailError> {
+        let email = templates::account_deleted_email();
+
+        let request = SendEmailRequest::builder()
+            .from(&self.from_address)
+            .to(vec![to.to_string()])
+            .subject(email.subject)
+            .html(email.html)
+            .text(email.text)
+            .build();
+
+        request
+            .execute(&self.client)
+            .await
+            .map_err(|e| EmailError::Send(e.to_string()))?;
+
+        tracing::info!("Account deletion confirmation email sent");
+        Ok(())
+    }
+
+    /// Tell a user that a file they uploaded was quarantined by the
+    /// malware scanner and can't be opened any more.
+    pub async fn send_asset_quarantined_email(
+        &self,
+        to: &str,
+        asset_name: &str,
+        signature: Option<&str>,
+    ) -> Result<(), EmailError> {
+        let email = templates::asset_quarantined_email(asset_name, signature);
+
+        let request = SendEmailRequest::builder()
+            .from(&self.from_address)
+            .to(vec![to.to_string()])
+            .subject(email.subject)
+            .html(email.html)
+            .text(email.text)
+            .build();
+
+        request
+            .execute(&self.client)
+            .await
+            .map_err(|e| EmailError::Send(e.to_string()))?;
+
+        tracing::info!("Asset quarantine notice sent");
+        Ok(())
+    }
 }
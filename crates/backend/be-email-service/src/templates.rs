@@ -1,10 +1,10 @@
-pub struct VerificationEmail {
+pub struct RenderedEmail {
     pub subject: &'static str,
     pub html: String,
     pub text: String,
 }
 
-pub fn verification_email(verification_url: &str, display_name: Option<&str>) -> VerificationEmail {
+pub fn verification_email(verification_url: &str, display_name: Option<&str>) -> RenderedEmail {
     let greeting = match display_name {
         Some(name) => format!("Hi {name},"),
         None => "Hi,".to_string(),
@@ -37,9 +37,92 @@ pub fn verification_email(verification_url: &str, display_name: Option<&str>) ->
          This link expires in 24 hours. If you didn't create an account, you can ignore this email."
     );
 
-    VerificationEmail {
+    RenderedEmail {
         subject: "Verify your email address",
         html,
         text,
     }
 }
+
+pub fn account_deleted_email() -> RenderedEmail {
+    let html = r#"<!DOCTYPE html>
+<html>
+<head><meta charset="utf-8"></head>
+<body style="font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif; max-width: 600px; margin: 0 auto; padding: 40px 20px; color: #1a1a1a;">
+  <h2 style="margin-bottom: 24px;">Hi,</h2>
+  <p>Your Eurora account has been deleted, as you asked.</p>
+  <p>Your threads, activities, assets, settings, and sign-in methods have been erased, and any subscription has been cancelled.</p>
+  <p style="color: #666; font-size: 14px;">This is the last email you'll get from us.</p>
+</body>
+</html>"#
+        .to_string();
+
+    let text = "Hi,\n\n\
+         Your Eurora account has been deleted, as you asked.\n\n\
+         Your threads, activities, assets, settings, and sign-in methods have been erased, and any subscription has been cancelled.\n\n\
+         This is the last email you'll get from us."
+        .to_string();
+
+    RenderedEmail {
+        subject: "Your Eurora account has been deleted",
+        html,
+        text,
+    }
+}
+
+pub fn asset_quarantined_email(asset_name: &str, signature: Option<&str>) -> RenderedEmail {
+    let name_html = escape_html(asset_name);
+    let detection_html = signature
+        .map(|sig| {
+            format!(
+                r#"<p style="color: #666; font-size: 14px;">Detected: {}</p>"#,
+                escape_html(sig)
+            )
+        })
+        .unwrap_or_default();
+    let detection_text = signature
+        .map(|sig| format!("Detected: {sig}\n\n"))
+        .unwrap_or_default();
+
+    let html = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head><meta charset="utf-8"></head>
+<body style="font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif; max-width: 600px; margin: 0 auto; padding: 40px 20px; color: #1a1a1a;">
+  <h2 style="margin-bottom: 24px;">Hi,</h2>
+  <p>Our malware scan flagged a file you uploaded to Eurora, <strong>{name_html}</strong>, so we've quarantined it. It can no longer be opened or downloaded.</p>
+  {detection_html}
+  <p style="color: #666; font-size: 14px;">If the file came from somewhere you don't trust, scan the device you uploaded it from. If you think this is a mistake, reply to this email.</p>
+</body>
+</html>"#
+    );
+
+    let text = format!(
+        "Hi,\n\n\
+         Our malware scan flagged a file you uploaded to Eurora, \"{asset_name}\", so we've quarantined it. It can no longer be opened or downloaded.\n\n\
+         {detection_text}\
+         If the file came from somewhere you don't trust, scan the device you uploaded it from. If you think this is a mistake, reply to this email."
+    );
+
+    RenderedEmail {
+        subject: "A file you uploaded was quarantined",
+        html,
+        text,
+    }
+}
+
+/// User-supplied text (file names) goes into HTML bodies escaped.
+fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,395 @@
+//! Builds the export archive.
+//!
+//! Layout of the zip:
+//!
+//! ```text
+//! manifest.json          format version, export id, counts
+//! account.json           profile row
+//! settings.json          cloud settings blob (absent if never synced)
+//! threads/<id>.json      thread row plus every message in every branch
+//! activities.json        activities, each with all of its sessions
+//! devices.json           the devices sessions and assets were synced from
+//! memories.json          facts the assistant remembers about the user
+//! automations.json
+//! workflows.json
+//! assets.json            asset metadata, with each asset's path in the archive
+//! assets/<id>.<ext>      asset contents
+//! ```
+//!
+//! The zip is written on a blocking thread fed over a channel, so
+//! compression never stalls the runtime while the async side is still
+//! fetching rows and asset bytes.
+
+use std::io::{Cursor, Write};
+
+use be_remote_db::{
+    Activity, ActivitySession, DataExport, DataExportAssetMode, DatabaseManager, Message,
+    PaginationParams, Thread,
+};
+use be_storage::StorageService;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::error::{ExportResult, ExportServiceError};
+
+/// Bumped when the archive layout changes incompatibly.
+pub(crate) const FORMAT_VERSION: u32 = 1;
+
+/// Page size for the paginated list queries.
+const PAGE: u32 = PaginationParams::MAX_LIMIT;
+
+#[derive(Debug, Default, Serialize)]
+struct Counts {
+    threads: usize,
+    messages: usize,
+    activities: usize,
+    activity_sessions: usize,
+    devices: usize,
+    memories: usize,
+    automations: usize,
+    workflows: usize,
+    assets: usize,
+    /// Assets whose contents couldn't be read; see `assets.json`.
+    missing_assets: usize,
+}
+
+#[derive(Serialize)]
+struct Manifest<'a> {
+    format_version: u32,
+    export_id: Uuid,
+    user_id: Uuid,
+    generated_at: DateTime<Utc>,
+    asset_mode: DataExportAssetMode,
+    counts: &'a Counts,
+}
+
+#[derive(Serialize)]
+struct ThreadEntry<'a> {
+    thread: &'a Thread,
+    messages: &'a [Message],
+}
+
+#[derive(Serialize)]
+struct ActivityEntry {
+    #[serde(flatten)]
+    activity: Activity,
+    sessions: Vec<ActivitySession>,
+}
+
+#[derive(Serialize)]
+struct AssetEntry {
+    id: Uuid,
+    name: String,
+    mime_type: String,
+    size_bytes: Option<i64>,
+    checksum_sha256: Option<String>,
+    metadata: serde_json::Value,
+    device_id: Option<Uuid>,
+    created_at: DateTime<Utc>,
+    /// Where the contents sit in the archive; `None` if they couldn't be
+    /// read.
+    path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Everything the user owns, as zip bytes.
+pub(crate) async fn build_archive(
+    db: &DatabaseManager,
+    storage: &StorageService,
+    export: &DataExport,
+) -> ExportResult<Vec<u8>> {
+    let user_id = export.user_id;
+    let mut zip = ZipSink::spawn();
+    let mut counts = Counts::default();
+
+    let user = db.get_user().id(user_id).call().await?;
+    zip.add_json("account.json", &user).await?;
+
+    if let Some(settings) = db.get_user_settings().user_id(user_id).call().await? {
+        zip.add_json("settings.json", &settings).await?;
+    }
+
+    for offset in pages() {
+        let threads = db
+            .list_threads()
+            .user_id(user_id)
+            .params(PaginationParams::new(offset, PAGE, "asc"))
+            .call()
+            .await?;
+        for thread in &threads {
+            let messages = db
+                .list_thread_messages()
+                .thread_id(thread.id)
+                .user_id(user_id)
+                .call()
+                .await?;
+            counts.messages += messages.len();
+            let entry = ThreadEntry {
+                thread,
+                messages: &messages,
+            };
+            zip.add_json(&format!("threads/{}.json", thread.id), &entry)
+                .await?;
+        }
+        counts.threads += threads.len();
+        if threads.len() < PAGE as usize {
+            break;
+        }
+    }
+
+    let mut activities = Vec::new();
+    for offset in pages() {
+        let page = db
+            .list_activities_with_latest_session()
+            .user_id(user_id)
+            .params(PaginationParams::new(offset, PAGE, "asc"))
+            .call()
+            .await?;
+        let done = page.len() < PAGE as usize;
+        for (activity, _) in page {
+            let mut sessions = Vec::new();
+            for offset in pages() {
+                let batch = db
+                    .list_sessions_for_activity()
+                    .user_id(user_id)
+                    .activity_id(activity.id)
+                    .params(PaginationParams::new(offset, PAGE, "asc"))
+                    .call()
+                    .await?;
+                let done = batch.len() < PAGE as usize;
+                sessions.extend(batch);
+                if done {
+                    break;
+                }
+            }
+            counts.activity_sessions += sessions.len();
+            activities.push(ActivityEntry { activity, sessions });
+        }
+        if done {
+            break;
+        }
+    }
+    counts.activities = activities.len();
+    zip.add_json("activities.json", &activities).await?;
+
+    let devices = db.list_devices().user_id(user_id).call().await?;
+    counts.devices = devices.len();
+    zip.add_json("devices.json", &devices).await?;
+
+    let memories = db.list_memories().user_id(user_id).call().await?;
+    counts.memories = memories.len();
+    zip.add_json("memories.json", &memories).await?;
+
+    let automations = db.list_automations().user_id(user_id).call().await?;
+    counts.automations = automations.len();
+    zip.add_json("automations.json", &automations).await?;
+
+    let workflows = db.list_workflows().user_id(user_id).call().await?;
+    counts.workflows = workflows.len();
+    zip.add_json("workflows.json", &workflows).await?;
+
+    let mut assets = Vec::new();
+    for offset in pages() {
+        let page = db
+            .list_assets()
+            .user_id(user_id)
+            .params(PaginationParams::new(offset, PAGE, "asc"))
+            .call()
+            .await?;
+        let done = page.len() < PAGE as usize;
+        for asset in page {
+            let read = match export.asset_mode {
+                DataExportAssetMode::Decrypted => storage.download(&asset.storage_uri).await,
+                DataExportAssetMode::AsStored => storage.download_raw(&asset.storage_uri).await,
+            };
+            let (path, error) = match read {
+                Ok(bytes) => {
+                    let path = asset_path(asset.id, &asset.mime_type);
+                    zip.add(&path, bytes, CompressionMethod::Stored).await?;
+                    (Some(path), None)
+                }
+                Err(e) => {
+                    tracing::warn!(asset_id = %asset.id, error = %e, "Asset left out of export");
+                    counts.missing_assets += 1;
+                    (None, Some("contents could not be read".to_owned()))
+                }
+            };
+            assets.push(AssetEntry {
+                id: asset.id,
+                name: asset.name,
+                mime_type: asset.mime_type,
+                size_bytes: asset.size_bytes,
+                checksum_sha256: asset.checksum_sha256.map(hex::encode),
+                metadata: asset.metadata,
+                device_id: asset.device_id,
+                created_at: asset.created_at,
+                path,
+                error,
+            });
+        }
+        if done {
+            break;
+        }
+    }
+    counts.assets = assets.len();
+    zip.add_json("assets.json", &assets).await?;
+
+    let manifest = Manifest {
+        format_version: FORMAT_VERSION,
+        export_id: export.id,
+        user_id,
+        generated_at: Utc::now(),
+        asset_mode: export.asset_mode,
+        counts: &counts,
+    };
+    zip.add_json("manifest.json", &manifest).await?;
+
+    zip.finish().await
+}
+
+/// Offsets for walking a paginated query; the caller breaks on a short
+/// page.
+fn pages() -> impl Iterator<Item = u32> {
+    (0..).map(|page| page * PAGE)
+}
+
+fn asset_path(id: Uuid, mime_type: &str) -> String {
+    let mime_base = mime_type.split(';').next().unwrap_or(mime_type).trim();
+    format!(
+        "assets/{id}.{}",
+        StorageService::extension_from_mime(mime_base)
+    )
+}
+
+/// Zip writer running on a blocking thread.
+struct ZipSink {
+    tx: Option<mpsc::Sender<Entry>>,
+    join: JoinHandle<zip::result::ZipResult<Vec<u8>>>,
+}
+
+struct Entry {
+    name: String,
+    bytes: Vec<u8>,
+    compression: CompressionMethod,
+}
+
+impl ZipSink {
+    fn spawn() -> Self {
+        let (tx, mut rx) = mpsc::channel::<Entry>(4);
+        let join = tokio::task::spawn_blocking(move || {
+            let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+            while let Some(entry) = rx.blocking_recv() {
+                let options = SimpleFileOptions::default()
+                    .compression_method(entry.compression)
+                    .large_file(entry.bytes.len() as u64 >= u64::from(u32::MAX));
+                writer.start_file(entry.name, options)?;
+                writer.write_all(&entry.bytes)?;
+            }
+            Ok(writer.finish()?.into_inner())
+        });
+        Self { tx: Some(tx), join }
+    }
+
+    async fn add_json<T: Serialize>(&mut self, name: &str, value: &T) -> ExportResult<()> {
+        let bytes = serde_json::to_vec_pretty(value)
+            .map_err(|e| ExportServiceError::internal(format!("serialize {name}: {e}")))?;
+        self.add(name, bytes, CompressionMethod::Deflated).await
+    }
+
+    /// Asset contents are mostly already compressed (images, PDFs), so
+    /// they go in `Stored`; JSON goes in `Deflated`.
+    async fn add(
+        &mut self,
+        name: &str,
+        bytes: Vec<u8>,
+        compression: CompressionMethod,
+    ) -> ExportResult<()> {
+        let entry = Entry {
+            name: name.to_owned(),
+            bytes,
+            compression,
+        };
+        let sent = match &self.tx {
+            Some(tx) => tx.send(entry).await.is_ok(),
+            None => false,
+        };
+        if sent {
+            return Ok(());
+        }
+        // The writer only hangs up after failing; surface its error.
+        self.tx = None;
+        match (&mut self.join).await {
+            Ok(Err(e)) => Err(ExportServiceError::internal(format!("write archive: {e}"))),
+            _ => Err(ExportServiceError::internal("archive writer stopped")),
+        }
+    }
+
+    async fn finish(mut self) -> ExportResult<Vec<u8>> {
+        self.tx = None;
+        self.join
+            .await
+            .map_err(|e| ExportServiceError::internal(format!("archive writer panicked: {e}")))?
+            .map_err(|e| ExportServiceError::internal(format!("write archive: {e}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[tokio::test]
+    async fn zip_sink_round_trips_entries() {
+        let mut sink = ZipSink::spawn();
+        sink.add_json("manifest.json", &serde_json::json!({ "format_version": 1 }))
+            .await
+            .unwrap();
+        sink.add(
+            "assets/a.png",
+            vec![0x89, b'P', b'N', b'G'],
+            CompressionMethod::Stored,
+        )
+        .await
+        .unwrap();
+        let bytes = sink.finish().await.unwrap();
+
+        let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(archive.len(), 2);
+
+        let mut manifest = String::new();
+        archive
+            .by_name("manifest.json")
+            .unwrap()
+            .read_to_string(&mut manifest)
+            .unwrap();
+        let manifest: serde_json::Value = serde_json::from_str(&manifest).unwrap();
+        assert_eq!(manifest["format_version"], 1);
+
+        let mut asset = Vec::new();
+        archive
+            .by_name("assets/a.png")
+            .unwrap()
+            .read_to_end(&mut asset)
+            .unwrap();
+        assert_eq!(asset, vec![0x89, b'P', b'N', b'G']);
+    }
+
+    #[test]
+    fn asset_paths_use_the_mime_extension() {
+        let id = Uuid::nil();
+        assert_eq!(
+            asset_path(id, "application/pdf"),
+            format!("assets/{id}.pdf")
+        );
+        assert_eq!(
+            asset_path(id, "image/png; foo=bar"),
+            format!("assets/{id}.png")
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use axum::Json;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use be_auth_core::{InvalidUserId, MissingClaims};
+use serde::Serialize;
+use thiserror::Error;
+
+/// Wire envelope for error responses emitted by this service. Same
+/// `{ error, message }` shape as the other REST services.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportErrorResponse {
+    /// Stable machine identifier (e.g. `not_found`, `gone`).
+    pub error: &'static str,
+    /// Human-readable description. Safe to surface in client UIs.
+    pub message: String,
+}
+
+#[derive(Error, Debug)]
+pub enum ExportServiceError {
+    #[error("Authentication failed: {0}")]
+    Unauthenticated(String),
+
+    #[error("Data export not found")]
+    NotFound,
+
+    /// The request clashes with the export's current state: another
+    /// export is already in flight, or this one isn't ready to download.
+    #[error("{0}")]
+    Conflict(String),
+
+    /// The archive existed but has passed its expiry and been deleted.
+    #[error("This export has expired; request a new one")]
+    Gone,
+
+    #[error("Database error: {0}")]
+    Database(#[source] be_remote_db::DbError),
+
+    #[error("Storage error: {0}")]
+    Storage(#[from] be_storage::StorageError),
+
+    #[error("Internal error: {0}")]
+    Internal(String),
+}
+
+impl ExportServiceError {
+    pub fn unauthenticated(msg: impl Into<String>) -> Self {
+        Self::Unauthenticated(msg.into())
+    }
+
+    pub fn conflict(msg: impl Into<String>) -> Self {
+        Self::Conflict(msg.into())
+    }
+
+    pub fn internal(msg: impl Into<String>) -> Self {
+        Self::Internal(msg.into())
+    }
+
+    /// Stable identifier surfaced to clients in the error envelope.
+    pub fn error_kind(&self) -> &'static str {
+        match self {
+            Self::Unauthenticated(_) => "unauthenticated",
+            Self::NotFound => "not_found",
+            Self::Conflict(_) => "conflict",
+            Self::Gone => "gone",
+            Self::Database(_) => "database_error",
+            Self::Storage(_) => "storage_error",
+            Self::Internal(_) => "internal_error",
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::Unauthenticated(_) => StatusCode::UNAUTHORIZED,
+            Self::NotFound => StatusCode::NOT_FOUND,
+            Self::Conflict(_) => StatusCode::CONFLICT,
+            Self::Gone => StatusCode::GONE,
+            Self::Database(_) | Self::Storage(_) | Self::Internal(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+}
+
+impl From<be_remote_db::DbError> for ExportServiceError {
+    fn from(err: be_remote_db::DbError) -> Self {
+        match err {
+            be_remote_db::DbError::NotFound { .. } => Self::NotFound,
+            other => Self::Database(other),
+        }
+    }
+}
+
+impl From<MissingClaims> for ExportServiceError {
+    fn from(_: MissingClaims) -> Self {
+        Self::unauthenticated("Missing authenticated claims")
+    }
+}
+
+impl From<InvalidUserId> for ExportServiceError {
+    fn from(err: InvalidUserId) -> Self {
+        Self::unauthenticated(err.to_string())
+    }
+}
+
+impl IntoResponse for ExportServiceError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let kind = self.error_kind();
+        let detail = self.to_string();
+
+        match &self {
+            Self::Unauthenticated(_) => {
+                tracing::warn!(error = %detail, "Export service authentication error");
+            }
+            Self::NotFound | Self::Conflict(_) | Self::Gone => {
+                tracing::debug!(error = %detail, "Export service client error");
+            }
+            Self::Database(_) | Self::Storage(_) | Self::Internal(_) => {
+                tracing::error!(error = %detail, "Export service internal error");
+            }
+        }
+
+        let message = match &self {
+            Self::Database(_) => "Database operation failed".to_string(),
+            Self::Storage(_) => "Storage operation failed".to_string(),
+            Self::Internal(_) => "Internal server error".to_string(),
+            _ => detail,
+        };
+
+        (
+            status,
+            Json(ExportErrorResponse {
+                error: kind,
+                message,
+            }),
+        )
+            .into_response()
+    }
+}
+
+pub type ExportResult<T> = std::result::Result<T, ExportServiceError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn db_not_found_maps_to_404() {
+        let err: ExportServiceError = be_remote_db::DbError::not_found("data export").into();
+        assert!(matches!(err, ExportServiceError::NotFound));
+        assert_eq!(err.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn expired_exports_are_gone() {
+        assert_eq!(ExportServiceError::Gone.status(), StatusCode::GONE);
+        assert_eq!(ExportServiceError::Gone.error_kind(), "gone");
+    }
+}
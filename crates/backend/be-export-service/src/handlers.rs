@@ -0,0 +1,163 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use be_auth_core::AuthUser;
+use be_remote_db::DataExportStatus;
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::AppState;
+use crate::error::{ExportResult, ExportServiceError};
+use crate::types::{CreateDataExportRequest, DataExportResponse, ListDataExportsResponse};
+use crate::worker::delete_archive;
+
+/// Queue an export. Responds `202` straight away; the worker builds the
+/// archive in the background and the client polls
+/// `GET /exports/{export_id}` until `download_url` appears.
+#[tracing::instrument(skip_all, fields(user_id))]
+pub async fn create_export(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    body: Option<Json<CreateDataExportRequest>>,
+) -> ExportResult<(StatusCode, Json<DataExportResponse>)> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+    let Json(body) = body.unwrap_or_default();
+
+    let export = state
+        .db
+        .create_data_export()
+        .user_id(user_id)
+        .asset_mode(body.asset_mode)
+        .call()
+        .await
+        .map_err(|e| {
+            if e.is_unique_violation() {
+                ExportServiceError::conflict("An export is already being prepared")
+            } else {
+                e.into()
+            }
+        })?;
+
+    tracing::info!(export_id = %export.id, asset_mode = ?export.asset_mode, "Data export requested");
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(DataExportResponse::from_row(export, Utc::now())),
+    ))
+}
+
+#[tracing::instrument(skip_all, fields(user_id))]
+pub async fn list_exports(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+) -> ExportResult<Json<ListDataExportsResponse>> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    let now = Utc::now();
+    let exports = state
+        .db
+        .list_data_exports()
+        .user_id(user_id)
+        .call()
+        .await?
+        .into_iter()
+        .map(|row| DataExportResponse::from_row(row, now))
+        .collect();
+    Ok(Json(ListDataExportsResponse { exports }))
+}
+
+#[tracing::instrument(skip_all, fields(user_id, export_id = %export_id))]
+pub async fn get_export(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(export_id): Path<Uuid>,
+) -> ExportResult<Json<DataExportResponse>> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    let export = state
+        .db
+        .get_data_export()
+        .id(export_id)
+        .user_id(user_id)
+        .call()
+        .await?;
+    Ok(Json(DataExportResponse::from_row(export, Utc::now())))
+}
+
+/// Delete an export and its archive ahead of expiry. Deleting one that's
+/// still being built cancels it: the worker discards the archive when it
+/// finds the row gone.
+#[tracing::instrument(skip_all, fields(user_id, export_id = %export_id))]
+pub async fn delete_export(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(export_id): Path<Uuid>,
+) -> ExportResult<StatusCode> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    let export = state
+        .db
+        .delete_data_export()
+        .id(export_id)
+        .user_id(user_id)
+        .call()
+        .await?;
+    if let Some(uri) = &export.storage_uri {
+        delete_archive(&state, export.id, uri).await;
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Stream the archive. Owner-only like every other route here; the link
+/// stops working at `expires_at` even if the sweep hasn't run yet.
+#[tracing::instrument(skip_all, fields(user_id, export_id = %export_id))]
+pub async fn download_export(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(export_id): Path<Uuid>,
+) -> ExportResult<Response> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    let export = state
+        .db
+        .get_data_export()
+        .id(export_id)
+        .user_id(user_id)
+        .call()
+        .await?;
+
+    let uri = match (export.status, &export.storage_uri, export.expires_at) {
+        (DataExportStatus::Expired, _, _) => return Err(ExportServiceError::Gone),
+        (DataExportStatus::Ready, Some(_), Some(expires_at)) if expires_at <= Utc::now() => {
+            return Err(ExportServiceError::Gone);
+        }
+        (DataExportStatus::Ready, Some(uri), Some(_)) => uri,
+        (DataExportStatus::Pending | DataExportStatus::Running, _, _) => {
+            return Err(ExportServiceError::conflict(
+                "This export is still being prepared",
+            ));
+        }
+        _ => return Err(ExportServiceError::conflict("This export has no archive")),
+    };
+
+    let bytes = state.storage.download(uri).await?;
+    let filename = format!("eurora-export-{}.zip", export.created_at.format("%Y-%m-%d"));
+    let headers = [
+        (header::CONTENT_TYPE, "application/zip".to_owned()),
+        (
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{filename}\""),
+        ),
+        (header::CACHE_CONTROL, "private, no-store".to_owned()),
+    ];
+    Ok((StatusCode::OK, headers, bytes).into_response())
+}
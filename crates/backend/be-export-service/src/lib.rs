@@ -0,0 +1,106 @@
+//! HTTP personal data export ("takeout") service.
+//!
+//! Lets a user download everything their account holds — threads with
+//! every message branch, activities and their sessions, assets, cloud
+//! settings, remembered facts, automations, and workflows — as one zip
+//! archive, to satisfy data-portability requests. Authentication and
+//! Casbin authorization are applied by the surrounding `be-authz`
+//! middleware in `be-monolith`; this crate only assumes that a verified
+//! [`be_auth_core::Claims`] has been inserted into request extensions by
+//! the time a handler runs.
+//!
+//! ## Endpoints
+//!
+//! | Method | Path                           | Outcome                                              |
+//! |--------|--------------------------------|------------------------------------------------------|
+//! | POST   | `/exports`                     | `202 DataExportResponse`, or `409` if one is in flight. |
+//! | GET    | `/exports`                     | `200 ListDataExportsResponse`, newest first.         |
+//! | GET    | `/exports/{export_id}`         | `200 DataExportResponse`; poll until `download_url`. |
+//! | DELETE | `/exports/{export_id}`         | `204`; deletes the archive or cancels the build.     |
+//! | GET    | `/exports/{export_id}/download`| `200 application/zip`, `409` if not ready, `410` once expired. |
+//!
+//! ## Lifecycle
+//!
+//! Archives are built asynchronously by the [`ExportWorkerHandle`] worker:
+//! `pending` → `running` → `ready` (or `failed`). A ready archive lives in
+//! object storage under `exports/` — encrypted at rest like assets — for
+//! seven days, after which the worker deletes it and the export becomes
+//! `expired`. Assets go into the archive decrypted or exactly as stored,
+//! per the request's `asset_mode`.
+
+mod archive;
+mod error;
+mod handlers;
+mod types;
+mod worker;
+
+use std::sync::Arc;
+
+use axum::{
+    Router,
+    routing::{get, post},
+};
+use be_remote_db::DatabaseManager;
+use be_storage::StorageService;
+use tower_http::trace::TraceLayer;
+
+pub use error::{ExportErrorResponse, ExportResult, ExportServiceError};
+pub use types::{CreateDataExportRequest, DataExportResponse, ListDataExportsResponse};
+pub use worker::ExportWorkerHandle;
+
+/// Shared state injected into Axum handlers via `State<Arc<AppState>>`.
+pub struct AppState {
+    pub db: Arc<DatabaseManager>,
+    pub storage: Arc<StorageService>,
+}
+
+impl AppState {
+    pub fn new(db: Arc<DatabaseManager>, storage: Arc<StorageService>) -> Self {
+        Self { db, storage }
+    }
+}
+
+/// Build the export router with the supplied dependencies.
+///
+/// Returns the bare router; the caller is expected to apply the
+/// cross-cutting layers (CORS, body limit, auth middleware) at the
+/// monolith level so all REST services share the same outer pipeline.
+pub fn create_router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route(
+            "/exports",
+            post(handlers::create_export).get(handlers::list_exports),
+        )
+        .route(
+            "/exports/{export_id}",
+            get(handlers::get_export).delete(handlers::delete_export),
+        )
+        .route(
+            "/exports/{export_id}/download",
+            get(handlers::download_export),
+        )
+        .layer(TraceLayer::new_for_http())
+        .with_state(state)
+}
+
+pub struct ExportService {
+    pub router: Router,
+    pub worker: ExportWorkerHandle,
+}
+
+/// Wire up application state, start the export worker, and return the
+/// router ready to merge into the monolith HTTP pipeline. The caller owns
+/// the returned [`ExportWorkerHandle`] and should shut it down after the
+/// server stops.
+pub fn init_export_service(
+    db: Arc<DatabaseManager>,
+    storage: Arc<StorageService>,
+) -> ExportService {
+    tracing::debug!("Initializing export service");
+    let state = Arc::new(AppState::new(db, storage));
+    let worker = worker::spawn_worker(state.clone());
+    ExportService {
+        router: create_router(state),
+        worker,
+    }
+}
@@ -0,0 +1,120 @@
+//! Wire types for `/exports`.
+
+use be_remote_db::{DataExport, DataExportAssetMode, DataExportStatus};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Body of `POST /exports`. Every field is optional.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CreateDataExportRequest {
+    /// `decrypted` (the default) writes asset contents as the user
+    /// uploaded them; `as_stored` copies the stored objects byte for byte,
+    /// encryption envelope included.
+    pub asset_mode: DataExportAssetMode,
+}
+
+impl Default for CreateDataExportRequest {
+    fn default() -> Self {
+        Self {
+            asset_mode: DataExportAssetMode::Decrypted,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DataExportResponse {
+    pub id: Uuid,
+    pub status: DataExportStatus,
+    pub asset_mode: DataExportAssetMode,
+    /// Archive size, once ready.
+    pub size_bytes: Option<i64>,
+    /// Why the export failed, in words fit for the user.
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    /// When the archive is deleted and `download_url` stops working.
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Path of the authenticated download endpoint. Only set while the
+    /// archive can be downloaded.
+    pub download_url: Option<String>,
+}
+
+impl DataExportResponse {
+    pub(crate) fn from_row(row: DataExport, now: DateTime<Utc>) -> Self {
+        let downloadable = row.status == DataExportStatus::Ready
+            && row.expires_at.is_some_and(|expires_at| expires_at > now);
+        Self {
+            download_url: downloadable.then(|| format!("/exports/{}/download", row.id)),
+            id: row.id,
+            status: row.status,
+            asset_mode: row.asset_mode,
+            size_bytes: row.size_bytes,
+            error: row.error,
+            created_at: row.created_at,
+            completed_at: row.completed_at,
+            expires_at: row.expires_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ListDataExportsResponse {
+    pub exports: Vec<DataExportResponse>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn row(status: DataExportStatus, expires_at: Option<DateTime<Utc>>) -> DataExport {
+        let now = Utc::now();
+        DataExport {
+            id: Uuid::now_v7(),
+            user_id: Uuid::now_v7(),
+            status,
+            asset_mode: DataExportAssetMode::Decrypted,
+            storage_uri: Some("exports/a.zip".into()),
+            size_bytes: Some(1),
+            error: None,
+            attempts: 1,
+            lease_until: None,
+            started_at: Some(now),
+            completed_at: Some(now),
+            expires_at,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn asset_mode_defaults_to_decrypted() {
+        let req: CreateDataExportRequest = serde_json::from_str("{}").unwrap();
+        assert_eq!(req.asset_mode, DataExportAssetMode::Decrypted);
+        let req: CreateDataExportRequest =
+            serde_json::from_str(r#"{"asset_mode":"as_stored"}"#).unwrap();
+        assert_eq!(req.asset_mode, DataExportAssetMode::AsStored);
+    }
+
+    #[test]
+    fn download_url_only_while_downloadable() {
+        let now = Utc::now();
+        let ready = row(DataExportStatus::Ready, Some(now + Duration::hours(1)));
+        let id = ready.id;
+        assert_eq!(
+            DataExportResponse::from_row(ready, now).download_url,
+            Some(format!("/exports/{id}/download"))
+        );
+
+        let lapsed = row(DataExportStatus::Ready, Some(now - Duration::seconds(1)));
+        assert_eq!(DataExportResponse::from_row(lapsed, now).download_url, None);
+
+        let running = row(DataExportStatus::Running, None);
+        assert_eq!(
+            DataExportResponse::from_row(running, now).download_url,
+            None
+        );
+    }
+}
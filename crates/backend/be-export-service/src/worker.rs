@@ -0,0 +1,265 @@
+//! Background worker that builds queued exports and deletes expired ones.
+//!
+//! Every tick first expires ready exports past their `expires_at` (deleting
+//! the archives), then claims pending exports (see
+//! [`DatabaseManager::claim_pending_data_exports`] for the lease scheme)
+//! and builds them one after another. Exports are rare and heavy, so
+//! there's no point running several at once on one instance.
+
+use std::sync::Arc;
+
+use be_remote_db::{DataExport, DataExportStatus, DatabaseManager, DbError, WebhookEventType};
+use chrono::{Duration as ChronoDuration, Utc};
+use notification_core::NotificationEvent;
+use serde_json::json;
+use tokio::sync::oneshot;
+use tokio::time::{Duration, sleep, timeout};
+
+use crate::AppState;
+use crate::archive::build_archive;
+
+/// How long a finished archive stays downloadable.
+pub(crate) const ARCHIVE_TTL: ChronoDuration = ChronoDuration::days(7);
+
+/// Wall-clock budget for building one archive.
+const BUILD_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// How long a claimed export stays leased. Longer than [`BUILD_TIMEOUT`]
+/// plus the upload so a slow build is never picked up a second time.
+const LEASE: ChronoDuration = ChronoDuration::minutes(45);
+
+/// Claims after which an export whose worker keeps dying is failed instead
+/// of retried.
+const MAX_ATTEMPTS: i32 = 3;
+
+/// Exports claimed per tick.
+const BATCH_SIZE: i64 = 2;
+
+/// Expired exports cleaned up per tick.
+const EXPIRE_BATCH_SIZE: i64 = 50;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Shown to the user; the detail goes to the logs.
+const FAILED_MESSAGE: &str = "The export could not be built. Please try again.";
+
+pub struct ExportWorkerHandle {
+    shutdown: oneshot::Sender<()>,
+    join: tokio::task::JoinHandle<()>,
+}
+
+impl ExportWorkerHandle {
+    pub async fn shutdown(self) {
+        let Self { shutdown, join } = self;
+        let _ = shutdown.send(());
+        let _ = join.await;
+    }
+}
+
+/// Spawn the background worker. An archive being built when shutdown is
+/// requested is abandoned; its lease lapses and another instance (or the
+/// next start) picks it up again.
+pub(crate) fn spawn_worker(state: Arc<AppState>) -> ExportWorkerHandle {
+    let (shutdown_tx, mut shutdown_rx) = oneshot::channel::<()>();
+
+    let join = tokio::spawn(async move {
+        tracing::info!("Data export worker started");
+        loop {
+            tokio::select! {
+                result = tick(&state) => {
+                    if let Err(e) = result {
+                        tracing::error!(error = %e, "Data export worker tick failed");
+                    }
+                }
+                _ = &mut shutdown_rx => break,
+            }
+
+            tokio::select! {
+                _ = sleep(POLL_INTERVAL) => {}
+                _ = &mut shutdown_rx => break,
+            }
+        }
+        tracing::info!("Data export worker shutting down");
+    });
+
+    ExportWorkerHandle {
+        shutdown: shutdown_tx,
+        join,
+    }
+}
+
+async fn tick(state: &AppState) -> Result<(), DbError> {
+    let expired = state
+        .db
+        .expire_data_exports()
+        .limit(EXPIRE_BATCH_SIZE)
+        .call()
+        .await?;
+    for export in expired {
+        if let Some(uri) = &export.storage_uri {
+            delete_archive(state, export.id, uri).await;
+        }
+    }
+
+    let claimed = state
+        .db
+        .claim_pending_data_exports()
+        .limit(BATCH_SIZE)
+        .lease(LEASE)
+        .call()
+        .await?;
+    for export in claimed {
+        run_export(state, export).await;
+    }
+
+    Ok(())
+}
+
+#[tracing::instrument(skip_all, fields(export_id = %export.id, user_id = %export.user_id))]
+async fn run_export(state: &AppState, export: DataExport) {
+    if export.attempts > MAX_ATTEMPTS {
+        tracing::error!(
+            attempts = export.attempts,
+            "Giving up on data export after repeated crashes"
+        );
+        fail(&state.db, &export).await;
+        return;
+    }
+
+    let bytes = match timeout(
+        BUILD_TIMEOUT,
+        build_archive(&state.db, &state.storage, &export),
+    )
+    .await
+    {
+        Ok(Ok(bytes)) => bytes,
+        Ok(Err(e)) => {
+            tracing::error!(error = %e, "Failed to build data export");
+            fail(&state.db, &export).await;
+            return;
+        }
+        Err(_) => {
+            tracing::error!("Data export timed out");
+            fail(&state.db, &export).await;
+            return;
+        }
+    };
+
+    let uri = archive_path(&export);
+    if let Err(e) = state.storage.write(&uri, &bytes).await {
+        tracing::error!(error = %e, "Failed to store data export archive");
+        fail(&state.db, &export).await;
+        return;
+    }
+
+    let completed = state
+        .db
+        .complete_data_export()
+        .id(export.id)
+        .storage_uri(uri.clone())
+        .size_bytes(bytes.len() as i64)
+        .expires_at(Utc::now() + ARCHIVE_TTL)
+        .call()
+        .await;
+    match completed {
+        Ok(completed) => {
+            tracing::info!(size_bytes = bytes.len(), "Data export ready");
+            notify_ready(&state.db, &export).await;
+            publish_finished(
+                &state.db,
+                export.user_id,
+                json!({
+                    "export_id": completed.id,
+                    "status": completed.status,
+                    "size_bytes": completed.size_bytes,
+                    "expires_at": completed.expires_at,
+                    "download_url": format!("/exports/{}/download", completed.id),
+                }),
+            )
+            .await;
+        }
+        Err(DbError::NotFound { .. }) => {
+            tracing::info!("Data export deleted while building; discarding archive");
+            delete_archive(state, export.id, &uri).await;
+        }
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to mark data export ready");
+            delete_archive(state, export.id, &uri).await;
+            fail(&state.db, &export).await;
+        }
+    }
+}
+
+/// Best effort: the export is ready either way, and a client that misses
+/// the push sees it the next time it lists exports.
+async fn notify_ready(db: &DatabaseManager, export: &DataExport) {
+    let event = NotificationEvent::ExportReady {
+        export_id: export.id,
+    };
+    let payload = match serde_json::to_value(event) {
+        Ok(payload) => payload,
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to encode data export notification");
+            return;
+        }
+    };
+    if let Err(e) = db
+        .create_notification()
+        .executor(&db.pool)
+        .user_id(export.user_id)
+        .payload(&payload)
+        .call()
+        .await
+    {
+        tracing::error!(error = %e, "Failed to notify data export ready");
+    }
+}
+
+async fn fail(db: &DatabaseManager, export: &DataExport) {
+    if let Err(e) = db
+        .fail_data_export()
+        .id(export.id)
+        .error(FAILED_MESSAGE.to_owned())
+        .call()
+        .await
+    {
+        tracing::error!(export_id = %export.id, error = %e, "Failed to record data export failure");
+        return;
+    }
+    publish_finished(
+        db,
+        export.user_id,
+        json!({
+            "export_id": export.id,
+            "status": DataExportStatus::Failed,
+            "error": FAILED_MESSAGE,
+        }),
+    )
+    .await;
+}
+
+/// Queue the `export.finished` webhook event. Best effort, like
+/// [`notify_ready`].
+async fn publish_finished(db: &DatabaseManager, user_id: uuid::Uuid, payload: serde_json::Value) {
+    if let Err(e) = db
+        .enqueue_webhook_event()
+        .executor(&db.pool)
+        .user_id(user_id)
+        .event_type(WebhookEventType::ExportFinished)
+        .payload(&payload)
+        .call()
+        .await
+    {
+        tracing::error!(error = %e, "Failed to queue data export webhook event");
+    }
+}
+
+pub(crate) async fn delete_archive(state: &AppState, export_id: uuid::Uuid, uri: &str) {
+    if let Err(e) = state.storage.delete(uri).await {
+        tracing::warn!(%export_id, error = %e, "Failed to delete data export archive");
+    }
+}
+
+fn archive_path(export: &DataExport) -> String {
+    format!("exports/{}/{}.zip", export.user_id, export.id)
+}
@@ -0,0 +1,165 @@
+//! Body sizes on either side of the compression layers.
+//!
+//! [`record_wire_sizes`] sits outside `CompressionLayer` and
+//! `RequestDecompressionLayer` and counts bodies as they cross the wire;
+//! [`record_decoded_sizes`] sits inside them and counts what handlers read
+//! and write. Both feed the `http.server.body.size` histogram, tagged with
+//! the route, the direction, the stage and the body's `Content-Encoding`,
+//! so the two stages side by side give what compression saves per route.
+//!
+//! A body of known length is recorded up front and passed on untouched,
+//! so it keeps its `Content-Length`. A streamed body is counted as it
+//! goes and recorded when it is dropped. Empty bodies and unmatched
+//! routes are not recorded.
+
+use axum::body::{Body, HttpBody as _};
+use axum::extract::{MatchedPath, Request};
+use axum::http::{HeaderMap, header};
+use axum::middleware::Next;
+use axum::response::Response;
+use futures::StreamExt;
+use opentelemetry::KeyValue;
+
+#[derive(Debug, Clone, Copy)]
+enum Stage {
+    Wire,
+    Decoded,
+}
+
+impl Stage {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Wire => "wire",
+            Self::Decoded => "decoded",
+        }
+    }
+}
+
+/// Count request and response bodies as sent and received.
+pub(crate) async fn record_wire_sizes(req: Request, next: Next) -> Response {
+    record(Stage::Wire, req, next).await
+}
+
+/// Count request and response bodies as handlers see them.
+pub(crate) async fn record_decoded_sizes(req: Request, next: Next) -> Response {
+    record(Stage::Decoded, req, next).await
+}
+
+async fn record(stage: Stage, req: Request, next: Next) -> Response {
+    let Some(route) = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_owned())
+    else {
+        return next.run(req).await;
+    };
+
+    let attributes = |direction: &'static str, headers: &HeaderMap| {
+        vec![
+            KeyValue::new("http.route", route.clone()),
+            KeyValue::new("direction", direction),
+            KeyValue::new("stage", stage.as_str()),
+            KeyValue::new("content_encoding", content_encoding(headers)),
+        ]
+    };
+    let request_attributes = attributes("request", req.headers());
+    let req = req.map(|body| counted(body, move |bytes| record_size(bytes, &request_attributes)));
+    let response = next.run(req).await;
+    let response_attributes = attributes("response", response.headers());
+    response.map(|body| counted(body, move |bytes| record_size(bytes, &response_attributes)))
+}
+
+fn content_encoding(headers: &HeaderMap) -> String {
+    headers
+        .get(header::CONTENT_ENCODING)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("identity")
+        .to_owned()
+}
+
+fn record_size(bytes: u64, attributes: &[KeyValue]) {
+    if bytes == 0 {
+        return;
+    }
+    opentelemetry::global::meter("be-monolith")
+        .u64_histogram("http.server.body.size")
+        .with_description("HTTP body sizes before and after compression")
+        .with_unit("By")
+        .build()
+        .record(bytes, attributes);
+}
+
+/// Pass `body` through, calling `on_end` with its byte count: at once
+/// when its length is known, otherwise once the body is dropped.
+fn counted(body: Body, on_end: impl FnOnce(u64) + Send + 'static) -> Body {
+    if let Some(len) = body.size_hint().exact() {
+        on_end(len);
+        return body;
+    }
+    let mut tally = Tally {
+        bytes: 0,
+        on_end: Some(on_end),
+    };
+    Body::from_stream(body.into_data_stream().inspect(move |chunk| {
+        if let Ok(chunk) = chunk {
+            tally.bytes += chunk.len() as u64;
+        }
+    }))
+}
+
+struct Tally<F: FnOnce(u64)> {
+    bytes: u64,
+    on_end: Option<F>,
+}
+
+impl<F: FnOnce(u64)> Drop for Tally<F> {
+    fn drop(&mut self) {
+        if let Some(on_end) = self.on_end.take() {
+            on_end(self.bytes);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn sized_body_is_recorded_up_front() {
+        let seen = Arc::new(AtomicU64::new(u64::MAX));
+        let body = counted(Body::from("hello, world"), {
+            let seen = seen.clone();
+            move |bytes| seen.store(bytes, Ordering::SeqCst)
+        });
+
+        let bytes = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+        assert_eq!(&bytes[..], b"hello, world");
+        assert_eq!(seen.load(Ordering::SeqCst), 12);
+    }
+
+    #[tokio::test]
+    async fn streamed_body_is_counted_as_it_goes() {
+        let seen = Arc::new(AtomicU64::new(u64::MAX));
+        let chunks = futures::stream::iter([Ok::<_, std::io::Error>("hello, "), Ok("world")]);
+        let body = counted(Body::from_stream(chunks), {
+            let seen = seen.clone();
+            move |bytes| seen.store(bytes, Ordering::SeqCst)
+        });
+        assert_eq!(seen.load(Ordering::SeqCst), u64::MAX);
+
+        let bytes = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+        assert_eq!(&bytes[..], b"hello, world");
+        assert_eq!(seen.load(Ordering::SeqCst), 12);
+    }
+
+    #[test]
+    fn missing_content_encoding_is_identity() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(content_encoding(&headers), "identity");
+        headers.insert(header::CONTENT_ENCODING, "zstd".parse().unwrap());
+        assert_eq!(content_encoding(&headers), "zstd");
+    }
+}
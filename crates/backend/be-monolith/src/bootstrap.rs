@@ -7,30 +7,41 @@
 use std::{collections::HashSet, net::SocketAddr, sync::Arc, time::Duration};
 
 use axum::extract::DefaultBodyLimit;
-use axum::http::{HeaderValue, Method, header};
+use axum::http::{HeaderValue, Method, StatusCode, header};
+use be_account_deletion::{AccountEraser, init_account_deletion_worker};
 use be_activity_service::init_activity_service;
+use be_asset_scan::{ScanConfig, init_asset_scan_worker};
 use be_asset_service::init_asset_service;
 use be_auth_core::JwtConfig;
-use be_auth_service::{CookieConfig, init_auth_service};
+use be_auth_service::{CookieConfig, init_auth_service, init_token_cleanup_worker};
 use be_authz::{
-    AuthzState, CasbinAuthz, HttpTokenGateState, OriginGuardConfig, TrustedProxies,
-    authz_middleware, http_token_gate_middleware, new_auth_failure_rate_limiter,
-    new_health_check_rate_limiter, origin_guard_middleware,
+    AuthzAdminState, AuthzError, AuthzState, CasbinAuthz, HttpTokenGateState, OriginGuardConfig,
+    TrustedProxies, admin_router, authz_middleware, http_token_gate_middleware,
+    new_auth_failure_rate_limiter, new_health_check_rate_limiter, origin_guard_middleware,
+    watch_interval_from_env,
 };
+use be_export_service::{ExportService, init_export_service};
+use be_notification_service::{NotificationService, init_notification_service};
 use be_payment_service::{PaymentService, init_payment_service};
-use be_remote_db::DatabaseManager;
+use be_remote_db::{DatabaseManager, PoolConfig};
+use be_retention_service::{RetentionService, init_retention_service};
 use be_settings_service::init_settings_service;
 use be_storage::StorageService;
-use be_thread_service::init_thread_service;
+use be_thread_service::{ThreadService, init_thread_service};
 use be_update_service::init_update_service;
+use be_viewer_service::init_viewer_service;
+use be_webhook_service::{WebhookService, init_webhook_service};
 use llm_core::LlmConfig;
+use tower_http::catch_panic::CatchPanicLayer;
+use tower_http::compression::CompressionLayer;
 use tower_http::cors::{AllowOrigin, CorsLayer};
-use tracing_subscriber::filter::{LevelFilter, Targets};
-use tracing_subscriber::layer::SubscriberExt;
-use tracing_subscriber::util::SubscriberInitExt;
+use tower_http::decompression::RequestDecompressionLayer;
 use url::Url;
 
+use crate::body_metrics::{record_decoded_sizes, record_wire_sizes};
+use crate::deadline::{DEFAULT_BUDGET, RouteBudgets, deadline_middleware};
 use crate::errors::BootstrapError;
+use crate::{init, logging, otel};
 
 /// The webview origin Tauri serves the desktop SPA from. Hard-coded
 /// because it's a property of the Tauri runtime, not something an
@@ -46,13 +57,25 @@ pub(crate) const DEV_MODE: bool = cfg!(debug_assertions);
 
 const HTTP_MAX_BODY_SIZE: usize = 50 * 1024 * 1024; // 50 MiB
 
+const ENV_HTTP_REQUEST_TIMEOUT_SECS: &str = "HTTP_REQUEST_TIMEOUT_SECS";
+
+const POOL_MONITOR_INTERVAL: Duration = Duration::from_secs(60);
+
 /// Boot the backend. Owns every fallible step between "process started" and
 /// "axum is serving HTTP".
 pub async fn run() -> Result<(), BootstrapError> {
     install_crypto_provider();
 
     let _sentry_guard = init_sentry();
-    init_tracing();
+    let tracer_provider = otel::init_tracer_provider()?;
+    let meter_provider = otel::init_meter_provider()?;
+    let logs = logging::init(tracer_provider.as_ref())?;
+
+    // `init` prepares a fresh self-hosted deployment (policies, schema,
+    // first admin, connectivity checks) and exits; see `init.rs`.
+    if std::env::args().nth(1).as_deref() == Some("init") {
+        return init::run().await;
+    }
 
     // `--migrate-only` short-circuits everything below: connect to Postgres
     // (which runs `sqlx::migrate!` as part of `DatabaseManager::new`) and
@@ -84,28 +107,56 @@ pub async fn run() -> Result<(), BootstrapError> {
     );
 
     let database_url = require_env("REMOTE_DATABASE_URL")?;
+    let replica_url = std::env::var("REMOTE_DATABASE_REPLICA_URL")
+        .ok()
+        .filter(|s| !s.trim().is_empty());
+    let pool_config =
+        PoolConfig::from_env().map_err(|source| BootstrapError::DatabaseConfig { source })?;
     let db_manager = Arc::new(
-        DatabaseManager::new(&database_url)
+        DatabaseManager::connect()
+            .primary_url(&database_url)
+            .maybe_replica_url(replica_url.as_deref())
+            .pool_config(pool_config)
+            .call()
             .await
             .map_err(|e| BootstrapError::Database { source: e.into() })?,
     );
+    if replica_url.is_some() {
+        tracing::info!("Read replica configured for list and search queries");
+    }
+    let pool_monitor = spawn_pool_monitor(db_manager.clone());
 
     let backend_url = require_url("BACKEND_URL")?;
     let web_url = require_url("WEB_URL")?;
     let http_addr = backend_bind_addr(&backend_url)?;
     let web_origins = compose_web_origins(&backend_url, &web_url);
+    let route_budgets = RouteBudgets::new(http_request_timeout()?);
 
     let jwt_config = JwtConfig::try_from_env()?;
 
     let model_path = require_env("AUTHZ_MODEL_PATH")?;
     let policy_path = require_env("AUTHZ_POLICY_PATH")?;
+    let authz_error = |source: AuthzError| BootstrapError::Authz {
+        model_path: model_path.clone(),
+        policy_path: policy_path.clone(),
+        source: source.into(),
+    };
     let authz = CasbinAuthz::new(&model_path, &policy_path)
         .await
-        .map_err(|source| BootstrapError::Authz {
-            model_path: model_path.clone(),
-            policy_path: policy_path.clone(),
-            source: source.into(),
-        })?;
+        .map_err(authz_error)?
+        .with_role_assignments(db_manager.clone())
+        .await
+        .map_err(authz_error)?;
+    let authz_watcher = watch_interval_from_env()
+        .map_err(authz_error)?
+        .map(|interval| {
+            tracing::info!(?interval, "Watching Casbin policies for changes");
+            authz.spawn_watcher(interval)
+        });
+    let authz_admin_router = admin_router(Arc::new(AuthzAdminState::new(
+        authz.clone(),
+        db_manager.clone(),
+    )));
 
     let email_service = if DEV_MODE {
         tracing::info!("Email service disabled in dev mode");
@@ -145,24 +196,61 @@ pub async fn run() -> Result<(), BootstrapError> {
         be_storage::StorageConfig::from_env().map_err(|source| BootstrapError::StorageConfig {
             source: source.into(),
         })?;
+    let tiering_config =
+        be_storage::TieringConfig::from_env().map_err(|source| BootstrapError::StorageConfig {
+            source: source.into(),
+        })?;
     let storage = Arc::new(
         StorageService::builder()
             .config(storage_config)
+            .maybe_tiering(tiering_config)
             .build()
             .map_err(|source| BootstrapError::StorageService {
                 source: source.into(),
             })?,
     );
 
-    let core_asset = Arc::new(be_asset::AssetService::new(
+    let scan_config =
+        ScanConfig::from_env().map_err(|source| BootstrapError::AssetScanConfig { source })?;
+    let core_asset = Arc::new(
+        be_asset::AssetService::new(db_manager.clone(), storage.clone())
+            .with_malware_scanning(scan_config.is_enabled())
+            .with_corruption_quarantine(
+                std::env::var("ASSET_QUARANTINE_CORRUPTED")
+                    .map(|v| v.eq_ignore_ascii_case("true"))
+                    .unwrap_or(false),
+            ),
+    );
+    let asset_scan_worker = init_asset_scan_worker(
+        &scan_config,
         db_manager.clone(),
         storage.clone(),
-    ));
+        email_service.clone(),
+    );
     let activity_router = init_activity_service(db_manager.clone(), core_asset.clone());
     let asset_router = init_asset_service(core_asset.clone());
     let settings_router = init_settings_service(db_manager.clone());
-    let thread_router =
-        init_thread_service(db_manager.clone(), core_asset.clone(), llm_config.clone())?;
+    let ThreadService {
+        router: thread_router,
+        scheduler: automation_scheduler,
+    } = init_thread_service(db_manager.clone(), core_asset.clone(), llm_config.clone())?;
+    let ExportService {
+        router: export_router,
+        worker: export_worker,
+    } = init_export_service(db_manager.clone(), storage.clone());
+    let RetentionService {
+        router: retention_router,
+        worker: retention_worker,
+    } = init_retention_service(db_manager.clone(), storage.clone());
+    let NotificationService {
+        router: notification_router,
+        worker: notification_worker,
+    } = init_notification_service(db_manager.clone());
+    let WebhookService {
+        router: webhook_router,
+        worker: webhook_worker,
+    } = init_webhook_service(db_manager.clone());
+    let viewer_router = init_viewer_service(db_manager.clone());
 
     let update_router = if DEV_MODE {
         tracing::info!("Update service disabled in dev mode");
@@ -174,22 +262,38 @@ pub async fn run() -> Result<(), BootstrapError> {
             .map_err(|source| BootstrapError::UpdateService { source })?
     };
 
-    let (payment_router, payment_drainer) = match payment_service {
-        Some(PaymentService { router, drainer }) => (router, Some(drainer)),
-        None => (axum::Router::new(), None),
+    let (payment_router, payment_drainer, subscription_canceller) = match payment_service {
+        Some(PaymentService {
+            router,
+            drainer,
+            canceller,
+        }) => (router, Some(drainer), Some(canceller)),
+        None => (axum::Router::new(), None, None),
     };
 
+    let account_deletion_worker = init_account_deletion_worker(AccountEraser {
+        db: db_manager.clone(),
+        storage: storage.clone(),
+        email: email_service.clone(),
+        billing: subscription_canceller,
+    });
+    let token_cleanup_worker = init_token_cleanup_worker(db_manager.clone());
+
     let auth_rate_limiter = new_auth_failure_rate_limiter();
     let health_rate_limiter = new_health_check_rate_limiter();
     let trusted_proxies = TrustedProxies::from_env();
 
-    let authz_state = Arc::new(AuthzState::new(
-        authz,
-        jwt_config,
-        auth_rate_limiter,
-        health_rate_limiter,
-        trusted_proxies,
-    ));
+    let authz_state = Arc::new(
+        AuthzState::new(
+            authz,
+            jwt_config,
+            auth_rate_limiter,
+            health_rate_limiter,
+            trusted_proxies,
+        )
+        .with_impersonation_audit(db_manager.clone())
+        .with_token_versions(db_manager.clone()),
+    );
 
     let token_gate_state = Arc::new(HttpTokenGateState::new(db_manager.clone()));
 
@@ -213,6 +317,12 @@ pub async fn run() -> Result<(), BootstrapError> {
 
     // Layer order matters: the last `.layer()` call is the OUTERMOST wrapper.
     //
+    // Next to the handlers: response compression (gzip or zstd, as the
+    // caller's `Accept-Encoding` allows; SSE streams are left alone) and
+    // decoding of gzip/zstd request bodies. Bodies are decoded before the
+    // extractors apply the body limit, so it bounds the decoded size. Body
+    // sizes are recorded on both sides of the two (see `body_metrics`).
+    //
     // Inner → outer:
     //   1. http_token_gate    — runs *after* authz so claims are already in
     //      request extensions; only inspects token-gated routes.
@@ -222,7 +332,18 @@ pub async fn run() -> Result<(), BootstrapError> {
     //      request with the session cookie attached is rejected before we
     //      even look at the JWT. Bearer-mode (desktop / mobile) and
     //      same-origin server-to-server callers bypass it.
-    //   4. CORS               — must be outermost so 401/403/429 short-circuit
+    //   4. deadline           — answers `504` once the route's budget is
+    //      spent (see `deadline`). Dropping the handler future aborts
+    //      whatever it was waiting on (an LLM call, Stripe, storage, a
+    //      database query). Only the response head is bounded, so WebSocket
+    //      and SSE streams live on past it.
+    //   5. catch_panic        — turns a panicking handler or middleware into
+    //      a logged `500` instead of a dropped connection. Inside the trace
+    //      span, so the error lands on the request's trace.
+    //   6. trace_context      — opens the per-request span (parented on the
+    //      caller's `traceparent`) outside the auth layers so rejected,
+    //      timed-out and panicked requests are traced too.
+    //   7. CORS               — must be outermost so 401/403/429 short-circuit
     //      responses still carry `Access-Control-*` headers; otherwise the
     //      browser surfaces the failure as a generic "Failed to fetch"
     //      instead of the real status.
@@ -232,10 +353,21 @@ pub async fn run() -> Result<(), BootstrapError> {
         .merge(asset_router)
         .merge(settings_router)
         .merge(thread_router)
+        .merge(export_router)
+        .merge(retention_router)
+        .merge(notification_router)
+        .merge(webhook_router)
+        .merge(viewer_router)
+        .merge(authz_admin_router)
+        .merge(logging::admin_router(logs.levels.clone()))
         .merge(auth_router)
         .merge(health_route)
         .merge(llm_info_route)
         .layer(DefaultBodyLimit::max(HTTP_MAX_BODY_SIZE))
+        .layer(axum::middleware::from_fn(record_decoded_sizes))
+        .layer(RequestDecompressionLayer::new())
+        .layer(CompressionLayer::new())
+        .layer(axum::middleware::from_fn(record_wire_sizes))
         .layer(axum::middleware::from_fn_with_state(
             token_gate_state,
             http_token_gate_middleware,
@@ -248,6 +380,12 @@ pub async fn run() -> Result<(), BootstrapError> {
             origin_guard_config,
             origin_guard_middleware,
         ))
+        .layer(axum::middleware::from_fn_with_state(
+            route_budgets,
+            deadline_middleware,
+        ))
+        .layer(CatchPanicLayer::custom(panic_response))
+        .layer(axum::middleware::from_fn(otel::trace_context_middleware))
         .layer(build_cors(&web_origins));
 
     tracing::info!("Starting HTTP server at {}", http_addr);
@@ -274,10 +412,68 @@ pub async fn run() -> Result<(), BootstrapError> {
     if let Some(drainer) = payment_drainer {
         drainer.shutdown().await;
     }
+    automation_scheduler.shutdown().await;
+    export_worker.shutdown().await;
+    retention_worker.shutdown().await;
+    notification_worker.shutdown().await;
+    webhook_worker.shutdown().await;
+    if let Some(worker) = asset_scan_worker {
+        worker.shutdown().await;
+    }
+    account_deletion_worker.shutdown().await;
+    token_cleanup_worker.shutdown().await;
+    if let Some(watcher) = authz_watcher {
+        watcher.abort();
+    }
+    pool_monitor.abort();
+
+    if let Some(provider) = tracer_provider {
+        otel::shutdown(provider);
+    }
+    if let Some(provider) = meter_provider {
+        otel::shutdown_metrics(provider);
+    }
 
     outcome
 }
 
+/// Log primary / replica pool occupancy once a minute, and warn when a
+/// pool is saturated so acquire timeouts have an obvious cause in the
+/// logs.
+fn spawn_pool_monitor(db: Arc<DatabaseManager>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(POOL_MONITOR_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            let health = db.pool_health();
+            tracing::debug!(
+                primary_size = health.primary.size,
+                primary_idle = health.primary.idle,
+                primary_max = health.primary.max_connections,
+                replica_size = health.replica.map(|r| r.size),
+                replica_idle = health.replica.map(|r| r.idle),
+                replica_available = health.replica_available,
+                "database pool health"
+            );
+            if health.primary.is_saturated() {
+                tracing::warn!(
+                    in_use = health.primary.in_use(),
+                    max = health.primary.max_connections,
+                    "primary database pool saturated"
+                );
+            }
+            if let Some(replica) = health.replica.filter(|r| r.is_saturated()) {
+                tracing::warn!(
+                    in_use = replica.in_use(),
+                    max = replica.max_connections,
+                    "replica database pool saturated"
+                );
+            }
+        }
+    })
+}
+
 fn install_crypto_provider() {
     rustls::crypto::aws_lc_rs::default_provider()
         .install_default()
@@ -309,6 +505,18 @@ fn init_sentry() -> Option<sentry::ClientInitGuard> {
     )))
 }
 
+/// Response for a request whose handler panicked. The panic message goes
+/// to the log (and from there to Sentry), never to the caller.
+fn panic_response(panic: Box<dyn std::any::Any + Send + 'static>) -> axum::response::Response {
+    let message = panic
+        .downcast_ref::<&str>()
+        .map(|s| (*s).to_owned())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "<non-string panic payload>".to_owned());
+    tracing::error!(panic = %message, "HTTP handler panicked");
+    axum::response::IntoResponse::into_response(StatusCode::INTERNAL_SERVER_ERROR)
+}
+
 /// True if `flag` appears anywhere in `argv`. Tiny by-design — adding clap
 /// for one boolean is overkill, and the binary deliberately doesn't grow
 /// other flags.
@@ -333,27 +541,6 @@ async fn run_migrations_only() -> Result<(), BootstrapError> {
     Ok(())
 }
 
-fn init_tracing() {
-    let app_level = if cfg!(debug_assertions) {
-        LevelFilter::DEBUG
-    } else {
-        LevelFilter::INFO
-    };
-    let global_filter = Targets::new()
-        .with_default(LevelFilter::WARN)
-        .with_target("be_", app_level)
-        .with_target("agent_", app_level)
-        .with_target("hyper", LevelFilter::OFF)
-        .with_target("tokio", LevelFilter::OFF);
-
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::fmt::layer())
-        .with(sentry::integrations::tracing::layer())
-        .with(global_filter)
-        .try_init()
-        .expect("failed to initialize tracing subscriber");
-}
-
 async fn init_posthog() -> Result<(), BootstrapError> {
     let Some(api_key) = std::env::var("POSTHOG_API_KEY")
         .ok()
@@ -365,6 +552,17 @@ async fn init_posthog() -> Result<(), BootstrapError> {
 
     let host = require_env("POSTHOG_HOST")?;
 
+    match std::env::var(be_analytics::ENV_ID_SALT)
+        .ok()
+        .filter(|s| !s.is_empty())
+    {
+        Some(salt) => be_analytics::set_id_salt(salt),
+        None => tracing::warn!(
+            "{} not set, events for anonymous-analytics users will carry no user ID",
+            be_analytics::ENV_ID_SALT
+        ),
+    }
+
     let posthog_options = posthog_rs::ClientOptionsBuilder::default()
         .api_key(api_key)
         .host(host)
@@ -389,13 +587,29 @@ fn log_dev_banner() {
 /// [`BootstrapError::MissingEnv`] if the variable is unset or blank
 /// after trimming — there are no in-source fallbacks, so dev and prod
 /// run the same code path. Dev defaults live in `.env.example`.
-fn require_env(name: &'static str) -> Result<String, BootstrapError> {
+pub(crate) fn require_env(name: &'static str) -> Result<String, BootstrapError> {
     std::env::var(name)
         .ok()
         .filter(|s| !s.trim().is_empty())
         .ok_or(BootstrapError::MissingEnv { name })
 }
 
+/// The budget for routes without one of their own: [`DEFAULT_BUDGET`]
+/// unless `HTTP_REQUEST_TIMEOUT_SECS` overrides it with a positive number
+/// of seconds.
+fn http_request_timeout() -> Result<Duration, BootstrapError> {
+    let Some(value) = std::env::var(ENV_HTTP_REQUEST_TIMEOUT_SECS)
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+    else {
+        return Ok(DEFAULT_BUDGET);
+    };
+    match value.trim().parse::<u64>() {
+        Ok(secs) if secs > 0 => Ok(Duration::from_secs(secs)),
+        _ => Err(BootstrapError::InvalidRequestTimeout { value }),
+    }
+}
+
 fn build_cors(web_origins: &HashSet<String>) -> CorsLayer {
     let allowed: Vec<HeaderValue> = web_origins
         .iter()
@@ -0,0 +1,143 @@
+//! Per-route request deadlines.
+//!
+//! Every request gets a budget picked from its route: most routes answer
+//! in well under a minute, routes that wait on a model get minutes, and
+//! uploads get long enough for a 50 MiB body on a slow link. Once the
+//! budget is spent the request is answered `504` and the handler future
+//! is dropped, which aborts whatever it was waiting on.
+//!
+//! The handler runs inside [`net_core::deadline::scope`], so the LLM
+//! providers and the web tools cap their own timeouts at what is left and
+//! fail with a real error instead of being cut off. Only the response
+//! head is bounded; WebSocket and SSE streams live on past it.
+
+use std::time::{Duration, Instant};
+
+use axum::extract::{MatchedPath, Request, State};
+use axum::http::{Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+
+/// Budget for routes not listed in [`ROUTE_BUDGETS`]. Overridden by
+/// `HTTP_REQUEST_TIMEOUT_SECS`; a listed route never gets less than the
+/// default.
+pub(crate) const DEFAULT_BUDGET: Duration = Duration::from_secs(60);
+
+/// Routes that wait on a model. Above the five-minute limit on a
+/// workflow run, the slowest of them.
+const MODEL_BUDGET: Duration = Duration::from_secs(6 * 60);
+
+/// Routes that carry a file body.
+const UPLOAD_BUDGET: Duration = Duration::from_secs(30 * 60);
+
+/// `(http_method, axum_matched_path, budget)`. The paths must mirror the
+/// routes the services declare exactly.
+const ROUTE_BUDGETS: &[(Method, &str, Duration)] = &[
+    (Method::POST, "/threads/{thread_id}/title", MODEL_BUDGET),
+    (Method::POST, "/workflows/{workflow_id}/run", MODEL_BUDGET),
+    (
+        Method::POST,
+        "/workflows/templates/{template_id}/run",
+        MODEL_BUDGET,
+    ),
+    (Method::POST, "/reports", MODEL_BUDGET),
+    (Method::POST, "/v1/assets", UPLOAD_BUDGET),
+    (
+        Method::PUT,
+        "/v1/assets/uploads/{upload_id}/chunks/{offset}",
+        UPLOAD_BUDGET,
+    ),
+    (
+        Method::POST,
+        "/v1/assets/uploads/{upload_id}/complete",
+        UPLOAD_BUDGET,
+    ),
+];
+
+/// State for [`deadline_middleware`].
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RouteBudgets {
+    default: Duration,
+}
+
+impl RouteBudgets {
+    pub(crate) fn new(default: Duration) -> Self {
+        Self { default }
+    }
+
+    fn for_route(&self, method: &Method, matched_path: Option<&str>) -> Duration {
+        matched_path
+            .and_then(|path| {
+                ROUTE_BUDGETS
+                    .iter()
+                    .find(|(m, p, _)| m == method && *p == path)
+            })
+            .map_or(self.default, |(_, _, budget)| self.default.max(*budget))
+    }
+}
+
+/// Answer `504` once the route's budget is spent.
+pub(crate) async fn deadline_middleware(
+    State(budgets): State<RouteBudgets>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let budget = budgets.for_route(
+        req.method(),
+        req.extensions()
+            .get::<MatchedPath>()
+            .map(MatchedPath::as_str),
+    );
+    let deadline = Instant::now() + budget;
+    match tokio::time::timeout(budget, net_core::deadline::scope(deadline, next.run(req))).await {
+        Ok(response) => response,
+        Err(_) => {
+            tracing::warn!(budget_secs = budget.as_secs(), "Request deadline passed");
+            StatusCode::GATEWAY_TIMEOUT.into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routes_get_their_own_budget() {
+        let budgets = RouteBudgets::new(DEFAULT_BUDGET);
+        assert_eq!(
+            budgets.for_route(&Method::POST, Some("/workflows/{workflow_id}/run")),
+            MODEL_BUDGET
+        );
+        assert_eq!(
+            budgets.for_route(
+                &Method::PUT,
+                Some("/v1/assets/uploads/{upload_id}/chunks/{offset}")
+            ),
+            UPLOAD_BUDGET
+        );
+        assert_eq!(
+            budgets.for_route(&Method::GET, Some("/threads/{thread_id}")),
+            DEFAULT_BUDGET
+        );
+        assert_eq!(budgets.for_route(&Method::GET, None), DEFAULT_BUDGET);
+    }
+
+    #[test]
+    fn a_raised_default_raises_listed_routes_too() {
+        let budgets = RouteBudgets::new(Duration::from_secs(60 * 60));
+        assert_eq!(
+            budgets.for_route(&Method::POST, Some("/reports")),
+            Duration::from_secs(60 * 60)
+        );
+    }
+
+    #[test]
+    fn the_method_is_part_of_the_match() {
+        let budgets = RouteBudgets::new(Duration::from_secs(5));
+        assert_eq!(
+            budgets.for_route(&Method::GET, Some("/reports")),
+            Duration::from_secs(5)
+        );
+    }
+}
@@ -57,10 +57,22 @@ For local development, generate a random one and add it to your `.env`:
   {name}=$(openssl rand -hex 32)
 
 For production, this MUST be a long random string and MUST NOT be a
-placeholder. Rotating the secret invalidates every existing session."
+placeholder. To rotate it without invalidating existing sessions, add the
+new secret as `{name}_V_<N>` instead of replacing this one."
     )]
     MissingJwtSecret { name: &'static str },
 
+    #[error(
+        "Invalid JWT signing key configuration: {source}
+
+See `crates/backend/be-auth-core/src/keys.rs` for how signing keys are
+declared and rotated."
+    )]
+    JwtKeys {
+        #[source]
+        source: be_auth_core::JwtConfigError,
+    },
+
     #[error(
         "Invalid `{name}` value `{value}`: {source}
 
@@ -94,6 +106,38 @@ HTTPS."
     )]
     InvalidCookieSecure { value: String },
 
+    #[error(
+        "Invalid `HTTP_REQUEST_TIMEOUT_SECS` value `{value}` (expected a positive number of seconds).
+
+Leave it unset for the default of 60 seconds. Routes that wait on a model
+or carry an upload have longer budgets of their own."
+    )]
+    InvalidRequestTimeout { value: String },
+
+    #[error(
+        "Invalid `{name}` value `{value}` (expected {expected}).
+
+Leave it unset for the default. The logging variables are described in
+`.env.example` and at the top of `crates/backend/be-monolith/src/logging.rs`."
+    )]
+    InvalidLogSetting {
+        name: &'static str,
+        value: String,
+        expected: &'static str,
+    },
+
+    #[error(
+        "Failed to open log files in `LOG_DIR` (`{path}`): {source}
+
+The directory must exist or be creatable, and be writable by the backend.
+Unset `LOG_DIR` to log to stdout only."
+    )]
+    LogDir {
+        path: String,
+        #[source]
+        source: tracing_appender::rolling::InitError,
+    },
+
     #[error(
         "Failed to bind HTTP listener at {addr}: {source}
 
@@ -123,6 +167,18 @@ with the `eurora` database created."
         source: anyhow::Error,
     },
 
+    #[error(
+        "Invalid database pool configuration: {source}
+
+The `REMOTE_DATABASE_*_CONNECTIONS` / `*_SECS` overrides must be
+non-negative integers, with min connections not above max. Unset them to
+use the defaults."
+    )]
+    DatabaseConfig {
+        #[source]
+        source: be_remote_db::DbError,
+    },
+
     #[error(
         "Failed to apply database migrations.
 
@@ -206,6 +262,19 @@ For local development, set `ASSET_STORAGE_BACKEND=fs` and
         source: anyhow::Error,
     },
 
+    #[error(
+        "Failed to load asset scanning configuration.
+
+  {source}
+
+Set `ASSET_SCAN_BACKEND` to `clamav` or `http`, or leave it unset to store
+uploads without scanning them."
+    )]
+    AssetScanConfig {
+        #[source]
+        source: be_asset_scan::ScanError,
+    },
+
     #[error(
         "Failed to wire the thread service from the LLM configuration.
 
@@ -235,6 +304,51 @@ init by design."
         source: anyhow::Error,
     },
 
+    #[error(
+        "Failed to build the OpenTelemetry exporter for `{endpoint}`.
+
+  {source}
+
+`OTEL_EXPORTER_OTLP_ENDPOINT` must be the OTLP/HTTP base URL of a collector,
+e.g. `http://localhost:4318`. Unset it to run without trace and metric export."
+    )]
+    OtelExporter {
+        endpoint: String,
+        #[source]
+        source: opentelemetry_otlp::ExporterBuildError,
+    },
+
+    #[error(
+        "Failed to write the default Casbin policy file `{path}`: {source}
+
+`be-monolith init` writes `AUTHZ_MODEL_PATH` and `AUTHZ_POLICY_PATH` when
+they don't exist yet. Point them at a writable location, or copy
+`config/authz/` from the repository there yourself."
+    )]
+    InitPolicyFile {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error(
+        "Could not create the admin account: {reason}
+
+Set `EURORA_ADMIN_EMAIL` (and optionally `EURORA_ADMIN_PASSWORD`, at least
+12 characters) or run `be-monolith init` from a terminal to be asked."
+    )]
+    InitAdmin { reason: String },
+
+    #[error(
+        "Setup finished, but these services are unreachable:
+
+  {failed}
+
+The database and admin account are ready. Fix the configuration above and
+re-run `be-monolith init`; every step is safe to repeat."
+    )]
+    InitChecks { failed: String },
+
     #[error("HTTP server error: {source}")]
     ServerRuntime {
         #[source]
@@ -254,6 +368,7 @@ impl From<be_auth_core::JwtConfigError> for BootstrapError {
             be_auth_core::JwtConfigError::MissingSecret { name } => {
                 BootstrapError::MissingJwtSecret { name }
             }
+            source => BootstrapError::JwtKeys { source },
         }
     }
 }
@@ -0,0 +1,311 @@
+//! `be-monolith init`: everything a fresh self-hosted backend needs before
+//! its first real start, in one command.
+//!
+//! 1. Writes the default Casbin model and policy to `AUTHZ_MODEL_PATH` and
+//!    `AUTHZ_POLICY_PATH` if nothing is there yet. Existing files are left
+//!    alone, so re-running `init` never undoes local policy edits.
+//! 2. Applies the database migrations.
+//! 3. Creates the first admin: a verified password user with the `Admin`
+//!    role assigned at runtime. The email comes from `EURORA_ADMIN_EMAIL`
+//!    or, on a terminal, a prompt; the password from
+//!    `EURORA_ADMIN_PASSWORD`, or one is generated and printed once. An
+//!    existing account with that email keeps its password and is only
+//!    verified and made admin.
+//! 4. Checks that asset storage accepts a write and that every LLM
+//!    provider answers its model list, reporting all failures at once.
+//!
+//! Every step is safe to repeat.
+
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use be_remote_db::DatabaseManager;
+use be_storage::{StorageConfig, StorageService};
+use llm_core::{DEFAULT_OPENROUTER_BASE_URL, LlmConfig, Provider};
+use secrecy::ExposeSecret;
+use uuid::Uuid;
+
+use crate::bootstrap::require_env;
+use crate::errors::BootstrapError;
+
+const ENV_ADMIN_EMAIL: &str = "EURORA_ADMIN_EMAIL";
+const ENV_ADMIN_PASSWORD: &str = "EURORA_ADMIN_PASSWORD";
+
+const ADMIN_ROLE: &str = "Admin";
+
+const DEFAULT_MODEL: &str = include_str!("../../../../config/authz/model.conf");
+const DEFAULT_POLICY: &str = include_str!("../../../../config/authz/policy.csv");
+
+/// How long each connectivity check may take.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Object written, then deleted, to check that storage accepts writes.
+const STORAGE_PROBE_PATH: &str = ".eurora-init-probe";
+
+pub async fn run() -> Result<(), BootstrapError> {
+    let model_path = require_env("AUTHZ_MODEL_PATH")?;
+    let policy_path = require_env("AUTHZ_POLICY_PATH")?;
+    write_default(&model_path, DEFAULT_MODEL)?;
+    write_default(&policy_path, DEFAULT_POLICY)?;
+
+    let database_url = require_env("REMOTE_DATABASE_URL")?;
+    println!("Applying database migrations…");
+    let db = DatabaseManager::new(&database_url)
+        .await
+        .map_err(|e| BootstrapError::Migration { source: e.into() })?;
+    println!("  migrations applied");
+
+    ensure_admin(&db).await?;
+
+    println!("Checking connectivity…");
+    let mut failed = Vec::new();
+    report("asset storage", check_storage().await, &mut failed);
+    match LlmConfig::from_env() {
+        Ok((cfg, _)) => {
+            let mut providers: Vec<_> = cfg.providers.iter().collect();
+            providers.sort_by(|a, b| a.0.cmp(b.0));
+            for (id, provider) in providers {
+                let name = format!("LLM provider `{id}` ({})", provider.kind());
+                report(&name, check_provider(provider).await, &mut failed);
+            }
+        }
+        Err(e) => report(
+            "LLM configuration",
+            Check::Failed(e.to_string()),
+            &mut failed,
+        ),
+    }
+
+    if !failed.is_empty() {
+        return Err(BootstrapError::InitChecks {
+            failed: failed.join("\n  "),
+        });
+    }
+    println!();
+    println!("Done. Start the backend with `be-monolith` and sign in as the admin.");
+    Ok(())
+}
+
+/// Write `contents` to `path` unless a file is already there.
+fn write_default(path: &str, contents: &str) -> Result<(), BootstrapError> {
+    let io_error = |source| BootstrapError::InitPolicyFile {
+        path: path.to_string(),
+        source,
+    };
+    if Path::new(path).exists() {
+        println!("Keeping existing {path}");
+        return Ok(());
+    }
+    if let Some(parent) = Path::new(path)
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+    {
+        std::fs::create_dir_all(parent).map_err(io_error)?;
+    }
+    std::fs::write(path, contents).map_err(io_error)?;
+    println!("Wrote default {path}");
+    Ok(())
+}
+
+async fn ensure_admin(db: &DatabaseManager) -> Result<(), BootstrapError> {
+    let db_error = |e: be_remote_db::DbError| BootstrapError::Database { source: e.into() };
+    let admin_error = |reason: String| BootstrapError::InitAdmin { reason };
+
+    let email = match env(ENV_ADMIN_EMAIL) {
+        Some(email) => email,
+        None if std::io::stdin().is_terminal() => prompt("Admin email: ")
+            .map_err(|e| admin_error(format!("could not read the email from the terminal: {e}")))?,
+        None => {
+            return Err(admin_error(format!(
+                "`{ENV_ADMIN_EMAIL}` is unset and there is no terminal to ask on"
+            )));
+        }
+    };
+    be_auth_service::validate_email(&email).map_err(|e| admin_error(e.to_string()))?;
+
+    let user = if db
+        .user_exists_by_email()
+        .email(&email)
+        .call()
+        .await
+        .map_err(db_error)?
+    {
+        println!("Using existing account {email}; its password is unchanged");
+        db.get_user()
+            .email(email.clone())
+            .call()
+            .await
+            .map_err(db_error)?
+    } else {
+        let (password, generated) = match env(ENV_ADMIN_PASSWORD) {
+            Some(password) => (password, false),
+            None => (Uuid::new_v4().simple().to_string(), true),
+        };
+        be_auth_service::validate_password(&password).map_err(|e| admin_error(e.to_string()))?;
+        let password_hash =
+            be_auth_service::hash_password(&password).map_err(|e| admin_error(e.to_string()))?;
+        let user = db
+            .create_user()
+            .email(email.clone())
+            .password_hash(password_hash)
+            .call()
+            .await
+            .map_err(db_error)?;
+        println!("Created admin account {email}");
+        if generated {
+            println!("  password: {password}");
+            println!("  (shown once; change it after signing in)");
+        }
+        user
+    };
+
+    db.set_email_verified()
+        .user_id(user.id)
+        .call()
+        .await
+        .map_err(db_error)?;
+    match db
+        .create_role_assignment()
+        .subject(&user.id.to_string())
+        .role(ADMIN_ROLE)
+        .call()
+        .await
+    {
+        Ok(_) => println!("  assigned the {ADMIN_ROLE} role"),
+        Err(e) if e.is_unique_violation() => println!("  already has the {ADMIN_ROLE} role"),
+        Err(e) => return Err(db_error(e)),
+    }
+    Ok(())
+}
+
+fn env(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+fn prompt(label: &str) -> std::io::Result<String> {
+    print!("{label}");
+    std::io::stdout().flush()?;
+    let mut line = String::new();
+    std::io::stdin().lock().read_line(&mut line)?;
+    Ok(line.trim().to_string())
+}
+
+enum Check {
+    Ok,
+    Skipped(&'static str),
+    Failed(String),
+}
+
+fn report(name: &str, check: Check, failed: &mut Vec<String>) {
+    match check {
+        Check::Ok => println!("  {name}: ok"),
+        Check::Skipped(why) => println!("  {name}: not checked ({why})"),
+        Check::Failed(why) => {
+            println!("  {name}: FAILED");
+            failed.push(format!("{name}: {why}"));
+        }
+    }
+}
+
+async fn check_storage() -> Check {
+    let storage = match StorageConfig::from_env()
+        .and_then(|config| StorageService::builder().config(config).build())
+    {
+        Ok(storage) => storage,
+        Err(e) => return Check::Failed(e.to_string()),
+    };
+    let probe = async {
+        storage.write(STORAGE_PROBE_PATH, b"ok").await?;
+        storage.delete(STORAGE_PROBE_PATH).await
+    };
+    match tokio::time::timeout(CHECK_TIMEOUT, probe).await {
+        Ok(Ok(())) => Check::Ok,
+        Ok(Err(e)) => Check::Failed(e.to_string()),
+        Err(_) => Check::Failed(format!("no answer within {CHECK_TIMEOUT:?}")),
+    }
+}
+
+/// List the provider's models, which needs the same URL and key a chat
+/// request does but costs nothing.
+async fn check_provider(provider: &Provider) -> Check {
+    let client = reqwest::Client::new();
+    let request = match provider {
+        Provider::OpenAI {
+            api_key,
+            base_url,
+            organization,
+        } => {
+            let base = base_url
+                .as_ref()
+                .map_or("https://api.openai.com/v1", |u| u.as_str());
+            let request = client
+                .get(models_url(base))
+                .bearer_auth(api_key.expose_secret());
+            match organization {
+                Some(org) => request.header("OpenAI-Organization", org),
+                None => request,
+            }
+        }
+        Provider::AzureOpenAI {
+            endpoint,
+            api_key,
+            api_version,
+            headers,
+        } => headers.iter().fold(
+            client
+                .get(format!(
+                    "{}/openai/models?api-version={api_version}",
+                    endpoint.as_str().trim_end_matches('/')
+                ))
+                .header("api-key", api_key.expose_secret()),
+            |request, (name, value)| request.header(name, value),
+        ),
+        Provider::OpenRouter {
+            base_url,
+            api_key,
+            headers,
+        } => {
+            let base = base_url
+                .as_ref()
+                .map_or(DEFAULT_OPENROUTER_BASE_URL, |u| u.as_str());
+            headers.iter().fold(
+                client
+                    .get(models_url(base))
+                    .bearer_auth(api_key.expose_secret()),
+                |request, (name, value)| request.header(name, value),
+            )
+        }
+        Provider::OpenAiCompatible {
+            base_url,
+            api_key,
+            headers,
+            ..
+        } => {
+            let request = client.get(models_url(base_url.as_str()));
+            let request = match api_key {
+                Some(key) => request.bearer_auth(key.expose_secret()),
+                None => request,
+            };
+            headers.iter().fold(request, |request, (name, value)| {
+                request.header(name, value)
+            })
+        }
+        Provider::Anthropic { .. } | Provider::Google { .. } | Provider::Bedrock { .. } => {
+            return Check::Skipped("no runtime client for this kind yet");
+        }
+    };
+
+    match request.timeout(CHECK_TIMEOUT).send().await {
+        Ok(response) if response.status().is_success() => Check::Ok,
+        Ok(response) => Check::Failed(format!("`GET /models` answered {}", response.status())),
+        Err(e) => Check::Failed(e.to_string()),
+    }
+}
+
+fn models_url(base: &str) -> String {
+    format!("{}/models", base.trim_end_matches('/'))
+}
@@ -0,0 +1,486 @@
+//! Log output, rotation, runtime levels and redaction.
+//!
+//! Configured from the environment at startup:
+//!
+//! | Variable        | Default  | Meaning                                            |
+//! |-----------------|----------|----------------------------------------------------|
+//! | `LOG_FORMAT`    | `text`   | `text`, or `json` for one JSON object per line.    |
+//! | `LOG_LEVELS`    | built in | `Targets` directives, e.g. `warn,be_=debug`.       |
+//! | `LOG_DIR`       | unset    | Also write to rolling files in this directory.     |
+//! | `LOG_ROTATION`  | `daily`  | `hourly`, `daily` or `never`.                      |
+//! | `LOG_MAX_FILES` | `14`     | Rotated files kept in `LOG_DIR`.                   |
+//!
+//! Levels can be changed on a running replica through the admin API:
+//!
+//! | Method | Path                    | Outcome                                        |
+//! |--------|-------------------------|------------------------------------------------|
+//! | GET    | `/admin/logging/levels` | `200 LogLevelsBody` with the live directives.  |
+//! | PUT    | `/admin/logging/levels` | `200 LogLevelsBody`, `400` if they don't parse. |
+//!
+//! Every line passes through [`redact`] on its way out. Bearer tokens,
+//! JWTs, provider and Stripe keys, and fields named like credentials are
+//! masked at every level. Prompt fields are masked at `INFO` and above,
+//! so they only reach the logs of a replica deliberately turned up to
+//! `DEBUG`.
+
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::{Arc, LazyLock, Mutex};
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tracing::{Event, Level, Metadata, Subscriber};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, MakeWriter};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{Layer, Registry, reload};
+
+use crate::errors::BootstrapError;
+use crate::otel;
+
+const ENV_LOG_FORMAT: &str = "LOG_FORMAT";
+const ENV_LOG_LEVELS: &str = "LOG_LEVELS";
+const ENV_LOG_DIR: &str = "LOG_DIR";
+const ENV_LOG_ROTATION: &str = "LOG_ROTATION";
+const ENV_LOG_MAX_FILES: &str = "LOG_MAX_FILES";
+
+const DEFAULT_LOG_MAX_FILES: usize = 14;
+
+/// Keeps logging alive. Dropping it flushes and stops the file writer, so
+/// hold it for the life of the process.
+pub struct Logging {
+    pub levels: LogLevels,
+    _file_guard: Option<WorkerGuard>,
+}
+
+/// The live level directives, shared with the admin API.
+#[derive(Clone)]
+pub struct LogLevels {
+    current: Arc<Mutex<String>>,
+    reload: Arc<dyn Fn(Targets) -> Result<(), reload::Error> + Send + Sync>,
+}
+
+impl LogLevels {
+    pub fn current(&self) -> String {
+        self.current
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Swap in `directives`, e.g. `warn,be_thread_service=debug`.
+    pub fn set(&self, directives: &str) -> Result<(), String> {
+        let targets = Targets::from_str(directives).map_err(|e| e.to_string())?;
+        (self.reload)(targets).map_err(|e| e.to_string())?;
+        *self.current.lock().unwrap_or_else(|e| e.into_inner()) = directives.to_string();
+        tracing::warn!(directives, "Log levels changed");
+        Ok(())
+    }
+}
+
+/// Levels when `LOG_LEVELS` is unset: our crates at `DEBUG` in debug
+/// builds and `INFO` in release, everything else at `WARN`.
+fn default_directives() -> &'static str {
+    if cfg!(debug_assertions) {
+        "warn,be_=debug,agent_=debug,hyper=off,tokio=off"
+    } else {
+        "warn,be_=info,agent_=info,hyper=off,tokio=off"
+    }
+}
+
+/// Install the global subscriber.
+pub fn init(tracer_provider: Option<&SdkTracerProvider>) -> Result<Logging, BootstrapError> {
+    let json = match env(ENV_LOG_FORMAT).as_deref() {
+        None | Some("text") => false,
+        Some("json") => true,
+        Some(other) => return Err(invalid(ENV_LOG_FORMAT, other, "`text` or `json`")),
+    };
+
+    let directives = env(ENV_LOG_LEVELS).unwrap_or_else(|| default_directives().to_string());
+    let targets = Targets::from_str(&directives).map_err(|_| {
+        invalid(
+            ENV_LOG_LEVELS,
+            &directives,
+            "comma-separated `target=level` directives",
+        )
+    })?;
+    let (filter, reload_handle) = reload::Layer::new(targets);
+
+    let mut outputs: Vec<Box<dyn Layer<Registry> + Send + Sync>> =
+        vec![output(json, true, Redacting(std::io::stdout))];
+    let mut file_guard = None;
+    if let Some(dir) = env(ENV_LOG_DIR) {
+        let appender = RollingFileAppender::builder()
+            .rotation(rotation()?)
+            .filename_prefix("be-monolith")
+            .filename_suffix("log")
+            .max_log_files(max_files()?)
+            .build(&dir)
+            .map_err(|source| BootstrapError::LogDir { path: dir, source })?;
+        let (writer, guard) = tracing_appender::non_blocking(appender);
+        // Files aren't terminals: no colour codes.
+        outputs.push(output(json, false, Redacting(writer)));
+        file_guard = Some(guard);
+    }
+
+    tracing_subscriber::registry()
+        .with(outputs)
+        .with(sentry::integrations::tracing::layer())
+        .with(
+            tracer_provider
+                .map(|provider| tracing_opentelemetry::layer().with_tracer(otel::tracer(provider))),
+        )
+        .with(filter)
+        .try_init()
+        .expect("failed to initialize tracing subscriber");
+
+    Ok(Logging {
+        levels: LogLevels {
+            current: Arc::new(Mutex::new(directives)),
+            reload: Arc::new(move |targets| reload_handle.reload(targets)),
+        },
+        _file_guard: file_guard,
+    })
+}
+
+/// A formatting layer writing to `writer`, as text or JSON lines.
+fn output<W>(json: bool, ansi: bool, writer: W) -> Box<dyn Layer<Registry> + Send + Sync>
+where
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = tracing_subscriber::fmt::layer()
+        .with_ansi(ansi && !json)
+        .with_writer(writer);
+    if json {
+        layer.event_format(JsonFormat).boxed()
+    } else {
+        layer.boxed()
+    }
+}
+
+fn rotation() -> Result<Rotation, BootstrapError> {
+    match env(ENV_LOG_ROTATION).as_deref() {
+        None | Some("daily") => Ok(Rotation::DAILY),
+        Some("hourly") => Ok(Rotation::HOURLY),
+        Some("never") => Ok(Rotation::NEVER),
+        Some(other) => Err(invalid(
+            ENV_LOG_ROTATION,
+            other,
+            "`hourly`, `daily` or `never`",
+        )),
+    }
+}
+
+fn max_files() -> Result<usize, BootstrapError> {
+    match env(ENV_LOG_MAX_FILES) {
+        None => Ok(DEFAULT_LOG_MAX_FILES),
+        Some(value) => value
+            .parse::<usize>()
+            .ok()
+            .filter(|n| *n > 0)
+            .ok_or_else(|| invalid(ENV_LOG_MAX_FILES, &value, "a positive number")),
+    }
+}
+
+fn env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|s| !s.trim().is_empty())
+}
+
+fn invalid(name: &'static str, value: &str, expected: &'static str) -> BootstrapError {
+    BootstrapError::InvalidLogSetting {
+        name,
+        value: value.to_string(),
+        expected,
+    }
+}
+
+// --- Admin API --------------------------------------------------------------
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogLevelsBody {
+    pub directives: String,
+}
+
+/// Build the log-level admin router. Layered inside the monolith's authz
+/// middleware like every other service router; `policy.csv` grants it to
+/// `Admin` only.
+pub fn admin_router(levels: LogLevels) -> Router {
+    Router::new()
+        .route("/admin/logging/levels", get(get_levels).put(put_levels))
+        .with_state(levels)
+}
+
+async fn get_levels(State(levels): State<LogLevels>) -> Json<LogLevelsBody> {
+    Json(LogLevelsBody {
+        directives: levels.current(),
+    })
+}
+
+async fn put_levels(State(levels): State<LogLevels>, Json(body): Json<LogLevelsBody>) -> Response {
+    match levels.set(body.directives.trim()) {
+        Ok(()) => Json(LogLevelsBody {
+            directives: levels.current(),
+        })
+        .into_response(),
+        Err(e) => (StatusCode::BAD_REQUEST, e).into_response(),
+    }
+}
+
+// --- JSON lines ---------------------------------------------------------------
+
+/// One JSON object per event: `timestamp`, `level`, `target`, `message`,
+/// the event's other fields under `fields`, and the names of the spans
+/// it happened in, outermost first.
+struct JsonFormat;
+
+impl<S, N> FormatEvent<S, N> for JsonFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> std::fmt::Result {
+        let mut fields = JsonFields::default();
+        event.record(&mut fields);
+        let meta = event.metadata();
+        let spans: Vec<&str> = ctx
+            .event_scope()
+            .map(|scope| scope.from_root().map(|span| span.name()).collect())
+            .unwrap_or_default();
+
+        let mut line = serde_json::Map::new();
+        line.insert(
+            "timestamp".into(),
+            chrono::Utc::now()
+                .to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+                .into(),
+        );
+        line.insert("level".into(), meta.level().as_str().into());
+        line.insert("target".into(), meta.target().into());
+        line.insert("message".into(), fields.message.unwrap_or_default().into());
+        if !fields.fields.is_empty() {
+            line.insert("fields".into(), fields.fields.into());
+        }
+        if !spans.is_empty() {
+            line.insert("spans".into(), spans.into());
+        }
+        writeln!(writer, "{}", serde_json::Value::Object(line))
+    }
+}
+
+#[derive(Default)]
+struct JsonFields {
+    message: Option<String>,
+    fields: serde_json::Map<String, serde_json::Value>,
+}
+
+impl JsonFields {
+    fn insert(&mut self, field: &tracing::field::Field, value: serde_json::Value) {
+        if field.name() == "message" {
+            self.message = Some(match value {
+                serde_json::Value::String(s) => s,
+                other => other.to_string(),
+            });
+        } else {
+            self.fields.insert(field.name().to_string(), value);
+        }
+    }
+}
+
+impl tracing::field::Visit for JsonFields {
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        self.insert(field, format!("{value:?}").into());
+    }
+
+    fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+        self.insert(field, value.into());
+    }
+
+    fn record_i64(&mut self, field: &tracing::field::Field, value: i64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_u64(&mut self, field: &tracing::field::Field, value: u64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_f64(&mut self, field: &tracing::field::Field, value: f64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_bool(&mut self, field: &tracing::field::Field, value: bool) {
+        self.insert(field, value.into());
+    }
+}
+
+// --- Redaction ------------------------------------------------------------------
+
+const REDACTED: &str = "[redacted]";
+
+/// An ANSI colour code. Text output to a terminal styles field names, so
+/// a name and its `=` can be separated by these.
+const ANSI: &str = r"\x1b\[[0-9;]*m";
+
+static SECRETS: LazyLock<Vec<(Regex, &'static str)>> = LazyLock::new(|| {
+    [
+        // `Authorization: Bearer …` and the like.
+        (r"(?i)\b(bearer)\s+[A-Za-z0-9._~+/=-]+", "$1 [redacted]"),
+        // JWTs: three base64url segments, the first a JSON header.
+        (
+            r"\beyJ[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+\.[A-Za-z0-9_-]*",
+            REDACTED,
+        ),
+        // OpenAI-style, Stripe and webhook signing keys.
+        (
+            r"\b(?:sk-[A-Za-z0-9_-]{16,}|(?:sk|rk)_(?:live|test)_[A-Za-z0-9]+|whsec_[A-Za-z0-9]+)",
+            REDACTED,
+        ),
+    ]
+    .into_iter()
+    .map(|(pattern, replacement)| {
+        (
+            Regex::new(pattern).expect("secret pattern compiles"),
+            replacement,
+        )
+    })
+    .collect()
+});
+
+static SECRET_FIELDS: LazyLock<Regex> = LazyLock::new(|| {
+    field_pattern(
+        "password|secret|api_key|apikey|access_token|refresh_token|token|authorization|cookie",
+    )
+});
+
+static PROMPT_FIELDS: LazyLock<Regex> =
+    LazyLock::new(|| field_pattern("prompt|system_prompt|messages|query"));
+
+/// Matches `name=value` as the text format writes fields and
+/// `"name":value` as the JSON format does, for any of `names`. Groups:
+/// what precedes the name, the name, the separator, the value.
+fn field_pattern(names: &str) -> Regex {
+    Regex::new(&format!(
+        r#"(?i)({ANSI}|\b)({names})((?:{ANSI})*"?\s*[:=]\s*(?:{ANSI})*)("(?:[^"\\]|\\.)*"|\[[^\]]*\]?|[^\s,}}\x1b]+)"#
+    ))
+    .expect("field pattern compiles")
+}
+
+/// `line` with secrets masked, and prompts too when `prompts` is set.
+pub fn redact(line: &str, prompts: bool) -> String {
+    let mut clean = line.to_string();
+    for (pattern, replacement) in SECRETS.iter() {
+        if pattern.is_match(&clean) {
+            clean = pattern.replace_all(&clean, *replacement).into_owned();
+        }
+    }
+    clean = mask_fields(&SECRET_FIELDS, &clean);
+    if prompts {
+        clean = mask_fields(&PROMPT_FIELDS, &clean);
+    }
+    clean
+}
+
+/// Replace the value of every field `pattern` matches, keeping the quotes
+/// around string values so JSON output stays valid.
+fn mask_fields(pattern: &Regex, line: &str) -> String {
+    pattern
+        .replace_all(line, |caps: &regex::Captures<'_>| {
+            let masked = if caps[4].starts_with('"') {
+                format!("\"{REDACTED}\"")
+            } else {
+                REDACTED.to_string()
+            };
+            format!("{}{}{}{masked}", &caps[1], &caps[2], &caps[3])
+        })
+        .into_owned()
+}
+
+/// Wraps a writer so every line goes through [`redact`]. The formatter
+/// writes each event in one call, so a line is never split across
+/// writes.
+struct Redacting<M>(M);
+
+struct RedactingWriter<W> {
+    inner: W,
+    prompts: bool,
+}
+
+impl<'a, M: MakeWriter<'a>> MakeWriter<'a> for Redacting<M> {
+    type Writer = RedactingWriter<M::Writer>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingWriter {
+            inner: self.0.make_writer(),
+            prompts: true,
+        }
+    }
+
+    fn make_writer_for(&'a self, meta: &Metadata<'_>) -> Self::Writer {
+        RedactingWriter {
+            inner: self.0.make_writer_for(meta),
+            prompts: *meta.level() <= Level::INFO,
+        }
+    }
+}
+
+impl<W: Write> Write for RedactingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let line = String::from_utf8_lossy(buf);
+        self.inner
+            .write_all(redact(&line, self.prompts).as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secrets_are_masked_at_every_level() {
+        let line = "auth failed header=\"Bearer abc.def\" key=sk-abcdefghijklmnopqrst \
+                    token=eyJhbGciOi.eyJzdWIi.c2ln password=\"hunter 2\"";
+        let clean = redact(line, false);
+        for secret in ["abc.def", "sk-abcdef", "eyJhbGciOi", "hunter"] {
+            assert!(!clean.contains(secret), "{secret} leaked: {clean}");
+        }
+        assert!(clean.starts_with("auth failed"));
+    }
+
+    #[test]
+    fn prompts_are_masked_only_when_asked() {
+        let line =
+            r#"{"message":"turn","fields":{"prompt":"my diary says \"hi\"","max_tokens":5}}"#;
+        let clean = redact(line, true);
+        assert_eq!(
+            clean,
+            r#"{"message":"turn","fields":{"prompt":"[redacted]","max_tokens":5}}"#
+        );
+        assert_eq!(redact(line, false), line);
+    }
+
+    #[test]
+    fn lookalike_field_names_are_left_alone() {
+        let line = "usage input_tokens=12 token_count=3 prompt_tokens=9";
+        assert_eq!(redact(line, true), line);
+    }
+}
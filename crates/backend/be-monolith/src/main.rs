@@ -5,8 +5,13 @@
 //! errors. Keeping `main` thin means a single `?` in `bootstrap` can carry
 //! any [`errors::BootstrapError`] variant out to the user.
 
+mod body_metrics;
 mod bootstrap;
+mod deadline;
 mod errors;
+mod init;
+mod logging;
+mod otel;
 
 use std::process::ExitCode;
 
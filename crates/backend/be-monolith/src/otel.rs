@@ -0,0 +1,244 @@
+//! OpenTelemetry trace and metric export and W3C trace-context
+//! propagation.
+//!
+//! Export is opt-in: with `OTEL_EXPORTER_OTLP_ENDPOINT` unset nothing is
+//! installed and [`trace_context_middleware`] only opens local
+//! `tracing` spans. When it is set, spans are batched to the collector
+//! over OTLP/HTTP and an inbound `traceparent` header (the desktop app
+//! sends one on every request and chat socket) becomes the parent of
+//! the request span, so a slow answer can be followed from the client
+//! through the handler into the provider call. Metrics recorded on the
+//! global meter (body sizes, asset integrity failures) go to the same
+//! collector once a minute.
+//!
+//! The exporter honours the standard `OTEL_EXPORTER_OTLP_*` variables
+//! (headers, timeout, protocol); only the service name gets a default
+//! of our own.
+
+use axum::extract::{MatchedPath, Request};
+use axum::http::HeaderMap;
+use axum::middleware::Next;
+use axum::response::Response;
+use opentelemetry::propagation::Extractor;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::{MetricExporter, SpanExporter, WithExportConfig as _};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::metrics::SdkMeterProvider;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::{SdkTracer, SdkTracerProvider};
+use tracing::Instrument;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+use crate::errors::BootstrapError;
+
+const ENV_OTLP_ENDPOINT: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
+const ENV_SERVICE_NAME: &str = "OTEL_SERVICE_NAME";
+const DEFAULT_SERVICE_NAME: &str = "eurora-backend";
+
+/// Build the tracer provider when an OTLP endpoint is configured and
+/// install the W3C propagator globally. Returns `None` when export is
+/// disabled.
+pub fn init_tracer_provider() -> Result<Option<SdkTracerProvider>, BootstrapError> {
+    let Some(endpoint) = otlp_endpoint() else {
+        return Ok(None);
+    };
+
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_endpoint(traces_endpoint(&endpoint))
+        .build()
+        .map_err(|source| BootstrapError::OtelExporter { endpoint, source })?;
+
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(resource())
+        .build();
+
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+    opentelemetry::global::set_tracer_provider(provider.clone());
+
+    Ok(Some(provider))
+}
+
+/// Build the meter provider when an OTLP endpoint is configured and
+/// install it as the global meter provider. Returns `None` when export is
+/// disabled, leaving the global meter a no-op.
+pub fn init_meter_provider() -> Result<Option<SdkMeterProvider>, BootstrapError> {
+    let Some(endpoint) = otlp_endpoint() else {
+        return Ok(None);
+    };
+
+    let exporter = MetricExporter::builder()
+        .with_http()
+        .with_endpoint(metrics_endpoint(&endpoint))
+        .build()
+        .map_err(|source| BootstrapError::OtelExporter { endpoint, source })?;
+
+    let provider = SdkMeterProvider::builder()
+        .with_periodic_exporter(exporter)
+        .with_resource(resource())
+        .build();
+
+    opentelemetry::global::set_meter_provider(provider.clone());
+
+    Ok(Some(provider))
+}
+
+fn otlp_endpoint() -> Option<String> {
+    std::env::var(ENV_OTLP_ENDPOINT)
+        .ok()
+        .filter(|s| !s.trim().is_empty())
+}
+
+fn resource() -> Resource {
+    let service_name = std::env::var(ENV_SERVICE_NAME)
+        .ok()
+        .filter(|s| !s.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_SERVICE_NAME.to_string());
+    Resource::builder().with_service_name(service_name).build()
+}
+
+/// Tracer handed to the `tracing-opentelemetry` layer.
+pub fn tracer(provider: &SdkTracerProvider) -> SdkTracer {
+    provider.tracer("be-monolith")
+}
+
+/// Flush buffered spans and stop the exporter. Called once the HTTP
+/// server has drained so the final requests still make it out.
+pub fn shutdown(provider: SdkTracerProvider) {
+    if let Err(e) = provider.shutdown() {
+        tracing::warn!(error = %e, "failed to flush OpenTelemetry spans on shutdown");
+    }
+}
+
+/// Export the last metric readings and stop the exporter.
+pub fn shutdown_metrics(provider: SdkMeterProvider) {
+    if let Err(e) = provider.shutdown() {
+        tracing::warn!(error = %e, "failed to flush OpenTelemetry metrics on shutdown");
+    }
+}
+
+/// Open one server span per HTTP request, parented on the caller's
+/// `traceparent` when it sent one.
+///
+/// Named after the matched route template (`GET /threads/{thread_id}`)
+/// rather than the concrete path so trace backends group by endpoint
+/// and IDs stay out of span names.
+pub async fn trace_context_middleware(req: Request, next: Next) -> Response {
+    let parent = opentelemetry::global::get_text_map_propagator(|propagator| {
+        propagator.extract(&HeaderExtractor(req.headers()))
+    });
+
+    let method = req.method().clone();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_owned());
+    let name = match route.as_deref() {
+        Some(route) => format!("{method} {route}"),
+        None => method.to_string(),
+    };
+
+    let span = tracing::info_span!(
+        "http.request",
+        otel.name = %name,
+        otel.kind = "server",
+        otel.status_code = tracing::field::Empty,
+        http.request.method = %method,
+        http.route = route,
+        http.response.status_code = tracing::field::Empty,
+    );
+    // Only fails when no OpenTelemetry layer is installed, i.e. export
+    // is disabled and there is nothing to link to anyway.
+    let _ = span.set_parent(parent);
+
+    let response = next.run(req).instrument(span.clone()).await;
+
+    let status = response.status();
+    span.record("http.response.status_code", status.as_u16());
+    if status.is_server_error() {
+        span.record("otel.status_code", "ERROR");
+    }
+    response
+}
+
+/// `OTEL_EXPORTER_OTLP_ENDPOINT` is the collector base URL; the HTTP
+/// exporter expects the full signal path when given one explicitly.
+fn traces_endpoint(base: &str) -> String {
+    signal_endpoint(base, "/v1/traces")
+}
+
+fn metrics_endpoint(base: &str) -> String {
+    signal_endpoint(base, "/v1/metrics")
+}
+
+fn signal_endpoint(base: &str, path: &str) -> String {
+    let base = base.trim().trim_end_matches('/');
+    if base.ends_with(path) {
+        base.to_string()
+    } else {
+        format!("{base}{path}")
+    }
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|v| v.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|k| k.as_str()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use opentelemetry::propagation::TextMapPropagator as _;
+    use opentelemetry::trace::TraceContextExt as _;
+
+    use super::*;
+
+    #[test]
+    fn traces_endpoint_appends_signal_path() {
+        assert_eq!(
+            traces_endpoint("http://localhost:4318"),
+            "http://localhost:4318/v1/traces"
+        );
+        assert_eq!(
+            traces_endpoint("http://localhost:4318/"),
+            "http://localhost:4318/v1/traces"
+        );
+        assert_eq!(
+            traces_endpoint("https://otel.example.com/v1/traces"),
+            "https://otel.example.com/v1/traces"
+        );
+    }
+
+    #[test]
+    fn metrics_endpoint_appends_signal_path() {
+        assert_eq!(
+            metrics_endpoint("http://localhost:4318/"),
+            "http://localhost:4318/v1/metrics"
+        );
+    }
+
+    #[test]
+    fn extracts_traceparent_from_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "traceparent",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"
+                .parse()
+                .unwrap(),
+        );
+        let cx = TraceContextPropagator::new().extract(&HeaderExtractor(&headers));
+        let span_context = cx.span().span_context().clone();
+        assert!(span_context.is_remote());
+        assert_eq!(
+            span_context.trace_id().to_string(),
+            "4bf92f3577b34da6a3ce929d0e0e4736"
+        );
+    }
+}
@@ -0,0 +1,66 @@
+use axum::Json;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use be_auth_core::InvalidUserId;
+use serde::Serialize;
+use thiserror::Error;
+
+/// Wire envelope for error responses emitted by this service. Same
+/// `{ error, message }` shape as the other REST services.
+#[derive(Debug, Clone, Serialize)]
+pub struct NotificationErrorResponse {
+    /// Stable machine identifier (e.g. `unauthenticated`).
+    pub error: &'static str,
+    /// Human-readable description. Safe to surface in client UIs.
+    pub message: String,
+}
+
+/// Errors that reject the upgrade. Once the socket is open, failures are
+/// logged and the connection closed; the client reconnects and replays.
+#[derive(Error, Debug)]
+pub enum NotificationServiceError {
+    #[error("Authentication failed: {0}")]
+    Unauthenticated(String),
+}
+
+impl NotificationServiceError {
+    pub fn unauthenticated(msg: impl Into<String>) -> Self {
+        Self::Unauthenticated(msg.into())
+    }
+
+    /// Stable identifier surfaced to clients in the error envelope.
+    pub fn error_kind(&self) -> &'static str {
+        match self {
+            Self::Unauthenticated(_) => "unauthenticated",
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::Unauthenticated(_) => StatusCode::UNAUTHORIZED,
+        }
+    }
+}
+
+impl From<InvalidUserId> for NotificationServiceError {
+    fn from(err: InvalidUserId) -> Self {
+        Self::unauthenticated(err.to_string())
+    }
+}
+
+impl IntoResponse for NotificationServiceError {
+    fn into_response(self) -> Response {
+        let message = self.to_string();
+        tracing::warn!(error = %message, "Notification service authentication error");
+        (
+            self.status(),
+            Json(NotificationErrorResponse {
+                error: self.error_kind(),
+                message,
+            }),
+        )
+            .into_response()
+    }
+}
+
+pub type NotificationResult<T> = std::result::Result<T, NotificationServiceError>;
@@ -0,0 +1,218 @@
+//! The push socket.
+//!
+//! A connection subscribes to its user's hub channel first and only then
+//! reads the table, so a notification committed while it replays is
+//! either replayed or delivered live — and possibly both, which is why
+//! live events at or below the replay's high-water mark are dropped.
+//!
+//! Token gating is enforced by the surrounding `be-authz` middleware before
+//! the upgrade handshake completes.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Query, State};
+use axum::response::Response;
+use be_auth_core::AuthUser;
+use be_remote_db::DbError;
+use futures::stream::SplitSink;
+use futures::{SinkExt, StreamExt};
+use notification_core::{
+    Notification, NotificationEvent, NotificationServerMessage, NotificationStreamQuery,
+};
+use thiserror::Error;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::{Instant, interval_at};
+use tracing::Instrument;
+use uuid::Uuid;
+
+use crate::AppState;
+use crate::error::NotificationResult;
+use crate::hub::HubEvent;
+
+/// Notifications replayed one by one on connect or after a gap. A client
+/// further behind than this gets a `Resync` instead.
+const REPLAY_LIMIT: i64 = 100;
+
+/// Well inside the usual 60s idle timeout of proxies and load balancers.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(25);
+
+#[derive(Debug, Error)]
+enum SessionError {
+    #[error("database error: {0}")]
+    Database(#[from] DbError),
+    #[error("socket error: {0}")]
+    Socket(#[from] axum::Error),
+}
+
+pub async fn notifications_ws(
+    ws: WebSocketUpgrade,
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Query(query): Query<NotificationStreamQuery>,
+) -> NotificationResult<Response> {
+    let user_id = user.user_id()?;
+    // The socket task outlives this handler; carry the request span into
+    // it so the connection hangs off the caller's trace.
+    let span = tracing::Span::current();
+    Ok(ws.on_upgrade(move |socket| {
+        handle_socket(socket, state, user_id, query.after).instrument(span)
+    }))
+}
+
+async fn handle_socket(socket: WebSocket, state: Arc<AppState>, user_id: Uuid, after: Option<i64>) {
+    let mut subscription = state.hub.subscribe(user_id);
+    let (sink, mut inbound) = socket.split();
+    let mut session = Session {
+        state: &state,
+        user_id,
+        sink,
+        cursor: 0,
+        replayed_up_to: 0,
+    };
+
+    let mut result = session.start(after).await;
+    let mut heartbeat = interval_at(Instant::now() + HEARTBEAT_INTERVAL, HEARTBEAT_INTERVAL);
+    while result.is_ok() {
+        result = tokio::select! {
+            event = subscription.recv() => match event {
+                Ok(HubEvent::Notification(notification)) => session.deliver(&notification).await,
+                Ok(HubEvent::Gap) | Err(RecvError::Lagged(_)) => session.catch_up().await,
+                Err(RecvError::Closed) => break,
+            },
+            _ = heartbeat.tick() => session.send(&NotificationServerMessage::Heartbeat).await,
+            frame = inbound.next() => match frame {
+                None | Some(Ok(Message::Close(_))) | Some(Err(_)) => break,
+                // Pings are answered by axum; the protocol has no client
+                // frames, so anything else is ignored.
+                Some(Ok(_)) => Ok(()),
+            },
+        };
+    }
+
+    match result {
+        Ok(()) | Err(SessionError::Socket(_)) => tracing::debug!("Notification socket closed"),
+        Err(e @ SessionError::Database(_)) => {
+            tracing::error!(error = %e, "Notification socket failed");
+            let _ = session.sink.send(Message::Close(None)).await;
+        }
+    }
+}
+
+struct Session<'a> {
+    state: &'a AppState,
+    user_id: Uuid,
+    sink: SplitSink<WebSocket, Message>,
+    /// The newest id the client has been given, directly or via a cursor.
+    cursor: i64,
+    /// Live events up to here were already covered by a replay.
+    replayed_up_to: i64,
+}
+
+impl Session<'_> {
+    async fn start(&mut self, after: Option<i64>) -> Result<(), SessionError> {
+        match after {
+            Some(after) => {
+                self.cursor = after;
+                self.catch_up().await?;
+            }
+            None => {
+                self.cursor = self.state.db.latest_notification_id().await?;
+                self.replayed_up_to = self.cursor;
+            }
+        }
+        self.send(&NotificationServerMessage::Ready {
+            cursor: self.cursor,
+        })
+        .await
+    }
+
+    /// Replay everything after the cursor, or resync when that's too much
+    /// or some of it has already been pruned.
+    async fn catch_up(&mut self) -> Result<(), SessionError> {
+        let missed = self
+            .state
+            .db
+            .list_notifications_after()
+            .user_id(self.user_id)
+            .after(self.cursor)
+            .limit(REPLAY_LIMIT + 1)
+            .call()
+            .await?;
+
+        let too_many = missed.len() as i64 > REPLAY_LIMIT;
+        if too_many || self.pruned_past_cursor().await? {
+            self.cursor = self.state.db.latest_notification_id().await?;
+            self.replayed_up_to = self.cursor;
+            tracing::info!(
+                cursor = self.cursor,
+                "Notification client too far behind; resyncing"
+            );
+            return self
+                .send(&NotificationServerMessage::Resync {
+                    cursor: self.cursor,
+                })
+                .await;
+        }
+
+        for notification in &missed {
+            self.send_notification(notification).await?;
+        }
+        self.replayed_up_to = self.replayed_up_to.max(self.cursor);
+        Ok(())
+    }
+
+    /// Whether rows after the cursor may have been pruned. Ids skipped by
+    /// rolled-back inserts can make this a false positive, which only
+    /// costs the client a needless resync.
+    async fn pruned_past_cursor(&self) -> Result<bool, SessionError> {
+        let oldest = self.state.db.oldest_notification_id().await?;
+        Ok(oldest.is_some_and(|oldest| oldest > self.cursor + 1))
+    }
+
+    async fn deliver(
+        &mut self,
+        notification: &be_remote_db::Notification,
+    ) -> Result<(), SessionError> {
+        if notification.id <= self.replayed_up_to {
+            return Ok(());
+        }
+        self.send_notification(notification).await
+    }
+
+    async fn send_notification(
+        &mut self,
+        notification: &be_remote_db::Notification,
+    ) -> Result<(), SessionError> {
+        self.cursor = self.cursor.max(notification.id);
+        let event = match serde_json::from_value::<NotificationEvent>(notification.payload.clone())
+        {
+            Ok(event) => event,
+            Err(e) => {
+                tracing::error!(
+                    error = %e,
+                    notification_id = notification.id,
+                    "Skipping malformed notification"
+                );
+                return Ok(());
+            }
+        };
+        self.send(&NotificationServerMessage::Notification {
+            notification: Notification {
+                id: notification.id,
+                event,
+                created_at: notification.created_at,
+            },
+        })
+        .await
+    }
+
+    async fn send(&mut self, message: &NotificationServerMessage) -> Result<(), SessionError> {
+        match serde_json::to_string(message) {
+            Ok(text) => self.sink.send(Message::Text(text.into())).await?,
+            Err(e) => tracing::error!(error = %e, "Failed to serialize notification frame"),
+        }
+        Ok(())
+    }
+}
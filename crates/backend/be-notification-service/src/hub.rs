@@ -0,0 +1,147 @@
+//! In-process fan-out from the listener to the sockets this replica holds.
+//!
+//! One broadcast channel per connected user, created on the first
+//! subscription and dropped with the last. Broadcast notifications and
+//! gaps go to every channel.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use be_remote_db::Notification;
+use parking_lot::Mutex;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// Events buffered per user before a slow socket lags. A lagged socket
+/// catches up from the table, so this only needs to absorb a burst.
+const CHANNEL_CAPACITY: usize = 64;
+
+#[derive(Debug, Clone)]
+pub(crate) enum HubEvent {
+    Notification(Arc<Notification>),
+    /// The listener may have missed notifications; subscribers should
+    /// catch up from the table.
+    Gap,
+}
+
+#[derive(Default)]
+pub(crate) struct NotificationHub {
+    users: Mutex<HashMap<Uuid, broadcast::Sender<HubEvent>>>,
+}
+
+impl NotificationHub {
+    pub(crate) fn subscribe(self: &Arc<Self>, user_id: Uuid) -> Subscription {
+        let receiver = self
+            .users
+            .lock()
+            .entry(user_id)
+            .or_insert_with(|| broadcast::channel(CHANNEL_CAPACITY).0)
+            .subscribe();
+        Subscription {
+            hub: self.clone(),
+            user_id,
+            receiver,
+        }
+    }
+
+    pub(crate) fn publish(&self, notification: Notification) {
+        let recipient = notification.user_id;
+        let event = HubEvent::Notification(Arc::new(notification));
+        let users = self.users.lock();
+        match recipient {
+            Some(user_id) => {
+                if let Some(sender) = users.get(&user_id) {
+                    let _ = sender.send(event);
+                }
+            }
+            None => {
+                for sender in users.values() {
+                    let _ = sender.send(event.clone());
+                }
+            }
+        }
+    }
+
+    pub(crate) fn gap(&self) {
+        for sender in self.users.lock().values() {
+            let _ = sender.send(HubEvent::Gap);
+        }
+    }
+}
+
+/// One socket's view of its user's channel.
+pub(crate) struct Subscription {
+    hub: Arc<NotificationHub>,
+    user_id: Uuid,
+    receiver: broadcast::Receiver<HubEvent>,
+}
+
+impl Subscription {
+    pub(crate) async fn recv(&mut self) -> Result<HubEvent, broadcast::error::RecvError> {
+        self.receiver.recv().await
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        let mut users = self.hub.users.lock();
+        // Our own receiver is still alive here, so 1 means it's the last.
+        if users
+            .get(&self.user_id)
+            .is_some_and(|sender| sender.receiver_count() <= 1)
+        {
+            users.remove(&self.user_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use serde_json::json;
+
+    fn notification(id: i64, user_id: Option<Uuid>) -> Notification {
+        Notification {
+            id,
+            user_id,
+            payload: json!({}),
+            created_at: Utc::now(),
+        }
+    }
+
+    fn received_id(event: HubEvent) -> i64 {
+        match event {
+            HubEvent::Notification(notification) => notification.id,
+            HubEvent::Gap => panic!("unexpected gap"),
+        }
+    }
+
+    #[tokio::test]
+    async fn routes_by_user_and_broadcasts_to_all() {
+        let hub = Arc::new(NotificationHub::default());
+        let (alice, bob) = (Uuid::now_v7(), Uuid::now_v7());
+        let mut alice_sub = hub.subscribe(alice);
+        let mut bob_sub = hub.subscribe(bob);
+
+        hub.publish(notification(1, Some(alice)));
+        hub.publish(notification(2, None));
+
+        assert_eq!(received_id(alice_sub.recv().await.unwrap()), 1);
+        assert_eq!(received_id(alice_sub.recv().await.unwrap()), 2);
+        assert_eq!(received_id(bob_sub.recv().await.unwrap()), 2);
+    }
+
+    #[test]
+    fn last_subscription_drops_the_channel() {
+        let hub = Arc::new(NotificationHub::default());
+        let user_id = Uuid::now_v7();
+        let first = hub.subscribe(user_id);
+        let second = hub.subscribe(user_id);
+
+        drop(first);
+        assert!(hub.users.lock().contains_key(&user_id));
+        drop(second);
+        assert!(hub.users.lock().is_empty());
+    }
+}
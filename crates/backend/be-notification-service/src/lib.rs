@@ -0,0 +1,88 @@
+//! WebSocket push of server-side events to desktop clients.
+//!
+//! Services publish a `notification-core::NotificationEvent` by inserting
+//! it with [`DatabaseManager::create_notification`] — inside their own
+//! transaction when the event should only go out if it commits. The row is
+//! the durable log; the insert also announces it over Postgres
+//! `LISTEN`/`NOTIFY`, which the [`NotificationWorkerHandle`] worker on every
+//! monolith replica relays to the sockets it holds. Authentication and
+//! Casbin authorization are applied by the surrounding `be-authz`
+//! middleware in `be-monolith`.
+//!
+//! ## Endpoints
+//!
+//! | Method | Path                          | Outcome                                        |
+//! |--------|-------------------------------|------------------------------------------------|
+//! | GET    | `/notifications/ws?after=<id>`| WebSocket upgrade; server → client frames only. |
+//!
+//! ## Delivery
+//!
+//! Each connection receives its user's notifications and broadcasts as
+//! `NotificationServerMessage` frames, plus a `Heartbeat` every 25 seconds.
+//! A client reconnects with the last id (or `Ready`/`Resync` cursor) it
+//! saw as `after`; the server replays what it missed before sending
+//! `Ready`. A client more than a hundred notifications behind, or behind
+//! by more than the week the log is kept, gets a `Resync` instead and
+//! should refetch its state.
+
+mod error;
+mod handlers;
+mod hub;
+mod worker;
+
+use std::sync::Arc;
+
+use axum::{Router, routing::get};
+use be_remote_db::DatabaseManager;
+use tower_http::trace::TraceLayer;
+
+pub use error::{NotificationErrorResponse, NotificationResult, NotificationServiceError};
+pub use worker::NotificationWorkerHandle;
+
+use crate::hub::NotificationHub;
+
+/// Shared state injected into Axum handlers via `State<Arc<AppState>>`.
+pub struct AppState {
+    pub db: Arc<DatabaseManager>,
+    hub: Arc<NotificationHub>,
+}
+
+impl AppState {
+    pub fn new(db: Arc<DatabaseManager>) -> Self {
+        Self {
+            db,
+            hub: Arc::default(),
+        }
+    }
+}
+
+/// Build the notification router with the supplied dependencies.
+///
+/// Returns the bare router; the caller is expected to apply the
+/// cross-cutting layers (CORS, body limit, auth middleware) at the
+/// monolith level so all REST services share the same outer pipeline.
+pub fn create_router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/notifications/ws", get(handlers::notifications_ws))
+        .layer(TraceLayer::new_for_http())
+        .with_state(state)
+}
+
+pub struct NotificationService {
+    pub router: Router,
+    pub worker: NotificationWorkerHandle,
+}
+
+/// Wire up application state, start the listener worker, and return the
+/// router ready to merge into the monolith HTTP pipeline. The caller owns
+/// the returned [`NotificationWorkerHandle`] and should shut it down after
+/// the server stops.
+pub fn init_notification_service(db: Arc<DatabaseManager>) -> NotificationService {
+    tracing::debug!("Initializing notification service");
+    let state = Arc::new(AppState::new(db));
+    let worker = worker::spawn_worker(state.clone());
+    NotificationService {
+        router: create_router(state),
+        worker,
+    }
+}
@@ -0,0 +1,107 @@
+//! Background worker that feeds the hub from Postgres and prunes the log.
+//!
+//! Every notification is announced on the `notifications` channel when its
+//! transaction commits; this worker holds the `LISTEN` connection and
+//! hands each one to the [`NotificationHub`](crate::hub::NotificationHub).
+//! Whenever the listener (re)connects it signals a gap, since anything
+//! announced while it was away was missed, and connected sockets catch up
+//! from the table.
+
+use std::sync::Arc;
+
+use be_remote_db::{DatabaseManager, DbError};
+use chrono::{Duration as ChronoDuration, Utc};
+use tokio::sync::oneshot;
+use tokio::time::{Duration, sleep};
+
+use crate::AppState;
+
+/// Wait before retrying a failed `LISTEN` connection.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Long enough to backfill a laptop that slept over a weekend; a client
+/// gone for longer gets a resync instead.
+const RETENTION: ChronoDuration = ChronoDuration::days(7);
+
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+pub struct NotificationWorkerHandle {
+    shutdown: oneshot::Sender<()>,
+    join: tokio::task::JoinHandle<()>,
+}
+
+impl NotificationWorkerHandle {
+    pub async fn shutdown(self) {
+        let Self { shutdown, join } = self;
+        let _ = shutdown.send(());
+        let _ = join.await;
+    }
+}
+
+/// Spawn the background worker.
+pub(crate) fn spawn_worker(state: Arc<AppState>) -> NotificationWorkerHandle {
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+
+    let join = tokio::spawn(async move {
+        tracing::info!("Notification worker started");
+        tokio::select! {
+            _ = listen(&state) => {}
+            _ = prune(&state.db) => {}
+            _ = shutdown_rx => {}
+        }
+        tracing::info!("Notification worker shutting down");
+    });
+
+    NotificationWorkerHandle {
+        shutdown: shutdown_tx,
+        join,
+    }
+}
+
+async fn listen(state: &AppState) {
+    loop {
+        let mut listener = match state.db.notification_listener().await {
+            Ok(listener) => listener,
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to listen for notifications");
+                sleep(RECONNECT_DELAY).await;
+                continue;
+            }
+        };
+        state.hub.gap();
+
+        loop {
+            match listener.recv().await {
+                Ok(Some(notification)) => state.hub.publish(notification),
+                Ok(None) => {
+                    tracing::warn!("Notification listener lost its connection; reconnecting");
+                    break;
+                }
+                Err(DbError::Encoding(e)) => {
+                    tracing::error!(error = %e, "Dropping undecodable notification");
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, "Notification listener failed; reconnecting");
+                    sleep(RECONNECT_DELAY).await;
+                    break;
+                }
+            }
+        }
+    }
+}
+
+async fn prune(db: &DatabaseManager) {
+    loop {
+        sleep(PRUNE_INTERVAL).await;
+        match db
+            .prune_notifications()
+            .cutoff(Utc::now() - RETENTION)
+            .call()
+            .await
+        {
+            Ok(0) => {}
+            Ok(pruned) => tracing::info!(pruned, "Pruned old notifications"),
+            Err(e) => tracing::error!(error = %e, "Failed to prune notifications"),
+        }
+    }
+}
@@ -0,0 +1,264 @@
+//! End-to-end runs of `/notifications/ws` against a real listener.
+//!
+//! Same setup as the other service tests: a fresh `#[sqlx::test]`
+//! database and `Claims` injected by a layer in place of the production
+//! authz middleware. Requires `DATABASE_URL`.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::Router;
+use axum::extract::Request;
+use axum::middleware::Next;
+use be_auth_core::{Claims, Role};
+use be_notification_service::{
+    NotificationService, NotificationWorkerHandle, init_notification_service,
+};
+use be_remote_db::DatabaseManager;
+use futures::StreamExt;
+use notification_core::{NotificationEvent, NotificationServerMessage};
+use sqlx::PgPool;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+use uuid::Uuid;
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+async fn seed_user(pool: &PgPool) -> Uuid {
+    let id = Uuid::now_v7();
+    sqlx::query("INSERT INTO users (id, email) VALUES ($1, $2)")
+        .bind(id)
+        .bind(format!("user-{id}@test.local"))
+        .execute(pool)
+        .await
+        .expect("seed user");
+    id
+}
+
+fn claims_for(user_id: Uuid) -> Claims {
+    Claims {
+        sub: user_id.to_string(),
+        email: format!("user-{user_id}@test.local"),
+        display_name: None,
+        iat: 0,
+        exp: i64::MAX,
+        token_type: "access".into(),
+        role: Role::Free,
+        aud: "eurora".into(),
+        email_verified: true,
+        jti: Uuid::now_v7().to_string(),
+        analytics: Default::default(),
+        token_version: 0,
+        act: None,
+    }
+}
+
+struct AppHarness {
+    db: Arc<DatabaseManager>,
+    user: Uuid,
+    addr: std::net::SocketAddr,
+    worker: NotificationWorkerHandle,
+}
+
+impl AppHarness {
+    async fn connect(&self, after: Option<i64>) -> Socket {
+        let query = after.map(|id| format!("?after={id}")).unwrap_or_default();
+        let (socket, _) =
+            tokio_tungstenite::connect_async(format!("ws://{}/notifications/ws{query}", self.addr))
+                .await
+                .expect("connect");
+        socket
+    }
+
+    async fn notify(&self, user_id: Option<Uuid>, event: NotificationEvent) -> i64 {
+        self.db
+            .create_notification()
+            .executor(&self.db.pool)
+            .maybe_user_id(user_id)
+            .payload(&serde_json::to_value(event).unwrap())
+            .call()
+            .await
+            .expect("create notification")
+            .id
+    }
+
+    /// Stop the listener so its connection doesn't hold up dropping the
+    /// test database.
+    async fn shutdown(self) {
+        self.worker.shutdown().await;
+    }
+}
+
+async fn spawn_app(pool: PgPool) -> AppHarness {
+    let user = seed_user(&pool).await;
+    let db = Arc::new(DatabaseManager::from_pool(pool));
+    let NotificationService { router, worker } = init_notification_service(db.clone());
+
+    let app: Router = router.layer(axum::middleware::from_fn(
+        move |mut req: Request, next: Next| async move {
+            req.extensions_mut().insert(claims_for(user));
+            next.run(req).await
+        },
+    ));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind ephemeral");
+    let addr = listener.local_addr().expect("local_addr");
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.expect("serve");
+    });
+
+    AppHarness {
+        db,
+        user,
+        addr,
+        worker,
+    }
+}
+
+/// The next non-heartbeat frame.
+async fn next_frame(socket: &mut Socket) -> NotificationServerMessage {
+    loop {
+        let message = tokio::time::timeout(Duration::from_secs(10), socket.next())
+            .await
+            .expect("frame before timeout")
+            .expect("socket open")
+            .expect("frame");
+        let Message::Text(text) = message else {
+            continue;
+        };
+        let frame = serde_json::from_str(&text).expect("decode frame");
+        if frame != NotificationServerMessage::Heartbeat {
+            return frame;
+        }
+    }
+}
+
+fn event_of(frame: NotificationServerMessage) -> (i64, NotificationEvent) {
+    match frame {
+        NotificationServerMessage::Notification { notification } => {
+            (notification.id, notification.event)
+        }
+        other => panic!("expected a notification, got {other:?}"),
+    }
+}
+
+#[sqlx::test(migrations = "../be-remote-db/src/migrations")]
+async fn live_notifications_reach_their_user_and_broadcasts_reach_everyone(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let other = seed_user(&app.db.pool).await;
+    let mut socket = app.connect(None).await;
+    assert_eq!(
+        next_frame(&mut socket).await,
+        NotificationServerMessage::Ready { cursor: 0 }
+    );
+
+    let export_id = Uuid::now_v7();
+    app.notify(Some(other), NotificationEvent::ExportReady { export_id })
+        .await;
+    let own = app
+        .notify(Some(app.user), NotificationEvent::ExportReady { export_id })
+        .await;
+    let broadcast = app
+        .notify(
+            None,
+            NotificationEvent::UpdateRequired {
+                min_version: "2.0.0".into(),
+            },
+        )
+        .await;
+
+    assert_eq!(
+        event_of(next_frame(&mut socket).await),
+        (own, NotificationEvent::ExportReady { export_id })
+    );
+    assert_eq!(event_of(next_frame(&mut socket).await).0, broadcast);
+    app.shutdown().await;
+}
+
+#[sqlx::test(migrations = "../be-remote-db/src/migrations")]
+async fn reconnect_replays_missed_notifications(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let export_id = Uuid::now_v7();
+    let first = app
+        .notify(Some(app.user), NotificationEvent::ExportReady { export_id })
+        .await;
+    let second = app
+        .notify(
+            Some(app.user),
+            NotificationEvent::SubscriptionChanged {
+                plan_id: "tier1".into(),
+            },
+        )
+        .await;
+
+    let mut socket = app.connect(Some(first)).await;
+
+    assert_eq!(event_of(next_frame(&mut socket).await).0, second);
+    assert_eq!(
+        next_frame(&mut socket).await,
+        NotificationServerMessage::Ready { cursor: second }
+    );
+    app.shutdown().await;
+}
+
+#[sqlx::test(migrations = "../be-remote-db/src/migrations")]
+async fn clients_too_far_behind_are_told_to_resync(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let mut last = 0;
+    for _ in 0..101 {
+        last = app
+            .notify(
+                Some(app.user),
+                NotificationEvent::ExportReady {
+                    export_id: Uuid::now_v7(),
+                },
+            )
+            .await;
+    }
+
+    let mut socket = app.connect(Some(0)).await;
+
+    assert_eq!(
+        next_frame(&mut socket).await,
+        NotificationServerMessage::Resync { cursor: last }
+    );
+    assert_eq!(
+        next_frame(&mut socket).await,
+        NotificationServerMessage::Ready { cursor: last }
+    );
+    app.shutdown().await;
+}
+
+#[sqlx::test(migrations = "../be-remote-db/src/migrations")]
+async fn clients_behind_the_pruned_log_are_told_to_resync(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let export_id = Uuid::now_v7();
+    let pruned = app
+        .notify(Some(app.user), NotificationEvent::ExportReady { export_id })
+        .await;
+    let kept = app
+        .notify(Some(app.user), NotificationEvent::ExportReady { export_id })
+        .await;
+    sqlx::query("UPDATE notifications SET created_at = now() - interval '30 days' WHERE id = $1")
+        .bind(pruned)
+        .execute(&app.db.pool)
+        .await
+        .expect("age notification");
+    app.db
+        .prune_notifications()
+        .cutoff(chrono::Utc::now() - chrono::Duration::days(7))
+        .call()
+        .await
+        .expect("prune");
+
+    let mut socket = app.connect(Some(pruned - 1)).await;
+
+    assert_eq!(
+        next_frame(&mut socket).await,
+        NotificationServerMessage::Resync { cursor: kept }
+    );
+    app.shutdown().await;
+}
@@ -1,13 +1,21 @@
-use be_analytics::{Event, capture_async};
+use be_analytics::{Event, Subject, capture_async};
 
-pub fn track_checkout_session_created(price_id: &str) {
-    let mut event = Event::new_anon("checkout_session_created");
+pub fn track_checkout_session_created(subject: &Subject, price_id: &str) {
+    let Some(mut event) = subject.event("checkout_session_created") else {
+        return;
+    };
     event.insert_prop("price_id", price_id).ok();
     capture_async(event);
 }
 
-pub fn track_checkout_session_creation_failed(price_id: Option<&str>, error_kind: &str) {
-    let mut event = Event::new_anon("checkout_session_creation_failed");
+pub fn track_checkout_session_creation_failed(
+    subject: &Subject,
+    price_id: Option<&str>,
+    error_kind: &str,
+) {
+    let Some(mut event) = subject.event("checkout_session_creation_failed") else {
+        return;
+    };
     if let Some(pid) = price_id {
         event.insert_prop("price_id", pid).ok();
     }
@@ -15,25 +23,37 @@ pub fn track_checkout_session_creation_failed(price_id: Option<&str>, error_kind
     capture_async(event);
 }
 
-pub fn track_checkout_status_checked(status: &str) {
-    let mut event = Event::new_anon("checkout_status_checked");
+pub fn track_checkout_status_checked(subject: &Subject, status: &str) {
+    let Some(mut event) = subject.event("checkout_status_checked") else {
+        return;
+    };
     event.insert_prop("status", status).ok();
     capture_async(event);
 }
 
-pub fn track_billing_portal_created() {
-    let event = Event::new_anon("billing_portal_created");
+pub fn track_billing_portal_created(subject: &Subject) {
+    let Some(event) = subject.event("billing_portal_created") else {
+        return;
+    };
     capture_async(event);
 }
 
-pub fn track_billing_portal_failed(error_kind: &str) {
-    let mut event = Event::new_anon("billing_portal_failed");
+pub fn track_billing_portal_failed(subject: &Subject, error_kind: &str) {
+    let Some(mut event) = subject.event("billing_portal_failed") else {
+        return;
+    };
     event.insert_prop("error_kind", error_kind).ok();
     capture_async(event);
 }
 
-pub fn track_subscription_status_checked(status: Option<&str>, price_id: Option<&str>) {
-    let mut event = Event::new_anon("subscription_status_checked");
+pub fn track_subscription_status_checked(
+    subject: &Subject,
+    status: Option<&str>,
+    price_id: Option<&str>,
+) {
+    let Some(mut event) = subject.event("subscription_status_checked") else {
+        return;
+    };
     event.insert_prop("status", status.unwrap_or("none")).ok();
     if let Some(pid) = price_id {
         event.insert_prop("price_id", pid).ok();
@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use be_remote_db::DatabaseManager;
+use stripe::StripeError;
+use stripe_billing::subscription::{CancelSubscription, ListSubscription};
+use uuid::Uuid;
+
+use crate::error::PaymentError;
+
+/// Cancels a user's Stripe subscriptions outright, for account deletion.
+///
+/// Cancellation is immediate rather than at period end: the account is
+/// about to stop existing, so there is nothing left to bill for. The
+/// webhook handler brings the local `stripe.subscriptions` rows up to date
+/// as Stripe reports the cancellations.
+pub struct SubscriptionCanceller {
+    client: stripe::Client,
+    db: Arc<DatabaseManager>,
+}
+
+impl SubscriptionCanceller {
+    pub fn new(client: stripe::Client, db: Arc<DatabaseManager>) -> Self {
+        Self { client, db }
+    }
+
+    /// Cancel every subscription Stripe still considers live for the
+    /// user's customer and return how many were cancelled. Idempotent: a
+    /// user with no customer, or whose subscriptions are already gone,
+    /// yields `0`.
+    pub async fn cancel_all_for_user(&self, user_id: Uuid) -> Result<usize, PaymentError> {
+        let Some(customer_id) = self
+            .db
+            .get_stripe_customer_id_for_user()
+            .user_id(user_id)
+            .call()
+            .await
+            .map_err(|e| {
+                PaymentError::Internal(anyhow::anyhow!("lookup stripe customer link: {e}"))
+            })?
+        else {
+            return Ok(0);
+        };
+
+        // Without a status filter Stripe lists every subscription that
+        // isn't already canceled.
+        let page = ListSubscription::new()
+            .customer(&customer_id)
+            .limit(100)
+            .send(&self.client)
+            .await?;
+
+        let mut cancelled = 0;
+        for sub in page.data {
+            match CancelSubscription::new(sub.id.as_str())
+                .send(&self.client)
+                .await
+            {
+                Ok(_) => cancelled += 1,
+                // Canceled between the list and now.
+                Err(StripeError::Stripe(_, 404)) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        tracing::info!(%user_id, %customer_id, cancelled, "Cancelled Stripe subscriptions");
+        Ok(cancelled)
+    }
+}
@@ -3,6 +3,7 @@ use std::sync::Arc;
 use axum::Json;
 use axum::extract::State;
 use axum::http::{HeaderMap, StatusCode};
+use be_analytics::Subject;
 use stripe_checkout::CheckoutSessionMode;
 use stripe_checkout::checkout_session::{
     CreateCheckoutSession, CreateCheckoutSessionLineItems, RetrieveCheckoutSession,
@@ -52,6 +53,7 @@ pub async fn create_checkout_session(
     AuthUser(claims): AuthUser,
     Json(body): Json<CreateCheckoutRequest>,
 ) -> Result<Json<CreateCheckoutResponse>, PaymentError> {
+    let subject = Subject::from_claims(&claims);
     if !state.config.is_approved_beta_email(&claims.email) {
         return Err(PaymentError::Forbidden(
             "Your account is not approved for beta access".to_string(),
@@ -63,7 +65,11 @@ pub async fn create_checkout_session(
         .allowed_price_ids()
         .contains(&body.price_id.as_str())
     {
-        analytics::track_checkout_session_creation_failed(Some(&body.price_id), "invalid_price_id");
+        analytics::track_checkout_session_creation_failed(
+            &subject,
+            Some(&body.price_id),
+            "invalid_price_id",
+        );
         return Err(PaymentError::InvalidField(
             "price_id is not a recognised plan",
         ));
@@ -72,10 +78,15 @@ pub async fn create_checkout_session(
     let customer_id = lookup_customer_id(&state, &claims)
         .await
         .inspect_err(|_| {
-            analytics::track_checkout_session_creation_failed(Some(&body.price_id), "provisioning");
+            analytics::track_checkout_session_creation_failed(
+                &subject,
+                Some(&body.price_id),
+                "provisioning",
+            );
         })?
         .ok_or_else(|| {
             analytics::track_checkout_session_creation_failed(
+                &subject,
                 Some(&body.price_id),
                 "billing_not_ready",
             );
@@ -103,18 +114,23 @@ pub async fn create_checkout_session(
         .send(&state.client)
         .await
         .inspect_err(|_| {
-            analytics::track_checkout_session_creation_failed(Some(&body.price_id), "stripe_error");
+            analytics::track_checkout_session_creation_failed(
+                &subject,
+                Some(&body.price_id),
+                "stripe_error",
+            );
         })?;
 
     let url = session.url.ok_or_else(|| {
         analytics::track_checkout_session_creation_failed(
+            &subject,
             Some(&body.price_id),
             "missing_checkout_url",
         );
         PaymentError::MissingField("checkout session URL")
     })?;
 
-    analytics::track_checkout_session_created(&body.price_id);
+    analytics::track_checkout_session_created(&subject, &body.price_id);
 
     Ok(Json(CreateCheckoutResponse {
         session_id: session.id.to_string(),
@@ -126,13 +142,14 @@ pub async fn create_portal_session(
     State(state): State<Arc<AppState>>,
     AuthUser(claims): AuthUser,
 ) -> Result<Json<CreatePortalResponse>, PaymentError> {
+    let subject = Subject::from_claims(&claims);
     let customer_id = lookup_customer_id(&state, &claims)
         .await
         .inspect_err(|e| {
-            analytics::track_billing_portal_failed(e.error_kind());
+            analytics::track_billing_portal_failed(&subject, e.error_kind());
         })?
         .ok_or_else(|| {
-            analytics::track_billing_portal_failed("billing_not_ready");
+            analytics::track_billing_portal_failed(&subject, "billing_not_ready");
             PaymentError::BillingNotReady
         })?;
     let return_url = format!("{}/settings/billing", state.config.frontend_url);
@@ -143,11 +160,11 @@ pub async fn create_portal_session(
         .send(&state.client)
         .await
         .map_err(|e| {
-            analytics::track_billing_portal_failed("stripe_error");
+            analytics::track_billing_portal_failed(&subject, "stripe_error");
             PaymentError::from(e)
         })?;
 
-    analytics::track_billing_portal_created();
+    analytics::track_billing_portal_created(&subject);
 
     Ok(Json(CreatePortalResponse { url: session.url }))
 }
@@ -156,8 +173,9 @@ pub async fn get_subscription_status(
     State(state): State<Arc<AppState>>,
     AuthUser(claims): AuthUser,
 ) -> Result<Json<SubscriptionStatus>, PaymentError> {
+    let subject = Subject::from_claims(&claims);
     let Some(customer_id) = lookup_customer_id(&state, &claims).await? else {
-        analytics::track_subscription_status_checked(None, None);
+        analytics::track_subscription_status_checked(&subject, None, None);
         return Ok(Json(SubscriptionStatus::default()));
     };
 
@@ -177,6 +195,7 @@ pub async fn get_subscription_status(
 
     let result = status.unwrap_or_default();
     analytics::track_subscription_status_checked(
+        &subject,
         result.status.as_deref(),
         result.price_id.as_deref(),
     );
@@ -189,6 +208,7 @@ pub async fn get_checkout_status(
     AuthUser(claims): AuthUser,
     axum::extract::Query(params): axum::extract::Query<CheckoutStatusQuery>,
 ) -> Result<Json<CheckoutStatusResponse>, PaymentError> {
+    let subject = Subject::from_claims(&claims);
     let session = RetrieveCheckoutSession::new(params.session_id.as_str())
         .send(&state.client)
         .await?;
@@ -215,7 +235,7 @@ pub async fn get_checkout_status(
         .map(|s| s.as_str().to_owned())
         .unwrap_or_else(|| "unknown".to_owned());
 
-    analytics::track_checkout_status_checked(&status);
+    analytics::track_checkout_status_checked(&subject, &status);
 
     Ok(Json(CheckoutStatusResponse { status }))
 }
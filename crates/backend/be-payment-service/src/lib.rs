@@ -15,6 +15,7 @@ use tower_http::trace::TraceLayer;
 
 pub mod analytics;
 pub mod auth;
+pub mod cancel;
 pub mod config;
 pub mod drainer;
 pub mod error;
@@ -24,6 +25,7 @@ pub mod service;
 pub mod types;
 pub mod webhook;
 
+use cancel::SubscriptionCanceller;
 use provision::StripeBillingProvisioner;
 use service::AppState;
 
@@ -72,6 +74,7 @@ pub fn create_router(state: Arc<AppState>) -> Result<Router> {
 pub struct PaymentService {
     pub router: Router,
     pub drainer: drainer::DrainerHandle,
+    pub canceller: Arc<SubscriptionCanceller>,
 }
 
 pub fn init_payment_service(db: Arc<DatabaseManager>) -> Result<PaymentService> {
@@ -83,10 +86,15 @@ pub fn init_payment_service(db: Arc<DatabaseManager>) -> Result<PaymentService>
         state.client.clone(),
         db.clone(),
     ));
+    let canceller = Arc::new(SubscriptionCanceller::new(state.client.clone(), db.clone()));
     let router = create_router(state)?;
     let drainer = drainer::spawn_drainer(db, provisioner);
 
-    Ok(PaymentService { router, drainer })
+    Ok(PaymentService {
+        router,
+        drainer,
+        canceller,
+    })
 }
 
 pub use config::PaymentConfig;
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use be_remote_db::DatabaseManager;
+use notification_core::NotificationEvent;
 use stripe_shared::Subscription;
 
 use crate::error::PaymentError;
@@ -15,6 +16,16 @@ fn serialize_or_null(value: &impl serde::Serialize, context: &str) -> serde_json
     }
 }
 
+/// Payload telling the customer's connected clients their plan changed.
+fn plan_changed(plan_id: &str) -> serde_json::Value {
+    serialize_or_null(
+        &NotificationEvent::SubscriptionChanged {
+            plan_id: plan_id.to_owned(),
+        },
+        "plan_changed_notification",
+    )
+}
+
 fn extract_subscription_items(
     sub: &Subscription,
 ) -> Vec<(String, String, Option<i64>, serde_json::Value)> {
@@ -207,6 +218,14 @@ pub async fn on_checkout_completed(
                     .call()
                     .await
                     .map_err(|e| anyhow::anyhow!("update account plan: {e}"))?;
+
+                db.create_notification_for_stripe_customer()
+                    .executor(&mut *tx)
+                    .stripe_customer_id(&customer_id)
+                    .payload(&plan_changed(&plan_id))
+                    .call()
+                    .await
+                    .map_err(|e| anyhow::anyhow!("notify plan change: {e}"))?;
             }
         }
     } else {
@@ -324,6 +343,14 @@ pub async fn on_subscription_updated(
         .await
         .map_err(|e| anyhow::anyhow!("update account plan: {e}"))?;
 
+    db.create_notification_for_stripe_customer()
+        .executor(&mut *tx)
+        .stripe_customer_id(&customer_id)
+        .payload(&plan_changed(&plan_id))
+        .call()
+        .await
+        .map_err(|e| anyhow::anyhow!("notify plan change: {e}"))?;
+
     tx.commit()
         .await
         .map_err(|e| anyhow::anyhow!("commit tx: {e}"))?;
@@ -388,6 +415,14 @@ pub async fn on_subscription_deleted(
         .await
         .map_err(|e| anyhow::anyhow!("reset account plan: {e}"))?;
 
+    db.create_notification_for_stripe_customer()
+        .executor(&mut *tx)
+        .stripe_customer_id(&customer_id)
+        .payload(&plan_changed("free"))
+        .call()
+        .await
+        .map_err(|e| anyhow::anyhow!("notify plan change: {e}"))?;
+
     tx.commit()
         .await
         .map_err(|e| anyhow::anyhow!("commit tx: {e}"))?;
@@ -1,20 +1,27 @@
 use bon::bon;
 use chrono::{DateTime, Utc};
-use sqlx::{
-    migrate::MigrateDatabase,
-    postgres::{PgPool, PgPoolOptions},
-};
-use std::time::Duration;
+use sqlx::{migrate::MigrateDatabase, postgres::PgPool};
 use uuid::Uuid;
 
 use crate::{
     MessageType, PaginationParams,
     error::{DbError, DbResult},
+    listener::{NOTIFICATION_CHANNEL, NotificationListener},
+    pool::{PoolConfig, PoolHealth, PoolStats, Replica, is_connection_error},
     types::{
-        Activity, ActivitySession, ActivityThread, Asset, AssetStatus, ClaimedProvisioningJob,
-        EmailVerificationToken, LoginToken, Message, OAuthCredentials, OAuthProvider, OAuthState,
-        PasswordCredentials, RefreshToken, SearchResultMessage, SearchResultThread, Thread,
-        TokenUsage, UpsertOutcome, User, UserSettingsRow,
+        AccountDeletion, AccountDeletionEvent, Activity, ActivitySession, ActivityThread, Asset,
+        AssetGrant, AssetScanStatus, AssetStatus, AssetStorageTier, AuthCleanup,
+        AuthTokenTableSizes, Automation, AutomationRun, AutomationRunStatus, ClaimedAutomation,
+        ClaimedProvisioningJob, ClaimedWebhookDelivery, DataExport, DataExportAssetMode,
+        EmailVerificationToken, ErasedAccountCounts, ExpiredAsset, ExpiredItemStats,
+        ExpiredUploadSession, ImpersonationRequest, ImpersonationSession, LegalHoldEvent,
+        LegalHoldSubject, LoginToken, Message, NamedAutomationRun, Notification, OAuthCredentials,
+        OAuthProvider, OAuthState, PasswordCredentials, PendingAssetScan, RefreshToken,
+        RetentionCategory, RetentionSetting, RoleAssignment, SearchResultMessage,
+        SearchResultThread, Thread, ThreadFolder, TierableAsset, TokenUsage, UploadChunk,
+        UploadSession, UpsertOutcome, User, UserAnalyticsConsent, UserMemory, UserSettingsRow,
+        WebhookDelivery, WebhookDeliveryStatus, WebhookEndpoint, WebhookEndpointKind,
+        WebhookEventType, Workflow,
     },
 };
 
@@ -51,32 +58,155 @@ fn regex_escape(query: &str) -> String {
     escaped
 }
 
+/// Whether user `$1` owns a thread or asset on legal hold.
+const ACCOUNT_HAS_LEGAL_HOLD: &str = "SELECT \
+    EXISTS (SELECT 1 FROM threads WHERE user_id = $1 AND legal_hold_at IS NOT NULL) \
+    OR EXISTS (SELECT 1 FROM assets WHERE user_id = $1 AND legal_hold_at IS NOT NULL)";
+
+/// SQL describing one retention category: the table its items live in
+/// (aliased `x`), the filter leaving out exempt items, the column their
+/// age is measured from, and the bytes each one frees.
+struct RetentionScope {
+    table: &'static str,
+    filter: String,
+    age_column: &'static str,
+    size_bytes: &'static str,
+}
+
+fn retention_scope(category: RetentionCategory) -> RetentionScope {
+    // Pinned and held assets, activity icons, and anything attached to a
+    // message in a starred or held thread are kept.
+    const ASSET_EXEMPTIONS: &str = "x.pinned_at IS NULL AND x.legal_hold_at IS NULL \
+        AND NOT EXISTS (SELECT 1 FROM activities a WHERE a.icon_asset_id = x.id) \
+        AND NOT EXISTS ( \
+            SELECT 1 FROM message_assets ma \
+            JOIN messages m ON m.id = ma.message_id \
+            JOIN threads t ON t.id = m.thread_id \
+            WHERE ma.asset_id = x.id \
+              AND (t.starred_at IS NOT NULL OR t.legal_hold_at IS NOT NULL))";
+
+    match category {
+        RetentionCategory::Screenshots => RetentionScope {
+            table: "assets",
+            filter: format!("x.mime_type LIKE 'image/%' AND {ASSET_EXEMPTIONS}"),
+            age_column: "x.created_at",
+            size_bytes: "COALESCE(x.size_bytes, 0)",
+        },
+        RetentionCategory::CapturedText => RetentionScope {
+            table: "assets",
+            filter: format!("x.mime_type NOT LIKE 'image/%' AND {ASSET_EXEMPTIONS}"),
+            age_column: "x.created_at",
+            size_bytes: "COALESCE(x.size_bytes, 0)",
+        },
+        RetentionCategory::ActivityHistory => RetentionScope {
+            table: "activity_sessions",
+            // Live sessions are never expired.
+            filter: "x.ended_at IS NOT NULL".into(),
+            age_column: "x.ended_at",
+            size_bytes: "0",
+        },
+        RetentionCategory::Threads => RetentionScope {
+            table: "threads",
+            filter: "x.starred_at IS NULL AND x.legal_hold_at IS NULL".into(),
+            age_column: "x.updated_at",
+            size_bytes: "0",
+        },
+    }
+}
+
 #[derive(Debug)]
 pub struct DatabaseManager {
+    /// The primary. Every write, and every read that must see the
+    /// caller's own writes, goes here.
     pub pool: PgPool,
+    replica: Option<Replica>,
 }
 
 #[bon]
 impl DatabaseManager {
+    /// Connect to the primary with the default pool settings and no
+    /// replica.
     pub async fn new(database_url: &str) -> DbResult<Self> {
-        if !sqlx::Postgres::database_exists(database_url).await? {
-            sqlx::Postgres::create_database(database_url).await?;
+        Self::connect().primary_url(database_url).call().await
+    }
+
+    /// Connect to the primary (creating the database and applying
+    /// migrations) and, when `replica_url` is set, attach a read replica.
+    ///
+    /// The replica pool connects lazily: an unreachable replica does not
+    /// block startup, it just leaves reads on the primary until it comes
+    /// up.
+    #[builder]
+    pub async fn connect(
+        primary_url: &str,
+        replica_url: Option<&str>,
+        #[builder(default)] pool_config: PoolConfig,
+    ) -> DbResult<Self> {
+        if !sqlx::Postgres::database_exists(primary_url).await? {
+            sqlx::Postgres::create_database(primary_url).await?;
         }
 
-        let pool = PgPoolOptions::new()
-            .max_connections(50)
-            .min_connections(3)
-            .acquire_timeout(Duration::from_secs(10))
-            .connect(database_url)
-            .await?;
+        let pool = pool_config.options().connect(primary_url).await?;
+
+        let replica = replica_url
+            .map(|url| pool_config.options().connect_lazy(url))
+            .transpose()?
+            .map(Replica::new);
 
-        let db_manager = DatabaseManager { pool };
+        let db_manager = DatabaseManager { pool, replica };
 
         Self::run_migrations(&db_manager.pool).await?;
 
         Ok(db_manager)
     }
 
+    /// Wrap an existing pool as the primary, with no replica.
+    pub fn from_pool(pool: PgPool) -> Self {
+        Self {
+            pool,
+            replica: None,
+        }
+    }
+
+    /// Attach `replica` as the read pool. Migrations are not run on it.
+    pub fn with_replica(mut self, replica: PgPool) -> Self {
+        self.replica = Some(Replica::new(replica));
+        self
+    }
+
+    /// Connection counts for the primary and replica pools.
+    pub fn pool_health(&self) -> PoolHealth {
+        PoolHealth {
+            primary: PoolStats::of(&self.pool),
+            replica: self.replica.as_ref().map(|r| PoolStats::of(&r.pool)),
+            replica_available: self.replica.as_ref().is_some_and(Replica::is_available),
+        }
+    }
+
+    /// Run a read-only query on the replica when one is configured and
+    /// not benched, falling back to the primary if the replica can't be
+    /// reached. Only for reads that tolerate replication lag — lists and
+    /// searches, never a lookup that follows a write in the same flow.
+    async fn read<'a, T, F, Fut>(&'a self, query: F) -> Result<T, sqlx::Error>
+    where
+        F: Fn(&'a PgPool) -> Fut,
+        Fut: Future<Output = Result<T, sqlx::Error>>,
+    {
+        if let Some(replica) = self.replica.as_ref().filter(|r| r.is_available()) {
+            match query(&replica.pool).await {
+                Err(e) if is_connection_error(&e) => {
+                    tracing::warn!(
+                        error = %e,
+                        "read replica unreachable, falling back to primary"
+                    );
+                    replica.bench();
+                }
+                result => return result,
+            }
+        }
+        query(&self.pool).await
+    }
+
     async fn run_migrations(pool: &PgPool) -> DbResult<()> {
         let migrator = sqlx::migrate!("./src/migrations");
         migrator.run(pool).await?;
@@ -99,7 +229,7 @@ impl DatabaseManager {
             r#"
             INSERT INTO users (id, email, display_name, email_verified, created_at, updated_at)
             VALUES ($1, $2, $3, $4, $5, $6)
-            RETURNING id, email, display_name, email_verified, created_at, updated_at
+            RETURNING id, email, display_name, email_verified, analytics_consent, token_version, created_at, updated_at
             "#,
         )
         .bind(user_id)
@@ -160,7 +290,7 @@ impl DatabaseManager {
             r#"
             INSERT INTO users (id, email, display_name, email_verified, created_at, updated_at)
             VALUES ($1, $2, $3, $4, $5, $6)
-            RETURNING id, email, display_name, email_verified, created_at, updated_at
+            RETURNING id, email, display_name, email_verified, analytics_consent, token_version, created_at, updated_at
             "#,
         )
         .bind(user_id)
@@ -218,7 +348,7 @@ impl DatabaseManager {
         };
 
         let query = format!(
-            "SELECT id, email, display_name, email_verified, created_at, updated_at FROM users WHERE {clause}"
+            "SELECT id, email, display_name, email_verified, analytics_consent, token_version, created_at, updated_at FROM users WHERE {clause}"
         );
 
         let user = sqlx::query_as::<_, User>(&query)
@@ -281,7 +411,8 @@ impl DatabaseManager {
             )
             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
             RETURNING id, user_id, provider, provider_user_id, access_token,
-                      refresh_token, access_token_expiry, scope, created_at, updated_at
+                      refresh_token, access_token_expiry, scope, needs_reauth,
+                      created_at, updated_at
             "#,
         )
         .bind(id)
@@ -309,7 +440,8 @@ impl DatabaseManager {
         let oauth_creds = sqlx::query_as::<_, OAuthCredentials>(
             r#"
             SELECT id, user_id, provider, provider_user_id, access_token,
-                   refresh_token, access_token_expiry, scope, created_at, updated_at
+                   refresh_token, access_token_expiry, scope, needs_reauth,
+                   created_at, updated_at
             FROM oauth_credentials
             WHERE provider = $1 AND user_id = $2
             "#,
@@ -339,6 +471,9 @@ impl DatabaseManager {
         Ok(result.map(|(p,)| p))
     }
 
+    /// `None` leaves a column unchanged. Storing a new refresh token
+    /// means the user granted access again, so it also clears
+    /// `needs_reauth`.
     #[builder]
     pub async fn update_oauth_credentials(
         &self,
@@ -357,10 +492,12 @@ impl DatabaseManager {
                 refresh_token = COALESCE($3, refresh_token),
                 access_token_expiry = COALESCE($4, access_token_expiry),
                 scope = COALESCE($5, scope),
+                needs_reauth = needs_reauth AND $3 IS NULL,
                 updated_at = $6
             WHERE id = $1
             RETURNING id, user_id, provider, provider_user_id, access_token,
-                      refresh_token, access_token_expiry, scope, created_at, updated_at
+                      refresh_token, access_token_expiry, scope, needs_reauth,
+                      created_at, updated_at
             "#,
         )
         .bind(id)
@@ -383,7 +520,8 @@ impl DatabaseManager {
     ) -> DbResult<User> {
         let user = sqlx::query_as::<_, User>(
             r#"
-            SELECT u.id, u.email, u.display_name, u.email_verified, u.created_at, u.updated_at
+            SELECT u.id, u.email, u.display_name, u.email_verified, u.analytics_consent, u.token_version,
+                   u.created_at, u.updated_at
             FROM users u
             INNER JOIN oauth_credentials oc ON u.id = oc.user_id
             WHERE oc.provider = $1 AND oc.provider_user_id = $2
@@ -466,19 +604,24 @@ impl DatabaseManager {
     /// Revoke every still-valid refresh token belonging to a user in a
     /// single statement and return the count.
     ///
-    /// Driven by the Apple server-to-server notification flow: when
-    /// Apple tells us consent has been revoked or the Apple ID
-    /// deleted, every active session for that user must be torn down
-    /// atomically. A loop of `revoke_refresh_token` per row would race
+    /// Driven by `POST /auth/logout/all` and by the Apple
+    /// server-to-server notification flow: when Apple tells us consent
+    /// has been revoked or the Apple ID deleted, every active session
+    /// for that user must be torn down atomically. A loop of `revoke_refresh_token` per row would race
     /// with refresh-token rotation; one statement under PG's row-level
     /// locking does not.
     ///
+    /// The user's token version is bumped in the same transaction, so
+    /// access tokens already handed out stop working too rather than
+    /// living on until they expire.
+    ///
     /// Returns `Ok(0)` when the user is already fully logged out (no
     /// active sessions). That is not an error condition — callers log
     /// the count at info level and move on.
     #[builder]
     pub async fn revoke_all_refresh_tokens_for_user(&self, user_id: Uuid) -> DbResult<u64> {
         let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
         let result = sqlx::query(
             r#"
             UPDATE refresh_tokens
@@ -488,11 +631,71 @@ impl DatabaseManager {
         )
         .bind(user_id)
         .bind(now)
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
+        self.bump_token_version()
+            .executor(&mut *tx)
+            .user_id(user_id)
+            .call()
+            .await?;
+        tx.commit().await?;
         Ok(result.rows_affected())
     }
 
+    /// Invalidate every token minted for the user so far. Run inside the
+    /// transaction that revokes their refresh tokens.
+    #[builder]
+    async fn bump_token_version<'e, E>(&self, executor: E, user_id: Uuid) -> DbResult<()>
+    where
+        E: sqlx::Executor<'e, Database = sqlx::Postgres>,
+    {
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET token_version = token_version + 1, updated_at = now()
+            WHERE id = $1
+            "#,
+        )
+        .bind(user_id)
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    /// The user's current token version; tokens carrying an older one
+    /// have been revoked. `NotFound` once the user is gone.
+    #[builder]
+    pub async fn get_token_version(&self, user_id: Uuid) -> DbResult<i32> {
+        let version = sqlx::query_scalar::<_, i32>("SELECT token_version FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(version)
+    }
+
+    /// Flag a link whose grant the provider no longer honours and drop
+    /// its tokens. The row itself stays: it is still how the user signs
+    /// in. [`Self::update_oauth_credentials`] clears the flag once a
+    /// later sign-in stores a new refresh token.
+    #[builder]
+    pub async fn mark_oauth_credentials_needs_reauth(&self, id: Uuid) -> DbResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE oauth_credentials
+            SET needs_reauth = true,
+                access_token = NULL,
+                refresh_token = NULL,
+                access_token_expiry = NULL,
+                updated_at = now()
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
     /// Delete the `oauth_credentials` row for `(provider, user_id)`.
     ///
     /// Idempotent by design — Apple may deliver the same termination
@@ -809,7 +1012,7 @@ impl DatabaseManager {
             r#"
             UPDATE users SET email_verified = true, updated_at = now()
             WHERE id = $1
-            RETURNING id, email, display_name, email_verified, created_at, updated_at
+            RETURNING id, email, display_name, email_verified, analytics_consent, token_version, created_at, updated_at
             "#,
         )
         .bind(token.user_id)
@@ -821,6 +1024,51 @@ impl DatabaseManager {
         Ok(user)
     }
 
+    #[builder]
+    pub async fn set_analytics_consent(
+        &self,
+        user_id: Uuid,
+        consent: UserAnalyticsConsent,
+    ) -> DbResult<User> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            UPDATE users SET analytics_consent = $2
+            WHERE id = $1
+            RETURNING id, email, display_name, email_verified, analytics_consent, token_version, created_at, updated_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(consent)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    /// Whether images this user uploads have their metadata stripped.
+    /// Unknown users get the column default.
+    #[builder]
+    pub async fn get_strip_image_metadata(&self, user_id: Uuid) -> DbResult<bool> {
+        let strip =
+            sqlx::query_scalar::<_, bool>("SELECT strip_image_metadata FROM users WHERE id = $1")
+                .bind(user_id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(strip.unwrap_or(true))
+    }
+
+    #[builder]
+    pub async fn set_strip_image_metadata(&self, user_id: Uuid, enabled: bool) -> DbResult<()> {
+        sqlx::query("UPDATE users SET strip_image_metadata = $2 WHERE id = $1")
+            .bind(user_id)
+            .bind(enabled)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
     #[builder]
     pub async fn set_email_verified(&self, user_id: Uuid) -> DbResult<()> {
         sqlx::query("UPDATE users SET email_verified = true, updated_at = now() WHERE id = $1")
@@ -852,29 +1100,34 @@ impl DatabaseManager {
         Ok(token)
     }
 
+    /// Delete auth rows that can no longer be used: expired or consumed
+    /// OAuth states, login tokens and email-verification tokens, and
+    /// expired refresh tokens. Revoked refresh tokens are kept for 30
+    /// days so a replayed one can still be told apart from a forged one
+    /// in the logs.
     #[builder]
-    pub async fn cleanup_expired_auth_data(&self) -> DbResult<()> {
+    pub async fn cleanup_expired_auth_data(&self) -> DbResult<AuthCleanup> {
         let mut tx = self.pool.begin().await?;
 
-        let deleted_states = sqlx::query_scalar::<_, i64>(
-            "WITH deleted AS (DELETE FROM oauth_state WHERE expires_at < now() - interval '1 hour' RETURNING 1) SELECT count(*) FROM deleted",
+        let oauth_states = sqlx::query_scalar::<_, i64>(
+            "WITH deleted AS (DELETE FROM oauth_state WHERE consumed = true OR expires_at < now() - interval '1 hour' RETURNING 1) SELECT count(*) FROM deleted",
         )
         .fetch_one(&mut *tx)
         .await?;
 
-        let deleted_login_tokens = sqlx::query_scalar::<_, i64>(
-            "WITH deleted AS (DELETE FROM login_tokens WHERE expires_at < now() - interval '1 hour' RETURNING 1) SELECT count(*) FROM deleted",
+        let login_tokens = sqlx::query_scalar::<_, i64>(
+            "WITH deleted AS (DELETE FROM login_tokens WHERE consumed = true OR expires_at < now() - interval '1 hour' RETURNING 1) SELECT count(*) FROM deleted",
         )
         .fetch_one(&mut *tx)
         .await?;
 
-        let deleted_refresh_tokens = sqlx::query_scalar::<_, i64>(
-            "WITH deleted AS (DELETE FROM refresh_tokens WHERE revoked = true AND created_at < now() - interval '30 days' RETURNING 1) SELECT count(*) FROM deleted",
+        let refresh_tokens = sqlx::query_scalar::<_, i64>(
+            "WITH deleted AS (DELETE FROM refresh_tokens WHERE expires_at < now() OR (revoked = true AND created_at < now() - interval '30 days') RETURNING 1) SELECT count(*) FROM deleted",
         )
         .fetch_one(&mut *tx)
         .await?;
 
-        let deleted_verification_tokens = sqlx::query_scalar::<_, i64>(
+        let verification_tokens = sqlx::query_scalar::<_, i64>(
             "WITH deleted AS (DELETE FROM email_verification_tokens WHERE (consumed = true AND created_at < now() - interval '2 days') OR expires_at < now() - interval '1 hour' RETURNING 1) SELECT count(*) FROM deleted",
         )
         .fetch_one(&mut *tx)
@@ -882,21 +1135,36 @@ impl DatabaseManager {
 
         tx.commit().await?;
 
-        if deleted_states > 0
-            || deleted_login_tokens > 0
-            || deleted_refresh_tokens > 0
-            || deleted_verification_tokens > 0
-        {
-            tracing::info!(
-                "Cleaned up expired auth data: {} oauth_states, {} login_tokens, {} refresh_tokens, {} verification_tokens",
-                deleted_states,
-                deleted_login_tokens,
-                deleted_refresh_tokens,
-                deleted_verification_tokens,
-            );
-        }
+        Ok(AuthCleanup {
+            oauth_states,
+            login_tokens,
+            refresh_tokens,
+            verification_tokens,
+        })
+    }
 
-        Ok(())
+    /// Live row counts of the auth token tables, as estimated by
+    /// autovacuum. Cheap enough to read on every cleanup tick, unlike
+    /// `count(*)`.
+    #[builder]
+    pub async fn auth_token_table_sizes(&self) -> DbResult<AuthTokenTableSizes> {
+        let sizes = sqlx::query_as::<_, AuthTokenTableSizes>(
+            r#"
+            SELECT
+                COALESCE((SELECT n_live_tup FROM pg_stat_user_tables
+                          WHERE relid = 'oauth_state'::regclass), 0) AS oauth_states,
+                COALESCE((SELECT n_live_tup FROM pg_stat_user_tables
+                          WHERE relid = 'login_tokens'::regclass), 0) AS login_tokens,
+                COALESCE((SELECT n_live_tup FROM pg_stat_user_tables
+                          WHERE relid = 'refresh_tokens'::regclass), 0) AS refresh_tokens,
+                COALESCE((SELECT n_live_tup FROM pg_stat_user_tables
+                          WHERE relid = 'email_verification_tokens'::regclass), 0)
+                    AS verification_tokens
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(sizes)
     }
 
     /// Upsert the parent activity by `(user_id, identity_key)` and insert a
@@ -905,9 +1173,13 @@ impl DatabaseManager {
     /// `display_name` and `icon_asset_id` are *set-once*: subsequent calls
     /// with the same identity leave the existing values intact. A future
     /// rename / re-icon endpoint will be the only thing that mutates them.
-    /// Any prior live session for the same parent is closed in the same
-    /// transaction, so a crashed-then-restarted client never leaves more
-    /// than one open session per activity.
+    /// Any prior live session for the same parent on the same device is
+    /// closed in the same transaction, so a crashed-then-restarted client
+    /// never leaves more than one open session per activity, and two
+    /// machines in the same app don't close each other's sessions.
+    ///
+    /// `device_id` tags the session when it names one of the user's
+    /// devices, and marks that device as seen; any other id is dropped.
     #[builder]
     pub async fn insert_activity_session(
         &self,
@@ -920,6 +1192,7 @@ impl DatabaseManager {
         process_id: Option<i32>,
         window_title: Option<String>,
         url: Option<String>,
+        device_id: Option<Uuid>,
         started_at: DateTime<Utc>,
         ended_at: Option<DateTime<Utc>>,
     ) -> DbResult<(Activity, ActivitySession)> {
@@ -928,6 +1201,24 @@ impl DatabaseManager {
 
         let mut tx = self.pool.begin().await?;
 
+        let device_id = match device_id {
+            Some(device_id) => {
+                sqlx::query_scalar::<_, Uuid>(
+                    r#"
+                    UPDATE devices
+                    SET last_seen_at = now()
+                    WHERE id = $1 AND user_id = $2
+                    RETURNING id
+                    "#,
+                )
+                .bind(device_id)
+                .bind(user_id)
+                .fetch_optional(&mut *tx)
+                .await?
+            }
+            None => None,
+        };
+
         let activity = sqlx::query_as::<_, Activity>(
             r#"
             INSERT INTO activities (id, user_id, identity_key, display_name, icon_asset_id, last_used_at, created_at, updated_at)
@@ -950,24 +1241,27 @@ impl DatabaseManager {
         // Close any prior live sessions for this parent — a crash that
         // skipped the closing PATCH would otherwise leave them open
         // forever, and the rail's live-indicator would point at a stale
-        // session id. Bounded blast radius: only the same (user, parent).
+        // session id. Bounded blast radius: only the same (user, parent,
+        // device).
         sqlx::query(
             r#"
             UPDATE activity_sessions
             SET ended_at = now(), updated_at = now()
             WHERE activity_id = $1 AND user_id = $2 AND ended_at IS NULL
+              AND device_id IS NOT DISTINCT FROM $3
             "#,
         )
         .bind(activity.id)
         .bind(user_id)
+        .bind(device_id)
         .execute(&mut *tx)
         .await?;
 
         let session = sqlx::query_as::<_, ActivitySession>(
             r#"
-            INSERT INTO activity_sessions (id, activity_id, user_id, process_name, process_id, window_title, url, started_at, ended_at, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, now(), now())
-            RETURNING id, activity_id, user_id, process_name, process_id, window_title, url, started_at, ended_at, created_at, updated_at
+            INSERT INTO activity_sessions (id, activity_id, user_id, process_name, process_id, window_title, url, device_id, started_at, ended_at, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, now(), now())
+            RETURNING id, activity_id, user_id, process_name, process_id, window_title, url, device_id, started_at, ended_at, created_at, updated_at
             "#,
         )
         .bind(session_id)
@@ -977,6 +1271,7 @@ impl DatabaseManager {
         .bind(process_id)
         .bind(&window_title)
         .bind(&url)
+        .bind(device_id)
         .bind(started_at)
         .bind(ended_at)
         .fetch_one(&mut *tx)
@@ -1030,7 +1325,7 @@ impl DatabaseManager {
                 ended_at     = COALESCE($5, ended_at),
                 updated_at   = now()
             WHERE id = $1 AND user_id = $2
-            RETURNING id, activity_id, user_id, process_name, process_id, window_title, url, started_at, ended_at, created_at, updated_at
+            RETURNING id, activity_id, user_id, process_name, process_id, window_title, url, device_id, started_at, ended_at, created_at, updated_at
             "#,
         )
         .bind(session_id)
@@ -1083,32 +1378,93 @@ impl DatabaseManager {
             params.order()
         );
 
-        let parents = sqlx::query_as::<_, Activity>(&parents_query)
-            .bind(user_id)
-            .bind(params.limit())
-            .bind(params.offset())
-            .fetch_all(&self.pool)
+        let parents = self
+            .read(|pool| {
+                sqlx::query_as::<_, Activity>(&parents_query)
+                    .bind(user_id)
+                    .bind(params.limit())
+                    .bind(params.offset())
+                    .fetch_all(pool)
+            })
+            .await?;
+
+        self.attach_latest_sessions(user_id, parents).await
+    }
+
+    /// Keyset page of parent activities for `GET /activities/stream`, in
+    /// the same `last_used_at DESC` order as
+    /// [`list_activities_with_latest_session`](Self::list_activities_with_latest_session).
+    ///
+    /// `after` is the `(last_used_at, id)` of the last row of the previous
+    /// page; `id` breaks ties so rows sharing a timestamp are neither
+    /// skipped nor repeated. `since` is inclusive and `until` exclusive,
+    /// both on `last_used_at`.
+    #[builder]
+    pub async fn list_activities_page(
+        &self,
+        user_id: Uuid,
+        after: Option<(DateTime<Utc>, Uuid)>,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+        limit: i64,
+    ) -> DbResult<Vec<(Activity, Option<ActivitySession>)>> {
+        let (after_last_used_at, after_id) = after.unzip();
+        let parents = self
+            .read(|pool| {
+                sqlx::query_as::<_, Activity>(
+                    r#"
+                    SELECT id, user_id, identity_key, display_name, icon_asset_id, last_used_at, created_at, updated_at
+                    FROM activities
+                    WHERE user_id = $1
+                      AND ($2::timestamptz IS NULL OR (last_used_at, id) < ($2, $3))
+                      AND ($4::timestamptz IS NULL OR last_used_at >= $4)
+                      AND ($5::timestamptz IS NULL OR last_used_at < $5)
+                    ORDER BY last_used_at DESC, id DESC
+                    LIMIT $6
+                    "#,
+                )
+                .bind(user_id)
+                .bind(after_last_used_at)
+                .bind(after_id)
+                .bind(since)
+                .bind(until)
+                .bind(limit)
+                .fetch_all(pool)
+            })
             .await?;
 
+        self.attach_latest_sessions(user_id, parents).await
+    }
+
+    /// Pair each parent with its most recent session. The `DISTINCT ON`
+    /// query is bounded by the page size.
+    async fn attach_latest_sessions(
+        &self,
+        user_id: Uuid,
+        parents: Vec<Activity>,
+    ) -> DbResult<Vec<(Activity, Option<ActivitySession>)>> {
         if parents.is_empty() {
             return Ok(Vec::new());
         }
 
         let parent_ids: Vec<Uuid> = parents.iter().map(|a| a.id).collect();
 
-        let latest_sessions = sqlx::query_as::<_, ActivitySession>(
-            r#"
-            SELECT DISTINCT ON (activity_id)
-                id, activity_id, user_id, process_name, process_id, window_title, url, started_at, ended_at, created_at, updated_at
-            FROM activity_sessions
-            WHERE user_id = $1 AND activity_id = ANY($2)
-            ORDER BY activity_id, started_at DESC
-            "#,
-        )
-        .bind(user_id)
-        .bind(&parent_ids)
-        .fetch_all(&self.pool)
-        .await?;
+        let latest_sessions = self
+            .read(|pool| {
+                sqlx::query_as::<_, ActivitySession>(
+                    r#"
+                    SELECT DISTINCT ON (activity_id)
+                        id, activity_id, user_id, process_name, process_id, window_title, url, device_id, started_at, ended_at, created_at, updated_at
+                    FROM activity_sessions
+                    WHERE user_id = $1 AND activity_id = ANY($2)
+                    ORDER BY activity_id, started_at DESC
+                    "#,
+                )
+                .bind(user_id)
+                .bind(&parent_ids)
+                .fetch_all(pool)
+            })
+            .await?;
 
         let mut by_parent = std::collections::HashMap::with_capacity(latest_sessions.len());
         for session in latest_sessions {
@@ -1138,7 +1494,7 @@ impl DatabaseManager {
     ) -> DbResult<Vec<ActivitySession>> {
         let query = format!(
             r#"
-            SELECT s.id, s.activity_id, s.user_id, s.process_name, s.process_id, s.window_title, s.url, s.started_at, s.ended_at, s.created_at, s.updated_at
+            SELECT s.id, s.activity_id, s.user_id, s.process_name, s.process_id, s.window_title, s.url, s.device_id, s.started_at, s.ended_at, s.created_at, s.updated_at
             FROM activity_sessions s
             JOIN activities a ON a.id = s.activity_id
             WHERE s.user_id = $1 AND a.user_id = $1 AND s.activity_id = $2
@@ -1148,17 +1504,104 @@ impl DatabaseManager {
             params.order()
         );
 
-        let sessions = sqlx::query_as::<_, ActivitySession>(&query)
-            .bind(user_id)
-            .bind(activity_id)
-            .bind(params.limit())
-            .bind(params.offset())
-            .fetch_all(&self.pool)
+        let sessions = self
+            .read(|pool| {
+                sqlx::query_as::<_, ActivitySession>(&query)
+                    .bind(user_id)
+                    .bind(activity_id)
+                    .bind(params.limit())
+                    .bind(params.offset())
+                    .fetch_all(pool)
+            })
             .await?;
 
         Ok(sessions)
     }
 
+    /// Sessions overlapping `[since, until)`, grouped under their parent
+    /// activity. Still-open sessions count as overlapping if they started
+    /// before `until`.
+    ///
+    /// Feeds timeline reports. `limit` caps the sessions read, oldest
+    /// first, so a busy range degrades to a truncated report rather than
+    /// an unbounded scan. Parents come back in the order of their first
+    /// session in the range. With `device_id`, only that device's
+    /// sessions are read.
+    #[builder]
+    pub async fn list_activity_sessions_between(
+        &self,
+        user_id: Uuid,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+        device_id: Option<Uuid>,
+        limit: i64,
+    ) -> DbResult<Vec<(Activity, Vec<ActivitySession>)>> {
+        let sessions = self
+            .read(|pool| {
+                sqlx::query_as::<_, ActivitySession>(
+                    r#"
+                    SELECT id, activity_id, user_id, process_name, process_id, window_title, url, device_id, started_at, ended_at, created_at, updated_at
+                    FROM activity_sessions
+                    WHERE user_id = $1
+                      AND started_at < $3
+                      AND (ended_at IS NULL OR ended_at > $2)
+                      AND ($4::uuid IS NULL OR device_id = $4)
+                    ORDER BY started_at, id
+                    LIMIT $5
+                    "#,
+                )
+                .bind(user_id)
+                .bind(since)
+                .bind(until)
+                .bind(device_id)
+                .bind(limit)
+                .fetch_all(pool)
+            })
+            .await?;
+        if sessions.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        let parent_ids: Vec<Uuid> = sessions
+            .iter()
+            .map(|s| s.activity_id)
+            .filter(|id| seen.insert(*id))
+            .collect();
+
+        let parents = self
+            .read(|pool| {
+                sqlx::query_as::<_, Activity>(
+                    r#"
+                    SELECT id, user_id, identity_key, display_name, icon_asset_id, last_used_at, created_at, updated_at
+                    FROM activities
+                    WHERE user_id = $1 AND id = ANY($2)
+                    "#,
+                )
+                .bind(user_id)
+                .bind(&parent_ids)
+                .fetch_all(pool)
+            })
+            .await?;
+
+        let mut by_id: std::collections::HashMap<Uuid, (Activity, Vec<ActivitySession>)> = parents
+            .into_iter()
+            .map(|parent| (parent.id, (parent, Vec::new())))
+            .collect();
+        for session in sessions {
+            if let Some((_, grouped)) = by_id.get_mut(&session.activity_id) {
+                grouped.push(session);
+            }
+        }
+
+        Ok(parent_ids
+            .into_iter()
+            .filter_map(|id| by_id.remove(&id))
+            .collect())
+    }
+
+    /// `device_id` tags the asset when it names one of the user's devices;
+    /// any other id is dropped.
     #[builder]
     pub async fn create_asset(
         &self,
@@ -1172,6 +1615,8 @@ impl DatabaseManager {
         storage_uri: String,
         status: Option<AssetStatus>,
         metadata: Option<serde_json::Value>,
+        #[builder(default)] scan_status: AssetScanStatus,
+        device_id: Option<Uuid>,
     ) -> DbResult<Asset> {
         let id = id.unwrap_or_else(Uuid::now_v7);
         let now = Utc::now();
@@ -1180,9 +1625,9 @@ impl DatabaseManager {
 
         let asset = sqlx::query_as::<_, Asset>(
             r#"
-            INSERT INTO assets (id, user_id, name, mime_type, size_bytes, checksum_sha256, storage_backend, storage_uri, status, metadata, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
-            RETURNING id, user_id, name, mime_type, size_bytes, checksum_sha256, storage_backend, storage_uri, status, metadata, created_at, updated_at
+            INSERT INTO assets (id, user_id, name, mime_type, size_bytes, checksum_sha256, storage_backend, storage_uri, status, metadata, scan_status, device_id, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, (SELECT id FROM devices WHERE id = $12 AND user_id = $2), $13, $14)
+            RETURNING id, user_id, name, mime_type, size_bytes, checksum_sha256, storage_backend, storage_uri, storage_tier, status, metadata, scan_status, scan_signature, scanned_at, device_id, created_at, updated_at
             "#,
         )
         .bind(id)
@@ -1195,6 +1640,8 @@ impl DatabaseManager {
         .bind(&storage_uri)
         .bind(status)
         .bind(&metadata)
+        .bind(scan_status)
+        .bind(device_id)
         .bind(now)
         .bind(now)
         .fetch_one(&self.pool)
@@ -1203,19 +1650,29 @@ impl DatabaseManager {
         Ok(asset)
     }
 
-    /// Fetch an asset row scoped to its owner.
+    /// Fetch an asset row the user may read: their own, or one shared
+    /// with them through an unexpired grant.
     ///
-    /// The `user_id` predicate is in the WHERE clause (not a separate
-    /// authorization check) so a row belonging to a different user
-    /// surfaces as `NotFound` rather than `Forbidden` — that avoids
-    /// leaking existence of another user's assets.
+    /// The access predicate is in the WHERE clause (not a separate
+    /// authorization check) so a row the user can't read surfaces as
+    /// `NotFound` rather than `Forbidden` — that avoids leaking existence
+    /// of another user's assets.
     #[builder]
     pub async fn get_asset_for_user(&self, asset_id: Uuid, user_id: Uuid) -> DbResult<Asset> {
         let asset = sqlx::query_as::<_, Asset>(
             r#"
-            SELECT id, user_id, name, mime_type, size_bytes, checksum_sha256, storage_backend, storage_uri, status, metadata, created_at, updated_at
+            SELECT id, user_id, name, mime_type, size_bytes, checksum_sha256, storage_backend, storage_uri, storage_tier, status, metadata, scan_status, scan_signature, scanned_at, device_id, created_at, updated_at
             FROM assets
-            WHERE id = $1 AND user_id = $2
+            WHERE id = $1
+              AND (
+                  user_id = $2
+                  OR EXISTS (
+                      SELECT 1 FROM asset_grants g
+                      WHERE g.asset_id = assets.id
+                        AND g.grantee_id = $2
+                        AND (g.expires_at IS NULL OR g.expires_at > now())
+                  )
+              )
             "#,
         )
         .bind(asset_id)
@@ -1226,1461 +1683,4677 @@ impl DatabaseManager {
         Ok(asset)
     }
 
-    /// Insert a link between `activity_id` and `thread_id` for the given user.
-    ///
-    /// Returns the newly inserted row on success, or `None` when the link
-    /// already exists (`ON CONFLICT DO NOTHING`) or either endpoint is not
-    /// owned by `user_id`. Callers that link opportunistically (e.g. the chat
-    /// send path, which fires once per message) can ignore the result — the
-    /// composite primary key on `(activity_id, thread_id)` keeps the table
-    /// from growing on repeat sends.
+    /// Every asset in `ids` owned by `user_id`, in no particular order.
+    /// Ids that don't exist or belong to someone else are simply absent,
+    /// matching [`Self::get_asset_for_user`]'s non-disclosure.
     #[builder]
-    pub async fn link_activity_to_thread(
+    pub async fn get_assets_for_user(&self, ids: &[Uuid], user_id: Uuid) -> DbResult<Vec<Asset>> {
+        let assets = sqlx::query_as::<_, Asset>(
+            r#"
+            SELECT id, user_id, name, mime_type, size_bytes, checksum_sha256, storage_backend, storage_uri, storage_tier, status, metadata, scan_status, scan_signature, scanned_at, device_id, created_at, updated_at
+            FROM assets
+            WHERE id = ANY($1) AND user_id = $2
+            "#,
+        )
+        .bind(ids)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(assets)
+    }
+
+    /// Delete the assets in `ids` owned by `user_id` and return the ids
+    /// that went. Held assets stay. Stored objects are the caller's job,
+    /// and should be removed first; check [`Self::list_held_assets`]
+    /// before removing them.
+    #[builder]
+    pub async fn delete_assets_for_user(&self, ids: &[Uuid], user_id: Uuid) -> DbResult<Vec<Uuid>> {
+        let deleted = sqlx::query_scalar::<_, Uuid>(
+            "DELETE FROM assets \
+             WHERE id = ANY($1) AND user_id = $2 AND legal_hold_at IS NULL \
+             RETURNING id",
+        )
+        .bind(ids)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(deleted)
+    }
+
+    /// Link every asset in `asset_ids` owned by `user_id` to the user's
+    /// `activity_id` and return the ids now linked, including ones that
+    /// already were. Fails with `NotFound` when the activity isn't the
+    /// user's; foreign or unknown asset ids are left out of the result.
+    #[builder]
+    pub async fn link_assets_to_activity(
         &self,
         activity_id: Uuid,
-        thread_id: Uuid,
         user_id: Uuid,
-    ) -> DbResult<Option<ActivityThread>> {
-        let now = Utc::now();
+        asset_ids: &[Uuid],
+    ) -> DbResult<Vec<Uuid>> {
+        let mut tx = self.pool.begin().await?;
 
-        let link = sqlx::query_as::<_, ActivityThread>(
+        let activity_exists = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (SELECT 1 FROM activities WHERE id = $1 AND user_id = $2)",
+        )
+        .bind(activity_id)
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        if !activity_exists {
+            return Err(DbError::not_found_with_id(
+                "activity",
+                activity_id.to_string(),
+            ));
+        }
+
+        let linked = sqlx::query_scalar::<_, Uuid>(
             r#"
-            WITH verified_activity AS (
-                SELECT id FROM activities WHERE id = $1 AND user_id = $3
+            WITH owned AS (
+                SELECT id FROM assets WHERE id = ANY($2) AND user_id = $3
             ),
-            verified_thread AS (
-                SELECT id FROM threads WHERE id = $2 AND user_id = $3
+            inserted AS (
+                INSERT INTO activity_assets (activity_id, asset_id)
+                SELECT $1, id FROM owned
+                ON CONFLICT (activity_id, asset_id) DO NOTHING
             )
-            INSERT INTO activity_threads (activity_id, thread_id, created_at)
-            SELECT va.id, vt.id, $4
-            FROM verified_activity va, verified_thread vt
-            ON CONFLICT (activity_id, thread_id) DO NOTHING
-            RETURNING activity_id, thread_id, created_at
+            SELECT id FROM owned
             "#,
         )
         .bind(activity_id)
-        .bind(thread_id)
+        .bind(asset_ids)
         .bind(user_id)
-        .bind(now)
-        .fetch_optional(&self.pool)
+        .fetch_all(&mut *tx)
         .await?;
 
-        Ok(link)
+        tx.commit().await?;
+
+        Ok(linked)
     }
 
+    /// Page of a user's assets, ordered by id (creation order, since ids are
+    /// UUIDv7).
     #[builder]
-    pub async fn create_thread(
+    pub async fn list_assets(
         &self,
-        id: Option<Uuid>,
         user_id: Uuid,
-        title: String,
-    ) -> DbResult<Thread> {
-        let id = id.unwrap_or_else(Uuid::now_v7);
-        let now = Utc::now();
-
-        let thread = sqlx::query_as::<_, Thread>(
+        params: PaginationParams,
+    ) -> DbResult<Vec<Asset>> {
+        let query = format!(
             r#"
-            INSERT INTO threads (id, user_id, title, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5)
-            RETURNING id, user_id, title, active_leaf_id, created_at, updated_at
+            SELECT id, user_id, name, mime_type, size_bytes, checksum_sha256, storage_backend, storage_uri, storage_tier, status, metadata, scan_status, scan_signature, scanned_at, device_id, created_at, updated_at
+            FROM assets
+            WHERE user_id = $1
+            ORDER BY id {}
+            LIMIT $2 OFFSET $3
             "#,
-        )
-        .bind(id)
-        .bind(user_id)
-        .bind(&title)
-        .bind(now)
-        .bind(now)
-        .fetch_one(&self.pool)
-        .await?;
-
-        Ok(thread)
-    }
+            params.order()
+        );
 
-    #[builder]
-    pub async fn delete_thread(&self, id: Uuid, user_id: Uuid) -> DbResult<()> {
-        let result = sqlx::query("DELETE FROM threads WHERE id = $1 AND user_id = $2")
-            .bind(id)
-            .bind(user_id)
-            .execute(&self.pool)
+        let assets = self
+            .read(|pool| {
+                sqlx::query_as::<_, Asset>(&query)
+                    .bind(user_id)
+                    .bind(params.limit())
+                    .bind(params.offset())
+                    .fetch_all(pool)
+            })
             .await?;
 
-        if result.rows_affected() == 0 {
-            return Err(DbError::NotFound {
-                entity: "thread",
-                id: Some(id.to_string()),
-            });
-        }
-        Ok(())
+        Ok(assets)
     }
 
+    /// Claim up to `limit` assets waiting for a malware scan, skipping ones
+    /// another worker holds under an unexpired lease.
+    ///
+    /// Claimed rows get `scan_lease_until = now + lease` and
+    /// `scan_attempts` incremented; the returned rows carry the new count.
     #[builder]
-    pub async fn get_thread(&self, id: Uuid, user_id: Uuid) -> DbResult<Thread> {
-        let thread = sqlx::query_as::<_, Thread>(
+    pub async fn claim_pending_asset_scans(
+        &self,
+        limit: i64,
+        lease: chrono::Duration,
+    ) -> DbResult<Vec<PendingAssetScan>> {
+        let lease_until = Utc::now() + lease;
+        let scans = sqlx::query_as::<_, PendingAssetScan>(
             r#"
-            SELECT id, user_id, title, active_leaf_id, created_at, updated_at
-            FROM threads
-            WHERE id = $1 AND user_id = $2
+            WITH due AS (
+                SELECT id
+                FROM assets
+                WHERE scan_status = 'pending'
+                  AND (scan_lease_until IS NULL OR scan_lease_until <= now())
+                ORDER BY created_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            UPDATE assets AS a
+            SET scan_lease_until = $2,
+                scan_attempts = a.scan_attempts + 1
+            FROM due
+            WHERE a.id = due.id
+            RETURNING a.id, a.user_id, a.name, a.size_bytes, a.storage_uri,
+                      a.scan_attempts AS attempts
             "#,
         )
-        .bind(id)
-        .bind(user_id)
-        .fetch_one(&self.pool)
+        .bind(limit)
+        .bind(lease_until)
+        .fetch_all(&self.pool)
         .await?;
 
-        Ok(thread)
+        Ok(scans)
     }
 
+    /// Record the verdict for a pending scan and release its lease.
+    ///
+    /// Returns `false` when the asset is gone or no longer pending (it
+    /// was deleted mid-scan), so the caller can skip notifying anyone.
     #[builder]
-    pub async fn update_thread(&self, id: Uuid, user_id: Uuid, title: String) -> DbResult<Thread> {
-        let now = Utc::now();
-
-        let thread = sqlx::query_as::<_, Thread>(
+    pub async fn finish_asset_scan(
+        &self,
+        id: Uuid,
+        status: AssetScanStatus,
+        signature: Option<String>,
+    ) -> DbResult<bool> {
+        let result = sqlx::query(
             r#"
-            UPDATE threads
-            SET title = $1, updated_at = $2
-            WHERE id = $3 AND user_id = $4
-            RETURNING id, user_id, title, active_leaf_id, created_at, updated_at
+            UPDATE assets
+            SET scan_status = $2, scan_signature = $3, scanned_at = now(),
+                scan_lease_until = NULL
+            WHERE id = $1 AND scan_status = 'pending'
             "#,
         )
-        .bind(&title)
-        .bind(now)
         .bind(id)
-        .bind(user_id)
-        .fetch_one(&self.pool)
+        .bind(status)
+        .bind(signature)
+        .execute(&self.pool)
         .await?;
 
-        Ok(thread)
+        Ok(result.rows_affected() > 0)
     }
 
+    /// Quarantine an asset outside the malware scan, e.g. because its
+    /// stored bytes no longer match its checksum. `signature` says why.
+    /// Returns `false` when the asset is gone.
     #[builder]
-    pub async fn set_active_leaf(
-        &self,
-        id: Uuid,
-        user_id: Uuid,
-        active_leaf_id: Option<Uuid>,
-    ) -> DbResult<()> {
+    pub async fn quarantine_asset(&self, id: Uuid, signature: &str) -> DbResult<bool> {
         let result = sqlx::query(
             r#"
-            UPDATE threads
-            SET active_leaf_id = $1
-            WHERE id = $2 AND user_id = $3
-              AND ($1 IS NULL OR EXISTS(
-                  SELECT 1 FROM messages WHERE id = $1 AND thread_id = $2 AND user_id = $3
-              ))
+            UPDATE assets
+            SET scan_status = 'quarantined', scan_signature = $2, scanned_at = now(),
+                scan_lease_until = NULL
+            WHERE id = $1
             "#,
         )
-        .bind(active_leaf_id)
         .bind(id)
-        .bind(user_id)
+        .bind(signature)
         .execute(&self.pool)
         .await?;
 
-        if result.rows_affected() == 0 {
-            return Err(DbError::not_found_with_id("thread or active leaf", id));
-        }
-
-        Ok(())
+        Ok(result.rows_affected() > 0)
     }
 
+    // --- asset grants -----------------------------------------------------
+
+    /// Share the owner's asset with `grantee_id` until `expires_at`, or for
+    /// good with `None`. Sharing it again with the same user replaces the
+    /// expiry. Fails with `NotFound` when the asset isn't `owner_id`'s and
+    /// with [`DbError::ForeignKeyViolation`] when the grantee doesn't exist.
     #[builder]
-    pub async fn get_sibling_info(
+    pub async fn share_asset_with_user(
         &self,
-        thread_id: Uuid,
-        user_id: Uuid,
-        message_ids: &[Uuid],
-    ) -> DbResult<Vec<crate::types::SiblingInfo>> {
-        if message_ids.is_empty() {
-            return Ok(Vec::new());
+        asset_id: Uuid,
+        owner_id: Uuid,
+        grantee_id: Uuid,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> DbResult<AssetGrant> {
+        let grantee_exists: bool =
+            sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM users WHERE id = $1)")
+                .bind(grantee_id)
+                .fetch_one(&self.pool)
+                .await?;
+        if !grantee_exists {
+            return Err(DbError::foreign_key("user"));
         }
 
-        let rows = sqlx::query_as::<_, crate::types::SiblingInfo>(
+        sqlx::query_as::<_, AssetGrant>(
             r#"
-            WITH thread_messages AS (
-                SELECT m.id, m.parent_message_id, m.thread_id, m.created_at
-                FROM messages m
-                JOIN threads t ON t.id = m.thread_id
-                WHERE m.thread_id = $2 AND t.user_id = $3
-            ),
-            siblings AS (
-                SELECT
-                    m.id,
-                    COUNT(*) OVER (PARTITION BY m.parent_message_id) AS sibling_count,
-                    ROW_NUMBER() OVER (PARTITION BY m.parent_message_id ORDER BY m.created_at, m.id) - 1 AS sibling_index
-                FROM thread_messages m
-                WHERE m.parent_message_id IS NOT NULL
-            ),
-            root_siblings AS (
-                SELECT
-                    m.id,
-                    COUNT(*) OVER (PARTITION BY m.thread_id) AS sibling_count,
-                    ROW_NUMBER() OVER (PARTITION BY m.thread_id ORDER BY m.created_at, m.id) - 1 AS sibling_index
-                FROM thread_messages m
-                WHERE m.parent_message_id IS NULL
-            )
-            SELECT q.message_id, q.sibling_count, q.sibling_index FROM (
-                SELECT s.id AS message_id, s.sibling_count, s.sibling_index FROM siblings s WHERE s.id = ANY($1)
-                UNION ALL
-                SELECT r.id AS message_id, r.sibling_count, r.sibling_index FROM root_siblings r WHERE r.id = ANY($1)
-            ) q
+            INSERT INTO asset_grants (id, asset_id, grantee_id, expires_at)
+            SELECT $1, a.id, $4, $5
+            FROM assets a
+            WHERE a.id = $2 AND a.user_id = $3
+            ON CONFLICT (asset_id, grantee_id) WHERE grantee_id IS NOT NULL
+            DO UPDATE SET expires_at = EXCLUDED.expires_at
+            RETURNING id, asset_id, grantee_id, expires_at, created_at
             "#,
         )
-        .bind(message_ids)
-        .bind(thread_id)
-        .bind(user_id)
-        .fetch_all(&self.pool)
-        .await?;
-
-        Ok(rows)
+        .bind(Uuid::now_v7())
+        .bind(asset_id)
+        .bind(owner_id)
+        .bind(grantee_id)
+        .bind(expires_at)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| DbError::not_found_with_id("asset", asset_id.to_string()))
     }
 
+    /// Let whoever holds the token hashed to `token_hash` read the owner's
+    /// asset until `expires_at`. Fails with `NotFound` when the asset isn't
+    /// `owner_id`'s.
     #[builder]
-    pub async fn get_adjacent_sibling(
+    pub async fn create_asset_link(
         &self,
-        thread_id: Uuid,
-        user_id: Uuid,
-        message_id: Uuid,
-        direction: i32,
-    ) -> DbResult<Option<Uuid>> {
-        let row = sqlx::query_scalar::<_, Uuid>(
+        asset_id: Uuid,
+        owner_id: Uuid,
+        token_hash: &[u8],
+        expires_at: DateTime<Utc>,
+    ) -> DbResult<AssetGrant> {
+        sqlx::query_as::<_, AssetGrant>(
             r#"
-            WITH target AS (
-                SELECT m.id, m.parent_message_id, m.thread_id
-                FROM messages m
-                JOIN threads t ON t.id = m.thread_id
-                WHERE m.id = $1 AND m.thread_id = $3 AND t.user_id = $4
-            ),
-            siblings AS (
-                SELECT m.id,
-                       ROW_NUMBER() OVER (ORDER BY m.created_at, m.id) AS rn,
-                       COUNT(*) OVER () AS total
-                FROM messages m, target t
-                WHERE (t.parent_message_id IS NOT NULL AND m.parent_message_id = t.parent_message_id)
-                   OR (t.parent_message_id IS NULL AND m.parent_message_id IS NULL AND m.thread_id = t.thread_id)
-            )
-            SELECT s2.id
-            FROM siblings s1
-            JOIN siblings s2 ON s2.rn = CASE
-                WHEN s1.rn + $2 < 1 THEN s1.total
-                WHEN s1.rn + $2 > s1.total THEN 1
-                ELSE s1.rn + $2
-            END
-            WHERE s1.id = $1
+            INSERT INTO asset_grants (id, asset_id, token_hash, expires_at)
+            SELECT $1, a.id, $4, $5
+            FROM assets a
+            WHERE a.id = $2 AND a.user_id = $3
+            RETURNING id, asset_id, grantee_id, expires_at, created_at
             "#,
         )
-        .bind(message_id)
-        .bind(direction)
-        .bind(thread_id)
-        .bind(user_id)
+        .bind(Uuid::now_v7())
+        .bind(asset_id)
+        .bind(owner_id)
+        .bind(token_hash)
+        .bind(expires_at)
         .fetch_optional(&self.pool)
-        .await?;
-
-        Ok(row)
+        .await?
+        .ok_or_else(|| DbError::not_found_with_id("asset", asset_id.to_string()))
     }
 
-    pub async fn find_deepest_leaf(
+    /// Every grant on the owner's asset, expired ones included, newest
+    /// first. Fails with `NotFound` when the asset isn't `owner_id`'s.
+    #[builder]
+    pub async fn list_asset_grants(
         &self,
-        thread_id: Uuid,
-        user_id: Uuid,
-        message_id: Uuid,
-    ) -> DbResult<Uuid> {
-        let leaf = sqlx::query_scalar::<_, Uuid>(
+        asset_id: Uuid,
+        owner_id: Uuid,
+    ) -> DbResult<Vec<AssetGrant>> {
+        let owned: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM assets WHERE id = $1 AND user_id = $2)",
+        )
+        .bind(asset_id)
+        .bind(owner_id)
+        .fetch_one(&self.pool)
+        .await?;
+        if !owned {
+            return Err(DbError::not_found_with_id("asset", asset_id.to_string()));
+        }
+
+        let grants = sqlx::query_as::<_, AssetGrant>(
             r#"
-            WITH RECURSIVE descendants AS (
-                SELECT m.id, m.created_at, 0 AS depth
-                FROM messages m
-                JOIN threads t ON t.id = m.thread_id
-                WHERE m.id = $1 AND m.thread_id = $2 AND t.user_id = $3
-                UNION ALL
-                SELECT m.id, m.created_at, d.depth + 1
-                FROM messages m
-                JOIN descendants d ON m.parent_message_id = d.id
-                WHERE m.thread_id = $2
-            )
-            SELECT id FROM descendants ORDER BY depth DESC, created_at DESC, id DESC LIMIT 1
+            SELECT id, asset_id, grantee_id, expires_at, created_at
+            FROM asset_grants
+            WHERE asset_id = $1
+            ORDER BY created_at DESC, id DESC
             "#,
         )
-        .bind(message_id)
-        .bind(thread_id)
-        .bind(user_id)
-        .fetch_one(&self.pool)
+        .bind(asset_id)
+        .fetch_all(&self.pool)
         .await?;
 
-        Ok(leaf)
+        Ok(grants)
     }
 
+    /// Revoke one grant, user or link, on the owner's asset.
     #[builder]
-    pub async fn list_threads(
+    pub async fn delete_asset_grant(
         &self,
-        user_id: Uuid,
-        params: PaginationParams,
-    ) -> DbResult<Vec<Thread>> {
-        let query = format!(
+        grant_id: Uuid,
+        asset_id: Uuid,
+        owner_id: Uuid,
+    ) -> DbResult<()> {
+        let result = sqlx::query(
             r#"
-            SELECT id, user_id, title, active_leaf_id, created_at, updated_at
-            FROM threads
-            WHERE user_id = $1
-            ORDER BY id {}
-            LIMIT $2 OFFSET $3
+            DELETE FROM asset_grants g
+            USING assets a
+            WHERE g.id = $1 AND g.asset_id = $2 AND a.id = g.asset_id AND a.user_id = $3
             "#,
-            params.order()
-        );
+        )
+        .bind(grant_id)
+        .bind(asset_id)
+        .bind(owner_id)
+        .execute(&self.pool)
+        .await?;
 
-        let threads = sqlx::query_as::<_, Thread>(&query)
-            .bind(user_id)
-            .bind(params.limit())
-            .bind(params.offset())
-            .fetch_all(&self.pool)
+        if result.rows_affected() == 0 {
+            return Err(DbError::not_found_with_id(
+                "asset_grant",
+                grant_id.to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Assets other users have shared with `user_id` through unexpired
+    /// grants, most recently shared first.
+    #[builder]
+    pub async fn list_assets_shared_with_user(&self, user_id: Uuid) -> DbResult<Vec<Asset>> {
+        let assets = self
+            .read(|pool| {
+                sqlx::query_as::<_, Asset>(
+                    r#"
+                    SELECT a.id, a.user_id, a.name, a.mime_type, a.size_bytes, a.checksum_sha256, a.storage_backend, a.storage_uri, a.storage_tier, a.status, a.metadata, a.scan_status, a.scan_signature, a.scanned_at, a.device_id, a.created_at, a.updated_at
+                    FROM asset_grants g
+                    JOIN assets a ON a.id = g.asset_id
+                    WHERE g.grantee_id = $1
+                      AND (g.expires_at IS NULL OR g.expires_at > now())
+                    ORDER BY g.created_at DESC, g.id DESC
+                    "#,
+                )
+                .bind(user_id)
+                .fetch_all(pool)
+            })
             .await?;
 
-        Ok(threads)
+        Ok(assets)
     }
 
-    /// List threads that have been linked (via `activity_threads`) to the
-    /// supplied `activity_id`. User-scoped on both the thread and the activity
-    /// so a caller can't surface another user's threads by guessing an
-    /// activity uuid. Ordering and pagination follow [`list_threads`].
+    /// The asset an unexpired link token hashed to `token_hash` opens.
+    pub async fn get_asset_by_link(&self, token_hash: &[u8]) -> DbResult<Asset> {
+        let asset = sqlx::query_as::<_, Asset>(
+            r#"
+            SELECT a.id, a.user_id, a.name, a.mime_type, a.size_bytes, a.checksum_sha256, a.storage_backend, a.storage_uri, a.storage_tier, a.status, a.metadata, a.scan_status, a.scan_signature, a.scanned_at, a.device_id, a.created_at, a.updated_at
+            FROM asset_grants g
+            JOIN assets a ON a.id = g.asset_id
+            WHERE g.token_hash = $1 AND g.expires_at > now()
+            "#,
+        )
+        .bind(token_hash)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(asset)
+    }
+
+    // --- asset upload sessions --------------------------------------------
+
+    /// Open a resumable upload of `total_size` bytes, sent in chunks of
+    /// `chunk_size`. `None` when the user already has `max_open`
+    /// unexpired sessions. `device_id` tags the session when it names one
+    /// of the user's devices; any other id is dropped.
+    ///
+    /// The count and the insert run under a lock on the user's row, so
+    /// concurrent opens can't together go past `max_open`.
     #[builder]
-    pub async fn list_threads_for_activity(
+    pub async fn create_upload_session(
         &self,
+        id: Uuid,
         user_id: Uuid,
-        activity_id: Uuid,
-        params: PaginationParams,
-    ) -> DbResult<Vec<Thread>> {
-        let query = format!(
-            r#"
-            SELECT t.id, t.user_id, t.title, t.active_leaf_id, t.created_at, t.updated_at
-            FROM threads t
-            JOIN activity_threads at ON at.thread_id = t.id
-            JOIN activities a        ON a.id = at.activity_id
-            WHERE t.user_id = $1 AND a.user_id = $1 AND at.activity_id = $2
-            ORDER BY t.id {}
-            LIMIT $3 OFFSET $4
-            "#,
-            params.order()
-        );
+        name: &str,
+        mime_type: &str,
+        metadata: Option<serde_json::Value>,
+        device_id: Option<Uuid>,
+        total_size: i64,
+        chunk_size: i64,
+        expires_at: DateTime<Utc>,
+        max_open: i64,
+    ) -> DbResult<Option<UploadSession>> {
+        let mut tx = self.pool.begin().await?;
 
-        let threads = sqlx::query_as::<_, Thread>(&query)
+        // `FOR NO KEY UPDATE` only queues other holders of the same lock;
+        // inserts elsewhere that reference the user go ahead.
+        sqlx::query("SELECT 1 FROM users WHERE id = $1 FOR NO KEY UPDATE")
             .bind(user_id)
-            .bind(activity_id)
-            .bind(params.limit())
-            .bind(params.offset())
-            .fetch_all(&self.pool)
+            .fetch_optional(&mut *tx)
             .await?;
 
-        Ok(threads)
+        let open = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT COUNT(*) FROM asset_upload_sessions
+            WHERE user_id = $1 AND expires_at > now()
+            "#,
+        )
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        if open >= max_open {
+            return Ok(None);
+        }
+
+        let session = sqlx::query_as::<_, UploadSession>(
+            r#"
+            INSERT INTO asset_upload_sessions
+                (id, user_id, name, mime_type, metadata, device_id, total_size, chunk_size, expires_at)
+            VALUES ($1, $2, $3, $4, $5, (SELECT id FROM devices WHERE id = $6 AND user_id = $2), $7, $8, $9)
+            RETURNING id, user_id, name, mime_type, metadata, device_id, total_size, chunk_size, expires_at, created_at
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(name)
+        .bind(mime_type)
+        .bind(metadata)
+        .bind(device_id)
+        .bind(total_size)
+        .bind(chunk_size)
+        .bind(expires_at)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(session))
     }
 
+    /// The user's upload session, unless it expired.
     #[builder]
-    pub async fn create_message(
+    pub async fn get_upload_session(&self, id: Uuid, user_id: Uuid) -> DbResult<UploadSession> {
+        let session = sqlx::query_as::<_, UploadSession>(
+            r#"
+            SELECT id, user_id, name, mime_type, metadata, device_id, total_size, chunk_size, expires_at, created_at
+            FROM asset_upload_sessions
+            WHERE id = $1 AND user_id = $2 AND expires_at > now()
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(session)
+    }
+
+    /// The chunks a session has received, by offset.
+    pub async fn list_upload_chunks(&self, session_id: Uuid) -> DbResult<Vec<UploadChunk>> {
+        let chunks = sqlx::query_as::<_, UploadChunk>(
+            r#"
+            SELECT byte_offset, size, storage_uri
+            FROM asset_upload_chunks
+            WHERE session_id = $1
+            ORDER BY byte_offset
+            "#,
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(chunks)
+    }
+
+    /// Record the chunk at `byte_offset`, replacing one sent there before,
+    /// and push the session's expiry out to `expires_at`. Fails with
+    /// `NotFound` when the session isn't the user's or has expired.
+    #[builder]
+    pub async fn record_upload_chunk(
         &self,
-        id: Option<Uuid>,
+        session_id: Uuid,
+        user_id: Uuid,
+        byte_offset: i64,
+        size: i64,
+        storage_uri: &str,
+        expires_at: DateTime<Utc>,
+    ) -> DbResult<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let touched = sqlx::query(
+            r#"
+            UPDATE asset_upload_sessions
+            SET expires_at = $3
+            WHERE id = $1 AND user_id = $2 AND expires_at > now()
+            "#,
+        )
+        .bind(session_id)
+        .bind(user_id)
+        .bind(expires_at)
+        .execute(&mut *tx)
+        .await?;
+        if touched.rows_affected() == 0 {
+            return Err(DbError::not_found_with_id(
+                "asset_upload_session",
+                session_id.to_string(),
+            ));
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO asset_upload_chunks (session_id, byte_offset, size, storage_uri)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (session_id, byte_offset)
+            DO UPDATE SET size = EXCLUDED.size, storage_uri = EXCLUDED.storage_uri, created_at = now()
+            "#,
+        )
+        .bind(session_id)
+        .bind(byte_offset)
+        .bind(size)
+        .bind(storage_uri)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Delete the user's upload session and return the objects its chunks
+    /// were written to, for the caller to remove.
+    #[builder]
+    pub async fn delete_upload_session(&self, id: Uuid, user_id: Uuid) -> DbResult<Vec<String>> {
+        let mut tx = self.pool.begin().await?;
+
+        let uris = sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT c.storage_uri
+            FROM asset_upload_chunks c
+            JOIN asset_upload_sessions s ON s.id = c.session_id
+            WHERE s.id = $1 AND s.user_id = $2
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let deleted =
+            sqlx::query("DELETE FROM asset_upload_sessions WHERE id = $1 AND user_id = $2")
+                .bind(id)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+        if deleted.rows_affected() == 0 {
+            return Err(DbError::not_found_with_id(
+                "asset_upload_session",
+                id.to_string(),
+            ));
+        }
+
+        tx.commit().await?;
+        Ok(uris)
+    }
+
+    /// Delete up to `limit` sessions past their expiry and return them
+    /// with their chunks' objects. Rows go first, unlike expired assets: a
+    /// chunk arriving just before the deadline keeps its session alive,
+    /// so the row has to decide, and a failed object delete only leaks
+    /// the object.
+    pub async fn delete_expired_upload_sessions(
+        &self,
+        limit: i64,
+    ) -> DbResult<Vec<ExpiredUploadSession>> {
+        let sessions = sqlx::query_as::<_, ExpiredUploadSession>(
+            r#"
+            WITH expired AS (
+                DELETE FROM asset_upload_sessions
+                WHERE id IN (
+                    SELECT id FROM asset_upload_sessions
+                    WHERE expires_at <= now()
+                    ORDER BY expires_at
+                    LIMIT $1
+                    FOR UPDATE SKIP LOCKED
+                )
+                RETURNING id
+            )
+            SELECT e.id,
+                   COALESCE(
+                       array_agg(c.storage_uri) FILTER (WHERE c.storage_uri IS NOT NULL),
+                       '{}'
+                   ) AS chunk_uris
+            FROM expired e
+            LEFT JOIN asset_upload_chunks c ON c.session_id = e.id
+            GROUP BY e.id
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(sessions)
+    }
+
+    /// Insert a link between `activity_id` and `thread_id` for the given user.
+    ///
+    /// Returns the newly inserted row on success, or `None` when the link
+    /// already exists (`ON CONFLICT DO NOTHING`) or either endpoint is not
+    /// owned by `user_id`. Callers that link opportunistically (e.g. the chat
+    /// send path, which fires once per message) can ignore the result — the
+    /// composite primary key on `(activity_id, thread_id)` keeps the table
+    /// from growing on repeat sends.
+    #[builder]
+    pub async fn link_activity_to_thread(
+        &self,
+        activity_id: Uuid,
         thread_id: Uuid,
         user_id: Uuid,
-        parent_message_id: Option<Uuid>,
-        message_type: MessageType,
-        content: serde_json::Value,
-        tool_call_id: Option<String>,
-        tool_calls: Option<serde_json::Value>,
-        additional_kwargs: Option<serde_json::Value>,
-    ) -> DbResult<Message> {
-        let id = id.unwrap_or_else(Uuid::now_v7);
+    ) -> DbResult<Option<ActivityThread>> {
         let now = Utc::now();
-        let additional_kwargs = additional_kwargs.unwrap_or_else(|| serde_json::json!({}));
 
-        let message = sqlx::query_as::<_, Message>(
+        let link = sqlx::query_as::<_, ActivityThread>(
             r#"
-            WITH verified_thread AS (
-                SELECT id FROM threads
-                WHERE id = $2 AND user_id = $3
-            ),
-            updated_thread AS (
-                UPDATE threads
-                SET updated_at = $10, active_leaf_id = $1
-                WHERE id = (SELECT id FROM verified_thread)
-                RETURNING id
+            WITH verified_activity AS (
+                SELECT id FROM activities WHERE id = $1 AND user_id = $3
             ),
-            inserted_message AS (
-                INSERT INTO messages (id, thread_id, user_id, parent_message_id, message_type, content, tool_call_id, tool_calls, additional_kwargs, created_at, updated_at)
-                SELECT $1, vc.id, $3, COALESCE($4, t.active_leaf_id), $5, $6, $7, $8, $9, $10, $10
-                FROM verified_thread vc
-                JOIN threads t ON t.id = vc.id
-                RETURNING id, thread_id, user_id, parent_message_id, message_type, content, tool_call_id, tool_calls, additional_kwargs, created_at, updated_at
+            verified_thread AS (
+                SELECT id FROM threads WHERE id = $2 AND user_id = $3
             )
-            SELECT * FROM inserted_message
+            INSERT INTO activity_threads (activity_id, thread_id, created_at)
+            SELECT va.id, vt.id, $4
+            FROM verified_activity va, verified_thread vt
+            ON CONFLICT (activity_id, thread_id) DO NOTHING
+            RETURNING activity_id, thread_id, created_at
+            "#,
+        )
+        .bind(activity_id)
+        .bind(thread_id)
+        .bind(user_id)
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(link)
+    }
+
+    #[builder]
+    pub async fn create_thread(
+        &self,
+        id: Option<Uuid>,
+        user_id: Uuid,
+        title: String,
+    ) -> DbResult<Thread> {
+        let id = id.unwrap_or_else(Uuid::now_v7);
+        let now = Utc::now();
+
+        let thread = sqlx::query_as::<_, Thread>(
+            r#"
+            INSERT INTO threads (id, user_id, title, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, user_id, title, active_leaf_id, model_provider, model_name,
+                      model_temperature, tags, pinned_at, starred, folder_id,
+                      created_at, updated_at
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(&title)
+        .bind(now)
+        .bind(now)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(thread)
+    }
+
+    /// Delete one of the user's threads. Fails with
+    /// [`DbError::OnLegalHold`] while the thread is held.
+    #[builder]
+    pub async fn delete_thread(&self, id: Uuid, user_id: Uuid) -> DbResult<()> {
+        let result = sqlx::query(
+            "DELETE FROM threads WHERE id = $1 AND user_id = $2 AND legal_hold_at IS NULL",
+        )
+        .bind(id)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            let held: bool = sqlx::query_scalar(
+                "SELECT EXISTS (SELECT 1 FROM threads WHERE id = $1 AND user_id = $2)",
+            )
+            .bind(id)
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+            if held {
+                return Err(DbError::on_legal_hold("thread"));
+            }
+            return Err(DbError::NotFound {
+                entity: "thread",
+                id: Some(id.to_string()),
+            });
+        }
+        Ok(())
+    }
+
+    #[builder]
+    pub async fn get_thread(&self, id: Uuid, user_id: Uuid) -> DbResult<Thread> {
+        let thread = sqlx::query_as::<_, Thread>(
+            r#"
+            SELECT id, user_id, title, active_leaf_id, model_provider, model_name,
+                   model_temperature, tags, pinned_at, starred, folder_id,
+                   created_at, updated_at
+            FROM threads
+            WHERE id = $1 AND user_id = $2
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(thread)
+    }
+
+    /// Rename the thread. `tags` replaces the thread's tags when given
+    /// and leaves them alone otherwise.
+    #[builder]
+    pub async fn update_thread(
+        &self,
+        id: Uuid,
+        user_id: Uuid,
+        title: String,
+        tags: Option<Vec<String>>,
+    ) -> DbResult<Thread> {
+        let now = Utc::now();
+
+        let thread = sqlx::query_as::<_, Thread>(
+            r#"
+            UPDATE threads
+            SET title = $1, tags = COALESCE($5, tags), updated_at = $2
+            WHERE id = $3 AND user_id = $4
+            RETURNING id, user_id, title, active_leaf_id, model_provider, model_name,
+                      model_temperature, tags, pinned_at, starred, folder_id,
+                      created_at, updated_at
+            "#,
+        )
+        .bind(&title)
+        .bind(now)
+        .bind(id)
+        .bind(user_id)
+        .bind(tags)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(thread)
+    }
+
+    /// Pin the thread to a model, or with `model_provider` / `model_name`
+    /// both `None` return it to the deployment default. Bumps
+    /// `updated_at` like any other thread edit.
+    #[builder]
+    pub async fn set_thread_model(
+        &self,
+        id: Uuid,
+        user_id: Uuid,
+        model_provider: Option<String>,
+        model_name: Option<String>,
+        model_temperature: Option<f32>,
+    ) -> DbResult<Thread> {
+        let thread = sqlx::query_as::<_, Thread>(
+            r#"
+            UPDATE threads
+            SET model_provider = $1, model_name = $2, model_temperature = $3,
+                updated_at = $4
+            WHERE id = $5 AND user_id = $6
+            RETURNING id, user_id, title, active_leaf_id, model_provider, model_name,
+                      model_temperature, tags, pinned_at, starred, folder_id,
+                      created_at, updated_at
+            "#,
+        )
+        .bind(model_provider)
+        .bind(model_name)
+        .bind(model_temperature)
+        .bind(Utc::now())
+        .bind(id)
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(thread)
+    }
+
+    /// Pin / unpin and star / unstar the thread. `None` leaves a flag as
+    /// it is; re-pinning a pinned thread keeps its original `pinned_at`,
+    /// so it doesn't jump to the top of the pinned group.
+    #[builder]
+    pub async fn set_thread_flags(
+        &self,
+        id: Uuid,
+        user_id: Uuid,
+        pinned: Option<bool>,
+        starred: Option<bool>,
+    ) -> DbResult<Thread> {
+        let thread = sqlx::query_as::<_, Thread>(
+            r#"
+            UPDATE threads
+            SET pinned_at = CASE
+                    WHEN $1 IS NULL THEN pinned_at
+                    WHEN $1 THEN COALESCE(pinned_at, $3)
+                END,
+                starred = COALESCE($2, starred),
+                updated_at = $3
+            WHERE id = $4 AND user_id = $5
+            RETURNING id, user_id, title, active_leaf_id, model_provider, model_name,
+                      model_temperature, tags, pinned_at, starred, folder_id,
+                      created_at, updated_at
+            "#,
+        )
+        .bind(pinned)
+        .bind(starred)
+        .bind(Utc::now())
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| DbError::NotFound {
+            entity: "thread",
+            id: Some(id.to_string()),
+        })?;
+
+        Ok(thread)
+    }
+
+    /// Move the thread into `folder_id`, or out of any folder with `None`.
+    /// The folder must belong to the same user.
+    #[builder]
+    pub async fn move_thread_to_folder(
+        &self,
+        id: Uuid,
+        user_id: Uuid,
+        folder_id: Option<Uuid>,
+    ) -> DbResult<Thread> {
+        let thread = sqlx::query_as::<_, Thread>(
+            r#"
+            UPDATE threads
+            SET folder_id = $1, updated_at = $2
+            WHERE id = $3 AND user_id = $4
+              AND ($1 IS NULL OR EXISTS (
+                  SELECT 1 FROM thread_folders WHERE id = $1 AND user_id = $4
+              ))
+            RETURNING id, user_id, title, active_leaf_id, model_provider, model_name,
+                      model_temperature, tags, pinned_at, starred, folder_id,
+                      created_at, updated_at
+            "#,
+        )
+        .bind(folder_id)
+        .bind(Utc::now())
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| DbError::NotFound {
+            entity: "thread",
+            id: Some(id.to_string()),
+        })?;
+
+        Ok(thread)
+    }
+
+    #[builder]
+    pub async fn set_active_leaf(
+        &self,
+        id: Uuid,
+        user_id: Uuid,
+        active_leaf_id: Option<Uuid>,
+    ) -> DbResult<()> {
+        let result = sqlx::query(
+            r#"
+            UPDATE threads
+            SET active_leaf_id = $1
+            WHERE id = $2 AND user_id = $3
+              AND ($1 IS NULL OR EXISTS(
+                  SELECT 1 FROM messages WHERE id = $1 AND thread_id = $2 AND user_id = $3
+              ))
+            "#,
+        )
+        .bind(active_leaf_id)
+        .bind(id)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(DbError::not_found_with_id("thread or active leaf", id));
+        }
+
+        Ok(())
+    }
+
+    #[builder]
+    pub async fn get_sibling_info(
+        &self,
+        thread_id: Uuid,
+        user_id: Uuid,
+        message_ids: &[Uuid],
+    ) -> DbResult<Vec<crate::types::SiblingInfo>> {
+        if message_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let rows = sqlx::query_as::<_, crate::types::SiblingInfo>(
+            r#"
+            WITH thread_messages AS (
+                SELECT m.id, m.parent_message_id, m.thread_id, m.created_at
+                FROM messages m
+                JOIN threads t ON t.id = m.thread_id
+                WHERE m.thread_id = $2 AND t.user_id = $3
+            ),
+            siblings AS (
+                SELECT
+                    m.id,
+                    COUNT(*) OVER (PARTITION BY m.parent_message_id) AS sibling_count,
+                    ROW_NUMBER() OVER (PARTITION BY m.parent_message_id ORDER BY m.created_at, m.id) - 1 AS sibling_index
+                FROM thread_messages m
+                WHERE m.parent_message_id IS NOT NULL
+            ),
+            root_siblings AS (
+                SELECT
+                    m.id,
+                    COUNT(*) OVER (PARTITION BY m.thread_id) AS sibling_count,
+                    ROW_NUMBER() OVER (PARTITION BY m.thread_id ORDER BY m.created_at, m.id) - 1 AS sibling_index
+                FROM thread_messages m
+                WHERE m.parent_message_id IS NULL
+            )
+            SELECT q.message_id, q.sibling_count, q.sibling_index FROM (
+                SELECT s.id AS message_id, s.sibling_count, s.sibling_index FROM siblings s WHERE s.id = ANY($1)
+                UNION ALL
+                SELECT r.id AS message_id, r.sibling_count, r.sibling_index FROM root_siblings r WHERE r.id = ANY($1)
+            ) q
+            "#,
+        )
+        .bind(message_ids)
+        .bind(thread_id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    #[builder]
+    pub async fn get_adjacent_sibling(
+        &self,
+        thread_id: Uuid,
+        user_id: Uuid,
+        message_id: Uuid,
+        direction: i32,
+    ) -> DbResult<Option<Uuid>> {
+        let row = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            WITH target AS (
+                SELECT m.id, m.parent_message_id, m.thread_id
+                FROM messages m
+                JOIN threads t ON t.id = m.thread_id
+                WHERE m.id = $1 AND m.thread_id = $3 AND t.user_id = $4
+            ),
+            siblings AS (
+                SELECT m.id,
+                       ROW_NUMBER() OVER (ORDER BY m.created_at, m.id) AS rn,
+                       COUNT(*) OVER () AS total
+                FROM messages m, target t
+                WHERE (t.parent_message_id IS NOT NULL AND m.parent_message_id = t.parent_message_id)
+                   OR (t.parent_message_id IS NULL AND m.parent_message_id IS NULL AND m.thread_id = t.thread_id)
+            )
+            SELECT s2.id
+            FROM siblings s1
+            JOIN siblings s2 ON s2.rn = CASE
+                WHEN s1.rn + $2 < 1 THEN s1.total
+                WHEN s1.rn + $2 > s1.total THEN 1
+                ELSE s1.rn + $2
+            END
+            WHERE s1.id = $1
+            "#,
+        )
+        .bind(message_id)
+        .bind(direction)
+        .bind(thread_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    pub async fn find_deepest_leaf(
+        &self,
+        thread_id: Uuid,
+        user_id: Uuid,
+        message_id: Uuid,
+    ) -> DbResult<Uuid> {
+        let leaf = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            WITH RECURSIVE descendants AS (
+                SELECT m.id, m.created_at, 0 AS depth
+                FROM messages m
+                JOIN threads t ON t.id = m.thread_id
+                WHERE m.id = $1 AND m.thread_id = $2 AND t.user_id = $3
+                UNION ALL
+                SELECT m.id, m.created_at, d.depth + 1
+                FROM messages m
+                JOIN descendants d ON m.parent_message_id = d.id
+                WHERE m.thread_id = $2
+            )
+            SELECT id FROM descendants ORDER BY depth DESC, created_at DESC, id DESC LIMIT 1
+            "#,
+        )
+        .bind(message_id)
+        .bind(thread_id)
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(leaf)
+    }
+
+    /// List the user's threads, pinned ones first (most recently pinned on
+    /// top) and the rest in `params` order. `folder_id`, `starred` and
+    /// `pinned` narrow the list when given.
+    #[builder]
+    pub async fn list_threads(
+        &self,
+        user_id: Uuid,
+        params: PaginationParams,
+        folder_id: Option<Uuid>,
+        starred: Option<bool>,
+        pinned: Option<bool>,
+    ) -> DbResult<Vec<Thread>> {
+        let query = format!(
+            r#"
+            SELECT id, user_id, title, active_leaf_id, model_provider, model_name,
+                   model_temperature, tags, pinned_at, starred, folder_id,
+                   created_at, updated_at
+            FROM threads
+            WHERE user_id = $1
+              AND ($4::uuid IS NULL OR folder_id = $4)
+              AND ($5::boolean IS NULL OR starred = $5)
+              AND ($6::boolean IS NULL OR (pinned_at IS NOT NULL) = $6)
+            ORDER BY pinned_at DESC NULLS LAST, id {}
+            LIMIT $2 OFFSET $3
+            "#,
+            params.order()
+        );
+
+        let threads = self
+            .read(|pool| {
+                sqlx::query_as::<_, Thread>(&query)
+                    .bind(user_id)
+                    .bind(params.limit())
+                    .bind(params.offset())
+                    .bind(folder_id)
+                    .bind(starred)
+                    .bind(pinned)
+                    .fetch_all(pool)
+            })
+            .await?;
+
+        Ok(threads)
+    }
+
+    /// List threads that have been linked (via `activity_threads`) to the
+    /// supplied `activity_id`. User-scoped on both the thread and the activity
+    /// so a caller can't surface another user's threads by guessing an
+    /// activity uuid. Ordering and pagination follow [`list_threads`].
+    #[builder]
+    pub async fn list_threads_for_activity(
+        &self,
+        user_id: Uuid,
+        activity_id: Uuid,
+        params: PaginationParams,
+    ) -> DbResult<Vec<Thread>> {
+        let query = format!(
+            r#"
+            SELECT t.id, t.user_id, t.title, t.active_leaf_id, t.model_provider, t.model_name,
+                   t.model_temperature, t.tags, t.pinned_at, t.starred, t.folder_id,
+                   t.created_at, t.updated_at
+            FROM threads t
+            JOIN activity_threads at ON at.thread_id = t.id
+            JOIN activities a        ON a.id = at.activity_id
+            WHERE t.user_id = $1 AND a.user_id = $1 AND at.activity_id = $2
+            ORDER BY t.pinned_at DESC NULLS LAST, t.id {}
+            LIMIT $3 OFFSET $4
+            "#,
+            params.order()
+        );
+
+        let threads = self
+            .read(|pool| {
+                sqlx::query_as::<_, Thread>(&query)
+                    .bind(user_id)
+                    .bind(activity_id)
+                    .bind(params.limit())
+                    .bind(params.offset())
+                    .fetch_all(pool)
+            })
+            .await?;
+
+        Ok(threads)
+    }
+
+    /// Create a folder at the end of the user's folder list.
+    #[builder]
+    pub async fn create_thread_folder(&self, user_id: Uuid, name: &str) -> DbResult<ThreadFolder> {
+        let now = Utc::now();
+
+        let folder = sqlx::query_as::<_, ThreadFolder>(
+            r#"
+            INSERT INTO thread_folders (id, user_id, name, position, created_at, updated_at)
+            VALUES (
+                $1, $2, $3,
+                (SELECT COALESCE(MAX(position) + 1, 0) FROM thread_folders WHERE user_id = $2),
+                $4, $4
+            )
+            RETURNING id, user_id, name, position, created_at, updated_at
+            "#,
+        )
+        .bind(Uuid::now_v7())
+        .bind(user_id)
+        .bind(name)
+        .bind(now)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(folder)
+    }
+
+    /// The user's folders in sidebar order.
+    #[builder]
+    pub async fn list_thread_folders(&self, user_id: Uuid) -> DbResult<Vec<ThreadFolder>> {
+        let folders = self
+            .read(|pool| {
+                sqlx::query_as::<_, ThreadFolder>(
+                    r#"
+                    SELECT id, user_id, name, position, created_at, updated_at
+                    FROM thread_folders
+                    WHERE user_id = $1
+                    ORDER BY position, id
+                    "#,
+                )
+                .bind(user_id)
+                .fetch_all(pool)
+            })
+            .await?;
+
+        Ok(folders)
+    }
+
+    /// Rename and/or reorder a folder. `None` leaves a field as it is.
+    /// Positions needn't be contiguous; ties sort by creation order.
+    #[builder]
+    pub async fn update_thread_folder(
+        &self,
+        id: Uuid,
+        user_id: Uuid,
+        name: Option<&str>,
+        position: Option<i32>,
+    ) -> DbResult<ThreadFolder> {
+        sqlx::query_as::<_, ThreadFolder>(
+            r#"
+            UPDATE thread_folders
+            SET name = COALESCE($3, name),
+                position = COALESCE($4, position),
+                updated_at = $5
+            WHERE id = $1 AND user_id = $2
+            RETURNING id, user_id, name, position, created_at, updated_at
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(name)
+        .bind(position)
+        .bind(Utc::now())
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| DbError::NotFound {
+            entity: "folder",
+            id: Some(id.to_string()),
+        })
+    }
+
+    /// Delete a folder. Its threads stay, unfiled.
+    #[builder]
+    pub async fn delete_thread_folder(&self, id: Uuid, user_id: Uuid) -> DbResult<()> {
+        let result = sqlx::query("DELETE FROM thread_folders WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(DbError::NotFound {
+                entity: "folder",
+                id: Some(id.to_string()),
+            });
+        }
+        Ok(())
+    }
+
+    #[builder]
+    pub async fn create_message(
+        &self,
+        id: Option<Uuid>,
+        thread_id: Uuid,
+        user_id: Uuid,
+        parent_message_id: Option<Uuid>,
+        message_type: MessageType,
+        content: serde_json::Value,
+        tool_call_id: Option<String>,
+        tool_calls: Option<serde_json::Value>,
+        additional_kwargs: Option<serde_json::Value>,
+    ) -> DbResult<Message> {
+        let id = id.unwrap_or_else(Uuid::now_v7);
+        let now = Utc::now();
+        let additional_kwargs = additional_kwargs.unwrap_or_else(|| serde_json::json!({}));
+
+        let message = sqlx::query_as::<_, Message>(
+            r#"
+            WITH verified_thread AS (
+                SELECT id FROM threads
+                WHERE id = $2 AND user_id = $3
+            ),
+            updated_thread AS (
+                UPDATE threads
+                SET updated_at = $10, active_leaf_id = $1
+                WHERE id = (SELECT id FROM verified_thread)
+                RETURNING id
+            ),
+            inserted_message AS (
+                INSERT INTO messages (id, thread_id, user_id, parent_message_id, message_type, content, tool_call_id, tool_calls, additional_kwargs, created_at, updated_at)
+                SELECT $1, vc.id, $3, COALESCE($4, t.active_leaf_id), $5, $6, $7, $8, $9, $10, $10
+                FROM verified_thread vc
+                JOIN threads t ON t.id = vc.id
+                RETURNING id, thread_id, user_id, parent_message_id, message_type, content, tool_call_id, tool_calls, additional_kwargs, created_at, updated_at
+            )
+            SELECT * FROM inserted_message
+            "#,
+        )
+        .bind(id)
+        .bind(thread_id)
+        .bind(user_id)
+        .bind(parent_message_id)
+        .bind(message_type)
+        .bind(&content)
+        .bind(&tool_call_id)
+        .bind(&tool_calls)
+        .bind(&additional_kwargs)
+        .bind(now)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(message)
+    }
+
+    /// Fetch a single message by id, scoped to (thread, user) for authorization.
+    ///
+    /// Returns `Ok(None)` when no row matches — the caller decides whether to
+    /// treat that as a not-found error or a benign missing reference.
+    pub async fn get_message(
+        &self,
+        thread_id: Uuid,
+        user_id: Uuid,
+        message_id: Uuid,
+    ) -> DbResult<Option<Message>> {
+        let row = sqlx::query_as::<_, Message>(
+            r#"
+            SELECT m.id, m.thread_id, m.user_id, m.parent_message_id, m.message_type,
+                   m.content, m.tool_call_id, m.tool_calls, m.additional_kwargs,
+                   m.created_at, m.updated_at
+            FROM messages m
+            JOIN threads t ON t.id = m.thread_id
+            WHERE m.id = $1 AND m.thread_id = $2 AND t.user_id = $3
+            "#,
+        )
+        .bind(message_id)
+        .bind(thread_id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row)
+    }
+
+    #[builder]
+    pub async fn list_messages(
+        &self,
+        thread_id: Uuid,
+        user_id: Uuid,
+        params: Option<PaginationParams>,
+    ) -> DbResult<Vec<Message>> {
+        let params = params.unwrap_or_default();
+
+        let query = format!(
+            r#"
+            WITH RECURSIVE branch AS (
+                SELECT m.id, m.thread_id, m.user_id, m.parent_message_id, m.message_type,
+                       m.content, m.tool_call_id, m.tool_calls, m.additional_kwargs,
+                       m.created_at, m.updated_at
+                FROM messages m
+                JOIN threads t ON t.active_leaf_id = m.id
+                WHERE t.id = $1 AND t.user_id = $2
+
+                UNION ALL
+
+                SELECT parent.id, parent.thread_id, parent.user_id, parent.parent_message_id,
+                       parent.message_type, parent.content, parent.tool_call_id, parent.tool_calls,
+                       parent.additional_kwargs,
+                       parent.created_at, parent.updated_at
+                FROM messages parent
+                JOIN branch child ON child.parent_message_id = parent.id
+                    AND parent.thread_id = $1 AND parent.user_id = $2
+            )
+            SELECT m.id, m.thread_id, m.user_id, m.parent_message_id, m.message_type,
+                   m.content, m.tool_call_id, m.tool_calls, m.additional_kwargs,
+                   m.created_at, m.updated_at
+            FROM branch m
+            ORDER BY m.created_at {}
+            LIMIT $3 OFFSET $4
+            "#,
+            params.order()
+        );
+
+        let messages = sqlx::query_as::<_, Message>(&query)
+            .bind(thread_id)
+            .bind(user_id)
+            .bind(params.limit())
+            .bind(params.offset())
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(messages)
+    }
+
+    /// Every message in a thread across all branches, oldest first. Unlike
+    /// [`Self::list_messages`] this doesn't follow the active leaf, so
+    /// regenerated and edited turns are included; `parent_message_id`
+    /// reconstructs the tree.
+    #[builder]
+    pub async fn list_thread_messages(
+        &self,
+        thread_id: Uuid,
+        user_id: Uuid,
+    ) -> DbResult<Vec<Message>> {
+        let messages = sqlx::query_as::<_, Message>(
+            r#"
+            SELECT id, thread_id, user_id, parent_message_id, message_type,
+                   content, tool_call_id, tool_calls, additional_kwargs,
+                   created_at, updated_at
+            FROM messages
+            WHERE thread_id = $1 AND user_id = $2
+            ORDER BY created_at, id
+            "#,
+        )
+        .bind(thread_id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(messages)
+    }
+
+    #[builder]
+    pub async fn list_branch_with_siblings(
+        &self,
+        thread_id: Uuid,
+        user_id: Uuid,
+        params: Option<PaginationParams>,
+    ) -> DbResult<Vec<crate::types::BranchMessageRow>> {
+        let params = params.unwrap_or_default();
+
+        let query = format!(
+            r#"
+            WITH RECURSIVE branch AS (
+                SELECT m.id, m.thread_id, m.user_id, m.parent_message_id, m.message_type,
+                       m.content, m.tool_call_id, m.tool_calls, m.additional_kwargs,
+                       m.created_at, m.updated_at
+                FROM messages m
+                JOIN threads t ON t.active_leaf_id = m.id
+                WHERE t.id = $1 AND t.user_id = $2
+
+                UNION ALL
+
+                SELECT parent.id, parent.thread_id, parent.user_id, parent.parent_message_id,
+                       parent.message_type, parent.content, parent.tool_call_id, parent.tool_calls,
+                       parent.additional_kwargs,
+                       parent.created_at, parent.updated_at
+                FROM messages parent
+                JOIN branch child ON child.parent_message_id = parent.id
+                    AND parent.thread_id = $1 AND parent.user_id = $2
+            ),
+            active_branch AS (
+                SELECT id, parent_message_id,
+                       ROW_NUMBER() OVER (ORDER BY created_at {order}) AS rn
+                FROM branch
+            ),
+            paginated AS (
+                SELECT ab.id, ab.parent_message_id, b.created_at
+                FROM active_branch ab
+                JOIN branch b ON b.id = ab.id
+                WHERE ab.rn > $4 AND ab.rn <= $3 + $4
+            ),
+            numbered_branch AS (
+                SELECT id,
+                       ROW_NUMBER() OVER (ORDER BY created_at ASC) - 1 AS depth
+                FROM paginated
+            ),
+            siblings AS (
+                SELECT
+                    nb.id AS branch_message_id,
+                    nb.depth AS branch_depth,
+                    s.id, s.thread_id, s.user_id, s.parent_message_id, s.message_type,
+                    s.content, s.tool_call_id, s.tool_calls, s.additional_kwargs,
+                    s.created_at, s.updated_at,
+                    ROW_NUMBER() OVER (
+                        PARTITION BY nb.id ORDER BY s.created_at, s.id
+                    ) - 1 AS sibling_index
+                FROM numbered_branch nb
+                JOIN paginated p ON p.id = nb.id
+                JOIN messages s ON s.thread_id = $1 AND s.user_id = $2
+                    AND (
+                        (p.parent_message_id IS NOT NULL AND s.parent_message_id = p.parent_message_id)
+                        OR (p.parent_message_id IS NULL AND s.parent_message_id IS NULL)
+                    )
+            )
+            SELECT branch_message_id, branch_depth::int4,
+                   id, thread_id, user_id, parent_message_id, message_type,
+                   content, tool_call_id, tool_calls, additional_kwargs,
+                   created_at, updated_at,
+                   sibling_index
+            FROM siblings
+            ORDER BY branch_depth ASC, sibling_index ASC
+            "#,
+            order = params.order()
+        );
+
+        let rows = sqlx::query_as::<_, crate::types::BranchMessageRow>(&query)
+            .bind(thread_id)
+            .bind(user_id)
+            .bind(params.limit())
+            .bind(params.offset())
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows)
+    }
+
+    #[builder]
+    pub async fn try_claim_webhook_event<'e, E>(
+        &self,
+        executor: E,
+        event_id: &str,
+        event_type: &str,
+    ) -> DbResult<bool>
+    where
+        E: sqlx::Executor<'e, Database = sqlx::Postgres>,
+    {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO stripe.webhook_events (event_id, event_type)
+            VALUES ($1, $2)
+            ON CONFLICT (event_id) DO NOTHING
+            "#,
+        )
+        .bind(event_id)
+        .bind(event_type)
+        .execute(executor)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    #[builder]
+    pub async fn upsert_stripe_customer<'e, E>(
+        &self,
+        executor: E,
+        customer_id: &str,
+        app_user_id: Option<Uuid>,
+        email: Option<&str>,
+        raw_data: &serde_json::Value,
+    ) -> DbResult<()>
+    where
+        E: sqlx::Executor<'e, Database = sqlx::Postgres>,
+    {
+        let now = Utc::now();
+
+        sqlx::query(
+            r#"
+            INSERT INTO stripe.customers (id, app_user_id, email, created_at, updated_at, raw_data)
+            VALUES ($1, $2, $3, $4, $4, $5)
+            ON CONFLICT (id) DO UPDATE
+            SET email = COALESCE(EXCLUDED.email, stripe.customers.email),
+                app_user_id = COALESCE(EXCLUDED.app_user_id, stripe.customers.app_user_id),
+                raw_data = EXCLUDED.raw_data,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(customer_id)
+        .bind(app_user_id)
+        .bind(email)
+        .bind(now)
+        .bind(raw_data)
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    #[builder]
+    pub async fn link_stripe_customer_to_user<'e, E>(
+        &self,
+        executor: E,
+        user_id: Uuid,
+        stripe_customer_id: &str,
+    ) -> DbResult<()>
+    where
+        E: sqlx::Executor<'e, Database = sqlx::Postgres>,
+    {
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET stripe_customer_id = $2
+            WHERE id = $1
+            "#,
+        )
+        .bind(user_id)
+        .bind(stripe_customer_id)
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    #[builder]
+    pub async fn get_stripe_customer_id_for_user(&self, user_id: Uuid) -> DbResult<Option<String>> {
+        let result: Option<Option<String>> =
+            sqlx::query_scalar("SELECT stripe_customer_id FROM users WHERE id = $1")
+                .bind(user_id)
+                .fetch_optional(&self.pool)
+                .await?;
+        Ok(result.flatten())
+    }
+
+    #[builder]
+    pub async fn enqueue_stripe_customer_provisioning<'e, E>(
+        &self,
+        executor: E,
+        user_id: Uuid,
+    ) -> DbResult<()>
+    where
+        E: sqlx::Executor<'e, Database = sqlx::Postgres>,
+    {
+        sqlx::query(
+            r#"
+            INSERT INTO stripe.customer_provisioning_jobs (user_id)
+            VALUES ($1)
+            ON CONFLICT (user_id) DO NOTHING
+            "#,
+        )
+        .bind(user_id)
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    /// Atomically claim up to `limit` due provisioning jobs, joining on
+    /// `users` so the drainer doesn't need a second round trip for the email.
+    ///
+    /// `FOR UPDATE OF j SKIP LOCKED` locks only the job row, not the user row,
+    /// and lets multiple drainer workers run safely in parallel. Selected
+    /// rows have `next_attempt_at` pushed into the future to act as a lease —
+    /// if the worker crashes mid-provision the row becomes claimable again
+    /// after the lease expires. Dead-lettered rows are skipped.
+    #[builder]
+    pub async fn claim_due_provisioning_jobs(
+        &self,
+        limit: i64,
+        lease: chrono::Duration,
+    ) -> DbResult<Vec<ClaimedProvisioningJob>> {
+        let lease_until = Utc::now() + lease;
+        let jobs = sqlx::query_as::<_, ClaimedProvisioningJob>(
+            r#"
+            WITH due AS (
+                SELECT j.user_id, u.email
+                FROM stripe.customer_provisioning_jobs j
+                INNER JOIN users u ON u.id = j.user_id
+                WHERE j.dead_letter = FALSE
+                  AND j.next_attempt_at <= now()
+                ORDER BY j.next_attempt_at
+                LIMIT $1
+                FOR UPDATE OF j SKIP LOCKED
+            )
+            UPDATE stripe.customer_provisioning_jobs AS j
+            SET next_attempt_at = $2,
+                updated_at = now()
+            FROM due
+            WHERE j.user_id = due.user_id
+            RETURNING j.user_id, due.email, j.attempts
+            "#,
+        )
+        .bind(limit)
+        .bind(lease_until)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(jobs)
+    }
+
+    /// Delete a provisioning job. Intended to be called inside the same
+    /// transaction as the link write so that "customer linked" and "job
+    /// removed" are atomic.
+    #[builder]
+    pub async fn delete_provisioning_job<'e, E>(&self, executor: E, user_id: Uuid) -> DbResult<()>
+    where
+        E: sqlx::Executor<'e, Database = sqlx::Postgres>,
+    {
+        sqlx::query("DELETE FROM stripe.customer_provisioning_jobs WHERE user_id = $1")
+            .bind(user_id)
+            .execute(executor)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn fail_stripe_customer_provisioning(
+        &self,
+        user_id: Uuid,
+        error: &str,
+        retry_after: chrono::Duration,
+    ) -> DbResult<()> {
+        let next_attempt_at = Utc::now() + retry_after;
+        sqlx::query(
+            r#"
+            UPDATE stripe.customer_provisioning_jobs
+            SET attempts = attempts + 1,
+                next_attempt_at = $2,
+                last_error = $3,
+                updated_at = now()
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .bind(next_attempt_at)
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Mark a provisioning job as dead — it will not be retried. The drainer
+    /// uses this for permanent (4xx, non-rate-limit) failures from Stripe.
+    pub async fn dead_letter_stripe_customer_provisioning(
+        &self,
+        user_id: Uuid,
+        error: &str,
+    ) -> DbResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE stripe.customer_provisioning_jobs
+            SET dead_letter = TRUE,
+                attempts = attempts + 1,
+                last_error = $2,
+                updated_at = now()
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    #[builder]
+    pub async fn upsert_stripe_subscription<'e, E>(
+        &self,
+        executor: E,
+        subscription_id: &str,
+        customer_id: &str,
+        status: &str,
+        cancel_at_period_end: bool,
+        canceled_at: Option<i64>,
+        current_period_start: i64,
+        current_period_end: i64,
+        raw_data: &serde_json::Value,
+    ) -> DbResult<()>
+    where
+        E: sqlx::Executor<'e, Database = sqlx::Postgres>,
+    {
+        let now = Utc::now();
+        let canceled_at_ts = canceled_at.and_then(|ts| {
+            let dt = chrono::DateTime::from_timestamp(ts, 0);
+            if dt.is_none() {
+                tracing::warn!(ts, "malformed canceled_at timestamp in stripe subscription");
+            }
+            dt
+        });
+        let period_start = chrono::DateTime::from_timestamp(current_period_start, 0)
+            .unwrap_or_else(|| {
+                tracing::warn!(current_period_start, "malformed current_period_start timestamp in stripe subscription, falling back to now");
+                now
+            });
+        let period_end = chrono::DateTime::from_timestamp(current_period_end, 0).unwrap_or_else(
+            || {
+                tracing::warn!(current_period_end, "malformed current_period_end timestamp in stripe subscription, falling back to now");
+                now
+            },
+        );
+
+        sqlx::query(
+            r#"
+            INSERT INTO stripe.subscriptions (
+                id, customer_id, status,
+                cancel_at_period_end, canceled_at,
+                current_period_start, current_period_end,
+                created_at, updated_at, raw_data
+            )
+            VALUES ($1, $2, $3::stripe.subscription_status, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT (id) DO UPDATE
+            SET status = $3::stripe.subscription_status,
+                customer_id = EXCLUDED.customer_id,
+                cancel_at_period_end = EXCLUDED.cancel_at_period_end,
+                canceled_at = EXCLUDED.canceled_at,
+                current_period_start = EXCLUDED.current_period_start,
+                current_period_end = EXCLUDED.current_period_end,
+                raw_data = EXCLUDED.raw_data,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(subscription_id)
+        .bind(customer_id)
+        .bind(status)
+        .bind(cancel_at_period_end)
+        .bind(canceled_at_ts)
+        .bind(period_start)
+        .bind(period_end)
+        .bind(now)
+        .bind(now)
+        .bind(raw_data)
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    #[builder]
+    pub async fn upsert_stripe_price<'e, E>(
+        &self,
+        executor: E,
+        price_id: &str,
+        currency: &str,
+        unit_amount: Option<i64>,
+        recurring_interval: Option<&str>,
+        active: bool,
+        raw_data: &serde_json::Value,
+    ) -> DbResult<()>
+    where
+        E: sqlx::Executor<'e, Database = sqlx::Postgres>,
+    {
+        let now = Utc::now();
+
+        sqlx::query(
+            r#"
+            INSERT INTO stripe.prices (id, currency, unit_amount, recurring_interval, active, created_at, updated_at, raw_data)
+            VALUES ($1, $2, $3, $4, $5, $6, $6, $7)
+            ON CONFLICT (id) DO UPDATE
+            SET currency = EXCLUDED.currency,
+                unit_amount = EXCLUDED.unit_amount,
+                recurring_interval = EXCLUDED.recurring_interval,
+                active = EXCLUDED.active,
+                raw_data = EXCLUDED.raw_data,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(price_id)
+        .bind(currency)
+        .bind(unit_amount)
+        .bind(recurring_interval)
+        .bind(active)
+        .bind(now)
+        .bind(raw_data)
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    #[builder]
+    pub async fn ensure_plan_price<'e, E>(
+        &self,
+        executor: E,
+        plan_id: &str,
+        stripe_price_id: &str,
+    ) -> DbResult<()>
+    where
+        E: sqlx::Executor<'e, Database = sqlx::Postgres>,
+    {
+        sqlx::query(
+            "INSERT INTO plan_prices (plan_id, stripe_price_id) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+        )
+        .bind(plan_id)
+        .bind(stripe_price_id)
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    #[builder]
+    pub async fn sync_stripe_subscription_items<'e, E>(
+        &self,
+        executor: E,
+        subscription_id: &str,
+        items: &[(String, String, Option<i64>, serde_json::Value)],
+    ) -> DbResult<()>
+    where
+        E: sqlx::Executor<'e, Database = sqlx::Postgres>,
+    {
+        if items.is_empty() {
+            sqlx::query("DELETE FROM stripe.subscription_items WHERE subscription_id = $1")
+                .bind(subscription_id)
+                .execute(executor)
+                .await?;
+            return Ok(());
+        }
+
+        let mut query = String::from(
+            "WITH deleted AS (DELETE FROM stripe.subscription_items WHERE subscription_id = $1) INSERT INTO stripe.subscription_items (id, subscription_id, price_id, quantity, raw_data) VALUES ",
+        );
+
+        let mut param_idx = 2u32;
+        for (i, _) in items.iter().enumerate() {
+            if i > 0 {
+                query.push_str(", ");
+            }
+            query.push_str(&format!(
+                "(${}, $1, ${}, ${}, ${})",
+                param_idx,
+                param_idx + 1,
+                param_idx + 2,
+                param_idx + 3,
+            ));
+            param_idx += 4;
+        }
+        query.push_str(" ON CONFLICT (id) DO UPDATE SET price_id = EXCLUDED.price_id, quantity = EXCLUDED.quantity, raw_data = EXCLUDED.raw_data");
+
+        let mut q = sqlx::query(&query).bind(subscription_id);
+        for (item_id, price_id, quantity, raw_data) in items {
+            q = q
+                .bind(item_id)
+                .bind(price_id)
+                .bind(quantity.map(|v| v as i32))
+                .bind(raw_data);
+        }
+
+        q.execute(executor).await?;
+
+        Ok(())
+    }
+
+    #[builder]
+    pub async fn update_stripe_subscription_status(
+        &self,
+        subscription_id: &str,
+        status: &str,
+        cancel_at_period_end: bool,
+        canceled_at: Option<i64>,
+        raw_data: &serde_json::Value,
+    ) -> DbResult<()> {
+        let now = Utc::now();
+        let canceled_at_ts = canceled_at.and_then(|ts| chrono::DateTime::from_timestamp(ts, 0));
+
+        let result = sqlx::query(
+            r#"
+            UPDATE stripe.subscriptions
+            SET status = $2::stripe.subscription_status,
+                cancel_at_period_end = $3,
+                canceled_at = $4,
+                raw_data = $5,
+                updated_at = $6
+            WHERE id = $1
+            "#,
+        )
+        .bind(subscription_id)
+        .bind(status)
+        .bind(cancel_at_period_end)
+        .bind(canceled_at_ts)
+        .bind(raw_data)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(DbError::not_found_with_id("subscription", subscription_id));
+        }
+
+        Ok(())
+    }
+
+    #[builder]
+    pub async fn update_stripe_subscription_status_with_executor<'e, E>(
+        &self,
+        executor: E,
+        subscription_id: &str,
+        status: &str,
+        cancel_at_period_end: bool,
+        canceled_at: Option<i64>,
+        raw_data: &serde_json::Value,
+    ) -> DbResult<()>
+    where
+        E: sqlx::Executor<'e, Database = sqlx::Postgres>,
+    {
+        let now = Utc::now();
+        let canceled_at_ts = canceled_at.and_then(|ts| chrono::DateTime::from_timestamp(ts, 0));
+
+        let result = sqlx::query(
+            r#"
+            UPDATE stripe.subscriptions
+            SET status = $2::stripe.subscription_status,
+                cancel_at_period_end = $3,
+                canceled_at = $4,
+                raw_data = $5,
+                updated_at = $6
+            WHERE id = $1
+            "#,
+        )
+        .bind(subscription_id)
+        .bind(status)
+        .bind(cancel_at_period_end)
+        .bind(canceled_at_ts)
+        .bind(raw_data)
+        .bind(now)
+        .execute(executor)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(DbError::not_found_with_id("subscription", subscription_id));
+        }
+
+        Ok(())
+    }
+
+    #[builder]
+    pub async fn ensure_user_plan<'e, E>(
+        &self,
+        executor: E,
+        user_id: Uuid,
+        plan_id: &str,
+    ) -> DbResult<()>
+    where
+        E: sqlx::Executor<'e, Database = sqlx::Postgres>,
+    {
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET plan_id = CASE
+                    WHEN (SELECT rank FROM (VALUES ('free',0),('tier1',1)) AS r(id,rank) WHERE r.id = $2)
+                       > (SELECT rank FROM (VALUES ('free',0),('tier1',1)) AS r(id,rank) WHERE r.id = users.plan_id)
+                    THEN $2
+                    ELSE users.plan_id
+                END
+            WHERE id = $1
+            "#,
+        )
+        .bind(user_id)
+        .bind(plan_id)
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    #[builder]
+    pub async fn get_plan_id_for_user(&self, user_id: Uuid) -> DbResult<Option<String>> {
+        let result: Option<String> = sqlx::query_scalar("SELECT plan_id FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(result)
+    }
+
+    #[builder]
+    pub async fn update_plan_by_stripe_customer<'e, E>(
+        &self,
+        executor: E,
+        stripe_customer_id: &str,
+        plan_id: &str,
+    ) -> DbResult<()>
+    where
+        E: sqlx::Executor<'e, Database = sqlx::Postgres>,
+    {
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET plan_id = $2
+            WHERE stripe_customer_id = $1
+            "#,
+        )
+        .bind(stripe_customer_id)
+        .bind(plan_id)
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    #[builder]
+    pub async fn resolve_plan_for_stripe_price<'e, E>(
+        &self,
+        executor: E,
+        price_id: &str,
+    ) -> DbResult<Option<String>>
+    where
+        E: sqlx::Executor<'e, Database = sqlx::Postgres>,
+    {
+        let result: Option<String> =
+            sqlx::query_scalar("SELECT plan_id FROM plan_prices WHERE stripe_price_id = $1")
+                .bind(price_id)
+                .fetch_optional(executor)
+                .await?;
+        Ok(result)
+    }
+
+    #[builder]
+    pub async fn record_token_usage(
+        &self,
+        user_id: Uuid,
+        thread_id: Uuid,
+        message_id: Uuid,
+        input_tokens: i64,
+        output_tokens: i64,
+        reasoning_tokens: Option<i64>,
+        cache_creation_tokens: Option<i64>,
+        cache_read_tokens: Option<i64>,
+    ) -> DbResult<TokenUsage> {
+        let record = sqlx::query_as::<_, TokenUsage>(
+            r#"
+            INSERT INTO token_usage (
+                user_id, thread_id, message_id,
+                input_tokens, output_tokens, reasoning_tokens,
+                cache_creation_tokens, cache_read_tokens
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id, user_id, thread_id, message_id,
+                      input_tokens, output_tokens, reasoning_tokens,
+                      cache_creation_tokens, cache_read_tokens, created_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(thread_id)
+        .bind(message_id)
+        .bind(input_tokens)
+        .bind(output_tokens)
+        .bind(reasoning_tokens.unwrap_or(0))
+        .bind(cache_creation_tokens.unwrap_or(0))
+        .bind(cache_read_tokens.unwrap_or(0))
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(record)
+    }
+
+    #[builder]
+    pub async fn get_token_limit_and_usage(
+        &self,
+        user_id: Uuid,
+        year_month: i32,
+    ) -> DbResult<(i64, i64)> {
+        let row: Option<(i64, Option<i64>)> = sqlx::query_as(
+            r#"
+            SELECT p.monthly_token_limit,
+                   mtt.total_tokens
+            FROM users u
+            JOIN plans p ON p.id = u.plan_id
+            LEFT JOIN monthly_token_totals mtt
+                   ON mtt.user_id = u.id
+                  AND mtt.year_month = $2
+            WHERE u.id = $1
+            "#,
+        )
+        .bind(user_id)
+        .bind(year_month)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some((limit, used)) => Ok((limit, used.unwrap_or(0))),
+            None => Ok((DEFAULT_TOKEN_LIMIT, 0)),
+        }
+    }
+
+    #[builder]
+    pub async fn consume_login_token_and_create_refresh_token(
+        &self,
+        login_token_hash: &[u8],
+        user_id: Uuid,
+        refresh_token_hash: Vec<u8>,
+        refresh_token_expires_at: DateTime<Utc>,
+    ) -> DbResult<()> {
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            UPDATE login_tokens
+            SET consumed = true, updated_at = $2
+            WHERE token_hash = $1 AND consumed = false AND expires_at > now()
+            "#,
+        )
+        .bind(login_token_hash)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO refresh_tokens (id, user_id, token_hash, expires_at, revoked, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(Uuid::now_v7())
+        .bind(user_id)
+        .bind(&refresh_token_hash)
+        .bind(refresh_token_expires_at)
+        .bind(false)
+        .bind(now)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    pub async fn search_messages(
+        &self,
+        user_id: Uuid,
+        query: &str,
+        limit: i64,
+        offset: i64,
+    ) -> DbResult<Vec<SearchResultMessage>> {
+        let tsquery = match build_prefix_tsquery(query) {
+            Some(q) => q,
+            None => return Ok(vec![]),
+        };
+        let ilike_pattern = format!("%{}%", query.replace('%', r"\%").replace('_', r"\_"));
+        let regex_pattern = regex_escape(query);
+        let results = self
+            .read(|pool| {
+                sqlx::query_as::<_, SearchResultMessage>(
+                    r#"
+                    WITH matched AS (
+                        SELECT id, thread_id, message_type,
+                               extract_content_text(content) AS content_text,
+                               search_tsv @@ to_tsquery('english', $1) AS fts_match,
+                               CASE WHEN search_tsv @@ to_tsquery('english', $1)
+                                    THEN ts_rank(search_tsv, to_tsquery('english', $1))
+                                    ELSE 0.0
+                               END AS rank,
+                               created_at
+                        FROM messages
+                        WHERE user_id = $2
+                          AND (search_tsv @@ to_tsquery('english', $1) OR extract_content_text(content) ILIKE $5)
+                        ORDER BY rank DESC, created_at DESC
+                        LIMIT $3 OFFSET $4
+                    )
+                    SELECT id, thread_id, message_type,
+                           regexp_replace(
+                               substring(content_text from GREATEST(1, position(lower($7) in lower(content_text)) - 80) for 200),
+                               $6, '<mark>\&</mark>', 'gi'
+                           ) AS snippet,
+                           rank, created_at
+                    FROM matched
+                    "#,
+                )
+                .bind(&tsquery)       // $1
+                .bind(user_id)        // $2
+                .bind(limit)          // $3
+                .bind(offset)         // $4
+                .bind(&ilike_pattern) // $5
+                .bind(&regex_pattern) // $6
+                .bind(query)          // $7
+                .fetch_all(pool)
+            })
+            .await?;
+
+        Ok(results)
+    }
+
+    pub async fn search_threads(
+        &self,
+        user_id: Uuid,
+        query: &str,
+        limit: i64,
+        offset: i64,
+    ) -> DbResult<Vec<SearchResultThread>> {
+        let tsquery = match build_prefix_tsquery(query) {
+            Some(q) => q,
+            None => return Ok(vec![]),
+        };
+        let ilike_pattern = format!("%{}%", query.replace('%', r"\%").replace('_', r"\_"));
+        let results = self
+            .read(|pool| {
+                sqlx::query_as::<_, SearchResultThread>(
+                    r#"
+                    SELECT id, title,
+                           GREATEST(
+                               CASE WHEN to_tsvector('english', immutable_unaccent(coalesce(title, ''))) @@ to_tsquery('english', $1)
+                                    THEN ts_rank(to_tsvector('english', immutable_unaccent(coalesce(title, ''))), to_tsquery('english', $1))
+                                    ELSE 0.0
+                               END,
+                               CASE WHEN similarity(coalesce(title, ''), $5) > 0.1
+                                    THEN similarity(coalesce(title, ''), $5)
+                                    ELSE 0.0
+                               END
+                           ) AS rank,
+                           updated_at
+                    FROM threads
+                    WHERE user_id = $2
+                      AND (
+                          to_tsvector('english', immutable_unaccent(coalesce(title, ''))) @@ to_tsquery('english', $1)
+                          OR coalesce(title, '') ILIKE $6
+                      )
+                    ORDER BY rank DESC, updated_at DESC
+                    LIMIT $3 OFFSET $4
+                    "#,
+                )
+                .bind(&tsquery)
+                .bind(user_id)
+                .bind(limit)
+                .bind(offset)
+                .bind(query)
+                .bind(&ilike_pattern)
+                .fetch_all(pool)
+            })
+            .await?;
+
+        Ok(results)
+    }
+
+    // --- user_settings ----------------------------------------------------
+
+    #[builder]
+    pub async fn get_user_settings(&self, user_id: Uuid) -> DbResult<Option<UserSettingsRow>> {
+        let row = sqlx::query_as::<_, UserSettingsRow>(
+            r#"
+            SELECT user_id, schema_version, settings, created_at, updated_at
+            FROM user_settings
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
+    /// Insert or update the user's settings blob with optimistic concurrency.
+    ///
+    /// Contract over `(current_row, base_updated_at)`:
+    ///
+    /// | current_row    | base_updated_at                          | outcome                         |
+    /// |----------------|------------------------------------------|---------------------------------|
+    /// | `None`         | `None`                                   | `Inserted` (fresh first run)    |
+    /// | `None`         | `Some(_)`                                | `Inserted` (recovery insert)    |
+    /// | `Some(row)`    | `None`                                   | `Conflict { current: row }`     |
+    /// | `Some(row)`    | `Some(t)` where `t != row.updated_at`    | `Conflict { current: row }`     |
+    /// | `Some(_)`      | `Some(t)` where `t == row.updated_at`    | `Updated`                       |
+    ///
+    /// The `(None, Some(_))` case is a recovery path: the client thought it
+    /// was updating a row that has since been deleted server-side. Rather
+    /// than fail, we insert and return the fresh row.
+    ///
+    /// Locking: a single transaction wraps `SELECT ... FOR UPDATE` and the
+    /// subsequent write, so two concurrent callers serialize cleanly.
+    #[builder]
+    pub async fn upsert_user_settings(
+        &self,
+        user_id: Uuid,
+        schema_version: i32,
+        settings: serde_json::Value,
+        base_updated_at: Option<DateTime<Utc>>,
+    ) -> DbResult<UpsertOutcome> {
+        let mut tx = self.pool.begin().await?;
+
+        let current = sqlx::query_as::<_, UserSettingsRow>(
+            r#"
+            SELECT user_id, schema_version, settings, created_at, updated_at
+            FROM user_settings
+            WHERE user_id = $1
+            FOR UPDATE
+            "#,
+        )
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let outcome = match (current, base_updated_at) {
+            (Some(row), None) => UpsertOutcome::Conflict { current: row },
+            (Some(row), Some(base)) if row.updated_at != base => {
+                UpsertOutcome::Conflict { current: row }
+            }
+            (Some(_), Some(_)) => {
+                let updated = sqlx::query_as::<_, UserSettingsRow>(
+                    r#"
+                    UPDATE user_settings
+                    SET schema_version = $2, settings = $3
+                    WHERE user_id = $1
+                    RETURNING user_id, schema_version, settings, created_at, updated_at
+                    "#,
+                )
+                .bind(user_id)
+                .bind(schema_version)
+                .bind(&settings)
+                .fetch_one(&mut *tx)
+                .await?;
+                UpsertOutcome::Updated(updated)
+            }
+            (None, _) => {
+                let inserted = sqlx::query_as::<_, UserSettingsRow>(
+                    r#"
+                    INSERT INTO user_settings (user_id, schema_version, settings)
+                    VALUES ($1, $2, $3)
+                    RETURNING user_id, schema_version, settings, created_at, updated_at
+                    "#,
+                )
+                .bind(user_id)
+                .bind(schema_version)
+                .bind(&settings)
+                .fetch_one(&mut *tx)
+                .await?;
+                UpsertOutcome::Inserted(inserted)
+            }
+        };
+
+        tx.commit().await?;
+        Ok(outcome)
+    }
+
+    #[builder]
+    pub async fn delete_user_settings(&self, user_id: Uuid) -> DbResult<()> {
+        sqlx::query(r#"DELETE FROM user_settings WHERE user_id = $1"#)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    // --- memories ---------------------------------------------------------
+
+    /// Store facts learned in `thread_id` (or added by hand, with `None`).
+    /// Facts the user already has, ignoring case, are skipped; the rows
+    /// actually inserted are returned.
+    #[builder]
+    pub async fn create_memories(
+        &self,
+        user_id: Uuid,
+        thread_id: Option<Uuid>,
+        contents: Vec<String>,
+    ) -> DbResult<Vec<UserMemory>> {
+        let ids: Vec<Uuid> = contents.iter().map(|_| Uuid::now_v7()).collect();
+
+        let memories = sqlx::query_as::<_, UserMemory>(
+            r#"
+            INSERT INTO user_memories (id, user_id, thread_id, content)
+            SELECT m.id, $3, $4, m.content
+            FROM UNNEST($1::uuid[], $2::text[]) AS m(id, content)
+            ON CONFLICT (user_id, lower(content)) DO NOTHING
+            RETURNING id, user_id, thread_id, content, created_at, updated_at
+            "#,
+        )
+        .bind(&ids)
+        .bind(&contents)
+        .bind(user_id)
+        .bind(thread_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(memories)
+    }
+
+    /// Add one fact by hand. Fails with [`DbError::UniqueViolation`] when
+    /// the user already has it.
+    #[builder]
+    pub async fn create_memory(&self, user_id: Uuid, content: &str) -> DbResult<UserMemory> {
+        let memory = sqlx::query_as::<_, UserMemory>(
+            r#"
+            INSERT INTO user_memories (id, user_id, content)
+            VALUES ($1, $2, $3)
+            RETURNING id, user_id, thread_id, content, created_at, updated_at
+            "#,
+        )
+        .bind(Uuid::now_v7())
+        .bind(user_id)
+        .bind(content)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(memory)
+    }
+
+    /// Every fact the user has, most recently written first.
+    #[builder]
+    pub async fn list_memories(&self, user_id: Uuid) -> DbResult<Vec<UserMemory>> {
+        let memories = self
+            .read(|pool| {
+                sqlx::query_as::<_, UserMemory>(
+                    r#"
+                    SELECT id, user_id, thread_id, content, created_at, updated_at
+                    FROM user_memories
+                    WHERE user_id = $1
+                    ORDER BY updated_at DESC, id DESC
+                    "#,
+                )
+                .bind(user_id)
+                .fetch_all(pool)
+            })
+            .await?;
+
+        Ok(memories)
+    }
+
+    #[builder]
+    pub async fn count_memories(&self, user_id: Uuid) -> DbResult<i64> {
+        let count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM user_memories WHERE user_id = $1")
+                .bind(user_id)
+                .fetch_one(&self.pool)
+                .await?;
+
+        Ok(count)
+    }
+
+    /// Reword a fact. Fails with [`DbError::UniqueViolation`] when the new
+    /// wording matches another fact the user has.
+    #[builder]
+    pub async fn update_memory(
+        &self,
+        id: Uuid,
+        user_id: Uuid,
+        content: &str,
+    ) -> DbResult<UserMemory> {
+        sqlx::query_as::<_, UserMemory>(
+            r#"
+            UPDATE user_memories
+            SET content = $3, updated_at = now()
+            WHERE id = $1 AND user_id = $2
+            RETURNING id, user_id, thread_id, content, created_at, updated_at
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(content)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| DbError::NotFound {
+            entity: "memory",
+            id: Some(id.to_string()),
+        })
+    }
+
+    #[builder]
+    pub async fn delete_memory(&self, id: Uuid, user_id: Uuid) -> DbResult<()> {
+        let result = sqlx::query("DELETE FROM user_memories WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(DbError::NotFound {
+                entity: "memory",
+                id: Some(id.to_string()),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Forget everything. Returns how many facts were deleted.
+    #[builder]
+    pub async fn delete_all_memories(&self, user_id: Uuid) -> DbResult<u64> {
+        let result = sqlx::query("DELETE FROM user_memories WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    // --- automations ------------------------------------------------------
+
+    #[builder]
+    pub async fn create_automation(
+        &self,
+        id: Option<Uuid>,
+        user_id: Uuid,
+        name: String,
+        prompt: String,
+        schedule: String,
+        tools: Vec<String>,
+        enabled: bool,
+        next_run_at: Option<DateTime<Utc>>,
+    ) -> DbResult<Automation> {
+        let id = id.unwrap_or_else(Uuid::now_v7);
+
+        let automation = sqlx::query_as::<_, Automation>(
+            r#"
+            INSERT INTO automations (id, user_id, name, prompt, schedule, tools, enabled, next_run_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id, user_id, name, prompt, schedule, tools, enabled,
+                      next_run_at, last_run_at, created_at, updated_at
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(&name)
+        .bind(&prompt)
+        .bind(&schedule)
+        .bind(&tools)
+        .bind(enabled)
+        .bind(next_run_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(automation)
+    }
+
+    #[builder]
+    pub async fn list_automations(&self, user_id: Uuid) -> DbResult<Vec<Automation>> {
+        let automations = sqlx::query_as::<_, Automation>(
+            r#"
+            SELECT id, user_id, name, prompt, schedule, tools, enabled,
+                   next_run_at, last_run_at, created_at, updated_at
+            FROM automations
+            WHERE user_id = $1
+            ORDER BY created_at
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(automations)
+    }
+
+    #[builder]
+    pub async fn get_automation(&self, id: Uuid, user_id: Uuid) -> DbResult<Automation> {
+        sqlx::query_as::<_, Automation>(
+            r#"
+            SELECT id, user_id, name, prompt, schedule, tools, enabled,
+                   next_run_at, last_run_at, created_at, updated_at
+            FROM automations
+            WHERE id = $1 AND user_id = $2
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| DbError::NotFound {
+            entity: "automation",
+            id: Some(id.to_string()),
+        })
+    }
+
+    /// Overwrite every user-editable field of an automation. The caller
+    /// merges a partial edit onto the current row and recomputes
+    /// `next_run_at` from the (possibly new) schedule.
+    #[builder]
+    pub async fn update_automation(
+        &self,
+        id: Uuid,
+        user_id: Uuid,
+        name: String,
+        prompt: String,
+        schedule: String,
+        tools: Vec<String>,
+        enabled: bool,
+        next_run_at: Option<DateTime<Utc>>,
+    ) -> DbResult<Automation> {
+        sqlx::query_as::<_, Automation>(
+            r#"
+            UPDATE automations
+            SET name = $3, prompt = $4, schedule = $5, tools = $6, enabled = $7, next_run_at = $8
+            WHERE id = $1 AND user_id = $2
+            RETURNING id, user_id, name, prompt, schedule, tools, enabled,
+                      next_run_at, last_run_at, created_at, updated_at
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(&name)
+        .bind(&prompt)
+        .bind(&schedule)
+        .bind(&tools)
+        .bind(enabled)
+        .bind(next_run_at)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| DbError::NotFound {
+            entity: "automation",
+            id: Some(id.to_string()),
+        })
+    }
+
+    #[builder]
+    pub async fn delete_automation(&self, id: Uuid, user_id: Uuid) -> DbResult<()> {
+        let result = sqlx::query("DELETE FROM automations WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(DbError::NotFound {
+                entity: "automation",
+                id: Some(id.to_string()),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Make an automation due immediately so the next scheduler tick picks
+    /// it up. The regular schedule resumes after that run.
+    #[builder]
+    pub async fn trigger_automation(&self, id: Uuid, user_id: Uuid) -> DbResult<Automation> {
+        sqlx::query_as::<_, Automation>(
+            r#"
+            UPDATE automations
+            SET next_run_at = now()
+            WHERE id = $1 AND user_id = $2
+            RETURNING id, user_id, name, prompt, schedule, tools, enabled,
+                      next_run_at, last_run_at, created_at, updated_at
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| DbError::NotFound {
+            entity: "automation",
+            id: Some(id.to_string()),
+        })
+    }
+
+    /// Atomically claim up to `limit` enabled automations whose
+    /// `next_run_at` has passed.
+    ///
+    /// Same lease scheme as [`Self::claim_due_provisioning_jobs`]: claimed
+    /// rows get `next_run_at` pushed to `now + lease`, so a worker that dies
+    /// mid-run leaves the automation claimable again once the lease lapses.
+    /// The returned row carries the lease in `automation.next_run_at`, which
+    /// [`Self::reschedule_automation`] uses to detect edits made mid-run.
+    #[builder]
+    pub async fn claim_due_automations(
+        &self,
+        limit: i64,
+        lease: chrono::Duration,
+    ) -> DbResult<Vec<ClaimedAutomation>> {
+        let lease_until = Utc::now() + lease;
+        let automations = sqlx::query_as::<_, ClaimedAutomation>(
+            r#"
+            WITH due AS (
+                SELECT id, next_run_at
+                FROM automations
+                WHERE enabled
+                  AND next_run_at <= now()
+                ORDER BY next_run_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            UPDATE automations AS a
+            SET next_run_at = $2
+            FROM due
+            WHERE a.id = due.id
+            RETURNING a.id, a.user_id, a.name, a.prompt, a.schedule, a.tools, a.enabled,
+                      a.next_run_at, a.last_run_at, a.created_at, a.updated_at,
+                      due.next_run_at AS scheduled_for
+            "#,
+        )
+        .bind(limit)
+        .bind(lease_until)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(automations)
+    }
+
+    /// Record that a claimed automation ran and release its lease.
+    ///
+    /// `next_run_at` is only replaced while it still holds `lease` — if the
+    /// user edited or re-triggered the automation during the run, the value
+    /// they set wins.
+    #[builder]
+    pub async fn reschedule_automation(
+        &self,
+        id: Uuid,
+        lease: DateTime<Utc>,
+        last_run_at: DateTime<Utc>,
+        next_run_at: Option<DateTime<Utc>>,
+    ) -> DbResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE automations
+            SET last_run_at = $3,
+                next_run_at = CASE WHEN next_run_at = $2 THEN $4 ELSE next_run_at END
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(lease)
+        .bind(last_run_at)
+        .bind(next_run_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    #[builder]
+    pub async fn create_automation_run(
+        &self,
+        id: Option<Uuid>,
+        automation_id: Uuid,
+        user_id: Uuid,
+        thread_id: Option<Uuid>,
+        scheduled_for: DateTime<Utc>,
+    ) -> DbResult<AutomationRun> {
+        let id = id.unwrap_or_else(Uuid::now_v7);
+
+        let run = sqlx::query_as::<_, AutomationRun>(
+            r#"
+            INSERT INTO automation_runs (id, automation_id, user_id, thread_id, scheduled_for)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, automation_id, user_id, thread_id, status, scheduled_for,
+                      started_at, finished_at, output, error
+            "#,
+        )
+        .bind(id)
+        .bind(automation_id)
+        .bind(user_id)
+        .bind(thread_id)
+        .bind(scheduled_for)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(run)
+    }
+
+    #[builder]
+    pub async fn finish_automation_run(
+        &self,
+        id: Uuid,
+        status: AutomationRunStatus,
+        output: Option<String>,
+        error: Option<String>,
+    ) -> DbResult<AutomationRun> {
+        let run = sqlx::query_as::<_, AutomationRun>(
+            r#"
+            UPDATE automation_runs
+            SET status = $2, output = $3, error = $4, finished_at = now()
+            WHERE id = $1
+            RETURNING id, automation_id, user_id, thread_id, status, scheduled_for,
+                      started_at, finished_at, output, error
+            "#,
+        )
+        .bind(id)
+        .bind(status)
+        .bind(&output)
+        .bind(&error)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(run)
+    }
+
+    /// Newest first. Scoped to the user so run history can't be read by
+    /// guessing an automation id.
+    #[builder]
+    pub async fn list_automation_runs(
+        &self,
+        automation_id: Uuid,
+        user_id: Uuid,
+        params: PaginationParams,
+    ) -> DbResult<Vec<AutomationRun>> {
+        let runs = self
+            .read(|pool| {
+                sqlx::query_as::<_, AutomationRun>(
+                    r#"
+                    SELECT id, automation_id, user_id, thread_id, status, scheduled_for,
+                           started_at, finished_at, output, error
+                    FROM automation_runs
+                    WHERE automation_id = $1 AND user_id = $2
+                    ORDER BY started_at DESC, id DESC
+                    LIMIT $3 OFFSET $4
+                    "#,
+                )
+                .bind(automation_id)
+                .bind(user_id)
+                .bind(params.limit())
+                .bind(params.offset())
+                .fetch_all(pool)
+            })
+            .await?;
+
+        Ok(runs)
+    }
+
+    /// Every run of the user's automations started at or after `since`,
+    /// oldest first, limited to `automation_ids` when given.
+    #[builder]
+    pub async fn list_automation_runs_since(
+        &self,
+        user_id: Uuid,
+        since: DateTime<Utc>,
+        automation_ids: Option<&[Uuid]>,
+    ) -> DbResult<Vec<NamedAutomationRun>> {
+        let runs = sqlx::query_as::<_, NamedAutomationRun>(
+            r#"
+            SELECT r.id, r.automation_id, r.user_id, r.thread_id, r.status, r.scheduled_for,
+                   r.started_at, r.finished_at, r.output, r.error, a.name AS automation_name
+            FROM automation_runs r
+            JOIN automations a ON a.id = r.automation_id
+            WHERE r.user_id = $1 AND r.started_at >= $2
+              AND ($3::uuid[] IS NULL OR r.automation_id = ANY($3))
+            ORDER BY r.started_at, r.id
+            "#,
+        )
+        .bind(user_id)
+        .bind(since)
+        .bind(automation_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(runs)
+    }
+
+    // --- workflows --------------------------------------------------------
+
+    #[builder]
+    pub async fn create_workflow(
+        &self,
+        id: Option<Uuid>,
+        user_id: Uuid,
+        name: String,
+        description: String,
+        definition: serde_json::Value,
+    ) -> DbResult<Workflow> {
+        let id = id.unwrap_or_else(Uuid::now_v7);
+
+        let workflow = sqlx::query_as::<_, Workflow>(
+            r#"
+            INSERT INTO workflows (id, user_id, name, description, definition)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, user_id, name, description, definition, created_at, updated_at
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(&name)
+        .bind(&description)
+        .bind(&definition)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(workflow)
+    }
+
+    #[builder]
+    pub async fn list_workflows(&self, user_id: Uuid) -> DbResult<Vec<Workflow>> {
+        let workflows = sqlx::query_as::<_, Workflow>(
+            r#"
+            SELECT id, user_id, name, description, definition, created_at, updated_at
+            FROM workflows
+            WHERE user_id = $1
+            ORDER BY created_at
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(workflows)
+    }
+
+    #[builder]
+    pub async fn get_workflow(&self, id: Uuid, user_id: Uuid) -> DbResult<Workflow> {
+        sqlx::query_as::<_, Workflow>(
+            r#"
+            SELECT id, user_id, name, description, definition, created_at, updated_at
+            FROM workflows
+            WHERE id = $1 AND user_id = $2
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| DbError::NotFound {
+            entity: "workflow",
+            id: Some(id.to_string()),
+        })
+    }
+
+    /// Replace a workflow's definition wholesale.
+    #[builder]
+    pub async fn update_workflow(
+        &self,
+        id: Uuid,
+        user_id: Uuid,
+        name: String,
+        description: String,
+        definition: serde_json::Value,
+    ) -> DbResult<Workflow> {
+        sqlx::query_as::<_, Workflow>(
+            r#"
+            UPDATE workflows
+            SET name = $3, description = $4, definition = $5
+            WHERE id = $1 AND user_id = $2
+            RETURNING id, user_id, name, description, definition, created_at, updated_at
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(&name)
+        .bind(&description)
+        .bind(&definition)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| DbError::NotFound {
+            entity: "workflow",
+            id: Some(id.to_string()),
+        })
+    }
+
+    #[builder]
+    pub async fn delete_workflow(&self, id: Uuid, user_id: Uuid) -> DbResult<()> {
+        let result = sqlx::query("DELETE FROM workflows WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(DbError::NotFound {
+                entity: "workflow",
+                id: Some(id.to_string()),
+            });
+        }
+
+        Ok(())
+    }
+
+    // --- data exports -----------------------------------------------------
+
+    /// Queue a data export. Fails with [`DbError::UniqueViolation`] while
+    /// the user already has one pending or running.
+    #[builder]
+    pub async fn create_data_export(
+        &self,
+        id: Option<Uuid>,
+        user_id: Uuid,
+        asset_mode: DataExportAssetMode,
+    ) -> DbResult<DataExport> {
+        let id = id.unwrap_or_else(Uuid::now_v7);
+
+        let export = sqlx::query_as::<_, DataExport>(
+            r#"
+            INSERT INTO data_exports (id, user_id, asset_mode)
+            VALUES ($1, $2, $3)
+            RETURNING id, user_id, status, asset_mode, storage_uri, size_bytes, error, attempts,
+                      lease_until, started_at, completed_at, expires_at, created_at, updated_at
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(asset_mode)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(export)
+    }
+
+    /// Newest first.
+    #[builder]
+    pub async fn list_data_exports(&self, user_id: Uuid) -> DbResult<Vec<DataExport>> {
+        let exports = sqlx::query_as::<_, DataExport>(
+            r#"
+            SELECT id, user_id, status, asset_mode, storage_uri, size_bytes, error, attempts,
+                   lease_until, started_at, completed_at, expires_at, created_at, updated_at
+            FROM data_exports
+            WHERE user_id = $1
+            ORDER BY created_at DESC, id DESC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(exports)
+    }
+
+    #[builder]
+    pub async fn get_data_export(&self, id: Uuid, user_id: Uuid) -> DbResult<DataExport> {
+        sqlx::query_as::<_, DataExport>(
+            r#"
+            SELECT id, user_id, status, asset_mode, storage_uri, size_bytes, error, attempts,
+                   lease_until, started_at, completed_at, expires_at, created_at, updated_at
+            FROM data_exports
+            WHERE id = $1 AND user_id = $2
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| DbError::NotFound {
+            entity: "data export",
+            id: Some(id.to_string()),
+        })
+    }
+
+    /// Delete an export row and return it, so the caller can remove the
+    /// archive it points at.
+    #[builder]
+    pub async fn delete_data_export(&self, id: Uuid, user_id: Uuid) -> DbResult<DataExport> {
+        sqlx::query_as::<_, DataExport>(
+            r#"
+            DELETE FROM data_exports
+            WHERE id = $1 AND user_id = $2
+            RETURNING id, user_id, status, asset_mode, storage_uri, size_bytes, error, attempts,
+                      lease_until, started_at, completed_at, expires_at, created_at, updated_at
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| DbError::NotFound {
+            entity: "data export",
+            id: Some(id.to_string()),
+        })
+    }
+
+    /// Atomically claim up to `limit` exports that are pending, or running
+    /// under a lease that has lapsed (the worker building them died).
+    ///
+    /// Claimed rows move to `running` with `lease_until = now + lease` and
+    /// `attempts` incremented; the returned rows carry the new values.
+    #[builder]
+    pub async fn claim_pending_data_exports(
+        &self,
+        limit: i64,
+        lease: chrono::Duration,
+    ) -> DbResult<Vec<DataExport>> {
+        let lease_until = Utc::now() + lease;
+        let exports = sqlx::query_as::<_, DataExport>(
+            r#"
+            WITH due AS (
+                SELECT id
+                FROM data_exports
+                WHERE status = 'pending'
+                   OR (status = 'running' AND lease_until <= now())
+                ORDER BY created_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            UPDATE data_exports AS e
+            SET status = 'running',
+                lease_until = $2,
+                attempts = e.attempts + 1,
+                started_at = COALESCE(e.started_at, now())
+            FROM due
+            WHERE e.id = due.id
+            RETURNING e.id, e.user_id, e.status, e.asset_mode, e.storage_uri, e.size_bytes,
+                      e.error, e.attempts, e.lease_until, e.started_at, e.completed_at,
+                      e.expires_at, e.created_at, e.updated_at
+            "#,
+        )
+        .bind(limit)
+        .bind(lease_until)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(exports)
+    }
+
+    /// Mark a running export ready for download until `expires_at`.
+    ///
+    /// Returns `NotFound` if the export is no longer running — the user
+    /// deleted it mid-build — so the caller knows to discard the archive.
+    #[builder]
+    pub async fn complete_data_export(
+        &self,
+        id: Uuid,
+        storage_uri: String,
+        size_bytes: i64,
+        expires_at: DateTime<Utc>,
+    ) -> DbResult<DataExport> {
+        sqlx::query_as::<_, DataExport>(
+            r#"
+            UPDATE data_exports
+            SET status = 'ready', storage_uri = $2, size_bytes = $3, expires_at = $4,
+                completed_at = now(), lease_until = NULL
+            WHERE id = $1 AND status = 'running'
+            RETURNING id, user_id, status, asset_mode, storage_uri, size_bytes, error, attempts,
+                      lease_until, started_at, completed_at, expires_at, created_at, updated_at
+            "#,
+        )
+        .bind(id)
+        .bind(&storage_uri)
+        .bind(size_bytes)
+        .bind(expires_at)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| DbError::NotFound {
+            entity: "data export",
+            id: Some(id.to_string()),
+        })
+    }
+
+    #[builder]
+    pub async fn fail_data_export(&self, id: Uuid, error: String) -> DbResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE data_exports
+            SET status = 'failed', error = $2, completed_at = now(), lease_until = NULL
+            WHERE id = $1 AND status = 'running'
+            "#,
+        )
+        .bind(id)
+        .bind(&error)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Move up to `limit` ready exports past their `expires_at` to
+    /// `expired` and return them, so the caller can delete their archives.
+    /// `storage_uri` is returned as it was and cleared on the row.
+    #[builder]
+    pub async fn expire_data_exports(&self, limit: i64) -> DbResult<Vec<DataExport>> {
+        let exports = sqlx::query_as::<_, DataExport>(
+            r#"
+            WITH due AS (
+                SELECT id, storage_uri
+                FROM data_exports
+                WHERE status = 'ready' AND expires_at <= now()
+                ORDER BY expires_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            UPDATE data_exports AS e
+            SET status = 'expired', storage_uri = NULL
+            FROM due
+            WHERE e.id = due.id
+            RETURNING e.id, e.user_id, e.status, e.asset_mode, due.storage_uri, e.size_bytes,
+                      e.error, e.attempts, e.lease_until, e.started_at, e.completed_at,
+                      e.expires_at, e.created_at, e.updated_at
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(exports)
+    }
+
+    // --- account deletions ------------------------------------------------
+
+    /// Schedule the user's account for deletion at `scheduled_for` and
+    /// revoke every refresh token and access token, in one transaction.
+    ///
+    /// Idempotent: if a deletion is already open for the user it is
+    /// returned unchanged (sessions are still revoked, in case one slipped
+    /// in). The `requested` audit event is only written for a new
+    /// deletion.
+    #[builder]
+    pub async fn schedule_account_deletion(
+        &self,
+        user_id: Uuid,
+        email: String,
+        scheduled_for: DateTime<Utc>,
+    ) -> DbResult<AccountDeletion> {
+        let mut tx = self.pool.begin().await?;
+
+        let inserted = sqlx::query_as::<_, AccountDeletion>(
+            r#"
+            INSERT INTO account_deletions (id, user_id, email, scheduled_for)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (user_id) WHERE status IN ('scheduled', 'running') DO NOTHING
+            RETURNING id, user_id, email, status, scheduled_for, attempts, lease_until, error,
+                      completed_at, created_at, updated_at
+            "#,
+        )
+        .bind(Uuid::now_v7())
+        .bind(user_id)
+        .bind(&email)
+        .bind(scheduled_for)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let revoked = sqlx::query(
+            r#"
+            UPDATE refresh_tokens
+            SET revoked = true, updated_at = now()
+            WHERE user_id = $1 AND revoked = false
+            "#,
+        )
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        self.bump_token_version()
+            .executor(&mut *tx)
+            .user_id(user_id)
+            .call()
+            .await?;
+
+        let deletion = match inserted {
+            Some(deletion) => {
+                sqlx::query(
+                    r#"
+                    INSERT INTO account_deletion_events (deletion_id, kind, detail)
+                    VALUES ($1, 'requested', $2)
+                    "#,
+                )
+                .bind(deletion.id)
+                .bind(serde_json::json!({
+                    "scheduled_for": scheduled_for,
+                    "refresh_tokens_revoked": revoked,
+                }))
+                .execute(&mut *tx)
+                .await?;
+                deletion
+            }
+            None => {
+                sqlx::query_as::<_, AccountDeletion>(
+                    r#"
+                    SELECT id, user_id, email, status, scheduled_for, attempts, lease_until, error,
+                           completed_at, created_at, updated_at
+                    FROM account_deletions
+                    WHERE user_id = $1 AND status IN ('scheduled', 'running')
+                    "#,
+                )
+                .bind(user_id)
+                .fetch_one(&mut *tx)
+                .await?
+            }
+        };
+
+        tx.commit().await?;
+        Ok(deletion)
+    }
+
+    /// The user's scheduled or running deletion, if any. Its existence is
+    /// what locks the account.
+    #[builder]
+    pub async fn get_open_account_deletion(
+        &self,
+        user_id: Uuid,
+    ) -> DbResult<Option<AccountDeletion>> {
+        let deletion = sqlx::query_as::<_, AccountDeletion>(
+            r#"
+            SELECT id, user_id, email, status, scheduled_for, attempts, lease_until, error,
+                   completed_at, created_at, updated_at
+            FROM account_deletions
+            WHERE user_id = $1 AND status IN ('scheduled', 'running')
+            "#,
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(deletion)
+    }
+
+    /// Atomically claim up to `limit` deletions whose grace period is over,
+    /// or that are running under a lease that has lapsed (the last attempt
+    /// died or failed).
+    ///
+    /// Claimed rows move to `running` with `lease_until = now + lease` and
+    /// `attempts` incremented; the returned rows carry the new values.
+    #[builder]
+    pub async fn claim_due_account_deletions(
+        &self,
+        limit: i64,
+        lease: chrono::Duration,
+    ) -> DbResult<Vec<AccountDeletion>> {
+        let lease_until = Utc::now() + lease;
+        let deletions = sqlx::query_as::<_, AccountDeletion>(
+            r#"
+            WITH due AS (
+                SELECT id
+                FROM account_deletions
+                WHERE (status = 'scheduled' AND scheduled_for <= now())
+                   OR (status = 'running' AND lease_until <= now())
+                ORDER BY scheduled_for
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            UPDATE account_deletions AS d
+            SET status = 'running',
+                lease_until = $2,
+                attempts = d.attempts + 1
+            FROM due
+            WHERE d.id = due.id
+            RETURNING d.id, d.user_id, d.email, d.status, d.scheduled_for, d.attempts,
+                      d.lease_until, d.error, d.completed_at, d.created_at, d.updated_at
+            "#,
+        )
+        .bind(limit)
+        .bind(lease_until)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(deletions)
+    }
+
+    /// Record the error of a failed attempt. The row stays `running`, so it
+    /// is claimed again once its lease lapses.
+    #[builder]
+    pub async fn fail_account_deletion_attempt(&self, id: Uuid, error: String) -> DbResult<()> {
+        sqlx::query(
+            r#"
+            UPDATE account_deletions
+            SET error = $2
+            WHERE id = $1 AND status = 'running'
+            "#,
+        )
+        .bind(id)
+        .bind(&error)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    #[builder]
+    pub async fn record_account_deletion_event(
+        &self,
+        deletion_id: Uuid,
+        kind: &str,
+        detail: Option<serde_json::Value>,
+    ) -> DbResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO account_deletion_events (deletion_id, kind, detail)
+            VALUES ($1, $2, $3)
+            "#,
+        )
+        .bind(deletion_id)
+        .bind(kind)
+        .bind(detail.unwrap_or_else(|| serde_json::json!({})))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Oldest first.
+    #[builder]
+    pub async fn list_account_deletion_events(
+        &self,
+        deletion_id: Uuid,
+    ) -> DbResult<Vec<AccountDeletionEvent>> {
+        let events = sqlx::query_as::<_, AccountDeletionEvent>(
+            r#"
+            SELECT id, deletion_id, kind, detail, created_at
+            FROM account_deletion_events
+            WHERE deletion_id = $1
+            ORDER BY created_at, id
+            "#,
+        )
+        .bind(deletion_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(events)
+    }
+
+    /// Every object-storage path the user's rows point at: asset contents,
+    /// data export archives and chunks of unfinished uploads.
+    #[builder]
+    pub async fn list_account_storage_uris(&self, user_id: Uuid) -> DbResult<Vec<String>> {
+        let uris = sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT storage_uri FROM assets WHERE user_id = $1 AND legal_hold_at IS NULL
+            UNION
+            SELECT storage_uri FROM data_exports
+            WHERE user_id = $1 AND storage_uri IS NOT NULL
+            UNION
+            SELECT c.storage_uri FROM asset_upload_chunks c
+            JOIN asset_upload_sessions s ON s.id = c.session_id
+            WHERE s.user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(uris)
+    }
+
+    /// Whether any of the user's threads or assets is on legal hold. An
+    /// account in that state can't be erased until the holds are released.
+    #[builder]
+    pub async fn account_has_legal_hold(&self, user_id: Uuid) -> DbResult<bool> {
+        let held = sqlx::query_scalar::<_, bool>(ACCOUNT_HAS_LEGAL_HOLD)
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(held)
+    }
+
+    /// Delete the user row — and with it, through `ON DELETE CASCADE`,
+    /// everything else the account owns — and mark the deletion
+    /// `completed`, clearing its `email`, in one transaction. The tables
+    /// counted in the `data_erased` audit event are deleted explicitly
+    /// first so the counts are exact.
+    ///
+    /// Fails with [`DbError::OnLegalHold`], changing nothing, while any of
+    /// the user's threads or assets is held: the cascade would remove them
+    /// too.
+    ///
+    /// Safe to call again after a crash: a user that's already gone yields
+    /// zero counts.
+    #[builder]
+    pub async fn erase_account(
+        &self,
+        deletion_id: Uuid,
+        user_id: Uuid,
+    ) -> DbResult<ErasedAccountCounts> {
+        let mut tx = self.pool.begin().await?;
+
+        let messages =
+            sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM messages WHERE user_id = $1")
+                .bind(user_id)
+                .fetch_one(&mut *tx)
+                .await?;
+        // Held rows are skipped here, including one held after the
+        // transaction began, and then checked for below.
+        let threads =
+            sqlx::query("DELETE FROM threads WHERE user_id = $1 AND legal_hold_at IS NULL")
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+        let assets = sqlx::query("DELETE FROM assets WHERE user_id = $1 AND legal_hold_at IS NULL")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        let held = sqlx::query_scalar::<_, bool>(ACCOUNT_HAS_LEGAL_HOLD)
+            .bind(user_id)
+            .fetch_one(&mut *tx)
+            .await?;
+        if held {
+            return Err(DbError::on_legal_hold("account"));
+        }
+        let oauth_credentials = sqlx::query("DELETE FROM oauth_credentials WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let counts = ErasedAccountCounts {
+            threads: threads as i64,
+            messages,
+            assets: assets as i64,
+            oauth_credentials: oauth_credentials as i64,
+        };
+
+        sqlx::query(
+            r#"
+            UPDATE account_deletions
+            SET status = 'completed', completed_at = now(), email = NULL, lease_until = NULL,
+                error = NULL
+            WHERE id = $1
+            "#,
+        )
+        .bind(deletion_id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO account_deletion_events (deletion_id, kind, detail)
+            VALUES ($1, 'data_erased', $2)
+            "#,
+        )
+        .bind(deletion_id)
+        .bind(serde_json::to_value(counts).unwrap_or_default())
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(counts)
+    }
+
+    // --- retention --------------------------------------------------------
+
+    /// The user's overrides; categories without a row use the default.
+    #[builder]
+    pub async fn list_retention_settings(&self, user_id: Uuid) -> DbResult<Vec<RetentionSetting>> {
+        let settings = sqlx::query_as::<_, RetentionSetting>(
+            r#"
+            SELECT user_id, category, max_age_days, created_at, updated_at
+            FROM retention_settings
+            WHERE user_id = $1
+            ORDER BY category
             "#,
         )
-        .bind(id)
-        .bind(thread_id)
         .bind(user_id)
-        .bind(parent_message_id)
-        .bind(message_type)
-        .bind(&content)
-        .bind(&tool_call_id)
-        .bind(&tool_calls)
-        .bind(&additional_kwargs)
-        .bind(now)
-        .fetch_one(&self.pool)
+        .fetch_all(&self.pool)
         .await?;
 
-        Ok(message)
+        Ok(settings)
     }
 
-    /// Fetch a single message by id, scoped to (thread, user) for authorization.
-    ///
-    /// Returns `Ok(None)` when no row matches — the caller decides whether to
-    /// treat that as a not-found error or a benign missing reference.
-    pub async fn get_message(
+    /// Override the limit for each listed category, in one transaction.
+    /// `None` keeps the category forever. Returns every override the user
+    /// now has.
+    #[builder]
+    pub async fn set_retention_settings(
         &self,
-        thread_id: Uuid,
         user_id: Uuid,
-        message_id: Uuid,
-    ) -> DbResult<Option<Message>> {
-        let row = sqlx::query_as::<_, Message>(
+        settings: Vec<(RetentionCategory, Option<i32>)>,
+    ) -> DbResult<Vec<RetentionSetting>> {
+        let mut tx = self.pool.begin().await?;
+
+        for (category, max_age_days) in settings {
+            sqlx::query(
+                r#"
+                INSERT INTO retention_settings (user_id, category, max_age_days)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (user_id, category)
+                DO UPDATE SET max_age_days = EXCLUDED.max_age_days
+                "#,
+            )
+            .bind(user_id)
+            .bind(category)
+            .bind(max_age_days)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let settings = sqlx::query_as::<_, RetentionSetting>(
             r#"
-            SELECT m.id, m.thread_id, m.user_id, m.parent_message_id, m.message_type,
-                   m.content, m.tool_call_id, m.tool_calls, m.additional_kwargs,
-                   m.created_at, m.updated_at
-            FROM messages m
-            JOIN threads t ON t.id = m.thread_id
-            WHERE m.id = $1 AND m.thread_id = $2 AND t.user_id = $3
+            SELECT user_id, category, max_age_days, created_at, updated_at
+            FROM retention_settings
+            WHERE user_id = $1
+            ORDER BY category
             "#,
         )
-        .bind(message_id)
-        .bind(thread_id)
         .bind(user_id)
-        .fetch_optional(&self.pool)
+        .fetch_all(&mut *tx)
         .await?;
-        Ok(row)
+
+        tx.commit().await?;
+        Ok(settings)
     }
 
+    /// Drop every override, putting the user back on the defaults.
     #[builder]
-    pub async fn list_messages(
+    pub async fn reset_retention_settings(&self, user_id: Uuid) -> DbResult<()> {
+        sqlx::query("DELETE FROM retention_settings WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// What expiring the user's `category` items older than `cutoff` would
+    /// remove. Exempt items are left out, as they are when sweeping.
+    #[builder]
+    pub async fn count_expired_items(
         &self,
-        thread_id: Uuid,
         user_id: Uuid,
-        params: Option<PaginationParams>,
-    ) -> DbResult<Vec<Message>> {
-        let params = params.unwrap_or_default();
-
+        category: RetentionCategory,
+        cutoff: DateTime<Utc>,
+    ) -> DbResult<ExpiredItemStats> {
+        let RetentionScope {
+            table,
+            filter,
+            age_column,
+            size_bytes,
+        } = retention_scope(category);
         let query = format!(
             r#"
-            WITH RECURSIVE branch AS (
-                SELECT m.id, m.thread_id, m.user_id, m.parent_message_id, m.message_type,
-                       m.content, m.tool_call_id, m.tool_calls, m.additional_kwargs,
-                       m.created_at, m.updated_at
-                FROM messages m
-                JOIN threads t ON t.active_leaf_id = m.id
-                WHERE t.id = $1 AND t.user_id = $2
-
-                UNION ALL
-
-                SELECT parent.id, parent.thread_id, parent.user_id, parent.parent_message_id,
-                       parent.message_type, parent.content, parent.tool_call_id, parent.tool_calls,
-                       parent.additional_kwargs,
-                       parent.created_at, parent.updated_at
-                FROM messages parent
-                JOIN branch child ON child.parent_message_id = parent.id
-                    AND parent.thread_id = $1 AND parent.user_id = $2
-            )
-            SELECT m.id, m.thread_id, m.user_id, m.parent_message_id, m.message_type,
-                   m.content, m.tool_call_id, m.tool_calls, m.additional_kwargs,
-                   m.created_at, m.updated_at
-            FROM branch m
-            ORDER BY m.created_at {}
-            LIMIT $3 OFFSET $4
-            "#,
-            params.order()
+            SELECT COUNT(*) AS count,
+                   COALESCE(SUM({size_bytes}), 0)::BIGINT AS size_bytes,
+                   MIN({age_column}) AS oldest
+            FROM {table} x
+            WHERE x.user_id = $1 AND {age_column} < $2 AND {filter}
+            "#
         );
 
-        let messages = sqlx::query_as::<_, Message>(&query)
-            .bind(thread_id)
+        let stats = sqlx::query_as::<_, ExpiredItemStats>(&query)
             .bind(user_id)
-            .bind(params.limit())
-            .bind(params.offset())
-            .fetch_all(&self.pool)
+            .bind(cutoff)
+            .fetch_one(&self.pool)
             .await?;
 
-        Ok(messages)
+        Ok(stats)
     }
 
+    /// Up to `limit` assets in `category`, across all users, older than
+    /// their owner's limit, oldest first. `default_max_age_days` applies to
+    /// users without an override; `None` keeps those users' items.
+    ///
+    /// Only lists: the caller deletes the stored objects first and then
+    /// the rows with [`Self::delete_assets`], so a failed object delete
+    /// leaves the row in place to be retried.
     #[builder]
-    pub async fn list_branch_with_siblings(
+    pub async fn list_expired_assets(
         &self,
-        thread_id: Uuid,
-        user_id: Uuid,
-        params: Option<PaginationParams>,
-    ) -> DbResult<Vec<crate::types::BranchMessageRow>> {
-        let params = params.unwrap_or_default();
-
+        category: RetentionCategory,
+        default_max_age_days: Option<i32>,
+        limit: i64,
+    ) -> DbResult<Vec<ExpiredAsset>> {
+        if !category.is_asset() {
+            return Err(DbError::Internal(format!(
+                "{} is not an asset retention category",
+                category.as_str()
+            )));
+        }
         let query = format!(
-            r#"
-            WITH RECURSIVE branch AS (
-                SELECT m.id, m.thread_id, m.user_id, m.parent_message_id, m.message_type,
-                       m.content, m.tool_call_id, m.tool_calls, m.additional_kwargs,
-                       m.created_at, m.updated_at
-                FROM messages m
-                JOIN threads t ON t.active_leaf_id = m.id
-                WHERE t.id = $1 AND t.user_id = $2
-
-                UNION ALL
-
-                SELECT parent.id, parent.thread_id, parent.user_id, parent.parent_message_id,
-                       parent.message_type, parent.content, parent.tool_call_id, parent.tool_calls,
-                       parent.additional_kwargs,
-                       parent.created_at, parent.updated_at
-                FROM messages parent
-                JOIN branch child ON child.parent_message_id = parent.id
-                    AND parent.thread_id = $1 AND parent.user_id = $2
-            ),
-            active_branch AS (
-                SELECT id, parent_message_id,
-                       ROW_NUMBER() OVER (ORDER BY created_at {order}) AS rn
-                FROM branch
-            ),
-            paginated AS (
-                SELECT ab.id, ab.parent_message_id, b.created_at
-                FROM active_branch ab
-                JOIN branch b ON b.id = ab.id
-                WHERE ab.rn > $4 AND ab.rn <= $3 + $4
-            ),
-            numbered_branch AS (
-                SELECT id,
-                       ROW_NUMBER() OVER (ORDER BY created_at ASC) - 1 AS depth
-                FROM paginated
-            ),
-            siblings AS (
-                SELECT
-                    nb.id AS branch_message_id,
-                    nb.depth AS branch_depth,
-                    s.id, s.thread_id, s.user_id, s.parent_message_id, s.message_type,
-                    s.content, s.tool_call_id, s.tool_calls, s.additional_kwargs,
-                    s.created_at, s.updated_at,
-                    ROW_NUMBER() OVER (
-                        PARTITION BY nb.id ORDER BY s.created_at, s.id
-                    ) - 1 AS sibling_index
-                FROM numbered_branch nb
-                JOIN paginated p ON p.id = nb.id
-                JOIN messages s ON s.thread_id = $1 AND s.user_id = $2
-                    AND (
-                        (p.parent_message_id IS NOT NULL AND s.parent_message_id = p.parent_message_id)
-                        OR (p.parent_message_id IS NULL AND s.parent_message_id IS NULL)
-                    )
-            )
-            SELECT branch_message_id, branch_depth::int4,
-                   id, thread_id, user_id, parent_message_id, message_type,
-                   content, tool_call_id, tool_calls, additional_kwargs,
-                   created_at, updated_at,
-                   sibling_index
-            FROM siblings
-            ORDER BY branch_depth ASC, sibling_index ASC
-            "#,
-            order = params.order()
+            "SELECT x.id, x.user_id, x.storage_uri {}",
+            expired_items_sql(category)
         );
 
-        let rows = sqlx::query_as::<_, crate::types::BranchMessageRow>(&query)
-            .bind(thread_id)
-            .bind(user_id)
-            .bind(params.limit())
-            .bind(params.offset())
+        let assets = sqlx::query_as::<_, ExpiredAsset>(&query)
+            .bind(category)
+            .bind(default_max_age_days)
+            .bind(limit)
             .fetch_all(&self.pool)
             .await?;
 
-        Ok(rows)
+        Ok(assets)
     }
 
+    /// Delete the assets in `ids`, except held ones.
     #[builder]
-    pub async fn try_claim_webhook_event<'e, E>(
+    pub async fn delete_assets(&self, ids: &[Uuid]) -> DbResult<u64> {
+        let result = sqlx::query("DELETE FROM assets WHERE id = ANY($1) AND legal_hold_at IS NULL")
+            .bind(ids)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Delete up to `limit` items in a non-asset `category`, across all
+    /// users, that are older than their owner's limit, and return how many
+    /// went. Limits work as in [`Self::list_expired_assets`]. Deleting a
+    /// thread takes its messages with it.
+    #[builder]
+    pub async fn delete_expired_items(
         &self,
-        executor: E,
-        event_id: &str,
-        event_type: &str,
-    ) -> DbResult<bool>
-    where
-        E: sqlx::Executor<'e, Database = sqlx::Postgres>,
-    {
+        category: RetentionCategory,
+        default_max_age_days: Option<i32>,
+        limit: i64,
+    ) -> DbResult<u64> {
+        if category.is_asset() {
+            return Err(DbError::Internal(format!(
+                "{} items must be deleted through list_expired_assets",
+                category.as_str()
+            )));
+        }
+        let table = retention_scope(category).table;
+        let query = format!(
+            "DELETE FROM {table} WHERE id IN (SELECT x.id {})",
+            expired_items_sql(category)
+        );
+
+        let result = sqlx::query(&query)
+            .bind(category)
+            .bind(default_max_age_days)
+            .bind(limit)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Pin or unpin one of the user's assets. Pinned assets never expire.
+    #[builder]
+    pub async fn set_asset_pinned(&self, id: Uuid, user_id: Uuid, pinned: bool) -> DbResult<()> {
         let result = sqlx::query(
             r#"
-            INSERT INTO stripe.webhook_events (event_id, event_type)
-            VALUES ($1, $2)
-            ON CONFLICT (event_id) DO NOTHING
+            UPDATE assets
+            SET pinned_at = CASE WHEN $3 THEN COALESCE(pinned_at, now()) END
+            WHERE id = $1 AND user_id = $2
             "#,
         )
-        .bind(event_id)
-        .bind(event_type)
-        .execute(executor)
+        .bind(id)
+        .bind(user_id)
+        .bind(pinned)
+        .execute(&self.pool)
         .await?;
 
-        Ok(result.rows_affected() > 0)
+        if result.rows_affected() == 0 {
+            return Err(DbError::NotFound {
+                entity: "asset",
+                id: Some(id.to_string()),
+            });
+        }
+        Ok(())
     }
 
+    /// Up to `limit` hot assets older than `cold_after_days`, oldest first,
+    /// for the tiering pass. Assets brought back from cold stay hot for
+    /// another `cold_after_days` after that, and ones still waiting for
+    /// the malware scan are left for it.
     #[builder]
-    pub async fn upsert_stripe_customer<'e, E>(
+    pub async fn list_assets_for_cold_tier(
         &self,
-        executor: E,
-        customer_id: &str,
-        app_user_id: Option<Uuid>,
-        email: Option<&str>,
-        raw_data: &serde_json::Value,
-    ) -> DbResult<()>
-    where
-        E: sqlx::Executor<'e, Database = sqlx::Postgres>,
-    {
-        let now = Utc::now();
+        cold_after_days: i32,
+        limit: i64,
+    ) -> DbResult<Vec<TierableAsset>> {
+        let assets = sqlx::query_as::<_, TierableAsset>(
+            r#"
+            SELECT id, storage_uri
+            FROM assets
+            WHERE storage_tier = 'hot'
+              AND scan_status <> 'pending'
+              AND created_at < now() - make_interval(days => $1)
+              AND (tiered_at IS NULL OR tiered_at < now() - make_interval(days => $1))
+            ORDER BY created_at
+            LIMIT $2
+            "#,
+        )
+        .bind(cold_after_days)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
 
-        sqlx::query(
+        Ok(assets)
+    }
+
+    /// Point the asset at its copy in `tier`, if it still points at
+    /// `from_uri`, and return whether it did. `false` means the asset was
+    /// deleted or moved by someone else in the meantime, so the caller
+    /// should remove the copy at `to_uri` instead of the original.
+    #[builder]
+    pub async fn move_asset_to_tier(
+        &self,
+        id: Uuid,
+        from_uri: &str,
+        to_uri: &str,
+        tier: AssetStorageTier,
+    ) -> DbResult<bool> {
+        let result = sqlx::query(
             r#"
-            INSERT INTO stripe.customers (id, app_user_id, email, created_at, updated_at, raw_data)
-            VALUES ($1, $2, $3, $4, $4, $5)
-            ON CONFLICT (id) DO UPDATE
-            SET email = COALESCE(EXCLUDED.email, stripe.customers.email),
-                app_user_id = COALESCE(EXCLUDED.app_user_id, stripe.customers.app_user_id),
-                raw_data = EXCLUDED.raw_data,
-                updated_at = EXCLUDED.updated_at
+            UPDATE assets
+            SET storage_uri = $3, storage_tier = $4, tiered_at = now()
+            WHERE id = $1 AND storage_uri = $2
             "#,
         )
-        .bind(customer_id)
-        .bind(app_user_id)
-        .bind(email)
-        .bind(now)
-        .bind(raw_data)
-        .execute(executor)
+        .bind(id)
+        .bind(from_uri)
+        .bind(to_uri)
+        .bind(tier)
+        .execute(&self.pool)
         .await?;
 
-        Ok(())
+        Ok(result.rows_affected() == 1)
     }
 
+    // --- notifications ----------------------------------------------------
+
+    /// Record a notification for `user_id`, or for every user when it is
+    /// `None`, and announce it on [`NOTIFICATION_CHANNEL`]. Inside a
+    /// transaction the announcement goes out on commit, and not at all on
+    /// rollback.
     #[builder]
-    pub async fn link_stripe_customer_to_user<'e, E>(
+    pub async fn create_notification<'e, E>(
         &self,
         executor: E,
-        user_id: Uuid,
-        stripe_customer_id: &str,
-    ) -> DbResult<()>
+        user_id: Option<Uuid>,
+        payload: &serde_json::Value,
+    ) -> DbResult<Notification>
     where
         E: sqlx::Executor<'e, Database = sqlx::Postgres>,
     {
-        sqlx::query(
+        let notification = sqlx::query_as::<_, Notification>(
             r#"
-            UPDATE users
-            SET stripe_customer_id = $2
-            WHERE id = $1
+            WITH n AS (
+                INSERT INTO notifications (user_id, payload)
+                VALUES ($1, $2)
+                RETURNING id, user_id, payload, created_at
+            )
+            SELECT n.id, n.user_id, n.payload, n.created_at
+            FROM n, LATERAL (SELECT pg_notify($3, row_to_json(n)::text)) AS announce
             "#,
         )
         .bind(user_id)
-        .bind(stripe_customer_id)
-        .execute(executor)
+        .bind(payload)
+        .bind(NOTIFICATION_CHANNEL)
+        .fetch_one(executor)
         .await?;
 
-        Ok(())
-    }
-
-    #[builder]
-    pub async fn get_stripe_customer_id_for_user(&self, user_id: Uuid) -> DbResult<Option<String>> {
-        let result: Option<Option<String>> =
-            sqlx::query_scalar("SELECT stripe_customer_id FROM users WHERE id = $1")
-                .bind(user_id)
-                .fetch_optional(&self.pool)
-                .await?;
-        Ok(result.flatten())
+        Ok(notification)
     }
 
+    /// [`create_notification`](Self::create_notification) for every user
+    /// billed under `stripe_customer_id` — usually exactly one.
     #[builder]
-    pub async fn enqueue_stripe_customer_provisioning<'e, E>(
+    pub async fn create_notification_for_stripe_customer<'e, E>(
         &self,
         executor: E,
-        user_id: Uuid,
+        stripe_customer_id: &str,
+        payload: &serde_json::Value,
     ) -> DbResult<()>
     where
         E: sqlx::Executor<'e, Database = sqlx::Postgres>,
     {
         sqlx::query(
             r#"
-            INSERT INTO stripe.customer_provisioning_jobs (user_id)
-            VALUES ($1)
-            ON CONFLICT (user_id) DO NOTHING
+            WITH n AS (
+                INSERT INTO notifications (user_id, payload)
+                SELECT id, $2 FROM users WHERE stripe_customer_id = $1
+                RETURNING id, user_id, payload, created_at
+            )
+            SELECT pg_notify($3, row_to_json(n)::text) FROM n
             "#,
         )
-        .bind(user_id)
+        .bind(stripe_customer_id)
+        .bind(payload)
+        .bind(NOTIFICATION_CHANNEL)
         .execute(executor)
         .await?;
+
         Ok(())
     }
 
-    /// Atomically claim up to `limit` due provisioning jobs, joining on
-    /// `users` so the drainer doesn't need a second round trip for the email.
-    ///
-    /// `FOR UPDATE OF j SKIP LOCKED` locks only the job row, not the user row,
-    /// and lets multiple drainer workers run safely in parallel. Selected
-    /// rows have `next_attempt_at` pushed into the future to act as a lease —
-    /// if the worker crashes mid-provision the row becomes claimable again
-    /// after the lease expires. Dead-lettered rows are skipped.
+    /// Notifications for `user_id`, broadcasts included, with an id above
+    /// `after`, oldest first.
     #[builder]
-    pub async fn claim_due_provisioning_jobs(
+    pub async fn list_notifications_after(
         &self,
+        user_id: Uuid,
+        after: i64,
         limit: i64,
-        lease: chrono::Duration,
-    ) -> DbResult<Vec<ClaimedProvisioningJob>> {
-        let lease_until = Utc::now() + lease;
-        let jobs = sqlx::query_as::<_, ClaimedProvisioningJob>(
+    ) -> DbResult<Vec<Notification>> {
+        let notifications = sqlx::query_as::<_, Notification>(
             r#"
-            WITH due AS (
-                SELECT j.user_id, u.email
-                FROM stripe.customer_provisioning_jobs j
-                INNER JOIN users u ON u.id = j.user_id
-                WHERE j.dead_letter = FALSE
-                  AND j.next_attempt_at <= now()
-                ORDER BY j.next_attempt_at
-                LIMIT $1
-                FOR UPDATE OF j SKIP LOCKED
-            )
-            UPDATE stripe.customer_provisioning_jobs AS j
-            SET next_attempt_at = $2,
-                updated_at = now()
-            FROM due
-            WHERE j.user_id = due.user_id
-            RETURNING j.user_id, due.email, j.attempts
+            SELECT id, user_id, payload, created_at
+            FROM notifications
+            WHERE (user_id = $1 OR user_id IS NULL) AND id > $2
+            ORDER BY id
+            LIMIT $3
             "#,
         )
+        .bind(user_id)
+        .bind(after)
         .bind(limit)
-        .bind(lease_until)
         .fetch_all(&self.pool)
         .await?;
-        Ok(jobs)
+
+        Ok(notifications)
     }
 
-    /// Delete a provisioning job. Intended to be called inside the same
-    /// transaction as the link write so that "customer linked" and "job
-    /// removed" are atomic.
+    /// The newest notification id, or 0 when there are none. A client
+    /// that starts from here is caught up.
+    pub async fn latest_notification_id(&self) -> DbResult<i64> {
+        let id: Option<i64> = sqlx::query_scalar("SELECT max(id) FROM notifications")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(id.unwrap_or(0))
+    }
+
+    /// The oldest notification id still kept, or `None` when there are
+    /// none. A cursor more than one below it may have missed pruned rows.
+    pub async fn oldest_notification_id(&self) -> DbResult<Option<i64>> {
+        let id = sqlx::query_scalar("SELECT min(id) FROM notifications")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(id)
+    }
+
+    /// Delete notifications created before `cutoff`. Returns how many went.
     #[builder]
-    pub async fn delete_provisioning_job<'e, E>(&self, executor: E, user_id: Uuid) -> DbResult<()>
-    where
-        E: sqlx::Executor<'e, Database = sqlx::Postgres>,
-    {
-        sqlx::query("DELETE FROM stripe.customer_provisioning_jobs WHERE user_id = $1")
-            .bind(user_id)
-            .execute(executor)
+    pub async fn prune_notifications(&self, cutoff: DateTime<Utc>) -> DbResult<u64> {
+        let result = sqlx::query("DELETE FROM notifications WHERE created_at < $1")
+            .bind(cutoff)
+            .execute(&self.pool)
             .await?;
-        Ok(())
+        Ok(result.rows_affected())
     }
 
-    pub async fn fail_stripe_customer_provisioning(
+    /// A dedicated connection listening on [`NOTIFICATION_CHANNEL`].
+    pub async fn notification_listener(&self) -> DbResult<NotificationListener> {
+        NotificationListener::connect(&self.pool).await
+    }
+
+    // --- webhooks ---------------------------------------------------------
+
+    /// Register an endpoint. Subscribing to `automation.daily_summary`
+    /// schedules the first summary a day out.
+    #[builder]
+    pub async fn create_webhook_endpoint(
         &self,
+        id: Option<Uuid>,
         user_id: Uuid,
-        error: &str,
-        retry_after: chrono::Duration,
-    ) -> DbResult<()> {
-        let next_attempt_at = Utc::now() + retry_after;
-        sqlx::query(
+        kind: WebhookEndpointKind,
+        url: &str,
+        secret: &str,
+        event_types: &[String],
+        automation_ids: Option<&[Uuid]>,
+    ) -> DbResult<WebhookEndpoint> {
+        let id = id.unwrap_or_else(Uuid::now_v7);
+
+        let endpoint = sqlx::query_as::<_, WebhookEndpoint>(
             r#"
-            UPDATE stripe.customer_provisioning_jobs
-            SET attempts = attempts + 1,
-                next_attempt_at = $2,
-                last_error = $3,
-                updated_at = now()
-            WHERE user_id = $1
+            INSERT INTO webhook_endpoints
+                (id, user_id, kind, url, secret, event_types, automation_ids, next_summary_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7,
+                    CASE WHEN $8 = ANY($6) THEN now() + interval '1 day' END)
+            RETURNING id, user_id, kind, url, secret, event_types, automation_ids, enabled,
+                      next_summary_at, created_at, updated_at
             "#,
         )
+        .bind(id)
         .bind(user_id)
-        .bind(next_attempt_at)
-        .bind(error)
-        .execute(&self.pool)
+        .bind(kind)
+        .bind(url)
+        .bind(secret)
+        .bind(event_types)
+        .bind(automation_ids)
+        .bind(WebhookEventType::AutomationDailySummary.as_str())
+        .fetch_one(&self.pool)
         .await?;
-        Ok(())
+
+        Ok(endpoint)
     }
 
-    /// Mark a provisioning job as dead — it will not be retried. The drainer
-    /// uses this for permanent (4xx, non-rate-limit) failures from Stripe.
-    pub async fn dead_letter_stripe_customer_provisioning(
-        &self,
-        user_id: Uuid,
-        error: &str,
-    ) -> DbResult<()> {
-        sqlx::query(
+    /// Oldest first.
+    pub async fn list_webhook_endpoints(&self, user_id: Uuid) -> DbResult<Vec<WebhookEndpoint>> {
+        let endpoints = sqlx::query_as::<_, WebhookEndpoint>(
             r#"
-            UPDATE stripe.customer_provisioning_jobs
-            SET dead_letter = TRUE,
-                attempts = attempts + 1,
-                last_error = $2,
-                updated_at = now()
+            SELECT id, user_id, kind, url, secret, event_types, automation_ids, enabled,
+                   next_summary_at, created_at, updated_at
+            FROM webhook_endpoints
             WHERE user_id = $1
+            ORDER BY created_at, id
             "#,
         )
         .bind(user_id)
-        .bind(error)
-        .execute(&self.pool)
+        .fetch_all(&self.pool)
         .await?;
-        Ok(())
+
+        Ok(endpoints)
     }
 
-    #[builder]
-    pub async fn upsert_stripe_subscription<'e, E>(
-        &self,
-        executor: E,
-        subscription_id: &str,
-        customer_id: &str,
-        status: &str,
-        cancel_at_period_end: bool,
-        canceled_at: Option<i64>,
-        current_period_start: i64,
-        current_period_end: i64,
-        raw_data: &serde_json::Value,
-    ) -> DbResult<()>
-    where
-        E: sqlx::Executor<'e, Database = sqlx::Postgres>,
-    {
-        let now = Utc::now();
-        let canceled_at_ts = canceled_at.and_then(|ts| {
-            let dt = chrono::DateTime::from_timestamp(ts, 0);
-            if dt.is_none() {
-                tracing::warn!(ts, "malformed canceled_at timestamp in stripe subscription");
-            }
-            dt
-        });
-        let period_start = chrono::DateTime::from_timestamp(current_period_start, 0)
-            .unwrap_or_else(|| {
-                tracing::warn!(current_period_start, "malformed current_period_start timestamp in stripe subscription, falling back to now");
-                now
-            });
-        let period_end = chrono::DateTime::from_timestamp(current_period_end, 0).unwrap_or_else(
-            || {
-                tracing::warn!(current_period_end, "malformed current_period_end timestamp in stripe subscription, falling back to now");
-                now
-            },
-        );
+    pub async fn count_webhook_endpoints(&self, user_id: Uuid) -> DbResult<i64> {
+        let count = sqlx::query_scalar("SELECT count(*) FROM webhook_endpoints WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count)
+    }
 
-        sqlx::query(
+    #[builder]
+    pub async fn get_webhook_endpoint(&self, id: Uuid, user_id: Uuid) -> DbResult<WebhookEndpoint> {
+        sqlx::query_as::<_, WebhookEndpoint>(
             r#"
-            INSERT INTO stripe.subscriptions (
-                id, customer_id, status,
-                cancel_at_period_end, canceled_at,
-                current_period_start, current_period_end,
-                created_at, updated_at, raw_data
-            )
-            VALUES ($1, $2, $3::stripe.subscription_status, $4, $5, $6, $7, $8, $9, $10)
-            ON CONFLICT (id) DO UPDATE
-            SET status = $3::stripe.subscription_status,
-                customer_id = EXCLUDED.customer_id,
-                cancel_at_period_end = EXCLUDED.cancel_at_period_end,
-                canceled_at = EXCLUDED.canceled_at,
-                current_period_start = EXCLUDED.current_period_start,
-                current_period_end = EXCLUDED.current_period_end,
-                raw_data = EXCLUDED.raw_data,
-                updated_at = EXCLUDED.updated_at
+            SELECT id, user_id, kind, url, secret, event_types, automation_ids, enabled,
+                   next_summary_at, created_at, updated_at
+            FROM webhook_endpoints
+            WHERE id = $1 AND user_id = $2
             "#,
         )
-        .bind(subscription_id)
-        .bind(customer_id)
-        .bind(status)
-        .bind(cancel_at_period_end)
-        .bind(canceled_at_ts)
-        .bind(period_start)
-        .bind(period_end)
-        .bind(now)
-        .bind(now)
-        .bind(raw_data)
-        .execute(executor)
-        .await?;
-
-        Ok(())
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| DbError::NotFound {
+            entity: "webhook endpoint",
+            id: Some(id.to_string()),
+        })
     }
 
+    /// Change an endpoint's subscriptions or pause it. Fields left `None`
+    /// keep their value; `automation_ids: Some(None)` clears the filter.
+    /// The summary schedule follows the `automation.daily_summary`
+    /// subscription.
     #[builder]
-    pub async fn upsert_stripe_price<'e, E>(
+    pub async fn update_webhook_endpoint(
         &self,
-        executor: E,
-        price_id: &str,
-        currency: &str,
-        unit_amount: Option<i64>,
-        recurring_interval: Option<&str>,
-        active: bool,
-        raw_data: &serde_json::Value,
-    ) -> DbResult<()>
-    where
-        E: sqlx::Executor<'e, Database = sqlx::Postgres>,
-    {
-        let now = Utc::now();
-
-        sqlx::query(
+        id: Uuid,
+        user_id: Uuid,
+        url: Option<&str>,
+        event_types: Option<&[String]>,
+        automation_ids: Option<Option<&[Uuid]>>,
+        enabled: Option<bool>,
+    ) -> DbResult<WebhookEndpoint> {
+        sqlx::query_as::<_, WebhookEndpoint>(
             r#"
-            INSERT INTO stripe.prices (id, currency, unit_amount, recurring_interval, active, created_at, updated_at, raw_data)
-            VALUES ($1, $2, $3, $4, $5, $6, $6, $7)
-            ON CONFLICT (id) DO UPDATE
-            SET currency = EXCLUDED.currency,
-                unit_amount = EXCLUDED.unit_amount,
-                recurring_interval = EXCLUDED.recurring_interval,
-                active = EXCLUDED.active,
-                raw_data = EXCLUDED.raw_data,
-                updated_at = EXCLUDED.updated_at
+            UPDATE webhook_endpoints
+            SET url = COALESCE($3, url),
+                event_types = COALESCE($4, event_types),
+                automation_ids = CASE WHEN $5 THEN $6 ELSE automation_ids END,
+                enabled = COALESCE($7, enabled),
+                next_summary_at = CASE
+                    WHEN $8 = ANY(COALESCE($4, event_types))
+                        THEN COALESCE(next_summary_at, now() + interval '1 day')
+                END
+            WHERE id = $1 AND user_id = $2
+            RETURNING id, user_id, kind, url, secret, event_types, automation_ids, enabled,
+                      next_summary_at, created_at, updated_at
             "#,
         )
-        .bind(price_id)
-        .bind(currency)
-        .bind(unit_amount)
-        .bind(recurring_interval)
-        .bind(active)
-        .bind(now)
-        .bind(raw_data)
-        .execute(executor)
-        .await?;
+        .bind(id)
+        .bind(user_id)
+        .bind(url)
+        .bind(event_types)
+        .bind(automation_ids.is_some())
+        .bind(automation_ids.flatten())
+        .bind(enabled)
+        .bind(WebhookEventType::AutomationDailySummary.as_str())
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| DbError::NotFound {
+            entity: "webhook endpoint",
+            id: Some(id.to_string()),
+        })
+    }
+
+    /// Delete an endpoint along with its delivery log.
+    #[builder]
+    pub async fn delete_webhook_endpoint(&self, id: Uuid, user_id: Uuid) -> DbResult<()> {
+        let result = sqlx::query("DELETE FROM webhook_endpoints WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(DbError::NotFound {
+                entity: "webhook endpoint",
+                id: Some(id.to_string()),
+            });
+        }
 
         Ok(())
     }
 
+    /// Queue `payload` for every enabled endpoint of `user_id` subscribed
+    /// to `event_type`. Returns how many deliveries were queued. Inside a
+    /// transaction nothing is sent on rollback.
+    ///
+    /// `automation_id` is set for events about an automation, and skips
+    /// endpoints that filter to other automations.
     #[builder]
-    pub async fn ensure_plan_price<'e, E>(
+    pub async fn enqueue_webhook_event<'e, E>(
         &self,
         executor: E,
-        plan_id: &str,
-        stripe_price_id: &str,
-    ) -> DbResult<()>
+        user_id: Uuid,
+        event_type: WebhookEventType,
+        automation_id: Option<Uuid>,
+        payload: &serde_json::Value,
+    ) -> DbResult<u64>
     where
         E: sqlx::Executor<'e, Database = sqlx::Postgres>,
     {
-        sqlx::query(
-            "INSERT INTO plan_prices (plan_id, stripe_price_id) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+        let result = sqlx::query(
+            r#"
+            INSERT INTO webhook_deliveries (endpoint_id, event_type, payload)
+            SELECT id, $2, $3
+            FROM webhook_endpoints
+            WHERE user_id = $1 AND enabled AND $2 = ANY(event_types)
+              AND ($4::uuid IS NULL OR automation_ids IS NULL OR $4 = ANY(automation_ids))
+            "#,
         )
-        .bind(plan_id)
-        .bind(stripe_price_id)
+        .bind(user_id)
+        .bind(event_type.as_str())
+        .bind(payload)
+        .bind(automation_id)
         .execute(executor)
         .await?;
 
-        Ok(())
+        Ok(result.rows_affected())
     }
 
+    /// Queue `payload` for one endpoint only, whatever it subscribes to.
     #[builder]
-    pub async fn sync_stripe_subscription_items<'e, E>(
+    pub async fn enqueue_webhook_delivery(
         &self,
-        executor: E,
-        subscription_id: &str,
-        items: &[(String, String, Option<i64>, serde_json::Value)],
-    ) -> DbResult<()>
-    where
-        E: sqlx::Executor<'e, Database = sqlx::Postgres>,
-    {
-        if items.is_empty() {
-            sqlx::query("DELETE FROM stripe.subscription_items WHERE subscription_id = $1")
-                .bind(subscription_id)
-                .execute(executor)
-                .await?;
-            return Ok(());
-        }
-
-        let mut query = String::from(
-            "WITH deleted AS (DELETE FROM stripe.subscription_items WHERE subscription_id = $1) INSERT INTO stripe.subscription_items (id, subscription_id, price_id, quantity, raw_data) VALUES ",
-        );
-
-        let mut param_idx = 2u32;
-        for (i, _) in items.iter().enumerate() {
-            if i > 0 {
-                query.push_str(", ");
-            }
-            query.push_str(&format!(
-                "(${}, $1, ${}, ${}, ${})",
-                param_idx,
-                param_idx + 1,
-                param_idx + 2,
-                param_idx + 3,
-            ));
-            param_idx += 4;
-        }
-        query.push_str(" ON CONFLICT (id) DO UPDATE SET price_id = EXCLUDED.price_id, quantity = EXCLUDED.quantity, raw_data = EXCLUDED.raw_data");
+        endpoint_id: Uuid,
+        event_type: WebhookEventType,
+        payload: &serde_json::Value,
+    ) -> DbResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO webhook_deliveries (endpoint_id, event_type, payload)
+            VALUES ($1, $2, $3)
+            "#,
+        )
+        .bind(endpoint_id)
+        .bind(event_type.as_str())
+        .bind(payload)
+        .execute(&self.pool)
+        .await?;
 
-        let mut q = sqlx::query(&query).bind(subscription_id);
-        for (item_id, price_id, quantity, raw_data) in items {
-            q = q
-                .bind(item_id)
-                .bind(price_id)
-                .bind(quantity.map(|v| v as i32))
-                .bind(raw_data);
-        }
+        Ok(())
+    }
 
-        q.execute(executor).await?;
+    /// Atomically claim up to `limit` enabled endpoints whose daily
+    /// summary is due, moving each one's `next_summary_at` a day on (or to
+    /// a day from now, if it fell more than a day behind). The returned
+    /// rows carry the new values.
+    #[builder]
+    pub async fn claim_due_webhook_summaries(&self, limit: i64) -> DbResult<Vec<WebhookEndpoint>> {
+        let endpoints = sqlx::query_as::<_, WebhookEndpoint>(
+            r#"
+            WITH due AS (
+                SELECT id
+                FROM webhook_endpoints
+                WHERE next_summary_at <= now() AND enabled
+                ORDER BY next_summary_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            UPDATE webhook_endpoints AS e
+            SET next_summary_at = CASE
+                WHEN e.next_summary_at + interval '1 day' > now()
+                    THEN e.next_summary_at + interval '1 day'
+                ELSE now() + interval '1 day'
+            END
+            FROM due
+            WHERE e.id = due.id
+            RETURNING e.id, e.user_id, e.kind, e.url, e.secret, e.event_types, e.automation_ids,
+                      e.enabled, e.next_summary_at, e.created_at, e.updated_at
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
 
-        Ok(())
+        Ok(endpoints)
     }
 
+    /// The latest `limit` deliveries to one of the user's endpoints,
+    /// newest first.
     #[builder]
-    pub async fn update_stripe_subscription_status(
+    pub async fn list_webhook_deliveries(
         &self,
-        subscription_id: &str,
-        status: &str,
-        cancel_at_period_end: bool,
-        canceled_at: Option<i64>,
-        raw_data: &serde_json::Value,
-    ) -> DbResult<()> {
-        let now = Utc::now();
-        let canceled_at_ts = canceled_at.and_then(|ts| chrono::DateTime::from_timestamp(ts, 0));
-
-        let result = sqlx::query(
+        endpoint_id: Uuid,
+        user_id: Uuid,
+        limit: i64,
+    ) -> DbResult<Vec<WebhookDelivery>> {
+        let deliveries = sqlx::query_as::<_, WebhookDelivery>(
             r#"
-            UPDATE stripe.subscriptions
-            SET status = $2::stripe.subscription_status,
-                cancel_at_period_end = $3,
-                canceled_at = $4,
-                raw_data = $5,
-                updated_at = $6
-            WHERE id = $1
+            SELECT d.id, d.endpoint_id, d.event_type, d.payload, d.status, d.attempts,
+                   d.next_attempt_at, d.response_status, d.error, d.delivered_at,
+                   d.created_at, d.updated_at
+            FROM webhook_deliveries d
+            JOIN webhook_endpoints e ON e.id = d.endpoint_id
+            WHERE d.endpoint_id = $1 AND e.user_id = $2
+            ORDER BY d.created_at DESC, d.id DESC
+            LIMIT $3
             "#,
         )
-        .bind(subscription_id)
-        .bind(status)
-        .bind(cancel_at_period_end)
-        .bind(canceled_at_ts)
-        .bind(raw_data)
-        .bind(now)
-        .execute(&self.pool)
+        .bind(endpoint_id)
+        .bind(user_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
         .await?;
 
-        if result.rows_affected() == 0 {
-            return Err(DbError::not_found_with_id("subscription", subscription_id));
-        }
-
-        Ok(())
+        Ok(deliveries)
     }
 
+    /// Atomically claim up to `limit` pending deliveries whose
+    /// `next_attempt_at` has passed, oldest first.
+    ///
+    /// Claimed rows get `next_attempt_at = now + lease` and `attempts`
+    /// incremented; the returned rows carry the new values. A worker that
+    /// dies mid-request leaves the delivery to be claimed again once the
+    /// lease lapses. Deliveries to a disabled endpoint wait until it is
+    /// enabled again.
     #[builder]
-    pub async fn update_stripe_subscription_status_with_executor<'e, E>(
+    pub async fn claim_due_webhook_deliveries(
         &self,
-        executor: E,
-        subscription_id: &str,
-        status: &str,
-        cancel_at_period_end: bool,
-        canceled_at: Option<i64>,
-        raw_data: &serde_json::Value,
-    ) -> DbResult<()>
-    where
-        E: sqlx::Executor<'e, Database = sqlx::Postgres>,
-    {
-        let now = Utc::now();
-        let canceled_at_ts = canceled_at.and_then(|ts| chrono::DateTime::from_timestamp(ts, 0));
-
-        let result = sqlx::query(
+        limit: i64,
+        lease: chrono::Duration,
+    ) -> DbResult<Vec<ClaimedWebhookDelivery>> {
+        let lease_until = Utc::now() + lease;
+        let deliveries = sqlx::query_as::<_, ClaimedWebhookDelivery>(
             r#"
-            UPDATE stripe.subscriptions
-            SET status = $2::stripe.subscription_status,
-                cancel_at_period_end = $3,
-                canceled_at = $4,
-                raw_data = $5,
-                updated_at = $6
-            WHERE id = $1
+            WITH due AS (
+                SELECT d.id
+                FROM webhook_deliveries d
+                JOIN webhook_endpoints e ON e.id = d.endpoint_id
+                WHERE d.status = 'pending' AND d.next_attempt_at <= now() AND e.enabled
+                ORDER BY d.next_attempt_at
+                LIMIT $1
+                FOR UPDATE OF d SKIP LOCKED
+            ),
+            claimed AS (
+                UPDATE webhook_deliveries AS d
+                SET next_attempt_at = $2, attempts = d.attempts + 1
+                FROM due
+                WHERE d.id = due.id
+                RETURNING d.*
+            )
+            SELECT c.id, c.endpoint_id, c.event_type, c.payload, c.status, c.attempts,
+                   c.next_attempt_at, c.response_status, c.error, c.delivered_at,
+                   c.created_at, c.updated_at, e.kind, e.url, e.secret
+            FROM claimed c
+            JOIN webhook_endpoints e ON e.id = c.endpoint_id
             "#,
         )
-        .bind(subscription_id)
-        .bind(status)
-        .bind(cancel_at_period_end)
-        .bind(canceled_at_ts)
-        .bind(raw_data)
-        .bind(now)
-        .execute(executor)
+        .bind(limit)
+        .bind(lease_until)
+        .fetch_all(&self.pool)
         .await?;
 
-        if result.rows_affected() == 0 {
-            return Err(DbError::not_found_with_id("subscription", subscription_id));
-        }
-
-        Ok(())
+        Ok(deliveries)
     }
 
+    /// Record the outcome of one attempt. `next_attempt_at` set leaves the
+    /// delivery pending for a retry then; otherwise it ends as `status`.
     #[builder]
-    pub async fn ensure_user_plan<'e, E>(
+    pub async fn record_webhook_attempt(
         &self,
-        executor: E,
-        user_id: Uuid,
-        plan_id: &str,
-    ) -> DbResult<()>
-    where
-        E: sqlx::Executor<'e, Database = sqlx::Postgres>,
-    {
+        id: Uuid,
+        status: WebhookDeliveryStatus,
+        response_status: Option<i16>,
+        error: Option<String>,
+        next_attempt_at: Option<DateTime<Utc>>,
+    ) -> DbResult<()> {
         sqlx::query(
             r#"
-            UPDATE users
-            SET plan_id = CASE
-                    WHEN (SELECT rank FROM (VALUES ('free',0),('tier1',1)) AS r(id,rank) WHERE r.id = $2)
-                       > (SELECT rank FROM (VALUES ('free',0),('tier1',1)) AS r(id,rank) WHERE r.id = users.plan_id)
-                    THEN $2
-                    ELSE users.plan_id
-                END
+            UPDATE webhook_deliveries
+            SET status = $2,
+                response_status = $3,
+                error = $4,
+                next_attempt_at = COALESCE($5, next_attempt_at),
+                delivered_at = CASE WHEN $2 = 'succeeded' THEN now() END
             WHERE id = $1
             "#,
         )
-        .bind(user_id)
-        .bind(plan_id)
-        .execute(executor)
+        .bind(id)
+        .bind(status)
+        .bind(response_status)
+        .bind(&error)
+        .bind(next_attempt_at)
+        .execute(&self.pool)
         .await?;
 
         Ok(())
     }
 
+    /// Delete deliveries created before `cutoff`, whatever their status.
+    /// Returns how many went.
     #[builder]
-    pub async fn get_plan_id_for_user(&self, user_id: Uuid) -> DbResult<Option<String>> {
-        let result: Option<String> = sqlx::query_scalar("SELECT plan_id FROM users WHERE id = $1")
-            .bind(user_id)
-            .fetch_optional(&self.pool)
+    pub async fn prune_webhook_deliveries(&self, cutoff: DateTime<Utc>) -> DbResult<u64> {
+        let result = sqlx::query("DELETE FROM webhook_deliveries WHERE created_at < $1")
+            .bind(cutoff)
+            .execute(&self.pool)
             .await?;
-        Ok(result)
+        Ok(result.rows_affected())
     }
 
-    #[builder]
-    pub async fn update_plan_by_stripe_customer<'e, E>(
-        &self,
-        executor: E,
-        stripe_customer_id: &str,
-        plan_id: &str,
-    ) -> DbResult<()>
-    where
-        E: sqlx::Executor<'e, Database = sqlx::Postgres>,
-    {
-        sqlx::query(
+    // --- authz role assignments -------------------------------------------
+
+    /// Every runtime role assignment, oldest first so reloads apply them
+    /// in a stable order.
+    pub async fn list_role_assignments(&self) -> DbResult<Vec<RoleAssignment>> {
+        let assignments = sqlx::query_as::<_, RoleAssignment>(
             r#"
-            UPDATE users
-            SET plan_id = $2
-            WHERE stripe_customer_id = $1
+            SELECT id, subject, role, created_at
+            FROM authz_role_assignments
+            ORDER BY created_at, id
             "#,
         )
-        .bind(stripe_customer_id)
-        .bind(plan_id)
-        .execute(executor)
+        .fetch_all(&self.pool)
         .await?;
 
-        Ok(())
+        Ok(assignments)
     }
 
+    /// Grant `role` to `subject`. Fails with
+    /// [`DbError::UniqueViolation`] if the pair already exists.
     #[builder]
-    pub async fn resolve_plan_for_stripe_price<'e, E>(
+    pub async fn create_role_assignment(
         &self,
-        executor: E,
-        price_id: &str,
-    ) -> DbResult<Option<String>>
-    where
-        E: sqlx::Executor<'e, Database = sqlx::Postgres>,
-    {
-        let result: Option<String> =
-            sqlx::query_scalar("SELECT plan_id FROM plan_prices WHERE stripe_price_id = $1")
-                .bind(price_id)
-                .fetch_optional(executor)
-                .await?;
-        Ok(result)
+        subject: &str,
+        role: &str,
+    ) -> DbResult<RoleAssignment> {
+        let assignment = sqlx::query_as::<_, RoleAssignment>(
+            r#"
+            INSERT INTO authz_role_assignments (id, subject, role)
+            VALUES ($1, $2, $3)
+            RETURNING id, subject, role, created_at
+            "#,
+        )
+        .bind(Uuid::now_v7())
+        .bind(subject)
+        .bind(role)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(assignment)
+    }
+
+    /// Remove one role assignment.
+    pub async fn delete_role_assignment(&self, id: Uuid) -> DbResult<()> {
+        let result = sqlx::query("DELETE FROM authz_role_assignments WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(DbError::NotFound {
+                entity: "role_assignment",
+                id: Some(id.to_string()),
+            });
+        }
+        Ok(())
     }
 
+    // --- impersonation ----------------------------------------------------
+
+    /// Record that `actor_id` is about to act as `user_id`. `id` becomes
+    /// the `jti` of the token issued for the session.
     #[builder]
-    pub async fn record_token_usage(
+    pub async fn create_impersonation_session(
         &self,
+        id: Uuid,
+        actor_id: Uuid,
         user_id: Uuid,
-        thread_id: Uuid,
-        message_id: Uuid,
-        input_tokens: i64,
-        output_tokens: i64,
-        reasoning_tokens: Option<i64>,
-        cache_creation_tokens: Option<i64>,
-        cache_read_tokens: Option<i64>,
-    ) -> DbResult<TokenUsage> {
-        let record = sqlx::query_as::<_, TokenUsage>(
+        reason: &str,
+        expires_at: DateTime<Utc>,
+    ) -> DbResult<ImpersonationSession> {
+        let session = sqlx::query_as::<_, ImpersonationSession>(
             r#"
-            INSERT INTO token_usage (
-                user_id, thread_id, message_id,
-                input_tokens, output_tokens, reasoning_tokens,
-                cache_creation_tokens, cache_read_tokens
-            )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-            RETURNING id, user_id, thread_id, message_id,
-                      input_tokens, output_tokens, reasoning_tokens,
-                      cache_creation_tokens, cache_read_tokens, created_at
+            INSERT INTO impersonation_sessions (id, actor_id, user_id, reason, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, actor_id, user_id, reason, expires_at, created_at
             "#,
         )
+        .bind(id)
+        .bind(actor_id)
         .bind(user_id)
-        .bind(thread_id)
-        .bind(message_id)
-        .bind(input_tokens)
-        .bind(output_tokens)
-        .bind(reasoning_tokens.unwrap_or(0))
-        .bind(cache_creation_tokens.unwrap_or(0))
-        .bind(cache_read_tokens.unwrap_or(0))
+        .bind(reason)
+        .bind(expires_at)
         .fetch_one(&self.pool)
         .await?;
 
-        Ok(record)
+        Ok(session)
     }
 
-    #[builder]
-    pub async fn get_token_limit_and_usage(
+    /// Every impersonation of `user_id`, newest first.
+    pub async fn list_impersonation_sessions(
         &self,
         user_id: Uuid,
-        year_month: i32,
-    ) -> DbResult<(i64, i64)> {
-        let row: Option<(i64, Option<i64>)> = sqlx::query_as(
+    ) -> DbResult<Vec<ImpersonationSession>> {
+        let sessions = sqlx::query_as::<_, ImpersonationSession>(
             r#"
-            SELECT p.monthly_token_limit,
-                   mtt.total_tokens
-            FROM users u
-            JOIN plans p ON p.id = u.plan_id
-            LEFT JOIN monthly_token_totals mtt
-                   ON mtt.user_id = u.id
-                  AND mtt.year_month = $2
-            WHERE u.id = $1
+            SELECT id, actor_id, user_id, reason, expires_at, created_at
+            FROM impersonation_sessions
+            WHERE user_id = $1
+            ORDER BY created_at DESC, id DESC
             "#,
         )
         .bind(user_id)
-        .bind(year_month)
-        .fetch_optional(&self.pool)
+        .fetch_all(&self.pool)
         .await?;
 
-        match row {
-            Some((limit, used)) => Ok((limit, used.unwrap_or(0))),
-            None => Ok((DEFAULT_TOKEN_LIMIT, 0)),
-        }
+        Ok(sessions)
     }
 
+    /// Append one request to a session's audit trail.
     #[builder]
-    pub async fn consume_login_token_and_create_refresh_token(
+    pub async fn record_impersonation_request(
         &self,
-        login_token_hash: &[u8],
-        user_id: Uuid,
-        refresh_token_hash: Vec<u8>,
-        refresh_token_expires_at: DateTime<Utc>,
+        session_id: Uuid,
+        method: &str,
+        path: &str,
+        status: i16,
     ) -> DbResult<()> {
-        let now = Utc::now();
-        let mut tx = self.pool.begin().await?;
-
         sqlx::query(
             r#"
-            UPDATE login_tokens
-            SET consumed = true, updated_at = $2
-            WHERE token_hash = $1 AND consumed = false AND expires_at > now()
+            INSERT INTO impersonation_requests (session_id, method, path, status)
+            VALUES ($1, $2, $3, $4)
             "#,
         )
-        .bind(login_token_hash)
-        .bind(now)
-        .execute(&mut *tx)
+        .bind(session_id)
+        .bind(method)
+        .bind(path)
+        .bind(status)
+        .execute(&self.pool)
         .await?;
 
-        sqlx::query(
+        Ok(())
+    }
+
+    /// Every request made while impersonating `user_id`, oldest first.
+    pub async fn list_impersonation_requests(
+        &self,
+        user_id: Uuid,
+    ) -> DbResult<Vec<ImpersonationRequest>> {
+        let requests = sqlx::query_as::<_, ImpersonationRequest>(
             r#"
-            INSERT INTO refresh_tokens (id, user_id, token_hash, expires_at, revoked, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            SELECT r.id, r.session_id, r.method, r.path, r.status, r.created_at
+            FROM impersonation_requests r
+            JOIN impersonation_sessions s ON s.id = r.session_id
+            WHERE s.user_id = $1
+            ORDER BY r.created_at, r.id
             "#,
         )
-        .bind(Uuid::now_v7())
         .bind(user_id)
-        .bind(&refresh_token_hash)
-        .bind(refresh_token_expires_at)
-        .bind(false)
-        .bind(now)
-        .bind(now)
-        .execute(&mut *tx)
+        .fetch_all(&self.pool)
         .await?;
 
-        tx.commit().await?;
+        Ok(requests)
+    }
+
+    /// Star or unstar one of the user's threads. Starred threads, and the
+    /// assets attached to their messages, never expire. Like any thread
+    /// update this bumps `updated_at`, so an unstarred thread gets a full
+    /// retention period before it expires.
+    #[builder]
+    pub async fn set_thread_starred(&self, id: Uuid, user_id: Uuid, starred: bool) -> DbResult<()> {
+        let result = sqlx::query(
+            r#"
+            UPDATE threads
+            SET starred_at = CASE WHEN $3 THEN COALESCE(starred_at, now()) END
+            WHERE id = $1 AND user_id = $2
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(starred)
+        .execute(&self.pool)
+        .await?;
 
+        if result.rows_affected() == 0 {
+            return Err(DbError::NotFound {
+                entity: "thread",
+                id: Some(id.to_string()),
+            });
+        }
         Ok(())
     }
 
-    pub async fn search_messages(
+    // --- legal holds ------------------------------------------------------
+
+    /// Put a thread or asset under legal hold, or release it, and record
+    /// who did it and why, in one transaction. Setting a hold that is
+    /// already set keeps its original time; the event is written either
+    /// way. Held items are skipped by the retention sweep and can't be
+    /// deleted by their owner.
+    #[builder]
+    pub async fn set_legal_hold(
         &self,
-        user_id: Uuid,
-        query: &str,
-        limit: i64,
-        offset: i64,
-    ) -> DbResult<Vec<SearchResultMessage>> {
-        let tsquery = match build_prefix_tsquery(query) {
-            Some(q) => q,
-            None => return Ok(vec![]),
-        };
-        let ilike_pattern = format!("%{}%", query.replace('%', r"\%").replace('_', r"\_"));
-        let regex_pattern = regex_escape(query);
-        let results = sqlx::query_as::<_, SearchResultMessage>(
-            r#"
-            WITH matched AS (
-                SELECT id, thread_id, message_type,
-                       extract_content_text(content) AS content_text,
-                       search_tsv @@ to_tsquery('english', $1) AS fts_match,
-                       CASE WHEN search_tsv @@ to_tsquery('english', $1)
-                            THEN ts_rank(search_tsv, to_tsquery('english', $1))
-                            ELSE 0.0
-                       END AS rank,
-                       created_at
-                FROM messages
-                WHERE user_id = $2
-                  AND (search_tsv @@ to_tsquery('english', $1) OR extract_content_text(content) ILIKE $5)
-                ORDER BY rank DESC, created_at DESC
-                LIMIT $3 OFFSET $4
-            )
-            SELECT id, thread_id, message_type,
-                   regexp_replace(
-                       substring(content_text from GREATEST(1, position(lower($7) in lower(content_text)) - 80) for 200),
-                       $6, '<mark>\&</mark>', 'gi'
-                   ) AS snippet,
-                   rank, created_at
-            FROM matched
-            "#,
-        )
-        .bind(&tsquery)       // $1
-        .bind(user_id)        // $2
-        .bind(limit)          // $3
-        .bind(offset)         // $4
-        .bind(&ilike_pattern) // $5
-        .bind(&regex_pattern) // $6
-        .bind(query)          // $7
-        .fetch_all(&self.pool)
+        subject: LegalHoldSubject,
+        subject_id: Uuid,
+        held: bool,
+        actor_id: Uuid,
+        reason: &str,
+    ) -> DbResult<LegalHoldEvent> {
+        let mut tx = self.pool.begin().await?;
+
+        let query = format!(
+            "UPDATE {} \
+             SET legal_hold_at = CASE WHEN $2 THEN COALESCE(legal_hold_at, now()) END \
+             WHERE id = $1",
+            subject.table()
+        );
+        let result = sqlx::query(&query)
+            .bind(subject_id)
+            .bind(held)
+            .execute(&mut *tx)
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(DbError::not_found_with_id(
+                subject.as_str(),
+                subject_id.to_string(),
+            ));
+        }
+
+        let event = sqlx::query_as::<_, LegalHoldEvent>(
+            r#"
+            INSERT INTO legal_hold_events (id, actor_id, subject_kind, subject_id, held, reason)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, actor_id, subject_kind, subject_id, held, reason, created_at
+            "#,
+        )
+        .bind(Uuid::now_v7())
+        .bind(actor_id)
+        .bind(subject.as_str())
+        .bind(subject_id)
+        .bind(held)
+        .bind(reason)
+        .fetch_one(&mut *tx)
         .await?;
 
-        Ok(results)
+        tx.commit().await?;
+        Ok(event)
     }
 
-    pub async fn search_threads(
+    /// The legal hold audit trail for one thread or asset, oldest first.
+    /// Kept after the item itself is gone.
+    #[builder]
+    pub async fn list_legal_hold_events(
         &self,
-        user_id: Uuid,
-        query: &str,
-        limit: i64,
-        offset: i64,
-    ) -> DbResult<Vec<SearchResultThread>> {
-        let tsquery = match build_prefix_tsquery(query) {
-            Some(q) => q,
-            None => return Ok(vec![]),
-        };
-        let ilike_pattern = format!("%{}%", query.replace('%', r"\%").replace('_', r"\_"));
-        let results = sqlx::query_as::<_, SearchResultThread>(
-            r#"
-            SELECT id, title,
-                   GREATEST(
-                       CASE WHEN to_tsvector('english', immutable_unaccent(coalesce(title, ''))) @@ to_tsquery('english', $1)
-                            THEN ts_rank(to_tsvector('english', immutable_unaccent(coalesce(title, ''))), to_tsquery('english', $1))
-                            ELSE 0.0
-                       END,
-                       CASE WHEN similarity(coalesce(title, ''), $5) > 0.1
-                            THEN similarity(coalesce(title, ''), $5)
-                            ELSE 0.0
-                       END
-                   ) AS rank,
-                   updated_at
-            FROM threads
-            WHERE user_id = $2
-              AND (
-                  to_tsvector('english', immutable_unaccent(coalesce(title, ''))) @@ to_tsquery('english', $1)
-                  OR coalesce(title, '') ILIKE $6
-              )
-            ORDER BY rank DESC, updated_at DESC
-            LIMIT $3 OFFSET $4
+        subject: LegalHoldSubject,
+        subject_id: Uuid,
+    ) -> DbResult<Vec<LegalHoldEvent>> {
+        let events = sqlx::query_as::<_, LegalHoldEvent>(
+            r#"
+            SELECT id, actor_id, subject_kind, subject_id, held, reason, created_at
+            FROM legal_hold_events
+            WHERE subject_kind = $1 AND subject_id = $2
+            ORDER BY created_at, id
             "#,
         )
-        .bind(&tsquery)
-        .bind(user_id)
-        .bind(limit)
-        .bind(offset)
-        .bind(query)
-        .bind(&ilike_pattern)
+        .bind(subject.as_str())
+        .bind(subject_id)
         .fetch_all(&self.pool)
         .await?;
 
-        Ok(results)
+        Ok(events)
     }
 
-    // --- user_settings ----------------------------------------------------
-
+    /// The ids in `ids` that are under legal hold.
     #[builder]
-    pub async fn get_user_settings(&self, user_id: Uuid) -> DbResult<Option<UserSettingsRow>> {
-        let row = sqlx::query_as::<_, UserSettingsRow>(
-            r#"
-            SELECT user_id, schema_version, settings, created_at, updated_at
-            FROM user_settings
-            WHERE user_id = $1
-            "#,
+    pub async fn list_held_assets(&self, ids: &[Uuid]) -> DbResult<Vec<Uuid>> {
+        let held = sqlx::query_scalar::<_, Uuid>(
+            "SELECT id FROM assets WHERE id = ANY($1) AND legal_hold_at IS NOT NULL",
         )
-        .bind(user_id)
-        .fetch_optional(&self.pool)
+        .bind(ids)
+        .fetch_all(&self.pool)
         .await?;
 
-        Ok(row)
+        Ok(held)
     }
 
-    /// Insert or update the user's settings blob with optimistic concurrency.
-    ///
-    /// Contract over `(current_row, base_updated_at)`:
-    ///
-    /// | current_row    | base_updated_at                          | outcome                         |
-    /// |----------------|------------------------------------------|---------------------------------|
-    /// | `None`         | `None`                                   | `Inserted` (fresh first run)    |
-    /// | `None`         | `Some(_)`                                | `Inserted` (recovery insert)    |
-    /// | `Some(row)`    | `None`                                   | `Conflict { current: row }`     |
-    /// | `Some(row)`    | `Some(t)` where `t != row.updated_at`    | `Conflict { current: row }`     |
-    /// | `Some(_)`      | `Some(t)` where `t == row.updated_at`    | `Updated`                       |
-    ///
-    /// The `(None, Some(_))` case is a recovery path: the client thought it
-    /// was updating a row that has since been deleted server-side. Rather
-    /// than fail, we insert and return the fresh row.
-    ///
-    /// Locking: a single transaction wraps `SELECT ... FOR UPDATE` and the
-    /// subsequent write, so two concurrent callers serialize cleanly.
+    // --- devices ----------------------------------------------------------
+
+    /// Register the user's device `id`, or rename it and mark it seen when
+    /// it already is. Fails with `NotFound` when `id` is another user's
+    /// device, so one account can't take over another's.
     #[builder]
-    pub async fn upsert_user_settings(
+    pub async fn upsert_device(
         &self,
+        id: Uuid,
         user_id: Uuid,
-        schema_version: i32,
-        settings: serde_json::Value,
-        base_updated_at: Option<DateTime<Utc>>,
-    ) -> DbResult<UpsertOutcome> {
-        let mut tx = self.pool.begin().await?;
-
-        let current = sqlx::query_as::<_, UserSettingsRow>(
+        name: &str,
+        platform: &str,
+    ) -> DbResult<Device> {
+        let device = sqlx::query_as::<_, Device>(
             r#"
-            SELECT user_id, schema_version, settings, created_at, updated_at
-            FROM user_settings
-            WHERE user_id = $1
-            FOR UPDATE
+            INSERT INTO devices (id, user_id, name, platform)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (id) DO UPDATE
+                SET name         = EXCLUDED.name,
+                    platform     = EXCLUDED.platform,
+                    last_seen_at = now()
+                WHERE devices.user_id = EXCLUDED.user_id
+            RETURNING id, user_id, name, platform, last_seen_at, created_at, updated_at
             "#,
         )
+        .bind(id)
         .bind(user_id)
-        .fetch_optional(&mut *tx)
+        .bind(name)
+        .bind(platform)
+        .fetch_optional(&self.pool)
         .await?;
 
-        let outcome = match (current, base_updated_at) {
-            (Some(row), None) => UpsertOutcome::Conflict { current: row },
-            (Some(row), Some(base)) if row.updated_at != base => {
-                UpsertOutcome::Conflict { current: row }
-            }
-            (Some(_), Some(_)) => {
-                let updated = sqlx::query_as::<_, UserSettingsRow>(
+        device.ok_or_else(|| DbError::not_found_with_id("device", id.to_string()))
+    }
+
+    /// Every device of `user_id`, most recently seen first.
+    #[builder]
+    pub async fn list_devices(&self, user_id: Uuid) -> DbResult<Vec<Device>> {
+        let devices = self
+            .read(|pool| {
+                sqlx::query_as::<_, Device>(
                     r#"
-                    UPDATE user_settings
-                    SET schema_version = $2, settings = $3
+                    SELECT id, user_id, name, platform, last_seen_at, created_at, updated_at
+                    FROM devices
                     WHERE user_id = $1
-                    RETURNING user_id, schema_version, settings, created_at, updated_at
-                    "#,
-                )
-                .bind(user_id)
-                .bind(schema_version)
-                .bind(&settings)
-                .fetch_one(&mut *tx)
-                .await?;
-                UpsertOutcome::Updated(updated)
-            }
-            (None, _) => {
-                let inserted = sqlx::query_as::<_, UserSettingsRow>(
-                    r#"
-                    INSERT INTO user_settings (user_id, schema_version, settings)
-                    VALUES ($1, $2, $3)
-                    RETURNING user_id, schema_version, settings, created_at, updated_at
+                    ORDER BY last_seen_at DESC, id
                     "#,
                 )
                 .bind(user_id)
-                .bind(schema_version)
-                .bind(&settings)
-                .fetch_one(&mut *tx)
-                .await?;
-                UpsertOutcome::Inserted(inserted)
-            }
-        };
+                .fetch_all(pool)
+            })
+            .await?;
 
-        tx.commit().await?;
-        Ok(outcome)
+        Ok(devices)
     }
 
+    /// Forget one of the user's devices. Its sessions and assets stay,
+    /// untagged.
     #[builder]
-    pub async fn delete_user_settings(&self, user_id: Uuid) -> DbResult<()> {
-        sqlx::query(r#"DELETE FROM user_settings WHERE user_id = $1"#)
+    pub async fn delete_device(&self, id: Uuid, user_id: Uuid) -> DbResult<()> {
+        let result = sqlx::query("DELETE FROM devices WHERE id = $1 AND user_id = $2")
+            .bind(id)
             .bind(user_id)
             .execute(&self.pool)
             .await?;
 
+        if result.rows_affected() == 0 {
+            return Err(DbError::not_found_with_id("device", id.to_string()));
+        }
         Ok(())
     }
 }
+
+/// `FROM ... LIMIT` tail selecting `category` items (aliased `x`) past
+/// their owner's limit, oldest first. Binds the category as `$1`, the
+/// default limit in days as `$2` (`NULL` keeps items of users without an
+/// override), and the row limit as `$3`.
+fn expired_items_sql(category: RetentionCategory) -> String {
+    let RetentionScope {
+        table,
+        filter,
+        age_column,
+        ..
+    } = retention_scope(category);
+    // A user's override wins even when it's NULL ("forever"), so test for
+    // the row rather than COALESCE the value.
+    format!(
+        r#"
+        FROM {table} x
+        LEFT JOIN retention_settings rs ON rs.user_id = x.user_id AND rs.category = $1
+        WHERE {filter}
+          AND {age_column} < now() - make_interval(
+                days => CASE WHEN rs.user_id IS NULL THEN $2::INTEGER ELSE rs.max_age_days END)
+        ORDER BY {age_column}
+        LIMIT $3
+        "#
+    )
+}
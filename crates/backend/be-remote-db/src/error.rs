@@ -14,6 +14,11 @@ pub enum DbError {
     #[error("Referenced {entity} does not exist")]
     ForeignKeyViolation { entity: &'static str },
 
+    /// The row exists but an admin put it under legal hold, so it can't be
+    /// deleted.
+    #[error("{entity} is under legal hold")]
+    OnLegalHold { entity: &'static str },
+
     #[error("Database connection error: {0}")]
     Connection(String),
 
@@ -70,6 +75,10 @@ impl DbError {
         Self::ForeignKeyViolation { entity }
     }
 
+    pub fn on_legal_hold(entity: &'static str) -> Self {
+        Self::OnLegalHold { entity }
+    }
+
     pub fn connection(msg: impl Into<String>) -> Self {
         Self::Connection(msg.into())
     }
@@ -106,6 +115,10 @@ impl DbError {
         matches!(self, Self::NotFound { .. })
     }
 
+    pub fn is_on_legal_hold(&self) -> bool {
+        matches!(self, Self::OnLegalHold { .. })
+    }
+
     pub fn is_unique_violation(&self) -> bool {
         matches!(self, Self::UniqueViolation { .. })
     }
@@ -1,12 +1,39 @@
 pub mod db;
 pub mod error;
+pub mod listener;
+pub mod pool;
 pub mod types;
 
 pub use db::DatabaseManager;
 pub use error::{DbError, DbResult};
+pub use listener::{NOTIFICATION_CHANNEL, NotificationListener};
+pub use pool::{PoolConfig, PoolHealth, PoolStats};
 pub use types::*;
 
 pub fn year_month_key(now: &chrono::DateTime<chrono::Utc>) -> i32 {
     use chrono::Datelike;
     now.year() * 100 + now.month() as i32
 }
+
+/// Remove the session that starts last, and its parent if that leaves it
+/// empty. Each group's sessions are oldest first, so it is the latest of
+/// the groups' last sessions.
+///
+/// Reading one session past a limit with
+/// [`DatabaseManager::list_activity_sessions_between`] tells whether the
+/// range was cut short; this drops that extra session again.
+pub fn drop_latest_session(groups: &mut Vec<(Activity, Vec<ActivitySession>)>) {
+    let Some(index) = groups
+        .iter()
+        .enumerate()
+        .filter_map(|(index, (_, sessions))| sessions.last().map(|s| (index, (s.started_at, s.id))))
+        .max_by_key(|&(_, key)| key)
+        .map(|(index, _)| index)
+    else {
+        return;
+    };
+    groups[index].1.pop();
+    if groups[index].1.is_empty() {
+        groups.remove(index);
+    }
+}
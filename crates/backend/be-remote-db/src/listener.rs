@@ -0,0 +1,41 @@
+//! `LISTEN` side of the notification log.
+//!
+//! [`DatabaseManager::create_notification`](crate::DatabaseManager::create_notification)
+//! announces every row it inserts on [`NOTIFICATION_CHANNEL`];
+//! [`NotificationListener`] holds the dedicated connection that receives
+//! those announcements.
+
+use sqlx::postgres::{PgListener, PgPool};
+
+use crate::error::{DbError, DbResult};
+use crate::types::Notification;
+
+/// The Postgres channel new notifications are announced on. The payload
+/// is the row as JSON.
+pub const NOTIFICATION_CHANNEL: &str = "notifications";
+
+pub struct NotificationListener {
+    listener: PgListener,
+}
+
+impl NotificationListener {
+    pub(crate) async fn connect(pool: &PgPool) -> DbResult<Self> {
+        let mut listener = PgListener::connect_with(pool).await?;
+        listener.listen(NOTIFICATION_CHANNEL).await?;
+        Ok(Self { listener })
+    }
+
+    /// Wait for the next notification.
+    ///
+    /// `Ok(None)` means the connection dropped; the next call reconnects.
+    /// Anything announced in between is missed, so callers should catch up
+    /// from the table.
+    pub async fn recv(&mut self) -> DbResult<Option<Notification>> {
+        let Some(message) = self.listener.try_recv().await? else {
+            return Ok(None);
+        };
+        serde_json::from_str(message.payload())
+            .map(Some)
+            .map_err(|e| DbError::Encoding(format!("notification payload: {e}")))
+    }
+}
@@ -0,0 +1,72 @@
+-- Scheduled automations: recurring agent tasks a user defines once
+-- ("every morning summarize yesterday's activity") and the thread
+-- service's scheduler runs on their behalf.
+--
+-- * `automations` holds the definition. `schedule` is a five-field cron
+--   expression evaluated in UTC; `next_run_at` is maintained by the
+--   scheduler and doubles as a lease while a run is in flight (see
+--   `claim_due_automations`). A NULL `next_run_at` means the schedule has
+--   no future occurrence and the automation won't fire again until it's
+--   edited or triggered by hand.
+-- * `automation_runs` is the run history. Each run gets its own thread so
+--   the result can be opened and continued like any other chat; deleting
+--   that thread keeps the run row and just clears the link.
+
+CREATE TYPE automation_run_status AS ENUM ('running', 'succeeded', 'failed', 'skipped');
+
+CREATE TABLE automations (
+    id              UUID PRIMARY KEY,
+    user_id         UUID NOT NULL,
+    name            VARCHAR(200) NOT NULL,
+    prompt          TEXT NOT NULL,
+    schedule        VARCHAR(200) NOT NULL,
+    tools           TEXT[] NOT NULL DEFAULT '{}',
+    enabled         BOOLEAN NOT NULL DEFAULT TRUE,
+    next_run_at     TIMESTAMP WITH TIME ZONE,
+    last_run_at     TIMESTAMP WITH TIME ZONE,
+    created_at      TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+    updated_at      TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+
+    CONSTRAINT fk_automations_user_id
+        FOREIGN KEY (user_id)
+        REFERENCES users(id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX idx_automations_user_id ON automations (user_id);
+CREATE INDEX idx_automations_due ON automations (next_run_at) WHERE enabled;
+
+CREATE TABLE automation_runs (
+    id              UUID PRIMARY KEY,
+    automation_id   UUID NOT NULL,
+    user_id         UUID NOT NULL,
+    thread_id       UUID,
+    status          automation_run_status NOT NULL DEFAULT 'running',
+    scheduled_for   TIMESTAMP WITH TIME ZONE NOT NULL,
+    started_at      TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+    finished_at     TIMESTAMP WITH TIME ZONE,
+    output          TEXT,
+    error           TEXT,
+
+    CONSTRAINT fk_automation_runs_automation_id
+        FOREIGN KEY (automation_id)
+        REFERENCES automations(id)
+        ON DELETE CASCADE,
+
+    CONSTRAINT fk_automation_runs_user_id
+        FOREIGN KEY (user_id)
+        REFERENCES users(id)
+        ON DELETE CASCADE,
+
+    CONSTRAINT fk_automation_runs_thread_id
+        FOREIGN KEY (thread_id)
+        REFERENCES threads(id)
+        ON DELETE SET NULL
+);
+
+CREATE INDEX idx_automation_runs_automation_started ON automation_runs (automation_id, started_at DESC);
+
+CREATE TRIGGER update_automations_updated_at
+    BEFORE UPDATE ON automations
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
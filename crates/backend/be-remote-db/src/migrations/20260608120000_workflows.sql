@@ -0,0 +1,30 @@
+-- Saved workflows: user-authored multi-step chains (prompt, tool, and
+-- condition steps) that the thread service runs on demand.
+--
+-- `definition` is the portable JSON form of `agent_graph::workflow::
+-- WorkflowDefinition`. It's stored as-is so a definition exported from one
+-- account imports into another unchanged; the thread service validates it
+-- on every write. `name` and `description` are copied out of it for
+-- listing without parsing the document.
+
+CREATE TABLE workflows (
+    id              UUID PRIMARY KEY,
+    user_id         UUID NOT NULL,
+    name            VARCHAR(200) NOT NULL,
+    description     TEXT NOT NULL DEFAULT '',
+    definition      JSONB NOT NULL,
+    created_at      TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+    updated_at      TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+
+    CONSTRAINT fk_workflows_user_id
+        FOREIGN KEY (user_id)
+        REFERENCES users(id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX idx_workflows_user_id ON workflows (user_id);
+
+CREATE TRIGGER update_workflows_updated_at
+    BEFORE UPDATE ON workflows
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
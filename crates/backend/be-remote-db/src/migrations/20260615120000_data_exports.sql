@@ -0,0 +1,50 @@
+-- Personal data exports ("takeout"): a user asks for a copy of everything
+-- the account holds, a background worker in the export service bundles it
+-- into a zip archive, and the archive stays downloadable until
+-- `expires_at`, after which the worker deletes it and marks the row
+-- `expired`. Rows are kept after expiry as a record of the request.
+--
+-- * `asset_mode` records whether asset bytes go into the archive
+--   decrypted or exactly as stored.
+-- * `lease_until` is set while a worker holds the export (see
+--   `claim_pending_data_exports`); a worker that dies mid-build leaves the
+--   export claimable again once the lease lapses. `attempts` counts
+--   claims so a build that keeps crashing is eventually given up on.
+-- * The partial unique index allows one in-flight export per user.
+
+CREATE TYPE data_export_status AS ENUM ('pending', 'running', 'ready', 'failed', 'expired');
+CREATE TYPE data_export_asset_mode AS ENUM ('decrypted', 'as_stored');
+
+CREATE TABLE data_exports (
+    id              UUID PRIMARY KEY,
+    user_id         UUID NOT NULL,
+    status          data_export_status NOT NULL DEFAULT 'pending',
+    asset_mode      data_export_asset_mode NOT NULL,
+    storage_uri     TEXT,
+    size_bytes      BIGINT,
+    error           TEXT,
+    attempts        INTEGER NOT NULL DEFAULT 0,
+    lease_until     TIMESTAMP WITH TIME ZONE,
+    started_at      TIMESTAMP WITH TIME ZONE,
+    completed_at    TIMESTAMP WITH TIME ZONE,
+    expires_at      TIMESTAMP WITH TIME ZONE,
+    created_at      TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+    updated_at      TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+
+    CONSTRAINT fk_data_exports_user_id
+        FOREIGN KEY (user_id)
+        REFERENCES users(id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX idx_data_exports_user_created ON data_exports (user_id, created_at DESC);
+CREATE UNIQUE INDEX uq_data_exports_in_flight ON data_exports (user_id)
+    WHERE status IN ('pending', 'running');
+CREATE INDEX idx_data_exports_queue ON data_exports (created_at)
+    WHERE status IN ('pending', 'running');
+CREATE INDEX idx_data_exports_expiry ON data_exports (expires_at) WHERE status = 'ready';
+
+CREATE TRIGGER update_data_exports_updated_at
+    BEFORE UPDATE ON data_exports
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,57 @@
+-- Account deletion: a user asks for their account to be deleted, the
+-- account is locked straight away (sessions revoked, no new sign-ins), and
+-- once `scheduled_for` passes a background worker erases everything the
+-- account holds and emails a confirmation.
+--
+-- * `user_id` deliberately has no foreign key: the row outlives the user
+--   as the record that the deletion happened.
+-- * `email` is kept only to send the confirmation and is cleared when the
+--   data is erased.
+-- * `lease_until` is set while a worker holds the deletion (see
+--   `claim_due_account_deletions`); a worker that dies or fails part-way
+--   leaves the deletion claimable again once the lease lapses. Every step
+--   is idempotent, so a retry simply picks up where the last one stopped.
+-- * The partial unique index allows one open deletion per user; its
+--   existence is what locks the account.
+-- * `account_deletion_events` is the audit trail: one row per step.
+
+CREATE TYPE account_deletion_status AS ENUM ('scheduled', 'running', 'completed');
+
+CREATE TABLE account_deletions (
+    id              UUID PRIMARY KEY,
+    user_id         UUID NOT NULL,
+    email           TEXT,
+    status          account_deletion_status NOT NULL DEFAULT 'scheduled',
+    scheduled_for   TIMESTAMP WITH TIME ZONE NOT NULL,
+    attempts        INTEGER NOT NULL DEFAULT 0,
+    lease_until     TIMESTAMP WITH TIME ZONE,
+    error           TEXT,
+    completed_at    TIMESTAMP WITH TIME ZONE,
+    created_at      TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+    updated_at      TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now()
+);
+
+CREATE UNIQUE INDEX uq_account_deletions_open ON account_deletions (user_id)
+    WHERE status IN ('scheduled', 'running');
+CREATE INDEX idx_account_deletions_queue ON account_deletions (scheduled_for)
+    WHERE status IN ('scheduled', 'running');
+
+CREATE TRIGGER update_account_deletions_updated_at
+    BEFORE UPDATE ON account_deletions
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TABLE account_deletion_events (
+    id              UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    deletion_id     UUID NOT NULL,
+    kind            TEXT NOT NULL,
+    detail          JSONB NOT NULL DEFAULT '{}',
+    created_at      TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+
+    CONSTRAINT fk_account_deletion_events_deletion_id
+        FOREIGN KEY (deletion_id)
+        REFERENCES account_deletions(id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX idx_account_deletion_events_deletion ON account_deletion_events (deletion_id, created_at);
@@ -0,0 +1,38 @@
+-- Data retention: per-user limits on how long each category of captured
+-- context is kept, enforced by a background worker in the retention
+-- service.
+--
+-- * A `retention_settings` row overrides the service's default for one
+--   category. `max_age_days IS NULL` means "keep forever"; a missing row
+--   means "use the default".
+-- * `assets.pinned_at` and `threads.starred_at` exempt an item from
+--   expiry. Assets attached to a message in a starred thread are exempt
+--   too, and so are activity icons.
+
+CREATE TYPE retention_category AS ENUM ('screenshots', 'captured_text', 'activity_history', 'threads');
+
+CREATE TABLE retention_settings (
+    user_id         UUID NOT NULL,
+    category        retention_category NOT NULL,
+    max_age_days    INTEGER CHECK (max_age_days IS NULL OR max_age_days >= 1),
+    created_at      TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+    updated_at      TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (user_id, category),
+
+    CONSTRAINT fk_retention_settings_user_id
+        FOREIGN KEY (user_id)
+        REFERENCES users(id)
+        ON DELETE CASCADE
+);
+
+CREATE TRIGGER update_retention_settings_updated_at
+    BEFORE UPDATE ON retention_settings
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+ALTER TABLE assets ADD COLUMN pinned_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE threads ADD COLUMN starred_at TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX idx_assets_created_at ON assets (created_at);
+CREATE INDEX idx_activity_sessions_ended_at ON activity_sessions (ended_at) WHERE ended_at IS NOT NULL;
@@ -0,0 +1,11 @@
+-- Per-user product analytics consent, copied into every JWT the auth
+-- service mints so backend services can drop or pseudonymise events
+-- without a database round trip.
+--
+-- Existing users default to 'anonymous', which is what the backend sent
+-- before the column existed: events carry no account identifier.
+
+CREATE TYPE analytics_consent AS ENUM ('off', 'anonymous', 'identified');
+
+ALTER TABLE users
+    ADD COLUMN analytics_consent analytics_consent NOT NULL DEFAULT 'anonymous';
@@ -0,0 +1,25 @@
+-- Link captured assets (screenshots, extracted text) to the parent
+-- activity they were taken in, so the timeline can group and bulk-manage
+-- them. The flat-model `activity_assets` table was dropped with the
+-- parent/child rework in 20260526120000; this recreates it against the
+-- stable `activities` parent.
+
+CREATE TABLE activity_assets (
+    activity_id UUID NOT NULL,
+    asset_id UUID NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (activity_id, asset_id),
+
+    CONSTRAINT fk_activity_assets_activity_id
+        FOREIGN KEY (activity_id)
+        REFERENCES activities(id)
+        ON DELETE CASCADE,
+
+    CONSTRAINT fk_activity_assets_asset_id
+        FOREIGN KEY (asset_id)
+        REFERENCES assets(id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX idx_activity_assets_asset_id ON activity_assets(asset_id);
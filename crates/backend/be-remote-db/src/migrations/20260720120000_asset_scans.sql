@@ -0,0 +1,25 @@
+-- Malware scanning of uploaded assets. When a scanner is configured new
+-- assets start `pending`; the scan worker in `be-asset-scan` claims them
+-- under a lease, scans the bytes, and records the verdict. Quarantined
+-- assets stay in storage so an admin can inspect them, but the asset
+-- service refuses to serve them.
+--
+-- Existing rows, and every upload on deployments without a scanner, are
+-- `skipped`: never scanned, served as before.
+--
+-- * `scan_lease_until` is set while a worker holds the scan; a worker that
+--   dies mid-scan leaves the asset claimable again once it lapses.
+--   `scan_attempts` counts claims so an asset the scanner keeps failing on
+--   is eventually given up on.
+-- * `scan_signature` names what the scanner found in a quarantined asset.
+
+CREATE TYPE asset_scan_status AS ENUM ('pending', 'clean', 'quarantined', 'skipped');
+
+ALTER TABLE assets
+    ADD COLUMN scan_status      asset_scan_status NOT NULL DEFAULT 'skipped',
+    ADD COLUMN scan_signature   TEXT,
+    ADD COLUMN scan_attempts    INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN scan_lease_until TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN scanned_at       TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX idx_assets_scan_queue ON assets (created_at) WHERE scan_status = 'pending';
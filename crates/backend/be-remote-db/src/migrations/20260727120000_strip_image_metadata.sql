@@ -0,0 +1,7 @@
+-- Per-user switch for stripping EXIF / XMP / text metadata from uploaded
+-- images. Photos carry GPS positions and camera serials, and screenshots
+-- often carry XMP, so stripping is on unless the user opts out (for
+-- example to keep capture timestamps in photos they upload on purpose).
+
+ALTER TABLE users
+    ADD COLUMN strip_image_metadata BOOLEAN NOT NULL DEFAULT true;
@@ -0,0 +1,25 @@
+-- Server-side events pushed to connected desktop clients over the
+-- notification WebSocket (`be-notification-service`).
+--
+-- Rows are the durable log a reconnecting client backfills from: it sends
+-- the id of the last notification it saw and gets everything newer. Each
+-- insert is also announced on the `notifications` channel with
+-- `pg_notify`, so every monolith replica can fan it out to the sockets it
+-- holds; the notify is only delivered once the inserting transaction
+-- commits.
+--
+-- * `user_id` is NULL for broadcasts every user receives (e.g. a forced
+--   update).
+-- * `payload` is the `notification-core::NotificationEvent` JSON.
+-- * Rows are short-lived; the notification service prunes them after a
+--   week.
+
+CREATE TABLE notifications (
+    id         BIGSERIAL PRIMARY KEY,
+    user_id    UUID REFERENCES users(id) ON DELETE CASCADE,
+    payload    JSONB NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_notifications_user_id ON notifications (user_id, id);
+CREATE INDEX idx_notifications_created_at ON notifications (created_at);
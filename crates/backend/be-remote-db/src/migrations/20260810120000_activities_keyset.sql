@@ -0,0 +1,7 @@
+-- `GET /activities/stream` pages by `(last_used_at, id)` rather than by
+-- offset. Extend the rail's recency index with the `id` tiebreaker so
+-- each batch is a single index range scan.
+
+DROP INDEX idx_activities_user_last_used;
+
+CREATE INDEX idx_activities_user_last_used ON activities (user_id, last_used_at DESC, id DESC);
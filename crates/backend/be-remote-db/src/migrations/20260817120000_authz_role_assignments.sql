@@ -0,0 +1,18 @@
+-- Role assignments layered on top of `config/authz/policy.csv`.
+--
+-- Each row becomes a Casbin grouping rule `g, subject, role` when the
+-- enforcer (re)loads, so permissions can change without a monolith
+-- restart. `subject` is either a user id, granting that user the role on
+-- top of the one in their token, or another role name, making it inherit
+-- `role`'s permissions.
+--
+-- Managed through the `/admin/authz` API, which itself requires the
+-- `Admin` role; seed the first admin with a direct insert.
+
+CREATE TABLE authz_role_assignments (
+    id         UUID PRIMARY KEY,
+    subject    TEXT NOT NULL,
+    role       TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT authz_role_assignments_subject_role_key UNIQUE (subject, role)
+);
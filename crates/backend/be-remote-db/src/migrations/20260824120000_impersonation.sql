@@ -0,0 +1,41 @@
+-- Support impersonation: an admin mints a short-lived access token for a
+-- user (`POST /admin/auth/impersonate`) to debug an issue from their side.
+--
+-- * `impersonation_sessions.id` is the token's `jti`, which ties every
+--   request the token makes back to the session.
+-- * `actor_id` deliberately has no foreign key: the record must outlive
+--   the staff member's account. The rows go with the impersonated user's.
+-- * `impersonation_requests` is the per-request audit trail, written by
+--   `authz_middleware` and shown to the user at `GET /auth/impersonations`.
+
+CREATE TABLE impersonation_sessions (
+    id          UUID PRIMARY KEY,
+    actor_id    UUID NOT NULL,
+    user_id     UUID NOT NULL,
+    reason      TEXT NOT NULL,
+    expires_at  TIMESTAMP WITH TIME ZONE NOT NULL,
+    created_at  TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+
+    CONSTRAINT fk_impersonation_sessions_user_id
+        FOREIGN KEY (user_id)
+        REFERENCES users(id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX idx_impersonation_sessions_user ON impersonation_sessions (user_id, created_at DESC);
+
+CREATE TABLE impersonation_requests (
+    id          UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    session_id  UUID NOT NULL,
+    method      TEXT NOT NULL,
+    path        TEXT NOT NULL,
+    status      SMALLINT NOT NULL,
+    created_at  TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+
+    CONSTRAINT fk_impersonation_requests_session_id
+        FOREIGN KEY (session_id)
+        REFERENCES impersonation_sessions(id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX idx_impersonation_requests_session ON impersonation_requests (session_id, created_at);
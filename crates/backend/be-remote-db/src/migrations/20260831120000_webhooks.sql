@@ -0,0 +1,70 @@
+-- Outbound webhooks: a user registers an HTTPS endpoint and the event
+-- types it wants (`thread.created`, `export.finished`, `automation.run`),
+-- and the webhook service POSTs each matching event to it, signed with
+-- the endpoint's secret.
+--
+-- * `enqueue_webhook_event` fans an event out into one
+--   `webhook_deliveries` row per subscribed, enabled endpoint. Publishers
+--   can call it inside their own transaction so the event only goes out if
+--   it commits.
+-- * A delivery is due while `status = 'pending'` and `next_attempt_at` has
+--   passed. Claiming it pushes `next_attempt_at` out by a lease, so a
+--   worker that dies mid-request leaves it claimable again; a failed
+--   attempt pushes it out by the retry backoff instead.
+-- * The delivery rows double as the delivery log shown to the user, and
+--   are pruned after 30 days.
+
+CREATE TYPE webhook_delivery_status AS ENUM ('pending', 'succeeded', 'failed');
+
+CREATE TABLE webhook_endpoints (
+    id              UUID PRIMARY KEY,
+    user_id         UUID NOT NULL,
+    url             TEXT NOT NULL,
+    secret          TEXT NOT NULL,
+    event_types     TEXT[] NOT NULL,
+    enabled         BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at      TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+    updated_at      TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+
+    CONSTRAINT fk_webhook_endpoints_user_id
+        FOREIGN KEY (user_id)
+        REFERENCES users(id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX idx_webhook_endpoints_user ON webhook_endpoints (user_id, created_at);
+
+CREATE TRIGGER update_webhook_endpoints_updated_at
+    BEFORE UPDATE ON webhook_endpoints
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TABLE webhook_deliveries (
+    id              UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    endpoint_id     UUID NOT NULL,
+    event_type      TEXT NOT NULL,
+    payload         JSONB NOT NULL,
+    status          webhook_delivery_status NOT NULL DEFAULT 'pending',
+    attempts        INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+    response_status SMALLINT,
+    error           TEXT,
+    delivered_at    TIMESTAMP WITH TIME ZONE,
+    created_at      TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+    updated_at      TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+
+    CONSTRAINT fk_webhook_deliveries_endpoint_id
+        FOREIGN KEY (endpoint_id)
+        REFERENCES webhook_endpoints(id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX idx_webhook_deliveries_endpoint ON webhook_deliveries (endpoint_id, created_at DESC);
+CREATE INDEX idx_webhook_deliveries_queue ON webhook_deliveries (next_attempt_at)
+    WHERE status = 'pending';
+CREATE INDEX idx_webhook_deliveries_created ON webhook_deliveries (created_at);
+
+CREATE TRIGGER update_webhook_deliveries_updated_at
+    BEFORE UPDATE ON webhook_deliveries
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
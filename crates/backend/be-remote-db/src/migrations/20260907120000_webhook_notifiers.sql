@@ -0,0 +1,20 @@
+-- Chat notifiers: a webhook endpoint can be a Slack or Discord incoming
+-- webhook, which gets a formatted message (Slack blocks, Discord embeds)
+-- instead of the signed JSON envelope.
+--
+-- * `automation_ids` narrows `automation.run` and
+--   `automation.daily_summary` to the listed automations; NULL means all.
+-- * `next_summary_at` is when the endpoint's next
+--   `automation.daily_summary` is due, and is only set while it
+--   subscribes to one. The webhook worker claims due endpoints, moves it
+--   a day on, and queues a summary of the runs since the last one.
+
+CREATE TYPE webhook_endpoint_kind AS ENUM ('generic', 'slack', 'discord');
+
+ALTER TABLE webhook_endpoints
+    ADD COLUMN kind             webhook_endpoint_kind NOT NULL DEFAULT 'generic',
+    ADD COLUMN automation_ids   UUID[],
+    ADD COLUMN next_summary_at  TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX idx_webhook_endpoints_summary_due ON webhook_endpoints (next_summary_at)
+    WHERE next_summary_at IS NOT NULL;
@@ -0,0 +1,11 @@
+-- Token versions: every JWT carries the user's `token_version` from
+-- when it was minted. Revoking all of a user's sessions (sign out
+-- everywhere, account deletion, Apple revoking consent) bumps the column
+-- in the same transaction as the refresh-token revocation, and access
+-- tokens carrying an older version are refused before they expire.
+--
+-- Tokens minted before this migration carry no version and deserialize
+-- as 0, which matches every existing row until it is first bumped.
+
+ALTER TABLE users
+    ADD COLUMN token_version INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,15 @@
+-- Indexes for the auth-token cleanup worker, which deletes expired and
+-- spent rows from the token tables once an hour. Without them each sweep
+-- is a sequential scan of tables that grow with every sign-in.
+--
+-- The hash lookups themselves are already indexed
+-- (`idx_refresh_tokens_token_hash`, `idx_login_tokens_token_hash`,
+-- `idx_email_verification_tokens_hash`).
+
+CREATE INDEX idx_refresh_tokens_expires_at ON refresh_tokens (expires_at);
+
+CREATE INDEX idx_refresh_tokens_revoked_created_at
+    ON refresh_tokens (created_at) WHERE revoked = true;
+
+CREATE INDEX idx_email_verification_tokens_expires_at
+    ON email_verification_tokens (expires_at);
@@ -0,0 +1,5 @@
+-- Set when the provider rejects the stored refresh token (the user
+-- revoked access, or the grant expired). Cleared when a later sign-in
+-- stores a new refresh token.
+ALTER TABLE oauth_credentials
+    ADD COLUMN needs_reauth BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,8 @@
+-- no-transaction
+-- Add `microsoft` to the `oauth_provider` enum.
+--
+-- Same constraints as `20260511120000_oauth_provider_apple.sql`:
+-- `ALTER TYPE ... ADD VALUE` can't run inside a transaction, so this file
+-- opts out of sqlx's wrapper and must stay a single statement.
+
+ALTER TYPE oauth_provider ADD VALUE 'microsoft';
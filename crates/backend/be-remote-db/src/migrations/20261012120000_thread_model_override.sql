@@ -0,0 +1,12 @@
+-- Model a thread is pinned to, so later turns keep answering with it even
+-- after the deployment's default chat model changes. `model_provider` is
+-- a provider id from the deployment's LLM config. All NULL means the
+-- thread follows the default.
+ALTER TABLE threads
+    ADD COLUMN model_provider TEXT,
+    ADD COLUMN model_name TEXT,
+    ADD COLUMN model_temperature REAL,
+    ADD CONSTRAINT threads_model_override_complete CHECK (
+        (model_provider IS NULL) = (model_name IS NULL)
+        AND (model_temperature IS NULL OR model_name IS NOT NULL)
+    );
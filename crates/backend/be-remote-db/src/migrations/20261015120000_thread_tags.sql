@@ -0,0 +1,4 @@
+-- Short topic tags generated alongside a thread's auto-title, shown in
+-- the sidebar next to it. Empty until the first exchange settles.
+ALTER TABLE threads
+    ADD COLUMN tags TEXT[] NOT NULL DEFAULT '{}';
@@ -0,0 +1,28 @@
+-- Sidebar organisation for threads: user-defined folders, plus pinning
+-- (pinned threads list first, most recently pinned on top) and starring.
+CREATE TABLE thread_folders (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL,
+    name VARCHAR(100) NOT NULL,
+    -- Sidebar order, ascending. New folders go last.
+    position INTEGER NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+
+    CONSTRAINT fk_thread_folders_user_id
+        FOREIGN KEY (user_id)
+        REFERENCES users(id)
+        ON DELETE CASCADE,
+
+    CONSTRAINT thread_folders_user_name_unique UNIQUE (user_id, name)
+);
+
+-- Deleting a folder leaves its threads unfiled rather than deleting them.
+ALTER TABLE threads
+    ADD COLUMN pinned_at TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN starred BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN folder_id UUID REFERENCES thread_folders(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_threads_user_folder ON threads (user_id, folder_id);
+CREATE INDEX idx_threads_user_pinned ON threads (user_id, pinned_at DESC)
+    WHERE pinned_at IS NOT NULL;
@@ -0,0 +1,29 @@
+-- Long-term memory: short facts about a user ("uses Arch Linux", "their
+-- manager is Dana") extracted from their messages or added by hand, and
+-- recalled into later chats when the user has memory turned on.
+CREATE TABLE user_memories (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL,
+    -- The thread the fact was learned in; NULL for facts added by hand or
+    -- once that thread is deleted.
+    thread_id UUID,
+    content VARCHAR(300) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+
+    CONSTRAINT fk_user_memories_user_id
+        FOREIGN KEY (user_id)
+        REFERENCES users(id)
+        ON DELETE CASCADE,
+
+    CONSTRAINT fk_user_memories_thread_id
+        FOREIGN KEY (thread_id)
+        REFERENCES threads(id)
+        ON DELETE SET NULL
+);
+
+-- One copy of each fact per user, ignoring case, so re-extracting a fact
+-- the user repeats is a no-op.
+CREATE UNIQUE INDEX user_memories_user_content_unique
+    ON user_memories (user_id, lower(content));
+CREATE INDEX idx_user_memories_user_updated ON user_memories (user_id, updated_at DESC);
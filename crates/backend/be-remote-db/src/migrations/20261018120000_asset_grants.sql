@@ -0,0 +1,43 @@
+-- Read access to one asset for someone other than its owner: a named
+-- user, or whoever holds a link token. Owners revoke a grant by deleting
+-- its row; grants go with the asset and with the grantee's account.
+CREATE TABLE asset_grants (
+    id UUID PRIMARY KEY,
+    asset_id UUID NOT NULL,
+    -- The user the asset is shared with; NULL for link grants.
+    grantee_id UUID,
+    -- SHA-256 of the link token, for link grants only. The token itself
+    -- is returned once, when the link is created.
+    token_hash BYTEA,
+    -- NULL for a user grant that doesn't expire. Link grants always do.
+    expires_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+
+    CONSTRAINT fk_asset_grants_asset_id
+        FOREIGN KEY (asset_id)
+        REFERENCES assets(id)
+        ON DELETE CASCADE,
+
+    CONSTRAINT fk_asset_grants_grantee_id
+        FOREIGN KEY (grantee_id)
+        REFERENCES users(id)
+        ON DELETE CASCADE,
+
+    CONSTRAINT asset_grants_chk_one_kind
+        CHECK ((grantee_id IS NULL) <> (token_hash IS NULL)),
+    CONSTRAINT asset_grants_chk_link_expires
+        CHECK (token_hash IS NULL OR expires_at IS NOT NULL),
+    CONSTRAINT asset_grants_chk_hash_len
+        CHECK (token_hash IS NULL OR octet_length(token_hash) = 32)
+);
+
+-- One grant per asset and user; granting again replaces the expiry.
+CREATE UNIQUE INDEX asset_grants_asset_grantee_unique
+    ON asset_grants (asset_id, grantee_id)
+    WHERE grantee_id IS NOT NULL;
+CREATE UNIQUE INDEX idx_asset_grants_token_hash
+    ON asset_grants (token_hash)
+    WHERE token_hash IS NOT NULL;
+CREATE INDEX idx_asset_grants_grantee
+    ON asset_grants (grantee_id, created_at DESC)
+    WHERE grantee_id IS NOT NULL;
@@ -0,0 +1,28 @@
+-- Legal holds: an admin can freeze a thread or an asset so it survives
+-- deletion and retention purging while the hold is set.
+--
+-- * `legal_hold_at` is set while the hold is on. Held rows are skipped by
+--   the retention sweep and refused by the user-facing delete paths; an
+--   asset attached to a message in a held thread is kept too.
+-- * `legal_hold_events` is the audit trail: one row per set or release.
+--   Like `impersonation_sessions.actor_id`, neither `actor_id` nor
+--   `subject_id` has a foreign key, so the record outlives both.
+
+ALTER TABLE threads ADD COLUMN legal_hold_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE assets ADD COLUMN legal_hold_at TIMESTAMP WITH TIME ZONE;
+
+CREATE TABLE legal_hold_events (
+    id            UUID PRIMARY KEY,
+    actor_id      UUID NOT NULL,
+    subject_kind  TEXT NOT NULL,
+    subject_id    UUID NOT NULL,
+    held          BOOLEAN NOT NULL,
+    reason        TEXT NOT NULL,
+    created_at    TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+
+    CONSTRAINT chk_legal_hold_events_subject_kind
+        CHECK (subject_kind IN ('thread', 'asset'))
+);
+
+CREATE INDEX idx_legal_hold_events_subject
+    ON legal_hold_events (subject_kind, subject_id, created_at);
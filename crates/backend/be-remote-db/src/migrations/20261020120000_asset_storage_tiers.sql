@@ -0,0 +1,18 @@
+-- Storage tiering: assets older than `ASSET_COLD_AFTER_DAYS` are moved to
+-- a cheaper cold store by the retention worker, and back to the hot store
+-- when they are read.
+--
+-- * `storage_tier` says which store `storage_uri` points into. Cold URIs
+--   also carry a `cold:` prefix, which is what `be-storage` routes on; the
+--   column is there to query by.
+-- * `tiered_at` is when the asset last changed tier. A restored asset isn't
+--   moved back to cold until `ASSET_COLD_AFTER_DAYS` after that, so one
+--   that is still being opened stays hot.
+
+CREATE TYPE asset_storage_tier AS ENUM ('hot', 'cold');
+
+ALTER TABLE assets
+    ADD COLUMN storage_tier asset_storage_tier NOT NULL DEFAULT 'hot',
+    ADD COLUMN tiered_at    TIMESTAMP WITH TIME ZONE;
+
+CREATE INDEX idx_assets_hot_by_age ON assets (created_at) WHERE storage_tier = 'hot';
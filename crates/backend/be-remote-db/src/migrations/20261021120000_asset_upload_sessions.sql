@@ -0,0 +1,44 @@
+-- Resumable uploads: a client opens a session for an asset of known size,
+-- sends it in fixed-size chunks in any order (retrying any that fail),
+-- and completes it once every chunk has arrived. Each chunk is its own
+-- object in storage until the session completes or expires.
+--
+-- `expires_at` moves forward with every chunk; sessions past it are
+-- deleted by the retention worker along with their chunk objects.
+CREATE TABLE asset_upload_sessions (
+    id          UUID PRIMARY KEY,
+    user_id     UUID NOT NULL,
+    name        TEXT NOT NULL,
+    mime_type   TEXT NOT NULL,
+    metadata    JSONB,
+    total_size  BIGINT NOT NULL,
+    chunk_size  BIGINT NOT NULL,
+    expires_at  TIMESTAMP WITH TIME ZONE NOT NULL,
+    created_at  TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+
+    CONSTRAINT fk_asset_upload_sessions_user_id
+        FOREIGN KEY (user_id)
+        REFERENCES users(id)
+        ON DELETE CASCADE,
+
+    CONSTRAINT chk_asset_upload_sessions_sizes
+        CHECK (total_size > 0 AND chunk_size > 0)
+);
+
+CREATE INDEX idx_asset_upload_sessions_expires_at
+    ON asset_upload_sessions (expires_at);
+
+CREATE TABLE asset_upload_chunks (
+    session_id   UUID NOT NULL,
+    byte_offset  BIGINT NOT NULL,
+    size         BIGINT NOT NULL,
+    storage_uri  TEXT NOT NULL,
+    created_at   TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+
+    PRIMARY KEY (session_id, byte_offset),
+
+    CONSTRAINT fk_asset_upload_chunks_session_id
+        FOREIGN KEY (session_id)
+        REFERENCES asset_upload_sessions(id)
+        ON DELETE CASCADE
+);
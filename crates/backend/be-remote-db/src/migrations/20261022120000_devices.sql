@@ -0,0 +1,39 @@
+-- Devices: the machines signed in to an account. The desktop makes up a
+-- device id once per install and registers it (with a display name and
+-- platform) on every start, so timelines from several machines can be
+-- told apart instead of colliding.
+--
+-- Sessions and assets synced from a device carry its id. Rows from
+-- before this migration, and from clients that don't send one, keep
+-- `device_id` NULL. Deleting a device leaves its rows in place, untagged.
+CREATE TABLE devices (
+    id            UUID PRIMARY KEY,
+    user_id       UUID NOT NULL,
+    name          TEXT NOT NULL,
+    platform      TEXT NOT NULL,
+    last_seen_at  TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+    created_at    TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+    updated_at    TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+
+    CONSTRAINT fk_devices_user_id
+        FOREIGN KEY (user_id)
+        REFERENCES users(id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX idx_devices_user_last_seen ON devices (user_id, last_seen_at DESC);
+
+CREATE TRIGGER update_devices_updated_at
+    BEFORE UPDATE ON devices
+    FOR EACH ROW
+    EXECUTE FUNCTION update_updated_at_column();
+
+ALTER TABLE activity_sessions ADD COLUMN device_id UUID
+    REFERENCES devices(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_activity_sessions_user_device_started
+    ON activity_sessions (user_id, device_id, started_at DESC)
+    WHERE device_id IS NOT NULL;
+
+ALTER TABLE assets ADD COLUMN device_id UUID
+    REFERENCES devices(id) ON DELETE SET NULL;
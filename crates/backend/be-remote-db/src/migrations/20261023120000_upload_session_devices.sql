@@ -0,0 +1,5 @@
+-- A resumable upload remembers the device that opened it, so the asset it
+-- completes into is tagged the same as a single-request upload. Deleting
+-- the device leaves the session in place, untagged.
+ALTER TABLE asset_upload_sessions ADD COLUMN device_id UUID
+    REFERENCES devices(id) ON DELETE SET NULL;
@@ -0,0 +1,286 @@
+//! Connection-pool sizing, the optional read replica, and pool health
+//! snapshots.
+//!
+//! Writes and anything that must observe its own writes go to the
+//! primary. Read-heavy listing and search queries go through
+//! [`DatabaseManager::read`](crate::DatabaseManager), which prefers the
+//! replica when one is configured. A replica that stops answering is
+//! benched for [`REPLICA_RETRY_AFTER`] and the query is retried on the
+//! primary, so a replica outage degrades to "all traffic on primary"
+//! instead of failing requests.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use sqlx::postgres::{PgPool, PgPoolOptions};
+
+use crate::error::{DbError, DbResult};
+
+/// How long a replica that failed with a connection-class error is
+/// skipped before reads try it again.
+pub const REPLICA_RETRY_AFTER: Duration = Duration::from_secs(30);
+
+const ENV_MAX_CONNECTIONS: &str = "REMOTE_DATABASE_MAX_CONNECTIONS";
+const ENV_MIN_CONNECTIONS: &str = "REMOTE_DATABASE_MIN_CONNECTIONS";
+const ENV_ACQUIRE_TIMEOUT_SECS: &str = "REMOTE_DATABASE_ACQUIRE_TIMEOUT_SECS";
+const ENV_IDLE_TIMEOUT_SECS: &str = "REMOTE_DATABASE_IDLE_TIMEOUT_SECS";
+const ENV_MAX_LIFETIME_SECS: &str = "REMOTE_DATABASE_MAX_LIFETIME_SECS";
+
+/// Sizing and timeouts applied to both the primary and the replica pool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolConfig {
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub acquire_timeout: Duration,
+    pub idle_timeout: Option<Duration>,
+    pub max_lifetime: Option<Duration>,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: 50,
+            min_connections: 3,
+            acquire_timeout: Duration::from_secs(10),
+            idle_timeout: Some(Duration::from_secs(10 * 60)),
+            max_lifetime: Some(Duration::from_secs(30 * 60)),
+        }
+    }
+}
+
+impl PoolConfig {
+    /// Read overrides from `REMOTE_DATABASE_*` variables, falling back to
+    /// [`PoolConfig::default`] for anything unset. A timeout of `0`
+    /// disables idle / lifetime recycling.
+    pub fn from_env() -> DbResult<Self> {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> DbResult<Self> {
+        let defaults = Self::default();
+        let read = |name: &str| -> DbResult<Option<u64>> {
+            match lookup(name).filter(|v| !v.trim().is_empty()) {
+                None => Ok(None),
+                Some(v) => v.trim().parse().map(Some).map_err(|_| {
+                    DbError::InvalidInput(format!(
+                        "{name} must be a non-negative integer, got `{v}`"
+                    ))
+                }),
+            }
+        };
+        let optional_secs = |value: Option<u64>, default: Option<Duration>| match value {
+            None => default,
+            Some(0) => None,
+            Some(secs) => Some(Duration::from_secs(secs)),
+        };
+
+        let config = Self {
+            max_connections: match read(ENV_MAX_CONNECTIONS)? {
+                Some(v) => u32::try_from(v).map_err(|_| {
+                    DbError::InvalidInput(format!("{ENV_MAX_CONNECTIONS} is out of range"))
+                })?,
+                None => defaults.max_connections,
+            },
+            min_connections: match read(ENV_MIN_CONNECTIONS)? {
+                Some(v) => u32::try_from(v).map_err(|_| {
+                    DbError::InvalidInput(format!("{ENV_MIN_CONNECTIONS} is out of range"))
+                })?,
+                None => defaults.min_connections,
+            },
+            acquire_timeout: read(ENV_ACQUIRE_TIMEOUT_SECS)?
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.acquire_timeout),
+            idle_timeout: optional_secs(read(ENV_IDLE_TIMEOUT_SECS)?, defaults.idle_timeout),
+            max_lifetime: optional_secs(read(ENV_MAX_LIFETIME_SECS)?, defaults.max_lifetime),
+        };
+
+        if config.max_connections == 0 {
+            return Err(DbError::InvalidInput(format!(
+                "{ENV_MAX_CONNECTIONS} must be at least 1"
+            )));
+        }
+        if config.min_connections > config.max_connections {
+            return Err(DbError::InvalidInput(format!(
+                "{ENV_MIN_CONNECTIONS} ({}) exceeds {ENV_MAX_CONNECTIONS} ({})",
+                config.min_connections, config.max_connections
+            )));
+        }
+        if config.acquire_timeout.is_zero() {
+            return Err(DbError::InvalidInput(format!(
+                "{ENV_ACQUIRE_TIMEOUT_SECS} must be at least 1"
+            )));
+        }
+        Ok(config)
+    }
+
+    pub(crate) fn options(&self) -> PgPoolOptions {
+        PgPoolOptions::new()
+            .max_connections(self.max_connections)
+            .min_connections(self.min_connections)
+            .acquire_timeout(self.acquire_timeout)
+            .idle_timeout(self.idle_timeout)
+            .max_lifetime(self.max_lifetime)
+    }
+}
+
+/// The replica pool plus the circuit that benches it after a failure.
+#[derive(Debug)]
+pub(crate) struct Replica {
+    pub(crate) pool: PgPool,
+    benched_until: Mutex<Option<Instant>>,
+}
+
+impl Replica {
+    pub(crate) fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            benched_until: Mutex::new(None),
+        }
+    }
+
+    pub(crate) fn is_available(&self) -> bool {
+        let mut benched = self.benched_until.lock().unwrap_or_else(|e| e.into_inner());
+        match *benched {
+            Some(until) if Instant::now() < until => false,
+            Some(_) => {
+                *benched = None;
+                true
+            }
+            None => true,
+        }
+    }
+
+    pub(crate) fn bench(&self) {
+        *self.benched_until.lock().unwrap_or_else(|e| e.into_inner()) =
+            Some(Instant::now() + REPLICA_RETRY_AFTER);
+    }
+}
+
+/// True for errors that say the server was unreachable or went away,
+/// as opposed to the query itself being wrong. Only these justify
+/// retrying on the primary.
+pub(crate) fn is_connection_error(error: &sqlx::Error) -> bool {
+    match error {
+        sqlx::Error::Io(_)
+        | sqlx::Error::Tls(_)
+        | sqlx::Error::Protocol(_)
+        | sqlx::Error::PoolTimedOut
+        | sqlx::Error::PoolClosed
+        | sqlx::Error::WorkerCrashed => true,
+        // Class 08: connection exception. Class 57: operator
+        // intervention (shutdown, recovery conflict on a hot standby).
+        sqlx::Error::Database(db) => db
+            .code()
+            .is_some_and(|code| code.starts_with("08") || code.starts_with("57")),
+        _ => false,
+    }
+}
+
+/// Point-in-time view of one pool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PoolStats {
+    /// Open connections, idle or in use.
+    pub size: u32,
+    pub idle: u32,
+    pub max_connections: u32,
+}
+
+impl PoolStats {
+    pub(crate) fn of(pool: &PgPool) -> Self {
+        Self {
+            size: pool.size(),
+            idle: u32::try_from(pool.num_idle()).unwrap_or(u32::MAX),
+            max_connections: pool.options().get_max_connections(),
+        }
+    }
+
+    pub fn in_use(&self) -> u32 {
+        self.size.saturating_sub(self.idle)
+    }
+
+    /// Every connection is checked out and the pool cannot grow, so the
+    /// next acquire waits.
+    pub fn is_saturated(&self) -> bool {
+        self.idle == 0 && self.size >= self.max_connections
+    }
+}
+
+/// Health snapshot of the primary and (when configured) replica pools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PoolHealth {
+    pub primary: PoolStats,
+    pub replica: Option<PoolStats>,
+    /// `false` while the replica is benched after a connection failure.
+    pub replica_available: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn config_from(vars: &[(&str, &str)]) -> DbResult<PoolConfig> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        PoolConfig::from_lookup(|name| vars.get(name).cloned())
+    }
+
+    #[test]
+    fn unset_env_yields_defaults() {
+        assert_eq!(config_from(&[]).unwrap(), PoolConfig::default());
+    }
+
+    #[test]
+    fn overrides_are_applied_and_zero_disables_recycling() {
+        let config = config_from(&[
+            (ENV_MAX_CONNECTIONS, "20"),
+            (ENV_MIN_CONNECTIONS, "2"),
+            (ENV_ACQUIRE_TIMEOUT_SECS, "5"),
+            (ENV_IDLE_TIMEOUT_SECS, "0"),
+            (ENV_MAX_LIFETIME_SECS, "600"),
+        ])
+        .unwrap();
+        assert_eq!(config.max_connections, 20);
+        assert_eq!(config.min_connections, 2);
+        assert_eq!(config.acquire_timeout, Duration::from_secs(5));
+        assert_eq!(config.idle_timeout, None);
+        assert_eq!(config.max_lifetime, Some(Duration::from_secs(600)));
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        assert!(config_from(&[(ENV_MAX_CONNECTIONS, "lots")]).is_err());
+        assert!(config_from(&[(ENV_MAX_CONNECTIONS, "0")]).is_err());
+        assert!(config_from(&[(ENV_MAX_CONNECTIONS, "4"), (ENV_MIN_CONNECTIONS, "5")]).is_err());
+        assert!(config_from(&[(ENV_ACQUIRE_TIMEOUT_SECS, "0")]).is_err());
+    }
+
+    #[test]
+    fn connection_errors_are_distinguished_from_query_errors() {
+        assert!(is_connection_error(&sqlx::Error::PoolTimedOut));
+        assert!(is_connection_error(&sqlx::Error::Io(std::io::Error::from(
+            std::io::ErrorKind::ConnectionRefused
+        ))));
+        assert!(!is_connection_error(&sqlx::Error::RowNotFound));
+        assert!(!is_connection_error(&sqlx::Error::ColumnNotFound(
+            "id".into()
+        )));
+    }
+
+    #[test]
+    fn saturation_requires_full_pool_with_no_idle() {
+        let stats = PoolStats {
+            size: 10,
+            idle: 0,
+            max_connections: 10,
+        };
+        assert!(stats.is_saturated());
+        assert_eq!(stats.in_use(), 10);
+        assert!(!PoolStats { idle: 1, ..stats }.is_saturated());
+        assert!(!PoolStats { size: 9, ..stats }.is_saturated());
+    }
+}
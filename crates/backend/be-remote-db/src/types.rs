@@ -66,10 +66,46 @@ pub struct User {
     pub email: String,
     pub display_name: Option<String>,
     pub email_verified: bool,
+    pub analytics_consent: UserAnalyticsConsent,
+    /// Stamped into every token minted for the user; bumped to revoke
+    /// them all at once.
+    pub token_version: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// Database-side counterpart to [`auth_core::AnalyticsConsent`], pinned
+/// to the `analytics_consent` Postgres enum. Kept in lockstep the same way
+/// as [`OAuthProvider`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[sqlx(type_name = "analytics_consent", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum UserAnalyticsConsent {
+    Off,
+    Anonymous,
+    Identified,
+}
+
+impl From<auth_core::AnalyticsConsent> for UserAnalyticsConsent {
+    fn from(c: auth_core::AnalyticsConsent) -> Self {
+        match c {
+            auth_core::AnalyticsConsent::Off => UserAnalyticsConsent::Off,
+            auth_core::AnalyticsConsent::Anonymous => UserAnalyticsConsent::Anonymous,
+            auth_core::AnalyticsConsent::Identified => UserAnalyticsConsent::Identified,
+        }
+    }
+}
+
+impl From<UserAnalyticsConsent> for auth_core::AnalyticsConsent {
+    fn from(c: UserAnalyticsConsent) -> Self {
+        match c {
+            UserAnalyticsConsent::Off => auth_core::AnalyticsConsent::Off,
+            UserAnalyticsConsent::Anonymous => auth_core::AnalyticsConsent::Anonymous,
+            UserAnalyticsConsent::Identified => auth_core::AnalyticsConsent::Identified,
+        }
+    }
+}
+
 #[derive(Debug, Clone, FromRow)]
 pub struct ClaimedProvisioningJob {
     pub user_id: Uuid,
@@ -102,6 +138,7 @@ pub enum OAuthProvider {
     Google,
     Github,
     Apple,
+    Microsoft,
 }
 
 impl std::fmt::Display for OAuthProvider {
@@ -116,6 +153,7 @@ impl From<auth_core::Provider> for OAuthProvider {
             auth_core::Provider::Google => OAuthProvider::Google,
             auth_core::Provider::Github => OAuthProvider::Github,
             auth_core::Provider::Apple => OAuthProvider::Apple,
+            auth_core::Provider::Microsoft => OAuthProvider::Microsoft,
         }
     }
 }
@@ -126,6 +164,7 @@ impl From<OAuthProvider> for auth_core::Provider {
             OAuthProvider::Google => auth_core::Provider::Google,
             OAuthProvider::Github => auth_core::Provider::Github,
             OAuthProvider::Apple => auth_core::Provider::Apple,
+            OAuthProvider::Microsoft => auth_core::Provider::Microsoft,
         }
     }
 }
@@ -140,6 +179,9 @@ pub struct OAuthCredentials {
     pub refresh_token: Option<Vec<u8>>,
     pub access_token_expiry: Option<DateTime<Utc>>,
     pub scope: Option<String>,
+    /// The provider refused the stored refresh token; the user has to
+    /// sign in with the provider again before its APIs can be used.
+    pub needs_reauth: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -200,6 +242,32 @@ pub struct EmailVerificationToken {
     pub updated_at: DateTime<Utc>,
 }
 
+/// Rows removed by one sweep of
+/// [`crate::DatabaseManager::cleanup_expired_auth_data`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AuthCleanup {
+    pub oauth_states: i64,
+    pub login_tokens: i64,
+    pub refresh_tokens: i64,
+    pub verification_tokens: i64,
+}
+
+impl AuthCleanup {
+    pub fn total(&self) -> i64 {
+        self.oauth_states + self.login_tokens + self.refresh_tokens + self.verification_tokens
+    }
+}
+
+/// Approximate live row counts of the auth token tables, read from
+/// Postgres statistics rather than counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, FromRow)]
+pub struct AuthTokenTableSizes {
+    pub oauth_states: i64,
+    pub login_tokens: i64,
+    pub refresh_tokens: i64,
+    pub verification_tokens: i64,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type, Default)]
 #[sqlx(type_name = "asset_status", rename_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
@@ -226,6 +294,31 @@ impl std::fmt::Display for AssetStatus {
     }
 }
 
+/// Outcome of malware scanning for an asset. `Skipped` assets were never
+/// scanned (no scanner configured, or the scanner kept failing) and are
+/// served like clean ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type, Default)]
+#[sqlx(type_name = "asset_scan_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum AssetScanStatus {
+    Pending,
+    Clean,
+    Quarantined,
+    #[default]
+    Skipped,
+}
+
+/// Which store an asset's object is in. Cold objects are in the cheaper
+/// store and moved back to hot when read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type, Default)]
+#[sqlx(type_name = "asset_storage_tier", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum AssetStorageTier {
+    #[default]
+    Hot,
+    Cold,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Asset {
     pub id: Uuid,
@@ -236,12 +329,161 @@ pub struct Asset {
     pub checksum_sha256: Option<Vec<u8>>,
     pub storage_backend: String,
     pub storage_uri: String,
+    pub storage_tier: AssetStorageTier,
     pub status: AssetStatus,
     pub metadata: serde_json::Value,
+    pub scan_status: AssetScanStatus,
+    pub scan_signature: Option<String>,
+    pub scanned_at: Option<DateTime<Utc>>,
+    /// The device that uploaded it, when the client said.
+    pub device_id: Option<Uuid>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// Read access to an asset for someone other than its owner, for
+/// [`DatabaseManager::share_asset_with_user`](crate::DatabaseManager::share_asset_with_user)
+/// and
+/// [`DatabaseManager::create_asset_link`](crate::DatabaseManager::create_asset_link).
+/// The link token's hash is never read back.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AssetGrant {
+    pub id: Uuid,
+    pub asset_id: Uuid,
+    /// The user the asset is shared with; `None` for a link.
+    pub grantee_id: Option<Uuid>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A resumable upload in progress, from
+/// [`DatabaseManager::create_upload_session`](crate::DatabaseManager::create_upload_session).
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct UploadSession {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub mime_type: String,
+    pub metadata: Option<serde_json::Value>,
+    /// The device that opened it, when the client said.
+    pub device_id: Option<Uuid>,
+    pub total_size: i64,
+    pub chunk_size: i64,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One received chunk of an [`UploadSession`].
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct UploadChunk {
+    pub byte_offset: i64,
+    pub size: i64,
+    pub storage_uri: String,
+}
+
+/// An upload session nobody touched within its TTL, with the objects its
+/// chunks were written to.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ExpiredUploadSession {
+    pub id: Uuid,
+    pub chunk_uris: Vec<String>,
+}
+
+/// An asset claimed for scanning by
+/// [`DatabaseManager::claim_pending_asset_scans`](crate::DatabaseManager::claim_pending_asset_scans).
+#[derive(Debug, Clone, FromRow)]
+pub struct PendingAssetScan {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub size_bytes: Option<i64>,
+    pub storage_uri: String,
+    /// Claims so far, including this one.
+    pub attempts: i32,
+}
+
+/// A Casbin grouping rule `g, subject, role` managed at runtime. `subject`
+/// is a user id or a role name.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct RoleAssignment {
+    pub id: Uuid,
+    pub subject: String,
+    pub role: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An admin acting as a user. `id` is the `jti` of the access token
+/// issued for it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct ImpersonationSession {
+    pub id: Uuid,
+    pub actor_id: Uuid,
+    pub user_id: Uuid,
+    pub reason: String,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One request made with an impersonation token.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct ImpersonationRequest {
+    pub id: Uuid,
+    pub session_id: Uuid,
+    pub method: String,
+    /// Route template (`/threads/{thread_id}`), not the raw URI.
+    pub path: String,
+    pub status: i16,
+    pub created_at: DateTime<Utc>,
+}
+
+/// What a legal hold applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LegalHoldSubject {
+    Thread,
+    Asset,
+}
+
+impl LegalHoldSubject {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Thread => "thread",
+            Self::Asset => "asset",
+        }
+    }
+
+    pub(crate) fn table(self) -> &'static str {
+        match self {
+            Self::Thread => "threads",
+            Self::Asset => "assets",
+        }
+    }
+}
+
+/// One entry in the legal hold audit trail: a hold set (`held`) or
+/// released on a thread or asset.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct LegalHoldEvent {
+    pub id: Uuid,
+    pub actor_id: Uuid,
+    /// `thread` or `asset`, as in [`LegalHoldSubject::as_str`].
+    pub subject_kind: String,
+    pub subject_id: Uuid,
+    pub held: bool,
+    pub reason: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A server-side event for one user, or for everyone when `user_id` is
+/// `None`. `payload` is the `notification-core` event JSON.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct Notification {
+    pub id: i64,
+    pub user_id: Option<Uuid>,
+    pub payload: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct MessageAsset {
     pub message_id: Uuid,
@@ -270,18 +512,55 @@ pub struct ActivitySession {
     pub process_id: Option<i32>,
     pub window_title: Option<String>,
     pub url: Option<String>,
+    /// The device the session was recorded on; see [`Device`].
+    pub device_id: Option<Uuid>,
     pub started_at: DateTime<Utc>,
     pub ended_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// A machine signed in to a user's account, from
+/// [`DatabaseManager::upsert_device`](crate::DatabaseManager::upsert_device).
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Device {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub platform: String,
+    pub last_seen_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Thread {
     pub id: Uuid,
     pub user_id: Uuid,
     pub title: Option<String>,
     pub active_leaf_id: Option<Uuid>,
+    /// Set together with `model_name` when the thread is pinned to a
+    /// model; see [`crate::DatabaseManager::set_thread_model`].
+    pub model_provider: Option<String>,
+    pub model_name: Option<String>,
+    pub model_temperature: Option<f32>,
+    pub tags: Vec<String>,
+    /// When the thread was pinned; `None` when it isn't.
+    pub pinned_at: Option<DateTime<Utc>>,
+    pub starred: bool,
+    pub folder_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A user-defined folder in the thread sidebar.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ThreadFolder {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    /// Sidebar order, ascending.
+    pub position: i32,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -397,3 +676,355 @@ pub enum UpsertOutcome {
     Updated(UserSettingsRow),
     Conflict { current: UserSettingsRow },
 }
+
+/// A fact the assistant remembers about a user.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct UserMemory {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// The thread the fact was extracted from; `None` for facts the user
+    /// added by hand.
+    pub thread_id: Option<Uuid>,
+    pub content: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Automation {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub prompt: String,
+    pub schedule: String,
+    pub tools: Vec<String>,
+    pub enabled: bool,
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// An automation handed to a scheduler worker by
+/// [`crate::DatabaseManager::claim_due_automations`]. `scheduled_for` is
+/// the `next_run_at` the row had before the claim overwrote it with the
+/// lease.
+#[derive(Debug, Clone, FromRow)]
+pub struct ClaimedAutomation {
+    #[sqlx(flatten)]
+    pub automation: Automation,
+    pub scheduled_for: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[sqlx(type_name = "automation_run_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum AutomationRunStatus {
+    Running,
+    Succeeded,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AutomationRun {
+    pub id: Uuid,
+    pub automation_id: Uuid,
+    pub user_id: Uuid,
+    pub thread_id: Option<Uuid>,
+    pub status: AutomationRunStatus,
+    pub scheduled_for: DateTime<Utc>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub output: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Workflow {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub description: String,
+    pub definition: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[sqlx(type_name = "data_export_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum DataExportStatus {
+    Pending,
+    Running,
+    Ready,
+    Failed,
+    Expired,
+}
+
+/// Whether a data export carries asset bytes decrypted or exactly as they
+/// sit in storage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[sqlx(type_name = "data_export_asset_mode", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum DataExportAssetMode {
+    Decrypted,
+    AsStored,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DataExport {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub status: DataExportStatus,
+    pub asset_mode: DataExportAssetMode,
+    pub storage_uri: Option<String>,
+    pub size_bytes: Option<i64>,
+    pub error: Option<String>,
+    pub attempts: i32,
+    pub lease_until: Option<DateTime<Utc>>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[sqlx(type_name = "account_deletion_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum AccountDeletionStatus {
+    Scheduled,
+    Running,
+    Completed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AccountDeletion {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// Where the confirmation goes; cleared once the data is erased.
+    pub email: Option<String>,
+    pub status: AccountDeletionStatus,
+    pub scheduled_for: DateTime<Utc>,
+    pub attempts: i32,
+    pub lease_until: Option<DateTime<Utc>>,
+    pub error: Option<String>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AccountDeletionEvent {
+    pub id: Uuid,
+    pub deletion_id: Uuid,
+    pub kind: String,
+    pub detail: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Row counts removed by [`crate::DatabaseManager::erase_account`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErasedAccountCounts {
+    pub threads: i64,
+    pub messages: i64,
+    pub assets: i64,
+    pub oauth_credentials: i64,
+}
+
+/// A kind of captured context with its own retention limit.
+///
+/// `screenshots` are image assets and `captured_text` every other asset;
+/// `activity_history` is finished activity sessions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Type)]
+#[sqlx(type_name = "retention_category", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionCategory {
+    Screenshots,
+    CapturedText,
+    ActivityHistory,
+    Threads,
+}
+
+impl RetentionCategory {
+    pub const ALL: [Self; 4] = [
+        Self::Screenshots,
+        Self::CapturedText,
+        Self::ActivityHistory,
+        Self::Threads,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Screenshots => "screenshots",
+            Self::CapturedText => "captured_text",
+            Self::ActivityHistory => "activity_history",
+            Self::Threads => "threads",
+        }
+    }
+
+    /// Whether items in this category are assets, whose stored objects
+    /// have to be deleted along with their rows.
+    pub fn is_asset(self) -> bool {
+        matches!(self, Self::Screenshots | Self::CapturedText)
+    }
+}
+
+/// A user's override of the default limit for one category.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct RetentionSetting {
+    pub user_id: Uuid,
+    pub category: RetentionCategory,
+    /// `None` keeps the category forever.
+    pub max_age_days: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// What expiring one category at a given cutoff would remove, from
+/// [`crate::DatabaseManager::count_expired_items`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct ExpiredItemStats {
+    pub count: i64,
+    /// Stored bytes freed; always `0` for categories that aren't assets.
+    pub size_bytes: i64,
+    pub oldest: Option<DateTime<Utc>>,
+}
+
+/// An asset past its category's retention limit.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ExpiredAsset {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub storage_uri: String,
+}
+
+/// An asset old enough to move to the cold store.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TierableAsset {
+    pub id: Uuid,
+    pub storage_uri: String,
+}
+
+/// An event a webhook endpoint can subscribe to. Stored and sent as its
+/// dotted name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WebhookEventType {
+    #[serde(rename = "thread.created")]
+    ThreadCreated,
+    #[serde(rename = "export.finished")]
+    ExportFinished,
+    #[serde(rename = "automation.run")]
+    AutomationRun,
+    /// Once a day, the automation runs since the last summary. Not
+    /// published by a service; the webhook worker builds it per endpoint.
+    #[serde(rename = "automation.daily_summary")]
+    AutomationDailySummary,
+}
+
+impl WebhookEventType {
+    pub const ALL: [Self; 4] = [
+        Self::ThreadCreated,
+        Self::ExportFinished,
+        Self::AutomationRun,
+        Self::AutomationDailySummary,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ThreadCreated => "thread.created",
+            Self::ExportFinished => "export.finished",
+            Self::AutomationRun => "automation.run",
+            Self::AutomationDailySummary => "automation.daily_summary",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|event| event.as_str() == name)
+    }
+}
+
+impl std::fmt::Display for WebhookEventType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// What an endpoint receives: the signed JSON envelope, or a message
+/// formatted for a chat incoming webhook.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[sqlx(type_name = "webhook_endpoint_kind", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookEndpointKind {
+    Generic,
+    Slack,
+    Discord,
+}
+
+/// A user's webhook endpoint. `secret` signs every delivery and is only
+/// ever shown to the user when the endpoint is created.
+#[derive(Debug, Clone, FromRow)]
+pub struct WebhookEndpoint {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub kind: WebhookEndpointKind,
+    pub url: String,
+    pub secret: String,
+    /// Dotted [`WebhookEventType`] names.
+    pub event_types: Vec<String>,
+    /// Automations whose events are sent; `None` for all of them.
+    pub automation_ids: Option<Vec<Uuid>>,
+    pub enabled: bool,
+    /// When the next `automation.daily_summary` is due, while subscribed.
+    pub next_summary_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[sqlx(type_name = "webhook_delivery_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookDeliveryStatus {
+    Pending,
+    Succeeded,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub endpoint_id: Uuid,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    /// HTTP status of the last attempt, when it got a response.
+    pub response_status: Option<i16>,
+    /// Why the last attempt failed.
+    pub error: Option<String>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A delivery handed to a webhook worker by
+/// [`crate::DatabaseManager::claim_due_webhook_deliveries`], with what it
+/// needs to send it.
+#[derive(Debug, Clone, FromRow)]
+pub struct ClaimedWebhookDelivery {
+    #[sqlx(flatten)]
+    pub delivery: WebhookDelivery,
+    pub kind: WebhookEndpointKind,
+    pub url: String,
+    pub secret: String,
+}
+
+/// An automation run with its automation's name, for summaries.
+#[derive(Debug, Clone, FromRow)]
+pub struct NamedAutomationRun {
+    #[sqlx(flatten)]
+    pub run: AutomationRun,
+    pub automation_name: String,
+}
@@ -0,0 +1,273 @@
+//! Integration tests for the account-deletion DB layer.
+//!
+//! Uses `#[sqlx::test]` like the other files here: each test runs against a
+//! freshly migrated, isolated database, and the binary is a no-op without
+//! `DATABASE_URL`.
+
+use be_remote_db::{AccountDeletionStatus, DatabaseManager, DbError, LegalHoldSubject};
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+async fn seed_user(pool: &PgPool) -> Uuid {
+    let id = Uuid::now_v7();
+    sqlx::query("INSERT INTO users (id, email) VALUES ($1, $2)")
+        .bind(id)
+        .bind(format!("user-{id}@test.local"))
+        .execute(pool)
+        .await
+        .expect("seed user");
+    id
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn scheduling_is_idempotent_and_revokes_sessions(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let user_id = seed_user(&db.pool).await;
+    let token = db
+        .create_refresh_token()
+        .user_id(user_id)
+        .token_hash(vec![7; 32])
+        .expires_at(Utc::now() + Duration::days(30))
+        .call()
+        .await
+        .unwrap();
+
+    let scheduled_for = Utc::now() + Duration::days(14);
+    let first = db
+        .schedule_account_deletion()
+        .user_id(user_id)
+        .email("a@test.local".into())
+        .scheduled_for(scheduled_for)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(first.status, AccountDeletionStatus::Scheduled);
+
+    let err = db
+        .get_refresh_token_by_hash()
+        .token_hash(&token.token_hash)
+        .call()
+        .await
+        .unwrap_err();
+    assert!(matches!(err, DbError::NotFound { .. }));
+
+    let second = db
+        .schedule_account_deletion()
+        .user_id(user_id)
+        .email("a@test.local".into())
+        .scheduled_for(scheduled_for + Duration::days(1))
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(second.id, first.id);
+    assert_eq!(second.scheduled_for, first.scheduled_for);
+
+    let open = db
+        .get_open_account_deletion()
+        .user_id(user_id)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(open.map(|d| d.id), Some(first.id));
+
+    let events = db
+        .list_account_deletion_events()
+        .deletion_id(first.id)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(
+        events.iter().map(|e| e.kind.as_str()).collect::<Vec<_>>(),
+        ["requested"]
+    );
+    assert_eq!(events[0].detail["refresh_tokens_revoked"], 1);
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn claim_waits_for_grace_period_then_erases(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let user_id = seed_user(&db.pool).await;
+    let bystander = seed_user(&db.pool).await;
+    for owner in [user_id, bystander] {
+        db.create_thread()
+            .user_id(owner)
+            .title("t".into())
+            .call()
+            .await
+            .unwrap();
+    }
+
+    db.schedule_account_deletion()
+        .user_id(bystander)
+        .email("b@test.local".into())
+        .scheduled_for(Utc::now() + Duration::days(14))
+        .call()
+        .await
+        .unwrap();
+    let due = db
+        .schedule_account_deletion()
+        .user_id(user_id)
+        .email("a@test.local".into())
+        .scheduled_for(Utc::now() - Duration::seconds(1))
+        .call()
+        .await
+        .unwrap();
+
+    let claimed = db
+        .claim_due_account_deletions()
+        .limit(10)
+        .lease(Duration::minutes(30))
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(claimed.iter().map(|d| d.id).collect::<Vec<_>>(), [due.id]);
+    assert_eq!(claimed[0].status, AccountDeletionStatus::Running);
+    assert_eq!(claimed[0].attempts, 1);
+
+    // Leased: not claimable again until the lease lapses.
+    let again = db
+        .claim_due_account_deletions()
+        .limit(10)
+        .lease(Duration::minutes(30))
+        .call()
+        .await
+        .unwrap();
+    assert!(again.is_empty());
+
+    let counts = db
+        .erase_account()
+        .deletion_id(due.id)
+        .user_id(user_id)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(counts.threads, 1);
+
+    let err = db.get_user().id(user_id).call().await.unwrap_err();
+    assert!(matches!(err, DbError::NotFound { .. }));
+    db.get_user().id(bystander).call().await.unwrap();
+
+    assert!(
+        db.get_open_account_deletion()
+            .user_id(user_id)
+            .call()
+            .await
+            .unwrap()
+            .is_none()
+    );
+    let events = db
+        .list_account_deletion_events()
+        .deletion_id(due.id)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(
+        events.iter().map(|e| e.kind.as_str()).collect::<Vec<_>>(),
+        ["requested", "data_erased"]
+    );
+
+    let row: (AccountDeletionStatus, Option<String>) =
+        sqlx::query_as("SELECT status, email FROM account_deletions WHERE id = $1")
+            .bind(due.id)
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+    assert_eq!(row, (AccountDeletionStatus::Completed, None));
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn held_threads_and_assets_block_erasure(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    for subject in [LegalHoldSubject::Thread, LegalHoldSubject::Asset] {
+        let user_id = seed_user(&db.pool).await;
+        let thread = db
+            .create_thread()
+            .user_id(user_id)
+            .title("t".into())
+            .call()
+            .await
+            .unwrap()
+            .id;
+        let asset = db
+            .create_asset()
+            .user_id(user_id)
+            .name("capture".to_owned())
+            .mime_type("image/png".to_owned())
+            .size_bytes(10)
+            .storage_backend("filesystem".to_owned())
+            .storage_uri(format!("assets/{}", Uuid::now_v7()))
+            .call()
+            .await
+            .unwrap()
+            .id;
+        let held = match subject {
+            LegalHoldSubject::Thread => thread,
+            LegalHoldSubject::Asset => asset,
+        };
+        db.set_legal_hold()
+            .subject(subject)
+            .subject_id(held)
+            .held(true)
+            .actor_id(Uuid::now_v7())
+            .reason("case 42")
+            .call()
+            .await
+            .unwrap();
+        let due = db
+            .schedule_account_deletion()
+            .user_id(user_id)
+            .email("a@test.local".into())
+            .scheduled_for(Utc::now() - Duration::seconds(1))
+            .call()
+            .await
+            .unwrap();
+
+        assert!(
+            db.account_has_legal_hold()
+                .user_id(user_id)
+                .call()
+                .await
+                .unwrap()
+        );
+        let err = db
+            .erase_account()
+            .deletion_id(due.id)
+            .user_id(user_id)
+            .call()
+            .await
+            .unwrap_err();
+        assert!(err.is_on_legal_hold());
+
+        // Nothing was erased, held or not.
+        db.get_user().id(user_id).call().await.unwrap();
+        db.get_thread()
+            .id(thread)
+            .user_id(user_id)
+            .call()
+            .await
+            .unwrap();
+        let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM assets WHERE id = $1")
+            .bind(asset)
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(remaining, 1);
+        let status: AccountDeletionStatus =
+            sqlx::query_scalar("SELECT status FROM account_deletions WHERE id = $1")
+                .bind(due.id)
+                .fetch_one(&db.pool)
+                .await
+                .unwrap();
+        assert_eq!(status, AccountDeletionStatus::Scheduled);
+
+        // A held asset's object is not handed to storage deletion.
+        let uris = db
+            .list_account_storage_uris()
+            .user_id(user_id)
+            .call()
+            .await
+            .unwrap();
+        assert_eq!(uris.len(), usize::from(subject == LegalHoldSubject::Thread));
+    }
+}
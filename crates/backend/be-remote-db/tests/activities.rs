@@ -0,0 +1,223 @@
+//! Integration tests for activity session range queries and devices.
+//!
+//! Uses `#[sqlx::test]` like the other files here: each test runs against a
+//! freshly migrated, isolated database, and the binary is a no-op without
+//! `DATABASE_URL`.
+
+use be_remote_db::{ActivitySession, DatabaseManager};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+async fn seed_user(pool: &PgPool) -> Uuid {
+    let id = Uuid::now_v7();
+    sqlx::query("INSERT INTO users (id, email) VALUES ($1, $2)")
+        .bind(id)
+        .bind(format!("user-{id}@test.local"))
+        .execute(pool)
+        .await
+        .expect("seed user");
+    id
+}
+
+fn at(hour: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 3, 2, hour, 0, 0).unwrap()
+}
+
+async fn session(
+    db: &DatabaseManager,
+    user_id: Uuid,
+    identity: &str,
+    started_at: DateTime<Utc>,
+    ended_at: Option<DateTime<Utc>>,
+) {
+    db.insert_activity_session()
+        .user_id(user_id)
+        .identity_key(identity.to_owned())
+        .display_name(identity.to_owned())
+        .process_name(identity.to_owned())
+        .started_at(started_at)
+        .maybe_ended_at(ended_at)
+        .call()
+        .await
+        .unwrap();
+}
+
+async fn live_session(
+    db: &DatabaseManager,
+    user_id: Uuid,
+    device_id: Option<Uuid>,
+) -> ActivitySession {
+    let (_, session) = db
+        .insert_activity_session()
+        .user_id(user_id)
+        .identity_key("editor".to_owned())
+        .display_name("editor".to_owned())
+        .process_name("editor".to_owned())
+        .maybe_device_id(device_id)
+        .started_at(at(10))
+        .call()
+        .await
+        .unwrap();
+    session
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn sessions_between_returns_overlapping_sessions_by_parent(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let user = seed_user(&db.pool).await;
+    let other = seed_user(&db.pool).await;
+
+    // Before, straddling the start, inside, and after the range.
+    session(&db, user, "editor", at(6), Some(at(7))).await;
+    session(&db, user, "browser", at(8), Some(at(10))).await;
+    session(&db, user, "editor", at(11), Some(at(12))).await;
+    session(&db, user, "browser", at(18), Some(at(19))).await;
+    // Someone else's session in the same range.
+    session(&db, other, "editor", at(10), Some(at(11))).await;
+
+    let grouped = db
+        .list_activity_sessions_between()
+        .user_id(user)
+        .since(at(9))
+        .until(at(17))
+        .limit(100)
+        .call()
+        .await
+        .unwrap();
+
+    let names: Vec<_> = grouped
+        .iter()
+        .map(|(activity, sessions)| (activity.display_name.as_str(), sessions.len()))
+        .collect();
+    assert_eq!(names, [("browser", 1), ("editor", 1)]);
+    assert!(
+        grouped
+            .iter()
+            .flat_map(|(_, sessions)| sessions)
+            .all(|s| s.user_id == user)
+    );
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn sessions_between_includes_open_sessions_and_honours_limit(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let user = seed_user(&db.pool).await;
+
+    for hour in 9..12 {
+        session(
+            &db,
+            user,
+            &format!("app-{hour}"),
+            at(hour),
+            Some(at(hour) + Duration::minutes(30)),
+        )
+        .await;
+    }
+    session(&db, user, "terminal", at(13), None).await;
+
+    let all = db
+        .list_activity_sessions_between()
+        .user_id(user)
+        .since(at(9))
+        .until(at(17))
+        .limit(100)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(all.len(), 4);
+    assert_eq!(all[3].0.display_name, "terminal");
+    assert!(all[3].1[0].ended_at.is_none());
+
+    let capped = db
+        .list_activity_sessions_between()
+        .user_id(user)
+        .since(at(9))
+        .until(at(17))
+        .limit(2)
+        .call()
+        .await
+        .unwrap();
+    let names: Vec<_> = capped
+        .iter()
+        .map(|(a, _)| a.display_name.as_str())
+        .collect();
+    assert_eq!(names, ["app-9", "app-10"]);
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn sessions_are_tagged_with_the_users_own_devices(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let user = seed_user(&db.pool).await;
+    let other = seed_user(&db.pool).await;
+
+    let laptop = db
+        .upsert_device()
+        .id(Uuid::now_v7())
+        .user_id(user)
+        .name("Laptop")
+        .platform("macos")
+        .call()
+        .await
+        .unwrap();
+    let desktop = db
+        .upsert_device()
+        .id(Uuid::now_v7())
+        .user_id(user)
+        .name("Desktop")
+        .platform("windows")
+        .call()
+        .await
+        .unwrap();
+    // Someone else can't claim the laptop's id.
+    assert!(
+        db.upsert_device()
+            .id(laptop.id)
+            .user_id(other)
+            .name("Mine now")
+            .platform("linux")
+            .call()
+            .await
+            .is_err()
+    );
+
+    let on_laptop = live_session(&db, user, Some(laptop.id)).await;
+    let on_desktop = live_session(&db, user, Some(desktop.id)).await;
+    let foreign = live_session(&db, other, Some(laptop.id)).await;
+    assert_eq!(on_laptop.device_id, Some(laptop.id));
+    assert_eq!(on_desktop.device_id, Some(desktop.id));
+    assert_eq!(foreign.device_id, None);
+
+    // The desktop's session didn't close the laptop's.
+    let grouped = db
+        .list_activity_sessions_between()
+        .user_id(user)
+        .since(at(9))
+        .until(at(17))
+        .device_id(laptop.id)
+        .limit(100)
+        .call()
+        .await
+        .unwrap();
+    let sessions: Vec<_> = grouped.iter().flat_map(|(_, s)| s).collect();
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].id, on_laptop.id);
+    assert!(sessions[0].ended_at.is_none());
+
+    db.delete_device()
+        .id(laptop.id)
+        .user_id(user)
+        .call()
+        .await
+        .unwrap();
+    let names: Vec<_> = db
+        .list_devices()
+        .user_id(user)
+        .call()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|d| d.name)
+        .collect();
+    assert_eq!(names, ["Desktop"]);
+}
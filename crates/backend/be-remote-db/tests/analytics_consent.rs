@@ -0,0 +1,32 @@
+//! Integration tests for the per-user analytics consent column.
+//!
+//! Uses `#[sqlx::test]` like the other files here: each test runs against a
+//! freshly migrated, isolated database, and the binary is a no-op without
+//! `DATABASE_URL`.
+
+use be_remote_db::{DatabaseManager, UserAnalyticsConsent};
+use sqlx::PgPool;
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn consent_defaults_to_anonymous_and_can_be_changed(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let user = db
+        .create_user()
+        .email("consent@test.local".into())
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(user.analytics_consent, UserAnalyticsConsent::Anonymous);
+
+    let updated = db
+        .set_analytics_consent()
+        .user_id(user.id)
+        .consent(UserAnalyticsConsent::Off)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(updated.analytics_consent, UserAnalyticsConsent::Off);
+
+    let fetched = db.get_user().id(user.id).call().await.unwrap();
+    assert_eq!(fetched.analytics_consent, UserAnalyticsConsent::Off);
+}
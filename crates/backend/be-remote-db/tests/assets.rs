@@ -5,7 +5,7 @@
 //! makes this binary a no-op so `cargo test` still passes in
 //! database-free environments.
 
-use be_remote_db::DatabaseManager;
+use be_remote_db::{AssetScanStatus, AssetStorageTier, DatabaseManager};
 use sqlx::PgPool;
 use uuid::Uuid;
 
@@ -36,7 +36,7 @@ async fn seed_asset(db: &DatabaseManager, user_id: Uuid) -> Uuid {
 
 #[sqlx::test(migrations = "./src/migrations")]
 async fn get_asset_for_user_returns_owner_row(pool: PgPool) {
-    let db = DatabaseManager { pool };
+    let db = DatabaseManager::from_pool(pool);
     let user_id = seed_user(&db.pool).await;
     let asset_id = seed_asset(&db, user_id).await;
 
@@ -58,7 +58,7 @@ async fn get_asset_for_user_treats_foreign_id_as_not_found(pool: PgPool) {
     // The ownership predicate lives inside the WHERE clause so user A's
     // asset surfaces as `NotFound` (not `Forbidden`) to user B — that
     // prevents probing the existence of another user's asset ids.
-    let db = DatabaseManager { pool };
+    let db = DatabaseManager::from_pool(pool);
     let owner = seed_user(&db.pool).await;
     let intruder = seed_user(&db.pool).await;
     let asset_id = seed_asset(&db, owner).await;
@@ -76,7 +76,7 @@ async fn get_asset_for_user_treats_foreign_id_as_not_found(pool: PgPool) {
 
 #[sqlx::test(migrations = "./src/migrations")]
 async fn get_asset_for_user_returns_not_found_for_missing_asset(pool: PgPool) {
-    let db = DatabaseManager { pool };
+    let db = DatabaseManager::from_pool(pool);
     let user_id = seed_user(&db.pool).await;
 
     let err = db
@@ -89,3 +89,620 @@ async fn get_asset_for_user_returns_not_found_for_missing_asset(pool: PgPool) {
 
     assert!(err.is_not_found(), "expected NotFound, got {err:?}");
 }
+
+async fn seed_activity(pool: &PgPool, user_id: Uuid) -> Uuid {
+    let id = Uuid::now_v7();
+    sqlx::query(
+        "INSERT INTO activities (id, user_id, identity_key, display_name) VALUES ($1, $2, $3, $3)",
+    )
+    .bind(id)
+    .bind(user_id)
+    .bind(format!("app-{id}"))
+    .execute(pool)
+    .await
+    .expect("seed activity");
+    id
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn batch_get_and_delete_skip_foreign_assets(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let owner = seed_user(&db.pool).await;
+    let other = seed_user(&db.pool).await;
+    let mine = seed_asset(&db, owner).await;
+    let theirs = seed_asset(&db, other).await;
+    let ids = [mine, theirs, Uuid::now_v7()];
+
+    let fetched = db
+        .get_assets_for_user()
+        .ids(&ids)
+        .user_id(owner)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(fetched.iter().map(|a| a.id).collect::<Vec<_>>(), [mine]);
+
+    let deleted = db
+        .delete_assets_for_user()
+        .ids(&ids)
+        .user_id(owner)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(deleted, [mine]);
+
+    // The other user's asset survives.
+    db.get_asset_for_user()
+        .asset_id(theirs)
+        .user_id(other)
+        .call()
+        .await
+        .expect("foreign asset kept");
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn link_assets_to_activity_is_idempotent_and_owner_scoped(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let owner = seed_user(&db.pool).await;
+    let other = seed_user(&db.pool).await;
+    let activity = seed_activity(&db.pool, owner).await;
+    let a = seed_asset(&db, owner).await;
+    let b = seed_asset(&db, owner).await;
+    let theirs = seed_asset(&db, other).await;
+
+    let mut linked = db
+        .link_assets_to_activity()
+        .activity_id(activity)
+        .user_id(owner)
+        .asset_ids(&[a, theirs])
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(linked, [a]);
+
+    // Re-linking `a` still reports it.
+    linked = db
+        .link_assets_to_activity()
+        .activity_id(activity)
+        .user_id(owner)
+        .asset_ids(&[a, b])
+        .call()
+        .await
+        .unwrap();
+    linked.sort();
+    let mut expected = vec![a, b];
+    expected.sort();
+    assert_eq!(linked, expected);
+
+    let rows: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM activity_assets WHERE activity_id = $1")
+            .bind(activity)
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+    assert_eq!(rows, 2);
+
+    // Someone else's activity is not found.
+    let err = db
+        .link_assets_to_activity()
+        .activity_id(activity)
+        .user_id(other)
+        .asset_ids(&[theirs])
+        .call()
+        .await
+        .unwrap_err();
+    assert!(err.is_not_found());
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn pending_scans_are_claimed_once_and_finished(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool.clone());
+    let user = seed_user(&pool).await;
+    let unscanned = seed_asset(&db, user).await;
+    let pending = db
+        .create_asset()
+        .user_id(user)
+        .name("upload.pdf".to_owned())
+        .mime_type("application/pdf".to_owned())
+        .storage_backend("filesystem".to_owned())
+        .storage_uri(format!("file:///tmp/{}.pdf", Uuid::now_v7()))
+        .scan_status(AssetScanStatus::Pending)
+        .call()
+        .await
+        .expect("create pending asset")
+        .id;
+
+    let lease = chrono::Duration::minutes(5);
+    let claimed = db
+        .claim_pending_asset_scans()
+        .limit(10)
+        .lease(lease)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(claimed.len(), 1);
+    assert_eq!(claimed[0].id, pending);
+    assert_eq!(claimed[0].attempts, 1);
+
+    // Leased: a second worker gets nothing.
+    let again = db
+        .claim_pending_asset_scans()
+        .limit(10)
+        .lease(lease)
+        .call()
+        .await
+        .unwrap();
+    assert!(again.is_empty());
+
+    let finished = db
+        .finish_asset_scan()
+        .id(pending)
+        .status(AssetScanStatus::Quarantined)
+        .signature("Eicar-Test-Signature".to_owned())
+        .call()
+        .await
+        .unwrap();
+    assert!(finished);
+
+    let asset = db
+        .get_asset_for_user()
+        .asset_id(pending)
+        .user_id(user)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(asset.scan_status, AssetScanStatus::Quarantined);
+    assert_eq!(
+        asset.scan_signature.as_deref(),
+        Some("Eicar-Test-Signature")
+    );
+    assert!(asset.scanned_at.is_some());
+
+    // Only pending rows take a verdict.
+    let refinished = db
+        .finish_asset_scan()
+        .id(pending)
+        .status(AssetScanStatus::Clean)
+        .call()
+        .await
+        .unwrap();
+    assert!(!refinished);
+
+    let untouched = db
+        .get_asset_for_user()
+        .asset_id(unscanned)
+        .user_id(user)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(untouched.scan_status, AssetScanStatus::Skipped);
+
+    // Corruption quarantines regardless of the scan state.
+    assert!(
+        db.quarantine_asset()
+            .id(unscanned)
+            .signature("integrity:sha256-mismatch")
+            .call()
+            .await
+            .unwrap()
+    );
+    let corrupted = db
+        .get_asset_for_user()
+        .asset_id(unscanned)
+        .user_id(user)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(corrupted.scan_status, AssetScanStatus::Quarantined);
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn image_metadata_stripping_defaults_on_and_can_be_disabled(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let user_id = seed_user(&db.pool).await;
+
+    assert!(
+        db.get_strip_image_metadata()
+            .user_id(user_id)
+            .call()
+            .await
+            .unwrap()
+    );
+
+    db.set_strip_image_metadata()
+        .user_id(user_id)
+        .enabled(false)
+        .call()
+        .await
+        .unwrap();
+    assert!(
+        !db.get_strip_image_metadata()
+            .user_id(user_id)
+            .call()
+            .await
+            .unwrap()
+    );
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn shared_assets_are_readable_until_revoked_or_expired(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let owner = seed_user(&db.pool).await;
+    let friend = seed_user(&db.pool).await;
+    let asset_id = seed_asset(&db, owner).await;
+    let read = |user_id: Uuid| {
+        db.get_asset_for_user()
+            .asset_id(asset_id)
+            .user_id(user_id)
+            .call()
+    };
+
+    // Only the owner can share.
+    let err = db
+        .share_asset_with_user()
+        .asset_id(asset_id)
+        .owner_id(friend)
+        .grantee_id(owner)
+        .call()
+        .await
+        .expect_err("non-owner must not share");
+    assert!(err.is_not_found(), "expected NotFound, got {err:?}");
+
+    let grant = db
+        .share_asset_with_user()
+        .asset_id(asset_id)
+        .owner_id(owner)
+        .grantee_id(friend)
+        .call()
+        .await
+        .expect("share");
+    assert_eq!(read(friend).await.expect("grantee reads").id, asset_id);
+    let shared = db
+        .list_assets_shared_with_user()
+        .user_id(friend)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(shared.len(), 1);
+
+    db.delete_asset_grant()
+        .grant_id(grant.id)
+        .asset_id(asset_id)
+        .owner_id(owner)
+        .call()
+        .await
+        .expect("revoke");
+    assert!(read(friend).await.unwrap_err().is_not_found());
+
+    // Sharing again with a past expiry is as good as no grant.
+    db.share_asset_with_user()
+        .asset_id(asset_id)
+        .owner_id(owner)
+        .grantee_id(friend)
+        .expires_at(chrono::Utc::now() - chrono::Duration::minutes(1))
+        .call()
+        .await
+        .expect("share");
+    assert!(read(friend).await.unwrap_err().is_not_found());
+    assert!(
+        db.list_assets_shared_with_user()
+            .user_id(friend)
+            .call()
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn asset_links_open_only_their_asset_until_expiry(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let owner = seed_user(&db.pool).await;
+    let asset_id = seed_asset(&db, owner).await;
+    let live = [1u8; 32];
+    let expired = [2u8; 32];
+
+    for (hash, expires_at) in [
+        (&live, chrono::Utc::now() + chrono::Duration::hours(1)),
+        (&expired, chrono::Utc::now() - chrono::Duration::hours(1)),
+    ] {
+        db.create_asset_link()
+            .asset_id(asset_id)
+            .owner_id(owner)
+            .token_hash(hash)
+            .expires_at(expires_at)
+            .call()
+            .await
+            .expect("create link");
+    }
+
+    assert_eq!(db.get_asset_by_link(&live).await.unwrap().id, asset_id);
+    assert!(
+        db.get_asset_by_link(&expired)
+            .await
+            .unwrap_err()
+            .is_not_found()
+    );
+    assert!(
+        db.get_asset_by_link(&[3u8; 32])
+            .await
+            .unwrap_err()
+            .is_not_found()
+    );
+
+    let grants = db
+        .list_asset_grants()
+        .asset_id(asset_id)
+        .owner_id(owner)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(grants.len(), 2);
+    assert!(grants.iter().all(|g| g.grantee_id.is_none()));
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn old_assets_are_listed_for_the_cold_tier_until_moved(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let user_id = seed_user(&db.pool).await;
+    let old = seed_asset(&db, user_id).await;
+    let fresh = seed_asset(&db, user_id).await;
+    sqlx::query("UPDATE assets SET created_at = now() - interval '100 days' WHERE id = $1")
+        .bind(old)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+
+    let listed = db
+        .list_assets_for_cold_tier()
+        .cold_after_days(90)
+        .limit(10)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(listed.iter().map(|a| a.id).collect::<Vec<_>>(), [old]);
+    let hot_uri = listed[0].storage_uri.clone();
+    let cold_uri = format!("cold:{hot_uri}");
+
+    // A stale source URI means someone else moved or replaced it first.
+    assert!(
+        !db.move_asset_to_tier()
+            .id(old)
+            .from_uri("elsewhere")
+            .to_uri(&cold_uri)
+            .tier(AssetStorageTier::Cold)
+            .call()
+            .await
+            .unwrap()
+    );
+    assert!(
+        db.move_asset_to_tier()
+            .id(old)
+            .from_uri(&hot_uri)
+            .to_uri(&cold_uri)
+            .tier(AssetStorageTier::Cold)
+            .call()
+            .await
+            .unwrap()
+    );
+    let moved = db
+        .get_asset_for_user()
+        .asset_id(old)
+        .user_id(user_id)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(moved.storage_tier, AssetStorageTier::Cold);
+    assert_eq!(moved.storage_uri, cold_uri);
+
+    // Restored assets stay hot for another full period.
+    db.move_asset_to_tier()
+        .id(old)
+        .from_uri(&cold_uri)
+        .to_uri(&hot_uri)
+        .tier(AssetStorageTier::Hot)
+        .call()
+        .await
+        .unwrap();
+    let listed = db
+        .list_assets_for_cold_tier()
+        .cold_after_days(90)
+        .limit(10)
+        .call()
+        .await
+        .unwrap();
+    assert!(
+        listed.is_empty(),
+        "{fresh} is too new, {old} was just restored"
+    );
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn upload_sessions_track_chunks_until_deleted_or_expired(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let user = seed_user(&db.pool).await;
+    let stranger = seed_user(&db.pool).await;
+    let in_a_day = chrono::Utc::now() + chrono::Duration::days(1);
+
+    let session = db
+        .create_upload_session()
+        .id(Uuid::now_v7())
+        .user_id(user)
+        .name("recording.webm")
+        .mime_type("application/octet-stream")
+        .total_size(10)
+        .chunk_size(4)
+        .expires_at(in_a_day)
+        .max_open(5)
+        .call()
+        .await
+        .unwrap()
+        .unwrap();
+
+    for (offset, size) in [(4, 4), (0, 4), (4, 4)] {
+        db.record_upload_chunk()
+            .session_id(session.id)
+            .user_id(user)
+            .byte_offset(offset)
+            .size(size)
+            .storage_uri(&format!("uploads/{}/{offset}", session.id))
+            .expires_at(in_a_day)
+            .call()
+            .await
+            .unwrap();
+    }
+    let offsets: Vec<i64> = db
+        .list_upload_chunks(session.id)
+        .await
+        .unwrap()
+        .iter()
+        .map(|c| c.byte_offset)
+        .collect();
+    assert_eq!(offsets, [0, 4], "a resent chunk replaces the first copy");
+
+    // Someone else's session is invisible.
+    let err = db
+        .record_upload_chunk()
+        .session_id(session.id)
+        .user_id(stranger)
+        .byte_offset(8)
+        .size(2)
+        .storage_uri("uploads/elsewhere")
+        .expires_at(in_a_day)
+        .call()
+        .await
+        .unwrap_err();
+    assert!(err.is_not_found());
+
+    let uris = db
+        .delete_upload_session()
+        .id(session.id)
+        .user_id(user)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(uris.len(), 2);
+    assert!(
+        db.get_upload_session()
+            .id(session.id)
+            .user_id(user)
+            .call()
+            .await
+            .unwrap_err()
+            .is_not_found()
+    );
+
+    // An idle session expires with its chunks.
+    let stale = db
+        .create_upload_session()
+        .id(Uuid::now_v7())
+        .user_id(user)
+        .name("draft.pdf")
+        .mime_type("application/pdf")
+        .total_size(4)
+        .chunk_size(4)
+        .expires_at(in_a_day)
+        .max_open(5)
+        .call()
+        .await
+        .unwrap()
+        .unwrap();
+    db.record_upload_chunk()
+        .session_id(stale.id)
+        .user_id(user)
+        .byte_offset(0)
+        .size(4)
+        .storage_uri("uploads/stale/0")
+        .expires_at(chrono::Utc::now() - chrono::Duration::seconds(1))
+        .call()
+        .await
+        .unwrap();
+
+    let expired = db.delete_expired_upload_sessions(10).await.unwrap();
+    assert_eq!(expired.len(), 1);
+    assert_eq!(expired[0].id, stale.id);
+    assert_eq!(expired[0].chunk_uris, ["uploads/stale/0"]);
+    assert!(
+        db.delete_expired_upload_sessions(10)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn upload_sessions_are_capped_per_user_even_when_opened_at_once(pool: PgPool) {
+    let db = std::sync::Arc::new(DatabaseManager::from_pool(pool));
+    let user = seed_user(&db.pool).await;
+    let in_a_day = chrono::Utc::now() + chrono::Duration::days(1);
+
+    let opens = (0..8).map(|_| {
+        let db = db.clone();
+        tokio::spawn(async move {
+            db.create_upload_session()
+                .id(Uuid::now_v7())
+                .user_id(user)
+                .name("recording.webm")
+                .mime_type("application/octet-stream")
+                .total_size(10)
+                .chunk_size(4)
+                .expires_at(in_a_day)
+                .max_open(3)
+                .call()
+                .await
+                .unwrap()
+        })
+    });
+    let mut opened = 0;
+    for open in opens.collect::<Vec<_>>() {
+        if open.await.unwrap().is_some() {
+            opened += 1;
+        }
+    }
+    assert_eq!(opened, 3);
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn upload_sessions_keep_only_the_users_own_device(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let user = seed_user(&db.pool).await;
+    let other = seed_user(&db.pool).await;
+    let in_a_day = chrono::Utc::now() + chrono::Duration::days(1);
+
+    let laptop = db
+        .upsert_device()
+        .id(Uuid::now_v7())
+        .user_id(user)
+        .name("Laptop")
+        .platform("macos")
+        .call()
+        .await
+        .unwrap();
+
+    for (owner, expected) in [(user, Some(laptop.id)), (other, None)] {
+        let session = db
+            .create_upload_session()
+            .id(Uuid::now_v7())
+            .user_id(owner)
+            .name("draft.pdf")
+            .mime_type("application/pdf")
+            .device_id(laptop.id)
+            .total_size(4)
+            .chunk_size(4)
+            .expires_at(in_a_day)
+            .max_open(5)
+            .call()
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(session.device_id, expected);
+        let stored = db
+            .get_upload_session()
+            .id(session.id)
+            .user_id(owner)
+            .call()
+            .await
+            .unwrap();
+        assert_eq!(stored.device_id, expected);
+    }
+}
@@ -0,0 +1,188 @@
+//! Integration tests for the automations DB layer.
+//!
+//! Uses `#[sqlx::test]` like the other files here: each test runs against a
+//! freshly migrated, isolated database, and the binary is a no-op without
+//! `DATABASE_URL`.
+
+use be_remote_db::{Automation, AutomationRunStatus, DatabaseManager, PaginationParams};
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+async fn seed_user(pool: &PgPool) -> Uuid {
+    let id = Uuid::now_v7();
+    sqlx::query("INSERT INTO users (id, email) VALUES ($1, $2)")
+        .bind(id)
+        .bind(format!("user-{id}@test.local"))
+        .execute(pool)
+        .await
+        .expect("seed user");
+    id
+}
+
+async fn seed_automation(db: &DatabaseManager, user_id: Uuid, due_in: Duration) -> Automation {
+    db.create_automation()
+        .user_id(user_id)
+        .name("Morning summary".to_owned())
+        .prompt("Summarize yesterday's activity.".to_owned())
+        .schedule("0 7 * * *".to_owned())
+        .tools(vec!["web_search".to_owned()])
+        .enabled(true)
+        .next_run_at(Utc::now() + due_in)
+        .call()
+        .await
+        .expect("create_automation")
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn get_automation_treats_foreign_id_as_not_found(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let owner = seed_user(&db.pool).await;
+    let other = seed_user(&db.pool).await;
+    let automation = seed_automation(&db, owner, Duration::hours(1)).await;
+
+    let err = db
+        .get_automation()
+        .id(automation.id)
+        .user_id(other)
+        .call()
+        .await
+        .expect_err("foreign automation must not resolve");
+    assert!(err.is_not_found());
+
+    let err = db
+        .delete_automation()
+        .id(automation.id)
+        .user_id(other)
+        .call()
+        .await
+        .expect_err("foreign automation must not be deletable");
+    assert!(err.is_not_found());
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn claim_due_automations_skips_future_and_leased_rows(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let user_id = seed_user(&db.pool).await;
+    let due = seed_automation(&db, user_id, Duration::seconds(-5)).await;
+    seed_automation(&db, user_id, Duration::hours(1)).await;
+
+    let claimed = db
+        .claim_due_automations()
+        .limit(10)
+        .lease(Duration::minutes(10))
+        .call()
+        .await
+        .expect("claim");
+    assert_eq!(claimed.len(), 1);
+    assert_eq!(claimed[0].automation.id, due.id);
+    assert_eq!(Some(claimed[0].scheduled_for), due.next_run_at);
+    assert!(claimed[0].automation.next_run_at > due.next_run_at);
+
+    // The lease keeps a second worker from picking the same row up.
+    let again = db
+        .claim_due_automations()
+        .limit(10)
+        .lease(Duration::minutes(10))
+        .call()
+        .await
+        .expect("claim again");
+    assert!(again.is_empty());
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn reschedule_automation_keeps_edits_made_during_a_run(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let user_id = seed_user(&db.pool).await;
+    let automation = seed_automation(&db, user_id, Duration::seconds(-5)).await;
+    let claimed = db
+        .claim_due_automations()
+        .limit(1)
+        .lease(Duration::minutes(10))
+        .call()
+        .await
+        .expect("claim")
+        .remove(0);
+    let lease = claimed.automation.next_run_at.expect("lease");
+
+    let edited_next = Utc::now() + Duration::days(3);
+    db.update_automation()
+        .id(automation.id)
+        .user_id(user_id)
+        .name(automation.name.clone())
+        .prompt(automation.prompt.clone())
+        .schedule("0 9 * * 1".to_owned())
+        .tools(automation.tools.clone())
+        .enabled(true)
+        .next_run_at(edited_next)
+        .call()
+        .await
+        .expect("update");
+
+    let now = Utc::now();
+    db.reschedule_automation()
+        .id(automation.id)
+        .lease(lease)
+        .last_run_at(now)
+        .next_run_at(now + Duration::days(1))
+        .call()
+        .await
+        .expect("reschedule");
+
+    let stored = db
+        .get_automation()
+        .id(automation.id)
+        .user_id(user_id)
+        .call()
+        .await
+        .expect("get");
+    assert_eq!(
+        stored.next_run_at.map(|t| t.timestamp_micros()),
+        Some(edited_next.timestamp_micros())
+    );
+    assert!(stored.last_run_at.is_some());
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn automation_runs_are_listed_newest_first(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let user_id = seed_user(&db.pool).await;
+    let automation = seed_automation(&db, user_id, Duration::hours(1)).await;
+
+    let mut ids = Vec::new();
+    for _ in 0..2 {
+        let run = db
+            .create_automation_run()
+            .automation_id(automation.id)
+            .user_id(user_id)
+            .scheduled_for(Utc::now())
+            .call()
+            .await
+            .expect("create run");
+        assert_eq!(run.status, AutomationRunStatus::Running);
+        ids.push(run.id);
+    }
+    let finished = db
+        .finish_automation_run()
+        .id(ids[0])
+        .status(AutomationRunStatus::Succeeded)
+        .output("done".to_owned())
+        .call()
+        .await
+        .expect("finish run");
+    assert!(finished.finished_at.is_some());
+
+    let runs = db
+        .list_automation_runs()
+        .automation_id(automation.id)
+        .user_id(user_id)
+        .params(PaginationParams::new(0, 10, "desc"))
+        .call()
+        .await
+        .expect("list runs");
+    assert_eq!(
+        runs.iter().map(|r| r.id).collect::<Vec<_>>(),
+        vec![ids[1], ids[0]]
+    );
+    assert_eq!(runs[1].output.as_deref(), Some("done"));
+}
@@ -0,0 +1,156 @@
+//! Integration tests for the data-export DB layer.
+//!
+//! Uses `#[sqlx::test]` like the other files here: each test runs against a
+//! freshly migrated, isolated database, and the binary is a no-op without
+//! `DATABASE_URL`.
+
+use be_remote_db::{DataExportAssetMode, DataExportStatus, DatabaseManager};
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+async fn seed_user(pool: &PgPool) -> Uuid {
+    let id = Uuid::now_v7();
+    sqlx::query("INSERT INTO users (id, email) VALUES ($1, $2)")
+        .bind(id)
+        .bind(format!("user-{id}@test.local"))
+        .execute(pool)
+        .await
+        .expect("seed user");
+    id
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn one_export_in_flight_per_user(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let user_id = seed_user(&db.pool).await;
+    let other = seed_user(&db.pool).await;
+
+    let export = db
+        .create_data_export()
+        .user_id(user_id)
+        .asset_mode(DataExportAssetMode::Decrypted)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(export.status, DataExportStatus::Pending);
+
+    let err = db
+        .create_data_export()
+        .user_id(user_id)
+        .asset_mode(DataExportAssetMode::AsStored)
+        .call()
+        .await
+        .expect_err("second in-flight export must be rejected");
+    assert!(err.is_unique_violation());
+
+    // Other users are unaffected, and can't see the first user's export.
+    db.create_data_export()
+        .user_id(other)
+        .asset_mode(DataExportAssetMode::AsStored)
+        .call()
+        .await
+        .unwrap();
+    let err = db
+        .get_data_export()
+        .id(export.id)
+        .user_id(other)
+        .call()
+        .await
+        .expect_err("foreign export must not resolve");
+    assert!(err.is_not_found());
+
+    // Once the first finishes, a new one can be queued.
+    let claimed = db
+        .claim_pending_data_exports()
+        .limit(10)
+        .lease(Duration::minutes(5))
+        .call()
+        .await
+        .unwrap();
+    db.fail_data_export()
+        .id(export.id)
+        .error("boom".to_owned())
+        .call()
+        .await
+        .unwrap();
+    assert!(claimed.iter().any(|e| e.id == export.id));
+    let failed = db
+        .get_data_export()
+        .id(export.id)
+        .user_id(user_id)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(failed.status, DataExportStatus::Failed);
+    db.create_data_export()
+        .user_id(user_id)
+        .asset_mode(DataExportAssetMode::Decrypted)
+        .call()
+        .await
+        .unwrap();
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn claim_complete_and_expire(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let user_id = seed_user(&db.pool).await;
+    let export = db
+        .create_data_export()
+        .user_id(user_id)
+        .asset_mode(DataExportAssetMode::Decrypted)
+        .call()
+        .await
+        .unwrap();
+
+    let claimed = db
+        .claim_pending_data_exports()
+        .limit(10)
+        .lease(Duration::minutes(5))
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(claimed.len(), 1);
+    assert_eq!(claimed[0].status, DataExportStatus::Running);
+    assert_eq!(claimed[0].attempts, 1);
+
+    // Leased, so a second worker doesn't pick it up.
+    assert!(
+        db.claim_pending_data_exports()
+            .limit(10)
+            .lease(Duration::minutes(5))
+            .call()
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    let ready = db
+        .complete_data_export()
+        .id(export.id)
+        .storage_uri("exports/archive.zip".to_owned())
+        .size_bytes(42)
+        .expires_at(Utc::now() - Duration::seconds(1))
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(ready.status, DataExportStatus::Ready);
+    assert_eq!(ready.lease_until, None);
+
+    let expired = db.expire_data_exports().limit(10).call().await.unwrap();
+    assert_eq!(expired.len(), 1);
+    assert_eq!(
+        expired[0].storage_uri.as_deref(),
+        Some("exports/archive.zip")
+    );
+
+    let row = db
+        .get_data_export()
+        .id(export.id)
+        .user_id(user_id)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(row.status, DataExportStatus::Expired);
+    assert_eq!(row.storage_uri, None);
+}
@@ -0,0 +1,110 @@
+//! Integration tests for the user memory store.
+//!
+//! Uses `#[sqlx::test]` like the other files here: each test runs against a
+//! freshly migrated, isolated database, and the binary is a no-op without
+//! `DATABASE_URL`.
+
+use be_remote_db::DatabaseManager;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+async fn seed_user(pool: &PgPool) -> Uuid {
+    let id = Uuid::now_v7();
+    sqlx::query("INSERT INTO users (id, email) VALUES ($1, $2)")
+        .bind(id)
+        .bind(format!("user-{id}@test.local"))
+        .execute(pool)
+        .await
+        .expect("seed user");
+    id
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn extracted_facts_skip_ones_the_user_already_has(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let user = seed_user(&db.pool).await;
+    let thread = db
+        .create_thread()
+        .user_id(user)
+        .title("chat".to_owned())
+        .call()
+        .await
+        .unwrap();
+
+    db.create_memory()
+        .user_id(user)
+        .content("Uses Arch Linux")
+        .call()
+        .await
+        .unwrap();
+    let inserted = db
+        .create_memories()
+        .user_id(user)
+        .thread_id(thread.id)
+        .contents(vec!["uses arch linux".into(), "Manager is Dana".into()])
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(inserted.len(), 1);
+    assert_eq!(inserted[0].content, "Manager is Dana");
+    assert_eq!(inserted[0].thread_id, Some(thread.id));
+
+    let err = db
+        .create_memory()
+        .user_id(user)
+        .content("MANAGER IS DANA")
+        .call()
+        .await
+        .expect_err("a fact the user already has is a conflict");
+    assert!(err.is_unique_violation());
+    assert_eq!(db.count_memories().user_id(user).call().await.unwrap(), 2);
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn memories_are_scoped_to_their_owner(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let owner = seed_user(&db.pool).await;
+    let other = seed_user(&db.pool).await;
+
+    let memory = db
+        .create_memory()
+        .user_id(owner)
+        .content("Prefers metric units")
+        .call()
+        .await
+        .unwrap();
+
+    let err = db
+        .update_memory()
+        .id(memory.id)
+        .user_id(other)
+        .content("Prefers imperial units")
+        .call()
+        .await
+        .expect_err("another user's memory must not be editable");
+    assert!(err.is_not_found());
+    assert!(
+        db.list_memories()
+            .user_id(other)
+            .call()
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    let deleted = db
+        .delete_all_memories()
+        .user_id(owner)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(deleted, 1);
+    assert!(
+        db.list_memories()
+            .user_id(owner)
+            .call()
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
@@ -0,0 +1,168 @@
+//! Integration tests for the notification log and its `LISTEN` channel.
+//!
+//! Uses `#[sqlx::test]` like the other files here: each test runs against a
+//! freshly migrated, isolated database, and the binary is a no-op without
+//! `DATABASE_URL`.
+
+use be_remote_db::DatabaseManager;
+use chrono::{Duration, Utc};
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+async fn seed_user(pool: &PgPool) -> Uuid {
+    let id = Uuid::now_v7();
+    sqlx::query("INSERT INTO users (id, email) VALUES ($1, $2)")
+        .bind(id)
+        .bind(format!("user-{id}@test.local"))
+        .execute(pool)
+        .await
+        .expect("seed user");
+    id
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn backfill_includes_broadcasts_but_not_other_users(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let user_id = seed_user(&db.pool).await;
+    let other = seed_user(&db.pool).await;
+
+    let mut ids = Vec::new();
+    for (recipient, n) in [
+        (Some(user_id), 1),
+        (Some(other), 2),
+        (None, 3),
+        (Some(user_id), 4),
+    ] {
+        let notification = db
+            .create_notification()
+            .executor(&db.pool)
+            .maybe_user_id(recipient)
+            .payload(&json!({ "n": n }))
+            .call()
+            .await
+            .unwrap();
+        ids.push(notification.id);
+    }
+
+    let all = db
+        .list_notifications_after()
+        .user_id(user_id)
+        .after(0)
+        .limit(10)
+        .call()
+        .await
+        .unwrap();
+    let seen: Vec<_> = all.iter().map(|n| n.payload["n"].clone()).collect();
+    assert_eq!(seen, [json!(1), json!(3), json!(4)]);
+
+    let newer = db
+        .list_notifications_after()
+        .user_id(user_id)
+        .after(ids[2])
+        .limit(10)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(newer.len(), 1);
+    assert_eq!(newer[0].id, ids[3]);
+    assert_eq!(db.latest_notification_id().await.unwrap(), ids[3]);
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn listener_receives_committed_notifications_only(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let user_id = seed_user(&db.pool).await;
+    let mut listener = db.notification_listener().await.unwrap();
+
+    let mut tx = db.pool.begin().await.unwrap();
+    db.create_notification()
+        .executor(&mut *tx)
+        .user_id(user_id)
+        .payload(&json!({ "n": "rolled back" }))
+        .call()
+        .await
+        .unwrap();
+    tx.rollback().await.unwrap();
+
+    let created = db
+        .create_notification()
+        .executor(&db.pool)
+        .user_id(user_id)
+        .payload(&json!({ "n": "committed" }))
+        .call()
+        .await
+        .unwrap();
+
+    let received = listener.recv().await.unwrap().expect("notification");
+    assert_eq!(received, created);
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn stripe_customer_notifications_reach_the_billed_user(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let user_id = seed_user(&db.pool).await;
+    db.upsert_stripe_customer()
+        .executor(&db.pool)
+        .customer_id("cus_123")
+        .app_user_id(user_id)
+        .raw_data(&json!({}))
+        .call()
+        .await
+        .unwrap();
+    db.link_stripe_customer_to_user()
+        .executor(&db.pool)
+        .user_id(user_id)
+        .stripe_customer_id("cus_123")
+        .call()
+        .await
+        .unwrap();
+
+    db.create_notification_for_stripe_customer()
+        .executor(&db.pool)
+        .stripe_customer_id("cus_123")
+        .payload(&json!({ "type": "subscription_changed" }))
+        .call()
+        .await
+        .unwrap();
+
+    let notifications = db
+        .list_notifications_after()
+        .user_id(user_id)
+        .after(0)
+        .limit(10)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(notifications.len(), 1);
+    assert_eq!(notifications[0].user_id, Some(user_id));
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn prune_drops_old_notifications(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let user_id = seed_user(&db.pool).await;
+    db.create_notification()
+        .executor(&db.pool)
+        .user_id(user_id)
+        .payload(&json!({}))
+        .call()
+        .await
+        .unwrap();
+
+    let kept = db
+        .prune_notifications()
+        .cutoff(Utc::now() - Duration::days(1))
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(kept, 0);
+
+    let pruned = db
+        .prune_notifications()
+        .cutoff(Utc::now() + Duration::days(1))
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(pruned, 1);
+}
@@ -0,0 +1,82 @@
+//! Integration tests for routing list queries to a read replica.
+//!
+//! The "replica" is either the test database itself (healthy) or a lazy
+//! pool pointed at a closed port (unreachable), which is enough to
+//! exercise the fallback without a second Postgres.
+
+use std::time::Duration;
+
+use be_remote_db::{DatabaseManager, PaginationParams};
+use sqlx::PgPool;
+use sqlx::postgres::PgPoolOptions;
+
+fn unreachable_replica() -> PgPool {
+    PgPoolOptions::new()
+        .acquire_timeout(Duration::from_millis(500))
+        .connect_lazy("postgres://postgres@127.0.0.1:1/replica")
+        .unwrap()
+}
+
+async fn seed_thread(db: &DatabaseManager) -> uuid::Uuid {
+    let user = db
+        .create_user()
+        .email("replica@test.local".into())
+        .call()
+        .await
+        .unwrap();
+    db.create_thread()
+        .user_id(user.id)
+        .title("hello".into())
+        .call()
+        .await
+        .unwrap();
+    user.id
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn lists_read_from_a_healthy_replica(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool.clone()).with_replica(pool);
+    let user_id = seed_thread(&db).await;
+
+    let threads = db
+        .list_threads()
+        .user_id(user_id)
+        .params(PaginationParams::new(0, 10, "desc"))
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(threads.len(), 1);
+
+    let health = db.pool_health();
+    assert!(health.replica.is_some());
+    assert!(health.replica_available);
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn unreachable_replica_falls_back_to_primary(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool).with_replica(unreachable_replica());
+    let user_id = seed_thread(&db).await;
+
+    let threads = db
+        .list_threads()
+        .user_id(user_id)
+        .params(PaginationParams::new(0, 10, "desc"))
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(threads.len(), 1);
+
+    // The failed replica is benched, so the next read skips it.
+    let health = db.pool_health();
+    assert!(!health.replica_available);
+    assert_eq!(health.replica.unwrap().size, 0);
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn without_replica_health_reports_primary_only(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let health = db.pool_health();
+    assert!(health.replica.is_none());
+    assert!(!health.replica_available);
+    assert!(health.primary.max_connections > 0);
+}
@@ -0,0 +1,298 @@
+//! Integration tests for the retention DB layer.
+//!
+//! Uses `#[sqlx::test]` like the other files here: each test runs against a
+//! freshly migrated, isolated database, and the binary is a no-op without
+//! `DATABASE_URL`.
+
+use be_remote_db::{DatabaseManager, LegalHoldSubject, RetentionCategory};
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+async fn seed_user(pool: &PgPool) -> Uuid {
+    let id = Uuid::now_v7();
+    sqlx::query("INSERT INTO users (id, email) VALUES ($1, $2)")
+        .bind(id)
+        .bind(format!("user-{id}@test.local"))
+        .execute(pool)
+        .await
+        .expect("seed user");
+    id
+}
+
+async fn seed_asset(db: &DatabaseManager, user_id: Uuid, mime_type: &str, age_days: i64) -> Uuid {
+    let id = db
+        .create_asset()
+        .user_id(user_id)
+        .name("capture".to_owned())
+        .mime_type(mime_type.to_owned())
+        .size_bytes(10)
+        .storage_backend("filesystem".to_owned())
+        .storage_uri(format!("assets/{}", Uuid::now_v7()))
+        .call()
+        .await
+        .expect("create_asset")
+        .id;
+    sqlx::query("UPDATE assets SET created_at = $2 WHERE id = $1")
+        .bind(id)
+        .bind(Utc::now() - Duration::days(age_days))
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    id
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn expired_assets_respect_limits_and_exemptions(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let user_id = seed_user(&db.pool).await;
+    let keeper = seed_user(&db.pool).await;
+
+    let old = seed_asset(&db, user_id, "image/png", 10).await;
+    let fresh = seed_asset(&db, user_id, "image/png", 1).await;
+    let pinned = seed_asset(&db, user_id, "image/png", 10).await;
+    let text = seed_asset(&db, user_id, "text/plain", 10).await;
+    let kept_forever = seed_asset(&db, keeper, "image/png", 10).await;
+    db.set_asset_pinned()
+        .id(pinned)
+        .user_id(user_id)
+        .pinned(true)
+        .call()
+        .await
+        .unwrap();
+    db.set_retention_settings()
+        .user_id(keeper)
+        .settings(vec![(RetentionCategory::Screenshots, None)])
+        .call()
+        .await
+        .unwrap();
+
+    let expired = db
+        .list_expired_assets()
+        .category(RetentionCategory::Screenshots)
+        .default_max_age_days(7)
+        .limit(10)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(expired.iter().map(|a| a.id).collect::<Vec<_>>(), [old]);
+
+    let preview = db
+        .count_expired_items()
+        .user_id(user_id)
+        .category(RetentionCategory::CapturedText)
+        .cutoff(Utc::now() - Duration::days(7))
+        .call()
+        .await
+        .unwrap();
+    assert_eq!((preview.count, preview.size_bytes), (1, 10));
+
+    assert_eq!(db.delete_assets().ids(&[old]).call().await.unwrap(), 1);
+    for id in [fresh, pinned, text] {
+        db.get_asset_for_user()
+            .asset_id(id)
+            .user_id(user_id)
+            .call()
+            .await
+            .unwrap();
+    }
+    db.get_asset_for_user()
+        .asset_id(kept_forever)
+        .user_id(keeper)
+        .call()
+        .await
+        .unwrap();
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn starred_threads_and_their_assets_are_kept(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let user_id = seed_user(&db.pool).await;
+
+    let mut threads = Vec::new();
+    for _ in 0..2 {
+        threads.push(
+            db.create_thread()
+                .user_id(user_id)
+                .title("t".into())
+                .call()
+                .await
+                .unwrap()
+                .id,
+        );
+    }
+    let (starred, plain) = (threads[0], threads[1]);
+    db.set_thread_starred()
+        .id(starred)
+        .user_id(user_id)
+        .starred(true)
+        .call()
+        .await
+        .unwrap();
+
+    let attachment = seed_asset(&db, user_id, "image/png", 10).await;
+    let message_id = Uuid::now_v7();
+    sqlx::query(
+        "INSERT INTO messages (id, thread_id, user_id, message_type, content) \
+         VALUES ($1, $2, $3, 'human', '[]')",
+    )
+    .bind(message_id)
+    .bind(starred)
+    .bind(user_id)
+    .execute(&db.pool)
+    .await
+    .unwrap();
+    sqlx::query("INSERT INTO message_assets (message_id, asset_id) VALUES ($1, $2)")
+        .bind(message_id)
+        .bind(attachment)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    // The `updated_at` trigger would undo the backdating.
+    for sql in [
+        "ALTER TABLE threads DISABLE TRIGGER update_threads_updated_at",
+        "UPDATE threads SET updated_at = now() - interval '400 days'",
+    ] {
+        sqlx::query(sql).execute(&db.pool).await.unwrap();
+    }
+
+    let expired = db
+        .list_expired_assets()
+        .category(RetentionCategory::Screenshots)
+        .default_max_age_days(7)
+        .limit(10)
+        .call()
+        .await
+        .unwrap();
+    assert!(expired.is_empty());
+
+    // Threads are kept forever by default.
+    let deleted = db
+        .delete_expired_items()
+        .category(RetentionCategory::Threads)
+        .limit(10)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(deleted, 0);
+
+    db.set_retention_settings()
+        .user_id(user_id)
+        .settings(vec![(RetentionCategory::Threads, Some(365))])
+        .call()
+        .await
+        .unwrap();
+    let deleted = db
+        .delete_expired_items()
+        .category(RetentionCategory::Threads)
+        .limit(10)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(deleted, 1);
+
+    db.get_thread()
+        .id(starred)
+        .user_id(user_id)
+        .call()
+        .await
+        .unwrap();
+    assert!(
+        db.get_thread()
+            .id(plain)
+            .user_id(user_id)
+            .call()
+            .await
+            .is_err()
+    );
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn held_items_survive_deletion_and_expiry_until_released(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let user_id = seed_user(&db.pool).await;
+    let admin_id = Uuid::now_v7();
+
+    let thread = db
+        .create_thread()
+        .user_id(user_id)
+        .title("t".into())
+        .call()
+        .await
+        .unwrap()
+        .id;
+    let asset = seed_asset(&db, user_id, "image/png", 10).await;
+    for (subject, id) in [
+        (LegalHoldSubject::Thread, thread),
+        (LegalHoldSubject::Asset, asset),
+    ] {
+        db.set_legal_hold()
+            .subject(subject)
+            .subject_id(id)
+            .held(true)
+            .actor_id(admin_id)
+            .reason("case 42")
+            .call()
+            .await
+            .unwrap();
+    }
+
+    let err = db
+        .delete_thread()
+        .id(thread)
+        .user_id(user_id)
+        .call()
+        .await
+        .unwrap_err();
+    assert!(err.is_on_legal_hold());
+    let deleted = db
+        .delete_assets_for_user()
+        .ids(&[asset])
+        .user_id(user_id)
+        .call()
+        .await
+        .unwrap();
+    assert!(deleted.is_empty());
+    assert_eq!(
+        db.list_held_assets().ids(&[asset]).call().await.unwrap(),
+        [asset]
+    );
+    let expired = db
+        .list_expired_assets()
+        .category(RetentionCategory::Screenshots)
+        .default_max_age_days(7)
+        .limit(10)
+        .call()
+        .await
+        .unwrap();
+    assert!(expired.is_empty());
+
+    db.set_legal_hold()
+        .subject(LegalHoldSubject::Thread)
+        .subject_id(thread)
+        .held(false)
+        .actor_id(admin_id)
+        .reason("case closed")
+        .call()
+        .await
+        .unwrap();
+    db.delete_thread()
+        .id(thread)
+        .user_id(user_id)
+        .call()
+        .await
+        .unwrap();
+
+    let events = db
+        .list_legal_hold_events()
+        .subject(LegalHoldSubject::Thread)
+        .subject_id(thread)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(
+        events.iter().map(|e| e.held).collect::<Vec<_>>(),
+        [true, false]
+    );
+    assert!(events.iter().all(|e| e.actor_id == admin_id));
+}
@@ -0,0 +1,158 @@
+//! Integration tests for thread pinning, starring and folders.
+//!
+//! Uses `#[sqlx::test]` like the other files here: each test runs against a
+//! freshly migrated, isolated database, and the binary is a no-op without
+//! `DATABASE_URL`.
+
+use be_remote_db::{DatabaseManager, PaginationParams, Thread};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+async fn seed_user(pool: &PgPool) -> Uuid {
+    let id = Uuid::now_v7();
+    sqlx::query("INSERT INTO users (id, email) VALUES ($1, $2)")
+        .bind(id)
+        .bind(format!("user-{id}@test.local"))
+        .execute(pool)
+        .await
+        .expect("seed user");
+    id
+}
+
+async fn seed_thread(db: &DatabaseManager, user_id: Uuid, title: &str) -> Thread {
+    db.create_thread()
+        .user_id(user_id)
+        .title(title.to_owned())
+        .call()
+        .await
+        .expect("create_thread")
+}
+
+fn titles(threads: &[Thread]) -> Vec<&str> {
+    threads
+        .iter()
+        .map(|t| t.title.as_deref().unwrap_or_default())
+        .collect()
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn pinned_threads_list_first_and_filters_apply(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let user = seed_user(&db.pool).await;
+    let first = seed_thread(&db, user, "first").await;
+    let second = seed_thread(&db, user, "second").await;
+    seed_thread(&db, user, "third").await;
+
+    for id in [second.id, first.id] {
+        db.set_thread_flags()
+            .id(id)
+            .user_id(user)
+            .pinned(true)
+            .call()
+            .await
+            .unwrap();
+    }
+    db.set_thread_flags()
+        .id(second.id)
+        .user_id(user)
+        .starred(true)
+        .call()
+        .await
+        .unwrap();
+
+    let all = db
+        .list_threads()
+        .user_id(user)
+        .params(PaginationParams::new(0, 10, "DESC"))
+        .call()
+        .await
+        .unwrap();
+    // Most recently pinned on top, then the rest newest first.
+    assert_eq!(titles(&all), ["first", "second", "third"]);
+
+    let starred = db
+        .list_threads()
+        .user_id(user)
+        .params(PaginationParams::new(0, 10, "DESC"))
+        .starred(true)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(titles(&starred), ["second"]);
+
+    let unpinned = db
+        .list_threads()
+        .user_id(user)
+        .params(PaginationParams::new(0, 10, "DESC"))
+        .pinned(false)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(titles(&unpinned), ["third"]);
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn folders_are_scoped_and_deleting_one_unfiles_its_threads(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let owner = seed_user(&db.pool).await;
+    let other = seed_user(&db.pool).await;
+    let thread = seed_thread(&db, owner, "filed").await;
+
+    let work = db
+        .create_thread_folder()
+        .user_id(owner)
+        .name("Work")
+        .call()
+        .await
+        .unwrap();
+    let later = db
+        .create_thread_folder()
+        .user_id(owner)
+        .name("Later")
+        .call()
+        .await
+        .unwrap();
+    assert!(later.position > work.position);
+
+    let foreign = db
+        .create_thread_folder()
+        .user_id(other)
+        .name("Theirs")
+        .call()
+        .await
+        .unwrap();
+    let err = db
+        .move_thread_to_folder()
+        .id(thread.id)
+        .user_id(owner)
+        .folder_id(foreign.id)
+        .call()
+        .await
+        .expect_err("another user's folder must not be usable");
+    assert!(err.is_not_found());
+
+    let moved = db
+        .move_thread_to_folder()
+        .id(thread.id)
+        .user_id(owner)
+        .folder_id(work.id)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(moved.folder_id, Some(work.id));
+
+    db.delete_thread_folder()
+        .id(work.id)
+        .user_id(owner)
+        .call()
+        .await
+        .unwrap();
+    let unfiled = db
+        .get_thread()
+        .id(thread.id)
+        .user_id(owner)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(unfiled.folder_id, None);
+}
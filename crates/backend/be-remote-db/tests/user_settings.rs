@@ -65,7 +65,7 @@ async fn upsert(
 
 #[sqlx::test(migrations = "./src/migrations")]
 async fn get_returns_none_for_unknown_user(pool: PgPool) {
-    let db = DatabaseManager { pool };
+    let db = DatabaseManager::from_pool(pool);
     let missing = Uuid::now_v7();
 
     let result = db
@@ -80,7 +80,7 @@ async fn get_returns_none_for_unknown_user(pool: PgPool) {
 
 #[sqlx::test(migrations = "./src/migrations")]
 async fn insert_then_read_round_trip(pool: PgPool) {
-    let db = DatabaseManager { pool };
+    let db = DatabaseManager::from_pool(pool);
     let user_id = seed_user(&db.pool).await;
     let payload = json!({"theme": "dark", "scale": 1.0});
 
@@ -106,7 +106,7 @@ async fn insert_then_read_round_trip(pool: PgPool) {
 
 #[sqlx::test(migrations = "./src/migrations")]
 async fn upsert_with_matching_base_updates(pool: PgPool) {
-    let db = DatabaseManager { pool };
+    let db = DatabaseManager::from_pool(pool);
     let user_id = seed_user(&db.pool).await;
 
     let initial = expect_inserted(upsert(&db, user_id, 1, json!({"v": 0}), None).await);
@@ -121,7 +121,7 @@ async fn upsert_with_matching_base_updates(pool: PgPool) {
 
 #[sqlx::test(migrations = "./src/migrations")]
 async fn upsert_with_no_base_against_existing_row_conflicts(pool: PgPool) {
-    let db = DatabaseManager { pool };
+    let db = DatabaseManager::from_pool(pool);
     let user_id = seed_user(&db.pool).await;
 
     let initial = expect_inserted(upsert(&db, user_id, 1, json!({"v": 0}), None).await);
@@ -144,7 +144,7 @@ async fn upsert_with_no_base_against_existing_row_conflicts(pool: PgPool) {
 
 #[sqlx::test(migrations = "./src/migrations")]
 async fn upsert_with_stale_base_returns_current(pool: PgPool) {
-    let db = DatabaseManager { pool };
+    let db = DatabaseManager::from_pool(pool);
     let user_id = seed_user(&db.pool).await;
 
     let initial = expect_inserted(upsert(&db, user_id, 1, json!({"v": 0}), None).await);
@@ -160,7 +160,7 @@ async fn upsert_with_stale_base_returns_current(pool: PgPool) {
 
 #[sqlx::test(migrations = "./src/migrations")]
 async fn upsert_with_base_against_missing_row_inserts(pool: PgPool) {
-    let db = DatabaseManager { pool };
+    let db = DatabaseManager::from_pool(pool);
     let user_id = seed_user(&db.pool).await;
     let stale_base = Utc::now();
 
@@ -181,7 +181,7 @@ async fn upsert_with_base_against_missing_row_inserts(pool: PgPool) {
 
 #[sqlx::test(migrations = "./src/migrations")]
 async fn schema_version_preserved_through_round_trip(pool: PgPool) {
-    let db = DatabaseManager { pool };
+    let db = DatabaseManager::from_pool(pool);
     let user_id = seed_user(&db.pool).await;
 
     let inserted = expect_inserted(upsert(&db, user_id, 7, json!({}), None).await);
@@ -203,7 +203,7 @@ async fn schema_version_preserved_through_round_trip(pool: PgPool) {
 
 #[sqlx::test(migrations = "./src/migrations")]
 async fn delete_is_idempotent(pool: PgPool) {
-    let db = DatabaseManager { pool };
+    let db = DatabaseManager::from_pool(pool);
     let user_id = seed_user(&db.pool).await;
 
     db.delete_user_settings()
@@ -237,7 +237,7 @@ async fn delete_is_idempotent(pool: PgPool) {
 
 #[sqlx::test(migrations = "./src/migrations")]
 async fn delete_cascades_from_users(pool: PgPool) {
-    let db = DatabaseManager { pool };
+    let db = DatabaseManager::from_pool(pool);
     let user_id = seed_user(&db.pool).await;
     expect_inserted(upsert(&db, user_id, 1, json!({"v": 0}), None).await);
 
@@ -258,7 +258,7 @@ async fn delete_cascades_from_users(pool: PgPool) {
 
 #[sqlx::test(migrations = "./src/migrations")]
 async fn concurrent_upserts_produce_one_winner_and_one_conflict(pool: PgPool) {
-    let db = Arc::new(DatabaseManager { pool });
+    let db = Arc::new(DatabaseManager::from_pool(pool));
     let user_id = seed_user(&db.pool).await;
     let initial = expect_inserted(upsert(&db, user_id, 1, json!({"v": 0}), None).await);
     let base = initial.updated_at;
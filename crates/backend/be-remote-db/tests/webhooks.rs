@@ -0,0 +1,239 @@
+//! Integration tests for the webhook DB layer.
+//!
+//! Uses `#[sqlx::test]` like the other files here: each test runs against a
+//! freshly migrated, isolated database, and the binary is a no-op without
+//! `DATABASE_URL`.
+
+use be_remote_db::{DatabaseManager, WebhookDeliveryStatus, WebhookEndpointKind, WebhookEventType};
+use chrono::{Duration, Utc};
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+async fn seed_user(pool: &PgPool) -> Uuid {
+    let id = Uuid::now_v7();
+    sqlx::query("INSERT INTO users (id, email) VALUES ($1, $2)")
+        .bind(id)
+        .bind(format!("user-{id}@test.local"))
+        .execute(pool)
+        .await
+        .expect("seed user");
+    id
+}
+
+async fn seed_endpoint(db: &DatabaseManager, user_id: Uuid, events: &[WebhookEventType]) -> Uuid {
+    let event_types: Vec<String> = events.iter().map(|e| e.as_str().to_owned()).collect();
+    db.create_webhook_endpoint()
+        .user_id(user_id)
+        .kind(WebhookEndpointKind::Generic)
+        .url("https://hooks.example.com/eurora")
+        .secret("whsec_test")
+        .event_types(&event_types)
+        .call()
+        .await
+        .unwrap()
+        .id
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn events_fan_out_to_subscribed_enabled_endpoints(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let user_id = seed_user(&db.pool).await;
+    let other = seed_user(&db.pool).await;
+
+    let threads = seed_endpoint(&db, user_id, &[WebhookEventType::ThreadCreated]).await;
+    let all = seed_endpoint(&db, user_id, &WebhookEventType::ALL).await;
+    let exports_only = seed_endpoint(&db, user_id, &[WebhookEventType::ExportFinished]).await;
+    seed_endpoint(&db, other, &WebhookEventType::ALL).await;
+    db.update_webhook_endpoint()
+        .id(all)
+        .user_id(user_id)
+        .enabled(false)
+        .call()
+        .await
+        .unwrap();
+
+    let queued = db
+        .enqueue_webhook_event()
+        .executor(&db.pool)
+        .user_id(user_id)
+        .event_type(WebhookEventType::ThreadCreated)
+        .payload(&json!({ "thread_id": Uuid::nil() }))
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(queued, 1, "only the enabled, subscribed endpoint");
+
+    let log = db
+        .list_webhook_deliveries()
+        .endpoint_id(threads)
+        .user_id(user_id)
+        .limit(10)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(log.len(), 1);
+    assert_eq!(log[0].event_type, "thread.created");
+    assert_eq!(log[0].status, WebhookDeliveryStatus::Pending);
+
+    for endpoint_id in [all, exports_only] {
+        let log = db
+            .list_webhook_deliveries()
+            .endpoint_id(endpoint_id)
+            .user_id(user_id)
+            .limit(10)
+            .call()
+            .await
+            .unwrap();
+        assert!(log.is_empty());
+    }
+
+    // Another user can't read the log.
+    let foreign = db
+        .list_webhook_deliveries()
+        .endpoint_id(threads)
+        .user_id(other)
+        .limit(10)
+        .call()
+        .await
+        .unwrap();
+    assert!(foreign.is_empty());
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn claims_lease_deliveries_until_an_outcome_is_recorded(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let user_id = seed_user(&db.pool).await;
+    let endpoint_id = seed_endpoint(&db, user_id, &[WebhookEventType::AutomationRun]).await;
+    db.enqueue_webhook_event()
+        .executor(&db.pool)
+        .user_id(user_id)
+        .event_type(WebhookEventType::AutomationRun)
+        .payload(&json!({}))
+        .call()
+        .await
+        .unwrap();
+
+    let claim = || async {
+        db.claim_due_webhook_deliveries()
+            .limit(10)
+            .lease(Duration::minutes(1))
+            .call()
+            .await
+            .unwrap()
+    };
+
+    let claimed = claim().await;
+    assert_eq!(claimed.len(), 1);
+    let delivery = &claimed[0].delivery;
+    assert_eq!(delivery.attempts, 1);
+    assert_eq!(claimed[0].url, "https://hooks.example.com/eurora");
+    assert_eq!(claimed[0].secret, "whsec_test");
+    assert!(claim().await.is_empty(), "leased deliveries are not due");
+
+    // A retry scheduled in the past is due again straight away.
+    db.record_webhook_attempt()
+        .id(delivery.id)
+        .status(WebhookDeliveryStatus::Pending)
+        .response_status(503)
+        .error("HTTP 503".to_owned())
+        .next_attempt_at(Utc::now() - Duration::seconds(1))
+        .call()
+        .await
+        .unwrap();
+    let claimed = claim().await;
+    assert_eq!(claimed.len(), 1);
+    assert_eq!(claimed[0].delivery.attempts, 2);
+    assert_eq!(claimed[0].delivery.response_status, Some(503));
+
+    db.record_webhook_attempt()
+        .id(delivery.id)
+        .status(WebhookDeliveryStatus::Succeeded)
+        .response_status(204)
+        .call()
+        .await
+        .unwrap();
+    let log = db
+        .list_webhook_deliveries()
+        .endpoint_id(endpoint_id)
+        .user_id(user_id)
+        .limit(10)
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(log[0].status, WebhookDeliveryStatus::Succeeded);
+    assert_eq!(log[0].error, None);
+    assert!(log[0].delivered_at.is_some());
+    assert!(claim().await.is_empty());
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn automation_filters_and_summary_schedule(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let user_id = seed_user(&db.pool).await;
+    let wanted = Uuid::now_v7();
+    let endpoint = db
+        .create_webhook_endpoint()
+        .user_id(user_id)
+        .kind(WebhookEndpointKind::Slack)
+        .url("https://hooks.slack.com/services/T0/B0/x")
+        .secret("whsec_test")
+        .event_types(&["automation.run".to_owned()])
+        .automation_ids(&[wanted])
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(endpoint.next_summary_at, None);
+
+    let enqueue = |automation_id: Option<Uuid>| {
+        let db = &db;
+        async move {
+            db.enqueue_webhook_event()
+                .executor(&db.pool)
+                .user_id(user_id)
+                .event_type(WebhookEventType::AutomationRun)
+                .maybe_automation_id(automation_id)
+                .payload(&json!({}))
+                .call()
+                .await
+                .unwrap()
+        }
+    };
+    assert_eq!(enqueue(Some(Uuid::now_v7())).await, 0);
+    assert_eq!(enqueue(Some(wanted)).await, 1);
+    assert_eq!(enqueue(None).await, 1);
+
+    // Subscribing to summaries schedules one; unsubscribing clears it,
+    // and clearing the filter widens it to every automation.
+    let updated = db
+        .update_webhook_endpoint()
+        .id(endpoint.id)
+        .user_id(user_id)
+        .event_types(&["automation.daily_summary".to_owned()])
+        .automation_ids(None)
+        .call()
+        .await
+        .unwrap();
+    assert!(updated.next_summary_at.is_some());
+    assert_eq!(updated.automation_ids, None);
+    assert!(
+        db.claim_due_webhook_summaries()
+            .limit(10)
+            .call()
+            .await
+            .unwrap()
+            .is_empty(),
+        "the first summary is a day out"
+    );
+
+    let updated = db
+        .update_webhook_endpoint()
+        .id(endpoint.id)
+        .user_id(user_id)
+        .event_types(&["automation.run".to_owned()])
+        .call()
+        .await
+        .unwrap();
+    assert_eq!(updated.next_summary_at, None);
+    assert_eq!(updated.kind, WebhookEndpointKind::Slack);
+}
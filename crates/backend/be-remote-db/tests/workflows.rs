@@ -0,0 +1,105 @@
+//! Integration tests for the saved-workflows DB layer.
+//!
+//! Uses `#[sqlx::test]` like the other files here: each test runs against a
+//! freshly migrated, isolated database, and the binary is a no-op without
+//! `DATABASE_URL`.
+
+use be_remote_db::{DatabaseManager, Workflow};
+use serde_json::json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+async fn seed_user(pool: &PgPool) -> Uuid {
+    let id = Uuid::now_v7();
+    sqlx::query("INSERT INTO users (id, email) VALUES ($1, $2)")
+        .bind(id)
+        .bind(format!("user-{id}@test.local"))
+        .execute(pool)
+        .await
+        .expect("seed user");
+    id
+}
+
+async fn seed_workflow(db: &DatabaseManager, user_id: Uuid) -> Workflow {
+    db.create_workflow()
+        .user_id(user_id)
+        .name("Standup notes".to_owned())
+        .description(String::new())
+        .definition(json!({ "id": "standup", "name": "Standup notes", "steps": [] }))
+        .call()
+        .await
+        .expect("create_workflow")
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn workflows_are_scoped_to_their_owner(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let owner = seed_user(&db.pool).await;
+    let other = seed_user(&db.pool).await;
+    let workflow = seed_workflow(&db, owner).await;
+
+    assert!(
+        db.list_workflows()
+            .user_id(other)
+            .call()
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    let err = db
+        .get_workflow()
+        .id(workflow.id)
+        .user_id(other)
+        .call()
+        .await
+        .expect_err("foreign workflow must not resolve");
+    assert!(err.is_not_found());
+
+    let err = db
+        .update_workflow()
+        .id(workflow.id)
+        .user_id(other)
+        .name("Hijacked".to_owned())
+        .description(String::new())
+        .definition(json!({}))
+        .call()
+        .await
+        .expect_err("foreign workflow must not be editable");
+    assert!(err.is_not_found());
+
+    let err = db
+        .delete_workflow()
+        .id(workflow.id)
+        .user_id(other)
+        .call()
+        .await
+        .expect_err("foreign workflow must not be deletable");
+    assert!(err.is_not_found());
+}
+
+#[sqlx::test(migrations = "./src/migrations")]
+async fn update_workflow_replaces_the_definition(pool: PgPool) {
+    let db = DatabaseManager::from_pool(pool);
+    let user_id = seed_user(&db.pool).await;
+    let workflow = seed_workflow(&db, user_id).await;
+
+    let definition = json!({ "id": "standup", "name": "Daily standup", "steps": [{ "id": "a" }] });
+    let updated = db
+        .update_workflow()
+        .id(workflow.id)
+        .user_id(user_id)
+        .name("Daily standup".to_owned())
+        .description("Yesterday, today, blockers.".to_owned())
+        .definition(definition.clone())
+        .call()
+        .await
+        .unwrap();
+
+    assert_eq!(updated.name, "Daily standup");
+    assert_eq!(updated.definition, definition);
+    assert!(updated.updated_at >= workflow.updated_at);
+
+    let listed = db.list_workflows().user_id(user_id).call().await.unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].definition, definition);
+}
@@ -0,0 +1,130 @@
+use axum::Json;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use be_auth_core::{InvalidUserId, MissingClaims};
+use serde::Serialize;
+use thiserror::Error;
+
+/// Wire envelope for error responses emitted by this service. Same
+/// `{ error, message }` shape as the other REST services.
+#[derive(Debug, Clone, Serialize)]
+pub struct RetentionErrorResponse {
+    /// Stable machine identifier (e.g. `not_found`, `invalid_request`).
+    pub error: &'static str,
+    /// Human-readable description. Safe to surface in client UIs.
+    pub message: String,
+}
+
+#[derive(Error, Debug)]
+pub enum RetentionServiceError {
+    #[error("Authentication failed: {0}")]
+    Unauthenticated(String),
+
+    /// The asset or thread to pin doesn't exist or isn't the caller's.
+    #[error("{0} not found")]
+    NotFound(&'static str),
+
+    #[error("{0}")]
+    InvalidRequest(String),
+
+    #[error("Database error: {0}")]
+    Database(#[source] be_remote_db::DbError),
+}
+
+impl RetentionServiceError {
+    pub fn unauthenticated(msg: impl Into<String>) -> Self {
+        Self::Unauthenticated(msg.into())
+    }
+
+    pub fn invalid(msg: impl Into<String>) -> Self {
+        Self::InvalidRequest(msg.into())
+    }
+
+    /// Stable identifier surfaced to clients in the error envelope.
+    pub fn error_kind(&self) -> &'static str {
+        match self {
+            Self::Unauthenticated(_) => "unauthenticated",
+            Self::NotFound(_) => "not_found",
+            Self::InvalidRequest(_) => "invalid_request",
+            Self::Database(_) => "database_error",
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::Unauthenticated(_) => StatusCode::UNAUTHORIZED,
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
+            Self::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+            Self::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl From<be_remote_db::DbError> for RetentionServiceError {
+    fn from(err: be_remote_db::DbError) -> Self {
+        match err {
+            be_remote_db::DbError::NotFound { entity, .. } => Self::NotFound(entity),
+            other => Self::Database(other),
+        }
+    }
+}
+
+impl From<MissingClaims> for RetentionServiceError {
+    fn from(_: MissingClaims) -> Self {
+        Self::unauthenticated("Missing authenticated claims")
+    }
+}
+
+impl From<InvalidUserId> for RetentionServiceError {
+    fn from(err: InvalidUserId) -> Self {
+        Self::unauthenticated(err.to_string())
+    }
+}
+
+impl IntoResponse for RetentionServiceError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let kind = self.error_kind();
+        let detail = self.to_string();
+
+        match &self {
+            Self::Unauthenticated(_) => {
+                tracing::warn!(error = %detail, "Retention service authentication error");
+            }
+            Self::NotFound(_) | Self::InvalidRequest(_) => {
+                tracing::debug!(error = %detail, "Retention service client error");
+            }
+            Self::Database(_) => {
+                tracing::error!(error = %detail, "Retention service internal error");
+            }
+        }
+
+        let message = match &self {
+            Self::Database(_) => "Database operation failed".to_string(),
+            _ => detail,
+        };
+
+        (
+            status,
+            Json(RetentionErrorResponse {
+                error: kind,
+                message,
+            }),
+        )
+            .into_response()
+    }
+}
+
+pub type RetentionResult<T> = std::result::Result<T, RetentionServiceError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn db_not_found_names_the_entity() {
+        let err: RetentionServiceError = be_remote_db::DbError::not_found("thread").into();
+        assert_eq!(err.status(), StatusCode::NOT_FOUND);
+        assert_eq!(err.to_string(), "thread not found");
+    }
+}
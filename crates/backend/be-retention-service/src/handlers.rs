@@ -0,0 +1,282 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use be_auth_core::AuthUser;
+use be_remote_db::{LegalHoldEvent, LegalHoldSubject};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::AppState;
+use crate::error::RetentionResult;
+use crate::types::{
+    LegalHoldEventsResponse, LegalHoldRequest, RetentionPreviewItem, RetentionPreviewRequest,
+    RetentionPreviewResponse, RetentionSettingsResponse, UpdateRetentionSettingsRequest,
+};
+
+#[tracing::instrument(skip_all, fields(user_id))]
+pub async fn get_retention(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+) -> RetentionResult<Json<RetentionSettingsResponse>> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    let rows = state
+        .db
+        .list_retention_settings()
+        .user_id(user_id)
+        .call()
+        .await?;
+    Ok(Json(RetentionSettingsResponse::from_rows(&rows)))
+}
+
+#[tracing::instrument(skip_all, fields(user_id))]
+pub async fn put_retention(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Json(body): Json<UpdateRetentionSettingsRequest>,
+) -> RetentionResult<Json<RetentionSettingsResponse>> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    let mut settings = Vec::with_capacity(body.settings.len());
+    for item in &body.settings {
+        item.validate()?;
+        settings.push((item.category, item.max_age_days.map(|days| days as i32)));
+    }
+
+    let rows = state
+        .db
+        .set_retention_settings()
+        .user_id(user_id)
+        .settings(settings)
+        .call()
+        .await?;
+    tracing::info!(changed = body.settings.len(), "Retention settings updated");
+    Ok(Json(RetentionSettingsResponse::from_rows(&rows)))
+}
+
+/// Put every category back on its default. Idempotent.
+#[tracing::instrument(skip_all, fields(user_id))]
+pub async fn delete_retention(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+) -> RetentionResult<StatusCode> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    state
+        .db
+        .reset_retention_settings()
+        .user_id(user_id)
+        .call()
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Count what the sweep would delete right now under the saved limits,
+/// with any limits in the body tried in their place. Nothing is saved.
+#[tracing::instrument(skip_all, fields(user_id))]
+pub async fn preview_retention(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    body: Option<Json<RetentionPreviewRequest>>,
+) -> RetentionResult<Json<RetentionPreviewResponse>> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+    let Json(body) = body.unwrap_or_default();
+    for item in &body.settings {
+        item.validate()?;
+    }
+
+    let rows = state
+        .db
+        .list_retention_settings()
+        .user_id(user_id)
+        .call()
+        .await?;
+    let saved = RetentionSettingsResponse::from_rows(&rows);
+
+    let now = Utc::now();
+    let mut categories = Vec::with_capacity(saved.settings.len());
+    for setting in saved.settings {
+        let max_age_days = body
+            .settings
+            .iter()
+            .rev()
+            .find(|item| item.category == setting.category)
+            .map_or(setting.max_age_days, |item| item.max_age_days);
+        let Some(days) = max_age_days else {
+            categories.push(RetentionPreviewItem::new(
+                setting.category,
+                None,
+                None,
+                Default::default(),
+            ));
+            continue;
+        };
+
+        let cutoff = now - Duration::days(i64::from(days));
+        let stats = state
+            .db
+            .count_expired_items()
+            .user_id(user_id)
+            .category(setting.category)
+            .cutoff(cutoff)
+            .call()
+            .await?;
+        categories.push(RetentionPreviewItem::new(
+            setting.category,
+            max_age_days,
+            Some(cutoff),
+            stats,
+        ));
+    }
+
+    Ok(Json(RetentionPreviewResponse { categories }))
+}
+
+#[tracing::instrument(skip_all, fields(user_id, asset_id = %asset_id))]
+pub async fn pin_asset(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(asset_id): Path<Uuid>,
+) -> RetentionResult<StatusCode> {
+    set_asset_pinned(&state, user, asset_id, true).await
+}
+
+#[tracing::instrument(skip_all, fields(user_id, asset_id = %asset_id))]
+pub async fn unpin_asset(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(asset_id): Path<Uuid>,
+) -> RetentionResult<StatusCode> {
+    set_asset_pinned(&state, user, asset_id, false).await
+}
+
+#[tracing::instrument(skip_all, fields(user_id, thread_id = %thread_id))]
+pub async fn star_thread(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(thread_id): Path<Uuid>,
+) -> RetentionResult<StatusCode> {
+    set_thread_starred(&state, user, thread_id, true).await
+}
+
+#[tracing::instrument(skip_all, fields(user_id, thread_id = %thread_id))]
+pub async fn unstar_thread(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(thread_id): Path<Uuid>,
+) -> RetentionResult<StatusCode> {
+    set_thread_starred(&state, user, thread_id, false).await
+}
+
+async fn set_asset_pinned(
+    state: &AppState,
+    user: AuthUser,
+    asset_id: Uuid,
+    pinned: bool,
+) -> RetentionResult<StatusCode> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    state
+        .db
+        .set_asset_pinned()
+        .id(asset_id)
+        .user_id(user_id)
+        .pinned(pinned)
+        .call()
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn set_thread_starred(
+    state: &AppState,
+    user: AuthUser,
+    thread_id: Uuid,
+    starred: bool,
+) -> RetentionResult<StatusCode> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    state
+        .db
+        .set_thread_starred()
+        .id(thread_id)
+        .user_id(user_id)
+        .starred(starred)
+        .call()
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Put a thread or asset under legal hold. Admin only; works on any
+/// user's data. Setting a hold twice is harmless, and both are audited.
+#[tracing::instrument(skip_all, fields(actor_id, subject = subject.as_str(), %subject_id))]
+pub async fn set_legal_hold(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path((subject, subject_id)): Path<(LegalHoldSubject, Uuid)>,
+    Json(body): Json<LegalHoldRequest>,
+) -> RetentionResult<Json<LegalHoldEvent>> {
+    update_legal_hold(&state, user, subject, subject_id, true, &body).await
+}
+
+/// Release a legal hold. The item goes back under its owner's retention
+/// limits and can be deleted again.
+#[tracing::instrument(skip_all, fields(actor_id, subject = subject.as_str(), %subject_id))]
+pub async fn release_legal_hold(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path((subject, subject_id)): Path<(LegalHoldSubject, Uuid)>,
+    Json(body): Json<LegalHoldRequest>,
+) -> RetentionResult<Json<LegalHoldEvent>> {
+    update_legal_hold(&state, user, subject, subject_id, false, &body).await
+}
+
+#[tracing::instrument(skip_all, fields(subject = subject.as_str(), %subject_id))]
+pub async fn list_legal_hold_events(
+    State(state): State<Arc<AppState>>,
+    Path((subject, subject_id)): Path<(LegalHoldSubject, Uuid)>,
+) -> RetentionResult<Json<LegalHoldEventsResponse>> {
+    let events = state
+        .db
+        .list_legal_hold_events()
+        .subject(subject)
+        .subject_id(subject_id)
+        .call()
+        .await?;
+    Ok(Json(LegalHoldEventsResponse { events }))
+}
+
+async fn update_legal_hold(
+    state: &AppState,
+    user: AuthUser,
+    subject: LegalHoldSubject,
+    subject_id: Uuid,
+    held: bool,
+    body: &LegalHoldRequest,
+) -> RetentionResult<Json<LegalHoldEvent>> {
+    let actor_id = user.user_id()?;
+    tracing::Span::current().record("actor_id", tracing::field::display(actor_id));
+    let reason = body.validate()?;
+
+    let event = state
+        .db
+        .set_legal_hold()
+        .subject(subject)
+        .subject_id(subject_id)
+        .held(held)
+        .actor_id(actor_id)
+        .reason(reason)
+        .call()
+        .await?;
+    tracing::info!(held, reason, "Legal hold updated");
+    Ok(Json(event))
+}
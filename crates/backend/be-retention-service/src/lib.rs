@@ -0,0 +1,140 @@
+//! HTTP data retention service.
+//!
+//! Lets a user choose how long each category of captured context is kept
+//! — screenshots, other captured text (page contents, documents),
+//! activity history, and threads — and enforces those limits server-side
+//! with a background worker. Authentication and Casbin authorization are
+//! applied by the surrounding `be-authz` middleware in `be-monolith`; this
+//! crate only assumes that a verified [`be_auth_core::Claims`] has been
+//! inserted into request extensions by the time a handler runs.
+//!
+//! ## Endpoints
+//!
+//! | Method | Path                                    | Outcome                                          |
+//! |--------|-----------------------------------------|--------------------------------------------------|
+//! | GET    | `/retention`                            | `200 RetentionSettingsResponse`, every category. |
+//! | PUT    | `/retention`                            | `200 RetentionSettingsResponse`, or `400`.       |
+//! | DELETE | `/retention`                            | `204`; every category back on its default.       |
+//! | POST   | `/retention/preview`                    | `200 RetentionPreviewResponse`; saves nothing.   |
+//! | PUT    | `/retention/pinned-assets/{asset_id}`   | `204`; the asset never expires.                  |
+//! | DELETE | `/retention/pinned-assets/{asset_id}`   | `204`.                                           |
+//! | PUT    | `/retention/starred-threads/{thread_id}`| `204`; the thread and its attachments never expire. |
+//! | DELETE | `/retention/starred-threads/{thread_id}`| `204`.                                           |
+//!
+//! Admins can also put any user's thread or asset under legal hold. Held
+//! items are skipped by the sweep and can't be deleted until the hold is
+//! released; every set and release is recorded with who did it and why.
+//! `{subject}` is `thread` or `asset`.
+//!
+//! | Method | Path                                               | Outcome                               |
+//! |--------|----------------------------------------------------|---------------------------------------|
+//! | PUT    | `/admin/legal-holds/{subject}/{subject_id}`        | `200 LegalHoldEvent`; needs a reason. |
+//! | DELETE | `/admin/legal-holds/{subject}/{subject_id}`        | `200 LegalHoldEvent`; needs a reason. |
+//! | GET    | `/admin/legal-holds/{subject}/{subject_id}/events` | `200 LegalHoldEventsResponse`.        |
+//!
+//! ## Limits
+//!
+//! Each category has a default (see [`default_max_age_days`]): screenshots
+//! 7 days, captured text 30, activity history 90, threads forever. A user
+//! can set any category to a number of days or to keep forever. Ages run
+//! from when an asset was uploaded, a session ended, or a thread was last
+//! updated.
+//!
+//! With a cold store configured, the same worker moves assets older than
+//! `ASSET_COLD_AFTER_DAYS` to it; they stay readable, just slower. It
+//! also deletes resumable uploads abandoned past their TTL.
+//!
+//! The desktop app holds captured context only in memory (the timeline
+//! keeps the last hour), so the server is where retention is enforced.
+
+mod error;
+mod handlers;
+mod types;
+mod worker;
+
+use std::sync::Arc;
+
+use axum::{
+    Router,
+    routing::{get, post, put},
+};
+use be_remote_db::DatabaseManager;
+use be_storage::StorageService;
+use tower_http::trace::TraceLayer;
+
+pub use error::{RetentionErrorResponse, RetentionResult, RetentionServiceError};
+pub use types::{
+    LegalHoldEventsResponse, LegalHoldRequest, MAX_LEGAL_HOLD_REASON_LEN, MAX_RETENTION_DAYS,
+    RetentionPreviewItem, RetentionPreviewRequest, RetentionPreviewResponse, RetentionSettingItem,
+    RetentionSettingResponse, RetentionSettingsResponse, UpdateRetentionSettingsRequest,
+    default_max_age_days,
+};
+pub use worker::RetentionWorkerHandle;
+
+/// Shared state injected into Axum handlers via `State<Arc<AppState>>`.
+pub struct AppState {
+    pub db: Arc<DatabaseManager>,
+    pub storage: Arc<StorageService>,
+}
+
+impl AppState {
+    pub fn new(db: Arc<DatabaseManager>, storage: Arc<StorageService>) -> Self {
+        Self { db, storage }
+    }
+}
+
+/// Build the retention router with the supplied dependencies.
+///
+/// Returns the bare router; the caller is expected to apply the
+/// cross-cutting layers (CORS, body limit, auth middleware) at the
+/// monolith level so all REST services share the same outer pipeline.
+pub fn create_router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route(
+            "/retention",
+            get(handlers::get_retention)
+                .put(handlers::put_retention)
+                .delete(handlers::delete_retention),
+        )
+        .route("/retention/preview", post(handlers::preview_retention))
+        .route(
+            "/retention/pinned-assets/{asset_id}",
+            put(handlers::pin_asset).delete(handlers::unpin_asset),
+        )
+        .route(
+            "/retention/starred-threads/{thread_id}",
+            put(handlers::star_thread).delete(handlers::unstar_thread),
+        )
+        .route(
+            "/admin/legal-holds/{subject}/{subject_id}",
+            put(handlers::set_legal_hold).delete(handlers::release_legal_hold),
+        )
+        .route(
+            "/admin/legal-holds/{subject}/{subject_id}/events",
+            get(handlers::list_legal_hold_events),
+        )
+        .layer(TraceLayer::new_for_http())
+        .with_state(state)
+}
+
+pub struct RetentionService {
+    pub router: Router,
+    pub worker: RetentionWorkerHandle,
+}
+
+/// Wire up application state, start the retention worker, and return the
+/// router ready to merge into the monolith HTTP pipeline. The caller owns
+/// the returned [`RetentionWorkerHandle`] and should shut it down after
+/// the server stops.
+pub fn init_retention_service(
+    db: Arc<DatabaseManager>,
+    storage: Arc<StorageService>,
+) -> RetentionService {
+    tracing::debug!("Initializing retention service");
+    let state = Arc::new(AppState::new(db, storage));
+    let worker = worker::spawn_worker(state.clone());
+    RetentionService {
+        router: create_router(state),
+        worker,
+    }
+}
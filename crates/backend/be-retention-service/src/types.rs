@@ -0,0 +1,223 @@
+//! Wire types for `/retention` and `/admin/legal-holds`, and the default
+//! limits.
+
+use be_remote_db::{ExpiredItemStats, LegalHoldEvent, RetentionCategory, RetentionSetting};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::RetentionServiceError;
+
+/// Longest limit a user can set, in days. Anything longer is what
+/// "keep forever" is for.
+pub const MAX_RETENTION_DAYS: u32 = 3650;
+
+/// Longest reason an admin can give for setting or releasing a legal hold.
+pub const MAX_LEGAL_HOLD_REASON_LEN: usize = 1000;
+
+/// How long a category is kept for users who haven't chosen; `None` keeps
+/// it forever.
+pub fn default_max_age_days(category: RetentionCategory) -> Option<u32> {
+    match category {
+        RetentionCategory::Screenshots => Some(7),
+        RetentionCategory::CapturedText => Some(30),
+        RetentionCategory::ActivityHistory => Some(90),
+        RetentionCategory::Threads => None,
+    }
+}
+
+/// One category's limit. In requests, a `null` or missing `max_age_days`
+/// keeps the category forever.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionSettingItem {
+    pub category: RetentionCategory,
+    pub max_age_days: Option<u32>,
+}
+
+impl RetentionSettingItem {
+    pub(crate) fn validate(&self) -> Result<(), RetentionServiceError> {
+        match self.max_age_days {
+            Some(days) if !(1..=MAX_RETENTION_DAYS).contains(&days) => {
+                Err(RetentionServiceError::invalid(format!(
+                    "max_age_days for {} must be between 1 and {MAX_RETENTION_DAYS}, or null to keep forever",
+                    self.category.as_str()
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Body of `PUT /retention`. Categories left out keep their current
+/// limit.
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateRetentionSettingsRequest {
+    pub settings: Vec<RetentionSettingItem>,
+}
+
+/// Body of `POST /retention/preview`: limits to try instead of the saved
+/// ones. Every field is optional; an empty body previews the saved
+/// limits.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct RetentionPreviewRequest {
+    pub settings: Vec<RetentionSettingItem>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RetentionSettingResponse {
+    pub category: RetentionCategory,
+    pub max_age_days: Option<u32>,
+    pub default_max_age_days: Option<u32>,
+    /// Whether the user has left this category on the default.
+    pub is_default: bool,
+}
+
+/// Every category, in [`RetentionCategory::ALL`] order.
+#[derive(Debug, Clone, Serialize)]
+pub struct RetentionSettingsResponse {
+    pub settings: Vec<RetentionSettingResponse>,
+}
+
+impl RetentionSettingsResponse {
+    pub(crate) fn from_rows(rows: &[RetentionSetting]) -> Self {
+        let settings = RetentionCategory::ALL
+            .into_iter()
+            .map(|category| {
+                let default_max_age_days = default_max_age_days(category);
+                match rows.iter().find(|row| row.category == category) {
+                    Some(row) => RetentionSettingResponse {
+                        category,
+                        max_age_days: row.max_age_days.map(|days| days as u32),
+                        default_max_age_days,
+                        is_default: false,
+                    },
+                    None => RetentionSettingResponse {
+                        category,
+                        max_age_days: default_max_age_days,
+                        default_max_age_days,
+                        is_default: true,
+                    },
+                }
+            })
+            .collect();
+        Self { settings }
+    }
+}
+
+/// What the next sweep would delete in one category.
+#[derive(Debug, Clone, Serialize)]
+pub struct RetentionPreviewItem {
+    pub category: RetentionCategory,
+    pub max_age_days: Option<u32>,
+    /// Items older than this go; absent when the category is kept forever.
+    pub cutoff: Option<DateTime<Utc>>,
+    pub count: i64,
+    /// Stored bytes freed; only assets count.
+    pub size_bytes: i64,
+    pub oldest: Option<DateTime<Utc>>,
+}
+
+impl RetentionPreviewItem {
+    pub(crate) fn new(
+        category: RetentionCategory,
+        max_age_days: Option<u32>,
+        cutoff: Option<DateTime<Utc>>,
+        stats: ExpiredItemStats,
+    ) -> Self {
+        Self {
+            category,
+            max_age_days,
+            cutoff,
+            count: stats.count,
+            size_bytes: stats.size_bytes,
+            oldest: stats.oldest,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RetentionPreviewResponse {
+    pub categories: Vec<RetentionPreviewItem>,
+}
+
+/// Body of `PUT` and `DELETE /admin/legal-holds/{subject}/{subject_id}`.
+/// The reason goes into the audit trail.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LegalHoldRequest {
+    pub reason: String,
+}
+
+impl LegalHoldRequest {
+    pub(crate) fn validate(&self) -> Result<&str, RetentionServiceError> {
+        let reason = self.reason.trim();
+        if reason.is_empty() {
+            return Err(RetentionServiceError::invalid(
+                "a reason is required to set or release a legal hold",
+            ));
+        }
+        if reason.chars().count() > MAX_LEGAL_HOLD_REASON_LEN {
+            return Err(RetentionServiceError::invalid(format!(
+                "reason must be at most {MAX_LEGAL_HOLD_REASON_LEN} characters"
+            )));
+        }
+        Ok(reason)
+    }
+}
+
+/// Audit trail of one thread or asset, oldest first.
+#[derive(Debug, Clone, Serialize)]
+pub struct LegalHoldEventsResponse {
+    pub events: Vec<LegalHoldEvent>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn categories_without_a_row_fall_back_to_the_default() {
+        let now = Utc::now();
+        let rows = [RetentionSetting {
+            user_id: Uuid::nil(),
+            category: RetentionCategory::Screenshots,
+            max_age_days: None,
+            created_at: now,
+            updated_at: now,
+        }];
+
+        let response = RetentionSettingsResponse::from_rows(&rows);
+        let screenshots = &response.settings[0];
+        assert_eq!(screenshots.max_age_days, None);
+        assert!(!screenshots.is_default);
+        let captured_text = &response.settings[1];
+        assert_eq!(captured_text.max_age_days, Some(30));
+        assert!(captured_text.is_default);
+    }
+
+    #[test]
+    fn limits_must_be_in_range() {
+        let item = |max_age_days| RetentionSettingItem {
+            category: RetentionCategory::Threads,
+            max_age_days,
+        };
+        assert!(item(None).validate().is_ok());
+        assert!(item(Some(1)).validate().is_ok());
+        assert!(item(Some(0)).validate().is_err());
+        assert!(item(Some(MAX_RETENTION_DAYS + 1)).validate().is_err());
+    }
+
+    #[test]
+    fn legal_holds_need_a_reason() {
+        let request = |reason: &str| LegalHoldRequest {
+            reason: reason.to_owned(),
+        };
+        assert_eq!(request(" case 42 ").validate().unwrap(), "case 42");
+        assert!(request("  ").validate().is_err());
+        assert!(
+            request(&"x".repeat(MAX_LEGAL_HOLD_REASON_LEN + 1))
+                .validate()
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,269 @@
+//! Background worker that deletes captured context past its retention
+//! limit.
+//!
+//! Every tick walks the categories in turn, deleting expired items in
+//! batches until a category is clean or has used up its share of the tick.
+//! Each user's limit comes from their `retention_settings` row, falling
+//! back to [`default_max_age_days`]; the exemptions live in the queries
+//! (see [`DatabaseManager::list_expired_assets`]).
+//!
+//! Assets are deleted from object storage first and from the database
+//! second, so a failed object delete leaves the row to be retried on the
+//! next tick instead of orphaning the object.
+//!
+//! When a cold store is configured (see [`be_storage::TieringConfig`]),
+//! each tick also moves assets older than its `cold_after_days` there:
+//! copy, repoint the row, then delete the hot object. A read of a cold
+//! asset moves it back, and it won't be moved again for another
+//! `cold_after_days`.
+//!
+//! It also deletes resumable upload sessions that went quiet past their
+//! TTL, with the chunks they received.
+
+use std::sync::Arc;
+
+use be_remote_db::{AssetStorageTier, DatabaseManager, DbError, RetentionCategory};
+use be_storage::StorageTier;
+use tokio::sync::oneshot;
+use tokio::time::{Duration, sleep};
+
+use crate::AppState;
+use crate::types::default_max_age_days;
+
+/// Items deleted per query.
+const BATCH_SIZE: i64 = 200;
+
+/// Batches per category per tick, so one category with a large backlog
+/// can't hold up the others.
+const MAX_BATCHES_PER_TICK: usize = 10;
+
+/// Limits are in days; there's nothing to gain from sweeping more often.
+const POLL_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+pub struct RetentionWorkerHandle {
+    shutdown: oneshot::Sender<()>,
+    join: tokio::task::JoinHandle<()>,
+}
+
+impl RetentionWorkerHandle {
+    pub async fn shutdown(self) {
+        let Self { shutdown, join } = self;
+        let _ = shutdown.send(());
+        let _ = join.await;
+    }
+}
+
+/// Spawn the background worker. A sweep interrupted by shutdown simply
+/// resumes on the next start.
+pub(crate) fn spawn_worker(state: Arc<AppState>) -> RetentionWorkerHandle {
+    let (shutdown_tx, mut shutdown_rx) = oneshot::channel::<()>();
+
+    let join = tokio::spawn(async move {
+        tracing::info!("Retention worker started");
+        loop {
+            tokio::select! {
+                _ = tick(&state) => {}
+                _ = &mut shutdown_rx => break,
+            }
+
+            tokio::select! {
+                _ = sleep(POLL_INTERVAL) => {}
+                _ = &mut shutdown_rx => break,
+            }
+        }
+        tracing::info!("Retention worker shutting down");
+    });
+
+    RetentionWorkerHandle {
+        shutdown: shutdown_tx,
+        join,
+    }
+}
+
+async fn tick(state: &AppState) {
+    for category in RetentionCategory::ALL {
+        let default = default_max_age_days(category).map(|days| days as i32);
+        let result = if category.is_asset() {
+            sweep_assets(state, category, default).await
+        } else {
+            sweep_rows(&state.db, category, default).await
+        };
+        match result {
+            Ok(0) => {}
+            Ok(deleted) => {
+                tracing::info!(
+                    category = category.as_str(),
+                    deleted,
+                    "Expired captured context"
+                );
+            }
+            Err(e) => {
+                tracing::error!(category = category.as_str(), error = %e, "Retention sweep failed");
+            }
+        }
+    }
+
+    match expire_uploads(state).await {
+        Ok(0) => {}
+        Ok(expired) => tracing::info!(expired, "Expired abandoned upload sessions"),
+        Err(e) => tracing::error!(error = %e, "Upload session sweep failed"),
+    }
+
+    if let Some(days) = state.storage.cold_after_days() {
+        match move_to_cold(state, days as i32).await {
+            Ok(0) => {}
+            Ok(moved) => tracing::info!(moved, "Moved assets to cold storage"),
+            Err(e) => tracing::error!(error = %e, "Cold storage pass failed"),
+        }
+    }
+}
+
+async fn sweep_rows(
+    db: &DatabaseManager,
+    category: RetentionCategory,
+    default: Option<i32>,
+) -> Result<u64, DbError> {
+    let mut deleted = 0;
+    for _ in 0..MAX_BATCHES_PER_TICK {
+        let batch = db
+            .delete_expired_items()
+            .category(category)
+            .maybe_default_max_age_days(default)
+            .limit(BATCH_SIZE)
+            .call()
+            .await?;
+        deleted += batch;
+        if batch < BATCH_SIZE as u64 {
+            break;
+        }
+    }
+    Ok(deleted)
+}
+
+async fn sweep_assets(
+    state: &AppState,
+    category: RetentionCategory,
+    default: Option<i32>,
+) -> Result<u64, DbError> {
+    let mut deleted = 0;
+    for _ in 0..MAX_BATCHES_PER_TICK {
+        let expired = state
+            .db
+            .list_expired_assets()
+            .category(category)
+            .maybe_default_max_age_days(default)
+            .limit(BATCH_SIZE)
+            .call()
+            .await?;
+        let full_batch = expired.len() as i64 == BATCH_SIZE;
+
+        let mut removed = Vec::with_capacity(expired.len());
+        for asset in expired {
+            match state.storage.delete(&asset.storage_uri).await {
+                Ok(()) => removed.push(asset.id),
+                Err(e) if e.is_not_found() => removed.push(asset.id),
+                Err(e) => {
+                    tracing::warn!(
+                        asset_id = %asset.id,
+                        user_id = %asset.user_id,
+                        error = %e,
+                        "Failed to delete expired asset object; will retry"
+                    );
+                }
+            }
+        }
+        if removed.is_empty() {
+            break;
+        }
+
+        deleted += state.db.delete_assets().ids(&removed).call().await?;
+        if !full_batch {
+            break;
+        }
+    }
+    Ok(deleted)
+}
+
+async fn move_to_cold(state: &AppState, cold_after_days: i32) -> Result<u64, DbError> {
+    let mut moved = 0;
+    for _ in 0..MAX_BATCHES_PER_TICK {
+        let assets = state
+            .db
+            .list_assets_for_cold_tier(cold_after_days, BATCH_SIZE)
+            .await?;
+        let full_batch = assets.len() as i64 == BATCH_SIZE;
+
+        let mut batch_moved = 0;
+        for asset in assets {
+            let cold_uri = match state
+                .storage
+                .copy_to_tier(&asset.storage_uri, StorageTier::Cold)
+                .await
+            {
+                Ok(uri) => uri,
+                Err(e) => {
+                    tracing::warn!(
+                        asset_id = %asset.id,
+                        error = %e,
+                        "Failed to copy asset to cold storage; will retry"
+                    );
+                    continue;
+                }
+            };
+            let repointed = state
+                .db
+                .move_asset_to_tier()
+                .id(asset.id)
+                .from_uri(&asset.storage_uri)
+                .to_uri(&cold_uri)
+                .tier(AssetStorageTier::Cold)
+                .call()
+                .await?;
+
+            let stale = if repointed {
+                batch_moved += 1;
+                &asset.storage_uri
+            } else {
+                &cold_uri
+            };
+            if let Err(e) = state.storage.delete(stale).await {
+                tracing::warn!(asset_id = %asset.id, error = %e, "Failed to delete stale tier copy");
+            }
+        }
+        moved += batch_moved;
+        if batch_moved == 0 || !full_batch {
+            break;
+        }
+    }
+    Ok(moved)
+}
+
+async fn expire_uploads(state: &AppState) -> Result<u64, DbError> {
+    let mut expired = 0;
+    for _ in 0..MAX_BATCHES_PER_TICK {
+        let sessions = state.db.delete_expired_upload_sessions(BATCH_SIZE).await?;
+        let full_batch = sessions.len() as i64 == BATCH_SIZE;
+        expired += sessions.len() as u64;
+
+        for session in sessions {
+            for uri in &session.chunk_uris {
+                match state.storage.delete(uri).await {
+                    Ok(()) => {}
+                    Err(e) if e.is_not_found() => {}
+                    Err(e) => {
+                        tracing::warn!(
+                            upload_id = %session.id,
+                            uri,
+                            error = %e,
+                            "Failed to delete expired upload chunk"
+                        );
+                    }
+                }
+            }
+        }
+        if !full_batch {
+            break;
+        }
+    }
+    Ok(expired)
+}
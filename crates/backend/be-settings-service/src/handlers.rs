@@ -4,8 +4,8 @@ use axum::{Json, extract::State, http::StatusCode};
 use be_auth_core::AuthUser;
 use be_remote_db::{UpsertOutcome, UserSettingsRow};
 use settings_core::{
-    GetSettingsResponse, PutSettingsAcceptedResponse, PutSettingsConflictResponse,
-    PutSettingsRequest,
+    CURRENT_SCHEMA_VERSION, GetSettingsResponse, PutSettingsAcceptedResponse,
+    PutSettingsConflictResponse, PutSettingsRequest, SettingsSchema,
 };
 
 use crate::AppState;
@@ -56,6 +56,14 @@ pub async fn put_settings(
     span.record("schema_version", schema_version);
     span.record("has_base", body.base_updated_at.is_some());
 
+    // Only the version this build knows the registry for is checked; a
+    // blob written by a newer client passes through untouched.
+    if body.schema_version == CURRENT_SCHEMA_VERSION {
+        SettingsSchema::current()
+            .validate_cloud_document(&body.settings)
+            .map_err(|e| SettingsServiceError::invalid_argument(e.to_string()))?;
+    }
+
     let outcome = state
         .db
         .upsert_user_settings()
@@ -85,6 +93,12 @@ pub async fn put_settings(
     })
 }
 
+/// The registry of editable settings, so clients can render forms
+/// without hard-coding bounds and options.
+pub async fn get_settings_schema() -> Json<SettingsSchema> {
+    Json(SettingsSchema::current())
+}
+
 #[tracing::instrument(skip_all, fields(user_id))]
 pub async fn delete_settings(
     State(state): State<Arc<AppState>>,
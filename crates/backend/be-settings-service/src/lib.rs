@@ -14,13 +14,17 @@
 //! | GET    | `/settings` | `200 GetSettingsResponse` or `404` (first-run upload).   |
 //! | PUT    | `/settings` | `200 PutSettingsAcceptedResponse` or `409 PutSettingsConflictResponse`. |
 //! | DELETE | `/settings` | `204` (idempotent). Used by "reset cloud settings" UI.   |
+//! | GET    | `/settings/schema` | `200 SettingsSchema`: the editable-key registry.  |
 //!
 //! ## Server is blob-opaque
 //!
-//! The settings document is stored verbatim as `serde_json::Value` and is
-//! never parsed by this crate. The server owns the indexed
-//! `(user_id, schema_version, updated_at)` metadata; the body shape is
-//! the client's contract. Optimistic concurrency lives in the database
+//! The settings document is stored verbatim as `serde_json::Value`. The
+//! server owns the indexed `(user_id, schema_version, updated_at)`
+//! metadata; the body shape is the client's contract. The one check it
+//! makes is against [`settings_core::SettingsSchema`]: a
+//! current-version blob whose registered keys hold invalid values is
+//! refused with `400`, while unknown keys and other versions pass
+//! through untouched. Optimistic concurrency lives in the database
 //! layer (see [`be_remote_db::DatabaseManager::upsert_user_settings`]);
 //! this crate is a thin translator that surfaces the three
 //! [`be_remote_db::UpsertOutcome`] variants as appropriate HTTP responses.
@@ -63,6 +67,7 @@ pub fn create_router(state: Arc<AppState>) -> Router {
                 .put(handlers::put_settings)
                 .delete(handlers::delete_settings),
         )
+        .route("/settings/schema", get(handlers::get_settings_schema))
         .layer(ServiceBuilder::new().layer(TraceLayer::new_for_http()))
         .with_state(state)
 }
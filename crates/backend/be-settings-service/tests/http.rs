@@ -29,7 +29,7 @@ use reqwest::StatusCode;
 use serde_json::{Value, json};
 use settings_core::{
     CURRENT_SCHEMA_VERSION, CloudSettings, GetSettingsResponse, PutSettingsAcceptedResponse,
-    PutSettingsConflictResponse, PutSettingsRequest, ThemePreference,
+    PutSettingsConflictResponse, PutSettingsRequest, SettingsSchema, ThemePreference,
 };
 use sqlx::PgPool;
 use uuid::Uuid;
@@ -57,6 +57,9 @@ fn claims_for(user_id: Uuid) -> Claims {
         aud: "eurora".into(),
         email_verified: true,
         jti: Uuid::now_v7().to_string(),
+        analytics: Default::default(),
+        token_version: 0,
+        act: None,
     }
 }
 
@@ -81,7 +84,7 @@ impl AppHarness {
 async fn spawn_app(pool: PgPool) -> AppHarness {
     let primary = seed_user(&pool).await;
     let other = seed_user(&pool).await;
-    let db = Arc::new(DatabaseManager { pool });
+    let db = Arc::new(DatabaseManager::from_pool(pool));
     let state = Arc::new(AppState::new(db));
 
     let active = Arc::new(Mutex::new(primary));
@@ -344,6 +347,39 @@ async fn unknown_blob_shape_round_trips_verbatim(pool: PgPool) {
     assert_eq!(get.settings, arbitrary);
 }
 
+#[sqlx::test(migrations = "../be-remote-db/src/migrations")]
+async fn schema_endpoint_serves_the_registry(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let res = reqwest::get(app.url("/settings/schema"))
+        .await
+        .expect("GET");
+    assert_eq!(res.status(), StatusCode::OK);
+    let schema: SettingsSchema = res.json().await.expect("schema");
+    assert_eq!(schema, SettingsSchema::current());
+}
+
+#[sqlx::test(migrations = "../be-remote-db/src/migrations")]
+async fn put_rejects_registered_keys_with_invalid_values(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let client = reqwest::Client::new();
+
+    let mut blob = sample_settings(ThemePreference::Dark);
+    blob["desktop"]["interfaceScale"] = json!(9.0);
+    let res = client
+        .put(app.url("/settings"))
+        .json(&put_request(blob, None))
+        .send()
+        .await
+        .expect("PUT");
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let env: Value = res.json().await.expect("envelope");
+    assert_eq!(env["error"], "invalid_argument");
+
+    // Nothing was stored.
+    let get = reqwest::get(app.url("/settings")).await.expect("GET");
+    assert_eq!(get.status(), StatusCode::NOT_FOUND);
+}
+
 #[sqlx::test(migrations = "../be-remote-db/src/migrations")]
 async fn users_do_not_see_each_others_settings(pool: PgPool) {
     let app = spawn_app(pool).await;
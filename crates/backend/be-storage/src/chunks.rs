@@ -0,0 +1,636 @@
+//! Content-addressable chunk store for large objects such as screen
+//! recordings.
+//!
+//! A recording is split into chunks, each stored once per user under the
+//! hex SHA-256 of its plaintext, and described by a JSON manifest listing
+//! the chunk hashes in order. Two recordings that overlap — a re-upload,
+//! a trimmed copy, consecutive segments of the same capture — share every
+//! chunk they have in common. With [`Chunking::ContentDefined`] chunk
+//! boundaries depend on the bytes around them rather than their offset,
+//! so an insertion near the start only changes the chunks it touches.
+//!
+//! Layout, relative to the storage root:
+//!
+//! ```text
+//! chunks/{user_id}/{hash[..2]}/{hash}
+//! recordings/{user_id}/{recording_id}.manifest.json
+//! ```
+//!
+//! Chunks and manifests go through [`StorageService::write`], so they are
+//! encrypted at rest like any other object when a key is configured.
+//! Deleting a recording only removes its manifest; chunks no manifest
+//! refers to any more are reclaimed by [`ChunkStore::collect_garbage`].
+
+use std::collections::HashSet;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use bon::bon;
+use futures_util::stream::{self, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{StorageError, StorageResult, StorageService};
+
+/// Manifest format written by this version of the store.
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// How long an incomplete manifest keeps its chunks alive. Covers an
+/// upload that is still writing chunks; anything older was abandoned and
+/// is removed by garbage collection.
+pub const PENDING_MANIFEST_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Chunks fetched ahead of the one being yielded while streaming a
+/// recording back.
+const READ_AHEAD: usize = 4;
+
+const MANIFEST_SUFFIX: &str = ".manifest.json";
+
+/// How a recording is cut into chunks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Chunking {
+    /// Every chunk is `size` bytes except the last.
+    Fixed { size: usize },
+    /// Boundaries are picked by a rolling gear hash, so identical runs of
+    /// bytes chunk identically wherever they appear. Chunks are never
+    /// shorter than `min_size` (except the last) or longer than
+    /// `max_size`, and land around `min_size + avg_size` on average.
+    ContentDefined {
+        min_size: usize,
+        avg_size: usize,
+        max_size: usize,
+    },
+}
+
+impl Default for Chunking {
+    fn default() -> Self {
+        Self::ContentDefined {
+            min_size: 256 * 1024,
+            avg_size: 1024 * 1024,
+            max_size: 4 * 1024 * 1024,
+        }
+    }
+}
+
+impl Chunking {
+    fn validate(&self) -> StorageResult<()> {
+        match *self {
+            Self::Fixed { size: 0 } => Err(StorageError::configuration(
+                "fixed chunk size must be at least 1 byte",
+            )),
+            Self::Fixed { .. } => Ok(()),
+            Self::ContentDefined {
+                min_size,
+                avg_size,
+                max_size,
+            } => {
+                if min_size == 0 || avg_size == 0 || min_size > max_size || avg_size > max_size {
+                    Err(StorageError::configuration(format!(
+                        "content-defined chunking needs 0 < min_size ({min_size}) <= max_size \
+                         ({max_size}) and 0 < avg_size ({avg_size}) <= max_size"
+                    )))
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    /// Split `data` into consecutive chunks covering all of it.
+    pub fn split<'a>(&self, data: &'a [u8]) -> Vec<&'a [u8]> {
+        let mut chunks = Vec::new();
+        let mut rest = data;
+        while !rest.is_empty() {
+            let len = match *self {
+                Self::Fixed { size } => rest.len().min(size),
+                Self::ContentDefined {
+                    min_size,
+                    avg_size,
+                    max_size,
+                } => gear_cut_point(rest, min_size, avg_size, max_size),
+            };
+            let (chunk, tail) = rest.split_at(len);
+            chunks.push(chunk);
+            rest = tail;
+        }
+        chunks
+    }
+}
+
+/// Gear hash values for every byte, derived with splitmix64 so the table
+/// (and therefore every chunk boundary) is stable across builds.
+static GEAR: [u64; 256] = gear_table();
+
+const fn gear_table() -> [u64; 256] {
+    let mut table = [0u64; 256];
+    let mut state: u64 = 0x6575_726f_7261_6364; // "euroracd"
+    let mut i = 0;
+    while i < 256 {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
+}
+
+/// Length of the next chunk of `data`. The hash is tested on its top
+/// bits, which depend on the last 64 bytes, so a boundary is decided by
+/// local content only.
+fn gear_cut_point(data: &[u8], min_size: usize, avg_size: usize, max_size: usize) -> usize {
+    if data.len() <= min_size {
+        return data.len();
+    }
+    let end = data.len().min(max_size);
+    let bits = avg_size.next_power_of_two().trailing_zeros();
+    let mut hash: u64 = 0;
+    for (i, &byte) in data.iter().enumerate().take(end).skip(min_size) {
+        hash = (hash << 1).wrapping_add(GEAR[byte as usize]);
+        if bits == 0 || hash >> (64 - bits) == 0 {
+            return i + 1;
+        }
+    }
+    end
+}
+
+/// One entry of a [`ChunkManifest`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkRef {
+    /// Lowercase hex SHA-256 of the chunk's plaintext.
+    pub hash: String,
+    pub size: u64,
+}
+
+/// Describes one stored recording: its chunks in order plus enough to
+/// check the reassembled bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkManifest {
+    pub version: u32,
+    pub size: u64,
+    /// Lowercase hex SHA-256 of the whole recording.
+    pub sha256: String,
+    pub mime_type: String,
+    /// `false` while the upload is still writing chunks. Incomplete
+    /// manifests can't be read back, but keep their chunks alive for
+    /// [`PENDING_MANIFEST_TTL`].
+    pub complete: bool,
+    /// Unix seconds.
+    pub created_at: u64,
+    pub chunks: Vec<ChunkRef>,
+}
+
+impl ChunkManifest {
+    fn from_json(path: &str, bytes: &[u8]) -> StorageResult<Self> {
+        let manifest: Self = serde_json::from_slice(bytes).map_err(|e| {
+            StorageError::download_failed(format!("malformed manifest {path}: {e}"))
+        })?;
+        if manifest.version != MANIFEST_VERSION {
+            return Err(StorageError::download_failed(format!(
+                "manifest {path} has unsupported version {}",
+                manifest.version
+            )));
+        }
+        // Hashes become object paths; refuse anything that could escape
+        // the user's chunk prefix.
+        if let Some(bad) = manifest.chunks.iter().find(|c| !is_chunk_hash(&c.hash)) {
+            return Err(StorageError::download_failed(format!(
+                "manifest {path} lists invalid chunk hash `{}`",
+                bad.hash
+            )));
+        }
+        let total: u64 = manifest.chunks.iter().map(|c| c.size).sum();
+        if total != manifest.size {
+            return Err(StorageError::download_failed(format!(
+                "manifest {path} chunks add up to {total} bytes, expected {}",
+                manifest.size
+            )));
+        }
+        Ok(manifest)
+    }
+}
+
+/// Result of [`ChunkStore::put_recording`].
+#[derive(Debug, Clone)]
+pub struct StoredRecording {
+    pub manifest_path: String,
+    pub manifest: ChunkManifest,
+    /// Chunks this upload actually wrote; the rest were already stored.
+    pub new_chunks: usize,
+    pub new_bytes: u64,
+}
+
+/// What one [`ChunkStore::collect_garbage`] pass removed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GcReport {
+    pub chunks_deleted: usize,
+    pub chunks_retained: usize,
+    /// Incomplete manifests older than [`PENDING_MANIFEST_TTL`].
+    pub stale_manifests_deleted: usize,
+}
+
+/// Chunked, deduplicating storage for recordings on top of a
+/// [`StorageService`].
+#[derive(Debug, Clone)]
+pub struct ChunkStore {
+    storage: StorageService,
+    chunking: Chunking,
+}
+
+#[bon]
+impl ChunkStore {
+    #[builder]
+    pub fn new(
+        storage: StorageService,
+        #[builder(default)] chunking: Chunking,
+    ) -> StorageResult<Self> {
+        chunking.validate()?;
+        Ok(Self { storage, chunking })
+    }
+
+    /// Object path of a chunk. `hash` is a lowercase hex SHA-256.
+    pub fn chunk_path(user_id: &Uuid, hash: &str) -> String {
+        format!("chunks/{user_id}/{}/{hash}", &hash[..2])
+    }
+
+    pub fn manifest_path(user_id: &Uuid, recording_id: &Uuid) -> String {
+        format!("recordings/{user_id}/{recording_id}{MANIFEST_SUFFIX}")
+    }
+
+    /// Store `content` as a chunked recording, writing only the chunks the
+    /// user doesn't already have.
+    ///
+    /// The manifest is written twice: first marked incomplete, before any
+    /// chunk, so garbage collection already treats the reused chunks as
+    /// referenced; then complete once every chunk is in place.
+    pub async fn put_recording(
+        &self,
+        user_id: &Uuid,
+        recording_id: &Uuid,
+        content: &[u8],
+        mime_type: &str,
+    ) -> StorageResult<StoredRecording> {
+        let pieces = self.chunking.split(content);
+        let mut manifest = ChunkManifest {
+            version: MANIFEST_VERSION,
+            size: content.len() as u64,
+            sha256: hex::encode(StorageService::calculate_sha256(content)),
+            mime_type: mime_type.to_string(),
+            complete: false,
+            created_at: unix_now(),
+            chunks: pieces
+                .iter()
+                .map(|piece| ChunkRef {
+                    hash: hex::encode(StorageService::calculate_sha256(piece)),
+                    size: piece.len() as u64,
+                })
+                .collect(),
+        };
+
+        let manifest_path = Self::manifest_path(user_id, recording_id);
+        self.write_manifest(&manifest_path, &manifest).await?;
+
+        let mut seen = HashSet::new();
+        let mut new_chunks = 0;
+        let mut new_bytes = 0;
+        for (piece, chunk) in pieces.iter().zip(&manifest.chunks) {
+            if !seen.insert(chunk.hash.as_str()) {
+                continue;
+            }
+            let path = Self::chunk_path(user_id, &chunk.hash);
+            if self.storage.exists(&path).await? {
+                continue;
+            }
+            self.storage.write(&path, piece).await?;
+            new_chunks += 1;
+            new_bytes += chunk.size;
+        }
+
+        manifest.complete = true;
+        self.write_manifest(&manifest_path, &manifest).await?;
+
+        tracing::info!(
+            %user_id,
+            %recording_id,
+            size = manifest.size,
+            chunks = manifest.chunks.len(),
+            new_chunks,
+            new_bytes,
+            "stored chunked recording"
+        );
+
+        Ok(StoredRecording {
+            manifest_path,
+            manifest,
+            new_chunks,
+            new_bytes,
+        })
+    }
+
+    /// Load the manifest of a fully uploaded recording.
+    pub async fn manifest(
+        &self,
+        user_id: &Uuid,
+        recording_id: &Uuid,
+    ) -> StorageResult<ChunkManifest> {
+        let path = Self::manifest_path(user_id, recording_id);
+        let manifest = self.read_manifest(&path).await?;
+        if !manifest.complete {
+            return Err(StorageError::not_found(path));
+        }
+        Ok(manifest)
+    }
+
+    /// Stream a recording back chunk by chunk, in order. Each chunk is
+    /// checked against its hash before it is yielded, and a few chunks
+    /// are fetched ahead so the consumer isn't waiting on one round trip
+    /// per chunk.
+    pub async fn read_recording(
+        &self,
+        user_id: &Uuid,
+        recording_id: &Uuid,
+    ) -> StorageResult<impl Stream<Item = StorageResult<Vec<u8>>> + Send + 'static> {
+        let manifest = self.manifest(user_id, recording_id).await?;
+        let store = self.clone();
+        let user_id = *user_id;
+
+        Ok(stream::iter(manifest.chunks)
+            .map(move |chunk| {
+                let store = store.clone();
+                async move { store.read_chunk(&user_id, &chunk).await }
+            })
+            .buffered(READ_AHEAD))
+    }
+
+    /// Reassemble a whole recording in memory. Prefer
+    /// [`Self::read_recording`] for anything that can be streamed.
+    pub async fn download_recording(
+        &self,
+        user_id: &Uuid,
+        recording_id: &Uuid,
+    ) -> StorageResult<Vec<u8>> {
+        let mut chunks = std::pin::pin!(self.read_recording(user_id, recording_id).await?);
+        let mut content = Vec::new();
+        while let Some(chunk) = chunks.next().await {
+            content.extend_from_slice(&chunk?);
+        }
+        Ok(content)
+    }
+
+    /// Remove a recording's manifest. Its chunks stay until the next
+    /// garbage collection finds them unreferenced.
+    pub async fn delete_recording(&self, user_id: &Uuid, recording_id: &Uuid) -> StorageResult<()> {
+        self.storage
+            .delete(&Self::manifest_path(user_id, recording_id))
+            .await
+    }
+
+    /// Delete the user's chunks that no manifest refers to, and incomplete
+    /// manifests older than [`PENDING_MANIFEST_TTL`].
+    ///
+    /// Manifests are scanned a second time just before deleting, so an
+    /// upload that started during the pass keeps the chunks it reuses.
+    /// A manifest that can't be read aborts the pass rather than risk
+    /// deleting chunks it references.
+    pub async fn collect_garbage(&self, user_id: &Uuid) -> StorageResult<GcReport> {
+        let mut report = GcReport::default();
+
+        let mut referenced = self.referenced_chunks(user_id, Some(&mut report)).await?;
+
+        let mut candidates = Vec::new();
+        for path in self.list_files(&format!("chunks/{user_id}/")).await? {
+            let hash = path.rsplit('/').next().unwrap_or_default();
+            if referenced.contains(hash) {
+                report.chunks_retained += 1;
+            } else {
+                candidates.push((hash.to_string(), path));
+            }
+        }
+
+        if !candidates.is_empty() {
+            referenced.extend(self.referenced_chunks(user_id, None).await?);
+        }
+
+        for (hash, path) in candidates {
+            if referenced.contains(&hash) {
+                report.chunks_retained += 1;
+                continue;
+            }
+            self.storage.delete(&path).await?;
+            report.chunks_deleted += 1;
+        }
+
+        tracing::info!(
+            %user_id,
+            chunks_deleted = report.chunks_deleted,
+            chunks_retained = report.chunks_retained,
+            stale_manifests_deleted = report.stale_manifests_deleted,
+            "chunk store garbage collection finished"
+        );
+
+        Ok(report)
+    }
+
+    /// Hashes referenced by the user's manifests. With a report, stale
+    /// incomplete manifests are deleted and counted instead of counted as
+    /// references.
+    async fn referenced_chunks(
+        &self,
+        user_id: &Uuid,
+        mut report: Option<&mut GcReport>,
+    ) -> StorageResult<HashSet<String>> {
+        let cutoff = unix_now().saturating_sub(PENDING_MANIFEST_TTL.as_secs());
+        let mut referenced = HashSet::new();
+
+        for path in self.list_files(&format!("recordings/{user_id}/")).await? {
+            if !path.ends_with(MANIFEST_SUFFIX) {
+                continue;
+            }
+            let manifest = match self.read_manifest(&path).await {
+                Ok(manifest) => manifest,
+                // Deleted since the listing.
+                Err(e) if e.is_not_found() => continue,
+                Err(e) => return Err(e),
+            };
+            if let Some(report) = report.as_deref_mut()
+                && !manifest.complete
+                && manifest.created_at < cutoff
+            {
+                self.storage.delete(&path).await?;
+                report.stale_manifests_deleted += 1;
+                continue;
+            }
+            referenced.extend(manifest.chunks.into_iter().map(|c| c.hash));
+        }
+
+        Ok(referenced)
+    }
+
+    async fn read_chunk(&self, user_id: &Uuid, chunk: &ChunkRef) -> StorageResult<Vec<u8>> {
+        let path = Self::chunk_path(user_id, &chunk.hash);
+        let bytes = self.storage.download(&path).await?;
+        if hex::encode(StorageService::calculate_sha256(&bytes)) != chunk.hash {
+            return Err(StorageError::download_failed(format!(
+                "chunk {path} does not match its hash"
+            )));
+        }
+        Ok(bytes)
+    }
+
+    async fn read_manifest(&self, path: &str) -> StorageResult<ChunkManifest> {
+        let bytes = self.storage.download(path).await?;
+        ChunkManifest::from_json(path, &bytes)
+    }
+
+    async fn write_manifest(&self, path: &str, manifest: &ChunkManifest) -> StorageResult<()> {
+        let json = serde_json::to_vec(manifest)
+            .map_err(|e| StorageError::upload_failed(format!("encode manifest {path}: {e}")))?;
+        self.storage.write(path, &json).await
+    }
+
+    /// Every file below `prefix`, recursively. A prefix that doesn't exist
+    /// yet lists as empty.
+    async fn list_files(&self, prefix: &str) -> StorageResult<Vec<String>> {
+        match self
+            .storage
+            .operator()
+            .list_with(prefix)
+            .recursive(true)
+            .await
+        {
+            Ok(entries) => Ok(entries
+                .into_iter()
+                .filter(|entry| entry.metadata().is_file())
+                .map(|entry| entry.path().to_string())
+                .collect()),
+            Err(e) if e.kind() == opendal::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+fn is_chunk_hash(s: &str) -> bool {
+    s.len() == 64 && s.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic pseudo-random bytes, so boundaries are exercised on
+    /// data without long runs.
+    fn noise(len: usize, seed: u64) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect()
+    }
+
+    const SMALL_CDC: Chunking = Chunking::ContentDefined {
+        min_size: 256,
+        avg_size: 1024,
+        max_size: 4096,
+    };
+
+    #[test]
+    fn fixed_chunking_splits_on_size() {
+        let data = noise(2500, 1);
+        let chunks = Chunking::Fixed { size: 1000 }.split(&data);
+        let sizes: Vec<usize> = chunks.iter().map(|c| c.len()).collect();
+        assert_eq!(sizes, [1000, 1000, 500]);
+        assert_eq!(chunks.concat(), data);
+        assert!(Chunking::Fixed { size: 10 }.split(&[]).is_empty());
+    }
+
+    #[test]
+    fn content_defined_chunks_respect_bounds_and_cover_input() {
+        let data = noise(200_000, 2);
+        let chunks = SMALL_CDC.split(&data);
+        assert_eq!(chunks.concat(), data);
+        let (last, rest) = chunks.split_last().unwrap();
+        assert!(rest.iter().all(|c| (256..=4096).contains(&c.len())));
+        assert!(last.len() <= 4096);
+        // Roughly min + avg on average; loose bounds keep this stable.
+        let mean = data.len() / chunks.len();
+        assert!((600..=3000).contains(&mean), "mean chunk size {mean}");
+    }
+
+    #[test]
+    fn content_defined_chunking_survives_an_insertion() {
+        let original = noise(200_000, 3);
+        let mut edited = b"a few inserted bytes".to_vec();
+        edited.extend_from_slice(&original);
+
+        let before: HashSet<&[u8]> = SMALL_CDC.split(&original).into_iter().collect();
+        let after = SMALL_CDC.split(&edited);
+        let shared = after.iter().filter(|c| before.contains(*c)).count();
+        assert!(
+            shared + 3 >= after.len(),
+            "only {shared} of {} chunks shared",
+            after.len()
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_chunking() {
+        assert!(Chunking::Fixed { size: 0 }.validate().is_err());
+        assert!(
+            Chunking::ContentDefined {
+                min_size: 10,
+                avg_size: 20,
+                max_size: 5,
+            }
+            .validate()
+            .is_err()
+        );
+        assert!(Chunking::default().validate().is_ok());
+    }
+
+    #[test]
+    fn manifest_rejects_unsafe_or_inconsistent_entries() {
+        let hash = "ab".repeat(32);
+        let manifest = ChunkManifest {
+            version: MANIFEST_VERSION,
+            size: 3,
+            sha256: hash.clone(),
+            mime_type: "video/mp4".into(),
+            complete: true,
+            created_at: 0,
+            chunks: vec![ChunkRef { hash, size: 3 }],
+        };
+        let json = serde_json::to_vec(&manifest).unwrap();
+        assert_eq!(ChunkManifest::from_json("m", &json).unwrap(), manifest);
+
+        let mut traversal = manifest.clone();
+        traversal.chunks[0].hash = "../../etc/passwd".into();
+        let json = serde_json::to_vec(&traversal).unwrap();
+        assert!(ChunkManifest::from_json("m", &json).is_err());
+
+        let mut short = manifest;
+        short.size = 4;
+        let json = serde_json::to_vec(&short).unwrap();
+        assert!(ChunkManifest::from_json("m", &json).is_err());
+    }
+
+    #[test]
+    fn chunk_paths_fan_out_on_hash_prefix() {
+        let user_id = Uuid::parse_str("550e8400-e29b-41d4-a716-446655440000").unwrap();
+        let hash = format!("ab{}", "0".repeat(62));
+        assert_eq!(
+            ChunkStore::chunk_path(&user_id, &hash),
+            format!("chunks/550e8400-e29b-41d4-a716-446655440000/ab/{hash}")
+        );
+    }
+}
@@ -31,6 +31,16 @@ pub enum StorageError {
 
     #[error("Encryption error: {0}")]
     Encryption(String),
+
+    #[error("Requested range is outside the object ({size} bytes)")]
+    RangeNotSatisfiable { size: u64 },
+
+    #[error("Integrity check failed for {path}: expected SHA-256 {expected}, got {actual}")]
+    IntegrityMismatch {
+        path: String,
+        expected: String,
+        actual: String,
+    },
 }
 
 impl StorageError {
@@ -69,6 +79,12 @@ impl StorageError {
     pub fn is_configuration(&self) -> bool {
         matches!(self, Self::Configuration(_) | Self::MissingEnvVar(_))
     }
+
+    /// The object was read but its bytes don't hash to the expected
+    /// checksum: it's corrupted, truncated, or was replaced.
+    pub fn is_integrity_mismatch(&self) -> bool {
+        matches!(self, Self::IntegrityMismatch { .. })
+    }
 }
 
 pub type StorageResult<T> = std::result::Result<T, StorageError>;
@@ -1,6 +1,15 @@
+mod chunks;
 mod error;
+mod tiering;
 
+pub use chunks::{
+    ChunkManifest, ChunkRef, ChunkStore, Chunking, GcReport, MANIFEST_VERSION,
+    PENDING_MANIFEST_TTL, StoredRecording,
+};
 pub use error::{StorageError, StorageResult};
+pub use tiering::{COLD_URI_PREFIX, DEFAULT_COLD_AFTER_DAYS, StorageTier, TieringConfig};
+
+use std::ops::Range;
 
 use bon::bon;
 use opendal::{Operator, services};
@@ -19,69 +28,169 @@ pub enum StorageConfig {
         endpoint: Option<String>,
         access_key_id: Option<SecretString>,
         secret_access_key: Option<SecretString>,
+        /// S3 storage class new objects are written with, e.g.
+        /// `STANDARD_IA` or `GLACIER_IR`. The bucket's default when unset.
+        storage_class: Option<String>,
+    },
+    /// Azure Blob Storage. Needs the `azblob` feature. Without an account
+    /// key or SAS token, credentials come from the environment
+    /// (`AZURE_*` variables or a managed identity).
+    Azblob {
+        container: String,
+        account_name: String,
+        /// `https://{account_name}.blob.core.windows.net` when unset.
+        endpoint: Option<String>,
+        account_key: Option<SecretString>,
+        sas_token: Option<SecretString>,
+    },
+    /// Google Cloud Storage. Needs the `gcs` feature. Without a
+    /// credential, Application Default Credentials are used
+    /// (`GOOGLE_APPLICATION_CREDENTIALS` or the VM metadata server).
+    Gcs {
+        bucket: String,
+        endpoint: Option<String>,
+        /// Base64-encoded service account key JSON.
+        credential: Option<SecretString>,
+        /// Path to a service account key JSON file.
+        credential_path: Option<String>,
+        /// Storage class new objects are written with, e.g. `NEARLINE`.
+        storage_class: Option<String>,
     },
 }
 
 impl StorageConfig {
     pub fn from_env() -> StorageResult<Self> {
-        let backend = std::env::var("ASSET_STORAGE_BACKEND")
-            .map_err(|_| StorageError::missing_env_var("ASSET_STORAGE_BACKEND"))?
-            .trim()
-            .to_lowercase();
+        Self::from_env_prefixed("ASSET_STORAGE")
+    }
+
+    /// Read `{prefix}_BACKEND` and the matching `{prefix}_FS_*`,
+    /// `{prefix}_S3_*`, `{prefix}_AZBLOB_*` or `{prefix}_GCS_*` variables.
+    pub(crate) fn from_env_prefixed(prefix: &str) -> StorageResult<Self> {
+        let name = |suffix: &str| format!("{prefix}_{suffix}");
+        let required = |suffix: &str| {
+            let var = name(suffix);
+            std::env::var(&var).map_err(|_| StorageError::missing_env_var(var))
+        };
+        let optional = |suffix: &str| {
+            std::env::var(name(suffix))
+                .ok()
+                .filter(|value| !value.trim().is_empty())
+        };
+
+        let backend = required("BACKEND")?.trim().to_lowercase();
 
         match backend.as_str() {
-            "s3" => {
-                let bucket = std::env::var("ASSET_STORAGE_S3_BUCKET")
-                    .map_err(|_| StorageError::missing_env_var("ASSET_STORAGE_S3_BUCKET"))?;
-                let region = std::env::var("ASSET_STORAGE_S3_REGION")
-                    .map_err(|_| StorageError::missing_env_var("ASSET_STORAGE_S3_REGION"))?;
-                let endpoint = std::env::var("ASSET_STORAGE_S3_ENDPOINT").ok();
-                let access_key_id = std::env::var("ASSET_STORAGE_S3_ACCESS_KEY_ID")
-                    .ok()
-                    .map(SecretString::from);
-                let secret_access_key = std::env::var("ASSET_STORAGE_S3_SECRET_ACCESS_KEY")
-                    .ok()
-                    .map(SecretString::from);
-
-                Ok(StorageConfig::S3 {
-                    bucket,
-                    region,
-                    endpoint,
-                    access_key_id,
-                    secret_access_key,
-                })
-            }
-            "fs" => {
-                let root = std::env::var("ASSET_STORAGE_FS_ROOT")
-                    .map_err(|_| StorageError::missing_env_var("ASSET_STORAGE_FS_ROOT"))?;
-                Ok(StorageConfig::FS { root })
-            }
+            "s3" => Ok(StorageConfig::S3 {
+                bucket: required("S3_BUCKET")?,
+                region: required("S3_REGION")?,
+                endpoint: optional("S3_ENDPOINT"),
+                access_key_id: optional("S3_ACCESS_KEY_ID").map(SecretString::from),
+                secret_access_key: optional("S3_SECRET_ACCESS_KEY").map(SecretString::from),
+                storage_class: optional("S3_STORAGE_CLASS"),
+            }),
+            "azblob" => Ok(StorageConfig::Azblob {
+                container: required("AZBLOB_CONTAINER")?,
+                account_name: required("AZBLOB_ACCOUNT_NAME")?,
+                endpoint: optional("AZBLOB_ENDPOINT"),
+                account_key: optional("AZBLOB_ACCOUNT_KEY").map(SecretString::from),
+                sas_token: optional("AZBLOB_SAS_TOKEN").map(SecretString::from),
+            }),
+            "gcs" => Ok(StorageConfig::Gcs {
+                bucket: required("GCS_BUCKET")?,
+                endpoint: optional("GCS_ENDPOINT"),
+                credential: optional("GCS_CREDENTIAL").map(SecretString::from),
+                credential_path: optional("GCS_CREDENTIAL_PATH"),
+                storage_class: optional("GCS_STORAGE_CLASS"),
+            }),
+            "fs" => Ok(StorageConfig::FS {
+                root: required("FS_ROOT")?,
+            }),
             other => Err(StorageError::configuration(format!(
-                "unknown ASSET_STORAGE_BACKEND `{other}` (expected `fs` or `s3`)"
+                "unknown {} `{other}` (expected `fs`, `s3`, `azblob` or `gcs`)",
+                name("BACKEND")
             ))),
         }
     }
+
+    pub fn backend_name(&self) -> &'static str {
+        match self {
+            StorageConfig::FS { .. } => "fs",
+            StorageConfig::S3 { .. } => "s3",
+            StorageConfig::Azblob { .. } => "azblob",
+            StorageConfig::Gcs { .. } => "gcs",
+        }
+    }
+}
+
+/// Part of an object returned by [`StorageService::download_range`].
+#[derive(Debug, Clone)]
+pub struct ObjectRange {
+    pub bytes: Vec<u8>,
+    /// Offset of `bytes[0]` within the object.
+    pub offset: u64,
+    /// Size of the whole object.
+    pub total_size: u64,
+}
+
+/// An object being written in pieces; see [`StorageService::writer`].
+pub struct ObjectWriter {
+    writer: opendal::Writer,
+}
+
+impl ObjectWriter {
+    /// Append `bytes` to the object.
+    pub async fn write(&mut self, bytes: Vec<u8>) -> StorageResult<()> {
+        Ok(self.writer.write(bytes).await?)
+    }
+
+    /// Finish the object. Until this returns it doesn't exist.
+    pub async fn close(&mut self) -> StorageResult<()> {
+        self.writer.close().await?;
+        Ok(())
+    }
+
+    /// Give up on the object, discarding what was written. Best effort:
+    /// a backend that can't abort leaves an incomplete upload for its own
+    /// lifecycle rules to clean up.
+    pub async fn abort(&mut self) {
+        if let Err(e) = self.writer.abort().await {
+            tracing::warn!(error = %e, "Failed to abort object write");
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct StorageService {
     operator: Operator,
     config: StorageConfig,
+    tiering: Option<tiering::ColdTier>,
     #[cfg(feature = "encryption")]
     encryption_key: std::sync::Arc<std::sync::RwLock<Option<be_encrypt::MainKey>>>,
 }
 
 #[bon]
 impl StorageService {
+    /// `tiering` adds a cold store that old assets are moved to; see
+    /// [`Self::copy_to_tier`].
     #[builder]
     pub fn new(
         config: StorageConfig,
+        tiering: Option<TieringConfig>,
         #[cfg(feature = "encryption")] encryption_key: Option<be_encrypt::MainKey>,
     ) -> StorageResult<Self> {
         let operator = Self::create_operator(&config)?;
+        let tiering = tiering
+            .map(|config| {
+                Ok::<_, StorageError>(tiering::ColdTier {
+                    operator: Self::create_operator(&config.cold)?,
+                    config,
+                })
+            })
+            .transpose()?;
         Ok(Self {
             operator,
             config,
+            tiering,
             #[cfg(feature = "encryption")]
             encryption_key: std::sync::Arc::new(std::sync::RwLock::new(encryption_key)),
         })
@@ -97,14 +206,16 @@ impl StorageService {
 
     pub fn from_env() -> StorageResult<Self> {
         let config = StorageConfig::from_env()?;
+        let tiering = TieringConfig::from_env()?;
         tracing::info!(
-            backend = match &config {
-                StorageConfig::FS { .. } => "fs",
-                StorageConfig::S3 { .. } => "s3",
-            },
+            backend = config.backend_name(),
+            cold_tier = tiering.is_some(),
             "Initializing storage service"
         );
-        Self::builder().config(config).build()
+        Self::builder()
+            .config(config)
+            .maybe_tiering(tiering)
+            .build()
     }
 
     fn create_operator(config: &StorageConfig) -> StorageResult<Operator> {
@@ -124,6 +235,7 @@ impl StorageService {
                 endpoint,
                 access_key_id,
                 secret_access_key,
+                storage_class,
             } => {
                 tracing::debug!("Creating S3 storage operator for bucket: {}", bucket);
 
@@ -141,8 +253,78 @@ impl StorageService {
                     builder = builder.secret_access_key(secret.expose_secret());
                 }
 
+                if let Some(class) = storage_class {
+                    builder = builder.default_storage_class(class);
+                }
+
                 Ok(Operator::new(builder)?.finish())
             }
+            #[cfg(feature = "azblob")]
+            StorageConfig::Azblob {
+                container,
+                account_name,
+                endpoint,
+                account_key,
+                sas_token,
+            } => {
+                tracing::debug!(
+                    "Creating Azure Blob storage operator for container: {}",
+                    container
+                );
+
+                let endpoint = endpoint
+                    .clone()
+                    .unwrap_or_else(|| format!("https://{account_name}.blob.core.windows.net"));
+                let mut builder = services::Azblob::default()
+                    .container(container)
+                    .account_name(account_name)
+                    .endpoint(&endpoint);
+
+                if let Some(key) = account_key {
+                    builder = builder.account_key(key.expose_secret());
+                }
+
+                if let Some(token) = sas_token {
+                    builder = builder.sas_token(token.expose_secret());
+                }
+
+                Ok(Operator::new(builder)?.finish())
+            }
+            #[cfg(feature = "gcs")]
+            StorageConfig::Gcs {
+                bucket,
+                endpoint,
+                credential,
+                credential_path,
+                storage_class,
+            } => {
+                tracing::debug!("Creating GCS storage operator for bucket: {}", bucket);
+
+                let mut builder = services::Gcs::default().bucket(bucket);
+
+                if let Some(ep) = endpoint {
+                    builder = builder.endpoint(ep);
+                }
+
+                if let Some(credential) = credential {
+                    builder = builder.credential(credential.expose_secret());
+                }
+
+                if let Some(path) = credential_path {
+                    builder = builder.credential_path(path);
+                }
+
+                if let Some(class) = storage_class {
+                    builder = builder.default_storage_class(class);
+                }
+
+                Ok(Operator::new(builder)?.finish())
+            }
+            #[allow(unreachable_patterns)]
+            other => Err(StorageError::configuration(format!(
+                "the `{0}` storage backend needs be-storage built with the `{0}` feature",
+                other.backend_name()
+            ))),
         }
     }
 
@@ -158,6 +340,14 @@ impl StorageService {
     }
 
     pub fn extension_from_mime(mime_type: &str) -> &'static str {
+        // mime_guess lists extensions alphabetically, which picks `jfif`
+        // for JPEG and may not know newer image formats at all.
+        match mime_type {
+            "image/jpeg" => return "jpg",
+            "image/webp" => return "webp",
+            "image/avif" => return "avif",
+            _ => {}
+        }
         mime_guess::get_mime_extensions_str(mime_type)
             .and_then(|exts| exts.first())
             .copied()
@@ -182,6 +372,17 @@ impl StorageService {
             content.len()
         );
 
+        self.write(&path, content).await?;
+
+        tracing::info!("Successfully uploaded asset {}", asset_id);
+
+        Ok(path)
+    }
+
+    /// Write `content` to `path`, encrypting it first when an encryption
+    /// key is configured. [`Self::upload`] is this plus asset path naming;
+    /// use this directly for objects that aren't assets.
+    pub async fn write(&self, path: &str, content: &[u8]) -> StorageResult<()> {
         #[cfg(feature = "encryption")]
         let content = {
             let key_guard = self
@@ -191,11 +392,11 @@ impl StorageService {
             match key_guard.as_ref() {
                 Some(key) => {
                     let encrypted = be_encrypt::encrypt(key, content, "asset").map_err(|e| {
-                        StorageError::Encryption(format!("Failed to encrypt asset: {}", e))
+                        StorageError::Encryption(format!("Failed to encrypt object: {}", e))
                     })?;
                     tracing::debug!(
-                        "Encrypted asset {} ({} -> {} bytes)",
-                        asset_id,
+                        "Encrypted object for {} ({} -> {} bytes)",
+                        path,
                         content.len(),
                         encrypted.len()
                     );
@@ -208,26 +409,129 @@ impl StorageService {
         #[cfg(not(feature = "encryption"))]
         let content = content.to_vec();
 
-        self.operator.write(&path, content).await?;
+        let (operator, key) = self.operator_for(path)?;
+        operator.write(key, content).await?;
 
-        tracing::info!("Successfully uploaded asset {}", asset_id);
+        Ok(())
+    }
 
-        Ok(path)
+    /// Whether [`Self::write`] encrypts objects. An encrypted object is
+    /// sealed as a whole, so it can't be written through
+    /// [`Self::writer`].
+    pub fn seals_objects(&self) -> bool {
+        #[cfg(feature = "encryption")]
+        {
+            self.encryption_key
+                .read()
+                .expect("encryption key lock poisoned")
+                .is_some()
+        }
+
+        #[cfg(not(feature = "encryption"))]
+        {
+            false
+        }
+    }
+
+    /// Open `path` for writing in pieces, so a large object never has to
+    /// be held in memory. Plaintext only: fails with
+    /// [`StorageError::Encryption`] when [`Self::seals_objects`].
+    pub async fn writer(&self, path: &str) -> StorageResult<ObjectWriter> {
+        if self.seals_objects() {
+            return Err(StorageError::Encryption(
+                "encrypted objects must be written whole".into(),
+            ));
+        }
+        let (operator, key) = self.operator_for(path)?;
+        let writer = operator.writer(key).await?;
+        Ok(ObjectWriter { writer })
+    }
+
+    /// Read the object at `path` exactly as stored, without decrypting it.
+    pub async fn download_raw(&self, path: &str) -> StorageResult<Vec<u8>> {
+        let (operator, key) = self.operator_for(path)?;
+        let content = operator.read(key).await.map_err(|e| {
+            if e.kind() == opendal::ErrorKind::NotFound {
+                StorageError::not_found(path)
+            } else {
+                StorageError::from(e)
+            }
+        })?;
+
+        Ok(content.to_vec())
     }
 
     pub async fn download(&self, path: &str) -> StorageResult<Vec<u8>> {
         tracing::debug!("Downloading asset from path: {}", path);
 
-        let content = self.operator.read(path).await.map_err(|e| {
+        let bytes = self.download_raw(path).await?;
+        let bytes = self.open_sealed(path, bytes)?;
+
+        tracing::debug!(
+            "Successfully downloaded {} bytes from {}",
+            bytes.len(),
+            path
+        );
+
+        Ok(bytes)
+    }
+
+    /// [`Self::download`], checking that the content hashes to
+    /// `expected_sha256`, the checksum taken at upload. The object is
+    /// streamed and hashed as it arrives; an encrypted object is hashed
+    /// after decrypting, since the checksum is of the plaintext. A
+    /// mismatch fails with [`StorageError::IntegrityMismatch`].
+    pub async fn download_verified(
+        &self,
+        path: &str,
+        expected_sha256: &[u8],
+    ) -> StorageResult<Vec<u8>> {
+        use futures_util::TryStreamExt as _;
+
+        let not_found = |e: opendal::Error| {
             if e.kind() == opendal::ErrorKind::NotFound {
                 StorageError::not_found(path)
             } else {
                 StorageError::from(e)
             }
-        })?;
+        };
+
+        let (operator, key) = self.operator_for(path)?;
+        let mut stream = operator
+            .reader(key)
+            .await
+            .map_err(not_found)?
+            .into_bytes_stream(..)
+            .await
+            .map_err(not_found)?;
+
+        let mut hasher = Sha256::new();
+        let mut stored = Vec::new();
+        while let Some(chunk) = stream.try_next().await? {
+            hasher.update(&chunk);
+            stored.extend_from_slice(&chunk);
+        }
 
-        let bytes = content.to_vec();
+        let sealed = is_sealed(&stored);
+        let bytes = self.open_sealed(path, stored)?;
+        let actual = if sealed {
+            Self::calculate_sha256(&bytes)
+        } else {
+            hasher.finalize().to_vec()
+        };
+
+        if actual != expected_sha256 {
+            return Err(StorageError::IntegrityMismatch {
+                path: path.to_owned(),
+                expected: hex::encode(expected_sha256),
+                actual: hex::encode(actual),
+            });
+        }
+        Ok(bytes)
+    }
 
+    /// Decrypt `bytes` read from `path` if they were encrypted on write.
+    fn open_sealed(&self, path: &str, bytes: Vec<u8>) -> StorageResult<Vec<u8>> {
         #[cfg(feature = "encryption")]
         let bytes = {
             let key_guard = self
@@ -256,19 +560,91 @@ impl StorageService {
             }
         };
 
+        #[cfg(not(feature = "encryption"))]
+        let _ = path;
+
+        Ok(bytes)
+    }
+
+    /// Read `length` bytes starting at `offset`, or everything from
+    /// `offset` on when `length` is `None`. A range running past the end
+    /// is clamped; one starting at or past the end fails with
+    /// [`StorageError::RangeNotSatisfiable`].
+    ///
+    /// Plaintext objects are fetched with a ranged read, so only the
+    /// requested bytes leave the backend. Encrypted objects are sealed as
+    /// a whole and have to be downloaded and decrypted in full first.
+    pub async fn download_range(
+        &self,
+        path: &str,
+        offset: u64,
+        length: Option<u64>,
+    ) -> StorageResult<ObjectRange> {
+        let not_found = |e: opendal::Error| {
+            if e.kind() == opendal::ErrorKind::NotFound {
+                StorageError::not_found(path)
+            } else {
+                StorageError::from(e)
+            }
+        };
+
+        let (operator, key) = self.operator_for(path)?;
+        let stored_size = operator
+            .stat(key)
+            .await
+            .map_err(not_found)?
+            .content_length();
+
+        #[cfg(feature = "encryption")]
+        {
+            let magic_len = be_encrypt::MAGIC.len() as u64;
+            if stored_size >= magic_len {
+                let head = operator
+                    .read_with(key)
+                    .range(0..magic_len)
+                    .await
+                    .map_err(not_found)?
+                    .to_vec();
+                if be_encrypt::is_encrypted(&head) {
+                    let plaintext = self.download(path).await?;
+                    let total_size = plaintext.len() as u64;
+                    let range = resolve_range(total_size, offset, length)?;
+                    return Ok(ObjectRange {
+                        bytes: plaintext[range.start as usize..range.end as usize].to_vec(),
+                        offset: range.start,
+                        total_size,
+                    });
+                }
+            }
+        }
+
+        let range = resolve_range(stored_size, offset, length)?;
         tracing::debug!(
-            "Successfully downloaded {} bytes from {}",
-            bytes.len(),
+            "Downloading bytes {}..{} of {} from {}",
+            range.start,
+            range.end,
+            stored_size,
             path
         );
-
-        Ok(bytes)
+        let bytes = operator
+            .read_with(key)
+            .range(range.clone())
+            .await
+            .map_err(not_found)?
+            .to_vec();
+
+        Ok(ObjectRange {
+            bytes,
+            offset: range.start,
+            total_size: stored_size,
+        })
     }
 
     pub async fn delete(&self, path: &str) -> StorageResult<()> {
         tracing::debug!("Deleting asset at path: {}", path);
 
-        self.operator.delete(path).await?;
+        let (operator, key) = self.operator_for(path)?;
+        operator.delete(key).await?;
 
         tracing::info!("Successfully deleted asset at {}", path);
 
@@ -276,7 +652,8 @@ impl StorageService {
     }
 
     pub async fn exists(&self, path: &str) -> StorageResult<bool> {
-        match self.operator.stat(path).await {
+        let (operator, key) = self.operator_for(path)?;
+        match operator.stat(key).await {
             Ok(_) => Ok(true),
             Err(e) if e.kind() == opendal::ErrorKind::NotFound => Ok(false),
             Err(e) => Err(e.into()),
@@ -287,18 +664,43 @@ impl StorageService {
         &self.config
     }
 
+    /// The hot store's operator. Objects in the cold tier are reached
+    /// through the path-based methods, which route on the URI.
     pub fn operator(&self) -> &Operator {
         &self.operator
     }
 
     pub fn get_backend_name(&self) -> &str {
-        match self.config {
-            StorageConfig::S3 { .. } => "s3",
-            StorageConfig::FS { .. } => "fs",
-        }
+        self.config.backend_name()
+    }
+}
+
+/// Whether `stored` was encrypted on write.
+fn is_sealed(stored: &[u8]) -> bool {
+    #[cfg(feature = "encryption")]
+    {
+        be_encrypt::is_encrypted(stored)
+    }
+
+    #[cfg(not(feature = "encryption"))]
+    {
+        let _ = stored;
+        false
     }
 }
 
+/// Clamp `offset` / `length` to an object of `size` bytes.
+fn resolve_range(size: u64, offset: u64, length: Option<u64>) -> StorageResult<Range<u64>> {
+    if offset >= size || length == Some(0) {
+        return Err(StorageError::RangeNotSatisfiable { size });
+    }
+    let end = match length {
+        Some(length) => offset.saturating_add(length).min(size),
+        None => size,
+    };
+    Ok(offset..end)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -329,11 +731,45 @@ mod tests {
     #[test]
     fn test_extension_from_mime() {
         assert_eq!(StorageService::extension_from_mime("image/png"), "png");
-        assert_eq!(StorageService::extension_from_mime("image/jpeg"), "jfif");
+        assert_eq!(StorageService::extension_from_mime("image/jpeg"), "jpg");
+        assert_eq!(StorageService::extension_from_mime("image/webp"), "webp");
+        assert_eq!(StorageService::extension_from_mime("image/avif"), "avif");
         assert_eq!(
             StorageService::extension_from_mime("application/pdf"),
             "pdf"
         );
         assert_eq!(StorageService::extension_from_mime("unknown/type"), "bin");
     }
+
+    #[cfg(not(feature = "azblob"))]
+    #[test]
+    fn backend_without_its_feature_is_a_configuration_error() {
+        let err = StorageService::builder()
+            .config(StorageConfig::Azblob {
+                container: "assets".into(),
+                account_name: "eurora".into(),
+                endpoint: None,
+                account_key: None,
+                sas_token: None,
+            })
+            .build()
+            .unwrap_err();
+        assert!(err.is_configuration());
+        assert!(err.to_string().contains("`azblob` feature"));
+    }
+
+    #[test]
+    fn test_resolve_range() {
+        assert_eq!(resolve_range(100, 0, None).unwrap(), 0..100);
+        assert_eq!(resolve_range(100, 10, Some(20)).unwrap(), 10..30);
+        assert_eq!(resolve_range(100, 90, Some(20)).unwrap(), 90..100);
+        assert_eq!(resolve_range(100, 99, Some(u64::MAX)).unwrap(), 99..100);
+
+        assert!(matches!(
+            resolve_range(100, 100, None),
+            Err(StorageError::RangeNotSatisfiable { size: 100 })
+        ));
+        assert!(resolve_range(100, 0, Some(0)).is_err());
+        assert!(resolve_range(0, 0, None).is_err());
+    }
 }
@@ -0,0 +1,155 @@
+//! Storage tiering: a second, cheaper store that old assets are moved to.
+//!
+//! The cold store is configured like the main one, with
+//! `ASSET_COLD_STORAGE_BACKEND` and the matching backend variables
+//! (`ASSET_COLD_STORAGE_S3_*` and so on); `ASSET_COLD_STORAGE_S3_STORAGE_CLASS`
+//! picks a cheaper S3 class for it, which may be the same bucket under a
+//! different class or a separate bucket entirely. Classes that need a
+//! restore request before reading (Glacier Flexible Retrieval, Deep
+//! Archive) are not supported; `GLACIER_IR` and `STANDARD_IA` are.
+//!
+//! An object in the cold store has a storage URI starting with
+//! [`COLD_URI_PREFIX`], so every path-based [`StorageService`] method
+//! reaches it without the caller knowing which tier it's in. Moving an
+//! object is [`StorageService::copy_to_tier`] followed, once the new URI
+//! is recorded, by [`StorageService::delete`] of the old one. A crash in
+//! between leaves a stray copy rather than a row pointing at nothing.
+
+use crate::{StorageConfig, StorageError, StorageResult, StorageService};
+
+/// Prefix marking a storage URI as living in the cold store.
+pub const COLD_URI_PREFIX: &str = "cold:";
+
+/// How old an asset gets before it's moved to the cold store when
+/// `ASSET_COLD_AFTER_DAYS` is unset.
+pub const DEFAULT_COLD_AFTER_DAYS: u32 = 90;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StorageTier {
+    Hot,
+    Cold,
+}
+
+impl StorageTier {
+    pub fn of(uri: &str) -> Self {
+        if uri.starts_with(COLD_URI_PREFIX) {
+            Self::Cold
+        } else {
+            Self::Hot
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Hot => "hot",
+            Self::Cold => "cold",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TieringConfig {
+    pub cold: StorageConfig,
+    /// Assets older than this many days are moved to the cold store.
+    pub cold_after_days: u32,
+}
+
+impl TieringConfig {
+    /// `None` when `ASSET_COLD_STORAGE_BACKEND` is unset: tiering is off.
+    pub fn from_env() -> StorageResult<Option<Self>> {
+        let configured = std::env::var("ASSET_COLD_STORAGE_BACKEND")
+            .is_ok_and(|backend| !backend.trim().is_empty());
+        if !configured {
+            return Ok(None);
+        }
+        let cold = StorageConfig::from_env_prefixed("ASSET_COLD_STORAGE")?;
+        let cold_after_days = match std::env::var("ASSET_COLD_AFTER_DAYS") {
+            Ok(value) if !value.trim().is_empty() => parse_days(&value)?,
+            _ => DEFAULT_COLD_AFTER_DAYS,
+        };
+        Ok(Some(Self {
+            cold,
+            cold_after_days,
+        }))
+    }
+}
+
+fn parse_days(value: &str) -> StorageResult<u32> {
+    match value.trim().parse::<u32>() {
+        Ok(days) if days > 0 => Ok(days),
+        _ => Err(StorageError::configuration(format!(
+            "invalid ASSET_COLD_AFTER_DAYS `{value}` (expected a positive number of days)"
+        ))),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ColdTier {
+    pub(crate) operator: opendal::Operator,
+    pub(crate) config: TieringConfig,
+}
+
+impl StorageService {
+    /// Days after which assets move to the cold store; `None` when no cold
+    /// store is configured.
+    pub fn cold_after_days(&self) -> Option<u32> {
+        self.tiering
+            .as_ref()
+            .map(|tier| tier.config.cold_after_days)
+    }
+
+    /// Copy the object at `uri` into `tier` and return its URI there,
+    /// leaving the original in place. The bytes are copied as stored, so
+    /// encrypted objects stay encrypted. Copying an object to the tier it
+    /// is already in returns `uri` unchanged.
+    pub async fn copy_to_tier(&self, uri: &str, tier: StorageTier) -> StorageResult<String> {
+        if StorageTier::of(uri) == tier {
+            return Ok(uri.to_owned());
+        }
+        let target = match tier {
+            StorageTier::Hot => uri.strip_prefix(COLD_URI_PREFIX).unwrap_or(uri).to_owned(),
+            StorageTier::Cold => format!("{COLD_URI_PREFIX}{uri}"),
+        };
+
+        let bytes = self.download_raw(uri).await?;
+        let (operator, key) = self.operator_for(&target)?;
+        operator.write(key, bytes).await?;
+
+        tracing::debug!(from = uri, to = %target, "Copied object between storage tiers");
+        Ok(target)
+    }
+
+    /// The operator holding `uri` and the object's key in it.
+    pub(crate) fn operator_for<'a>(
+        &self,
+        uri: &'a str,
+    ) -> StorageResult<(&opendal::Operator, &'a str)> {
+        match uri.strip_prefix(COLD_URI_PREFIX) {
+            None => Ok((&self.operator, uri)),
+            Some(key) => match &self.tiering {
+                Some(tier) => Ok((&tier.operator, key)),
+                None => Err(StorageError::configuration(format!(
+                    "`{uri}` is in the cold tier, but ASSET_COLD_STORAGE_BACKEND is unset"
+                ))),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tier_is_read_from_the_uri() {
+        assert_eq!(StorageTier::of("user/asset.png"), StorageTier::Hot);
+        assert_eq!(StorageTier::of("cold:user/asset.png"), StorageTier::Cold);
+    }
+
+    #[test]
+    fn cold_after_days_must_be_positive() {
+        assert_eq!(parse_days(" 30 ").unwrap(), 30);
+        assert!(parse_days("0").is_err());
+        assert!(parse_days("soon").is_err());
+    }
+}
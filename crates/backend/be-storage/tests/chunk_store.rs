@@ -0,0 +1,162 @@
+//! Round-trips through [`ChunkStore`] on the filesystem backend:
+//! deduplication between overlapping recordings, streaming reassembly,
+//! and garbage collection after deletes.
+
+use be_storage::{ChunkStore, Chunking, StorageConfig, StorageService};
+use futures_util::StreamExt;
+use tempfile::TempDir;
+use uuid::Uuid;
+
+const CHUNKING: Chunking = Chunking::ContentDefined {
+    min_size: 1024,
+    avg_size: 4096,
+    max_size: 16 * 1024,
+};
+
+fn store() -> (ChunkStore, StorageService, TempDir) {
+    let root = tempfile::tempdir().expect("storage tempdir");
+    let storage = StorageService::builder()
+        .config(StorageConfig::FS {
+            root: root.path().to_string_lossy().into_owned(),
+        })
+        .build()
+        .expect("build storage");
+    let chunks = ChunkStore::builder()
+        .storage(storage.clone())
+        .chunking(CHUNKING)
+        .build()
+        .expect("build chunk store");
+    (chunks, storage, root)
+}
+
+fn recording(len: usize, seed: u64) -> Vec<u8> {
+    let mut state = seed;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn overlapping_recordings_share_chunks() {
+    let (chunks, _storage, _root) = store();
+    let user = Uuid::now_v7();
+    let first = recording(500_000, 7);
+    // Same capture with a new segment at the front.
+    let mut second = recording(20_000, 8);
+    second.extend_from_slice(&first);
+
+    let a = chunks
+        .put_recording(&user, &Uuid::now_v7(), &first, "video/mp4")
+        .await
+        .unwrap();
+    assert_eq!(a.new_chunks, a.manifest.chunks.len());
+
+    let b = chunks
+        .put_recording(&user, &Uuid::now_v7(), &second, "video/mp4")
+        .await
+        .unwrap();
+    assert!(
+        b.new_bytes < 60_000,
+        "second upload wrote {} new bytes",
+        b.new_bytes
+    );
+
+    // A different user never reuses another user's chunks.
+    let c = chunks
+        .put_recording(&Uuid::now_v7(), &Uuid::now_v7(), &first, "video/mp4")
+        .await
+        .unwrap();
+    assert_eq!(c.new_chunks, c.manifest.chunks.len());
+}
+
+#[tokio::test]
+async fn streams_recording_back_in_order() {
+    let (chunks, _storage, _root) = store();
+    let user = Uuid::now_v7();
+    let recording_id = Uuid::now_v7();
+    let content = recording(300_000, 11);
+
+    chunks
+        .put_recording(&user, &recording_id, &content, "video/webm")
+        .await
+        .unwrap();
+
+    let mut stream = std::pin::pin!(chunks.read_recording(&user, &recording_id).await.unwrap());
+    let mut reassembled = Vec::new();
+    let mut pieces = 0;
+    while let Some(piece) = stream.next().await {
+        reassembled.extend_from_slice(&piece.unwrap());
+        pieces += 1;
+    }
+    assert!(pieces > 1);
+    assert_eq!(reassembled, content);
+
+    let manifest = chunks.manifest(&user, &recording_id).await.unwrap();
+    assert_eq!(manifest.size, content.len() as u64);
+    assert_eq!(manifest.mime_type, "video/webm");
+
+    let missing = chunks.manifest(&user, &Uuid::now_v7()).await.unwrap_err();
+    assert!(missing.is_not_found());
+}
+
+#[tokio::test]
+async fn corrupted_chunk_fails_the_read() {
+    let (chunks, storage, _root) = store();
+    let user = Uuid::now_v7();
+    let recording_id = Uuid::now_v7();
+    let stored = chunks
+        .put_recording(&user, &recording_id, &recording(50_000, 3), "video/mp4")
+        .await
+        .unwrap();
+
+    let victim = ChunkStore::chunk_path(&user, &stored.manifest.chunks[0].hash);
+    storage
+        .write(&victim, b"not the original bytes")
+        .await
+        .unwrap();
+
+    assert!(
+        chunks
+            .download_recording(&user, &recording_id)
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn garbage_collection_keeps_only_referenced_chunks() {
+    let (chunks, _storage, _root) = store();
+    let user = Uuid::now_v7();
+    let shared = recording(200_000, 21);
+    let mut extended = shared.clone();
+    extended.extend_from_slice(&recording(100_000, 22));
+
+    let keep = Uuid::now_v7();
+    let drop = Uuid::now_v7();
+    chunks
+        .put_recording(&user, &keep, &shared, "video/mp4")
+        .await
+        .unwrap();
+    let dropped = chunks
+        .put_recording(&user, &drop, &extended, "video/mp4")
+        .await
+        .unwrap();
+
+    let report = chunks.collect_garbage(&user).await.unwrap();
+    assert_eq!(report.chunks_deleted, 0);
+
+    chunks.delete_recording(&user, &drop).await.unwrap();
+    let report = chunks.collect_garbage(&user).await.unwrap();
+    assert_eq!(report.chunks_deleted, dropped.new_chunks);
+    assert!(report.chunks_retained > 0);
+
+    assert_eq!(
+        chunks.download_recording(&user, &keep).await.unwrap(),
+        shared
+    );
+}
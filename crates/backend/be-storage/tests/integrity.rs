@@ -0,0 +1,40 @@
+//! Checksum verification on download, on the filesystem backend.
+
+use be_storage::{StorageConfig, StorageService};
+use uuid::Uuid;
+
+#[tokio::test]
+async fn verified_download_catches_a_corrupted_object() {
+    let root = tempfile::tempdir().unwrap();
+    let storage = StorageService::builder()
+        .config(StorageConfig::FS {
+            root: root.path().to_string_lossy().into_owned(),
+        })
+        .build()
+        .unwrap();
+
+    let content = b"the original bytes".to_vec();
+    let checksum = StorageService::calculate_sha256(&content);
+    let path = storage
+        .upload(&Uuid::now_v7(), &Uuid::now_v7(), &content, "text/plain")
+        .await
+        .unwrap();
+    assert_eq!(
+        storage.download_verified(&path, &checksum).await.unwrap(),
+        content
+    );
+
+    std::fs::write(root.path().join(&path), b"the 0riginal bytes").unwrap();
+    let err = storage
+        .download_verified(&path, &checksum)
+        .await
+        .unwrap_err();
+    assert!(err.is_integrity_mismatch(), "{err}");
+    assert!(err.to_string().contains(&hex::encode(&checksum)));
+
+    let err = storage
+        .download_verified("nobody/missing.txt", &checksum)
+        .await
+        .unwrap_err();
+    assert!(err.is_not_found());
+}
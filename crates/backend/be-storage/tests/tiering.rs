@@ -0,0 +1,61 @@
+//! Moving objects between the hot and cold stores, both on the
+//! filesystem backend.
+
+use be_storage::{StorageConfig, StorageService, StorageTier, TieringConfig};
+use tempfile::TempDir;
+use uuid::Uuid;
+
+fn fs(root: &TempDir) -> StorageConfig {
+    StorageConfig::FS {
+        root: root.path().to_string_lossy().into_owned(),
+    }
+}
+
+#[tokio::test]
+async fn objects_round_trip_through_the_cold_tier() {
+    let (hot_root, cold_root) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+    let storage = StorageService::builder()
+        .config(fs(&hot_root))
+        .tiering(TieringConfig {
+            cold: fs(&cold_root),
+            cold_after_days: 30,
+        })
+        .build()
+        .unwrap();
+    assert_eq!(storage.cold_after_days(), Some(30));
+
+    let hot = storage
+        .upload(
+            &Uuid::now_v7(),
+            &Uuid::now_v7(),
+            b"old screenshot",
+            "image/png",
+        )
+        .await
+        .unwrap();
+    let cold = storage.copy_to_tier(&hot, StorageTier::Cold).await.unwrap();
+    assert_eq!(StorageTier::of(&cold), StorageTier::Cold);
+    storage.delete(&hot).await.unwrap();
+
+    assert!(!storage.exists(&hot).await.unwrap());
+    assert_eq!(storage.download(&cold).await.unwrap(), b"old screenshot");
+    let range = storage.download_range(&cold, 4, Some(10)).await.unwrap();
+    assert_eq!(range.bytes, b"screenshot");
+
+    let restored = storage.copy_to_tier(&cold, StorageTier::Hot).await.unwrap();
+    assert_eq!(restored, hot);
+    assert_eq!(
+        storage.download(&restored).await.unwrap(),
+        b"old screenshot"
+    );
+}
+
+#[tokio::test]
+async fn cold_uris_fail_without_a_cold_store() {
+    let root = tempfile::tempdir().unwrap();
+    let storage = StorageService::builder().config(fs(&root)).build().unwrap();
+    assert_eq!(storage.cold_after_days(), None);
+
+    let err = storage.download("cold:user/asset.png").await.unwrap_err();
+    assert!(err.is_configuration());
+}
@@ -0,0 +1,143 @@
+//! Chat turns still running, keyed by the `request_id` the client put on
+//! its `Send`/`Regenerate` frame.
+//!
+//! The socket that opened a turn can always cancel it in-band. The
+//! registry is for everything else: another window of the same client, or
+//! a client whose socket is wedged, calls
+//! `POST /threads/{thread_id}/chat/{request_id}/cancel` and the turn stops
+//! exactly as if its own socket had sent `Cancel`. The provider stream is
+//! dropped, nothing past the cut is billed, and the partial answer is kept
+//! with `cancelled: true`.
+//!
+//! Entries live as long as the [`ActiveTurnGuard`] the chat handler holds
+//! for the socket, so a finished turn can no longer be found.
+
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::sync::{Arc, Mutex};
+
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+struct ActiveTurn {
+    user_id: Uuid,
+    thread_id: Uuid,
+    cancel: CancellationToken,
+}
+
+/// Running turns by request id. Shared through [`crate::AppState`].
+#[derive(Default)]
+pub(crate) struct ActiveTurns {
+    turns: Mutex<HashMap<Uuid, ActiveTurn>>,
+}
+
+impl ActiveTurns {
+    /// Track a turn until the returned guard drops. `None` when a running
+    /// turn already holds `request_id`.
+    pub(crate) fn register(
+        self: &Arc<Self>,
+        request_id: Uuid,
+        user_id: Uuid,
+        thread_id: Uuid,
+        cancel: CancellationToken,
+    ) -> Option<ActiveTurnGuard> {
+        let mut turns = self.turns.lock().unwrap_or_else(|e| e.into_inner());
+        match turns.entry(request_id) {
+            Entry::Occupied(_) => None,
+            Entry::Vacant(slot) => {
+                slot.insert(ActiveTurn {
+                    user_id,
+                    thread_id,
+                    cancel,
+                });
+                Some(ActiveTurnGuard {
+                    turns: Arc::clone(self),
+                    request_id,
+                })
+            }
+        }
+    }
+
+    /// Cancel the turn if it is running on `thread_id` for `user_id`.
+    /// Another user's turn is reported the same as no turn at all.
+    pub(crate) fn cancel(&self, request_id: Uuid, user_id: Uuid, thread_id: Uuid) -> bool {
+        let turns = self.turns.lock().unwrap_or_else(|e| e.into_inner());
+        match turns.get(&request_id) {
+            Some(turn) if turn.user_id == user_id && turn.thread_id == thread_id => {
+                turn.cancel.cancel();
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Removes its turn from [`ActiveTurns`] on drop.
+pub(crate) struct ActiveTurnGuard {
+    turns: Arc<ActiveTurns>,
+    request_id: Uuid,
+}
+
+impl Drop for ActiveTurnGuard {
+    fn drop(&mut self) {
+        self.turns
+            .turns
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.request_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancel_trips_the_owners_token() {
+        let turns = Arc::new(ActiveTurns::default());
+        let (request_id, user_id, thread_id) = (Uuid::now_v7(), Uuid::now_v7(), Uuid::now_v7());
+        let cancel = CancellationToken::new();
+        let _guard = turns
+            .register(request_id, user_id, thread_id, cancel.clone())
+            .unwrap();
+
+        assert!(turns.cancel(request_id, user_id, thread_id));
+        assert!(cancel.is_cancelled());
+    }
+
+    #[test]
+    fn other_users_and_threads_cannot_cancel() {
+        let turns = Arc::new(ActiveTurns::default());
+        let (request_id, user_id, thread_id) = (Uuid::now_v7(), Uuid::now_v7(), Uuid::now_v7());
+        let cancel = CancellationToken::new();
+        let _guard = turns
+            .register(request_id, user_id, thread_id, cancel.clone())
+            .unwrap();
+
+        assert!(!turns.cancel(request_id, Uuid::now_v7(), thread_id));
+        assert!(!turns.cancel(request_id, user_id, Uuid::now_v7()));
+        assert!(!cancel.is_cancelled());
+    }
+
+    #[test]
+    fn request_id_is_free_again_once_the_guard_drops() {
+        let turns = Arc::new(ActiveTurns::default());
+        let (request_id, user_id, thread_id) = (Uuid::now_v7(), Uuid::now_v7(), Uuid::now_v7());
+        let guard = turns
+            .register(request_id, user_id, thread_id, CancellationToken::new())
+            .unwrap();
+        assert!(
+            turns
+                .register(request_id, user_id, thread_id, CancellationToken::new())
+                .is_none()
+        );
+
+        drop(guard);
+        assert!(!turns.cancel(request_id, user_id, thread_id));
+        assert!(
+            turns
+                .register(request_id, user_id, thread_id, CancellationToken::new())
+                .is_some()
+        );
+    }
+}
@@ -3,13 +3,14 @@
 //! exhausts the tool-call budget.
 
 use std::sync::Arc;
+use std::time::Instant;
 
 use agent_chain::{
-    AIMessage, AnyMessage, BaseChatModel, SystemMessage,
+    AIMessage, AnyMessage, BaseChatModel, BaseLanguageModel, SystemMessage,
     language_models::{ToolChoice, ToolLike},
     messages::{
-        AIMessageChunk, ContentBlock, ContentBlocks, TextContentBlock, ToolCall, ToolMessage,
-        ToolStatus,
+        AIMessageChunk, Annotation, ContentBlock, ContentBlocks, TextContentBlock, ToolCall,
+        ToolMessage, ToolStatus,
     },
 };
 use be_remote_db::{DatabaseManager, MessageType};
@@ -18,12 +19,16 @@ use thread_core::{ChatServerMessage, MessageNode, ToolErrorWire};
 use tokio::sync::mpsc;
 use tokio_stream::StreamExt;
 use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
 use uuid::Uuid;
 
+use crate::citations::SourceIndex;
 use crate::conversion::convert_db_message_to_base_message;
 use crate::glm_xml_tool_calls;
 use crate::remote_tool_bus::RemoteToolBus;
+use crate::title::GeneratedTitle;
 use crate::tool_catalog::{TurnCatalog, TurnEntry};
+use crate::untrusted;
 
 /// Appended on the forced-synthesis turn that fires when the tool-call
 /// budget runs out. The model has actually called tools and gathered
@@ -280,6 +285,19 @@ fn truncate_tool_message_if_needed(msg: &mut AnyMessage, max_bytes: usize) -> Op
     Some(original_len)
 }
 
+/// Usage totals snapshotted by [`ChatAccumulator::mark`].
+struct UsageMark {
+    input_tokens: i64,
+    output_tokens: i64,
+    reasoning_len: usize,
+}
+
+/// Output tokens for `len` bytes of streamed text, by the
+/// [`estimate_message_tokens`] heuristic.
+fn estimate_text_tokens(len: usize) -> i64 {
+    len.div_ceil(4) as i64
+}
+
 /// Running totals of an AI response as it is streamed across one or more LLM rounds.
 #[derive(Default)]
 struct ChatAccumulator {
@@ -314,6 +332,34 @@ impl ChatAccumulator {
         }
     }
 
+    /// Where the running totals stand before a round starts, so
+    /// [`Self::charge_cut_round`] can tell whether the round reported
+    /// its own usage.
+    fn mark(&self) -> UsageMark {
+        UsageMark {
+            input_tokens: self.input_tokens,
+            output_tokens: self.output_tokens,
+            reasoning_len: self.reasoning.len(),
+        }
+    }
+
+    /// Charge a round the user cut off before the provider reported its
+    /// usage, which it only sends on the final chunk. Input is the
+    /// request estimate the trimmer already made; output is estimated
+    /// from the text and reasoning streamed up to the cut, with the same
+    /// four-characters-a-token heuristic. The provider stream is dropped
+    /// at the cut, so nothing after it is charged. A no-op when the round
+    /// did report usage.
+    fn charge_cut_round(&mut self, mark: &UsageMark, input_estimate: usize, streamed: &str) {
+        if self.input_tokens != mark.input_tokens || self.output_tokens != mark.output_tokens {
+            return;
+        }
+        let reasoning_tokens = estimate_text_tokens(self.reasoning.len() - mark.reasoning_len);
+        self.input_tokens += input_estimate as i64;
+        self.output_tokens += estimate_text_tokens(streamed.len()) + reasoning_tokens;
+        self.reasoning_tokens += reasoning_tokens;
+    }
+
     fn push_content(&mut self, text: &str) {
         self.content.push_str(text);
     }
@@ -342,13 +388,18 @@ impl ChatAccumulator {
         !self.content.is_empty() || !self.reasoning.is_empty()
     }
 
-    fn to_content_value(&self) -> Value {
+    /// `citations` are attached to the text block; see [`crate::citations`].
+    fn to_content_value(&self, citations: &[Annotation]) -> Value {
         let mut blocks = Vec::new();
         if !self.reasoning.is_empty() {
             blocks.push(serde_json::json!({"type": "reasoning", "reasoning": self.reasoning}));
         }
         if !self.content.is_empty() {
-            blocks.push(serde_json::json!({"type": "text", "text": self.content}));
+            let mut text = serde_json::json!({"type": "text", "text": self.content});
+            if !citations.is_empty() {
+                text["annotations"] = serde_json::json!(citations);
+            }
+            blocks.push(text);
         }
         Value::Array(blocks)
     }
@@ -387,12 +438,63 @@ fn text_content_blocks(text: String) -> ContentBlocks {
     }
 }
 
+/// Run one provider round inside an `llm.call` span so a slow turn can
+/// be read off the trace: which model answered, how many tokens moved,
+/// and how long the provider took end to end.
 async fn run_round(
     chat_model: &(dyn BaseChatModel + Send + Sync),
     messages: &[AnyMessage],
     tx: &mpsc::Sender<ChatServerMessage>,
     token: &CancellationToken,
     acc: &mut ChatAccumulator,
+) -> Result<RoundResult, String> {
+    let span = tracing::info_span!(
+        "llm.call",
+        otel.kind = "client",
+        gen_ai.system = chat_model.llm_type(),
+        gen_ai.request.model = chat_model.model_name(),
+        gen_ai.usage.input_tokens = tracing::field::Empty,
+        gen_ai.usage.output_tokens = tracing::field::Empty,
+        gen_ai.response.finish_reason = tracing::field::Empty,
+        llm.latency_ms = tracing::field::Empty,
+        llm.cancelled = tracing::field::Empty,
+        otel.status_code = tracing::field::Empty,
+    );
+    // The accumulator is cumulative across rounds; diff it so each span
+    // carries only its own round's usage.
+    let (input_before, output_before) = (acc.input_tokens, acc.output_tokens);
+    let started = Instant::now();
+
+    let result = stream_round(chat_model, messages, tx, token, acc)
+        .instrument(span.clone())
+        .await;
+
+    span.record("llm.latency_ms", started.elapsed().as_millis() as u64);
+    span.record("gen_ai.usage.input_tokens", acc.input_tokens - input_before);
+    span.record(
+        "gen_ai.usage.output_tokens",
+        acc.output_tokens - output_before,
+    );
+    match &result {
+        Ok(round) => {
+            span.record("llm.cancelled", round.cancelled);
+            if let Some(reason) = round.finish_reason.as_deref() {
+                span.record("gen_ai.response.finish_reason", reason);
+            }
+        }
+        Err(_) => {
+            span.record("otel.status_code", "ERROR");
+        }
+    }
+    result
+}
+
+async fn stream_round(
+    chat_model: &(dyn BaseChatModel + Send + Sync),
+    messages: &[AnyMessage],
+    tx: &mpsc::Sender<ChatServerMessage>,
+    token: &CancellationToken,
+    acc: &mut ChatAccumulator,
 ) -> Result<RoundResult, String> {
     // `BaseChatModel::stream` takes ownership of its message vec, so we must
     // clone here. The clone is bounded by the chat history length and the
@@ -445,6 +547,7 @@ async fn run_round(
     };
 
     tokio::pin!(provider_stream);
+    let mark = acc.mark();
     let mut round_content = String::new();
     let mut tool_calls: Vec<ToolCall> = Vec::new();
     // Suppress GLM `<tool_call>` envelopes from the *streamed* content so
@@ -463,6 +566,7 @@ async fn run_round(
             chunk = provider_stream.next() => chunk,
             () = token.cancelled() => {
                 tracing::info!("Chat stream cancelled during provider streaming");
+                acc.charge_cut_round(&mark, trim_result.estimated_tokens_after, &round_content);
                 acc.push_content(&round_content);
                 return Ok(RoundResult {
                     content: round_content,
@@ -514,6 +618,7 @@ async fn run_round(
             .is_err()
         {
             tracing::info!("Chat stream receiver dropped, client disconnected");
+            acc.charge_cut_round(&mark, trim_result.estimated_tokens_after, &round_content);
             acc.push_content(&round_content);
             return Ok(RoundResult {
                 content: round_content,
@@ -631,7 +736,9 @@ where
                 "Tool result exceeded byte cap; truncated to fit context window"
             );
         }
-        results.push(result_msg);
+        // Tool results carry outside content (pages, search results,
+        // files), so they reach the model delimited as untrusted.
+        results.push(untrusted::guard_tool_message(result_msg, &tool_name));
     }
 
     ToolExecOutcome::Completed(results)
@@ -918,13 +1025,21 @@ fn try_lift_glm_xml(
     true
 }
 
+/// A partial answer from a cancelled turn is saved with `cancelled: true`
+/// in its `additional_kwargs`, so clients can show it as interrupted. Its
+/// usage is what the provider reported for finished rounds plus an
+/// estimate for the round that was cut (see
+/// [`ChatAccumulator::charge_cut_round`]).
 async fn save_accumulated_message(
     db: &DatabaseManager,
     thread_id: Uuid,
     user_id: Uuid,
     acc: &ChatAccumulator,
+    sources: &SourceIndex,
+    cancelled: bool,
 ) -> Option<be_remote_db::Message> {
-    let content_value = acc.to_content_value();
+    let citations = sources.annotate(&acc.content);
+    let content_value = acc.to_content_value(&citations);
 
     let ai_message = match db
         .create_message()
@@ -932,6 +1047,7 @@ async fn save_accumulated_message(
         .user_id(user_id)
         .message_type(MessageType::Ai)
         .content(content_value)
+        .maybe_additional_kwargs(cancelled.then(|| serde_json::json!({ "cancelled": true })))
         .call()
         .await
     {
@@ -1000,11 +1116,15 @@ async fn save_turn_result(
     user_id: Uuid,
     human_message_id: Uuid,
     acc: &ChatAccumulator,
+    sources: &SourceIndex,
+    cancelled: bool,
 ) -> Result<Option<Box<MessageNode>>, String> {
     if !acc.has_content() {
         return Ok(None);
     }
-    let Some(ai_message) = save_accumulated_message(db, thread_id, user_id, acc).await else {
+    let Some(ai_message) =
+        save_accumulated_message(db, thread_id, user_id, acc, sources, cancelled).await
+    else {
         return Err("Failed to save AI message".to_string());
     };
     match convert_db_message_to_base_message(ai_message) {
@@ -1036,6 +1156,7 @@ async fn drive_turn<B>(
     catalog: &TurnCatalog,
     remote_bus: &B,
     mut messages: Vec<AnyMessage>,
+    sources: &SourceIndex,
     thread_id: Uuid,
     user_id: Uuid,
     human_message_id: Uuid,
@@ -1220,7 +1341,17 @@ where
         }
     }
 
-    match save_turn_result(db, thread_id, user_id, human_message_id, &acc).await {
+    match save_turn_result(
+        db,
+        thread_id,
+        user_id,
+        human_message_id,
+        &acc,
+        sources,
+        cancelled,
+    )
+    .await
+    {
         Ok(_) if cancelled => AgentTurnOutcome::Cancelled,
         Ok(node) => AgentTurnOutcome::Completed { ai_node: node },
         Err(_) if cancelled => {
@@ -1258,6 +1389,8 @@ where
 /// whose [`TurnEntry`] is `Remote`. The bus is taken as a concrete
 /// `Arc<B>` so the agent loop can be exercised with stub buses in
 /// tests; production callers pass [`crate::remote_tool_bus::ChatRemoteBus`].
+/// `sources` are the passages tagged in `messages`, which the saved answer's
+/// citations are resolved against.
 #[bon::builder]
 pub async fn run_agent_loop<B>(
     title_model: Arc<dyn BaseChatModel + Send + Sync>,
@@ -1268,6 +1401,7 @@ pub async fn run_agent_loop<B>(
     catalog: Arc<TurnCatalog>,
     remote_bus: Arc<B>,
     messages: Vec<AnyMessage>,
+    sources: SourceIndex,
     thread_id: Uuid,
     user_id: Uuid,
     human_message_id: Uuid,
@@ -1283,6 +1417,7 @@ pub async fn run_agent_loop<B>(
         catalog.as_ref(),
         remote_bus.as_ref(),
         messages,
+        &sources,
         thread_id,
         user_id,
         human_message_id,
@@ -1301,11 +1436,14 @@ pub async fn run_agent_loop<B>(
         title_model.as_ref(),
         thread_id,
         user_id,
+        false,
     )
     .await
     {
-        Ok(Some(title)) => {
-            let _ = tx.send(ChatServerMessage::TitleUpdated { title }).await;
+        Ok(Some(GeneratedTitle { title, tags })) => {
+            let _ = tx
+                .send(ChatServerMessage::TitleUpdated { title, tags })
+                .await;
         }
         Ok(None) => {}
         Err(e) => {
@@ -1773,6 +1911,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn cut_round_without_reported_usage_is_charged_an_estimate() {
+        let mut acc = ChatAccumulator::default();
+        let mark = acc.mark();
+        acc.reasoning.push_str("thinking");
+        acc.charge_cut_round(&mark, 120, "partial answer");
+        assert_eq!(acc.input_tokens, 120);
+        // 14 bytes of answer and 8 of reasoning, four to a token.
+        assert_eq!(acc.output_tokens, 4 + 2);
+        assert_eq!(acc.reasoning_tokens, 2);
+    }
+
+    #[test]
+    fn cut_round_that_reported_usage_is_not_charged_again() {
+        let mut acc = ChatAccumulator::default();
+        let mark = acc.mark();
+        acc.input_tokens = 50;
+        acc.output_tokens = 7;
+        acc.charge_cut_round(&mark, 120, "partial answer");
+        assert_eq!((acc.input_tokens, acc.output_tokens), (50, 7));
+    }
+
     /// End-to-end propagation tests for [`run_round`]: when a chat model
     /// yields chunks that carry `finish_reason` in `response_metadata`
     /// (the OpenAI-compatible client's contract — see
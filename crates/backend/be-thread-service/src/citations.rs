@@ -0,0 +1,495 @@
+//! Citations from answers back to the source text they rest on.
+//!
+//! When a turn's context carries source text (an article the user is
+//! reading, a PDF, an attached document, a transcript), [`index_sources`]
+//! splits each source into passages, tags every passage with an id
+//! (`[c1]`, `[c2]`, …) in the text the model sees, and adds a system
+//! message asking the model to cite those ids after the statements they
+//! support.
+//!
+//! Once the answer is in, [`SourceIndex::annotate`] turns each marker in
+//! it into an [`Annotation::Citation`] on the saved text block: the span of
+//! the marker in the answer, the passage's text, the source's title and
+//! asset id, and where the passage sits in the source (`paragraph`, or
+//! `start_seconds` for transcripts). Clients render markers as links and
+//! highlight the passage from that.
+//!
+//! Passage ids are only meaningful within one turn, so markers in earlier
+//! answers are removed before the history goes back to the model.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use agent_chain::messages::{Annotation, ContentBlock};
+use agent_chain::{AnyMessage, SystemMessage};
+use serde_json::Value;
+
+/// Passages are built from whole paragraphs (or transcript lines) up to
+/// about this many bytes, so a citation points at something a reader can
+/// take in at a glance.
+const PASSAGE_TARGET_LEN: usize = 800;
+/// Passages tagged in one turn. Sources past this are left untagged.
+const MAX_PASSAGES: usize = 500;
+/// Longest text between brackets considered as a marker.
+const MARKER_MAX_LEN: usize = 64;
+
+const CITATION_INSTRUCTIONS: &str = "Source text in this conversation is split into passages, \
+each starting with an id in square brackets such as [c1]. When a statement in your answer is \
+based on a source, put the id of the passage that supports it right after the statement, like \
+this [c3], or [c2, c5] for several. Cite only ids that appear in the sources, don't cite \
+statements that come from general knowledge, and don't otherwise mention the ids.";
+
+/// One tagged passage and the source it came from.
+#[derive(Debug)]
+struct SourceChunk {
+    title: Option<String>,
+    file_id: Option<String>,
+    passage: Passage,
+}
+
+#[derive(Debug, PartialEq)]
+struct Passage {
+    /// 1-based paragraph the passage starts at, for prose.
+    paragraph: Option<usize>,
+    /// Where the passage starts, for transcripts.
+    start_seconds: Option<u32>,
+    text: String,
+}
+
+/// The passages tagged for one turn, by id (`c1` is the first).
+#[derive(Debug, Default)]
+pub struct SourceIndex {
+    chunks: Vec<SourceChunk>,
+}
+
+impl SourceIndex {
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// One citation per passage id cited in `answer`. `start_index` and
+    /// `end_index` are the char offsets of the marker. Ids the model made
+    /// up are skipped.
+    pub fn annotate(&self, answer: &str) -> Vec<Annotation> {
+        let mut annotations = Vec::new();
+        for marker in find_markers(answer) {
+            let start = answer[..marker.start].chars().count();
+            let end = start + answer[marker.start..marker.end].chars().count();
+            for number in marker.ids {
+                let Some(chunk) = number.checked_sub(1).and_then(|i| self.chunks.get(i)) else {
+                    continue;
+                };
+                annotations.push(chunk.citation(number, start, end));
+            }
+        }
+        annotations
+    }
+}
+
+impl SourceChunk {
+    fn citation(&self, number: usize, start: usize, end: usize) -> Annotation {
+        let mut extras = HashMap::new();
+        if let Some(file_id) = &self.file_id {
+            extras.insert("file_id".to_string(), Value::from(file_id.clone()));
+        }
+        if let Some(paragraph) = self.passage.paragraph {
+            extras.insert("paragraph".to_string(), Value::from(paragraph));
+        }
+        if let Some(seconds) = self.passage.start_seconds {
+            extras.insert("start_seconds".to_string(), Value::from(seconds));
+        }
+        Annotation::Citation {
+            id: Some(format!("c{number}")),
+            url: None,
+            title: self.title.clone(),
+            start_index: Some(start as i64),
+            end_index: Some(end as i64),
+            cited_text: Some(self.passage.text.clone()),
+            extras: (!extras.is_empty()).then_some(extras),
+        }
+    }
+}
+
+/// Tag the source text in `messages` with passage ids and, if there was
+/// any, prepend the instructions for citing them. Also removes the
+/// markers from earlier answers. Call once the source blocks have been
+/// downloaded; blocks still without text are left alone.
+pub fn index_sources(messages: &mut Vec<AnyMessage>) -> SourceIndex {
+    strip_past_citations(messages);
+
+    let mut index = SourceIndex::default();
+    for message in messages.iter_mut() {
+        let blocks: &mut [ContentBlock] = match message {
+            AnyMessage::HumanMessage(m) => &mut m.content,
+            AnyMessage::SystemMessage(m) => &mut m.content,
+            _ => continue,
+        };
+        for block in blocks {
+            let ContentBlock::PlainText(plain) = block else {
+                continue;
+            };
+            if !plain.mime_type.starts_with("text/") {
+                continue;
+            }
+            let Some(text) = plain.text.as_deref() else {
+                continue;
+            };
+            let passages = passages(text);
+            if passages.is_empty() || index.chunks.len() + passages.len() > MAX_PASSAGES {
+                continue;
+            }
+            let mut tagged = String::new();
+            for passage in passages {
+                if !tagged.is_empty() {
+                    tagged.push_str("\n\n");
+                }
+                let _ = write!(tagged, "[c{}] {}", index.chunks.len() + 1, passage.text);
+                index.chunks.push(SourceChunk {
+                    title: plain.title.clone(),
+                    file_id: plain.file_id.clone(),
+                    passage,
+                });
+            }
+            plain.text = Some(tagged);
+        }
+    }
+
+    if !index.is_empty() {
+        messages.insert(
+            0,
+            SystemMessage::builder()
+                .content(CITATION_INSTRUCTIONS)
+                .build()
+                .into(),
+        );
+    }
+    index
+}
+
+/// Remove the markers, and the citations behind them, from earlier
+/// answers. Their ids belonged to another turn's numbering.
+fn strip_past_citations(messages: &mut [AnyMessage]) {
+    for message in messages {
+        let AnyMessage::AIMessage(ai) = message else {
+            continue;
+        };
+        for block in ai.content.iter_mut() {
+            let ContentBlock::Text(text) = block else {
+                continue;
+            };
+            let cited = text.annotations.as_ref().is_some_and(|annotations| {
+                annotations
+                    .iter()
+                    .any(|a| matches!(a, Annotation::Citation { .. }))
+            });
+            if cited {
+                text.annotations = None;
+                text.text = strip_markers(&text.text);
+            }
+        }
+    }
+}
+
+fn strip_markers(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut last = 0;
+    for marker in find_markers(text) {
+        stripped.push_str(text[last..marker.start].trim_end_matches(' '));
+        last = marker.end;
+    }
+    stripped.push_str(&text[last..]);
+    stripped
+}
+
+/// A `[c1]` or `[c1, c2]` marker: its byte span and the ids it cites.
+struct Marker {
+    start: usize,
+    end: usize,
+    ids: Vec<usize>,
+}
+
+fn find_markers(text: &str) -> Vec<Marker> {
+    let mut markers = Vec::new();
+    let mut from = 0;
+    while let Some(offset) = text[from..].find('[') {
+        let start = from + offset;
+        from = start + 1;
+        let Some(len) = text[from..].find(']') else {
+            break;
+        };
+        let Some(ids) = parse_marker_ids(&text[from..from + len]) else {
+            continue;
+        };
+        from += len + 1;
+        markers.push(Marker {
+            start,
+            end: from,
+            ids,
+        });
+    }
+    markers
+}
+
+fn parse_marker_ids(inner: &str) -> Option<Vec<usize>> {
+    if inner.len() > MARKER_MAX_LEN {
+        return None;
+    }
+    inner
+        .split(',')
+        .map(|id| id.trim().strip_prefix('c')?.parse().ok())
+        .collect()
+}
+
+/// Split a source into passages: runs of transcript lines when most lines
+/// start with a timestamp, runs of paragraphs otherwise.
+fn passages(text: &str) -> Vec<Passage> {
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    let stamped = lines
+        .iter()
+        .filter(|line| leading_timestamp(line).is_some())
+        .count();
+
+    let units = if lines.len() >= 2 && stamped * 5 >= lines.len() * 4 {
+        lines
+            .into_iter()
+            .map(|line| Passage {
+                paragraph: None,
+                start_seconds: leading_timestamp(line),
+                text: line.to_string(),
+            })
+            .collect()
+    } else {
+        paragraphs(text)
+            .into_iter()
+            .enumerate()
+            .map(|(i, text)| Passage {
+                paragraph: Some(i + 1),
+                start_seconds: None,
+                text,
+            })
+            .collect()
+    };
+    group(units)
+}
+
+fn paragraphs(text: &str) -> Vec<String> {
+    let mut paragraphs = Vec::new();
+    let mut current = String::new();
+    for line in text.lines() {
+        let line = line.trim_end();
+        if line.trim().is_empty() {
+            if !current.is_empty() {
+                paragraphs.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        current.push_str(line);
+    }
+    if !current.is_empty() {
+        paragraphs.push(current);
+    }
+    paragraphs
+}
+
+/// Merge consecutive units into passages of up to [`PASSAGE_TARGET_LEN`].
+/// A unit is never split; one longer than the target is a passage of its
+/// own. A passage keeps the position of its first unit.
+fn group(units: Vec<Passage>) -> Vec<Passage> {
+    let mut grouped: Vec<Passage> = Vec::new();
+    for unit in units {
+        match grouped.last_mut() {
+            Some(last) if last.text.len() + 2 + unit.text.len() <= PASSAGE_TARGET_LEN => {
+                let separator = if unit.paragraph.is_some() {
+                    "\n\n"
+                } else {
+                    "\n"
+                };
+                last.text.push_str(separator);
+                last.text.push_str(&unit.text);
+            }
+            _ => grouped.push(unit),
+        }
+    }
+    grouped
+}
+
+/// Seconds from a timestamp at the start of a transcript line: `1:23`,
+/// `01:02:03`, optionally in brackets or parentheses.
+fn leading_timestamp(line: &str) -> Option<u32> {
+    let line = line.strip_prefix(['[', '(']).unwrap_or(line);
+    let end = line
+        .find(|c: char| !(c.is_ascii_digit() || c == ':'))
+        .unwrap_or(line.len());
+    let parts: Vec<&str> = line[..end].split(':').collect();
+    if !(2..=3).contains(&parts.len()) {
+        return None;
+    }
+    let mut seconds: u32 = 0;
+    for (i, part) in parts.iter().enumerate() {
+        let value: u32 = part.parse().ok()?;
+        if i > 0 && (part.len() != 2 || value >= 60) {
+            return None;
+        }
+        seconds = seconds.checked_mul(60)?.checked_add(value)?;
+    }
+    Some(seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use agent_chain::messages::{PlainTextContentBlock, TextContentBlock};
+    use agent_chain::{AIMessage, HumanMessage};
+
+    fn source(text: &str) -> ContentBlock {
+        ContentBlock::PlainText(
+            PlainTextContentBlock::builder()
+                .mime_type("text/markdown".to_string())
+                .text(text.to_string())
+                .title("Guide".to_string())
+                .file_id("asset-1".to_string())
+                .build(),
+        )
+    }
+
+    fn source_text(messages: &[AnyMessage]) -> String {
+        messages
+            .iter()
+            .find_map(|m| match m {
+                AnyMessage::HumanMessage(h) => h.content.iter().find_map(|b| match b {
+                    ContentBlock::PlainText(p) => p.text.clone(),
+                    _ => None,
+                }),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn sources_are_tagged_and_instructions_prepended() {
+        let long = "word ".repeat(200);
+        let text = format!("# Title\n\nShort intro.\n\n{long}");
+        let mut messages: Vec<AnyMessage> = vec![
+            HumanMessage::builder()
+                .content(vec![source(&text)])
+                .build()
+                .into(),
+        ];
+
+        let index = index_sources(&mut messages);
+
+        assert_eq!(index.chunks.len(), 2);
+        assert!(matches!(messages[0], AnyMessage::SystemMessage(_)));
+        let tagged = source_text(&messages);
+        assert!(tagged.starts_with("[c1] # Title\n\nShort intro.\n\n[c2] word"));
+        assert_eq!(index.chunks[1].passage.paragraph, Some(3));
+    }
+
+    #[test]
+    fn no_sources_means_no_instructions() {
+        let mut messages: Vec<AnyMessage> =
+            vec![HumanMessage::builder().content("hello").build().into()];
+        assert!(index_sources(&mut messages).is_empty());
+        assert_eq!(messages.len(), 1);
+    }
+
+    #[test]
+    fn transcripts_are_split_by_line_with_timestamps() {
+        let line = "words ".repeat(30);
+        let text = (0..10)
+            .map(|i| format!("[0:{:02}] {line}", i * 5))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let passages = passages(&text);
+        assert!(passages.len() > 1);
+        assert_eq!(passages[0].start_seconds, Some(0));
+        assert!(passages[1].start_seconds.unwrap() > 0);
+        assert!(passages.iter().all(|p| p.paragraph.is_none()));
+    }
+
+    #[test]
+    fn timestamps_parse() {
+        assert_eq!(leading_timestamp("1:23 hello"), Some(83));
+        assert_eq!(leading_timestamp("[01:02:03] hello"), Some(3723));
+        assert_eq!(leading_timestamp("(0:05)"), Some(5));
+        assert_eq!(leading_timestamp("1:2 hello"), None);
+        assert_eq!(leading_timestamp("12 hello"), None);
+        assert_eq!(leading_timestamp("99999999999:00"), None);
+    }
+
+    #[test]
+    fn markers_become_citations_and_unknown_ids_are_dropped() {
+        let mut messages: Vec<AnyMessage> = vec![
+            HumanMessage::builder()
+                .content(vec![source("First.\n\nSecond.")])
+                .build()
+                .into(),
+        ];
+        let index = index_sources(&mut messages);
+
+        let answer = "Café first [c1]. Both [c1, c9] and [link] [c]";
+        let citations = index.annotate(answer);
+        assert_eq!(citations.len(), 2);
+        let Annotation::Citation {
+            id,
+            title,
+            start_index,
+            end_index,
+            cited_text,
+            extras,
+            ..
+        } = &citations[0]
+        else {
+            panic!("expected a citation");
+        };
+        assert_eq!(id.as_deref(), Some("c1"));
+        assert_eq!(title.as_deref(), Some("Guide"));
+        assert_eq!((*start_index, *end_index), (Some(11), Some(15)));
+        assert_eq!(cited_text.as_deref(), Some("First.\n\nSecond."));
+        let extras = extras.as_ref().unwrap();
+        assert_eq!(extras["file_id"], "asset-1");
+        assert_eq!(extras["paragraph"], 1);
+    }
+
+    #[test]
+    fn earlier_answers_lose_their_markers() {
+        let cited = TextContentBlock {
+            id: None,
+            text: "It rains [c1]. Often [c2, c3].".to_string(),
+            annotations: Some(vec![Annotation::Citation {
+                id: Some("c1".to_string()),
+                url: None,
+                title: None,
+                start_index: None,
+                end_index: None,
+                cited_text: None,
+                extras: None,
+            }]),
+            index: None,
+            extras: None,
+        };
+        let mut messages: Vec<AnyMessage> = vec![
+            AIMessage::builder()
+                .content(vec![ContentBlock::Text(cited)])
+                .build()
+                .into(),
+        ];
+        index_sources(&mut messages);
+        let AnyMessage::AIMessage(ai) = &messages[0] else {
+            panic!("expected the answer");
+        };
+        let ContentBlock::Text(text) = &ai.content[0] else {
+            panic!("expected text");
+        };
+        assert_eq!(text.text, "It rains. Often.");
+        assert!(text.annotations.is_none());
+    }
+}
@@ -2,18 +2,32 @@
 //!
 //! Three jobs:
 //!
-//! 1. Translate `be_remote_db::Thread` rows into [`thread_core::Thread`].
+//! 1. Translate `be_remote_db::Thread` rows into [`thread_core::Thread`]
+//!    (and automation and workflow rows into their wire shapes alongside).
 //! 2. Translate `be_remote_db::Message` rows into [`AnyMessage`] (carried
 //!    typed by [`thread_core::MessageNode::message`]).
 //! 3. Build the active-branch [`MessageNode`] spine (with sibling metadata
 //!    so the list view can render variant chevrons) from the database's
 //!    `BranchMessageRow` shape.
 
+use std::collections::HashMap;
+
 use agent_chain::{AIMessage, AnyMessage, HumanMessage, SystemMessage, ToolCall, ToolMessage};
 use agent_chain_core::messages::ContentBlocks;
-use be_remote_db::{BranchMessageRow, Message, MessageType, Thread as DbThread};
+use agent_graph::workflow::{WorkflowDefinition, WorkflowParam};
+use be_remote_db::{
+    Automation as DbAutomation, AutomationRun as DbAutomationRun,
+    AutomationRunStatus as DbAutomationRunStatus, BranchMessageRow, Message, MessageType,
+    Thread as DbThread, ThreadFolder as DbThreadFolder, UserMemory as DbUserMemory,
+    Workflow as DbWorkflow,
+};
 use serde_json::Value;
-use thread_core::{MessageNode, Thread as WireThread};
+use thread_core::{
+    Automation as WireAutomation, AutomationRun as WireAutomationRun,
+    AutomationRunStatus as WireAutomationRunStatus, Memory as WireMemory, MessageNode,
+    Thread as WireThread, ThreadFolder as WireThreadFolder, ThreadModel, Workflow as WireWorkflow,
+    WorkflowParam as WireWorkflowParam, WorkflowTemplate,
+};
 use uuid::Uuid;
 
 use crate::error::{ThreadServiceError, ThreadServiceResult};
@@ -25,6 +39,7 @@ use crate::error::{ThreadServiceError, ThreadServiceResult};
 /// string (not nullable). Title-less threads are just freshly created ones
 /// that haven't run their `generate_title` step yet.
 pub fn db_thread_to_wire(thread: DbThread) -> WireThread {
+    let model = thread_model(&thread);
     WireThread {
         id: thread.id,
         user_id: thread.user_id,
@@ -32,15 +47,125 @@ pub fn db_thread_to_wire(thread: DbThread) -> WireThread {
         created_at: thread.created_at,
         updated_at: thread.updated_at,
         active_leaf_id: thread.active_leaf_id,
+        model,
+        tags: thread.tags,
+        pinned: thread.pinned_at.is_some(),
+        starred: thread.starred,
+        folder_id: thread.folder_id,
+    }
+}
+
+pub fn db_thread_folder_to_wire(folder: DbThreadFolder) -> WireThreadFolder {
+    WireThreadFolder {
+        id: folder.id,
+        name: folder.name,
+        position: folder.position,
+        created_at: folder.created_at,
+        updated_at: folder.updated_at,
+    }
+}
+
+/// The model the thread is pinned to, if any. The columns are set and
+/// cleared together (a table constraint enforces it).
+pub fn thread_model(thread: &DbThread) -> Option<ThreadModel> {
+    Some(ThreadModel {
+        provider: thread.model_provider.clone()?,
+        model: thread.model_name.clone()?,
+        temperature: thread.model_temperature,
+    })
+}
+
+pub fn db_memory_to_wire(memory: DbUserMemory) -> WireMemory {
+    WireMemory {
+        id: memory.id,
+        content: memory.content,
+        thread_id: memory.thread_id,
+        created_at: memory.created_at,
+        updated_at: memory.updated_at,
+    }
+}
+
+pub fn db_automation_to_wire(automation: DbAutomation) -> WireAutomation {
+    WireAutomation {
+        id: automation.id,
+        name: automation.name,
+        prompt: automation.prompt,
+        schedule: automation.schedule,
+        tools: automation.tools,
+        enabled: automation.enabled,
+        next_run_at: automation.next_run_at,
+        last_run_at: automation.last_run_at,
+        created_at: automation.created_at,
+        updated_at: automation.updated_at,
+    }
+}
+
+pub fn db_automation_run_to_wire(run: DbAutomationRun) -> WireAutomationRun {
+    WireAutomationRun {
+        id: run.id,
+        automation_id: run.automation_id,
+        thread_id: run.thread_id,
+        status: match run.status {
+            DbAutomationRunStatus::Running => WireAutomationRunStatus::Running,
+            DbAutomationRunStatus::Succeeded => WireAutomationRunStatus::Succeeded,
+            DbAutomationRunStatus::Failed => WireAutomationRunStatus::Failed,
+            DbAutomationRunStatus::Skipped => WireAutomationRunStatus::Skipped,
+        },
+        scheduled_for: run.scheduled_for,
+        started_at: run.started_at,
+        finished_at: run.finished_at,
+        output: run.output,
+        error: run.error,
+    }
+}
+
+/// Definitions are validated before they're stored, so a row that no
+/// longer parses only lists without parameters rather than failing the
+/// whole response.
+pub fn db_workflow_to_wire(workflow: DbWorkflow) -> WireWorkflow {
+    let params = serde_json::from_value::<WorkflowDefinition>(workflow.definition.clone())
+        .map(|definition| workflow_params_to_wire(&definition.params))
+        .unwrap_or_default();
+    WireWorkflow {
+        id: workflow.id,
+        name: workflow.name,
+        description: workflow.description,
+        params,
+        definition: workflow.definition,
+        created_at: workflow.created_at,
+        updated_at: workflow.updated_at,
+    }
+}
+
+pub fn workflow_template_to_wire(definition: WorkflowDefinition) -> WorkflowTemplate {
+    WorkflowTemplate {
+        id: definition.id.clone(),
+        name: definition.name.clone(),
+        description: definition.description.clone(),
+        params: workflow_params_to_wire(&definition.params),
+        tools: definition.tools().into_iter().map(str::to_string).collect(),
+        definition: serde_json::to_value(&definition).unwrap_or_default(),
     }
 }
 
+fn workflow_params_to_wire(params: &[WorkflowParam]) -> Vec<WireWorkflowParam> {
+    params
+        .iter()
+        .map(|param| WireWorkflowParam {
+            name: param.name.clone(),
+            description: param.description.clone(),
+            default: param.default.clone(),
+        })
+        .collect()
+}
+
 /// Convert a stored `Message` row into the matching [`AnyMessage`] variant.
 ///
-/// AI rows additionally hydrate their `tool_calls` from the JSON column on
-/// disk; tool rows require a `tool_call_id`. Rows missing required pieces
-/// surface as `Internal` errors — we never want a corrupt row to silently
-/// degrade a message's meaning at chat-context-prep time.
+/// AI rows additionally hydrate their `tool_calls` and `additional_kwargs`
+/// (where a cancelled turn's partial answer is marked `cancelled`) from the
+/// JSON columns on disk; tool rows require a `tool_call_id`. Rows missing
+/// required pieces surface as `Internal` errors — we never want a corrupt row
+/// to silently degrade a message's meaning at chat-context-prep time.
 pub fn convert_db_message_to_base_message(db_message: Message) -> ThreadServiceResult<AnyMessage> {
     let id = db_message.id.to_string();
     let content = parse_content_blocks(db_message.content);
@@ -60,6 +185,7 @@ pub fn convert_db_message_to_base_message(db_message: Message) -> ThreadServiceR
                 .id(id)
                 .content(content)
                 .tool_calls(tool_calls)
+                .additional_kwargs(parse_additional_kwargs(db_message.additional_kwargs))
                 .build();
             Ok(AnyMessage::AIMessage(message))
         }
@@ -81,6 +207,11 @@ fn parse_content_blocks(value: Value) -> ContentBlocks {
     serde_json::from_value(value).unwrap_or_default()
 }
 
+/// Anything but a JSON object reads as no kwargs.
+fn parse_additional_kwargs(value: Value) -> HashMap<String, Value> {
+    serde_json::from_value(value).unwrap_or_default()
+}
+
 fn parse_tool_calls(tool_calls: &Option<Value>) -> ThreadServiceResult<Vec<ToolCall>> {
     match tool_calls {
         None => Ok(Vec::new()),
@@ -42,6 +42,10 @@ pub enum ThreadServiceError {
         source: base64::DecodeError,
     },
 
+    /// The local-mode Ollama server failed or couldn't be reached.
+    #[error("Local model server error: {0}")]
+    Upstream(String),
+
     #[error("Internal error: {0}")]
     Internal(String),
 }
@@ -80,6 +84,7 @@ impl ThreadServiceError {
             Self::Storage(_) => "storage_error",
             Self::Asset(_) => "asset_error",
             Self::InvalidBase64 { .. } => "invalid_base64",
+            Self::Upstream(_) => "upstream_error",
             Self::Internal(_) => "internal_error",
         }
     }
@@ -92,6 +97,7 @@ impl ThreadServiceError {
             }
             Self::NotFound(_) => StatusCode::NOT_FOUND,
             Self::Conflict(_) => StatusCode::CONFLICT,
+            Self::Upstream(_) => StatusCode::BAD_GATEWAY,
             Self::Database(_) | Self::Storage(_) | Self::Asset(_) | Self::Internal(_) => {
                 StatusCode::INTERNAL_SERVER_ERROR
             }
@@ -113,6 +119,9 @@ impl From<be_remote_db::DbError> for ThreadServiceError {
             DbError::ForeignKeyViolation { entity } => {
                 Self::InvalidArgument(format!("Referenced {entity} does not exist"))
             }
+            DbError::OnLegalHold { entity } => {
+                Self::Conflict(format!("The {entity} is under legal hold"))
+            }
             DbError::InvalidInput(msg) => Self::InvalidArgument(msg),
             other => Self::Database(other),
         }
@@ -172,6 +181,9 @@ impl IntoResponse for ThreadServiceError {
             Self::Conflict(_) => {
                 tracing::info!(error = %detail, "Thread service conflict");
             }
+            Self::Upstream(_) => {
+                tracing::warn!(error = %detail, "Thread service upstream error");
+            }
             Self::Database(_) | Self::Storage(_) | Self::Asset(_) | Self::Internal(_) => {
                 tracing::error!(error = %detail, "Thread service internal error");
             }
@@ -0,0 +1,328 @@
+//! Scheduled automation CRUD, run history, and "run now".
+//!
+//! Definitions are validated here — the schedule must parse and every
+//! granted tool must be one the server offers — so the scheduler can trust
+//! what it reads back. `next_run_at` is derived from the schedule on every
+//! write that could move it.
+
+use std::sync::Arc;
+
+use axum::Json;
+use axum::extract::{Path, Query, State};
+use be_auth_core::AuthUser;
+use be_remote_db::PaginationParams;
+use chrono::{DateTime, Utc};
+use thread_core::{
+    AutomationResponse, AutomationTool, CreateAutomationRequest, DeleteAutomationResponse,
+    ListAutomationRunsQuery, ListAutomationRunsResponse, ListAutomationToolsResponse,
+    ListAutomationsResponse, UpdateAutomationRequest,
+};
+use uuid::Uuid;
+
+use crate::conversion::{db_automation_run_to_wire, db_automation_to_wire};
+use crate::error::{ThreadServiceError, ThreadServiceResult};
+use crate::schedule::CronSchedule;
+use crate::service::AppState;
+
+const RUNS_DEFAULT_LIMIT: u32 = 20;
+const RUNS_DEFAULT_OFFSET: u32 = 0;
+
+/// Matches the `automations.name` column.
+const MAX_NAME_CHARS: usize = 200;
+
+/// Matches the `automations.schedule` column.
+const MAX_SCHEDULE_CHARS: usize = 200;
+
+#[tracing::instrument(skip(state, user))]
+pub async fn list_automations(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+) -> ThreadServiceResult<Json<ListAutomationsResponse>> {
+    let user_id = user.user_id()?;
+
+    let automations = state.db.list_automations().user_id(user_id).call().await?;
+
+    Ok(Json(ListAutomationsResponse {
+        automations: automations.into_iter().map(db_automation_to_wire).collect(),
+    }))
+}
+
+#[tracing::instrument(skip(state, user, body))]
+pub async fn create_automation(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Json(body): Json<CreateAutomationRequest>,
+) -> ThreadServiceResult<Json<AutomationResponse>> {
+    let user_id = user.user_id()?;
+    let name = validate_name(body.name)?;
+    let prompt = validate_prompt(body.prompt)?;
+    let schedule = parse_schedule(&body.schedule)?;
+    validate_tools(&state, &body.tools)?;
+
+    let automation = state
+        .db
+        .create_automation()
+        .user_id(user_id)
+        .name(name)
+        .prompt(prompt)
+        .schedule(body.schedule)
+        .tools(body.tools)
+        .enabled(body.enabled)
+        .maybe_next_run_at(next_run_at(&schedule, body.enabled))
+        .call()
+        .await?;
+
+    tracing::info!("Created automation {}", automation.id);
+
+    Ok(Json(AutomationResponse {
+        automation: db_automation_to_wire(automation),
+    }))
+}
+
+#[tracing::instrument(skip(state, user), fields(automation_id = %automation_id))]
+pub async fn get_automation(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(automation_id): Path<Uuid>,
+) -> ThreadServiceResult<Json<AutomationResponse>> {
+    let user_id = user.user_id()?;
+
+    let automation = state
+        .db
+        .get_automation()
+        .id(automation_id)
+        .user_id(user_id)
+        .call()
+        .await?;
+
+    Ok(Json(AutomationResponse {
+        automation: db_automation_to_wire(automation),
+    }))
+}
+
+#[tracing::instrument(skip(state, user, body), fields(automation_id = %automation_id))]
+pub async fn update_automation(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(automation_id): Path<Uuid>,
+    Json(body): Json<UpdateAutomationRequest>,
+) -> ThreadServiceResult<Json<AutomationResponse>> {
+    let user_id = user.user_id()?;
+    let current = state
+        .db
+        .get_automation()
+        .id(automation_id)
+        .user_id(user_id)
+        .call()
+        .await?;
+
+    let name = match body.name {
+        Some(name) => validate_name(name)?,
+        None => current.name,
+    };
+    let prompt = match body.prompt {
+        Some(prompt) => validate_prompt(prompt)?,
+        None => current.prompt,
+    };
+    let tools = match body.tools {
+        Some(tools) => {
+            validate_tools(&state, &tools)?;
+            tools
+        }
+        None => current.tools,
+    };
+    let enabled = body.enabled.unwrap_or(current.enabled);
+    let schedule_text = body.schedule.unwrap_or(current.schedule.clone());
+    let schedule = parse_schedule(&schedule_text)?;
+
+    // A pending occurrence only moves when the edit could move it; renaming
+    // an automation shouldn't shift when it next fires.
+    let next = if enabled == current.enabled && schedule_text == current.schedule {
+        current.next_run_at
+    } else {
+        next_run_at(&schedule, enabled)
+    };
+
+    let automation = state
+        .db
+        .update_automation()
+        .id(automation_id)
+        .user_id(user_id)
+        .name(name)
+        .prompt(prompt)
+        .schedule(schedule_text)
+        .tools(tools)
+        .enabled(enabled)
+        .maybe_next_run_at(next)
+        .call()
+        .await?;
+
+    Ok(Json(AutomationResponse {
+        automation: db_automation_to_wire(automation),
+    }))
+}
+
+#[tracing::instrument(skip(state, user), fields(automation_id = %automation_id))]
+pub async fn delete_automation(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(automation_id): Path<Uuid>,
+) -> ThreadServiceResult<Json<DeleteAutomationResponse>> {
+    let user_id = user.user_id()?;
+
+    state
+        .db
+        .delete_automation()
+        .id(automation_id)
+        .user_id(user_id)
+        .call()
+        .await?;
+
+    tracing::info!("Deleted automation {}", automation_id);
+
+    Ok(Json(DeleteAutomationResponse {}))
+}
+
+/// Token-gated like the chat socket, since it spends tokens on the user's
+/// behalf.
+///
+/// Makes the automation due now rather than running it inline, so the run
+/// goes through the scheduler like any other and shows up in the history
+/// within one poll interval.
+#[tracing::instrument(skip(state, user), fields(automation_id = %automation_id))]
+pub async fn run_automation(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(automation_id): Path<Uuid>,
+) -> ThreadServiceResult<Json<AutomationResponse>> {
+    let user_id = user.user_id()?;
+    let current = state
+        .db
+        .get_automation()
+        .id(automation_id)
+        .user_id(user_id)
+        .call()
+        .await?;
+    if !current.enabled {
+        return Err(ThreadServiceError::invalid_argument(
+            "automation is disabled",
+        ));
+    }
+
+    let automation = state
+        .db
+        .trigger_automation()
+        .id(automation_id)
+        .user_id(user_id)
+        .call()
+        .await?;
+
+    Ok(Json(AutomationResponse {
+        automation: db_automation_to_wire(automation),
+    }))
+}
+
+#[tracing::instrument(skip(state, user), fields(automation_id = %automation_id))]
+pub async fn list_automation_runs(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(automation_id): Path<Uuid>,
+    Query(query): Query<ListAutomationRunsQuery>,
+) -> ThreadServiceResult<Json<ListAutomationRunsResponse>> {
+    let user_id = user.user_id()?;
+    let limit = query.limit.unwrap_or(RUNS_DEFAULT_LIMIT);
+    let offset = query.offset.unwrap_or(RUNS_DEFAULT_OFFSET);
+
+    // Resolve the automation first so an unknown or foreign id is a 404
+    // rather than an empty history.
+    state
+        .db
+        .get_automation()
+        .id(automation_id)
+        .user_id(user_id)
+        .call()
+        .await?;
+    let runs = state
+        .db
+        .list_automation_runs()
+        .automation_id(automation_id)
+        .user_id(user_id)
+        .params(PaginationParams::new(offset, limit, "DESC"))
+        .call()
+        .await?;
+
+    Ok(Json(ListAutomationRunsResponse {
+        runs: runs.into_iter().map(db_automation_run_to_wire).collect(),
+    }))
+}
+
+/// Tools the UI can offer when granting an automation access.
+#[tracing::instrument(skip(state, _user))]
+pub async fn list_automation_tools(
+    State(state): State<Arc<AppState>>,
+    _user: AuthUser,
+) -> ThreadServiceResult<Json<ListAutomationToolsResponse>> {
+    Ok(Json(ListAutomationToolsResponse {
+        tools: state
+            .providers
+            .web_tools
+            .iter()
+            .map(|tool| AutomationTool {
+                name: tool.name().to_string(),
+                description: tool.description().to_string(),
+            })
+            .collect(),
+    }))
+}
+
+fn validate_name(name: String) -> ThreadServiceResult<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(ThreadServiceError::invalid_argument(
+            "name must not be empty",
+        ));
+    }
+    if name.chars().count() > MAX_NAME_CHARS {
+        return Err(ThreadServiceError::invalid_argument(format!(
+            "name must be at most {MAX_NAME_CHARS} characters"
+        )));
+    }
+    Ok(name.to_string())
+}
+
+fn validate_prompt(prompt: String) -> ThreadServiceResult<String> {
+    if prompt.trim().is_empty() {
+        return Err(ThreadServiceError::invalid_argument(
+            "prompt must not be empty",
+        ));
+    }
+    Ok(prompt)
+}
+
+fn parse_schedule(schedule: &str) -> ThreadServiceResult<CronSchedule> {
+    if schedule.chars().count() > MAX_SCHEDULE_CHARS {
+        return Err(ThreadServiceError::invalid_argument(format!(
+            "schedule must be at most {MAX_SCHEDULE_CHARS} characters"
+        )));
+    }
+    CronSchedule::parse(schedule).map_err(|e| ThreadServiceError::invalid_argument(e.to_string()))
+}
+
+fn validate_tools(state: &AppState, tools: &[String]) -> ThreadServiceResult<()> {
+    match tools.iter().find(|name| {
+        !state
+            .providers
+            .web_tools
+            .iter()
+            .any(|tool| tool.name() == name.as_str())
+    }) {
+        Some(unknown) => Err(ThreadServiceError::invalid_argument(format!(
+            "unknown tool {unknown:?}"
+        ))),
+        None => Ok(()),
+    }
+}
+
+fn next_run_at(schedule: &CronSchedule, enabled: bool) -> Option<DateTime<Utc>> {
+    enabled.then(|| schedule.next_after(Utc::now())).flatten()
+}
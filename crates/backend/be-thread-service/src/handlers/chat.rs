@@ -25,7 +25,9 @@
 //! [`ChatServerMessage::ToolRequest`] / [`ChatServerMessage::ToolCancel`]
 //! on the same socket and the client returns [`ChatClientMessage::ToolResponse`]
 //! frames the bus correlates by `call_id`. At any point the client can send
-//! [`ChatClientMessage::Cancel`] (or just drop the socket) to abort.
+//! [`ChatClientMessage::Cancel`] (or just drop the socket) to abort. A turn
+//! whose command carried a `request_id` can also be stopped through
+//! [`cancel_chat`] while it runs.
 //!
 //! Token gating is enforced by the surrounding `be-authz` middleware before
 //! the upgrade handshake completes.
@@ -38,6 +40,7 @@ use agent_chain::HumanMessage;
 use agent_chain::messages::{AnyMessage, ContentBlock};
 use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
 use axum::extract::{Path, State};
+use axum::http::StatusCode;
 use axum::response::Response;
 use be_remote_db::{MessageType, PaginationParams};
 use futures::Stream;
@@ -49,16 +52,20 @@ use thread_core::{
 };
 use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
 use uuid::Uuid;
 
 use be_auth_core::AuthUser;
 
 use crate::agent_loop::run_agent_loop;
-use crate::conversion::convert_db_message_to_base_message;
+use crate::conversion::{convert_db_message_to_base_message, thread_model};
 use crate::error::{ThreadServiceError, ThreadServiceResult};
 use crate::llm::{LlmContext, prepare_llm_context};
+use crate::memory::{learn_from_message, recall_system_message};
 use crate::preliminary::rewrite_preliminary_blocks;
 use crate::remote_tool_bus::ChatRemoteBus;
+use crate::report::ReportGenerator;
+use crate::report::tool::GenerateReportTool;
 use crate::service::AppState;
 
 /// Trailing messages from the active branch fed back to the LLM as
@@ -71,7 +78,7 @@ const CONTEXT_MESSAGE_LIMIT: u32 = 5;
 /// looping over its own tool calls without converging on an answer.
 /// On exhaustion the loop runs one forced synthesis with
 /// `tool_choice=none` and finalises whatever it has accumulated.
-const MAX_TOOL_ROUNDS: usize = 15;
+pub(crate) const MAX_TOOL_ROUNDS: usize = 15;
 
 /// Buffer depth for the agent-loop → WebSocket-writer channel. Sized
 /// to absorb a small burst of `Chunk` frames before the writer
@@ -91,6 +98,15 @@ enum InitialCommand {
     Regenerate(RegenerateRequest),
 }
 
+impl InitialCommand {
+    fn request_id(&self) -> Option<Uuid> {
+        match self {
+            Self::Send(req) => req.request_id,
+            Self::Regenerate(req) => req.request_id,
+        }
+    }
+}
+
 /// Typed protocol fault surfaced by the prelude reader.
 ///
 /// Each variant carries enough context to render an actionable message back
@@ -116,6 +132,9 @@ enum ProtocolError {
     /// expected frame.
     #[error("timed out waiting for the {stage} frame")]
     Timeout { stage: &'static str },
+    /// Another running turn already holds the command's `request_id`.
+    #[error("request_id is already in use by a running turn")]
+    RequestIdInUse,
 }
 
 impl From<ProtocolError> for ThreadServiceError {
@@ -143,17 +162,56 @@ pub async fn chat_ws(
     Path(thread_id): Path<Uuid>,
 ) -> ThreadServiceResult<Response> {
     let user_id = user.user_id()?;
-    Ok(ws.on_upgrade(move |socket| handle_socket(socket, state, user_id, thread_id)))
+    // The socket task outlives this handler; carry the request span into
+    // it so every turn on the connection hangs off the caller's trace.
+    let span = tracing::Span::current();
+    Ok(ws.on_upgrade(move |socket| {
+        handle_socket(socket, state, user_id, thread_id).instrument(span)
+    }))
+}
+
+/// Cancel a running turn by the `request_id` its `Send`/`Regenerate` frame
+/// carried, from outside the turn's socket. The turn winds down as if its
+/// socket had sent `Cancel`. `404` when no such turn of the caller's is
+/// running on this thread, including once it has finished.
+#[tracing::instrument(skip(state, user), fields(thread_id = %thread_id, request_id = %request_id))]
+pub async fn cancel_chat(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path((thread_id, request_id)): Path<(Uuid, Uuid)>,
+) -> ThreadServiceResult<StatusCode> {
+    let user_id = user.user_id()?;
+    if !state.active_turns.cancel(request_id, user_id, thread_id) {
+        return Err(ThreadServiceError::not_found("chat turn"));
+    }
+    tracing::info!("Chat turn cancelled by request id");
+    Ok(StatusCode::NO_CONTENT)
 }
 
 async fn handle_socket(socket: WebSocket, state: Arc<AppState>, user_id: Uuid, thread_id: Uuid) {
     let (mut sender, mut receiver) = socket.split();
+    let cancel = CancellationToken::new();
 
     // Strict prelude: `CapabilityUpdate` then `Send`/`Regenerate`. Anything
-    // else closes the socket with an `Error { kind: "protocol", ... }`.
-    let (capability, command) = match wait_for_capability_then_initial_command(&mut receiver).await
-    {
-        Ok(pair) => pair,
+    // else closes the socket with an `Error { kind: "protocol", ... }`. A
+    // turn with a `request_id` stays registered until the socket closes,
+    // so `cancel_chat` can reach it from outside.
+    let prelude = wait_for_capability_then_initial_command(&mut receiver)
+        .await
+        .and_then(|(capability, command)| {
+            let registration = match command.request_id() {
+                Some(request_id) => Some(
+                    state
+                        .active_turns
+                        .register(request_id, user_id, thread_id, cancel.clone())
+                        .ok_or(ProtocolError::RequestIdInUse)?,
+                ),
+                None => None,
+            };
+            Ok((capability, command, registration))
+        });
+    let (capability, command, _registration) = match prelude {
+        Ok(prelude) => prelude,
         Err(err) => {
             let detail = err.to_string();
             tracing::info!(error = %detail, "Chat protocol violation");
@@ -168,7 +226,6 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>, user_id: Uuid, t
         }
     };
 
-    let cancel = CancellationToken::new();
     let (tx, mut rx) = mpsc::channel::<ChatServerMessage>(SERVER_CHANNEL_DEPTH);
     // The bus is shared between the reader task (which calls `resolve` on
     // `ToolResponse` frames) and the agent loop (which calls `call` to
@@ -536,12 +593,13 @@ async fn run_turn(
             ThreadServiceError::Internal(format!("Failed to serialize additional_kwargs: {e}"))
         })?;
 
+    let memory_text = human_message.text();
     messages.push(human_message.into());
 
     // Prepare the LLM context *before* persisting the human message so that
     // a context-prep failure doesn't leave a half-completed turn (a human
     // row with no AI response) in the thread history.
-    let prepared = prepare_turn(&state, user_id, messages, capability).await?;
+    let prepared = prepare_turn(&state, user_id, thread_id, messages, capability).await?;
 
     let human_db_message = state
         .db
@@ -599,6 +657,10 @@ async fn run_turn(
         return Ok(());
     }
 
+    tokio::spawn(
+        learn_from_message(state.clone(), user_id, thread_id, memory_text).in_current_span(),
+    );
+
     spawn_agent_loop(
         state,
         prepared,
@@ -656,7 +718,7 @@ async fn regenerate_ai_response(
         .await?;
 
     let messages = load_active_branch_context(&state, user_id, thread_id).await?;
-    let prepared = prepare_turn(&state, user_id, messages, capability).await?;
+    let prepared = prepare_turn(&state, user_id, thread_id, messages, capability).await?;
 
     spawn_agent_loop(
         state,
@@ -693,10 +755,22 @@ struct SpawnContext {
 /// [`rewrite_preliminary_blocks`] alongside any user content so inline
 /// payloads (large text, base64 images) become asset references before
 /// reaching the LLM — identical to the user-content rewrite path.
+///
+/// The thread row is re-read every turn so a model pinned to the thread
+/// (see [`crate::handlers::threads::set_thread_model`]) applies from the
+/// next message on.
+///
+/// With memory on, the remembered facts most relevant to the latest human
+/// message go in a system message ahead of the history (see
+/// [`crate::memory`]).
+///
+/// `generate_report` is offered on every turn; it only reads the user's
+/// own timeline.
 async fn prepare_turn(
     state: &AppState,
     user_id: Uuid,
-    messages: Vec<AnyMessage>,
+    thread_id: Uuid,
+    mut messages: Vec<AnyMessage>,
     capability: CapabilityUpdatePayload,
 ) -> ThreadServiceResult<LlmContext> {
     let CapabilityUpdatePayload {
@@ -705,10 +779,33 @@ async fn prepare_turn(
         system_blocks: prelude_blocks,
     } = capability;
     let prelude_blocks = rewrite_preliminary_blocks(state, user_id, prelude_blocks).await?;
+    let settings = state.shared_settings(user_id).await;
+    let mut server_tools = if settings.web_access {
+        state.providers.web_tools.clone()
+    } else {
+        Vec::new()
+    };
+    server_tools.push(Arc::new(GenerateReportTool::new(
+        ReportGenerator::from_state(state),
+        user_id,
+    )));
+    if settings.memory {
+        recall_memories(state, user_id, &mut messages).await;
+    }
+    let thread = state
+        .db
+        .get_thread()
+        .id(thread_id)
+        .user_id(user_id)
+        .call()
+        .await?;
+    let chat = state.chat_model_for(thread_model(&thread).as_ref())?;
     prepare_llm_context(
         &state.providers,
+        &chat,
         &state.asset_service,
         messages,
+        server_tools,
         remote_tools,
         &active_contexts,
         prelude_blocks,
@@ -716,6 +813,29 @@ async fn prepare_turn(
     .await
 }
 
+/// Prepend the recall message, if the user has anything remembered. A
+/// failed lookup costs the turn its memories, not the turn itself.
+async fn recall_memories(state: &AppState, user_id: Uuid, messages: &mut Vec<AnyMessage>) {
+    let memories = match state.db.list_memories().user_id(user_id).call().await {
+        Ok(memories) => memories,
+        Err(e) => {
+            tracing::warn!(error = %e, "Failed to load memories; answering without them");
+            return;
+        }
+    };
+    let query = messages
+        .iter()
+        .rev()
+        .find_map(|m| match m {
+            AnyMessage::HumanMessage(h) => Some(h.text()),
+            _ => None,
+        })
+        .unwrap_or_default();
+    if let Some(recall) = recall_system_message(&memories, &query) {
+        messages.insert(0, recall.into());
+    }
+}
+
 /// Spawn the agent loop with the prepared context. Mirror image of
 /// [`prepare_turn`] — both call sites in this module use it to keep the
 /// `run_agent_loop` builder wiring in exactly one place.
@@ -728,6 +848,7 @@ fn spawn_agent_loop(state: Arc<AppState>, prepared: LlmContext, ctx: SpawnContex
         messages,
         chat_model,
         catalog,
+        sources,
     } = prepared;
     let SpawnContext {
         thread_id,
@@ -749,11 +870,13 @@ fn spawn_agent_loop(state: Arc<AppState>, prepared: LlmContext, ctx: SpawnContex
             .catalog(catalog)
             .remote_bus(bus)
             .messages(messages)
+            .sources(sources)
             .thread_id(thread_id)
             .user_id(user_id)
             .human_message_id(human_message_id)
             .max_tool_rounds(MAX_TOOL_ROUNDS)
-            .call(),
+            .call()
+            .in_current_span(),
     );
 }
 
@@ -782,12 +905,14 @@ mod tests {
             parent_message_id: None,
             asset_chips_json: None,
             activity_id: None,
+            request_id: None,
         }
     }
 
     fn regenerate_request() -> RegenerateRequest {
         RegenerateRequest {
             ai_message_id: Uuid::nil(),
+            request_id: None,
         }
     }
 
@@ -0,0 +1,141 @@
+//! User-defined thread folders: list, create, rename / reorder, delete.
+//!
+//! Threads are filed into a folder through `POST /threads/{id}/folder`
+//! (see [`super::threads::move_thread`]); deleting a folder unfiles its
+//! threads rather than deleting them.
+
+use std::sync::Arc;
+
+use axum::Json;
+use axum::extract::{Path, State};
+use be_auth_core::AuthUser;
+use thread_core::{
+    CreateThreadFolderRequest, DeleteThreadFolderResponse, ListThreadFoldersResponse,
+    ThreadFolderResponse, UpdateThreadFolderRequest,
+};
+use uuid::Uuid;
+
+use crate::conversion::db_thread_folder_to_wire;
+use crate::error::{ThreadServiceError, ThreadServiceResult};
+use crate::service::AppState;
+
+/// Matches the `thread_folders.name` column.
+const MAX_NAME_CHARS: usize = 100;
+
+#[tracing::instrument(skip(state, user))]
+pub async fn list_thread_folders(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+) -> ThreadServiceResult<Json<ListThreadFoldersResponse>> {
+    let user_id = user.user_id()?;
+
+    let folders = state
+        .db
+        .list_thread_folders()
+        .user_id(user_id)
+        .call()
+        .await?;
+
+    Ok(Json(ListThreadFoldersResponse {
+        folders: folders.into_iter().map(db_thread_folder_to_wire).collect(),
+    }))
+}
+
+/// A name the user already uses is a 409.
+#[tracing::instrument(skip(state, user, body))]
+pub async fn create_thread_folder(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Json(body): Json<CreateThreadFolderRequest>,
+) -> ThreadServiceResult<Json<ThreadFolderResponse>> {
+    let user_id = user.user_id()?;
+    let name = validate_name(&body.name)?;
+
+    let folder = state
+        .db
+        .create_thread_folder()
+        .user_id(user_id)
+        .name(name)
+        .call()
+        .await?;
+
+    tracing::info!("Created thread folder {}", folder.id);
+
+    Ok(Json(ThreadFolderResponse {
+        folder: db_thread_folder_to_wire(folder),
+    }))
+}
+
+#[tracing::instrument(skip(state, user, body), fields(folder_id = %folder_id))]
+pub async fn update_thread_folder(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(folder_id): Path<Uuid>,
+    Json(body): Json<UpdateThreadFolderRequest>,
+) -> ThreadServiceResult<Json<ThreadFolderResponse>> {
+    let user_id = user.user_id()?;
+    let name = body.name.as_deref().map(validate_name).transpose()?;
+
+    let folder = state
+        .db
+        .update_thread_folder()
+        .id(folder_id)
+        .user_id(user_id)
+        .maybe_name(name)
+        .maybe_position(body.position)
+        .call()
+        .await?;
+
+    Ok(Json(ThreadFolderResponse {
+        folder: db_thread_folder_to_wire(folder),
+    }))
+}
+
+#[tracing::instrument(skip(state, user), fields(folder_id = %folder_id))]
+pub async fn delete_thread_folder(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(folder_id): Path<Uuid>,
+) -> ThreadServiceResult<Json<DeleteThreadFolderResponse>> {
+    let user_id = user.user_id()?;
+
+    state
+        .db
+        .delete_thread_folder()
+        .id(folder_id)
+        .user_id(user_id)
+        .call()
+        .await?;
+
+    tracing::info!("Deleted thread folder {}", folder_id);
+
+    Ok(Json(DeleteThreadFolderResponse {}))
+}
+
+fn validate_name(name: &str) -> ThreadServiceResult<&str> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(ThreadServiceError::invalid_argument(
+            "name must not be empty",
+        ));
+    }
+    if name.chars().count() > MAX_NAME_CHARS {
+        return Err(ThreadServiceError::invalid_argument(format!(
+            "name must be at most {MAX_NAME_CHARS} characters"
+        )));
+    }
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folder_name_is_trimmed_and_bounded() {
+        assert_eq!(validate_name("  Work  ").unwrap(), "Work");
+        assert!(validate_name("   ").is_err());
+        assert!(validate_name(&"f".repeat(100)).is_ok());
+        assert!(validate_name(&"f".repeat(101)).is_err());
+    }
+}
@@ -0,0 +1,243 @@
+//! Local-mode model management: list, pull and delete the models on the
+//! deployment's Ollama server.
+//!
+//! Only served when `EURORA_OLLAMA_URL` is set (see
+//! [`crate::llm::local_models_from_env`]); otherwise every route is a
+//! `404`, which the desktop takes to mean "not in local mode". The models
+//! are the server's, not the caller's, so these routes are meant for
+//! self-hosted deployments whose users all run the box.
+
+use std::sync::Arc;
+
+use agent_chain::ollama::{OllamaModels, PullProgress};
+use axum::Json;
+use axum::body::Body;
+use axum::extract::{Query, State};
+use axum::http::{StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use be_auth_core::AuthUser;
+use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt};
+use thread_core::{
+    DeleteLocalModelQuery, ListLocalModelsResponse, LocalModel, LocalModelPullEvent,
+    PullLocalModelRequest,
+};
+
+use crate::error::{ThreadServiceError, ThreadServiceResult};
+use crate::service::AppState;
+
+/// Longest model name accepted, tag and namespace included.
+const MAX_MODEL_NAME_CHARS: usize = 200;
+
+#[tracing::instrument(skip_all, fields(user_id))]
+pub async fn list_local_models(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+) -> ThreadServiceResult<Json<ListLocalModelsResponse>> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    let store = model_store(&state)?;
+    let models = store.list().await.map_err(store_error)?;
+    Ok(Json(ListLocalModelsResponse {
+        models: models.into_iter().map(local_model_to_wire).collect(),
+    }))
+}
+
+/// Pull a model, streaming [`LocalModelPullEvent`]s as NDJSON until it is
+/// installed or fails. Errors before the pull starts (an unknown name is
+/// only reported once it does) are ordinary error responses.
+#[tracing::instrument(skip_all, fields(user_id, model = %body.model))]
+pub async fn pull_local_model(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Json(body): Json<PullLocalModelRequest>,
+) -> ThreadServiceResult<Response> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    let model = validate_model_name(&body.model)?;
+    let store = model_store(&state)?;
+    let pull = store.pull(model).await.map_err(store_error)?;
+    tracing::info!("Local model pull started");
+
+    let events = stream::unfold(Some(pull), |pull| async move {
+        let mut pull = pull?;
+        let (event, rest) = match pull.next().await? {
+            Ok(progress) if progress.is_success() => {
+                tracing::info!("Local model pull finished");
+                (LocalModelPullEvent::Done, None)
+            }
+            Ok(progress) => (progress_event(progress), Some(pull)),
+            Err(e) => {
+                tracing::warn!(error = %e, "Local model pull failed");
+                let message = e.to_string();
+                (LocalModelPullEvent::Failed { message }, None)
+            }
+        };
+        Some((event, rest))
+    });
+    let lines = events.map(|event| Ok::<_, std::convert::Infallible>(encode_line(&event)));
+
+    Ok((
+        [(header::CONTENT_TYPE, "application/x-ndjson")],
+        Body::from_stream(lines),
+    )
+        .into_response())
+}
+
+#[tracing::instrument(skip_all, fields(user_id, model = %query.model))]
+pub async fn delete_local_model(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Query(query): Query<DeleteLocalModelQuery>,
+) -> ThreadServiceResult<StatusCode> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    let model = validate_model_name(&query.model)?;
+    let store = model_store(&state)?;
+    store.delete(model).await.map_err(store_error)?;
+    tracing::info!("Local model deleted");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn model_store(state: &AppState) -> ThreadServiceResult<&OllamaModels> {
+    state
+        .local_models
+        .as_ref()
+        .ok_or_else(|| ThreadServiceError::not_found("Local model management is not enabled"))
+}
+
+/// Ollama's own messages (e.g. "pull model manifest: file does not
+/// exist") are meant for the user, so client errors pass them through.
+fn store_error(err: agent_chain::Error) -> ThreadServiceError {
+    match err {
+        agent_chain::Error::Api {
+            status: 404,
+            message,
+        } => ThreadServiceError::NotFound(message),
+        agent_chain::Error::Api { status, message } if (400..500).contains(&status) => {
+            ThreadServiceError::InvalidArgument(message)
+        }
+        other => ThreadServiceError::Upstream(other.to_string()),
+    }
+}
+
+/// `name[:tag]`, optionally namespaced (`namespace/name`,
+/// `hf.co/user/repo:tag`).
+fn validate_model_name(name: &str) -> ThreadServiceResult<&str> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(ThreadServiceError::invalid_argument("model is required"));
+    }
+    if name.chars().count() > MAX_MODEL_NAME_CHARS {
+        return Err(ThreadServiceError::invalid_argument(format!(
+            "model must be at most {MAX_MODEL_NAME_CHARS} characters"
+        )));
+    }
+    let allowed = |c: char| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-' | ':' | '/');
+    if !name.chars().all(allowed)
+        || name.starts_with(['/', '.', '-'])
+        || name.ends_with('/')
+        || name.contains("//")
+        || name.split('/').any(|part| part == "..")
+    {
+        return Err(ThreadServiceError::invalid_argument(format!(
+            "`{name}` is not a valid model name"
+        )));
+    }
+    Ok(name)
+}
+
+fn progress_event(progress: PullProgress) -> LocalModelPullEvent {
+    LocalModelPullEvent::Progress {
+        status: progress.status,
+        total: progress.total,
+        completed: progress.completed,
+    }
+}
+
+fn local_model_to_wire(model: agent_chain::ollama::LocalModel) -> LocalModel {
+    LocalModel {
+        modified_at: model
+            .modified_at
+            .as_deref()
+            .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+            .map(|at| at.with_timezone(&Utc)),
+        name: model.name,
+        size_bytes: model.size,
+        digest: model.digest,
+        family: model.details.family,
+        parameter_size: model.details.parameter_size,
+        quantization: model.details.quantization_level,
+    }
+}
+
+fn encode_line(event: &LocalModelPullEvent) -> Vec<u8> {
+    // Plain data with string keys; serialization can't fail.
+    let mut line = serde_json::to_vec(event).unwrap_or_default();
+    line.push(b'\n');
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn model_names_follow_ollama_naming() {
+        for ok in [
+            "llama3.1",
+            "llama3.1:8b",
+            "library/qwen2.5-coder:7b-instruct-q4_K_M",
+            "hf.co/bartowski/Llama-3.2-1B-Instruct-GGUF:Q4_K_M",
+            "  mistral  ",
+        ] {
+            assert!(validate_model_name(ok).is_ok(), "{ok}");
+        }
+        for bad in [
+            "",
+            "   ",
+            "/etc/passwd",
+            "../llama",
+            "a/../b",
+            "llama 3",
+            "llama;rm",
+            "-rf",
+            "a//b",
+        ] {
+            assert!(validate_model_name(bad).is_err(), "{bad}");
+        }
+        assert!(validate_model_name(&"a".repeat(MAX_MODEL_NAME_CHARS + 1)).is_err());
+    }
+
+    #[test]
+    fn store_errors_keep_ollama_messages() {
+        let err = store_error(agent_chain::Error::api(404, "model 'x' not found"));
+        assert!(matches!(err, ThreadServiceError::NotFound(ref m) if m == "model 'x' not found"));
+        let err = store_error(agent_chain::Error::api(400, "invalid model name"));
+        assert!(matches!(err, ThreadServiceError::InvalidArgument(_)));
+        let err = store_error(agent_chain::Error::api(500, "disk full"));
+        assert!(matches!(err, ThreadServiceError::Upstream(_)));
+    }
+
+    #[test]
+    fn listed_models_convert_to_wire() {
+        let model: agent_chain::ollama::LocalModel = serde_json::from_value(serde_json::json!({
+            "name": "llama3.1:8b",
+            "size": 4920753328u64,
+            "digest": "46e0c10c039e",
+            "modified_at": "2025-01-02T03:04:05.123456789-07:00",
+            "details": { "family": "llama", "quantization_level": "Q4_K_M" }
+        }))
+        .unwrap();
+        let wire = local_model_to_wire(model);
+        assert_eq!(wire.size_bytes, 4_920_753_328);
+        assert_eq!(wire.quantization.as_deref(), Some("Q4_K_M"));
+        assert_eq!(
+            wire.modified_at.unwrap().to_rfc3339(),
+            "2025-01-02T10:04:05.123456789+00:00"
+        );
+    }
+}
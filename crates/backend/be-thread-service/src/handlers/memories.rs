@@ -0,0 +1,154 @@
+//! Review and edit what the assistant remembers about the user: list,
+//! add, reword, and forget one fact or all of them.
+//!
+//! These work whether or not the `shared.memory` setting is on, so a user
+//! who turns memory off can still see and clear what was kept.
+
+use std::sync::Arc;
+
+use axum::Json;
+use axum::extract::{Path, State};
+use be_auth_core::AuthUser;
+use thread_core::{DeleteMemoryResponse, ListMemoriesResponse, MemoryRequest, MemoryResponse};
+use uuid::Uuid;
+
+use crate::conversion::db_memory_to_wire;
+use crate::error::{ThreadServiceError, ThreadServiceResult};
+use crate::memory::{MEMORIES_PER_USER_MAX, MEMORY_MAX_CHARS};
+use crate::service::AppState;
+
+#[tracing::instrument(skip(state, user))]
+pub async fn list_memories(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+) -> ThreadServiceResult<Json<ListMemoriesResponse>> {
+    let user_id = user.user_id()?;
+
+    let memories = state.db.list_memories().user_id(user_id).call().await?;
+
+    Ok(Json(ListMemoriesResponse {
+        memories: memories.into_iter().map(db_memory_to_wire).collect(),
+    }))
+}
+
+/// A fact the user already has, ignoring case, is a 409.
+#[tracing::instrument(skip(state, user, body))]
+pub async fn create_memory(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Json(body): Json<MemoryRequest>,
+) -> ThreadServiceResult<Json<MemoryResponse>> {
+    let user_id = user.user_id()?;
+    let content = validate_content(&body.content)?;
+
+    let count = state.db.count_memories().user_id(user_id).call().await?;
+    if count >= MEMORIES_PER_USER_MAX as i64 {
+        return Err(ThreadServiceError::invalid_argument(format!(
+            "at most {MEMORIES_PER_USER_MAX} memories are kept; delete some first"
+        )));
+    }
+
+    let memory = state
+        .db
+        .create_memory()
+        .user_id(user_id)
+        .content(&content)
+        .call()
+        .await?;
+
+    Ok(Json(MemoryResponse {
+        memory: db_memory_to_wire(memory),
+    }))
+}
+
+#[tracing::instrument(skip(state, user, body), fields(memory_id = %memory_id))]
+pub async fn update_memory(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(memory_id): Path<Uuid>,
+    Json(body): Json<MemoryRequest>,
+) -> ThreadServiceResult<Json<MemoryResponse>> {
+    let user_id = user.user_id()?;
+    let content = validate_content(&body.content)?;
+
+    let memory = state
+        .db
+        .update_memory()
+        .id(memory_id)
+        .user_id(user_id)
+        .content(&content)
+        .call()
+        .await?;
+
+    Ok(Json(MemoryResponse {
+        memory: db_memory_to_wire(memory),
+    }))
+}
+
+#[tracing::instrument(skip(state, user), fields(memory_id = %memory_id))]
+pub async fn delete_memory(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(memory_id): Path<Uuid>,
+) -> ThreadServiceResult<Json<DeleteMemoryResponse>> {
+    let user_id = user.user_id()?;
+
+    state
+        .db
+        .delete_memory()
+        .id(memory_id)
+        .user_id(user_id)
+        .call()
+        .await?;
+
+    Ok(Json(DeleteMemoryResponse {}))
+}
+
+#[tracing::instrument(skip(state, user))]
+pub async fn delete_all_memories(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+) -> ThreadServiceResult<Json<DeleteMemoryResponse>> {
+    let user_id = user.user_id()?;
+
+    let deleted = state
+        .db
+        .delete_all_memories()
+        .user_id(user_id)
+        .call()
+        .await?;
+
+    tracing::info!("Forgot {} memories", deleted);
+
+    Ok(Json(DeleteMemoryResponse {}))
+}
+
+fn validate_content(content: &str) -> ThreadServiceResult<String> {
+    let content = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    if content.is_empty() {
+        return Err(ThreadServiceError::invalid_argument(
+            "content must not be empty",
+        ));
+    }
+    if content.chars().count() > MEMORY_MAX_CHARS {
+        return Err(ThreadServiceError::invalid_argument(format!(
+            "content must be at most {MEMORY_MAX_CHARS} characters"
+        )));
+    }
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_is_collapsed_and_bounded() {
+        assert_eq!(
+            validate_content("  Uses   Arch\nLinux ").unwrap(),
+            "Uses Arch Linux"
+        );
+        assert!(validate_content(" \n ").is_err());
+        assert!(validate_content(&"m".repeat(MEMORY_MAX_CHARS + 1)).is_err());
+    }
+}
@@ -1,4 +1,11 @@
+pub mod automations;
 pub mod chat;
+pub mod folders;
+pub mod local_models;
+pub mod memories;
 pub mod messages;
+pub mod models;
+pub mod reports;
 pub mod search;
 pub mod threads;
+pub mod workflows;
@@ -0,0 +1,104 @@
+//! The models the deployment's gateway providers offer, for the model
+//! picker behind thread model pins.
+//!
+//! A passthrough of each gateway's `GET /models`, fetched per request so
+//! the list follows what the gateway offers today. A gateway that can't
+//! be reached is logged and left out; the others still list.
+
+use std::sync::Arc;
+
+use agent_chain::openai::RemoteModel;
+use axum::Json;
+use axum::extract::State;
+use be_auth_core::AuthUser;
+use futures::future::join_all;
+use thread_core::{ListProviderModelsResponse, ModelPricing, ProviderModel};
+
+use crate::error::ThreadServiceResult;
+use crate::service::AppState;
+
+#[tracing::instrument(skip_all, fields(user_id))]
+pub async fn list_provider_models(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+) -> ThreadServiceResult<Json<ListProviderModelsResponse>> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    let listings = join_all(
+        state
+            .model_catalogs
+            .iter()
+            .map(|(provider, catalog)| async move { (provider, catalog.list().await) }),
+    )
+    .await;
+
+    let mut models = Vec::new();
+    for (provider, listing) in listings {
+        match listing {
+            Ok(listed) => models.extend(
+                listed
+                    .into_iter()
+                    .map(|model| provider_model_to_wire(provider, model)),
+            ),
+            Err(e) => tracing::warn!(
+                provider = %provider,
+                error = %e,
+                "Failed to list the provider's models"
+            ),
+        }
+    }
+    Ok(Json(ListProviderModelsResponse { models }))
+}
+
+fn provider_model_to_wire(provider: &str, model: RemoteModel) -> ProviderModel {
+    let pricing = model
+        .pricing
+        .map(|pricing| ModelPricing {
+            input_per_million: pricing.prompt_per_million(),
+            output_per_million: pricing.completion_per_million(),
+        })
+        .filter(|p| p.input_per_million.is_some() || p.output_per_million.is_some());
+    ProviderModel {
+        provider: provider.to_string(),
+        model: model.id,
+        name: model.name,
+        context_length: model
+            .context_length
+            .map(|n| u32::try_from(n).unwrap_or(u32::MAX)),
+        pricing,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gateway_prices_map_to_per_million_tokens() {
+        let model: RemoteModel = serde_json::from_value(serde_json::json!({
+            "id": "openai/gpt-4o-mini",
+            "name": "OpenAI: GPT-4o-mini",
+            "context_length": 128000,
+            "pricing": { "prompt": "0.00000015", "completion": "0.0000006" }
+        }))
+        .unwrap();
+        let wire = provider_model_to_wire("openrouter", model);
+        assert_eq!(wire.provider, "openrouter");
+        assert_eq!(wire.model, "openai/gpt-4o-mini");
+        assert_eq!(wire.context_length, Some(128_000));
+        let pricing = wire.pricing.unwrap();
+        assert!((pricing.input_per_million.unwrap() - 0.15).abs() < 1e-9);
+        assert!((pricing.output_per_million.unwrap() - 0.6).abs() < 1e-9);
+    }
+
+    #[test]
+    fn unpriced_models_carry_no_pricing() {
+        let model: RemoteModel = serde_json::from_value(serde_json::json!({
+            "id": "openrouter/auto",
+            "pricing": { "prompt": "-1", "completion": "-1" }
+        }))
+        .unwrap();
+        assert_eq!(provider_model_to_wire("openrouter", model).pricing, None);
+    }
+}
@@ -0,0 +1,30 @@
+//! Timeline reports: render a template against the user's activity in a
+//! time range and store the result as an asset (see [`crate::report`]).
+
+use std::sync::Arc;
+
+use axum::Json;
+use axum::extract::State;
+use be_auth_core::AuthUser;
+use chrono::Utc;
+use thread_core::{GenerateReportRequest, GenerateReportResponse};
+
+use crate::error::ThreadServiceResult;
+use crate::report::ReportGenerator;
+use crate::service::AppState;
+
+/// Token-gated: a template with `{{summary}}` costs a model call.
+#[tracing::instrument(skip(state, user, body))]
+pub async fn generate_report(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Json(body): Json<GenerateReportRequest>,
+) -> ThreadServiceResult<Json<GenerateReportResponse>> {
+    let user_id = user.user_id()?;
+
+    let report = ReportGenerator::from_state(&state)
+        .generate(user_id, &body, Utc::now())
+        .await?;
+
+    Ok(Json(report))
+}
@@ -7,17 +7,26 @@ use be_remote_db::PaginationParams;
 use thread_core::{
     CreateThreadRequest, CreateThreadResponse, DeleteThreadResponse, GenerateThreadTitleRequest,
     GenerateThreadTitleResponse, GetThreadResponse, ListThreadsQuery, ListThreadsResponse,
+    MoveThreadRequest, MoveThreadResponse, SetThreadModelRequest, SetThreadModelResponse,
+    ThreadModel, UpdateThreadRequest, UpdateThreadResponse,
 };
 use uuid::Uuid;
 
 use crate::conversion::db_thread_to_wire;
-use crate::error::ThreadServiceResult;
+use crate::error::{ThreadServiceError, ThreadServiceResult};
 use crate::service::AppState;
 use crate::title::{TITLE_DEFAULT, auto_generate_title_if_needed};
+use crate::webhooks;
 
 const LIST_DEFAULT_LIMIT: u32 = 20;
 const LIST_DEFAULT_OFFSET: u32 = 0;
 
+/// Longest model name accepted for a thread pin. Real names are well
+/// under this; the cap just keeps junk out of the row.
+const MODEL_NAME_MAX_LEN: usize = 200;
+/// The range OpenAI-compatible APIs accept.
+const TEMPERATURE_RANGE: std::ops::RangeInclusive<f32> = 0.0..=2.0;
+
 #[tracing::instrument(skip(state, user, body))]
 pub async fn create_thread(
     State(state): State<Arc<AppState>>,
@@ -39,6 +48,7 @@ pub async fn create_thread(
         .await?;
 
     tracing::info!("Created thread {}", thread.id);
+    webhooks::thread_created(&state.db, &thread).await;
 
     Ok(Json(CreateThreadResponse {
         thread: db_thread_to_wire(thread),
@@ -60,6 +70,9 @@ pub async fn list_threads(
         .list_threads()
         .user_id(user_id)
         .params(PaginationParams::new(offset, limit, "DESC"))
+        .maybe_folder_id(query.folder_id)
+        .maybe_starred(query.starred)
+        .maybe_pinned(query.pinned)
         .call()
         .await?;
 
@@ -140,6 +153,57 @@ pub async fn delete_thread(
     Ok(Json(DeleteThreadResponse {}))
 }
 
+/// Pin / unpin and star / unstar a thread. Omitted flags are left alone.
+#[tracing::instrument(skip(state, user), fields(thread_id = %thread_id))]
+pub async fn update_thread(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(thread_id): Path<Uuid>,
+    Json(body): Json<UpdateThreadRequest>,
+) -> ThreadServiceResult<Json<UpdateThreadResponse>> {
+    let user_id = user.user_id()?;
+
+    let thread = state
+        .db
+        .set_thread_flags()
+        .id(thread_id)
+        .user_id(user_id)
+        .maybe_pinned(body.pinned)
+        .maybe_starred(body.starred)
+        .call()
+        .await?;
+
+    Ok(Json(UpdateThreadResponse {
+        thread: db_thread_to_wire(thread),
+    }))
+}
+
+/// File the thread into one of the user's folders, or take it out of its
+/// folder with `folder_id: null`. A folder the user doesn't own is
+/// reported the same as a missing thread.
+#[tracing::instrument(skip(state, user), fields(thread_id = %thread_id))]
+pub async fn move_thread(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(thread_id): Path<Uuid>,
+    Json(body): Json<MoveThreadRequest>,
+) -> ThreadServiceResult<Json<MoveThreadResponse>> {
+    let user_id = user.user_id()?;
+
+    let thread = state
+        .db
+        .move_thread_to_folder()
+        .id(thread_id)
+        .user_id(user_id)
+        .maybe_folder_id(body.folder_id)
+        .call()
+        .await?;
+
+    Ok(Json(MoveThreadResponse {
+        thread: db_thread_to_wire(thread),
+    }))
+}
+
 /// Token-gated. The `be-authz` middleware checks the user's monthly token
 /// limit before this handler runs; on exhaustion it short-circuits with a
 /// 429 and this code never executes.
@@ -149,13 +213,14 @@ pub async fn delete_thread(
 /// endpoint. It remains useful for "regenerate title" UX and for clients
 /// that lost the wire frame. Idempotency is enforced by
 /// [`auto_generate_title_if_needed`]: a thread that already has a
-/// user-meaningful title is returned untouched.
+/// user-meaningful title is returned untouched unless the request sets
+/// `regenerate`, in which case its title and tags are replaced.
 #[tracing::instrument(skip(state, user), fields(thread_id = %thread_id))]
 pub async fn generate_thread_title(
     State(state): State<Arc<AppState>>,
     user: AuthUser,
     Path(thread_id): Path<Uuid>,
-    Json(_): Json<GenerateThreadTitleRequest>,
+    Json(body): Json<GenerateThreadTitleRequest>,
 ) -> ThreadServiceResult<Json<GenerateThreadTitleResponse>> {
     let user_id = user.user_id()?;
 
@@ -167,6 +232,7 @@ pub async fn generate_thread_title(
         state.providers.title.as_ref(),
         thread_id,
         user_id,
+        body.regenerate,
     )
     .await?;
 
@@ -182,3 +248,101 @@ pub async fn generate_thread_title(
         thread: db_thread_to_wire(thread),
     }))
 }
+
+/// Pin the thread to a model, or with `model: null` return it to the
+/// deployment default. Every later turn in the thread, including
+/// regenerations, uses the pinned model; earlier messages are untouched.
+///
+/// The pin is checked against the deployment's LLM config up front, so
+/// an unknown provider or one this service can't drive is rejected here
+/// rather than on the next message.
+#[tracing::instrument(skip(state, user, body), fields(thread_id = %thread_id))]
+pub async fn set_thread_model(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(thread_id): Path<Uuid>,
+    Json(body): Json<SetThreadModelRequest>,
+) -> ThreadServiceResult<Json<SetThreadModelResponse>> {
+    let user_id = user.user_id()?;
+
+    let model = body.model.map(normalize_thread_model).transpose()?;
+    state.chat_model_for(model.as_ref())?;
+
+    let (provider, name, temperature) = match model {
+        Some(m) => (Some(m.provider), Some(m.model), m.temperature),
+        None => (None, None, None),
+    };
+    let thread = state
+        .db
+        .set_thread_model()
+        .id(thread_id)
+        .user_id(user_id)
+        .maybe_model_provider(provider)
+        .maybe_model_name(name)
+        .maybe_model_temperature(temperature)
+        .call()
+        .await?;
+
+    Ok(Json(SetThreadModelResponse {
+        thread: db_thread_to_wire(thread),
+    }))
+}
+
+/// Trim the names and reject values no provider would accept.
+fn normalize_thread_model(model: ThreadModel) -> ThreadServiceResult<ThreadModel> {
+    let provider = model.provider.trim().to_string();
+    let name = model.model.trim().to_string();
+    if provider.is_empty() || name.is_empty() {
+        return Err(ThreadServiceError::invalid_argument(
+            "model provider and name are required",
+        ));
+    }
+    if name.chars().count() > MODEL_NAME_MAX_LEN {
+        return Err(ThreadServiceError::invalid_argument(format!(
+            "model name must be at most {MODEL_NAME_MAX_LEN} characters"
+        )));
+    }
+    if let Some(t) = model.temperature
+        && !TEMPERATURE_RANGE.contains(&t)
+    {
+        return Err(ThreadServiceError::invalid_argument(format!(
+            "temperature must be between {} and {}",
+            TEMPERATURE_RANGE.start(),
+            TEMPERATURE_RANGE.end()
+        )));
+    }
+    Ok(ThreadModel {
+        provider,
+        model: name,
+        temperature: model.temperature,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(provider: &str, name: &str, temperature: Option<f32>) -> ThreadModel {
+        ThreadModel {
+            provider: provider.into(),
+            model: name.into(),
+            temperature,
+        }
+    }
+
+    #[test]
+    fn thread_model_is_trimmed() {
+        let normalized = normalize_thread_model(model(" openai ", " gpt-4o ", Some(0.7))).unwrap();
+        assert_eq!(normalized, model("openai", "gpt-4o", Some(0.7)));
+    }
+
+    #[test]
+    fn thread_model_rejects_blank_names_and_bad_temperatures() {
+        assert!(normalize_thread_model(model("", "gpt-4o", None)).is_err());
+        assert!(normalize_thread_model(model("openai", "  ", None)).is_err());
+        assert!(normalize_thread_model(model("openai", &"m".repeat(201), None)).is_err());
+        assert!(normalize_thread_model(model("openai", "gpt-4o", Some(-0.1))).is_err());
+        assert!(normalize_thread_model(model("openai", "gpt-4o", Some(2.5))).is_err());
+        assert!(normalize_thread_model(model("openai", "gpt-4o", Some(f32::NAN))).is_err());
+    }
+}
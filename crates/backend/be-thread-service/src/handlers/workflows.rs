@@ -0,0 +1,406 @@
+//! Workflow templates, saved-workflow CRUD, and execution.
+//!
+//! Definitions arrive as JSON in the `agent_graph::workflow` format and are
+//! validated on every write, so anything stored here compiles unless it
+//! names a tool the user no longer has. Sharing is by definition: `GET`
+//! returns the full document and `POST` accepts the same document back,
+//! on any account.
+//!
+//! Runs execute inline and are recorded as a thread — a human message
+//! describing the invocation and an AI message holding the output — so the
+//! result can be continued as a chat and its token usage is charged like
+//! any other turn.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use agent_chain::HumanMessage;
+use agent_graph::workflow::{
+    CompiledWorkflow, WorkflowDefinition, WorkflowError, WorkflowRuntime, builtin_templates,
+};
+use axum::Json;
+use axum::extract::{Path, State};
+use be_auth_core::AuthUser;
+use be_remote_db::MessageType;
+use thread_core::{
+    DeleteWorkflowResponse, ListWorkflowTemplatesResponse, ListWorkflowsResponse,
+    RunWorkflowRequest, RunWorkflowResponse, SaveWorkflowRequest, WorkflowResponse,
+    WorkflowStepOutput,
+};
+use tokio::time::Duration;
+use uuid::Uuid;
+
+use crate::conversion::{db_workflow_to_wire, workflow_template_to_wire};
+use crate::error::{ThreadServiceError, ThreadServiceResult};
+use crate::service::AppState;
+use crate::webhooks;
+
+/// Matches the `workflows.name` column.
+const MAX_NAME_CHARS: usize = 200;
+
+/// Wall-clock budget for one run. The request is held open until the run
+/// finishes, so a stuck step fails the request instead of hanging it.
+const RUN_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+#[tracing::instrument(skip(_user))]
+pub async fn list_workflow_templates(
+    _user: AuthUser,
+) -> ThreadServiceResult<Json<ListWorkflowTemplatesResponse>> {
+    Ok(Json(ListWorkflowTemplatesResponse {
+        templates: builtin_templates()
+            .into_iter()
+            .map(workflow_template_to_wire)
+            .collect(),
+    }))
+}
+
+#[tracing::instrument(skip(state, user))]
+pub async fn list_workflows(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+) -> ThreadServiceResult<Json<ListWorkflowsResponse>> {
+    let user_id = user.user_id()?;
+
+    let workflows = state.db.list_workflows().user_id(user_id).call().await?;
+
+    Ok(Json(ListWorkflowsResponse {
+        workflows: workflows.into_iter().map(db_workflow_to_wire).collect(),
+    }))
+}
+
+#[tracing::instrument(skip(state, user, body))]
+pub async fn create_workflow(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Json(body): Json<SaveWorkflowRequest>,
+) -> ThreadServiceResult<Json<WorkflowResponse>> {
+    let user_id = user.user_id()?;
+    let definition = parse_definition(body.definition)?;
+
+    let workflow = state
+        .db
+        .create_workflow()
+        .user_id(user_id)
+        .name(definition.name.clone())
+        .description(definition.description.clone())
+        .definition(definition_to_value(&definition)?)
+        .call()
+        .await?;
+
+    tracing::info!("Created workflow {}", workflow.id);
+
+    Ok(Json(WorkflowResponse {
+        workflow: db_workflow_to_wire(workflow),
+    }))
+}
+
+#[tracing::instrument(skip(state, user), fields(workflow_id = %workflow_id))]
+pub async fn get_workflow(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(workflow_id): Path<Uuid>,
+) -> ThreadServiceResult<Json<WorkflowResponse>> {
+    let user_id = user.user_id()?;
+
+    let workflow = state
+        .db
+        .get_workflow()
+        .id(workflow_id)
+        .user_id(user_id)
+        .call()
+        .await?;
+
+    Ok(Json(WorkflowResponse {
+        workflow: db_workflow_to_wire(workflow),
+    }))
+}
+
+#[tracing::instrument(skip(state, user, body), fields(workflow_id = %workflow_id))]
+pub async fn update_workflow(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(workflow_id): Path<Uuid>,
+    Json(body): Json<SaveWorkflowRequest>,
+) -> ThreadServiceResult<Json<WorkflowResponse>> {
+    let user_id = user.user_id()?;
+    let definition = parse_definition(body.definition)?;
+
+    let workflow = state
+        .db
+        .update_workflow()
+        .id(workflow_id)
+        .user_id(user_id)
+        .name(definition.name.clone())
+        .description(definition.description.clone())
+        .definition(definition_to_value(&definition)?)
+        .call()
+        .await?;
+
+    Ok(Json(WorkflowResponse {
+        workflow: db_workflow_to_wire(workflow),
+    }))
+}
+
+#[tracing::instrument(skip(state, user), fields(workflow_id = %workflow_id))]
+pub async fn delete_workflow(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(workflow_id): Path<Uuid>,
+) -> ThreadServiceResult<Json<DeleteWorkflowResponse>> {
+    let user_id = user.user_id()?;
+
+    state
+        .db
+        .delete_workflow()
+        .id(workflow_id)
+        .user_id(user_id)
+        .call()
+        .await?;
+
+    tracing::info!("Deleted workflow {}", workflow_id);
+
+    Ok(Json(DeleteWorkflowResponse {}))
+}
+
+/// Token-gated like the chat socket, since it spends tokens on the user's
+/// behalf.
+#[tracing::instrument(skip(state, user, body), fields(workflow_id = %workflow_id))]
+pub async fn run_workflow(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(workflow_id): Path<Uuid>,
+    Json(body): Json<RunWorkflowRequest>,
+) -> ThreadServiceResult<Json<RunWorkflowResponse>> {
+    let user_id = user.user_id()?;
+
+    let workflow = state
+        .db
+        .get_workflow()
+        .id(workflow_id)
+        .user_id(user_id)
+        .call()
+        .await?;
+    let definition = serde_json::from_value(workflow.definition).map_err(|e| {
+        ThreadServiceError::Internal(format!("Stored workflow no longer parses: {e}"))
+    })?;
+
+    Ok(Json(
+        execute(&state, user_id, definition, body.params).await?,
+    ))
+}
+
+/// Run a built-in template without saving it first. Token-gated like
+/// [`run_workflow`].
+#[tracing::instrument(skip(state, user, body), fields(template_id = %template_id))]
+pub async fn run_workflow_template(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(template_id): Path<String>,
+    Json(body): Json<RunWorkflowRequest>,
+) -> ThreadServiceResult<Json<RunWorkflowResponse>> {
+    let user_id = user.user_id()?;
+
+    let definition = builtin_templates()
+        .into_iter()
+        .find(|template| template.id == template_id)
+        .ok_or_else(|| ThreadServiceError::not_found(format!("workflow template {template_id}")))?;
+
+    Ok(Json(
+        execute(&state, user_id, definition, body.params).await?,
+    ))
+}
+
+/// Compile and run `definition`, recording the run as a new thread.
+async fn execute(
+    state: &AppState,
+    user_id: Uuid,
+    definition: WorkflowDefinition,
+    params: HashMap<String, String>,
+) -> ThreadServiceResult<RunWorkflowResponse> {
+    let tools = if state.web_access_enabled(user_id).await {
+        state.providers.web_tools.clone()
+    } else {
+        Vec::new()
+    };
+    let workflow = CompiledWorkflow::compile(
+        definition,
+        WorkflowRuntime::new(state.providers.chat.clone(), tools),
+    )
+    .map_err(workflow_error)?;
+    // Checked before the thread exists so bad input doesn't leave an empty
+    // thread behind.
+    let params = workflow.resolve_params(params).map_err(workflow_error)?;
+
+    let name = workflow.definition().name.clone();
+    let thread = state
+        .db
+        .create_thread()
+        .user_id(user_id)
+        .title(name.clone())
+        .call()
+        .await?;
+    webhooks::thread_created(&state.db, &thread).await;
+    let human_message = HumanMessage::builder()
+        .content(invocation_text(&name, &params))
+        .build();
+    let content = serde_json::to_value(&human_message.content)
+        .map_err(|e| ThreadServiceError::Internal(format!("Failed to serialize content: {e}")))?;
+    state
+        .db
+        .create_message()
+        .thread_id(thread.id)
+        .user_id(user_id)
+        .message_type(MessageType::Human)
+        .content(content)
+        .call()
+        .await?;
+
+    let run = tokio::time::timeout(RUN_TIMEOUT, workflow.run(params))
+        .await
+        .map_err(|_| ThreadServiceError::Internal("Workflow run timed out".to_string()))?
+        .map_err(workflow_error)?;
+    tracing::info!(thread_id = %thread.id, steps = run.steps.len(), "Workflow run finished");
+
+    let ai_message = state
+        .db
+        .create_message()
+        .thread_id(thread.id)
+        .user_id(user_id)
+        .message_type(MessageType::Ai)
+        .content(serde_json::json!([{ "type": "text", "text": run.output }]))
+        .call()
+        .await?;
+
+    if let Some(usage) = run
+        .usage
+        .as_ref()
+        .filter(|u| u.input_tokens > 0 || u.output_tokens > 0)
+        && let Err(e) = state
+            .db
+            .record_token_usage()
+            .user_id(user_id)
+            .thread_id(thread.id)
+            .message_id(ai_message.id)
+            .input_tokens(usage.input_tokens)
+            .output_tokens(usage.output_tokens)
+            .maybe_reasoning_tokens(
+                usage
+                    .output_token_details
+                    .as_ref()
+                    .and_then(|d| d.reasoning),
+            )
+            .maybe_cache_creation_tokens(
+                usage
+                    .input_token_details
+                    .as_ref()
+                    .and_then(|d| d.cache_creation),
+            )
+            .maybe_cache_read_tokens(
+                usage
+                    .input_token_details
+                    .as_ref()
+                    .and_then(|d| d.cache_read),
+            )
+            .call()
+            .await
+    {
+        tracing::error!("Failed to record token usage: {e}");
+    }
+
+    Ok(RunWorkflowResponse {
+        thread_id: thread.id,
+        output: run.output,
+        steps: run
+            .steps
+            .into_iter()
+            .map(|step| WorkflowStepOutput {
+                id: step.id,
+                output: step.output,
+            })
+            .collect(),
+    })
+}
+
+fn parse_definition(value: serde_json::Value) -> ThreadServiceResult<WorkflowDefinition> {
+    let mut definition: WorkflowDefinition = serde_json::from_value(value)
+        .map_err(|e| ThreadServiceError::invalid_argument(format!("invalid workflow: {e}")))?;
+    definition.validate().map_err(workflow_error)?;
+
+    definition.name = definition.name.trim().to_string();
+    if definition.name.is_empty() {
+        return Err(ThreadServiceError::invalid_argument(
+            "name must not be empty",
+        ));
+    }
+    if definition.name.chars().count() > MAX_NAME_CHARS {
+        return Err(ThreadServiceError::invalid_argument(format!(
+            "name must be at most {MAX_NAME_CHARS} characters"
+        )));
+    }
+    Ok(definition)
+}
+
+fn definition_to_value(definition: &WorkflowDefinition) -> ThreadServiceResult<serde_json::Value> {
+    serde_json::to_value(definition)
+        .map_err(|e| ThreadServiceError::Internal(format!("Failed to serialize workflow: {e}")))
+}
+
+/// Problems with the definition or the caller's input are the caller's to
+/// fix; a step that failed at runtime is ours.
+fn workflow_error(error: WorkflowError) -> ThreadServiceError {
+    match error {
+        WorkflowError::StepFailed { .. } => ThreadServiceError::Internal(error.to_string()),
+        _ => ThreadServiceError::invalid_argument(error.to_string()),
+    }
+}
+
+/// Text of the human message that opens a run's thread.
+fn invocation_text(name: &str, params: &HashMap<String, String>) -> String {
+    let mut text = format!("Run workflow \"{name}\"");
+    let mut params: Vec<_> = params.iter().filter(|(_, v)| !v.is_empty()).collect();
+    params.sort();
+    for (key, value) in params {
+        text.push_str(&format!("\n\n{key}:\n{value}"));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_definition_rejects_invalid_workflows() {
+        let err = parse_definition(serde_json::json!({ "name": "No steps" })).unwrap_err();
+        assert!(matches!(err, ThreadServiceError::InvalidArgument(_)));
+
+        let err = parse_definition(serde_json::json!({
+            "id": "blank",
+            "name": "   ",
+            "steps": [{ "id": "a", "type": "prompt", "prompt": "hi" }],
+        }))
+        .unwrap_err();
+        assert!(matches!(err, ThreadServiceError::InvalidArgument(_)));
+    }
+
+    #[test]
+    fn builtin_templates_pass_save_validation() {
+        for template in builtin_templates() {
+            let value = serde_json::to_value(&template).unwrap();
+            assert_eq!(parse_definition(value).unwrap(), template);
+        }
+    }
+
+    #[test]
+    fn invocation_text_lists_non_empty_params_in_order() {
+        let params = HashMap::from([
+            ("topic".to_string(), "rust".to_string()),
+            ("focus".to_string(), String::new()),
+            ("audience".to_string(), "beginners".to_string()),
+        ]);
+        assert_eq!(
+            invocation_text("Explain", &params),
+            "Run workflow \"Explain\"\n\naudience:\nbeginners\n\ntopic:\nrust"
+        );
+    }
+}
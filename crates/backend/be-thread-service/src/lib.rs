@@ -1,30 +1,64 @@
 //! HTTP + WebSocket thread service.
 //!
-//! Exposes an Axum router under `/threads` for CRUD, message-tree, and
-//! search endpoints, plus a WebSocket upgrade at `/threads/{id}/chat` for
+//! Exposes an Axum router under `/threads` for CRUD, folder, message-tree,
+//! and search endpoints, plus a WebSocket upgrade at `/threads/{id}/chat` for
 //! streaming chat. Authentication and Casbin authorization are applied by
 //! the surrounding `be-authz` middleware in `be-monolith`; this crate only
 //! assumes that a verified [`be_auth_core::Claims`] has been inserted into
 //! request extensions by the time a handler runs.
 //!
-//! Token gating for the two cost-bearing endpoints (`POST /threads/{id}/title`
-//! and the chat WebSocket) is also enforced by `be-authz` ahead of dispatch
-//! — handlers in this crate trust that gating has already passed.
+//! A turn opened with a `request_id` can also be stopped from outside its
+//! socket with `POST /threads/{id}/chat/{request_id}/cancel`.
+//!
+//! Token gating for the cost-bearing endpoints (`POST /threads/{id}/title`,
+//! `POST /automations/{id}/run`, the workflow run endpoints,
+//! `POST /reports`, and the chat WebSocket) is also enforced by `be-authz`
+//! ahead of dispatch — handlers in this crate trust that gating has
+//! already passed.
+//!
+//! The facts the assistant remembers about the user are reviewed and
+//! edited under `/memories`.
+//!
+//! Scheduled automations live under `/automations`; the background
+//! [`SchedulerHandle`] worker runs them when they come due. Workflow
+//! templates and saved workflows live under `/workflows` and run inline.
+//!
+//! `POST /reports` renders a Markdown template against the user's activity
+//! timeline in a range and stores the result as a Markdown or PDF asset;
+//! the same generator is offered to the assistant as the `generate_report`
+//! tool.
+//!
+//! `/models` lists the models the deployment's gateway providers
+//! (OpenRouter, other OpenAI-compatible servers) offer, with prices where
+//! the gateway reports them, for picking a thread's model.
+//!
+//! In local mode (`EURORA_OLLAMA_URL` set) `/models/local` lists, pulls
+//! and deletes the models on the deployment's Ollama server for the
+//! desktop model picker; elsewhere those routes are `404`. Pulling and
+//! deleting are for admins only, since the models are shared.
 
+mod active_turns;
 mod agent_loop;
+mod citations;
 mod conversion;
 mod describe_image_tool;
 mod error;
 mod glm_xml_tool_calls;
 mod handlers;
 mod llm;
+mod memory;
 mod message_projection;
 mod preliminary;
 mod remote_tool_bus;
+mod report;
+mod schedule;
+mod scheduler;
 mod service;
 mod title;
 mod tool_catalog;
 mod tools;
+mod untrusted;
+mod webhooks;
 
 #[cfg(test)]
 mod test_support;
@@ -32,7 +66,7 @@ mod test_support;
 use std::sync::Arc;
 
 use axum::Router;
-use axum::routing::{get, post};
+use axum::routing::{get, patch, post};
 use be_asset::AssetService;
 use be_remote_db::DatabaseManager;
 use llm_core::LlmConfig;
@@ -40,6 +74,7 @@ use tower_http::trace::TraceLayer;
 
 pub use error::{ThreadServiceError, ThreadServiceResult};
 pub use llm::BuildError;
+pub use scheduler::SchedulerHandle;
 pub use service::AppState;
 
 /// Build the thread router with the supplied dependencies.
@@ -59,12 +94,22 @@ pub fn create_router(state: Arc<AppState>) -> Router {
         )
         .route(
             "/threads/{thread_id}",
-            get(handlers::threads::get_thread).delete(handlers::threads::delete_thread),
+            get(handlers::threads::get_thread)
+                .patch(handlers::threads::update_thread)
+                .delete(handlers::threads::delete_thread),
+        )
+        .route(
+            "/threads/{thread_id}/folder",
+            post(handlers::threads::move_thread),
         )
         .route(
             "/threads/{thread_id}/title",
             post(handlers::threads::generate_thread_title),
         )
+        .route(
+            "/threads/{thread_id}/model",
+            post(handlers::threads::set_thread_model),
+        )
         .route(
             "/threads/{thread_id}/messages",
             get(handlers::messages::get_messages),
@@ -74,27 +119,117 @@ pub fn create_router(state: Arc<AppState>) -> Router {
             post(handlers::messages::switch_branch),
         )
         .route("/threads/{thread_id}/chat", get(handlers::chat::chat_ws))
+        .route(
+            "/threads/{thread_id}/chat/{request_id}/cancel",
+            post(handlers::chat::cancel_chat),
+        )
         .route("/threads/search", get(handlers::search::search_threads))
+        .route(
+            "/threads/folders",
+            get(handlers::folders::list_thread_folders)
+                .post(handlers::folders::create_thread_folder),
+        )
+        .route(
+            "/threads/folders/{folder_id}",
+            patch(handlers::folders::update_thread_folder)
+                .delete(handlers::folders::delete_thread_folder),
+        )
         .route(
             "/threads/messages/search",
             get(handlers::search::search_messages),
         )
+        .route(
+            "/memories",
+            get(handlers::memories::list_memories)
+                .post(handlers::memories::create_memory)
+                .delete(handlers::memories::delete_all_memories),
+        )
+        .route(
+            "/memories/{memory_id}",
+            patch(handlers::memories::update_memory).delete(handlers::memories::delete_memory),
+        )
+        .route(
+            "/automations",
+            post(handlers::automations::create_automation)
+                .get(handlers::automations::list_automations),
+        )
+        .route(
+            "/automations/tools",
+            get(handlers::automations::list_automation_tools),
+        )
+        .route(
+            "/automations/{automation_id}",
+            get(handlers::automations::get_automation)
+                .patch(handlers::automations::update_automation)
+                .delete(handlers::automations::delete_automation),
+        )
+        .route(
+            "/automations/{automation_id}/runs",
+            get(handlers::automations::list_automation_runs),
+        )
+        .route(
+            "/automations/{automation_id}/run",
+            post(handlers::automations::run_automation),
+        )
+        .route(
+            "/workflows",
+            post(handlers::workflows::create_workflow).get(handlers::workflows::list_workflows),
+        )
+        .route(
+            "/workflows/templates",
+            get(handlers::workflows::list_workflow_templates),
+        )
+        .route(
+            "/workflows/templates/{template_id}/run",
+            post(handlers::workflows::run_workflow_template),
+        )
+        .route(
+            "/workflows/{workflow_id}",
+            get(handlers::workflows::get_workflow)
+                .put(handlers::workflows::update_workflow)
+                .delete(handlers::workflows::delete_workflow),
+        )
+        .route(
+            "/workflows/{workflow_id}/run",
+            post(handlers::workflows::run_workflow),
+        )
+        .route("/reports", post(handlers::reports::generate_report))
+        .route("/models", get(handlers::models::list_provider_models))
+        .route(
+            "/models/local",
+            get(handlers::local_models::list_local_models)
+                .delete(handlers::local_models::delete_local_model),
+        )
+        .route(
+            "/models/local/pull",
+            post(handlers::local_models::pull_local_model),
+        )
         .layer(TraceLayer::new_for_http())
         .with_state(state)
 }
 
-/// Wire up application state and return the router ready to merge into the
-/// monolith HTTP pipeline.
+pub struct ThreadService {
+    pub router: Router,
+    pub scheduler: SchedulerHandle,
+}
+
+/// Wire up application state, start the automation scheduler, and return
+/// the router ready to merge into the monolith HTTP pipeline.
 ///
 /// `llm_config` carries the resolved [`LlmConfig`]; the caller is expected
 /// to load it once at startup (typically via [`LlmConfig::from_env`]) and
-/// share it across services.
+/// share it across services. The caller owns the returned
+/// [`SchedulerHandle`] and should shut it down after the server stops.
 pub fn init_thread_service(
     db: Arc<DatabaseManager>,
     asset_service: Arc<AssetService>,
     llm_config: Arc<LlmConfig>,
-) -> Result<Router, BuildError> {
+) -> Result<ThreadService, BuildError> {
     tracing::debug!("Initializing thread service");
     let state = Arc::new(AppState::try_new(db, asset_service, llm_config)?);
-    Ok(create_router(state))
+    let scheduler = scheduler::spawn_scheduler(state.clone());
+    Ok(ThreadService {
+        router: create_router(state),
+        scheduler,
+    })
 }
@@ -2,7 +2,9 @@ use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
 use agent_chain::language_models::ToolLike;
-use agent_chain::messages::ContentBlock;
+use agent_chain::messages::{
+    ContentBlock, FileContentBlock, PlainTextContentBlock, TextContentBlock,
+};
 use agent_chain::{AnyMessage, BaseChatModel, BaseTool, SystemMessage};
 use base64::{Engine as _, engine::general_purpose};
 use be_asset::AssetService;
@@ -10,32 +12,48 @@ use be_storage::StorageService;
 use futures::stream::{self, StreamExt};
 use thread_core::{WireActiveContext, WireToolDescriptor};
 
+use crate::citations::{SourceIndex, index_sources};
 use crate::describe_image_tool::{self, DescribeImageTool};
 use crate::error::ThreadServiceError;
 use crate::llm::Providers;
 use crate::llm::openai_schema;
 use crate::message_projection::{collect_thread_images, project_for_text_llm};
+use crate::preliminary;
 use crate::tool_catalog::{
     TurnCatalog, build_context_system_message, build_prelude_system_message,
 };
+use crate::untrusted;
 
 /// Per-turn LLM context: the messages to invoke the model with, the bound
-/// model itself, and the unified tool catalog the agent loop will dispatch
-/// from.
+/// model itself, the unified tool catalog the agent loop will dispatch
+/// from, and the source passages the answer may cite.
 pub struct LlmContext {
     pub messages: Vec<AnyMessage>,
     pub chat_model: Arc<dyn BaseChatModel + Send + Sync>,
     pub catalog: Arc<TurnCatalog>,
+    pub sources: SourceIndex,
 }
 
 /// Build the per-turn LLM context.
 ///
+/// Document attachments (`File` blocks) are always read into text first:
+/// PDFs through `pdf-core`, text formats as-is, so every model sees their
+/// contents whether or not its provider accepts files. Source text is then
+/// split into citable passages (see [`crate::citations`]). The prelude and
+/// tool results are outside content and reach the model delimited as
+/// such (see [`crate::untrusted`]).
+///
 /// In text-only mode (no vision provider configured) we resolve every
 /// referenced asset inline into the message blocks and hand the chat model a
 /// vanilla message history. In vision mode we instead leave images referenced
 /// by id, register a `describe_image` tool that the model can call to inspect
 /// them lazily, and prepend a system prompt teaching the model how to use it.
 ///
+/// `chat` is the model to bind: the deployment's chat role, or the
+/// model the thread is pinned to.
+///
+/// `server_tools` are extra server-local tools for this turn on top of the
+/// vision defaults (today the web tools, when the user allows them).
 /// `remote_descriptors` are the tool descriptors the client advertised in
 /// its `CapabilityUpdate` frame, `active_contexts` are the structured
 /// contexts the client said are live, and `prelude_blocks` is the
@@ -48,10 +66,13 @@ pub struct LlmContext {
 /// prelude first (what the user is doing) and the contexts second (which
 /// tools are pinned to what) so the model reads context before tool
 /// guidance.
+#[allow(clippy::too_many_arguments)]
 pub async fn prepare_llm_context(
     providers: &Providers,
+    chat: &Arc<dyn BaseChatModel + Send + Sync>,
     asset_service: &Arc<AssetService>,
     mut messages: Vec<AnyMessage>,
+    server_tools: Vec<Arc<dyn BaseTool>>,
     remote_descriptors: Vec<WireToolDescriptor>,
     active_contexts: &[WireActiveContext],
     prelude_blocks: Vec<ContentBlock>,
@@ -64,26 +85,38 @@ pub async fn prepare_llm_context(
     if let Some(system_message) = build_context_system_message(active_contexts) {
         messages.insert(0, system_message.into());
     }
-    if let Some(prelude_message) = build_prelude_system_message(prelude_blocks) {
+    let prelude = build_prelude_system_message(prelude_blocks);
+    let has_prelude = prelude.is_some();
+    if let Some(prelude_message) = prelude {
         messages.insert(0, prelude_message.into());
     }
 
     resolve_blocks::<PlainTextBlock>(asset_service, &mut messages).await;
+    resolve_blocks::<DocumentBlock>(asset_service, &mut messages).await;
+    // The prelude is still at the head: it's page content the client
+    // read, so it goes to the model delimited as untrusted.
+    if has_prelude && let Some(AnyMessage::SystemMessage(prelude)) = messages.first_mut() {
+        untrusted::guard_blocks("activity", &mut prelude.content);
+    }
+    let sources = index_sources(&mut messages);
 
     let Some(vision) = providers.vision.as_ref() else {
         resolve_blocks::<ImageBlock>(asset_service, &mut messages).await;
-        let catalog = build_catalog(Vec::new(), remote_descriptors, active_contexts)?;
-        let chat_model = bind_chat_model(&providers.chat, &catalog)?;
+        let catalog = build_catalog(server_tools, remote_descriptors, active_contexts)?;
+        let chat_model = bind_chat_model(chat, &catalog)?;
+        guard_outside_content(&mut messages, has_prelude, &catalog);
         return Ok(LlmContext {
             messages,
             chat_model,
             catalog,
+            sources,
         });
     };
 
     let allowed_images = collect_thread_images(&messages);
 
     let mut server_local: Vec<Arc<dyn BaseTool>> = vision.default_tools.to_vec();
+    server_local.extend(server_tools);
 
     if !allowed_images.is_empty() {
         let describe = Arc::new(DescribeImageTool::new(
@@ -95,7 +128,8 @@ pub async fn prepare_llm_context(
     }
 
     let catalog = build_catalog(server_local, remote_descriptors, active_contexts)?;
-    let chat_model = bind_chat_model(&providers.chat, &catalog)?;
+    let chat_model = bind_chat_model(chat, &catalog)?;
+    guard_outside_content(&mut messages, has_prelude, &catalog);
 
     project_for_text_llm(&mut messages);
 
@@ -125,9 +159,18 @@ pub async fn prepare_llm_context(
         messages,
         chat_model,
         catalog,
+        sources,
     })
 }
 
+/// Prepend the rule for delimited outside content when the turn can carry
+/// any: the activity prelude, or the result of a tool call.
+fn guard_outside_content(messages: &mut Vec<AnyMessage>, has_prelude: bool, catalog: &TurnCatalog) {
+    if has_prelude || !catalog.is_empty() {
+        messages.insert(0, untrusted::guard_system_message().into());
+    }
+}
+
 fn build_catalog(
     server_local: Vec<Arc<dyn BaseTool>>,
     remote: Vec<WireToolDescriptor>,
@@ -231,6 +274,74 @@ impl ResolvableBlock for ImageBlock {
     }
 }
 
+/// A `File` attachment, replaced by its text once downloaded.
+struct DocumentBlock;
+impl ResolvableBlock for DocumentBlock {
+    const LABEL: &'static str = "document";
+
+    fn pending_url(block: &ContentBlock) -> Option<&str> {
+        let ContentBlock::File(file) = block else {
+            return None;
+        };
+        if file.base64.is_some() {
+            return None;
+        }
+        file.url.as_deref()
+    }
+
+    fn apply(block: &mut ContentBlock, bytes: &[u8]) {
+        if let ContentBlock::File(file) = block {
+            *block = document_to_text(file, bytes);
+        }
+    }
+}
+
+/// The model-facing form of a document attachment: its text as a
+/// `PlainText` block, or a short note when there's no text to give.
+fn document_to_text(file: &FileContentBlock, bytes: &[u8]) -> ContentBlock {
+    let name = preliminary::filename(&file.extras).unwrap_or("attachment");
+    let mime = file
+        .mime_type
+        .as_deref()
+        .unwrap_or("application/octet-stream");
+
+    let text = match mime {
+        "application/pdf" => match pdf_core::parse_bytes(bytes) {
+            Ok(parsed) if parsed.has_text() => parsed.markdown,
+            Ok(_) => None,
+            Err(e) => {
+                tracing::warn!("Failed to read PDF attachment {name}: {e}");
+                None
+            }
+        },
+        "application/json" => Some(String::from_utf8_lossy(bytes).into_owned()),
+        mime if mime.starts_with("text/") => Some(String::from_utf8_lossy(bytes).into_owned()),
+        _ => None,
+    };
+
+    match text {
+        Some(text) => ContentBlock::PlainText(
+            PlainTextContentBlock::builder()
+                .mime_type(if mime == "application/pdf" {
+                    "text/markdown".to_string()
+                } else {
+                    mime.to_string()
+                })
+                .text(text)
+                .title(name.to_string())
+                .maybe_file_id(file.file_id.clone())
+                .build(),
+        ),
+        None => ContentBlock::Text(
+            TextContentBlock::builder()
+                .text(format!(
+                    "[attached file `{name}` ({mime}) — its text could not be extracted]"
+                ))
+                .build(),
+        ),
+    }
+}
+
 async fn resolve_blocks<B: ResolvableBlock>(
     asset_service: &AssetService,
     messages: &mut [AnyMessage],
@@ -297,3 +408,44 @@ async fn download_many(
         .collect()
         .await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(mime: &str) -> FileContentBlock {
+        FileContentBlock::builder()
+            .file_id("asset".to_string())
+            .url("users/u/asset".to_string())
+            .mime_type(mime.to_string())
+            .extras(HashMap::from([(
+                preliminary::FILENAME_KEY.to_string(),
+                serde_json::Value::String("notes.md".to_string()),
+            )]))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn text_documents_become_titled_plain_text() {
+        let block = document_to_text(&document("text/markdown"), b"# Notes");
+        let ContentBlock::PlainText(plain) = block else {
+            panic!("expected plain text");
+        };
+        assert_eq!(plain.text.as_deref(), Some("# Notes"));
+        assert_eq!(plain.title.as_deref(), Some("notes.md"));
+        assert_eq!(plain.file_id.as_deref(), Some("asset"));
+    }
+
+    #[test]
+    fn unreadable_documents_become_a_note() {
+        let pdf = document_to_text(&document("application/pdf"), b"not a pdf");
+        let binary = document_to_text(&document("application/octet-stream"), &[0, 1, 2]);
+        for block in [pdf, binary] {
+            let ContentBlock::Text(note) = block else {
+                panic!("expected a text note");
+            };
+            assert!(note.text.contains("notes.md"));
+        }
+    }
+}
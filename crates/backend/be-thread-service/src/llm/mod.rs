@@ -3,4 +3,7 @@ mod openai_schema;
 mod providers;
 
 pub use context::{LlmContext, prepare_llm_context};
-pub use providers::{BuildError, Providers, build_providers};
+pub use providers::{
+    BuildError, Providers, build_providers, build_thread_chat_model, local_models_from_env,
+    model_catalogs,
+};
@@ -13,12 +13,20 @@
 //! so this only fires for future config-file paths.
 use std::sync::Arc;
 
-use agent_chain::{BaseChatModel, BaseTool, openai::ChatOpenAI};
-use llm_core::{LlmConfig, ModelRef, Provider, ProviderId};
+use agent_chain::ollama::OllamaModels;
+use agent_chain::openai::{ChatOpenAI, OpenAiModels};
+use agent_chain::web::{WebTools, engine_from_env};
+use agent_chain::{BaseChatModel, BaseTool};
+use llm_core::{DEFAULT_OPENROUTER_BASE_URL, LlmConfig, ModelRef, Provider, ProviderId};
 use secrecy::ExposeSecret;
+use thread_core::ThreadModel;
 
 use crate::tools::firecrawl_tools;
 
+/// Native API base of the Ollama server whose models `/models/local`
+/// manages, e.g. `http://localhost:11434` (no `/v1`).
+const ENV_OLLAMA_URL: &str = "EURORA_OLLAMA_URL";
+
 /// Errors raised while turning [`LlmConfig`] into a concrete [`Providers`].
 #[derive(Debug, thiserror::Error)]
 pub enum BuildError {
@@ -33,18 +41,15 @@ pub enum BuildError {
          wired in be-thread-service"
     )]
     KindNotYetWired { kind: &'static str },
-
-    #[error(
-        "provider `{provider}` carries non-empty `headers` or `overrides`, which are not yet \
-         honoured by the underlying agent-chain OpenAI client"
-    )]
-    UnsupportedFeature { provider: ProviderId },
 }
 
 pub struct Providers {
     pub chat: Arc<dyn BaseChatModel + Send + Sync>,
     pub title: Arc<dyn BaseChatModel + Send + Sync>,
     pub vision: Option<VisionConfig>,
+    /// `fetch_url`, plus `web_search` when a search engine is configured.
+    /// Offered on every turn of users who haven't turned web access off.
+    pub web_tools: Vec<Arc<dyn BaseTool>>,
 }
 
 pub struct VisionConfig {
@@ -58,12 +63,16 @@ pub struct VisionConfig {
 /// a `FIRECRAWL_API_KEY` env var set — without the key the tools would fail
 /// on every call, so we'd rather hand the model a tool-less context than
 /// pretend the tools work.
+///
+/// Web tools are built independently of the roles: `fetch_url` needs no
+/// configuration and `web_search` uses whichever engine
+/// [`engine_from_env`] finds.
 pub fn build_providers(cfg: &LlmConfig) -> Result<Providers, BuildError> {
-    let chat = build_chat_model(cfg, "chat", &cfg.roles.chat)?;
-    let title = build_chat_model(cfg, "title", &cfg.roles.title)?;
+    let chat = build_chat_model(cfg, "chat", &cfg.roles.chat, None)?;
+    let title = build_chat_model(cfg, "title", &cfg.roles.title, None)?;
     let vision = match cfg.roles.vision.as_ref() {
         Some(role) => {
-            let model = build_chat_model(cfg, "vision", role)?;
+            let model = build_chat_model(cfg, "vision", role, None)?;
             let default_tools = if std::env::var("FIRECRAWL_API_KEY").is_ok_and(|v| !v.is_empty()) {
                 firecrawl_tools()
             } else {
@@ -85,13 +94,101 @@ pub fn build_providers(cfg: &LlmConfig) -> Result<Providers, BuildError> {
         chat,
         title,
         vision,
+        web_tools: build_web_tools(),
     })
 }
 
+/// Chat model for a thread pinned to [`ThreadModel`], resolved against
+/// the same provider map as the deployment's roles. Built per turn: the
+/// clients are cheap, and caching them would outlive a changed pin.
+pub fn build_thread_chat_model(
+    cfg: &LlmConfig,
+    model: &ThreadModel,
+) -> Result<Arc<dyn BaseChatModel + Send + Sync>, BuildError> {
+    let model_ref = ModelRef {
+        provider: model.provider.clone(),
+        model: model.model.clone(),
+    };
+    build_chat_model(cfg, "thread", &model_ref, model.temperature.map(f64::from))
+}
+
+/// Client for the local-mode model store, when `EURORA_OLLAMA_URL` is set.
+///
+/// Separate from the chat roles on purpose: they reach Ollama through its
+/// OpenAI-compatible shim, and the operator opts in to letting users pull
+/// and delete models on the server by naming it here.
+pub fn local_models_from_env() -> Option<OllamaModels> {
+    let url = std::env::var(ENV_OLLAMA_URL)
+        .ok()
+        .filter(|v| !v.trim().is_empty())?;
+    let models = OllamaModels::new(Some(url.trim()));
+    tracing::info!(
+        base_url = models.base_url(),
+        "Local model management enabled"
+    );
+    Some(models)
+}
+
+/// Model-list clients for the providers that serve `GET /models` to
+/// their users: OpenRouter and other OpenAI-compatible gateways. OpenAI
+/// itself and Azure are left out; their lists are the account's, not a
+/// catalogue to pick from. Sorted by provider id.
+pub fn model_catalogs(cfg: &LlmConfig) -> Vec<(ProviderId, OpenAiModels)> {
+    let mut catalogs: Vec<(ProviderId, OpenAiModels)> = cfg
+        .providers
+        .iter()
+        .filter_map(|(id, provider)| {
+            let client = match provider {
+                Provider::OpenRouter {
+                    base_url,
+                    api_key,
+                    headers,
+                } => OpenAiModels::new(
+                    base_url
+                        .as_ref()
+                        .map_or(DEFAULT_OPENROUTER_BASE_URL, |u| u.as_str()),
+                    Some(api_key.expose_secret()),
+                    headers,
+                ),
+                Provider::OpenAiCompatible {
+                    base_url,
+                    api_key,
+                    headers,
+                    ..
+                } => OpenAiModels::new(
+                    base_url.as_str(),
+                    api_key.as_ref().map(|k| k.expose_secret()),
+                    headers,
+                ),
+                _ => return None,
+            };
+            Some((id.clone(), client))
+        })
+        .collect();
+    catalogs.sort_by(|a, b| a.0.cmp(&b.0));
+    catalogs
+}
+
+fn build_web_tools() -> Vec<Arc<dyn BaseTool>> {
+    let engine = engine_from_env();
+    if engine.is_none() {
+        tracing::info!(
+            "No web search engine configured (SEARXNG_URL, BRAVE_SEARCH_API_KEY or \
+             BING_SEARCH_API_KEY) — offering fetch_url only"
+        );
+    }
+    let web = Arc::new(WebTools::builder().maybe_engine(engine).build());
+    web.tools()
+        .into_iter()
+        .map(|tool| Arc::new(tool) as Arc<dyn BaseTool>)
+        .collect()
+}
+
 fn build_chat_model(
     cfg: &LlmConfig,
     role: &'static str,
     model_ref: &ModelRef,
+    temperature: Option<f64>,
 ) -> Result<Arc<dyn BaseChatModel + Send + Sync>, BuildError> {
     let provider =
         cfg.providers
@@ -101,6 +198,8 @@ fn build_chat_model(
                 provider: model_ref.provider.clone(),
             })?;
 
+    // Every OpenAI-shaped client asks for usage on the streamed final
+    // chunk (`stream_usage`): without it streamed turns report no tokens.
     match provider {
         Provider::OpenAI {
             api_key,
@@ -112,6 +211,45 @@ fn build_chat_model(
                 .api_key(api_key.expose_secret().to_string())
                 .maybe_api_base(base_url.as_ref().map(|u| u.as_str().to_string()))
                 .maybe_organization(organization.clone())
+                .maybe_temperature(temperature)
+                .stream_usage(true)
+                .build();
+            Ok(Arc::new(model))
+        }
+        Provider::AzureOpenAI {
+            endpoint,
+            api_key,
+            api_version,
+            headers,
+        } => {
+            let model = ChatOpenAI::builder()
+                .model(model_ref.model.clone())
+                .api_base(endpoint.as_str().to_string())
+                .azure_api_version(api_version.clone())
+                .api_key(api_key.expose_secret().to_string())
+                .default_headers(headers.clone())
+                .maybe_temperature(temperature)
+                .stream_usage(true)
+                .build();
+            Ok(Arc::new(model))
+        }
+        Provider::OpenRouter {
+            base_url,
+            api_key,
+            headers,
+        } => {
+            let model = ChatOpenAI::builder()
+                .model(model_ref.model.clone())
+                .api_base(
+                    base_url
+                        .as_ref()
+                        .map_or(DEFAULT_OPENROUTER_BASE_URL, |u| u.as_str())
+                        .to_string(),
+                )
+                .api_key(api_key.expose_secret().to_string())
+                .default_headers(headers.clone())
+                .maybe_temperature(temperature)
+                .stream_usage(true)
                 .build();
             Ok(Arc::new(model))
         }
@@ -121,11 +259,6 @@ fn build_chat_model(
             headers,
             overrides,
         } => {
-            if !headers.is_empty() || !overrides.is_empty() {
-                return Err(BuildError::UnsupportedFeature {
-                    provider: model_ref.provider.clone(),
-                });
-            }
             // Pass an explicit placeholder when no key is configured: the
             // alternative is `ChatOpenAI` falling back to `OPENAI_API_KEY`
             // from the environment, which would silently send the operator's
@@ -134,12 +267,28 @@ fn build_chat_model(
                 .as_ref()
                 .map(|k| k.expose_secret().to_string())
                 .unwrap_or_else(|| "not-needed".to_string());
+            // `strip` keys map to disabled params with no replacement,
+            // which removes them from the payload. A server that rejects
+            // the usage request streams fine with `stream_options` stripped.
+            let extra_body = (!overrides.force.is_empty())
+                .then(|| overrides.force.clone().into_iter().collect());
+            let disabled_params = (!overrides.strip.is_empty()).then(|| {
+                overrides
+                    .strip
+                    .iter()
+                    .map(|key| (key.clone(), None))
+                    .collect()
+            });
             let model = ChatOpenAI::builder()
                 .model(model_ref.model.clone())
                 .api_base(base_url.as_str().to_string())
-                .temperature(0.0)
+                .temperature(temperature.unwrap_or(0.0))
                 .top_p(1.0)
                 .api_key(api_key_value)
+                .default_headers(headers.clone())
+                .maybe_extra_body(extra_body)
+                .maybe_disabled_params(disabled_params)
+                .stream_usage(true)
                 .build();
             Ok(Arc::new(model))
         }
@@ -0,0 +1,303 @@
+//! Long-term memory: short facts about the user that outlive a thread.
+//!
+//! With the user's `shared.memory` setting on, every chat turn does two
+//! things:
+//!
+//! - [`learn_from_message`] runs in the background once the new human
+//!   message is saved. It shows the title model that message plus the
+//!   facts already on file, and stores the new, stable facts it reports
+//!   ("Uses Arch Linux", "Manager is Dana").
+//! - [`recall_system_message`] picks the stored facts most relevant to
+//!   the latest human message and renders them into a system message
+//!   ahead of the history.
+//!
+//! Users review, correct and delete facts through `/memories`
+//! ([`crate::handlers::memories`]), whether or not the setting is on.
+
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use agent_chain::messages::AnyMessage;
+use agent_chain::{BaseChatModel, HumanMessage, SystemMessage};
+use be_remote_db::{DatabaseManager, UserMemory};
+use uuid::Uuid;
+
+use crate::error::ThreadServiceResult;
+use crate::service::AppState;
+use crate::title::{clamp_chars, strip_think_blocks, strip_wrapping_markers};
+
+/// Longest fact kept, in chars. Matches the `user_memories.content` column.
+pub(crate) const MEMORY_MAX_CHARS: usize = 300;
+/// Facts kept per user. Extraction stops adding once a user has this many;
+/// deleting some makes room again.
+pub(crate) const MEMORIES_PER_USER_MAX: usize = 200;
+/// Facts recalled into one turn.
+const RECALL_MAX: usize = 15;
+/// New facts taken from one message.
+const EXTRACT_MAX_FACTS: usize = 5;
+/// How much of the message the extractor reads. Facts about the user come
+/// early in what they write; long pastes are mostly not about them.
+const EXTRACT_MESSAGE_CHAR_LIMIT: usize = 2_000;
+/// Known facts shown to the extractor so it doesn't restate them.
+const EXTRACT_KNOWN_FACTS: usize = 50;
+
+const EXTRACT_SYSTEM_PROMPT: &str = "You keep a short list of facts about a user, learned from \
+what they tell an assistant.
+
+Rules:
+- Read the user's message and list facts about the user worth remembering in \
+  later conversations: their tools and setup, job, projects, the people they \
+  mention by role, and preferences they state.
+- Only stable facts the user states about themselves. Skip questions, one-off \
+  tasks, and anything about the topic at hand rather than the user.
+- Skip facts already in the known list, and anything sensitive: health, \
+  passwords or keys, financial details.
+- One fact per line, starting with \"- \". Under 15 words, without the \
+  subject: \"Uses Arch Linux\", \"Manager is Dana\".
+- At most 5 facts. If there is nothing worth remembering, output NONE.";
+
+/// Words too common to say whether a fact is relevant to a message.
+const STOPWORDS: &[&str] = &[
+    "the", "and", "for", "are", "was", "with", "that", "this", "from", "have", "has", "you",
+    "your", "use", "uses", "using", "what", "how", "can", "not", "but", "they", "their",
+];
+
+/// Learn facts from a freshly saved human message, if the user has memory
+/// on. Spawned off the turn, so failures are logged and never reach the
+/// chat.
+pub(crate) async fn learn_from_message(
+    state: Arc<AppState>,
+    user_id: Uuid,
+    thread_id: Uuid,
+    text: String,
+) {
+    if !state.shared_settings(user_id).await.memory {
+        return;
+    }
+    match extract_and_store(
+        state.db.as_ref(),
+        state.providers.title.as_ref(),
+        user_id,
+        thread_id,
+        &text,
+    )
+    .await
+    {
+        Ok(stored) if !stored.is_empty() => {
+            tracing::debug!(thread_id = %thread_id, count = stored.len(), "Stored new memories");
+        }
+        Ok(_) => {}
+        Err(e) => {
+            tracing::warn!(thread_id = %thread_id, error = %e, "Memory extraction failed");
+        }
+    }
+}
+
+/// Ask `model` for new facts in `text` and store them. Returns the rows
+/// actually inserted; a model failure yields none rather than an error.
+async fn extract_and_store(
+    db: &DatabaseManager,
+    model: &(dyn BaseChatModel + Send + Sync),
+    user_id: Uuid,
+    thread_id: Uuid,
+    text: &str,
+) -> ThreadServiceResult<Vec<UserMemory>> {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let known = db.list_memories().user_id(user_id).call().await?;
+    let room = MEMORIES_PER_USER_MAX.saturating_sub(known.len());
+    if room == 0 {
+        return Ok(Vec::new());
+    }
+
+    let prompt = build_extract_prompt(&known, &text);
+    let raw = match model.invoke(prompt, None).await {
+        Ok(message) => message.content.to_string(),
+        Err(e) => {
+            tracing::warn!(thread_id = %thread_id, error = %e, "Memory model failed");
+            return Ok(Vec::new());
+        }
+    };
+    let mut facts = parse_facts(&raw);
+    facts.truncate(room);
+    if facts.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    Ok(db
+        .create_memories()
+        .user_id(user_id)
+        .thread_id(thread_id)
+        .contents(facts)
+        .call()
+        .await?)
+}
+
+fn build_extract_prompt(known: &[UserMemory], text: &str) -> Vec<AnyMessage> {
+    let known_list = if known.is_empty() {
+        "(none)".to_string()
+    } else {
+        known
+            .iter()
+            .take(EXTRACT_KNOWN_FACTS)
+            .map(|m| format!("- {}", m.content))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let message = clamp_chars(text, EXTRACT_MESSAGE_CHAR_LIMIT);
+    let user_content = format!(
+        "<known>\n{known_list}\n</known>\n\n<message>\n{message}\n</message>\n\nNew facts:"
+    );
+    vec![
+        SystemMessage::builder()
+            .content(EXTRACT_SYSTEM_PROMPT.to_string())
+            .build()
+            .into(),
+        HumanMessage::builder().content(user_content).build().into(),
+    ]
+}
+
+/// The bulleted lines of the extractor's output as facts. Anything else
+/// (`NONE`, a preamble, reasoning) is ignored; over-long facts and
+/// repeats are dropped, and at most [`EXTRACT_MAX_FACTS`] are kept.
+fn parse_facts(raw: &str) -> Vec<String> {
+    let mut facts: Vec<String> = Vec::new();
+    for line in strip_think_blocks(raw).lines() {
+        let Some(item) = line.trim_start().strip_prefix(['-', '*', '•']) else {
+            continue;
+        };
+        let unwrapped = strip_wrapping_markers(item);
+        let fact = unwrapped
+            .trim_end_matches('.')
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        if fact.is_empty()
+            || fact.eq_ignore_ascii_case("none")
+            || fact.chars().count() > MEMORY_MAX_CHARS
+            || facts.iter().any(|f| f.eq_ignore_ascii_case(&fact))
+        {
+            continue;
+        }
+        facts.push(fact);
+        if facts.len() == EXTRACT_MAX_FACTS {
+            break;
+        }
+    }
+    facts
+}
+
+/// Render the facts most relevant to `query` (the latest human message)
+/// as a system message, or `None` when the user has none.
+///
+/// Relevance is word overlap with the message. Below [`RECALL_MAX`] facts
+/// every one is included; past that, the best matches win and recency
+/// breaks ties, so a user with a long list still gets their latest facts
+/// when nothing matches.
+pub(crate) fn recall_system_message(memories: &[UserMemory], query: &str) -> Option<SystemMessage> {
+    let facts = select_relevant(memories, query);
+    if facts.is_empty() {
+        return None;
+    }
+    let mut content = String::from(
+        "What you remember about the user from earlier conversations. Use it when it \
+         helps; don't bring it up otherwise, and don't claim to remember anything else.\n",
+    );
+    for fact in facts {
+        content.push_str("\n- ");
+        content.push_str(fact);
+    }
+    Some(SystemMessage::builder().content(content).build())
+}
+
+/// `memories` arrive newest first; the stable sort keeps that order among
+/// equally relevant facts.
+fn select_relevant<'a>(memories: &'a [UserMemory], query: &str) -> Vec<&'a str> {
+    let query_words = words(query);
+    let mut scored: Vec<(usize, &str)> = memories
+        .iter()
+        .map(|m| {
+            let overlap = words(&m.content).intersection(&query_words).count();
+            (overlap, m.content.as_str())
+        })
+        .collect();
+    scored.sort_by_key(|(overlap, _)| Reverse(*overlap));
+    scored
+        .into_iter()
+        .take(RECALL_MAX)
+        .map(|(_, fact)| fact)
+        .collect()
+}
+
+fn words(s: &str) -> HashSet<String> {
+    s.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= 3)
+        .map(str::to_lowercase)
+        .filter(|w| !STOPWORDS.contains(&w.as_str()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn memory(content: &str) -> UserMemory {
+        UserMemory {
+            id: Uuid::now_v7(),
+            user_id: Uuid::nil(),
+            thread_id: None,
+            content: content.to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn parse_facts_keeps_bullets_only() {
+        let raw = "<think>the user uses arch</think>Here are the facts:\n\
+                   - Uses Arch Linux.\n\
+                   * **Manager is Dana**\n\
+                   - uses arch linux\n\
+                   NONE";
+        assert_eq!(parse_facts(raw), ["Uses Arch Linux", "Manager is Dana"]);
+    }
+
+    #[test]
+    fn parse_facts_treats_none_as_empty() {
+        assert!(parse_facts("NONE").is_empty());
+        assert!(parse_facts("- None").is_empty());
+    }
+
+    #[test]
+    fn parse_facts_caps_count_and_length() {
+        let raw = (0..8)
+            .map(|i| format!("- Fact {i}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert_eq!(parse_facts(&raw).len(), EXTRACT_MAX_FACTS);
+        assert!(parse_facts(&format!("- {}", "x".repeat(MEMORY_MAX_CHARS + 1))).is_empty());
+    }
+
+    #[test]
+    fn relevant_facts_come_first_then_newest() {
+        let mut memories: Vec<UserMemory> = (0..RECALL_MAX)
+            .map(|i| memory(&format!("Fact {i}")))
+            .collect();
+        memories.push(memory("Runs Arch Linux on a ThinkPad"));
+        let picked = select_relevant(&memories, "How do I update my Arch packages?");
+        assert_eq!(picked.len(), RECALL_MAX);
+        assert_eq!(picked[0], "Runs Arch Linux on a ThinkPad");
+        assert_eq!(picked[1], "Fact 0");
+    }
+
+    #[test]
+    fn no_memories_no_message() {
+        assert!(recall_system_message(&[], "hello").is_none());
+        assert!(recall_system_message(&[memory("Manager is Dana")], "hello").is_some());
+    }
+}
@@ -1,11 +1,22 @@
 //! Rewrite in-line content blocks into asset references.
 //!
 //! Some content blocks arrive from the client with their payload inlined
-//! (a `text` field on `PlainText`, a `base64` field on `Image`). Persisting
-//! those payloads in the message row would bloat the database and the
-//! follow-up LLM context payloads. Instead we upload them to the asset
-//! service up-front and replace the inline payload with a `file_id` + `url`
-//! pair pointing at the asset row.
+//! (a `text` field on `PlainText`, a `base64` field on `Image` or `File`).
+//! Persisting those payloads in the message row would bloat the database
+//! and the follow-up LLM context payloads. Instead we upload them to the
+//! asset service up-front and replace the inline payload with a `file_id`
+//! + `url` pair pointing at the asset row.
+//!
+//! Attachments the client uploaded earlier arrive the other way round: a
+//! `file_id` naming one of the caller's assets and nothing else. Those are
+//! looked up under the caller's id and given the asset's storage `url`, so
+//! the context pass downloads them like any other block. A `url` the
+//! client supplies itself is never trusted — it would let a turn read any
+//! object in storage.
+//!
+//! `File` blocks carrying an image MIME type are turned into `Image`
+//! blocks first, so they take the same path as any other image (inline
+//! for multimodal models, `describe_image` behind a vision model).
 //!
 //! This used to be exposed as a separate `POST /threads/{id}/preliminary-blocks`
 //! endpoint that the desktop client called before opening the chat WebSocket.
@@ -15,12 +26,17 @@
 use base64::{Engine as _, engine::general_purpose};
 use std::collections::HashMap;
 
-use agent_chain::messages::ContentBlock;
+use agent_chain::messages::{ContentBlock, FileContentBlock, ImageContentBlock};
+use asset_core::{Asset, ScanStatus};
 use uuid::Uuid;
 
 use crate::error::{ThreadServiceError, ThreadServiceResult};
 use crate::service::AppState;
 
+/// `extras` key holding an attachment's file name, which the context pass
+/// uses to title extracted document text.
+pub(crate) const FILENAME_KEY: &str = "filename";
+
 /// Maximum number of blocks accepted in a single rewrite pass.
 ///
 /// The previous REST endpoint enforced this; we keep it here so the chat
@@ -28,9 +44,10 @@ use crate::service::AppState;
 /// turn with thousands of inline payloads.
 pub const MAX_PRELIMINARY_BLOCKS: usize = 50;
 
-/// Replace inline `PlainText.text` and `Image.base64` payloads in `blocks`
-/// with asset references uploaded under `user_id`. Other block variants
-/// pass through unchanged.
+/// Replace inline `PlainText.text`, `Image.base64` and `File.base64`
+/// payloads in `blocks` with asset references uploaded under `user_id`,
+/// and resolve `file_id`-only attachments against the caller's assets.
+/// Other block variants pass through unchanged.
 pub async fn rewrite_preliminary_blocks(
     state: &AppState,
     user_id: Uuid,
@@ -42,11 +59,17 @@ pub async fn rewrite_preliminary_blocks(
         )));
     }
 
+    let blocks: Vec<ContentBlock> = blocks.into_iter().map(promote_image_file).collect();
+    let assets = load_referenced_assets(state, user_id, &blocks).await?;
+
     let mut rewritten: Vec<ContentBlock> = Vec::with_capacity(blocks.len());
     for block in blocks {
         match block {
             ContentBlock::PlainText(mut plain) => {
                 let Some(text) = plain.text.take() else {
+                    let asset = referenced_asset(&assets, plain.file_id.as_deref())?;
+                    plain.url = Some(asset.storage_uri.clone());
+                    set_filename(&mut plain.extras, &asset.name);
                     rewritten.push(ContentBlock::PlainText(plain));
                     continue;
                 };
@@ -71,6 +94,11 @@ pub async fn rewrite_preliminary_blocks(
             }
             ContentBlock::Image(mut image) => {
                 let Some(b64) = image.base64.take() else {
+                    let asset = referenced_asset(&assets, image.file_id.as_deref())?;
+                    image.url = Some(asset.storage_uri.clone());
+                    image
+                        .mime_type
+                        .get_or_insert_with(|| asset.mime_type.clone());
                     rewritten.push(ContentBlock::Image(image));
                     continue;
                 };
@@ -94,6 +122,40 @@ pub async fn rewrite_preliminary_blocks(
                 image.url = Some(storage_uri);
                 rewritten.push(ContentBlock::Image(image));
             }
+            ContentBlock::File(mut file) => {
+                let Some(b64) = file.base64.take() else {
+                    let asset = referenced_asset(&assets, file.file_id.as_deref())?;
+                    file.url = Some(asset.storage_uri.clone());
+                    file.mime_type
+                        .get_or_insert_with(|| asset.mime_type.clone());
+                    set_filename(&mut file.extras, &asset.name);
+                    rewritten.push(ContentBlock::File(file));
+                    continue;
+                };
+                let content = general_purpose::STANDARD
+                    .decode(&b64)
+                    .map_err(|e| ThreadServiceError::invalid_base64("file.base64", e))?;
+                let mime = file.mime_type.clone().ok_or_else(|| {
+                    ThreadServiceError::invalid_argument("file attachments need a mime_type")
+                })?;
+                let name = filename(&file.extras)
+                    .map(str::to_owned)
+                    .unwrap_or_else(|| {
+                        format!(
+                            "attachment.{}",
+                            be_storage::StorageService::extension_from_mime(&mime)
+                        )
+                    });
+
+                let (asset_id, storage_uri) =
+                    upload_block_content(state, &name, &content, &mime, &file.extras, user_id)
+                        .await?;
+
+                file.file_id = Some(asset_id);
+                file.url = Some(storage_uri);
+                set_filename(&mut file.extras, &name);
+                rewritten.push(ContentBlock::File(file));
+            }
             other => rewritten.push(other),
         }
     }
@@ -101,6 +163,115 @@ pub async fn rewrite_preliminary_blocks(
     Ok(rewritten)
 }
 
+/// A `File` block holding an image is handled as an `Image` block.
+fn promote_image_file(block: ContentBlock) -> ContentBlock {
+    match block {
+        ContentBlock::File(file)
+            if file
+                .mime_type
+                .as_deref()
+                .is_some_and(|mime| mime.starts_with("image/")) =>
+        {
+            let FileContentBlock {
+                id,
+                file_id,
+                mime_type,
+                index,
+                url,
+                base64,
+                extras,
+            } = file;
+            ContentBlock::Image(ImageContentBlock {
+                id,
+                file_id,
+                mime_type,
+                index,
+                url,
+                base64,
+                extras,
+            })
+        }
+        other => other,
+    }
+}
+
+/// The asset id of every attachment in `blocks` that carries no inline
+/// payload, i.e. the ones that must be resolved by `file_id`.
+fn referenced_asset_ids(blocks: &[ContentBlock]) -> ThreadServiceResult<Vec<Uuid>> {
+    let mut ids = Vec::new();
+    for block in blocks {
+        let file_id = match block {
+            ContentBlock::PlainText(plain) if plain.text.is_none() => plain.file_id.as_deref(),
+            ContentBlock::Image(image) if image.base64.is_none() => image.file_id.as_deref(),
+            ContentBlock::File(file) if file.base64.is_none() => file.file_id.as_deref(),
+            _ => continue,
+        };
+        let file_id = file_id.ok_or_else(|| {
+            ThreadServiceError::invalid_argument(
+                "attachments must carry inline data or an asset file_id",
+            )
+        })?;
+        let id = Uuid::parse_str(file_id).map_err(|_| {
+            ThreadServiceError::invalid_argument(format!("invalid attachment file_id: {file_id}"))
+        })?;
+        ids.push(id);
+    }
+    Ok(ids)
+}
+
+/// Look up the referenced assets in one query, scoped to `user_id`. An id
+/// the caller doesn't own fails the turn the same way a missing one does.
+async fn load_referenced_assets(
+    state: &AppState,
+    user_id: Uuid,
+    blocks: &[ContentBlock],
+) -> ThreadServiceResult<HashMap<String, Asset>> {
+    let ids = referenced_asset_ids(blocks)?;
+    if ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let mut assets = HashMap::with_capacity(ids.len());
+    for (id, result) in state.asset_service.get_assets(&ids, user_id).await? {
+        let asset = result.map_err(|e| match e {
+            be_asset::AssetError::NotFound => {
+                ThreadServiceError::invalid_argument(format!("attachment {id} not found"))
+            }
+            other => other.into(),
+        })?;
+        if asset.scan_status == ScanStatus::Quarantined {
+            return Err(ThreadServiceError::invalid_argument(format!(
+                "attachment {id} was quarantined by the malware scanner"
+            )));
+        }
+        assets.insert(id.to_string(), asset);
+    }
+    Ok(assets)
+}
+
+fn referenced_asset<'a>(
+    assets: &'a HashMap<String, Asset>,
+    file_id: Option<&str>,
+) -> ThreadServiceResult<&'a Asset> {
+    // `referenced_asset_ids` already rejected blocks without a parseable
+    // id; re-parse so `file_id` matches the map's canonical form.
+    file_id
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .and_then(|id| assets.get(&id.to_string()))
+        .ok_or_else(|| ThreadServiceError::Internal("attachment was not resolved".to_string()))
+}
+
+pub(crate) fn filename(extras: &Option<HashMap<String, serde_json::Value>>) -> Option<&str> {
+    extras.as_ref()?.get(FILENAME_KEY)?.as_str()
+}
+
+fn set_filename(extras: &mut Option<HashMap<String, serde_json::Value>>, name: &str) {
+    extras
+        .get_or_insert_with(HashMap::new)
+        .entry(FILENAME_KEY.to_string())
+        .or_insert_with(|| serde_json::Value::String(name.to_string()));
+}
+
 async fn upload_block_content(
     state: &AppState,
     name: &str,
@@ -121,6 +292,7 @@ async fn upload_block_content(
                 content: content.to_vec(),
                 mime_type: mime_type.to_string(),
                 metadata,
+                device_id: None,
             },
             user_id,
         )
@@ -128,3 +300,58 @@ async fn upload_block_content(
 
     Ok((asset.id.to_string(), asset.storage_uri))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agent_chain::messages::PlainTextContentBlock;
+
+    fn file_block(mime: &str) -> FileContentBlock {
+        FileContentBlock::builder()
+            .file_id(Uuid::nil().to_string())
+            .mime_type(mime.to_string())
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn image_files_become_image_blocks() {
+        let promoted = promote_image_file(ContentBlock::File(file_block("image/png")));
+        let ContentBlock::Image(image) = promoted else {
+            panic!("expected an image block");
+        };
+        assert_eq!(
+            image.file_id.as_deref(),
+            Some(Uuid::nil().to_string().as_str())
+        );
+
+        let document = promote_image_file(ContentBlock::File(file_block("application/pdf")));
+        assert!(matches!(document, ContentBlock::File(_)));
+    }
+
+    #[test]
+    fn only_payload_less_attachments_are_references() {
+        let inline = PlainTextContentBlock::builder()
+            .text("hi".to_string())
+            .build();
+        let blocks = vec![
+            ContentBlock::PlainText(inline),
+            ContentBlock::File(file_block("application/pdf")),
+        ];
+        assert_eq!(referenced_asset_ids(&blocks).unwrap(), vec![Uuid::nil()]);
+    }
+
+    #[test]
+    fn client_urls_and_bad_ids_are_rejected() {
+        let by_url = PlainTextContentBlock::builder()
+            .url("users/someone-else/asset.txt".to_string())
+            .build();
+        assert!(referenced_asset_ids(&[ContentBlock::PlainText(by_url)]).is_err());
+
+        let bad_id = FileContentBlock::builder()
+            .file_id("not-a-uuid".to_string())
+            .build()
+            .unwrap();
+        assert!(referenced_asset_ids(&[ContentBlock::File(bad_id)]).is_err());
+    }
+}
@@ -0,0 +1,712 @@
+//! Timeline reports.
+//!
+//! A report renders a Markdown template (see
+//! [`thread_core::GenerateReportRequest`]) against the user's activity
+//! sessions in a time range, optionally from one device only: totals,
+//! tables of the top activities and of the time per device, and, when the
+//! template asks for `{{summary}}`, a short summary written by the chat
+//! model. The rendered document is stored as an asset, as
+//! Markdown or as a PDF laid out by [`pdf`].
+//!
+//! Two call sites share [`ReportGenerator`]: `POST /reports`
+//! ([`crate::handlers::reports`]) and the `generate_report` tool
+//! ([`tool`]) offered on every chat turn.
+
+mod pdf;
+pub(crate) mod tool;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use agent_chain::{BaseChatModel, HumanMessage, SystemMessage};
+use be_asset::{AssetService, CreateAssetInput};
+use be_remote_db::{Activity, ActivitySession, DatabaseManager, Device};
+use chrono::{DateTime, TimeDelta, Utc};
+use serde_json::json;
+use thread_core::{GenerateReportRequest, GenerateReportResponse, ReportFormat};
+use uuid::Uuid;
+
+use crate::error::{ThreadServiceError, ThreadServiceResult};
+use crate::service::AppState;
+use crate::title::{clamp_chars, strip_think_blocks};
+use crate::untrusted;
+
+/// Longest range one report covers.
+const MAX_RANGE_DAYS: i64 = 92;
+/// Most sessions read for one report; past this the report says it's
+/// incomplete rather than scanning further.
+const MAX_SESSIONS: i64 = 5_000;
+const MAX_TEMPLATE_CHARS: usize = 20_000;
+const MAX_TITLE_CHARS: usize = 200;
+/// Rows in the `{{top_activities}}` table, and activities described to
+/// the summary model.
+const TOP_ACTIVITIES: usize = 10;
+/// Window titles per activity described to the summary model.
+const TITLES_PER_ACTIVITY: usize = 5;
+const TITLE_SAMPLE_MAX_CHARS: usize = 120;
+const SUMMARY_MAX_CHARS: usize = 2_000;
+
+/// Every placeholder a template may use.
+const PLACEHOLDERS: &[&str] = &[
+    "title",
+    "since",
+    "until",
+    "total_time",
+    "activity_count",
+    "session_count",
+    "top_activities",
+    "devices",
+    "summary",
+];
+
+/// Used when the request carries no template.
+const DEFAULT_TEMPLATE: &str = "# {{title}}
+
+_{{since}} – {{until}}_
+
+## Summary
+
+{{summary}}
+
+## At a glance
+
+- Tracked time: {{total_time}}
+- Activities: {{activity_count}}
+- Sessions: {{session_count}}
+
+## Top activities
+
+{{top_activities}}
+
+## Devices
+
+{{devices}}
+";
+
+const SUMMARY_SYSTEM_PROMPT: &str = "You summarize how a person spent their time on their \
+computer, from activity data they recorded themselves.
+
+Rules:
+- Write one or two short paragraphs in the second person (\"You spent …\").
+- Mention the main activities and what they suggest the person worked on.
+- Only state what the data shows. Do not invent projects, people or outcomes.
+- Plain prose: no headings, no lists, no tables.";
+
+const SUMMARY_UNAVAILABLE: &str = "_The summary could not be generated._";
+
+/// Builds reports for one user at a time.
+pub(crate) struct ReportGenerator {
+    db: Arc<DatabaseManager>,
+    asset_service: Arc<AssetService>,
+    model: Arc<dyn BaseChatModel + Send + Sync>,
+}
+
+impl ReportGenerator {
+    /// Summaries use the deployment's chat model.
+    pub(crate) fn from_state(state: &AppState) -> Self {
+        Self {
+            db: state.db.clone(),
+            asset_service: state.asset_service.clone(),
+            model: state.providers.chat.clone(),
+        }
+    }
+
+    /// Render the report `request` describes and store it for `user_id`.
+    pub(crate) async fn generate(
+        &self,
+        user_id: Uuid,
+        request: &GenerateReportRequest,
+        now: DateTime<Utc>,
+    ) -> ThreadServiceResult<GenerateReportResponse> {
+        let GenerateReportRequest {
+            since,
+            until,
+            template,
+            title,
+            format,
+            device_id,
+        } = request;
+        let (since, until, device_id) = (*since, *until, *device_id);
+        if until <= since {
+            return Err(ThreadServiceError::invalid_argument(
+                "`until` must be after `since`",
+            ));
+        }
+        if until - since > TimeDelta::days(MAX_RANGE_DAYS) {
+            return Err(ThreadServiceError::invalid_argument(format!(
+                "a report covers at most {MAX_RANGE_DAYS} days"
+            )));
+        }
+        let template = template.as_deref().unwrap_or(DEFAULT_TEMPLATE);
+        if template.chars().count() > MAX_TEMPLATE_CHARS {
+            return Err(ThreadServiceError::invalid_argument(format!(
+                "the template is longer than {MAX_TEMPLATE_CHARS} characters"
+            )));
+        }
+        let segments = parse_template(template).map_err(ThreadServiceError::invalid_argument)?;
+        let title = match title.as_deref().map(str::trim) {
+            Some(title) if !title.is_empty() => clamp_chars(title, MAX_TITLE_CHARS),
+            _ => default_title(since, until),
+        };
+
+        let devices = self.db.list_devices().user_id(user_id).call().await?;
+        if let Some(device_id) = device_id
+            && !devices.iter().any(|device| device.id == device_id)
+        {
+            return Err(ThreadServiceError::invalid_argument(format!(
+                "device {device_id} is not registered"
+            )));
+        }
+
+        let grouped = self
+            .db
+            .list_activity_sessions_between()
+            .user_id(user_id)
+            .since(since)
+            .until(until)
+            .maybe_device_id(device_id)
+            .limit(MAX_SESSIONS)
+            .call()
+            .await?;
+        let stats = aggregate(grouped, &devices, since, until, now);
+
+        let mut values = HashMap::from([
+            ("title", title.clone()),
+            ("since", format_timestamp(since)),
+            ("until", format_timestamp(until)),
+            ("total_time", format_duration(stats.total)),
+            ("activity_count", stats.activities.len().to_string()),
+            ("session_count", stats.sessions.to_string()),
+            ("top_activities", top_activities_table(&stats)),
+            ("devices", devices_table(&stats)),
+        ]);
+        if segments
+            .iter()
+            .any(|segment| matches!(segment, Segment::Placeholder("summary")))
+        {
+            values.insert("summary", self.summarize(since, until, &stats).await);
+        }
+        let markdown = render(&segments, &values);
+
+        let (content, mime_type, extension) = match format {
+            ReportFormat::Markdown => (markdown.clone().into_bytes(), "text/markdown", "md"),
+            ReportFormat::Pdf => (
+                pdf::markdown_to_pdf(&title, &markdown),
+                "application/pdf",
+                "pdf",
+            ),
+        };
+        let asset = self
+            .asset_service
+            .create_asset(
+                CreateAssetInput {
+                    name: format!("{title}.{extension}"),
+                    content,
+                    mime_type: mime_type.to_owned(),
+                    metadata: Some(json!({
+                        "report": {
+                            "since": since,
+                            "until": until,
+                            "device_id": device_id,
+                            "sessions": stats.sessions,
+                            "truncated": stats.truncated,
+                        }
+                    })),
+                    device_id: None,
+                },
+                user_id,
+            )
+            .await?;
+
+        Ok(GenerateReportResponse {
+            asset_id: asset.id,
+            name: asset.name,
+            mime_type: asset.mime_type,
+            markdown,
+        })
+    }
+
+    /// A few sentences on the range from the chat model. Best-effort: a
+    /// failed call leaves a note in the report instead of failing it.
+    async fn summarize(
+        &self,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+        stats: &TimelineStats,
+    ) -> String {
+        if stats.activities.is_empty() {
+            return "No activity was recorded in this range.".to_owned();
+        }
+        let mut facts = format!(
+            "Range: {} to {}\nTracked time: {}\nSessions: {}\n\nTop activities:\n",
+            format_timestamp(since),
+            format_timestamp(until),
+            format_duration(stats.total),
+            stats.sessions,
+        );
+        for activity in stats.activities.iter().take(TOP_ACTIVITIES) {
+            facts.push_str(&format!(
+                "- {}: {} over {} session(s)",
+                activity.name,
+                format_duration(activity.time),
+                activity.sessions
+            ));
+            if !activity.titles.is_empty() {
+                let titles: Vec<String> = activity
+                    .titles
+                    .iter()
+                    .take(TITLES_PER_ACTIVITY)
+                    .map(|title| clamp_chars(title, TITLE_SAMPLE_MAX_CHARS))
+                    .collect();
+                facts.push_str(&format!(". Windows: {}", titles.join("; ")));
+            }
+            facts.push('\n');
+        }
+        if stats.devices.len() > 1 {
+            facts.push_str("\nDevices:\n");
+            for device in &stats.devices {
+                facts.push_str(&format!(
+                    "- {}: {}\n",
+                    device.name,
+                    format_duration(device.time)
+                ));
+            }
+        }
+
+        // Window titles are whatever the apps and pages chose to show.
+        let mut human = HumanMessage::builder().content(facts).build();
+        untrusted::guard_blocks("timeline", &mut human.content);
+        let messages = vec![
+            SystemMessage::builder()
+                .content(SUMMARY_SYSTEM_PROMPT.to_string())
+                .build()
+                .into(),
+            untrusted::guard_system_message().into(),
+            human.into(),
+        ];
+        match self.model.invoke(messages, None).await {
+            Ok(response) => {
+                let summary = strip_think_blocks(&response.content.to_string());
+                let summary = summary.trim();
+                if summary.is_empty() {
+                    SUMMARY_UNAVAILABLE.to_owned()
+                } else {
+                    clamp_chars(summary, SUMMARY_MAX_CHARS)
+                }
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "Report summary failed; leaving it out");
+                SUMMARY_UNAVAILABLE.to_owned()
+            }
+        }
+    }
+}
+
+/// Time spent in one activity within the range.
+#[derive(Debug, Clone, PartialEq)]
+struct ActivityTotal {
+    name: String,
+    time: TimeDelta,
+    sessions: usize,
+    /// Distinct window titles, in the order first seen.
+    titles: Vec<String>,
+}
+
+/// Time spent on one device within the range.
+#[derive(Debug, Clone, PartialEq)]
+struct DeviceTotal {
+    /// `None` for sessions synced before devices were registered.
+    id: Option<Uuid>,
+    name: String,
+    time: TimeDelta,
+    sessions: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct TimelineStats {
+    total: TimeDelta,
+    sessions: usize,
+    /// Most time first.
+    activities: Vec<ActivityTotal>,
+    /// Most time first.
+    devices: Vec<DeviceTotal>,
+    /// The session cap was hit, so later sessions are missing.
+    truncated: bool,
+}
+
+/// Sum session time per activity and per device, clipped to
+/// `[since, until)`. Sessions still open count up to `now`. `devices`
+/// names the devices.
+fn aggregate(
+    grouped: Vec<(Activity, Vec<ActivitySession>)>,
+    devices: &[Device],
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> TimelineStats {
+    let mut stats = TimelineStats::default();
+    for (activity, sessions) in grouped {
+        let mut total = ActivityTotal {
+            name: activity.display_name,
+            time: TimeDelta::zero(),
+            sessions: sessions.len(),
+            titles: Vec::new(),
+        };
+        for session in sessions {
+            let start = session.started_at.max(since);
+            let end = session.ended_at.unwrap_or(now).min(until);
+            let time = (end - start).max(TimeDelta::zero());
+            total.time += time;
+            let index = match stats
+                .devices
+                .iter()
+                .position(|device| device.id == session.device_id)
+            {
+                Some(index) => index,
+                None => {
+                    stats.devices.push(DeviceTotal {
+                        id: session.device_id,
+                        name: device_name(devices, session.device_id),
+                        time: TimeDelta::zero(),
+                        sessions: 0,
+                    });
+                    stats.devices.len() - 1
+                }
+            };
+            stats.devices[index].time += time;
+            stats.devices[index].sessions += 1;
+            if let Some(title) = session.window_title.map(|t| t.trim().to_owned())
+                && !title.is_empty()
+                && !total.titles.contains(&title)
+            {
+                total.titles.push(title);
+            }
+        }
+        stats.total += total.time;
+        stats.sessions += total.sessions;
+        stats.activities.push(total);
+    }
+    stats.truncated = stats.sessions as i64 >= MAX_SESSIONS;
+    stats
+        .activities
+        .sort_by(|a, b| b.time.cmp(&a.time).then_with(|| a.name.cmp(&b.name)));
+    stats
+        .devices
+        .sort_by(|a, b| b.time.cmp(&a.time).then_with(|| a.name.cmp(&b.name)));
+    stats
+}
+
+fn device_name(devices: &[Device], id: Option<Uuid>) -> String {
+    devices
+        .iter()
+        .find(|device| Some(device.id) == id)
+        .map_or_else(|| "Unknown device".to_owned(), |device| device.name.clone())
+}
+
+fn top_activities_table(stats: &TimelineStats) -> String {
+    if stats.activities.is_empty() {
+        return "_No activity was recorded in this range._".to_owned();
+    }
+    let mut table = "| Activity | Time | Sessions | Share |\n|---|---|---|---|\n".to_owned();
+    for activity in stats.activities.iter().take(TOP_ACTIVITIES) {
+        let share = if stats.total > TimeDelta::zero() {
+            activity.time.num_seconds() * 100 / stats.total.num_seconds().max(1)
+        } else {
+            0
+        };
+        table.push_str(&format!(
+            "| {} | {} | {} | {share}% |\n",
+            activity.name.replace('|', "\\|"),
+            format_duration(activity.time),
+            activity.sessions,
+        ));
+    }
+    if stats.truncated {
+        table.push_str(&format!(
+            "\n_Only the first {MAX_SESSIONS} sessions in this range were counted._\n"
+        ));
+    }
+    table.truncate(table.trim_end().len());
+    table
+}
+
+fn devices_table(stats: &TimelineStats) -> String {
+    if stats.devices.is_empty() {
+        return "_No activity was recorded in this range._".to_owned();
+    }
+    let mut table = "| Device | Time | Sessions |\n|---|---|---|\n".to_owned();
+    for device in &stats.devices {
+        table.push_str(&format!(
+            "| {} | {} | {} |\n",
+            device.name.replace('|', "\\|"),
+            format_duration(device.time),
+            device.sessions,
+        ));
+    }
+    table.truncate(table.trim_end().len());
+    table
+}
+
+/// `3h 25m`, or `25m` under an hour.
+fn format_duration(duration: TimeDelta) -> String {
+    let minutes = duration.num_minutes().max(0);
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{minutes}m"),
+        (hours, minutes) => format!("{hours}h {minutes}m"),
+    }
+}
+
+fn format_timestamp(at: DateTime<Utc>) -> String {
+    at.format("%Y-%m-%d %H:%M UTC").to_string()
+}
+
+fn default_title(since: DateTime<Utc>, until: DateTime<Utc>) -> String {
+    format!(
+        "Activity report {} – {}",
+        since.format("%Y-%m-%d"),
+        until.format("%Y-%m-%d")
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Segment<'a> {
+    Text(&'a str),
+    Placeholder(&'a str),
+}
+
+/// Split `template` at its `{{placeholder}}`s. Whitespace inside the
+/// braces is ignored; unknown names and unclosed braces are errors so a
+/// typo doesn't end up in the stored report.
+fn parse_template(template: &str) -> Result<Vec<Segment<'_>>, String> {
+    let mut segments = Vec::new();
+    let mut rest = template;
+    while let Some(open) = rest.find("{{") {
+        if open > 0 {
+            segments.push(Segment::Text(&rest[..open]));
+        }
+        let after = &rest[open + 2..];
+        let close = after
+            .find("}}")
+            .ok_or_else(|| "the template has a `{{` without a closing `}}`".to_owned())?;
+        let name = after[..close].trim();
+        if !PLACEHOLDERS.contains(&name) {
+            return Err(format!(
+                "unknown placeholder `{{{{{name}}}}}`; use one of {}",
+                PLACEHOLDERS.join(", ")
+            ));
+        }
+        segments.push(Segment::Placeholder(name));
+        rest = &after[close + 2..];
+    }
+    if !rest.is_empty() {
+        segments.push(Segment::Text(rest));
+    }
+    Ok(segments)
+}
+
+fn render(segments: &[Segment<'_>], values: &HashMap<&str, String>) -> String {
+    segments
+        .iter()
+        .map(|segment| match segment {
+            Segment::Text(text) => *text,
+            Segment::Placeholder(name) => values.get(name).map_or("", String::as_str),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::TimeZone;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 2, hour, minute, 0).unwrap()
+    }
+
+    fn activity(name: &str) -> Activity {
+        Activity {
+            id: Uuid::now_v7(),
+            user_id: Uuid::nil(),
+            identity_key: name.to_lowercase(),
+            display_name: name.to_owned(),
+            icon_asset_id: None,
+            last_used_at: at(0, 0),
+            created_at: at(0, 0),
+            updated_at: at(0, 0),
+        }
+    }
+
+    fn session(
+        started_at: DateTime<Utc>,
+        ended_at: Option<DateTime<Utc>>,
+        title: &str,
+    ) -> ActivitySession {
+        session_on(None, started_at, ended_at, title)
+    }
+
+    fn session_on(
+        device_id: Option<Uuid>,
+        started_at: DateTime<Utc>,
+        ended_at: Option<DateTime<Utc>>,
+        title: &str,
+    ) -> ActivitySession {
+        ActivitySession {
+            id: Uuid::now_v7(),
+            activity_id: Uuid::nil(),
+            user_id: Uuid::nil(),
+            process_name: "app".to_owned(),
+            process_id: None,
+            window_title: Some(title.to_owned()),
+            url: None,
+            device_id,
+            started_at,
+            ended_at,
+            created_at: started_at,
+            updated_at: started_at,
+        }
+    }
+
+    #[test]
+    fn aggregate_clips_sessions_to_the_range_and_ranks_by_time() {
+        let stats = aggregate(
+            vec![
+                (
+                    activity("Browser"),
+                    vec![
+                        // Straddles the start: only 9:00–9:30 counts.
+                        session(at(8, 30), Some(at(9, 30)), "Docs"),
+                        session(at(10, 0), Some(at(10, 15)), "Docs"),
+                    ],
+                ),
+                (
+                    activity("Editor"),
+                    // Still open: counts up to `now`.
+                    vec![session(at(11, 0), None, "main.rs")],
+                ),
+            ],
+            &[],
+            at(9, 0),
+            at(17, 0),
+            at(12, 30),
+        );
+
+        assert_eq!(stats.total, TimeDelta::minutes(135));
+        assert_eq!(stats.sessions, 3);
+        assert!(!stats.truncated);
+        let names: Vec<_> = stats.activities.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, ["Editor", "Browser"]);
+        assert_eq!(stats.activities[0].time, TimeDelta::minutes(90));
+        assert_eq!(stats.activities[1].titles, ["Docs"]);
+    }
+
+    #[test]
+    fn aggregate_totals_time_per_device() {
+        let laptop = Device {
+            id: Uuid::now_v7(),
+            user_id: Uuid::nil(),
+            name: "Laptop".to_owned(),
+            platform: "macos".to_owned(),
+            last_seen_at: at(0, 0),
+            created_at: at(0, 0),
+            updated_at: at(0, 0),
+        };
+        let stats = aggregate(
+            vec![(
+                activity("Editor"),
+                vec![
+                    session_on(Some(laptop.id), at(9, 0), Some(at(10, 0)), "a.rs"),
+                    session_on(Some(laptop.id), at(11, 0), Some(at(11, 30)), "b.rs"),
+                    session_on(None, at(12, 0), Some(at(12, 15)), "c.rs"),
+                ],
+            )],
+            std::slice::from_ref(&laptop),
+            at(9, 0),
+            at(17, 0),
+            at(17, 0),
+        );
+
+        assert_eq!(
+            stats.devices,
+            [
+                DeviceTotal {
+                    id: Some(laptop.id),
+                    name: "Laptop".to_owned(),
+                    time: TimeDelta::minutes(90),
+                    sessions: 2,
+                },
+                DeviceTotal {
+                    id: None,
+                    name: "Unknown device".to_owned(),
+                    time: TimeDelta::minutes(15),
+                    sessions: 1,
+                },
+            ]
+        );
+        assert_eq!(
+            devices_table(&stats),
+            "| Device | Time | Sessions |\n|---|---|---|\n\
+             | Laptop | 1h 30m | 2 |\n| Unknown device | 15m | 1 |"
+        );
+    }
+
+    #[test]
+    fn templates_reject_unknown_and_unclosed_placeholders() {
+        assert_eq!(
+            parse_template("# {{ title }}\n{{summary}}").unwrap(),
+            [
+                Segment::Text("# "),
+                Segment::Placeholder("title"),
+                Segment::Text("\n"),
+                Segment::Placeholder("summary"),
+            ]
+        );
+        assert!(
+            parse_template("{{titel}}")
+                .unwrap_err()
+                .contains("`{{titel}}`")
+        );
+        assert!(parse_template("{{title").is_err());
+        assert!(parse_template(DEFAULT_TEMPLATE).is_ok());
+    }
+
+    #[test]
+    fn render_fills_placeholders() {
+        let segments = parse_template("{{title}}: {{total_time}} over {{session_count}}").unwrap();
+        let values = HashMap::from([
+            ("title", "Week".to_owned()),
+            ("total_time", format_duration(TimeDelta::minutes(205))),
+            ("session_count", "12".to_owned()),
+        ]);
+        assert_eq!(render(&segments, &values), "Week: 3h 25m over 12");
+    }
+
+    #[test]
+    fn top_activities_table_escapes_pipes_and_shows_shares() {
+        let stats = TimelineStats {
+            total: TimeDelta::minutes(60),
+            sessions: 2,
+            activities: vec![
+                ActivityTotal {
+                    name: "a|b".to_owned(),
+                    time: TimeDelta::minutes(45),
+                    sessions: 1,
+                    titles: Vec::new(),
+                },
+                ActivityTotal {
+                    name: "Mail".to_owned(),
+                    time: TimeDelta::minutes(15),
+                    sessions: 1,
+                    titles: Vec::new(),
+                },
+            ],
+            devices: Vec::new(),
+            truncated: false,
+        };
+        assert_eq!(
+            top_activities_table(&stats),
+            "| Activity | Time | Sessions | Share |\n|---|---|---|---|\n\
+             | a\\|b | 45m | 1 | 75% |\n| Mail | 15m | 1 | 25% |"
+        );
+    }
+}
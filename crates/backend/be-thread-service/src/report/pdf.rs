@@ -0,0 +1,434 @@
+//! Just enough PDF to print a report.
+//!
+//! Lays the rendered Markdown out as text on A4 pages: headings in bold,
+//! tables and code blocks in a monospace font with aligned columns,
+//! everything else as wrapped paragraphs. Only the standard Type 1 fonts
+//! are used, so nothing is embedded; characters outside Windows-1252
+//! print as `?`. Line widths are estimated from an average glyph width,
+//! which is close enough for wrapping prose.
+
+/// A4, in points.
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 56.0;
+const CONTENT_WIDTH: f32 = PAGE_WIDTH - 2.0 * MARGIN;
+const BODY_SIZE: f32 = 11.0;
+const SMALL_SIZE: f32 = 9.0;
+const LEADING: f32 = 1.35;
+const BULLET_INDENT: f32 = 12.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Font {
+    Regular,
+    Bold,
+    Mono,
+}
+
+impl Font {
+    fn resource(self) -> &'static str {
+        match self {
+            Self::Regular => "F1",
+            Self::Bold => "F2",
+            Self::Mono => "F3",
+        }
+    }
+
+    /// Average glyph width as a fraction of the font size.
+    fn char_width(self) -> f32 {
+        match self {
+            Self::Regular => 0.5,
+            Self::Bold => 0.55,
+            Self::Mono => 0.6,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Line {
+    font: Font,
+    size: f32,
+    indent: f32,
+    /// Extra space above the line, dropped at the top of a page.
+    space_before: f32,
+    text: String,
+}
+
+/// `markdown` as a PDF document titled `title`.
+pub(crate) fn markdown_to_pdf(title: &str, markdown: &str) -> Vec<u8> {
+    let pages = paginate(&layout(markdown));
+    write_document(title, &pages)
+}
+
+fn layout(markdown: &str) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut paragraph = String::new();
+    let mut table: Vec<Vec<String>> = Vec::new();
+    let mut in_code = false;
+
+    for raw in markdown.lines() {
+        let text = raw.trim();
+        if text.starts_with("```") {
+            flush_paragraph(&mut lines, &mut paragraph);
+            flush_table(&mut lines, &mut table);
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            push_wrapped(&mut lines, Font::Mono, SMALL_SIZE, 0.0, 0.0, raw.trim_end());
+            continue;
+        }
+        if text.starts_with('|') {
+            flush_paragraph(&mut lines, &mut paragraph);
+            if !is_table_rule(text) {
+                table.push(table_cells(text));
+            }
+            continue;
+        }
+        flush_table(&mut lines, &mut table);
+
+        if text.is_empty() {
+            flush_paragraph(&mut lines, &mut paragraph);
+        } else if let Some((level, heading)) = heading(text) {
+            flush_paragraph(&mut lines, &mut paragraph);
+            let size = match level {
+                1 => 18.0,
+                2 => 14.0,
+                _ => 12.0,
+            };
+            push_wrapped(
+                &mut lines,
+                Font::Bold,
+                size,
+                0.0,
+                size * 0.6,
+                &inline(heading),
+            );
+        } else if let Some(item) = text.strip_prefix("- ").or_else(|| text.strip_prefix("* ")) {
+            flush_paragraph(&mut lines, &mut paragraph);
+            push_wrapped(
+                &mut lines,
+                Font::Regular,
+                BODY_SIZE,
+                BULLET_INDENT,
+                0.0,
+                &format!("• {}", inline(item)),
+            );
+        } else {
+            if !paragraph.is_empty() {
+                paragraph.push(' ');
+            }
+            paragraph.push_str(text);
+        }
+    }
+    flush_paragraph(&mut lines, &mut paragraph);
+    flush_table(&mut lines, &mut table);
+    lines
+}
+
+fn flush_paragraph(lines: &mut Vec<Line>, paragraph: &mut String) {
+    if paragraph.is_empty() {
+        return;
+    }
+    push_wrapped(
+        lines,
+        Font::Regular,
+        BODY_SIZE,
+        0.0,
+        BODY_SIZE * 0.5,
+        &inline(paragraph),
+    );
+    paragraph.clear();
+}
+
+/// Print the collected rows with each column padded to its widest cell.
+fn flush_table(lines: &mut Vec<Line>, table: &mut Vec<Vec<String>>) {
+    if table.is_empty() {
+        return;
+    }
+    let columns = table.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|i| {
+            table
+                .iter()
+                .filter_map(|row| row.get(i))
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    for (i, row) in table.drain(..).enumerate() {
+        let text = widths
+            .iter()
+            .enumerate()
+            .map(|(column, &width)| {
+                let cell = row.get(column).map_or("", String::as_str);
+                format!("{cell:<width$}")
+            })
+            .collect::<Vec<_>>()
+            .join("  ");
+        let space_before = if i == 0 { BODY_SIZE * 0.5 } else { 0.0 };
+        push_wrapped(
+            lines,
+            Font::Mono,
+            SMALL_SIZE,
+            0.0,
+            space_before,
+            text.trim_end(),
+        );
+    }
+}
+
+fn is_table_rule(row: &str) -> bool {
+    row.chars().all(|c| matches!(c, '|' | '-' | ':' | ' '))
+}
+
+fn table_cells(row: &str) -> Vec<String> {
+    let row = row.trim().trim_start_matches('|');
+    let row = row.strip_suffix('|').unwrap_or(row);
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut chars = row.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => {
+                cell.push('|');
+                chars.next();
+            }
+            '|' => cells.push(inline(std::mem::take(&mut cell).trim())),
+            c => cell.push(c),
+        }
+    }
+    cells.push(inline(cell.trim()));
+    cells
+}
+
+fn heading(text: &str) -> Option<(usize, &str)> {
+    let level = text.chars().take_while(|&c| c == '#').count();
+    let rest = text[level..].strip_prefix(' ')?;
+    (1..=6).contains(&level).then_some((level, rest.trim()))
+}
+
+/// Drop the inline Markdown markers that would otherwise print literally.
+fn inline(text: &str) -> String {
+    let text = text.replace("**", "").replace('`', "");
+    match text.strip_prefix('_').and_then(|t| t.strip_suffix('_')) {
+        Some(inner) if !inner.is_empty() => inner.to_owned(),
+        _ => text,
+    }
+}
+
+fn push_wrapped(
+    lines: &mut Vec<Line>,
+    font: Font,
+    size: f32,
+    indent: f32,
+    space_before: f32,
+    text: &str,
+) {
+    let max_chars = ((CONTENT_WIDTH - indent) / (size * font.char_width())) as usize;
+    for (i, part) in wrap(text, max_chars.max(1)).into_iter().enumerate() {
+        lines.push(Line {
+            font,
+            size,
+            indent,
+            space_before: if i == 0 { space_before } else { 0.0 },
+            text: part,
+        });
+    }
+}
+
+/// Break `text` into lines of at most `max_chars`, at spaces where
+/// possible.
+fn wrap(text: &str, max_chars: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut line_chars = 0;
+    for word in text.split(' ') {
+        let mut word: Vec<char> = word.chars().collect();
+        // Words that can't fit on any line are split.
+        while word.len() > max_chars {
+            if line_chars > 0 {
+                lines.push(std::mem::take(&mut line));
+                line_chars = 0;
+            }
+            lines.push(word.drain(..max_chars).collect());
+        }
+        let needed = usize::from(line_chars > 0) + word.len();
+        if line_chars > 0 && line_chars + needed > max_chars {
+            lines.push(std::mem::take(&mut line));
+            line_chars = 0;
+        }
+        if line_chars > 0 {
+            line.push(' ');
+            line_chars += 1;
+        }
+        line.extend(word.iter());
+        line_chars += word.len();
+    }
+    if line_chars > 0 || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// One content stream per page.
+fn paginate(lines: &[Line]) -> Vec<String> {
+    let mut pages = Vec::new();
+    let mut page = String::new();
+    let mut y = PAGE_HEIGHT - MARGIN;
+    for line in lines {
+        let mut advance = line.size * LEADING;
+        if !page.is_empty() {
+            advance += line.space_before;
+            if y - advance < MARGIN {
+                pages.push(std::mem::take(&mut page));
+                y = PAGE_HEIGHT - MARGIN;
+                advance = line.size * LEADING;
+            }
+        }
+        y -= advance;
+        page.push_str(&format!(
+            "BT /{} {:.1} Tf {:.1} {:.1} Td {} Tj ET\n",
+            line.font.resource(),
+            line.size,
+            MARGIN + line.indent,
+            y,
+            pdf_string(&line.text),
+        ));
+    }
+    if !page.is_empty() || pages.is_empty() {
+        pages.push(page);
+    }
+    pages
+}
+
+/// A literal string in WinAnsiEncoding, with everything outside ASCII
+/// written as an octal escape so the content stream stays ASCII.
+fn pdf_string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('(');
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            ' '..='~' => out.push(c),
+            c => match win_ansi(c) {
+                Some(byte) => out.push_str(&format!("\\{byte:03o}")),
+                None => out.push('?'),
+            },
+        }
+    }
+    out.push(')');
+    out
+}
+
+/// The Windows-1252 byte for a non-ASCII `c`, if it has one.
+fn win_ansi(c: char) -> Option<u8> {
+    Some(match c {
+        '€' => 0x80,
+        '…' => 0x85,
+        '‘' => 0x91,
+        '’' => 0x92,
+        '“' => 0x93,
+        '”' => 0x94,
+        '•' => 0x95,
+        '–' => 0x96,
+        '—' => 0x97,
+        '\u{a0}'..='\u{ff}' => c as u8,
+        _ => return None,
+    })
+}
+
+fn write_document(title: &str, pages: &[String]) -> Vec<u8> {
+    // 1 catalog, 2 page tree, 3–5 fonts, 6 info, then a page and its
+    // content stream for each page.
+    const FIRST_PAGE: usize = 7;
+    let kids: Vec<String> = (0..pages.len())
+        .map(|i| format!("{} 0 R", FIRST_PAGE + 2 * i))
+        .collect();
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_owned(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            pages.len()
+        ),
+    ];
+    for font in ["Helvetica", "Helvetica-Bold", "Courier"] {
+        objects.push(format!(
+            "<< /Type /Font /Subtype /Type1 /BaseFont /{font} /Encoding /WinAnsiEncoding >>"
+        ));
+    }
+    objects.push(format!(
+        "<< /Title {} /Producer (Eurora) >>",
+        pdf_string(title)
+    ));
+    for (i, content) in pages.iter().enumerate() {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {PAGE_WIDTH} {PAGE_HEIGHT}] \
+             /Resources << /Font << /F1 3 0 R /F2 4 0 R /F3 5 0 R >> >> /Contents {} 0 R >>",
+            FIRST_PAGE + 2 * i + 1
+        ));
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{content}endstream",
+            content.len()
+        ));
+    }
+
+    let mut out = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(out.len());
+        out.extend_from_slice(format!("{} 0 obj\n{object}\nendobj\n", i + 1).as_bytes());
+    }
+    let xref = out.len();
+    let mut tail = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        tail.push_str(&format!("{offset:010} 00000 n \n"));
+    }
+    tail.push_str(&format!(
+        "trailer\n<< /Size {} /Root 1 0 R /Info 6 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+        objects.len() + 1
+    ));
+    out.extend_from_slice(tail.as_bytes());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrap_breaks_at_spaces_and_splits_long_words() {
+        assert_eq!(wrap("one two three", 7), ["one two", "three"]);
+        assert_eq!(wrap("abcdefghij", 4), ["abcd", "efgh", "ij"]);
+        assert_eq!(wrap("", 4), [""]);
+    }
+
+    #[test]
+    fn strings_are_escaped_and_encoded() {
+        assert_eq!(pdf_string("a (b) \\"), "(a \\(b\\) \\\\)");
+        assert_eq!(pdf_string("9–5 café ✓"), "(9\\2265 caf\\351 ?)");
+    }
+
+    #[test]
+    fn tables_are_aligned_in_monospace() {
+        let lines = layout("| Activity | Time |\n|---|---|\n| Editor | 2h 5m |\n| a\\|b | 5m |");
+        let texts: Vec<_> = lines.iter().map(|l| l.text.as_str()).collect();
+        assert_eq!(texts, ["Activity  Time", "Editor    2h 5m", "a|b       5m"]);
+        assert!(lines.iter().all(|l| l.font == Font::Mono));
+    }
+
+    #[test]
+    fn documents_parse_and_long_ones_span_pages() {
+        let short = markdown_to_pdf("Week", "# Week\n\nSome text.");
+        assert!(short.starts_with(b"%PDF"));
+        assert_eq!(pdf_core::parse_bytes(&short).unwrap().page_count, 1);
+
+        let long: String = (0..200).map(|i| format!("- item {i}\n")).collect();
+        let long = markdown_to_pdf("Long", &long);
+        assert!(pdf_core::parse_bytes(&long).unwrap().page_count > 1);
+    }
+}
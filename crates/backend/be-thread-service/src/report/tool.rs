@@ -0,0 +1,218 @@
+//! `generate_report`: the report generator as a tool the assistant can
+//! call during a chat turn. Bound to the turn's user; the report is
+//! stored as their asset exactly as `POST /reports` would store it.
+
+use std::fmt;
+
+use agent_chain::async_trait;
+use agent_chain::callbacks::manager::CallbackManagerForToolRun;
+use agent_chain::error::{Error, Result};
+use agent_chain::runnables::RunnableConfig;
+use agent_chain::tools::{ArgsSchema, BaseTool, ToolInput, ToolOutput};
+use chrono::{DateTime, Utc};
+use serde_json::{Value, json};
+use thread_core::{GenerateReportRequest, ReportFormat};
+use uuid::Uuid;
+
+use super::ReportGenerator;
+use crate::title::clamp_chars;
+
+pub(crate) const TOOL_NAME: &str = "generate_report";
+
+const TOOL_DESCRIPTION: &str = "Generate a report of how the user spent their time on their \
+    computer between two moments, from their recorded activity timeline, and save it as a \
+    document they can download. Use it when the user asks for a report, recap or summary of \
+    their activity over a period. The report is Markdown rendered from a template; pass \
+    `template` only when the user wants a particular layout. The response names the saved \
+    document and includes its Markdown.";
+
+/// Longest rendered Markdown handed back to the model.
+const MAX_OUTPUT_CHARS: usize = 8_000;
+
+pub(crate) struct GenerateReportTool {
+    generator: ReportGenerator,
+    user_id: Uuid,
+    args_schema: ArgsSchema,
+}
+
+impl GenerateReportTool {
+    pub(crate) fn new(generator: ReportGenerator, user_id: Uuid) -> Self {
+        let args_schema = ArgsSchema::JsonSchema(json!({
+            "type": "object",
+            "properties": {
+                "since": {
+                    "type": "string",
+                    "description": "Start of the period, RFC 3339 (e.g. 2026-03-02T00:00:00Z)."
+                },
+                "until": {
+                    "type": "string",
+                    "description": "End of the period, RFC 3339. Defaults to now. At most \
+                                    92 days after `since`."
+                },
+                "title": {
+                    "type": "string",
+                    "description": "Title of the report."
+                },
+                "template": {
+                    "type": "string",
+                    "description": "Markdown with {{placeholders}}: title, since, until, \
+                                    total_time, activity_count, session_count, \
+                                    top_activities (a table), devices (a table of time \
+                                    per device) and summary."
+                },
+                "format": {
+                    "type": "string",
+                    "enum": ["markdown", "pdf"],
+                    "description": "File format of the saved document. Defaults to markdown."
+                }
+            },
+            "required": ["since"],
+            "additionalProperties": false
+        }));
+        Self {
+            generator,
+            user_id,
+            args_schema,
+        }
+    }
+}
+
+impl fmt::Debug for GenerateReportTool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GenerateReportTool")
+            .field("user_id", &self.user_id)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl BaseTool for GenerateReportTool {
+    fn name(&self) -> &str {
+        TOOL_NAME
+    }
+
+    fn description(&self) -> &str {
+        TOOL_DESCRIPTION
+    }
+
+    fn args_schema(&self) -> Option<&ArgsSchema> {
+        Some(&self.args_schema)
+    }
+
+    async fn tool_run(
+        &self,
+        input: ToolInput,
+        _run_manager: Option<&CallbackManagerForToolRun>,
+        _config: &RunnableConfig,
+    ) -> Result<ToolOutput> {
+        let now = Utc::now();
+        let request = request_from_input(input, now)?;
+        let report = self
+            .generator
+            .generate(self.user_id, &request, now)
+            .await
+            .map_err(|e| Error::ToolException(format!("Report generation failed: {e}")))?;
+        Ok(ToolOutput::String(format!(
+            "Saved \"{}\" ({}) as asset {}.\n\n{}",
+            report.name,
+            report.mime_type,
+            report.asset_id,
+            clamp_chars(&report.markdown, MAX_OUTPUT_CHARS)
+        )))
+    }
+}
+
+fn request_from_input(input: ToolInput, now: DateTime<Utc>) -> Result<GenerateReportRequest> {
+    let value = match input {
+        ToolInput::ToolCall(tc) => tc.args,
+        ToolInput::Dict(map) => Value::Object(map.into_iter().collect()),
+        ToolInput::String(s) => serde_json::from_str::<Value>(&s).map_err(|e| {
+            Error::ToolException(format!("{TOOL_NAME} input was not valid JSON: {e}"))
+        })?,
+    };
+    request_from_value(&value, now)
+}
+
+fn request_from_value(value: &Value, now: DateTime<Utc>) -> Result<GenerateReportRequest> {
+    let since = timestamp(value, "since")?
+        .ok_or_else(|| Error::ToolException(format!("{TOOL_NAME} argument 'since' is required")))?;
+    let until = timestamp(value, "until")?.unwrap_or(now);
+    let format = match optional_string(value, "format")?.as_deref() {
+        None | Some("markdown") => ReportFormat::Markdown,
+        Some("pdf") => ReportFormat::Pdf,
+        Some(other) => {
+            return Err(Error::ToolException(format!(
+                "{TOOL_NAME} argument 'format' must be \"markdown\" or \"pdf\", not \"{other}\""
+            )));
+        }
+    };
+    Ok(GenerateReportRequest {
+        since,
+        until,
+        template: optional_string(value, "template")?,
+        title: optional_string(value, "title")?,
+        format,
+        device_id: None,
+    })
+}
+
+fn optional_string(value: &Value, field: &str) -> Result<Option<String>> {
+    match value.get(field) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(s)) => Ok(Some(s.clone())),
+        Some(_) => Err(Error::ToolException(format!(
+            "{TOOL_NAME} argument '{field}' must be a string"
+        ))),
+    }
+}
+
+fn timestamp(value: &Value, field: &str) -> Result<Option<DateTime<Utc>>> {
+    optional_string(value, field)?
+        .map(|s| {
+            DateTime::parse_from_rfc3339(&s)
+                .map(|at| at.with_timezone(&Utc))
+                .map_err(|e| {
+                    Error::ToolException(format!(
+                        "{TOOL_NAME} argument '{field}' is not an RFC 3339 timestamp: {e}"
+                    ))
+                })
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::TimeZone;
+
+    #[test]
+    fn request_defaults_until_to_now_and_format_to_markdown() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 9, 12, 0, 0).unwrap();
+        let request =
+            request_from_value(&json!({ "since": "2026-03-02T00:00:00+01:00" }), now).unwrap();
+        assert_eq!(
+            request.since,
+            Utc.with_ymd_and_hms(2026, 3, 1, 23, 0, 0).unwrap()
+        );
+        assert_eq!(request.until, now);
+        assert_eq!(request.format, ReportFormat::Markdown);
+        assert_eq!(request.template, None);
+    }
+
+    #[test]
+    fn request_rejects_bad_arguments() {
+        let now = Utc::now();
+        for args in [
+            json!({}),
+            json!({ "since": "last monday" }),
+            json!({ "since": "2026-03-02T00:00:00Z", "format": "docx" }),
+            json!({ "since": "2026-03-02T00:00:00Z", "title": 3 }),
+        ] {
+            assert!(
+                matches!(request_from_value(&args, now), Err(Error::ToolException(_))),
+                "{args}"
+            );
+        }
+    }
+}
@@ -0,0 +1,252 @@
+//! Cron expressions for scheduled automations.
+//!
+//! Five whitespace-separated fields — minute, hour, day of month, month,
+//! day of week — evaluated in UTC. Each field accepts `*`, a number, a
+//! range `a-b`, a step `*/n` or `a-b/n`, and comma-separated lists of
+//! those. Day of week runs 0–7 with both 0 and 7 meaning Sunday. As in
+//! classic cron, when both day fields are restricted a day matches if
+//! *either* does.
+//!
+//! Month and weekday names, `@daily`-style macros, and seconds are not
+//! supported; the UI builds expressions from presets so there's no need.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Timelike, Utc};
+
+/// How far ahead [`CronSchedule::next_after`] looks before giving up. Long
+/// enough to cover leap days; an expression with no match in that window
+/// (e.g. `0 0 30 2 *`) never fires.
+const MAX_SEARCH_DAYS: u32 = 366 * 5;
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+#[error("invalid schedule {expr:?}: {reason}")]
+pub struct ScheduleError {
+    expr: String,
+    reason: String,
+}
+
+/// A parsed five-field cron expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    /// Whether the day-of-month / day-of-week field was `*`. Drives the
+    /// either-or day matching rule.
+    any_day_of_month: bool,
+    any_day_of_week: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expr: &str) -> Result<Self, ScheduleError> {
+        let error = |reason: String| ScheduleError {
+            expr: expr.to_string(),
+            reason,
+        };
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let [minute, hour, dom, month, dow] = fields[..] else {
+            return Err(error(format!("expected 5 fields, got {}", fields.len())));
+        };
+
+        let minutes = parse_field(minute, 0, 59).map_err(|r| error(format!("minute: {r}")))?;
+        let hours = parse_field(hour, 0, 23).map_err(|r| error(format!("hour: {r}")))?;
+        let days_of_month =
+            parse_field(dom, 1, 31).map_err(|r| error(format!("day of month: {r}")))?;
+        let months = parse_field(month, 1, 12).map_err(|r| error(format!("month: {r}")))?;
+        let mut days_of_week =
+            parse_field(dow, 0, 7).map_err(|r| error(format!("day of week: {r}")))?;
+        if days_of_week & (1u64 << 7) != 0 {
+            days_of_week = (days_of_week | 1) & !(1u64 << 7);
+        }
+
+        Ok(Self {
+            minutes,
+            hours,
+            days_of_month,
+            months,
+            days_of_week,
+            any_day_of_month: dom == "*",
+            any_day_of_week: dow == "*",
+        })
+    }
+
+    /// First occurrence strictly after `after`, at minute granularity.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let mut date = start.date_naive();
+        let (mut from_hour, mut from_minute) = (start.hour(), start.minute());
+
+        for _ in 0..MAX_SEARCH_DAYS {
+            if self.matches_day(date) {
+                for hour in from_hour..24 {
+                    if self.hours & (1u64 << hour) == 0 {
+                        continue;
+                    }
+                    let first_minute = if hour == from_hour { from_minute } else { 0 };
+                    if let Some(minute) =
+                        (first_minute..60).find(|m| self.minutes & (1u64 << m) != 0)
+                    {
+                        return Some(date.and_hms_opt(hour, minute, 0)?.and_utc());
+                    }
+                }
+            }
+            date = date.succ_opt()?;
+            (from_hour, from_minute) = (0, 0);
+        }
+        None
+    }
+
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        if self.months & (1u64 << date.month()) == 0 {
+            return false;
+        }
+        let dom = self.days_of_month & (1u64 << date.day()) != 0;
+        let dow = self.days_of_week & (1u64 << date.weekday().num_days_from_sunday()) != 0;
+        match (self.any_day_of_month, self.any_day_of_week) {
+            (true, true) => true,
+            (false, true) => dom,
+            (true, false) => dow,
+            (false, false) => dom || dow,
+        }
+    }
+}
+
+/// Parse one field into a bitset of the values it selects.
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step.parse().map_err(|_| format!("invalid step {step:?}"))?;
+                if step == 0 {
+                    return Err("step must be at least 1".into());
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (lo, hi) = if range == "*" {
+            (min, max)
+        } else if let Some((lo, hi)) = range.split_once('-') {
+            (parse_value(lo, min, max)?, parse_value(hi, min, max)?)
+        } else {
+            let value = parse_value(range, min, max)?;
+            (value, value)
+        };
+        if lo > hi {
+            return Err(format!("range {range:?} is backwards"));
+        }
+        for value in (lo..=hi).step_by(step as usize) {
+            bits |= 1u64 << value;
+        }
+    }
+    Ok(bits)
+}
+
+fn parse_value(value: &str, min: u32, max: u32) -> Result<u32, String> {
+    let parsed: u32 = value
+        .parse()
+        .map_err(|_| format!("invalid value {value:?}"))?;
+    if !(min..=max).contains(&parsed) {
+        return Err(format!("{parsed} is outside {min}-{max}"));
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap()
+    }
+
+    fn next(expr: &str, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        CronSchedule::parse(expr).unwrap().next_after(after)
+    }
+
+    #[test]
+    fn daily_schedule_rolls_to_the_next_day_once_passed() {
+        assert_eq!(
+            next("0 7 * * *", at(2026, 6, 1, 6, 59)),
+            Some(at(2026, 6, 1, 7, 0))
+        );
+        assert_eq!(
+            next("0 7 * * *", at(2026, 6, 1, 7, 0)),
+            Some(at(2026, 6, 2, 7, 0))
+        );
+    }
+
+    #[test]
+    fn steps_ranges_and_lists() {
+        assert_eq!(
+            next("*/15 9-17 * * *", at(2026, 6, 1, 9, 16)),
+            Some(at(2026, 6, 1, 9, 30))
+        );
+        assert_eq!(
+            next("*/15 9-17 * * *", at(2026, 6, 1, 17, 45)),
+            Some(at(2026, 6, 2, 9, 0))
+        );
+        assert_eq!(
+            next("5,35 * * * *", at(2026, 6, 1, 10, 5)),
+            Some(at(2026, 6, 1, 10, 35))
+        );
+    }
+
+    #[test]
+    fn weekday_schedules_skip_the_weekend() {
+        // 2026-06-05 is a Friday.
+        assert_eq!(
+            next("30 8 * * 1-5", at(2026, 6, 5, 9, 0)),
+            Some(at(2026, 6, 8, 8, 30))
+        );
+        // 7 is Sunday as well as 0.
+        assert_eq!(
+            next("0 0 * * 7", at(2026, 6, 5, 9, 0)),
+            Some(at(2026, 6, 7, 0, 0))
+        );
+    }
+
+    #[test]
+    fn restricted_day_fields_match_either() {
+        // The 1st of the month or any Monday, whichever comes first.
+        assert_eq!(
+            next("0 12 1 * 1", at(2026, 6, 2, 0, 0)),
+            Some(at(2026, 6, 8, 12, 0))
+        );
+        assert_eq!(
+            next("0 12 1 * 1", at(2026, 6, 29, 13, 0)),
+            Some(at(2026, 7, 1, 12, 0))
+        );
+    }
+
+    #[test]
+    fn impossible_dates_never_fire() {
+        assert_eq!(next("0 0 30 2 *", at(2026, 1, 1, 0, 0)), None);
+        assert_eq!(
+            next("0 0 29 2 *", at(2026, 1, 1, 0, 0)),
+            Some(at(2028, 2, 29, 0, 0))
+        );
+    }
+
+    #[test]
+    fn malformed_expressions_are_rejected() {
+        for expr in [
+            "",
+            "* * * *",
+            "* * * * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "* * * 13 *",
+            "* * * * 8",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "a * * * *",
+        ] {
+            assert!(CronSchedule::parse(expr).is_err(), "accepted {expr:?}");
+        }
+    }
+}
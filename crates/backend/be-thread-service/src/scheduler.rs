@@ -0,0 +1,400 @@
+//! Background runner for scheduled automations.
+//!
+//! Every tick claims the automations whose `next_run_at` has passed (see
+//! [`DatabaseManager::claim_due_automations`] for the lease scheme), runs
+//! each one as a single agent turn in a fresh thread, records the outcome
+//! in `automation_runs`, and moves `next_run_at` to the schedule's next
+//! occurrence.
+//!
+//! Runs have no client attached, so the catalog holds only the server
+//! tools the automation was granted — there's nobody to dispatch a remote
+//! tool to. Token budget is checked up front the same way `be-authz` gates
+//! the chat socket; an exhausted budget records a `skipped` run instead of
+//! spending tokens the user doesn't have.
+
+use std::sync::Arc;
+
+use agent_chain::HumanMessage;
+use agent_chain::messages::AnyMessage;
+use agent_chain::tools::BaseTool;
+use async_trait::async_trait;
+use be_remote_db::{
+    Automation, AutomationRunStatus, ClaimedAutomation, DatabaseManager, DbError, MessageType,
+    year_month_key,
+};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde_json::Value;
+use thread_core::{ChatServerMessage, MessageNode, ToolErrorWire, WireToolDescriptor};
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::{Duration, sleep};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use crate::agent_loop::run_agent_loop;
+use crate::error::{ThreadServiceError, ThreadServiceResult};
+use crate::handlers::chat::MAX_TOOL_ROUNDS;
+use crate::llm::{LlmContext, prepare_llm_context};
+use crate::remote_tool_bus::RemoteToolBus;
+use crate::schedule::CronSchedule;
+use crate::service::AppState;
+use crate::webhooks;
+
+/// Maximum number of automations claimed per tick. Runs within a batch
+/// execute concurrently, so this also caps how many agent turns the
+/// scheduler has in flight.
+const BATCH_SIZE: usize = 4;
+
+/// Wall-clock budget for one run. A run that hasn't finished by then is
+/// cancelled and recorded as failed.
+const RUN_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// How long a claimed automation stays leased. Longer than
+/// [`RUN_TIMEOUT`] so a slow run is never picked up a second time.
+const LEASE: ChronoDuration = ChronoDuration::minutes(15);
+
+/// Time between ticks when nothing was due.
+const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Time between ticks when the last one filled a whole batch.
+const BUSY_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Buffer depth for the agent-loop → scheduler channel. Nothing streams
+/// anywhere, so this only needs to keep the loop from parking on chunks
+/// while the run drains them.
+const RUN_CHANNEL_DEPTH: usize = 32;
+
+pub struct SchedulerHandle {
+    shutdown: oneshot::Sender<()>,
+    join: tokio::task::JoinHandle<()>,
+}
+
+impl SchedulerHandle {
+    pub async fn shutdown(self) {
+        let Self { shutdown, join } = self;
+        let _ = shutdown.send(());
+        let _ = join.await;
+    }
+}
+
+/// Spawn the background worker that runs due automations.
+pub(crate) fn spawn_scheduler(state: Arc<AppState>) -> SchedulerHandle {
+    let (shutdown_tx, mut shutdown_rx) = oneshot::channel::<()>();
+
+    let join = tokio::spawn(async move {
+        tracing::info!("Automation scheduler started");
+        loop {
+            let claimed = match tick(&state).await {
+                Ok(n) => n,
+                Err(e) => {
+                    tracing::error!(error = %e, "Automation scheduler tick failed");
+                    0
+                }
+            };
+
+            let next_delay = if claimed >= BATCH_SIZE {
+                BUSY_POLL_INTERVAL
+            } else {
+                IDLE_POLL_INTERVAL
+            };
+
+            tokio::select! {
+                _ = sleep(next_delay) => {}
+                _ = &mut shutdown_rx => {
+                    tracing::info!("Automation scheduler shutting down");
+                    break;
+                }
+            }
+        }
+    });
+
+    SchedulerHandle {
+        shutdown: shutdown_tx,
+        join,
+    }
+}
+
+async fn tick(state: &AppState) -> Result<usize, DbError> {
+    let claimed = state
+        .db
+        .claim_due_automations()
+        .limit(BATCH_SIZE as i64)
+        .lease(LEASE)
+        .call()
+        .await?;
+
+    let count = claimed.len();
+    if count > 0 {
+        tracing::debug!(claimed = count, "Scheduler claimed automations");
+    }
+
+    futures::future::join_all(claimed.into_iter().map(|c| run_claimed(state, c))).await;
+
+    Ok(count)
+}
+
+#[tracing::instrument(skip_all, fields(automation_id = %claimed.automation.id))]
+async fn run_claimed(state: &AppState, claimed: ClaimedAutomation) {
+    let ClaimedAutomation {
+        automation,
+        scheduled_for,
+    } = claimed;
+
+    if let Err(e) = record_run(state, &automation, scheduled_for).await {
+        tracing::error!(error = %e, "Failed to record automation run");
+    }
+
+    // Rescheduling happens whatever the run's outcome so a failing
+    // automation keeps its cadence instead of retrying every tick.
+    let now = Utc::now();
+    let next_run_at = CronSchedule::parse(&automation.schedule)
+        .map_err(|e| tracing::warn!(error = %e, "Stored schedule no longer parses"))
+        .ok()
+        .and_then(|schedule| schedule.next_after(now));
+    if let Err(e) = state
+        .db
+        .reschedule_automation()
+        .id(automation.id)
+        .lease(automation.next_run_at.unwrap_or(scheduled_for))
+        .last_run_at(now)
+        .maybe_next_run_at(next_run_at)
+        .call()
+        .await
+    {
+        tracing::error!(error = %e, "Failed to reschedule automation");
+    }
+}
+
+/// Execute one run and persist its `automation_runs` row. Errors are the
+/// bookkeeping failures; a failed agent turn is a recorded outcome.
+async fn record_run(
+    state: &AppState,
+    automation: &Automation,
+    scheduled_for: DateTime<Utc>,
+) -> ThreadServiceResult<()> {
+    if let Some(reason) = skip_reason(&state.db, automation.user_id).await? {
+        let run = state
+            .db
+            .create_automation_run()
+            .automation_id(automation.id)
+            .user_id(automation.user_id)
+            .scheduled_for(scheduled_for)
+            .call()
+            .await?;
+        let run = state
+            .db
+            .finish_automation_run()
+            .id(run.id)
+            .status(AutomationRunStatus::Skipped)
+            .error(reason.to_string())
+            .call()
+            .await?;
+        webhooks::automation_run(&state.db, &automation.name, &run).await;
+        tracing::info!(reason, "Skipped automation run");
+        return Ok(());
+    }
+
+    let thread = state
+        .db
+        .create_thread()
+        .user_id(automation.user_id)
+        .title(automation.name.clone())
+        .call()
+        .await?;
+    webhooks::thread_created(&state.db, &thread).await;
+    let run = state
+        .db
+        .create_automation_run()
+        .automation_id(automation.id)
+        .user_id(automation.user_id)
+        .thread_id(thread.id)
+        .scheduled_for(scheduled_for)
+        .call()
+        .await?;
+
+    let (status, output, error) =
+        match tokio::time::timeout(RUN_TIMEOUT, run_turn(state, automation, thread.id)).await {
+            Ok(Ok(text)) => (AutomationRunStatus::Succeeded, Some(text), None),
+            Ok(Err(e)) => (AutomationRunStatus::Failed, None, Some(e.to_string())),
+            Err(_) => (
+                AutomationRunStatus::Failed,
+                None,
+                Some("Run timed out".to_string()),
+            ),
+        };
+    tracing::info!(run_id = %run.id, ?status, "Automation run finished");
+
+    let run = state
+        .db
+        .finish_automation_run()
+        .id(run.id)
+        .status(status)
+        .maybe_output(output)
+        .maybe_error(error)
+        .call()
+        .await?;
+    webhooks::automation_run(&state.db, &automation.name, &run).await;
+    Ok(())
+}
+
+/// Why a run shouldn't start, if it shouldn't.
+async fn skip_reason(
+    db: &DatabaseManager,
+    user_id: Uuid,
+) -> ThreadServiceResult<Option<&'static str>> {
+    let (limit, used) = db
+        .get_token_limit_and_usage()
+        .user_id(user_id)
+        .year_month(year_month_key(&Utc::now()))
+        .call()
+        .await?;
+    Ok((used >= limit).then_some("Monthly token limit reached"))
+}
+
+/// Persist the prompt as the thread's first message and drive one agent
+/// turn over it. Returns the final assistant text.
+///
+/// Dropping the future (the run timeout) cancels the spawned loop through
+/// the drop guard.
+async fn run_turn(
+    state: &AppState,
+    automation: &Automation,
+    thread_id: Uuid,
+) -> ThreadServiceResult<String> {
+    let human_message = HumanMessage::builder()
+        .content(automation.prompt.clone())
+        .build();
+    let content = serde_json::to_value(&human_message.content)
+        .map_err(|e| ThreadServiceError::Internal(format!("Failed to serialize content: {e}")))?;
+
+    let server_tools = if state.web_access_enabled(automation.user_id).await {
+        granted_tools(&state.providers.web_tools, &automation.tools)
+    } else {
+        Vec::new()
+    };
+    let LlmContext {
+        messages,
+        chat_model,
+        catalog,
+        sources,
+    } = prepare_llm_context(
+        &state.providers,
+        &state.providers.chat,
+        &state.asset_service,
+        vec![human_message.into()],
+        server_tools,
+        Vec::new(),
+        &[],
+        Vec::new(),
+    )
+    .await?;
+
+    let human_db_message = state
+        .db
+        .create_message()
+        .thread_id(thread_id)
+        .user_id(automation.user_id)
+        .message_type(MessageType::Human)
+        .content(content)
+        .call()
+        .await?;
+
+    let token = CancellationToken::new();
+    let _cancel_on_drop = token.clone().drop_guard();
+    let (tx, mut rx) = mpsc::channel::<ChatServerMessage>(RUN_CHANNEL_DEPTH);
+    tokio::spawn(
+        run_agent_loop()
+            .title_model(state.providers.title.clone())
+            .tx(tx)
+            .token(token)
+            .db(state.db.clone())
+            .chat_model(chat_model)
+            .catalog(catalog)
+            .remote_bus(Arc::new(DetachedBus))
+            .messages(messages)
+            .sources(sources)
+            .thread_id(thread_id)
+            .user_id(automation.user_id)
+            .human_message_id(human_db_message.id)
+            .max_tool_rounds(MAX_TOOL_ROUNDS)
+            .call(),
+    );
+
+    while let Some(event) = rx.recv().await {
+        match event {
+            ChatServerMessage::Final { messages } => return Ok(final_text(&messages)),
+            ChatServerMessage::Error { message, .. } => {
+                return Err(ThreadServiceError::Internal(message));
+            }
+            _ => {}
+        }
+    }
+    Err(ThreadServiceError::Internal(
+        "Agent loop ended without a result".to_string(),
+    ))
+}
+
+/// The subset of `available` the automation was granted, by name.
+fn granted_tools(available: &[Arc<dyn BaseTool>], granted: &[String]) -> Vec<Arc<dyn BaseTool>> {
+    available
+        .iter()
+        .filter(|tool| granted.iter().any(|name| tool.name() == name.as_str()))
+        .cloned()
+        .collect()
+}
+
+/// Text of the last AI message in the turn's final nodes.
+fn final_text(nodes: &[MessageNode]) -> String {
+    nodes
+        .iter()
+        .rev()
+        .find_map(|node| match &node.message {
+            AnyMessage::AIMessage(ai) => Some(ai.text()),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+/// [`RemoteToolBus`] for runs with no client attached. The catalog never
+/// contains remote tools, so this only fires if the model invents one.
+struct DetachedBus;
+
+#[async_trait]
+impl RemoteToolBus for DetachedBus {
+    async fn call(
+        &self,
+        _descriptor: &WireToolDescriptor,
+        _arguments: Value,
+    ) -> Result<Value, ToolErrorWire> {
+        Err(ToolErrorWire::Transport {
+            message: "no client is connected to run this tool".into(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use agent_chain::AIMessage;
+
+    fn node(message: AnyMessage) -> MessageNode {
+        MessageNode {
+            parent_id: None,
+            message,
+            children: vec![],
+            sibling_index: 0,
+            depth: 0,
+        }
+    }
+
+    #[test]
+    fn final_text_takes_the_last_ai_message() {
+        let nodes = vec![
+            node(AIMessage::builder().content("checking").build().into()),
+            node(HumanMessage::builder().content("ignored").build().into()),
+            node(AIMessage::builder().content("all done").build().into()),
+        ];
+        assert_eq!(final_text(&nodes), "all done");
+        assert_eq!(final_text(&[]), "");
+    }
+}
@@ -1,10 +1,18 @@
 use std::sync::Arc;
 
+use agent_chain::BaseChatModel;
+use agent_chain::ollama::OllamaModels;
+use agent_chain::openai::OpenAiModels;
 use be_asset::AssetService;
 use be_remote_db::DatabaseManager;
-use llm_core::LlmConfig;
+use llm_core::{LlmConfig, ProviderId};
+use settings_core::{CloudSettings, SharedSettings};
+use thread_core::ThreadModel;
+use uuid::Uuid;
 
-use crate::llm::{BuildError, Providers};
+use crate::active_turns::ActiveTurns;
+use crate::error::{ThreadServiceError, ThreadServiceResult};
+use crate::llm::{BuildError, Providers, build_thread_chat_model};
 
 /// Shared state injected into Axum handlers via `State<Arc<AppState>>`.
 pub struct AppState {
@@ -12,6 +20,13 @@ pub struct AppState {
     pub asset_service: Arc<AssetService>,
     pub providers: Providers,
     pub llm_config: Arc<LlmConfig>,
+    /// The Ollama model store behind `/models/local`; `None` outside
+    /// local mode.
+    pub local_models: Option<OllamaModels>,
+    /// Model lists behind `GET /models`, one per gateway provider.
+    pub model_catalogs: Vec<(ProviderId, OpenAiModels)>,
+    /// Chat turns in flight, for cancelling one by its request id.
+    pub(crate) active_turns: Arc<ActiveTurns>,
 }
 
 impl AppState {
@@ -31,6 +46,66 @@ impl AppState {
             asset_service,
             providers,
             llm_config,
+            local_models: crate::llm::local_models_from_env(),
+            model_catalogs: crate::llm::model_catalogs(&llm_config),
+            active_turns: Arc::default(),
         })
     }
+
+    /// The chat model for a turn: the thread's pinned model, else the
+    /// deployment's chat role. A pin the deployment can no longer serve
+    /// (its provider was removed from the config) fails the turn rather
+    /// than quietly answering with a different model.
+    pub(crate) fn chat_model_for(
+        &self,
+        model: Option<&ThreadModel>,
+    ) -> ThreadServiceResult<Arc<dyn BaseChatModel + Send + Sync>> {
+        match model {
+            None => Ok(self.providers.chat.clone()),
+            Some(model) => build_thread_chat_model(&self.llm_config, model).map_err(|e| {
+                ThreadServiceError::invalid_argument(format!(
+                    "the thread's model `{}/{}` is unavailable: {e}",
+                    model.provider, model.model
+                ))
+            }),
+        }
+    }
+
+    /// The user's synced shared settings. Users who never synced settings
+    /// get the defaults; a row that can't be read fails closed, with web
+    /// access and memory off, rather than overriding an opt-out (or
+    /// assuming an opt-in) we couldn't see.
+    pub(crate) async fn shared_settings(&self, user_id: Uuid) -> SharedSettings {
+        let closed = || SharedSettings {
+            web_access: false,
+            memory: false,
+            ..SharedSettings::default()
+        };
+        match self.db.get_user_settings().user_id(user_id).call().await {
+            Ok(Some(row)) => match serde_json::from_value::<CloudSettings>(row.settings) {
+                Ok(settings) => settings.shared,
+                Err(e) => {
+                    tracing::warn!(
+                        error = %e,
+                        "Unparseable user settings; web tools and memory disabled"
+                    );
+                    closed()
+                }
+            },
+            Ok(None) => SharedSettings::default(),
+            Err(e) => {
+                tracing::warn!(
+                    error = %e,
+                    "Failed to load user settings; web tools and memory disabled"
+                );
+                closed()
+            }
+        }
+    }
+
+    /// Whether the user's synced `shared.webAccess` setting lets the
+    /// assistant use the web tools.
+    pub(crate) async fn web_access_enabled(&self, user_id: Uuid) -> bool {
+        self.shared_settings(user_id).await.web_access
+    }
 }
@@ -1,16 +1,18 @@
-//! Auto-generation of chat thread titles.
+//! Auto-generation of chat thread titles and topic tags.
 //!
 //! A thread is born with [`TITLE_DEFAULT`] (`"New Chat"`). The first time a
 //! turn settles on that thread, [`auto_generate_title_if_needed`] feeds a
-//! short transcript to the title model and writes the result back to the
-//! database. Two call sites use it:
+//! short transcript to the title model and writes the title, plus up to
+//! [`TAGS_MAX`] topic tags from the same completion, back to the database.
+//! Two call sites use it:
 //!
 //! - The agent loop ([`crate::agent_loop`]) invokes it inline at the end of
 //!   every turn so the title arrives on the same WebSocket as the response,
 //!   and the new title is broadcast to the client via the
 //!   [`thread_core::ChatServerMessage::TitleUpdated`] wire frame.
 //! - The HTTP handler ([`crate::handlers::threads::generate_thread_title`])
-//!   exposes a manual rename / regenerate path for client-driven retries.
+//!   exposes a manual rename / regenerate path for client-driven retries,
+//!   and with `regenerate` replaces a title and tags that already exist.
 //!
 //! All title shaping (transcript flattening, prompt construction, raw-output
 //! sanitisation) is centralised here so both call sites stay byte-identical.
@@ -38,35 +40,62 @@ const TITLE_MAX_WORDS: usize = 6;
 /// more than this much context, and capping keeps long pastes or large
 /// asset references from blowing up the title-model prompt.
 const TITLE_TURN_CHAR_LIMIT: usize = 500;
+/// Most tags kept per thread; the sidebar has room for about this many.
+const TAGS_MAX: usize = 3;
+/// Longest tag kept, in chars. Longer ones are sentences, not topics.
+const TAG_MAX_CHARS: usize = 24;
 
 const TITLE_SYSTEM_PROMPT: &str = "You generate short titles for chat conversations.
 
 Rules:
-- Output ONLY the title text. Nothing before it, nothing after it.
-- 2-6 words. Sentence case.
+- Output the title on the first line, then a second line starting with \
+  \"Tags:\" and 1-3 lowercase topic tags separated by commas. Nothing else.
+- The title is 2-6 words. Sentence case.
 - No markdown: no **, no _, no #, no backticks, no code fences.
 - No quotation marks. No \"Title:\" prefix. No trailing punctuation.
 - Summarize the user's topic, not the assistant's response. Never echo \
   refusals like \"I can't help with that\" — describe what the user was \
   trying to do.
-- If the topic is unclear, output: New conversation
+- Tags are short topics (one or two words), not sentences.
+- If the topic is unclear, output the title New conversation and no tags.
 
-Examples:
-- User asks how to deploy a Rust service to Fly.io  ->  Deploy Rust service to Fly.io
-- User asks to search the web for React 19 features  ->  Search for React 19 features
-- User pastes a stack trace and asks for help  ->  Debugging a stack trace
-- User says \"hi\"  ->  New conversation";
+Examples (conversation, then your output):
 
-/// Generate and persist an auto-title for `thread_id` when the thread is
-/// still carrying the placeholder. Idempotent and best-effort.
+User asks how to deploy a Rust service to Fly.io
+Deploy Rust service to Fly.io
+Tags: rust, deployment
+
+User asks to search the web for React 19 features
+Search for React 19 features
+Tags: react, web search
+
+User pastes a stack trace and asks for help
+Debugging a stack trace
+Tags: debugging
+
+User says \"hi\"
+New conversation
+Tags:";
+
+/// A freshly generated title and the tags that came with it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedTitle {
+    pub title: String,
+    pub tags: Vec<String>,
+}
+
+/// Generate and persist an auto-title and tags for `thread_id` when the
+/// thread is still carrying the placeholder, or unconditionally when
+/// `regenerate` is set. Idempotent (unless regenerating) and best-effort.
 ///
-/// Returns `Some(title)` when a fresh title was written to the database;
+/// Returns `Some` when a fresh title was written to the database;
 /// callers (the agent loop) use that as the cue to broadcast a
 /// [`thread_core::ChatServerMessage::TitleUpdated`] frame. Returns `None`
 /// when nothing was written, which happens in any of these cases:
 ///
-/// - the thread already has a user-meaningful title (idempotency — never
-///   overwrite a real title, auto-generated or user-chosen),
+/// - the thread already has a user-meaningful title and `regenerate` is
+///   off (idempotency — never overwrite a real title, auto-generated or
+///   user-chosen, unless asked to),
 /// - the recent-messages window has no user-readable text yet, or
 /// - the title model failed or returned something that sanitises to empty.
 ///
@@ -82,14 +111,16 @@ pub async fn auto_generate_title_if_needed(
     title_model: &(dyn BaseChatModel + Send + Sync),
     thread_id: Uuid,
     user_id: Uuid,
-) -> ThreadServiceResult<Option<String>> {
+    regenerate: bool,
+) -> ThreadServiceResult<Option<GeneratedTitle>> {
     let existing = db
         .get_thread()
         .id(thread_id)
         .user_id(user_id)
         .call()
         .await?;
-    if let Some(current) = existing.title.as_deref()
+    if !regenerate
+        && let Some(current) = existing.title.as_deref()
         && !current.trim().is_empty()
         && current != TITLE_DEFAULT
     {
@@ -117,9 +148,9 @@ pub async fn auto_generate_title_if_needed(
     };
 
     let prompt = build_title_prompt(&transcript);
-    let title = match title_model.invoke(prompt, None).await {
-        Ok(message) => match sanitize_title(&message.content.to_string()) {
-            Some(t) => t,
+    let (title, tags) = match title_model.invoke(prompt, None).await {
+        Ok(message) => match parse_title_output(&message.content.to_string()) {
+            Some(parsed) => parsed,
             None => {
                 tracing::debug!(
                     thread_id = %thread_id,
@@ -142,10 +173,11 @@ pub async fn auto_generate_title_if_needed(
         .id(thread_id)
         .user_id(user_id)
         .title(title.clone())
+        .tags(tags.clone())
         .call()
         .await?;
 
-    Ok(Some(title))
+    Ok(Some(GeneratedTitle { title, tags }))
 }
 
 /// Flatten the recent message rows into a `User: ... / Assistant: ...`
@@ -214,7 +246,7 @@ fn text_from_content(content: serde_json::Value) -> Option<String> {
 /// Truncate `s` to at most `max` chars (not bytes), appending `…` if the
 /// string was cut. Operates on `char` boundaries to keep multi-byte
 /// codepoints intact.
-fn clamp_chars(s: &str, max: usize) -> String {
+pub(crate) fn clamp_chars(s: &str, max: usize) -> String {
     if s.chars().count() <= max {
         return s.to_string();
     }
@@ -247,6 +279,61 @@ fn build_title_prompt(transcript: &str) -> Vec<AnyMessage> {
     ]
 }
 
+/// Split the raw model output into a sanitised title and its tags. The
+/// title is whatever precedes the `Tags:` line (all of it, when the model
+/// left the line out); `None` when no usable title remains.
+fn parse_title_output(raw: &str) -> Option<(String, Vec<String>)> {
+    let without_think = strip_think_blocks(raw);
+    let mut title_lines = Vec::new();
+    let mut tags = Vec::new();
+    for line in without_think.lines() {
+        match strip_tags_label(line) {
+            Some(list) => tags = sanitize_tags(list),
+            None if tags.is_empty() => title_lines.push(line),
+            None => {}
+        }
+    }
+    let title = sanitize_title(&title_lines.join(" "))?;
+    Some((title, tags))
+}
+
+/// The text after a leading `Tags:` label (case-insensitive, markdown
+/// emphasis tolerated), or `None` when `line` isn't the tags line.
+fn strip_tags_label(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start_matches(['*', '_', '-', ' ', '\t']);
+    let label = trimmed.get(..4)?;
+    if !label.eq_ignore_ascii_case("tags") {
+        return None;
+    }
+    let rest = trimmed[4..].trim_start_matches(['*', '_']);
+    rest.strip_prefix(':')
+}
+
+/// Normalise a comma-separated tag list: lowercase, wrapping markers and
+/// `#` stripped, inner whitespace collapsed. Drops empties, duplicates and
+/// over-long entries, and keeps at most [`TAGS_MAX`].
+fn sanitize_tags(list: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for raw in list.split(',') {
+        let unwrapped = strip_wrapping_markers(raw)
+            .trim_end_matches('.')
+            .to_string();
+        let tag = unwrapped
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+        if tag.is_empty() || tag.chars().count() > TAG_MAX_CHARS || tags.contains(&tag) {
+            continue;
+        }
+        tags.push(tag);
+        if tags.len() == TAGS_MAX {
+            break;
+        }
+    }
+    tags
+}
+
 /// Clean up the raw model output into a presentable title, or `None` if
 /// nothing usable remains.
 ///
@@ -284,7 +371,7 @@ fn sanitize_title(raw: &str) -> Option<String> {
 /// thinking. We don't use `regex` here — a hand-rolled scan over `<think`
 /// and `</think>` keeps the dependency surface small and is faster than
 /// compiling a regex on every call.
-fn strip_think_blocks(s: &str) -> String {
+pub(crate) fn strip_think_blocks(s: &str) -> String {
     let lower = s.to_ascii_lowercase();
     let mut out = String::with_capacity(s.len());
     let mut cursor = 0;
@@ -331,7 +418,7 @@ fn strip_title_label(s: &str) -> &str {
 /// Strip wrapping markdown / quote / backtick characters from both ends,
 /// repeating until the string stabilises. Catches cases like
 /// `**"Foo"**` or `"**Foo**"` where one strip pass isn't enough.
-fn strip_wrapping_markers(s: &str) -> String {
+pub(crate) fn strip_wrapping_markers(s: &str) -> String {
     const MARKERS: &[char] = &['*', '_', '#', '`', '"', '\'', '“', '”', '‘', '’', ' ', '\t'];
     let mut current = s.to_string();
     loop {
@@ -494,6 +581,44 @@ mod tests {
         assert_eq!(sanitize_title("<think>only thinking</think>"), None);
     }
 
+    #[test]
+    fn parse_title_output_splits_title_and_tags() {
+        assert_eq!(
+            parse_title_output("Deploy Rust service to Fly.io\nTags: Rust, deployment"),
+            Some((
+                "Deploy Rust service to Fly.io".to_string(),
+                vec!["rust".to_string(), "deployment".to_string()]
+            ))
+        );
+        assert_eq!(
+            parse_title_output("<think>hm</think>Title: Foo\n**Tags:** #bar"),
+            Some(("Foo".to_string(), vec!["bar".to_string()]))
+        );
+    }
+
+    #[test]
+    fn parse_title_output_tolerates_missing_tags() {
+        assert_eq!(
+            parse_title_output("Just a title"),
+            Some(("Just a title".to_string(), vec![]))
+        );
+        assert_eq!(
+            parse_title_output("New conversation\nTags:"),
+            Some(("New conversation".to_string(), vec![]))
+        );
+        assert_eq!(parse_title_output("Tags: only, tags"), None);
+    }
+
+    #[test]
+    fn sanitize_tags_dedupes_clamps_and_drops_sentences() {
+        assert_eq!(
+            sanitize_tags(
+                " Web  Search, web search, `api`, a tag that is far too long to keep, x, y"
+            ),
+            vec!["web search".to_string(), "api".to_string(), "x".to_string()]
+        );
+    }
+
     #[test]
     fn strip_think_blocks_handles_no_close_tag() {
         // Unterminated <think> drops everything from the open onward.
@@ -25,6 +25,8 @@ use serde_json::Value;
 use thiserror::Error;
 use thread_core::{WireActiveContext, WireToolDescriptor};
 
+use crate::untrusted;
+
 /// A single entry in a [`TurnCatalog`]. The variant determines the dispatch
 /// path taken by the agent loop.
 #[derive(Clone)]
@@ -199,7 +201,8 @@ pub fn build_prelude_system_message(blocks: Vec<ContentBlock>) -> Option<SystemM
 /// handler skips the prepend in that case.
 ///
 /// Per-key formatters live below; new context kinds add an arm to the
-/// `match` and a private `format_<key>` helper.
+/// `match` and a private `format_<key>` helper. Page-supplied strings
+/// (titles, generic context data) go through [`untrusted::sanitize`].
 pub fn build_context_system_message(contexts: &[WireActiveContext]) -> Option<SystemMessage> {
     if contexts.is_empty() {
         return None;
@@ -223,14 +226,18 @@ pub fn build_context_system_message(contexts: &[WireActiveContext]) -> Option<Sy
 }
 
 fn format_youtube_watch_page(buf: &mut String, data: &Value) {
-    let title = data
-        .get("title")
-        .and_then(Value::as_str)
-        .unwrap_or("(unknown)");
-    let channel = data
-        .get("channel")
-        .and_then(Value::as_str)
-        .unwrap_or("(unknown)");
+    let title = untrusted::sanitize(
+        "youtube::watch_page",
+        data.get("title")
+            .and_then(Value::as_str)
+            .unwrap_or("(unknown)"),
+    );
+    let channel = untrusted::sanitize(
+        "youtube::watch_page",
+        data.get("channel")
+            .and_then(Value::as_str)
+            .unwrap_or("(unknown)"),
+    );
     let duration = data
         .get("duration_seconds")
         .and_then(Value::as_f64)
@@ -255,10 +262,12 @@ fn format_youtube_watch_page(buf: &mut String, data: &Value) {
 
 fn format_web_page(buf: &mut String, data: &Value) {
     let url = data.get("url").and_then(Value::as_str).unwrap_or("unknown");
-    let title = data
-        .get("title")
-        .and_then(Value::as_str)
-        .unwrap_or("unknown");
+    let title = untrusted::sanitize(
+        "web::page",
+        data.get("title")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown"),
+    );
     let host = data
         .get("host")
         .and_then(Value::as_str)
@@ -286,6 +295,7 @@ fn format_web_page(buf: &mut String, data: &Value) {
 fn format_generic(buf: &mut String, ctx: &WireActiveContext) {
     writeln!(buf, "## Context `{}`", ctx.key).expect("write to String");
     let pretty = serde_json::to_string_pretty(&ctx.data).unwrap_or_else(|_| ctx.data.to_string());
+    let pretty = untrusted::sanitize(&ctx.key, &pretty);
     writeln!(buf, "```json\n{pretty}\n```").expect("write to String");
 }
 
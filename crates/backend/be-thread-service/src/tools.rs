@@ -6,7 +6,7 @@ use agent_chain::error::{Error, Result};
 use agent_chain::tools::tool;
 use serde_json::Value;
 
-const FIRECRAWL_BASE_URL: &str = "https://firecrawl.inference.nebul.io/v1";
+const DEFAULT_FIRECRAWL_BASE_URL: &str = "https://firecrawl.inference.nebul.io/v1";
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
 const SCRAPE_TIMEOUT: Duration = Duration::from_secs(60);
 const CRAWL_POLL_INTERVAL: Duration = Duration::from_secs(2);
@@ -22,12 +22,28 @@ static HTTP_CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
         .expect("Failed to build HTTP client")
 });
 
+/// `timeout`, shortened to what is left of the HTTP request being served
+/// when a workflow run calls the tool inline.
+fn within_request(timeout: Duration) -> Duration {
+    net_core::deadline::cap(Some(timeout)).unwrap_or(timeout)
+}
+
 fn firecrawl_api_key() -> Result<String> {
     std::env::var("FIRECRAWL_API_KEY").map_err(|_| {
         Error::ToolException("FIRECRAWL_API_KEY environment variable is not set".into())
     })
 }
 
+/// `FIRECRAWL_BASE_URL`, e.g. `https://api.firecrawl.dev/v1` or a
+/// self-hosted instance, falling back to [`DEFAULT_FIRECRAWL_BASE_URL`].
+static FIRECRAWL_BASE_URL: LazyLock<String> = LazyLock::new(|| {
+    std::env::var("FIRECRAWL_BASE_URL")
+        .ok()
+        .map(|url| url.trim().trim_end_matches('/').to_string())
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| DEFAULT_FIRECRAWL_BASE_URL.to_string())
+});
+
 fn authorization_header(api_key: &str) -> (&'static str, String) {
     ("Authorization", format!("Bearer {api_key}"))
 }
@@ -213,14 +229,14 @@ async fn firecrawl_search(query: String, limit: Option<u64>) -> Result<String> {
 
     let (header_name, header_value) = authorization_header(&api_key);
     let response = HTTP_CLIENT
-        .post(format!("{FIRECRAWL_BASE_URL}/search"))
+        .post(format!("{}/search", *FIRECRAWL_BASE_URL))
         .header(header_name, header_value)
         .header("Content-Type", "application/json")
         .json(&serde_json::json!({
             "query": query,
             "limit": limit,
         }))
-        .timeout(REQUEST_TIMEOUT)
+        .timeout(within_request(REQUEST_TIMEOUT))
         .send()
         .await
         .map_err(|e| Error::ToolException(format!("Search request failed: {e}")))?;
@@ -243,7 +259,7 @@ async fn firecrawl_scrape(url: String) -> Result<String> {
 
     let (header_name, header_value) = authorization_header(&api_key);
     let response = HTTP_CLIENT
-        .post(format!("{FIRECRAWL_BASE_URL}/scrape"))
+        .post(format!("{}/scrape", *FIRECRAWL_BASE_URL))
         .header(header_name, header_value)
         .header("Content-Type", "application/json")
         .json(&serde_json::json!({
@@ -251,7 +267,7 @@ async fn firecrawl_scrape(url: String) -> Result<String> {
             "formats": ["markdown"],
             "onlyMainContent": true,
         }))
-        .timeout(SCRAPE_TIMEOUT)
+        .timeout(within_request(SCRAPE_TIMEOUT))
         .send()
         .await
         .map_err(|e| Error::ToolException(format!("Scrape request failed: {e}")))?;
@@ -292,11 +308,11 @@ async fn firecrawl_map(url: String, search: Option<String>, limit: Option<u64>)
 
     let (header_name, header_value) = authorization_header(&api_key);
     let response = HTTP_CLIENT
-        .post(format!("{FIRECRAWL_BASE_URL}/map"))
+        .post(format!("{}/map", *FIRECRAWL_BASE_URL))
         .header(header_name, header_value)
         .header("Content-Type", "application/json")
         .json(&request_body)
-        .timeout(REQUEST_TIMEOUT)
+        .timeout(within_request(REQUEST_TIMEOUT))
         .send()
         .await
         .map_err(|e| Error::ToolException(format!("Map request failed: {e}")))?;
@@ -322,7 +338,7 @@ async fn firecrawl_crawl(url: String, limit: Option<u64>) -> Result<String> {
     // Start the crawl job
     let (header_name, header_value) = authorization_header(&api_key);
     let response = HTTP_CLIENT
-        .post(format!("{FIRECRAWL_BASE_URL}/crawl"))
+        .post(format!("{}/crawl", *FIRECRAWL_BASE_URL))
         .header(header_name, &header_value)
         .header("Content-Type", "application/json")
         .json(&serde_json::json!({
@@ -333,7 +349,7 @@ async fn firecrawl_crawl(url: String, limit: Option<u64>) -> Result<String> {
                 "onlyMainContent": true,
             },
         }))
-        .timeout(REQUEST_TIMEOUT)
+        .timeout(within_request(REQUEST_TIMEOUT))
         .send()
         .await
         .map_err(|e| Error::ToolException(format!("Crawl request failed: {e}")))?;
@@ -365,9 +381,9 @@ async fn firecrawl_crawl(url: String, limit: Option<u64>) -> Result<String> {
 
         let (header_name, header_value) = authorization_header(&api_key);
         let poll_response = HTTP_CLIENT
-            .get(format!("{FIRECRAWL_BASE_URL}/crawl/{job_id}"))
+            .get(format!("{}/crawl/{job_id}", *FIRECRAWL_BASE_URL))
             .header(header_name, header_value)
-            .timeout(REQUEST_TIMEOUT)
+            .timeout(within_request(REQUEST_TIMEOUT))
             .send()
             .await
             .map_err(|e| Error::ToolException(format!("Crawl status check failed: {e}")))?;
@@ -0,0 +1,73 @@
+//! Webhook events published by this service.
+//!
+//! Best effort: the thread or run exists either way, so a failure to
+//! queue the event is logged rather than failing the request that caused
+//! it. Delivery itself belongs to `be-webhook-service`.
+
+use be_remote_db::{AutomationRun, DatabaseManager, Thread, WebhookEventType};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+pub(crate) async fn thread_created(db: &DatabaseManager, thread: &Thread) {
+    let payload = json!({
+        "thread_id": thread.id,
+        "title": thread.title,
+        "created_at": thread.created_at,
+    });
+    publish(
+        db,
+        thread.user_id,
+        WebhookEventType::ThreadCreated,
+        None,
+        payload,
+    )
+    .await;
+}
+
+pub(crate) async fn automation_run(
+    db: &DatabaseManager,
+    automation_name: &str,
+    run: &AutomationRun,
+) {
+    let payload = json!({
+        "automation_id": run.automation_id,
+        "automation_name": automation_name,
+        "run_id": run.id,
+        "thread_id": run.thread_id,
+        "status": run.status,
+        "scheduled_for": run.scheduled_for,
+        "started_at": run.started_at,
+        "finished_at": run.finished_at,
+        "output": run.output,
+        "error": run.error,
+    });
+    publish(
+        db,
+        run.user_id,
+        WebhookEventType::AutomationRun,
+        Some(run.automation_id),
+        payload,
+    )
+    .await;
+}
+
+async fn publish(
+    db: &DatabaseManager,
+    user_id: Uuid,
+    event_type: WebhookEventType,
+    automation_id: Option<Uuid>,
+    payload: Value,
+) {
+    if let Err(e) = db
+        .enqueue_webhook_event()
+        .executor(&db.pool)
+        .user_id(user_id)
+        .event_type(event_type)
+        .maybe_automation_id(automation_id)
+        .payload(&payload)
+        .call()
+        .await
+    {
+        tracing::error!(%user_id, %event_type, error = %e, "Failed to queue webhook event");
+    }
+}
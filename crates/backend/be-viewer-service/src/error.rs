@@ -0,0 +1,131 @@
+use axum::Json;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use be_auth_core::{InvalidUserId, MissingClaims};
+use serde::Serialize;
+use thiserror::Error;
+use utoipa::ToSchema;
+
+/// Wire envelope for error responses emitted by this service. Same
+/// `{ error, message }` shape as the other REST services.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ViewerErrorResponse {
+    /// Stable machine identifier (e.g. `not_found`, `invalid_argument`).
+    #[schema(value_type = String)]
+    pub error: &'static str,
+    /// Human-readable description. Safe to surface in client UIs.
+    pub message: String,
+}
+
+#[derive(Error, Debug)]
+pub enum ViewerServiceError {
+    #[error("Authentication failed: {0}")]
+    Unauthenticated(String),
+
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+
+    #[error("Not found")]
+    NotFound,
+
+    #[error("Database error: {0}")]
+    Database(#[source] be_remote_db::DbError),
+}
+
+impl ViewerServiceError {
+    pub fn unauthenticated(msg: impl Into<String>) -> Self {
+        Self::Unauthenticated(msg.into())
+    }
+
+    pub fn invalid_argument(msg: impl Into<String>) -> Self {
+        Self::InvalidArgument(msg.into())
+    }
+
+    /// Stable identifier surfaced to clients in the error envelope.
+    pub fn error_kind(&self) -> &'static str {
+        match self {
+            Self::Unauthenticated(_) => "unauthenticated",
+            Self::InvalidArgument(_) => "invalid_argument",
+            Self::NotFound => "not_found",
+            Self::Database(_) => "database_error",
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::Unauthenticated(_) => StatusCode::UNAUTHORIZED,
+            Self::InvalidArgument(_) => StatusCode::BAD_REQUEST,
+            Self::NotFound => StatusCode::NOT_FOUND,
+            Self::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl From<be_remote_db::DbError> for ViewerServiceError {
+    fn from(err: be_remote_db::DbError) -> Self {
+        match err {
+            be_remote_db::DbError::NotFound { .. } => Self::NotFound,
+            other => Self::Database(other),
+        }
+    }
+}
+
+impl From<MissingClaims> for ViewerServiceError {
+    fn from(_: MissingClaims) -> Self {
+        Self::unauthenticated("Missing authenticated claims")
+    }
+}
+
+impl From<InvalidUserId> for ViewerServiceError {
+    fn from(err: InvalidUserId) -> Self {
+        Self::unauthenticated(err.to_string())
+    }
+}
+
+impl IntoResponse for ViewerServiceError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let kind = self.error_kind();
+        let detail = self.to_string();
+
+        match &self {
+            Self::Unauthenticated(_) => {
+                tracing::warn!(error = %detail, "Viewer service authentication error");
+            }
+            Self::InvalidArgument(_) | Self::NotFound => {
+                tracing::debug!(error = %detail, "Viewer service client error");
+            }
+            Self::Database(_) => {
+                tracing::error!(error = %detail, "Viewer service internal error");
+            }
+        }
+
+        let message = match &self {
+            Self::Database(_) => "Database operation failed".to_string(),
+            _ => detail,
+        };
+
+        (
+            status,
+            Json(ViewerErrorResponse {
+                error: kind,
+                message,
+            }),
+        )
+            .into_response()
+    }
+}
+
+pub type ViewerResult<T> = std::result::Result<T, ViewerServiceError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn db_not_found_maps_to_404() {
+        let err: ViewerServiceError = be_remote_db::DbError::not_found("thread").into();
+        assert!(matches!(err, ViewerServiceError::NotFound));
+        assert_eq!(err.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,272 @@
+use std::sync::Arc;
+
+use activity_core::{DEFAULT_LIST_LIMIT, MAX_LIST_LIMIT, MAX_TIMELINE_SESSIONS};
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use be_auth_core::AuthUser;
+use be_remote_db::PaginationParams;
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::AppState;
+use crate::error::{ViewerErrorResponse, ViewerResult, ViewerServiceError};
+use crate::openapi::ViewerApi;
+use crate::summary::{self, MAX_UTC_OFFSET_MINUTES};
+use crate::types::{
+    DailySummary, DailySummaryQuery, GetThreadResponse, ListActivitiesResponse,
+    ListMessagesResponse, ListThreadsResponse, PageQuery, ViewerActivity,
+};
+
+/// The page size and offset `query` asks for, the size clamped to
+/// `1..=MAX_LIST_LIMIT`.
+fn page(query: &PageQuery) -> (u32, u32) {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_LIST_LIMIT)
+        .clamp(1, MAX_LIST_LIMIT);
+    (limit, query.offset.unwrap_or(0))
+}
+
+/// Offset of the page after one of `len` rows at `offset`, unless that
+/// page came back short. A full last page is followed by an empty one.
+fn next_offset(offset: u32, limit: u32, len: usize) -> Option<u32> {
+    (len >= limit as usize).then(|| offset.saturating_add(limit))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/viewer/threads",
+    tag = "threads",
+    params(PageQuery),
+    responses(
+        (status = 200, description = "Pinned threads first, then newest first", body = ListThreadsResponse),
+        (status = 401, description = "Not signed in", body = ViewerErrorResponse),
+    ),
+)]
+#[tracing::instrument(skip_all, fields(user_id))]
+pub async fn list_threads(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Query(query): Query<PageQuery>,
+) -> ViewerResult<Json<ListThreadsResponse>> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+    let (limit, offset) = page(&query);
+
+    let threads = state
+        .db
+        .list_threads()
+        .user_id(user_id)
+        .params(PaginationParams::new(offset, limit, "DESC"))
+        .call()
+        .await?;
+
+    Ok(Json(ListThreadsResponse {
+        next_offset: next_offset(offset, limit, threads.len()),
+        threads: threads.into_iter().map(Into::into).collect(),
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/viewer/threads/{thread_id}",
+    tag = "threads",
+    params(("thread_id" = Uuid, Path, description = "The thread")),
+    responses(
+        (status = 200, description = "The thread", body = GetThreadResponse),
+        (status = 401, description = "Not signed in", body = ViewerErrorResponse),
+        (status = 404, description = "No such thread, or not the caller's", body = ViewerErrorResponse),
+    ),
+)]
+#[tracing::instrument(skip_all, fields(user_id, thread_id = %thread_id))]
+pub async fn get_thread(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(thread_id): Path<Uuid>,
+) -> ViewerResult<Json<GetThreadResponse>> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    let thread = state
+        .db
+        .get_thread()
+        .id(thread_id)
+        .user_id(user_id)
+        .call()
+        .await?;
+
+    Ok(Json(GetThreadResponse {
+        thread: thread.into(),
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/viewer/threads/{thread_id}/messages",
+    tag = "threads",
+    params(("thread_id" = Uuid, Path, description = "The thread"), PageQuery),
+    responses(
+        (status = 200, description = "The active branch, oldest first", body = ListMessagesResponse),
+        (status = 401, description = "Not signed in", body = ViewerErrorResponse),
+        (status = 404, description = "No such thread, or not the caller's", body = ViewerErrorResponse),
+    ),
+)]
+#[tracing::instrument(skip_all, fields(user_id, thread_id = %thread_id))]
+pub async fn list_thread_messages(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(thread_id): Path<Uuid>,
+    Query(query): Query<PageQuery>,
+) -> ViewerResult<Json<ListMessagesResponse>> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+    let (limit, offset) = page(&query);
+
+    // An unknown thread reads as an empty branch; check it exists first so
+    // it surfaces as a 404 instead.
+    state
+        .db
+        .get_thread()
+        .id(thread_id)
+        .user_id(user_id)
+        .call()
+        .await?;
+    let messages = state
+        .db
+        .list_messages()
+        .thread_id(thread_id)
+        .user_id(user_id)
+        .params(PaginationParams::new(offset, limit, "ASC"))
+        .call()
+        .await?;
+
+    Ok(Json(ListMessagesResponse {
+        next_offset: next_offset(offset, limit, messages.len()),
+        messages: messages.into_iter().map(Into::into).collect(),
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/viewer/activities",
+    tag = "activities",
+    params(PageQuery),
+    responses(
+        (status = 200, description = "Most recently used first", body = ListActivitiesResponse),
+        (status = 401, description = "Not signed in", body = ViewerErrorResponse),
+    ),
+)]
+#[tracing::instrument(skip_all, fields(user_id))]
+pub async fn list_activities(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Query(query): Query<PageQuery>,
+) -> ViewerResult<Json<ListActivitiesResponse>> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+    let (limit, offset) = page(&query);
+
+    let activities = state
+        .db
+        .list_activities_with_latest_session()
+        .user_id(user_id)
+        .params(PaginationParams::new(offset, limit, "DESC"))
+        .call()
+        .await?;
+
+    Ok(Json(ListActivitiesResponse {
+        next_offset: next_offset(offset, limit, activities.len()),
+        activities: activities
+            .into_iter()
+            .map(|(activity, session)| ViewerActivity::new(activity, session))
+            .collect(),
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/viewer/summaries/daily",
+    tag = "activities",
+    params(DailySummaryQuery),
+    responses(
+        (status = 200, description = "Time per activity over the day", body = DailySummary),
+        (status = 400, description = "The offset is out of range", body = ViewerErrorResponse),
+        (status = 401, description = "Not signed in", body = ViewerErrorResponse),
+    ),
+)]
+#[tracing::instrument(skip_all, fields(user_id, date = %query.date))]
+pub async fn daily_summary(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Query(query): Query<DailySummaryQuery>,
+) -> ViewerResult<Json<DailySummary>> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    let (since, until) =
+        summary::day_bounds(query.date, query.utc_offset_minutes).ok_or_else(|| {
+            ViewerServiceError::invalid_argument(format!(
+                "utc_offset_minutes must be between -{MAX_UTC_OFFSET_MINUTES} and {MAX_UTC_OFFSET_MINUTES}"
+            ))
+        })?;
+
+    // One extra session tells us whether the day was cut short.
+    let mut groups = state
+        .db
+        .list_activity_sessions_between()
+        .user_id(user_id)
+        .since(since)
+        .until(until)
+        .maybe_device_id(query.device_id)
+        .limit(i64::from(MAX_TIMELINE_SESSIONS) + 1)
+        .call()
+        .await?;
+    let mut session_count: usize = groups.iter().map(|(_, sessions)| sessions.len()).sum();
+    let truncated = session_count > MAX_TIMELINE_SESSIONS as usize;
+    if truncated {
+        be_remote_db::drop_latest_session(&mut groups);
+        session_count -= 1;
+    }
+    let (activities, active_seconds) = summary::summarize(groups, since, until, Utc::now());
+    tracing::debug!(session_count, truncated, "Built daily summary");
+
+    Ok(Json(DailySummary {
+        date: query.date,
+        since,
+        until,
+        active_seconds,
+        session_count: u32::try_from(session_count).unwrap_or(u32::MAX),
+        activities,
+        truncated,
+    }))
+}
+
+/// The OpenAPI document for every route in this service.
+pub async fn openapi() -> Json<utoipa::openapi::OpenApi> {
+    Json(<ViewerApi as utoipa::OpenApi>::openapi())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pages_are_clamped_and_chained() {
+        assert_eq!(page(&PageQuery::default()), (DEFAULT_LIST_LIMIT, 0));
+        let query = PageQuery {
+            limit: Some(0),
+            offset: Some(40),
+        };
+        assert_eq!(page(&query), (1, 40));
+        let query = PageQuery {
+            limit: Some(10_000),
+            offset: None,
+        };
+        assert_eq!(page(&query), (MAX_LIST_LIMIT, 0));
+
+        assert_eq!(next_offset(40, 20, 20), Some(60));
+        assert_eq!(next_offset(40, 20, 7), None);
+    }
+}
@@ -0,0 +1,86 @@
+//! HTTP read-only viewer service.
+//!
+//! A small, stable REST/JSON surface under `/v1/viewer` over the read
+//! paths the planned web app and third-party dashboards need, described
+//! by an OpenAPI document generated from the handlers themselves. Unlike
+//! the routes the desktop app talks to, the shapes here are published:
+//! they only grow, and only with fields a viewer needs. Authentication
+//! and Casbin authorization are applied by the surrounding `be-authz`
+//! middleware in `be-monolith`; this crate only assumes that a verified
+//! [`be_auth_core::Claims`] has been inserted into request extensions by
+//! the time a handler runs.
+//!
+//! ## Endpoints
+//!
+//! | Method | Path                                      | Outcome                                        |
+//! |--------|-------------------------------------------|------------------------------------------------|
+//! | GET    | `/v1/viewer/threads`                      | `200 ListThreadsResponse`, pinned then newest. |
+//! | GET    | `/v1/viewer/threads/{thread_id}`          | `200 GetThreadResponse` or `404`.              |
+//! | GET    | `/v1/viewer/threads/{thread_id}/messages` | `200 ListMessagesResponse`, the active branch. |
+//! | GET    | `/v1/viewer/activities`                   | `200 ListActivitiesResponse`, latest first.    |
+//! | GET    | `/v1/viewer/summaries/daily`              | `200 DailySummary` for one day of the timeline.|
+//! | GET    | `/v1/viewer/openapi.json`                 | `200` OpenAPI 3.1 document for the above.      |
+//!
+//! ## Pagination
+//!
+//! List routes take `limit` (1 to 100, default 20) and `offset`, and
+//! answer with `next_offset`: the offset of the next page, or `null` once
+//! a page comes back short.
+
+mod error;
+mod handlers;
+mod openapi;
+mod summary;
+mod types;
+
+use std::sync::Arc;
+
+use axum::{Router, routing::get};
+use be_remote_db::DatabaseManager;
+use tower_http::trace::TraceLayer;
+
+pub use error::{ViewerErrorResponse, ViewerResult, ViewerServiceError};
+pub use openapi::ViewerApi;
+pub use types::{
+    DailyActivityTotal, DailySummary, GetThreadResponse, ListActivitiesResponse,
+    ListMessagesResponse, ListThreadsResponse, MessageRole, ViewerActivity, ViewerMessage,
+    ViewerSession, ViewerThread,
+};
+
+/// Shared state injected into Axum handlers via `State<Arc<AppState>>`.
+pub struct AppState {
+    pub db: Arc<DatabaseManager>,
+}
+
+impl AppState {
+    pub fn new(db: Arc<DatabaseManager>) -> Self {
+        Self { db }
+    }
+}
+
+/// Build the viewer router with the supplied dependencies.
+///
+/// Returns the bare router; the caller is expected to apply the
+/// cross-cutting layers (CORS, body limit, auth middleware) at the
+/// monolith level so all REST services share the same outer pipeline.
+pub fn create_router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/v1/viewer/threads", get(handlers::list_threads))
+        .route("/v1/viewer/threads/{thread_id}", get(handlers::get_thread))
+        .route(
+            "/v1/viewer/threads/{thread_id}/messages",
+            get(handlers::list_thread_messages),
+        )
+        .route("/v1/viewer/activities", get(handlers::list_activities))
+        .route("/v1/viewer/summaries/daily", get(handlers::daily_summary))
+        .route("/v1/viewer/openapi.json", get(handlers::openapi))
+        .layer(TraceLayer::new_for_http())
+        .with_state(state)
+}
+
+/// Wire up application state and return the router ready to merge into
+/// the monolith HTTP pipeline.
+pub fn init_viewer_service(db: Arc<DatabaseManager>) -> Router {
+    tracing::debug!("Initializing viewer service");
+    create_router(Arc::new(AppState::new(db)))
+}
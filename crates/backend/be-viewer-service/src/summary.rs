@@ -0,0 +1,186 @@
+//! The daily summary: a day's sessions folded into time per activity.
+
+use be_remote_db::{Activity, ActivitySession};
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime, TimeDelta, Utc};
+
+use crate::types::DailyActivityTotal;
+
+/// Furthest a UTC offset strays from zero, in minutes (UTC+14:00).
+pub(crate) const MAX_UTC_OFFSET_MINUTES: i32 = 14 * 60;
+
+/// `[since, until)` in UTC for `date` at `utc_offset_minutes` from UTC.
+/// `None` for an offset past [`MAX_UTC_OFFSET_MINUTES`] or a date at the
+/// edge of the representable range.
+pub(crate) fn day_bounds(
+    date: NaiveDate,
+    utc_offset_minutes: i32,
+) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    if utc_offset_minutes.abs() > MAX_UTC_OFFSET_MINUTES {
+        return None;
+    }
+    let offset = FixedOffset::east_opt(utc_offset_minutes * 60)?;
+    let since = date
+        .and_time(NaiveTime::MIN)
+        .and_local_timezone(offset)
+        .single()?
+        .to_utc();
+    Some((since, since.checked_add_signed(TimeDelta::days(1))?))
+}
+
+/// Per-activity totals, most time first, and the time covered by at
+/// least one session. Sessions are clipped to `[since, until)`; a live
+/// one counts up to `now`.
+pub(crate) fn summarize(
+    groups: Vec<(Activity, Vec<ActivitySession>)>,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> (Vec<DailyActivityTotal>, i64) {
+    let clip = |session: &ActivitySession| {
+        let start = session.started_at.max(since);
+        let end = session.ended_at.unwrap_or(now).min(until);
+        (start < end).then_some((start, end))
+    };
+
+    let mut spans = Vec::new();
+    let mut totals: Vec<DailyActivityTotal> = groups
+        .into_iter()
+        .map(|(activity, sessions)| {
+            let mut seconds = 0;
+            for (start, end) in sessions.iter().filter_map(clip) {
+                seconds += (end - start).num_seconds();
+                spans.push((start, end));
+            }
+            DailyActivityTotal {
+                activity_id: activity.id,
+                display_name: activity.display_name,
+                icon_asset_id: activity.icon_asset_id,
+                seconds,
+                sessions: u32::try_from(sessions.len()).unwrap_or(u32::MAX),
+            }
+        })
+        .collect();
+    totals.sort_by(|a, b| {
+        b.seconds
+            .cmp(&a.seconds)
+            .then_with(|| a.display_name.cmp(&b.display_name))
+    });
+
+    (totals, covered_seconds(spans))
+}
+
+/// Seconds covered by the union of `spans`.
+fn covered_seconds(mut spans: Vec<(DateTime<Utc>, DateTime<Utc>)>) -> i64 {
+    spans.sort_unstable();
+    let mut covered = 0;
+    let mut current: Option<(DateTime<Utc>, DateTime<Utc>)> = None;
+    for (start, end) in spans {
+        match current {
+            Some((open_start, open_end)) if start <= open_end => {
+                current = Some((open_start, open_end.max(end)));
+            }
+            _ => {
+                if let Some((open_start, open_end)) = current {
+                    covered += (open_end - open_start).num_seconds();
+                }
+                current = Some((start, end));
+            }
+        }
+    }
+    if let Some((open_start, open_end)) = current {
+        covered += (open_end - open_start).num_seconds();
+    }
+    covered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::TimeZone;
+    use uuid::Uuid;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 2, hour, minute, 0).unwrap()
+    }
+
+    fn activity(name: &str) -> Activity {
+        Activity {
+            id: Uuid::now_v7(),
+            user_id: Uuid::nil(),
+            identity_key: name.to_lowercase(),
+            display_name: name.to_owned(),
+            icon_asset_id: None,
+            last_used_at: at(0, 0),
+            created_at: at(0, 0),
+            updated_at: at(0, 0),
+        }
+    }
+
+    fn session(started_at: DateTime<Utc>, ended_at: Option<DateTime<Utc>>) -> ActivitySession {
+        ActivitySession {
+            id: Uuid::now_v7(),
+            activity_id: Uuid::nil(),
+            user_id: Uuid::nil(),
+            process_name: "app".to_owned(),
+            process_id: None,
+            window_title: None,
+            url: None,
+            device_id: None,
+            started_at,
+            ended_at,
+            created_at: started_at,
+            updated_at: started_at,
+        }
+    }
+
+    #[test]
+    fn day_bounds_follow_the_callers_offset() {
+        let date = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+        assert_eq!(
+            day_bounds(date, 0),
+            Some((at(0, 0), at(0, 0) + TimeDelta::days(1)))
+        );
+        let (since, until) = day_bounds(date, 120).unwrap();
+        assert_eq!(since, Utc.with_ymd_and_hms(2026, 3, 1, 22, 0, 0).unwrap());
+        assert_eq!(until - since, TimeDelta::days(1));
+        assert_eq!(day_bounds(date, MAX_UTC_OFFSET_MINUTES + 1), None);
+    }
+
+    #[test]
+    fn totals_clip_to_the_day_and_overlaps_count_once() {
+        let (since, until) = (at(0, 0), at(23, 0));
+        let (totals, active) = summarize(
+            vec![
+                (
+                    activity("Browser"),
+                    vec![
+                        // Started the evening before.
+                        session(since - TimeDelta::hours(1), Some(at(0, 30))),
+                        session(at(9, 0), Some(at(10, 0))),
+                    ],
+                ),
+                (
+                    activity("Editor"),
+                    vec![
+                        // Overlaps the browser session, as on a second device.
+                        session(at(9, 30), Some(at(11, 30))),
+                        // Still live.
+                        session(at(12, 0), None),
+                    ],
+                ),
+            ],
+            since,
+            until,
+            at(12, 15),
+        );
+
+        assert_eq!(totals[0].display_name, "Editor");
+        assert_eq!(totals[0].seconds, (2 * 60 + 15) * 60);
+        assert_eq!(totals[0].sessions, 2);
+        assert_eq!(totals[1].display_name, "Browser");
+        assert_eq!(totals[1].seconds, 90 * 60);
+        // 00:00–00:30, 09:00–11:30 and 12:00–12:15.
+        assert_eq!(active, (30 + 150 + 15) * 60);
+    }
+}
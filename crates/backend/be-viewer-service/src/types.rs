@@ -0,0 +1,222 @@
+//! Wire types for `/v1/viewer`.
+//!
+//! Deliberately narrower than the rows they come from: no `user_id`, no
+//! model temperature, no process ids. Anything added here becomes part
+//! of the published schema, so fields are opt-in.
+
+use agent_chain_core::messages::ContentBlocks;
+use be_remote_db::{Activity, ActivitySession, Message, MessageType, Thread};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+/// `limit` and `offset` for the list routes.
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PageQuery {
+    /// Page size, 1 to 100. Defaults to 20.
+    pub limit: Option<u32>,
+    /// Rows to skip. Defaults to 0.
+    pub offset: Option<u32>,
+}
+
+/// Query of `GET /v1/viewer/summaries/daily`.
+#[derive(Debug, Clone, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DailySummaryQuery {
+    /// The calendar day to summarise, `YYYY-MM-DD`.
+    pub date: NaiveDate,
+    /// The caller's offset from UTC in minutes, so `date` means their
+    /// day. Defaults to 0.
+    #[serde(default)]
+    pub utc_offset_minutes: i32,
+    /// Only count sessions from this device.
+    #[serde(default)]
+    pub device_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ViewerThread {
+    pub id: Uuid,
+    pub title: Option<String>,
+    pub tags: Vec<String>,
+    pub starred: bool,
+    /// When the thread was pinned; `None` when it isn't.
+    pub pinned_at: Option<DateTime<Utc>>,
+    pub folder_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Thread> for ViewerThread {
+    fn from(thread: Thread) -> Self {
+        Self {
+            id: thread.id,
+            title: thread.title,
+            tags: thread.tags,
+            starred: thread.starred,
+            pinned_at: thread.pinned_at,
+            folder_id: thread.folder_id,
+            created_at: thread.created_at,
+            updated_at: thread.updated_at,
+        }
+    }
+}
+
+/// Who a message is from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageRole {
+    Human,
+    System,
+    Ai,
+    Tool,
+}
+
+impl From<MessageType> for MessageRole {
+    fn from(message_type: MessageType) -> Self {
+        match message_type {
+            MessageType::Human => Self::Human,
+            MessageType::System => Self::System,
+            MessageType::Ai => Self::Ai,
+            MessageType::Tool => Self::Tool,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ViewerMessage {
+    pub id: Uuid,
+    pub parent_message_id: Option<Uuid>,
+    pub role: MessageRole,
+    /// The message's text blocks, for viewers that render plain text.
+    pub text: String,
+    /// The stored content blocks as they are, for viewers that render
+    /// images, citations and tool calls too.
+    #[schema(value_type = Object)]
+    pub content: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<Message> for ViewerMessage {
+    fn from(message: Message) -> Self {
+        let text = serde_json::from_value::<ContentBlocks>(message.content.clone())
+            .map(|blocks| blocks.to_string())
+            .unwrap_or_default();
+        Self {
+            id: message.id,
+            parent_message_id: message.parent_message_id,
+            role: message.message_type.into(),
+            text,
+            content: message.content,
+            created_at: message.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ViewerSession {
+    pub id: Uuid,
+    pub process_name: String,
+    pub window_title: Option<String>,
+    pub url: Option<String>,
+    /// The device the session was recorded on.
+    pub device_id: Option<Uuid>,
+    pub started_at: DateTime<Utc>,
+    /// `None` while the session is live.
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+impl From<ActivitySession> for ViewerSession {
+    fn from(session: ActivitySession) -> Self {
+        Self {
+            id: session.id,
+            process_name: session.process_name,
+            window_title: session.window_title,
+            url: session.url,
+            device_id: session.device_id,
+            started_at: session.started_at,
+            ended_at: session.ended_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ViewerActivity {
+    pub id: Uuid,
+    pub display_name: String,
+    pub icon_asset_id: Option<Uuid>,
+    pub last_used_at: DateTime<Utc>,
+    pub latest_session: Option<ViewerSession>,
+}
+
+impl ViewerActivity {
+    pub(crate) fn new(activity: Activity, latest_session: Option<ActivitySession>) -> Self {
+        Self {
+            id: activity.id,
+            display_name: activity.display_name,
+            icon_asset_id: activity.icon_asset_id,
+            last_used_at: activity.last_used_at,
+            latest_session: latest_session.map(ViewerSession::from),
+        }
+    }
+}
+
+/// A page of threads, pinned first and then newest first.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ListThreadsResponse {
+    pub threads: Vec<ViewerThread>,
+    /// Offset of the next page; `None` on the last one.
+    pub next_offset: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct GetThreadResponse {
+    pub thread: ViewerThread,
+}
+
+/// A page of the messages on a thread's active branch, oldest first.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ListMessagesResponse {
+    pub messages: Vec<ViewerMessage>,
+    /// Offset of the next page; `None` on the last one.
+    pub next_offset: Option<u32>,
+}
+
+/// A page of activities, most recently used first.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ListActivitiesResponse {
+    pub activities: Vec<ViewerActivity>,
+    /// Offset of the next page; `None` on the last one.
+    pub next_offset: Option<u32>,
+}
+
+/// Time spent in one activity over the day.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct DailyActivityTotal {
+    pub activity_id: Uuid,
+    pub display_name: String,
+    pub icon_asset_id: Option<Uuid>,
+    pub seconds: i64,
+    pub sessions: u32,
+}
+
+/// Where the time went on one day.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DailySummary {
+    pub date: NaiveDate,
+    /// Start of the day in UTC.
+    pub since: DateTime<Utc>,
+    /// End of the day in UTC, exclusive.
+    pub until: DateTime<Utc>,
+    /// Time covered by at least one session, so sessions overlapping on
+    /// two devices count once.
+    pub active_seconds: i64,
+    pub session_count: u32,
+    /// Per activity, most time first.
+    pub activities: Vec<DailyActivityTotal>,
+    /// The day held more sessions than a summary reads; the latest are
+    /// missing.
+    pub truncated: bool,
+}
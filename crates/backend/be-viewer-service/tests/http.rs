@@ -0,0 +1,255 @@
+//! End-to-end HTTP round-trips for the viewer service.
+//!
+//! Same harness as the settings service: `#[sqlx::test]` provisions a
+//! fresh, migrated database per test, the router is served on an
+//! ephemeral port, and `Claims` are injected straight into request
+//! extensions in place of the production `authz_middleware`. Requires
+//! `DATABASE_URL`; without it run `cargo test -p be-viewer-service --lib`.
+
+use std::sync::{Arc, Mutex};
+
+use axum::Router;
+use axum::extract::Request;
+use axum::middleware::Next;
+use be_auth_core::{Claims, Role};
+use be_remote_db::{DatabaseManager, MessageType};
+use be_viewer_service::{
+    AppState, DailySummary, GetThreadResponse, ListActivitiesResponse, ListMessagesResponse,
+    ListThreadsResponse, MessageRole, create_router,
+};
+use chrono::{TimeDelta, TimeZone, Utc};
+use reqwest::StatusCode;
+use serde_json::{Value, json};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+async fn seed_user(pool: &PgPool) -> Uuid {
+    let id = Uuid::now_v7();
+    sqlx::query("INSERT INTO users (id, email) VALUES ($1, $2)")
+        .bind(id)
+        .bind(format!("user-{id}@test.local"))
+        .execute(pool)
+        .await
+        .expect("seed user");
+    id
+}
+
+fn claims_for(user_id: Uuid) -> Claims {
+    Claims {
+        sub: user_id.to_string(),
+        email: format!("user-{user_id}@test.local"),
+        display_name: None,
+        iat: 0,
+        exp: i64::MAX,
+        token_type: "access".into(),
+        role: Role::Free,
+        aud: "eurora".into(),
+        email_verified: true,
+        jti: Uuid::now_v7().to_string(),
+        analytics: Default::default(),
+        token_version: 0,
+        act: None,
+    }
+}
+
+struct AppHarness {
+    base_url: String,
+    db: Arc<DatabaseManager>,
+    primary: Uuid,
+    other: Uuid,
+    active: Arc<Mutex<Uuid>>,
+}
+
+impl AppHarness {
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    fn act_as(&self, user_id: Uuid) {
+        *self.active.lock().expect("mutex not poisoned") = user_id;
+    }
+
+    async fn get(&self, path: &str) -> (StatusCode, Value) {
+        let response = reqwest::Client::new()
+            .get(self.url(path))
+            .send()
+            .await
+            .expect("GET");
+        let status = response.status();
+        (status, response.json().await.expect("JSON body"))
+    }
+}
+
+/// Spin up the viewer router on an ephemeral port, acting as the first
+/// of two seeded users.
+async fn spawn_app(pool: PgPool) -> AppHarness {
+    let primary = seed_user(&pool).await;
+    let other = seed_user(&pool).await;
+    let db = Arc::new(DatabaseManager::from_pool(pool));
+    let state = Arc::new(AppState::new(db.clone()));
+
+    let active = Arc::new(Mutex::new(primary));
+    let active_for_layer = active.clone();
+
+    let app: Router = create_router(state).layer(axum::middleware::from_fn(
+        move |mut req: Request, next: Next| {
+            let user_id = *active_for_layer.lock().expect("mutex not poisoned");
+            async move {
+                req.extensions_mut().insert(claims_for(user_id));
+                next.run(req).await
+            }
+        },
+    ));
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind ephemeral");
+    let addr = listener.local_addr().expect("local_addr");
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.expect("serve");
+    });
+
+    AppHarness {
+        base_url: format!("http://{addr}"),
+        db,
+        primary,
+        other,
+        active,
+    }
+}
+
+#[sqlx::test(migrations = "../be-remote-db/src/migrations")]
+async fn threads_and_messages_page_for_their_owner_only(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let mut threads = Vec::new();
+    for title in ["First", "Second", "Third"] {
+        let thread = app
+            .db
+            .create_thread()
+            .user_id(app.primary)
+            .title(title.to_owned())
+            .call()
+            .await
+            .unwrap();
+        threads.push(thread.id);
+    }
+    let thread_id = threads[2];
+    for (message_type, text) in [(MessageType::Human, "Hi"), (MessageType::Ai, "Hello")] {
+        app.db
+            .create_message()
+            .thread_id(thread_id)
+            .user_id(app.primary)
+            .message_type(message_type)
+            .content(json!([{ "type": "text", "text": text }]))
+            .call()
+            .await
+            .unwrap();
+    }
+
+    let (status, body) = app.get("/v1/viewer/threads?limit=2").await;
+    assert_eq!(status, StatusCode::OK);
+    let page: ListThreadsResponse = serde_json::from_value(body).unwrap();
+    assert_eq!(page.threads.len(), 2);
+    assert_eq!(page.threads[0].title.as_deref(), Some("Third"));
+    assert_eq!(page.next_offset, Some(2));
+    let (_, body) = app.get("/v1/viewer/threads?limit=2&offset=2").await;
+    let page: ListThreadsResponse = serde_json::from_value(body).unwrap();
+    assert_eq!(page.threads.len(), 1);
+    assert_eq!(page.next_offset, None);
+
+    let (status, body) = app.get(&format!("/v1/viewer/threads/{thread_id}")).await;
+    assert_eq!(status, StatusCode::OK);
+    let thread: GetThreadResponse = serde_json::from_value(body).unwrap();
+    assert_eq!(thread.thread.id, thread_id);
+
+    let (status, body) = app
+        .get(&format!("/v1/viewer/threads/{thread_id}/messages"))
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let messages: ListMessagesResponse = serde_json::from_value(body).unwrap();
+    let roles: Vec<_> = messages
+        .messages
+        .iter()
+        .map(|m| (m.role, m.text.as_str()))
+        .collect();
+    assert_eq!(
+        roles,
+        [(MessageRole::Human, "Hi"), (MessageRole::Ai, "Hello")]
+    );
+
+    app.act_as(app.other);
+    let (_, body) = app.get("/v1/viewer/threads").await;
+    let page: ListThreadsResponse = serde_json::from_value(body).unwrap();
+    assert!(page.threads.is_empty());
+    let (status, body) = app.get(&format!("/v1/viewer/threads/{thread_id}")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["error"], "not_found");
+    let (status, _) = app
+        .get(&format!("/v1/viewer/threads/{thread_id}/messages"))
+        .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[sqlx::test(migrations = "../be-remote-db/src/migrations")]
+async fn activities_and_the_daily_summary_read_the_timeline(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let at = |hour| Utc.with_ymd_and_hms(2026, 3, 2, hour, 0, 0).unwrap();
+    for (identity, started_at, ended_at) in [
+        ("editor", at(9), at(11)),
+        ("browser", at(11), at(12)),
+        ("editor", at(23), at(23) + TimeDelta::hours(1)),
+    ] {
+        app.db
+            .insert_activity_session()
+            .user_id(app.primary)
+            .identity_key(identity.to_owned())
+            .display_name(identity.to_owned())
+            .process_name(identity.to_owned())
+            .started_at(started_at)
+            .ended_at(ended_at)
+            .call()
+            .await
+            .unwrap();
+    }
+
+    let (status, body) = app.get("/v1/viewer/activities").await;
+    assert_eq!(status, StatusCode::OK);
+    let page: ListActivitiesResponse = serde_json::from_value(body).unwrap();
+    assert_eq!(page.activities.len(), 2);
+    assert!(page.activities[0].latest_session.is_some());
+
+    let (status, body) = app.get("/v1/viewer/summaries/daily?date=2026-03-02").await;
+    assert_eq!(status, StatusCode::OK);
+    let summary: DailySummary = serde_json::from_value(body).unwrap();
+    assert_eq!(summary.session_count, 3);
+    assert_eq!(summary.active_seconds, 4 * 3600);
+    assert_eq!(summary.activities[0].display_name, "editor");
+    assert_eq!(summary.activities[0].seconds, 3 * 3600);
+
+    // The caller's day at UTC+2 ends at 22:00 UTC, before the late session.
+    let (_, body) = app
+        .get("/v1/viewer/summaries/daily?date=2026-03-02&utc_offset_minutes=120")
+        .await;
+    let summary: DailySummary = serde_json::from_value(body).unwrap();
+    assert_eq!(summary.session_count, 2);
+
+    let (status, body) = app
+        .get("/v1/viewer/summaries/daily?date=2026-03-02&utc_offset_minutes=5000")
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "invalid_argument");
+
+    app.act_as(app.other);
+    let (_, body) = app.get("/v1/viewer/summaries/daily?date=2026-03-02").await;
+    let summary: DailySummary = serde_json::from_value(body).unwrap();
+    assert_eq!(summary.session_count, 0);
+}
+
+#[sqlx::test(migrations = "../be-remote-db/src/migrations")]
+async fn serves_the_openapi_document(pool: PgPool) {
+    let app = spawn_app(pool).await;
+    let (status, body) = app.get("/v1/viewer/openapi.json").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["openapi"].as_str().unwrap().starts_with("3."));
+    assert!(body["paths"]["/v1/viewer/summaries/daily"]["get"].is_object());
+}
@@ -0,0 +1,139 @@
+use axum::Json;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use be_auth_core::{InvalidUserId, MissingClaims};
+use serde::Serialize;
+use thiserror::Error;
+
+/// Wire envelope for error responses emitted by this service. Same
+/// `{ error, message }` shape as the other REST services.
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookErrorResponse {
+    /// Stable machine identifier (e.g. `not_found`, `invalid_argument`).
+    pub error: &'static str,
+    /// Human-readable description. Safe to surface in client UIs.
+    pub message: String,
+}
+
+#[derive(Error, Debug)]
+pub enum WebhookServiceError {
+    #[error("Authentication failed: {0}")]
+    Unauthenticated(String),
+
+    #[error("Webhook endpoint not found")]
+    NotFound,
+
+    #[error("{0}")]
+    InvalidArgument(String),
+
+    /// The user already has as many endpoints as they may register.
+    #[error("{0}")]
+    Conflict(String),
+
+    #[error("Database error: {0}")]
+    Database(#[source] be_remote_db::DbError),
+}
+
+impl WebhookServiceError {
+    pub fn unauthenticated(msg: impl Into<String>) -> Self {
+        Self::Unauthenticated(msg.into())
+    }
+
+    pub fn invalid_argument(msg: impl Into<String>) -> Self {
+        Self::InvalidArgument(msg.into())
+    }
+
+    pub fn conflict(msg: impl Into<String>) -> Self {
+        Self::Conflict(msg.into())
+    }
+
+    /// Stable identifier surfaced to clients in the error envelope.
+    pub fn error_kind(&self) -> &'static str {
+        match self {
+            Self::Unauthenticated(_) => "unauthenticated",
+            Self::NotFound => "not_found",
+            Self::InvalidArgument(_) => "invalid_argument",
+            Self::Conflict(_) => "conflict",
+            Self::Database(_) => "database_error",
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        match self {
+            Self::Unauthenticated(_) => StatusCode::UNAUTHORIZED,
+            Self::NotFound => StatusCode::NOT_FOUND,
+            Self::InvalidArgument(_) => StatusCode::BAD_REQUEST,
+            Self::Conflict(_) => StatusCode::CONFLICT,
+            Self::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl From<be_remote_db::DbError> for WebhookServiceError {
+    fn from(err: be_remote_db::DbError) -> Self {
+        match err {
+            be_remote_db::DbError::NotFound { .. } => Self::NotFound,
+            other => Self::Database(other),
+        }
+    }
+}
+
+impl From<MissingClaims> for WebhookServiceError {
+    fn from(_: MissingClaims) -> Self {
+        Self::unauthenticated("Missing authenticated claims")
+    }
+}
+
+impl From<InvalidUserId> for WebhookServiceError {
+    fn from(err: InvalidUserId) -> Self {
+        Self::unauthenticated(err.to_string())
+    }
+}
+
+impl IntoResponse for WebhookServiceError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let kind = self.error_kind();
+        let detail = self.to_string();
+
+        match &self {
+            Self::Unauthenticated(_) => {
+                tracing::warn!(error = %detail, "Webhook service authentication error");
+            }
+            Self::NotFound | Self::InvalidArgument(_) | Self::Conflict(_) => {
+                tracing::debug!(error = %detail, "Webhook service client error");
+            }
+            Self::Database(_) => {
+                tracing::error!(error = %detail, "Webhook service internal error");
+            }
+        }
+
+        let message = match &self {
+            Self::Database(_) => "Database operation failed".to_string(),
+            _ => detail,
+        };
+
+        (
+            status,
+            Json(WebhookErrorResponse {
+                error: kind,
+                message,
+            }),
+        )
+            .into_response()
+    }
+}
+
+pub type WebhookResult<T> = std::result::Result<T, WebhookServiceError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn db_not_found_maps_to_404() {
+        let err: WebhookServiceError = be_remote_db::DbError::not_found("webhook endpoint").into();
+        assert!(matches!(err, WebhookServiceError::NotFound));
+        assert_eq!(err.status(), StatusCode::NOT_FOUND);
+    }
+}
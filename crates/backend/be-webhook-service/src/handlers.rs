@@ -0,0 +1,269 @@
+use std::sync::Arc;
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use be_auth_core::AuthUser;
+use be_remote_db::WebhookEventType;
+use uuid::Uuid;
+
+use crate::AppState;
+use crate::destination;
+use crate::error::{WebhookResult, WebhookServiceError};
+use crate::signature::generate_secret;
+use crate::types::{
+    CreateWebhookEndpointRequest, CreateWebhookEndpointResponse, ListWebhookDeliveriesQuery,
+    ListWebhookDeliveriesResponse, ListWebhookEndpointsResponse, UpdateWebhookEndpointRequest,
+    WebhookEndpointResponse,
+};
+
+/// Endpoints one user may register.
+const MAX_ENDPOINTS_PER_USER: i64 = 10;
+
+/// Automations one endpoint may be narrowed to.
+const MAX_AUTOMATION_IDS: usize = 50;
+
+const DELIVERIES_DEFAULT_LIMIT: u32 = 50;
+const DELIVERIES_MAX_LIMIT: u32 = 100;
+
+/// Register an endpoint. The response is the only place the signing
+/// secret is ever shown.
+#[tracing::instrument(skip_all, fields(user_id))]
+pub async fn create_endpoint(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Json(body): Json<CreateWebhookEndpointRequest>,
+) -> WebhookResult<(StatusCode, Json<CreateWebhookEndpointResponse>)> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    let url = destination::validate(body.kind, &body.url)
+        .map_err(WebhookServiceError::InvalidArgument)?;
+    let event_types = event_type_names(&body.event_types)?;
+    let automation_ids = automation_filter(body.automation_ids)?;
+    if state.db.count_webhook_endpoints(user_id).await? >= MAX_ENDPOINTS_PER_USER {
+        return Err(WebhookServiceError::conflict(format!(
+            "At most {MAX_ENDPOINTS_PER_USER} webhook endpoints can be registered"
+        )));
+    }
+
+    let secret = generate_secret();
+    let endpoint = state
+        .db
+        .create_webhook_endpoint()
+        .user_id(user_id)
+        .kind(body.kind)
+        .url(url.as_str())
+        .secret(&secret)
+        .event_types(&event_types)
+        .maybe_automation_ids(automation_ids.as_deref())
+        .call()
+        .await?;
+
+    tracing::info!(
+        endpoint_id = %endpoint.id,
+        kind = ?endpoint.kind,
+        ?event_types,
+        "Webhook endpoint registered"
+    );
+    Ok((
+        StatusCode::CREATED,
+        Json(CreateWebhookEndpointResponse {
+            endpoint: endpoint.into(),
+            secret,
+        }),
+    ))
+}
+
+#[tracing::instrument(skip_all, fields(user_id))]
+pub async fn list_endpoints(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+) -> WebhookResult<Json<ListWebhookEndpointsResponse>> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    let endpoints = state
+        .db
+        .list_webhook_endpoints(user_id)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect();
+    Ok(Json(ListWebhookEndpointsResponse { endpoints }))
+}
+
+#[tracing::instrument(skip_all, fields(user_id, endpoint_id = %endpoint_id))]
+pub async fn update_endpoint(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(endpoint_id): Path<Uuid>,
+    Json(body): Json<UpdateWebhookEndpointRequest>,
+) -> WebhookResult<Json<WebhookEndpointResponse>> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    let url = match body.url.as_deref() {
+        Some(raw) => {
+            // The new URL has to suit the endpoint's kind.
+            let endpoint = state
+                .db
+                .get_webhook_endpoint()
+                .id(endpoint_id)
+                .user_id(user_id)
+                .call()
+                .await?;
+            Some(
+                destination::validate(endpoint.kind, raw)
+                    .map_err(WebhookServiceError::InvalidArgument)?,
+            )
+        }
+        None => None,
+    };
+    let event_types = body
+        .event_types
+        .as_deref()
+        .map(event_type_names)
+        .transpose()?;
+    let automation_ids = body.automation_ids.map(automation_filter).transpose()?;
+
+    let endpoint = state
+        .db
+        .update_webhook_endpoint()
+        .id(endpoint_id)
+        .user_id(user_id)
+        .maybe_url(url.as_ref().map(|url| url.as_str()))
+        .maybe_event_types(event_types.as_deref())
+        .maybe_automation_ids(automation_ids.as_ref().map(Option::as_deref))
+        .maybe_enabled(body.enabled)
+        .call()
+        .await?;
+    Ok(Json(endpoint.into()))
+}
+
+#[tracing::instrument(skip_all, fields(user_id, endpoint_id = %endpoint_id))]
+pub async fn delete_endpoint(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(endpoint_id): Path<Uuid>,
+) -> WebhookResult<StatusCode> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    state
+        .db
+        .delete_webhook_endpoint()
+        .id(endpoint_id)
+        .user_id(user_id)
+        .call()
+        .await?;
+    tracing::info!("Webhook endpoint deleted");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// The endpoint's delivery log, newest first.
+#[tracing::instrument(skip_all, fields(user_id, endpoint_id = %endpoint_id))]
+pub async fn list_deliveries(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(endpoint_id): Path<Uuid>,
+    Query(query): Query<ListWebhookDeliveriesQuery>,
+) -> WebhookResult<Json<ListWebhookDeliveriesResponse>> {
+    let user_id = user.user_id()?;
+    tracing::Span::current().record("user_id", tracing::field::display(user_id));
+
+    // Resolves the endpoint first so someone else's id is a 404 rather
+    // than an empty log.
+    state
+        .db
+        .get_webhook_endpoint()
+        .id(endpoint_id)
+        .user_id(user_id)
+        .call()
+        .await?;
+    let limit = query
+        .limit
+        .unwrap_or(DELIVERIES_DEFAULT_LIMIT)
+        .clamp(1, DELIVERIES_MAX_LIMIT);
+    let deliveries = state
+        .db
+        .list_webhook_deliveries()
+        .endpoint_id(endpoint_id)
+        .user_id(user_id)
+        .limit(i64::from(limit))
+        .call()
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect();
+    Ok(Json(ListWebhookDeliveriesResponse { deliveries }))
+}
+
+/// Deduplicated dotted names, in the order given.
+fn event_type_names(event_types: &[WebhookEventType]) -> WebhookResult<Vec<String>> {
+    if event_types.is_empty() {
+        return Err(WebhookServiceError::invalid_argument(
+            "Subscribe to at least one event type",
+        ));
+    }
+    let mut names: Vec<String> = Vec::with_capacity(event_types.len());
+    for event_type in event_types {
+        if !names.iter().any(|name| name == event_type.as_str()) {
+            names.push(event_type.as_str().to_owned());
+        }
+    }
+    Ok(names)
+}
+
+/// Deduplicated automation filter; `None` (every automation) when empty.
+fn automation_filter(ids: Option<Vec<Uuid>>) -> WebhookResult<Option<Vec<Uuid>>> {
+    let Some(ids) = ids else {
+        return Ok(None);
+    };
+    let mut unique: Vec<Uuid> = Vec::with_capacity(ids.len());
+    for id in ids {
+        if !unique.contains(&id) {
+            unique.push(id);
+        }
+    }
+    if unique.len() > MAX_AUTOMATION_IDS {
+        return Err(WebhookServiceError::invalid_argument(format!(
+            "An endpoint can be limited to at most {MAX_AUTOMATION_IDS} automations"
+        )));
+    }
+    Ok((!unique.is_empty()).then_some(unique))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_types_are_required_and_deduplicated() {
+        assert!(event_type_names(&[]).is_err());
+        assert_eq!(
+            event_type_names(&[
+                WebhookEventType::ExportFinished,
+                WebhookEventType::ThreadCreated,
+                WebhookEventType::ExportFinished,
+            ])
+            .unwrap(),
+            ["export.finished", "thread.created"]
+        );
+    }
+
+    #[test]
+    fn automation_filter_is_deduplicated_and_capped() {
+        let id = Uuid::now_v7();
+        assert_eq!(automation_filter(None).unwrap(), None);
+        assert_eq!(automation_filter(Some(vec![])).unwrap(), None);
+        assert_eq!(
+            automation_filter(Some(vec![id, id])).unwrap(),
+            Some(vec![id])
+        );
+        let too_many = (0..=MAX_AUTOMATION_IDS).map(|_| Uuid::now_v7()).collect();
+        assert!(automation_filter(Some(too_many)).is_err());
+    }
+}
@@ -0,0 +1,115 @@
+//! HTTP outbound webhook service.
+//!
+//! Lets a user register HTTPS endpoints that receive their events — for
+//! wiring Eurora into Zapier-style tooling. Authentication and Casbin
+//! authorization are applied by the surrounding `be-authz` middleware in
+//! `be-monolith`; this crate only assumes that a verified
+//! [`be_auth_core::Claims`] has been inserted into request extensions by
+//! the time a handler runs.
+//!
+//! ## Endpoints
+//!
+//! | Method | Path                                | Outcome                                             |
+//! |--------|-------------------------------------|-----------------------------------------------------|
+//! | POST   | `/webhooks`                         | `201 CreateWebhookEndpointResponse`, secret included. |
+//! | GET    | `/webhooks`                         | `200 ListWebhookEndpointsResponse`.                 |
+//! | PATCH  | `/webhooks/{endpoint_id}`           | `200 WebhookEndpointResponse`.                      |
+//! | DELETE | `/webhooks/{endpoint_id}`           | `204`; the delivery log goes with it.               |
+//! | GET    | `/webhooks/{endpoint_id}/deliveries`| `200 ListWebhookDeliveriesResponse`, newest first.  |
+//!
+//! ## Delivery
+//!
+//! Services publish an event with
+//! [`DatabaseManager::enqueue_webhook_event`], which queues one delivery per
+//! subscribed endpoint. The [`WebhookWorkerHandle`] worker POSTs each one
+//! as JSON, signed as described in [`signature`], and retries failures
+//! with backoff for about a day before marking the delivery `failed`. Any
+//! `2xx` response counts as delivered. Deliveries are kept for 30 days as
+//! the endpoint's delivery log.
+//!
+//! ## Slack and Discord
+//!
+//! An endpoint created with `kind: "slack"` or `kind: "discord"` must use
+//! that platform's incoming webhook URL, and is sent a formatted message
+//! (see `notifier`) instead of the signed envelope. Any endpoint can be
+//! narrowed to some automations with `automation_ids`, and can subscribe
+//! to `automation.daily_summary`, which the worker queues once a day with
+//! the run counts of each automation.
+
+mod destination;
+mod error;
+mod handlers;
+mod notifier;
+pub mod signature;
+mod types;
+mod worker;
+
+use std::sync::Arc;
+
+use axum::{
+    Router,
+    routing::{get, patch},
+};
+use be_remote_db::DatabaseManager;
+use tower_http::trace::TraceLayer;
+
+pub use error::{WebhookErrorResponse, WebhookResult, WebhookServiceError};
+pub use types::{
+    CreateWebhookEndpointRequest, CreateWebhookEndpointResponse, ListWebhookDeliveriesQuery,
+    ListWebhookDeliveriesResponse, ListWebhookEndpointsResponse, UpdateWebhookEndpointRequest,
+    WebhookDeliveryResponse, WebhookEndpointResponse,
+};
+pub use worker::WebhookWorkerHandle;
+
+/// Shared state injected into Axum handlers via `State<Arc<AppState>>`.
+pub struct AppState {
+    pub db: Arc<DatabaseManager>,
+}
+
+impl AppState {
+    pub fn new(db: Arc<DatabaseManager>) -> Self {
+        Self { db }
+    }
+}
+
+/// Build the webhook router with the supplied dependencies.
+///
+/// Returns the bare router; the caller is expected to apply the
+/// cross-cutting layers (CORS, body limit, auth middleware) at the
+/// monolith level so all REST services share the same outer pipeline.
+pub fn create_router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route(
+            "/webhooks",
+            get(handlers::list_endpoints).post(handlers::create_endpoint),
+        )
+        .route(
+            "/webhooks/{endpoint_id}",
+            patch(handlers::update_endpoint).delete(handlers::delete_endpoint),
+        )
+        .route(
+            "/webhooks/{endpoint_id}/deliveries",
+            get(handlers::list_deliveries),
+        )
+        .layer(TraceLayer::new_for_http())
+        .with_state(state)
+}
+
+pub struct WebhookService {
+    pub router: Router,
+    pub worker: WebhookWorkerHandle,
+}
+
+/// Wire up application state, start the delivery worker, and return the
+/// router ready to merge into the monolith HTTP pipeline. The caller owns
+/// the returned [`WebhookWorkerHandle`] and should shut it down after the
+/// server stops.
+pub fn init_webhook_service(db: Arc<DatabaseManager>) -> WebhookService {
+    tracing::debug!("Initializing webhook service");
+    let state = Arc::new(AppState::new(db));
+    let worker = worker::spawn_worker(state.clone());
+    WebhookService {
+        router: create_router(state),
+        worker,
+    }
+}
@@ -0,0 +1,278 @@
+//! Messages for Slack and Discord incoming webhooks.
+//!
+//! Chat endpoints can't verify a signature or make sense of the JSON
+//! envelope, so they are sent a message built from the event instead:
+//! Slack blocks or a Discord embed. Both are rendered from the same
+//! [`Message`], truncated to each platform's limits.
+
+use be_remote_db::{WebhookEndpointKind, WebhookEventType};
+use serde_json::{Value, json};
+
+/// Slack caps a header at 150 characters and a section at 3000.
+const SLACK_HEADER_CHARS: usize = 150;
+const SLACK_SECTION_CHARS: usize = 3000;
+const SLACK_FIELD_CHARS: usize = 2000;
+const SLACK_MAX_FIELDS: usize = 10;
+
+/// Discord caps an embed title at 256 characters, its description at 4096
+/// and a field value at 1024.
+const DISCORD_TITLE_CHARS: usize = 256;
+const DISCORD_DESCRIPTION_CHARS: usize = 4096;
+const DISCORD_FIELD_CHARS: usize = 1024;
+const DISCORD_MAX_FIELDS: usize = 25;
+
+const COLOR_SUCCESS: u32 = 0x2eb67d;
+const COLOR_FAILURE: u32 = 0xe01e5a;
+const COLOR_NEUTRAL: u32 = 0x6e56cf;
+
+/// Platform-neutral message for one event.
+#[derive(Debug, PartialEq)]
+struct Message {
+    title: String,
+    body: String,
+    fields: Vec<(String, String)>,
+    color: u32,
+}
+
+/// Body to POST to an endpoint of `kind`, or `None` for
+/// [`WebhookEndpointKind::Generic`], which gets the signed envelope.
+pub(crate) fn render(
+    kind: WebhookEndpointKind,
+    event_type: &str,
+    payload: &Value,
+) -> Option<Value> {
+    let message = message(event_type, payload);
+    match kind {
+        WebhookEndpointKind::Generic => None,
+        WebhookEndpointKind::Slack => Some(slack(&message)),
+        WebhookEndpointKind::Discord => Some(discord(&message)),
+    }
+}
+
+fn message(event_type: &str, payload: &Value) -> Message {
+    let str_field = |key: &str| payload.get(key).and_then(Value::as_str).unwrap_or_default();
+    match WebhookEventType::parse(event_type) {
+        Some(WebhookEventType::AutomationRun) => {
+            let status = str_field("status");
+            let (body, color) = match status {
+                "succeeded" => (str_field("output"), COLOR_SUCCESS),
+                "failed" => (str_field("error"), COLOR_FAILURE),
+                _ => ("", COLOR_NEUTRAL),
+            };
+            Message {
+                title: format!("{} {}", str_field("automation_name"), status),
+                body: body.to_owned(),
+                fields: Vec::new(),
+                color,
+            }
+        }
+        Some(WebhookEventType::AutomationDailySummary) => {
+            let automations = payload
+                .get("automations")
+                .and_then(Value::as_array)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let count = |entry: &Value, key: &str| entry.get(key).and_then(Value::as_u64);
+            let failed: u64 = automations.iter().filter_map(|a| count(a, "failed")).sum();
+            let fields = automations
+                .iter()
+                .map(|a| {
+                    let name = a.get("automation_name").and_then(Value::as_str);
+                    let summary = format!(
+                        "{} succeeded, {} failed, {} skipped",
+                        count(a, "succeeded").unwrap_or(0),
+                        count(a, "failed").unwrap_or(0),
+                        count(a, "skipped").unwrap_or(0),
+                    );
+                    (name.unwrap_or("Automation").to_owned(), summary)
+                })
+                .collect();
+            Message {
+                title: "Daily automation summary".to_owned(),
+                body: format!(
+                    "{} runs across {} automations in the last day.",
+                    payload
+                        .get("total_runs")
+                        .and_then(Value::as_u64)
+                        .unwrap_or(0),
+                    automations.len(),
+                ),
+                fields,
+                color: if failed > 0 {
+                    COLOR_FAILURE
+                } else {
+                    COLOR_SUCCESS
+                },
+            }
+        }
+        Some(WebhookEventType::ThreadCreated) => Message {
+            title: "New thread".to_owned(),
+            body: str_field("title").to_owned(),
+            fields: Vec::new(),
+            color: COLOR_NEUTRAL,
+        },
+        Some(WebhookEventType::ExportFinished) => {
+            let (body, color) = match str_field("status") {
+                "ready" => (
+                    format!("Your data export is ready: {}", str_field("download_url")),
+                    COLOR_SUCCESS,
+                ),
+                _ => (
+                    format!("Your data export failed: {}", str_field("error")),
+                    COLOR_FAILURE,
+                ),
+            };
+            Message {
+                title: "Data export".to_owned(),
+                body,
+                fields: Vec::new(),
+                color,
+            }
+        }
+        None => Message {
+            title: event_type.to_owned(),
+            body: String::new(),
+            fields: Vec::new(),
+            color: COLOR_NEUTRAL,
+        },
+    }
+}
+
+fn slack(message: &Message) -> Value {
+    let mut blocks = vec![json!({
+        "type": "header",
+        "text": { "type": "plain_text", "text": truncate(&message.title, SLACK_HEADER_CHARS) },
+    })];
+    if !message.body.is_empty() {
+        blocks.push(json!({
+            "type": "section",
+            "text": { "type": "mrkdwn", "text": truncate(&message.body, SLACK_SECTION_CHARS) },
+        }));
+    }
+    if !message.fields.is_empty() {
+        let fields: Vec<Value> = message
+            .fields
+            .iter()
+            .take(SLACK_MAX_FIELDS)
+            .map(|(name, value)| {
+                let text = format!("*{name}*\n{value}");
+                json!({ "type": "mrkdwn", "text": truncate(&text, SLACK_FIELD_CHARS) })
+            })
+            .collect();
+        blocks.push(json!({ "type": "section", "fields": fields }));
+    }
+    json!({
+        // Shown in notifications and by clients that can't render blocks.
+        "text": truncate(&message.title, SLACK_SECTION_CHARS),
+        "blocks": blocks,
+    })
+}
+
+fn discord(message: &Message) -> Value {
+    let fields: Vec<Value> = message
+        .fields
+        .iter()
+        .take(DISCORD_MAX_FIELDS)
+        .map(|(name, value)| {
+            json!({
+                "name": truncate(name, DISCORD_TITLE_CHARS),
+                "value": truncate(value, DISCORD_FIELD_CHARS),
+                "inline": true,
+            })
+        })
+        .collect();
+    json!({
+        "embeds": [{
+            "title": truncate(&message.title, DISCORD_TITLE_CHARS),
+            "description": truncate(&message.body, DISCORD_DESCRIPTION_CHARS),
+            "color": message.color,
+            "fields": fields,
+        }],
+    })
+}
+
+/// At most `max` characters of `text`, ending in an ellipsis when cut.
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_owned();
+    }
+    let mut cut: String = text.chars().take(max - 1).collect();
+    cut.push('…');
+    cut
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_payload(status: &str) -> Value {
+        json!({
+            "automation_name": "Morning digest",
+            "status": status,
+            "output": "All caught up.",
+            "error": "Model unavailable",
+        })
+    }
+
+    #[test]
+    fn generic_endpoints_get_no_message() {
+        assert_eq!(
+            render(WebhookEndpointKind::Generic, "automation.run", &json!({})),
+            None
+        );
+    }
+
+    #[test]
+    fn automation_runs_show_output_or_error() {
+        let slack = render(
+            WebhookEndpointKind::Slack,
+            "automation.run",
+            &run_payload("succeeded"),
+        )
+        .unwrap();
+        assert_eq!(slack["text"], "Morning digest succeeded");
+        assert_eq!(slack["blocks"][1]["text"]["text"], "All caught up.");
+
+        let discord = render(
+            WebhookEndpointKind::Discord,
+            "automation.run",
+            &run_payload("failed"),
+        )
+        .unwrap();
+        let embed = &discord["embeds"][0];
+        assert_eq!(embed["title"], "Morning digest failed");
+        assert_eq!(embed["description"], "Model unavailable");
+        assert_eq!(embed["color"], COLOR_FAILURE);
+    }
+
+    #[test]
+    fn summaries_list_each_automation() {
+        let payload = json!({
+            "total_runs": 3,
+            "automations": [
+                { "automation_name": "Digest", "succeeded": 2, "failed": 0, "skipped": 0 },
+                { "automation_name": "Triage", "succeeded": 0, "failed": 1, "skipped": 0 },
+            ],
+        });
+        let message = message("automation.daily_summary", &payload);
+        assert_eq!(message.body, "3 runs across 2 automations in the last day.");
+        assert_eq!(
+            message.fields[1],
+            (
+                "Triage".to_owned(),
+                "0 succeeded, 1 failed, 0 skipped".to_owned()
+            )
+        );
+        assert_eq!(message.color, COLOR_FAILURE);
+    }
+
+    #[test]
+    fn long_text_is_cut_to_the_platform_limit() {
+        let mut payload = run_payload("succeeded");
+        payload["output"] = json!("x".repeat(5000));
+        let slack = render(WebhookEndpointKind::Slack, "automation.run", &payload).unwrap();
+        let text = slack["blocks"][1]["text"]["text"].as_str().unwrap();
+        assert_eq!(text.chars().count(), SLACK_SECTION_CHARS);
+        assert!(text.ends_with('…'));
+    }
+}
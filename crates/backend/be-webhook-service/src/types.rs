@@ -0,0 +1,180 @@
+//! Wire types for `/webhooks`.
+
+use be_remote_db::{
+    WebhookDelivery, WebhookDeliveryStatus, WebhookEndpoint, WebhookEndpointKind, WebhookEventType,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateWebhookEndpointRequest {
+    /// `generic` (the default) for the signed JSON envelope, or `slack` /
+    /// `discord` for a formatted message to that platform's incoming
+    /// webhook.
+    #[serde(default = "default_kind")]
+    pub kind: WebhookEndpointKind,
+    /// Public `https://` URL the events are POSTed to.
+    pub url: String,
+    /// At least one of `thread.created`, `export.finished`,
+    /// `automation.run`, `automation.daily_summary`.
+    pub event_types: Vec<WebhookEventType>,
+    /// Only send automation events for these automations. Omitted or
+    /// empty for all of them.
+    #[serde(default)]
+    pub automation_ids: Option<Vec<Uuid>>,
+}
+
+fn default_kind() -> WebhookEndpointKind {
+    WebhookEndpointKind::Generic
+}
+
+/// Body of `PATCH /webhooks/{endpoint_id}`. Omitted fields are unchanged.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct UpdateWebhookEndpointRequest {
+    pub url: Option<String>,
+    pub event_types: Option<Vec<WebhookEventType>>,
+    /// Replaces the automation filter; an empty list clears it.
+    pub automation_ids: Option<Vec<Uuid>>,
+    /// `false` pauses the endpoint: events keep queueing and are sent once
+    /// it is enabled again.
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookEndpointResponse {
+    pub id: Uuid,
+    pub kind: WebhookEndpointKind,
+    pub url: String,
+    pub event_types: Vec<String>,
+    /// `None` when automation events are sent for every automation.
+    pub automation_ids: Option<Vec<Uuid>>,
+    pub enabled: bool,
+    /// When the next daily summary is sent, while subscribed to one.
+    pub next_summary_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<WebhookEndpoint> for WebhookEndpointResponse {
+    fn from(row: WebhookEndpoint) -> Self {
+        Self {
+            id: row.id,
+            kind: row.kind,
+            url: row.url,
+            event_types: row.event_types,
+            automation_ids: row.automation_ids,
+            enabled: row.enabled,
+            next_summary_at: row.next_summary_at,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+/// Body of `POST /webhooks`. The only response that carries the signing
+/// secret; it can't be read back later.
+#[derive(Debug, Clone, Serialize)]
+pub struct CreateWebhookEndpointResponse {
+    #[serde(flatten)]
+    pub endpoint: WebhookEndpointResponse,
+    pub secret: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ListWebhookEndpointsResponse {
+    pub endpoints: Vec<WebhookEndpointResponse>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ListWebhookDeliveriesQuery {
+    /// At most 100; defaults to 50.
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookDeliveryResponse {
+    pub id: Uuid,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: i32,
+    /// HTTP status of the last attempt, when it got a response.
+    pub response_status: Option<u16>,
+    /// Why the last attempt failed.
+    pub error: Option<String>,
+    /// When the next attempt is due, while the delivery is pending.
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<WebhookDelivery> for WebhookDeliveryResponse {
+    fn from(row: WebhookDelivery) -> Self {
+        Self {
+            next_attempt_at: (row.status == WebhookDeliveryStatus::Pending)
+                .then_some(row.next_attempt_at),
+            id: row.id,
+            event_type: row.event_type,
+            payload: row.payload,
+            status: row.status,
+            attempts: row.attempts,
+            response_status: row.response_status.and_then(|s| u16::try_from(s).ok()),
+            error: row.error,
+            delivered_at: row.delivered_at,
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ListWebhookDeliveriesResponse {
+    pub deliveries: Vec<WebhookDeliveryResponse>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_types_use_dotted_names() {
+        let req: CreateWebhookEndpointRequest = serde_json::from_str(
+            r#"{"url":"https://example.com","event_types":["thread.created","automation.run"]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            req.event_types,
+            [
+                WebhookEventType::ThreadCreated,
+                WebhookEventType::AutomationRun
+            ]
+        );
+        assert!(
+            serde_json::from_str::<CreateWebhookEndpointRequest>(
+                r#"{"url":"https://example.com","event_types":["thread.deleted"]}"#
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn endpoints_default_to_generic() {
+        let req: CreateWebhookEndpointRequest = serde_json::from_str(
+            r#"{"url":"https://example.com","event_types":["automation.daily_summary"]}"#,
+        )
+        .unwrap();
+        assert_eq!(req.kind, WebhookEndpointKind::Generic);
+        assert_eq!(req.automation_ids, None);
+        assert_eq!(req.event_types, [WebhookEventType::AutomationDailySummary]);
+
+        let req: CreateWebhookEndpointRequest = serde_json::from_value(serde_json::json!({
+            "kind": "discord",
+            "url": "https://discord.com/api/webhooks/1/a",
+            "event_types": ["automation.run"],
+        }))
+        .unwrap();
+        assert_eq!(req.kind, WebhookEndpointKind::Discord);
+    }
+}
@@ -0,0 +1,461 @@
+//! Background worker that sends queued deliveries and prunes the log.
+//!
+//! Every tick claims the deliveries that are due (see
+//! [`DatabaseManager::claim_due_webhook_deliveries`] for the lease scheme)
+//! and sends them concurrently. A `2xx` response marks a delivery
+//! `succeeded`; anything else is retried after the next of
+//! [`RETRY_DELAYS`], and once those run out the delivery is `failed`.
+//!
+//! It also queues daily summaries: endpoints subscribed to
+//! `automation.daily_summary` are claimed once a day (see
+//! [`DatabaseManager::claim_due_webhook_summaries`]) and sent a tally of
+//! the automation runs of the day before, unless there were none.
+
+use std::sync::Arc;
+
+use be_remote_db::{
+    AutomationRunStatus, ClaimedWebhookDelivery, DatabaseManager, DbError, NamedAutomationRun,
+    WebhookDeliveryStatus, WebhookEndpoint, WebhookEventType,
+};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use reqwest::header::{CONTENT_TYPE, HeaderValue};
+use serde::Serialize;
+use serde_json::{Value, json};
+use tokio::sync::oneshot;
+use tokio::time::{Duration, sleep};
+use url::Url;
+use uuid::Uuid;
+
+use crate::AppState;
+use crate::destination;
+use crate::notifier;
+use crate::signature::{DELIVERY_HEADER, EVENT_HEADER, SIGNATURE_HEADER, sign};
+
+/// Wait before each retry; a delivery gets one attempt more than there are
+/// entries. Adds up to a little over a day, long enough to ride out a
+/// receiver's outage.
+const RETRY_DELAYS: [ChronoDuration; 6] = [
+    ChronoDuration::minutes(1),
+    ChronoDuration::minutes(5),
+    ChronoDuration::minutes(30),
+    ChronoDuration::hours(2),
+    ChronoDuration::hours(6),
+    ChronoDuration::hours(16),
+];
+
+/// Budget for one attempt, connection included. Receivers are expected to
+/// acknowledge quickly and do their work afterwards.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// How long a claimed delivery stays leased. Longer than
+/// [`REQUEST_TIMEOUT`] plus name resolution so a slow attempt is never
+/// claimed a second time.
+const LEASE: ChronoDuration = ChronoDuration::minutes(2);
+
+/// Deliveries claimed, and sent concurrently, per tick.
+const BATCH_SIZE: usize = 16;
+
+/// Time between ticks when nothing was due.
+const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Time between ticks when the last one filled a whole batch.
+const BUSY_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// How long the delivery log is kept.
+const RETENTION: ChronoDuration = ChronoDuration::days(30);
+
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Time between checks for due daily summaries.
+const SUMMARY_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Endpoints whose summary is queued per check.
+const SUMMARY_BATCH_SIZE: i64 = 50;
+
+/// Longest error kept on a delivery.
+const MAX_ERROR_CHARS: usize = 500;
+
+const USER_AGENT: &str = "Eurora-Webhooks/1.0";
+
+pub struct WebhookWorkerHandle {
+    shutdown: oneshot::Sender<()>,
+    join: tokio::task::JoinHandle<()>,
+}
+
+impl WebhookWorkerHandle {
+    pub async fn shutdown(self) {
+        let Self { shutdown, join } = self;
+        let _ = shutdown.send(());
+        let _ = join.await;
+    }
+}
+
+/// Spawn the background worker. Attempts in flight when shutdown is
+/// requested are abandoned; their leases lapse and they are sent again.
+pub(crate) fn spawn_worker(state: Arc<AppState>) -> WebhookWorkerHandle {
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+
+    let join = tokio::spawn(async move {
+        tracing::info!("Webhook worker started");
+        tokio::select! {
+            _ = deliver(&state.db) => {}
+            _ = prune(&state.db) => {}
+            _ = summarize(&state.db) => {}
+            _ = shutdown_rx => {}
+        }
+        tracing::info!("Webhook worker shutting down");
+    });
+
+    WebhookWorkerHandle {
+        shutdown: shutdown_tx,
+        join,
+    }
+}
+
+async fn deliver(db: &DatabaseManager) {
+    loop {
+        let claimed = match tick(db).await {
+            Ok(n) => n,
+            Err(e) => {
+                tracing::error!(error = %e, "Webhook worker tick failed");
+                0
+            }
+        };
+        sleep(if claimed >= BATCH_SIZE {
+            BUSY_POLL_INTERVAL
+        } else {
+            IDLE_POLL_INTERVAL
+        })
+        .await;
+    }
+}
+
+async fn tick(db: &DatabaseManager) -> Result<usize, DbError> {
+    let claimed = db
+        .claim_due_webhook_deliveries()
+        .limit(BATCH_SIZE as i64)
+        .lease(LEASE)
+        .call()
+        .await?;
+    let count = claimed.len();
+    futures::future::join_all(claimed.into_iter().map(|c| run_delivery(db, c))).await;
+    Ok(count)
+}
+
+#[tracing::instrument(
+    skip_all,
+    fields(delivery_id = %claimed.delivery.id, endpoint_id = %claimed.delivery.endpoint_id)
+)]
+async fn run_delivery(db: &DatabaseManager, claimed: ClaimedWebhookDelivery) {
+    let delivery = &claimed.delivery;
+    let outcome = send(&claimed).await;
+    let recorded = match outcome {
+        Ok(status) => {
+            tracing::debug!(status, "Webhook delivered");
+            db.record_webhook_attempt()
+                .id(delivery.id)
+                .status(WebhookDeliveryStatus::Succeeded)
+                .response_status(status as i16)
+                .call()
+                .await
+        }
+        Err(failure) => {
+            let retry_in = retry_delay(delivery.attempts);
+            tracing::info!(
+                attempts = delivery.attempts,
+                error = %failure.error,
+                retrying = retry_in.is_some(),
+                "Webhook delivery attempt failed"
+            );
+            db.record_webhook_attempt()
+                .id(delivery.id)
+                .status(if retry_in.is_some() {
+                    WebhookDeliveryStatus::Pending
+                } else {
+                    WebhookDeliveryStatus::Failed
+                })
+                .maybe_response_status(failure.status.map(|s| s as i16))
+                .error(failure.error)
+                .maybe_next_attempt_at(retry_in.map(|delay| Utc::now() + delay))
+                .call()
+                .await
+        }
+    };
+    if let Err(e) = recorded {
+        tracing::error!(error = %e, "Failed to record webhook delivery attempt");
+    }
+}
+
+/// Wait before the next attempt after `attempts` failed ones, or `None`
+/// once the delivery should be given up on.
+fn retry_delay(attempts: i32) -> Option<ChronoDuration> {
+    usize::try_from(attempts)
+        .ok()
+        .and_then(|n| n.checked_sub(1))
+        .and_then(|i| RETRY_DELAYS.get(i).copied())
+}
+
+/// JSON body of every delivery.
+#[derive(Serialize)]
+struct Envelope<'a> {
+    /// The delivery id, also sent as `Eurora-Delivery`.
+    id: Uuid,
+    #[serde(rename = "type")]
+    event_type: &'a str,
+    created_at: DateTime<Utc>,
+    data: &'a serde_json::Value,
+}
+
+struct Failure {
+    status: Option<u16>,
+    error: String,
+}
+
+impl Failure {
+    fn new(status: Option<u16>, error: impl Into<String>) -> Self {
+        let error: String = error.into();
+        Self {
+            status,
+            error: error.chars().take(MAX_ERROR_CHARS).collect(),
+        }
+    }
+}
+
+/// Make one attempt. Returns the response status on success.
+async fn send(claimed: &ClaimedWebhookDelivery) -> Result<u16, Failure> {
+    let delivery = &claimed.delivery;
+    let url = Url::parse(&claimed.url).map_err(|e| Failure::new(None, e.to_string()))?;
+    let pinned = destination::resolve(&url)
+        .await
+        .map_err(|e| Failure::new(None, e))?;
+
+    let mut client = reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .redirect(reqwest::redirect::Policy::none())
+        .timeout(REQUEST_TIMEOUT);
+    if let Some((domain, addrs)) = &pinned {
+        client = client.resolve_to_addrs(domain, addrs);
+    }
+    let client = client
+        .build()
+        .map_err(|e| Failure::new(None, e.to_string()))?;
+
+    // Chat webhooks get a formatted message and nothing to verify.
+    let message = notifier::render(claimed.kind, &delivery.event_type, &delivery.payload);
+    let request = match message {
+        Some(message) => client
+            .post(url)
+            .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+            .body(message.to_string()),
+        None => {
+            let body = serde_json::to_vec(&Envelope {
+                id: delivery.id,
+                event_type: &delivery.event_type,
+                created_at: delivery.created_at,
+                data: &delivery.payload,
+            })
+            .map_err(|e| Failure::new(None, e.to_string()))?;
+            let signature = sign(&claimed.secret, Utc::now().timestamp(), &body);
+            client
+                .post(url)
+                .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+                .header(SIGNATURE_HEADER, signature)
+                .header(EVENT_HEADER, &delivery.event_type)
+                .header(DELIVERY_HEADER, delivery.id.to_string())
+                .body(body)
+        }
+    };
+
+    let response = request.send().await.map_err(|e| {
+        let error = if e.is_timeout() {
+            "Timed out waiting for a response".to_owned()
+        } else {
+            e.without_url().to_string()
+        };
+        Failure::new(None, error)
+    })?;
+
+    let status = response.status();
+    if status.is_success() {
+        Ok(status.as_u16())
+    } else {
+        Err(Failure::new(
+            Some(status.as_u16()),
+            format!("HTTP {status}"),
+        ))
+    }
+}
+
+async fn prune(db: &DatabaseManager) {
+    loop {
+        sleep(PRUNE_INTERVAL).await;
+        match db
+            .prune_webhook_deliveries()
+            .cutoff(Utc::now() - RETENTION)
+            .call()
+            .await
+        {
+            Ok(0) => {}
+            Ok(pruned) => tracing::info!(pruned, "Pruned old webhook deliveries"),
+            Err(e) => tracing::error!(error = %e, "Failed to prune webhook deliveries"),
+        }
+    }
+}
+
+async fn summarize(db: &DatabaseManager) {
+    loop {
+        match queue_summaries(db).await {
+            Ok(0) => {}
+            Ok(queued) => tracing::info!(queued, "Queued automation summaries"),
+            Err(e) => tracing::error!(error = %e, "Failed to queue automation summaries"),
+        }
+        sleep(SUMMARY_POLL_INTERVAL).await;
+    }
+}
+
+async fn queue_summaries(db: &DatabaseManager) -> Result<usize, DbError> {
+    let endpoints = db
+        .claim_due_webhook_summaries()
+        .limit(SUMMARY_BATCH_SIZE)
+        .call()
+        .await?;
+    let mut queued = 0;
+    for endpoint in endpoints {
+        if queue_summary(db, &endpoint).await? {
+            queued += 1;
+        }
+    }
+    Ok(queued)
+}
+
+/// Queue `endpoint`'s summary of the last day. Returns `false` when
+/// there were no runs to report.
+async fn queue_summary(db: &DatabaseManager, endpoint: &WebhookEndpoint) -> Result<bool, DbError> {
+    let until = Utc::now();
+    let since = until - ChronoDuration::days(1);
+    let runs = db
+        .list_automation_runs_since()
+        .user_id(endpoint.user_id)
+        .since(since)
+        .maybe_automation_ids(endpoint.automation_ids.as_deref())
+        .call()
+        .await?;
+    if runs.is_empty() {
+        return Ok(false);
+    }
+    db.enqueue_webhook_delivery()
+        .endpoint_id(endpoint.id)
+        .event_type(WebhookEventType::AutomationDailySummary)
+        .payload(&summary_payload(since, until, &runs))
+        .call()
+        .await?;
+    Ok(true)
+}
+
+/// Run counts per automation, in the order each first ran.
+fn summary_payload(
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+    runs: &[NamedAutomationRun],
+) -> Value {
+    let mut automations: Vec<(Uuid, &str, [u64; 3])> = Vec::new();
+    for named in runs {
+        let index = match automations
+            .iter()
+            .position(|(id, ..)| *id == named.run.automation_id)
+        {
+            Some(index) => index,
+            None => {
+                automations.push((named.run.automation_id, &named.automation_name, [0; 3]));
+                automations.len() - 1
+            }
+        };
+        let counts = &mut automations[index].2;
+        match named.run.status {
+            AutomationRunStatus::Succeeded => counts[0] += 1,
+            AutomationRunStatus::Failed => counts[1] += 1,
+            AutomationRunStatus::Skipped => counts[2] += 1,
+            AutomationRunStatus::Running => {}
+        }
+    }
+    json!({
+        "since": since,
+        "until": until,
+        "total_runs": runs.len(),
+        "automations": automations
+            .into_iter()
+            .map(|(id, name, [succeeded, failed, skipped])| json!({
+                "automation_id": id,
+                "automation_name": name,
+                "succeeded": succeeded,
+                "failed": failed,
+                "skipped": skipped,
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retries_back_off_then_stop() {
+        assert_eq!(retry_delay(1), Some(ChronoDuration::minutes(1)));
+        assert_eq!(retry_delay(2), Some(ChronoDuration::minutes(5)));
+        assert_eq!(
+            retry_delay(RETRY_DELAYS.len() as i32),
+            Some(ChronoDuration::hours(16))
+        );
+        assert_eq!(retry_delay(RETRY_DELAYS.len() as i32 + 1), None);
+        assert_eq!(retry_delay(0), None);
+    }
+
+    #[test]
+    fn envelope_nests_the_payload() {
+        let data = serde_json::json!({ "thread_id": Uuid::nil() });
+        let created_at = Utc::now();
+        let value = serde_json::to_value(Envelope {
+            id: Uuid::nil(),
+            event_type: "thread.created",
+            created_at,
+            data: &data,
+        })
+        .unwrap();
+        assert_eq!(value["type"], "thread.created");
+        assert_eq!(value["data"], data);
+    }
+
+    #[test]
+    fn summaries_count_runs_per_automation() {
+        let run = |automation_id: Uuid, name: &str, status| NamedAutomationRun {
+            run: be_remote_db::AutomationRun {
+                id: Uuid::now_v7(),
+                automation_id,
+                user_id: Uuid::nil(),
+                thread_id: None,
+                status,
+                scheduled_for: Utc::now(),
+                started_at: Utc::now(),
+                finished_at: None,
+                output: None,
+                error: None,
+            },
+            automation_name: name.to_owned(),
+        };
+        let (digest, triage) = (Uuid::now_v7(), Uuid::now_v7());
+        let runs = [
+            run(digest, "Digest", AutomationRunStatus::Succeeded),
+            run(triage, "Triage", AutomationRunStatus::Failed),
+            run(digest, "Digest", AutomationRunStatus::Succeeded),
+            run(digest, "Digest", AutomationRunStatus::Skipped),
+        ];
+        let now = Utc::now();
+        let payload = summary_payload(now - ChronoDuration::days(1), now, &runs);
+        assert_eq!(payload["total_runs"], 4);
+        assert_eq!(payload["automations"][0]["automation_name"], "Digest");
+        assert_eq!(payload["automations"][0]["succeeded"], 2);
+        assert_eq!(payload["automations"][0]["skipped"], 1);
+        assert_eq!(payload["automations"][1]["failed"], 1);
+    }
+}
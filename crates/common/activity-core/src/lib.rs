@@ -19,6 +19,10 @@
 //! response embeds both rows so the client can prepend a rail item and
 //! show "live now" without a second round-trip.
 //!
+//! Sessions carry the [`Device`] they were recorded on, so an account
+//! used on several machines gets one timeline that can still be filtered
+//! or grouped by machine.
+//!
 //! Types are pure data with `serde` derives; the optional `specta` feature
 //! adds `specta::Type` so the same definitions can be re-exported as TS.
 //! No HTTP, database, or gRPC dependencies live here on purpose — pulling
@@ -57,7 +61,8 @@ pub struct Activity {
 /// focus event); `ended_at` is ratcheted forward by heartbeat PATCHes and
 /// finalised on `Stopping`. `url` and `window_title` are optional because
 /// not every strategy produces them (the default strategy has no URL; an
-/// extension-less browser tab has no title yet).
+/// extension-less browser tab has no title yet). `device_id` is `None` for
+/// sessions synced before devices were registered.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "specta", derive(Type))]
 pub struct ActivitySession {
@@ -67,6 +72,8 @@ pub struct ActivitySession {
     pub process_id: Option<i32>,
     pub window_title: Option<String>,
     pub url: Option<String>,
+    #[serde(default)]
+    pub device_id: Option<Uuid>,
     pub started_at: DateTime<Utc>,
     pub ended_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
@@ -97,7 +104,8 @@ pub struct ActivityInsert {
 /// `session_id` and `ended_at` are sent at insert time so a subsequent
 /// PATCH targets the same row (idempotent retries / heartbeat) and an
 /// unexpected crash before the first heartbeat still leaves a bounded
-/// `ended_at` instead of `NULL`.
+/// `ended_at` instead of `NULL`. `device_id` is the sender's registered
+/// [`Device`]; an id the user hasn't registered is ignored.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "specta", derive(Type))]
 pub struct InsertActivitySessionRequest {
@@ -111,6 +119,8 @@ pub struct InsertActivitySessionRequest {
     pub window_title: Option<String>,
     #[serde(default)]
     pub url: Option<String>,
+    #[serde(default)]
+    pub device_id: Option<Uuid>,
     pub started_at: DateTime<Utc>,
     #[serde(default)]
     pub ended_at: Option<DateTime<Utc>>,
@@ -202,6 +212,121 @@ pub struct ListActivitySessionsResponse {
     pub sessions: Vec<ActivitySession>,
 }
 
+/// Default number of activities per batch on `GET /activities/stream`.
+pub const DEFAULT_STREAM_BATCH_SIZE: u32 = 20;
+
+/// Query parameters for `GET /activities/stream`.
+///
+/// The stream walks the same `last_used_at DESC` order as
+/// `GET /activities`, but pages by an opaque keyset `cursor` instead of an
+/// offset, so rows bumped to the top mid-walk can't shift later batches.
+/// `since` (inclusive) and `until` (exclusive) bound `last_used_at`.
+/// `limit` caps the rows sent before the stream ends; omit it to walk the
+/// whole range. `batch_size` is capped at [`MAX_LIST_LIMIT`] like a page.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct StreamActivitiesQuery {
+    #[serde(default)]
+    pub cursor: Option<String>,
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub until: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub batch_size: Option<u32>,
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+/// One newline-delimited JSON line of `GET /activities/stream`.
+///
+/// `next_cursor` resumes the walk right after this batch — pass it back
+/// as `cursor` to continue after a dropped connection or a `limit`. It is
+/// `None` on the last batch of the range.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct ActivityBatch {
+    pub activities: Vec<ActivityWithLatestSession>,
+    pub next_cursor: Option<String>,
+}
+
+/// Longest range `GET /activities/timeline` covers in one call.
+pub const MAX_TIMELINE_DAYS: u32 = 31;
+
+/// Most sessions `GET /activities/timeline` returns. A range holding more
+/// comes back with `truncated` set and its latest sessions missing.
+pub const MAX_TIMELINE_SESSIONS: u32 = 5_000;
+
+/// Query parameters for `GET /activities/timeline`.
+///
+/// `since` is inclusive and `until` exclusive; a session is included when
+/// any part of it falls in the range, including sessions still open.
+/// `device_id` narrows it to one [`Device`]'s sessions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct ActivityTimelineQuery {
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    #[serde(default)]
+    pub device_id: Option<Uuid>,
+}
+
+/// One parent activity with its sessions in the requested range, oldest
+/// first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct ActivityWithSessions {
+    #[serde(flatten)]
+    pub activity: Activity,
+    pub sessions: Vec<ActivitySession>,
+}
+
+/// Response body for `GET /activities/timeline`, parents ordered by their
+/// first session in the range.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct ActivityTimelineResponse {
+    pub activities: Vec<ActivityWithSessions>,
+    /// The range held more than [`MAX_TIMELINE_SESSIONS`] sessions.
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+/// Longest device name accepted by `PUT /devices/{id}`, in characters.
+pub const MAX_DEVICE_NAME_CHARS: usize = 100;
+
+/// A machine signed in to the account.
+///
+/// The desktop makes up the id once per install and registers it with
+/// `PUT /devices/{id}` on every start, which also renames the device and
+/// moves `last_seen_at` forward. `platform` is the OS as Rust names it
+/// (`macos`, `windows`, `linux`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct Device {
+    pub id: Uuid,
+    pub name: String,
+    pub platform: String,
+    pub last_seen_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Request body for `PUT /devices/{id}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct RegisterDeviceRequest {
+    /// At most [`MAX_DEVICE_NAME_CHARS`] characters.
+    pub name: String,
+    pub platform: String,
+}
+
+/// Response body for `GET /devices`, most recently seen first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct ListDevicesResponse {
+    pub devices: Vec<Device>,
+}
+
 /// JSON error body returned by the activity service on non-2xx responses.
 ///
 /// Mirrors the shape used by `be-update-service` so the desktop client
@@ -231,6 +356,14 @@ pub fn type_collection() -> specta::Types {
         .register::<ActivityWithLatestSession>()
         .register::<ListActivitiesResponse>()
         .register::<ListActivitySessionsResponse>()
+        .register::<StreamActivitiesQuery>()
+        .register::<ActivityBatch>()
+        .register::<ActivityTimelineQuery>()
+        .register::<ActivityWithSessions>()
+        .register::<ActivityTimelineResponse>()
+        .register::<Device>()
+        .register::<RegisterDeviceRequest>()
+        .register::<ListDevicesResponse>()
         .register::<ActivityErrorResponse>()
 }
 
@@ -251,6 +384,7 @@ mod tests {
             process_id: Some(42),
             window_title: None,
             url: Some("https://youtube.com/watch?v=abc".into()),
+            device_id: None,
             started_at: Utc::now(),
             ended_at: None,
         };
@@ -280,6 +414,7 @@ mod tests {
         assert!(back.window_title.is_none());
         assert!(back.url.is_none());
         assert!(back.process_id.is_none());
+        assert!(back.device_id.is_none());
         assert!(back.ended_at.is_none());
     }
 
@@ -290,6 +425,26 @@ mod tests {
         assert!(q.offset.is_none());
     }
 
+    #[test]
+    fn stream_query_decodes_time_range() {
+        let q: StreamActivitiesQuery =
+            serde_json::from_str(r#"{"since":"2026-01-01T00:00:00Z","batch_size":50}"#).unwrap();
+        assert!(q.cursor.is_none());
+        assert!(q.since.is_some());
+        assert!(q.until.is_none());
+        assert_eq!(q.batch_size, Some(50));
+        assert!(q.limit.is_none());
+    }
+
+    #[test]
+    fn timeline_query_device_is_optional() {
+        let q: ActivityTimelineQuery = serde_json::from_str(
+            r#"{"since":"2026-01-01T00:00:00Z","until":"2026-01-02T00:00:00Z"}"#,
+        )
+        .unwrap();
+        assert!(q.device_id.is_none());
+    }
+
     #[test]
     fn update_request_round_trips_with_partial_fields() {
         let req = UpdateActivitySessionRequest {
@@ -347,6 +502,7 @@ mod tests {
             process_id: Some(99),
             window_title: Some("Great Video".into()),
             url: Some("https://youtube.com/watch?v=abc".into()),
+            device_id: None,
             started_at: Utc::now(),
             ended_at: None,
             created_at: Utc::now(),
@@ -395,6 +551,14 @@ mod tests {
             "ActivityWithLatestSession",
             "ListActivitiesResponse",
             "ListActivitySessionsResponse",
+            "StreamActivitiesQuery",
+            "ActivityBatch",
+            "ActivityTimelineQuery",
+            "ActivityWithSessions",
+            "ActivityTimelineResponse",
+            "Device",
+            "RegisterDeviceRequest",
+            "ListDevicesResponse",
             "ActivityErrorResponse",
         ] {
             assert!(
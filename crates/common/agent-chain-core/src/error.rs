@@ -86,12 +86,18 @@ pub enum Error {
     #[error("Invalid configuration: {0}")]
     InvalidConfig(String),
 
+    #[error("Model '{model}' does not support {capability}")]
+    UnsupportedCapability { model: String, capability: String },
+
     #[error("Tool invocation error: {0}")]
     ToolInvocation(String),
 
     #[error("{0}")]
     ToolException(String),
 
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+
     #[error("Validation error: {0}")]
     ValidationError(String),
 
@@ -134,6 +140,7 @@ impl Error {
         match self {
             Error::ToolException(msg) => Some(msg),
             Error::ToolInvocation(msg) => Some(msg),
+            Error::PermissionDenied(msg) => Some(msg),
             _ => None,
         }
     }
@@ -185,6 +192,13 @@ impl Error {
         Self::UnableToInferProvider(model.into())
     }
 
+    pub fn unsupported_capability(model: impl Into<String>, capability: impl Into<String>) -> Self {
+        Self::UnsupportedCapability {
+            model: model.into(),
+            capability: capability.into(),
+        }
+    }
+
     pub fn other(message: impl Into<String>) -> Self {
         Self::Other(message.into())
     }
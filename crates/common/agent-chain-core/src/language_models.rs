@@ -3,6 +3,7 @@ pub mod chat_models;
 mod fake;
 mod fake_chat_models;
 mod llms;
+mod middleware;
 mod model_profile;
 mod utils;
 
@@ -25,6 +26,10 @@ pub use llms::{
     update_cache,
 };
 
+pub use middleware::{
+    ChatModelMiddleware, ChatModelWithMiddleware, LoggingMiddleware, ModelRequest,
+};
+
 pub use fake::{FakeListLLM, FakeStreamingListLLM};
 
 pub use fake_chat_models::{
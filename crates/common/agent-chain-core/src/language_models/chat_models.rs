@@ -897,6 +897,19 @@ pub trait BaseChatModel: BaseLanguageModel {
                             ai_response_meta,
                         );
 
+                        // A provider marks its final chunk (the one carrying
+                        // usage and the finish reason) through
+                        // `generation_info`; carry that onto the chunk so
+                        // callers can tell it apart from an empty delta.
+                        last_chunk_position = generation_chunk
+                            .generation_info
+                            .as_ref()
+                            .and_then(|info| info.get("chunk_position"))
+                            .and_then(|v| serde_json::from_value::<ChunkPosition>(v.clone()).ok());
+                        if last_chunk_position.is_some() {
+                            ai_chunk.set_chunk_position(last_chunk_position.clone());
+                        }
+
                         if output_version.as_deref() == Some("v1") {
                             ai_chunk = super::utils::update_chunk_content_to_blocks(&ai_chunk, "v1");
                             apply_block_indices(&mut ai_chunk, &mut block_index, &mut block_index_type);
@@ -910,11 +923,6 @@ pub trait BaseChatModel: BaseLanguageModel {
                             );
                         }
 
-                        last_chunk_position = generation_chunk
-                            .generation_info
-                            .as_ref()
-                            .and_then(|info| info.get("chunk_position"))
-                            .and_then(|v| serde_json::from_value::<ChunkPosition>(v.clone()).ok());
                         chunks.push(generation_chunk);
                         yielded = true;
                         yield Ok(ai_chunk);
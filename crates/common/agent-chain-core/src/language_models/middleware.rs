@@ -0,0 +1,565 @@
+//! Middleware around chat model calls.
+//!
+//! Callbacks observe a run; middleware takes part in it. A
+//! [`ChatModelWithMiddleware`] wraps any [`BaseChatModel`] and runs each
+//! [`ChatModelMiddleware`] around every request the model makes, whether it
+//! is generated, streamed or made with tools:
+//!
+//! - `before_request` sees the outgoing [`ModelRequest`] and may rewrite its
+//!   messages or stop sequences, or fail the call (e.g. a content filter).
+//! - `on_chunk` sees, and may rewrite, each streamed chunk.
+//! - `after_response` sees the complete response, with usage metadata for
+//!   cost tracking. For a streamed call the chunks have already gone out,
+//!   so changes made here are only visible to later middleware.
+//! - `on_error` is told about any error, from the model or from a hook.
+//!
+//! Middleware runs in the order it was added. An error from a hook ends the
+//! call and is returned to the caller.
+//!
+//! ```ignore
+//! let model = ChatModelWithMiddleware::new(Arc::new(ChatOpenAI::new("gpt-4o")))
+//!     .with(LoggingMiddleware)
+//!     .with(MyContentFilter::default());
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::base::{BaseLanguageModel, LangSmithParams, LanguageModelConfig};
+use super::chat_models::{
+    BaseChatModel, ChatGenerationStream, ChatModelConfig, ToolChoice, ToolLike,
+};
+use crate::callbacks::{CallbackManagerForLLMRun, Callbacks};
+use crate::error::{Error, Result};
+use crate::messages::{AIMessage, AnyMessage};
+use crate::outputs::{
+    ChatGeneration, ChatGenerationChunk, ChatResult, LLMResult, merge_chat_generation_chunks,
+};
+use crate::tools::ToolDefinition;
+
+/// One request to the wrapped model, as seen by middleware.
+#[derive(Debug, Clone)]
+pub struct ModelRequest {
+    /// Unique per request, for correlating the hooks of one call.
+    pub id: Uuid,
+    pub model: String,
+    pub llm_type: String,
+    pub messages: Vec<AnyMessage>,
+    pub stop: Option<Vec<String>>,
+    /// Tools offered to the model, bound or passed with the call.
+    pub tools: Vec<ToolDefinition>,
+    pub streaming: bool,
+    pub started_at: Instant,
+}
+
+impl ModelRequest {
+    /// Time since the request was created.
+    pub fn elapsed(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+}
+
+/// Hooks run around each request of a [`ChatModelWithMiddleware`]. Every
+/// hook defaults to doing nothing.
+#[async_trait]
+pub trait ChatModelMiddleware: Send + Sync + fmt::Debug {
+    async fn before_request(&self, _request: &mut ModelRequest) -> Result<()> {
+        Ok(())
+    }
+
+    async fn on_chunk(
+        &self,
+        _request: &ModelRequest,
+        _chunk: &mut ChatGenerationChunk,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    async fn after_response(
+        &self,
+        _request: &ModelRequest,
+        _response: &mut ChatResult,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    async fn on_error(&self, _request: &ModelRequest, _error: &Error) {}
+}
+
+/// A chat model with [`ChatModelMiddleware`] around every request.
+///
+/// Binding tools keeps the middleware: the bound model is wrapped again.
+#[derive(Clone)]
+pub struct ChatModelWithMiddleware {
+    inner: Arc<dyn BaseChatModel>,
+    middleware: Vec<Arc<dyn ChatModelMiddleware>>,
+    tools: Vec<ToolDefinition>,
+}
+
+impl fmt::Debug for ChatModelWithMiddleware {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChatModelWithMiddleware")
+            .field("inner", &self.inner.llm_type())
+            .field("middleware", &self.middleware)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ChatModelWithMiddleware {
+    pub fn new(inner: Arc<dyn BaseChatModel>) -> Self {
+        Self {
+            inner,
+            middleware: Vec::new(),
+            tools: Vec::new(),
+        }
+    }
+
+    /// Add `middleware` after the middleware already added.
+    pub fn with(self, middleware: impl ChatModelMiddleware + 'static) -> Self {
+        self.with_shared(Arc::new(middleware))
+    }
+
+    /// Like [`Self::with`], for middleware shared with other models, e.g. a
+    /// cost tracker.
+    pub fn with_shared(mut self, middleware: Arc<dyn ChatModelMiddleware>) -> Self {
+        self.middleware.push(middleware);
+        self
+    }
+
+    pub fn inner(&self) -> &Arc<dyn BaseChatModel> {
+        &self.inner
+    }
+
+    fn request(
+        &self,
+        messages: Vec<AnyMessage>,
+        stop: Option<Vec<String>>,
+        tools: Vec<ToolDefinition>,
+        streaming: bool,
+    ) -> ModelRequest {
+        ModelRequest {
+            id: Uuid::new_v4(),
+            model: self.inner.model_name().to_owned(),
+            llm_type: self.inner.llm_type().to_owned(),
+            messages,
+            stop,
+            tools,
+            streaming,
+            started_at: Instant::now(),
+        }
+    }
+
+    async fn before(&self, request: &mut ModelRequest) -> Result<()> {
+        for middleware in &self.middleware {
+            if let Err(e) = middleware.before_request(request).await {
+                notify_error(&self.middleware, request, &e).await;
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    async fn finish(
+        &self,
+        request: &ModelRequest,
+        result: Result<ChatResult>,
+    ) -> Result<ChatResult> {
+        let mut response = match result {
+            Ok(response) => response,
+            Err(e) => {
+                notify_error(&self.middleware, request, &e).await;
+                return Err(e);
+            }
+        };
+        after(&self.middleware, request, &mut response).await?;
+        Ok(response)
+    }
+}
+
+async fn after(
+    middleware: &[Arc<dyn ChatModelMiddleware>],
+    request: &ModelRequest,
+    response: &mut ChatResult,
+) -> Result<()> {
+    for m in middleware {
+        if let Err(e) = m.after_response(request, response).await {
+            notify_error(middleware, request, &e).await;
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+async fn notify_error(
+    middleware: &[Arc<dyn ChatModelMiddleware>],
+    request: &ModelRequest,
+    error: &Error,
+) {
+    for m in middleware {
+        m.on_error(request, error).await;
+    }
+}
+
+#[async_trait]
+impl BaseLanguageModel for ChatModelWithMiddleware {
+    fn llm_type(&self) -> &str {
+        self.inner.llm_type()
+    }
+
+    fn model_name(&self) -> &str {
+        self.inner.model_name()
+    }
+
+    fn config(&self) -> &LanguageModelConfig {
+        self.inner.config()
+    }
+
+    fn callbacks(&self) -> Option<&Callbacks> {
+        self.inner.callbacks()
+    }
+
+    async fn generate_prompt(
+        &self,
+        prompts: Vec<Vec<AnyMessage>>,
+        stop: Option<Vec<String>>,
+        _callbacks: Option<Callbacks>,
+    ) -> Result<LLMResult> {
+        let mut generations = Vec::new();
+        for messages in prompts {
+            let result = self._generate(messages, stop.clone(), None).await?;
+            generations.push(result.generations.into_iter().map(|g| g.into()).collect());
+        }
+        Ok(LLMResult::builder().generations(generations).build())
+    }
+
+    fn get_ls_params(&self, stop: Option<&[String]>) -> LangSmithParams {
+        self.inner.get_ls_params(stop)
+    }
+
+    fn identifying_params(&self) -> HashMap<String, Value> {
+        self.inner.identifying_params()
+    }
+
+    fn get_token_ids(&self, text: &str) -> Vec<u32> {
+        self.inner.get_token_ids(text)
+    }
+}
+
+#[async_trait]
+impl BaseChatModel for ChatModelWithMiddleware {
+    fn chat_config(&self) -> &ChatModelConfig {
+        self.inner.chat_config()
+    }
+
+    async fn _generate(
+        &self,
+        messages: Vec<AnyMessage>,
+        stop: Option<Vec<String>>,
+        run_manager: Option<&CallbackManagerForLLMRun>,
+    ) -> Result<ChatResult> {
+        let mut request = self.request(messages, stop, self.tools.clone(), false);
+        self.before(&mut request).await?;
+        let result = self
+            .inner
+            ._generate(request.messages.clone(), request.stop.clone(), run_manager)
+            .await;
+        self.finish(&request, result).await
+    }
+
+    fn _combine_llm_outputs(
+        &self,
+        llm_outputs: &[Option<HashMap<String, Value>>],
+    ) -> HashMap<String, Value> {
+        self.inner._combine_llm_outputs(llm_outputs)
+    }
+
+    fn has_stream_impl(&self) -> bool {
+        self.inner.has_stream_impl()
+    }
+
+    fn has_streaming_field(&self) -> Option<bool> {
+        self.inner.has_streaming_field()
+    }
+
+    async fn _stream(
+        &self,
+        messages: Vec<AnyMessage>,
+        stop: Option<Vec<String>>,
+        run_manager: Option<&CallbackManagerForLLMRun>,
+    ) -> Result<ChatGenerationStream> {
+        if !self.inner.has_stream_impl() {
+            // Let the caller fall back to `_generate` before any hook runs,
+            // so the request isn't seen twice.
+            return Err(Error::NotImplemented("Streaming not implemented".into()));
+        }
+        let mut request = self.request(messages, stop, self.tools.clone(), true);
+        self.before(&mut request).await?;
+        let mut chunks = match self
+            .inner
+            ._stream(request.messages.clone(), request.stop.clone(), run_manager)
+            .await
+        {
+            Ok(chunks) => chunks,
+            Err(e) => {
+                notify_error(&self.middleware, &request, &e).await;
+                return Err(e);
+            }
+        };
+
+        let middleware = self.middleware.clone();
+        let stream = async_stream::stream! {
+            let mut received = Vec::new();
+            while let Some(chunk) = chunks.next().await {
+                let mut chunk = match chunk {
+                    Ok(chunk) => chunk,
+                    Err(e) => {
+                        notify_error(&middleware, &request, &e).await;
+                        yield Err(e);
+                        return;
+                    }
+                };
+                for m in &middleware {
+                    if let Err(e) = m.on_chunk(&request, &mut chunk).await {
+                        notify_error(&middleware, &request, &e).await;
+                        yield Err(e);
+                        return;
+                    }
+                }
+                received.push(chunk.clone());
+                yield Ok(chunk);
+            }
+            if let Some(merged) = merge_chat_generation_chunks(received) {
+                let generation: ChatGeneration = merged.into();
+                let mut response = ChatResult::builder().generations(vec![generation]).build();
+                if let Err(e) = after(&middleware, &request, &mut response).await {
+                    yield Err(e);
+                }
+            }
+        };
+        Ok(Box::pin(stream))
+    }
+
+    async fn generate_with_tools(
+        &self,
+        messages: Vec<AnyMessage>,
+        tools: &[ToolDefinition],
+        tool_choice: Option<&ToolChoice>,
+        stop: Option<Vec<String>>,
+    ) -> Result<AIMessage> {
+        let mut request = self.request(messages, stop, tools.to_vec(), false);
+        self.before(&mut request).await?;
+        let result = self
+            .inner
+            .generate_with_tools(
+                request.messages.clone(),
+                &request.tools,
+                tool_choice,
+                request.stop.clone(),
+            )
+            .await
+            .map(|message| {
+                let generation = ChatGeneration::builder().message(message.into()).build();
+                ChatResult::builder().generations(vec![generation]).build()
+            });
+        let response = self.finish(&request, result).await?;
+        self.get_first_message(&response)
+    }
+
+    fn bind_tools(
+        &self,
+        tools: &[ToolLike],
+        tool_choice: Option<ToolChoice>,
+    ) -> Result<Box<dyn BaseChatModel>> {
+        let bound = self.inner.bind_tools(tools, tool_choice)?;
+        Ok(Box::new(Self {
+            inner: Arc::from(bound),
+            middleware: self.middleware.clone(),
+            // Provider built-in tools have no definition; they still reach
+            // the model, middleware just doesn't see them.
+            tools: tools
+                .iter()
+                .filter_map(|t| t.to_definition().ok())
+                .collect(),
+        }))
+    }
+}
+
+/// Middleware that logs each request through `tracing`: its start at
+/// `debug`, its outcome with latency and token usage at `info`, and
+/// failures at `warn`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LoggingMiddleware;
+
+#[async_trait]
+impl ChatModelMiddleware for LoggingMiddleware {
+    async fn before_request(&self, request: &mut ModelRequest) -> Result<()> {
+        tracing::debug!(
+            request_id = %request.id,
+            model = %request.model,
+            messages = request.messages.len(),
+            tools = request.tools.len(),
+            streaming = request.streaming,
+            "Chat model request"
+        );
+        Ok(())
+    }
+
+    async fn after_response(
+        &self,
+        request: &ModelRequest,
+        response: &mut ChatResult,
+    ) -> Result<()> {
+        let usage = response
+            .generations
+            .first()
+            .and_then(|generation| match &generation.message {
+                AnyMessage::AIMessage(message) => message.usage_metadata.as_ref(),
+                _ => None,
+            });
+        tracing::info!(
+            request_id = %request.id,
+            model = %request.model,
+            latency_ms = request.elapsed().as_millis() as u64,
+            input_tokens = usage.map(|u| u.input_tokens),
+            output_tokens = usage.map(|u| u.output_tokens),
+            "Chat model response"
+        );
+        Ok(())
+    }
+
+    async fn on_error(&self, request: &ModelRequest, error: &Error) {
+        tracing::warn!(
+            request_id = %request.id,
+            model = %request.model,
+            latency_ms = request.elapsed().as_millis() as u64,
+            error = %error,
+            "Chat model request failed"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::language_models::FakeListChatModel;
+    use crate::messages::HumanMessage;
+
+    /// Records every hook call and upper-cases streamed chunks.
+    #[derive(Debug, Default)]
+    struct Recorder {
+        events: Mutex<Vec<String>>,
+        reject: bool,
+    }
+
+    impl Recorder {
+        fn events(&self) -> Vec<String> {
+            self.events.lock().unwrap().clone()
+        }
+
+        fn record(&self, event: impl Into<String>) {
+            self.events.lock().unwrap().push(event.into());
+        }
+    }
+
+    #[async_trait]
+    impl ChatModelMiddleware for Recorder {
+        async fn before_request(&self, request: &mut ModelRequest) -> Result<()> {
+            self.record(format!("before:{}", request.streaming));
+            if self.reject {
+                return Err(Error::ValidationError("blocked".into()));
+            }
+            Ok(())
+        }
+
+        async fn on_chunk(
+            &self,
+            _request: &ModelRequest,
+            chunk: &mut ChatGenerationChunk,
+        ) -> Result<()> {
+            self.record("chunk");
+            let text = chunk.message.text().to_uppercase();
+            chunk.message = AIMessage::builder().content(text).build().into();
+            Ok(())
+        }
+
+        async fn after_response(
+            &self,
+            _request: &ModelRequest,
+            response: &mut ChatResult,
+        ) -> Result<()> {
+            self.record(format!("after:{}", response.generations[0].message.text()));
+            Ok(())
+        }
+
+        async fn on_error(&self, _request: &ModelRequest, error: &Error) {
+            self.record(format!("error:{error}"));
+        }
+    }
+
+    fn wrapped(recorder: &Arc<Recorder>) -> ChatModelWithMiddleware {
+        let model = FakeListChatModel::builder()
+            .responses(vec!["hi".to_string()])
+            .build();
+        ChatModelWithMiddleware::new(Arc::new(model)).with_shared(recorder.clone())
+    }
+
+    fn prompt() -> Vec<AnyMessage> {
+        vec![HumanMessage::builder().content("hello").build().into()]
+    }
+
+    #[tokio::test]
+    async fn generate_runs_request_hooks() {
+        let recorder = Arc::new(Recorder::default());
+        let result = wrapped(&recorder)
+            ._generate(prompt(), None, None)
+            .await
+            .unwrap();
+        assert_eq!(result.generations[0].message.text(), "hi");
+        assert_eq!(recorder.events(), ["before:false", "after:hi"]);
+    }
+
+    #[tokio::test]
+    async fn stream_chunks_pass_through_middleware() {
+        let recorder = Arc::new(Recorder::default());
+        let stream = wrapped(&recorder)
+            ._stream(prompt(), None, None)
+            .await
+            .unwrap();
+        let chunks: Vec<_> = stream.collect().await;
+        let text: String = chunks
+            .into_iter()
+            .map(|c| c.unwrap().message.text())
+            .collect();
+        assert_eq!(text, "HI");
+        assert_eq!(
+            recorder.events(),
+            ["before:true", "chunk", "chunk", "after:HI"]
+        );
+    }
+
+    #[tokio::test]
+    async fn rejected_requests_never_reach_the_model() {
+        let recorder = Arc::new(Recorder {
+            reject: true,
+            ..Default::default()
+        });
+        let err = wrapped(&recorder)
+            ._generate(prompt(), None, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::ValidationError(_)));
+        assert_eq!(
+            recorder.events(),
+            ["before:false", "error:Validation error: blocked"]
+        );
+    }
+}
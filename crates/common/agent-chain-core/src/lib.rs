@@ -60,11 +60,12 @@ pub use error::{Error, Result};
 
 pub use language_models::{
     AIMessageChunkStream, BaseChatModel, BaseLLM, BaseLanguageModel, ChatChunk,
-    ChatGenerationStream, ChatModelConfig, ChatStream, DisableStreaming, FakeChatModel,
-    FakeListChatModel, FakeListLLM, FakeMessagesListChatModel, FakeStreamingListLLM,
-    GenericFakeChatModel, LLM, LLMConfig, LangSmithParams, LanguageModelConfig, ModelProfile,
-    ModelProfileRegistry, OpenAiDataBlockFilter, ParrotFakeChatModel, ParsedDataUri,
-    SimpleChatModel, ToolChoice, UsageMetadata, agenerate_from_stream, collect_and_merge_stream,
+    ChatGenerationStream, ChatModelConfig, ChatModelMiddleware, ChatModelWithMiddleware,
+    ChatStream, DisableStreaming, FakeChatModel, FakeListChatModel, FakeListLLM,
+    FakeMessagesListChatModel, FakeStreamingListLLM, GenericFakeChatModel, LLM, LLMConfig,
+    LangSmithParams, LanguageModelConfig, LoggingMiddleware, ModelProfile, ModelProfileRegistry,
+    ModelRequest, OpenAiDataBlockFilter, ParrotFakeChatModel, ParsedDataUri, SimpleChatModel,
+    ToolChoice, UsageMetadata, agenerate_from_stream, collect_and_merge_stream,
     generate_from_stream, get_prompts_from_cache, is_openai_data_block, parse_data_uri,
     update_cache,
 };
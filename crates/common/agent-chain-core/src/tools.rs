@@ -48,6 +48,7 @@ macro_rules! impl_base_tool_getters {
 
 pub mod base;
 pub mod convert;
+pub mod permission;
 pub mod render;
 pub mod retriever;
 pub mod simple;
@@ -71,6 +72,11 @@ pub use convert::{
     tool_from_schema,
 };
 
+pub use permission::{
+    ConsentDecision, ConsentHandler, DecisionStore, InMemoryDecisionStore, PermissionRequest,
+    PermissionedTool, RiskLevel, ToolPermissions,
+};
+
 pub use render::{ToolsRenderer, render_text_description, render_text_description_and_args};
 
 pub use retriever::{RetrieverInput, RetrieverTool};
@@ -0,0 +1,394 @@
+//! Permission and consent layer for tool execution.
+//!
+//! Every tool carries a [`RiskLevel`]. [`ToolPermissions::authorize`]
+//! decides whether a call may proceed:
+//!
+//! - [`RiskLevel::Safe`] tools (reading context the user already shares)
+//!   always run.
+//! - [`RiskLevel::Elevated`] tools (writing files, mutating remote state)
+//!   ask the [`ConsentHandler`] unless the user previously chose
+//!   [`ConsentDecision::AllowAlways`] for that tool.
+//! - [`RiskLevel::Dangerous`] tools (shell, arbitrary code) ask every
+//!   time; a stored "always" is not honoured for them.
+//!
+//! [`PermissionedTool`] enforces this inside the tool execution path, so
+//! a denied call never reaches the wrapped tool and the model receives
+//! [`Error::PermissionDenied`] as the tool result instead.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Debug};
+use std::sync::{Arc, RwLock};
+
+use async_trait::async_trait;
+use bon::Builder;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::callbacks::Callbacks;
+use crate::callbacks::manager::CallbackManagerForToolRun;
+use crate::error::{Error, Result};
+use crate::runnables::RunnableConfig;
+use crate::tools::base::{
+    ArgsSchema, BaseTool, DynTool, ErrorHandler, ResponseFormat, ToolInput, ToolOutput,
+};
+
+/// How much harm a tool call can do without the user noticing.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[serde(rename_all = "snake_case")]
+pub enum RiskLevel {
+    /// Reads context the user is already sharing. Never prompts.
+    #[default]
+    Safe,
+    /// Changes files, settings, or remote state. Prompts until the user
+    /// allows the tool permanently.
+    Elevated,
+    /// Runs arbitrary commands or code. Prompts on every call.
+    Dangerous,
+}
+
+/// The user's answer to a consent prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[serde(rename_all = "snake_case")]
+pub enum ConsentDecision {
+    AllowOnce,
+    AllowAlways,
+    Deny,
+}
+
+/// What the user is asked to approve.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PermissionRequest {
+    pub tool: String,
+    pub risk: RiskLevel,
+    pub arguments: Value,
+}
+
+/// Asks the user to approve a tool call. Desktop implementations surface
+/// a prompt in the UI and resolve once the user answers.
+#[async_trait]
+pub trait ConsentHandler: Send + Sync {
+    async fn request_consent(&self, request: &PermissionRequest) -> ConsentDecision;
+}
+
+/// Remembers which tools the user allowed permanently.
+pub trait DecisionStore: Send + Sync {
+    fn is_always_allowed(&self, tool: &str) -> bool;
+
+    fn allow_always(&self, tool: &str);
+
+    fn revoke(&self, tool: &str);
+}
+
+/// Process-lifetime [`DecisionStore`], for tests and callers without a
+/// settings file.
+#[derive(Debug, Default)]
+pub struct InMemoryDecisionStore {
+    allowed: RwLock<HashSet<String>>,
+}
+
+impl DecisionStore for InMemoryDecisionStore {
+    fn is_always_allowed(&self, tool: &str) -> bool {
+        self.allowed
+            .read()
+            .map(|allowed| allowed.contains(tool))
+            .unwrap_or(false)
+    }
+
+    fn allow_always(&self, tool: &str) {
+        if let Ok(mut allowed) = self.allowed.write() {
+            allowed.insert(tool.to_string());
+        }
+    }
+
+    fn revoke(&self, tool: &str) {
+        if let Ok(mut allowed) = self.allowed.write() {
+            allowed.remove(tool);
+        }
+    }
+}
+
+/// Per-tool risk levels plus the consent handler and decision store
+/// used to enforce them.
+#[derive(Builder)]
+pub struct ToolPermissions {
+    /// Risk level by tool name.
+    #[builder(default)]
+    risks: HashMap<String, RiskLevel>,
+    /// Risk level for tools missing from `risks`.
+    #[builder(default)]
+    default_risk: RiskLevel,
+    handler: Arc<dyn ConsentHandler>,
+    #[builder(default = Arc::new(InMemoryDecisionStore::default()) as Arc<dyn DecisionStore>)]
+    store: Arc<dyn DecisionStore>,
+}
+
+impl Debug for ToolPermissions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ToolPermissions")
+            .field("risks", &self.risks)
+            .field("default_risk", &self.default_risk)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ToolPermissions {
+    pub fn risk_of(&self, tool: &str) -> RiskLevel {
+        self.risks.get(tool).copied().unwrap_or(self.default_risk)
+    }
+
+    /// Run the consent flow for one call. `Ok(())` means the call may
+    /// proceed; a refusal is [`Error::PermissionDenied`].
+    pub async fn authorize(&self, tool: &str, arguments: &Value) -> Result<()> {
+        self.authorize_with_risk(tool, self.risk_of(tool), arguments)
+            .await
+    }
+
+    /// Like [`Self::authorize`], for callers that know the risk level
+    /// from elsewhere (e.g. a wire descriptor) rather than from `risks`.
+    pub async fn authorize_with_risk(
+        &self,
+        tool: &str,
+        risk: RiskLevel,
+        arguments: &Value,
+    ) -> Result<()> {
+        match risk {
+            RiskLevel::Safe => return Ok(()),
+            RiskLevel::Elevated if self.store.is_always_allowed(tool) => return Ok(()),
+            RiskLevel::Elevated | RiskLevel::Dangerous => {}
+        }
+
+        let request = PermissionRequest {
+            tool: tool.to_string(),
+            risk,
+            arguments: arguments.clone(),
+        };
+        match self.handler.request_consent(&request).await {
+            ConsentDecision::AllowOnce => Ok(()),
+            ConsentDecision::AllowAlways => {
+                self.store.allow_always(tool);
+                Ok(())
+            }
+            ConsentDecision::Deny => Err(Error::PermissionDenied(format!(
+                "the user declined to run `{tool}`"
+            ))),
+        }
+    }
+}
+
+/// Wraps a tool so every call goes through [`ToolPermissions`] first.
+pub struct PermissionedTool {
+    inner: DynTool,
+    permissions: Arc<ToolPermissions>,
+    /// Overrides the risk level `permissions` would assign by name.
+    risk: Option<RiskLevel>,
+}
+
+impl PermissionedTool {
+    pub fn new(inner: DynTool, permissions: Arc<ToolPermissions>) -> Self {
+        Self {
+            inner,
+            permissions,
+            risk: None,
+        }
+    }
+
+    /// Wrap a tool whose risk is fixed by the tool itself rather than
+    /// configured per name, e.g. a built-in shell tool that must never
+    /// fall back to [`RiskLevel::Safe`].
+    pub fn with_risk(inner: DynTool, permissions: Arc<ToolPermissions>, risk: RiskLevel) -> Self {
+        Self {
+            inner,
+            permissions,
+            risk: Some(risk),
+        }
+    }
+
+    /// Wrap every tool in `tools` with the same permissions.
+    pub fn wrap_all(tools: Vec<DynTool>, permissions: &Arc<ToolPermissions>) -> Vec<DynTool> {
+        tools
+            .into_iter()
+            .map(|tool| Arc::new(Self::new(tool, permissions.clone())) as DynTool)
+            .collect()
+    }
+}
+
+impl Debug for PermissionedTool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PermissionedTool")
+            .field("inner", &self.inner)
+            .field("risk", &self.risk)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl BaseTool for PermissionedTool {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn description(&self) -> &str {
+        self.inner.description()
+    }
+
+    fn args_schema(&self) -> Option<&ArgsSchema> {
+        self.inner.args_schema()
+    }
+
+    fn return_direct(&self) -> bool {
+        self.inner.return_direct()
+    }
+
+    fn verbose(&self) -> bool {
+        self.inner.verbose()
+    }
+
+    fn tags(&self) -> Option<&[String]> {
+        self.inner.tags()
+    }
+
+    fn metadata(&self) -> Option<&HashMap<String, Value>> {
+        self.inner.metadata()
+    }
+
+    fn handle_tool_error(&self) -> &ErrorHandler {
+        self.inner.handle_tool_error()
+    }
+
+    fn handle_validation_error(&self) -> &ErrorHandler {
+        self.inner.handle_validation_error()
+    }
+
+    fn response_format(&self) -> ResponseFormat {
+        self.inner.response_format()
+    }
+
+    fn callbacks(&self) -> Option<&Callbacks> {
+        self.inner.callbacks()
+    }
+
+    fn extras(&self) -> Option<&HashMap<String, Value>> {
+        self.inner.extras()
+    }
+
+    async fn tool_run(
+        &self,
+        input: ToolInput,
+        run_manager: Option<&CallbackManagerForToolRun>,
+        config: &RunnableConfig,
+    ) -> Result<ToolOutput> {
+        let arguments = match &input {
+            ToolInput::String(s) => Value::String(s.clone()),
+            ToolInput::Dict(d) => Value::Object(d.clone().into_iter().collect()),
+            ToolInput::ToolCall(call) => call.args.clone(),
+        };
+        let name = self.inner.name();
+        let risk = self.risk.unwrap_or_else(|| self.permissions.risk_of(name));
+        self.permissions
+            .authorize_with_risk(name, risk, &arguments)
+            .await?;
+        self.inner.tool_run(input, run_manager, config).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::simple::Tool;
+    use std::sync::Mutex;
+
+    /// Answers with a fixed decision and records every prompt.
+    struct ScriptedHandler {
+        decision: ConsentDecision,
+        prompts: Mutex<Vec<PermissionRequest>>,
+    }
+
+    impl ScriptedHandler {
+        fn new(decision: ConsentDecision) -> Arc<Self> {
+            Arc::new(Self {
+                decision,
+                prompts: Mutex::new(Vec::new()),
+            })
+        }
+
+        fn prompt_count(&self) -> usize {
+            self.prompts.lock().unwrap().len()
+        }
+    }
+
+    #[async_trait]
+    impl ConsentHandler for ScriptedHandler {
+        async fn request_consent(&self, request: &PermissionRequest) -> ConsentDecision {
+            self.prompts.lock().unwrap().push(request.clone());
+            self.decision
+        }
+    }
+
+    fn permissions(handler: Arc<ScriptedHandler>) -> ToolPermissions {
+        ToolPermissions::builder()
+            .risks(HashMap::from([
+                ("write_file".to_string(), RiskLevel::Elevated),
+                ("run_shell".to_string(), RiskLevel::Dangerous),
+            ]))
+            .handler(handler)
+            .build()
+    }
+
+    #[tokio::test]
+    async fn safe_tools_never_prompt() {
+        let handler = ScriptedHandler::new(ConsentDecision::Deny);
+        let permissions = permissions(handler.clone());
+        permissions
+            .authorize("read_page", &Value::Null)
+            .await
+            .unwrap();
+        assert_eq!(handler.prompt_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn allow_always_is_remembered_for_elevated_tools_only() {
+        let handler = ScriptedHandler::new(ConsentDecision::AllowAlways);
+        let permissions = permissions(handler.clone());
+
+        for _ in 0..2 {
+            permissions
+                .authorize("write_file", &Value::Null)
+                .await
+                .unwrap();
+            permissions
+                .authorize("run_shell", &Value::Null)
+                .await
+                .unwrap();
+        }
+        // One prompt for write_file (then remembered), two for run_shell.
+        assert_eq!(handler.prompt_count(), 3);
+    }
+
+    #[tokio::test]
+    async fn denied_calls_never_reach_the_tool() {
+        let handler = ScriptedHandler::new(ConsentDecision::Deny);
+        let permissions = Arc::new(permissions(handler.clone()));
+        let tool = PermissionedTool::new(
+            Arc::new(Tool::from_function(
+                |_| panic!("tool ran despite denial"),
+                "run_shell",
+                "Run a shell command",
+            )),
+            permissions,
+        );
+
+        let err = tool
+            .run(ToolInput::String("rm -rf /".to_string()), None, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::PermissionDenied(_)));
+        assert_eq!(
+            handler.prompts.lock().unwrap()[0].arguments,
+            Value::String("rm -rf /".to_string())
+        );
+    }
+}
@@ -0,0 +1,293 @@
+//! Model capability registry.
+//!
+//! Models differ in how much context they take, how many tokens they can
+//! produce, which temperatures they accept and whether they handle images
+//! or tool calls. Providers consult this registry before every request:
+//! numeric parameters are clamped into range and unsupported inputs are
+//! rejected with [`Error::UnsupportedCapability`], rather than sending a
+//! request the API would refuse with a provider-specific error.
+//!
+//! Capabilities come from two places:
+//!
+//! - **Static data** for hosted models: the OpenAI model profiles and a
+//!   table of Claude families.
+//! - **Runtime probing** for models whose capabilities depend on the
+//!   server, such as Ollama models. Probe results are [`register`]ed and
+//!   take precedence over static data.
+//!
+//! Anything not known is `None` and passes through unchanged, so a model
+//! missing from the registry behaves exactly as it did before.
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, RwLock};
+
+use crate::error::{Error, Result};
+use crate::language_models::ModelProfile;
+use crate::messages::AnyMessage;
+
+/// Inclusive range of accepted sampling temperatures.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TemperatureRange {
+    pub min: f64,
+    pub max: f64,
+}
+
+impl TemperatureRange {
+    pub const fn new(min: f64, max: f64) -> Self {
+        Self { min, max }
+    }
+
+    /// A model that only runs at `value`, like OpenAI's o-series.
+    pub const fn fixed(value: f64) -> Self {
+        Self::new(value, value)
+    }
+}
+
+/// What a model supports. `None` means unknown, which is never enforced.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModelCapabilities {
+    /// Total tokens the model can attend to, prompt and output combined.
+    pub context_window: Option<u32>,
+    pub max_output_tokens: Option<u32>,
+    pub temperature: Option<TemperatureRange>,
+    pub image_inputs: Option<bool>,
+    pub tool_calling: Option<bool>,
+}
+
+impl ModelCapabilities {
+    /// `temperature` moved into the supported range.
+    pub fn clamp_temperature(&self, temperature: f64) -> f64 {
+        match self.temperature {
+            Some(range) if !(range.min..=range.max).contains(&temperature) => {
+                let clamped = temperature.clamp(range.min, range.max);
+                tracing::debug!(temperature, clamped, "Clamped temperature to model range");
+                clamped
+            }
+            _ => temperature,
+        }
+    }
+
+    /// `max_tokens` capped at the model's output limit.
+    pub fn clamp_max_tokens(&self, max_tokens: u32) -> u32 {
+        match self.max_output_tokens {
+            Some(limit) if max_tokens > limit => {
+                tracing::debug!(max_tokens, limit, "Clamped max tokens to model limit");
+                limit
+            }
+            _ => max_tokens,
+        }
+    }
+
+    /// A requested context size capped at the model's context window.
+    pub fn clamp_context(&self, context: u32) -> u32 {
+        match self.context_window {
+            Some(window) if context > window => {
+                tracing::debug!(context, window, "Clamped context size to model window");
+                window
+            }
+            _ => context,
+        }
+    }
+
+    /// Reject a request to `model` that needs something the model is known
+    /// not to support: images in `messages`, or any of `tool_count` tools.
+    pub fn check_request(
+        &self,
+        model: &str,
+        messages: &[AnyMessage],
+        tool_count: usize,
+    ) -> Result<()> {
+        if self.image_inputs == Some(false) && messages.iter().any(has_images) {
+            return Err(Error::unsupported_capability(model, "image input"));
+        }
+        if self.tool_calling == Some(false) && tool_count > 0 {
+            return Err(Error::unsupported_capability(model, "tool calling"));
+        }
+        Ok(())
+    }
+}
+
+impl From<&ModelProfile> for ModelCapabilities {
+    fn from(profile: &ModelProfile) -> Self {
+        Self {
+            context_window: profile.max_input_tokens,
+            max_output_tokens: profile.max_output_tokens,
+            temperature: None,
+            image_inputs: profile.image_inputs,
+            tool_calling: profile.tool_calling,
+        }
+    }
+}
+
+/// Capabilities registered at runtime, keyed by `(provider, model)`.
+static REGISTERED: LazyLock<RwLock<HashMap<(String, String), ModelCapabilities>>> =
+    LazyLock::new(Default::default);
+
+/// Everything known about `model` served by `provider`: registered
+/// capabilities if any, otherwise the built-in data.
+pub fn lookup(provider: &str, model: &str) -> ModelCapabilities {
+    registered(provider, model).unwrap_or_else(|| builtin(provider, model))
+}
+
+/// Capabilities registered for `model`, typically by a probe.
+pub fn registered(provider: &str, model: &str) -> Option<ModelCapabilities> {
+    REGISTERED
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&(provider.to_owned(), model.to_owned()))
+        .cloned()
+}
+
+/// Record `capabilities` for `model`, replacing the built-in data.
+pub fn register(provider: &str, model: &str, capabilities: ModelCapabilities) {
+    REGISTERED
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert((provider.to_owned(), model.to_owned()), capabilities);
+}
+
+/// Ask an Ollama server what `model` supports and [`register`] the answer.
+#[cfg(feature = "ollama")]
+pub async fn probe_ollama(
+    models: &crate::ollama::OllamaModels,
+    model: &str,
+) -> Result<ModelCapabilities> {
+    let capabilities = models.capabilities(model).await?;
+    register("ollama", model, capabilities.clone());
+    Ok(capabilities)
+}
+
+fn builtin(provider: &str, model: &str) -> ModelCapabilities {
+    match provider {
+        "anthropic" => anthropic(model),
+        "openai" | "azure_openai" => openai(model),
+        _ => ModelCapabilities::default(),
+    }
+}
+
+/// Claude families by name prefix, most specific first, with their output
+/// limit. All take 200k tokens of context, images and tools, and a
+/// temperature between 0 and 1.
+const CLAUDE_OUTPUT_LIMITS: &[(&str, u32)] = &[
+    ("claude-opus-4", 32_000),
+    ("claude-sonnet-4", 64_000),
+    ("claude-haiku-4", 64_000),
+    ("claude-3-7-sonnet", 64_000),
+    ("claude-3-5-", 8_192),
+    ("claude-3-", 4_096),
+];
+
+fn anthropic(model: &str) -> ModelCapabilities {
+    let Some(&(_, max_output_tokens)) = CLAUDE_OUTPUT_LIMITS
+        .iter()
+        .find(|(prefix, _)| model.starts_with(prefix))
+    else {
+        return ModelCapabilities::default();
+    };
+    ModelCapabilities {
+        context_window: Some(200_000),
+        max_output_tokens: Some(max_output_tokens),
+        temperature: Some(TemperatureRange::new(0.0, 1.0)),
+        image_inputs: Some(true),
+        tool_calling: Some(true),
+    }
+}
+
+fn openai(model: &str) -> ModelCapabilities {
+    #[cfg(feature = "openai")]
+    let mut capabilities = crate::openai::data::PROFILES
+        .get(model)
+        .map(ModelCapabilities::from)
+        .unwrap_or_default();
+    #[cfg(not(feature = "openai"))]
+    let mut capabilities = ModelCapabilities::default();
+
+    let lower = model.to_lowercase();
+    if ["o1", "o3", "o4"].iter().any(|p| lower.starts_with(p)) {
+        capabilities.temperature = Some(TemperatureRange::fixed(1.0));
+    } else if lower.starts_with("gpt-") {
+        capabilities.temperature = Some(TemperatureRange::new(0.0, 2.0));
+    }
+    capabilities
+}
+
+fn has_images(message: &AnyMessage) -> bool {
+    match message {
+        AnyMessage::HumanMessage(m) => m.has_images(),
+        AnyMessage::ToolMessage(m) => m.content.has_images(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::{ContentBlock, ContentBlocks, HumanMessage, ImageContentBlock};
+
+    #[test]
+    fn unknown_models_pass_through() {
+        let capabilities = lookup("anthropic", "my-proxy-model");
+        assert_eq!(capabilities, ModelCapabilities::default());
+        assert_eq!(capabilities.clamp_temperature(7.0), 7.0);
+        assert_eq!(capabilities.clamp_max_tokens(1_000_000), 1_000_000);
+    }
+
+    #[test]
+    fn claude_parameters_are_clamped() {
+        let capabilities = lookup("anthropic", "claude-3-5-haiku-20241022");
+        assert_eq!(capabilities.clamp_temperature(1.5), 1.0);
+        assert_eq!(capabilities.clamp_temperature(-0.1), 0.0);
+        assert_eq!(capabilities.clamp_max_tokens(16_384), 8_192);
+        assert_eq!(
+            lookup("anthropic", "claude-opus-4-1-20250805").max_output_tokens,
+            Some(32_000)
+        );
+    }
+
+    #[test]
+    fn o_series_runs_at_a_fixed_temperature() {
+        assert_eq!(lookup("openai", "o3-mini").clamp_temperature(0.2), 1.0);
+        assert_eq!(lookup("openai", "gpt-4o").clamp_temperature(0.2), 0.2);
+    }
+
+    #[test]
+    fn registered_capabilities_win() {
+        register(
+            "anthropic",
+            "claude-3-haiku-test",
+            ModelCapabilities {
+                max_output_tokens: Some(100),
+                ..Default::default()
+            },
+        );
+        let capabilities = lookup("anthropic", "claude-3-haiku-test");
+        assert_eq!(capabilities.clamp_max_tokens(4_096), 100);
+        assert_eq!(capabilities.temperature, None);
+    }
+
+    #[test]
+    fn unsupported_inputs_are_rejected() {
+        let capabilities = ModelCapabilities {
+            image_inputs: Some(false),
+            tool_calling: Some(false),
+            ..Default::default()
+        };
+        let text: AnyMessage = HumanMessage::builder().content("hi").build().into();
+        assert!(capabilities.check_request("m", &[text.clone()], 0).is_ok());
+
+        let err = capabilities.check_request("m", &[text], 1).unwrap_err();
+        assert_eq!(err.to_string(), "Model 'm' does not support tool calling");
+
+        let image: AnyMessage = HumanMessage::builder()
+            .content(ContentBlocks::from(vec![ContentBlock::Image(
+                ImageContentBlock::builder()
+                    .url("https://example.com/a.png".to_string())
+                    .build()
+                    .unwrap(),
+            )]))
+            .build()
+            .into();
+        let err = capabilities.check_request("m", &[image], 0).unwrap_err();
+        assert!(matches!(err, Error::UnsupportedCapability { .. }));
+    }
+}
@@ -0,0 +1,563 @@
+//! Built-in read-only file-system tools: `read_file`, `list_dir` and
+//! `grep_files`.
+//!
+//! Every path is resolved against a set of user-approved [`FileRoots`];
+//! anything that resolves outside them — via `..`, an absolute path, or
+//! a symlink — is refused. Content is size-capped and binary files are
+//! detected (a NUL byte or invalid UTF-8 in the first block) and reported
+//! by size instead of being dumped into the context window.
+//!
+//! [`FileRoots::tools`] returns the three tools as [`StructuredTool`]s,
+//! ready to hand to the same tool-calling loop as any other tool.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use std::sync::Arc;
+//! use agent_chain::files::FileRoots;
+//!
+//! let roots = Arc::new(FileRoots::new(["/home/me/Documents"]));
+//! let tools = roots.tools();
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use agent_chain_core::tools::{ArgsSchema, StructuredTool};
+use regex::RegexBuilder;
+use serde_json::{Value, json};
+
+use crate::error::{Error, Result};
+
+pub const READ_FILE: &str = "read_file";
+pub const LIST_DIR: &str = "list_dir";
+pub const GREP_FILES: &str = "grep_files";
+
+/// Upper bound on what `read_file` returns.
+pub const DEFAULT_MAX_READ_BYTES: usize = 256 * 1024;
+
+/// Files larger than this are skipped by `grep_files`.
+pub const DEFAULT_MAX_GREP_FILE_BYTES: u64 = 2 * 1024 * 1024;
+
+/// Upper bound on `grep_files` matches and `list_dir` entries.
+pub const DEFAULT_MAX_RESULTS: usize = 200;
+
+/// How much of a file is inspected to decide whether it is binary.
+const BINARY_SNIFF_BYTES: usize = 8 * 1024;
+
+/// Directories `grep_files` never descends into: VCS metadata and
+/// dependency / build output that would drown real matches.
+const SKIPPED_DIRS: &[&str] = &[
+    ".git",
+    ".hg",
+    ".svn",
+    "node_modules",
+    "target",
+    "__pycache__",
+];
+
+/// Directories the agent may read, plus the size limits it reads under.
+#[derive(Debug, Clone)]
+pub struct FileRoots {
+    roots: Vec<PathBuf>,
+    pub max_read_bytes: usize,
+    pub max_grep_file_bytes: u64,
+    pub max_results: usize,
+}
+
+impl FileRoots {
+    /// Roots that don't exist (or can't be canonicalized) are dropped
+    /// with a warning rather than failing the whole set.
+    pub fn new<P: AsRef<Path>>(roots: impl IntoIterator<Item = P>) -> Self {
+        let roots = roots
+            .into_iter()
+            .filter_map(|root| match root.as_ref().canonicalize() {
+                Ok(root) => Some(root),
+                Err(e) => {
+                    tracing::warn!("Ignoring file root {}: {e}", root.as_ref().display());
+                    None
+                }
+            })
+            .collect();
+        Self {
+            roots,
+            max_read_bytes: DEFAULT_MAX_READ_BYTES,
+            max_grep_file_bytes: DEFAULT_MAX_GREP_FILE_BYTES,
+            max_results: DEFAULT_MAX_RESULTS,
+        }
+    }
+
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.roots.is_empty()
+    }
+
+    /// Resolve a model-supplied path. Absolute paths must land inside a
+    /// root; relative ones are tried against each root in order.
+    pub fn resolve(&self, path: &str) -> Result<PathBuf> {
+        let candidate = Path::new(path);
+        let candidates: Vec<PathBuf> = if candidate.is_absolute() {
+            vec![candidate.to_path_buf()]
+        } else {
+            self.roots.iter().map(|root| root.join(candidate)).collect()
+        };
+
+        let mut outside = false;
+        for candidate in candidates {
+            let Ok(resolved) = candidate.canonicalize() else {
+                continue;
+            };
+            if self.contains(&resolved) {
+                return Ok(resolved);
+            }
+            outside = true;
+        }
+        Err(Error::ToolException(if outside {
+            format!("`{path}` is outside the folders shared with the assistant")
+        } else {
+            format!("`{path}` does not exist")
+        }))
+    }
+
+    fn contains(&self, path: &Path) -> bool {
+        self.roots.iter().any(|root| path.starts_with(root))
+    }
+
+    /// The three tools, bound to these roots.
+    pub fn tools(self: &Arc<Self>) -> Vec<StructuredTool> {
+        let roots_list = self
+            .roots
+            .iter()
+            .map(|r| r.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        vec![
+            self.tool(
+                READ_FILE,
+                format!(
+                    "Read a text file from the user's shared folders ({roots_list}). Returns \
+                     at most {} KiB; use `offset_line` and `max_lines` to page through larger \
+                     files.",
+                    self.max_read_bytes / 1024
+                ),
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "path": { "type": "string", "description": "Absolute path, or relative to a shared folder." },
+                        "offset_line": { "type": "integer", "minimum": 0, "description": "First line to return (0-based)." },
+                        "max_lines": { "type": "integer", "minimum": 1, "description": "Maximum number of lines to return." }
+                    },
+                    "required": ["path"]
+                }),
+                |roots, args| roots.read_file(&args),
+            ),
+            self.tool(
+                LIST_DIR,
+                format!(
+                    "List a directory in the user's shared folders ({roots_list}). Omit `path` \
+                     to list the shared folders themselves."
+                ),
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "path": { "type": "string", "description": "Absolute path, or relative to a shared folder." }
+                    }
+                }),
+                |roots, args| roots.list_dir(&args),
+            ),
+            self.tool(
+                GREP_FILES,
+                format!(
+                    "Search file contents in the user's shared folders ({roots_list}) with a \
+                     regular expression. Returns matching lines with their paths and line \
+                     numbers, at most {} matches.",
+                    self.max_results
+                ),
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "pattern": { "type": "string", "description": "Regular expression (Rust regex syntax)." },
+                        "path": { "type": "string", "description": "Directory or file to search. Defaults to every shared folder." },
+                        "case_insensitive": { "type": "boolean", "default": true },
+                        "extensions": { "type": "array", "items": { "type": "string" }, "description": "Only search files with these extensions, e.g. [\"md\", \"txt\"]." }
+                    },
+                    "required": ["pattern"]
+                }),
+                |roots, args| roots.grep_files(&args),
+            ),
+        ]
+    }
+
+    fn tool(
+        self: &Arc<Self>,
+        name: &str,
+        description: String,
+        schema: Value,
+        run: fn(&FileRoots, HashMap<String, Value>) -> Result<Value>,
+    ) -> StructuredTool {
+        let roots = Arc::clone(self);
+        StructuredTool::builder()
+            .name(name)
+            .description(description)
+            .args_schema(ArgsSchema::JsonSchema(schema))
+            .coroutine(Arc::new(move |args: HashMap<String, Value>| {
+                let roots = Arc::clone(&roots);
+                Box::pin(async move {
+                    // Directory walks and reads block; keep them off the
+                    // async workers.
+                    tokio::task::spawn_blocking(move || run(&roots, args))
+                        .await
+                        .map_err(|e| Error::Other(format!("file tool panicked: {e}")))?
+                })
+            }))
+            .build()
+    }
+
+    fn read_file(&self, args: &HashMap<String, Value>) -> Result<Value> {
+        let path = self.resolve(required_str(args, "path")?)?;
+        if !path.is_file() {
+            return Err(Error::ToolException(format!(
+                "`{}` is not a file",
+                path.display()
+            )));
+        }
+        let size = fs::metadata(&path).map_err(io_error)?.len();
+
+        let mut bytes = Vec::new();
+        fs::File::open(&path)
+            .map_err(io_error)?
+            .take(self.max_read_bytes as u64)
+            .read_to_end(&mut bytes)
+            .map_err(io_error)?;
+        let Some(text) = decode_text(&bytes) else {
+            return Ok(json!({ "path": path, "size": size, "binary": true }));
+        };
+
+        let offset = optional_usize(args, "offset_line").unwrap_or(0);
+        let max_lines = optional_usize(args, "max_lines");
+        let total_lines = text.lines().count();
+        let content = match max_lines {
+            None if offset == 0 => text.to_string(),
+            _ => text
+                .lines()
+                .skip(offset)
+                .take(max_lines.unwrap_or(usize::MAX))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        };
+
+        Ok(json!({
+            "path": path,
+            "size": size,
+            "content": content,
+            "total_lines": total_lines,
+            "truncated": size > bytes.len() as u64,
+        }))
+    }
+
+    fn list_dir(&self, args: &HashMap<String, Value>) -> Result<Value> {
+        let Some(path) = args
+            .get("path")
+            .and_then(Value::as_str)
+            .filter(|p| !p.is_empty())
+        else {
+            return Ok(json!({ "roots": self.roots }));
+        };
+        let path = self.resolve(path)?;
+
+        let mut entries = Vec::new();
+        let mut truncated = false;
+        for entry in fs::read_dir(&path).map_err(io_error)? {
+            let Ok(entry) = entry else { continue };
+            if entries.len() >= self.max_results {
+                truncated = true;
+                break;
+            }
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let kind = if file_type.is_dir() {
+                "dir"
+            } else if file_type.is_symlink() {
+                "symlink"
+            } else {
+                "file"
+            };
+            let size = if file_type.is_file() {
+                entry.metadata().ok().map(|m| m.len())
+            } else {
+                None
+            };
+            entries.push(json!({
+                "name": entry.file_name().to_string_lossy(),
+                "kind": kind,
+                "size": size,
+            }));
+        }
+        entries.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str()));
+
+        Ok(json!({ "path": path, "entries": entries, "truncated": truncated }))
+    }
+
+    fn grep_files(&self, args: &HashMap<String, Value>) -> Result<Value> {
+        let pattern = required_str(args, "pattern")?;
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(
+                args.get("case_insensitive")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(true),
+            )
+            .build()
+            .map_err(|e| Error::ToolInvocation(format!("invalid pattern: {e}")))?;
+        let extensions: Vec<String> = args
+            .get("extensions")
+            .and_then(Value::as_array)
+            .map(|exts| {
+                exts.iter()
+                    .filter_map(Value::as_str)
+                    .map(|e| e.trim_start_matches('.').to_ascii_lowercase())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut stack = match args.get("path").and_then(Value::as_str) {
+            Some(path) if !path.is_empty() => vec![self.resolve(path)?],
+            _ => self.roots.clone(),
+        };
+
+        let mut matches = Vec::new();
+        let mut truncated = false;
+        'walk: while let Some(path) = stack.pop() {
+            let Ok(metadata) = fs::metadata(&path) else {
+                continue;
+            };
+            if metadata.is_dir() {
+                let Ok(entries) = fs::read_dir(&path) else {
+                    continue;
+                };
+                for entry in entries.flatten() {
+                    let name = entry.file_name();
+                    let name = name.to_string_lossy();
+                    if name.starts_with('.') || SKIPPED_DIRS.contains(&&*name) {
+                        continue;
+                    }
+                    // Symlinks are followed only if they stay inside a root.
+                    match entry.path().canonicalize() {
+                        Ok(child) if self.contains(&child) => stack.push(child),
+                        _ => {}
+                    }
+                }
+                continue;
+            }
+            if metadata.len() > self.max_grep_file_bytes || !has_extension(&path, &extensions) {
+                continue;
+            }
+            let Ok(bytes) = fs::read(&path) else {
+                continue;
+            };
+            let Some(text) = decode_text(&bytes) else {
+                continue;
+            };
+            for (index, line) in text.lines().enumerate() {
+                if regex.is_match(line) {
+                    if matches.len() >= self.max_results {
+                        truncated = true;
+                        break 'walk;
+                    }
+                    matches.push(json!({
+                        "path": path,
+                        "line_number": index + 1,
+                        "line": truncate_line(line),
+                    }));
+                }
+            }
+        }
+
+        Ok(json!({ "matches": matches, "truncated": truncated }))
+    }
+}
+
+/// `Some(text)` unless the bytes look binary. A cut-off multi-byte
+/// sequence at the very end (from a size-capped read) is tolerated.
+fn decode_text(bytes: &[u8]) -> Option<&str> {
+    let sniff = &bytes[..bytes.len().min(BINARY_SNIFF_BYTES)];
+    if sniff.contains(&0) {
+        return None;
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(text) => Some(text),
+        Err(e) if e.error_len().is_none() => std::str::from_utf8(&bytes[..e.valid_up_to()]).ok(),
+        Err(_) => None,
+    }
+}
+
+fn has_extension(path: &Path, extensions: &[String]) -> bool {
+    extensions.is_empty()
+        || path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| extensions.iter().any(|x| x.eq_ignore_ascii_case(e)))
+}
+
+/// Minified files put a whole bundle on one line; keep matches readable.
+fn truncate_line(line: &str) -> &str {
+    const MAX_LINE_CHARS: usize = 500;
+    match line.char_indices().nth(MAX_LINE_CHARS) {
+        Some((end, _)) => &line[..end],
+        None => line,
+    }
+}
+
+fn required_str<'a>(args: &'a HashMap<String, Value>, key: &str) -> Result<&'a str> {
+    args.get(key)
+        .and_then(Value::as_str)
+        .ok_or_else(|| Error::ToolInvocation(format!("missing string argument `{key}`")))
+}
+
+fn optional_usize(args: &HashMap<String, Value>, key: &str) -> Option<usize> {
+    args.get(key)
+        .and_then(Value::as_u64)
+        .and_then(|n| usize::try_from(n).ok())
+}
+
+fn io_error(e: std::io::Error) -> Error {
+    Error::ToolException(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agent_chain_core::tools::{BaseTool, ToolInput, ToolOutput};
+
+    fn fixture() -> (tempfile::TempDir, Arc<FileRoots>) {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("notes")).unwrap();
+        fs::write(
+            dir.path().join("notes/plan.md"),
+            "# Plan\nship the thing\nthen rest\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("notes/todo.txt"), "Ship it\n").unwrap();
+        fs::write(dir.path().join("image.bin"), [0u8, 1, 2, 3]).unwrap();
+        fs::create_dir(dir.path().join("node_modules")).unwrap();
+        fs::write(dir.path().join("node_modules/dep.md"), "ship\n").unwrap();
+        let roots = Arc::new(FileRoots::new([dir.path()]));
+        (dir, roots)
+    }
+
+    fn args(pairs: &[(&str, Value)]) -> HashMap<String, Value> {
+        pairs
+            .iter()
+            .map(|(k, v)| ((*k).to_string(), v.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn paths_outside_roots_are_refused() {
+        let (dir, roots) = fixture();
+        assert!(roots.resolve("notes/plan.md").is_ok());
+        assert!(roots.resolve("../").is_err());
+        assert!(roots.resolve("/etc/hosts").is_err());
+        assert!(roots.resolve("missing.txt").is_err());
+        let absolute = dir.path().join("notes/plan.md");
+        assert!(roots.resolve(absolute.to_str().unwrap()).is_ok());
+    }
+
+    #[test]
+    fn read_file_pages_and_detects_binary() {
+        let (_dir, roots) = fixture();
+        let out = roots
+            .read_file(&args(&[
+                ("path", json!("notes/plan.md")),
+                ("offset_line", json!(1)),
+                ("max_lines", json!(1)),
+            ]))
+            .unwrap();
+        assert_eq!(out["content"], "ship the thing");
+        assert_eq!(out["truncated"], false);
+
+        let out = roots
+            .read_file(&args(&[("path", json!("image.bin"))]))
+            .unwrap();
+        assert_eq!(out["binary"], true);
+        assert_eq!(out["size"], 4);
+    }
+
+    #[test]
+    fn read_file_caps_size() {
+        let (_dir, roots) = fixture();
+        let mut roots = (*roots).clone();
+        roots.max_read_bytes = 4;
+        let out = roots
+            .read_file(&args(&[("path", json!("notes/plan.md"))]))
+            .unwrap();
+        assert_eq!(out["content"], "# Pl");
+        assert_eq!(out["truncated"], true);
+    }
+
+    #[test]
+    fn list_dir_without_path_lists_roots() {
+        let (_dir, roots) = fixture();
+        let out = roots.list_dir(&HashMap::new()).unwrap();
+        assert_eq!(out["roots"].as_array().unwrap().len(), 1);
+
+        let out = roots.list_dir(&args(&[("path", json!("notes"))])).unwrap();
+        let names: Vec<_> = out["entries"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["name"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(names, vec!["plan.md", "todo.txt"]);
+    }
+
+    #[test]
+    fn grep_skips_binary_and_dependency_dirs() {
+        let (_dir, roots) = fixture();
+        let out = roots
+            .grep_files(&args(&[("pattern", json!("ship"))]))
+            .unwrap();
+        let mut hits: Vec<_> = out["matches"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["line"].as_str().unwrap().to_string())
+            .collect();
+        hits.sort();
+        assert_eq!(hits, vec!["Ship it", "ship the thing"]);
+
+        let out = roots
+            .grep_files(&args(&[
+                ("pattern", json!("ship")),
+                ("extensions", json!(["md"])),
+            ]))
+            .unwrap();
+        assert_eq!(out["matches"].as_array().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn tools_run_through_the_tool_interface() {
+        let (_dir, roots) = fixture();
+        let tools = roots.tools();
+        let read = tools.iter().find(|t| t.name() == READ_FILE).unwrap();
+        let output = read
+            .run(
+                ToolInput::Dict(args(&[("path", json!("notes/todo.txt"))])),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        let ToolOutput::Json(output) = output else {
+            panic!("expected JSON output");
+        };
+        assert_eq!(output["content"], "Ship it\n");
+    }
+}
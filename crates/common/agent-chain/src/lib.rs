@@ -13,7 +13,13 @@
 //! - **Core layer** ([`chat_model`]): Base `ChatModel` trait that all providers implement
 //! - **Provider layer** ([`providers`]): Provider-specific implementations (ChatAnthropic, ChatOpenAI)
 //! - **Message layer** ([`messages`]): Message types for threads
+//! - **Capabilities** ([`capabilities`]): What each model supports, used to clamp or
+//!   reject request parameters before they reach the API
 //! - **Tools layer** ([`tools`]): Tool definitions and the `#[tool]` macro
+//! - **MCP layer** (`mcp`): Tools proxied from external MCP servers
+//! - **Shell tool** (`shell`): Built-in, policy-confined `run_command` tool
+//! - **File tools** (`files`): Built-in read-only file tools scoped to approved roots
+//! - **Web tools** (`web`): Built-in `web_search` and `fetch_url` tools
 //!
 //! # Quick Start
 //!
@@ -45,10 +51,23 @@
 //! - `default`: Includes all providers
 //! - `anthropic`: Anthropic/Claude support
 //! - `openai`: OpenAI/GPT support
+//! - `mcp`: Model Context Protocol client for external tool servers
+//! - `shell`: Built-in `run_command` tool
+//! - `files`: Built-in `read_file`, `list_dir` and `grep_files` tools
+//! - `web`: Built-in `web_search` and `fetch_url` tools
 //! - `dynamic-image`: Image processing support
 //! - `specta`: Specta derive support
 
+pub mod capabilities;
+#[cfg(feature = "files")]
+pub mod files;
+#[cfg(feature = "mcp")]
+pub mod mcp;
 pub mod providers;
+#[cfg(feature = "shell")]
+pub mod shell;
+#[cfg(feature = "web")]
+pub mod web;
 
 pub use providers::*;
 
@@ -145,6 +164,10 @@ pub fn init_chat_model(
 /// This provides a more flexible way to create chat models when you need
 /// to configure options beyond just the model name.
 ///
+/// Temperature and max tokens are clamped to what the model supports, per
+/// [`capabilities::lookup`]; a temperature that isn't a finite, non-negative
+/// number is rejected.
+///
 /// # Example
 ///
 /// ```ignore
@@ -211,9 +234,24 @@ impl ChatModelBuilder {
     }
 
     /// Build the chat model.
-    pub fn build(self) -> Result<Arc<dyn BaseChatModel>> {
+    pub fn build(mut self) -> Result<Arc<dyn BaseChatModel>> {
         let (_model_name, provider) = parse_model(&self.model, self.provider.as_deref())?;
 
+        if let Some(temp) = self.temperature
+            && !(temp.is_finite() && temp >= 0.0)
+        {
+            return Err(Error::InvalidConfig(format!(
+                "temperature must be a non-negative number, got {temp}"
+            )));
+        }
+        let capabilities = capabilities::lookup(&provider, &_model_name);
+        self.temperature = self
+            .temperature
+            .map(|temp| capabilities.clamp_temperature(temp));
+        self.max_tokens = self
+            .max_tokens
+            .map(|max| capabilities.clamp_max_tokens(max));
+
         match provider.as_str() {
             #[cfg(feature = "anthropic")]
             "anthropic" => {
@@ -0,0 +1,537 @@
+//! Model Context Protocol client.
+//!
+//! Lets agents consume tools from external MCP servers. Each server is
+//! launched as a subprocess and spoken to over stdio (newline-delimited
+//! JSON-RPC 2.0), the transport every MCP server supports.
+//!
+//! - [`McpClient::connect`] spawns the server and performs the
+//!   `initialize` handshake.
+//! - [`McpClient::list_tools`] discovers the server's tools;
+//!   [`McpClient::tool_definitions`] translates the allowed ones into
+//!   [`ToolDefinition`]s.
+//! - [`McpClient::tools`] wraps every allowed tool in a
+//!   [`StructuredTool`] whose coroutine proxies the call back to the
+//!   server, so MCP tools plug into the same tool-calling loop as local
+//!   ones.
+//!
+//! Which tools an agent may use is decided by the user per server via
+//! [`McpServerConfig::allowed_tools`]; tools outside the allowlist are
+//! never surfaced to the model.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use agent_chain::mcp::{McpClient, McpServerConfig};
+//!
+//! let config = McpServerConfig {
+//!     name: "files".into(),
+//!     command: "mcp-server-filesystem".into(),
+//!     args: vec!["/home/me/notes".into()],
+//!     env: Default::default(),
+//!     allowed_tools: Some(vec!["read_file".into(), "list_directory".into()]),
+//! };
+//! let client = McpClient::connect(config).await?;
+//! let tools = client.tools().await?;
+//! ```
+
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use agent_chain_core::tools::{ArgsSchema, StructuredTool, ToolDefinition};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::oneshot;
+
+use crate::error::{Error, Result};
+
+/// Protocol revision sent in the `initialize` handshake.
+pub const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// How long to wait for any single response from a server.
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Most providers cap tool names at 64 characters.
+const MAX_TOOL_NAME_LEN: usize = 64;
+
+/// User configuration for one MCP server.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpServerConfig {
+    /// Short identifier; prefixes the server's tool names so tools from
+    /// different servers can't collide.
+    pub name: String,
+    /// Executable that starts the server.
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Tools the agent may call, by the server's own tool name. `None`
+    /// allows every tool the server advertises; an empty list allows
+    /// none.
+    #[serde(default)]
+    pub allowed_tools: Option<Vec<String>>,
+}
+
+impl McpServerConfig {
+    pub fn allows(&self, tool: &str) -> bool {
+        self.allowed_tools
+            .as_ref()
+            .is_none_or(|allowed| allowed.iter().any(|t| t == tool))
+    }
+
+    /// Name the model sees for one of this server's tools:
+    /// `<server>__<tool>`, restricted to `[A-Za-z0-9_-]` and capped at 64
+    /// characters.
+    pub fn qualified_tool_name(&self, tool: &str) -> String {
+        let mut name: String = format!("{}__{}", self.name, tool)
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        name.truncate(MAX_TOOL_NAME_LEN);
+        name
+    }
+}
+
+/// A tool as advertised by an MCP server's `tools/list`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpTool {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub input_schema: Value,
+}
+
+impl McpTool {
+    /// Translate into a provider-neutral [`ToolDefinition`] named per
+    /// [`McpServerConfig::qualified_tool_name`].
+    pub fn to_tool_definition(&self, config: &McpServerConfig) -> ToolDefinition {
+        ToolDefinition {
+            name: config.qualified_tool_name(&self.name),
+            description: self.description.clone().unwrap_or_default(),
+            parameters: normalize_input_schema(self.input_schema.clone()),
+        }
+    }
+}
+
+type Pending = Arc<Mutex<HashMap<u64, oneshot::Sender<Result<Value>>>>>;
+
+/// A connected MCP server. The subprocess is killed when the client is
+/// dropped.
+pub struct McpClient {
+    config: McpServerConfig,
+    stdin: tokio::sync::Mutex<ChildStdin>,
+    pending: Pending,
+    next_id: AtomicU64,
+    _child: Child,
+}
+
+impl std::fmt::Debug for McpClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("McpClient")
+            .field("server", &self.config.name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl McpClient {
+    /// Spawn the server described by `config` and complete the MCP
+    /// `initialize` handshake.
+    pub async fn connect(config: McpServerConfig) -> Result<Arc<Self>> {
+        let mut child = Command::new(&config.command)
+            .args(&config.args)
+            .envs(&config.env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true)
+            .spawn()?;
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| Error::other("MCP server stdin unavailable"))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| Error::other("MCP server stdout unavailable"))?;
+
+        let pending: Pending = Arc::default();
+        tokio::spawn(read_responses(
+            config.name.clone(),
+            BufReader::new(stdout),
+            pending.clone(),
+        ));
+
+        let client = Arc::new(Self {
+            config,
+            stdin: tokio::sync::Mutex::new(stdin),
+            pending,
+            next_id: AtomicU64::new(1),
+            _child: child,
+        });
+        client
+            .request(
+                "initialize",
+                json!({
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": { "name": "agent-chain", "version": env!("CARGO_PKG_VERSION") },
+                }),
+            )
+            .await?;
+        client
+            .send(&json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }))
+            .await?;
+        Ok(client)
+    }
+
+    pub fn config(&self) -> &McpServerConfig {
+        &self.config
+    }
+
+    /// Every tool the server advertises, following pagination cursors.
+    /// Not filtered by the allowlist.
+    pub async fn list_tools(&self) -> Result<Vec<McpTool>> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct ListToolsResult {
+            tools: Vec<McpTool>,
+            #[serde(default)]
+            next_cursor: Option<String>,
+        }
+
+        let mut tools = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let params = match &cursor {
+                Some(cursor) => json!({ "cursor": cursor }),
+                None => json!({}),
+            };
+            let page: ListToolsResult =
+                serde_json::from_value(self.request("tools/list", params).await?)?;
+            tools.extend(page.tools);
+            match page.next_cursor {
+                Some(next) if !next.is_empty() => cursor = Some(next),
+                _ => return Ok(tools),
+            }
+        }
+    }
+
+    /// Definitions of the tools the allowlist permits.
+    pub async fn tool_definitions(&self) -> Result<Vec<ToolDefinition>> {
+        Ok(self
+            .list_tools()
+            .await?
+            .iter()
+            .filter(|t| self.config.allows(&t.name))
+            .map(|t| t.to_tool_definition(&self.config))
+            .collect())
+    }
+
+    /// Allowed tools wrapped as [`StructuredTool`]s that proxy each call
+    /// to this server.
+    pub async fn tools(self: &Arc<Self>) -> Result<Vec<StructuredTool>> {
+        let tools = self
+            .list_tools()
+            .await?
+            .into_iter()
+            .filter(|t| self.config.allows(&t.name))
+            .map(|tool| {
+                let definition = tool.to_tool_definition(&self.config);
+                let client = Arc::clone(self);
+                let remote_name = tool.name;
+                StructuredTool::builder()
+                    .name(definition.name)
+                    .description(definition.description)
+                    .args_schema(ArgsSchema::JsonSchema(definition.parameters))
+                    .coroutine(Arc::new(move |args: HashMap<String, Value>| {
+                        let client = Arc::clone(&client);
+                        let remote_name = remote_name.clone();
+                        Box::pin(async move {
+                            let arguments = Value::Object(args.into_iter().collect());
+                            client.call_tool(&remote_name, arguments).await
+                        })
+                    }))
+                    .build()
+            })
+            .collect();
+        Ok(tools)
+    }
+
+    /// Invoke `name` (the server's own tool name) with `arguments`.
+    ///
+    /// Refuses tools outside the allowlist. Text content comes back as a
+    /// single string; results with non-text content come back as the raw
+    /// content array. A result flagged `isError` becomes
+    /// [`Error::ToolException`] so the agent's error handler sees it.
+    pub async fn call_tool(&self, name: &str, arguments: Value) -> Result<Value> {
+        if !self.config.allows(name) {
+            return Err(Error::ToolInvocation(format!(
+                "tool `{name}` is not allowed for MCP server `{}`",
+                self.config.name
+            )));
+        }
+        let result = self
+            .request(
+                "tools/call",
+                json!({ "name": name, "arguments": arguments }),
+            )
+            .await?;
+        parse_call_result(result)
+    }
+
+    async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        self.pending
+            .lock()
+            .map_err(|e| Error::LockPoisoned(e.to_string()))?
+            .insert(id, tx);
+
+        let message = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        if let Err(err) = self.send(&message).await {
+            self.forget(id);
+            return Err(err);
+        }
+
+        match tokio::time::timeout(REQUEST_TIMEOUT, rx).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(Error::other(format!(
+                "MCP server `{}` exited before answering `{method}`",
+                self.config.name
+            ))),
+            Err(_) => {
+                self.forget(id);
+                Err(Error::Timeout(format!(
+                    "MCP server `{}` did not answer `{method}` within {}s",
+                    self.config.name,
+                    REQUEST_TIMEOUT.as_secs()
+                )))
+            }
+        }
+    }
+
+    async fn send(&self, message: &Value) -> Result<()> {
+        let mut line = serde_json::to_vec(message)?;
+        line.push(b'\n');
+        let mut stdin = self.stdin.lock().await;
+        stdin.write_all(&line).await?;
+        stdin.flush().await?;
+        Ok(())
+    }
+
+    fn forget(&self, id: u64) {
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.remove(&id);
+        }
+    }
+}
+
+/// Route responses to their waiting requests until the server closes
+/// stdout, then fail whatever is still outstanding.
+async fn read_responses(
+    server: String,
+    stdout: BufReader<tokio::process::ChildStdout>,
+    pending: Pending,
+) {
+    let mut lines = stdout.lines();
+    loop {
+        let line = match lines.next_line().await {
+            Ok(Some(line)) => line,
+            Ok(None) => break,
+            Err(err) => {
+                tracing::warn!("MCP server `{server}` stdout read failed: {err}");
+                break;
+            }
+        };
+        let Ok(message) = serde_json::from_str::<Value>(&line) else {
+            tracing::debug!("MCP server `{server}` wrote a non-JSON line");
+            continue;
+        };
+        // Server-initiated requests and notifications (logging,
+        // list-changed) carry a `method`; this client ignores them.
+        if message.get("method").is_some() {
+            continue;
+        }
+        let Some(id) = message.get("id").and_then(Value::as_u64) else {
+            continue;
+        };
+        let Some(tx) = pending.lock().ok().and_then(|mut p| p.remove(&id)) else {
+            continue;
+        };
+        let _ = tx.send(parse_response(message));
+    }
+    // Dropping the senders wakes every waiter with a closed-channel error.
+    if let Ok(mut pending) = pending.lock() {
+        pending.clear();
+    }
+}
+
+fn parse_response(mut message: Value) -> Result<Value> {
+    if let Some(error) = message.get("error") {
+        let code = error.get("code").and_then(Value::as_i64).unwrap_or(0);
+        let text = error
+            .get("message")
+            .and_then(Value::as_str)
+            .unwrap_or("unknown error");
+        return Err(Error::ToolInvocation(format!("MCP error {code}: {text}")));
+    }
+    Ok(message
+        .get_mut("result")
+        .map(Value::take)
+        .unwrap_or(Value::Null))
+}
+
+fn parse_call_result(result: Value) -> Result<Value> {
+    let is_error = result
+        .get("isError")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let content = result
+        .get("content")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+
+    let all_text = content
+        .iter()
+        .all(|block| block.get("type").and_then(Value::as_str) == Some("text"));
+    let text = || {
+        content
+            .iter()
+            .filter_map(|block| block.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    if is_error {
+        return Err(Error::ToolException(text()));
+    }
+    if all_text {
+        Ok(Value::String(text()))
+    } else {
+        Ok(Value::Array(content))
+    }
+}
+
+/// Coerce an MCP `inputSchema` into the object schema providers expect:
+/// `type: object` with a `properties` map, minus the `$schema` marker some
+/// providers reject.
+fn normalize_input_schema(schema: Value) -> Value {
+    let Value::Object(mut schema) = schema else {
+        return json!({ "type": "object", "properties": {} });
+    };
+    schema.remove("$schema");
+    schema.insert("type".into(), json!("object"));
+    schema.entry("properties").or_insert_with(|| json!({}));
+    Value::Object(schema)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(allowed_tools: Option<Vec<&str>>) -> McpServerConfig {
+        McpServerConfig {
+            name: "files".into(),
+            command: "mcp-files".into(),
+            args: Vec::new(),
+            env: HashMap::new(),
+            allowed_tools: allowed_tools.map(|t| t.into_iter().map(String::from).collect()),
+        }
+    }
+
+    #[test]
+    fn allowlist_defaults_to_everything() {
+        assert!(config(None).allows("read_file"));
+        assert!(config(Some(vec!["read_file"])).allows("read_file"));
+        assert!(!config(Some(vec!["read_file"])).allows("write_file"));
+        assert!(!config(Some(vec![])).allows("read_file"));
+    }
+
+    #[test]
+    fn tool_names_are_namespaced_and_sanitized() {
+        let config = config(None);
+        assert_eq!(config.qualified_tool_name("read_file"), "files__read_file");
+        assert_eq!(config.qualified_tool_name("git.log"), "files__git_log");
+        assert_eq!(
+            config.qualified_tool_name(&"x".repeat(100)).len(),
+            MAX_TOOL_NAME_LEN
+        );
+    }
+
+    #[test]
+    fn translates_tool_schema() {
+        let tool: McpTool = serde_json::from_value(json!({
+            "name": "read_file",
+            "description": "Read a file",
+            "inputSchema": {
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "type": "object",
+                "properties": { "path": { "type": "string" } },
+                "required": ["path"],
+            },
+        }))
+        .unwrap();
+        let definition = tool.to_tool_definition(&config(None));
+        assert_eq!(definition.name, "files__read_file");
+        assert_eq!(definition.description, "Read a file");
+        assert!(definition.parameters.get("$schema").is_none());
+        assert_eq!(definition.parameters["required"], json!(["path"]));
+
+        let bare: McpTool = serde_json::from_value(json!({ "name": "ping" })).unwrap();
+        assert_eq!(
+            bare.to_tool_definition(&config(None)).parameters,
+            json!({ "type": "object", "properties": {} })
+        );
+    }
+
+    #[test]
+    fn call_results_map_to_values_and_errors() {
+        let text = parse_call_result(json!({
+            "content": [{ "type": "text", "text": "a" }, { "type": "text", "text": "b" }],
+        }))
+        .unwrap();
+        assert_eq!(text, json!("a\nb"));
+
+        let mixed = parse_call_result(json!({
+            "content": [{ "type": "image", "data": "AA==", "mimeType": "image/png" }],
+        }))
+        .unwrap();
+        assert!(mixed.is_array());
+
+        let err = parse_call_result(json!({
+            "content": [{ "type": "text", "text": "no such file" }],
+            "isError": true,
+        }))
+        .unwrap_err();
+        assert_eq!(err.as_tool_exception(), Some("no such file"));
+    }
+
+    #[test]
+    fn rpc_errors_become_tool_invocation_errors() {
+        let err = parse_response(json!({
+            "jsonrpc": "2.0", "id": 1,
+            "error": { "code": -32601, "message": "method not found" },
+        }))
+        .unwrap_err();
+        assert!(err.to_string().contains("method not found"));
+        assert_eq!(
+            parse_response(json!({ "jsonrpc": "2.0", "id": 1, "result": { "ok": true } })).unwrap(),
+            json!({ "ok": true })
+        );
+    }
+}
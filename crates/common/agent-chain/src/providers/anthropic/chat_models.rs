@@ -12,6 +12,7 @@ use serde::Deserialize;
 
 use crate::ToolChoice;
 use crate::callbacks::{CallbackManagerForLLMRun, Callbacks};
+use crate::capabilities::{self, ModelCapabilities};
 use crate::chat_models::{BaseChatModel, ChatModelConfig, LangSmithParams};
 use crate::error::{Error, Result};
 use crate::language_models::{BaseLanguageModel, LanguageModelConfig};
@@ -175,12 +176,19 @@ impl ChatAnthropic {
     /// Build the HTTP client with configured timeout.
     fn build_client(&self) -> reqwest::Client {
         let mut builder = reqwest::Client::builder();
-        if let Some(timeout) = self.timeout {
-            builder = builder.timeout(std::time::Duration::from_secs(timeout));
+        // Inside a backend request, give up when the request would.
+        let timeout = self.timeout.map(std::time::Duration::from_secs);
+        if let Some(timeout) = net_core::deadline::cap(timeout) {
+            builder = builder.timeout(timeout);
         }
         builder.build().unwrap_or_else(|_| reqwest::Client::new())
     }
 
+    /// What this model supports, from the capability registry.
+    fn capabilities(&self) -> ModelCapabilities {
+        capabilities::lookup("anthropic", &self.model)
+    }
+
     /// Convert messages to Anthropic API format.
     fn format_messages(&self, messages: &[AnyMessage]) -> (Option<String>, Vec<serde_json::Value>) {
         let mut system_message = None;
@@ -259,10 +267,11 @@ impl ChatAnthropic {
         tools: Option<&[serde_json::Value]>,
     ) -> serde_json::Value {
         let (system_message, thread_messages) = self.format_messages(messages);
+        let capabilities = self.capabilities();
 
         let mut payload = serde_json::json!({
             "model": self.model,
-            "max_tokens": self.max_tokens,
+            "max_tokens": capabilities.clamp_max_tokens(self.max_tokens),
             "messages": thread_messages
         });
 
@@ -271,7 +280,7 @@ impl ChatAnthropic {
         }
 
         if let Some(temp) = self.temperature {
-            payload["temperature"] = serde_json::json!(temp);
+            payload["temperature"] = serde_json::json!(capabilities.clamp_temperature(temp));
         }
 
         if let Some(k) = self.top_k {
@@ -516,6 +525,8 @@ impl ChatAnthropic {
         stop: Option<Vec<String>>,
         _run_manager: Option<&CallbackManagerForLLMRun>,
     ) -> Result<ChatResult> {
+        self.capabilities()
+            .check_request(&self.model, &messages, 0)?;
         let payload = self.build_request_payload(&messages, stop, None);
 
         let resp: AnthropicResponse = (|| self.send_json_request(&payload))
@@ -534,6 +545,8 @@ impl ChatAnthropic {
         tool_choice: Option<&ToolChoice>,
         stop: Option<Vec<String>>,
     ) -> Result<AIMessage> {
+        self.capabilities()
+            .check_request(&self.model, &messages, tools.len())?;
         let anthropic_tools: Vec<serde_json::Value> = tools
             .iter()
             .map(|t| {
@@ -694,6 +707,16 @@ mod tests {
         assert_eq!(model.api_key, Some("test-key".to_string()));
     }
 
+    #[test]
+    fn test_payload_clamps_to_model_limits() {
+        let model = ChatAnthropic::new("claude-3-5-haiku-20241022")
+            .temperature(1.5)
+            .max_tokens(16_384);
+        let payload = model.build_request_payload(&[], None, None);
+        assert_eq!(payload["temperature"], 1.0);
+        assert_eq!(payload["max_tokens"], 8_192);
+    }
+
     #[test]
     fn test_llm_type() {
         let model = ChatAnthropic::new("claude-sonnet-4-5-20250929");
@@ -6,9 +6,11 @@ mod chat_models;
 mod compat;
 mod embeddings;
 mod llms;
+mod models;
 mod utils;
 
 pub use chat_models::*;
 pub use embeddings::*;
 pub use llms::*;
+pub use models::{LocalModel, LocalModelDetails, OllamaModels, PullProgress, PullStream};
 pub use utils::{merge_auth_headers, parse_url_with_auth, validate_model};
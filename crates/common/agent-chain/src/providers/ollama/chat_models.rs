@@ -32,8 +32,10 @@ use tokio::io::AsyncBufReadExt;
 use tokio_util::io::StreamReader;
 
 use super::compat::convert_from_v1_to_ollama;
+use super::models::OllamaModels;
 use super::utils::{merge_auth_headers, parse_url_with_auth, validate_model};
 use crate::callbacks::{CallbackManagerForLLMRun, Callbacks};
+use crate::capabilities::{self, ModelCapabilities};
 use crate::chat_models::{
     BaseChatModel, ChatChunk, ChatModelConfig, LangSmithParams, ToolChoice, UsageMetadata,
 };
@@ -83,6 +85,11 @@ pub struct ChatOllama {
     base_url: Option<String>,
     #[builder(default)]
     validate_model_on_init: bool,
+    /// Ask the server what the model supports (`/api/show`) before the
+    /// first request, so `num_ctx` is clamped and unsupported images or
+    /// tools are rejected up front.
+    #[builder(default)]
+    probe_capabilities: bool,
     mirostat: Option<i32>,
     mirostat_eta: Option<f64>,
     mirostat_tau: Option<f64>,
@@ -127,6 +134,7 @@ impl Clone for ChatOllama {
             temperature: self.temperature,
             base_url: self.base_url.clone(),
             validate_model_on_init: self.validate_model_on_init,
+            probe_capabilities: self.probe_capabilities,
             mirostat: self.mirostat,
             mirostat_eta: self.mirostat_eta,
             mirostat_tau: self.mirostat_tau,
@@ -248,6 +256,10 @@ impl ChatOllama {
             }
             builder = builder.default_headers(header_map);
         }
+        // Inside a backend request, give up when the request would.
+        if let Some(timeout) = net_core::deadline::remaining() {
+            builder = builder.timeout(timeout);
+        }
 
         builder.build().unwrap_or_else(|_| reqwest::Client::new())
     }
@@ -276,6 +288,29 @@ impl ChatOllama {
         Ok(())
     }
 
+    /// Capabilities registered for this model, by a probe or the caller.
+    fn capabilities(&self) -> ModelCapabilities {
+        capabilities::lookup("ollama", &self.model)
+    }
+
+    /// Lazily probe the model's capabilities if `probe_capabilities` is set.
+    ///
+    /// A failed probe only costs the checks: the request goes ahead and
+    /// the server reports whatever it doesn't support.
+    async fn ensure_capabilities_probed(&self) {
+        if !self.probe_capabilities || capabilities::registered("ollama", &self.model).is_some() {
+            return;
+        }
+        let models = OllamaModels::new(self.base_url.as_deref());
+        if let Err(e) = capabilities::probe_ollama(&models, &self.model).await {
+            tracing::warn!(
+                model = %self.model,
+                error = %e,
+                "Failed to probe Ollama model capabilities"
+            );
+        }
+    }
+
     /// Returns true if reasoning mode is enabled (truthy value).
     fn is_reasoning_enabled(&self) -> bool {
         match &self.reasoning {
@@ -394,6 +429,7 @@ impl ChatOllama {
             options.insert("mirostat_tau".to_string(), serde_json::json!(tau));
         }
         if let Some(ctx) = self.num_ctx {
+            let ctx = self.capabilities().clamp_context(ctx);
             options.insert("num_ctx".to_string(), serde_json::json!(ctx));
         }
         if let Some(gpu) = self.num_gpu {
@@ -751,6 +787,9 @@ impl BaseChatModel for ChatOllama {
         stop: Option<Vec<String>>,
     ) -> Result<AIMessage> {
         self.ensure_model_validated().await?;
+        self.ensure_capabilities_probed().await;
+        self.capabilities()
+            .check_request(&self.model, &messages, tools.len())?;
 
         let ollama_tools: Vec<serde_json::Value> = tools
             .iter()
@@ -956,6 +995,9 @@ impl ChatOllama {
     ) -> Result<std::pin::Pin<Box<dyn futures::Stream<Item = Result<OllamaStreamChunk>> + Send>>>
     {
         self.ensure_model_validated().await?;
+        self.ensure_capabilities_probed().await;
+        self.capabilities()
+            .check_request(&self.model, &messages, self.bound_tools.len())?;
 
         let client = self.build_client();
         let tools = if !self.bound_tools.is_empty() {
@@ -1613,6 +1655,31 @@ mod tests {
         assert_eq!(url, "http://custom:8080");
     }
 
+    #[test]
+    fn test_build_options_clamps_num_ctx() {
+        crate::capabilities::register(
+            "ollama",
+            "small-ctx-test",
+            ModelCapabilities {
+                context_window: Some(2048),
+                tool_calling: Some(false),
+                ..Default::default()
+            },
+        );
+        let model = ChatOllama::builder()
+            .model("small-ctx-test")
+            .num_ctx(8192u32)
+            .build();
+        let options = model.build_options(None).unwrap();
+        assert_eq!(options["num_ctx"], 2048);
+        assert!(
+            model
+                .capabilities()
+                .check_request("small-ctx-test", &[], 1)
+                .is_err()
+        );
+    }
+
     #[test]
     fn test_build_generation_info_includes_timing() {
         let response = OllamaResponse {
@@ -1,4 +1,6 @@
 mod chat_models;
 pub mod data;
+mod models;
 
 pub use chat_models::*;
+pub use models::{OpenAiModels, RemoteModel, RemoteModelPricing};
@@ -59,6 +59,7 @@ use serde::{Deserialize, Serialize};
 use crate::ToolChoice;
 use crate::callbacks::CallbackManagerForLLMRun;
 use crate::callbacks::Callbacks;
+use crate::capabilities::{self, ModelCapabilities};
 use crate::chat_models::{
     BaseChatModel, ChatChunk, ChatModelConfig, ChatStream, LangSmithParams, UsageMetadata,
 };
@@ -209,6 +210,13 @@ pub struct ChatOpenAI {
     #[builder(default = default_api_base())]
     api_base: String,
     organization: Option<String>,
+    /// Azure OpenAI `api-version`. When set, `api_base` is the resource
+    /// endpoint, `model` names the deployment, and requests authenticate
+    /// with an `api-key` header instead of a bearer token.
+    azure_api_version: Option<String>,
+    /// Headers sent with every request, e.g. a gateway's tenant header.
+    #[builder(default)]
+    default_headers: HashMap<String, String>,
     top_p: Option<f64>,
     frequency_penalty: Option<f64>,
     presence_penalty: Option<f64>,
@@ -275,6 +283,7 @@ impl std::fmt::Debug for ChatOpenAI {
             .field("temperature", &self.temperature)
             .field("max_tokens", &self.max_tokens)
             .field("api_base", &self.api_base)
+            .field("azure_api_version", &self.azure_api_version)
             .field("streaming", &self.streaming)
             .field("n", &self.n)
             .field("api_key_fn", &self.api_key_fn.as_ref().map(|_| "<fn>"))
@@ -949,17 +958,62 @@ impl ChatOpenAI {
         if let Some(ref f) = self.api_key_fn {
             return Ok(f());
         }
+        let env_var = if self.azure_api_version.is_some() {
+            "AZURE_OPENAI_API_KEY"
+        } else {
+            "OPENAI_API_KEY"
+        };
         self.api_key
             .clone()
-            .or_else(|| env::var("OPENAI_API_KEY").ok())
-            .ok_or_else(|| Error::missing_config("OPENAI_API_KEY"))
+            .or_else(|| env::var(env_var).ok())
+            .ok_or_else(|| Error::missing_config(env_var))
+    }
+
+    /// URL of an API endpoint, e.g. `chat/completions`.
+    ///
+    /// On Azure, chat completions are scoped to the deployment while the
+    /// Responses API is scoped to the resource and takes the deployment
+    /// as the payload's `model`.
+    fn endpoint_url(&self, path: &str) -> String {
+        let base = self.api_base.trim_end_matches('/');
+        match &self.azure_api_version {
+            None => format!("{base}/{path}"),
+            Some(version) if path == "responses" => {
+                format!("{base}/openai/responses?api-version={version}")
+            }
+            Some(version) => format!(
+                "{base}/openai/deployments/{}/{path}?api-version={version}",
+                self.model
+            ),
+        }
+    }
+
+    /// A JSON POST to `url` carrying the credentials, organization and
+    /// default headers.
+    fn post_request(&self, client: &reqwest::Client, url: &str) -> Result<reqwest::RequestBuilder> {
+        let api_key = self.get_api_key()?;
+        let mut request = client.post(url).header("Content-Type", "application/json");
+        request = if self.azure_api_version.is_some() {
+            request.header("api-key", api_key)
+        } else {
+            request.header("Authorization", format!("Bearer {api_key}"))
+        };
+        if let Some(ref org) = self.organization {
+            request = request.header("OpenAI-Organization", org);
+        }
+        for (name, value) in &self.default_headers {
+            request = request.header(name, value);
+        }
+        Ok(request)
     }
 
     /// Build the HTTP client with configured timeout and proxy.
     fn build_client(&self) -> Result<reqwest::Client> {
         let mut builder = reqwest::Client::builder();
-        if let Some(timeout) = self.timeout {
-            builder = builder.timeout(std::time::Duration::from_secs(timeout));
+        // Inside a backend request, give up when the request would.
+        let timeout = self.timeout.map(std::time::Duration::from_secs);
+        if let Some(timeout) = net_core::deadline::cap(timeout) {
+            builder = builder.timeout(timeout);
         }
         if let Some(ref proxy_url) = self.proxy {
             let proxy = reqwest::Proxy::all(proxy_url)
@@ -976,12 +1030,24 @@ impl ChatOpenAI {
         self.stream_usage.unwrap_or(false)
     }
 
+    /// What this model supports, from the capability registry.
+    fn capabilities(&self) -> ModelCapabilities {
+        capabilities::lookup("openai", &self.model)
+    }
+
+    /// `max_tokens` capped at the model's output limit.
+    fn effective_max_tokens(&self) -> Option<u32> {
+        self.max_tokens
+            .map(|max| self.capabilities().clamp_max_tokens(max))
+    }
+
     /// Effective temperature after model-specific validation.
-    /// gpt-5 (non-chat) models only support temperature=1.
+    /// gpt-5 (non-chat) models only support temperature=1; everything else
+    /// is clamped to the model's range.
     fn effective_temperature(&self) -> Option<f64> {
         let model_lower = self.model.to_lowercase();
 
-        if model_lower.starts_with("gpt-5")
+        let temperature = if model_lower.starts_with("gpt-5")
             && !model_lower.contains("chat")
             && self.reasoning_effort.as_deref() != Some("none")
             && self
@@ -997,7 +1063,8 @@ impl ChatOpenAI {
             }
         } else {
             self.temperature
-        }
+        };
+        temperature.map(|t| self.capabilities().clamp_temperature(t))
     }
 
     /// Format message content, filtering out block types not supported by OpenAI
@@ -1405,7 +1472,7 @@ impl ChatOpenAI {
             "messages": formatted_messages
         });
 
-        if let Some(max_tokens) = self.max_tokens {
+        if let Some(max_tokens) = self.effective_max_tokens() {
             payload["max_completion_tokens"] = serde_json::json!(max_tokens);
         }
 
@@ -1536,7 +1603,7 @@ impl ChatOpenAI {
             "input": input
         });
 
-        if let Some(max_tokens) = self.max_tokens {
+        if let Some(max_tokens) = self.effective_max_tokens() {
             payload["max_output_tokens"] = serde_json::json!(max_tokens);
         }
 
@@ -1681,18 +1748,10 @@ impl ChatOpenAI {
         stop: Option<Vec<String>>,
         tools: Option<&[serde_json::Value]>,
     ) -> Result<ChatStream> {
-        let api_key = self.get_api_key()?;
         let client = self.build_client()?;
         let payload = self.build_responses_api_payload(&messages, stop, tools, true);
 
-        let mut request = client
-            .post(format!("{}/responses", self.api_base))
-            .header("Authorization", format!("Bearer {api_key}"))
-            .header("Content-Type", "application/json");
-
-        if let Some(ref org) = self.organization {
-            request = request.header("OpenAI-Organization", org);
-        }
+        let request = self.post_request(&client, &self.endpoint_url("responses"))?;
 
         let response = request.json(&payload).send().await.map_err(Error::Http)?;
 
@@ -1714,6 +1773,9 @@ impl ChatOpenAI {
                 std::collections::HashMap::new();
             let mut stream_response_metadata: std::collections::HashMap<String, serde_json::Value> =
                 std::collections::HashMap::new();
+            // `response.completed` carries the final chunk; a `[DONE]` some
+            // proxies append after it must not yield a second one.
+            let mut finished = false;
 
             use futures::StreamExt;
 
@@ -1732,6 +1794,10 @@ impl ChatOpenAI {
 
                             if let Some(data) = line.strip_prefix("data: ") {
                                 if data == "[DONE]" {
+                                    if finished {
+                                        continue;
+                                    }
+                                    finished = true;
                                     let mut final_chunk = ChatChunk::final_chunk(usage.take(), finish_reason.take());
                                     if !tool_call_acc.is_empty() {
                                         let tcs: Vec<ToolCall> = tool_call_acc
@@ -1804,6 +1870,7 @@ impl ChatOpenAI {
                                             }
                                         }
                                         "response.completed" | "response.incomplete" => {
+                                            finished = true;
                                             if let Some(resp) = event.response {
                                                 if let Some(ref resp_usage) = resp.usage {
                                                     usage = Some(Self::create_usage_metadata_responses(
@@ -2136,17 +2203,8 @@ impl ChatOpenAI {
         url: &str,
         payload: &serde_json::Value,
     ) -> Result<T> {
-        let api_key = self.get_api_key()?;
         let client = self.build_client()?;
-
-        let mut request = client
-            .post(url)
-            .header("Authorization", format!("Bearer {api_key}"))
-            .header("Content-Type", "application/json");
-
-        if let Some(ref org) = self.organization {
-            request = request.header("OpenAI-Organization", org);
-        }
+        let request = self.post_request(&client, url)?;
 
         let resp = request.json(payload).send().await.map_err(Error::Http)?;
 
@@ -2173,17 +2231,8 @@ impl ChatOpenAI {
         url: &str,
         payload: &serde_json::Value,
     ) -> Result<(T, HashMap<String, String>)> {
-        let api_key = self.get_api_key()?;
         let client = self.build_client()?;
-
-        let mut request = client
-            .post(url)
-            .header("Authorization", format!("Bearer {api_key}"))
-            .header("Content-Type", "application/json");
-
-        if let Some(ref org) = self.organization {
-            request = request.header("OpenAI-Organization", org);
-        }
+        let request = self.post_request(&client, url)?;
 
         let resp = request.json(payload).send().await.map_err(Error::Http)?;
 
@@ -2223,7 +2272,7 @@ impl ChatOpenAI {
         messages: Vec<AnyMessage>,
         stop: Option<Vec<String>>,
     ) -> Result<ChatResult> {
-        let url = format!("{}/responses", self.api_base);
+        let url = self.endpoint_url("responses");
         let payload = self.build_responses_api_payload(&messages, stop, None, false);
 
         if self.include_response_headers {
@@ -2427,6 +2476,8 @@ impl ChatOpenAI {
         tools: Option<&[ToolDefinition]>,
         tool_choice: Option<&ToolChoice>,
     ) -> Result<ChatStream> {
+        self.capabilities()
+            .check_request(&self.model, &messages, tools.map_or(0, <[_]>::len))?;
         if self.should_use_responses_api(None) {
             let mut openai_tools: Vec<serde_json::Value> = tools
                 .unwrap_or_default()
@@ -2453,7 +2504,6 @@ impl ChatOpenAI {
             return self.stream_responses_api(messages, stop, tools_slice).await;
         }
 
-        let api_key = self.get_api_key()?;
         let client = self.build_client()?;
 
         let openai_tools: Option<Vec<serde_json::Value>> =
@@ -2493,14 +2543,7 @@ impl ChatOpenAI {
             payload["tool_choice"] = choice_json;
         }
 
-        let mut request = client
-            .post(format!("{}/chat/completions", self.api_base))
-            .header("Authorization", format!("Bearer {api_key}"))
-            .header("Content-Type", "application/json");
-
-        if let Some(ref org) = self.organization {
-            request = request.header("OpenAI-Organization", org);
-        }
+        let request = self.post_request(&client, &self.endpoint_url("chat/completions"))?;
 
         let response = request.json(&payload).send().await.map_err(Error::Http)?;
 
@@ -2516,10 +2559,7 @@ impl ChatOpenAI {
         let stream = async_stream::stream! {
             let mut bytes_stream = response.bytes_stream();
             let mut buffer = String::new();
-            let mut usage: Option<UsageMetadata> = None;
-            let mut finish_reason: Option<String> = None;
-            let mut tool_call_acc: std::collections::HashMap<u32, (String, String, String)> =
-                std::collections::HashMap::new();
+            let mut state = ChatCompletionsStreamState::default();
 
             use futures::StreamExt;
 
@@ -2534,92 +2574,8 @@ impl ChatOpenAI {
 
                             for line in event_data.lines() {
                                 if let Some(data) = line.strip_prefix("data: ") {
-                                    if data == "[DONE]" {
-                                        let finish = finish_reason.take();
-                                        // GLM-family providers occasionally
-                                        // report `finish_reason: "tool_calls"`
-                                        // without emitting any tool_call
-                                        // deltas. Surface it here so the
-                                        // condition is visible in logs;
-                                        // the orchestrator (be-thread-service
-                                        // `drive_turn`) handles recovery.
-                                        if finish.as_deref() == Some("tool_calls")
-                                            && tool_call_acc.is_empty()
-                                        {
-                                            tracing::warn!(
-                                                "Provider stream ended with finish_reason=tool_calls \
-                                                 but no tool-call deltas were emitted; final chunk \
-                                                 will carry an empty tool_calls list",
-                                            );
-                                        }
-                                        let mut final_chunk = ChatChunk::final_chunk(usage.take(), finish);
-                                        if !tool_call_acc.is_empty() {
-                                            let mut sorted: Vec<_> = tool_call_acc.drain().collect();
-                                            sorted.sort_by_key(|(idx, _)| *idx);
-                                            let tcs: Vec<ToolCall> = sorted
-                                                .into_iter()
-                                                .map(|(_, (id, name, args))| {
-                                                    let parsed_args = serde_json::from_str(&args)
-                                                        .unwrap_or(serde_json::Value::Object(Default::default()));
-                                                    ToolCall::builder()
-                                                        .name(name)
-                                                        .args(parsed_args)
-                                                        .id(id)
-                                                        .build()
-                                                })
-                                                .collect();
-                                            final_chunk.tool_calls = tcs;
-                                        }
-                                        yield Ok(final_chunk);
-                                        continue;
-                                    }
-
-                                    match serde_json::from_str::<OpenAIStreamChunk>(data) {
-                                        Ok(chunk) => {
-                                            if let Some(choice) = chunk.choices.first() {
-                                                if let Some(ref reasoning) = choice.delta.reasoning_content {
-                                                    let mut kwargs = std::collections::HashMap::new();
-                                                    kwargs.insert(
-                                                        "reasoning_content".to_string(),
-                                                        serde_json::Value::String(reasoning.clone()),
-                                                    );
-                                                    yield Ok(ChatChunk::builder()
-                                                        .content("")
-                                                        .additional_kwargs(kwargs)
-                                                        .build());
-                                                }
-                                                if let Some(ref content) = choice.delta.content {
-                                                    yield Ok(ChatChunk::builder().content(content.clone()).build());
-                                                }
-                                                if let Some(ref tcs) = choice.delta.tool_calls {
-                                                    for tc in tcs {
-                                                        let entry = tool_call_acc
-                                                            .entry(tc.index)
-                                                            .or_insert_with(|| (String::new(), String::new(), String::new()));
-                                                        if let Some(ref id) = tc.id {
-                                                            entry.0 = id.clone();
-                                                        }
-                                                        if let Some(ref func) = tc.function {
-                                                            if let Some(ref name) = func.name {
-                                                                entry.1 = name.clone();
-                                                            }
-                                                            if let Some(ref args) = func.arguments {
-                                                                entry.2.push_str(args);
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                                if let Some(ref reason) = choice.finish_reason {
-                                                    finish_reason = Some(reason.clone());
-                                                }
-                                            }
-                                            if let Some(ref u) = chunk.usage {
-                                                usage = Some(Self::create_usage_metadata(u, chunk.service_tier.as_deref()));
-                                            }
-                                        }
-                                        Err(e) => {
-                                            tracing::warn!("Failed to parse SSE chunk: {e}");
-                                        }
+                                    for chunk in state.on_data(data) {
+                                        yield Ok(chunk);
                                     }
                                 }
                             }
@@ -2627,10 +2583,24 @@ impl ChatOpenAI {
                     }
                     Err(e) => {
                         yield Err(Error::Http(e));
-                        break;
+                        return;
                     }
                 }
             }
+
+            // Some OpenAI-compatible servers close the stream without a
+            // trailing blank line or without `[DONE]`. The finish reason,
+            // usage and tool calls still reach the caller on a final chunk.
+            for line in buffer.lines() {
+                if let Some(data) = line.strip_prefix("data: ") {
+                    for chunk in state.on_data(data) {
+                        yield Ok(chunk);
+                    }
+                }
+            }
+            if let Some(final_chunk) = state.finish() {
+                yield Ok(final_chunk);
+            }
         };
 
         Ok(Box::pin(stream) as Pin<Box<dyn Stream<Item = Result<ChatChunk>> + Send>>)
@@ -3045,7 +3015,20 @@ impl BaseChatModel for ChatOpenAI {
                             .response_metadata(response_metadata)
                             .additional_kwargs(chat_chunk.additional_kwargs.clone())
                             .build();
-                        yield Ok(ChatGenerationChunk::builder().message(message.into()).build());
+                        // The final chunk carries the round's usage and
+                        // finish reason; `BaseChatModel::stream` marks it
+                        // `ChunkPosition::Last` instead of appending an
+                        // empty chunk of its own.
+                        let generation_info = chat_chunk.is_final.then(|| {
+                            HashMap::from([(
+                                "chunk_position".to_string(),
+                                serde_json::json!("last"),
+                            )])
+                        });
+                        yield Ok(ChatGenerationChunk::builder()
+                            .message(message.into())
+                            .maybe_generation_info(generation_info)
+                            .build());
                     }
                     Err(e) => {
                         yield Err(e);
@@ -3309,11 +3292,13 @@ impl ChatOpenAI {
         stop: Option<Vec<String>>,
         _run_manager: Option<&CallbackManagerForLLMRun>,
     ) -> Result<ChatResult> {
+        self.capabilities()
+            .check_request(&self.model, &messages, 0)?;
         if self.should_use_responses_api(None) {
             return self.generate_responses_api(messages, stop).await;
         }
 
-        let url = format!("{}/chat/completions", self.api_base);
+        let url = self.endpoint_url("chat/completions");
         let payload = self.build_request_payload(&messages, stop, None, false);
 
         if self.include_response_headers {
@@ -3342,6 +3327,8 @@ impl ChatOpenAI {
         tool_choice: Option<&ToolChoice>,
         stop: Option<Vec<String>>,
     ) -> Result<AIMessage> {
+        self.capabilities()
+            .check_request(&self.model, &messages, tools.len())?;
         let openai_tools: Vec<serde_json::Value> = tools
             .iter()
             .map(|t| {
@@ -3372,7 +3359,7 @@ impl ChatOpenAI {
             .collect();
 
         if self.should_use_responses_api(None) {
-            let url = format!("{}/responses", self.api_base);
+            let url = self.endpoint_url("responses");
             let mut all_tools_json = openai_tools.clone();
             for bt in &self.bound_builtin_tools {
                 all_tools_json.push(bt.clone());
@@ -3406,7 +3393,7 @@ impl ChatOpenAI {
             return Self::extract_ai_message(result);
         }
 
-        let url = format!("{}/chat/completions", self.api_base);
+        let url = self.endpoint_url("chat/completions");
         let mut payload = self.build_request_payload(&messages, stop, Some(&openai_tools), false);
 
         if let Some(choice) = tool_choice {
@@ -3489,6 +3476,119 @@ struct TokenDetails {
     reasoning_tokens: Option<u32>,
 }
 
+/// What a Chat Completions stream has reported so far that belongs on its
+/// final chunk. Tool calls arrive as per-index deltas, `finish_reason` on
+/// the last choice delta and usage on a trailing chunk with no choices, so
+/// all three are held until the stream ends.
+#[derive(Default)]
+struct ChatCompletionsStreamState {
+    usage: Option<UsageMetadata>,
+    finish_reason: Option<String>,
+    /// `(id, name, arguments)` by tool-call index.
+    tool_calls: HashMap<u32, (String, String, String)>,
+    finished: bool,
+}
+
+impl ChatCompletionsStreamState {
+    /// The chunks to yield for one SSE `data:` payload.
+    fn on_data(&mut self, data: &str) -> Vec<ChatChunk> {
+        if data == "[DONE]" {
+            return self.finish().into_iter().collect();
+        }
+        let chunk = match serde_json::from_str::<OpenAIStreamChunk>(data) {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                tracing::warn!("Failed to parse SSE chunk: {e}");
+                return Vec::new();
+            }
+        };
+
+        let mut chunks = Vec::new();
+        if let Some(choice) = chunk.choices.first() {
+            if let Some(ref reasoning) = choice.delta.reasoning_content {
+                let mut kwargs = HashMap::new();
+                kwargs.insert(
+                    "reasoning_content".to_string(),
+                    serde_json::Value::String(reasoning.clone()),
+                );
+                chunks.push(
+                    ChatChunk::builder()
+                        .content("")
+                        .additional_kwargs(kwargs)
+                        .build(),
+                );
+            }
+            if let Some(ref content) = choice.delta.content {
+                chunks.push(ChatChunk::builder().content(content.clone()).build());
+            }
+            if let Some(ref tcs) = choice.delta.tool_calls {
+                for tc in tcs {
+                    let entry = self
+                        .tool_calls
+                        .entry(tc.index)
+                        .or_insert_with(|| (String::new(), String::new(), String::new()));
+                    if let Some(ref id) = tc.id {
+                        entry.0 = id.clone();
+                    }
+                    if let Some(ref func) = tc.function {
+                        if let Some(ref name) = func.name {
+                            entry.1 = name.clone();
+                        }
+                        if let Some(ref args) = func.arguments {
+                            entry.2.push_str(args);
+                        }
+                    }
+                }
+            }
+            if let Some(ref reason) = choice.finish_reason {
+                self.finish_reason = Some(reason.clone());
+            }
+        }
+        if let Some(ref u) = chunk.usage {
+            self.usage = Some(ChatOpenAI::create_usage_metadata(
+                u,
+                chunk.service_tier.as_deref(),
+            ));
+        }
+        chunks
+    }
+
+    /// The final chunk, carrying the usage, the finish reason and the
+    /// assembled tool calls. `None` once it has been taken.
+    fn finish(&mut self) -> Option<ChatChunk> {
+        if std::mem::replace(&mut self.finished, true) {
+            return None;
+        }
+        let finish = self.finish_reason.take();
+        // GLM-family providers occasionally report `finish_reason:
+        // "tool_calls"` without emitting any tool_call deltas. Surface it
+        // here so the condition is visible in logs; the orchestrator
+        // (be-thread-service `drive_turn`) handles recovery.
+        if finish.as_deref() == Some("tool_calls") && self.tool_calls.is_empty() {
+            tracing::warn!(
+                "Provider stream ended with finish_reason=tool_calls but no tool-call deltas \
+                 were emitted; final chunk will carry an empty tool_calls list",
+            );
+        }
+        let mut final_chunk = ChatChunk::final_chunk(self.usage.take(), finish);
+        let mut sorted: Vec<_> = self.tool_calls.drain().collect();
+        sorted.sort_by_key(|(idx, _)| *idx);
+        final_chunk.tool_calls = sorted
+            .into_iter()
+            .map(|(_, (id, name, args))| {
+                let parsed_args = serde_json::from_str(&args)
+                    .unwrap_or(serde_json::Value::Object(Default::default()));
+                ToolCall::builder()
+                    .name(name)
+                    .args(parsed_args)
+                    .id(id)
+                    .build()
+            })
+            .collect();
+        Some(final_chunk)
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct OpenAIStreamChunk {
     choices: Vec<OpenAIStreamChoice>,
@@ -3659,6 +3759,32 @@ mod tests {
         assert_eq!(model.seed, Some(42));
     }
 
+    #[test]
+    fn test_endpoint_url() {
+        let model = ChatOpenAI::builder()
+            .model("gpt-4o")
+            .api_base("https://gateway.example.com/v1/")
+            .build();
+        assert_eq!(
+            model.endpoint_url("chat/completions"),
+            "https://gateway.example.com/v1/chat/completions"
+        );
+
+        let model = ChatOpenAI::builder()
+            .model("prod-gpt-4o")
+            .api_base("https://acme.openai.azure.com")
+            .azure_api_version("2024-10-21")
+            .build();
+        assert_eq!(
+            model.endpoint_url("chat/completions"),
+            "https://acme.openai.azure.com/openai/deployments/prod-gpt-4o/chat/completions?api-version=2024-10-21"
+        );
+        assert_eq!(
+            model.endpoint_url("responses"),
+            "https://acme.openai.azure.com/openai/responses?api-version=2024-10-21"
+        );
+    }
+
     #[test]
     fn test_llm_type() {
         let model = ChatOpenAI::new("gpt-4o");
@@ -3722,6 +3848,21 @@ mod tests {
         assert_eq!(payload["max_completion_tokens"], 100);
     }
 
+    #[test]
+    fn test_build_request_payload_clamps_to_model_limits() {
+        let model = ChatOpenAI::builder()
+            .model("gpt-4o")
+            .temperature(3.0)
+            .max_tokens(1_000_000u32)
+            .build();
+        let payload = model.build_request_payload(&[], None, None, false);
+        assert_eq!(payload["temperature"], 2.0);
+        assert_eq!(
+            payload["max_completion_tokens"],
+            model.capabilities().max_output_tokens.unwrap()
+        );
+    }
+
     #[test]
     fn test_build_request_payload_developer_role_for_o_series() {
         use crate::messages::SystemMessage;
@@ -3744,6 +3885,52 @@ mod tests {
         assert_eq!(payload["stream_options"]["include_usage"], true);
     }
 
+    #[test]
+    fn test_stream_tool_call_deltas_arrive_on_final_chunk() {
+        let mut state = ChatCompletionsStreamState::default();
+        let events = [
+            r#"{"choices":[{"delta":{"content":"Checking."},"finish_reason":null}]}"#,
+            r#"{"choices":[{"delta":{"tool_calls":[{"index":0,"id":"call_1","function":{"name":"search","arguments":"{\"q\":"}}]},"finish_reason":null}]}"#,
+            r#"{"choices":[{"delta":{"tool_calls":[{"index":0,"function":{"arguments":"\"rust\"}"}}]},"finish_reason":null}]}"#,
+            r#"{"choices":[{"delta":{},"finish_reason":"tool_calls"}]}"#,
+            r#"{"choices":[],"usage":{"prompt_tokens":12,"completion_tokens":7,"total_tokens":19}}"#,
+        ];
+        let mut chunks: Vec<ChatChunk> = events.iter().flat_map(|e| state.on_data(e)).collect();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].content, "Checking.");
+        assert!(!chunks[0].is_final);
+
+        chunks = state.on_data("[DONE]");
+        assert_eq!(chunks.len(), 1);
+        let final_chunk = &chunks[0];
+        assert!(final_chunk.is_final);
+        assert_eq!(final_chunk.finish_reason.as_deref(), Some("tool_calls"));
+        assert_eq!(final_chunk.tool_calls.len(), 1);
+        assert_eq!(final_chunk.tool_calls[0].name, "search");
+        assert_eq!(
+            final_chunk.tool_calls[0].args,
+            serde_json::json!({"q": "rust"})
+        );
+        let usage = final_chunk.usage_metadata.as_ref().unwrap();
+        assert_eq!((usage.input_tokens, usage.output_tokens), (12, 7));
+        assert!(state.finish().is_none());
+    }
+
+    #[test]
+    fn test_stream_length_stop_without_done_still_finishes() {
+        let mut state = ChatCompletionsStreamState::default();
+        state
+            .on_data(r#"{"choices":[{"delta":{"content":"The answer is"},"finish_reason":null}]}"#);
+        state.on_data(r#"{"choices":[{"delta":{},"finish_reason":"length"}],"usage":{"prompt_tokens":5,"completion_tokens":3,"total_tokens":8}}"#);
+
+        let final_chunk = state.finish().unwrap();
+        assert!(final_chunk.is_final);
+        assert_eq!(final_chunk.finish_reason.as_deref(), Some("length"));
+        assert!(final_chunk.tool_calls.is_empty());
+        assert_eq!(final_chunk.usage_metadata.unwrap().output_tokens, 3);
+        assert!(state.on_data("[DONE]").is_empty());
+    }
+
     #[test]
     fn test_filter_disabled_params_remove() {
         let mut disabled = HashMap::new();
@@ -0,0 +1,211 @@
+//! List the models an OpenAI-compatible server offers.
+//!
+//! Wraps `GET /models`, which OpenAI, OpenRouter, vLLM, LM Studio and
+//! Ollama's OpenAI shim all serve. Gateways that resell other providers'
+//! models report more than the OpenAI shape: OpenRouter adds a display
+//! name, the context length and per-token prices, which are kept when
+//! present.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use std::collections::HashMap;
+//! use agent_chain::openai::OpenAiModels;
+//!
+//! let models = OpenAiModels::new("https://openrouter.ai/api/v1", Some("sk-or-..."), &HashMap::new());
+//! for model in models.list().await? {
+//!     println!("{} {:?}", model.id, model.pricing.and_then(|p| p.prompt_per_million()));
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+/// How long a listing may take. Gateways list hundreds of models, but a
+/// server that hasn't answered by then is not going to.
+const LIST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// A model listed by `GET /models`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemoteModel {
+    /// Name to pass as the chat request's `model`, e.g.
+    /// `anthropic/claude-sonnet-4.5` on OpenRouter.
+    pub id: String,
+    /// Display name, where the server has one.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Context window in tokens, where the server reports it.
+    #[serde(default)]
+    pub context_length: Option<u64>,
+    #[serde(default)]
+    pub pricing: Option<RemoteModelPricing>,
+}
+
+/// Prices as OpenRouter reports them: decimal strings in US dollars per
+/// token.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RemoteModelPricing {
+    pub prompt: Option<String>,
+    pub completion: Option<String>,
+}
+
+impl RemoteModelPricing {
+    /// US dollars per million input tokens.
+    pub fn prompt_per_million(&self) -> Option<f64> {
+        per_million(self.prompt.as_deref())
+    }
+
+    /// US dollars per million output tokens.
+    pub fn completion_per_million(&self) -> Option<f64> {
+        per_million(self.completion.as_deref())
+    }
+}
+
+/// A per-token price scaled to a million tokens. Routers whose price
+/// depends on the model they pick report `-1`, which counts as unknown.
+fn per_million(price: Option<&str>) -> Option<f64> {
+    price?
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|price| price.is_finite() && *price >= 0.0)
+        .map(|price| price * 1_000_000.0)
+}
+
+/// Client for an OpenAI-compatible server's model list.
+#[derive(Debug, Clone)]
+pub struct OpenAiModels {
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl OpenAiModels {
+    /// Client for the server at `base_url` (the API base, e.g.
+    /// `http://localhost:8000/v1`), sending `api_key` as a bearer token
+    /// and `headers` with every request, as [`super::ChatOpenAI`] does.
+    /// Headers that aren't valid HTTP are skipped.
+    pub fn new(base_url: &str, api_key: Option<&str>, headers: &HashMap<String, String>) -> Self {
+        let mut header_map = reqwest::header::HeaderMap::new();
+        if let Some(api_key) = api_key
+            && let Ok(value) = reqwest::header::HeaderValue::from_str(&format!("Bearer {api_key}"))
+        {
+            header_map.insert(reqwest::header::AUTHORIZATION, value);
+        }
+        for (key, value) in headers {
+            if let (Ok(name), Ok(val)) = (
+                reqwest::header::HeaderName::from_bytes(key.as_bytes()),
+                reqwest::header::HeaderValue::from_str(value),
+            ) {
+                header_map.insert(name, val);
+            }
+        }
+        let client = reqwest::Client::builder()
+            .default_headers(header_map)
+            .timeout(LIST_TIMEOUT)
+            .build()
+            .unwrap_or_else(|_| reqwest::Client::new());
+
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client,
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Every model the server lists.
+    pub async fn list(&self) -> Result<Vec<RemoteModel>> {
+        #[derive(Deserialize)]
+        struct ModelsResponse {
+            #[serde(default)]
+            data: Vec<RemoteModel>,
+        }
+
+        let response = self
+            .client
+            .get(format!("{}/models", self.base_url))
+            .send()
+            .await
+            .map_err(Error::Http)?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response
+                .text()
+                .await
+                .unwrap_or_else(|e| format!("<failed to read response body: {e}>"));
+            return Err(Error::api(
+                status.as_u16(),
+                error_message(&body).unwrap_or(body),
+            ));
+        }
+        let models: ModelsResponse = response.json().await.map_err(Error::Http)?;
+        Ok(models.data)
+    }
+}
+
+/// The message of an OpenAI-style `{"error": {"message": "..."}}` body.
+fn error_message(body: &str) -> Option<String> {
+    serde_json::from_str::<serde_json::Value>(body)
+        .ok()?
+        .get("error")?
+        .get("message")?
+        .as_str()
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_openrouter_listing_deserializes() {
+        let model: RemoteModel = serde_json::from_value(serde_json::json!({
+            "id": "anthropic/claude-sonnet-4.5",
+            "name": "Anthropic: Claude Sonnet 4.5",
+            "context_length": 1000000,
+            "pricing": { "prompt": "0.000003", "completion": "0.000015", "request": "0" },
+            "architecture": { "modality": "text+image->text" }
+        }))
+        .unwrap();
+        let pricing = model.pricing.unwrap();
+        assert!((pricing.prompt_per_million().unwrap() - 3.0).abs() < 1e-9);
+        assert!((pricing.completion_per_million().unwrap() - 15.0).abs() < 1e-9);
+        assert_eq!(model.context_length, Some(1_000_000));
+    }
+
+    #[test]
+    fn test_openai_listing_deserializes() {
+        let model: RemoteModel = serde_json::from_value(serde_json::json!({
+            "id": "meta-llama/Llama-3.1-8B-Instruct",
+            "object": "model",
+            "created": 1700000000,
+            "owned_by": "vllm"
+        }))
+        .unwrap();
+        assert_eq!(model.name, None);
+        assert_eq!(model.pricing, None);
+    }
+
+    #[test]
+    fn test_variable_prices_are_unknown() {
+        let pricing = RemoteModelPricing {
+            prompt: Some("-1".to_string()),
+            completion: Some("n/a".to_string()),
+        };
+        assert_eq!(pricing.prompt_per_million(), None);
+        assert_eq!(pricing.completion_per_million(), None);
+    }
+
+    #[test]
+    fn test_new_trims_trailing_slash() {
+        let models = OpenAiModels::new("http://localhost:1234/v1/", None, &HashMap::new());
+        assert_eq!(models.base_url(), "http://localhost:1234/v1");
+    }
+}
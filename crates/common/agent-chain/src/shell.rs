@@ -0,0 +1,756 @@
+//! Built-in `run_command` tool.
+//!
+//! Lets an agent run a command on the user's machine under a
+//! [`ShellPolicy`]:
+//!
+//! - The command line is split into argv and spawned directly, never
+//!   through a shell. Pipes, redirects, `;`, `&&`, backticks and `$(...)`
+//!   are rejected, so one command line can't chain several programs.
+//! - The program must be on [`ShellPolicy::allowed`], named bare so it is
+//!   looked up on `PATH`. A path (`./git`, `/tmp/cargo`) is refused, since
+//!   it could name a file the repository or the agent put there. There is
+//!   no "anything but" mode: an allowed program can still start others
+//!   (`git -c alias.x='!…'`, `make`), so the list should only name
+//!   programs the user trusts with the workspace.
+//! - [`ShellPolicy::denied`] backstops the allowlist. By default it holds
+//!   [`DEFAULT_DENIED`]: shells, interpreters and wrappers whose job is to
+//!   run another program (`env`, `xargs`, `find`, `timeout`, …), which
+//!   would turn one allowed name into every program on the machine.
+//! - The working directory must resolve inside [`ShellPolicy::root`].
+//!   This confines where the command *starts*, not which paths its
+//!   arguments name; pair the tool with an OS-level sandbox if that
+//!   matters.
+//! - The child gets a scrubbed environment (only `PATH`, minus relative
+//!   entries, `HOME` and `LANG`), is killed after
+//!   [`ShellPolicy::timeout`], and each output stream is capped at
+//!   [`ShellPolicy::max_output_bytes`].
+//!
+//! Every attempt — including rejected ones — is written to the
+//! [`AuditLog`] together with the model's stated reason and the run ids
+//! that tie it back to the model request that triggered it.
+//!
+//! The tool is [`RiskLevel::Dangerous`]: [`RunCommandTool::permissioned`]
+//! wraps it so the user is asked before every call.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use agent_chain::shell::{RunCommandTool, ShellPolicy};
+//!
+//! let policy = ShellPolicy::builder()
+//!     .root("/home/me/project")
+//!     .allowed(vec!["git".into(), "cargo".into(), "ls".into()])
+//!     .build();
+//! let tool = RunCommandTool::new(policy).permissioned(permissions);
+//! ```
+
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::fmt::{self, Debug};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+
+use agent_chain_core::callbacks::manager::CallbackManagerForToolRun;
+use agent_chain_core::runnables::RunnableConfig;
+use agent_chain_core::tools::{
+    ArgsSchema, BaseTool, DynTool, PermissionedTool, RiskLevel, ToolInput, ToolOutput,
+    ToolPermissions,
+};
+use async_trait::async_trait;
+use bon::Builder;
+use serde::Serialize;
+use serde_json::{Value, json};
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::Command;
+use uuid::Uuid;
+
+use crate::error::{Error, Result};
+
+/// Name the model sees.
+pub const TOOL_NAME: &str = "run_command";
+
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Per stream, so stdout and stderr together stay under 128 KiB.
+pub const DEFAULT_MAX_OUTPUT_BYTES: usize = 64 * 1024;
+
+/// Programs refused even when allowed, unless a caller overrides
+/// [`ShellPolicy::denied`]. An entry also covers its versioned and
+/// suffixed variants (`python` covers `python3.12`, `mkfs` covers
+/// `mkfs.ext4`).
+pub const DEFAULT_DENIED: &[&str] = &[
+    // Shells.
+    "sh",
+    "bash",
+    "zsh",
+    "fish",
+    "dash",
+    "ksh",
+    "csh",
+    "tcsh",
+    "pwsh",
+    "powershell",
+    "cmd",
+    "busybox",
+    // Interpreters that take a program on the command line.
+    "python",
+    "perl",
+    "ruby",
+    "node",
+    "deno",
+    "bun",
+    "php",
+    "lua",
+    "tclsh",
+    "osascript",
+    "awk",
+    "gawk",
+    "mawk",
+    // Wrappers that run the program named in their arguments.
+    "env",
+    "xargs",
+    "find",
+    "nohup",
+    "timeout",
+    "nice",
+    "ionice",
+    "setsid",
+    "stdbuf",
+    "chroot",
+    "unshare",
+    "script",
+    "watch",
+    "parallel",
+    // Privilege escalation.
+    "sudo",
+    "su",
+    "doas",
+    "pkexec",
+    "runas",
+    // Irreversible.
+    "dd",
+    "mkfs",
+    "shutdown",
+    "reboot",
+    "halt",
+];
+
+/// Environment variables passed through to the child. Everything else is
+/// dropped so API keys in the app's environment can't leak into output.
+const PASSTHROUGH_ENV: &[&str] = &["PATH", "HOME", "LANG"];
+
+static ARGS_SCHEMA: LazyLock<ArgsSchema> = LazyLock::new(|| {
+    ArgsSchema::JsonSchema(json!({
+        "type": "object",
+        "properties": {
+            "command": {
+                "type": "string",
+                "description": "Program and arguments, e.g. `git status --short`. Not run through a shell: pipes, redirects and command chaining are rejected."
+            },
+            "cwd": {
+                "type": "string",
+                "description": "Working directory relative to the workspace root. Defaults to the root."
+            },
+            "reason": {
+                "type": "string",
+                "description": "One sentence on why this command is needed. Shown to the user and recorded in the audit log."
+            }
+        },
+        "required": ["command", "reason"]
+    }))
+});
+
+/// Limits applied to every `run_command` call.
+#[derive(Debug, Clone, Builder)]
+pub struct ShellPolicy {
+    /// Directory commands run in; `cwd` arguments must resolve inside it.
+    #[builder(into)]
+    pub root: PathBuf,
+    /// Programs that may run, by file name. An empty list allows none.
+    pub allowed: Vec<String>,
+    /// Programs that never run, by file name. Checked before `allowed`.
+    #[builder(default = DEFAULT_DENIED.iter().map(|p| (*p).to_string()).collect())]
+    pub denied: Vec<String>,
+    #[builder(default = DEFAULT_TIMEOUT)]
+    pub timeout: Duration,
+    #[builder(default = DEFAULT_MAX_OUTPUT_BYTES)]
+    pub max_output_bytes: usize,
+}
+
+impl ShellPolicy {
+    /// Whether `program` may run. Only bare names are: anything with a
+    /// path separator is refused, whatever its file name.
+    pub fn allows(&self, program: &str) -> bool {
+        if program.contains(['/', '\\']) {
+            return false;
+        }
+        // Windows resolves `git.exe` for `git`; compare without it.
+        let name = program.strip_suffix(".exe").unwrap_or(program);
+        if self.denied.iter().any(|d| is_variant_of(name, d)) {
+            return false;
+        }
+        self.allowed.iter().any(|a| a == name)
+    }
+
+    /// Resolve `cwd` against `root`, rejecting anything that escapes it
+    /// (`..`, absolute paths, symlinks pointing outside).
+    pub fn resolve_cwd(&self, cwd: Option<&str>) -> Result<PathBuf> {
+        let root = self
+            .root
+            .canonicalize()
+            .map_err(|e| Error::ToolException(format!("workspace root unavailable: {e}")))?;
+        let Some(cwd) = cwd.filter(|c| !c.is_empty()) else {
+            return Ok(root);
+        };
+        let resolved = root
+            .join(cwd)
+            .canonicalize()
+            .map_err(|e| Error::ToolException(format!("working directory `{cwd}`: {e}")))?;
+        if !resolved.starts_with(&root) {
+            return Err(Error::ToolException(format!(
+                "working directory `{cwd}` is outside the workspace"
+            )));
+        }
+        Ok(resolved)
+    }
+}
+
+/// Whether `name` is `program` or a versioned or suffixed variant of it:
+/// `python3.12` and `mkfs.ext4` are, `envsubst` is not a variant of `env`.
+fn is_variant_of(name: &str, program: &str) -> bool {
+    name.strip_prefix(program).is_some_and(|rest| {
+        rest.chars()
+            .next()
+            .is_none_or(|c| c == '.' || c == '-' || c.is_ascii_digit())
+    })
+}
+
+/// `path` without its empty and relative entries, which would let a bare
+/// program name resolve to a file in the working directory.
+fn absolute_path_entries(path: &OsStr) -> OsString {
+    std::env::join_paths(std::env::split_paths(path).filter(|dir| dir.is_absolute()))
+        .unwrap_or_default()
+}
+
+/// What happened to one `run_command` call.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum AuditOutcome {
+    /// Rejected by the policy before anything was spawned.
+    Rejected {
+        reason: String,
+    },
+    /// Could not be spawned (e.g. program not found).
+    SpawnFailed {
+        error: String,
+    },
+    Exited {
+        exit_code: Option<i32>,
+    },
+    TimedOut,
+}
+
+/// One entry in the audit trail.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditRecord {
+    /// The tool run. Callback handlers see the same id in
+    /// `on_tool_start`.
+    pub run_id: Uuid,
+    /// The chain or agent run that issued the tool call, i.e. the model
+    /// request behind it.
+    pub parent_run_id: Option<Uuid>,
+    pub command: String,
+    pub cwd: Option<PathBuf>,
+    /// Why the model says it needs the command.
+    pub reason: String,
+    pub outcome: AuditOutcome,
+}
+
+/// Receives an [`AuditRecord`] for every `run_command` call.
+pub trait AuditLog: Send + Sync {
+    fn record(&self, record: &AuditRecord);
+}
+
+/// Default [`AuditLog`]: one `tracing` event per call on the
+/// `agent_chain::shell::audit` target, so it can be routed separately
+/// from ordinary logs.
+#[derive(Debug, Default)]
+pub struct TracingAuditLog;
+
+impl AuditLog for TracingAuditLog {
+    fn record(&self, record: &AuditRecord) {
+        let outcome = serde_json::to_string(&record.outcome).unwrap_or_default();
+        tracing::info!(
+            target: "agent_chain::shell::audit",
+            run_id = %record.run_id,
+            parent_run_id = ?record.parent_run_id,
+            command = %record.command,
+            cwd = ?record.cwd,
+            reason = %record.reason,
+            outcome = %outcome,
+            "run_command"
+        );
+    }
+}
+
+/// The `run_command` tool. See the module docs for what it enforces.
+pub struct RunCommandTool {
+    policy: ShellPolicy,
+    audit: Arc<dyn AuditLog>,
+    description: String,
+}
+
+impl RunCommandTool {
+    pub fn new(policy: ShellPolicy) -> Self {
+        Self::with_audit_log(policy, Arc::new(TracingAuditLog))
+    }
+
+    pub fn with_audit_log(policy: ShellPolicy, audit: Arc<dyn AuditLog>) -> Self {
+        let allowed = if policy.allowed.is_empty() {
+            "none".to_string()
+        } else {
+            policy.allowed.join(", ")
+        };
+        let description = format!(
+            "Run a command in the user's workspace and return its exit code, stdout and \
+             stderr. The user approves every call. Allowed programs: {allowed}. Commands are \
+             killed after {}s.",
+            policy.timeout.as_secs()
+        );
+        Self {
+            policy,
+            audit,
+            description,
+        }
+    }
+
+    /// Wrap the tool so every call asks the user first, regardless of how
+    /// `permissions` classifies tools by name.
+    pub fn permissioned(self, permissions: Arc<ToolPermissions>) -> DynTool {
+        Arc::new(PermissionedTool::with_risk(
+            Arc::new(self),
+            permissions,
+            RiskLevel::Dangerous,
+        ))
+    }
+
+    async fn execute(&self, argv: &[String], cwd: &Path) -> Result<(Value, AuditOutcome)> {
+        let mut command = Command::new(&argv[0]);
+        command
+            .args(&argv[1..])
+            .current_dir(cwd)
+            .env_clear()
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        for key in PASSTHROUGH_ENV {
+            if let Some(value) = std::env::var_os(key) {
+                let value = if *key == "PATH" {
+                    absolute_path_entries(&value)
+                } else {
+                    value
+                };
+                command.env(key, value);
+            }
+        }
+
+        let mut child = match command.spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                let error = format!("failed to start `{}`: {e}", argv[0]);
+                return Ok((
+                    json!({ "error": error }),
+                    AuditOutcome::SpawnFailed { error },
+                ));
+            }
+        };
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+        let cap = self.policy.max_output_bytes;
+
+        let run = async move {
+            let (stdout, stderr, status) = tokio::join!(
+                read_capped(stdout, cap),
+                read_capped(stderr, cap),
+                child.wait()
+            );
+            (stdout, stderr, status)
+        };
+
+        // On timeout the future — and with it the child — is dropped,
+        // which kills the process via `kill_on_drop`.
+        match tokio::time::timeout(self.policy.timeout, run).await {
+            Ok(((stdout, stdout_truncated), (stderr, stderr_truncated), status)) => {
+                let exit_code = status
+                    .map_err(|e| Error::ToolException(format!("waiting for command: {e}")))?
+                    .code();
+                Ok((
+                    json!({
+                        "exit_code": exit_code,
+                        "stdout": stdout,
+                        "stderr": stderr,
+                        "truncated": stdout_truncated || stderr_truncated,
+                    }),
+                    AuditOutcome::Exited { exit_code },
+                ))
+            }
+            Err(_) => Ok((
+                json!({
+                    "error": format!(
+                        "command killed after {}s",
+                        self.policy.timeout.as_secs()
+                    ),
+                    "timed_out": true,
+                }),
+                AuditOutcome::TimedOut,
+            )),
+        }
+    }
+}
+
+impl Debug for RunCommandTool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RunCommandTool")
+            .field("policy", &self.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl BaseTool for RunCommandTool {
+    fn name(&self) -> &str {
+        TOOL_NAME
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn args_schema(&self) -> Option<&ArgsSchema> {
+        Some(&ARGS_SCHEMA)
+    }
+
+    async fn tool_run(
+        &self,
+        input: ToolInput,
+        run_manager: Option<&CallbackManagerForToolRun>,
+        _config: &RunnableConfig,
+    ) -> Result<ToolOutput> {
+        let args = input_args(input)?;
+        let command = string_arg(&args, "command")?;
+        let reason = string_arg(&args, "reason").unwrap_or_default();
+        let cwd = args.get("cwd").and_then(Value::as_str);
+
+        let audit = |cwd: Option<PathBuf>, outcome: AuditOutcome| {
+            self.audit.record(&AuditRecord {
+                run_id: run_manager.map_or_else(Uuid::nil, |m| m.run_id()),
+                parent_run_id: run_manager.and_then(|m| m.parent_run_id()),
+                command: command.clone(),
+                cwd,
+                reason: reason.clone(),
+                outcome,
+            });
+        };
+
+        let prepared = split_command(&command).and_then(|argv| {
+            if !self.policy.allows(&argv[0]) {
+                return Err(Error::ToolException(format!(
+                    "`{}` is not an allowed program",
+                    argv[0]
+                )));
+            }
+            Ok((argv, self.policy.resolve_cwd(cwd)?))
+        });
+        let (argv, cwd) = match prepared {
+            Ok(prepared) => prepared,
+            Err(e) => {
+                audit(
+                    None,
+                    AuditOutcome::Rejected {
+                        reason: e.to_string(),
+                    },
+                );
+                return Err(e);
+            }
+        };
+
+        let (output, outcome) = self.execute(&argv, &cwd).await?;
+        audit(Some(cwd), outcome);
+        Ok(ToolOutput::Json(output))
+    }
+}
+
+fn input_args(input: ToolInput) -> Result<HashMap<String, Value>> {
+    match input {
+        ToolInput::String(s) => match serde_json::from_str(&s) {
+            Ok(Value::Object(obj)) => Ok(obj.into_iter().collect()),
+            _ => Ok(HashMap::from([("command".to_string(), Value::String(s))])),
+        },
+        ToolInput::Dict(d) => Ok(d),
+        ToolInput::ToolCall(call) => call
+            .args
+            .as_object()
+            .map(|obj| obj.clone().into_iter().collect())
+            .ok_or_else(|| Error::ToolInvocation("ToolCall args must be an object".to_string())),
+    }
+}
+
+fn string_arg(args: &HashMap<String, Value>, key: &str) -> Result<String> {
+    args.get(key)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| Error::ToolInvocation(format!("missing string argument `{key}`")))
+}
+
+/// Split a command line into argv the way a POSIX shell would for plain
+/// words and quotes, but refuse every construct that would make a shell
+/// do more than run one program.
+pub fn split_command(command: &str) -> Result<Vec<String>> {
+    let mut argv = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut chars = command.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => return Err(rejected("unterminated single quote")),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c) => current.push(c),
+                            None => return Err(rejected("unterminated double quote")),
+                        },
+                        // Expansion inside double quotes is still expansion.
+                        Some('$' | '`') => {
+                            return Err(rejected("variable and command substitution"));
+                        }
+                        Some(c) => current.push(c),
+                        None => return Err(rejected("unterminated double quote")),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                match chars.next() {
+                    Some(c) => current.push(c),
+                    None => return Err(rejected("trailing backslash")),
+                }
+            }
+            '|' | '&' | ';' | '<' | '>' | '`' | '$' | '(' | ')' | '\n' | '\r' => {
+                return Err(rejected(&format!("`{}`", c.escape_default())));
+            }
+            c if c.is_whitespace() => {
+                if in_word {
+                    argv.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            c => {
+                in_word = true;
+                current.push(c);
+            }
+        }
+    }
+    if in_word {
+        argv.push(current);
+    }
+    if argv.is_empty() {
+        return Err(Error::ToolInvocation("empty command".to_string()));
+    }
+    Ok(argv)
+}
+
+fn rejected(what: &str) -> Error {
+    Error::ToolException(format!(
+        "{what} is not supported; run a single program with plain arguments"
+    ))
+}
+
+/// Read a stream to the end, keeping at most `cap` bytes. Keeps draining
+/// past the cap so a chatty child doesn't block on a full pipe.
+async fn read_capped<R: AsyncRead + Unpin>(reader: Option<R>, cap: usize) -> (String, bool) {
+    let Some(mut reader) = reader else {
+        return (String::new(), false);
+    };
+    let mut kept = Vec::new();
+    let mut truncated = false;
+    let mut buf = [0u8; 8192];
+    loop {
+        match reader.read(&mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                let room = cap.saturating_sub(kept.len());
+                if n > room {
+                    truncated = true;
+                }
+                kept.extend_from_slice(&buf[..n.min(room)]);
+            }
+        }
+    }
+    (String::from_utf8_lossy(&kept).into_owned(), truncated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingAuditLog(Mutex<Vec<AuditRecord>>);
+
+    impl AuditLog for RecordingAuditLog {
+        fn record(&self, record: &AuditRecord) {
+            self.0.lock().unwrap().push(record.clone());
+        }
+    }
+
+    fn policy(root: &Path, allowed: &[&str]) -> ShellPolicy {
+        ShellPolicy::builder()
+            .root(root)
+            .allowed(allowed.iter().map(|a| (*a).to_string()).collect())
+            .build()
+    }
+
+    #[test]
+    fn splits_words_and_quotes() {
+        assert_eq!(
+            split_command(r#"git commit -m "fix: a b" 'x y' c\ d"#).unwrap(),
+            vec!["git", "commit", "-m", "fix: a b", "x y", "c d"]
+        );
+        assert_eq!(split_command("echo ''").unwrap(), vec!["echo", ""]);
+    }
+
+    #[test]
+    fn rejects_shell_constructs() {
+        for command in [
+            "ls | wc",
+            "ls; rm -rf /",
+            "ls && rm x",
+            "cat < f",
+            "echo x > f",
+            "echo $(whoami)",
+            "echo `whoami`",
+            r#"echo "$HOME""#,
+            "echo 'unterminated",
+            "",
+        ] {
+            assert!(split_command(command).is_err(), "{command:?} was accepted");
+        }
+        // Quoted metacharacters are plain text.
+        assert_eq!(split_command("echo 'a|b'").unwrap(), vec!["echo", "a|b"]);
+    }
+
+    #[test]
+    fn policy_only_allows_bare_names() {
+        let root = std::env::temp_dir();
+        let strict = policy(&root, &["git", "cargo"]);
+        assert!(strict.allows("git"));
+        assert!(strict.allows("git.exe"));
+        for program in [
+            "/usr/bin/git",
+            "/tmp/git",
+            "./tools/git",
+            "../x/cargo",
+            r"tools\git.exe",
+            r"C:\tools\cargo.exe",
+        ] {
+            assert!(!strict.allows(program), "{program} was allowed");
+        }
+        assert!(!strict.allows("ls"));
+        assert!(!policy(&root, &[]).allows("git"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn path_keeps_only_absolute_entries() {
+        assert_eq!(
+            absolute_path_entries(OsStr::new("/usr/bin::.:bin:/bin")),
+            OsString::from("/usr/bin:/bin")
+        );
+    }
+
+    #[test]
+    fn denied_programs_win_over_the_allowlist() {
+        let root = std::env::temp_dir();
+        let policy = policy(
+            &root,
+            &["bash", "env", "envsubst", "find", "python3.12", "mkfs.ext4"],
+        );
+        for program in ["bash", "/usr/bin/env", "find", "python3.12", "mkfs.ext4"] {
+            assert!(!policy.allows(program), "{program} was allowed");
+        }
+        assert!(policy.allows("envsubst"));
+    }
+
+    #[test]
+    fn cwd_is_confined_to_root() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        let policy = policy(dir.path(), &[]);
+
+        let root = dir.path().canonicalize().unwrap();
+        assert_eq!(policy.resolve_cwd(None).unwrap(), root);
+        assert_eq!(policy.resolve_cwd(Some("sub")).unwrap(), root.join("sub"));
+        assert!(policy.resolve_cwd(Some("..")).is_err());
+        assert!(policy.resolve_cwd(Some("sub/../..")).is_err());
+        assert!(policy.resolve_cwd(Some("/")).is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn runs_caps_output_and_audits() {
+        let dir = tempfile::tempdir().unwrap();
+        let audit = Arc::new(RecordingAuditLog::default());
+        let mut policy = policy(dir.path(), &["printf", "rm"]);
+        policy.max_output_bytes = 4;
+        let tool = RunCommandTool::with_audit_log(policy, audit.clone());
+
+        let output = tool
+            .run(
+                ToolInput::Dict(HashMap::from([
+                    ("command".to_string(), json!("printf hello")),
+                    ("reason".to_string(), json!("check output")),
+                ])),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        let ToolOutput::Json(output) = output else {
+            panic!("expected JSON output");
+        };
+        assert_eq!(output["exit_code"], 0);
+        assert_eq!(output["stdout"], "hell");
+        assert_eq!(output["truncated"], true);
+
+        let err = tool
+            .run(ToolInput::String("ls".to_string()), None, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::ToolException(_)));
+
+        let records = audit.0.lock().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].reason, "check output");
+        assert_eq!(
+            records[0].outcome,
+            AuditOutcome::Exited { exit_code: Some(0) }
+        );
+        assert!(matches!(records[1].outcome, AuditOutcome::Rejected { .. }));
+    }
+}
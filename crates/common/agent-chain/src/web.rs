@@ -0,0 +1,1004 @@
+//! Built-in web tools: `web_search` and `fetch_url`.
+//!
+//! `web_search` goes through a pluggable [`SearchEngine`]. SearxNG, Brave
+//! and Bing ship here; [`engine_from_env`] picks one from the environment.
+//! `fetch_url` downloads a page, honours the site's `robots.txt`, and
+//! reduces HTML to its readable text — the `<article>` / `<main>` content
+//! without navigation, scripts or page chrome — capped to a size that fits
+//! a context window.
+//!
+//! URLs that resolve to loopback, private or link-local addresses are
+//! refused unless [`WebTools::allow_private_hosts`] is set, so a model
+//! running on a server can't be talked into probing the network it runs
+//! in. Redirects are followed by hand so every hop gets the same check,
+//! and each request connects only to the addresses that were checked.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use std::sync::Arc;
+//! use agent_chain::web::{WebTools, engine_from_env};
+//!
+//! let web = Arc::new(WebTools::builder().maybe_engine(engine_from_env()).build());
+//! let tools = web.tools();
+//! ```
+
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use agent_chain_core::tools::{ArgsSchema, StructuredTool};
+use async_trait::async_trait;
+use bon::Builder;
+use net_core::is_public;
+use reqwest::header::{ACCEPT, CONTENT_TYPE, LOCATION};
+use scraper::{ElementRef, Html, Node, Selector};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use url::{Host, Url};
+
+use crate::error::{Error, Result};
+
+pub const WEB_SEARCH: &str = "web_search";
+pub const FETCH_URL: &str = "fetch_url";
+
+/// Product token matched against `User-agent` lines in `robots.txt`.
+pub const ROBOTS_AGENT: &str = "EuroraBot";
+
+const USER_AGENT: &str = "EuroraBot/1.0 (+https://www.eurora-labs.com)";
+
+/// Upper bound on the bytes `fetch_url` downloads.
+pub const DEFAULT_MAX_FETCH_BYTES: usize = 2 * 1024 * 1024;
+
+/// Upper bound on the text `fetch_url` returns, in characters.
+pub const DEFAULT_MAX_TEXT_CHARS: usize = 30_000;
+
+/// Upper bound on `web_search` results.
+pub const DEFAULT_MAX_RESULTS: usize = 10;
+
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(20);
+
+const MAX_REDIRECTS: usize = 5;
+
+const MAX_ROBOTS_BYTES: usize = 512 * 1024;
+
+const BRAVE_ENDPOINT: &str = "https://api.search.brave.com/res/v1/web/search";
+
+const BING_ENDPOINT: &str = "https://api.bing.microsoft.com/v7.0/search";
+
+/// Elements whose content is never part of a page's readable text.
+const SKIPPED_ELEMENTS: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "iframe", "nav", "header", "footer", "aside",
+    "form", "button",
+];
+
+/// Tried in order; the first match is taken as the page's main content.
+const CONTENT_ROOTS: &[&str] = &["article", "main", "[role=main]", "body"];
+
+/// Elements rendered on their own line.
+const BLOCK_ELEMENTS: &[&str] = &[
+    "p",
+    "div",
+    "section",
+    "article",
+    "main",
+    "li",
+    "ul",
+    "ol",
+    "dl",
+    "dt",
+    "dd",
+    "blockquote",
+    "pre",
+    "table",
+    "tr",
+    "figure",
+    "figcaption",
+    "br",
+    "hr",
+];
+
+/// One hit from a [`SearchEngine`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchResult {
+    pub title: String,
+    pub url: String,
+    pub snippet: String,
+}
+
+/// A web search backend for the `web_search` tool.
+#[async_trait]
+pub trait SearchEngine: Send + Sync {
+    /// Short name shown to the model and in logs, e.g. `"brave"`.
+    fn name(&self) -> &str;
+
+    /// At most `limit` results for `query`.
+    async fn search(
+        &self,
+        client: &reqwest::Client,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<SearchResult>>;
+}
+
+/// A self-hosted [SearxNG](https://docs.searxng.org) instance. The JSON
+/// output format has to be enabled in its `settings.yml`.
+pub struct SearxngEngine {
+    endpoint: Url,
+}
+
+impl SearxngEngine {
+    pub fn new(base_url: &str) -> Result<Self> {
+        let mut endpoint = Url::parse(base_url)
+            .map_err(|e| Error::InvalidConfig(format!("invalid SearxNG URL `{base_url}`: {e}")))?;
+        endpoint
+            .path_segments_mut()
+            .map_err(|()| Error::InvalidConfig(format!("invalid SearxNG URL `{base_url}`")))?
+            .pop_if_empty()
+            .push("search");
+        Ok(Self { endpoint })
+    }
+}
+
+#[async_trait]
+impl SearchEngine for SearxngEngine {
+    fn name(&self) -> &str {
+        "searxng"
+    }
+
+    async fn search(
+        &self,
+        client: &reqwest::Client,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<SearchResult>> {
+        let mut url = self.endpoint.clone();
+        url.query_pairs_mut()
+            .append_pair("q", query)
+            .append_pair("format", "json");
+        let body = get_json(client.get(url), self.name()).await?;
+        Ok(parse_results(
+            &body["results"],
+            "title",
+            "url",
+            "content",
+            limit,
+        ))
+    }
+}
+
+/// The [Brave Search API](https://brave.com/search/api/).
+pub struct BraveEngine {
+    api_key: String,
+}
+
+impl BraveEngine {
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            api_key: api_key.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl SearchEngine for BraveEngine {
+    fn name(&self) -> &str {
+        "brave"
+    }
+
+    async fn search(
+        &self,
+        client: &reqwest::Client,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<SearchResult>> {
+        let mut url = Url::parse(BRAVE_ENDPOINT)
+            .map_err(|e| Error::InvalidConfig(format!("invalid Brave endpoint: {e}")))?;
+        url.query_pairs_mut()
+            .append_pair("q", query)
+            .append_pair("count", &limit.min(20).to_string());
+        let request = client
+            .get(url)
+            .header(ACCEPT, "application/json")
+            .header("X-Subscription-Token", &self.api_key);
+        let body = get_json(request, self.name()).await?;
+        Ok(parse_results(
+            &body["web"]["results"],
+            "title",
+            "url",
+            "description",
+            limit,
+        ))
+    }
+}
+
+/// The [Bing Web Search API](https://learn.microsoft.com/bing/search-apis/).
+pub struct BingEngine {
+    api_key: String,
+}
+
+impl BingEngine {
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            api_key: api_key.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl SearchEngine for BingEngine {
+    fn name(&self) -> &str {
+        "bing"
+    }
+
+    async fn search(
+        &self,
+        client: &reqwest::Client,
+        query: &str,
+        limit: usize,
+    ) -> Result<Vec<SearchResult>> {
+        let mut url = Url::parse(BING_ENDPOINT)
+            .map_err(|e| Error::InvalidConfig(format!("invalid Bing endpoint: {e}")))?;
+        url.query_pairs_mut()
+            .append_pair("q", query)
+            .append_pair("count", &limit.min(50).to_string());
+        let request = client
+            .get(url)
+            .header("Ocp-Apim-Subscription-Key", &self.api_key);
+        let body = get_json(request, self.name()).await?;
+        Ok(parse_results(
+            &body["webPages"]["value"],
+            "name",
+            "url",
+            "snippet",
+            limit,
+        ))
+    }
+}
+
+/// Pick a [`SearchEngine`] from the environment.
+///
+/// `WEB_SEARCH_ENGINE` (`searxng`, `brave` or `bing`) selects one
+/// explicitly; otherwise the first configured of `SEARXNG_URL`,
+/// `BRAVE_SEARCH_API_KEY` and `BING_SEARCH_API_KEY` wins. `None` when
+/// nothing usable is configured.
+pub fn engine_from_env() -> Option<Arc<dyn SearchEngine>> {
+    let var = |key: &str| std::env::var(key).ok().filter(|v| !v.trim().is_empty());
+    let searxng = || {
+        let base_url = var("SEARXNG_URL")?;
+        match SearxngEngine::new(&base_url) {
+            Ok(engine) => Some(Arc::new(engine) as Arc<dyn SearchEngine>),
+            Err(e) => {
+                tracing::warn!("Ignoring SEARXNG_URL: {e}");
+                None
+            }
+        }
+    };
+    let brave = || {
+        var("BRAVE_SEARCH_API_KEY")
+            .map(|key| Arc::new(BraveEngine::new(key)) as Arc<dyn SearchEngine>)
+    };
+    let bing = || {
+        var("BING_SEARCH_API_KEY")
+            .map(|key| Arc::new(BingEngine::new(key)) as Arc<dyn SearchEngine>)
+    };
+
+    match var("WEB_SEARCH_ENGINE")
+        .map(|v| v.trim().to_ascii_lowercase())
+        .as_deref()
+    {
+        Some("searxng") => searxng(),
+        Some("brave") => brave(),
+        Some("bing") => bing(),
+        Some(other) => {
+            tracing::warn!("Unknown WEB_SEARCH_ENGINE `{other}`; web search is disabled");
+            None
+        }
+        None => searxng().or_else(brave).or_else(bing),
+    }
+}
+
+/// Settings shared by the web tools.
+#[derive(Builder)]
+pub struct WebTools {
+    /// Backend for `web_search`. Without one only `fetch_url` is offered.
+    pub engine: Option<Arc<dyn SearchEngine>>,
+    #[builder(default = DEFAULT_MAX_FETCH_BYTES)]
+    pub max_fetch_bytes: usize,
+    #[builder(default = DEFAULT_MAX_TEXT_CHARS)]
+    pub max_text_chars: usize,
+    #[builder(default = DEFAULT_MAX_RESULTS)]
+    pub max_results: usize,
+    /// Applies to a whole tool call, redirects and `robots.txt` included.
+    #[builder(default = DEFAULT_TIMEOUT)]
+    pub timeout: Duration,
+    /// Check the site's `robots.txt` before fetching.
+    #[builder(default = true)]
+    pub respect_robots: bool,
+    /// Allow URLs that resolve to loopback, private or link-local
+    /// addresses. Off by default; only turn it on where the caller's own
+    /// network is the user's, e.g. on the desktop.
+    #[builder(default)]
+    pub allow_private_hosts: bool,
+    #[builder(skip = http_client())]
+    client: reqwest::Client,
+}
+
+impl WebTools {
+    /// `fetch_url`, plus `web_search` when an engine is configured.
+    pub fn tools(self: &Arc<Self>) -> Vec<StructuredTool> {
+        let mut tools = Vec::with_capacity(2);
+
+        if let Some(engine) = &self.engine {
+            let web = Arc::clone(self);
+            tools.push(
+                StructuredTool::builder()
+                    .name(WEB_SEARCH)
+                    .description(format!(
+                        "Search the web ({}). Returns up to {} results with title, URL and \
+                         snippet; call `{FETCH_URL}` to read a result in full.",
+                        engine.name(),
+                        self.max_results
+                    ))
+                    .args_schema(ArgsSchema::JsonSchema(json!({
+                        "type": "object",
+                        "properties": {
+                            "query": { "type": "string", "description": "What to search for." },
+                            "limit": { "type": "integer", "minimum": 1, "maximum": self.max_results, "description": "Maximum number of results." }
+                        },
+                        "required": ["query"]
+                    })))
+                    .coroutine(Arc::new(move |args: HashMap<String, Value>| {
+                        let web = Arc::clone(&web);
+                        Box::pin(async move { web.with_timeout(web.web_search(&args)).await })
+                    }))
+                    .build(),
+            );
+        }
+
+        let web = Arc::clone(self);
+        tools.push(
+            StructuredTool::builder()
+                .name(FETCH_URL)
+                .description(format!(
+                    "Fetch a web page and return its readable text, at most {} characters. \
+                     Pages disallowed by the site's robots.txt are refused.",
+                    self.max_text_chars
+                ))
+                .args_schema(ArgsSchema::JsonSchema(json!({
+                    "type": "object",
+                    "properties": {
+                        "url": { "type": "string", "description": "Absolute http(s) URL." }
+                    },
+                    "required": ["url"]
+                })))
+                .coroutine(Arc::new(move |args: HashMap<String, Value>| {
+                    let web = Arc::clone(&web);
+                    Box::pin(async move { web.with_timeout(web.fetch_url(&args)).await })
+                }))
+                .build(),
+        );
+
+        tools
+    }
+
+    async fn with_timeout(&self, work: impl Future<Output = Result<Value>>) -> Result<Value> {
+        tokio::time::timeout(self.timeout, work)
+            .await
+            .map_err(|_| Error::Timeout(format!("gave up after {:?}", self.timeout)))?
+    }
+
+    async fn web_search(&self, args: &HashMap<String, Value>) -> Result<Value> {
+        let engine = self
+            .engine
+            .as_ref()
+            .ok_or_else(|| Error::ToolException("no search engine is configured".into()))?;
+        let query = required_str(args, "query")?.trim();
+        if query.is_empty() {
+            return Err(Error::ToolInvocation("`query` must not be empty".into()));
+        }
+        let max = self.max_results.max(1);
+        let limit = optional_usize(args, "limit").unwrap_or(max).clamp(1, max);
+
+        let mut results = engine.search(&self.client, query, limit).await?;
+        results.truncate(limit);
+        Ok(json!({ "query": query, "engine": engine.name(), "results": results }))
+    }
+
+    async fn fetch_url(&self, args: &HashMap<String, Value>) -> Result<Value> {
+        let raw = required_str(args, "url")?;
+        let url = Url::parse(raw)
+            .map_err(|e| Error::ToolInvocation(format!("invalid URL `{raw}`: {e}")))?;
+        check_scheme(&url)?;
+
+        if self.respect_robots && !self.robots_allow(&url).await? {
+            return Err(Error::PermissionDenied(format!(
+                "{url} is disallowed by the site's robots.txt"
+            )));
+        }
+
+        let (final_url, response) = self.get(url).await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(Error::ToolException(format!(
+                "fetching {final_url} failed (HTTP {status})"
+            )));
+        }
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let (body, body_truncated) = read_capped(response, self.max_fetch_bytes).await?;
+        let body = String::from_utf8_lossy(&body);
+
+        let is_html = content_type.contains("html")
+            || (content_type.is_empty() && body.trim_start().starts_with('<'));
+        let (title, text) = if is_html {
+            let page = extract_readable(&body);
+            (page.title, page.text)
+        } else if content_type.starts_with("text/")
+            || content_type.contains("json")
+            || content_type.contains("xml")
+        {
+            (None, body.into_owned())
+        } else {
+            return Err(Error::ToolException(format!(
+                "{final_url} is `{content_type}`, not a text or HTML page"
+            )));
+        };
+
+        let (content, text_truncated) = truncate_chars(&text, self.max_text_chars);
+        Ok(json!({
+            "url": final_url.as_str(),
+            "title": title,
+            "content": content,
+            "truncated": body_truncated || text_truncated,
+        }))
+    }
+
+    /// Whether the site's `robots.txt` lets us fetch `url`. Per RFC 9309 a
+    /// missing file (4xx) allows everything and an unreachable one (5xx)
+    /// allows nothing.
+    async fn robots_allow(&self, url: &Url) -> Result<bool> {
+        let mut robots_url = url.clone();
+        robots_url.set_path("/robots.txt");
+        robots_url.set_query(None);
+        robots_url.set_fragment(None);
+
+        let (_, response) = self.get(robots_url).await?;
+        let status = response.status();
+        if status.is_client_error() {
+            return Ok(true);
+        }
+        if !status.is_success() {
+            return Ok(false);
+        }
+        let (body, _) = read_capped(response, MAX_ROBOTS_BYTES).await?;
+        let robots = Robots::parse(&String::from_utf8_lossy(&body), ROBOTS_AGENT);
+
+        let path = match url.query() {
+            Some(query) => format!("{}?{query}", url.path()),
+            None => url.path().to_string(),
+        };
+        Ok(robots.allows(&path))
+    }
+
+    /// GET `url`, following redirects and checking every hop's host.
+    /// Each hop connects to exactly the addresses that passed the check,
+    /// so a DNS answer that changes in between can't point it inward.
+    async fn get(&self, mut url: Url) -> Result<(Url, reqwest::Response)> {
+        for _ in 0..=MAX_REDIRECTS {
+            check_scheme(&url)?;
+            let client = match self.check_host(&url).await? {
+                Some((domain, addrs)) => http_client_builder()
+                    .resolve_to_addrs(&domain, &addrs)
+                    .build()?,
+                None => self.client.clone(),
+            };
+
+            let response = client.get(url.clone()).send().await?;
+            if !response.status().is_redirection() {
+                return Ok((url, response));
+            }
+            let location = response
+                .headers()
+                .get(LOCATION)
+                .and_then(|v| v.to_str().ok())
+                .ok_or_else(|| {
+                    Error::ToolException(format!("redirect from {url} has no Location header"))
+                })?;
+            url = url
+                .join(location)
+                .map_err(|e| Error::ToolException(format!("bad redirect from {url}: {e}")))?;
+        }
+        Err(Error::ToolException(format!(
+            "more than {MAX_REDIRECTS} redirects"
+        )))
+    }
+
+    /// Refuse `url` if its host isn't public. Returns the domain with the
+    /// addresses to pin it to, or `None` when there is nothing to pin (an
+    /// IP literal, or private hosts are allowed).
+    async fn check_host(&self, url: &Url) -> Result<Option<(String, Vec<SocketAddr>)>> {
+        if self.allow_private_hosts {
+            return Ok(None);
+        }
+        let denied = || Error::PermissionDenied(format!("{url} points at a non-public address"));
+        match url.host() {
+            Some(Host::Ipv4(ip)) if !is_public(IpAddr::V4(ip)) => Err(denied()),
+            Some(Host::Ipv6(ip)) if !is_public(IpAddr::V6(ip)) => Err(denied()),
+            Some(Host::Ipv4(_) | Host::Ipv6(_)) => Ok(None),
+            Some(Host::Domain(domain)) => {
+                let port = url.port_or_known_default().unwrap_or(443);
+                let addrs: Vec<SocketAddr> = tokio::net::lookup_host((domain, port))
+                    .await
+                    .map_err(|e| Error::ToolException(format!("could not resolve {domain}: {e}")))?
+                    .collect();
+                if addrs.is_empty() {
+                    return Err(Error::ToolException(format!("{domain} has no addresses")));
+                }
+                if !addrs.iter().all(|addr| is_public(addr.ip())) {
+                    return Err(denied());
+                }
+                Ok(Some((domain.to_owned(), addrs)))
+            }
+            None => Err(Error::ToolInvocation(format!("{url} has no host"))),
+        }
+    }
+}
+
+fn http_client() -> reqwest::Client {
+    http_client_builder()
+        .build()
+        .expect("Failed to build HTTP client")
+}
+
+fn http_client_builder() -> reqwest::ClientBuilder {
+    reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .redirect(reqwest::redirect::Policy::none())
+        .connect_timeout(Duration::from_secs(10))
+}
+
+fn check_scheme(url: &Url) -> Result<()> {
+    match url.scheme() {
+        "http" | "https" => Ok(()),
+        other => Err(Error::ToolInvocation(format!(
+            "only http(s) URLs can be fetched, not `{other}`"
+        ))),
+    }
+}
+
+async fn get_json(request: reqwest::RequestBuilder, engine: &str) -> Result<Value> {
+    let response = request.send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(Error::ToolException(format!(
+            "{engine} search failed (HTTP {status})"
+        )));
+    }
+    Ok(response.json().await?)
+}
+
+fn parse_results(
+    results: &Value,
+    title: &str,
+    url: &str,
+    snippet: &str,
+    limit: usize,
+) -> Vec<SearchResult> {
+    let field = |result: &Value, key: &str| {
+        strip_tags(result.get(key).and_then(Value::as_str).unwrap_or_default())
+    };
+    results
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .map(|result| SearchResult {
+            title: field(result, title),
+            url: field(result, url),
+            snippet: field(result, snippet),
+        })
+        .filter(|result| !result.url.is_empty())
+        .take(limit)
+        .collect()
+}
+
+/// Search APIs highlight matched terms with inline markup (`<strong>`).
+fn strip_tags(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+    out
+}
+
+async fn read_capped(mut response: reqwest::Response, cap: usize) -> Result<(Vec<u8>, bool)> {
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        let room = cap.saturating_sub(body.len());
+        if chunk.len() > room {
+            body.extend_from_slice(&chunk[..room]);
+            return Ok((body, true));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok((body, false))
+}
+
+fn truncate_chars(text: &str, max_chars: usize) -> (String, bool) {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => (text[..end].to_string(), true),
+        None => (text.to_string(), false),
+    }
+}
+
+fn required_str<'a>(args: &'a HashMap<String, Value>, key: &str) -> Result<&'a str> {
+    args.get(key)
+        .and_then(Value::as_str)
+        .ok_or_else(|| Error::ToolInvocation(format!("missing string argument `{key}`")))
+}
+
+fn optional_usize(args: &HashMap<String, Value>, key: &str) -> Option<usize> {
+    args.get(key)
+        .and_then(Value::as_u64)
+        .and_then(|n| usize::try_from(n).ok())
+}
+
+/// The readable part of an HTML page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadablePage {
+    pub title: Option<String>,
+    /// Plain text, one block per line, headings prefixed with `#` and list
+    /// items with `-`.
+    pub text: String,
+}
+
+/// Reduce an HTML document to its main content.
+///
+/// The first of `<article>`, `<main>`, `[role=main]` and `<body>` is taken
+/// as the content root; navigation, headers, footers, forms, scripts and
+/// hidden elements inside it are dropped.
+pub fn extract_readable(html: &str) -> ReadablePage {
+    let document = Html::parse_document(html);
+
+    let title = Selector::parse("title")
+        .ok()
+        .and_then(|selector| document.select(&selector).next())
+        .map(|title| title.text().collect::<Vec<_>>().join(" "))
+        .map(|title| title.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|title| !title.is_empty());
+
+    let mut text = String::new();
+    let root = CONTENT_ROOTS
+        .iter()
+        .filter_map(|css| Selector::parse(css).ok())
+        .find_map(|selector| document.select(&selector).next());
+    if let Some(root) = root {
+        render_children(root, &mut text);
+    }
+
+    ReadablePage {
+        title,
+        text: tidy(&text),
+    }
+}
+
+fn render_children(element: ElementRef<'_>, out: &mut String) {
+    for child in element.children() {
+        match child.value() {
+            Node::Text(text) => push_text(out, text),
+            Node::Element(_) => {
+                if let Some(child) = ElementRef::wrap(child) {
+                    render_element(child, out);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn render_element(element: ElementRef<'_>, out: &mut String) {
+    let value = element.value();
+    let name = value.name();
+    if SKIPPED_ELEMENTS.contains(&name)
+        || value.attr("hidden").is_some()
+        || value.attr("aria-hidden") == Some("true")
+    {
+        return;
+    }
+
+    let heading = heading_level(name);
+    let block = heading.is_some() || BLOCK_ELEMENTS.contains(&name);
+    if block {
+        out.push('\n');
+    }
+    if let Some(level) = heading {
+        out.push_str(&"#".repeat(level));
+        out.push(' ');
+    } else if name == "li" {
+        out.push_str("- ");
+    }
+    render_children(element, out);
+    if block {
+        out.push('\n');
+    }
+}
+
+fn heading_level(name: &str) -> Option<usize> {
+    match name {
+        "h1" => Some(1),
+        "h2" => Some(2),
+        "h3" => Some(3),
+        "h4" => Some(4),
+        "h5" => Some(5),
+        "h6" => Some(6),
+        _ => None,
+    }
+}
+
+/// Append `text` with its whitespace collapsed, keeping a single space
+/// where the source had any so inline elements don't run together.
+fn push_text(out: &mut String, text: &str) {
+    let mut words = text.split_whitespace().peekable();
+    if words.peek().is_none() {
+        if !text.is_empty() {
+            push_space(out);
+        }
+        return;
+    }
+    if text.starts_with(char::is_whitespace) {
+        push_space(out);
+    }
+    for (i, word) in words.enumerate() {
+        if i > 0 {
+            out.push(' ');
+        }
+        out.push_str(word);
+    }
+    if text.ends_with(char::is_whitespace) {
+        push_space(out);
+    }
+}
+
+fn push_space(out: &mut String) {
+    if !out.is_empty() && !out.ends_with([' ', '\n']) {
+        out.push(' ');
+    }
+}
+
+/// Trim every line and collapse runs of blank lines into one.
+fn tidy(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut blank = true;
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() {
+            if !blank {
+                out.push('\n');
+                blank = true;
+            }
+            continue;
+        }
+        out.push_str(line);
+        out.push('\n');
+        blank = false;
+    }
+    out.trim_end().to_string()
+}
+
+/// The `robots.txt` rules that apply to one user agent.
+#[derive(Debug, Default)]
+struct Robots {
+    /// `(allow, pattern)` pairs.
+    rules: Vec<(bool, String)>,
+}
+
+impl Robots {
+    /// Rules from the groups naming `agent`, or from the `*` groups when
+    /// none does.
+    fn parse(text: &str, agent: &str) -> Self {
+        let agent = agent.to_ascii_lowercase();
+        let mut specific = Vec::new();
+        let mut wildcard = Vec::new();
+        let mut named = false;
+        let mut group: Vec<String> = Vec::new();
+        let mut in_rules = false;
+
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let key = key.trim().to_ascii_lowercase();
+            let value = value.trim();
+            match key.as_str() {
+                "user-agent" => {
+                    // Consecutive `User-agent` lines share one group.
+                    if in_rules {
+                        group.clear();
+                        in_rules = false;
+                    }
+                    let value = value.to_ascii_lowercase();
+                    named |= value == agent;
+                    group.push(value);
+                }
+                "allow" | "disallow" => {
+                    in_rules = true;
+                    // An empty `Disallow:` allows everything; it adds no rule.
+                    if value.is_empty() {
+                        continue;
+                    }
+                    let rule = (key == "allow", value.to_string());
+                    if group.iter().any(|a| *a == agent) {
+                        specific.push(rule.clone());
+                    }
+                    if group.iter().any(|a| a == "*") {
+                        wildcard.push(rule);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Self {
+            rules: if named { specific } else { wildcard },
+        }
+    }
+
+    /// The longest matching pattern decides; `Allow` wins a tie.
+    fn allows(&self, path: &str) -> bool {
+        let mut best: Option<(usize, bool)> = None;
+        for (allow, pattern) in &self.rules {
+            if !pattern_matches(pattern, path) {
+                continue;
+            }
+            let len = pattern.len();
+            if best.is_none_or(|(l, a)| len > l || (len == l && *allow && !a)) {
+                best = Some((len, *allow));
+            }
+        }
+        best.is_none_or(|(_, allow)| allow)
+    }
+}
+
+/// `robots.txt` path matching: a prefix match where `*` matches any run of
+/// characters and a trailing `$` anchors the end.
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let Some(mut rest) = path.strip_prefix(parts.next().unwrap_or_default()) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return !anchored || rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    if anchored {
+        rest.ends_with(last)
+    } else {
+        rest.contains(last)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agent_chain_core::tools::{BaseTool, ToolInput};
+
+    #[test]
+    fn robots_prefers_the_named_group() {
+        let robots = Robots::parse(
+            "User-agent: *\nDisallow: /\n\nUser-agent: EuroraBot\nDisallow: /private\n",
+            ROBOTS_AGENT,
+        );
+        assert!(robots.allows("/docs/intro"));
+        assert!(!robots.allows("/private/notes"));
+
+        let robots = Robots::parse("User-agent: *\nDisallow: /\n", ROBOTS_AGENT);
+        assert!(!robots.allows("/docs"));
+    }
+
+    #[test]
+    fn robots_longest_match_wins() {
+        let robots = Robots::parse(
+            "User-agent: *\nDisallow: /docs\nAllow: /docs/public\nDisallow: /*.pdf$\n",
+            ROBOTS_AGENT,
+        );
+        assert!(!robots.allows("/docs/internal"));
+        assert!(robots.allows("/docs/public/guide"));
+        assert!(!robots.allows("/files/report.pdf"));
+        assert!(robots.allows("/files/report.pdf?download=1"));
+        assert!(robots.allows("/about"));
+    }
+
+    #[test]
+    fn robots_empty_disallow_allows_everything() {
+        let robots = Robots::parse("User-agent: *\nDisallow:\n", ROBOTS_AGENT);
+        assert!(robots.allows("/anything"));
+    }
+
+    #[test]
+    fn extract_prefers_article_and_drops_chrome() {
+        let page = extract_readable(
+            r#"<html><head><title> Release
+                notes </title><script>track()</script></head>
+            <body>
+              <nav><a href="/">Home</a></nav>
+              <article>
+                <h1>Version 2</h1>
+                <p>Faster <b>sync</b> and fewer bugs.</p>
+                <ul><li>One</li><li>Two</li></ul>
+                <aside>Related posts</aside>
+                <div hidden>secret</div>
+              </article>
+              <footer>Copyright</footer>
+            </body></html>"#,
+        );
+        assert_eq!(page.title.as_deref(), Some("Release notes"));
+        assert!(page.text.starts_with("# Version 2"));
+        assert!(page.text.contains("Faster sync and fewer bugs."));
+        assert!(page.text.contains("- One"));
+        assert!(page.text.contains("- Two"));
+        for dropped in ["Home", "Related", "secret", "Copyright", "track"] {
+            assert!(
+                !page.text.contains(dropped),
+                "{dropped} leaked into {page:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn parses_brave_results_and_strips_highlighting() {
+        let body = json!({
+            "web": { "results": [
+                { "title": "Eurora", "url": "https://www.eurora-labs.com", "description": "An <strong>AI</strong> assistant" },
+                { "title": "No URL" }
+            ]}
+        });
+        let results = parse_results(&body["web"]["results"], "title", "url", "description", 10);
+        assert_eq!(
+            results,
+            vec![SearchResult {
+                title: "Eurora".into(),
+                url: "https://www.eurora-labs.com".into(),
+                snippet: "An AI assistant".into(),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn fetch_refuses_private_hosts() {
+        let web = Arc::new(WebTools::builder().build());
+        let tools = web.tools();
+        assert_eq!(
+            tools.iter().map(|t| t.name()).collect::<Vec<_>>(),
+            vec![FETCH_URL]
+        );
+
+        let args = HashMap::from([("url".to_string(), json!("http://127.0.0.1:8080/admin"))]);
+        let err = tools[0]
+            .run(ToolInput::Dict(args), None, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::PermissionDenied(_)), "{err:?}");
+    }
+}
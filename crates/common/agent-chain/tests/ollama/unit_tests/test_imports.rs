@@ -11,5 +11,7 @@ fn test_all_imports() {
             |model| agent_chain::providers::ollama::ChatOllama::new(model);
         let _: fn(&str) -> agent_chain::providers::ollama::OllamaEmbeddings =
             |model| agent_chain::providers::ollama::OllamaEmbeddings::new(model);
+        let _: fn(Option<&str>) -> agent_chain::providers::ollama::OllamaModels =
+            agent_chain::providers::ollama::OllamaModels::new;
     }
 }
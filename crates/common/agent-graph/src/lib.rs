@@ -9,6 +9,8 @@
 //!
 //! 1. **Graph API** - Build workflows using `StateGraph` with nodes and edges
 //! 2. **Functional API** - Build workflows using `#[task]` and `#[entrypoint]` decorators
+//! 3. **Workflow definitions** - Describe prompt/tool/condition steps as data
+//!    (see [`workflow`]) and compile them onto a `StateGraph`
 //!
 //! # Example: Graph API
 //!
@@ -65,6 +67,7 @@ pub mod func;
 pub mod graph;
 pub mod stream;
 pub mod types;
+pub mod workflow;
 
 pub use checkpoint::InMemorySaver;
 pub use constants::{END, START};
@@ -0,0 +1,47 @@
+//! Declarative workflows compiled onto [`StateGraph`](crate::StateGraph).
+//!
+//! A [`WorkflowDefinition`] is plain data — it round-trips through JSON, so
+//! users can save, share, and edit workflows without writing code. Pairing
+//! one with a [`WorkflowRuntime`] (the chat model and tools its steps may
+//! use) yields a [`CompiledWorkflow`] that runs with caller-supplied
+//! parameters.
+//!
+//! ```ignore
+//! use agent_graph::workflow::{CompiledWorkflow, WorkflowRuntime, builtin_templates};
+//!
+//! let template = builtin_templates().into_iter().find(|t| t.id == "weekly_review").unwrap();
+//! let workflow = CompiledWorkflow::compile(template, WorkflowRuntime::new(model, tools))?;
+//! let run = workflow.run(params).await?;
+//! println!("{}", run.output);
+//! ```
+
+mod definition;
+mod executor;
+mod templates;
+
+pub use definition::{
+    Condition, ConditionOp, END_STEP, StepAction, WorkflowDefinition, WorkflowParam, WorkflowStep,
+};
+pub use executor::{CompiledWorkflow, MAX_STEPS, StepOutput, WorkflowRun, WorkflowRuntime};
+pub use templates::builtin_templates;
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum WorkflowError {
+    #[error("invalid workflow: {0}")]
+    Invalid(String),
+
+    #[error("missing required parameter {0:?}")]
+    MissingParam(String),
+
+    #[error("unknown parameter {0:?}")]
+    UnknownParam(String),
+
+    #[error("unknown tool {0:?}")]
+    UnknownTool(String),
+
+    #[error("step {step:?} failed: {message}")]
+    StepFailed { step: String, message: String },
+
+    #[error("workflow ran more than {0} steps")]
+    TooManySteps(usize),
+}
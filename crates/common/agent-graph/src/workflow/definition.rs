@@ -0,0 +1,309 @@
+//! Serializable workflow definitions.
+//!
+//! A workflow is a list of steps plus the parameters a caller fills in.
+//! Steps run in list order unless one names its successor with `next`;
+//! condition steps pick between two successors. Prompt text, tool
+//! arguments, and condition values are templates: `{{name}}` expands to a
+//! parameter and `{{steps.<id>}}` to an earlier step's output.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::WorkflowError;
+
+/// Step id that ends the workflow when used as a `next`, `then`, or `else`
+/// target.
+pub const END_STEP: &str = "end";
+
+/// A complete workflow, as saved, shared, and executed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkflowDefinition {
+    /// Stable slug, e.g. `"meeting_prep"`.
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub params: Vec<WorkflowParam>,
+    pub steps: Vec<WorkflowStep>,
+    /// Template for the workflow's result. Defaults to the output of the
+    /// last prompt or tool step that ran.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+}
+
+/// An input the caller supplies when running the workflow.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkflowParam {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Used when the caller leaves the parameter out. A parameter without
+    /// a default is required.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkflowStep {
+    /// Unique within the workflow; later steps read this step's output as
+    /// `{{steps.<id>}}`.
+    pub id: String,
+    #[serde(flatten)]
+    pub action: StepAction,
+    /// Step to run afterwards. Defaults to the next step in the list, or
+    /// the end of the workflow after the last one. Ignored on condition
+    /// steps, which route with `then`/`else`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StepAction {
+    /// Ask the chat model. The output is the reply text.
+    Prompt {
+        prompt: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        system: Option<String>,
+    },
+    /// Call a tool by name. String values anywhere in `arguments` are
+    /// templates. The output is the tool's result rendered as text.
+    Tool {
+        tool: String,
+        #[serde(default)]
+        arguments: Map<String, Value>,
+    },
+    /// Route on a test of a templated value. The output is `"true"` or
+    /// `"false"`.
+    Condition {
+        when: Condition,
+        then: String,
+        #[serde(rename = "else")]
+        otherwise: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Condition {
+    pub value: String,
+    pub op: ConditionOp,
+    /// Right-hand side for `contains` and `equals`. Also a template.
+    #[serde(default)]
+    pub operand: String,
+}
+
+/// Comparisons ignore case and surrounding whitespace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConditionOp {
+    Contains,
+    Equals,
+    NotEmpty,
+}
+
+impl ConditionOp {
+    pub fn test(self, value: &str, operand: &str) -> bool {
+        let value = value.trim().to_lowercase();
+        let operand = operand.trim().to_lowercase();
+        match self {
+            Self::Contains => value.contains(&operand),
+            Self::Equals => value == operand,
+            Self::NotEmpty => !value.is_empty(),
+        }
+    }
+}
+
+impl WorkflowDefinition {
+    /// Names of the tools the workflow calls, without duplicates, in step
+    /// order.
+    pub fn tools(&self) -> Vec<&str> {
+        let mut tools: Vec<&str> = Vec::new();
+        for step in &self.steps {
+            if let StepAction::Tool { tool, .. } = &step.action
+                && !tools.contains(&tool.as_str())
+            {
+                tools.push(tool);
+            }
+        }
+        tools
+    }
+
+    /// Check the definition is internally consistent: unique step ids,
+    /// routing targets that exist, and template references to known
+    /// parameters and steps. Tool names are checked when compiling, since
+    /// they depend on what the runtime offers.
+    pub fn validate(&self) -> Result<(), WorkflowError> {
+        let invalid = |msg: String| Err(WorkflowError::Invalid(msg));
+
+        if self.steps.is_empty() {
+            return invalid("a workflow needs at least one step".into());
+        }
+
+        let mut params = HashSet::new();
+        for param in &self.params {
+            if param.name.is_empty() || param.name.starts_with("steps.") {
+                return invalid(format!("invalid parameter name {:?}", param.name));
+            }
+            if !params.insert(param.name.as_str()) {
+                return invalid(format!("duplicate parameter {:?}", param.name));
+            }
+        }
+
+        let mut steps = HashSet::new();
+        for step in &self.steps {
+            // `__`-prefixed names are reserved for the graph's own nodes.
+            if step.id.is_empty() || step.id == END_STEP || step.id.starts_with("__") {
+                return invalid(format!("invalid step id {:?}", step.id));
+            }
+            if !steps.insert(step.id.as_str()) {
+                return invalid(format!("duplicate step id {:?}", step.id));
+            }
+        }
+
+        let target_ok = |target: &str| target == END_STEP || steps.contains(target);
+        let refs_ok = |template: &str| -> Result<(), WorkflowError> {
+            for reference in template_refs(template) {
+                let known = match reference.strip_prefix("steps.") {
+                    Some(step) => steps.contains(step),
+                    None => params.contains(reference),
+                };
+                if !known {
+                    return Err(WorkflowError::Invalid(format!(
+                        "unknown reference {{{{{reference}}}}}"
+                    )));
+                }
+            }
+            Ok(())
+        };
+
+        for step in &self.steps {
+            let targets: Vec<&str> = match &step.action {
+                StepAction::Prompt { prompt, system } => {
+                    refs_ok(prompt)?;
+                    if let Some(system) = system {
+                        refs_ok(system)?;
+                    }
+                    step.next.as_deref().into_iter().collect()
+                }
+                StepAction::Tool { arguments, .. } => {
+                    for_each_string(arguments, &mut |s| refs_ok(s))?;
+                    step.next.as_deref().into_iter().collect()
+                }
+                StepAction::Condition {
+                    when,
+                    then,
+                    otherwise,
+                } => {
+                    refs_ok(&when.value)?;
+                    refs_ok(&when.operand)?;
+                    vec![then.as_str(), otherwise.as_str()]
+                }
+            };
+            if let Some(bad) = targets.into_iter().find(|t| !target_ok(t)) {
+                return invalid(format!("step {:?} routes to unknown step {bad:?}", step.id));
+            }
+        }
+
+        if let Some(output) = &self.output {
+            refs_ok(output)?;
+        }
+        Ok(())
+    }
+}
+
+/// The `name` inside every `{{ name }}` in `template`, trimmed.
+pub(crate) fn template_refs(template: &str) -> impl Iterator<Item = &str> {
+    template
+        .split("{{")
+        .skip(1)
+        .filter_map(|rest| rest.split_once("}}"))
+        .map(|(name, _)| name.trim())
+}
+
+fn for_each_string(
+    map: &Map<String, Value>,
+    f: &mut impl FnMut(&str) -> Result<(), WorkflowError>,
+) -> Result<(), WorkflowError> {
+    fn walk(
+        value: &Value,
+        f: &mut impl FnMut(&str) -> Result<(), WorkflowError>,
+    ) -> Result<(), WorkflowError> {
+        match value {
+            Value::String(s) => f(s),
+            Value::Array(items) => items.iter().try_for_each(|v| walk(v, f)),
+            Value::Object(map) => map.values().try_for_each(|v| walk(v, f)),
+            _ => Ok(()),
+        }
+    }
+    map.values().try_for_each(|v| walk(v, f))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn definition(steps: Value) -> WorkflowDefinition {
+        serde_json::from_value(json!({
+            "id": "test",
+            "name": "Test",
+            "params": [{ "name": "topic" }],
+            "steps": steps,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn steps_deserialize_by_type_tag() {
+        let def = definition(json!([
+            { "id": "a", "type": "prompt", "prompt": "About {{topic}}" },
+            { "id": "b", "type": "tool", "tool": "web_search", "arguments": { "query": "{{topic}}" } },
+            {
+                "id": "c", "type": "condition",
+                "when": { "value": "{{steps.a}}", "op": "contains", "operand": "yes" },
+                "then": "a", "else": "end"
+            },
+        ]));
+        assert!(matches!(def.steps[0].action, StepAction::Prompt { .. }));
+        assert!(matches!(def.steps[1].action, StepAction::Tool { .. }));
+        assert!(matches!(
+            def.steps[2].action,
+            StepAction::Condition { ref otherwise, .. } if otherwise == "end"
+        ));
+        def.validate().unwrap();
+    }
+
+    #[test]
+    fn validate_rejects_unknown_references_and_targets() {
+        let unknown_param = definition(json!([
+            { "id": "a", "type": "prompt", "prompt": "{{subject}}" },
+        ]));
+        assert!(unknown_param.validate().is_err());
+
+        let unknown_step = definition(json!([
+            { "id": "a", "type": "prompt", "prompt": "{{steps.b}}" },
+        ]));
+        assert!(unknown_step.validate().is_err());
+
+        let unknown_target = definition(json!([
+            { "id": "a", "type": "prompt", "prompt": "hi", "next": "nowhere" },
+        ]));
+        assert!(unknown_target.validate().is_err());
+
+        let duplicate = definition(json!([
+            { "id": "a", "type": "prompt", "prompt": "hi" },
+            { "id": "a", "type": "prompt", "prompt": "again" },
+        ]));
+        assert!(duplicate.validate().is_err());
+    }
+
+    #[test]
+    fn condition_ops_ignore_case_and_whitespace() {
+        assert!(ConditionOp::Contains.test("Yes, definitely", "YES"));
+        assert!(ConditionOp::Equals.test("  done\n", "Done"));
+        assert!(!ConditionOp::NotEmpty.test(" \n", ""));
+    }
+}
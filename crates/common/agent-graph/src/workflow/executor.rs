@@ -0,0 +1,489 @@
+//! Compiling a [`WorkflowDefinition`] into a runnable graph.
+//!
+//! Every step becomes a node named after its id. Each node gets a
+//! conditional edge that follows the step's routing, or jumps straight to
+//! [`END`] once a step has failed, so an error stops the run without the
+//! graph needing to know about errors.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use agent_chain_core::messages::add_usage;
+use agent_chain_core::tools::{BaseTool, ToolInput};
+use agent_chain_core::{AnyMessage, BaseChatModel, HumanMessage, SystemMessage, UsageMetadata};
+use serde_json::Value;
+
+use super::WorkflowError;
+use super::definition::{END_STEP, StepAction, WorkflowDefinition, WorkflowStep};
+use crate::constants::{END, START};
+use crate::graph::{CompiledGraph, StateGraph};
+
+/// Upper bound on steps executed in one run. Condition steps can route
+/// backwards, so a definition can loop; this keeps a loop that never exits
+/// from running forever.
+pub const MAX_STEPS: usize = 50;
+
+/// What a workflow's steps may call.
+#[derive(Clone)]
+pub struct WorkflowRuntime {
+    model: Arc<dyn BaseChatModel + Send + Sync>,
+    tools: HashMap<String, Arc<dyn BaseTool>>,
+}
+
+impl WorkflowRuntime {
+    pub fn new(
+        model: Arc<dyn BaseChatModel + Send + Sync>,
+        tools: impl IntoIterator<Item = Arc<dyn BaseTool>>,
+    ) -> Self {
+        Self {
+            model,
+            tools: tools
+                .into_iter()
+                .map(|tool| (tool.name().to_string(), tool))
+                .collect(),
+        }
+    }
+}
+
+/// Result of a successful run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkflowRun {
+    /// The rendered `output` template, or the last prompt/tool output.
+    pub output: String,
+    /// Every step that ran, in execution order.
+    pub steps: Vec<StepOutput>,
+    /// Token usage summed over all prompt steps, if the model reported any.
+    pub usage: Option<UsageMetadata>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepOutput {
+    pub id: String,
+    pub output: String,
+}
+
+#[derive(Debug, Clone, Default)]
+struct WorkflowState {
+    params: HashMap<String, String>,
+    outputs: HashMap<String, String>,
+    trace: Vec<StepOutput>,
+    last_output: Option<String>,
+    usage: Option<UsageMetadata>,
+    error: Option<WorkflowError>,
+}
+
+impl WorkflowState {
+    /// Expand `{{name}}` and `{{steps.<id>}}`. A step that hasn't run (yet,
+    /// or at all on this path) expands to an empty string.
+    fn render(&self, template: &str) -> String {
+        let mut pieces = template.split("{{");
+        let mut out = pieces.next().unwrap_or_default().to_string();
+        for piece in pieces {
+            match piece.split_once("}}") {
+                Some((name, rest)) => {
+                    let name = name.trim();
+                    let value = match name.strip_prefix("steps.") {
+                        Some(step) => self.outputs.get(step),
+                        None => self.params.get(name),
+                    };
+                    out.push_str(value.map(String::as_str).unwrap_or_default());
+                    out.push_str(rest);
+                }
+                None => {
+                    out.push_str("{{");
+                    out.push_str(piece);
+                }
+            }
+        }
+        out
+    }
+
+    fn render_value(&self, value: &Value) -> Value {
+        match value {
+            Value::String(s) => Value::String(self.render(s)),
+            Value::Array(items) => {
+                Value::Array(items.iter().map(|v| self.render_value(v)).collect())
+            }
+            Value::Object(map) => Value::Object(
+                map.iter()
+                    .map(|(k, v)| (k.clone(), self.render_value(v)))
+                    .collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+}
+
+pub struct CompiledWorkflow {
+    definition: WorkflowDefinition,
+    graph: CompiledGraph<WorkflowState>,
+}
+
+impl CompiledWorkflow {
+    /// Validate `definition`, check every tool it calls is in `runtime`,
+    /// and build the graph.
+    pub fn compile(
+        definition: WorkflowDefinition,
+        runtime: WorkflowRuntime,
+    ) -> Result<Self, WorkflowError> {
+        definition.validate()?;
+        for step in &definition.steps {
+            if let StepAction::Tool { tool, .. } = &step.action
+                && !runtime.tools.contains_key(tool)
+            {
+                return Err(WorkflowError::UnknownTool(tool.clone()));
+            }
+        }
+
+        let runtime = Arc::new(runtime);
+        let mut graph = StateGraph::<WorkflowState>::new();
+        graph.add_edge(START, definition.steps[0].id.clone());
+
+        for (index, step) in definition.steps.iter().enumerate() {
+            let step = Arc::new(step.clone());
+
+            let node_step = step.clone();
+            let node_runtime = runtime.clone();
+            graph.add_node(step.id.clone(), move |state| {
+                let step = node_step.clone();
+                let runtime = node_runtime.clone();
+                async move { run_step(&step, &runtime, state).await }
+            });
+
+            let following = definition.steps.get(index + 1).map(|s| s.id.clone());
+            let route_step = step.clone();
+            graph.add_conditional_edges(
+                step.id.clone(),
+                move |state: &WorkflowState| {
+                    let next = route(&route_step, following.as_deref(), state);
+                    async move { next }
+                },
+                None,
+            );
+        }
+
+        Ok(Self {
+            definition,
+            graph: graph.compile(),
+        })
+    }
+
+    pub fn definition(&self) -> &WorkflowDefinition {
+        &self.definition
+    }
+
+    /// Fill in defaults for parameters left out of `params`, rejecting
+    /// missing required parameters and names the definition doesn't
+    /// declare. [`Self::run`] does this itself; it's exposed so callers can
+    /// reject bad input before setting anything up for a run.
+    pub fn resolve_params(
+        &self,
+        mut params: HashMap<String, String>,
+    ) -> Result<HashMap<String, String>, WorkflowError> {
+        if let Some(unknown) = params
+            .keys()
+            .find(|name| !self.definition.params.iter().any(|p| &p.name == *name))
+        {
+            return Err(WorkflowError::UnknownParam(unknown.clone()));
+        }
+        for param in &self.definition.params {
+            if params.contains_key(&param.name) {
+                continue;
+            }
+            match &param.default {
+                Some(default) => {
+                    params.insert(param.name.clone(), default.clone());
+                }
+                None => return Err(WorkflowError::MissingParam(param.name.clone())),
+            }
+        }
+        Ok(params)
+    }
+
+    /// Run with `params`, resolved as by [`Self::resolve_params`].
+    pub async fn run(&self, params: HashMap<String, String>) -> Result<WorkflowRun, WorkflowError> {
+        let params = self.resolve_params(params)?;
+        let state = self
+            .graph
+            .invoke(WorkflowState {
+                params,
+                ..Default::default()
+            })
+            .await;
+        if let Some(error) = state.error {
+            return Err(error);
+        }
+
+        let output = match &self.definition.output {
+            Some(template) => state.render(template),
+            None => state.last_output.clone().unwrap_or_default(),
+        };
+        Ok(WorkflowRun {
+            output,
+            steps: state.trace,
+            usage: state.usage,
+        })
+    }
+}
+
+async fn run_step(
+    step: &WorkflowStep,
+    runtime: &WorkflowRuntime,
+    mut state: WorkflowState,
+) -> WorkflowState {
+    if state.trace.len() >= MAX_STEPS {
+        state.error = Some(WorkflowError::TooManySteps(MAX_STEPS));
+        return state;
+    }
+    let failed = |message: String| WorkflowError::StepFailed {
+        step: step.id.clone(),
+        message,
+    };
+
+    let output = match &step.action {
+        StepAction::Prompt { prompt, system } => {
+            let mut messages: Vec<AnyMessage> = Vec::new();
+            if let Some(system) = system {
+                messages.push(
+                    SystemMessage::builder()
+                        .content(state.render(system))
+                        .build()
+                        .into(),
+                );
+            }
+            messages.push(
+                HumanMessage::builder()
+                    .content(state.render(prompt))
+                    .build()
+                    .into(),
+            );
+            match runtime.model.invoke(messages, None).await {
+                Ok(reply) => {
+                    if reply.usage_metadata.is_some() {
+                        state.usage = Some(add_usage(
+                            state.usage.as_ref(),
+                            reply.usage_metadata.as_ref(),
+                        ));
+                    }
+                    Ok(reply.text())
+                }
+                Err(e) => Err(failed(e.to_string())),
+            }
+        }
+        StepAction::Tool { tool, arguments } => {
+            let arguments = arguments
+                .iter()
+                .map(|(k, v)| (k.clone(), state.render_value(v)))
+                .collect();
+            // Presence is checked at compile time.
+            match runtime.tools[tool]
+                .run(ToolInput::Dict(arguments), None, None)
+                .await
+            {
+                Ok(output) => Ok(output.to_string_lossy()),
+                Err(e) => Err(failed(e.to_string())),
+            }
+        }
+        StepAction::Condition { when, .. } => {
+            let passed = when
+                .op
+                .test(&state.render(&when.value), &state.render(&when.operand));
+            Ok(passed.to_string())
+        }
+    };
+
+    match output {
+        Ok(output) => {
+            if !matches!(step.action, StepAction::Condition { .. }) {
+                state.last_output = Some(output.clone());
+            }
+            state.outputs.insert(step.id.clone(), output.clone());
+            state.trace.push(StepOutput {
+                id: step.id.clone(),
+                output,
+            });
+        }
+        Err(e) => state.error = Some(e),
+    }
+    state
+}
+
+/// Graph node to run after `step`, given the step that follows it in the
+/// definition's list.
+fn route(step: &WorkflowStep, following: Option<&str>, state: &WorkflowState) -> String {
+    if state.error.is_some() {
+        return END.to_string();
+    }
+    let target = match &step.action {
+        StepAction::Condition {
+            then, otherwise, ..
+        } => {
+            if state.outputs.get(&step.id).is_some_and(|o| o == "true") {
+                Some(then.as_str())
+            } else {
+                Some(otherwise.as_str())
+            }
+        }
+        _ => step.next.as_deref().or(following),
+    };
+    match target {
+        Some(END_STEP) | None => END.to_string(),
+        Some(id) => id.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agent_chain_core::FakeListChatModel;
+    use agent_chain_core::tools::{StructuredTool, create_args_schema};
+    use serde_json::json;
+
+    fn runtime(responses: &[&str], tools: Vec<Arc<dyn BaseTool>>) -> WorkflowRuntime {
+        let model = FakeListChatModel::builder()
+            .responses(responses.iter().map(|r| r.to_string()).collect())
+            .build();
+        WorkflowRuntime::new(Arc::new(model), tools)
+    }
+
+    fn definition(value: Value) -> WorkflowDefinition {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn params(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn branching() -> WorkflowDefinition {
+        definition(json!({
+            "id": "branching",
+            "name": "Branching",
+            "params": [{ "name": "mode", "default": "short" }],
+            "steps": [
+                {
+                    "id": "check", "type": "condition",
+                    "when": { "value": "{{mode}}", "op": "equals", "operand": "long" },
+                    "then": "long", "else": "short"
+                },
+                { "id": "long", "type": "prompt", "prompt": "long", "next": "end" },
+                { "id": "short", "type": "prompt", "prompt": "short" },
+            ],
+            "output": "[{{steps.long}}{{steps.short}}]",
+        }))
+    }
+
+    #[tokio::test]
+    async fn conditions_pick_a_branch() {
+        let workflow = CompiledWorkflow::compile(branching(), runtime(&["reply"], vec![])).unwrap();
+
+        let run = workflow.run(params(&[("mode", "LONG")])).await.unwrap();
+        let ran: Vec<&str> = run.steps.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ran, ["check", "long"]);
+        assert_eq!(run.output, "[reply]");
+
+        let run = workflow.run(HashMap::new()).await.unwrap();
+        let ran: Vec<&str> = run.steps.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ran, ["check", "short"]);
+    }
+
+    #[tokio::test]
+    async fn tool_arguments_and_prompts_are_templated() {
+        let schema = create_args_schema(
+            "echo",
+            HashMap::from([("text".to_string(), json!({ "type": "string" }))]),
+            vec!["text".to_string()],
+            None,
+        );
+        let echo: Arc<dyn BaseTool> = Arc::new(StructuredTool::from_function(
+            |args| {
+                Ok(json!(format!(
+                    "echo: {}",
+                    args["text"].as_str().unwrap_or_default()
+                )))
+            },
+            "echo",
+            "Echo the input",
+            schema,
+        ));
+        let workflow = CompiledWorkflow::compile(
+            definition(json!({
+                "id": "tooling",
+                "name": "Tooling",
+                "params": [{ "name": "topic" }],
+                "steps": [
+                    { "id": "lookup", "type": "tool", "tool": "echo", "arguments": { "text": "{{topic}}" } },
+                    { "id": "write", "type": "prompt", "prompt": "Use {{steps.lookup}}" },
+                ],
+            })),
+            runtime(&["written"], vec![echo]),
+        )
+        .unwrap();
+
+        let run = workflow.run(params(&[("topic", "rust")])).await.unwrap();
+        assert!(run.steps[0].output.contains("echo: rust"));
+        assert_eq!(run.output, "written");
+    }
+
+    #[tokio::test]
+    async fn params_are_checked_before_running() {
+        let workflow = CompiledWorkflow::compile(
+            definition(json!({
+                "id": "p",
+                "name": "P",
+                "params": [{ "name": "required" }],
+                "steps": [{ "id": "a", "type": "prompt", "prompt": "{{required}}" }],
+            })),
+            runtime(&["unused"], vec![]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            workflow.run(HashMap::new()).await,
+            Err(WorkflowError::MissingParam("required".into()))
+        );
+        assert_eq!(
+            workflow
+                .run(params(&[("required", "x"), ("extra", "y")]))
+                .await,
+            Err(WorkflowError::UnknownParam("extra".into()))
+        );
+    }
+
+    #[tokio::test]
+    async fn loops_are_cut_off() {
+        let workflow = CompiledWorkflow::compile(
+            definition(json!({
+                "id": "loop",
+                "name": "Loop",
+                "steps": [{
+                    "id": "again", "type": "condition",
+                    "when": { "value": "", "op": "equals", "operand": "" },
+                    "then": "again", "else": "end"
+                }],
+            })),
+            runtime(&[], vec![]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            workflow.run(HashMap::new()).await,
+            Err(WorkflowError::TooManySteps(MAX_STEPS))
+        );
+    }
+
+    #[test]
+    fn compile_rejects_tools_the_runtime_lacks() {
+        let result = CompiledWorkflow::compile(
+            definition(json!({
+                "id": "t",
+                "name": "T",
+                "steps": [{ "id": "a", "type": "tool", "tool": "missing" }],
+            })),
+            runtime(&[], vec![]),
+        );
+        assert!(matches!(result, Err(WorkflowError::UnknownTool(t)) if t == "missing"));
+    }
+}
@@ -0,0 +1,38 @@
+//! Built-in workflow templates.
+//!
+//! Stored as JSON in the same format users save, so a template doubles as
+//! an example definition and can be copied and edited as-is.
+
+use super::definition::WorkflowDefinition;
+
+const TEMPLATES: &[&str] = &[
+    include_str!("templates/summarize_pdf.json"),
+    include_str!("templates/meeting_prep.json"),
+    include_str!("templates/weekly_review.json"),
+];
+
+/// The templates shipped with the crate.
+pub fn builtin_templates() -> Vec<WorkflowDefinition> {
+    TEMPLATES
+        .iter()
+        .map(|json| serde_json::from_str(json).expect("built-in workflow template is valid JSON"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn builtin_templates_validate_and_have_unique_ids() {
+        let templates = builtin_templates();
+        let ids: HashSet<&str> = templates.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids.len(), templates.len());
+        for template in &templates {
+            template
+                .validate()
+                .unwrap_or_else(|e| panic!("{}: {e}", template.id));
+        }
+    }
+}
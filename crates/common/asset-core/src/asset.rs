@@ -20,10 +20,58 @@ pub struct Asset {
     pub storage_uri: String,
     #[cfg_attr(feature = "specta", specta(type = Unknown))]
     pub metadata: serde_json::Value,
+    /// The device that uploaded it, when the client said.
+    #[serde(default)]
+    pub device_id: Option<Uuid>,
+    pub scan_status: ScanStatus,
+    #[serde(default)]
+    pub storage_tier: StorageTier,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// Malware scan state of an asset. `quarantined` assets can't be
+/// downloaded; `skipped` ones were never scanned (scanning is off on this
+/// deployment, or the scanner couldn't process the file).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+#[serde(rename_all = "lowercase")]
+pub enum ScanStatus {
+    Pending,
+    Clean,
+    Quarantined,
+    Skipped,
+}
+
+/// Which store an asset's bytes are in. Opening a `cold` asset takes
+/// longer, since it's fetched from cheaper storage and moved back to
+/// `hot` on the way; clients should say so rather than look stuck.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+#[serde(rename_all = "lowercase")]
+pub enum StorageTier {
+    #[default]
+    Hot,
+    Cold,
+}
+
+/// Response header set on downloads that had to fetch the asset from the
+/// cold store, with the value `cold`.
+pub const STORAGE_TIER_HEADER: &str = "x-storage-tier";
+
+/// Key set in an image asset's `metadata` to record whether EXIF / XMP /
+/// text metadata was stripped from it on upload.
+pub const METADATA_STRIPPED_KEY: &str = "metadata_stripped";
+
+/// Per-user upload settings, for `GET` and `PUT /v1/assets/preferences`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct AssetPreferences {
+    /// Strip EXIF (including GPS position), XMP and text metadata from
+    /// uploaded JPEG, PNG and WebP images. On by default.
+    pub strip_image_metadata: bool,
+}
+
 /// Request body for `POST /v1/assets`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "specta", derive(Type))]
@@ -35,4 +83,199 @@ pub struct CreateAssetRequest {
     #[serde(default)]
     #[cfg_attr(feature = "specta", specta(type = Option<Unknown>))]
     pub metadata: Option<serde_json::Value>,
+    /// The uploading device, as registered with `PUT /devices/{id}`. An
+    /// id the user hasn't registered is ignored.
+    #[serde(default)]
+    pub device_id: Option<Uuid>,
+}
+
+/// Most ids a single batch request may carry.
+pub const MAX_BATCH_SIZE: usize = 100;
+
+/// Request body for `POST /v1/assets/batch/get` and
+/// `POST /v1/assets/batch/delete`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct BatchAssetsRequest {
+    /// Up to [`MAX_BATCH_SIZE`] asset ids. Duplicates are answered once.
+    pub ids: Vec<Uuid>,
+}
+
+/// Request body for `POST /v1/assets/batch/link`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct BatchLinkAssetsRequest {
+    pub activity_id: Uuid,
+    /// Up to [`MAX_BATCH_SIZE`] asset ids. Duplicates are answered once.
+    pub asset_ids: Vec<Uuid>,
+}
+
+/// Why one item of a batch failed. `error` is the same machine-readable
+/// kind the single-item endpoints return (`not_found`, …).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct BatchItemError {
+    pub error: String,
+    pub message: String,
+}
+
+/// Outcome for one id of a batch delete or link. Succeeded when `error`
+/// is `null`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct BatchItemResult {
+    pub id: Uuid,
+    pub error: Option<BatchItemError>,
+}
+
+/// Outcome for one id of a batch get: exactly one of `asset` and `error`
+/// is set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct BatchAssetResult {
+    pub id: Uuid,
+    pub asset: Option<Asset>,
+    pub error: Option<BatchItemError>,
+}
+
+/// Response for `POST /v1/assets/batch/get`, one result per distinct
+/// requested id, in request order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct BatchGetAssetsResponse {
+    pub results: Vec<BatchAssetResult>,
+}
+
+/// Response for `POST /v1/assets/batch/delete` and
+/// `POST /v1/assets/batch/link`, one result per distinct requested id, in
+/// request order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct BatchItemsResponse {
+    pub results: Vec<BatchItemResult>,
+}
+
+/// Longest a share link may stay valid: 30 days.
+pub const MAX_LINK_LIFETIME_SECS: u32 = 30 * 24 * 60 * 60;
+
+/// Read access to an asset for someone other than its owner: a named
+/// user (`grantee_id` set) or whoever holds a link (`grantee_id` null).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct AssetGrant {
+    pub id: Uuid,
+    pub asset_id: Uuid,
+    pub grantee_id: Option<Uuid>,
+    /// `null` for a user grant that doesn't expire. Links always expire.
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Request body for `POST /v1/assets/{asset_id}/grants`. Sharing again
+/// with the same user replaces the expiry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct CreateAssetGrantRequest {
+    pub user_id: Uuid,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Request body for `POST /v1/assets/{asset_id}/links`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct CreateAssetLinkRequest {
+    /// How long the link works, up to [`MAX_LINK_LIFETIME_SECS`].
+    pub expires_in_secs: u32,
+}
+
+/// Response for `POST /v1/assets/{asset_id}/links`. `token` is only ever
+/// returned here; anyone with `path` can download the asset until the
+/// grant expires or is revoked.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct AssetLink {
+    pub grant: AssetGrant,
+    pub token: String,
+    /// `/shared/assets/{token}`, relative to the API base URL.
+    pub path: String,
+}
+
+/// Response for `GET /v1/assets/{asset_id}/grants`, newest first,
+/// expired grants included.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct ListAssetGrantsResponse {
+    pub grants: Vec<AssetGrant>,
+}
+
+/// Response for `GET /v1/assets/shared-with-me`, most recently shared
+/// first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct SharedAssetsResponse {
+    pub assets: Vec<Asset>,
+}
+
+/// Largest asset a resumable upload may assemble: 1 GiB.
+pub const MAX_UPLOAD_SIZE: u64 = 1024 * 1024 * 1024;
+
+/// Largest resumable upload the server has to hold in memory to check or
+/// rewrite: 64 MiB. That is images, text and JSON, and every type when
+/// the server encrypts stored objects; other assets are streamed into
+/// place and may use the full [`MAX_UPLOAD_SIZE`].
+pub const MAX_BUFFERED_UPLOAD_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Upload sessions a user may have open at once.
+pub const MAX_OPEN_UPLOADS: u32 = 8;
+
+/// Chunk size the server asks resumable uploads to use: 8 MiB.
+pub const UPLOAD_CHUNK_SIZE: u64 = 8 * 1024 * 1024;
+
+/// How long an upload session lives after its last chunk: 24 hours.
+/// Sessions that go quiet for longer are deleted along with their chunks.
+pub const UPLOAD_SESSION_TTL_SECS: u32 = 24 * 60 * 60;
+
+/// Request body for `POST /v1/assets/uploads`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct CreateUploadRequest {
+    pub name: String,
+    pub mime_type: String,
+    /// Size of the whole asset, up to [`MAX_UPLOAD_SIZE`] (or
+    /// [`MAX_BUFFERED_UPLOAD_SIZE`] for types checked as a whole).
+    #[cfg_attr(feature = "specta", specta(type = BigInt))]
+    pub size: u64,
+    #[serde(default)]
+    #[cfg_attr(feature = "specta", specta(type = Option<Unknown>))]
+    pub metadata: Option<serde_json::Value>,
+    /// The uploading device, as registered with `PUT /devices/{id}`. The
+    /// completed asset is tagged with it; an id the user hasn't
+    /// registered is ignored.
+    #[serde(default)]
+    pub device_id: Option<Uuid>,
+}
+
+/// A resumable upload in progress. Chunks go to
+/// `PUT /v1/assets/uploads/{id}/chunks/{offset}` in any order; after a
+/// disconnect `GET /v1/assets/uploads/{id}` says which arrived, and
+/// `POST /v1/assets/uploads/{id}/complete` turns the session into an
+/// asset once all have.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct UploadSession {
+    pub id: Uuid,
+    pub name: String,
+    pub mime_type: String,
+    #[cfg_attr(feature = "specta", specta(type = BigInt))]
+    pub size: u64,
+    /// Chunks start at multiples of this and are exactly this long, except
+    /// the last one, which runs to `size`.
+    #[cfg_attr(feature = "specta", specta(type = BigInt))]
+    pub chunk_size: u64,
+    /// Offsets of the chunks received so far, ascending.
+    #[cfg_attr(feature = "specta", specta(type = Vec<BigInt>))]
+    pub received_offsets: Vec<u64>,
+    /// Pushed back by every chunk; see [`UPLOAD_SESSION_TTL_SECS`].
+    pub expires_at: DateTime<Utc>,
 }
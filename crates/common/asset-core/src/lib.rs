@@ -6,7 +6,15 @@
 
 pub mod asset;
 
-pub use asset::{Asset, CreateAssetRequest};
+pub use asset::{
+    Asset, AssetGrant, AssetLink, AssetPreferences, BatchAssetResult, BatchAssetsRequest,
+    BatchGetAssetsResponse, BatchItemError, BatchItemResult, BatchItemsResponse,
+    BatchLinkAssetsRequest, CreateAssetGrantRequest, CreateAssetLinkRequest, CreateAssetRequest,
+    CreateUploadRequest, ListAssetGrantsResponse, MAX_BATCH_SIZE, MAX_BUFFERED_UPLOAD_SIZE,
+    MAX_LINK_LIFETIME_SECS, MAX_OPEN_UPLOADS, MAX_UPLOAD_SIZE, METADATA_STRIPPED_KEY,
+    STORAGE_TIER_HEADER, ScanStatus, SharedAssetsResponse, StorageTier, UPLOAD_CHUNK_SIZE,
+    UPLOAD_SESSION_TTL_SECS, UploadSession,
+};
 
 /// Build a [`specta::Types`] containing every asset wire type the frontend
 /// needs. Consumed by `euro-codegen` to emit `asset.ts`.
@@ -14,7 +22,25 @@ pub use asset::{Asset, CreateAssetRequest};
 pub fn type_collection() -> specta::Types {
     specta::Types::default()
         .register::<Asset>()
+        .register::<ScanStatus>()
+        .register::<StorageTier>()
+        .register::<AssetPreferences>()
         .register::<CreateAssetRequest>()
+        .register::<BatchAssetsRequest>()
+        .register::<BatchLinkAssetsRequest>()
+        .register::<BatchItemError>()
+        .register::<BatchItemResult>()
+        .register::<BatchAssetResult>()
+        .register::<BatchGetAssetsResponse>()
+        .register::<BatchItemsResponse>()
+        .register::<AssetGrant>()
+        .register::<CreateAssetGrantRequest>()
+        .register::<CreateAssetLinkRequest>()
+        .register::<AssetLink>()
+        .register::<ListAssetGrantsResponse>()
+        .register::<SharedAssetsResponse>()
+        .register::<CreateUploadRequest>()
+        .register::<UploadSession>()
 }
 
 #[cfg(test)]
@@ -27,7 +53,28 @@ mod tests {
             .into_unsorted_iter()
             .map(|ndt| ndt.name.to_string())
             .collect();
-        for expected in ["Asset", "CreateAssetRequest"] {
+        for expected in [
+            "Asset",
+            "ScanStatus",
+            "StorageTier",
+            "AssetPreferences",
+            "CreateAssetRequest",
+            "BatchAssetsRequest",
+            "BatchLinkAssetsRequest",
+            "BatchItemError",
+            "BatchItemResult",
+            "BatchAssetResult",
+            "BatchGetAssetsResponse",
+            "BatchItemsResponse",
+            "AssetGrant",
+            "CreateAssetGrantRequest",
+            "CreateAssetLinkRequest",
+            "AssetLink",
+            "ListAssetGrantsResponse",
+            "SharedAssetsResponse",
+            "CreateUploadRequest",
+            "UploadSession",
+        ] {
             assert!(
                 names.iter().any(|n| n == expected),
                 "missing {expected} from collection: {names:?}"
@@ -30,6 +30,44 @@ impl std::fmt::Display for Role {
     }
 }
 
+/// How much product analytics the backend may send about a user.
+///
+/// Carried in [`Claims`] so every service can honour it without a
+/// database round trip. Tokens minted before the field existed
+/// deserialize as [`AnalyticsConsent::Off`]: when in doubt, send nothing.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+#[serde(rename_all = "snake_case")]
+pub enum AnalyticsConsent {
+    /// No events at all.
+    #[default]
+    Off,
+    /// Events keyed by a salted hash of the user ID, so usage can be
+    /// counted per user without being tied back to the account.
+    Anonymous,
+    /// Events keyed by the user ID.
+    Identified,
+}
+
+impl AnalyticsConsent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnalyticsConsent::Off => "off",
+            AnalyticsConsent::Anonymous => "anonymous",
+            AnalyticsConsent::Identified => "identified",
+        }
+    }
+}
+
+/// RFC 8693 `act` claim: who is actually holding a token issued for
+/// someone else.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct Actor {
+    /// User id of the support staff member impersonating `sub`.
+    pub sub: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[cfg_attr(feature = "specta", derive(Type))]
 pub struct Claims {
@@ -52,4 +90,43 @@ pub struct Claims {
     /// after hashing (see refresh-token rotation).
     #[serde(default)]
     pub jti: String,
+    #[serde(default)]
+    pub analytics: AnalyticsConsent,
+    /// The user's token version when the token was minted. Signing out
+    /// everywhere bumps the stored version, and tokens carrying an older
+    /// one are refused from then on even though they haven't expired.
+    #[serde(default)]
+    pub token_version: i32,
+    /// Set only on impersonation tokens, which are short-lived, come
+    /// without a refresh token, and have every request they make
+    /// recorded for the user to see.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<Actor>,
+}
+
+impl Claims {
+    pub fn is_impersonated(&self) -> bool {
+        self.act.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn claims_without_analytics_fail_closed() {
+        let claims: Claims = serde_json::from_value(serde_json::json!({
+            "sub": "u",
+            "email": "u@example.com",
+            "exp": 0,
+            "iat": 0,
+            "token_type": "access",
+            "role": "Free",
+        }))
+        .unwrap();
+        assert_eq!(claims.analytics, AnalyticsConsent::Off);
+        // Tokens minted before versions existed match a never-bumped user.
+        assert_eq!(claims.token_version, 0);
+    }
 }
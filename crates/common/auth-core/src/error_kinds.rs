@@ -21,11 +21,24 @@ pub const EMAIL_NOT_VERIFIED: &str = "email_not_verified";
 /// this value to surface the correct UX.
 pub const OAUTH_EMAIL_CONFLICT: &str = "oauth_email_conflict";
 
+/// The account is scheduled for deletion and locked: no new sessions are
+/// minted for it until the deletion completes.
+pub const ACCOUNT_PENDING_DELETION: &str = "account_pending_deletion";
+
+/// The caller holds a support impersonation token, which is refused by
+/// every route that touches the user's credentials, consent, or account.
+pub const IMPERSONATION_FORBIDDEN: &str = "impersonation_forbidden";
+
 /// Caller exceeded a rate limit (failed-auth limiter, resend-cooldown,
 /// etc.). Includes a `Retry-After`-style hint in the response message
 /// when applicable.
 pub const RATE_LIMITED: &str = "rate_limited";
 
+/// `/auth/login-token/wait` held the request for its full window and
+/// the web sign-in hasn't associated the challenge yet. Not a failure:
+/// call again straight away.
+pub const LOGIN_TOKEN_PENDING: &str = "login_token_pending";
+
 /// Catch-all for unexpected server-side failures. Detail is logged but
 /// never echoed to the client.
 pub const INTERNAL_ERROR: &str = "internal_error";
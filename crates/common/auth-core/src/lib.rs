@@ -16,15 +16,17 @@ pub mod provider;
 pub mod requests;
 pub mod responses;
 
-pub use claims::{Claims, Role};
+pub use claims::{Actor, AnalyticsConsent, Claims, Role};
 pub use provider::Provider;
 pub use requests::{
     AppleIdTokenLoginRequest, AppleNativeUser, AssociateLoginTokenRequest, CheckEmailRequest,
     GoogleIdTokenLoginRequest, LoginByLoginTokenRequest, LoginRequest,
-    MobileThirdPartyAuthUrlRequest, RegisterRequest, ThirdPartyAuthUrlRequest, VerifyEmailRequest,
+    LoopbackThirdPartyAuthUrlRequest, MobileThirdPartyAuthUrlRequest, RegisterRequest,
+    ThirdPartyAuthUrlRequest, UpdateAnalyticsConsentRequest, VerifyEmailRequest,
 };
 pub use responses::{
-    AuthErrorResponse, AuthSuccessResponse, CheckEmailResponse, CheckEmailStatus,
+    AccountDeletionResponse, AuthErrorResponse, AuthSuccessResponse, CheckEmailResponse,
+    CheckEmailStatus, ImpersonatedRequest, ImpersonationSessionInfo, ImpersonationsResponse,
     ThirdPartyAuthUrlResponse, TokenResponse, UserInfo, UserResponse,
 };
 
@@ -35,13 +37,16 @@ pub use responses::{
 pub fn type_collection() -> specta::Types {
     specta::Types::default()
         .register::<Claims>()
+        .register::<Actor>()
         .register::<Role>()
+        .register::<AnalyticsConsent>()
         .register::<Provider>()
         .register::<LoginRequest>()
         .register::<RegisterRequest>()
         .register::<ThirdPartyAuthUrlRequest>()
         .register::<ThirdPartyAuthUrlResponse>()
         .register::<MobileThirdPartyAuthUrlRequest>()
+        .register::<LoopbackThirdPartyAuthUrlRequest>()
         .register::<GoogleIdTokenLoginRequest>()
         .register::<AppleIdTokenLoginRequest>()
         .register::<AppleNativeUser>()
@@ -51,11 +56,16 @@ pub fn type_collection() -> specta::Types {
         .register::<CheckEmailResponse>()
         .register::<CheckEmailStatus>()
         .register::<VerifyEmailRequest>()
+        .register::<UpdateAnalyticsConsentRequest>()
         .register::<TokenResponse>()
         .register::<UserInfo>()
         .register::<UserResponse>()
         .register::<AuthSuccessResponse>()
         .register::<AuthErrorResponse>()
+        .register::<AccountDeletionResponse>()
+        .register::<ImpersonatedRequest>()
+        .register::<ImpersonationSessionInfo>()
+        .register::<ImpersonationsResponse>()
 }
 
 #[cfg(test)]
@@ -70,13 +80,16 @@ mod tests {
             .collect();
         for expected in [
             "Claims",
+            "Actor",
             "Role",
+            "AnalyticsConsent",
             "Provider",
             "LoginRequest",
             "RegisterRequest",
             "ThirdPartyAuthUrlRequest",
             "ThirdPartyAuthUrlResponse",
             "MobileThirdPartyAuthUrlRequest",
+            "LoopbackThirdPartyAuthUrlRequest",
             "GoogleIdTokenLoginRequest",
             "AppleIdTokenLoginRequest",
             "AppleNativeUser",
@@ -86,11 +99,16 @@ mod tests {
             "CheckEmailResponse",
             "CheckEmailStatus",
             "VerifyEmailRequest",
+            "UpdateAnalyticsConsentRequest",
             "TokenResponse",
             "UserInfo",
             "UserResponse",
             "AuthSuccessResponse",
             "AuthErrorResponse",
+            "AccountDeletionResponse",
+            "ImpersonatedRequest",
+            "ImpersonationSessionInfo",
+            "ImpersonationsResponse",
         ] {
             assert!(
                 names.iter().any(|n| n == expected),
@@ -5,8 +5,8 @@ use specta::Type;
 
 /// Third-party identity provider supported by the auth service.
 ///
-/// Wire format is lowercase JSON (`"google"`, `"github"`, `"apple"`)
-/// so it reads naturally in URLs and request bodies.
+/// Wire format is lowercase JSON (`"google"`, `"github"`, `"apple"`,
+/// `"microsoft"`) so it reads naturally in URLs and request bodies.
 #[derive(Debug, Serialize, Deserialize, Copy, Clone, Eq, PartialEq, Hash)]
 #[cfg_attr(feature = "specta", derive(Type))]
 #[serde(rename_all = "lowercase")]
@@ -14,6 +14,7 @@ pub enum Provider {
     Google,
     Github,
     Apple,
+    Microsoft,
 }
 
 impl Provider {
@@ -22,6 +23,7 @@ impl Provider {
             Provider::Google => "google",
             Provider::Github => "github",
             Provider::Apple => "apple",
+            Provider::Microsoft => "microsoft",
         }
     }
 }
@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 #[cfg(feature = "specta")]
 use specta::Type;
 
-use crate::Provider;
+use crate::{AnalyticsConsent, Provider};
 
 /// Request body for `POST /auth/login`.
 ///
@@ -73,6 +73,22 @@ pub struct MobileThirdPartyAuthUrlRequest {
     pub code_challenge_method: String,
 }
 
+/// Request body for `POST /auth/oauth/loopback/url`.
+///
+/// Desktop sign-in without the hosted web page (RFC 8252 §7.3): the
+/// client listens on a loopback port and asks for an authorisation URL
+/// whose provider redirect lands there. `redirect_uri` must be
+/// `http://127.0.0.1:{port}/…` or `http://[::1]:{port}/…`. The client
+/// then posts the `code` and `state` it receives to `POST /auth/login`
+/// as a [`LoginRequest::ThirdParty`], and the backend exchanges the
+/// code against the same redirect URI.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct LoopbackThirdPartyAuthUrlRequest {
+    pub provider: Provider,
+    pub redirect_uri: String,
+}
+
 /// Request body for `POST /auth/oauth/google/id-token`.
 ///
 /// Used by mobile after a native Google Sign-In flow: the client sends
@@ -130,7 +146,8 @@ pub struct AppleNativeUser {
     pub last_name: Option<String>,
 }
 
-/// Request body for `POST /auth/login-token/exchange`.
+/// Request body for `POST /auth/login-token/exchange` and
+/// `POST /auth/login-token/wait`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "specta", derive(Type))]
 pub struct LoginByLoginTokenRequest {
@@ -158,6 +175,13 @@ pub struct VerifyEmailRequest {
     pub token: String,
 }
 
+/// Request body for `PUT /auth/analytics-consent`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct UpdateAnalyticsConsentRequest {
+    pub consent: AnalyticsConsent,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
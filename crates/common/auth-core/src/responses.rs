@@ -5,7 +5,7 @@ use specta::Type;
 #[cfg(feature = "specta")]
 use specta_typescript::BigInt;
 
-use crate::{Provider, Role};
+use crate::{AnalyticsConsent, Provider, Role};
 
 /// Bearer-mode session response, used by non-browser clients (desktop /
 /// mobile) that send `Authorization: Bearer …` and need the tokens in
@@ -31,6 +31,8 @@ pub struct UserInfo {
     pub display_name: Option<String>,
     pub email_verified: bool,
     pub role: Role,
+    #[serde(default)]
+    pub analytics: AnalyticsConsent,
 }
 
 /// Cookie-mode session response. Tokens are delivered via `Set-Cookie`
@@ -84,6 +86,54 @@ pub struct CheckEmailResponse {
     pub provider: Option<Provider>,
 }
 
+/// Response body for `DELETE /auth/account`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct AccountDeletionResponse {
+    /// When the account's data is erased, in seconds since the Unix
+    /// epoch. The account stays locked until then.
+    #[cfg_attr(feature = "specta", specta(type = BigInt))]
+    pub scheduled_for: i64,
+}
+
+/// One request made while support staff were acting as the caller.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct ImpersonatedRequest {
+    pub method: String,
+    /// Route template, e.g. `/threads/{thread_id}`.
+    pub path: String,
+    /// HTTP status the request was answered with.
+    pub status: u16,
+    /// Seconds since the Unix epoch.
+    #[cfg_attr(feature = "specta", specta(type = BigInt))]
+    pub at: i64,
+}
+
+/// A support session in which staff acted as the caller.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct ImpersonationSessionInfo {
+    pub id: String,
+    /// Why the session was opened, as entered by the staff member.
+    pub reason: String,
+    /// Seconds since the Unix epoch.
+    #[cfg_attr(feature = "specta", specta(type = BigInt))]
+    pub started_at: i64,
+    /// When the session's token stopped working, in seconds since the
+    /// Unix epoch.
+    #[cfg_attr(feature = "specta", specta(type = BigInt))]
+    pub expires_at: i64,
+    pub requests: Vec<ImpersonatedRequest>,
+}
+
+/// Response body for `GET /auth/impersonations`, newest session first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct ImpersonationsResponse {
+    pub sessions: Vec<ImpersonationSessionInfo>,
+}
+
 /// JSON error body returned by the auth service on non-2xx responses.
 ///
 /// Mirrors the shape used by `be-update-service` and `be-activity-service`
@@ -172,6 +222,7 @@ mod tests {
                 display_name: Some("U".into()),
                 email_verified: true,
                 role: crate::Role::Free,
+                analytics: AnalyticsConsent::Anonymous,
             },
         });
         let json = serde_json::to_string(&payload).unwrap();
@@ -10,11 +10,12 @@
 //! # Resolution
 //!
 //! The only loader currently exposed is [`LlmConfig::from_env`]. The full
-//! [`Provider`] enum models OpenAI, Anthropic, Google, Bedrock, and a generic
-//! OpenAI-compatible kind so that adding new providers later is a code change
-//! to the consumers of this crate (e.g. `be-thread-service`) rather than a
-//! schema change. The env loader currently only emits `OpenAI` or
-//! `OpenAiCompatible` — the schema is forward-compatible with the rest.
+//! [`Provider`] enum models OpenAI, Azure OpenAI, OpenRouter, Anthropic,
+//! Google, Bedrock, and a generic OpenAI-compatible kind so that adding new
+//! providers later is a code change to the consumers of this crate (e.g.
+//! `be-thread-service`) rather than a schema change. The env loader doesn't
+//! emit `Anthropic`, `Google` or `Bedrock` yet — the schema is
+//! forward-compatible with them.
 //!
 //! # Secrets
 //!
@@ -28,12 +29,12 @@ mod provider;
 mod redacted;
 mod validate;
 
-pub use load::{ConfigSource, from_env};
+pub use load::{ConfigSource, DEFAULT_AZURE_API_VERSION, DEFAULT_OPENROUTER_BASE_URL, from_env};
 pub use provider::{
     AwsCreds, GoogleCreds, ModelRef, Provider, ProviderId, ProviderIdError, ProviderKind,
     RequestOverrides, Roles, validate_provider_id,
 };
-pub use redacted::{RedactedLlmConfig, RedactedProvider};
+pub use redacted::{RedactedLlmConfig, RedactedProvider, is_loopback};
 pub use validate::ConfigError;
 
 use std::collections::HashMap;
@@ -19,17 +19,29 @@ const ENV_OPENAI_KEY: &str = "OPENAI_API_KEY";
 const ENV_OPENAI_ORG: &str = "EURORA_OPENAI_ORG";
 const ENV_LLM_BASE_URL: &str = "EURORA_LLM_BASE_URL";
 const ENV_LLM_API_KEY: &str = "EURORA_LLM_API_KEY";
+const ENV_LLM_HEADERS: &str = "EURORA_LLM_HEADERS";
+const ENV_AZURE_API_VERSION: &str = "EURORA_AZURE_API_VERSION";
+const ENV_OPENROUTER_APP_URL: &str = "EURORA_OPENROUTER_APP_URL";
+const ENV_OPENROUTER_APP_TITLE: &str = "EURORA_OPENROUTER_APP_TITLE";
 const ENV_CHAT_MODEL: &str = "EURORA_CHAT_MODEL";
 const ENV_TITLE_MODEL: &str = "EURORA_TITLE_MODEL";
 const ENV_VISION_MODEL: &str = "EURORA_VISION_MODEL";
 
+/// Azure OpenAI `api-version` used when `EURORA_AZURE_API_VERSION` is unset:
+/// the latest GA data-plane version.
+pub const DEFAULT_AZURE_API_VERSION: &str = "2024-10-21";
+
+/// API base of an `openrouter` provider without `EURORA_LLM_BASE_URL`.
+pub const DEFAULT_OPENROUTER_BASE_URL: &str = "https://openrouter.ai/api/v1";
+
 /// Load configuration from environment variables.
 ///
 /// # Variable surface
 ///
 /// Common to every kind:
 ///
-/// - `EURORA_LLM_KIND` — `openai` (default) or `openai_compatible`. Other
+/// - `EURORA_LLM_KIND` — `openai` (default), `azure_openai`, `openrouter`
+///   or `openai_compatible`. Other
 ///   kinds defined in [`crate::Provider`] (`anthropic`, `google`, `bedrock`)
 ///   are recognised by the schema but rejected here until their runtime
 ///   wiring lands.
@@ -44,15 +56,35 @@ const ENV_VISION_MODEL: &str = "EURORA_VISION_MODEL";
 /// - `EURORA_LLM_BASE_URL` — optional override for the API base.
 /// - `EURORA_OPENAI_ORG` — optional `OpenAI-Organization` value.
 ///
+/// `azure_openai`:
+///
+/// - `EURORA_LLM_BASE_URL` — required; the resource endpoint, e.g.
+///   `https://acme.openai.azure.com`. The model variables name deployments.
+/// - `EURORA_LLM_API_KEY` — required.
+/// - `EURORA_AZURE_API_VERSION` — optional; defaults to
+///   [`DEFAULT_AZURE_API_VERSION`].
+/// - `EURORA_LLM_HEADERS` — optional, as for `openai_compatible`.
+///
+/// `openrouter`:
+///
+/// - `EURORA_LLM_API_KEY` — required.
+/// - `EURORA_LLM_BASE_URL` — optional; defaults to
+///   [`DEFAULT_OPENROUTER_BASE_URL`].
+/// - `EURORA_OPENROUTER_APP_URL` / `EURORA_OPENROUTER_APP_TITLE` — optional;
+///   sent as `HTTP-Referer` / `X-Title` to attribute usage to the app.
+/// - `EURORA_LLM_HEADERS` — optional, as for `openai_compatible`.
+///
 /// `openai_compatible`:
 ///
 /// - `EURORA_LLM_BASE_URL` — required.
 /// - `EURORA_LLM_API_KEY` — optional; many local servers don't need one.
+/// - `EURORA_LLM_HEADERS` — optional JSON object of extra headers sent with
+///   every request, e.g. `{"X-Tenant": "acme"}` for a gateway.
 ///
 /// # Single-provider shape
 ///
 /// The env loader produces a config with exactly one provider, named after
-/// its kind (`openai` or `openai_compatible`), and assigns every role to that
+/// its kind (e.g. `openai` or `openai_compatible`), and assigns every role to that
 /// provider. Multi-provider configurations require a future config-file
 /// loader; the schema in this crate already supports them.
 pub fn from_env() -> Result<(LlmConfig, ConfigSource), ConfigError> {
@@ -92,6 +124,8 @@ fn parse_kind() -> Result<ProviderKind, ConfigError> {
     let raw = optional_env(ENV_KIND).unwrap_or_else(|| "openai".to_string());
     match raw.as_str() {
         "openai" => Ok(ProviderKind::OpenAI),
+        "azure_openai" => Ok(ProviderKind::AzureOpenAI),
+        "openrouter" => Ok(ProviderKind::OpenRouter),
         "openai_compatible" => Ok(ProviderKind::OpenAiCompatible),
         "anthropic" => Err(ConfigError::KindNotYetWired { kind: "anthropic" }),
         "google" => Err(ConfigError::KindNotYetWired { kind: "google" }),
@@ -99,7 +133,7 @@ fn parse_kind() -> Result<ProviderKind, ConfigError> {
         _ => Err(ConfigError::UnknownEnumValue {
             name: ENV_KIND,
             value: raw,
-            expected: "openai | openai_compatible",
+            expected: "openai | azure_openai | openrouter | openai_compatible",
         }),
     }
 }
@@ -123,10 +157,39 @@ fn build_provider(kind: ProviderKind) -> Result<Provider, ConfigError> {
             Ok(Provider::OpenAiCompatible {
                 base_url,
                 api_key,
-                headers: HashMap::new(),
+                headers: parse_headers(ENV_LLM_HEADERS)?,
                 overrides: Default::default(),
             })
         }
+        ProviderKind::OpenRouter => {
+            let base_url = parse_optional_url(ENV_LLM_BASE_URL)?;
+            let api_key = SecretString::from(require_env(ENV_LLM_API_KEY)?);
+            let mut headers = parse_headers(ENV_LLM_HEADERS)?;
+            if let Some(app_url) = optional_env(ENV_OPENROUTER_APP_URL) {
+                headers.insert("HTTP-Referer".to_string(), app_url);
+            }
+            if let Some(app_title) = optional_env(ENV_OPENROUTER_APP_TITLE) {
+                headers.insert("X-Title".to_string(), app_title);
+            }
+            Ok(Provider::OpenRouter {
+                base_url,
+                api_key,
+                headers,
+            })
+        }
+        ProviderKind::AzureOpenAI => {
+            let endpoint =
+                parse_optional_url(ENV_LLM_BASE_URL)?.ok_or(ConfigError::AzureEndpointRequired)?;
+            let api_key = SecretString::from(require_env(ENV_LLM_API_KEY)?);
+            let api_version = optional_env(ENV_AZURE_API_VERSION)
+                .unwrap_or_else(|| DEFAULT_AZURE_API_VERSION.to_string());
+            Ok(Provider::AzureOpenAI {
+                endpoint,
+                api_key,
+                api_version,
+                headers: parse_headers(ENV_LLM_HEADERS)?,
+            })
+        }
         ProviderKind::Anthropic | ProviderKind::Google | ProviderKind::Bedrock => {
             // parse_kind() already rejects these — keep the match exhaustive.
             Err(ConfigError::KindNotYetWired {
@@ -158,3 +221,10 @@ fn parse_optional_url(name: &'static str) -> Result<Option<url::Url>, ConfigErro
         .map(Some)
         .map_err(|source| ConfigError::InvalidUrl { name, source })
 }
+
+fn parse_headers(name: &'static str) -> Result<HashMap<String, String>, ConfigError> {
+    let Some(raw) = optional_env(name) else {
+        return Ok(HashMap::new());
+    };
+    serde_json::from_str(&raw).map_err(|source| ConfigError::InvalidHeaders { name, source })
+}
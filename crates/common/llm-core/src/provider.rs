@@ -45,6 +45,25 @@ pub enum Provider {
         region: String,
         credentials: AwsCreds,
     },
+    /// An Azure OpenAI resource. `endpoint` is the resource URL (e.g.
+    /// `https://acme.openai.azure.com`) and the model named in a
+    /// [`ModelRef`] is the deployment name.
+    AzureOpenAI {
+        endpoint: Url,
+        api_key: SecretString,
+        api_version: String,
+        headers: HashMap<String, String>,
+    },
+    /// OpenRouter, or another gateway with its API. Differs from
+    /// [`Provider::OpenAiCompatible`] in what it knows about the gateway:
+    /// `base_url` defaults to [`crate::DEFAULT_OPENROUTER_BASE_URL`], the
+    /// key is required, and model listings carry prices. `headers` holds its attribution headers (`HTTP-Referer`,
+    /// `X-Title`) along with any others.
+    OpenRouter {
+        base_url: Option<Url>,
+        api_key: SecretString,
+        headers: HashMap<String, String>,
+    },
     /// Any server speaking the OpenAI Chat Completions wire format. Used for
     /// Ollama (with its OpenAI shim), LM Studio, vLLM, llama.cpp's HTTP
     /// server, Groq, OpenRouter, etc.
@@ -64,6 +83,8 @@ impl Provider {
             Provider::Anthropic { .. } => ProviderKind::Anthropic,
             Provider::Google { .. } => ProviderKind::Google,
             Provider::Bedrock { .. } => ProviderKind::Bedrock,
+            Provider::AzureOpenAI { .. } => ProviderKind::AzureOpenAI,
+            Provider::OpenRouter { .. } => ProviderKind::OpenRouter,
             Provider::OpenAiCompatible { .. } => ProviderKind::OpenAiCompatible,
         }
     }
@@ -77,6 +98,10 @@ pub enum ProviderKind {
     Anthropic,
     Google,
     Bedrock,
+    #[serde(rename = "azure_openai")]
+    AzureOpenAI,
+    #[serde(rename = "openrouter")]
+    OpenRouter,
     OpenAiCompatible,
 }
 
@@ -87,6 +112,8 @@ impl ProviderKind {
             ProviderKind::Anthropic => "anthropic",
             ProviderKind::Google => "google",
             ProviderKind::Bedrock => "bedrock",
+            ProviderKind::AzureOpenAI => "azure_openai",
+            ProviderKind::OpenRouter => "openrouter",
             ProviderKind::OpenAiCompatible => "openai_compatible",
         }
     }
@@ -258,6 +285,8 @@ mod tests {
             ProviderKind::Anthropic,
             ProviderKind::Google,
             ProviderKind::Bedrock,
+            ProviderKind::AzureOpenAI,
+            ProviderKind::OpenRouter,
             ProviderKind::OpenAiCompatible,
         ] {
             validate_provider_id(kind.as_str())
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
-use url::Url;
+use url::{Host, Url};
 
 use crate::{LlmConfig, Provider, ProviderId, Roles};
 
@@ -43,6 +43,19 @@ pub enum RedactedProvider {
         region: String,
         credentials: RedactedAwsCreds,
     },
+    #[serde(rename = "azure_openai")]
+    AzureOpenAI {
+        endpoint: Url,
+        has_api_key: bool,
+        api_version: String,
+        header_names: Vec<String>,
+    },
+    #[serde(rename = "openrouter")]
+    OpenRouter {
+        base_url: Option<Url>,
+        has_api_key: bool,
+        header_names: Vec<String>,
+    },
     #[serde(rename = "openai_compatible")]
     OpenAiCompatible {
         base_url: Url,
@@ -127,6 +140,24 @@ impl From<&Provider> for RedactedProvider {
                     },
                 },
             },
+            Provider::AzureOpenAI {
+                endpoint,
+                api_version,
+                headers,
+                ..
+            } => RedactedProvider::AzureOpenAI {
+                endpoint: endpoint.clone(),
+                has_api_key: true,
+                api_version: api_version.clone(),
+                header_names: headers.keys().cloned().collect(),
+            },
+            Provider::OpenRouter {
+                base_url, headers, ..
+            } => RedactedProvider::OpenRouter {
+                base_url: base_url.clone(),
+                has_api_key: true,
+                header_names: headers.keys().cloned().collect(),
+            },
             Provider::OpenAiCompatible {
                 base_url,
                 api_key,
@@ -141,3 +172,129 @@ impl From<&Provider> for RedactedProvider {
         }
     }
 }
+
+impl RedactedProvider {
+    /// Whether requests to this provider stay on the backend's machine.
+    /// Only an explicit loopback `base_url` counts; hosted defaults and
+    /// cloud SDK providers are remote.
+    pub fn is_local(&self) -> bool {
+        let base_url = match self {
+            RedactedProvider::OpenAI { base_url, .. }
+            | RedactedProvider::Anthropic { base_url, .. }
+            | RedactedProvider::OpenRouter { base_url, .. } => base_url.as_ref(),
+            RedactedProvider::OpenAiCompatible { base_url, .. } => Some(base_url),
+            RedactedProvider::AzureOpenAI { endpoint, .. } => Some(endpoint),
+            RedactedProvider::Google { .. } | RedactedProvider::Bedrock { .. } => None,
+        };
+        base_url.is_some_and(is_loopback)
+    }
+}
+
+impl RedactedLlmConfig {
+    /// Providers behind any role that would send prompts off the
+    /// backend's machine. A role pointing at an unknown provider counts as
+    /// remote.
+    pub fn remote_providers(&self) -> Vec<&ProviderId> {
+        let roles = [
+            Some(&self.roles.chat),
+            Some(&self.roles.title),
+            self.roles.vision.as_ref(),
+        ];
+        let mut remote: Vec<&ProviderId> = roles
+            .into_iter()
+            .flatten()
+            .map(|role| &role.provider)
+            .filter(|id| {
+                !self
+                    .providers
+                    .get(*id)
+                    .is_some_and(RedactedProvider::is_local)
+            })
+            .collect();
+        remote.sort();
+        remote.dedup();
+        remote
+    }
+}
+
+/// `localhost`, `*.localhost` or a loopback IP.
+pub fn is_loopback(url: &Url) -> bool {
+    match url.host() {
+        Some(Host::Domain(domain)) => {
+            let domain = domain.to_ascii_lowercase();
+            domain == "localhost" || domain.ends_with(".localhost")
+        }
+        Some(Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ModelRef;
+
+    fn compatible(base_url: &str) -> RedactedProvider {
+        RedactedProvider::OpenAiCompatible {
+            base_url: Url::parse(base_url).unwrap(),
+            has_api_key: false,
+            header_names: Vec::new(),
+            has_overrides: false,
+        }
+    }
+
+    fn model(provider: &str) -> ModelRef {
+        ModelRef {
+            provider: provider.to_string(),
+            model: "m".to_string(),
+        }
+    }
+
+    #[test]
+    fn only_loopback_base_urls_are_local() {
+        assert!(compatible("http://localhost:11434/v1").is_local());
+        assert!(compatible("http://127.0.0.1:11434/v1").is_local());
+        assert!(compatible("http://[::1]:11434/v1").is_local());
+        assert!(!compatible("http://ollama.lan:11434/v1").is_local());
+        assert!(
+            !RedactedProvider::OpenAI {
+                has_api_key: true,
+                base_url: None,
+                organization: None,
+            }
+            .is_local()
+        );
+    }
+
+    #[test]
+    fn remote_providers_covers_every_role() {
+        let config = RedactedLlmConfig {
+            providers: HashMap::from([
+                (
+                    "ollama".to_string(),
+                    compatible("http://localhost:11434/v1"),
+                ),
+                (
+                    "cloud".to_string(),
+                    compatible("https://api.example.com/v1"),
+                ),
+            ]),
+            roles: Roles {
+                chat: model("ollama"),
+                title: model("ollama"),
+                vision: Some(model("cloud")),
+            },
+        };
+        assert_eq!(config.remote_providers(), ["cloud"]);
+
+        let config = RedactedLlmConfig {
+            roles: Roles {
+                vision: Some(model("missing")),
+                ..config.roles
+            },
+            ..config
+        };
+        assert_eq!(config.remote_providers(), ["missing"]);
+    }
+}
@@ -36,7 +36,8 @@ pub enum ConfigError {
 
     #[error(
         "provider kind `{kind}` is recognised in the schema but not yet supported by the env loader; \
-         set `EURORA_LLM_KIND=openai` (default) or `openai_compatible`"
+         set `EURORA_LLM_KIND=openai` (default), `azure_openai`, `openrouter` or \
+         `openai_compatible`"
     )]
     KindNotYetWired { kind: &'static str },
 
@@ -46,6 +47,21 @@ pub enum ConfigError {
     )]
     OpenAiCompatibleBaseUrlRequired,
 
+    #[error(
+        "`EURORA_LLM_BASE_URL` is required when `EURORA_LLM_KIND=azure_openai` (the resource \
+         endpoint, e.g. https://acme.openai.azure.com)"
+    )]
+    AzureEndpointRequired,
+
+    #[error(
+        "environment variable `{name}` is not a JSON object of header names to values: {source}"
+    )]
+    InvalidHeaders {
+        name: &'static str,
+        #[source]
+        source: serde_json::Error,
+    },
+
     #[error("base url `{url}` for provider `{provider}` must use http or https scheme")]
     InvalidScheme { provider: ProviderId, url: url::Url },
 }
@@ -58,10 +74,16 @@ pub(crate) fn validate(config: &LlmConfig) -> Result<(), ConfigError> {
     }
 
     for (id, provider) in &config.providers {
-        if let crate::Provider::OpenAiCompatible { base_url, .. } = provider
-            && base_url.scheme() != "http"
-            && base_url.scheme() != "https"
-        {
+        let base_url = match provider {
+            crate::Provider::OpenAiCompatible { base_url, .. } => base_url,
+            crate::Provider::AzureOpenAI { endpoint, .. } => endpoint,
+            crate::Provider::OpenRouter {
+                base_url: Some(base_url),
+                ..
+            } => base_url,
+            _ => continue,
+        };
+        if base_url.scheme() != "http" && base_url.scheme() != "https" {
             return Err(ConfigError::InvalidScheme {
                 provider: id.clone(),
                 url: base_url.clone(),
@@ -17,6 +17,10 @@ const ALL_VARS: &[&str] = &[
     "EURORA_OPENAI_ORG",
     "EURORA_LLM_BASE_URL",
     "EURORA_LLM_API_KEY",
+    "EURORA_LLM_HEADERS",
+    "EURORA_AZURE_API_VERSION",
+    "EURORA_OPENROUTER_APP_URL",
+    "EURORA_OPENROUTER_APP_TITLE",
     "EURORA_CHAT_MODEL",
     "EURORA_TITLE_MODEL",
     "EURORA_VISION_MODEL",
@@ -137,6 +141,96 @@ fn openai_compatible_accepts_keyless_local_server() {
     assert!(api_key.is_none());
 }
 
+#[test]
+fn openai_compatible_reads_custom_headers() {
+    let _g = env_lock();
+    clear_env();
+    set("EURORA_LLM_KIND", "openai_compatible");
+    set("EURORA_LLM_BASE_URL", "https://gateway.example.com/v1");
+    set("EURORA_LLM_HEADERS", r#"{"X-Tenant": "acme"}"#);
+    set("EURORA_CHAT_MODEL", "llama3.2");
+
+    let (config, _) = LlmConfig::from_env().expect("loads");
+    let Provider::OpenAiCompatible { headers, .. } = &config.providers["openai_compatible"] else {
+        panic!("expected OpenAiCompatible");
+    };
+    assert_eq!(headers.get("X-Tenant").map(String::as_str), Some("acme"));
+
+    set("EURORA_LLM_HEADERS", "X-Tenant: acme");
+    let err = LlmConfig::from_env().expect_err("headers must be JSON");
+    assert!(matches!(
+        err,
+        ConfigError::InvalidHeaders {
+            name: "EURORA_LLM_HEADERS",
+            ..
+        }
+    ));
+}
+
+#[test]
+fn azure_openai_uses_endpoint_key_and_api_version() {
+    let _g = env_lock();
+    clear_env();
+    set("EURORA_LLM_KIND", "azure_openai");
+    set("EURORA_CHAT_MODEL", "prod-gpt-4o");
+
+    let err = LlmConfig::from_env().expect_err("missing endpoint");
+    assert!(matches!(err, ConfigError::AzureEndpointRequired));
+
+    set("EURORA_LLM_BASE_URL", "https://acme.openai.azure.com");
+    let err = LlmConfig::from_env().expect_err("missing key");
+    assert!(matches!(err, ConfigError::MissingEnv("EURORA_LLM_API_KEY")));
+
+    set("EURORA_LLM_API_KEY", "azure-key");
+    let (config, _) = LlmConfig::from_env().expect("loads");
+    let Provider::AzureOpenAI {
+        endpoint,
+        api_key,
+        api_version,
+        ..
+    } = &config.providers["azure_openai"]
+    else {
+        panic!("expected AzureOpenAI");
+    };
+    assert_eq!(endpoint.as_str(), "https://acme.openai.azure.com/");
+    assert_eq!(api_key.expose_secret(), "azure-key");
+    assert_eq!(api_version, llm_core::DEFAULT_AZURE_API_VERSION);
+    assert_eq!(config.roles.chat.model, "prod-gpt-4o");
+
+    set("EURORA_AZURE_API_VERSION", "2025-04-01-preview");
+    let (config, _) = LlmConfig::from_env().expect("loads");
+    let Provider::AzureOpenAI { api_version, .. } = &config.providers["azure_openai"] else {
+        panic!("expected AzureOpenAI");
+    };
+    assert_eq!(api_version, "2025-04-01-preview");
+}
+
+#[test]
+fn openrouter_sends_attribution_headers() {
+    let _g = env_lock();
+    clear_env();
+    set("EURORA_LLM_KIND", "openrouter");
+    set("EURORA_LLM_API_KEY", "sk-or-test");
+    set("EURORA_OPENROUTER_APP_URL", "https://eurora-labs.com");
+    set("EURORA_OPENROUTER_APP_TITLE", "Eurora");
+    set("EURORA_CHAT_MODEL", "anthropic/claude-sonnet-4.5");
+
+    let (config, _) = LlmConfig::from_env().expect("loads");
+    let Provider::OpenRouter {
+        base_url, headers, ..
+    } = &config.providers["openrouter"]
+    else {
+        panic!("expected OpenRouter");
+    };
+    assert!(base_url.is_none());
+    assert_eq!(
+        headers.get("HTTP-Referer").map(String::as_str),
+        Some("https://eurora-labs.com")
+    );
+    assert_eq!(headers.get("X-Title").map(String::as_str), Some("Eurora"));
+    assert!(!config.redacted().providers["openrouter"].is_local());
+}
+
 #[test]
 fn missing_chat_model_is_an_error() {
     let _g = env_lock();
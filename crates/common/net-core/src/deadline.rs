@@ -0,0 +1,75 @@
+//! The deadline of the HTTP request being served.
+//!
+//! The backend runs each handler inside [`scope`] with the route's
+//! deadline. Code that calls out (the LLM providers, OAuth, the web
+//! tools) caps its own timeout with [`cap`], so a provider call gives up
+//! when the request would, with a transport error, rather than being cut
+//! off mid-flight by the `504`.
+//!
+//! The deadline is task-local: work a handler spawns (a chat turn, a
+//! background job) runs outside it and keeps its own timeouts.
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+tokio::task_local! {
+    static DEADLINE: Instant;
+}
+
+/// Run `fut` with `deadline` as the current request's deadline.
+pub async fn scope<F: Future>(deadline: Instant, fut: F) -> F::Output {
+    DEADLINE.scope(deadline, fut).await
+}
+
+/// Time left before the current request's deadline. `None` outside a
+/// request; zero once it has passed.
+pub fn remaining() -> Option<Duration> {
+    DEADLINE
+        .try_with(|deadline| deadline.saturating_duration_since(Instant::now()))
+        .ok()
+}
+
+/// `timeout`, shortened to what is left of the current request.
+pub fn cap(timeout: Option<Duration>) -> Option<Duration> {
+    match (timeout, remaining()) {
+        (Some(timeout), Some(left)) => Some(timeout.min(left)),
+        (timeout, left) => timeout.or(left),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn cap_is_the_shorter_of_timeout_and_remaining() {
+        assert_eq!(
+            cap(Some(Duration::from_secs(30))),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(cap(None), None);
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        scope(deadline, async {
+            let short = cap(Some(Duration::from_secs(30))).unwrap();
+            assert!(short <= Duration::from_secs(10));
+            assert!(cap(None).unwrap() <= Duration::from_secs(10));
+            assert_eq!(
+                cap(Some(Duration::from_secs(1))),
+                Some(Duration::from_secs(1))
+            );
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn spawned_work_runs_outside_the_deadline() {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let spawned = scope(deadline, async {
+            tokio::spawn(async { remaining() }).await
+        })
+        .await
+        .unwrap();
+        assert_eq!(spawned, None);
+    }
+}
@@ -0,0 +1,138 @@
+//! Shared rules for requests the server makes on a user's behalf.
+//!
+//! `agent-chain`'s `fetch_url` and `be-webhook-service`'s deliveries both
+//! connect to URLs someone else chose. They refuse anything that resolves
+//! to an internal address, and then pin the connection to the addresses
+//! they checked. [`is_public`] is the one definition of "internal" they
+//! share.
+//!
+//! [`deadline`] carries the deadline of the HTTP request being served, so
+//! calls made while serving it stop when it would.
+
+pub mod deadline;
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Whether `ip` is reachable on the public internet.
+///
+/// An IPv6 address that carries an IPv4 one (IPv4-mapped, IPv4-compatible,
+/// NAT64 `64:ff9b::/96` or 6to4 `2002::/16`) is judged by the IPv4
+/// address inside it, since that is where the packets end up.
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            // Ranges the std helpers don't cover: 0.0.0.0/8 ("this
+            // network"), 100.64.0.0/10 (carrier-grade NAT),
+            // 198.18.0.0/15 (benchmarking) and 240.0.0.0/4 (reserved,
+            // which takes in the broadcast address).
+            let this_network = a == 0;
+            let shared = a == 100 && (b & 0xc0) == 64;
+            let benchmarking = a == 198 && (b & 0xfe) == 18;
+            let reserved = a >= 240;
+            !(v4.is_private()
+                || v4.is_loopback()
+                || v4.is_link_local()
+                || v4.is_multicast()
+                || v4.is_documentation()
+                || this_network
+                || shared
+                || benchmarking
+                || reserved)
+        }
+        IpAddr::V6(v6) => {
+            if v6.is_loopback() || v6.is_unspecified() {
+                return false;
+            }
+            match embedded_ipv4(v6) {
+                Some(v4) => is_public(IpAddr::V4(v4)),
+                None => !(v6.is_unique_local() || v6.is_unicast_link_local() || v6.is_multicast()),
+            }
+        }
+    }
+}
+
+/// The IPv4 address `v6` routes to, if it is one of the forms that embed
+/// one.
+fn embedded_ipv4(v6: Ipv6Addr) -> Option<Ipv4Addr> {
+    let segments = v6.segments();
+    let octets = v6.octets();
+    match segments {
+        // IPv4-mapped `::ffff:a.b.c.d` and IPv4-compatible `::a.b.c.d`.
+        [0, 0, 0, 0, 0, 0xffff, ..] | [0, 0, 0, 0, 0, 0, ..] => Some(Ipv4Addr::new(
+            octets[12], octets[13], octets[14], octets[15],
+        )),
+        // NAT64 `64:ff9b::a.b.c.d`.
+        [0x64, 0xff9b, 0, 0, 0, 0, ..] => Some(Ipv4Addr::new(
+            octets[12], octets[13], octets[14], octets[15],
+        )),
+        // 6to4 `2002:aabb:ccdd::/48`.
+        [0x2002, ..] => Some(Ipv4Addr::new(octets[2], octets[3], octets[4], octets[5])),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn private_addresses_are_not_public() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "192.168.0.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!is_public(ip.parse().unwrap()), "{ip}");
+        }
+        for ip in ["1.1.1.1", "100.128.0.1", "2606:4700:4700::1111"] {
+            assert!(is_public(ip.parse().unwrap()), "{ip}");
+        }
+    }
+
+    #[test]
+    fn reserved_and_embedded_addresses_are_judged_by_their_ipv4() {
+        let cases = [
+            // Multicast.
+            ("224.0.0.1", false),
+            ("239.255.255.250", false),
+            ("ff02::1", false),
+            ("ff0e::1", false),
+            // Benchmarking, 198.18.0.0/15.
+            ("198.18.0.1", false),
+            ("198.19.255.254", false),
+            ("198.17.255.255", true),
+            ("198.20.0.1", true),
+            // Reserved, 240.0.0.0/4.
+            ("240.0.0.1", false),
+            ("255.255.255.255", false),
+            ("239.0.0.0", false),
+            ("0.1.2.3", false),
+            // NAT64, 64:ff9b::/96.
+            ("64:ff9b::127.0.0.1", false),
+            ("64:ff9b::10.0.0.1", false),
+            ("64:ff9b::1.1.1.1", true),
+            // 6to4, 2002::/16, with the IPv4 address in bits 16-48.
+            ("2002:7f00:1::", false),
+            ("2002:a9fe:a9fe::1", false),
+            ("2002:101:101::1", true),
+            // IPv4-compatible, ::a.b.c.d.
+            ("::127.0.0.1", false),
+            ("::192.168.1.1", false),
+            ("::1.1.1.1", true),
+            // IPv4-mapped still follows the same rule.
+            ("::ffff:198.18.0.1", false),
+            ("::ffff:1.1.1.1", true),
+        ];
+        for (ip, public) in cases {
+            assert_eq!(is_public(ip.parse().unwrap()), public, "{ip}");
+        }
+    }
+}
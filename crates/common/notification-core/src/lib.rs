@@ -0,0 +1,47 @@
+//! Shared WebSocket wire types for Eurora push notifications.
+//!
+//! Used by `be-notification-service` (the socket), the backend services that
+//! publish events, and the desktop client. The same types feed
+//! Specta-generated TypeScript bindings via the workspace-level
+//! `euro-codegen` orchestrator.
+
+pub mod notification;
+
+pub use notification::{
+    Notification, NotificationEvent, NotificationServerMessage, NotificationStreamQuery,
+};
+
+/// Build a [`specta::Types`] containing every notification wire type the
+/// frontend needs. Consumed by `euro-codegen` to emit `notification.ts`.
+#[cfg(feature = "specta")]
+pub fn type_collection() -> specta::Types {
+    specta::Types::default()
+        .register::<Notification>()
+        .register::<NotificationEvent>()
+        .register::<NotificationServerMessage>()
+        .register::<NotificationStreamQuery>()
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "specta")]
+    #[test]
+    fn type_collection_contains_all_wire_types() {
+        let types = super::type_collection();
+        let names: Vec<String> = types
+            .into_unsorted_iter()
+            .map(|ndt| ndt.name.to_string())
+            .collect();
+        for expected in [
+            "Notification",
+            "NotificationEvent",
+            "NotificationServerMessage",
+            "NotificationStreamQuery",
+        ] {
+            assert!(
+                names.iter().any(|n| n == expected),
+                "missing {expected} from collection: {names:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "specta")]
+use specta::Type;
+#[cfg(feature = "specta")]
+use specta_typescript::BigInt;
+use uuid::Uuid;
+
+/// Something that happened server-side that a connected client should
+/// react to without polling.
+///
+/// Clients must ignore events they don't know: new kinds are added without
+/// bumping the protocol, and an older client decodes them as
+/// [`NotificationEvent::Unknown`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotificationEvent {
+    /// Another user shared a thread with this one.
+    ThreadShared { thread_id: Uuid },
+    /// The user's plan changed; refetch entitlements and usage limits.
+    SubscriptionChanged { plan_id: String },
+    /// A data export finished building and can be downloaded.
+    ExportReady { export_id: Uuid },
+    /// Clients older than `min_version` must update before continuing.
+    /// Sent to every user.
+    UpdateRequired { min_version: String },
+    /// An event kind this build doesn't know about.
+    #[serde(other)]
+    Unknown,
+}
+
+/// One delivered event. `id` increases monotonically and is the cursor a
+/// reconnecting client resumes from.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct Notification {
+    #[cfg_attr(feature = "specta", specta(type = BigInt))]
+    pub id: i64,
+    pub event: NotificationEvent,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Query string for `GET /notifications/ws`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct NotificationStreamQuery {
+    /// The last cursor the client saw. Everything newer is replayed before
+    /// live events; omit it on first connect.
+    #[serde(default)]
+    #[cfg_attr(feature = "specta", specta(type = Option<BigInt>))]
+    pub after: Option<i64>,
+}
+
+/// Server → client frames on the notification socket. The client never
+/// sends anything; the server ignores whatever it does send.
+///
+/// On connect the server replays what the client missed as
+/// `Notification` frames, then sends `Ready`, then streams live events.
+/// A `Resync` can replace the replay, or arrive later if this connection
+/// fell behind; either way the client should refetch whatever state the
+/// events would have invalidated and continue from the given cursor.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotificationServerMessage {
+    /// A replayed or live event.
+    Notification { notification: Notification },
+    /// The replay is done. `cursor` is the one to reconnect with if no
+    /// notification arrives before the connection drops.
+    Ready {
+        #[cfg_attr(feature = "specta", specta(type = BigInt))]
+        cursor: i64,
+    },
+    /// Events were missed and can't be replayed one by one.
+    Resync {
+        #[cfg_attr(feature = "specta", specta(type = BigInt))]
+        cursor: i64,
+    },
+    /// Sent on an otherwise idle connection so both ends can tell it is
+    /// still alive. A client that hears nothing for a few heartbeat
+    /// intervals should reconnect.
+    Heartbeat,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn events_are_tagged_by_type() {
+        let export_id = Uuid::nil();
+        let value = serde_json::to_value(NotificationEvent::ExportReady { export_id }).unwrap();
+        assert_eq!(
+            value,
+            json!({ "type": "export_ready", "export_id": export_id })
+        );
+    }
+
+    #[test]
+    fn unknown_events_decode_as_unknown() {
+        let event: NotificationEvent =
+            serde_json::from_value(json!({ "type": "from_the_future", "x": 1 })).unwrap();
+        assert_eq!(event, NotificationEvent::Unknown);
+    }
+}
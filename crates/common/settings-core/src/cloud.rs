@@ -74,6 +74,8 @@ mod tests {
 
         assert_eq!(d.shared.theme, ThemePreference::System);
         assert!(d.shared.dynamic_accent);
+        assert!(d.shared.web_access);
+        assert!(!d.shared.memory);
 
         assert_eq!(d.desktop.interface_scale.get(), DEFAULT_SCALE);
         assert_eq!(d.desktop.text_scale.get(), DEFAULT_SCALE);
@@ -104,6 +106,8 @@ mod tests {
             "shared": {
                 "theme": "dark",
                 "dynamicAccent": false,
+                "webAccess": true,
+                "memory": false,
                 "futureSharedKnob": "x",
             },
             "desktop": {
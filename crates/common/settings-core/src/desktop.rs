@@ -136,6 +136,59 @@ impl<'de> Deserialize<'de> for TextScale {
     }
 }
 
+/// Speaking-rate multiplier for reading responses aloud, where `1.0` is
+/// the voice's normal pace. Always finite and within
+/// `[SpeechRate::MIN, SpeechRate::MAX]` by construction.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[serde(transparent)]
+#[cfg_attr(feature = "specta", specta(transparent))]
+pub struct SpeechRate(f32);
+
+impl SpeechRate {
+    /// Below this, platform voices start dragging words apart.
+    pub const MIN: Self = Self(0.5);
+    /// Beyond this, most voices stop being intelligible.
+    pub const MAX: Self = Self(2.0);
+    pub const DEFAULT: Self = Self(1.0);
+
+    pub fn new(value: f32) -> Self {
+        if value.is_finite() {
+            Self(value.clamp(Self::MIN.0, Self::MAX.0))
+        } else {
+            Self::DEFAULT
+        }
+    }
+
+    pub const fn get(self) -> f32 {
+        self.0
+    }
+}
+
+impl Default for SpeechRate {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl From<f32> for SpeechRate {
+    fn from(value: f32) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<SpeechRate> for f32 {
+    fn from(value: SpeechRate) -> Self {
+        value.0
+    }
+}
+
+impl<'de> Deserialize<'de> for SpeechRate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        f32::deserialize(deserializer).map(Self::new)
+    }
+}
+
 /// Desktop-only cloud-synced settings. Mobile and web each have their
 /// own platform sections to keep concepts that don't translate (window
 /// chrome scaling, telemetry SDKs that don't run on the other
@@ -178,6 +231,13 @@ pub struct DesktopSettings {
     /// specific to the data actually collected.
     #[builder(default)]
     pub telemetry: TelemetryConsent,
+    /// Voice used to read responses aloud: a platform voice id, or the
+    /// provider's voice name when speech goes through a provider. `None`
+    /// uses the system default.
+    #[builder(into)]
+    pub speech_voice: Option<String>,
+    #[builder(into, default)]
+    pub speech_rate: SpeechRate,
     // `flatten` of an empty Map already emits nothing — no
     // `skip_serializing_if` needed, and using it here would force
     // tauri-specta out of unified mode where the IPC surface lives.
@@ -242,6 +302,13 @@ mod tests {
         assert_eq!(TextScale::new(f32::NAN), TextScale::DEFAULT);
     }
 
+    #[test]
+    fn speech_rate_clamps_out_of_range() {
+        assert_eq!(SpeechRate::new(0.1), SpeechRate::MIN);
+        assert_eq!(SpeechRate::new(9.0), SpeechRate::MAX);
+        assert_eq!(SpeechRate::new(f32::NAN), SpeechRate::DEFAULT);
+    }
+
     #[test]
     fn deserialize_clamps_out_of_range_scales() {
         let raw = serde_json::json!({
@@ -24,6 +24,11 @@
 //! `Deserialize`. Bump [`CURRENT_SCHEMA_VERSION`] when the structural
 //! shape of the blob changes incompatibly.
 //!
+//! The one exception is [`SettingsSchema`]: the server serves the
+//! registry of editable keys and refuses a current-version blob whose
+//! known keys fall outside it, so a misbehaving client can't persist a
+//! value every other client would then have to clamp.
+//!
 //! ## Forward-compatibility
 //!
 //! Every leaf section carries a flattened `extras: serde_json::Map`
@@ -58,17 +63,19 @@ pub mod cloud;
 pub mod desktop;
 pub mod dto;
 pub mod mobile;
+pub mod schema;
 pub mod shared;
 pub mod telemetry;
 pub mod web;
 
 pub use cloud::{CURRENT_SCHEMA_VERSION, CloudSettings};
-pub use desktop::{DEFAULT_SCALE, DesktopSettings, InterfaceScale, TextScale};
+pub use desktop::{DEFAULT_SCALE, DesktopSettings, InterfaceScale, SpeechRate, TextScale};
 pub use dto::{
     GetSettingsResponse, PutSettingsAcceptedResponse, PutSettingsConflictResponse,
     PutSettingsRequest,
 };
 pub use mobile::MobileSettings;
+pub use schema::{InvalidSetting, SettingDefinition, SettingScope, SettingType, SettingsSchema};
 pub use shared::{SharedSettings, ThemePreference};
 pub use telemetry::{DESKTOP_CONSENT_VERSION, TelemetryConsent};
 pub use web::WebSettings;
@@ -90,6 +97,10 @@ pub fn type_collection() -> specta::Types {
         .register::<PutSettingsRequest>()
         .register::<PutSettingsAcceptedResponse>()
         .register::<PutSettingsConflictResponse>()
+        .register::<SettingsSchema>()
+        .register::<SettingDefinition>()
+        .register::<SettingScope>()
+        .register::<SettingType>()
 }
 
 #[cfg(test)]
@@ -114,6 +125,10 @@ mod tests {
             "PutSettingsRequest",
             "PutSettingsAcceptedResponse",
             "PutSettingsConflictResponse",
+            "SettingsSchema",
+            "SettingDefinition",
+            "SettingScope",
+            "SettingType",
         ] {
             assert!(
                 names.iter().any(|n| n == expected),
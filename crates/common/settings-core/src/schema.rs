@@ -0,0 +1,375 @@
+//! Typed registry of the user-editable settings.
+//!
+//! The section structs describe the *shape* of each settings document;
+//! this module describes which fields a settings form may write, what
+//! values they accept, and where they live. One [`SettingDefinition`]
+//! per key, shared by `be-settings-service` (which serves the registry at
+//! `GET /settings/schema` and rejects blobs that violate it) and the
+//! desktop (which renders forms from it and validates every write before
+//! it touches disk).
+//!
+//! Keys are dotted JSON paths into the document named by the
+//! definition's [`SettingScope`]: `desktop.interfaceScale` is the
+//! `interfaceScale` field of the cloud blob's `desktop` section,
+//! `general.autostart` the `autostart` field of the `general` section of
+//! the desktop's per-install `local.json`.
+//!
+//! Fields with their own write path are deliberately absent: telemetry
+//! consent must go through the consent prompt so its version is stamped,
+//! and the API endpoint switch has to re-point in-flight clients.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use thiserror::Error;
+
+use crate::{
+    cloud::CURRENT_SCHEMA_VERSION,
+    desktop::{DesktopSettings, InterfaceScale, SpeechRate, TextScale},
+    shared::SharedSettings,
+};
+
+/// Which settings document a key lives in, and therefore who may see it.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[serde(rename_all = "camelCase")]
+pub enum SettingScope {
+    /// Per-install `local.json` on the desktop. Never crosses the wire.
+    Local,
+    /// Cloud blob, `shared` section: applies on every platform.
+    Shared,
+    /// Cloud blob, `desktop` section.
+    Desktop,
+}
+
+impl SettingScope {
+    /// `true` for scopes stored in the cloud-synced blob.
+    pub fn is_cloud(self) -> bool {
+        !matches!(self, Self::Local)
+    }
+}
+
+/// The values a setting accepts. Drives both the form control the UI
+/// renders and the check [`SettingsSchema::validate`] applies.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum SettingType {
+    Bool,
+    /// A number within `[min, max]`, offered in increments of `step`.
+    Number {
+        min: f32,
+        max: f32,
+        step: f32,
+    },
+    /// One of a fixed set of strings.
+    Choice {
+        options: Vec<String>,
+    },
+}
+
+impl SettingType {
+    /// JSON type a value must have, as named in [`InvalidSetting::WrongType`].
+    pub fn json_type(&self) -> &'static str {
+        match self {
+            Self::Bool => "boolean",
+            Self::Number { .. } => "number",
+            Self::Choice { .. } => "string",
+        }
+    }
+}
+
+/// One entry in the registry.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[serde(rename_all = "camelCase")]
+pub struct SettingDefinition {
+    /// Dotted path within the scope's document, e.g. `shared.theme`.
+    pub key: String,
+    pub scope: SettingScope,
+    #[serde(rename = "type")]
+    pub value_type: SettingType,
+    /// Fresh-install value, identical to what the section's `Default`
+    /// impl produces.
+    #[cfg_attr(feature = "specta", specta(type = specta_typescript::Unknown))]
+    pub default: Value,
+}
+
+impl SettingDefinition {
+    /// The JSON path segments of [`Self::key`].
+    pub fn path(&self) -> impl Iterator<Item = &str> {
+        self.key.split('.')
+    }
+}
+
+/// `GET /settings/schema` — 200 body, and the desktop's
+/// `settings_get_schema` result.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsSchema {
+    /// The cloud blob version the registry describes.
+    pub schema_version: u32,
+    pub settings: Vec<SettingDefinition>,
+}
+
+/// Why a value was refused by [`SettingsSchema::validate`].
+#[derive(Debug, Clone, Error, PartialEq)]
+pub enum InvalidSetting {
+    #[error("unknown setting `{0}`")]
+    UnknownKey(String),
+    #[error("`{key}` must be a {expected}")]
+    WrongType { key: String, expected: &'static str },
+    #[error("`{key}` must be between {min} and {max}")]
+    OutOfRange { key: String, min: f32, max: f32 },
+    #[error("`{key}` must be one of {options:?}")]
+    UnknownOption { key: String, options: Vec<String> },
+}
+
+impl SettingsSchema {
+    /// The registry for [`CURRENT_SCHEMA_VERSION`].
+    pub fn current() -> Self {
+        let shared = SharedSettings::default();
+        let desktop = DesktopSettings::default();
+        let settings = vec![
+            SettingDefinition {
+                key: "general.autostart".into(),
+                scope: SettingScope::Local,
+                value_type: SettingType::Bool,
+                default: json!(true),
+            },
+            SettingDefinition {
+                key: "telemetry.analyticsDisabled".into(),
+                scope: SettingScope::Local,
+                value_type: SettingType::Bool,
+                default: json!(false),
+            },
+            SettingDefinition {
+                key: "regionCapture.storageFormat".into(),
+                scope: SettingScope::Local,
+                value_type: SettingType::Choice {
+                    options: vec!["png".into(), "jpeg".into(), "webp".into(), "avif".into()],
+                },
+                default: json!("webp"),
+            },
+            SettingDefinition {
+                key: "regionCapture.storageQuality".into(),
+                scope: SettingScope::Local,
+                value_type: SettingType::Number {
+                    min: 1.0,
+                    max: 100.0,
+                    step: 1.0,
+                },
+                default: json!(75),
+            },
+            SettingDefinition {
+                key: "shared.theme".into(),
+                scope: SettingScope::Shared,
+                value_type: SettingType::Choice {
+                    options: vec!["system".into(), "light".into(), "dark".into()],
+                },
+                default: json!(shared.theme),
+            },
+            SettingDefinition {
+                key: "shared.dynamicAccent".into(),
+                scope: SettingScope::Shared,
+                value_type: SettingType::Bool,
+                default: json!(shared.dynamic_accent),
+            },
+            SettingDefinition {
+                key: "shared.webAccess".into(),
+                scope: SettingScope::Shared,
+                value_type: SettingType::Bool,
+                default: json!(shared.web_access),
+            },
+            SettingDefinition {
+                key: "shared.memory".into(),
+                scope: SettingScope::Shared,
+                value_type: SettingType::Bool,
+                default: json!(shared.memory),
+            },
+            SettingDefinition {
+                key: "desktop.interfaceScale".into(),
+                scope: SettingScope::Desktop,
+                value_type: SettingType::Number {
+                    min: InterfaceScale::MIN.get(),
+                    max: InterfaceScale::MAX.get(),
+                    step: 0.05,
+                },
+                default: json!(desktop.interface_scale),
+            },
+            SettingDefinition {
+                key: "desktop.textScale".into(),
+                scope: SettingScope::Desktop,
+                value_type: SettingType::Number {
+                    min: TextScale::MIN.get(),
+                    max: TextScale::MAX.get(),
+                    step: 0.05,
+                },
+                default: json!(desktop.text_scale),
+            },
+            SettingDefinition {
+                key: "desktop.speechRate".into(),
+                scope: SettingScope::Desktop,
+                value_type: SettingType::Number {
+                    min: SpeechRate::MIN.get(),
+                    max: SpeechRate::MAX.get(),
+                    step: 0.1,
+                },
+                default: json!(desktop.speech_rate),
+            },
+        ];
+        Self {
+            schema_version: CURRENT_SCHEMA_VERSION,
+            settings,
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&SettingDefinition> {
+        self.settings.iter().find(|def| def.key == key)
+    }
+
+    /// Check `value` against the definition registered for `key`.
+    pub fn validate(&self, key: &str, value: &Value) -> Result<(), InvalidSetting> {
+        let def = self
+            .get(key)
+            .ok_or_else(|| InvalidSetting::UnknownKey(key.to_owned()))?;
+        check(def, value)
+    }
+
+    /// Check every registered cloud key present in a cloud settings
+    /// blob. Missing keys pass: readers fill them from `Default`, and
+    /// fields the registry doesn't know (`extras`) are left alone so the
+    /// blob stays forward-compatible.
+    pub fn validate_cloud_document(&self, document: &Value) -> Result<(), InvalidSetting> {
+        for def in self.settings.iter().filter(|def| def.scope.is_cloud()) {
+            if let Some(value) = lookup(document, def) {
+                check(def, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn lookup<'a>(document: &'a Value, def: &SettingDefinition) -> Option<&'a Value> {
+    def.path()
+        .try_fold(document, |node, segment| node.get(segment))
+}
+
+fn check(def: &SettingDefinition, value: &Value) -> Result<(), InvalidSetting> {
+    let wrong_type = || InvalidSetting::WrongType {
+        key: def.key.clone(),
+        expected: def.value_type.json_type(),
+    };
+    match &def.value_type {
+        SettingType::Bool => value.as_bool().map(drop).ok_or_else(wrong_type),
+        SettingType::Number { min, max, .. } => {
+            let number = value.as_f64().ok_or_else(wrong_type)?;
+            // Bounds come from `f32` newtypes; compare at that precision
+            // so a client's `0.85` isn't refused against `0.85f32`'s
+            // slightly larger `f64` expansion.
+            let number = number as f32;
+            if number.is_finite() && (*min..=*max).contains(&number) {
+                Ok(())
+            } else {
+                Err(InvalidSetting::OutOfRange {
+                    key: def.key.clone(),
+                    min: *min,
+                    max: *max,
+                })
+            }
+        }
+        SettingType::Choice { options } => {
+            let choice = value.as_str().ok_or_else(wrong_type)?;
+            if options.iter().any(|option| option == choice) {
+                Ok(())
+            } else {
+                Err(InvalidSetting::UnknownOption {
+                    key: def.key.clone(),
+                    options: options.clone(),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cloud::CloudSettings, shared::ThemePreference};
+
+    #[test]
+    fn every_default_passes_its_own_validation() {
+        let schema = SettingsSchema::current();
+        for def in &schema.settings {
+            assert_eq!(
+                schema.validate(&def.key, &def.default),
+                Ok(()),
+                "{}",
+                def.key
+            );
+        }
+    }
+
+    #[test]
+    fn cloud_defaults_match_the_section_defaults() {
+        let schema = SettingsSchema::current();
+        let document = serde_json::to_value(CloudSettings::default()).unwrap();
+        for def in schema.settings.iter().filter(|def| def.scope.is_cloud()) {
+            assert_eq!(lookup(&document, def), Some(&def.default), "{}", def.key);
+        }
+    }
+
+    #[test]
+    fn theme_options_all_deserialize() {
+        let schema = SettingsSchema::current();
+        let SettingType::Choice { options } = &schema.get("shared.theme").unwrap().value_type
+        else {
+            panic!("theme is not a choice");
+        };
+        for option in options {
+            serde_json::from_value::<ThemePreference>(json!(option)).unwrap();
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_keys_wrong_types_and_out_of_range_values() {
+        let schema = SettingsSchema::current();
+        assert!(matches!(
+            schema.validate("shared.nope", &json!(true)),
+            Err(InvalidSetting::UnknownKey(_))
+        ));
+        assert!(matches!(
+            schema.validate("shared.webAccess", &json!("yes")),
+            Err(InvalidSetting::WrongType { .. })
+        ));
+        assert!(matches!(
+            schema.validate("desktop.textScale", &json!(4.0)),
+            Err(InvalidSetting::OutOfRange { .. })
+        ));
+        assert!(matches!(
+            schema.validate("shared.theme", &json!("sepia")),
+            Err(InvalidSetting::UnknownOption { .. })
+        ));
+        // A JS client writes the bound as a plain `f64`.
+        assert_eq!(
+            schema.validate("desktop.interfaceScale", &json!(0.85)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn cloud_document_ignores_missing_and_unknown_fields() {
+        let schema = SettingsSchema::current();
+        let document = json!({
+            "shared": { "theme": "dark", "futureKnob": 3 },
+            "futureSection": { "anything": [] },
+        });
+        assert_eq!(schema.validate_cloud_document(&document), Ok(()));
+
+        let document = json!({ "desktop": { "interfaceScale": 0.1 } });
+        assert!(matches!(
+            schema.validate_cloud_document(&document),
+            Err(InvalidSetting::OutOfRange { .. })
+        ));
+    }
+}
@@ -24,15 +24,24 @@ pub enum ThemePreference {
 ///
 /// `dynamic_accent` defaults to `true` — the design pulls the OS / wallpaper
 /// accent by default and a user who has never touched the toggle should see
-/// the dynamic behaviour. This is the one field where the product default
-/// differs from `bool::default()`, which is why this struct has a hand-rolled
-/// `Default` instead of `#[derive]`.
+/// the dynamic behaviour. `web_access` likewise defaults to `true`: the
+/// assistant may search and read the web unless the user opts out. Those
+/// two fields are where the product default differs from `bool::default()`,
+/// which is why this struct has a hand-rolled `Default` instead of
+/// `#[derive]`.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[cfg_attr(feature = "specta", derive(specta::Type))]
 #[serde(default, rename_all = "camelCase")]
 pub struct SharedSettings {
     pub theme: ThemePreference,
     pub dynamic_accent: bool,
+    /// Whether the assistant may use the `web_search` and `fetch_url`
+    /// tools. Enforced server-side when a chat turn is prepared.
+    pub web_access: bool,
+    /// Whether the assistant remembers facts about the user across
+    /// threads: learning them from new messages and recalling them into
+    /// later chats. Off unless the user opts in.
+    pub memory: bool,
     // `flatten` of an empty Map already emits nothing — no
     // `skip_serializing_if` needed, and using it here would force
     // tauri-specta out of unified mode where the IPC surface lives.
@@ -49,6 +58,8 @@ impl Default for SharedSettings {
         Self {
             theme: ThemePreference::default(),
             dynamic_accent: true,
+            web_access: true,
+            memory: false,
             extras: Map::new(),
         }
     }
@@ -74,11 +85,28 @@ mod tests {
         assert!(SharedSettings::default().dynamic_accent);
     }
 
+    #[test]
+    fn missing_web_access_defaults_to_enabled() {
+        // Blobs written before the toggle existed must keep web tools on.
+        let parsed: SharedSettings =
+            serde_json::from_value(serde_json::json!({ "theme": "dark" })).unwrap();
+        assert!(parsed.web_access);
+    }
+
+    #[test]
+    fn missing_memory_defaults_to_off() {
+        let parsed: SharedSettings =
+            serde_json::from_value(serde_json::json!({ "theme": "dark" })).unwrap();
+        assert!(!parsed.memory);
+    }
+
     #[test]
     fn round_trip_preserves_unknown_fields() {
         let raw = serde_json::json!({
             "theme": "dark",
             "dynamicAccent": true,
+            "webAccess": false,
+            "memory": true,
             "futureSharedKnob": "preserve me",
         });
         let parsed: SharedSettings = serde_json::from_value(raw.clone()).unwrap();
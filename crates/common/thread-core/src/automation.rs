@@ -0,0 +1,175 @@
+//! Scheduled automation CRUD + run history wire types.
+//!
+//! An automation is a prompt the server runs on the user's behalf on a
+//! cron schedule. Each run produces its own thread, linked from the
+//! [`AutomationRun`] record.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "specta")]
+use specta::Type;
+
+/// A persisted automation as returned to the client.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct Automation {
+    pub id: Uuid,
+    pub name: String,
+    pub prompt: String,
+    /// Five-field cron expression (`minute hour day-of-month month
+    /// day-of-week`), evaluated in UTC.
+    pub schedule: String,
+    /// Names of the server tools the agent may call during a run. Only
+    /// tools listed by `GET /automations/tools` are accepted.
+    pub tools: Vec<String>,
+    pub enabled: bool,
+    /// `None` when disabled or when the schedule has no future occurrence.
+    #[serde(default)]
+    pub next_run_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request body for `POST /automations`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct CreateAutomationRequest {
+    pub name: String,
+    pub prompt: String,
+    pub schedule: String,
+    #[serde(default)]
+    pub tools: Vec<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Request body for `PATCH /automations/{automation_id}`. Absent fields
+/// are left unchanged.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct UpdateAutomationRequest {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub prompt: Option<String>,
+    #[serde(default)]
+    pub schedule: Option<String>,
+    #[serde(default)]
+    pub tools: Option<Vec<String>>,
+    #[serde(default)]
+    pub enabled: Option<bool>,
+}
+
+/// Response body for the single-automation endpoints (create, get,
+/// update, run now).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct AutomationResponse {
+    pub automation: Automation,
+}
+
+/// Response body for `GET /automations`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct ListAutomationsResponse {
+    pub automations: Vec<Automation>,
+}
+
+/// Response body for `DELETE /automations/{automation_id}`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct DeleteAutomationResponse {}
+
+/// A tool an automation may be granted.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct AutomationTool {
+    pub name: String,
+    pub description: String,
+}
+
+/// Response body for `GET /automations/tools`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct ListAutomationToolsResponse {
+    pub tools: Vec<AutomationTool>,
+}
+
+/// Outcome of an automation run. `Skipped` runs never started, e.g.
+/// because the monthly token budget was exhausted; their `error` says why.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+#[serde(rename_all = "snake_case")]
+pub enum AutomationRunStatus {
+    Running,
+    Succeeded,
+    Failed,
+    Skipped,
+}
+
+/// One execution of an automation.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct AutomationRun {
+    pub id: Uuid,
+    pub automation_id: Uuid,
+    /// Thread holding the conversation for this run. `None` for skipped
+    /// runs and once the user deletes the thread.
+    #[serde(default)]
+    pub thread_id: Option<Uuid>,
+    pub status: AutomationRunStatus,
+    pub scheduled_for: DateTime<Utc>,
+    pub started_at: DateTime<Utc>,
+    #[serde(default)]
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Final assistant text of a successful run.
+    #[serde(default)]
+    pub output: Option<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// Query parameters for `GET /automations/{automation_id}/runs`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct ListAutomationRunsQuery {
+    #[serde(default)]
+    pub limit: Option<u32>,
+    #[serde(default)]
+    pub offset: Option<u32>,
+}
+
+/// Response body for `GET /automations/{automation_id}/runs`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct ListAutomationRunsResponse {
+    pub runs: Vec<AutomationRun>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn create_automation_request_defaults_tools_and_enabled() {
+        let req: CreateAutomationRequest =
+            serde_json::from_str(r#"{"name":"Daily","prompt":"Summarize","schedule":"0 7 * * *"}"#)
+                .unwrap();
+        assert!(req.tools.is_empty());
+        assert!(req.enabled);
+    }
+
+    #[test]
+    fn run_status_serializes_snake_case() {
+        let s = serde_json::to_string(&AutomationRunStatus::Succeeded).unwrap();
+        assert_eq!(s, r#""succeeded""#);
+    }
+}
@@ -46,13 +46,14 @@ use crate::tool_wire::{ToolErrorWire, WireActiveContext, WireToolDescriptor};
 
 /// Frame sent by the client over the chat WebSocket.
 ///
-/// Bidirectional from day one; the current set is `Send` (start a turn from
-/// a new human message), `Regenerate` (re-roll an existing AI response under
-/// the same human parent so it becomes a sibling variant), `Cancel`
-/// (interrupt the in-flight turn), and the tool-routing pair
+/// Bidirectional from day one; the current set is `Send` (start a turn from a
+/// new human message), `Regenerate` (re-roll an existing AI response under the
+/// same human parent so it becomes a sibling variant), `Cancel` (interrupt the
+/// in-flight turn; what the model wrote so far is kept as an AI message with
+/// `cancelled: true` in its `additional_kwargs`), and the tool-routing pair
 /// `CapabilityUpdate` + `ToolResponse`. New variants can be added without
-/// breaking older clients because serde rejects unknown tagged variants only
-/// on deserialize, never on encode.
+/// breaking older clients because serde rejects unknown tagged variants only on
+/// deserialize, never on encode.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[cfg_attr(feature = "specta", derive(Type))]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -121,6 +122,16 @@ pub struct CapabilityUpdatePayload {
 /// when the user sent the message, so the server can record the link in
 /// `activity_threads`. Optional because non-desktop clients (web, mobile)
 /// have no timeline; absent values skip the link step entirely.
+///
+/// Attachments ride in `content_blocks` as `image`, `text-plain` or `file`
+/// blocks. Each either inlines its bytes (`base64`, or `text` for plain
+/// text) or names an asset the caller already uploaded by `file_id`; a
+/// client-supplied `url` is rejected. Documents are read into text before
+/// the model sees them.
+///
+/// `request_id` is a fresh id the client picks for the turn. While the turn
+/// runs, `POST /threads/{thread_id}/chat/{request_id}/cancel` stops it the
+/// same way a `Cancel` frame on the socket would.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[cfg_attr(feature = "specta", derive(Type))]
 pub struct ChatSendRequest {
@@ -131,6 +142,8 @@ pub struct ChatSendRequest {
     pub asset_chips_json: Option<String>,
     #[serde(default)]
     pub activity_id: Option<Uuid>,
+    #[serde(default)]
+    pub request_id: Option<Uuid>,
 }
 
 /// Payload of a [`ChatClientMessage::Regenerate`] frame.
@@ -138,10 +151,13 @@ pub struct ChatSendRequest {
 /// The server resolves the AI message's parent (a human message), rewinds
 /// `active_leaf` to that parent, and runs the agent loop on the existing
 /// context. The newly produced AI message lands as a sibling of the original.
+/// `request_id` works as on [`ChatSendRequest`].
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[cfg_attr(feature = "specta", derive(Type))]
 pub struct RegenerateRequest {
     pub ai_message_id: Uuid,
+    #[serde(default)]
+    pub request_id: Option<Uuid>,
 }
 
 /// Frame sent by the server over the chat WebSocket.
@@ -162,14 +178,27 @@ pub struct RegenerateRequest {
 pub enum ChatServerMessage {
     /// The user's message has been persisted; clients should display it.
     ConfirmedHumanMessage { message: MessageNode },
-    /// One streaming chunk from the AI.
+    /// One streaming chunk from the AI. The last chunk of each model
+    /// round has `chunk_position: "last"` and carries the round's
+    /// `usage_metadata` and the provider's `finish_reason` (in
+    /// `response_metadata`, e.g. `"stop"`, `"tool_calls"` or `"length"`).
     Chunk { chunk: AIMessageChunk },
     /// The thread's auto-generated title was updated this turn. Non-terminal;
     /// clients update the thread row in place. Emitted at most once per turn,
-    /// always before any terminal frame.
-    TitleUpdated { title: String },
+    /// always before any terminal frame. `tags` are the topic tags generated
+    /// with the title; omitted when there are none.
+    TitleUpdated {
+        title: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        tags: Vec<String>,
+    },
     /// The turn ended successfully; tree positions for everything that was
-    /// persisted during this turn (human + AI + any tool messages).
+    /// persisted during this turn (human + AI + any tool messages). When
+    /// the answer cites source passages, its text block carries one
+    /// `citation` annotation per cited passage: the marker's char span in
+    /// the text, the passage (`cited_text`), the source `title`, and in
+    /// `extras` the source's `file_id` plus its `paragraph` or, for
+    /// transcripts, `start_seconds`.
     Final { messages: Vec<MessageNode> },
     /// The turn aborted with an error. The connection is closed after this.
     Error { kind: String, message: String },
@@ -240,12 +269,28 @@ mod tests {
             parent_message_id: None,
             asset_chips_json: None,
             activity_id: None,
+            request_id: None,
         });
         let s = serde_json::to_string(&m).unwrap();
         assert!(s.contains("\"type\":\"send\""));
         assert!(s.contains("\"content_blocks\""));
     }
 
+    #[test]
+    fn request_id_may_be_omitted() {
+        let m: ChatClientMessage = serde_json::from_str(
+            r#"{"type":"regenerate","ai_message_id":"00000000-0000-0000-0000-000000000000"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            m,
+            ChatClientMessage::Regenerate(RegenerateRequest {
+                ai_message_id: Uuid::nil(),
+                request_id: None,
+            })
+        );
+    }
+
     #[test]
     fn chat_client_message_serializes_unit_cancel() {
         let s = serde_json::to_string(&ChatClientMessage::Cancel).unwrap();
@@ -256,6 +301,7 @@ mod tests {
     fn chat_client_message_serializes_regenerate_with_tag() {
         let m = ChatClientMessage::Regenerate(RegenerateRequest {
             ai_message_id: Uuid::nil(),
+            request_id: Some(Uuid::nil()),
         });
         let s = serde_json::to_string(&m).unwrap();
         assert!(s.contains("\"type\":\"regenerate\""));
@@ -275,6 +321,7 @@ mod tests {
             },
             ChatServerMessage::TitleUpdated {
                 title: "Deploy Rust service to Fly.io".into(),
+                tags: vec!["rust".into(), "deployment".into()],
             },
             ChatServerMessage::Final { messages: vec![] },
             ChatServerMessage::Error {
@@ -291,17 +338,29 @@ mod tests {
 
     /// Lock the wire shape of `TitleUpdated`. Clients key off the snake-case
     /// `type` tag and a single `title` string; any rename here is an explicit
-    /// protocol break.
+    /// protocol break. `tags` is additive: absent when empty, so the
+    /// tag-less frame is byte-identical to what older clients expect.
     #[test]
     fn title_updated_golden_json() {
         let m = ChatServerMessage::TitleUpdated {
             title: "Deploy Rust service to Fly.io".into(),
+            tags: vec![],
         };
         let s = serde_json::to_string(&m).unwrap();
         assert_eq!(
             s,
             r#"{"type":"title_updated","title":"Deploy Rust service to Fly.io"}"#
         );
+
+        let m = ChatServerMessage::TitleUpdated {
+            title: "Deploy Rust service to Fly.io".into(),
+            tags: vec!["rust".into()],
+        };
+        let s = serde_json::to_string(&m).unwrap();
+        assert_eq!(
+            s,
+            r#"{"type":"title_updated","title":"Deploy Rust service to Fly.io","tags":["rust"]}"#
+        );
     }
 
     // ------------------------------------------------------------------
@@ -0,0 +1,62 @@
+//! User-defined thread folder wire types.
+//!
+//! Folders are flat and per-user; a thread sits in at most one. Deleting a
+//! folder leaves its threads in place, unfiled.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "specta")]
+use specta::Type;
+
+/// A folder as returned to the client.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct ThreadFolder {
+    pub id: Uuid,
+    pub name: String,
+    /// Sort key in the sidebar, ascending. New folders go last.
+    pub position: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request body for `POST /threads/folders`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct CreateThreadFolderRequest {
+    /// Unique per user, at most 100 characters.
+    pub name: String,
+}
+
+/// Request body for `PATCH /threads/folders/{folder_id}`. Omitted fields
+/// are left as they are.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct UpdateThreadFolderRequest {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub position: Option<i32>,
+}
+
+/// Response body for `POST /threads/folders` and
+/// `PATCH /threads/folders/{folder_id}`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct ThreadFolderResponse {
+    pub folder: ThreadFolder,
+}
+
+/// Response body for `GET /threads/folders`, ordered by `position`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct ListThreadFoldersResponse {
+    pub folders: Vec<ThreadFolder>,
+}
+
+/// Response body for `DELETE /threads/folders/{folder_id}`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct DeleteThreadFolderResponse {}
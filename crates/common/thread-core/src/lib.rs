@@ -17,6 +17,13 @@
 //! ## Module layout
 //!
 //! - [`thread`] — thread CRUD + search response shapes.
+//! - [`folder`] — user-defined folders threads are filed into.
+//! - [`automation`] — scheduled automation CRUD + run history.
+//! - [`workflow`] — workflow templates, saved workflows, and runs.
+//! - [`local_model`] — Ollama model list / pull / delete in local mode.
+//! - [`memory`] — review and edit the facts the assistant remembers.
+//! - [`provider_model`] — models the deployment's gateways offer.
+//! - [`report`] — timeline reports rendered from templates.
 //! - [`messages`] — message tree, branch switch, message search.
 //! - [`chat`] — chat WebSocket frame enums and their payloads.
 //! - [`tool_wire`] — wire-side primitives for the unified tool-execution
@@ -29,31 +36,64 @@
 //! Every type is re-exported at the crate root for backwards-compatible
 //! `use thread_core::Foo;` imports.
 
+pub mod automation;
 pub mod chat;
 pub mod context_chip;
 pub mod error;
+pub mod folder;
+pub mod local_model;
+pub mod memory;
 pub mod messages;
+pub mod provider_model;
+pub mod report;
 pub mod thread;
 pub mod tool_backend;
 pub mod tool_wire;
+pub mod workflow;
 
+pub use automation::{
+    Automation, AutomationResponse, AutomationRun, AutomationRunStatus, AutomationTool,
+    CreateAutomationRequest, DeleteAutomationResponse, ListAutomationRunsQuery,
+    ListAutomationRunsResponse, ListAutomationToolsResponse, ListAutomationsResponse,
+    UpdateAutomationRequest,
+};
 pub use chat::{
     CapabilityUpdatePayload, ChatClientMessage, ChatSendRequest, ChatServerMessage,
     RegenerateRequest,
 };
 pub use context_chip::ContextChip;
 pub use error::ThreadErrorResponse;
+pub use folder::{
+    CreateThreadFolderRequest, DeleteThreadFolderResponse, ListThreadFoldersResponse, ThreadFolder,
+    ThreadFolderResponse, UpdateThreadFolderRequest,
+};
+pub use local_model::{
+    DeleteLocalModelQuery, ListLocalModelsResponse, LocalModel, LocalModelPullEvent,
+    PullLocalModelRequest,
+};
+pub use memory::{
+    DeleteMemoryResponse, ListMemoriesResponse, Memory, MemoryRequest, MemoryResponse,
+};
 pub use messages::{
     GetMessagesQuery, GetMessagesResponse, MessageNode, SearchMessageResult, SearchMessagesQuery,
     SearchMessagesResponse, SwitchBranchRequest,
 };
+pub use provider_model::{ListProviderModelsResponse, ModelPricing, ProviderModel};
+pub use report::{GenerateReportRequest, GenerateReportResponse, ReportFormat};
 pub use thread::{
     CreateThreadRequest, CreateThreadResponse, DeleteThreadResponse, GenerateThreadTitleRequest,
     GenerateThreadTitleResponse, GetThreadResponse, ListThreadsQuery, ListThreadsResponse,
-    SearchThreadResult, SearchThreadsQuery, SearchThreadsResponse, Thread,
+    MoveThreadRequest, MoveThreadResponse, SearchThreadResult, SearchThreadsQuery,
+    SearchThreadsResponse, SetThreadModelRequest, SetThreadModelResponse, Thread, ThreadModel,
+    UpdateThreadRequest, UpdateThreadResponse,
 };
 pub use tool_backend::{ToolBackend, ToolBackendCall};
 pub use tool_wire::{ToolErrorWire, ToolSource, WireActiveContext, WireToolDescriptor};
+pub use workflow::{
+    DeleteWorkflowResponse, ListWorkflowTemplatesResponse, ListWorkflowsResponse,
+    RunWorkflowRequest, RunWorkflowResponse, SaveWorkflowRequest, Workflow, WorkflowParam,
+    WorkflowResponse, WorkflowStepOutput, WorkflowTemplate,
+};
 
 /// Build a [`specta::Types`] containing every thread wire type the desktop
 /// app needs. Used by the codegen binary to emit `thread.ts`.
@@ -61,12 +101,25 @@ pub use tool_wire::{ToolErrorWire, ToolSource, WireActiveContext, WireToolDescri
 pub fn type_collection() -> specta::Types {
     specta::Types::default()
         .register::<Thread>()
+        .register::<ThreadModel>()
+        .register::<SetThreadModelRequest>()
+        .register::<SetThreadModelResponse>()
         .register::<CreateThreadRequest>()
         .register::<CreateThreadResponse>()
         .register::<ListThreadsQuery>()
         .register::<ListThreadsResponse>()
         .register::<GetThreadResponse>()
         .register::<DeleteThreadResponse>()
+        .register::<UpdateThreadRequest>()
+        .register::<UpdateThreadResponse>()
+        .register::<MoveThreadRequest>()
+        .register::<MoveThreadResponse>()
+        .register::<ThreadFolder>()
+        .register::<CreateThreadFolderRequest>()
+        .register::<UpdateThreadFolderRequest>()
+        .register::<ThreadFolderResponse>()
+        .register::<ListThreadFoldersResponse>()
+        .register::<DeleteThreadFolderResponse>()
         .register::<MessageNode>()
         .register::<GetMessagesQuery>()
         .register::<GetMessagesResponse>()
@@ -89,6 +142,45 @@ pub fn type_collection() -> specta::Types {
         .register::<ToolSource>()
         .register::<ToolErrorWire>()
         .register::<WireActiveContext>()
+        .register::<Automation>()
+        .register::<CreateAutomationRequest>()
+        .register::<UpdateAutomationRequest>()
+        .register::<AutomationResponse>()
+        .register::<ListAutomationsResponse>()
+        .register::<DeleteAutomationResponse>()
+        .register::<AutomationTool>()
+        .register::<ListAutomationToolsResponse>()
+        .register::<AutomationRunStatus>()
+        .register::<AutomationRun>()
+        .register::<ListAutomationRunsQuery>()
+        .register::<ListAutomationRunsResponse>()
+        .register::<WorkflowParam>()
+        .register::<Workflow>()
+        .register::<WorkflowTemplate>()
+        .register::<SaveWorkflowRequest>()
+        .register::<WorkflowResponse>()
+        .register::<ListWorkflowsResponse>()
+        .register::<ListWorkflowTemplatesResponse>()
+        .register::<DeleteWorkflowResponse>()
+        .register::<RunWorkflowRequest>()
+        .register::<WorkflowStepOutput>()
+        .register::<RunWorkflowResponse>()
+        .register::<LocalModel>()
+        .register::<ListLocalModelsResponse>()
+        .register::<PullLocalModelRequest>()
+        .register::<LocalModelPullEvent>()
+        .register::<DeleteLocalModelQuery>()
+        .register::<ProviderModel>()
+        .register::<ModelPricing>()
+        .register::<ListProviderModelsResponse>()
+        .register::<Memory>()
+        .register::<ListMemoriesResponse>()
+        .register::<MemoryRequest>()
+        .register::<MemoryResponse>()
+        .register::<DeleteMemoryResponse>()
+        .register::<ReportFormat>()
+        .register::<GenerateReportRequest>()
+        .register::<GenerateReportResponse>()
 }
 
 #[cfg(all(test, feature = "specta"))]
@@ -104,8 +196,13 @@ mod tests {
             .collect();
         for expected in [
             "Thread",
+            "ThreadModel",
+            "SetThreadModelRequest",
             "CreateThreadRequest",
             "ListThreadsQuery",
+            "UpdateThreadRequest",
+            "MoveThreadRequest",
+            "ThreadFolder",
             "MessageNode",
             "GetMessagesResponse",
             "SwitchBranchRequest",
@@ -116,6 +213,17 @@ mod tests {
             "ToolSource",
             "ToolErrorWire",
             "WireActiveContext",
+            "Automation",
+            "CreateAutomationRequest",
+            "AutomationRun",
+            "Workflow",
+            "WorkflowTemplate",
+            "RunWorkflowResponse",
+            "LocalModel",
+            "LocalModelPullEvent",
+            "ProviderModel",
+            "Memory",
+            "GenerateReportRequest",
         ] {
             assert!(
                 names.iter().any(|n| n == expected),
@@ -0,0 +1,110 @@
+//! Local model management wire types.
+//!
+//! Deployments that run their models on an Ollama server ("local mode")
+//! let the desktop model picker list, pull and delete the models installed
+//! there under `/models/local`. Other deployments answer those routes with
+//! `404 not_found`. Pulling and deleting need the `Admin` role.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "specta")]
+use specta::Type;
+#[cfg(feature = "specta")]
+use specta_typescript::BigInt;
+
+/// A model installed on the deployment's Ollama server.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct LocalModel {
+    /// Name as used in model settings and pulls, e.g. `llama3.1:8b`.
+    pub name: String,
+    /// Size on disk, in bytes.
+    #[cfg_attr(feature = "specta", specta(type = BigInt))]
+    pub size_bytes: u64,
+    pub digest: String,
+    #[serde(default)]
+    pub family: Option<String>,
+    /// Human-readable parameter count, e.g. `8.0B`.
+    #[serde(default)]
+    pub parameter_size: Option<String>,
+    /// e.g. `Q4_K_M`.
+    #[serde(default)]
+    pub quantization: Option<String>,
+    #[serde(default)]
+    pub modified_at: Option<DateTime<Utc>>,
+}
+
+/// Response body for `GET /models/local`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct ListLocalModelsResponse {
+    pub models: Vec<LocalModel>,
+}
+
+/// Request body for `POST /models/local/pull`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct PullLocalModelRequest {
+    /// Name in the Ollama library, with an optional tag, e.g.
+    /// `llama3.1:8b`.
+    pub model: String,
+}
+
+/// One line of the `application/x-ndjson` body of
+/// `POST /models/local/pull`.
+///
+/// The stream ends after a `Done` or a `Failed`; a body that ends without
+/// either means the connection dropped and the pull can be retried, which
+/// resumes it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LocalModelPullEvent {
+    /// The pull moved on. `status` is Ollama's free-form phase, e.g.
+    /// `pulling manifest`; byte counts are set while a layer downloads.
+    Progress {
+        status: String,
+        #[serde(default)]
+        #[cfg_attr(feature = "specta", specta(type = Option<BigInt>))]
+        total: Option<u64>,
+        #[serde(default)]
+        #[cfg_attr(feature = "specta", specta(type = Option<BigInt>))]
+        completed: Option<u64>,
+    },
+    /// The model is installed.
+    Done,
+    /// The pull stopped; `message` says why.
+    Failed { message: String },
+}
+
+/// Query string for `DELETE /models/local`. Model names can contain `/`,
+/// so the name travels as a parameter rather than a path segment.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct DeleteLocalModelQuery {
+    pub model: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pull_events_are_tagged() {
+        let line = serde_json::to_string(&LocalModelPullEvent::Progress {
+            status: "pulling 6a07".to_owned(),
+            total: Some(100),
+            completed: Some(40),
+        })
+        .unwrap();
+        assert_eq!(
+            line,
+            r#"{"type":"progress","status":"pulling 6a07","total":100,"completed":40}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<LocalModelPullEvent>(r#"{"type":"done"}"#).unwrap(),
+            LocalModelPullEvent::Done
+        );
+    }
+}
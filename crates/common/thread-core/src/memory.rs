@@ -0,0 +1,55 @@
+//! Long-term memory wire types.
+//!
+//! With the `shared.memory` setting on, the server learns short facts
+//! about the user from their messages and recalls the relevant ones into
+//! later chats. These endpoints let the user review, correct, add and
+//! forget those facts.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "specta")]
+use specta::Type;
+
+/// One remembered fact.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct Memory {
+    pub id: Uuid,
+    /// A short statement about the user, e.g. "Uses Arch Linux".
+    pub content: String,
+    /// The thread the fact was learned in; `None` for facts added by hand
+    /// or whose thread was deleted.
+    #[serde(default)]
+    pub thread_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Response body for `GET /memories`, most recently written first.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct ListMemoriesResponse {
+    pub memories: Vec<Memory>,
+}
+
+/// Request body for `POST /memories` and `PATCH /memories/{memory_id}`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct MemoryRequest {
+    /// At most 300 characters; unique per user, ignoring case.
+    pub content: String,
+}
+
+/// Response body for `POST /memories` and `PATCH /memories/{memory_id}`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct MemoryResponse {
+    pub memory: Memory,
+}
+
+/// Response body for `DELETE /memories/{memory_id}` and `DELETE /memories`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct DeleteMemoryResponse {}
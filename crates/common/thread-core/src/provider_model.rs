@@ -0,0 +1,51 @@
+//! The models the deployment's providers offer.
+//!
+//! `GET /models` passes through the model lists of the providers that
+//! serve one (OpenRouter and other OpenAI-compatible gateways), so a model
+//! picker can offer any of them for [`ThreadModel`](crate::ThreadModel)
+//! pins instead of only the deployment's defaults.
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "specta")]
+use specta::Type;
+
+/// A model one of the deployment's providers offers.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct ProviderModel {
+    /// Provider id from the deployment's LLM config, as used in
+    /// [`ThreadModel::provider`](crate::ThreadModel::provider).
+    pub provider: String,
+    /// Model name as used in [`ThreadModel::model`](crate::ThreadModel::model),
+    /// e.g. `anthropic/claude-sonnet-4.5`.
+    pub model: String,
+    /// Display name, where the provider has one.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Context window in tokens, where the provider reports it.
+    #[serde(default)]
+    pub context_length: Option<u32>,
+    /// Price, where the provider reports one.
+    #[serde(default)]
+    pub pricing: Option<ModelPricing>,
+}
+
+/// What a model costs, in US dollars per million tokens. A side the
+/// provider doesn't price, or prices per request, is `None`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct ModelPricing {
+    #[serde(default)]
+    pub input_per_million: Option<f64>,
+    #[serde(default)]
+    pub output_per_million: Option<f64>,
+}
+
+/// Response body for `GET /models`. Providers whose list couldn't be
+/// fetched are left out rather than failing the whole listing.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct ListProviderModelsResponse {
+    pub models: Vec<ProviderModel>,
+}
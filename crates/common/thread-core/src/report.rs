@@ -0,0 +1,60 @@
+//! Timeline report wire types.
+//!
+//! A report renders a Markdown template against the user's activity in a
+//! time range, on every device or just one: totals, the top activities,
+//! the time per device, and optionally a model-written summary. The
+//! result is stored as an asset, as Markdown or PDF, so it can be
+//! attached to a chat or downloaded later.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "specta")]
+use specta::Type;
+
+/// File format of a generated report.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    #[default]
+    Markdown,
+    Pdf,
+}
+
+/// Request body for `POST /reports`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct GenerateReportRequest {
+    /// Start of the range, inclusive.
+    pub since: DateTime<Utc>,
+    /// End of the range, exclusive. At most 92 days after `since`.
+    pub until: DateTime<Utc>,
+    /// Markdown with `{{placeholder}}`s; the built-in template when unset.
+    /// Supported placeholders: `title`, `since`, `until`, `total_time`,
+    /// `activity_count`, `session_count`, `top_activities`, `devices` and
+    /// `summary`.
+    #[serde(default)]
+    pub template: Option<String>,
+    /// Title of the report and name of the stored asset.
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub format: ReportFormat,
+    /// Only count activity from this registered device.
+    #[serde(default)]
+    pub device_id: Option<Uuid>,
+}
+
+/// Response body for `POST /reports`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct GenerateReportResponse {
+    /// The stored report.
+    pub asset_id: Uuid,
+    pub name: String,
+    pub mime_type: String,
+    /// The rendered Markdown, whichever format was stored.
+    pub markdown: String,
+}
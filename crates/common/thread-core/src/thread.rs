@@ -18,6 +18,33 @@ pub struct Thread {
     pub updated_at: DateTime<Utc>,
     #[serde(default)]
     pub active_leaf_id: Option<Uuid>,
+    /// The model every turn in this thread uses; `None` follows the
+    /// deployment default.
+    #[serde(default)]
+    pub model: Option<ThreadModel>,
+    /// Short topic tags generated with the auto-title.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Pinned threads list ahead of the rest, most recently pinned first.
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub starred: bool,
+    /// The [`ThreadFolder`](crate::ThreadFolder) the thread is filed in.
+    #[serde(default)]
+    pub folder_id: Option<Uuid>,
+}
+
+/// A model pinned to a thread.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct ThreadModel {
+    /// Provider id from the deployment's LLM config, e.g. `openai`.
+    pub provider: String,
+    pub model: String,
+    /// Sampling temperature; `None` keeps the provider's default.
+    #[serde(default)]
+    pub temperature: Option<f32>,
 }
 
 /// Request body for `POST /threads`.
@@ -43,6 +70,15 @@ pub struct ListThreadsQuery {
     pub limit: Option<u32>,
     #[serde(default)]
     pub offset: Option<u32>,
+    /// Only threads filed in this folder.
+    #[serde(default)]
+    pub folder_id: Option<Uuid>,
+    /// Only starred (`true`) or unstarred (`false`) threads.
+    #[serde(default)]
+    pub starred: Option<bool>,
+    /// Only pinned (`true`) or unpinned (`false`) threads.
+    #[serde(default)]
+    pub pinned: Option<bool>,
 }
 
 /// Response body for `GET /threads`.
@@ -59,6 +95,40 @@ pub struct GetThreadResponse {
     pub thread: Thread,
 }
 
+/// Request body for `PATCH /threads/{thread_id}`. Omitted fields are left
+/// as they are; re-pinning a pinned thread keeps its place.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct UpdateThreadRequest {
+    #[serde(default)]
+    pub pinned: Option<bool>,
+    #[serde(default)]
+    pub starred: Option<bool>,
+}
+
+/// Response body for `PATCH /threads/{thread_id}`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct UpdateThreadResponse {
+    pub thread: Thread,
+}
+
+/// Request body for `POST /threads/{thread_id}/folder`. `null` takes the
+/// thread out of its folder.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct MoveThreadRequest {
+    #[serde(default)]
+    pub folder_id: Option<Uuid>,
+}
+
+/// Response body for `POST /threads/{thread_id}/folder`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct MoveThreadResponse {
+    pub thread: Thread,
+}
+
 /// Response body for `DELETE /threads/{thread_id}`.
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 #[cfg_attr(feature = "specta", derive(Type))]
@@ -66,12 +136,15 @@ pub struct DeleteThreadResponse {}
 
 /// Request body for `POST /threads/{thread_id}/title`.
 ///
-/// The endpoint reads recent thread history server-side, so no payload is
-/// needed. An empty body type keeps the request well-typed for code-gen and
-/// leaves room to add fields later.
+/// The endpoint reads recent thread history server-side. By default it
+/// only titles a thread still carrying the placeholder; `regenerate`
+/// replaces an existing title and tags too.
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 #[cfg_attr(feature = "specta", derive(Type))]
-pub struct GenerateThreadTitleRequest {}
+pub struct GenerateThreadTitleRequest {
+    #[serde(default)]
+    pub regenerate: bool,
+}
 
 /// Response body for `POST /threads/{thread_id}/title`.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -80,6 +153,22 @@ pub struct GenerateThreadTitleResponse {
     pub thread: Thread,
 }
 
+/// Request body for `POST /threads/{thread_id}/model`. `null` unpins the
+/// thread so it follows the deployment default again.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct SetThreadModelRequest {
+    #[serde(default)]
+    pub model: Option<ThreadModel>,
+}
+
+/// Response body for `POST /threads/{thread_id}/model`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct SetThreadModelResponse {
+    pub thread: Thread,
+}
+
 /// Query parameters for `GET /threads/search`.
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 #[cfg_attr(feature = "specta", derive(Type))]
@@ -128,11 +217,31 @@ mod tests {
         assert!(back.title.is_none());
     }
 
+    #[test]
+    fn thread_decodes_without_model() {
+        // Forward-compat: rows from servers that predate model pinning,
+        // tags and thread organization.
+        let thread: Thread = serde_json::from_str(
+            r#"{"id":"00000000-0000-0000-0000-000000000000",
+                "user_id":"00000000-0000-0000-0000-000000000000",
+                "title":"t","created_at":"2026-01-01T00:00:00Z",
+                "updated_at":"2026-01-01T00:00:00Z"}"#,
+        )
+        .unwrap();
+        assert!(thread.model.is_none());
+        assert!(thread.tags.is_empty());
+        assert!(!thread.pinned && !thread.starred);
+        assert!(thread.folder_id.is_none());
+    }
+
     #[test]
     fn list_threads_query_round_trips() {
         let q = ListThreadsQuery {
             limit: Some(10),
             offset: Some(5),
+            folder_id: Some(Uuid::nil()),
+            starred: Some(true),
+            pinned: None,
         };
         let s = serde_json::to_string(&q).unwrap();
         let back: ListThreadsQuery = serde_json::from_str(&s).unwrap();
@@ -122,9 +122,9 @@ pub struct WireToolDescriptor {
     /// `["youtube::watch_page"]`).
     #[serde(default)]
     pub required_contexts: Vec<String>,
-    /// If true, the server must obtain explicit user approval before
-    /// dispatching the call. Not enforced in v1 (all v1 tools are
-    /// read-only) but declared so the protocol is stable.
+    /// If true, the user must approve the call before it runs. The
+    /// desktop enforces this client-side: its tool backend prompts
+    /// before dispatching and reports a refusal as a tool error.
     #[serde(default)]
     pub requires_user_approval: bool,
 }
@@ -0,0 +1,137 @@
+//! Workflow CRUD + execution wire types.
+//!
+//! A workflow is a reusable chain of prompt, tool, and condition steps
+//! with named parameters. Definitions travel as opaque JSON (the
+//! `agent_graph::workflow::WorkflowDefinition` format) so a workflow
+//! exported from one account can be pasted into another unchanged; the
+//! server validates them on save. The parameter list is pulled out
+//! alongside so the client can render a run form without parsing the
+//! definition.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[cfg(feature = "specta")]
+use specta::Type;
+#[cfg(feature = "specta")]
+use specta_typescript::Unknown;
+
+/// A parameter the caller fills in when running a workflow.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct WorkflowParam {
+    pub name: String,
+    pub description: String,
+    /// `None` means the parameter is required.
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+/// A saved workflow as returned to the client.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct Workflow {
+    pub id: Uuid,
+    pub name: String,
+    pub description: String,
+    pub params: Vec<WorkflowParam>,
+    /// The full definition, suitable for sharing and re-importing.
+    #[cfg_attr(feature = "specta", specta(type = Unknown))]
+    pub definition: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A built-in workflow shipped with the server.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct WorkflowTemplate {
+    /// Stable slug, e.g. `"meeting_prep"`.
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub params: Vec<WorkflowParam>,
+    /// Tools the template calls. It can only run when all of them are
+    /// available to the user.
+    pub tools: Vec<String>,
+    #[cfg_attr(feature = "specta", specta(type = Unknown))]
+    pub definition: serde_json::Value,
+}
+
+/// Request body for `POST /workflows` and `PUT /workflows/{workflow_id}`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct SaveWorkflowRequest {
+    #[cfg_attr(feature = "specta", specta(type = Unknown))]
+    pub definition: serde_json::Value,
+}
+
+/// Response body for the single-workflow endpoints (create, get, update).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct WorkflowResponse {
+    pub workflow: Workflow,
+}
+
+/// Response body for `GET /workflows`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct ListWorkflowsResponse {
+    pub workflows: Vec<Workflow>,
+}
+
+/// Response body for `GET /workflows/templates`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct ListWorkflowTemplatesResponse {
+    pub templates: Vec<WorkflowTemplate>,
+}
+
+/// Response body for `DELETE /workflows/{workflow_id}`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct DeleteWorkflowResponse {}
+
+/// Request body for `POST /workflows/{workflow_id}/run` and
+/// `POST /workflows/templates/{template_id}/run`. Parameters left out use
+/// their defaults.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct RunWorkflowRequest {
+    #[serde(default)]
+    pub params: HashMap<String, String>,
+}
+
+/// Output of one executed step.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct WorkflowStepOutput {
+    pub id: String,
+    pub output: String,
+}
+
+/// Response body for the run endpoints.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "specta", derive(Type))]
+pub struct RunWorkflowResponse {
+    /// Thread the run was recorded in, so the result can be continued as
+    /// a chat.
+    pub thread_id: Uuid,
+    pub output: String,
+    /// Every step that ran, in execution order.
+    pub steps: Vec<WorkflowStepOutput>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_workflow_request_defaults_params() {
+        let req: RunWorkflowRequest = serde_json::from_str("{}").unwrap();
+        assert!(req.params.is_empty());
+    }
+}
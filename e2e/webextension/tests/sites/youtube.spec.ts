@@ -132,6 +132,26 @@ test.describe('YouTube tool surface', { tag: '@youtube' }, () => {
 			expect(sample.duration).toBeGreaterThanOrEqual(0);
 		});
 
+		test('youtube_get_transcript_window returns cited segments around a position', async ({
+			sw,
+		}) => {
+			const result = await invokeTool<ToolResult<typeof youtubeTools.getTranscriptWindow>>(
+				sw,
+				'youtube_get_transcript_window',
+				{ at: 60, before: 20, after: 20 },
+			);
+			expect(result.at).toBe(60);
+			expect(result.window_start).toBe(40);
+			expect(result.window_end).toBe(80);
+			expect(result.segments.length).toBeGreaterThan(0);
+			for (const segment of result.segments) {
+				expect(segment.end).toBeGreaterThan(40);
+				expect(segment.start).toBeLessThan(80);
+			}
+			expect(result.text.split('\n')).toHaveLength(result.segments.length);
+			expect(result.text.startsWith('[')).toBe(true);
+		});
+
 		test('youtube_get_current_frame captures a base64-encoded PNG', async ({ sw }) => {
 			const result = await invokeTool<ToolResult<typeof youtubeTools.getCurrentFrame>>(
 				sw,
@@ -146,6 +166,22 @@ test.describe('YouTube tool surface', { tag: '@youtube' }, () => {
 			/// from the standard `89 50 4E 47 0D 0A 1A 0A` magic header.
 			expect(result.image_base64.startsWith('iVBORw')).toBe(true);
 		});
+
+		test('youtube_get_recent_frames ends with the frame on screen', async ({ sw }) => {
+			const result = await invokeTool<ToolResult<typeof youtubeTools.getRecentFrames>>(
+				sw,
+				'youtube_get_recent_frames',
+				{ count: 3 },
+			);
+			expect(result.video_id).toBe('CXKoCMVqM9s');
+			expect(result.frames.length).toBeGreaterThan(0);
+			expect(result.frames.length).toBeLessThanOrEqual(3);
+			const last = result.frames[result.frames.length - 1];
+			expect(last.current_time).toBeLessThanOrEqual(result.current_time);
+			/// JPEGs start with `/9j/` when base64-encoded from the
+			/// `FF D8 FF` SOI marker.
+			expect(last.image_base64.startsWith('/9j/')).toBe(true);
+		});
 	});
 
 	test.describe('home page', () => {
@@ -477,7 +477,11 @@ export class ChatService {
 						// socket on cancel). Mutating the thread in place lets
 						// the sidebar's Svelte `$state` binding repaint without
 						// an extra HTTP round trip.
-						this.updateThread({ ...entry.thread, title: event.title });
+						this.updateThread({
+							...entry.thread,
+							title: event.title,
+							tags: event.tags ?? entry.thread.tags,
+						});
 						break;
 					case 'final': {
 						const aiMsg = event.messages[0];
@@ -19,6 +19,18 @@ export type Activity = {
 	updated_at: string,
 };
 
+/**
+ *  One newline-delimited JSON line of `GET /activities/stream`.
+ * 
+ *  `next_cursor` resumes the walk right after this batch — pass it back
+ *  as `cursor` to continue after a dropped connection or a `limit`. It is
+ *  `None` on the last batch of the range.
+ */
+export type ActivityBatch = {
+	activities: ActivityWithLatestSession[],
+	next_cursor: string | null,
+};
+
 /**
  *  JSON error body returned by the activity service on non-2xx responses.
  * 
@@ -56,7 +68,8 @@ export type ActivityInsert = {
  *  focus event); `ended_at` is ratcheted forward by heartbeat PATCHes and
  *  finalised on `Stopping`. `url` and `window_title` are optional because
  *  not every strategy produces them (the default strategy has no URL; an
- *  extension-less browser tab has no title yet).
+ *  extension-less browser tab has no title yet). `device_id` is `None` for
+ *  sessions synced before devices were registered.
  */
 export type ActivitySession = {
 	id: string,
@@ -65,12 +78,36 @@ export type ActivitySession = {
 	process_id: number | null,
 	window_title: string | null,
 	url: string | null,
+	device_id?: string | null,
 	started_at: string,
 	ended_at: string | null,
 	created_at: string,
 	updated_at: string,
 };
 
+/**
+ *  Query parameters for `GET /activities/timeline`.
+ * 
+ *  `since` is inclusive and `until` exclusive; a session is included when
+ *  any part of it falls in the range, including sessions still open.
+ *  `device_id` narrows it to one [`Device`]'s sessions.
+ */
+export type ActivityTimelineQuery = {
+	since: string,
+	until: string,
+	device_id?: string | null,
+};
+
+/**
+ *  Response body for `GET /activities/timeline`, parents ordered by their
+ *  first session in the range.
+ */
+export type ActivityTimelineResponse = {
+	activities: ActivityWithSessions[],
+	/**  The range held more than [`MAX_TIMELINE_SESSIONS`] sessions. */
+	truncated?: boolean,
+};
+
 /**
  *  One element of [`ListActivitiesResponse`].
  * 
@@ -85,13 +122,38 @@ export type ActivityWithLatestSession = {
 	latest_session: ActivitySession | null,
 } & Activity;
 
+/**
+ *  One parent activity with its sessions in the requested range, oldest
+ *  first.
+ */
+export type ActivityWithSessions = {
+	sessions: ActivitySession[],
+} & Activity;
+
+/**
+ *  A machine signed in to the account.
+ * 
+ *  The desktop makes up the id once per install and registers it with
+ *  `PUT /devices/{id}` on every start, which also renames the device and
+ *  moves `last_seen_at` forward. `platform` is the OS as Rust names it
+ *  (`macos`, `windows`, `linux`).
+ */
+export type Device = {
+	id: string,
+	name: string,
+	platform: string,
+	last_seen_at: string,
+	created_at: string,
+};
+
 /**
  *  Request body for `POST /activity-sessions`.
  * 
  *  `session_id` and `ended_at` are sent at insert time so a subsequent
  *  PATCH targets the same row (idempotent retries / heartbeat) and an
  *  unexpected crash before the first heartbeat still leaves a bounded
- *  `ended_at` instead of `NULL`.
+ *  `ended_at` instead of `NULL`. `device_id` is the sender's registered
+ *  [`Device`]; an id the user hasn't registered is ignored.
  */
 export type InsertActivitySessionRequest = {
 	session_id?: string | null,
@@ -100,6 +162,7 @@ export type InsertActivitySessionRequest = {
 	process_id?: number | null,
 	window_title?: string | null,
 	url?: string | null,
+	device_id?: string | null,
 	started_at: string,
 	ended_at?: string | null,
 };
@@ -133,6 +196,36 @@ export type ListActivitySessionsResponse = {
 	sessions: ActivitySession[],
 };
 
+/**  Response body for `GET /devices`, most recently seen first. */
+export type ListDevicesResponse = {
+	devices: Device[],
+};
+
+/**  Request body for `PUT /devices/{id}`. */
+export type RegisterDeviceRequest = {
+	/**  At most [`MAX_DEVICE_NAME_CHARS`] characters. */
+	name: string,
+	platform: string,
+};
+
+/**
+ *  Query parameters for `GET /activities/stream`.
+ * 
+ *  The stream walks the same `last_used_at DESC` order as
+ *  `GET /activities`, but pages by an opaque keyset `cursor` instead of an
+ *  offset, so rows bumped to the top mid-walk can't shift later batches.
+ *  `since` (inclusive) and `until` (exclusive) bound `last_used_at`.
+ *  `limit` caps the rows sent before the stream ends; omit it to walk the
+ *  whole range. `batch_size` is capped at [`MAX_LIST_LIMIT`] like a page.
+ */
+export type StreamActivitiesQuery = {
+	cursor?: string | null,
+	since?: string | null,
+	until?: string | null,
+	batch_size?: number | null,
+	limit?: number | null,
+};
+
 /**
  *  Request body for `PATCH /activity-sessions/{id}`.
  * 
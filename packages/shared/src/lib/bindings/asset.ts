@@ -9,10 +9,124 @@ export type Asset = {
 	checksum_sha256: string | null,
 	storage_uri: string,
 	metadata: unknown,
+	/**  The device that uploaded it, when the client said. */
+	device_id?: string | null,
+	scan_status: ScanStatus,
+	storage_tier?: StorageTier,
 	created_at: string,
 	updated_at: string,
 };
 
+/**
+ *  Read access to an asset for someone other than its owner: a named
+ *  user (`grantee_id` set) or whoever holds a link (`grantee_id` null).
+ */
+export type AssetGrant = {
+	id: string,
+	asset_id: string,
+	grantee_id: string | null,
+	/**  `null` for a user grant that doesn't expire. Links always expire. */
+	expires_at: string | null,
+	created_at: string,
+};
+
+/**
+ *  Response for `POST /v1/assets/{asset_id}/links`. `token` is only ever
+ *  returned here; anyone with `path` can download the asset until the
+ *  grant expires or is revoked.
+ */
+export type AssetLink = {
+	grant: AssetGrant,
+	token: string,
+	/**  `/shared/assets/{token}`, relative to the API base URL. */
+	path: string,
+};
+
+/**  Per-user upload settings, for `GET` and `PUT /v1/assets/preferences`. */
+export type AssetPreferences = {
+	/**
+	 *  Strip EXIF (including GPS position), XMP and text metadata from
+	 *  uploaded JPEG, PNG and WebP images. On by default.
+	 */
+	strip_image_metadata: boolean,
+};
+
+/**
+ *  Outcome for one id of a batch get: exactly one of `asset` and `error`
+ *  is set.
+ */
+export type BatchAssetResult = {
+	id: string,
+	asset: Asset | null,
+	error: BatchItemError | null,
+};
+
+/**
+ *  Request body for `POST /v1/assets/batch/get` and
+ *  `POST /v1/assets/batch/delete`.
+ */
+export type BatchAssetsRequest = {
+	/**  Up to [`MAX_BATCH_SIZE`] asset ids. Duplicates are answered once. */
+	ids: string[],
+};
+
+/**
+ *  Response for `POST /v1/assets/batch/get`, one result per distinct
+ *  requested id, in request order.
+ */
+export type BatchGetAssetsResponse = {
+	results: BatchAssetResult[],
+};
+
+/**
+ *  Why one item of a batch failed. `error` is the same machine-readable
+ *  kind the single-item endpoints return (`not_found`, …).
+ */
+export type BatchItemError = {
+	error: string,
+	message: string,
+};
+
+/**
+ *  Outcome for one id of a batch delete or link. Succeeded when `error`
+ *  is `null`.
+ */
+export type BatchItemResult = {
+	id: string,
+	error: BatchItemError | null,
+};
+
+/**
+ *  Response for `POST /v1/assets/batch/delete` and
+ *  `POST /v1/assets/batch/link`, one result per distinct requested id, in
+ *  request order.
+ */
+export type BatchItemsResponse = {
+	results: BatchItemResult[],
+};
+
+/**  Request body for `POST /v1/assets/batch/link`. */
+export type BatchLinkAssetsRequest = {
+	activity_id: string,
+	/**  Up to [`MAX_BATCH_SIZE`] asset ids. Duplicates are answered once. */
+	asset_ids: string[],
+};
+
+/**
+ *  Request body for `POST /v1/assets/{asset_id}/grants`. Sharing again
+ *  with the same user replaces the expiry.
+ */
+export type CreateAssetGrantRequest = {
+	user_id: string,
+	expires_at?: string | null,
+};
+
+/**  Request body for `POST /v1/assets/{asset_id}/links`. */
+export type CreateAssetLinkRequest = {
+	/**  How long the link works, up to [`MAX_LINK_LIFETIME_SECS`]. */
+	expires_in_secs: number,
+};
+
 /**  Request body for `POST /v1/assets`. */
 export type CreateAssetRequest = {
 	name: string,
@@ -20,4 +134,80 @@ export type CreateAssetRequest = {
 	content: string,
 	mime_type: string,
 	metadata?: unknown | null,
+	/**
+	 *  The uploading device, as registered with `PUT /devices/{id}`. An
+	 *  id the user hasn't registered is ignored.
+	 */
+	device_id?: string | null,
+};
+
+/**  Request body for `POST /v1/assets/uploads`. */
+export type CreateUploadRequest = {
+	name: string,
+	mime_type: string,
+	/**
+	 *  Size of the whole asset, up to [`MAX_UPLOAD_SIZE`] (or
+	 *  [`MAX_BUFFERED_UPLOAD_SIZE`] for types checked as a whole).
+	 */
+	size: bigint,
+	metadata?: unknown | null,
+	/**
+	 *  The uploading device, as registered with `PUT /devices/{id}`. The
+	 *  completed asset is tagged with it; an id the user hasn't
+	 *  registered is ignored.
+	 */
+	device_id?: string | null,
+};
+
+/**
+ *  Response for `GET /v1/assets/{asset_id}/grants`, newest first,
+ *  expired grants included.
+ */
+export type ListAssetGrantsResponse = {
+	grants: AssetGrant[],
+};
+
+/**
+ *  Malware scan state of an asset. `quarantined` assets can't be
+ *  downloaded; `skipped` ones were never scanned (scanning is off on this
+ *  deployment, or the scanner couldn't process the file).
+ */
+export type ScanStatus = "pending" | "clean" | "quarantined" | "skipped";
+
+/**
+ *  Response for `GET /v1/assets/shared-with-me`, most recently shared
+ *  first.
+ */
+export type SharedAssetsResponse = {
+	assets: Asset[],
+};
+
+/**
+ *  Which store an asset's bytes are in. Opening a `cold` asset takes
+ *  longer, since it's fetched from cheaper storage and moved back to
+ *  `hot` on the way; clients should say so rather than look stuck.
+ */
+export type StorageTier = "hot" | "cold";
+
+/**
+ *  A resumable upload in progress. Chunks go to
+ *  `PUT /v1/assets/uploads/{id}/chunks/{offset}` in any order; after a
+ *  disconnect `GET /v1/assets/uploads/{id}` says which arrived, and
+ *  `POST /v1/assets/uploads/{id}/complete` turns the session into an
+ *  asset once all have.
+ */
+export type UploadSession = {
+	id: string,
+	name: string,
+	mime_type: string,
+	size: bigint,
+	/**
+	 *  Chunks start at multiples of this and are exactly this long, except
+	 *  the last one, which runs to `size`.
+	 */
+	chunk_size: bigint,
+	/**  Offsets of the chunks received so far, ascending. */
+	received_offsets: bigint[],
+	/**  Pushed back by every chunk; see [`UPLOAD_SESSION_TTL_SECS`]. */
+	expires_at: string,
 };
@@ -1,4 +1,40 @@
 // This file has been generated by Specta. Do not edit this file manually.
+/**  Response body for `DELETE /auth/account`. */
+export type AccountDeletionResponse = {
+	/**
+	 *  When the account's data is erased, in seconds since the Unix
+	 *  epoch. The account stays locked until then.
+	 */
+	scheduled_for: bigint,
+};
+
+/**
+ *  RFC 8693 `act` claim: who is actually holding a token issued for
+ *  someone else.
+ */
+export type Actor = {
+	/**  User id of the support staff member impersonating `sub`. */
+	sub: string,
+};
+
+/**
+ *  How much product analytics the backend may send about a user.
+ * 
+ *  Carried in [`Claims`] so every service can honour it without a
+ *  database round trip. Tokens minted before the field existed
+ *  deserialize as [`AnalyticsConsent::Off`]: when in doubt, send nothing.
+ */
+export type AnalyticsConsent = 
+/**  No events at all. */
+"off" | 
+/**
+ *  Events keyed by a salted hash of the user ID, so usage can be
+ *  counted per user without being tied back to the account.
+ */
+"anonymous" | 
+/**  Events keyed by the user ID. */
+"identified";
+
 /**
  *  Request body for `POST /auth/oauth/apple/id-token`.
  * 
@@ -102,6 +138,19 @@ export type Claims = {
 	 *  after hashing (see refresh-token rotation).
 	 */
 	jti?: string,
+	analytics?: AnalyticsConsent,
+	/**
+	 *  The user's token version when the token was minted. Signing out
+	 *  everywhere bumps the stored version, and tokens carrying an older
+	 *  one are refused from then on even though they haven't expired.
+	 */
+	token_version?: number,
+	/**
+	 *  Set only on impersonation tokens, which are short-lived, come
+	 *  without a refresh token, and have every request they make
+	 *  recorded for the user to see.
+	 */
+	act?: Actor | null,
 };
 
 /**
@@ -118,7 +167,41 @@ export type GoogleIdTokenLoginRequest = {
 	nonce?: string | null,
 };
 
-/**  Request body for `POST /auth/login-token/exchange`. */
+/**  One request made while support staff were acting as the caller. */
+export type ImpersonatedRequest = {
+	method: string,
+	/**  Route template, e.g. `/threads/{thread_id}`. */
+	path: string,
+	/**  HTTP status the request was answered with. */
+	status: number,
+	/**  Seconds since the Unix epoch. */
+	at: bigint,
+};
+
+/**  A support session in which staff acted as the caller. */
+export type ImpersonationSessionInfo = {
+	id: string,
+	/**  Why the session was opened, as entered by the staff member. */
+	reason: string,
+	/**  Seconds since the Unix epoch. */
+	started_at: bigint,
+	/**
+	 *  When the session's token stopped working, in seconds since the
+	 *  Unix epoch.
+	 */
+	expires_at: bigint,
+	requests: ImpersonatedRequest[],
+};
+
+/**  Response body for `GET /auth/impersonations`, newest session first. */
+export type ImpersonationsResponse = {
+	sessions: ImpersonationSessionInfo[],
+};
+
+/**
+ *  Request body for `POST /auth/login-token/exchange` and
+ *  `POST /auth/login-token/wait`.
+ */
 export type LoginByLoginTokenRequest = {
 	token: string,
 };
@@ -140,6 +223,22 @@ export type LoginByLoginTokenRequest = {
  */
 export type LoginRequest = { kind: "email_password"; login: string; password: string } | { kind: "third_party"; provider: Provider; code: string; state: string };
 
+/**
+ *  Request body for `POST /auth/oauth/loopback/url`.
+ *
+ *  Desktop sign-in without the hosted web page (RFC 8252 §7.3): the
+ *  client listens on a loopback port and asks for an authorisation URL
+ *  whose provider redirect lands there. `redirect_uri` must be
+ *  `http://127.0.0.1:{port}/…` or `http://[::1]:{port}/…`. The client
+ *  then posts the `code` and `state` it receives to `POST /auth/login`
+ *  as a [`LoginRequest::ThirdParty`], and the backend exchanges the
+ *  code against the same redirect URI.
+ */
+export type LoopbackThirdPartyAuthUrlRequest = {
+	provider: Provider,
+	redirect_uri: string,
+};
+
 /**
  *  Request body for `POST /auth/oauth/mobile/url`.
  * 
@@ -158,10 +257,10 @@ export type MobileThirdPartyAuthUrlRequest = {
 /**
  *  Third-party identity provider supported by the auth service.
  * 
- *  Wire format is lowercase JSON (`"google"`, `"github"`, `"apple"`)
- *  so it reads naturally in URLs and request bodies.
+ *  Wire format is lowercase JSON (`"google"`, `"github"`, `"apple"`,
+ *  `"microsoft"`) so it reads naturally in URLs and request bodies.
  */
-export type Provider = "google" | "github" | "apple";
+export type Provider = "google" | "github" | "apple" | "microsoft";
 
 /**  Request body for `POST /auth/register`. */
 export type RegisterRequest = {
@@ -203,6 +302,11 @@ export type TokenResponse = {
 	expires_in: bigint,
 };
 
+/**  Request body for `PUT /auth/analytics-consent`. */
+export type UpdateAnalyticsConsentRequest = {
+	consent: AnalyticsConsent,
+};
+
 /**
  *  Public-facing user profile. Mirrors the subset of [`crate::Claims`]
  *  the SPA renders; never carries the JWT itself.
@@ -213,6 +317,7 @@ export type UserInfo = {
 	display_name?: string | null,
 	email_verified: boolean,
 	role: Role,
+	analytics?: AnalyticsConsent,
 };
 
 /**
@@ -0,0 +1,69 @@
+// This file has been generated by Specta. Do not edit this file manually.
+/**
+ *  One delivered event. `id` increases monotonically and is the cursor a
+ *  reconnecting client resumes from.
+ */
+export type Notification = {
+	id: bigint,
+	event: NotificationEvent,
+	created_at: string,
+};
+
+/**
+ *  Something that happened server-side that a connected client should
+ *  react to without polling.
+ * 
+ *  Clients must ignore events they don't know: new kinds are added without
+ *  bumping the protocol, and an older client decodes them as
+ *  [`NotificationEvent::Unknown`].
+ */
+export type NotificationEvent = 
+/**  Another user shared a thread with this one. */
+{ type: "thread_shared"; thread_id: string } | 
+/**  The user's plan changed; refetch entitlements and usage limits. */
+{ type: "subscription_changed"; plan_id: string } | 
+/**  A data export finished building and can be downloaded. */
+{ type: "export_ready"; export_id: string } | 
+/**
+ *  Clients older than `min_version` must update before continuing.
+ *  Sent to every user.
+ */
+{ type: "update_required"; min_version: string } | 
+/**  An event kind this build doesn't know about. */
+({ type: "unknown" }) & { thread_id?: never; plan_id?: never; export_id?: never; min_version?: never };
+
+/**
+ *  Server → client frames on the notification socket. The client never
+ *  sends anything; the server ignores whatever it does send.
+ * 
+ *  On connect the server replays what the client missed as
+ *  `Notification` frames, then sends `Ready`, then streams live events.
+ *  A `Resync` can replace the replay, or arrive later if this connection
+ *  fell behind; either way the client should refetch whatever state the
+ *  events would have invalidated and continue from the given cursor.
+ */
+export type NotificationServerMessage = 
+/**  A replayed or live event. */
+{ type: "notification"; notification: Notification } | 
+/**
+ *  The replay is done. `cursor` is the one to reconnect with if no
+ *  notification arrives before the connection drops.
+ */
+{ type: "ready"; cursor: bigint } | 
+/**  Events were missed and can't be replayed one by one. */
+{ type: "resync"; cursor: bigint } | 
+/**
+ *  Sent on an otherwise idle connection so both ends can tell it is
+ *  still alive. A client that hears nothing for a few heartbeat
+ *  intervals should reconnect.
+ */
+({ type: "heartbeat" }) & { notification?: never; cursor?: never };
+
+/**  Query string for `GET /notifications/ws`. */
+export type NotificationStreamQuery = {
+	/**
+	 *  The last cursor the client saw. Everything newer is replayed before
+	 *  live events; omit it on first connect.
+	 */
+	after?: bigint | null,
+};
@@ -68,6 +68,13 @@ export type DesktopSettings = {
 	 *  specific to the data actually collected.
 	 */
 	telemetry?: TelemetryConsent,
+	/**
+	 *  Voice used to read responses aloud: a platform voice id, or the
+	 *  provider's voice name when speech goes through a provider. `None`
+	 *  uses the system default.
+	 */
+	speechVoice?: string | null,
+	speechRate?: SpeechRate,
 } & { [key in string]: unknown };
 
 /**
@@ -149,6 +156,38 @@ export type PutSettingsRequest = {
 	baseUpdatedAt: string | null,
 };
 
+/**  One entry in the registry. */
+export type SettingDefinition = {
+	/**  Dotted path within the scope's document, e.g. `shared.theme`. */
+	key: string,
+	scope: SettingScope,
+	type: SettingType,
+	/**
+	 *  Fresh-install value, identical to what the section's `Default`
+	 *  impl produces.
+	 */
+	default: unknown,
+};
+
+/**  Which settings document a key lives in, and therefore who may see it. */
+export type SettingScope = "local" | "shared" | "desktop";
+
+/**
+ *  The values a setting accepts. Drives both the form control the UI
+ *  renders and the check [`SettingsSchema::validate`] applies.
+ */
+export type SettingType = { kind: "bool" } | { kind: "number"; min: number; max: number; step: number } | { kind: "choice"; options: string[] };
+
+/**
+ *  `GET /settings/schema` — 200 body, and the desktop's
+ *  `settings_get_schema` result.
+ */
+export type SettingsSchema = {
+	/**  The cloud blob version the registry describes. */
+	schemaVersion: number,
+	settings: SettingDefinition[],
+};
+
 /**
  *  Cross-platform cloud-synced settings. Anything in this section
  *  applies identically on desktop, mobile, and web; per-platform
@@ -161,15 +200,35 @@ export type PutSettingsRequest = {
  * 
  *  `dynamic_accent` defaults to `true` — the design pulls the OS / wallpaper
  *  accent by default and a user who has never touched the toggle should see
- *  the dynamic behaviour. This is the one field where the product default
- *  differs from `bool::default()`, which is why this struct has a hand-rolled
- *  `Default` instead of `#[derive]`.
+ *  the dynamic behaviour. `web_access` likewise defaults to `true`: the
+ *  assistant may search and read the web unless the user opts out. Those
+ *  two fields are where the product default differs from `bool::default()`,
+ *  which is why this struct has a hand-rolled `Default` instead of
+ *  `#[derive]`.
  */
 export type SharedSettings = {
 	theme?: ThemePreference,
 	dynamicAccent?: boolean,
+	/**
+	 *  Whether the assistant may use the `web_search` and `fetch_url`
+	 *  tools. Enforced server-side when a chat turn is prepared.
+	 */
+	webAccess?: boolean,
+	/**
+	 *  Whether the assistant remembers facts about the user across
+	 *  threads: learning them from new messages and recalling them into
+	 *  later chats. Off unless the user opts in.
+	 */
+	memory?: boolean,
 } & { [key in string]: unknown };
 
+/**
+ *  Speaking-rate multiplier for reading responses aloud, where `1.0` is
+ *  the voice's normal pace. Always finite and within
+ *  `[SpeechRate::MIN, SpeechRate::MAX]` by construction.
+ */
+export type SpeechRate = number | null;
+
 /**
  *  Per-platform telemetry consent record. Lives under each platform
  *  section of [`crate::CloudSettings`] — never under
@@ -57,6 +57,67 @@ export type AudioContentBlock = {
 	extras?: { [key in string]: unknown } | null,
 };
 
+/**  A persisted automation as returned to the client. */
+export type Automation = {
+	id: string,
+	name: string,
+	prompt: string,
+	/**
+	 *  Five-field cron expression (`minute hour day-of-month month
+	 *  day-of-week`), evaluated in UTC.
+	 */
+	schedule: string,
+	/**
+	 *  Names of the server tools the agent may call during a run. Only
+	 *  tools listed by `GET /automations/tools` are accepted.
+	 */
+	tools: string[],
+	enabled: boolean,
+	/**  `None` when disabled or when the schedule has no future occurrence. */
+	next_run_at?: string | null,
+	last_run_at?: string | null,
+	created_at: string,
+	updated_at: string,
+};
+
+/**
+ *  Response body for the single-automation endpoints (create, get,
+ *  update, run now).
+ */
+export type AutomationResponse = {
+	automation: Automation,
+};
+
+/**  One execution of an automation. */
+export type AutomationRun = {
+	id: string,
+	automation_id: string,
+	/**
+	 *  Thread holding the conversation for this run. `None` for skipped
+	 *  runs and once the user deletes the thread.
+	 */
+	thread_id?: string | null,
+	status: AutomationRunStatus,
+	scheduled_for: string,
+	started_at: string,
+	finished_at?: string | null,
+	/**  Final assistant text of a successful run. */
+	output?: string | null,
+	error?: string | null,
+};
+
+/**
+ *  Outcome of an automation run. `Skipped` runs never started, e.g.
+ *  because the monthly token budget was exhausted; their `error` says why.
+ */
+export type AutomationRunStatus = "running" | "succeeded" | "failed" | "skipped";
+
+/**  A tool an automation may be granted. */
+export type AutomationTool = {
+	name: string,
+	description: string,
+};
+
 export type BlockIndex = number | string;
 
 /**
@@ -98,13 +159,14 @@ export type CapabilityUpdatePayload = {
 /**
  *  Frame sent by the client over the chat WebSocket.
  * 
- *  Bidirectional from day one; the current set is `Send` (start a turn from
- *  a new human message), `Regenerate` (re-roll an existing AI response under
- *  the same human parent so it becomes a sibling variant), `Cancel`
- *  (interrupt the in-flight turn), and the tool-routing pair
+ *  Bidirectional from day one; the current set is `Send` (start a turn from a
+ *  new human message), `Regenerate` (re-roll an existing AI response under the
+ *  same human parent so it becomes a sibling variant), `Cancel` (interrupt the
+ *  in-flight turn; what the model wrote so far is kept as an AI message with
+ *  `cancelled: true` in its `additional_kwargs`), and the tool-routing pair
  *  `CapabilityUpdate` + `ToolResponse`. New variants can be added without
- *  breaking older clients because serde rejects unknown tagged variants only
- *  on deserialize, never on encode.
+ *  breaking older clients because serde rejects unknown tagged variants only on
+ *  deserialize, never on encode.
  */
 export type ChatClientMessage = {
 	type: "send",
@@ -146,12 +208,23 @@ export type ChatMessage = {
  *  when the user sent the message, so the server can record the link in
  *  `activity_threads`. Optional because non-desktop clients (web, mobile)
  *  have no timeline; absent values skip the link step entirely.
+ * 
+ *  Attachments ride in `content_blocks` as `image`, `text-plain` or `file`
+ *  blocks. Each either inlines its bytes (`base64`, or `text` for plain
+ *  text) or names an asset the caller already uploaded by `file_id`; a
+ *  client-supplied `url` is rejected. Documents are read into text before
+ *  the model sees them.
+ * 
+ *  `request_id` is a fresh id the client picks for the turn. While the turn
+ *  runs, `POST /threads/{thread_id}/chat/{request_id}/cancel` stops it the
+ *  same way a `Cancel` frame on the socket would.
  */
 export type ChatSendRequest = {
 	content_blocks: ContentBlock[],
 	parent_message_id?: string | null,
 	asset_chips_json?: string | null,
 	activity_id?: string | null,
+	request_id?: string | null,
 };
 
 /**
@@ -171,17 +244,28 @@ export type ChatSendRequest = {
 export type ChatServerMessage = 
 /**  The user's message has been persisted; clients should display it. */
 { type: "confirmed_human_message"; message: MessageNode } | 
-/**  One streaming chunk from the AI. */
+/**
+ *  One streaming chunk from the AI. The last chunk of each model
+ *  round has `chunk_position: "last"` and carries the round's
+ *  `usage_metadata` and the provider's `finish_reason` (in
+ *  `response_metadata`, e.g. `"stop"`, `"tool_calls"` or `"length"`).
+ */
 { type: "chunk"; chunk: AIMessageChunk } | 
 /**
  *  The thread's auto-generated title was updated this turn. Non-terminal;
  *  clients update the thread row in place. Emitted at most once per turn,
- *  always before any terminal frame.
+ *  always before any terminal frame. `tags` are the topic tags generated
+ *  with the title; omitted when there are none.
  */
-{ type: "title_updated"; title: string } | 
+{ type: "title_updated"; title: string; tags?: string[] } | 
 /**
  *  The turn ended successfully; tree positions for everything that was
- *  persisted during this turn (human + AI + any tool messages).
+ *  persisted during this turn (human + AI + any tool messages). When
+ *  the answer cites source passages, its text block carries one
+ *  `citation` annotation per cited passage: the marker's char span in
+ *  the text, the passage (`cited_text`), the source `title`, and in
+ *  `extras` the source's `file_id` plus its `paragraph` or, for
+ *  transcripts, `start_seconds`.
  */
 { type: "final"; messages: MessageNode[] } | 
 /**  The turn aborted with an error. The connection is closed after this. */
@@ -233,6 +317,21 @@ export type ContentBlock = {
 
 export type ContentBlocks = ContentBlock[];
 
+/**  Request body for `POST /automations`. */
+export type CreateAutomationRequest = {
+	name: string,
+	prompt: string,
+	schedule: string,
+	tools?: string[],
+	enabled?: boolean,
+};
+
+/**  Request body for `POST /threads/folders`. */
+export type CreateThreadFolderRequest = {
+	/**  Unique per user, at most 100 characters. */
+	name: string,
+};
+
 /**  Request body for `POST /threads`. */
 export type CreateThreadRequest = {
 	title?: string | null,
@@ -243,9 +342,21 @@ export type CreateThreadResponse = {
 	thread: Thread,
 };
 
+/**  Response body for `DELETE /automations/{automation_id}`. */
+export type DeleteAutomationResponse = Record<string, never>;
+
+/**  Response body for `DELETE /memories/{memory_id}` and `DELETE /memories`. */
+export type DeleteMemoryResponse = Record<string, never>;
+
+/**  Response body for `DELETE /threads/folders/{folder_id}`. */
+export type DeleteThreadFolderResponse = Record<string, never>;
+
 /**  Response body for `DELETE /threads/{thread_id}`. */
 export type DeleteThreadResponse = Record<string, never>;
 
+/**  Response body for `DELETE /workflows/{workflow_id}`. */
+export type DeleteWorkflowResponse = Record<string, never>;
+
 export type FileContentBlock = {
 	id?: string | null,
 	file_id?: string | null,
@@ -256,14 +367,46 @@ export type FileContentBlock = {
 	extras?: { [key in string]: unknown } | null,
 };
 
+/**  Request body for `POST /reports`. */
+export type GenerateReportRequest = {
+	/**  Start of the range, inclusive. */
+	since: string,
+	/**  End of the range, exclusive. At most 92 days after `since`. */
+	until: string,
+	/**
+	 *  Markdown with `{{placeholder}}`s; the built-in template when unset.
+	 *  Supported placeholders: `title`, `since`, `until`, `total_time`,
+	 *  `activity_count`, `session_count`, `top_activities`, `devices` and
+	 *  `summary`.
+	 */
+	template?: string | null,
+	/**  Title of the report and name of the stored asset. */
+	title?: string | null,
+	format?: ReportFormat,
+	/**  Only count activity from this registered device. */
+	device_id?: string | null,
+};
+
+/**  Response body for `POST /reports`. */
+export type GenerateReportResponse = {
+	/**  The stored report. */
+	asset_id: string,
+	name: string,
+	mime_type: string,
+	/**  The rendered Markdown, whichever format was stored. */
+	markdown: string,
+};
+
 /**
  *  Request body for `POST /threads/{thread_id}/title`.
  * 
- *  The endpoint reads recent thread history server-side, so no payload is
- *  needed. An empty body type keeps the request well-typed for code-gen and
- *  leaves room to add fields later.
+ *  The endpoint reads recent thread history server-side. By default it
+ *  only titles a thread still carrying the placeholder; `regenerate`
+ *  replaces an existing title and tags too.
  */
-export type GenerateThreadTitleRequest = Record<string, never>;
+export type GenerateThreadTitleRequest = {
+	regenerate?: boolean,
+};
 
 /**  Response body for `POST /threads/{thread_id}/title`. */
 export type GenerateThreadTitleResponse = {
@@ -327,10 +470,55 @@ export type InvalidToolCallBlock = {
 	extras?: { [key in string]: unknown } | null,
 };
 
+/**  Query parameters for `GET /automations/{automation_id}/runs`. */
+export type ListAutomationRunsQuery = {
+	limit?: number | null,
+	offset?: number | null,
+};
+
+/**  Response body for `GET /automations/{automation_id}/runs`. */
+export type ListAutomationRunsResponse = {
+	runs: AutomationRun[],
+};
+
+/**  Response body for `GET /automations/tools`. */
+export type ListAutomationToolsResponse = {
+	tools: AutomationTool[],
+};
+
+/**  Response body for `GET /automations`. */
+export type ListAutomationsResponse = {
+	automations: Automation[],
+};
+
+/**  Response body for `GET /memories`, most recently written first. */
+export type ListMemoriesResponse = {
+	memories: Memory[],
+};
+
+/**
+ *  Response body for `GET /models`. Providers whose list couldn't be
+ *  fetched are left out rather than failing the whole listing.
+ */
+export type ListProviderModelsResponse = {
+	models: ProviderModel[],
+};
+
+/**  Response body for `GET /threads/folders`, ordered by `position`. */
+export type ListThreadFoldersResponse = {
+	folders: ThreadFolder[],
+};
+
 /**  Query parameters for `GET /threads`. */
 export type ListThreadsQuery = {
 	limit?: number | null,
 	offset?: number | null,
+	/**  Only threads filed in this folder. */
+	folder_id?: string | null,
+	/**  Only starred (`true`) or unstarred (`false`) threads. */
+	starred?: boolean | null,
+	/**  Only pinned (`true`) or unpinned (`false`) threads. */
+	pinned?: boolean | null,
 };
 
 /**  Response body for `GET /threads`. */
@@ -338,6 +526,41 @@ export type ListThreadsResponse = {
 	threads: Thread[],
 };
 
+/**  Response body for `GET /workflows/templates`. */
+export type ListWorkflowTemplatesResponse = {
+	templates: WorkflowTemplate[],
+};
+
+/**  Response body for `GET /workflows`. */
+export type ListWorkflowsResponse = {
+	workflows: Workflow[],
+};
+
+/**  One remembered fact. */
+export type Memory = {
+	id: string,
+	/**  A short statement about the user, e.g. "Uses Arch Linux". */
+	content: string,
+	/**
+	 *  The thread the fact was learned in; `None` for facts added by hand
+	 *  or whose thread was deleted.
+	 */
+	thread_id?: string | null,
+	created_at: string,
+	updated_at: string,
+};
+
+/**  Request body for `POST /memories` and `PATCH /memories/{memory_id}`. */
+export type MemoryRequest = {
+	/**  At most 300 characters; unique per user, ignoring case. */
+	content: string,
+};
+
+/**  Response body for `POST /memories` and `PATCH /memories/{memory_id}`. */
+export type MemoryResponse = {
+	memory: Memory,
+};
+
 /**  One node in the message tree returned by message-list endpoints. */
 export type MessageNode = {
 	parent_id?: string | null,
@@ -347,6 +570,28 @@ export type MessageNode = {
 	depth: number,
 };
 
+/**
+ *  What a model costs, in US dollars per million tokens. A side the
+ *  provider doesn't price, or prices per request, is `None`.
+ */
+export type ModelPricing = {
+	input_per_million?: number | null,
+	output_per_million?: number | null,
+};
+
+/**
+ *  Request body for `POST /threads/{thread_id}/folder`. `null` takes the
+ *  thread out of its folder.
+ */
+export type MoveThreadRequest = {
+	folder_id?: string | null,
+};
+
+/**  Response body for `POST /threads/{thread_id}/folder`. */
+export type MoveThreadResponse = {
+	thread: Thread,
+};
+
 export type NonStandardContentBlock = {
 	id?: string | null,
 	value?: { [key in string]: unknown },
@@ -371,6 +616,26 @@ export type PlainTextContentBlock = {
 	extras?: { [key in string]: unknown } | null,
 };
 
+/**  A model one of the deployment's providers offers. */
+export type ProviderModel = {
+	/**
+	 *  Provider id from the deployment's LLM config, as used in
+	 *  [`ThreadModel::provider`](crate::ThreadModel::provider).
+	 */
+	provider: string,
+	/**
+	 *  Model name as used in [`ThreadModel::model`](crate::ThreadModel::model),
+	 *  e.g. `anthropic/claude-sonnet-4.5`.
+	 */
+	model: string,
+	/**  Display name, where the provider has one. */
+	name?: string | null,
+	/**  Context window in tokens, where the provider reports it. */
+	context_length?: number | null,
+	/**  Price, where the provider reports one. */
+	pricing?: ModelPricing | null,
+};
+
 export type ReasoningContentBlock = {
 	id?: string | null,
 	reasoning?: string | null,
@@ -384,11 +649,16 @@ export type ReasoningContentBlock = {
  *  The server resolves the AI message's parent (a human message), rewinds
  *  `active_leaf` to that parent, and runs the agent loop on the existing
  *  context. The newly produced AI message lands as a sibling of the original.
+ *  `request_id` works as on [`ChatSendRequest`].
  */
 export type RegenerateRequest = {
 	ai_message_id: string,
+	request_id?: string | null,
 };
 
+/**  File format of a generated report. */
+export type ReportFormat = "markdown" | "pdf";
+
 export type RemoveMessage = {
 	id: string,
 	name?: string | null,
@@ -396,6 +666,32 @@ export type RemoveMessage = {
 	response_metadata?: { [key in string]: unknown },
 };
 
+/**
+ *  Request body for `POST /workflows/{workflow_id}/run` and
+ *  `POST /workflows/templates/{template_id}/run`. Parameters left out use
+ *  their defaults.
+ */
+export type RunWorkflowRequest = {
+	params?: { [key in string]: string },
+};
+
+/**  Response body for the run endpoints. */
+export type RunWorkflowResponse = {
+	/**
+	 *  Thread the run was recorded in, so the result can be continued as
+	 *  a chat.
+	 */
+	thread_id: string,
+	output: string,
+	/**  Every step that ran, in execution order. */
+	steps: WorkflowStepOutput[],
+};
+
+/**  Request body for `POST /workflows` and `PUT /workflows/{workflow_id}`. */
+export type SaveWorkflowRequest = {
+	definition: unknown,
+};
+
 /**  One message hit returned by full-text search. */
 export type SearchMessageResult = {
 	id: string,
@@ -465,6 +761,21 @@ export type ServerToolResult = {
 
 export type ServerToolStatus = "success" | "error";
 
+/**
+ *  Request body for `POST /threads/{thread_id}/model`. `null` unpins the
+ *  thread so it follows the deployment default again.
+ */
+export type SetThreadModelRequest = {
+	model?: ThreadModel | null,
+	/**  Short topic tags generated with the auto-title. */
+	tags?: string[],
+};
+
+/**  Response body for `POST /threads/{thread_id}/model`. */
+export type SetThreadModelResponse = {
+	thread: Thread,
+};
+
 /**
  *  Request body for `POST /threads/{thread_id}/messages/switch-branch`.
  * 
@@ -500,6 +811,18 @@ export type Thread = {
 	created_at: string,
 	updated_at: string,
 	active_leaf_id?: string | null,
+	/**
+	 *  The model every turn in this thread uses; `None` follows the
+	 *  deployment default.
+	 */
+	model?: ThreadModel | null,
+	/**  Short topic tags generated with the auto-title. */
+	tags?: string[],
+	/**  Pinned threads list ahead of the rest, most recently pinned first. */
+	pinned?: boolean,
+	starred?: boolean,
+	/**  The [`ThreadFolder`](crate::ThreadFolder) the thread is filed in. */
+	folder_id?: string | null,
 };
 
 /**
@@ -514,6 +837,33 @@ export type ThreadErrorResponse = {
 	details?: string | null,
 };
 
+/**  A folder as returned to the client. */
+export type ThreadFolder = {
+	id: string,
+	name: string,
+	/**  Sort key in the sidebar, ascending. New folders go last. */
+	position: number,
+	created_at: string,
+	updated_at: string,
+};
+
+/**
+ *  Response body for `POST /threads/folders` and
+ *  `PATCH /threads/folders/{folder_id}`.
+ */
+export type ThreadFolderResponse = {
+	folder: ThreadFolder,
+};
+
+/**  A model pinned to a thread. */
+export type ThreadModel = {
+	/**  Provider id from the deployment's LLM config, e.g. `openai`. */
+	provider: string,
+	model: string,
+	/**  Sampling temperature; `None` keeps the provider's default. */
+	temperature?: number | null,
+};
+
 export type ToolCall = {
 	id?: string | null,
 	name: string,
@@ -602,6 +952,41 @@ export type ToolSource =
 
 export type ToolStatus = "success" | "error";
 
+/**
+ *  Request body for `PATCH /automations/{automation_id}`. Absent fields
+ *  are left unchanged.
+ */
+export type UpdateAutomationRequest = {
+	name?: string | null,
+	prompt?: string | null,
+	schedule?: string | null,
+	tools?: string[] | null,
+	enabled?: boolean | null,
+};
+
+/**
+ *  Request body for `PATCH /threads/folders/{folder_id}`. Omitted fields
+ *  are left as they are.
+ */
+export type UpdateThreadFolderRequest = {
+	name?: string | null,
+	position?: number | null,
+};
+
+/**
+ *  Request body for `PATCH /threads/{thread_id}`. Omitted fields are left
+ *  as they are; re-pinning a pinned thread keeps its place.
+ */
+export type UpdateThreadRequest = {
+	pinned?: boolean | null,
+	starred?: boolean | null,
+};
+
+/**  Response body for `PATCH /threads/{thread_id}`. */
+export type UpdateThreadResponse = {
+	thread: Thread,
+};
+
 export type UsageMetadata = {
 	input_tokens: bigint,
 	output_tokens: bigint,
@@ -699,3 +1084,49 @@ export type WireToolDescriptor = {
  *  deserializes this; the original field handles all I/O.
  */
 export type WireToolResult = ({ Ok: unknown }) & { Err?: never } | ({ Err: ToolErrorWire }) & { Ok?: never };
+
+/**  A saved workflow as returned to the client. */
+export type Workflow = {
+	id: string,
+	name: string,
+	description: string,
+	params: WorkflowParam[],
+	/**  The full definition, suitable for sharing and re-importing. */
+	definition: unknown,
+	created_at: string,
+	updated_at: string,
+};
+
+/**  A parameter the caller fills in when running a workflow. */
+export type WorkflowParam = {
+	name: string,
+	description: string,
+	/**  `None` means the parameter is required. */
+	default?: string | null,
+};
+
+/**  Response body for the single-workflow endpoints (create, get, update). */
+export type WorkflowResponse = {
+	workflow: Workflow,
+};
+
+/**  Output of one executed step. */
+export type WorkflowStepOutput = {
+	id: string,
+	output: string,
+};
+
+/**  A built-in workflow shipped with the server. */
+export type WorkflowTemplate = {
+	/**  Stable slug, e.g. `"meeting_prep"`. */
+	id: string,
+	name: string,
+	description: string,
+	params: WorkflowParam[],
+	/**
+	 *  Tools the template calls. It can only run when all of them are
+	 *  available to the user.
+	 */
+	tools: string[],
+	definition: unknown,
+};